@@ -47,6 +47,7 @@
           clang
           libclang.lib
           pkg-config
+          protobuf # protoc, for the grpc feature
           rustPlatform.bindgenHook
         ]
         ++ runtimeDependencies
@@ -0,0 +1,7 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // gRPC service & message types, see `web::grpc`
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/mina_indexer/v1/indexer.proto")?;
+
+    Ok(())
+}
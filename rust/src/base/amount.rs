@@ -7,11 +7,22 @@ use crate::{
 use anyhow::anyhow;
 use rust_decimal::{prelude::ToPrimitive, Decimal};
 use serde::{Deserialize, Serialize};
-use std::ops::{Add, AddAssign, Sub};
+use std::ops::{Add, Sub};
+use thiserror::Error;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default, Hash)]
 pub struct Amount(pub u64);
 
+/// Checked amount arithmetic errors (nanomina)
+#[derive(Debug, Error, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum AmountError {
+    #[error("amount overflow: {lhs} + {rhs}")]
+    Overflow { lhs: u64, rhs: u64 },
+
+    #[error("amount underflow: {lhs} - {rhs}")]
+    Underflow { lhs: u64, rhs: u64 },
+}
+
 //////////
 // impl //
 //////////
@@ -20,58 +31,113 @@ impl Amount {
     pub fn new(amount: u64) -> Self {
         Self(amount * MINA_SCALE)
     }
+
+    pub fn checked_add(self, rhs: Amount) -> Result<Amount, AmountError> {
+        self.0
+            .checked_add(rhs.0)
+            .map(Self)
+            .ok_or(AmountError::Overflow {
+                lhs: self.0,
+                rhs: rhs.0,
+            })
+    }
+
+    pub fn checked_sub(self, rhs: Amount) -> Result<Amount, AmountError> {
+        self.0
+            .checked_sub(rhs.0)
+            .map(Self)
+            .ok_or(AmountError::Underflow {
+                lhs: self.0,
+                rhs: rhs.0,
+            })
+    }
+
+    /// Adds a signed amount, a negative amount is subtracted
+    pub fn checked_add_signed(self, rhs: i64) -> Result<Amount, AmountError> {
+        if rhs < 0 {
+            self.checked_sub(Self(rhs.unsigned_abs()))
+        } else {
+            self.checked_add(Self(rhs as u64))
+        }
+    }
+
+    pub fn saturating_add(self, rhs: Amount) -> Amount {
+        Self(self.0.saturating_add(rhs.0))
+    }
+
+    pub fn saturating_sub(self, rhs: Amount) -> Amount {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+
+    /// Sum of the amounts, errors on overflow
+    pub fn checked_sum<I>(amounts: I) -> Result<Amount, AmountError>
+    where
+        I: IntoIterator<Item = Amount>,
+    {
+        amounts
+            .into_iter()
+            .try_fold(Self::default(), |acc, amount| acc.checked_add(amount))
+    }
+
+    /// Sum of the amounts, saturating at [u64::MAX]
+    pub fn saturating_sum<I>(amounts: I) -> Amount
+    where
+        I: IntoIterator<Item = Amount>,
+    {
+        amounts
+            .into_iter()
+            .fold(Self::default(), |acc, amount| acc.saturating_add(amount))
+    }
 }
 
 ////////////////
 // operations //
 ////////////////
 
+// Operators are checked, overflow & underflow are surfaced as [AmountError]
+// instead of wrapping, saturating or panicking. Use [Amount::saturating_add]
+// or [Amount::saturating_sub] where clamping is intended.
+
 impl Add<Amount> for Amount {
-    type Output = Amount;
+    type Output = Result<Amount, AmountError>;
 
     fn add(self, rhs: Amount) -> Self::Output {
-        Self(self.0 + rhs.0)
+        self.checked_add(rhs)
     }
 }
 
 impl Add<u64> for Amount {
-    type Output = Amount;
+    type Output = Result<Amount, AmountError>;
 
     fn add(self, rhs: u64) -> Self::Output {
-        Self(self.0 + rhs)
+        self + Self(rhs)
     }
 }
 
 impl Add<i64> for Amount {
-    type Output = Amount;
+    type Output = Result<Amount, AmountError>;
 
     fn add(self, rhs: i64) -> Self::Output {
-        Self(self.0 + rhs as u64)
+        self.checked_add_signed(rhs)
     }
 }
 
 impl Sub<Amount> for Amount {
-    type Output = Amount;
+    type Output = Result<Amount, AmountError>;
 
     fn sub(self, rhs: Amount) -> Self::Output {
-        Self(self.0.saturating_sub(rhs.0))
+        self.checked_sub(rhs)
     }
 }
 
 impl Sub<u64> for Amount {
-    type Output = Amount;
+    type Output = Result<Amount, AmountError>;
 
     fn sub(self, rhs: u64) -> Self::Output {
         self - Self(rhs)
     }
 }
 
-impl AddAssign<Amount> for Amount {
-    fn add_assign(&mut self, rhs: Amount) {
-        *self = Self(self.0 + rhs.0)
-    }
-}
-
 /////////////////
 // converisons //
 /////////////////
@@ -126,9 +192,80 @@ impl std::fmt::Display for Amount {
 
 #[cfg(test)]
 mod tests {
-    use super::Amount;
+    use super::{Amount, AmountError};
     use std::str::FromStr;
 
+    #[test]
+    fn checked_arithmetic_at_boundary() {
+        let max = Amount(u64::MAX);
+        let one = Amount(1);
+
+        assert_eq!(max.checked_add(Amount(0)), Ok(max));
+        assert_eq!(Amount(u64::MAX - 1).checked_add(one), Ok(max));
+        assert_eq!(
+            max.checked_add(one),
+            Err(AmountError::Overflow {
+                lhs: u64::MAX,
+                rhs: 1
+            })
+        );
+
+        assert_eq!(one.checked_sub(one), Ok(Amount(0)));
+        assert_eq!(
+            Amount(0).checked_sub(one),
+            Err(AmountError::Underflow { lhs: 0, rhs: 1 })
+        );
+
+        assert_eq!(max.saturating_add(max), max);
+        assert_eq!(Amount(0).saturating_sub(max), Amount(0));
+    }
+
+    #[test]
+    fn sums_at_boundary() {
+        let halves = [Amount(u64::MAX / 2), Amount(u64::MAX / 2 + 1)];
+        assert_eq!(Amount::checked_sum(halves), Ok(Amount(u64::MAX)));
+        assert_eq!(Amount::checked_sum([]), Ok(Amount(0)));
+
+        let over = [Amount(u64::MAX), Amount(1), Amount(2)];
+        assert!(Amount::checked_sum(over).is_err());
+        assert_eq!(Amount::saturating_sum(over), Amount(u64::MAX));
+    }
+
+    #[test]
+    fn operators_are_checked() {
+        assert_eq!(Amount(1) + Amount(2), Ok(Amount(3)));
+        assert_eq!(
+            Amount(u64::MAX) + Amount(1),
+            Err(AmountError::Overflow {
+                lhs: u64::MAX,
+                rhs: 1
+            })
+        );
+
+        assert_eq!(Amount(3) - 2u64, Ok(Amount(1)));
+        assert_eq!(
+            Amount(1) - Amount(2),
+            Err(AmountError::Underflow { lhs: 1, rhs: 2 })
+        );
+    }
+
+    #[test]
+    fn add_signed() {
+        assert_eq!(Amount(10) + 5i64, Ok(Amount(15)));
+        assert_eq!(Amount(10) + -4i64, Ok(Amount(6)));
+        assert_eq!(
+            Amount(3) + -4i64,
+            Err(AmountError::Underflow { lhs: 3, rhs: 4 })
+        );
+        assert_eq!(
+            Amount(u64::MAX) + i64::MAX,
+            Err(AmountError::Overflow {
+                lhs: u64::MAX,
+                rhs: i64::MAX as u64
+            })
+        );
+    }
+
     #[test]
     fn roundtrip() -> anyhow::Result<()> {
         let amt = Amount::default();
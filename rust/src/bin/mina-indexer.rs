@@ -5,15 +5,30 @@ use mina_indexer::{
     chain::ChainId,
     cli::{
         database::DatabaseArgs,
-        server::{ServerArgs, ServerArgsJson},
+        server::{ReplicaArgs, ServerArgs, ServerArgsJson},
     },
     client,
     constants::*,
+    crash::{self, CrashContextLogger},
+    ingestion::{
+        backfill::BlockBackfillConfig, http::start_ingest_http_server, IngestionQueueConfig,
+    },
     ledger::genesis::GenesisLedger,
-    server::{GenesisVersion, IndexerConfiguration, IndexerVersion, InitializationMode},
-    store::{restore_snapshot, version::IndexerStoreVersion, IndexerStore},
+    maintenance::{MaintenanceConfig, RetentionPolicy},
+    server::{
+        GenesisVersion, IndexerConfiguration, IndexerVersion, InitializationMode, NetworkIndexers,
+    },
+    store::{
+        dataset::compare_datasets, export::ExportFormat, fixed_keys::FixedKeys,
+        incremental_snapshot::restore_incremental_snapshot, restore_snapshot,
+        version::IndexerStoreVersion, IndexerStore,
+    },
     unix_socket_server::remove_unix_socket,
-    web::start_web_server,
+    web::{
+        graphql::GraphQLOptions,
+        runtime_config::{RuntimeConfig, SharedRuntimeConfig},
+        start_grpc_server, start_web_server, WebServerOptions,
+    },
 };
 use std::{
     fs::{self, File},
@@ -37,6 +52,12 @@ struct Cli {
     /// Path to the Unix domain socket file
     #[arg(long, default_value = "./mina-indexer.sock", num_args = 1)]
     socket: PathBuf,
+
+    /// Open the database as a read-only secondary instance, safe to run
+    /// alongside a live indexer. Servers are started as replicas & commands
+    /// which write to the database are refused.
+    #[arg(long, global = true)]
+    read_only: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -67,6 +88,10 @@ enum ServerCommand {
     /// Start a new mina indexer
     Start(Box<ServerArgs>),
 
+    /// Serve REST & GraphQL from a read-only replica of a running indexer's
+    /// database
+    Replica(Box<ReplicaArgs>),
+
     /// Shutdown the server
     Shutdown,
 }
@@ -93,6 +118,11 @@ enum DatabaseCommand {
         #[arg(long, default_value = "./snapshot")]
         output_path: PathBuf,
 
+        /// Full path to a snapshot directory. Adds a compressed snapshot,
+        /// sharing table files with the directory's previous snapshots.
+        #[arg(long, conflicts_with = "output_path")]
+        output: Option<PathBuf>,
+
         /// Full path to a mina indexer database directory.
         /// If null, snapshot a running indexer database.
         #[arg(long)]
@@ -105,11 +135,145 @@ enum DatabaseCommand {
         #[arg(long, default_value = "./snapshot")]
         snapshot_file: PathBuf,
 
+        /// Full path to a snapshot directory created with `--output`
+        #[arg(long, conflicts_with = "snapshot_file")]
+        snapshot_dir: Option<PathBuf>,
+
+        /// Name of the snapshot to restore from the snapshot directory
+        /// [default: latest]
+        #[arg(long, requires = "snapshot_dir")]
+        snapshot_name: Option<String>,
+
         /// Full path to the database directory
         #[arg(long)]
         restore_dir: PathBuf,
     },
 
+    /// Run pending sub-store migrations of a mina indexer database
+    Migrate {
+        /// Full path to a mina indexer database directory
+        #[arg(long)]
+        database_dir: PathBuf,
+
+        /// Only report pending migrations
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Replay canonical blocks in a height range, rewriting derived data
+    ReplayRange {
+        /// Full path to a mina indexer database directory
+        #[arg(long)]
+        database_dir: PathBuf,
+
+        /// First block height to replay
+        #[arg(long)]
+        from_height: u32,
+
+        /// Last block height to replay
+        #[arg(long)]
+        to_height: u32,
+    },
+
+    /// Verify the user command JSON round-trip against stored blocks
+    VerifyJsonRoundtrip {
+        /// Full path to a mina indexer database directory
+        #[arg(long)]
+        database_dir: PathBuf,
+
+        /// Number of evenly spaced blocks to verify
+        #[arg(long, default_value_t = 100, conflicts_with = "all")]
+        sample: usize,
+
+        /// Verify all stored blocks
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Verify the parent pointers & contiguous heights of the canonical chain
+    VerifyLinkage {
+        /// Full path to a mina indexer database directory
+        #[arg(long)]
+        database_dir: PathBuf,
+    },
+
+    /// Verify the stored canonical hashes against the best tip's ancestors
+    VerifyCanonicity {
+        /// Full path to a mina indexer database directory
+        #[arg(long)]
+        database_dir: PathBuf,
+
+        /// Lowest block height to verify
+        #[arg(long, default_value_t = 1)]
+        from_height: u32,
+    },
+
+    /// Replay the canonical chain's ledger diffs, verifying the staged
+    /// ledgers against the block-declared staged ledger hashes at each
+    /// ledger cadence point
+    Verify {
+        /// Full path to a mina indexer database directory
+        #[arg(long)]
+        database_dir: PathBuf,
+
+        /// Block height of the staged ledger to start replaying from
+        #[arg(long, default_value_t = 1)]
+        from_height: u32,
+
+        /// Number of blocks between verified staged ledgers
+        #[arg(long, default_value_t = LEDGER_CADENCE)]
+        ledger_cadence: u32,
+    },
+
+    /// Export a deterministic dataset of all derived data as sorted NDJSON
+    /// tables, for diffing indexer versions
+    ExportDataset {
+        /// Full path to a mina indexer database directory
+        #[arg(long)]
+        database_dir: PathBuf,
+
+        /// Directory to write the dataset tables to
+        #[arg(long)]
+        output: PathBuf,
+    },
+
+    /// Export canonical blocks, user commands, internal commands & account
+    /// diffs in a height range to CSV or Parquet files
+    Export {
+        /// Full path to a mina indexer database directory
+        #[arg(long)]
+        database_dir: PathBuf,
+
+        /// Directory to write the table files to
+        #[arg(long)]
+        output_dir: PathBuf,
+
+        /// File format: csv or parquet (requires the parquet_export feature)
+        #[arg(long, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+
+        /// First exported block height
+        #[arg(long, default_value_t = 1)]
+        start_height: u32,
+
+        /// Last exported block height [default: best block height]
+        #[arg(long)]
+        end_height: Option<u32>,
+    },
+
+    /// Compare two exported datasets table by table
+    CompareDatasets {
+        /// Dataset directory A
+        dataset_a: PathBuf,
+
+        /// Dataset directory B
+        dataset_b: PathBuf,
+
+        /// Max number of differing rows reported per table
+        #[arg(long, default_value_t = 10)]
+        max_differences: usize,
+    },
+
     /// Query mina indexer database version
     Version {
         /// Output JSON data
@@ -122,13 +286,16 @@ enum DatabaseCommand {
 pub async fn main() -> anyhow::Result<()> {
     let args = Cli::parse();
     let domain_socket_path = args.socket;
+    let read_only = args.read_only;
     Toplevel::new(|s| async move {
         s.start(SubsystemBuilder::new("Main", |s| async move {
             match args.command {
                 IndexerCommand::Client(cli) => cli.run(domain_socket_path).await,
-                IndexerCommand::Database { db_command } => db_command.run(domain_socket_path).await,
+                IndexerCommand::Database { db_command } => {
+                    db_command.run(domain_socket_path, read_only).await
+                }
                 IndexerCommand::Server { server_command } => {
-                    server_command.run(s, domain_socket_path).await
+                    server_command.run(s, domain_socket_path, read_only).await
                 }
                 IndexerCommand::Version => Ok(println!("{VERSION}")),
             }
@@ -141,13 +308,18 @@ pub async fn main() -> anyhow::Result<()> {
 }
 
 impl ServerCommand {
-    async fn run(self, subsys: SubsystemHandle, domain_socket_path: PathBuf) -> anyhow::Result<()> {
+    async fn run(
+        self,
+        subsys: SubsystemHandle,
+        domain_socket_path: PathBuf,
+        read_only: bool,
+    ) -> anyhow::Result<()> {
         let (args, mode) = match self {
             Self::Shutdown => return client::ClientCli::Shutdown.run(domain_socket_path).await,
+            Self::Replica(args) => return run_replica(subsys, *args).await,
             Self::Start(args) => {
                 if let Some(config_path) = args.db.config {
-                    let contents = std::fs::read(config_path)?;
-                    let args: ServerArgsJson = serde_json::from_slice(&contents)?;
+                    let args = ServerArgsJson::from_file(&config_path)?;
                     (args.into(), InitializationMode::Sync)
                 } else if args.self_check {
                     (*args, InitializationMode::Replay)
@@ -156,41 +328,117 @@ impl ServerCommand {
                 }
             }
         };
+
+        if read_only {
+            return run_replica(subsys, args.into()).await;
+        }
         let args = args.with_dynamic_defaults(std::process::id());
         let database_dir = args.db.database_dir.clone();
+        let accept_constants_change = args.db.accept_constants_change;
+        let sql_mirror = args.db.sql_mirror.clone();
+        let networks = args.networks.clone();
+        let trace_accounts = args.db.trace_accounts.clone();
+        let validate_blocks = args.db.validate_blocks;
+        let future_block_horizon = args.db.future_block_horizon;
+        let staking_ledger_batch_size = args.db.staking_ledger_batch_size;
+        let staking_ledgers_from_epoch = args.db.staking_ledgers_from_epoch;
+        let watch_accounts = args.watch_accounts.clone();
+        let watch_webhook = args.watch_webhook.clone();
         let web_hostname = args.web_hostname.clone();
         let web_port = args.web_port;
+        let ingest_http_port = args.ingest_http_port;
+        let grpc_port = args.grpc_port;
+        let runtime_config_path = args.runtime_config.clone();
+        let web_options = WebServerOptions {
+            runtime_config: boot_runtime_config(
+                runtime_config_path.as_deref(),
+                args.expose_raw_block_json,
+            )?,
+            public_api: args.public_api.options(),
+            ..Default::default()
+        };
 
-        // initialize logging
-        stderrlog::new()
-            .module(module_path!())
-            .color(ColorChoice::Never)
-            .timestamp(Timestamp::Microsecond)
-            .verbosity(args.db.log_level.0)
-            .init()
-            .unwrap();
+        // initialize logging & crash context
+        init_logging(args.db.log_level.0)?;
+        crash::install_panic_hook(&database_dir);
 
         check_or_write_pid_file(&database_dir);
 
         debug!("Reading mina indexer config from store");
-        let db = Arc::new(IndexerStore::new(&database_dir)?);
-        let mut config = IndexerConfiguration::read_indexer_config(&db)?;
-        config.initialization_mode = mode;
+        let db = Arc::new(IndexerStore::new_with_networks(
+            &database_dir,
+            &networks,
+            accept_constants_change,
+        )?);
+
+        // each network is indexed in its own namespace, the first one serves
+        // the UDS, web server & SQL mirror
+        let network_stores = if networks.is_empty() {
+            vec![db.clone()]
+        } else {
+            networks
+                .iter()
+                .map(|network| db.network_store(network))
+                .collect::<anyhow::Result<Vec<_>>>()?
+        };
+        if let Some(path) = sql_mirror {
+            network_stores[0].enable_sql_mirror(&path)?;
+        }
 
         info!("Starting the mina indexer filesystem watchers & UDS server");
-        let store = db.clone();
+        let indexers = NetworkIndexers::default();
+        for (n, store) in network_stores.iter().enumerate() {
+            let mut config = IndexerConfiguration::read_indexer_config(store)?;
+            config.initialization_mode = mode.clone();
+            config.trace_accounts = trace_accounts.clone();
+            config.validate_blocks = validate_blocks;
+            config.future_block_horizon = future_block_horizon;
+            config.staking_ledger_batch_size = staking_ledger_batch_size;
+            config.staking_ledgers_from_epoch = staking_ledgers_from_epoch;
+            config.watch_accounts = watch_accounts.clone();
+            config.watch_webhook = watch_webhook.clone();
 
-        subsys.start(SubsystemBuilder::new("Indexer", move |s| {
-            config.start_indexer(s, store)
-        }));
+            let name = match store.namespace() {
+                Some(network) => format!("Indexer {network}"),
+                None => "Indexer".to_string(),
+            };
+            let store = store.clone();
+            let indexers = indexers.clone();
+
+            subsys.start(SubsystemBuilder::new(name, move |s| {
+                config.start_network_indexer(s, store, indexers, n == 0)
+            }));
+        }
+
+        if let Some(port) = ingest_http_port {
+            info!("Starting the HTTP block ingestion endpoint on {web_hostname}:{port}");
+            let host = web_hostname.clone();
+            let indexers = indexers.clone();
+
+            subsys.start(SubsystemBuilder::new("HTTP Block Ingestion", move |s| {
+                start_ingest_http_server(s, indexers, (host, port))
+            }));
+        }
+
+        if let Some(port) = grpc_port {
+            info!("Starting the gRPC server listening on {web_hostname}:{port}");
+            let store = network_stores[0].clone();
+            let host = web_hostname.clone();
+
+            subsys.start(SubsystemBuilder::new("gRPC Server", move |s| {
+                start_grpc_server(s, store, (host, port))
+            }));
+        }
 
         info!("Starting the web server listening on {web_hostname}:{web_port}");
-        let store = db.clone();
+        let store = network_stores[0].clone();
         let host = web_hostname.clone();
+        let runtime_config = web_options.runtime_config.clone();
 
         subsys.start(SubsystemBuilder::new("Web Server", move |s| {
-            start_web_server(s, store, (host, web_port))
+            start_web_server(s, store, (host, web_port), web_options)
         }));
+        start_runtime_config_watcher(&subsys, runtime_config, runtime_config_path);
 
         println!("GraphQL server started at: http://{web_hostname}:{web_port}/graphql");
         subsys.on_shutdown_requested().await;
@@ -198,6 +446,7 @@ impl ServerCommand {
         info!("Shutting down primary database instance");
         db.database.cancel_all_background_work(true);
         remove_pid(&database_dir);
+        drop(network_stores);
         drop(db);
         remove_unix_socket(&domain_socket_path)?;
 
@@ -205,16 +454,107 @@ impl ServerCommand {
     }
 }
 
+/// Serves the web server from a read-only replica, periodically catching up
+/// with the primary
+async fn run_replica(subsys: SubsystemHandle, args: ReplicaArgs) -> anyhow::Result<()> {
+    init_logging(LevelFilter::Info)?;
+
+    let database_dir = args.database_dir;
+    if !database_dir.exists() {
+        error!("Database dir {database_dir:#?} does not exist");
+        process::exit(1);
+    }
+
+    let web_hostname = args.web_hostname;
+    let web_port = args.web_port;
+    let catch_up_interval = Duration::from_secs(args.catch_up_interval);
+    let web_options = WebServerOptions {
+        runtime_config: boot_runtime_config(
+            args.runtime_config.as_deref(),
+            args.expose_raw_block_json,
+        )?,
+        max_replication_lag_blocks: Some(args.max_lag_blocks),
+        lag_header: args.lag_header,
+        public_api: args.public_api.options(),
+    };
+
+    let tmp_dir = TempDir::new()?;
+    let db = Arc::new(IndexerStore::read_only(&database_dir, tmp_dir.as_ref())?);
+
+    info!(
+        "Catching up with the primary every {}s",
+        args.catch_up_interval
+    );
+    let store = db.clone();
+
+    subsys.start(SubsystemBuilder::new(
+        "Replica Catch-up",
+        move |s| async move {
+            loop {
+                tokio::select! {
+                    _ = s.on_shutdown_requested() => {
+                        break;
+                    }
+
+                    _ = tokio::time::sleep(catch_up_interval) => {
+                        // failures are logged, the lag is reported as it grows
+                        store.catch_up_with_primary().ok();
+                    }
+                }
+            }
+
+            info!("Replica catch-up successfully shutdown");
+            anyhow::Ok(())
+        },
+    ));
+
+    info!("Starting the web server listening on {web_hostname}:{web_port}");
+    let store = db.clone();
+    let host = web_hostname.clone();
+    let runtime_config = web_options.runtime_config.clone();
+
+    subsys.start(SubsystemBuilder::new("Web Server", move |s| {
+        start_web_server(s, store, (host, web_port), web_options)
+    }));
+    start_runtime_config_watcher(&subsys, runtime_config, args.runtime_config);
+
+    println!("GraphQL server started at: http://{web_hostname}:{web_port}/graphql");
+    subsys.on_shutdown_requested().await;
+
+    info!("Shutting down read-only database instance");
+    db.database.cancel_all_background_work(true);
+    Ok(())
+}
+
 impl DatabaseCommand {
-    async fn run(self, domain_socket_path: PathBuf) -> anyhow::Result<()> {
+    /// Whether the command writes to a database
+    fn is_write(&self) -> bool {
+        match self {
+            Self::Ingest { .. }
+            | Self::Create(_)
+            | Self::Restore { .. }
+            | Self::ReplayRange { .. } => true,
+            Self::Migrate { dry_run, .. } => !dry_run,
+            Self::Snapshot { .. }
+            | Self::VerifyJsonRoundtrip { .. }
+            | Self::VerifyLinkage { .. }
+            | Self::VerifyCanonicity { .. }
+            | Self::Verify { .. }
+            | Self::ExportDataset { .. }
+            | Self::Export { .. }
+            | Self::CompareDatasets { .. }
+            | Self::Version { .. } => false,
+        }
+    }
+
+    async fn run(self, domain_socket_path: PathBuf, read_only: bool) -> anyhow::Result<()> {
         // initialize logging
-        stderrlog::new()
-            .module(module_path!())
-            .color(ColorChoice::Never)
-            .timestamp(Timestamp::Microsecond)
-            .verbosity(LevelFilter::Info)
-            .init()
-            .unwrap();
+        init_logging(LevelFilter::Info)?;
+
+        if read_only && self.is_write() {
+            error!("Refusing to write to the database in read-only mode");
+            process::exit(1);
+        }
 
         match self {
             Self::Version { json } => {
@@ -230,8 +570,11 @@ impl DatabaseCommand {
             }
             Self::Snapshot {
                 output_path,
+                output,
                 database_dir,
             } => {
+                let incremental = output.is_some();
+                let output_path = output.unwrap_or(output_path);
                 if let Some(database_dir) = database_dir {
                     if !database_dir.exists() {
                         error!("Database dir {database_dir:#?} does not exist");
@@ -239,21 +582,246 @@ impl DatabaseCommand {
                         info!("Creating snapshot of database dir {database_dir:#?}");
                         let tmp_dir = TempDir::new()?;
                         let db = IndexerStore::read_only(&database_dir, tmp_dir.as_ref())?;
-                        db.create_snapshot(&output_path)?;
+                        if incremental {
+                            println!("{}", db.create_incremental_snapshot(&output_path)?);
+                        } else {
+                            db.create_snapshot(&output_path)?;
+                        }
                     }
                 } else {
                     info!("Creating snapshot of running mina indexer");
-                    return client::ClientCli::CreateSnapshot { output_path }
-                        .run(domain_socket_path)
-                        .await;
+                    return client::ClientCli::CreateSnapshot {
+                        output_path,
+                        incremental,
+                    }
+                    .run(domain_socket_path)
+                    .await;
                 }
             }
             Self::Restore {
                 snapshot_file,
+                snapshot_dir,
+                snapshot_name,
                 restore_dir,
             } => {
-                info!("Restoring mina indexer database from snapshot file {snapshot_file:#?} to {restore_dir:#?}");
-                restore_snapshot(&snapshot_file, &restore_dir).unwrap_or_else(|e| error!("{e}"))
+                if let Some(snapshot_dir) = snapshot_dir {
+                    info!("Restoring mina indexer database from snapshot dir {snapshot_dir:#?} to {restore_dir:#?}");
+                    restore_incremental_snapshot(
+                        &snapshot_dir,
+                        snapshot_name.as_deref(),
+                        &restore_dir,
+                    )
+                    .map(|_| ())
+                    .unwrap_or_else(|e| error!("{e}"))
+                } else {
+                    info!("Restoring mina indexer database from snapshot file {snapshot_file:#?} to {restore_dir:#?}");
+                    restore_snapshot(&snapshot_file, &restore_dir).unwrap_or_else(|e| error!("{e}"))
+                }
+            }
+            Self::Migrate {
+                database_dir,
+                dry_run,
+            } => {
+                if !database_dir.exists() {
+                    error!("Database dir {database_dir:#?} does not exist");
+                    process::exit(1);
+                }
+
+                let tmp_dir = TempDir::new()?;
+                let db = if read_only {
+                    IndexerStore::read_only(&database_dir, tmp_dir.as_ref())?
+                } else {
+                    IndexerStore::new_with_sub_stores(&database_dir, &[], true)?
+                };
+                let pending = db.pending_migrations(&IndexerStore::SUB_STORES)?;
+                if pending.is_empty() {
+                    info!("No pending sub-store migrations");
+                }
+
+                for migration in pending.iter() {
+                    info!("Pending sub-store migration {migration}");
+                }
+
+                if !dry_run && !pending.is_empty() {
+                    drop(db);
+                    IndexerStore::new(&database_dir)?;
+                    info!("Ran {} sub-store migrations", pending.len());
+                }
+            }
+            Self::ReplayRange {
+                database_dir,
+                from_height,
+                to_height,
+            } => {
+                if !database_dir.exists() {
+                    error!("Database dir {database_dir:#?} does not exist");
+                    process::exit(1);
+                }
+
+                let db = IndexerStore::new(&database_dir)?;
+                let num_rewritten = db.replay_range(from_height, to_height)?;
+                info!("Replayed blocks {from_height}..={to_height}, rewrote {num_rewritten} derived entries");
+            }
+            Self::VerifyJsonRoundtrip {
+                database_dir,
+                sample,
+                all,
+            } => {
+                if !database_dir.exists() {
+                    error!("Database dir {database_dir:#?} does not exist");
+                    process::exit(1);
+                }
+
+                let tmp_dir = TempDir::new()?;
+                let db = IndexerStore::read_only(&database_dir, tmp_dir.as_ref())?;
+                let report = db.verify_json_roundtrip((!all).then_some(sample))?;
+
+                for (state_hash, mismatch) in report.mismatches.iter() {
+                    error!("Block {state_hash} {mismatch}");
+                }
+
+                info!(
+                    "Verified {} commands in {} blocks, {} mismatches",
+                    report.num_commands,
+                    report.num_blocks,
+                    report.mismatches.len()
+                );
+
+                if !report.mismatches.is_empty() {
+                    process::exit(1);
+                }
+            }
+            Self::VerifyLinkage { database_dir } => {
+                if !database_dir.exists() {
+                    error!("Database dir {database_dir:#?} does not exist");
+                    process::exit(1);
+                }
+
+                let tmp_dir = TempDir::new()?;
+                let db = IndexerStore::read_only(&database_dir, tmp_dir.as_ref())?;
+                let report = db.verify_linkage()?;
+
+                for linkage_error in report.errors.iter() {
+                    error!("{linkage_error}");
+                }
+
+                info!(
+                    "Verified {} canonical blocks, {} linkage errors",
+                    report.num_blocks,
+                    report.errors.len()
+                );
+
+                if !report.errors.is_empty() {
+                    process::exit(1);
+                }
+            }
+            Self::VerifyCanonicity {
+                database_dir,
+                from_height,
+            } => {
+                if !database_dir.exists() {
+                    error!("Database dir {database_dir:#?} does not exist");
+                    process::exit(1);
+                }
+
+                let tmp_dir = TempDir::new()?;
+                let db = IndexerStore::read_only(&database_dir, tmp_dir.as_ref())?;
+                let report = db.verify_canonicity(from_height)?;
+
+                for mismatch in report.mismatches.iter() {
+                    error!("{mismatch}");
+                }
+
+                info!(
+                    "Verified canonicity of {} heights in {}..={}, {} mismatches",
+                    report.num_heights,
+                    report.from_height,
+                    report.to_height,
+                    report.mismatches.len()
+                );
+
+                if !report.mismatches.is_empty() {
+                    process::exit(1);
+                }
+            }
+            Self::Verify {
+                database_dir,
+                from_height,
+                ledger_cadence,
+            } => {
+                if !database_dir.exists() {
+                    error!("Database dir {database_dir:#?} does not exist");
+                    process::exit(1);
+                }
+
+                let tmp_dir = TempDir::new()?;
+                let db = IndexerStore::read_only(&database_dir, tmp_dir.as_ref())?;
+                let report = db.verify_replay(from_height, ledger_cadence)?;
+
+                for mismatch in report.mismatches.iter() {
+                    error!("{mismatch}");
+                }
+
+                info!(
+                    "Replayed {} blocks, verified {} staged ledger checkpoints, {} mismatches",
+                    report.num_blocks,
+                    report.num_checkpoints,
+                    report.mismatches.len()
+                );
+
+                if !report.mismatches.is_empty() {
+                    process::exit(1);
+                }
+            }
+            Self::ExportDataset {
+                database_dir,
+                output,
+            } => {
+                if !database_dir.exists() {
+                    error!("Database dir {database_dir:#?} does not exist");
+                    process::exit(1);
+                }
+
+                let tmp_dir = TempDir::new()?;
+                let db = IndexerStore::read_only(&database_dir, tmp_dir.as_ref())?;
+                let export = db.export_dataset(&output)?;
+
+                for (table, num_rows) in export.num_rows {
+                    info!("{table}: {num_rows} rows");
+                }
+            }
+            Self::Export {
+                database_dir,
+                output_dir,
+                format,
+                start_height,
+                end_height,
+            } => {
+                if !database_dir.exists() {
+                    error!("Database dir {database_dir:#?} does not exist");
+                    process::exit(1);
+                }
+
+                let tmp_dir = TempDir::new()?;
+                let db = IndexerStore::read_only(&database_dir, tmp_dir.as_ref())?;
+                let export =
+                    db.export_canonical_chain(&output_dir, format, start_height, end_height)?;
+
+                for (table, num_rows) in export.num_rows {
+                    info!("{table}: {num_rows} rows");
+                }
+            }
+            Self::CompareDatasets {
+                dataset_a,
+                dataset_b,
+                max_differences,
+            } => {
+                let comparison = compare_datasets(&dataset_a, &dataset_b, max_differences)?;
+                print!("{comparison}");
+
+                if !comparison.is_identical() {
+                    process::exit(1);
+                }
             }
             Self::Ingest {
                 database_dir,
@@ -263,12 +831,15 @@ impl DatabaseCommand {
             }
             Self::Create(args) => {
                 let database_dir = args.database_dir.clone();
+                let accept_constants_change = args.accept_constants_change;
+                let sql_mirror = args.sql_mirror.clone();
                 debug!("Ensuring mina indexer database exists in {database_dir:#?}");
 
                 if let Err(e) = fs::create_dir_all(&database_dir) {
                     error!("Failed to create database directory: {e}");
                     process::exit(1);
                 }
+                crash::install_panic_hook(&database_dir);
 
                 debug!("Building mina indexer configuration");
                 let mut mode = InitializationMode::BuildDB;
@@ -279,14 +850,32 @@ impl DatabaseCommand {
                     }
                 };
 
-                let config = if let Some(config_path) = args.config {
-                    let contents = std::fs::read(config_path)?;
-                    let args: ServerArgsJson = serde_json::from_slice(&contents)?;
+                let mut config = if let Some(config_path) = args.config {
+                    let args = ServerArgsJson::from_file(&config_path)?;
                     IndexerConfiguration::from((args, domain_socket_path))
                 } else {
                     process_indexer_configuration((*args).into(), mode, domain_socket_path)?
                 };
-                let db = Arc::new(IndexerStore::new(&database_dir)?);
+                let network = config.version.network.clone();
+                let db = Arc::new(IndexerStore::new_with_networks(
+                    &database_dir,
+                    &[network.clone()],
+                    accept_constants_change,
+                )?);
+
+                // a new network in an existing database is built from scratch
+                let network_db = db.network_store(&network)?;
+                if network_db
+                    .get_pinned_cf(network_db.default_cf(), IndexerStore::INDEXER_CONFIG_KEY)?
+                    .is_none()
+                {
+                    config.initialization_mode = InitializationMode::BuildDB;
+                }
+                drop(network_db);
+
+                if let Some(path) = sql_mirror {
+                    db.enable_sql_mirror(&path)?;
+                }
                 let store = db.clone();
 
                 tokio::select! {
@@ -309,6 +898,52 @@ impl DatabaseCommand {
     }
 }
 
+/// The runtime config file's settings if given, otherwise the flags'
+fn boot_runtime_config(
+    path: Option<&Path>,
+    expose_raw_block_json: bool,
+) -> anyhow::Result<SharedRuntimeConfig> {
+    let config = match path {
+        Some(path) => RuntimeConfig::from_file(path)?,
+        None => RuntimeConfig {
+            graphql: GraphQLOptions {
+                expose_raw_block_json,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    };
+
+    Ok(SharedRuntimeConfig::new(config))
+}
+
+/// Reloads the runtime config file, if any, on change
+fn start_runtime_config_watcher(
+    subsys: &SubsystemHandle,
+    runtime_config: SharedRuntimeConfig,
+    path: Option<PathBuf>,
+) {
+    if let Some(path) = path {
+        subsys.start(SubsystemBuilder::new("Runtime Config Watcher", move |s| {
+            runtime_config.watch(path, s)
+        }));
+    }
+}
+
+/// Logs to stderr, recording recent lines for the crash context
+fn init_logging(verbosity: LevelFilter) -> anyhow::Result<()> {
+    let mut logger = stderrlog::new();
+    logger
+        .module(module_path!())
+        .color(ColorChoice::Never)
+        .timestamp(Timestamp::Microsecond)
+        .verbosity(verbosity);
+
+    log::set_max_level(logger.log_level_filter());
+    log::set_boxed_logger(Box::new(CrashContextLogger::new(logger)))?;
+    Ok(())
+}
+
 /// Creates directories, processes constants & parses genesis ledger.
 /// Returns indexer config.
 fn process_indexer_configuration(
@@ -324,12 +959,40 @@ fn process_indexer_configuration(
     let canonical_update_threshold = args.db.canonical_update_threshold;
     let ledger_cadence = args.db.ledger_cadence;
     let reporting_freq = args.db.reporting_freq;
+    let ingestion_queue = IngestionQueueConfig {
+        max_depth: args.db.ingestion_queue_depth,
+        max_bytes: args.db.ingestion_queue_bytes,
+    };
     let do_not_ingest_orphan_blocks = args.db.do_not_ingest_orphan_blocks;
+    let no_recursive = args.db.no_recursive;
+    let replace_staking_ledger = args.db.replace_staking_ledger;
+    let trace_accounts = args.db.trace_accounts;
+    let validate_blocks = args.db.validate_blocks;
+    let future_block_horizon = args.db.future_block_horizon;
+    let staking_ledger_batch_size = args.db.staking_ledger_batch_size;
+    let staking_ledgers_from_epoch = args.db.staking_ledgers_from_epoch;
     let fetch_new_blocks_exe = args.fetch_new_blocks_exe;
     let fetch_new_blocks_delay = args.fetch_new_blocks_delay;
     let missing_block_recovery_exe = args.missing_block_recovery_exe;
     let missing_block_recovery_delay = args.missing_block_recovery_delay;
     let missing_block_recovery_batch = args.missing_block_recovery_batch.unwrap_or(false);
+    let block_backfill = args.block_backfill.then(|| BlockBackfillConfig {
+        url_template: args.block_archive_url,
+        list_url_template: args.block_archive_list_url,
+        delay: args.block_backfill_delay,
+    });
+    let watch_accounts = args.watch_accounts;
+    let watch_webhook = args.watch_webhook;
+    let maintenance = MaintenanceConfig {
+        latency_budget_ms: args.maintenance_latency_budget,
+        yield_ms: args.maintenance_yield,
+        paused: args.maintenance_paused,
+        retention: RetentionPolicy {
+            prune_orphaned_after: args.prune_orphaned_after,
+            prune_noncanonical_below_height: args.prune_noncanonical_below_height,
+        },
+        ..Default::default()
+    };
 
     // ensure blocks dir exists
     if let Some(ref blocks_dir) = blocks_dir {
@@ -371,13 +1034,19 @@ fn process_indexer_configuration(
         (PcbVersion::V1, ChainId::v1(), GenesisVersion::v1())
     };
 
-    let genesis_ledger = parse_genesis_ledger(args.db.genesis_ledger, &version)?;
+    // the config file's genesis section supersedes the defaults
+    let genesis_config = args.db.genesis.unwrap_or_default();
+    let genesis_ledger = parse_genesis_ledger(
+        genesis_config.ledger.clone().or(args.db.genesis_ledger),
+        &version,
+    )?;
     let version = IndexerVersion {
         network,
         version,
         chain_id,
         genesis,
-    };
+    }
+    .with_genesis_config(&genesis_config);
 
     Ok(IndexerConfiguration {
         genesis_ledger,
@@ -390,6 +1059,7 @@ fn process_indexer_configuration(
         initialization_mode,
         ledger_cadence,
         reporting_freq,
+        ingestion_queue,
         domain_socket_path,
         fetch_new_blocks_exe,
         fetch_new_blocks_delay,
@@ -397,6 +1067,18 @@ fn process_indexer_configuration(
         missing_block_recovery_delay,
         missing_block_recovery_batch,
         do_not_ingest_orphan_blocks,
+        no_recursive,
+        replace_staking_ledger,
+        maintenance,
+        trace_accounts,
+        validate_blocks,
+        future_block_horizon,
+        staking_ledger_batch_size,
+        staking_ledgers_from_epoch,
+        chain_constants: genesis_config.chain_constants(),
+        block_backfill,
+        watch_accounts,
+        watch_webhook,
     })
 }
 
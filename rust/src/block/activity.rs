@@ -0,0 +1,279 @@
+//! Per-block & daily account activity of the canonical chain
+//!
+//! Distinct account counts across a day are exact: each day keeps the hashed
+//! public keys of its canonical blocks, along with the number of the day's
+//! blocks involving each one, so blocks can be unapplied on reorgs.
+
+use super::precomputed::PrecomputedBlock;
+use crate::{
+    base::public_key::PublicKey,
+    command::UserCommandWithStatusT,
+    ledger::diff::{account::AccountDiff, LedgerDiff},
+};
+use blake2::{
+    digest::{Update, VariableOutput},
+    Blake2bVar,
+};
+use serde::{Deserialize, Serialize};
+
+/// Number of milliseconds in a (UTC) day
+pub const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+/// Compact public key hash, distinct counts are exact up to 64-bit hash
+/// collisions
+pub type PkHash = u64;
+
+/// Account activity of a block
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockActivity {
+    /// Block date time (epoch millis)
+    pub date_time: i64,
+
+    /// Hashes of all public keys involved in the block, ascending
+    pub active_accounts: Vec<PkHash>,
+
+    /// Hashes of the user command senders (zkapp fee payers), ascending
+    pub txn_senders: Vec<PkHash>,
+
+    /// Hashes of the zkapp accounts updated by the block, ascending
+    pub zkapp_accounts: Vec<PkHash>,
+
+    /// Number of accounts created by the block
+    pub new_accounts: u32,
+}
+
+/// Account activity of a day's canonical blocks
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyActivity {
+    /// Heights of the day's canonical blocks, ascending
+    pub heights: Vec<u32>,
+
+    /// Number of accounts created by the day's canonical blocks
+    pub new_accounts: u32,
+
+    pub active_accounts: PkCounts,
+    pub txn_senders: PkCounts,
+    pub zkapp_accounts: PkCounts,
+}
+
+/// Public key hashes, ascending, with the number of blocks involving each
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PkCounts(Vec<(PkHash, u32)>);
+
+/// Activity series of a block or a day
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ActivityStats {
+    pub num_blocks: u32,
+    pub active_accounts: u32,
+    pub txn_senders: u32,
+    pub new_accounts: u32,
+    pub zkapp_accounts: u32,
+}
+
+/// Hashes the public key
+pub fn pk_hash(pk: &PublicKey) -> PkHash {
+    let mut hasher = Blake2bVar::new(8).expect("valid output size");
+    hasher.update(pk.0.as_bytes());
+
+    let mut bytes = [0; 8];
+    hasher
+        .finalize_variable(&mut bytes)
+        .expect("valid output size");
+    u64::from_be_bytes(bytes)
+}
+
+/// Day since the Unix epoch of the date time (epoch millis)
+pub fn day_of(date_time: i64) -> u32 {
+    date_time.div_euclid(MILLIS_PER_DAY) as u32
+}
+
+impl BlockActivity {
+    pub fn new(block: &PrecomputedBlock, diff: &LedgerDiff) -> Self {
+        let zkapp_accounts = diff
+            .account_diffs
+            .iter()
+            .flatten()
+            .filter(|diff| is_zkapp_update(diff))
+            .map(AccountDiff::public_key);
+
+        Self {
+            date_time: block.timestamp() as i64,
+            active_accounts: hash_set(diff.public_keys_seen.iter().cloned()),
+            txn_senders: hash_set(block.commands().iter().map(|cmd| cmd.sender())),
+            zkapp_accounts: hash_set(zkapp_accounts),
+            new_accounts: diff
+                .new_pk_balances
+                .values()
+                .map(|token_balances| token_balances.len() as u32)
+                .sum(),
+        }
+    }
+
+    pub fn day(&self) -> u32 {
+        day_of(self.date_time)
+    }
+
+    pub fn stats(&self) -> ActivityStats {
+        ActivityStats {
+            num_blocks: 1,
+            active_accounts: self.active_accounts.len() as u32,
+            txn_senders: self.txn_senders.len() as u32,
+            new_accounts: self.new_accounts,
+            zkapp_accounts: self.zkapp_accounts.len() as u32,
+        }
+    }
+}
+
+impl DailyActivity {
+    /// Adds the canonical block's activity, `false` if it was already added
+    pub fn apply(&mut self, height: u32, block: &BlockActivity) -> bool {
+        match self.heights.binary_search(&height) {
+            Ok(_) => return false,
+            Err(index) => self.heights.insert(index, height),
+        }
+
+        self.new_accounts += block.new_accounts;
+        self.active_accounts.add(&block.active_accounts);
+        self.txn_senders.add(&block.txn_senders);
+        self.zkapp_accounts.add(&block.zkapp_accounts);
+        true
+    }
+
+    /// Removes the block's activity, `false` if it was never added
+    pub fn unapply(&mut self, height: u32, block: &BlockActivity) -> bool {
+        match self.heights.binary_search(&height) {
+            Ok(index) => self.heights.remove(index),
+            Err(_) => return false,
+        };
+
+        self.new_accounts = self.new_accounts.saturating_sub(block.new_accounts);
+        self.active_accounts.remove(&block.active_accounts);
+        self.txn_senders.remove(&block.txn_senders);
+        self.zkapp_accounts.remove(&block.zkapp_accounts);
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heights.is_empty()
+    }
+
+    pub fn stats(&self) -> ActivityStats {
+        ActivityStats {
+            num_blocks: self.heights.len() as u32,
+            active_accounts: self.active_accounts.len(),
+            txn_senders: self.txn_senders.len(),
+            new_accounts: self.new_accounts,
+            zkapp_accounts: self.zkapp_accounts.len(),
+        }
+    }
+}
+
+impl PkCounts {
+    /// Number of distinct public keys
+    pub fn len(&self) -> u32 {
+        self.0.len() as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, pk_hash: PkHash) -> bool {
+        self.index_of(pk_hash).is_ok()
+    }
+
+    fn add(&mut self, pk_hashes: &[PkHash]) {
+        for pk_hash in pk_hashes {
+            match self.index_of(*pk_hash) {
+                Ok(index) => self.0[index].1 += 1,
+                Err(index) => self.0.insert(index, (*pk_hash, 1)),
+            }
+        }
+    }
+
+    fn remove(&mut self, pk_hashes: &[PkHash]) {
+        for pk_hash in pk_hashes {
+            if let Ok(index) = self.index_of(*pk_hash) {
+                if self.0[index].1 > 1 {
+                    self.0[index].1 -= 1;
+                } else {
+                    self.0.remove(index);
+                }
+            }
+        }
+    }
+
+    fn index_of(&self, pk_hash: PkHash) -> Result<usize, usize> {
+        self.0.binary_search_by_key(&pk_hash, |(hash, _)| *hash)
+    }
+}
+
+/// Zkapp account diffs which update the account itself, i.e. not only its
+/// balance or nonce
+fn is_zkapp_update(diff: &AccountDiff) -> bool {
+    use AccountDiff::*;
+
+    matches!(
+        diff,
+        Zkapp(_)
+            | ZkappStateDiff(_)
+            | ZkappPermissionsDiff(_)
+            | ZkappVerificationKeyDiff(_)
+            | ZkappUriDiff(_)
+            | ZkappTokenSymbolDiff(_)
+            | ZkappTimingDiff(_)
+            | ZkappVotingForDiff(_)
+            | ZkappActionsDiff(_)
+            | ZkappEventsDiff(_)
+    )
+}
+
+fn hash_set(pks: impl Iterator<Item = PublicKey>) -> Vec<PkHash> {
+    let mut hashes: Vec<_> = pks.map(|pk| pk_hash(&pk)).collect();
+    hashes.sort();
+    hashes.dedup();
+    hashes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(active_accounts: Vec<PkHash>, new_accounts: u32) -> BlockActivity {
+        BlockActivity {
+            active_accounts,
+            new_accounts,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn daily_apply_unapply() {
+        let (a, b) = (block(vec![1, 2], 1), block(vec![2, 3], 2));
+        let mut daily = DailyActivity::default();
+
+        assert!(daily.apply(1, &a));
+        assert!(daily.apply(2, &b));
+        assert!(!daily.apply(2, &b));
+        assert_eq!(
+            daily.stats(),
+            ActivityStats {
+                num_blocks: 2,
+                active_accounts: 3,
+                new_accounts: 3,
+                ..Default::default()
+            }
+        );
+
+        // pk 2 is still active in block 1
+        assert!(daily.unapply(2, &b));
+        assert!(!daily.unapply(2, &b));
+        assert!(daily.active_accounts.contains(2));
+        assert!(!daily.active_accounts.contains(3));
+        assert_eq!(daily.stats(), a.stats());
+
+        assert!(daily.unapply(1, &a));
+        assert!(daily.is_empty());
+        assert_eq!(daily, DailyActivity::default());
+    }
+}
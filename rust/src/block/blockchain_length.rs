@@ -1,16 +1,12 @@
+use super::open_block_file;
 use anyhow::bail;
-use std::{
-    fs::File,
-    io::{BufReader, Read},
-    path::Path,
-};
+use std::{io::Read, path::Path};
 
 pub struct BlockchainLength(u32);
 
 impl BlockchainLength {
     pub fn from_path(path: &Path) -> anyhow::Result<u32> {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
+        let reader = open_block_file(path)?;
         let mut buffer = String::with_capacity(1000);
 
         // Limit the reader to read only the first 1000 bytes
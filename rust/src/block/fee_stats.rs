@@ -0,0 +1,148 @@
+//! Block user command fees & fee estimation
+
+use super::precomputed::PrecomputedBlock;
+use crate::{command::UserCommandWithStatusT, utility::functions::percentile};
+use serde::{Deserialize, Serialize};
+
+/// Max number of user commands in a block, i.e. `2^transaction_capacity_log_2`
+pub const MAX_BLOCK_USER_COMMANDS: u32 = 128;
+
+/// Distribution of the user command fees included in a block
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockFeeStats {
+    /// Included user command fees (nanomina), ascending. Zkapp commands
+    /// contribute their fee payer's fee.
+    pub fees: Vec<u64>,
+}
+
+/// Suggested fees (nanomina) from the fees of recent blocks
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FeeEstimate {
+    pub num_blocks: u32,
+    pub num_full_blocks: u32,
+    pub num_fees: u32,
+
+    /// 25th percentile fee
+    pub low: u64,
+
+    /// Median fee
+    pub medium: u64,
+
+    /// 90th percentile fee
+    pub high: u64,
+}
+
+impl BlockFeeStats {
+    pub fn new(mut fees: Vec<u64>) -> Self {
+        fees.sort();
+        Self { fees }
+    }
+
+    pub fn from_precomputed(block: &PrecomputedBlock) -> Self {
+        Self::new(block.commands().iter().map(|cmd| cmd.fee()).collect())
+    }
+
+    pub fn count(&self) -> u32 {
+        self.fees.len() as u32
+    }
+
+    pub fn min(&self) -> Option<u64> {
+        self.fees.first().copied()
+    }
+
+    pub fn median(&self) -> Option<u64> {
+        (!self.fees.is_empty()).then(|| percentile(&self.fees, 50))
+    }
+
+    pub fn max(&self) -> Option<u64> {
+        self.fees.last().copied()
+    }
+
+    /// Whether the block's user command count is within 10% of
+    /// [MAX_BLOCK_USER_COMMANDS]
+    pub fn is_full(&self) -> bool {
+        self.count() * 10 >= MAX_BLOCK_USER_COMMANDS * 9
+    }
+}
+
+impl FeeEstimate {
+    /// Estimates fees from the blocks' stats, `None` if none of the blocks
+    /// include user commands
+    pub fn new(stats: &[BlockFeeStats]) -> Option<Self> {
+        let mut fees: Vec<u64> = stats
+            .iter()
+            .flat_map(|stats| stats.fees.iter().copied())
+            .collect();
+
+        if fees.is_empty() {
+            return None;
+        }
+
+        fees.sort();
+        Some(Self {
+            num_blocks: stats.len() as u32,
+            num_full_blocks: stats.iter().filter(|stats| stats.is_full()).count() as u32,
+            num_fees: fees.len() as u32,
+            low: percentile(&fees, 25),
+            medium: percentile(&fees, 50),
+            high: percentile(&fees, 90),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_fee_stats() {
+        let stats = BlockFeeStats::default();
+        assert_eq!(stats.count(), 0);
+        assert_eq!(
+            (stats.min(), stats.median(), stats.max()),
+            (None, None, None)
+        );
+        assert!(!stats.is_full());
+
+        let stats = BlockFeeStats::new(vec![30, 10, 20, 40]);
+        assert_eq!(stats.fees, vec![10, 20, 30, 40]);
+        assert_eq!(
+            (stats.min(), stats.median(), stats.max()),
+            (Some(10), Some(20), Some(40))
+        );
+
+        // full blocks
+        assert!(!BlockFeeStats::new(vec![1; 115]).is_full());
+        assert!(BlockFeeStats::new(vec![1; 116]).is_full());
+        assert!(BlockFeeStats::new(vec![1; MAX_BLOCK_USER_COMMANDS as usize]).is_full());
+    }
+
+    #[test]
+    fn fee_estimate() {
+        assert_eq!(FeeEstimate::new(&[]), None);
+        assert_eq!(FeeEstimate::new(&[BlockFeeStats::default()]), None);
+
+        let stats = [
+            BlockFeeStats::new((1..=50).collect()),
+            BlockFeeStats::default(),
+            BlockFeeStats::new((51..=100).rev().collect()),
+            BlockFeeStats::new(vec![1; MAX_BLOCK_USER_COMMANDS as usize]),
+        ];
+        assert_eq!(
+            FeeEstimate::new(&stats[..3]),
+            Some(FeeEstimate {
+                num_blocks: 3,
+                num_full_blocks: 0,
+                num_fees: 100,
+                low: 25,
+                medium: 50,
+                high: 90,
+            })
+        );
+
+        let estimate = FeeEstimate::new(&stats).unwrap();
+        assert_eq!(estimate.num_full_blocks, 1);
+        assert_eq!(estimate.num_fees, 228);
+        assert_eq!((estimate.low, estimate.medium), (1, 1));
+    }
+}
@@ -1,38 +1,42 @@
-use super::StateHash;
+use super::{open_block_file, StateHash};
 use anyhow::bail;
-use std::{
-    fs::File,
-    io::{BufReader, Read},
-    path::Path,
-};
+use std::{io::Read, path::Path};
 
 pub struct GenesisStateHash(StateHash);
 
 impl GenesisStateHash {
     pub fn from_path(path: &Path) -> anyhow::Result<StateHash> {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
+        let reader = open_block_file(path)?;
         let mut buffer = String::with_capacity(400);
 
         // Limit the reader to read only the first 400 bytes
         reader.take(400).read_to_string(&mut buffer)?;
+        find_genesis_state_hash(&buffer)
+    }
+
+    /// Genesis state hash of the precomputed block file contents
+    pub fn from_bytes(contents: &[u8]) -> anyhow::Result<StateHash> {
+        let buffer = String::from_utf8_lossy(&contents[..contents.len().min(400)]);
+        find_genesis_state_hash(&buffer)
+    }
+}
 
-        // Locate "genesis_state_hash" within the buffer
-        let gen_state_hash_key = "\"genesis_state_hash\"";
-        if let Some(hash_pos) = buffer.find(gen_state_hash_key) {
-            let hash_start = hash_pos + gen_state_hash_key.len();
+fn find_genesis_state_hash(buffer: &str) -> anyhow::Result<StateHash> {
+    // Locate "genesis_state_hash" within the buffer
+    let gen_state_hash_key = "\"genesis_state_hash\"";
+    if let Some(hash_pos) = buffer.find(gen_state_hash_key) {
+        let hash_start = hash_pos + gen_state_hash_key.len();
 
-            // Find the first quote after the colon
-            if let Some(quote_start) = buffer[hash_start..].find('"') {
-                let start = hash_start + quote_start + 1;
-                if start + StateHash::LEN <= buffer.len() {
-                    let genesis_state_hash = &buffer[start..][..StateHash::LEN];
-                    return Ok(genesis_state_hash.into());
-                }
+        // Find the first quote after the colon
+        if let Some(quote_start) = buffer[hash_start..].find('"') {
+            let start = hash_start + quote_start + 1;
+            if start + StateHash::LEN <= buffer.len() {
+                let genesis_state_hash = &buffer[start..][..StateHash::LEN];
+                return Ok(genesis_state_hash.into());
             }
         }
-        bail!("Failed to find genesis_state_hash in the file")
     }
+    bail!("Failed to find genesis_state_hash in the file")
 }
 
 impl From<GenesisStateHash> for StateHash {
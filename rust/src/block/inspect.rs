@@ -0,0 +1,138 @@
+//! Diagnostics for precomputed block files which fail to parse
+
+use super::{
+    precomputed::{PcbVersion, PrecomputedBlock},
+    read_block_file,
+};
+use std::{fmt::Write, path::Path};
+
+/// Default number of bytes shown on either side of the failing offset
+pub const HEX_CONTEXT_DEFAULT: usize = 32;
+
+const HEX_DUMP_WIDTH: usize = 16;
+
+/// Parses the block file & reports where parsing failed, with `hex_context`
+/// bytes on either side of the failing offset
+///
+/// JSON errors are located in the (decompressed) file contents. bin_prot
+/// errors of base58 encoded versioned fields are located in the field's
+/// decoded bytes.
+pub fn inspect_block_file(
+    path: &Path,
+    version: PcbVersion,
+    hex_context: usize,
+) -> anyhow::Result<String> {
+    let error = match PrecomputedBlock::parse_file(path, version.clone()) {
+        Ok(block) => {
+            return Ok(format!(
+                "Parsed {version} block {} (length {}) from {}",
+                block.state_hash(),
+                block.blockchain_length(),
+                path.display()
+            ))
+        }
+        Err(error) => error,
+    };
+
+    let mut report = format!(
+        "Failed to parse {version} block {}: {error}",
+        path.display()
+    );
+    let Some(json_error) = error.downcast_ref::<serde_json::Error>() else {
+        return Ok(report);
+    };
+
+    let contents = read_block_file(path)?;
+    let file_offset = json_file_offset(&contents, json_error.line(), json_error.column());
+    writeln!(report, "\n\nJSON error at file byte offset {file_offset}")?;
+    report.push_str(&hex_dump(&contents, file_offset, hex_context));
+
+    if let Some(offset) = bin_prot_offset(&json_error.to_string()) {
+        if let Some(bytes) = json_string_before(&contents, file_offset)
+            .and_then(|s| bs58::decode(s).with_check(None).into_vec().ok())
+            .filter(|bytes| !bytes.is_empty())
+        {
+            // skip the version check byte, bin_prot offsets start after it
+            writeln!(
+                report,
+                "\nbin_prot error at byte offset {offset} of the decoded field"
+            )?;
+            report.push_str(&hex_dump(&bytes[1..], offset as usize, hex_context));
+        }
+    }
+
+    Ok(report)
+}
+
+/// Byte offset reported by a bin_prot error message
+pub fn bin_prot_offset(message: &str) -> Option<u64> {
+    let (_, rest) = message.split_once("at byte offset ")?;
+    let digits = rest.split(|c: char| !c.is_ascii_digit()).next()?;
+    digits.parse().ok()
+}
+
+/// Hex dump of `bytes` within `context` bytes of `offset`, the byte at
+/// `offset` is bracketed
+pub fn hex_dump(bytes: &[u8], offset: usize, context: usize) -> String {
+    let start = offset.saturating_sub(context) / HEX_DUMP_WIDTH * HEX_DUMP_WIDTH;
+    let end = bytes
+        .len()
+        .min(offset.saturating_add(context).saturating_add(1));
+
+    let mut dump = String::new();
+    for line_start in (start..end).step_by(HEX_DUMP_WIDTH) {
+        let line = &bytes[line_start..end.min(line_start + HEX_DUMP_WIDTH)];
+        let hex: Vec<String> = line
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| {
+                if line_start + i == offset {
+                    format!("[{byte:02x}]")
+                } else {
+                    format!(" {byte:02x} ")
+                }
+            })
+            .collect();
+        let ascii: String = line
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        let _ = writeln!(
+            dump,
+            "{line_start:08x} {:width$} |{ascii}|",
+            hex.concat(),
+            width = 4 * HEX_DUMP_WIDTH
+        );
+    }
+
+    if offset >= bytes.len() {
+        let _ = writeln!(dump, "{offset:08x} <end of input>");
+    }
+    dump
+}
+
+/// Byte offset of serde_json's (1-based) line & column
+fn json_file_offset(contents: &[u8], line: usize, column: usize) -> usize {
+    let line_start = contents
+        .split_inclusive(|&byte| byte == b'\n')
+        .take(line.saturating_sub(1))
+        .map(<[u8]>::len)
+        .sum::<usize>();
+
+    // the column points at the last byte read
+    (line_start + column.saturating_sub(1)).min(contents.len())
+}
+
+/// The JSON string literal ending at or before `offset`
+fn json_string_before(contents: &[u8], offset: usize) -> Option<&str> {
+    let before = &contents[..contents.len().min(offset + 1)];
+    let close = before.iter().rposition(|&byte| byte == b'"')?;
+    let open = before[..close].iter().rposition(|&byte| byte == b'"')?;
+    std::str::from_utf8(&contents[open + 1..close]).ok()
+}
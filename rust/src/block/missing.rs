@@ -0,0 +1,26 @@
+//! Blocks referenced as a parent, but never seen
+
+use crate::base::state_hash::StateHash;
+use serde::{Deserialize, Serialize};
+
+/// Parent of a stored block which is not in the store
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissingBlock {
+    pub state_hash: StateHash,
+
+    /// Expected blockchain length, one less than the referencing block's
+    pub blockchain_length: u32,
+
+    /// First stored block referencing the missing block as its parent
+    pub referenced_by: StateHash,
+}
+
+impl std::fmt::Display for MissingBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Missing block {} (length {}) referenced by {}",
+            self.state_hash, self.blockchain_length, self.referenced_by
+        )
+    }
+}
@@ -1,13 +1,21 @@
 //! Indexer internal block representation used in the witness tree
 
+pub mod activity;
 pub mod blockchain_length;
 pub mod epoch_data;
+pub mod fee_stats;
 pub mod genesis;
 pub mod genesis_state_hash;
+pub mod inspect;
+pub mod missing;
 pub mod parser;
 pub mod precomputed;
 pub mod previous_state_hash;
+pub mod receipt;
 pub mod store;
+pub mod supercharge;
+pub mod supply;
+pub mod validation;
 pub mod vrf_output;
 
 mod post_hardfork;
@@ -17,9 +25,17 @@ use crate::{
     base::state_hash::StateHash, canonicity::Canonicity, chain::Network, constants::*,
     utility::functions::is_valid_file_name,
 };
+use flate2::read::GzDecoder;
+use log::debug;
 use precomputed::PcbVersion;
 use serde::{Deserialize, Serialize};
-use std::{cmp::Ordering, path::Path};
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
 
 // re-export types
 pub type AccountCreated = post_hardfork::account_created::AccountCreated;
@@ -60,6 +76,11 @@ impl Block {
     pub fn summary(&self) -> String {
         format!("(length {}): {}", self.blockchain_length, self.state_hash)
     }
+
+    /// Whether `self` is a better tip than `other`, see [Block::cmp]
+    pub fn better_than(&self, other: &Self) -> bool {
+        self < other
+    }
 }
 
 impl From<Block> for BlockWithoutHeight {
@@ -176,21 +197,15 @@ impl std::cmp::PartialOrd for BlockComparison {
 
 impl std::cmp::Ord for BlockComparison {
     fn cmp(&self, other: &Self) -> Ordering {
-        if self.version == PcbVersion::V2 && other.version == PcbVersion::V1 {
-            // hardfork blocks are better than pre-hardfork blocks
-            return Ordering::Less;
-        }
-
-        let length_cmp = self.blockchain_length.cmp(&other.blockchain_length);
-        let vrf_cmp = self.hash_last_vrf_output.cmp(&other.hash_last_vrf_output);
-        let hash_cmp = self.state_hash.cmp(&other.state_hash);
-
-        match (length_cmp, vrf_cmp, hash_cmp) {
-            (Ordering::Greater, _, _)
-            | (Ordering::Equal, Ordering::Greater, _)
-            | (Ordering::Equal, Ordering::Equal, Ordering::Greater) => Ordering::Less,
-            _ => Ordering::Greater,
-        }
+        // hardfork blocks are better than pre-hardfork blocks
+        let is_hardfork = |block: &Self| block.version == PcbVersion::V2;
+
+        select_longer_chain(
+            (is_hardfork(self), is_hardfork(other)),
+            (self.blockchain_length, other.blockchain_length),
+            (&self.hash_last_vrf_output, &other.hash_last_vrf_output),
+            (&self.state_hash, &other.state_hash),
+        )
     }
 }
 
@@ -205,26 +220,35 @@ impl std::cmp::Ord for Block {
     /// A < B means A is better than B
     /// https://github.com/MinaProtocol/mina/tree/develop/docs/specs/consensus#62-select-chain
     fn cmp(&self, other: &Self) -> Ordering {
-        if self.genesis_state_hash.0 == HARDFORK_GENESIS_HASH
-            && other.genesis_state_hash.0 == MAINNET_GENESIS_HASH
-        {
-            // hardfork blocks are better than pre-hardfork blocks
-            return Ordering::Less;
-        }
-
-        let length_cmp = self.blockchain_length.cmp(&other.blockchain_length);
-        let vrf_cmp = self.hash_last_vrf_output.cmp(&other.hash_last_vrf_output);
-        let hash_cmp = self.state_hash.cmp(&other.state_hash);
-
-        match (length_cmp, vrf_cmp, hash_cmp) {
-            (Ordering::Greater, _, _)
-            | (Ordering::Equal, Ordering::Greater, _)
-            | (Ordering::Equal, Ordering::Equal, Ordering::Greater) => Ordering::Less,
-            _ => Ordering::Greater,
-        }
+        // hardfork blocks are better than pre-hardfork blocks
+        let is_hardfork = |block: &Self| block.genesis_state_hash.0 == HARDFORK_GENESIS_HASH;
+
+        select_longer_chain(
+            (is_hardfork(self), is_hardfork(other)),
+            (self.blockchain_length, other.blockchain_length),
+            (&self.hash_last_vrf_output, &other.hash_last_vrf_output),
+            (&self.state_hash, &other.state_hash),
+        )
     }
 }
 
+/// Total order on tips, using only consensus data: hardfork, length, last VRF
+/// output hash & state hash, each compared in descending order so the better
+/// tip is less. Independent of the order in which blocks are seen.
+fn select_longer_chain(
+    hardfork: (bool, bool),
+    length: (u32, u32),
+    vrf: (&VrfOutput, &VrfOutput),
+    state_hash: (&StateHash, &StateHash),
+) -> Ordering {
+    hardfork
+        .1
+        .cmp(&hardfork.0)
+        .then(length.1.cmp(&length.0))
+        .then(vrf.1.cmp(vrf.0))
+        .then(state_hash.1.cmp(state_hash.0))
+}
+
 impl std::default::Default for StateHash {
     fn default() -> Self {
         Self("3NLDEFAULTDEFAULTDEFAULTDEFAULTDEFAULTDEFAULTDEFAULT".into())
@@ -293,9 +317,121 @@ pub fn is_valid_block_file<P>(path: P) -> bool
 where
     P: AsRef<Path>,
 {
+    let path = path.as_ref();
+    if is_gzip_block_file(path) {
+        return is_valid_file_name(path.with_extension(""), &StateHash::is_valid);
+    }
+
     is_valid_file_name(path, &StateHash::is_valid)
 }
 
+/// Checks whether the block file is gzip compressed, i.e. `*.json.gz`
+pub fn is_gzip_block_file(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some("gz")
+}
+
+/// Opens the block file, transparently decompressing `*.json.gz` files
+pub fn open_block_file(path: &Path) -> anyhow::Result<Box<dyn Read>> {
+    let reader = BufReader::new(File::open(path)?);
+    if is_gzip_block_file(path) {
+        return Ok(Box::new(GzDecoder::new(reader)));
+    }
+
+    Ok(Box::new(reader))
+}
+
+/// Size of the block file's (decompressed) contents
+///
+/// Gzip files record their decompressed size (mod 2^32) in the last 4 bytes
+pub fn block_file_size(path: &Path) -> std::io::Result<u64> {
+    if !is_gzip_block_file(path) {
+        return Ok(path.metadata()?.len());
+    }
+
+    let mut file = File::open(path)?;
+    let mut isize = [0; 4];
+    file.seek(SeekFrom::End(-4))?;
+    file.read_exact(&mut isize)?;
+
+    Ok(u32::from_le_bytes(isize) as u64)
+}
+
+/// Reads the block file's (decompressed) contents
+pub fn read_block_file(path: &Path) -> anyhow::Result<Vec<u8>> {
+    let mut contents = vec![];
+    open_block_file(path)?.read_to_end(&mut contents)?;
+    Ok(contents)
+}
+
+/// Collects the valid block files in `blocks_dir`
+///
+/// If `recursive`, subdirectories are scanned up to [BLOCKS_DIR_MAX_DEPTH]
+/// levels deep, e.g. blocks nested by height range. Block files with the same
+/// height & state hash found in several subdirectories are only collected once.
+pub fn get_block_file_paths(blocks_dir: &Path, recursive: bool) -> anyhow::Result<Vec<PathBuf>> {
+    let mut seen = HashSet::new();
+    let mut paths = vec![];
+    let max_depth = if recursive { BLOCKS_DIR_MAX_DEPTH } else { 0 };
+
+    collect_block_file_paths(blocks_dir, max_depth, &mut seen, &mut paths)?;
+    Ok(paths)
+}
+
+fn collect_block_file_paths(
+    dir: &Path,
+    depth: u32,
+    seen: &mut HashSet<(u32, String)>,
+    paths: &mut Vec<PathBuf>,
+) -> anyhow::Result<()> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            if depth > 0 {
+                collect_block_file_paths(&path, depth - 1, seen, paths)?;
+            }
+            continue;
+        }
+
+        if !is_valid_block_file(&path) {
+            continue;
+        }
+
+        let (height, state_hash) = extract_height_and_hash(&path);
+        if seen.insert((height, state_hash.to_string())) {
+            // keep paths relative to the current dir, like glob
+            let path = path.strip_prefix(".").unwrap_or(&path).to_path_buf();
+            paths.push(path);
+        } else {
+            debug!("Skipping duplicate block file {}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Block file name isn't of the form `{network}-{block height}-{state hash}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockFileNameError(pub PathBuf);
+
+/// Strips the `.json` or `.json.gz` extension from the block file's name
+fn block_file_stem(path: &Path) -> &str {
+    try_block_file_stem(path).expect("Failed to extract filename from path")
+}
+
+fn try_block_file_stem(path: &Path) -> Result<&str, BlockFileNameError> {
+    let name = path
+        .file_name()
+        .and_then(|x| x.to_str())
+        .ok_or_else(|| BlockFileNameError(path.to_path_buf()))?;
+    let name = name.strip_suffix(".gz").unwrap_or(name);
+    Ok(name.strip_suffix(".json").unwrap_or(name))
+}
+
 pub fn sort_by_height_and_lexicographical_order(paths: &mut [&std::path::PathBuf]) {
     paths.sort_by(|a, b| {
         let (height_a, hash_a) = extract_height_and_hash(a);
@@ -309,10 +445,7 @@ pub fn sort_by_height_and_lexicographical_order(paths: &mut [&std::path::PathBuf
 }
 
 pub fn extract_height_and_hash(path: &Path) -> (u32, &str) {
-    let filename = path
-        .file_stem()
-        .and_then(|x| x.to_str())
-        .expect("Failed to extract filename from path");
+    let filename = block_file_stem(path);
 
     let mut parts = filename.split('-');
 
@@ -340,7 +473,7 @@ pub fn extract_state_hash(path: &Path) -> &str {
 }
 
 pub fn extract_network(path: &Path) -> Network {
-    let name = path.file_stem().and_then(|x| x.to_str()).unwrap();
+    let name = block_file_stem(path);
     let dash_pos = name.find('-').unwrap();
     Network::from(&name[..dash_pos])
 }
@@ -348,23 +481,25 @@ pub fn extract_network(path: &Path) -> Network {
 /// Extracts all three values from file name
 ///
 /// Valid block file names have the form: {network}-{block height}-{state hash}
-pub fn extract_network_height_hash(path: &Path) -> (Network, u32, StateHash) {
-    let name = path.file_stem().and_then(|x| x.to_str()).unwrap();
-    let network_end = name.find('-').expect("valid block file has network");
-    let height_end = name[network_end + 1..]
-        .find('-')
-        .expect("valid block file has height");
-    let block_height = name[network_end + 1..][..height_end]
-        .parse::<u32>()
-        .expect("block height is u32");
-    let state_hash = &name[network_end + 1..][height_end + 1..];
-    (
-        Network::from(&name[..network_end]),
-        block_height,
-        state_hash.into(),
-    )
+pub fn extract_network_height_hash(
+    path: &Path,
+) -> Result<(Network, u32, StateHash), BlockFileNameError> {
+    let invalid = || BlockFileNameError(path.to_path_buf());
+    let name = try_block_file_stem(path)?;
+    let (network, rest) = name.split_once('-').ok_or_else(invalid)?;
+    let (block_height, state_hash) = rest.split_once('-').ok_or_else(invalid)?;
+    let block_height = block_height.parse::<u32>().map_err(|_| invalid())?;
+    Ok((Network::from(network), block_height, state_hash.into()))
 }
 
+impl std::fmt::Display for BlockFileNameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid block file name {}", self.0.display())
+    }
+}
+
+impl std::error::Error for BlockFileNameError {}
+
 #[cfg(test)]
 mod block_tests {
     use super::*;
@@ -420,7 +555,7 @@ mod block_tests {
                 2,
                 StateHash::from("3NLyWnjZqUECniE1q719CoLmes6WDQAod4vrTeLfN7XXJbHv6EHH")
             ),
-            extract_network_height_hash(path0)
+            extract_network_height_hash(path0).unwrap()
         );
         assert_eq!(
             (
@@ -428,7 +563,43 @@ mod block_tests {
                 3,
                 StateHash::from("3NKd5So3VNqGZtRZiWsti4yaEe1fX79yz5TbfG6jBZqgMnCQQp3R")
             ),
-            extract_network_height_hash(path1)
+            extract_network_height_hash(path1).unwrap()
+        );
+
+        // malformed names are errors
+        for name in [
+            "mainnet.json",
+            "mainnet-3NLyWnjZqUECniE1q719CoLmes6WDQAod4vrTeLfN7XXJbHv6EHH.json",
+            "mainnet-two-3NLyWnjZqUECniE1q719CoLmes6WDQAod4vrTeLfN7XXJbHv6EHH.json",
+        ] {
+            let path = Path::new(name);
+            assert_eq!(
+                extract_network_height_hash(path),
+                Err(BlockFileNameError(path.to_path_buf()))
+            );
+        }
+    }
+
+    #[test]
+    fn gzip_block_files() {
+        let path = Path::new(
+            "/tmp/blocks/100-199/mainnet-105-3NKd5So3VNqGZtRZiWsti4yaEe1fX79yz5TbfG6jBZqgMnCQQp3R.json.gz",
+        );
+
+        assert!(is_gzip_block_file(path));
+        assert!(is_valid_block_file(path));
+        assert!(!is_valid_block_file(path.with_extension("tar.gz")));
+        assert_eq!(
+            (
+                Network::Mainnet,
+                105,
+                StateHash::from("3NKd5So3VNqGZtRZiWsti4yaEe1fX79yz5TbfG6jBZqgMnCQQp3R")
+            ),
+            extract_network_height_hash(path)
+        );
+        assert_eq!(
+            extract_state_hash(path),
+            "3NKd5So3VNqGZtRZiWsti4yaEe1fX79yz5TbfG6jBZqgMnCQQp3R"
         );
     }
 
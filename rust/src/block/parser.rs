@@ -1,19 +1,31 @@
 use super::{
-    extract_block_height,
+    block_file_size, extract_block_height,
     genesis_state_hash::GenesisStateHash,
+    get_block_file_paths,
     precomputed::{PcbVersion, PrecomputedBlock},
 };
 use crate::{
     canonicity::canonical_chain_discovery::discovery, chain::ChainData,
-    utility::functions::calculate_total_size,
+    constants::BLOCK_PARSER_PARSE_AHEAD, utility::functions::calculate_total_size,
 };
 use anyhow::{anyhow, bail};
-use glob::glob;
 use log::{debug, info};
 use std::{
+    collections::VecDeque,
     path::{Path, PathBuf},
     vec::IntoIter,
 };
+use tokio::task::JoinHandle;
+
+/// Block file which could not be read or parsed
+///
+/// The parser has already moved past the file, so ingestion can skip it &
+/// continue with the next block
+#[derive(Debug)]
+pub struct BlockParseError {
+    pub path: PathBuf,
+    pub error: anyhow::Error,
+}
 
 /// Splits block paths into three collections:
 /// - _deep canonical_ (chain of canonical blocks with at least
@@ -24,6 +36,11 @@ use std::{
 ///
 /// Traverses deep canoncial, recent, then orphaned (orphaned paths bypass the
 /// witness tree)
+///
+/// Up to `parse_ahead` blocks are parsed on blocking worker threads ahead of
+/// the consumed block, while it's applied. Blocks are still returned in
+/// traversal order.
+#[derive(Default)]
 pub struct BlockParser {
     pub blocks_dir: PathBuf,
     pub blocks_processed: u32,
@@ -36,9 +53,30 @@ pub struct BlockParser {
     pub version: PcbVersion,
     pub chain_data: ChainData,
 
+    /// Max number of blocks parsed ahead of the consumed block
+    pub parse_ahead: usize,
+
     canonical_paths: IntoIter<PathBuf>,
     recent_paths: IntoIter<PathBuf>,
     orphaned_paths: IntoIter<PathBuf>,
+
+    /// Blocks being parsed ahead, in traversal order
+    pending: VecDeque<PendingBlock>,
+}
+
+/// Which of the parser's paths a block file comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PathKind {
+    DeepCanonical,
+    Recent,
+    Orphaned,
+}
+
+/// Block file being parsed ahead of the consumed block
+struct PendingBlock {
+    path: PathBuf,
+    kind: PathKind,
+    parsed: JoinHandle<anyhow::Result<(PrecomputedBlock, PcbVersion, u64)>>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -56,14 +94,35 @@ pub enum ParsedBlock {
 }
 
 impl BlockParser {
+    /// Paths of the blocks which haven't been consumed yet
     pub fn paths(&self) -> BlockParserPaths {
+        let pending = |kind| {
+            self.pending
+                .iter()
+                .filter(move |pending| pending.kind == kind)
+                .map(|pending| pending.path.clone())
+        };
+
         BlockParserPaths {
-            canonical_paths: self.canonical_paths.clone().collect(),
-            recent_paths: self.recent_paths.clone().collect(),
-            orphaned_paths: self.orphaned_paths.clone().collect(),
+            canonical_paths: pending(PathKind::DeepCanonical)
+                .chain(self.canonical_paths.clone())
+                .collect(),
+            recent_paths: pending(PathKind::Recent)
+                .chain(self.recent_paths.clone())
+                .collect(),
+            orphaned_paths: pending(PathKind::Orphaned)
+                .chain(self.orphaned_paths.clone())
+                .collect(),
         }
     }
 
+    /// Parse at most `parse_ahead` blocks ahead of the consumed block (blocks
+    /// are parsed one at a time with 0 or 1)
+    pub fn with_parse_ahead(mut self, parse_ahead: usize) -> Self {
+        self.parse_ahead = parse_ahead;
+        self
+    }
+
     /// Returns a new block parser which employs canonical chain discovery
     pub async fn new_with_canonical_chain_discovery(
         blocks_dir: &Path,
@@ -71,6 +130,7 @@ impl BlockParser {
         canonical_threshold: u32,
         do_not_ingest_orphan_blocks: bool,
         reporting_freq: u32,
+        recursive: bool,
     ) -> anyhow::Result<Self> {
         Self::with_canonical_chain_discovery(
             blocks_dir,
@@ -78,6 +138,7 @@ impl BlockParser {
             canonical_threshold,
             do_not_ingest_orphan_blocks,
             reporting_freq,
+            recursive,
         )
         .await
     }
@@ -88,8 +149,9 @@ impl BlockParser {
         blocks_dir: &Path,
         version: PcbVersion,
         min_length_filter: Option<u32>,
+        recursive: bool,
     ) -> anyhow::Result<Self> {
-        Self::new_length_sorted_filtered(blocks_dir, version, min_length_filter, None)
+        Self::new_length_sorted_filtered(blocks_dir, version, min_length_filter, None, recursive)
     }
 
     /// Returns a new length-sorted block parser with paths filtered by min or
@@ -99,15 +161,12 @@ impl BlockParser {
         version: PcbVersion,
         min_length: Option<u32>,
         max_length: Option<u32>,
+        recursive: bool,
     ) -> anyhow::Result<Self> {
         if blocks_dir.exists() {
             let blocks_dir = blocks_dir.to_owned();
-            let mut paths: Vec<PathBuf> = glob(&format!("{}/*-*-*.json", blocks_dir.display()))?
-                .flatten()
-                .collect();
-            let total_num_bytes = paths
-                .iter()
-                .fold(0, |acc, p| acc + p.metadata().unwrap().len());
+            let mut paths = get_block_file_paths(&blocks_dir, recursive)?;
+            let total_num_bytes = calculate_total_size(&paths);
 
             if let Some(min) = min_length {
                 paths.retain(|p| extract_block_height(p) > min)
@@ -132,6 +191,8 @@ impl BlockParser {
                 canonical_paths: vec![].into_iter(),
                 orphaned_paths: vec![].into_iter(),
                 chain_data: ChainData::default(),
+                parse_ahead: BLOCK_PARSER_PARSE_AHEAD,
+                pending: VecDeque::new(),
             })
         } else {
             Ok(Self::empty(blocks_dir, &[]))
@@ -142,9 +203,7 @@ impl BlockParser {
     pub fn new_testing(blocks_dir: &Path) -> anyhow::Result<Self> {
         if blocks_dir.exists() {
             let blocks_dir = blocks_dir.to_owned();
-            let mut paths: Vec<PathBuf> = glob(&format!("{}/*-*-*.json", blocks_dir.display()))?
-                .flatten()
-                .collect();
+            let mut paths = get_block_file_paths(&blocks_dir, true)?;
             paths.sort_by_cached_key(|path| extract_block_height(path));
 
             println!("===== Testing block parser paths =====");
@@ -169,19 +228,17 @@ impl BlockParser {
         canonical_threshold: u32,
         do_not_ingest_orphan_blocks: bool,
         reporting_freq: u32,
+        recursive: bool,
     ) -> anyhow::Result<Self> {
         info!("Block parser with canonical chain discovery");
         if blocks_dir.exists() {
-            let pattern = format!("{}/*-*-*.json", blocks_dir.display());
             let blocks_dir = blocks_dir.to_owned();
-            let paths: Vec<PathBuf> = glob(&pattern)?.flatten().collect();
+            let paths = get_block_file_paths(&blocks_dir, recursive)?;
             if let Ok((canonical_paths, recent_paths, orphaned_paths)) =
                 discovery(canonical_threshold, reporting_freq, paths.iter().collect())
             {
                 info!("Canonical chain discovery successful");
-                let deep_canonical_bytes = canonical_paths
-                    .iter()
-                    .fold(0, |acc, p| acc + p.metadata().unwrap().len());
+                let deep_canonical_bytes = calculate_total_size(&canonical_paths);
 
                 let total_num_bytes = if do_not_ingest_orphan_blocks {
                     calculate_total_size(&canonical_paths) + calculate_total_size(&recent_paths)
@@ -213,6 +270,8 @@ impl BlockParser {
                         orphaned_paths.into_iter()
                     },
                     chain_data: ChainData::default(),
+                    parse_ahead: BLOCK_PARSER_PARSE_AHEAD,
+                    pending: VecDeque::new(),
                 })
             } else {
                 Ok(Self::empty(&blocks_dir, &paths))
@@ -222,34 +281,57 @@ impl BlockParser {
         }
     }
 
-    fn consume_block(
-        &mut self,
+    /// Reads & parses the block file with the PCB version of its chain
+    fn parse_block_file(
         path: &Path,
-        designation: &dyn Fn(PrecomputedBlock) -> ParsedBlock,
-    ) -> anyhow::Result<Option<(ParsedBlock, u64)>> {
-        let block_bytes = path.metadata().unwrap().len();
-        let genesis_state_hash = GenesisStateHash::from_path(path)?;
-        let curr_pcb_version = self.version.clone();
-        let (new_pcb_version, _) = self
-            .chain_data
+        chain_data: &ChainData,
+    ) -> anyhow::Result<(PrecomputedBlock, PcbVersion, u64)> {
+        let parse_error = |error: anyhow::Error| BlockParseError {
+            path: path.to_path_buf(),
+            error,
+        };
+
+        let block_bytes = block_file_size(path).map_err(|e| parse_error(e.into()))?;
+        let genesis_state_hash = GenesisStateHash::from_path(path).map_err(parse_error)?;
+        let (pcb_version, _) = chain_data
             .0
             .get(&genesis_state_hash)
             .cloned()
-            .expect("PCB version");
+            .ok_or_else(|| {
+                parse_error(anyhow!("Unknown genesis state hash {genesis_state_hash}"))
+            })?;
 
-        // if the PCB version changed, change block parser version
-        if curr_pcb_version != new_pcb_version {
-            debug!("Changing block parser version: {curr_pcb_version} -> {new_pcb_version}");
-            self.version = new_pcb_version.clone();
+        let block = PrecomputedBlock::parse_file(path, pcb_version.clone()).map_err(parse_error)?;
+        Ok((block, pcb_version, block_bytes))
+    }
+
+    /// Next path in traversal order
+    fn next_path(&mut self) -> Option<(PathBuf, PathKind)> {
+        if let Some(next_path) = self.canonical_paths.next() {
+            Some((next_path, PathKind::DeepCanonical))
+        } else if let Some(next_path) = self.recent_paths.next() {
+            Some((next_path, PathKind::Recent))
+        } else {
+            self.orphaned_paths
+                .next()
+                .map(|next_path| (next_path, PathKind::Orphaned))
         }
+    }
 
-        match PrecomputedBlock::parse_file(path, new_pcb_version).map(designation) {
-            Ok(parsed_block) => {
-                self.blocks_processed += 1;
-                self.bytes_processed += block_bytes;
-                Ok(Some((parsed_block, block_bytes)))
-            }
-            Err(e) => bail!("Block parsing error: {e}"),
+    /// Starts parsing the next blocks, up to `parse_ahead`
+    fn spawn_parse_ahead(&mut self) {
+        while self.pending.len() < self.parse_ahead.max(1) {
+            let Some((path, kind)) = self.next_path() else {
+                break;
+            };
+
+            let chain_data = self.chain_data.clone();
+            let parsed = tokio::task::spawn_blocking({
+                let path = path.clone();
+                move || Self::parse_block_file(&path, &chain_data)
+            });
+
+            self.pending.push_back(PendingBlock { path, kind, parsed });
         }
     }
 
@@ -258,19 +340,43 @@ impl BlockParser {
     /// - recent
     /// - orphaned
     pub async fn next_block(&mut self) -> anyhow::Result<Option<(ParsedBlock, u64)>> {
-        if let Some(next_path) = self.canonical_paths.next() {
-            return self.consume_block(&next_path, &ParsedBlock::DeepCanonical);
-        }
+        Ok(self
+            .next_block_with_path()
+            .await?
+            .map(|(parsed_block, block_bytes, _)| (parsed_block, block_bytes)))
+    }
 
-        if let Some(next_path) = self.recent_paths.next() {
-            return self.consume_block(&next_path, &ParsedBlock::Recent);
-        }
+    /// Same as [BlockParser::next_block], also returns the block's path
+    pub async fn next_block_with_path(
+        &mut self,
+    ) -> anyhow::Result<Option<(ParsedBlock, u64, PathBuf)>> {
+        self.spawn_parse_ahead();
+        let Some(PendingBlock { path, kind, parsed }) = self.pending.pop_front() else {
+            return Ok(None);
+        };
+
+        // keep the workers busy while the block is applied
+        self.spawn_parse_ahead();
+        let (block, new_pcb_version, block_bytes) = parsed.await??;
 
-        if let Some(next_path) = self.orphaned_paths.next() {
-            return self.consume_block(&next_path, &ParsedBlock::Orphaned);
+        // if the PCB version changed, change block parser version
+        if self.version != new_pcb_version {
+            debug!(
+                "Changing block parser version: {} -> {new_pcb_version}",
+                self.version
+            );
+            self.version = new_pcb_version;
         }
 
-        Ok(None)
+        let parsed_block = match kind {
+            PathKind::DeepCanonical => ParsedBlock::DeepCanonical(block),
+            PathKind::Recent => ParsedBlock::Recent(block),
+            PathKind::Orphaned => ParsedBlock::Orphaned(block),
+        };
+
+        self.blocks_processed += 1;
+        self.bytes_processed += block_bytes;
+        Ok(Some((parsed_block, block_bytes, path)))
     }
 
     /// Gets the precomputed block with supplied `state_hash`, it must exist
@@ -297,9 +403,7 @@ impl BlockParser {
     }
 
     fn empty(blocks_dir: &Path, paths: &[PathBuf]) -> Self {
-        let total_num_bytes = paths
-            .iter()
-            .fold(0, |acc, p| acc + p.metadata().unwrap().len());
+        let total_num_bytes = calculate_total_size(paths);
         Self {
             total_num_bytes,
             bytes_processed: 0,
@@ -314,6 +418,8 @@ impl BlockParser {
             recent_paths: Vec::from(paths).into_iter(),
             orphaned_paths: vec![].into_iter(),
             chain_data: ChainData::default(),
+            parse_ahead: BLOCK_PARSER_PARSE_AHEAD,
+            pending: VecDeque::new(),
         }
     }
 }
@@ -328,6 +434,19 @@ impl From<ParsedBlock> for PrecomputedBlock {
     }
 }
 
+impl std::fmt::Display for BlockParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Block parsing error {}: {:#}",
+            self.path.display(),
+            self.error
+        )
+    }
+}
+
+impl std::error::Error for BlockParseError {}
+
 #[cfg(test)]
 mod tests {
     use crate::{base::state_hash::StateHash, chain::Network};
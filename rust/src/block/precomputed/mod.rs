@@ -9,7 +9,7 @@ use super::{
     post_hardfork::{
         account_accessed::AccountAccessed, account_created::AccountCreated, token_used::TokenUsed,
     },
-    Block, StateHash, VrfOutput,
+    read_block_file, Block, StateHash, VrfOutput,
 };
 use crate::{
     base::{blockchain_length::BlockchainLength, public_key::PublicKey},
@@ -137,10 +137,30 @@ impl PrecomputedBlock {
         Ok(precomputed_block)
     }
 
+    /// JSON representation of the block in the precomputed block file format,
+    /// i.e. the `data` object of V2 block files
+    pub fn to_file_json(&self) -> anyhow::Result<serde_json::Value> {
+        Ok(match self {
+            Self::V1(v1) => serde_json::to_value(BlockFileV1 {
+                scheduled_time: v1.scheduled_time,
+                protocol_state: v1.protocol_state.clone().into(),
+                staged_ledger_diff: v1.staged_ledger_diff.clone().into(),
+            })?,
+            Self::V2(v2) => serde_json::to_value(BlockFileDataV2 {
+                scheduled_time: v2.scheduled_time,
+                protocol_state: v2.protocol_state.clone(),
+                staged_ledger_diff: v2.staged_ledger_diff.clone(),
+                tokens_used: v2.tokens_used.clone(),
+                accounts_accessed: v2.accounts_accessed.clone(),
+                accounts_created: v2.accounts_created.clone(),
+            })?,
+        })
+    }
+
     /// Parses the precomputed block if the path is a valid block file
     pub fn parse_file(path: &Path, version: PcbVersion) -> anyhow::Result<Self> {
-        let (network, blockchain_length, state_hash) = extract_network_height_hash(path);
-        let contents = std::fs::read(path)?;
+        let (network, blockchain_length, state_hash) = extract_network_height_hash(path)?;
+        let contents = read_block_file(path)?;
         let precomputed_block = PrecomputedBlock::from_file_contents(
             BlockFileContents {
                 contents,
@@ -193,6 +213,63 @@ impl PrecomputedBlock {
         }
     }
 
+    /// Accounts created by each user command, in block order
+    ///
+    /// Post-hardfork blocks only list accounts created per block, so each is
+    /// attributed to the first applied command which can create it
+    pub fn command_accounts_created(&self) -> Vec<Vec<AccountCreated>> {
+        let commands = self.commands();
+        match self {
+            Self::V1(_) => commands
+                .iter()
+                .map(|cmd| {
+                    let status = cmd.status_data();
+                    let mut accounts_created = vec![];
+
+                    if let Some(fee) = status.fee_payer_account_creation_fee_paid() {
+                        accounts_created.push(AccountCreated {
+                            public_key: cmd.fee_payer_pk(),
+                            token: TokenAddress::default(),
+                            creation_fee: fee.into(),
+                        });
+                    }
+                    if let Some(fee) = status.receiver_account_creation_fee_paid() {
+                        accounts_created.push(AccountCreated {
+                            public_key: cmd.receiver().first().expect("receiver").to_owned(),
+                            token: TokenAddress::default(),
+                            creation_fee: fee.into(),
+                        });
+                    }
+                    accounts_created
+                })
+                .collect(),
+            Self::V2(_) => {
+                let mut unattributed = self.accounts_created_v2();
+                commands
+                    .into_iter()
+                    .map(|cmd| {
+                        if !cmd.is_applied() {
+                            return vec![];
+                        }
+
+                        SignedCommand::from(cmd)
+                            .receiver_accounts()
+                            .into_iter()
+                            .filter_map(|(pk, token)| {
+                                unattributed
+                                    .iter()
+                                    .position(|created| {
+                                        created.public_key == pk && created.token == token
+                                    })
+                                    .map(|idx| unattributed.remove(idx))
+                            })
+                            .collect()
+                    })
+                    .collect()
+            }
+        }
+    }
+
     pub fn tokens_used(&self) -> Vec<TokenUsed> {
         match self {
             Self::V1(_v1) => vec![],
@@ -209,12 +286,20 @@ impl PrecomputedBlock {
     // Staged ledger diff //
     ////////////////////////
 
+    /// User commands in ledger application order, pre diff then post diff.
+    /// A command's position is its index in the block
     pub fn commands(&self) -> Vec<UserCommandWithStatus> {
         let mut commands = self.commands_pre_diff();
         commands.append(&mut self.commands_post_diff());
         commands
     }
 
+    /// Index of the block's first internal command. Internal commands are
+    /// applied after all user commands, see [Self::commands]
+    pub fn internal_commands_start_index(&self) -> u32 {
+        (self.commands_pre_diff().len() + self.commands_post_diff().len()) as u32
+    }
+
     pub fn commands_pre_diff(&self) -> Vec<UserCommandWithStatus> {
         match self {
             Self::V1(v1) => v1
@@ -433,6 +518,39 @@ impl PrecomputedBlock {
         }
     }
 
+    /// First pass ledger hash of the ledger proof statement target.
+    /// Pre-hardfork blocks have a single pass, their staged ledger hash.
+    pub fn first_pass_ledger_hash(&self) -> LedgerHash {
+        match self {
+            Self::V1(_) => self.staged_ledger_hash(),
+            Self::V2(v2) => v2
+                .protocol_state
+                .body
+                .blockchain_state
+                .ledger_proof_statement
+                .target
+                .first_pass_ledger
+                .to_owned(),
+        }
+    }
+
+    /// Second pass ledger hash of the ledger proof statement target, in which
+    /// zkapp account updates are applied (post-hardfork only)
+    pub fn second_pass_ledger_hash(&self) -> Option<LedgerHash> {
+        match self {
+            Self::V1(_) => None,
+            Self::V2(v2) => Some(
+                v2.protocol_state
+                    .body
+                    .blockchain_state
+                    .ledger_proof_statement
+                    .target
+                    .second_pass_ledger
+                    .to_owned(),
+            ),
+        }
+    }
+
     pub fn completed_works(&self) -> Vec<SnarkWorkSummary> {
         let mut completed_works = self.completed_works_post_diff().unwrap_or_default();
         completed_works.append(&mut self.completed_works_pre_diff());
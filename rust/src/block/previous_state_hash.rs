@@ -1,18 +1,13 @@
-use crate::block::StateHash;
+use crate::block::{open_block_file, StateHash};
 use anyhow::bail;
-use std::{
-    fs::File,
-    io::{BufReader, Read},
-    path::Path,
-};
+use std::{io::Read, path::Path};
 
 #[derive(PartialEq, Eq)]
 pub struct PreviousStateHash(StateHash);
 
 impl PreviousStateHash {
     pub fn from_path(path: &Path) -> anyhow::Result<StateHash> {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
+        let reader = open_block_file(path)?;
         let mut buffer = String::with_capacity(200);
 
         // Limit the reader to read only the first 200 bytes
@@ -0,0 +1,159 @@
+//! Block receipt times & producer latency
+
+use crate::{constants::global_slot_to_millis, utility::functions::percentile};
+use serde::{Deserialize, Serialize};
+use std::{path::Path, time::UNIX_EPOCH};
+
+/// When the indexer received a block
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockReceipt {
+    /// Block file's mtime at ingest (epoch millis)
+    pub file_modified_at: Option<u64>,
+
+    /// Wall-clock time the filesystem watcher saw the block file, or the
+    /// HTTP ingestion endpoint received the block (epoch millis)
+    pub watcher_seen_at: Option<u64>,
+
+    /// Only blocks picked up by the watcher have meaningful receipt times,
+    /// blocks ingested from a blocks dir at startup may come from historical
+    /// mirrors
+    pub latency_reliable: bool,
+}
+
+/// Summary of block latencies (seconds)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LatencyStats {
+    pub num_blocks: u32,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+}
+
+impl BlockReceipt {
+    /// Receipt for a block file ingested at startup
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            file_modified_at: file_modified_millis(path)?,
+            watcher_seen_at: None,
+            latency_reliable: false,
+        })
+    }
+
+    /// Receipt for a block file seen by the filesystem watcher at
+    /// `seen_at` (epoch millis)
+    pub fn from_watcher(path: &Path, seen_at: u64) -> anyhow::Result<Self> {
+        Ok(Self {
+            file_modified_at: file_modified_millis(path)?,
+            watcher_seen_at: Some(seen_at),
+            latency_reliable: true,
+        })
+    }
+
+    /// Receipt for a block posted to the HTTP ingestion endpoint at
+    /// `received_at` (epoch millis)
+    pub fn from_http(received_at: u64) -> Self {
+        Self {
+            file_modified_at: None,
+            watcher_seen_at: Some(received_at),
+            latency_reliable: true,
+        }
+    }
+
+    /// Prefers the watcher time over the file's mtime
+    pub fn received_at(&self) -> Option<u64> {
+        self.watcher_seen_at.or(self.file_modified_at)
+    }
+
+    /// Seconds between the nominal start of `global_slot` & receipt
+    pub fn latency_seconds(&self, global_slot: u32) -> Option<f64> {
+        self.received_at()
+            .map(|received_at| latency_seconds(received_at, global_slot))
+    }
+}
+
+impl LatencyStats {
+    /// Computes stats for the given latencies, `None` if there are none
+    pub fn new(mut latencies: Vec<f64>) -> Option<Self> {
+        if latencies.is_empty() {
+            return None;
+        }
+
+        latencies.sort_by(f64::total_cmp);
+        let num_blocks = latencies.len();
+        let mean = latencies.iter().sum::<f64>() / num_blocks as f64;
+
+        Some(Self {
+            num_blocks: num_blocks as u32,
+            min: latencies[0],
+            max: latencies[num_blocks - 1],
+            mean,
+            p50: percentile(&latencies, 50),
+            p90: percentile(&latencies, 90),
+            p99: percentile(&latencies, 99),
+        })
+    }
+}
+
+/// Seconds between the nominal start of `global_slot` & `received_at` (epoch
+/// millis), negative if received before the slot started
+pub fn latency_seconds(received_at: u64, global_slot: u32) -> f64 {
+    (received_at as i64 - global_slot_to_millis(global_slot) as i64) as f64 / 1000.0
+}
+
+fn file_modified_millis(path: &Path) -> anyhow::Result<Option<u64>> {
+    Ok(path
+        .metadata()?
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{HARDFORK_GENESIS_TIMESTAMP, MAINNET_GENESIS_TIMESTAMP};
+
+    #[test]
+    fn slot_start_latency() {
+        assert_eq!(global_slot_to_millis(0), MAINNET_GENESIS_TIMESTAMP);
+        assert_eq!(global_slot_to_millis(564480), HARDFORK_GENESIS_TIMESTAMP);
+
+        let slot_start = global_slot_to_millis(10);
+        assert_eq!(latency_seconds(slot_start + 12_500, 10), 12.5);
+        assert_eq!(latency_seconds(slot_start - 1_000, 10), -1.0);
+    }
+
+    #[test]
+    fn received_at_prefers_watcher() {
+        let mut receipt = BlockReceipt {
+            file_modified_at: Some(MAINNET_GENESIS_TIMESTAMP + 5_000),
+            watcher_seen_at: None,
+            latency_reliable: false,
+        };
+        assert_eq!(receipt.latency_seconds(0), Some(5.0));
+
+        receipt.watcher_seen_at = Some(MAINNET_GENESIS_TIMESTAMP + 7_000);
+        assert_eq!(receipt.latency_seconds(0), Some(7.0));
+    }
+
+    #[test]
+    fn latency_stats() {
+        assert_eq!(LatencyStats::new(vec![]), None);
+
+        let stats = LatencyStats::new((1..=100).rev().map(f64::from).collect()).unwrap();
+        assert_eq!(stats.num_blocks, 100);
+        assert_eq!(stats.min, 1.0);
+        assert_eq!(stats.max, 100.0);
+        assert_eq!(stats.mean, 50.5);
+        assert_eq!(stats.p50, 50.0);
+        assert_eq!(stats.p90, 90.0);
+        assert_eq!(stats.p99, 99.0);
+
+        let stats = LatencyStats::new(vec![3.0]).unwrap();
+        assert_eq!((stats.p50, stats.p99), (3.0, 3.0));
+    }
+}
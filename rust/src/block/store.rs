@@ -1,14 +1,25 @@
-use super::{precomputed::PcbVersion, BlockComparison};
+use super::{
+    activity::{BlockActivity, DailyActivity},
+    fee_stats::BlockFeeStats,
+    missing::MissingBlock,
+    precomputed::PcbVersion,
+    receipt::BlockReceipt,
+    supply::{BlockSupply, EpochSupply, Supply},
+    BlockComparison,
+};
 use crate::{
     base::public_key::PublicKey,
     block::{precomputed::PrecomputedBlock, StateHash},
     event::db::DbEvent,
-    ledger::diff::{account::AccountDiff, LedgerDiff},
+    ledger::{
+        diff::{account::AccountDiff, LedgerDiff},
+        token::{provenance::TokenProvenance, TokenAddress},
+    },
     store::DbUpdate,
 };
 use speedb::{DBIterator, Direction, IteratorMode, WriteBatch};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BlockUpdate {
     pub state_hash: StateHash,
     pub blockchain_length: u32,
@@ -241,6 +252,34 @@ pub trait BlockStore {
         batch: &mut WriteBatch,
     ) -> anyhow::Result<()>;
 
+    /// Get the indexed `(owner, parent token)` pairs of the given tokens
+    fn get_token_owners(
+        &self,
+        tokens: &[TokenAddress],
+    ) -> anyhow::Result<Vec<Option<(PublicKey, TokenAddress)>>>;
+
+    /// Index the owners of the tokens used in the given block
+    fn set_token_owners_batch(
+        &self,
+        block: &PrecomputedBlock,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()>;
+
+    /// Record the provenance of the tokens first used in the given block &
+    /// upgrade unknown provenance tokens whose owners are now known
+    fn update_token_provenance_batch(
+        &self,
+        block: &PrecomputedBlock,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()>;
+
+    /// Get the provenance of the given token
+    fn get_token_provenance(&self, token: &TokenAddress)
+        -> anyhow::Result<Option<TokenProvenance>>;
+
+    /// Get the number of tokens with unknown provenance
+    fn get_num_unknown_provenance_tokens(&self) -> anyhow::Result<u32>;
+
     /// Index the epoch slot for a block
     fn add_epoch_slots_produced(
         &self,
@@ -277,6 +316,103 @@ pub trait BlockStore {
         other: &StateHash,
     ) -> anyhow::Result<Option<std::cmp::Ordering>>;
 
+    /// Index the block's receipt times
+    fn set_block_receipt(
+        &self,
+        state_hash: &StateHash,
+        receipt: &BlockReceipt,
+    ) -> anyhow::Result<()>;
+
+    /// Get the block's receipt times
+    fn get_block_receipt(&self, state_hash: &StateHash) -> anyhow::Result<Option<BlockReceipt>>;
+
+    /// Index the block's user command fee stats
+    fn set_block_fee_stats_batch(
+        &self,
+        state_hash: &StateHash,
+        fee_stats: &BlockFeeStats,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()>;
+
+    /// Get the block's user command fee stats
+    fn get_block_fee_stats(&self, state_hash: &StateHash) -> anyhow::Result<Option<BlockFeeStats>>;
+
+    /// Index the block's account activity
+    fn set_block_activity_batch(
+        &self,
+        state_hash: &StateHash,
+        activity: &BlockActivity,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()>;
+
+    /// Get the block's account activity
+    fn get_block_activity(&self, state_hash: &StateHash) -> anyhow::Result<Option<BlockActivity>>;
+
+    /// Add the canonical block's activity to its day's rollup (idempotent)
+    fn apply_block_activity(&self, state_hash: &StateHash, height: u32) -> anyhow::Result<()>;
+
+    /// Remove the no longer canonical block's activity from its day's rollup
+    fn unapply_block_activity(&self, state_hash: &StateHash, height: u32) -> anyhow::Result<()>;
+
+    /// Get the day's canonical block activity rollup
+    fn get_daily_activity(&self, day: u32) -> anyhow::Result<Option<DailyActivity>>;
+
+    /// Get up to `limit` daily activity rollups from `start_day` to `end_day`
+    /// (inclusive), ascending
+    fn get_daily_activities(
+        &self,
+        start_day: u32,
+        end_day: u32,
+        limit: usize,
+    ) -> anyhow::Result<Vec<(u32, DailyActivity)>>;
+
+    /// Record the block's parent as missing if it isn't in the store & remove
+    /// the block from the missing blocks
+    fn update_missing_blocks_batch(
+        &self,
+        block: &PrecomputedBlock,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()>;
+
+    /// Get up to `limit` missing blocks, ascending by height
+    fn get_missing_blocks(&self, limit: usize) -> anyhow::Result<Vec<MissingBlock>>;
+
+    /// Get the number of missing blocks
+    fn get_num_missing_blocks(&self) -> anyhow::Result<u32>;
+
+    /// Get the activity of up to `limit` canonical blocks with date times
+    /// (epoch millis) from `start` to `end` (inclusive), ascending
+    fn get_canonical_block_activities(
+        &self,
+        start: i64,
+        end: i64,
+        limit: usize,
+    ) -> anyhow::Result<Vec<(u32, StateHash, BlockActivity)>>;
+
+    /// Index the block's supply data
+    fn set_block_supply_batch(
+        &self,
+        state_hash: &StateHash,
+        supply: &BlockSupply,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()>;
+
+    /// Get the block's supply data
+    fn get_block_supply(&self, state_hash: &StateHash) -> anyhow::Result<Option<BlockSupply>>;
+
+    /// Add the canonical block's supply to its epoch's rollup (idempotent)
+    fn apply_block_supply(&self, state_hash: &StateHash, height: u32) -> anyhow::Result<()>;
+
+    /// Remove the no longer canonical block's supply from its epoch's rollup
+    fn unapply_block_supply(&self, state_hash: &StateHash, height: u32) -> anyhow::Result<()>;
+
+    /// Get the epoch's canonical block supply rollup
+    fn get_epoch_supply(&self, epoch: u32) -> anyhow::Result<Option<EpochSupply>>;
+
+    /// Get the epoch's supply statistics, defaults to the latest epoch with
+    /// canonical blocks
+    fn get_supply(&self, epoch: Option<u32>) -> anyhow::Result<Option<Supply>>;
+
     ///////////////
     // Iterators //
     ///////////////
@@ -386,6 +522,47 @@ pub trait BlockStore {
         pk: &PublicKey,
     ) -> anyhow::Result<()>;
 
+    /// Increment the epoch & pk orphaned block production counts
+    fn increment_block_orphaned_production_count(
+        &self,
+        state_hash: &StateHash,
+    ) -> anyhow::Result<()>;
+
+    /// Decrement the epoch & pk orphaned block production counts
+    fn decrement_block_orphaned_production_count(
+        &self,
+        state_hash: &StateHash,
+    ) -> anyhow::Result<()>;
+
+    /// Count the non-canonical blocks at heights up to `settled_height` as
+    /// orphaned, each height is only settled once
+    fn settle_orphaned_blocks(&self, settled_height: u32) -> anyhow::Result<()>;
+
+    /// Get the height up to which orphaned blocks have been counted
+    fn get_orphans_settled_height(&self) -> anyhow::Result<u32>;
+
+    /// Drop the stored ledger diffs of the settled orphaned blocks at heights
+    /// up to `height`, returning the number of diffs dropped
+    ///
+    /// Dropped diffs are rebuilt from the stored block when requested
+    fn prune_orphaned_ledger_diffs(&self, height: u32) -> anyhow::Result<u32>;
+
+    /// Get the height up to which orphaned blocks' ledger diffs have been
+    /// pruned
+    fn get_orphans_pruned_height(&self) -> anyhow::Result<u32>;
+
+    /// Drop the stored blocks, ledger diffs, sort index entries, heights'
+    /// block lists, `NewBlock` & `NewBestTip` events & command/SNARK indexes
+    /// of the settled orphaned blocks at heights up to `height` in a single
+    /// batch, returning the number of blocks dropped
+    ///
+    /// The dropped blocks' metadata (height, slot, parent, etc) is kept, so
+    /// they're still known & aren't ingested again
+    fn prune_orphaned_blocks(&self, height: u32) -> anyhow::Result<u32>;
+
+    /// Get the height up to which orphaned blocks have been pruned
+    fn get_orphaned_blocks_pruned_height(&self) -> anyhow::Result<u32>;
+
     /// Get the block production count for `pk` in `epoch`
     /// (default: current epoch)
     fn get_block_production_pk_epoch_count(
@@ -410,6 +587,14 @@ pub trait BlockStore {
         epoch: Option<u32>,
     ) -> anyhow::Result<u32>;
 
+    /// Get the orphaned block production count for `pk` in `epoch`
+    /// (default: current epoch)
+    fn get_block_production_pk_orphaned_epoch_count(
+        &self,
+        pk: &PublicKey,
+        epoch: Option<u32>,
+    ) -> anyhow::Result<u32>;
+
     /// Get the total block production count for `pk`
     fn get_block_production_pk_total_count(&self, pk: &PublicKey) -> anyhow::Result<u32>;
 
@@ -438,6 +623,10 @@ pub trait BlockStore {
         epoch: Option<u32>,
     ) -> anyhow::Result<u32>;
 
+    /// Get the total orphaned block production count for `epoch`
+    /// (default: current epoch)
+    fn get_block_production_orphaned_epoch_count(&self, epoch: Option<u32>) -> anyhow::Result<u32>;
+
     /// Get the total block production count
     fn get_block_production_total_count(&self) -> anyhow::Result<u32>;
 
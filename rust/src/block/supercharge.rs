@@ -0,0 +1,67 @@
+//! Pre-hardfork supercharged coinbase audit
+//!
+//! Before the hardfork, a block's coinbase was supercharged iff its winner
+//! account had no locked tokens at the block's global slot, according to the
+//! staking ledger of the block's epoch
+
+use super::precomputed::{PcbVersion, PrecomputedBlock};
+use crate::{
+    canonicity::{store::CanonicityStore, Canonicity},
+    ledger::{coinbase::Coinbase, staking::StakingAccount, store::staking::StakingLedgerStore},
+    store::IndexerStore,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SuperchargeAudit {
+    /// The paid coinbase matches the winner account's eligibility
+    ExpectedMatches,
+
+    /// The paid coinbase doesn't match the winner account's eligibility
+    Mismatch,
+
+    /// The block's staking ledger (or its winner account) isn't in the store
+    LedgerUnavailable,
+}
+
+impl SuperchargeAudit {
+    /// Audits the coinbase of canonical pre-hardfork blocks
+    ///
+    /// Computed on demand since staking ledgers may be ingested after the
+    /// blocks of their epoch
+    pub fn audit(store: &IndexerStore, block: &PrecomputedBlock) -> anyhow::Result<Option<Self>> {
+        if block.version() != PcbVersion::V1 {
+            return Ok(None);
+        }
+
+        let coinbase = Coinbase::from_precomputed(block);
+        if !coinbase.is_coinbase_applied()
+            || store.get_block_canonicity(&block.state_hash())? != Some(Canonicity::Canonical)
+        {
+            return Ok(None);
+        }
+
+        let Some(winner_account) = store.get_staking_account(
+            &block.block_stake_winner(),
+            block.epoch_count(),
+            Some(&block.genesis_state_hash()),
+        )?
+        else {
+            return Ok(Some(Self::LedgerUnavailable));
+        };
+
+        let expected = is_supercharge_eligible(&winner_account, block.global_slot_since_genesis());
+        Ok(Some(if expected == coinbase.supercharge {
+            Self::ExpectedMatches
+        } else {
+            Self::Mismatch
+        }))
+    }
+}
+
+/// Whether the winner account has no locked tokens at the global slot
+pub fn is_supercharge_eligible(winner_account: &StakingAccount, global_slot: u32) -> bool {
+    winner_account.timing.as_ref().map_or(true, |timing| {
+        timing.current_minimum_balance(global_slot) == 0
+    })
+}
@@ -0,0 +1,208 @@
+//! Currency supply & coinbase emission of the canonical chain
+//!
+//! Each epoch rolls up its canonical blocks' coinbases & keeps the total
+//! currency of its highest canonical block. Locked supply depends on the
+//! global slot, it's computed from the epoch's staking ledger account timings
+//! when queried.
+
+use super::precomputed::PrecomputedBlock;
+use crate::ledger::{coinbase::Coinbase, staking::StakingLedger};
+use serde::{Deserialize, Serialize};
+
+/// Supply data of a block
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockSupply {
+    pub epoch: u32,
+    pub global_slot: u32,
+
+    /// Total currency (nanomina) after the block
+    pub total_currency: u64,
+
+    /// Coinbase amount (nanomina), 0 if the block has no coinbase
+    pub coinbase: u64,
+
+    pub supercharged: bool,
+}
+
+/// Supply rollup of an epoch's canonical blocks
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpochSupply {
+    /// Heights of the epoch's canonical blocks, ascending
+    pub heights: Vec<u32>,
+
+    /// Total coinbase emission (nanomina)
+    pub coinbase: u64,
+
+    /// Number of supercharged coinbases
+    pub num_supercharged: u32,
+
+    /// Total currency (nanomina) after the highest canonical block
+    pub total_currency: u64,
+
+    /// Global slot of the highest canonical block
+    pub global_slot: u32,
+}
+
+/// Supply statistics of an epoch
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Supply {
+    pub epoch: u32,
+    pub num_blocks: u32,
+
+    /// Height & global slot of the epoch's highest canonical block
+    pub blockchain_length: u32,
+    pub global_slot: u32,
+
+    /// Amounts in nanomina
+    pub total_currency: u64,
+    pub coinbase_emission: u64,
+    pub num_supercharged: u32,
+
+    /// Unknown without the epoch's staking ledger
+    pub locked_supply: Option<u64>,
+    pub circulating_supply: Option<u64>,
+}
+
+impl BlockSupply {
+    pub fn new(block: &PrecomputedBlock) -> Self {
+        let coinbase = Coinbase::from_precomputed(block);
+        Self {
+            epoch: block.epoch_count(),
+            global_slot: block.global_slot_since_genesis(),
+            total_currency: block.total_currency(),
+            coinbase: if coinbase.is_coinbase_applied() {
+                coinbase.amount()
+            } else {
+                0
+            },
+            supercharged: coinbase.is_coinbase_applied() && coinbase.supercharge,
+        }
+    }
+}
+
+impl EpochSupply {
+    /// Adds the canonical block's supply, `false` if it was already added
+    pub fn apply(&mut self, height: u32, block: &BlockSupply) -> bool {
+        match self.heights.binary_search(&height) {
+            Ok(_) => return false,
+            Err(index) => self.heights.insert(index, height),
+        }
+
+        self.coinbase += block.coinbase;
+        self.num_supercharged += block.supercharged as u32;
+
+        if self.heights.last() == Some(&height) {
+            self.total_currency = block.total_currency;
+            self.global_slot = block.global_slot;
+        }
+        true
+    }
+
+    /// Removes the block's supply, `false` if it was never added
+    ///
+    /// `prev` is the supply of the next highest canonical block of the epoch,
+    /// only needed when removing the highest one
+    pub fn unapply(
+        &mut self,
+        height: u32,
+        block: &BlockSupply,
+        prev: Option<&BlockSupply>,
+    ) -> bool {
+        match self.heights.binary_search(&height) {
+            Ok(index) => self.heights.remove(index),
+            Err(_) => return false,
+        };
+
+        self.coinbase = self.coinbase.saturating_sub(block.coinbase);
+        self.num_supercharged = self
+            .num_supercharged
+            .saturating_sub(block.supercharged as u32);
+
+        if self.heights.last().map_or(true, |last| *last < height) {
+            let prev = prev.copied().unwrap_or_default();
+            self.total_currency = prev.total_currency;
+            self.global_slot = prev.global_slot;
+        }
+        true
+    }
+
+    /// Height of the epoch's highest canonical block preceding `height`
+    pub fn prev_height(&self, height: u32) -> Option<u32> {
+        self.heights.iter().rev().find(|h| **h < height).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heights.is_empty()
+    }
+
+    /// Supply statistics, with the locked supply from the epoch's staking
+    /// ledger if present
+    pub fn supply(&self, epoch: u32, staking_ledger: Option<&StakingLedger>) -> Supply {
+        let locked_supply = staking_ledger.map(|ledger| ledger.locked_supply(self.global_slot));
+        Supply {
+            epoch,
+            num_blocks: self.heights.len() as u32,
+            blockchain_length: self.heights.last().copied().unwrap_or_default(),
+            global_slot: self.global_slot,
+            total_currency: self.total_currency,
+            coinbase_emission: self.coinbase,
+            num_supercharged: self.num_supercharged,
+            locked_supply,
+            circulating_supply: locked_supply
+                .map(|locked| self.total_currency.saturating_sub(locked)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_supply(global_slot: u32, total_currency: u64, supercharged: bool) -> BlockSupply {
+        BlockSupply {
+            epoch: 0,
+            global_slot,
+            total_currency,
+            coinbase: if supercharged { 1440 } else { 720 },
+            supercharged,
+        }
+    }
+
+    #[test]
+    fn epoch_supply_apply_unapply() {
+        let mut epoch = EpochSupply::default();
+        let blocks = [
+            (2, block_supply(3, 1720, false)),
+            (3, block_supply(5, 3160, true)),
+            (4, block_supply(6, 3880, false)),
+        ];
+
+        // apply out of order & idempotently
+        assert!(epoch.apply(3, &blocks[1].1));
+        assert!(epoch.apply(2, &blocks[0].1));
+        assert!(!epoch.apply(2, &blocks[0].1));
+        assert_eq!(epoch.heights, vec![2, 3]);
+        assert_eq!((epoch.total_currency, epoch.global_slot), (3160, 5));
+
+        assert!(epoch.apply(4, &blocks[2].1));
+        assert_eq!(epoch.coinbase, 2880);
+        assert_eq!(epoch.num_supercharged, 1);
+        assert_eq!((epoch.total_currency, epoch.global_slot), (3880, 6));
+
+        // unapply the highest block
+        assert_eq!(epoch.prev_height(4), Some(3));
+        assert!(epoch.unapply(4, &blocks[2].1, Some(&blocks[1].1)));
+        assert!(!epoch.unapply(4, &blocks[2].1, Some(&blocks[1].1)));
+        assert_eq!(epoch.coinbase, 2160);
+        assert_eq!((epoch.total_currency, epoch.global_slot), (3160, 5));
+
+        // unapply a lower block
+        assert!(epoch.unapply(2, &blocks[0].1, None));
+        assert_eq!(epoch.coinbase, 1440);
+        assert_eq!((epoch.total_currency, epoch.global_slot), (3160, 5));
+
+        assert!(epoch.unapply(3, &blocks[1].1, None));
+        assert!(epoch.is_empty());
+        assert_eq!(epoch, EpochSupply::default());
+    }
+}
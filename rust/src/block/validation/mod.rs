@@ -0,0 +1,169 @@
+//! Block validation mode
+//!
+//! Opt-in cross-check of post-hardfork blocks' `accounts_created` &
+//! `accounts_accessed` against the canonical ledger. Mismatches are recorded
+//! as [ValidationError]s in the [store::ValidationStore] instead of halting
+//! ingestion:
+//! - created accounts must not be in the ledger before the block is applied,
+//!   but must be after
+//! - accessed accounts must be in the ledger after the block is applied, with
+//!   the same balance, nonce & delegate
+
+pub mod store;
+
+use super::precomputed::PrecomputedBlock;
+use crate::{
+    base::{public_key::PublicKey, state_hash::StateHash},
+    ledger::{token::TokenAddress, Ledger},
+};
+use serde::{Deserialize, Serialize};
+
+/// Mismatch between a block's assertions & the canonical ledger
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationError {
+    pub state_hash: StateHash,
+    pub blockchain_length: u32,
+    pub kind: ValidationErrorKind,
+    pub public_key: PublicKey,
+    pub token: TokenAddress,
+
+    /// Value asserted by the block, if any
+    pub expected: Option<String>,
+
+    /// Value in the ledger, if any
+    pub actual: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValidationErrorKind {
+    /// Created account was already in the ledger
+    AccountCreatedExists,
+
+    /// Created account is missing from the ledger
+    AccountCreatedMissing,
+
+    /// Accessed account is missing from the ledger
+    AccountAccessedMissing,
+
+    AccountAccessedBalance,
+    AccountAccessedNonce,
+    AccountAccessedDelegate,
+}
+
+impl ValidationError {
+    fn new(
+        block: &PrecomputedBlock,
+        kind: ValidationErrorKind,
+        public_key: PublicKey,
+        token: TokenAddress,
+    ) -> Self {
+        Self {
+            state_hash: block.state_hash(),
+            blockchain_length: block.blockchain_length(),
+            kind,
+            public_key,
+            token,
+            expected: None,
+            actual: None,
+        }
+    }
+
+    fn mismatch(mut self, expected: impl ToString, actual: impl ToString) -> Self {
+        self.expected = Some(expected.to_string());
+        self.actual = Some(actual.to_string());
+        self
+    }
+}
+
+/// Accounts the block creates which are already in the ledger, checked
+/// before the block is applied
+pub fn check_accounts_created(block: &PrecomputedBlock, ledger: &Ledger) -> Vec<ValidationError> {
+    block
+        .accounts_created_v2()
+        .into_iter()
+        .filter(|created| {
+            ledger
+                .get_account(&created.public_key, &created.token)
+                .is_some()
+        })
+        .map(|created| {
+            ValidationError::new(
+                block,
+                ValidationErrorKind::AccountCreatedExists,
+                created.public_key,
+                created.token,
+            )
+        })
+        .collect()
+}
+
+/// Created & accessed accounts which are missing from, or differ from, the
+/// ledger, checked after the block is applied
+pub fn check_accounts_accessed(block: &PrecomputedBlock, ledger: &Ledger) -> Vec<ValidationError> {
+    use ValidationErrorKind::*;
+
+    let mut errors: Vec<_> = block
+        .accounts_created_v2()
+        .into_iter()
+        .filter(|created| {
+            ledger
+                .get_account(&created.public_key, &created.token)
+                .is_none()
+        })
+        .map(|created| {
+            ValidationError::new(
+                block,
+                AccountCreatedMissing,
+                created.public_key,
+                created.token,
+            )
+        })
+        .collect();
+
+    for accessed in block.accounts_accessed() {
+        let expected = accessed.account;
+        let pk = expected.public_key.clone();
+        let token = expected.token.clone().unwrap_or_default();
+        let error = |kind| ValidationError::new(block, kind, pk.clone(), token.clone());
+
+        let Some(actual) = ledger.get_account(&pk, &token) else {
+            errors.push(error(AccountAccessedMissing));
+            continue;
+        };
+
+        // ledger balances include the account creation fee
+        let balance = actual.clone().display().balance;
+        if balance != expected.balance {
+            errors.push(error(AccountAccessedBalance).mismatch(expected.balance.0, balance.0));
+        }
+
+        let nonce = actual.nonce.unwrap_or_default();
+        let expected_nonce = expected.nonce.unwrap_or_default();
+        if nonce != expected_nonce {
+            errors.push(error(AccountAccessedNonce).mismatch(expected_nonce.0, nonce.0));
+        }
+
+        if actual.delegate != expected.delegate {
+            errors.push(
+                error(AccountAccessedDelegate).mismatch(&expected.delegate, &actual.delegate),
+            );
+        }
+    }
+
+    errors
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} {} (token {}) in block {} (length {})",
+            self.kind, self.public_key, self.token, self.state_hash, self.blockchain_length
+        )?;
+
+        if let (Some(expected), Some(actual)) = (self.expected.as_ref(), self.actual.as_ref()) {
+            write!(f, ": expected {expected}, got {actual}")?;
+        }
+        Ok(())
+    }
+}
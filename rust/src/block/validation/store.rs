@@ -0,0 +1,23 @@
+//! Store of block validation errors
+
+use super::ValidationError;
+use crate::base::state_hash::StateHash;
+use speedb::Direction;
+
+pub trait ValidationStore {
+    /// Record the block's validation errors, replacing any previously
+    /// recorded for it
+    fn set_validation_errors(
+        &self,
+        state_hash: &StateHash,
+        blockchain_length: u32,
+        errors: &[ValidationError],
+    ) -> anyhow::Result<()>;
+
+    /// Get the recorded validation errors, ordered by block height
+    fn get_validation_errors(
+        &self,
+        direction: Direction,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ValidationError>>;
+}
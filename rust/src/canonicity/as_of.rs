@@ -0,0 +1,122 @@
+//! Canonical chain as of a historical canonical tip
+//!
+//! The canonicity store only tracks current canonicity, so the chain ending
+//! at an earlier height is derived by walking parent hashes from the
+//! canonical block at that height
+
+use super::store::CanonicityStore;
+use crate::{
+    base::state_hash::StateHash, block::store::BlockStore,
+    constants::MAINNET_TRANSITION_FRONTIER_K, store::IndexerStore,
+};
+use anyhow::Context;
+use std::collections::HashMap;
+
+/// Blocks of the canonical chain ending at `height`, built once per request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalChainAsOf {
+    /// Height of the historical canonical tip
+    pub height: u32,
+
+    /// Walked chain state hashes by height
+    walked: HashMap<u32, StateHash>,
+
+    /// Lowest walked height
+    walked_from: u32,
+
+    /// Memoized block heights
+    block_heights: HashMap<StateHash, Option<u32>>,
+
+    /// Memoized canonical state hashes below the walked chain
+    canonical_hashes: HashMap<u32, Option<StateHash>>,
+}
+
+impl CanonicalChainAsOf {
+    /// Max number of blocks walked. Reorgs are bounded by the transition
+    /// frontier's `k`, so below the walked blocks the chain ending at
+    /// `height` agrees with the current canonical chain.
+    pub const MAX_WALK_DEPTH: u32 = MAINNET_TRANSITION_FRONTIER_K;
+
+    /// Walk the chain ending at the canonical block at `height`
+    pub fn new(store: &IndexerStore, height: u32) -> anyhow::Result<Self> {
+        let mut state_hash = store
+            .get_canonical_hash_at_height(height)?
+            .with_context(|| format!("Missing canonical block at height {height}"))?;
+
+        let mut walked = HashMap::new();
+        let mut walked_from = height;
+
+        loop {
+            walked.insert(walked_from, state_hash.clone());
+
+            if walked_from <= 1 || height - walked_from + 1 >= Self::MAX_WALK_DEPTH {
+                break;
+            }
+
+            match store.get_block_parent_hash(&state_hash)? {
+                Some(parent_hash) if store.get_block_height(&parent_hash)?.is_some() => {
+                    state_hash = parent_hash;
+                    walked_from -= 1;
+                }
+                _ => break,
+            }
+        }
+
+        Ok(Self {
+            height,
+            walked,
+            walked_from,
+            block_heights: HashMap::new(),
+            canonical_hashes: HashMap::new(),
+        })
+    }
+
+    /// Whether the block is in the chain ending at `height`
+    pub fn contains(
+        &mut self,
+        store: &IndexerStore,
+        state_hash: &StateHash,
+    ) -> anyhow::Result<bool> {
+        let block_height = match self.block_heights.get(state_hash) {
+            Some(block_height) => *block_height,
+            None => {
+                let block_height = store.get_block_height(state_hash)?;
+                self.block_heights.insert(state_hash.clone(), block_height);
+                block_height
+            }
+        };
+
+        match block_height {
+            Some(block_height) => self.contains_at(store, state_hash, block_height),
+            None => Ok(false),
+        }
+    }
+
+    /// Whether the block at `block_height` is in the chain ending at `height`
+    pub fn contains_at(
+        &mut self,
+        store: &IndexerStore,
+        state_hash: &StateHash,
+        block_height: u32,
+    ) -> anyhow::Result<bool> {
+        if block_height > self.height {
+            return Ok(false);
+        }
+
+        if block_height >= self.walked_from {
+            return Ok(self.walked.get(&block_height) == Some(state_hash));
+        }
+
+        let canonical_hash = match self.canonical_hashes.get(&block_height) {
+            Some(canonical_hash) => canonical_hash.clone(),
+            None => {
+                let canonical_hash = store.get_canonical_hash_at_height(block_height)?;
+                self.canonical_hashes
+                    .insert(block_height, canonical_hash.clone());
+                canonical_hash
+            }
+        };
+
+        Ok(canonical_hash.as_ref() == Some(state_hash))
+    }
+}
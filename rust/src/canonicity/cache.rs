@@ -0,0 +1,211 @@
+//! In-memory cache of canonical state hashes by height
+
+use crate::base::state_hash::StateHash;
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    RwLock,
+};
+
+/// Canonical heights are dense, so the cached state hashes are stored
+/// contiguously starting at `start_height`. Heights outside the cached range
+/// fall back to the store.
+#[derive(Debug, Default)]
+pub struct CanonicalHashCache {
+    inner: RwLock<CacheInner>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct CacheInner {
+    loaded: bool,
+    start_height: u32,
+    hashes: Vec<StateHash>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanonicalHashCacheStats {
+    pub loaded: bool,
+
+    /// Lowest cached height
+    pub start_height: u32,
+
+    /// Number of cached heights
+    pub len: u32,
+
+    /// Lookups served by the cache
+    pub hits: u64,
+
+    /// Lookups which fell back to the store
+    pub misses: u64,
+}
+
+impl CanonicalHashCache {
+    /// Get the cached canonical state hash at `height`, recording a hit or
+    /// miss
+    pub fn get(&self, height: u32) -> Option<StateHash> {
+        let inner = self.inner.read().expect("canonical hash cache lock");
+        let state_hash = inner.index(height).map(|idx| inner.hashes[idx].clone());
+
+        if state_hash.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+
+        state_hash
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.inner.read().expect("canonical hash cache lock").loaded
+    }
+
+    /// Populate the cache with the contiguous prefix of `canonical_hashes`
+    /// (ascending by height), unless it's already loaded
+    pub fn load_with<F, I>(&self, canonical_hashes: F) -> anyhow::Result<()>
+    where
+        F: FnOnce() -> anyhow::Result<I>,
+        I: IntoIterator<Item = anyhow::Result<(u32, StateHash)>>,
+    {
+        let mut inner = self.inner.write().expect("canonical hash cache lock");
+        if inner.loaded {
+            return Ok(());
+        }
+
+        let mut hashes = vec![];
+        let mut start_height = 0;
+        for entry in canonical_hashes()? {
+            let (height, state_hash) = entry?;
+
+            if hashes.is_empty() {
+                start_height = height;
+            } else if height != start_height + hashes.len() as u32 {
+                break;
+            }
+
+            hashes.push(state_hash);
+        }
+
+        *inner = CacheInner {
+            loaded: true,
+            start_height,
+            hashes,
+        };
+        Ok(())
+    }
+
+    /// Cache the canonical state hash at `height` if it's within or extends
+    /// the cached range
+    pub fn set(&self, height: u32, state_hash: &StateHash) {
+        let mut inner = self.inner.write().expect("canonical hash cache lock");
+        if !inner.loaded {
+            return;
+        }
+
+        if inner.hashes.is_empty() {
+            inner.start_height = height;
+        }
+
+        if let Some(idx) = inner.index(height) {
+            inner.hashes[idx] = state_hash.clone();
+        } else if height == inner.end_height() {
+            inner.hashes.push(state_hash.clone());
+        }
+    }
+
+    /// Remove the cached canonical state hashes at & above `height`
+    pub fn truncate(&self, height: u32) {
+        let mut inner = self.inner.write().expect("canonical hash cache lock");
+        let len = height.saturating_sub(inner.start_height) as usize;
+        inner.hashes.truncate(len);
+    }
+
+    pub fn stats(&self) -> CanonicalHashCacheStats {
+        let inner = self.inner.read().expect("canonical hash cache lock");
+        CanonicalHashCacheStats {
+            loaded: inner.loaded,
+            start_height: inner.start_height,
+            len: inner.hashes.len() as u32,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl CacheInner {
+    fn index(&self, height: u32) -> Option<usize> {
+        (height >= self.start_height && height < self.end_height())
+            .then_some((height - self.start_height) as usize)
+    }
+
+    /// One past the highest cached height
+    fn end_height(&self) -> u32 {
+        self.start_height + self.hashes.len() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state_hash(n: u32) -> StateHash {
+        StateHash(format!("3N{n:050}"))
+    }
+
+    fn loaded(entries: Vec<(u32, StateHash)>) -> anyhow::Result<CanonicalHashCache> {
+        let cache = CanonicalHashCache::default();
+        cache.load_with(|| Ok(entries.into_iter().map(Ok)))?;
+        Ok(cache)
+    }
+
+    #[test]
+    fn loads_contiguous_prefix() -> anyhow::Result<()> {
+        let cache = loaded(vec![
+            (2, state_hash(2)),
+            (3, state_hash(3)),
+            (4, state_hash(4)),
+            (6, state_hash(6)),
+        ])?;
+
+        assert_eq!(cache.get(1), None);
+        assert_eq!(cache.get(2), Some(state_hash(2)));
+        assert_eq!(cache.get(4), Some(state_hash(4)));
+        assert_eq!(cache.get(6), None);
+
+        let stats = cache.stats();
+        assert_eq!((stats.start_height, stats.len), (2, 3));
+        assert_eq!((stats.hits, stats.misses), (2, 2));
+        Ok(())
+    }
+
+    #[test]
+    fn set_and_truncate() -> anyhow::Result<()> {
+        // unloaded caches ignore updates
+        let cache = CanonicalHashCache::default();
+        cache.set(1, &state_hash(1));
+        assert_eq!(cache.get(1), None);
+
+        let cache = loaded(vec![])?;
+        for height in 1..=5 {
+            cache.set(height, &state_hash(height));
+        }
+
+        // gaps aren't cached
+        cache.set(7, &state_hash(7));
+        assert_eq!(cache.get(7), None);
+        assert_eq!(cache.stats().len, 5);
+
+        // reorg
+        cache.truncate(4);
+        assert_eq!(cache.get(3), Some(state_hash(3)));
+        assert_eq!(cache.get(4), None);
+
+        cache.set(4, &state_hash(40));
+        assert_eq!(cache.get(4), Some(state_hash(40)));
+
+        cache.truncate(0);
+        assert_eq!(cache.stats().len, 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+//! Canonicity consistency verification
+//!
+//! Recomputes the canonical chain by walking parent links from the best tip
+//! & compares it with the stored canonical hash at each height. Mismatches
+//! are reported with the canonical block events recorded at their height.
+
+use super::store::CanonicityStore;
+use crate::{
+    base::state_hash::StateHash,
+    block::store::BlockStore,
+    event::{
+        db::{DbCanonicityEvent, DbEvent},
+        store::EventStore,
+        IndexerEvent,
+    },
+    store::IndexerStore,
+    utility::store::common::u32_from_be_bytes,
+};
+use log::{info, trace, warn};
+use speedb::IteratorMode;
+
+/// Stored canonical hash differing from the best tip's ancestor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicityMismatch {
+    pub height: u32,
+
+    /// Best tip's ancestor at the height
+    pub expected: StateHash,
+    pub stored: Option<StateHash>,
+
+    /// Canonical block events at the height, in sequence order
+    pub events: Vec<CanonicityEventRef>,
+}
+
+/// Canonical block event in the event log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicityEventRef {
+    pub seq_num: u32,
+    pub state_hash: StateHash,
+}
+
+/// Summary of a canonicity verification
+#[derive(Debug, Default)]
+pub struct CanonicityReport {
+    pub from_height: u32,
+    pub to_height: u32,
+    pub num_heights: u32,
+    pub mismatches: Vec<CanonicityMismatch>,
+}
+
+impl IndexerStore {
+    /// Compare the best tip's ancestors with the stored canonical hashes at
+    /// each canonical height from `from_height` up
+    pub fn verify_canonicity(&self, from_height: u32) -> anyhow::Result<CanonicityReport> {
+        let mut report = CanonicityReport {
+            from_height,
+            ..Default::default()
+        };
+
+        let Some(mut state_hash) = self.get_best_block_hash()? else {
+            return Ok(report);
+        };
+        let Some(mut height) = self.get_block_height(&state_hash)? else {
+            return Ok(report);
+        };
+        report.to_height = height;
+
+        // pending blocks above the highest canonical block aren't compared
+        let mut max_canonical_height = height;
+        while max_canonical_height > 0
+            && self
+                .get_canonical_hash_at_height(max_canonical_height)?
+                .is_none()
+        {
+            max_canonical_height -= 1;
+        }
+
+        while height >= from_height.max(1) {
+            if height <= max_canonical_height {
+                trace!("Verifying canonicity at height {height}");
+                report.num_heights += 1;
+
+                let stored = self.get_canonical_hash_at_height(height)?;
+                if stored.as_ref() != Some(&state_hash) {
+                    report.mismatches.push(CanonicityMismatch {
+                        height,
+                        expected: state_hash.clone(),
+                        stored,
+                        events: vec![],
+                    });
+                }
+            }
+
+            // the best chain starts at its genesis block
+            if self.get_block_genesis_state_hash(&state_hash)?.as_ref() == Some(&state_hash) {
+                break;
+            }
+
+            match self.get_block_parent_hash(&state_hash)? {
+                Some(parent_hash) => {
+                    state_hash = parent_hash;
+                    height -= 1;
+                }
+                None => break,
+            }
+        }
+
+        self.add_canonicity_mismatch_events(&mut report.mismatches)?;
+        Ok(report)
+    }
+
+    /// Verify canonicity from `from_height` up & record the result as an
+    /// event
+    pub fn record_canonicity_verification(
+        &self,
+        from_height: u32,
+    ) -> anyhow::Result<CanonicityReport> {
+        let report = self.verify_canonicity(from_height)?;
+        for mismatch in report.mismatches.iter() {
+            warn!("{mismatch}");
+        }
+
+        info!(
+            "Verified canonicity of heights {}..={}, {} mismatches",
+            report.from_height,
+            report.to_height,
+            report.mismatches.len()
+        );
+
+        self.add_event(&IndexerEvent::Db(DbEvent::Canonicity(
+            DbCanonicityEvent::CanonicityVerified {
+                from_height: report.from_height,
+                to_height: report.to_height,
+                num_mismatches: report.mismatches.len() as u32,
+            },
+        )))?;
+
+        Ok(report)
+    }
+
+    /// Collects the canonical block events at each mismatched height
+    fn add_canonicity_mismatch_events(
+        &self,
+        mismatches: &mut [CanonicityMismatch],
+    ) -> anyhow::Result<()> {
+        if mismatches.is_empty() {
+            return Ok(());
+        }
+
+        for (key, value) in self.event_log_iterator(IteratorMode::Start).flatten() {
+            if let Ok(IndexerEvent::Db(DbEvent::Canonicity(
+                DbCanonicityEvent::NewCanonicalBlock {
+                    state_hash,
+                    blockchain_length,
+                },
+            ))) = serde_json::from_slice(&value[5..])
+            {
+                if let Some(mismatch) = mismatches
+                    .iter_mut()
+                    .find(|mismatch| mismatch.height == blockchain_length)
+                {
+                    mismatch.events.push(CanonicityEventRef {
+                        seq_num: u32_from_be_bytes(&key)?,
+                        state_hash,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for CanonicityMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            height,
+            expected,
+            stored,
+            events,
+        } = self;
+
+        match stored {
+            Some(stored) => write!(
+                f,
+                "Canonical hash at height {height} is {stored}, expected {expected}"
+            )?,
+            None => write!(
+                f,
+                "No canonical hash at height {height}, expected {expected}"
+            )?,
+        }
+
+        if !events.is_empty() {
+            let events: Vec<_> = events
+                .iter()
+                .map(|event| format!("#{} {}", event.seq_num, event.state_hash))
+                .collect();
+            write!(f, " (set by events {})", events.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+//! Canonical chain linkage verification
+//!
+//! Walks the canonical chain from its highest block down to its genesis
+//! block, verifying each block's stored height & parent pointer against the
+//! canonical block one height below.
+
+use super::store::CanonicityStore;
+use crate::{base::state_hash::StateHash, block::store::BlockStore, store::IndexerStore};
+use log::trace;
+
+/// Canonical chain linkage inconsistency
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkageError {
+    /// No canonical block at the height, below the highest canonical block
+    MissingCanonicalBlock { height: u32 },
+
+    /// Canonical block's stored height differs from its canonical height
+    HeightMismatch {
+        state_hash: StateHash,
+        height: u32,
+        stored_height: Option<u32>,
+    },
+
+    /// Canonical block's stored parent isn't the canonical block one height
+    /// below
+    ParentMismatch {
+        state_hash: StateHash,
+        height: u32,
+        parent_hash: Option<StateHash>,
+        expected: StateHash,
+    },
+}
+
+/// Summary of a canonical chain linkage verification
+#[derive(Debug, Default)]
+pub struct LinkageReport {
+    pub num_blocks: u32,
+    pub errors: Vec<LinkageError>,
+}
+
+impl IndexerStore {
+    /// Verify the parent pointers & contiguous heights of the canonical chain
+    pub fn verify_linkage(&self) -> anyhow::Result<LinkageReport> {
+        let mut report = LinkageReport::default();
+        let mut height = match self.get_best_block_height()? {
+            Some(best_height) => best_height,
+            None => return Ok(report),
+        };
+
+        // start from the highest canonical block
+        let mut state_hash = loop {
+            if let Some(state_hash) = self.get_canonical_hash_at_height(height)? {
+                break state_hash;
+            }
+
+            if height == 0 {
+                return Ok(report);
+            }
+            height -= 1;
+        };
+
+        loop {
+            trace!("Verifying linkage of canonical block {state_hash} at height {height}");
+            report.num_blocks += 1;
+
+            let stored_height = self.get_block_height(&state_hash)?;
+            if stored_height != Some(height) {
+                report.errors.push(LinkageError::HeightMismatch {
+                    state_hash: state_hash.clone(),
+                    height,
+                    stored_height,
+                });
+            }
+
+            // the canonical chain starts at its genesis block
+            if height <= 1
+                || self.get_block_genesis_state_hash(&state_hash)?.as_ref() == Some(&state_hash)
+            {
+                break;
+            }
+
+            let parent_hash = self.get_block_parent_hash(&state_hash)?;
+            match self.get_canonical_hash_at_height(height - 1)? {
+                Some(expected) => {
+                    if parent_hash.as_ref() != Some(&expected) {
+                        report.errors.push(LinkageError::ParentMismatch {
+                            state_hash,
+                            height,
+                            parent_hash,
+                            expected: expected.clone(),
+                        });
+                    }
+
+                    state_hash = expected;
+                    height -= 1;
+                }
+                None => {
+                    report
+                        .errors
+                        .push(LinkageError::MissingCanonicalBlock { height: height - 1 });
+                    break;
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+impl std::fmt::Display for LinkageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingCanonicalBlock { height } => {
+                write!(f, "Missing canonical block at height {height}")
+            }
+            Self::HeightMismatch {
+                state_hash,
+                height,
+                stored_height,
+            } => match stored_height {
+                Some(stored_height) => write!(
+                    f,
+                    "Canonical block {state_hash} at height {height} has stored height {stored_height}"
+                ),
+                None => write!(
+                    f,
+                    "Canonical block {state_hash} at height {height} has no stored height"
+                ),
+            },
+            Self::ParentMismatch {
+                state_hash,
+                height,
+                parent_hash,
+                expected,
+            } => match parent_hash {
+                Some(parent_hash) => write!(
+                    f,
+                    "Canonical block {state_hash} at height {height} has parent {parent_hash}, expected {expected}"
+                ),
+                None => write!(
+                    f,
+                    "Canonical block {state_hash} at height {height} has no stored parent, expected {expected}"
+                ),
+            },
+        }
+    }
+}
@@ -1,10 +1,14 @@
+pub mod as_of;
+pub mod cache;
 pub mod canonical_chain_discovery;
+pub mod consistency;
+pub mod linkage;
 pub mod store;
 
 use crate::{base::state_hash::StateHash, store::DbUpdate};
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct CanonicityDiff {
     pub state_hash: StateHash,
     pub blockchain_length: u32,
@@ -20,6 +24,25 @@ pub enum Canonicity {
     Pending,
 }
 
+/// Resolution of a date time to the canonical chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CanonicalBlockAt {
+    /// Highest canonical block produced at or before the date time
+    Block {
+        height: u32,
+        state_hash: StateHash,
+
+        /// Block date time (epoch millis)
+        date_time: i64,
+    },
+
+    /// Date time precedes the first canonical block
+    BeforeGenesis,
+
+    /// Date time follows the canonical tip
+    AfterTip,
+}
+
 impl std::fmt::Debug for CanonicityDiff {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
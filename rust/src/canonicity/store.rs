@@ -1,7 +1,7 @@
 use crate::{
     base::state_hash::StateHash,
     block::store::DbBlockUpdate,
-    canonicity::{Canonicity, CanonicityUpdate},
+    canonicity::{CanonicalBlockAt, Canonicity, CanonicityUpdate},
 };
 
 pub trait CanonicityStore {
@@ -26,6 +26,19 @@ pub trait CanonicityStore {
     /// Get the state hash of the canonical block at the given global slot
     fn get_canonical_hash_at_slot(&self, global_slot: u32) -> anyhow::Result<Option<StateHash>>;
 
+    /// Get the state hash of the highest canonical block at or before the
+    /// given global slot
+    fn get_canonical_hash_at_or_before_slot(
+        &self,
+        global_slot: u32,
+    ) -> anyhow::Result<Option<StateHash>>;
+
+    /// Resolve the canonical block at the given date time (epoch millis)
+    ///
+    /// Canonical block date times are nearly monotonic, for the rare
+    /// non-monotonic pair, the highest block at or before the date time wins
+    fn get_canonical_block_at(&self, date_time: i64) -> anyhow::Result<CanonicalBlockAt>;
+
     /// Get block canonicity
     fn get_block_canonicity(&self, state_hash: &StateHash) -> anyhow::Result<Option<Canonicity>>;
 
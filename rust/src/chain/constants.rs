@@ -0,0 +1,89 @@
+//! Chain constants
+//!
+//! Consensus parameters of the indexed network which default to mainnet's
+//! compile-time values & are overridden by the config file's genesis section
+
+use crate::constants::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChainConstants {
+    /// Transition frontier length
+    pub k: u32,
+
+    /// Number of slots per epoch
+    pub slots_per_epoch: u32,
+
+    /// Slot duration in milliseconds
+    pub slot_time_millis: u64,
+
+    /// Genesis timestamp in epoch milliseconds
+    pub genesis_timestamp: u64,
+}
+
+impl ChainConstants {
+    /// Epoch of the global slot
+    pub fn epoch(&self, global_slot: u32) -> u32 {
+        global_slot / self.slots_per_epoch
+    }
+
+    /// Slot of the global slot within its epoch
+    pub fn epoch_slot(&self, global_slot: u32) -> u32 {
+        global_slot % self.slots_per_epoch
+    }
+
+    /// Convert epoch milliseconds to global slot number
+    pub fn millis_to_global_slot(&self, millis: i64) -> u32 {
+        let millis_since_genesis = millis as u64 - self.genesis_timestamp;
+        (millis_since_genesis / self.slot_time_millis) as u32
+    }
+
+    /// Convert global slot number to the slot's nominal start in epoch
+    /// milliseconds
+    pub fn global_slot_to_millis(&self, global_slot: u32) -> u64 {
+        self.genesis_timestamp + global_slot as u64 * self.slot_time_millis
+    }
+}
+
+impl std::default::Default for ChainConstants {
+    fn default() -> Self {
+        Self {
+            k: MAINNET_TRANSITION_FRONTIER_K,
+            slots_per_epoch: MAINNET_EPOCH_SLOT_COUNT,
+            slot_time_millis: MAINNET_BLOCK_SLOT_TIME_MILLIS,
+            genesis_timestamp: MAINNET_GENESIS_TIMESTAMP,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_slot_timing() {
+        let constants = ChainConstants {
+            k: 10,
+            slots_per_epoch: 48,
+            slot_time_millis: 20_000,
+            genesis_timestamp: 1_700_000_000_000,
+        };
+
+        assert_eq!(constants.epoch(100), 2);
+        assert_eq!(constants.epoch_slot(100), 4);
+        assert_eq!(constants.global_slot_to_millis(3), 1_700_000_060_000);
+        assert_eq!(constants.millis_to_global_slot(1_700_000_065_000), 3);
+
+        // mainnet defaults agree with the compile-time conversions
+        let mainnet = ChainConstants::default();
+        assert_eq!(
+            mainnet.global_slot_to_millis(564480),
+            global_slot_to_millis(564480)
+        );
+        assert_eq!(
+            mainnet.millis_to_global_slot(HARDFORK_GENESIS_TIMESTAMP as i64),
+            millis_to_global_slot(HARDFORK_GENESIS_TIMESTAMP as i64)
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Genesis eras
+//!
+//! Each hardfork starts a new chain from a fork-point genesis, restarting
+//! epoch numbering. An era is the span of the chain following one genesis.
+//! Epochs are identified by (era, epoch) or by a flattened epoch number which
+//! continues across the eras.
+
+use super::ChainData;
+use crate::{base::state_hash::StateHash, block::precomputed::PcbVersion, constants::*};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisEra {
+    /// Index of the era, the original genesis' era is 0
+    pub era: u32,
+
+    pub genesis_state_hash: StateHash,
+    pub version: PcbVersion,
+
+    /// Flattened epoch number of the era's first epoch
+    pub epoch_offset: u32,
+}
+
+impl GenesisEra {
+    /// Flattened epoch number of the era's epoch
+    pub fn flat_epoch(&self, epoch: u32) -> u32 {
+        self.epoch_offset + epoch
+    }
+}
+
+/// Known genesis eras of the configured network, ascending by era
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisRegistry(pub Vec<GenesisEra>);
+
+impl GenesisRegistry {
+    /// Mainnet's original & hardfork genesis eras
+    pub fn mainnet() -> Self {
+        Self(vec![
+            GenesisEra {
+                era: 0,
+                genesis_state_hash: MAINNET_GENESIS_HASH.into(),
+                version: PcbVersion::V1,
+                epoch_offset: 0,
+            },
+            GenesisEra {
+                era: 1,
+                genesis_state_hash: HARDFORK_GENESIS_HASH.into(),
+                version: PcbVersion::V2,
+                epoch_offset: HARDFORK_GENESIS_EPOCH_OFFSET,
+            },
+        ])
+    }
+
+    /// Registers the network's configured genesis as a new era, unless it's
+    /// already registered
+    pub fn with_genesis(mut self, genesis_state_hash: StateHash, version: PcbVersion) -> Self {
+        if !self.contains(&genesis_state_hash) {
+            self.0.push(GenesisEra {
+                era: self.0.len() as u32,
+                genesis_state_hash,
+                version,
+                epoch_offset: 0,
+            });
+        }
+
+        self
+    }
+
+    /// Whether the genesis state hash is registered
+    pub fn contains(&self, genesis_state_hash: &StateHash) -> bool {
+        self.era(genesis_state_hash).is_some()
+    }
+
+    /// Era of the genesis state hash
+    pub fn era(&self, genesis_state_hash: &StateHash) -> Option<&GenesisEra> {
+        self.0
+            .iter()
+            .find(|era| era.genesis_state_hash == *genesis_state_hash)
+    }
+
+    /// Eras, most recent first
+    pub fn eras_desc(&self) -> impl Iterator<Item = &GenesisEra> {
+        self.0.iter().rev()
+    }
+
+    /// Flattened epoch number of the era's epoch
+    ///
+    /// Unregistered genesis state hashes are treated as a single era network,
+    /// i.e. the epoch is already flat
+    pub fn flat_epoch(&self, genesis_state_hash: &StateHash, epoch: u32) -> u32 {
+        self.era(genesis_state_hash)
+            .map_or(epoch, |era| era.flat_epoch(epoch))
+    }
+
+    /// Era & epoch of the flattened epoch number
+    pub fn era_epoch(&self, flat_epoch: u32) -> Option<(&GenesisEra, u32)> {
+        self.eras_desc()
+            .find(|era| era.epoch_offset <= flat_epoch)
+            .map(|era| (era, flat_epoch - era.epoch_offset))
+    }
+}
+
+/////////////////
+// Conversions //
+/////////////////
+
+impl From<&ChainData> for GenesisRegistry {
+    /// Registry of the chain data's genesis state hashes, one era per PCB
+    /// version
+    fn from(value: &ChainData) -> Self {
+        let mainnet = Self::mainnet();
+        let mut eras: Vec<_> = value
+            .0
+            .iter()
+            .map(|(genesis_state_hash, (version, _))| (version.clone(), genesis_state_hash))
+            .collect();
+        eras.sort();
+
+        Self(
+            eras.into_iter()
+                .enumerate()
+                .map(|(era, (version, genesis_state_hash))| GenesisEra {
+                    era: era as u32,
+                    epoch_offset: mainnet
+                        .era(genesis_state_hash)
+                        .map_or(0, |era| era.epoch_offset),
+                    genesis_state_hash: genesis_state_hash.clone(),
+                    version,
+                })
+                .collect(),
+        )
+    }
+}
+
+impl std::default::Default for GenesisRegistry {
+    fn default() -> Self {
+        Self::mainnet()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_epochs() {
+        let registry = GenesisRegistry::mainnet();
+        let v1: StateHash = MAINNET_GENESIS_HASH.into();
+        let v2: StateHash = HARDFORK_GENESIS_HASH.into();
+
+        // the hardfork happened during epoch 79
+        assert_eq!(HARDFORK_GENESIS_EPOCH_OFFSET, 80);
+        assert_eq!(registry.flat_epoch(&v1, 42), 42);
+        assert_eq!(registry.flat_epoch(&v2, 0), 80);
+        assert_eq!(registry.flat_epoch(&StateHash::default(), 3), 3);
+
+        let (era, epoch) = registry.era_epoch(42).unwrap();
+        assert_eq!((era.era, epoch), (0, 42));
+
+        let (era, epoch) = registry.era_epoch(81).unwrap();
+        assert_eq!((era.era, epoch), (1, 1));
+
+        // mainnet chain data
+        assert_eq!(GenesisRegistry::from(&ChainData::default()), registry);
+    }
+
+    #[test]
+    fn configured_genesis() {
+        let registry = GenesisRegistry::mainnet();
+        let v1: StateHash = MAINNET_GENESIS_HASH.into();
+        let other: StateHash = "3NKNUvH2Vw7C2tXTxtYJgXQsJy8Yoqa6sf4VxBT2tuiEsKpHCt6Q".into();
+
+        // mainnet's genesis is already registered
+        assert_eq!(registry.clone().with_genesis(v1, PcbVersion::V1), registry);
+
+        // other networks' genesis starts its own era
+        let registry = registry.with_genesis(other.clone(), PcbVersion::V1);
+        let era = registry.era(&other).unwrap();
+        assert_eq!((era.era, era.epoch_offset), (2, 0));
+        assert_eq!(registry.flat_epoch(&other, 3), 3);
+    }
+}
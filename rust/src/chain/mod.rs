@@ -2,6 +2,8 @@
 
 pub mod store;
 
+mod constants;
+mod era;
 mod id;
 mod network;
 
@@ -9,12 +11,26 @@ use crate::{base::state_hash::StateHash, block::precomputed::PcbVersion, constan
 use std::collections::HashMap;
 
 // re-export types
+pub type ChainConstants = constants::ChainConstants;
 pub type ChainId = id::ChainId;
+pub type GenesisEra = era::GenesisEra;
+pub type GenesisRegistry = era::GenesisRegistry;
 pub type Network = network::Network;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ChainData(pub HashMap<StateHash, (PcbVersion, ChainId)>);
 
+impl ChainData {
+    /// Genesis state hash of the chain with the given PCB version
+    pub fn genesis_state_hash(&self, version: &PcbVersion) -> Option<&StateHash> {
+        self.0
+            .iter()
+            .find_map(|(genesis_state_hash, (pcb_version, _))| {
+                (pcb_version == version).then_some(genesis_state_hash)
+            })
+    }
+}
+
 /////////////
 // default //
 /////////////
@@ -4,7 +4,7 @@ use bincode::{Decode, Encode};
 use clap::builder::OsStr;
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Clone, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[derive(Default, Clone, PartialEq, Eq, Hash, Encode, Decode, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Network {
     #[default]
@@ -1,4 +1,5 @@
-use super::{ChainId, Network};
+use super::{ChainConstants, ChainId, GenesisRegistry, Network};
+use crate::base::state_hash::StateHash;
 
 pub trait ChainStore {
     /// Persists a (chain id, network) pair
@@ -21,4 +22,34 @@ pub trait ChainStore {
     ///
     /// Error if not present
     fn get_chain_id(&self) -> anyhow::Result<ChainId>;
+
+    /// Persists the known genesis eras of the configured network
+    ///
+    /// Error propogates from db
+    fn set_genesis_registry(&self, registry: &GenesisRegistry) -> anyhow::Result<()>;
+
+    /// Gets the known genesis eras of the configured network
+    ///
+    /// Defaults to mainnet's eras if not present
+    fn get_genesis_registry(&self) -> anyhow::Result<GenesisRegistry>;
+
+    /// Whether the state hash is a genesis block of the configured network
+    ///
+    /// See [GenesisRegistry::contains]
+    fn is_genesis_hash(&self, state_hash: &StateHash) -> anyhow::Result<bool>;
+
+    /// Gets the flattened epoch number of the genesis era's epoch
+    ///
+    /// See [GenesisRegistry::flat_epoch]
+    fn get_flat_epoch(&self, genesis_state_hash: &StateHash, epoch: u32) -> anyhow::Result<u32>;
+
+    /// Persists the configured network's chain constants
+    ///
+    /// Error propogates from db
+    fn set_chain_constants(&self, constants: &ChainConstants) -> anyhow::Result<()>;
+
+    /// Gets the configured network's chain constants
+    ///
+    /// Defaults to mainnet's constants if not present
+    fn get_chain_constants(&self) -> anyhow::Result<ChainConstants>;
 }
@@ -1,4 +1,8 @@
-use crate::{chain::Network, cli::LogLevelFilter, constants::*};
+use crate::{
+    chain::Network,
+    cli::{server::GenesisConfig, LogLevelFilter},
+    constants::*,
+};
 use std::path::PathBuf;
 
 #[derive(clap::Parser, Debug, Clone, Default)]
@@ -56,6 +60,14 @@ pub struct DatabaseArgs {
     #[arg(long, default_value_t = BLOCK_REPORTING_FREQ_NUM)]
     pub reporting_freq: u32,
 
+    /// Max number of parsed blocks awaiting application
+    #[arg(long, default_value_t = INGESTION_QUEUE_DEPTH)]
+    pub ingestion_queue_depth: u32,
+
+    /// Max number of parsed block bytes awaiting application
+    #[arg(long, default_value_t = INGESTION_QUEUE_BYTES)]
+    pub ingestion_queue_bytes: u64,
+
     /// Interval for pruning the root branch
     #[arg(long, default_value_t = PRUNE_INTERVAL_DEFAULT)]
     pub prune_interval: u32,
@@ -68,7 +80,7 @@ pub struct DatabaseArgs {
     #[arg(long, default_value_t = CANONICAL_UPDATE_THRESHOLD)]
     pub canonical_update_threshold: u32,
 
-    /// Start from a config file (bypasses other args)
+    /// Start from a TOML or JSON config file (bypasses other args)
     #[arg(long)]
     pub config: Option<PathBuf>,
 
@@ -79,4 +91,56 @@ pub struct DatabaseArgs {
     /// Switch to not ingest orphan blocks
     #[arg(long, default_value_t = false)]
     pub do_not_ingest_orphan_blocks: bool,
+
+    /// Switch to only look for blocks at the top level of the blocks dir
+    /// (by default, nested subdirectories are scanned too)
+    #[arg(long, default_value_t = false)]
+    pub no_recursive: bool,
+
+    /// Switch to replace stored staking ledgers whose files have changed
+    /// (by default, changed staking ledgers are reported & skipped)
+    #[arg(long, default_value_t = false)]
+    pub replace_staking_ledger: bool,
+
+    /// Log every canonical ledger diff touching these public keys, with the
+    /// resulting balance (comma separated)
+    #[arg(long = "trace-account", value_name = "PK", value_delimiter = ',')]
+    pub trace_accounts: Vec<String>,
+
+    /// Cross-check post-hardfork canonical blocks' created & accessed
+    /// accounts against the ledger, recording mismatches as validation
+    /// errors
+    #[arg(long, default_value_t = false)]
+    pub validate_blocks: bool,
+
+    /// Max number of blocks above the best tip to add to the witness tree,
+    /// higher blocks are stored & added later (default: 2 * transition
+    /// frontier length)
+    #[arg(long)]
+    pub future_block_horizon: Option<u32>,
+
+    /// Number of staking ledger accounts written per write batch, 0 writes
+    /// each staking ledger in a single batch [default: 10000]
+    #[arg(long)]
+    pub staking_ledger_batch_size: Option<usize>,
+
+    /// Only ingest startup staking ledgers from this epoch on, interrupted
+    /// ingestions otherwise resume from their checkpoints [default: all
+    /// epochs]
+    #[arg(long)]
+    pub staking_ledgers_from_epoch: Option<u32>,
+
+    /// Switch to open a store whose derivation-affecting constants differ
+    /// from this binary's (the affected derived data is recorded in an event)
+    #[arg(long, default_value_t = false)]
+    pub accept_constants_change: bool,
+
+    /// Path to a DuckDB file mirroring the canonical chain (requires the
+    /// sql_mirror feature)
+    #[arg(long, value_name = "FILE")]
+    pub sql_mirror: Option<PathBuf>,
+
+    /// Genesis section of the config file
+    #[arg(skip)]
+    pub genesis: Option<GenesisConfig>,
 }
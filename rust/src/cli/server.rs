@@ -1,6 +1,17 @@
 use super::{database::DatabaseArgs, LogLevelFilter};
-use crate::constants::*;
-use std::{path::PathBuf, str::FromStr};
+use crate::{
+    base::state_hash::StateHash,
+    block::vrf_output::VrfOutput,
+    chain::{ChainConstants, Network},
+    constants::*,
+    ingestion::IngestionQueueConfig,
+    web::public_api::PublicApiOptions,
+};
+use anyhow::Context;
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 #[derive(clap::Parser, Debug, Clone, Default)]
 #[command(author, version, about, long_about = None)]
@@ -40,11 +51,163 @@ pub struct ServerArgs {
     #[arg(long)]
     pub missing_block_recovery_batch: Option<bool>,
 
+    /// Backfill blocks missing from the blocks dir or the witness tree from
+    /// the block archive. HTTP(S) archives require the `backfill` feature
+    #[arg(long, default_value_t = false)]
+    pub block_backfill: bool,
+
+    /// Block archive URL template, substituting `{network}`, `{height}` &
+    /// `{state_hash}` (`file://` reads from a local mirror)
+    #[arg(long, default_value = BLOCK_ARCHIVE_URL_TEMPLATE)]
+    pub block_archive_url: String,
+
+    /// Block archive listing URL template, substituting `{network}` &
+    /// `{height}`, for heights without known state hashes
+    #[arg(long, default_value = BLOCK_ARCHIVE_LIST_URL_TEMPLATE)]
+    pub block_archive_list_url: String,
+
+    /// Delay (sec) in between block archive backfill attempts
+    #[arg(long, default_value_t = BLOCK_BACKFILL_DELAY_SECS)]
+    pub block_backfill_delay: u64,
+
+    /// Target duration (ms) of a single maintenance batch
+    #[arg(long, default_value_t = MAINTENANCE_LATENCY_BUDGET_MS)]
+    pub maintenance_latency_budget: u64,
+
+    /// Delay (ms) in between maintenance batches
+    #[arg(long, default_value_t = MAINTENANCE_YIELD_MS)]
+    pub maintenance_yield: u64,
+
+    /// Start with background maintenance paused
+    #[arg(long, default_value_t = false)]
+    pub maintenance_paused: bool,
+
+    /// Public keys to add to the address watchlist
+    #[arg(long = "watch-account", value_delimiter = ',')]
+    pub watch_accounts: Vec<String>,
+
+    /// URL to POST watched account notifications to (JSON)
+    #[arg(long)]
+    pub watch_webhook: Option<String>,
+
+    /// Prune settled orphaned blocks' data this many blocks below the best
+    /// tip [default: disabled]
+    #[arg(long)]
+    pub prune_orphaned_after: Option<u32>,
+
+    /// Prune settled orphaned blocks' data below this height [default:
+    /// disabled]
+    #[arg(long)]
+    pub prune_noncanonical_below_height: Option<u32>,
+
+    /// Expose stored blocks' raw JSON via GraphQL
+    #[arg(long, default_value_t = false)]
+    pub expose_raw_block_json: bool,
+
+    /// Path to the JSON runtime config, watched & reloaded on change. Its
+    /// GraphQL limits & feature flags supersede the corresponding flags, all
+    /// other settings are boot-only
+    #[arg(long)]
+    pub runtime_config: Option<PathBuf>,
+
+    /// Port for the HTTP block ingestion endpoint, served on the web hostname
+    /// [default: disabled]
+    #[arg(long)]
+    pub ingest_http_port: Option<u16>,
+
+    /// Port for the gRPC API, served on the web hostname. Requires the `grpc`
+    /// feature [default: disabled]
+    #[arg(long)]
+    pub grpc_port: Option<u16>,
+
+    #[clap(flatten)]
+    pub public_api: PublicApiArgs,
+
+    /// Networks to index from the database directory, each in its own
+    /// namespace [default: the database's legacy network]
+    #[arg(long, value_delimiter = ',')]
+    pub networks: Vec<Network>,
+
     /// Indexer process ID
     #[arg(last = true)]
     pub pid: Option<u32>,
 }
 
+#[derive(clap::Parser, Debug, Clone)]
+#[command(author, version, about, long_about = None)]
+pub struct ReplicaArgs {
+    /// Path to the primary mina indexer database directory
+    #[arg(long)]
+    pub database_dir: PathBuf,
+
+    /// Web server hostname for REST and GraphQL
+    #[arg(long, default_value = DEFAULT_WEB_HOSTNAME)]
+    pub web_hostname: String,
+
+    /// Web server port for REST and GraphQL
+    #[arg(long, default_value_t = DEFAULT_WEB_PORT)]
+    pub web_port: u16,
+
+    /// Delay (sec) in between catch-ups with the primary
+    #[arg(long, default_value_t = REPLICA_CATCH_UP_INTERVAL_SECS)]
+    pub catch_up_interval: u64,
+
+    /// Max number of blocks behind the primary before `/ready` fails
+    #[arg(long, default_value_t = REPLICA_MAX_LAG_BLOCKS)]
+    pub max_lag_blocks: u32,
+
+    /// Add the replication lag header to GraphQL responses
+    #[arg(long, default_value_t = false)]
+    pub lag_header: bool,
+
+    /// Expose stored blocks' raw JSON via GraphQL
+    #[arg(long, default_value_t = false)]
+    pub expose_raw_block_json: bool,
+
+    /// Path to the JSON runtime config, watched & reloaded on change. Its
+    /// GraphQL limits & feature flags supersede the corresponding flags, all
+    /// other settings are boot-only
+    #[arg(long)]
+    pub runtime_config: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub public_api: PublicApiArgs,
+}
+
+#[derive(clap::Args, Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct PublicApiArgs {
+    /// Serve the web server publicly, rate limiting each client IP & capping
+    /// request sizes & GraphQL query depth & complexity
+    #[arg(long, default_value_t = false)]
+    pub public_api: bool,
+
+    /// Requests allowed per client IP per minute in public API mode
+    #[arg(long, default_value_t = PUBLIC_API_RATE_LIMIT_PER_MIN)]
+    pub rate_limit_per_min: u32,
+
+    /// Requests a client IP can make in a burst in public API mode
+    #[arg(long, default_value_t = PUBLIC_API_RATE_LIMIT_BURST)]
+    pub rate_limit_burst: u32,
+
+    /// Max size (bytes) of a request body in public API mode
+    #[arg(long, default_value_t = PUBLIC_API_MAX_REQUEST_BYTES)]
+    pub max_request_bytes: usize,
+
+    /// Max GraphQL query depth in public API mode
+    #[arg(long, default_value_t = PUBLIC_API_MAX_QUERY_DEPTH)]
+    pub max_query_depth: usize,
+
+    /// Max GraphQL query complexity in public API mode
+    #[arg(long, default_value_t = PUBLIC_API_MAX_QUERY_COMPLEXITY)]
+    pub max_query_complexity: usize,
+
+    /// Rate limit clients by the last `X-Forwarded-For` address in public API
+    /// mode, only enable behind a trusted reverse proxy
+    #[arg(long, default_value_t = false)]
+    pub trust_forwarded_for: bool,
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct ServerArgsJson {
     pub genesis_ledger: Option<String>,
@@ -59,6 +222,13 @@ pub struct ServerArgsJson {
     pub log_level: String,
     pub ledger_cadence: u32,
     pub reporting_freq: u32,
+
+    #[serde(default = "IngestionQueueConfig::default_max_depth")]
+    pub ingestion_queue_depth: u32,
+
+    #[serde(default = "IngestionQueueConfig::default_max_bytes")]
+    pub ingestion_queue_bytes: u64,
+
     pub prune_interval: u32,
     pub canonical_threshold: u32,
     pub canonical_update_threshold: u32,
@@ -66,12 +236,118 @@ pub struct ServerArgsJson {
     pub web_port: u16,
     pub pid: Option<u32>,
     pub do_not_ingest_orphan_blocks: bool,
+
+    #[serde(default)]
+    pub no_recursive: bool,
+
+    #[serde(default)]
+    pub replace_staking_ledger: bool,
+
+    #[serde(default)]
+    pub accept_constants_change: bool,
+
     pub fetch_new_blocks_exe: Option<String>,
     pub fetch_new_blocks_delay: Option<u64>,
     pub missing_block_recovery_exe: Option<String>,
     pub missing_block_recovery_delay: Option<u64>,
     pub missing_block_recovery_batch: Option<bool>,
     pub network: String,
+
+    #[serde(default)]
+    pub block_backfill: Option<bool>,
+
+    #[serde(default)]
+    pub block_archive_url: Option<String>,
+
+    #[serde(default)]
+    pub block_archive_list_url: Option<String>,
+
+    #[serde(default)]
+    pub block_backfill_delay: Option<u64>,
+
+    #[serde(default)]
+    pub maintenance_latency_budget: Option<u64>,
+
+    #[serde(default)]
+    pub maintenance_yield: Option<u64>,
+
+    #[serde(default)]
+    pub maintenance_paused: Option<bool>,
+
+    #[serde(default)]
+    pub watch_accounts: Vec<String>,
+
+    #[serde(default)]
+    pub watch_webhook: Option<String>,
+
+    #[serde(default)]
+    pub prune_orphaned_after: Option<u32>,
+
+    #[serde(default)]
+    pub prune_noncanonical_below_height: Option<u32>,
+
+    #[serde(default)]
+    pub sql_mirror: Option<String>,
+
+    #[serde(default)]
+    pub trace_accounts: Vec<String>,
+
+    #[serde(default)]
+    pub validate_blocks: bool,
+
+    #[serde(default)]
+    pub future_block_horizon: Option<u32>,
+
+    #[serde(default)]
+    pub staking_ledger_batch_size: Option<usize>,
+
+    #[serde(default)]
+    pub staking_ledgers_from_epoch: Option<u32>,
+
+    #[serde(default)]
+    pub expose_raw_block_json: Option<bool>,
+
+    #[serde(default)]
+    pub networks: Vec<String>,
+
+    #[serde(default)]
+    pub runtime_config: Option<String>,
+
+    #[serde(default)]
+    pub ingest_http_port: Option<u16>,
+
+    #[serde(default)]
+    pub grpc_port: Option<u16>,
+
+    #[serde(default)]
+    pub public_api: Option<PublicApiArgs>,
+
+    #[serde(default)]
+    pub genesis: Option<GenesisConfig>,
+}
+
+/// Genesis section of the config file, for private & test networks which fork
+/// from different genesis parameters. Unset values default to those of the
+/// `genesis_hash` protocol version's mainnet genesis.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct GenesisConfig {
+    /// Path to the genesis ledger (JSON, optionally gzip'd)
+    pub ledger: Option<PathBuf>,
+
+    pub state_hash: Option<StateHash>,
+    pub prev_state_hash: Option<StateHash>,
+    pub blockchain_length: Option<u32>,
+    pub global_slot: Option<u32>,
+    pub last_vrf_output: Option<VrfOutput>,
+
+    /// Transition frontier length
+    pub k: Option<u32>,
+    pub slots_per_epoch: Option<u32>,
+    pub slot_time_millis: Option<u64>,
+
+    /// Genesis timestamp in epoch milliseconds
+    pub genesis_timestamp: Option<u64>,
 }
 
 //////////
@@ -85,6 +361,63 @@ impl ServerArgs {
     }
 }
 
+impl PublicApiArgs {
+    /// Public API limits, if enabled
+    pub fn options(&self) -> Option<PublicApiOptions> {
+        self.public_api.then_some(PublicApiOptions {
+            rate_limit_per_min: self.rate_limit_per_min,
+            rate_limit_burst: self.rate_limit_burst,
+            max_request_bytes: self.max_request_bytes,
+            max_query_depth: self.max_query_depth,
+            max_query_complexity: self.max_query_complexity,
+            trust_forwarded_for: self.trust_forwarded_for,
+        })
+    }
+}
+
+impl Default for PublicApiArgs {
+    fn default() -> Self {
+        Self {
+            public_api: false,
+            rate_limit_per_min: PUBLIC_API_RATE_LIMIT_PER_MIN,
+            rate_limit_burst: PUBLIC_API_RATE_LIMIT_BURST,
+            max_request_bytes: PUBLIC_API_MAX_REQUEST_BYTES,
+            max_query_depth: PUBLIC_API_MAX_QUERY_DEPTH,
+            max_query_complexity: PUBLIC_API_MAX_QUERY_COMPLEXITY,
+            trust_forwarded_for: false,
+        }
+    }
+}
+
+impl ServerArgsJson {
+    /// Reads the TOML (`.toml` extension) or JSON config file
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            toml::from_str(&contents)
+                .with_context(|| format!("Invalid TOML config file {}", path.display()))
+        } else {
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Invalid JSON config file {}", path.display()))
+        }
+    }
+}
+
+impl GenesisConfig {
+    /// The configured chain constants, defaulting to mainnet's
+    pub fn chain_constants(&self) -> ChainConstants {
+        let default = ChainConstants::default();
+        ChainConstants {
+            k: self.k.unwrap_or(default.k),
+            slots_per_epoch: self.slots_per_epoch.unwrap_or(default.slots_per_epoch),
+            slot_time_millis: self.slot_time_millis.unwrap_or(default.slot_time_millis),
+            genesis_timestamp: self.genesis_timestamp.unwrap_or(default.genesis_timestamp),
+        }
+    }
+}
+
 /////////////////
 // conversions //
 /////////////////
@@ -112,6 +445,8 @@ impl From<ServerArgs> for ServerArgsJson {
             log_level: value.db.log_level.to_string(),
             ledger_cadence: value.db.ledger_cadence,
             reporting_freq: value.db.reporting_freq,
+            ingestion_queue_depth: value.db.ingestion_queue_depth,
+            ingestion_queue_bytes: value.db.ingestion_queue_bytes,
             prune_interval: value.db.prune_interval,
             canonical_threshold: value.db.canonical_threshold,
             canonical_update_threshold: value.db.canonical_update_threshold,
@@ -125,8 +460,35 @@ impl From<ServerArgs> for ServerArgsJson {
                 .missing_block_recovery_exe
                 .map(|p| p.display().to_string()),
             missing_block_recovery_batch: value.missing_block_recovery_batch,
+            block_backfill: Some(value.block_backfill),
+            block_archive_url: Some(value.block_archive_url),
+            block_archive_list_url: Some(value.block_archive_list_url),
+            block_backfill_delay: Some(value.block_backfill_delay),
+            maintenance_latency_budget: Some(value.maintenance_latency_budget),
+            maintenance_yield: Some(value.maintenance_yield),
+            maintenance_paused: Some(value.maintenance_paused),
+            watch_accounts: value.watch_accounts,
+            watch_webhook: value.watch_webhook,
+            prune_orphaned_after: value.prune_orphaned_after,
+            prune_noncanonical_below_height: value.prune_noncanonical_below_height,
+            expose_raw_block_json: Some(value.expose_raw_block_json),
+            networks: value.networks.iter().map(ToString::to_string).collect(),
+            runtime_config: value.runtime_config.map(|path| path.display().to_string()),
+            ingest_http_port: value.ingest_http_port,
+            grpc_port: value.grpc_port,
+            public_api: Some(value.public_api),
+            genesis: value.db.genesis,
             network: value.db.network.to_string(),
             do_not_ingest_orphan_blocks: value.db.do_not_ingest_orphan_blocks,
+            no_recursive: value.db.no_recursive,
+            replace_staking_ledger: value.db.replace_staking_ledger,
+            accept_constants_change: value.db.accept_constants_change,
+            sql_mirror: value.db.sql_mirror.map(|p| p.display().to_string()),
+            trace_accounts: value.db.trace_accounts,
+            validate_blocks: value.db.validate_blocks,
+            future_block_horizon: value.db.future_block_horizon,
+            staking_ledger_batch_size: value.db.staking_ledger_batch_size,
+            staking_ledgers_from_epoch: value.db.staking_ledgers_from_epoch,
         }
     }
 }
@@ -146,12 +508,24 @@ impl From<ServerArgsJson> for ServerArgs {
             log_level: LogLevelFilter::from_str(&value.log_level).expect("log level"),
             ledger_cadence: value.ledger_cadence,
             reporting_freq: value.reporting_freq,
+            ingestion_queue_depth: value.ingestion_queue_depth,
+            ingestion_queue_bytes: value.ingestion_queue_bytes,
             prune_interval: value.prune_interval,
             canonical_threshold: value.canonical_threshold,
             canonical_update_threshold: value.canonical_update_threshold,
             config: None,
             network: (&value.network as &str).into(),
             do_not_ingest_orphan_blocks: value.do_not_ingest_orphan_blocks,
+            no_recursive: value.no_recursive,
+            replace_staking_ledger: value.replace_staking_ledger,
+            accept_constants_change: value.accept_constants_change,
+            sql_mirror: value.sql_mirror.map(Into::into),
+            trace_accounts: value.trace_accounts,
+            validate_blocks: value.validate_blocks,
+            future_block_horizon: value.future_block_horizon,
+            staking_ledger_batch_size: value.staking_ledger_batch_size,
+            staking_ledgers_from_epoch: value.staking_ledgers_from_epoch,
+            genesis: value.genesis,
         };
         Self {
             db,
@@ -164,6 +538,52 @@ impl From<ServerArgsJson> for ServerArgs {
             missing_block_recovery_delay: value.missing_block_recovery_delay,
             missing_block_recovery_exe: value.missing_block_recovery_exe.map(Into::into),
             missing_block_recovery_batch: value.missing_block_recovery_batch,
+            block_backfill: value.block_backfill.unwrap_or_default(),
+            block_archive_url: value
+                .block_archive_url
+                .unwrap_or_else(|| BLOCK_ARCHIVE_URL_TEMPLATE.to_string()),
+            block_archive_list_url: value
+                .block_archive_list_url
+                .unwrap_or_else(|| BLOCK_ARCHIVE_LIST_URL_TEMPLATE.to_string()),
+            block_backfill_delay: value
+                .block_backfill_delay
+                .unwrap_or(BLOCK_BACKFILL_DELAY_SECS),
+            maintenance_latency_budget: value
+                .maintenance_latency_budget
+                .unwrap_or(MAINTENANCE_LATENCY_BUDGET_MS),
+            maintenance_yield: value.maintenance_yield.unwrap_or(MAINTENANCE_YIELD_MS),
+            maintenance_paused: value.maintenance_paused.unwrap_or_default(),
+            watch_accounts: value.watch_accounts,
+            watch_webhook: value.watch_webhook,
+            prune_orphaned_after: value.prune_orphaned_after,
+            prune_noncanonical_below_height: value.prune_noncanonical_below_height,
+            expose_raw_block_json: value.expose_raw_block_json.unwrap_or_default(),
+            networks: value
+                .networks
+                .iter()
+                .map(|network| network.as_str().into())
+                .collect(),
+            runtime_config: value.runtime_config.map(Into::into),
+            ingest_http_port: value.ingest_http_port,
+            grpc_port: value.grpc_port,
+            public_api: value.public_api.unwrap_or_default(),
+        }
+    }
+}
+
+/// Serves the server's web endpoints from a replica of its database
+impl From<ServerArgs> for ReplicaArgs {
+    fn from(value: ServerArgs) -> Self {
+        Self {
+            database_dir: value.db.database_dir,
+            web_hostname: value.web_hostname,
+            web_port: value.web_port,
+            catch_up_interval: REPLICA_CATCH_UP_INTERVAL_SECS,
+            max_lag_blocks: REPLICA_MAX_LAG_BLOCKS,
+            lag_header: false,
+            expose_raw_block_json: value.expose_raw_block_json,
+            runtime_config: value.runtime_config,
+            public_api: value.public_api,
         }
     }
 }
@@ -174,6 +594,11 @@ impl From<DatabaseArgs> for ServerArgs {
             db: value,
             web_hostname: DEFAULT_WEB_HOSTNAME.to_string(),
             web_port: DEFAULT_WEB_PORT,
+            block_archive_url: BLOCK_ARCHIVE_URL_TEMPLATE.to_string(),
+            block_archive_list_url: BLOCK_ARCHIVE_LIST_URL_TEMPLATE.to_string(),
+            block_backfill_delay: BLOCK_BACKFILL_DELAY_SECS,
+            maintenance_latency_budget: MAINTENANCE_LATENCY_BUDGET_MS,
+            maintenance_yield: MAINTENANCE_YIELD_MS,
             ..Default::default()
         }
     }
@@ -1,7 +1,20 @@
-use crate::constants::MAINNET_GENESIS_HASH;
+use crate::{
+    block::{
+        inspect::{inspect_block_file, HEX_CONTEXT_DEFAULT},
+        precomputed::PcbVersion,
+    },
+    constants::{EVENTS_FOLLOW_POLL_MS, MAINNET_GENESIS_HASH},
+    event::page::{EventKind, EventPage},
+    ledger::bootstrap::Bootstrap,
+    state::tree_graph::TreeFormat,
+};
 use bincode::{config, Decode, Encode};
 use clap::{Parser, Subcommand};
-use std::{path::PathBuf, process};
+use std::{
+    path::{Path, PathBuf},
+    process,
+    time::Duration,
+};
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt, BufReader},
     net::UnixStream,
@@ -21,6 +34,18 @@ pub enum ClientCli {
     #[clap(subcommand)]
     Blocks(Blocks),
 
+    /// Canonical root ledger & event sequence number for bootstrapping
+    /// downstream replicas (newline-delimited JSON, manifest first)
+    Bootstrap {
+        /// Path to write the bootstrap [default: stdout]
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Max number of accounts per chunk
+        #[arg(long, default_value_t = Bootstrap::DEFAULT_CHUNK_SIZE)]
+        chunk_size: u32,
+    },
+
     /// Query the chain
     #[clap(subcommand)]
     Chain(Chain),
@@ -31,12 +56,40 @@ pub enum ClientCli {
         /// Full path to the new snapshot file
         #[arg(long, default_value = "./snapshot")]
         output_path: PathBuf,
+
+        /// Add a compressed snapshot to the `output_path` snapshot dir
+        #[arg(long, default_value_t = false)]
+        incremental: bool,
+    },
+
+    /// Query the event log
+    Events {
+        /// Event kinds to include, comma separated (NEW_BLOCK, NEW_BEST_TIP,
+        /// NEW_CANONICAL, LEDGER, STAKING, REORG, OTHER) [default: all]
+        #[arg(long, value_delimiter = ',')]
+        kinds: Vec<EventKind>,
+
+        /// First event sequence number
+        #[arg(long, default_value_t = 0)]
+        from_seq: u32,
+
+        /// Max number of events per page
+        #[arg(long, default_value_t = 100)]
+        limit: u32,
+
+        /// Tail new events as they're written (newline-delimited JSON)
+        #[arg(long, default_value_t = false)]
+        follow: bool,
     },
 
     /// Query best & staged ledgers
     #[clap(subcommand)]
     Ledgers(Ledgers),
 
+    /// Control background maintenance
+    #[clap(subcommand)]
+    Maintenance(Maintenance),
+
     /// Query staking ledgers
     #[command(subcommand)]
     StakingLedgers(StakingLedgers),
@@ -61,6 +114,16 @@ pub enum ClientCli {
         /// Output JSON data
         #[arg(long, default_value_t = false)]
         json: bool,
+
+        /// Output the witness tree's root & dangling branches with each
+        /// block's canonicity as a Graphviz DOT or JSON graph (dot, json)
+        #[arg(long)]
+        tree_format: Option<TreeFormat>,
+
+        /// Network indexed by the daemon [default: the daemon's primary
+        /// network]
+        #[arg(long)]
+        network: Option<String>,
     },
 
     /// Query transactions (user commands)
@@ -71,6 +134,14 @@ pub enum ClientCli {
     #[clap(subcommand)]
     InternalCommands(InternalCommands),
 
+    /// Query zkapp events & actions
+    #[clap(subcommand)]
+    Zkapps(Zkapps),
+
+    /// Manage the address watchlist & query its notifications
+    #[clap(subcommand)]
+    Watchlist(Watchlist),
+
     /// Query a running mina indexer for database version
     DbVersion,
 }
@@ -84,6 +155,33 @@ pub enum Accounts {
         #[arg(long)]
         public_key: String,
     },
+
+    /// Query an account's balance changes over the best chain
+    BalanceHistory {
+        /// Retrieve public key's balance history
+        #[arg(long)]
+        public_key: String,
+
+        /// Token address [default: MINA]
+        #[arg(long)]
+        token: Option<String>,
+
+        /// First block height
+        #[arg(long, default_value_t = 0)]
+        start_height: u32,
+
+        /// Last block height [default: best tip]
+        #[arg(long)]
+        end_height: Option<u32>,
+
+        /// Max number of balance changes
+        #[arg(long, default_value_t = 1000)]
+        limit: u32,
+
+        /// Path to write the balance history [default: stdout]
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
 }
 
 #[derive(Subcommand, Debug, Encode, Decode)]
@@ -174,6 +272,32 @@ pub enum Blocks {
         #[arg(long, default_value_t = false)]
         verbose: bool,
     },
+
+    /// Query blocks referenced as a parent, but never seen
+    Missing {
+        /// Max number of missing blocks, lowest first
+        #[arg(long, default_value_t = 100)]
+        limit: usize,
+
+        /// Path to write the missing blocks [default: stdout]
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+
+    /// Parse a block file & report where parsing fails (runs locally)
+    Inspect {
+        /// Path to the precomputed block file
+        #[arg(long)]
+        path: PathBuf,
+
+        /// Number of bytes to show on either side of the failing offset
+        #[arg(long, default_value_t = HEX_CONTEXT_DEFAULT)]
+        hex_context: usize,
+
+        /// Parse as a v2 (post-hardfork) block
+        #[arg(long, default_value_t = false)]
+        v2: bool,
+    },
 }
 
 #[derive(Subcommand, Debug, Encode, Decode)]
@@ -201,6 +325,21 @@ pub enum Chain {
         #[arg(long, default_value_t = false)]
         verbose: bool,
     },
+
+    /// Query the most recent reorgs (best tip branch switches)
+    Reorgs {
+        /// Max number of reorgs, most recent first
+        #[arg(long, default_value_t = 10)]
+        limit: u32,
+    },
+
+    /// Query the total, locked & circulating supply & coinbase emission of an
+    /// epoch's canonical blocks
+    Supply {
+        /// Epoch [default: latest epoch]
+        #[arg(long)]
+        epoch: Option<u32>,
+    },
 }
 
 #[derive(Subcommand, Debug, Encode, Decode)]
@@ -255,6 +394,30 @@ pub enum Ledgers {
         /// Memoize the staged ledger
         #[arg(long, default_value_t = false)]
         memoize: bool,
+
+        /// Only include these accounts (comma separated) [default: all]
+        #[arg(long = "public-key", value_delimiter = ',')]
+        public_keys: Vec<String>,
+    },
+
+    /// Query canonical staged ledgers by global slot, i.e. the ledger of the
+    /// highest canonical block at or before the slot
+    Slot {
+        /// Global slot (since genesis) of the ledger
+        #[arg(long)]
+        global_slot: u32,
+
+        /// Path to write the ledger [default: stdout]
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Memoize the staged ledger
+        #[arg(long, default_value_t = false)]
+        memoize: bool,
+
+        /// Only include these accounts (comma separated) [default: all]
+        #[arg(long = "public-key", value_delimiter = ',')]
+        public_keys: Vec<String>,
     },
 }
 
@@ -316,6 +479,29 @@ pub enum StakingLedgers {
         #[arg(long)]
         public_key: String,
     },
+
+    /// Report a delegate's epoch rewards & delegator payouts
+    Rewards {
+        /// Epoch of the delegate's blocks & staking ledger
+        #[arg(long)]
+        epoch: u32,
+
+        /// Genesis state hash
+        #[arg(long, default_value = MAINNET_GENESIS_HASH)]
+        genesis_state_hash: String,
+
+        /// Delegate (block producer) public key
+        #[arg(long)]
+        public_key: String,
+
+        /// Format delegator payouts as CSV
+        #[arg(long, default_value_t = false)]
+        csv: bool,
+
+        /// Path to write the rewards report [default: stdout]
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
 }
 
 #[derive(Subcommand, Debug, Encode, Decode)]
@@ -387,6 +573,10 @@ pub enum Transactions {
         #[arg(long, default_value_t = false)]
         csv: bool,
 
+        /// Restrict to the canonical chain as of the given height
+        #[arg(long)]
+        as_of_height: Option<u32>,
+
         /// Verbose transaction output
         #[arg(long, default_value_t = false)]
         verbose: bool,
@@ -435,11 +625,150 @@ pub enum InternalCommands {
         /// Format output as CSV
         #[arg(long, default_value_t = false)]
         csv: bool,
+
+        /// Restrict to the canonical chain as of the given height
+        #[arg(long)]
+        as_of_height: Option<u32>,
+    },
+}
+
+#[derive(Subcommand, Debug, Encode, Decode)]
+#[command(author, version, about, long_about = None)]
+pub enum Zkapps {
+    /// Query a zkapp account's events over the best chain
+    Events {
+        /// Retrieve public key's events
+        #[arg(long)]
+        public_key: String,
+
+        /// Token address [default: MINA]
+        #[arg(long)]
+        token: Option<String>,
+
+        /// First block height
+        #[arg(long, default_value_t = 0)]
+        start_height: u32,
+
+        /// Last block height [default: best tip]
+        #[arg(long)]
+        end_height: Option<u32>,
+
+        /// Max number of blocks
+        #[arg(long, default_value_t = 1000)]
+        limit: u32,
+
+        /// Path to write the events [default: stdout]
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+
+    /// Query a zkapp account's actions & action states over the best chain
+    Actions {
+        /// Retrieve public key's actions
+        #[arg(long)]
+        public_key: String,
+
+        /// Token address [default: MINA]
+        #[arg(long)]
+        token: Option<String>,
+
+        /// First block height
+        #[arg(long, default_value_t = 0)]
+        start_height: u32,
+
+        /// Last block height [default: best tip]
+        #[arg(long)]
+        end_height: Option<u32>,
+
+        /// Max number of blocks
+        #[arg(long, default_value_t = 1000)]
+        limit: u32,
+
+        /// Path to write the actions [default: stdout]
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug, Encode, Decode)]
+#[command(author, version, about, long_about = None)]
+pub enum Maintenance {
+    /// Pause background maintenance, running tasks continue on resume
+    Pause,
+
+    /// Resume background maintenance
+    Resume,
+
+    /// Show the maintenance tasks' progress & last runs
+    Status,
+}
+
+#[derive(Subcommand, Debug, Encode, Decode)]
+#[command(author, version, about, long_about = None)]
+pub enum Watchlist {
+    /// Watch the account's involvement in canonical blocks
+    Add {
+        /// Public key of the account to watch
+        #[arg(long)]
+        public_key: String,
+
+        /// Label included in the account's notifications
+        #[arg(long)]
+        label: Option<String>,
+    },
+
+    /// Stop watching the account
+    Remove {
+        /// Public key of the watched account
+        #[arg(long)]
+        public_key: String,
+    },
+
+    /// List the watched accounts
+    List,
+
+    /// Query the most recent notifications
+    Notifications {
+        /// Only the given account's notifications
+        #[arg(long)]
+        public_key: Option<String>,
+
+        /// Max number of notifications, highest block first
+        #[arg(long, default_value_t = 100)]
+        limit: u32,
     },
 }
 
 impl ClientCli {
     pub async fn run(&self, domain_socket_path: PathBuf) -> anyhow::Result<()> {
+        if let Self::Blocks(Blocks::Inspect {
+            path,
+            hex_context,
+            v2,
+        }) = self
+        {
+            let version = if *v2 { PcbVersion::V2 } else { PcbVersion::V1 };
+            println!("{}", inspect_block_file(path, version, *hex_context)?);
+            return Ok(());
+        }
+
+        if let Self::Events {
+            kinds,
+            from_seq,
+            limit,
+            follow: true,
+        } = self
+        {
+            return Self::follow_events(kinds, *from_seq, *limit, &domain_socket_path).await;
+        }
+
+        let msg = self.request(&domain_socket_path).await?;
+        println!("{msg}");
+        Ok(())
+    }
+
+    /// Sends the command to the server & returns its response
+    async fn request(&self, domain_socket_path: &Path) -> anyhow::Result<String> {
         let conn = UnixStream::connect(domain_socket_path)
             .await
             .unwrap_or_else(|e| {
@@ -455,8 +784,36 @@ impl ClientCli {
         reader.read_to_end(&mut buffer).await?;
 
         let msg = String::from_utf8(buffer)?;
-        let msg = msg.trim_end();
-        println!("{msg}");
-        Ok(())
+        Ok(msg.trim_end().to_string())
+    }
+
+    /// Polls the event log from `from_seq`, printing each new event
+    async fn follow_events(
+        kinds: &[EventKind],
+        mut from_seq: u32,
+        limit: u32,
+        domain_socket_path: &Path,
+    ) -> anyhow::Result<()> {
+        loop {
+            let request = Self::Events {
+                kinds: kinds.to_vec(),
+                from_seq,
+                limit,
+                follow: false,
+            };
+            let response = request.request(domain_socket_path).await?;
+            let page: EventPage = serde_json::from_str(&response)
+                .map_err(|_| anyhow::anyhow!("Unexpected events response: {response}"))?;
+
+            for event in page.events.iter() {
+                println!("{}", serde_json::to_string(event)?);
+            }
+
+            // wait for new events once caught up
+            if page.next_seq_num == from_seq {
+                tokio::time::sleep(Duration::from_millis(EVENTS_FOLLOW_POLL_MS)).await;
+            }
+            from_seq = page.next_seq_num;
+        }
     }
 }
@@ -0,0 +1,377 @@
+//! Combined account activity feed
+//!
+//! Interleaves an account's sent & received user commands, internal
+//! commands & account creation fees into a single reverse chronological
+//! feed, i.e. descending by block height, state hash & index in block.
+//! The feed is a k-way merge of the per-kind account indices, one block
+//! height at a time.
+
+use super::{
+    internal::DbInternalCommandWithData,
+    signed::{SignedCommandWithData, TxnHash},
+    store::UserCommandStore,
+    CommandType,
+};
+use crate::{
+    base::{public_key::PublicKey, state_hash::StateHash},
+    canonicity::{store::CanonicityStore, Canonicity},
+    ledger::token::TokenAddress,
+    store::{column_families::ColumnFamilyHelpers, IndexerStore},
+    utility::store::{
+        command::user::{pk_txn_sort_key_prefix, txn_hash_of_key},
+        common::{state_hash_suffix, u32_from_be_bytes, U32_LEN},
+    },
+};
+use anyhow::{bail, Context};
+use speedb::{ColumnFamily, Direction, IteratorMode};
+use std::iter::Peekable;
+
+/// Kinds of account activity, in intra-command feed order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AccountActivityKind {
+    Payment,
+    Delegation,
+    Zkapp,
+    Coinbase,
+    FeeTransfer,
+
+    /// Account creation fee paid by the account when it was created by a
+    /// user command
+    CreationFee,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivityDirection {
+    In,
+    Out,
+    SelfTransfer,
+}
+
+/// Entry of an account's activity feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountActivity {
+    pub kind: AccountActivityKind,
+    pub blockchain_length: u32,
+    pub state_hash: StateHash,
+
+    /// Index of the command in the block's ledger application order
+    pub index_in_block: u32,
+
+    pub direction: ActivityDirection,
+
+    /// Other account of a user command (`None` for internal commands &
+    /// creation fees)
+    pub counterparty: Option<PublicKey>,
+
+    pub amount: u64,
+    pub token: TokenAddress,
+
+    /// Hash of the user command (`None` for internal commands)
+    pub txn_hash: Option<TxnHash>,
+
+    pub canonical: bool,
+}
+
+/// Position of an entry in the feed. The feed is sorted descending by cursor
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AccountActivityCursor {
+    pub blockchain_length: u32,
+    pub state_hash: StateHash,
+    pub index_in_block: u32,
+    pub kind: AccountActivityKind,
+}
+
+/// Account index entry `(height, key, value)`
+type IndexEntry = (u32, Box<[u8]>, Box<[u8]>);
+
+impl IndexerStore {
+    /// Get up to `limit` of the account's activity entries of the given
+    /// kinds (all kinds if empty), strictly after the cursor
+    pub fn get_account_activity(
+        &self,
+        pk: &PublicKey,
+        kinds: &[AccountActivityKind],
+        cursor: Option<&AccountActivityCursor>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<AccountActivity>> {
+        use AccountActivityKind::*;
+
+        let includes = |kind| kinds.is_empty() || kinds.contains(&kind);
+        let user_commands = [Payment, Delegation, Zkapp, CreationFee]
+            .into_iter()
+            .any(includes);
+        let internal_commands = [Coinbase, FeeTransfer].into_iter().any(includes);
+
+        // reverse iterators are exclusive of their start
+        let start = pk_txn_sort_key_prefix(
+            pk,
+            cursor.map_or(u32::MAX, |cursor| {
+                cursor.blockchain_length.saturating_add(1)
+            }),
+        );
+
+        let mut sent =
+            self.account_index(self.txn_from_height_sort_cf(), pk, &start, user_commands);
+        let mut received =
+            self.account_index(self.txn_to_height_sort_cf(), pk, &start, user_commands);
+        let mut internal = self.account_index(
+            self.internal_commands_pk_block_height_sort_cf(),
+            pk,
+            &start,
+            internal_commands,
+        );
+
+        let mut activity = vec![];
+        while activity.len() < limit {
+            // merge the highest block height of all indices
+            let Some(height) = [sent.peek(), received.peek(), internal.peek()]
+                .into_iter()
+                .flatten()
+                .map(|(height, _, _)| *height)
+                .max()
+            else {
+                break;
+            };
+
+            let mut height_activity = vec![];
+            for index in [&mut sent, &mut received] {
+                while let Some((_, key, _)) = index.next_if(|(h, _, _)| *h == height) {
+                    let state_hash = state_hash_suffix(&key)?;
+                    let txn_hash = txn_hash_of_key(&key);
+                    let cmd = self
+                        .get_user_command_state_hash(&txn_hash, &state_hash)?
+                        .with_context(|| {
+                            format!("user command {txn_hash} in block {state_hash}")
+                        })?;
+
+                    height_activity.append(&mut self.user_command_activity(pk, cmd)?);
+                }
+            }
+
+            while let Some((_, _, value)) = internal.next_if(|(h, _, _)| *h == height) {
+                let cmd: DbInternalCommandWithData = serde_json::from_slice(&value)?;
+                height_activity.push(self.internal_command_activity(cmd)?);
+            }
+
+            // sent & received self transfers are the same entry
+            height_activity.sort_by_key(|entry| std::cmp::Reverse(entry.cursor()));
+            height_activity.dedup_by_key(|entry| entry.cursor());
+
+            activity.extend(height_activity.into_iter().filter(|entry| {
+                includes(entry.kind) && cursor.map_or(true, |cursor| entry.cursor() < *cursor)
+            }));
+        }
+
+        activity.truncate(limit);
+        Ok(activity)
+    }
+
+    /// Account index entries, descending from `start`
+    fn account_index<'a>(
+        &'a self,
+        cf: &'a ColumnFamily,
+        pk: &'a PublicKey,
+        start: &[u8],
+        enabled: bool,
+    ) -> Peekable<Box<dyn Iterator<Item = IndexEntry> + 'a>> {
+        let entries: Box<dyn Iterator<Item = IndexEntry> + 'a> = if enabled {
+            Box::new(
+                self.iterator_cf(cf, IteratorMode::From(start, Direction::Reverse))
+                    .flatten()
+                    .take_while(|(key, _)| key.starts_with(pk.0.as_bytes()))
+                    .map(|(key, value)| (pk_key_height(&key), key, value)),
+            )
+        } else {
+            Box::new(std::iter::empty())
+        };
+
+        entries.peekable()
+    }
+
+    /// Activity entries of the account's user command
+    fn user_command_activity(
+        &self,
+        pk: &PublicKey,
+        cmd: SignedCommandWithData,
+    ) -> anyhow::Result<Vec<AccountActivity>> {
+        let sender = cmd.command.source_pk();
+        let receiver_accounts = cmd.command.receiver_accounts();
+        let is_receiver = receiver_accounts.iter().any(|(receiver, _)| receiver == pk);
+
+        let (direction, counterparty, token) = if sender == *pk {
+            let direction = if is_receiver {
+                ActivityDirection::SelfTransfer
+            } else {
+                ActivityDirection::Out
+            };
+            let counterparty = receiver_accounts
+                .iter()
+                .map(|(receiver, _)| receiver)
+                .find(|receiver| *receiver != pk)
+                .cloned();
+
+            (direction, counterparty, TokenAddress::default())
+        } else {
+            let token = receiver_accounts
+                .iter()
+                .find(|(receiver, _)| receiver == pk)
+                .map(|(_, token)| token.clone())
+                .unwrap_or_default();
+
+            (ActivityDirection::In, Some(sender), token)
+        };
+
+        let canonical = self.is_canonical(&cmd.state_hash)?;
+        let mut activity = vec![AccountActivity {
+            kind: match cmd.command.kind() {
+                CommandType::Payment => AccountActivityKind::Payment,
+                CommandType::Delegation => AccountActivityKind::Delegation,
+                CommandType::Zkapp => AccountActivityKind::Zkapp,
+            },
+            blockchain_length: cmd.blockchain_length,
+            state_hash: cmd.state_hash.clone(),
+            index_in_block: cmd.index_in_block,
+            direction,
+            counterparty,
+            amount: cmd.command.amount(),
+            token,
+            txn_hash: Some(cmd.tx_hash.clone()),
+            canonical,
+        }];
+
+        // creation fees of the account's token accounts
+        let creation_fees: Vec<_> = match cmd.receiver_account_creation_fee() {
+            Some(fee) if cmd.command.receiver_pk().first() == Some(pk) => {
+                vec![(TokenAddress::default(), fee)]
+            }
+            Some(_) => vec![],
+            None => cmd
+                .accounts_created
+                .iter()
+                .filter(|created| created.public_key == *pk)
+                .map(|created| (created.token.clone(), created.creation_fee))
+                .collect(),
+        };
+
+        for (token, fee) in creation_fees {
+            activity.push(AccountActivity {
+                kind: AccountActivityKind::CreationFee,
+                blockchain_length: cmd.blockchain_length,
+                state_hash: cmd.state_hash.clone(),
+                index_in_block: cmd.index_in_block,
+                direction: ActivityDirection::Out,
+                counterparty: None,
+                amount: fee.0,
+                token,
+                txn_hash: Some(cmd.tx_hash.clone()),
+                canonical,
+            });
+        }
+
+        Ok(activity)
+    }
+
+    fn internal_command_activity(
+        &self,
+        cmd: DbInternalCommandWithData,
+    ) -> anyhow::Result<AccountActivity> {
+        let (kind, amount) = match &cmd {
+            DbInternalCommandWithData::Coinbase { amount, .. } => {
+                (AccountActivityKind::Coinbase, *amount)
+            }
+            DbInternalCommandWithData::FeeTransfer { amount, .. } => {
+                (AccountActivityKind::FeeTransfer, *amount)
+            }
+        };
+
+        Ok(AccountActivity {
+            kind,
+            blockchain_length: cmd.block_height(),
+            state_hash: cmd.state_hash().clone(),
+            index_in_block: cmd.index_in_block(),
+            direction: ActivityDirection::In,
+            counterparty: None,
+            amount,
+            token: TokenAddress::default(),
+            txn_hash: None,
+            canonical: self.is_canonical(cmd.state_hash())?,
+        })
+    }
+
+    fn is_canonical(&self, state_hash: &StateHash) -> anyhow::Result<bool> {
+        Ok(self.get_block_canonicity(state_hash)? == Some(Canonicity::Canonical))
+    }
+}
+
+impl AccountActivity {
+    pub fn cursor(&self) -> AccountActivityCursor {
+        AccountActivityCursor {
+            blockchain_length: self.blockchain_length,
+            state_hash: self.state_hash.clone(),
+            index_in_block: self.index_in_block,
+            kind: self.kind,
+        }
+    }
+}
+
+/// Parse the [u32] height following the public key of account index keys
+fn pk_key_height(key: &[u8]) -> u32 {
+    u32_from_be_bytes(&key[PublicKey::LEN..][..U32_LEN]).expect("u32 height BE bytes")
+}
+
+impl AccountActivityKind {
+    const ALL: [Self; 6] = [
+        Self::Payment,
+        Self::Delegation,
+        Self::Zkapp,
+        Self::Coinbase,
+        Self::FeeTransfer,
+        Self::CreationFee,
+    ];
+}
+
+/////////////
+// Cursors //
+/////////////
+
+/// Opaque cursor format `{blockchain_length}:{state_hash}:{index_in_block}:{kind}`
+impl std::fmt::Display for AccountActivityCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = AccountActivityKind::ALL
+            .iter()
+            .position(|kind| *kind == self.kind)
+            .expect("activity kind");
+
+        write!(
+            f,
+            "{}:{}:{}:{kind}",
+            self.blockchain_length, self.state_hash, self.index_in_block
+        )
+    }
+}
+
+impl std::str::FromStr for AccountActivityCursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let [blockchain_length, state_hash, index_in_block, kind] = parts[..] else {
+            bail!("Invalid account activity cursor: {s}")
+        };
+
+        let Some(kind) = kind
+            .parse::<usize>()
+            .ok()
+            .and_then(|kind| AccountActivityKind::ALL.get(kind))
+        else {
+            bail!("Invalid account activity cursor kind: {s}")
+        };
+
+        Ok(Self {
+            blockchain_length: blockchain_length.parse()?,
+            state_hash: state_hash.parse()?,
+            index_in_block: index_in_block.parse()?,
+            kind: *kind,
+        })
+    }
+}
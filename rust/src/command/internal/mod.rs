@@ -151,6 +151,10 @@ pub enum DbInternalCommandWithData {
         kind: InternalCommandKind,
         date_time: i64,
         block_height: u32,
+
+        /// Index in the block's ledger application order
+        #[serde(default)]
+        index_in_block: u32,
     },
     Coinbase {
         receiver: PublicKey,
@@ -159,6 +163,10 @@ pub enum DbInternalCommandWithData {
         kind: InternalCommandKind,
         date_time: i64,
         block_height: u32,
+
+        /// Index in the block's ledger application order
+        #[serde(default)]
+        index_in_block: u32,
     },
 }
 
@@ -168,6 +176,7 @@ impl DbInternalCommandWithData {
         state_hash: StateHash,
         block_height: u32,
         date_time: i64,
+        index_in_block: u32,
     ) -> Self {
         match cmd {
             DbInternalCommand::Coinbase { receiver, amount } => Self::Coinbase {
@@ -176,6 +185,7 @@ impl DbInternalCommandWithData {
                 date_time,
                 state_hash,
                 block_height,
+                index_in_block,
                 kind: InternalCommandKind::Coinbase,
             },
             DbInternalCommand::FeeTransfer { receiver, amount } => Self::FeeTransfer {
@@ -184,6 +194,7 @@ impl DbInternalCommandWithData {
                 date_time,
                 state_hash,
                 block_height,
+                index_in_block,
                 kind: InternalCommandKind::FeeTransfer,
             },
             DbInternalCommand::FeeTransferViaCoinbase { receiver, amount } => Self::FeeTransfer {
@@ -192,25 +203,51 @@ impl DbInternalCommandWithData {
                 date_time,
                 state_hash,
                 block_height,
+                index_in_block,
                 kind: InternalCommandKind::FeeTransferViaCoinbase,
             },
         }
     }
 
     pub fn from_precomputed(block: &PrecomputedBlock) -> Vec<Self> {
+        let start_index = block.internal_commands_start_index();
         DbInternalCommand::from_precomputed(block)
             .into_iter()
-            .map(|cmd| {
+            .enumerate()
+            .map(|(i, cmd)| {
                 Self::from_internal_cmd(
                     cmd,
                     block.state_hash(),
                     block.blockchain_length(),
                     block.timestamp() as i64,
+                    start_index + i as u32,
                 )
             })
             .collect()
     }
 
+    pub fn index_in_block(&self) -> u32 {
+        match self {
+            Self::Coinbase { index_in_block, .. } | Self::FeeTransfer { index_in_block, .. } => {
+                *index_in_block
+            }
+        }
+    }
+
+    pub fn state_hash(&self) -> &StateHash {
+        match self {
+            Self::Coinbase { state_hash, .. } | Self::FeeTransfer { state_hash, .. } => state_hash,
+        }
+    }
+
+    pub fn block_height(&self) -> u32 {
+        match self {
+            Self::Coinbase { block_height, .. } | Self::FeeTransfer { block_height, .. } => {
+                *block_height
+            }
+        }
+    }
+
     pub fn public_keys(&self) -> PublicKey {
         match self {
             Self::Coinbase { receiver, .. } => receiver.clone(),
@@ -369,17 +406,9 @@ mod tests {
             ]
         );
 
-        let cmds: Vec<DbInternalCommandWithData> = internal_cmds
-            .into_iter()
-            .map(|cmd| {
-                DbInternalCommandWithData::from_internal_cmd(
-                    cmd,
-                    block.state_hash(),
-                    block.blockchain_length(),
-                    block.timestamp() as i64,
-                )
-            })
-            .collect();
+        // internal commands are applied after user commands
+        let start_index = block.commands().len() as u32;
+        let cmds = DbInternalCommandWithData::from_precomputed(&block);
         assert_eq!(
             cmds,
             vec![
@@ -390,6 +419,7 @@ mod tests {
                     kind: InternalCommandKind::Coinbase,
                     block_height: block.blockchain_length(),
                     date_time: block.timestamp() as i64,
+                    index_in_block: start_index,
                 },
                 DbInternalCommandWithData::FeeTransfer {
                     receiver: "B62qs2YyNuo1LbNo5sbhPByDDAB7NZiejFM6H1ctND5ui7wH4PWa7qm".into(),
@@ -398,6 +428,7 @@ mod tests {
                     kind: InternalCommandKind::FeeTransfer,
                     block_height: block.blockchain_length(),
                     date_time: block.timestamp() as i64,
+                    index_in_block: start_index + 1,
                 }
             ]
         );
@@ -16,6 +16,14 @@ pub trait InternalCommandStore {
         batch: &mut WriteBatch,
     ) -> anyhow::Result<()>;
 
+    /// Remove the given blocks' internal commands & their block & sort
+    /// indexes. Per account lists & counts are kept
+    fn remove_internal_commands_batch(
+        &self,
+        blocks: &[PrecomputedBlock],
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()>;
+
     /// Set the block's `index`-th internal command
     fn set_block_internal_command(
         &self,
@@ -62,11 +70,13 @@ pub trait InternalCommandStore {
     /// Get number of blocks that the public key has internal commands for
     fn get_pk_num_internal_commands(&self, pk: &PublicKey) -> anyhow::Result<Option<u32>>;
 
-    /// Write the account's internal commands to a CSV file
+    /// Write the account's internal commands to a CSV file, optionally
+    /// restricted to the canonical chain as of `as_of_height`
     fn write_internal_commands_csv(
         &self,
         pk: PublicKey,
         path: Option<PathBuf>,
+        as_of_height: Option<u32>,
     ) -> anyhow::Result<PathBuf>;
 
     ///////////////
@@ -79,6 +89,24 @@ pub trait InternalCommandStore {
     /// Internal commands iterator via global slot
     fn internal_commands_global_slot_iterator(&self, mode: IteratorMode) -> DBIterator<'_>;
 
+    /// Internal commands iterator via block height, starting from the first
+    /// (`Forward`) or last (`Reverse`) internal command at `block_height`
+    fn internal_commands_block_height_iterator_from(
+        &self,
+        block_height: u32,
+        direction: Direction,
+    ) -> DBIterator<'_>;
+
+    /// Account internal commands iterator via block height, starting from the
+    /// account's first (`Forward`) or last (`Reverse`) internal command at
+    /// `block_height`
+    fn internal_commands_pk_block_height_iterator_from(
+        &self,
+        pk: &PublicKey,
+        block_height: u32,
+        direction: Direction,
+    ) -> DBIterator<'_>;
+
     /// Account internal commands iterator via block height
     fn internal_commands_pk_block_height_iterator(
         &self,
@@ -150,6 +178,13 @@ pub trait InternalCommandStore {
         epoch: u32,
     ) -> anyhow::Result<()>;
 
+    /// Decrement internal commands counts given `internal_command` in `epoch`
+    fn decrement_internal_commands_counts(
+        &self,
+        internal_command: &DbInternalCommandWithData,
+        epoch: u32,
+    ) -> anyhow::Result<()>;
+
     /// get canonical internal commands count
     fn get_canonical_internal_commands_count(&self) -> anyhow::Result<u32>;
 
@@ -1,4 +1,7 @@
+pub mod activity;
 pub mod internal;
+pub mod precondition;
+pub mod roundtrip;
 pub mod signed;
 pub mod store;
 pub mod zkapp;
@@ -7,6 +10,7 @@ use crate::{
     base::{amount::Amount, nonce::Nonce, public_key::PublicKey, state_hash::StateHash},
     block::precomputed::PrecomputedBlock,
     command::signed::{SignedCommand, SignedCommandWithKind},
+    ledger::token::TokenAddress,
     mina_blocks::v2::{
         self,
         staged_ledger_diff::{
@@ -86,6 +90,14 @@ impl CommandStatusData {
         matches!(self, Self::Applied { .. })
     }
 
+    /// Kind of the failure which caused a failed command
+    pub fn failure_kind(&self) -> Option<String> {
+        if let Self::Failed(failures, _) = self {
+            return failures.first().map(|failure| failure.kind());
+        }
+        None
+    }
+
     fn balance_data(&self) -> Option<&mina_rs::TransactionStatusBalanceData> {
         if let Self::Applied { balance_data, .. } = self {
             return balance_data.as_ref();
@@ -153,13 +165,16 @@ impl CommandStatusData {
                 auxiliary_data: None,
                 balance_data: None,
             },
-            StatusAndFailure(_, (((fails,),),)) => Self::Failed(
-                vec![fails]
-                    .into_iter()
-                    .map(|reason| reason.to_owned())
-                    .collect(),
-                None,
-            ),
+            StatusAndFailure(..) => {
+                // the cause first, followed by the cancelled updates
+                let (mut failures, cancelled): (Vec<_>, Vec<_>) =
+                    data.failures().into_iter().flatten().partition(|failure| {
+                        *failure != mina_rs::TransactionStatusFailedType::Cancelled
+                    });
+
+                failures.extend(cancelled);
+                Self::Failed(failures, None)
+            }
         }
     }
 }
@@ -173,6 +188,18 @@ pub enum UserCommandWithStatus {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ZkappCommand(v2::staged_ledger_diff::ZkappCommandData);
 
+/// Status of a zkapp command's account update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccountUpdateStatus {
+    Applied,
+
+    /// The account update itself failed
+    Failed,
+
+    /// Not applied due to another account update's failure
+    Cancelled,
+}
+
 pub trait UserCommandWithStatusT {
     fn is_applied(&self) -> bool;
 
@@ -201,6 +228,20 @@ pub trait UserCommandWithStatusT {
     fn signer(&self) -> PublicKey;
 
     fn receiver_account_creation_fee_paid(&self) -> bool;
+
+    /// Tokens of every account the command accesses, deduped, MINA (the fee
+    /// token) first
+    ///
+    /// Includes zkapp account updates without a balance change, e.g.
+    /// precondition-only accesses & implicitly created accounts
+    fn tokens(&self) -> Vec<TokenAddress>;
+
+    /// Status of each zkapp account update, depth-first (empty for signed
+    /// commands)
+    ///
+    /// A failed zkapp command applies none of its account updates, only the
+    /// fee payer's
+    fn account_update_statuses(&self) -> Vec<AccountUpdateStatus>;
 }
 
 impl UserCommandWithStatusT for UserCommandWithStatus {
@@ -226,6 +267,52 @@ impl UserCommandWithStatusT for UserCommandWithStatus {
             .is_some()
     }
 
+    fn tokens(&self) -> Vec<TokenAddress> {
+        // fee payers always pay in MINA
+        let mut tokens = vec![TokenAddress::default()];
+
+        for (_, token) in SignedCommand::from(self.clone()).receiver_accounts() {
+            if !tokens.contains(&token) {
+                tokens.push(token);
+            }
+        }
+
+        tokens
+    }
+
+    fn account_update_statuses(&self) -> Vec<AccountUpdateStatus> {
+        let Self::V2(v2) = self else {
+            return vec![];
+        };
+
+        if !self.is_zkapp_command() {
+            return vec![];
+        }
+
+        let num_updates = SignedCommand::from(self.clone()).receiver_accounts().len();
+        if self.is_applied() {
+            return vec![AccountUpdateStatus::Applied; num_updates];
+        }
+
+        // failures are index-mapped, the fee payer first
+        let failures = v2.status.failures();
+        (1..=num_updates)
+            .map(|index| {
+                let failed = failures.get(index).is_some_and(|update_failures| {
+                    update_failures
+                        .iter()
+                        .any(|failure| *failure != mina_rs::TransactionStatusFailedType::Cancelled)
+                });
+
+                if failed {
+                    AccountUpdateStatus::Failed
+                } else {
+                    AccountUpdateStatus::Cancelled
+                }
+            })
+            .collect()
+    }
+
     fn status_data(&self) -> CommandStatusData {
         match self {
             Self::V1(v1) => CommandStatusData::from_transaction_status_v1(&v1.t.status.t),
@@ -472,9 +559,13 @@ pub const MEMO_LEN: usize = 32;
 /// 0th byte - tag to distinguish digests from other data
 /// 1st byte - is length, always 32 for digests
 /// bytes 2 to 33 - are data, 0-right-padded if length is less than 32
-
+///
+/// Malformed memos decode to the empty string
 pub fn decode_memo(encoded: &[u8]) -> String {
-    let value = &encoded[2..(encoded[1] as usize + 2).min(encoded.len())];
+    let value = match encoded {
+        [_, len, data @ ..] => &data[..(*len as usize).min(data.len())],
+        _ => return String::new(),
+    };
     String::from_utf8(value.to_vec()).unwrap_or_default()
 }
 
@@ -700,9 +791,14 @@ impl From<v2::staged_ledger_diff::Status> for mina_rs::TransactionStatus2 {
 
         match value {
             Status::Status((StatusKind::Applied,)) => Self::Applied,
-            Status::StatusAndFailure(StatusKind::Failed, (((failure,),),)) => {
-                Self::Failed(vec![Versioned::new(failure)])
-            }
+            Status::StatusAndFailure(StatusKind::Failed, _) => Self::Failed(
+                value
+                    .failures()
+                    .into_iter()
+                    .flatten()
+                    .map(Versioned::new)
+                    .collect(),
+            ),
             _ => unreachable!(),
         }
     }
@@ -760,9 +856,7 @@ impl From<CommandStatusData> for serde_json::Value {
                 let reason_json = Value::Array(
                     reason
                         .iter()
-                        .map(|r| {
-                            Value::String(serde_json::to_string(&r).expect("serialize reason"))
-                        })
+                        .map(|r| serde_json::to_value(r).expect("serialize reason"))
                         .collect(),
                 );
                 let balance_json = to_balance_json(&balance_data);
@@ -995,7 +1089,9 @@ pub fn to_mina_format(json: Value) -> Value {
                 // signed command
                 if let Value::Object(mut data) = obj["data"].clone() {
                     let kind = obj["data"]["kind"].clone();
-                    if kind == Value::String("Signed_command".into()) {
+                    if kind == Value::String("Signed_command".into())
+                        || kind == Value::String("Zkapp_command".into())
+                    {
                         data.remove("kind");
                         obj["data"] = Value::Array(vec![kind, Value::Object(data)]);
                     }
@@ -1018,14 +1114,13 @@ pub fn to_mina_format(json: Value) -> Value {
                     if kind == Value::String("Stake_delegation".into()) {
                         body.remove("kind");
 
-                        if let Some(set_delegate) = body.remove("Set_delegate") {
-                            obj["body"] = Value::Array(vec![
-                                kind,
-                                Value::Array(vec!["Set_delegate".into(), set_delegate]),
-                            ]);
-                        } else {
-                            obj["body"] = Value::Array(vec![kind, Value::Object(body)]);
-                        }
+                        // v1 delegations carry the delegator alongside the new delegate
+                        let set_delegate =
+                            body.remove("Set_delegate").unwrap_or(Value::Object(body));
+                        obj["body"] = Value::Array(vec![
+                            kind,
+                            Value::Array(vec!["Set_delegate".into(), set_delegate]),
+                        ]);
                     }
                 }
 
@@ -1087,6 +1182,11 @@ mod test {
         ];
         let actual = decode_memo(&bytes);
         assert_eq!(&expected, &actual);
+
+        // malformed memos don't panic
+        assert_eq!(decode_memo(&[]), "");
+        assert_eq!(decode_memo(&[1]), "");
+        assert_eq!(decode_memo(&[1, 32, 77, 73]), "MI");
     }
 
     #[tokio::test]
@@ -1099,6 +1199,7 @@ mod test {
             MAINNET_CANONICAL_THRESHOLD,
             false,
             BLOCK_REPORTING_FREQ_NUM,
+            true,
         )
         .await
         .unwrap();
@@ -1253,47 +1354,12 @@ mod test {
 
     #[test]
     fn mainnet_user_command_with_status_json() -> anyhow::Result<()> {
+        use super::roundtrip::{
+            convert_v1_to_v2, normalize as convert, normalized_mina_json as to_mina_json,
+        };
         use crate::block::precomputed::PrecomputedBlock;
         use serde_json::*;
 
-        fn to_mina_json(json: Value) -> Value {
-            to_mina_format(super::convert(true, fee_convert(json)))
-        }
-
-        fn convert(value: Value) -> Value {
-            super::convert(true, value)
-        }
-
-        fn convert_v1_to_v2(json: Value) -> Value {
-            match json {
-                Value::Object(mut obj) => {
-                    obj.iter_mut().for_each(|(key, value)| {
-                        if key == "body" {
-                            if let Value::Array(arr) = value {
-                                if let Value::Object(map) = arr.get_mut(1).unwrap() {
-                                    map.remove("source_pk");
-                                    map.remove("token_id");
-                                }
-                            }
-                        } else if key == "common" {
-                            if let Value::Object(map) = value {
-                                map.remove("fee_token");
-                            }
-                        } else if key == "status" {
-                            if let Value::Array(arr) = value {
-                                *value = Value::Array(vec![arr.first().cloned().unwrap()]);
-                            }
-                        } else {
-                            *value = convert_v1_to_v2(value.clone())
-                        }
-                    });
-                    Value::Object(obj)
-                }
-                Value::Array(arr) => Value::Array(arr.into_iter().map(convert_v1_to_v2).collect()),
-                x => x,
-            }
-        }
-
         // v1
         let path: PathBuf = "./tests/data/non_sequential_blocks/mainnet-220897-3NL4HLb7MQrxmAqVw8D4vEXCj2tdT8zgP9DFWGRoDxP72b4wxyUw.json".into();
         let contents = std::fs::read(path.clone())?;
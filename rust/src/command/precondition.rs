@@ -0,0 +1,253 @@
+//! Compact zkapp command precondition summaries
+//!
+//! Records which preconditions a zkapp command's fee payer & account updates
+//! check, without the checked values, & cross-references a failed command's
+//! precondition failures with the failing account update's checks
+
+use super::{UserCommandWithStatus, UserCommandWithStatusT};
+use crate::{
+    base::nonce::Nonce,
+    mina_blocks::v2::staged_ledger_diff::{
+        Elt, NetworkPreconditions, Precondition, Preconditions, StakingEpochDataPreconditions,
+        UserCommandData, ZkappCommandData,
+    },
+    protocol::serialization_types::staged_ledger_diff::TransactionStatusFailedType,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZkappPreconditionSummary {
+    /// Global slot the fee payer's command is valid until
+    pub fee_payer_valid_until: Option<u64>,
+
+    /// Nonce the fee payer's account must have
+    pub fee_payer_nonce: Nonce,
+
+    /// Preconditions checked by each account update, depth-first
+    pub account_updates: Vec<AccountUpdatePreconditions>,
+
+    /// Precondition failures of a failed command
+    #[serde(default)]
+    pub failed_preconditions: Vec<FailedPrecondition>,
+}
+
+/// Precondition categories checked by an account update
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountUpdatePreconditions {
+    pub nonce: bool,
+    pub balance: bool,
+
+    /// Indices of the checked app state fields
+    pub app_state: Vec<u32>,
+
+    pub action_state: bool,
+    pub receipt_chain_hash: bool,
+    pub delegate: bool,
+    pub proved_state: bool,
+    pub is_new: bool,
+
+    /// Whether any protocol state precondition is checked
+    pub network: bool,
+
+    /// Whether the global slot range is checked
+    pub valid_while: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PreconditionCategory {
+    Nonce,
+    Balance,
+    AppState,
+    ActionState,
+    ReceiptChainHash,
+    Delegate,
+    ProvedState,
+    IsNew,
+    Network,
+    ValidWhile,
+}
+
+/// Precondition failure of an account update
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailedPrecondition {
+    /// Depth-first index of the failing account update
+    pub account_update_index: u32,
+
+    pub category: PreconditionCategory,
+
+    /// Index of the unsatisfied app state field
+    pub app_state_index: Option<u32>,
+
+    /// Whether the account update checks the precondition, i.e. `false`
+    /// means the failure is inconsistent with the command
+    pub checked: bool,
+}
+
+impl ZkappPreconditionSummary {
+    /// Summary of a zkapp command's preconditions (`None` for signed
+    /// commands)
+    pub fn from_user_command(user_cmd: &UserCommandWithStatus) -> Option<Self> {
+        let UserCommandWithStatus::V2(v2) = user_cmd else {
+            return None;
+        };
+
+        let UserCommandData::ZkappCommandData(data) = &v2.data.1 else {
+            return None;
+        };
+
+        let mut summary = Self::from_zkapp_command(data);
+        if !user_cmd.is_applied() {
+            // failures are index-mapped, the fee payer first
+            summary.failed_preconditions = v2
+                .status
+                .failures()
+                .iter()
+                .enumerate()
+                .skip(1)
+                .flat_map(|(index, failures)| {
+                    let account_update_index = index as u32 - 1;
+                    let checks = summary.account_updates.get(account_update_index as usize);
+                    failures.iter().filter_map(move |failure| {
+                        FailedPrecondition::new(account_update_index, failure, checks)
+                    })
+                })
+                .collect();
+        }
+
+        Some(summary)
+    }
+
+    /// Summary of the command's checked preconditions, without failures
+    pub fn from_zkapp_command(data: &ZkappCommandData) -> Self {
+        let fee_payer = &data.fee_payer.body;
+        let mut account_updates = vec![];
+        for update in data.account_updates.iter() {
+            AccountUpdatePreconditions::collect(&update.elt, &mut account_updates);
+        }
+
+        Self {
+            fee_payer_valid_until: fee_payer.valid_until.as_ref().map(|slot| slot.0),
+            fee_payer_nonce: fee_payer.nonce,
+            account_updates,
+            failed_preconditions: vec![],
+        }
+    }
+}
+
+impl AccountUpdatePreconditions {
+    /// Collects the preconditions of the update tree, depth-first
+    fn collect(elt: &Elt, account_updates: &mut Vec<Self>) {
+        account_updates.push(Self::from(&elt.account_update.body.preconditions));
+        for call in elt.calls.iter() {
+            Self::collect(&call.elt, account_updates);
+        }
+    }
+
+    /// Whether the account update checks the precondition category
+    pub fn checks(&self, category: PreconditionCategory, app_state_index: Option<u32>) -> bool {
+        use PreconditionCategory::*;
+
+        match category {
+            Nonce => self.nonce,
+            Balance => self.balance,
+            AppState => app_state_index.map_or(!self.app_state.is_empty(), |index| {
+                self.app_state.contains(&index)
+            }),
+            ActionState => self.action_state,
+            ReceiptChainHash => self.receipt_chain_hash,
+            Delegate => self.delegate,
+            ProvedState => self.proved_state,
+            IsNew => self.is_new,
+            Network => self.network,
+            ValidWhile => self.valid_while,
+        }
+    }
+}
+
+impl FailedPrecondition {
+    /// `None` if the failure isn't a precondition failure
+    fn new(
+        account_update_index: u32,
+        failure: &TransactionStatusFailedType,
+        checks: Option<&AccountUpdatePreconditions>,
+    ) -> Option<Self> {
+        let (category, app_state_index) = PreconditionCategory::of_failure(failure)?;
+        Some(Self {
+            account_update_index,
+            category,
+            app_state_index,
+            checked: checks.is_some_and(|checks| checks.checks(category, app_state_index)),
+        })
+    }
+}
+
+impl PreconditionCategory {
+    /// Category (& app state index) of a precondition failure
+    pub fn of_failure(failure: &TransactionStatusFailedType) -> Option<(Self, Option<u32>)> {
+        use TransactionStatusFailedType::*;
+
+        Some(match failure {
+            AccountNoncePreconditionUnsatisfied => (Self::Nonce, None),
+            AccountBalancePreconditionUnsatisfied => (Self::Balance, None),
+            AccountAppStatePreconditionUnsatisfied(index) => (Self::AppState, Some(*index as u32)),
+            AccountActionStatePreconditionUnsatisfied => (Self::ActionState, None),
+            AccountReceiptChainHashPreconditionUnsatisfied => (Self::ReceiptChainHash, None),
+            AccountDelegatePreconditionUnsatisfied => (Self::Delegate, None),
+            AccountProvedStatePreconditionUnsatisfied => (Self::ProvedState, None),
+            AccountIsNewPreconditionUnsatisfied => (Self::IsNew, None),
+            ProtocolStatePreconditionUnsatisfied => (Self::Network, None),
+            ValidWhilePreconditionUnsatisfied => (Self::ValidWhile, None),
+            _ => return None,
+        })
+    }
+}
+
+fn is_checked<T>(precondition: &Precondition<T>) -> bool {
+    matches!(precondition, Precondition::Check(_))
+}
+
+fn is_epoch_data_checked(epoch_data: &StakingEpochDataPreconditions) -> bool {
+    is_checked(&epoch_data.ledger.hash)
+        || is_checked(&epoch_data.ledger.total_currency)
+        || is_checked(&epoch_data.seed)
+        || is_checked(&epoch_data.start_checkpoint)
+        || is_checked(&epoch_data.lock_checkpoint)
+        || is_checked(&epoch_data.epoch_length)
+}
+
+fn is_network_checked(network: &NetworkPreconditions) -> bool {
+    is_checked(&network.snarked_ledger_hash)
+        || is_checked(&network.blockchain_length)
+        || is_checked(&network.min_window_density)
+        || is_checked(&network.total_currency)
+        || is_checked(&network.global_slot_since_genesis)
+        || is_epoch_data_checked(&network.staking_epoch_data)
+        || is_epoch_data_checked(&network.next_epoch_data)
+}
+
+/////////////////
+// Conversions //
+/////////////////
+
+impl From<&Preconditions> for AccountUpdatePreconditions {
+    fn from(value: &Preconditions) -> Self {
+        let account = &value.account;
+        Self {
+            nonce: is_checked(&account.nonce),
+            balance: is_checked(&account.balance),
+            app_state: account
+                .state
+                .iter()
+                .enumerate()
+                .filter_map(|(index, state)| is_checked(state).then_some(index as u32))
+                .collect(),
+            action_state: is_checked(&account.action_state),
+            receipt_chain_hash: is_checked(&account.receipt_chain_hash),
+            delegate: is_checked(&account.delegate),
+            proved_state: is_checked(&account.proved_state),
+            is_new: is_checked(&account.is_new),
+            network: is_network_checked(&value.network),
+            valid_while: is_checked(&value.valid_while),
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Command JSON round-trip verification
+//!
+//! Re-serializes a block's user commands via [to_mina_format] & structurally
+//! compares them against the block's original command JSON. Memos &
+//! signatures are not compared. V2 commands are compared after stripping the
+//! V1-only fields & status data.
+
+use super::{convert, fee_convert, to_mina_format, UserCommandWithStatus};
+use crate::{
+    base::state_hash::StateHash,
+    block::{precomputed::PrecomputedBlock, store::BlockStore},
+    protocol::serialization_types::staged_ledger_diff as mina_rs,
+    store::{column_families::ColumnFamilyHelpers, IndexerStore},
+};
+use anyhow::{bail, Context};
+use log::trace;
+use serde_json::Value;
+use speedb::IteratorMode;
+
+/// Re-serialized command which differs from the original
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandMismatch {
+    /// Index of the command in the block
+    pub index: usize,
+
+    /// Normalized original command JSON
+    pub expected: Value,
+
+    /// Normalized re-serialized command JSON
+    pub actual: Value,
+
+    /// Differing JSON paths
+    pub diffs: Vec<String>,
+}
+
+/// Summary of a stored block round-trip verification
+#[derive(Debug, Default)]
+pub struct RoundtripReport {
+    pub num_blocks: u32,
+    pub num_commands: u32,
+    pub mismatches: Vec<(StateHash, CommandMismatch)>,
+}
+
+/// Normalize command JSON for comparison: numbers are converted to strings &
+/// memos & signatures are nulled
+pub fn normalize(json: Value) -> Value {
+    convert(true, json)
+}
+
+/// Convert our command JSON to the normalized Mina format
+pub fn normalized_mina_json(json: Value) -> Value {
+    to_mina_format(convert(true, fee_convert(json)))
+}
+
+/// Strip the V1-only payload fields & status data from Mina format command
+/// JSON
+pub fn convert_v1_to_v2(json: Value) -> Value {
+    match json {
+        Value::Object(mut obj) => {
+            obj.iter_mut().for_each(|(key, value)| {
+                if key == "body" {
+                    if let Value::Array(arr) = value {
+                        if let Some(Value::Object(map)) = arr.get_mut(1) {
+                            map.remove("source_pk");
+                            map.remove("token_id");
+                        }
+                    }
+                } else if key == "common" {
+                    if let Value::Object(map) = value {
+                        map.remove("fee_token");
+                    }
+                } else if key == "status" {
+                    if let Value::Array(arr) = value {
+                        *value = v2_status(arr);
+                    }
+                } else {
+                    *value = convert_v1_to_v2(value.clone())
+                }
+            });
+            Value::Object(obj)
+        }
+        Value::Array(arr) => Value::Array(arr.into_iter().map(convert_v1_to_v2).collect()),
+        x => x,
+    }
+}
+
+/// V2 statuses drop the auxiliary & balance data & nest the failures, i.e.
+/// `["Applied"]` or `["Failed", [[[<reason>, ..]]]]`
+fn v2_status(status: &[Value]) -> Value {
+    let kind = status.first().cloned().unwrap_or(Value::Null);
+    match status.get(1) {
+        Some(reasons @ Value::Array(_)) if kind == "Failed" => Value::Array(vec![
+            kind,
+            Value::Array(vec![Value::Array(vec![reasons.to_owned()])]),
+        ]),
+        _ => Value::Array(vec![kind]),
+    }
+}
+
+/// Re-serialize the command via our conversion pipeline
+pub fn command_mina_json(command: UserCommandWithStatus) -> Value {
+    let is_v2 = matches!(command, UserCommandWithStatus::V2(_));
+    let json = normalized_mina_json(command.into());
+
+    if is_v2 {
+        convert_v1_to_v2(json)
+    } else {
+        json
+    }
+}
+
+/// Original JSON of the block's user commands, in [PrecomputedBlock::commands]
+/// order
+pub fn original_commands_json(block: &PrecomputedBlock) -> anyhow::Result<Vec<Value>> {
+    let staged_ledger_diff = match block {
+        PrecomputedBlock::V1(v1) => serde_json::to_value(mina_rs::StagedLedgerDiffJson::from(
+            v1.staged_ledger_diff.clone(),
+        ))?,
+        PrecomputedBlock::V2(v2) => serde_json::to_value(&v2.staged_ledger_diff)?,
+    };
+
+    let mut commands = vec![];
+    if let Some(diffs) = staged_ledger_diff["diff"].as_array() {
+        for diff in diffs.iter().filter(|diff| !diff.is_null()) {
+            match diff["commands"].as_array() {
+                Some(diff_commands) => commands.extend(diff_commands.iter().cloned()),
+                None => bail!(
+                    "Missing commands in staged ledger diff {}",
+                    block.state_hash()
+                ),
+            }
+        }
+    }
+
+    Ok(commands)
+}
+
+/// Compare the block's re-serialized user commands to the original JSON
+pub fn verify_block(block: &PrecomputedBlock) -> anyhow::Result<Vec<CommandMismatch>> {
+    let commands = block.commands();
+    let original = original_commands_json(block)?;
+
+    if commands.len() != original.len() {
+        bail!(
+            "Block {} has {} commands, original JSON has {}",
+            block.state_hash(),
+            commands.len(),
+            original.len()
+        )
+    }
+
+    let mut mismatches = vec![];
+    for (index, (command, original)) in commands.into_iter().zip(original).enumerate() {
+        let expected = normalize(original);
+        let actual = command_mina_json(command);
+
+        if expected != actual {
+            let mut diffs = vec![];
+            json_diff("$", &expected, &actual, &mut diffs);
+
+            mismatches.push(CommandMismatch {
+                index,
+                expected,
+                actual,
+                diffs,
+            });
+        }
+    }
+
+    Ok(mismatches)
+}
+
+/// Collect the paths at which `actual` differs from `expected`
+pub fn json_diff(path: &str, expected: &Value, actual: &Value, diffs: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected_value) in expected.iter() {
+                let key_path = format!("{path}.{key}");
+                match actual.get(key) {
+                    Some(actual_value) => json_diff(&key_path, expected_value, actual_value, diffs),
+                    None => diffs.push(format!("{key_path}: missing, expected {expected_value}")),
+                }
+            }
+
+            for (key, actual_value) in actual.iter() {
+                if !expected.contains_key(key) {
+                    diffs.push(format!("{path}.{key}: unexpected {actual_value}"));
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            if expected.len() != actual.len() {
+                diffs.push(format!(
+                    "{path}: expected {} elements, got {}",
+                    expected.len(),
+                    actual.len()
+                ));
+            }
+
+            for (idx, (expected, actual)) in expected.iter().zip(actual.iter()).enumerate() {
+                json_diff(&format!("{path}[{idx}]"), expected, actual, diffs);
+            }
+        }
+        (expected, actual) if expected != actual => {
+            diffs.push(format!("{path}: expected {expected}, got {actual}"))
+        }
+        _ => (),
+    }
+}
+
+impl IndexerStore {
+    /// Verify the command JSON round-trip of the stored blocks
+    ///
+    /// With `sample`, only about `sample` evenly spaced blocks are verified
+    pub fn verify_json_roundtrip(&self, sample: Option<usize>) -> anyhow::Result<RoundtripReport> {
+        let state_hashes: Vec<StateHash> = self
+            .iterator_cf(self.blocks_state_hash_cf(), IteratorMode::Start)
+            .flatten()
+            .map(|(key, _)| StateHash::from_bytes(&key))
+            .collect::<anyhow::Result<_>>()?;
+
+        let step = match sample {
+            Some(0) => return Ok(RoundtripReport::default()),
+            Some(sample) => state_hashes.len().div_ceil(sample).max(1),
+            None => 1,
+        };
+
+        let mut report = RoundtripReport::default();
+        for state_hash in state_hashes.into_iter().step_by(step) {
+            trace!("Verifying command JSON round-trip of block {state_hash}");
+            let (block, _) = self
+                .get_block(&state_hash)?
+                .with_context(|| format!("Block missing from store {state_hash}"))?;
+
+            report.num_blocks += 1;
+            report.num_commands += block.commands().len() as u32;
+            report.mismatches.extend(
+                verify_block(&block)?
+                    .into_iter()
+                    .map(|mismatch| (state_hash.clone(), mismatch)),
+            );
+        }
+
+        Ok(report)
+    }
+}
+
+impl std::fmt::Display for CommandMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "command {}", self.index)?;
+        for diff in self.diffs.iter() {
+            write!(f, "\n  {diff}")?;
+        }
+        Ok(())
+    }
+}
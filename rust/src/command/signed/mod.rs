@@ -1,8 +1,13 @@
 mod txn_hash;
 
 use crate::{
-    command::*,
-    mina_blocks::v2::{self, staged_ledger_diff::UserCommandData},
+    block::AccountCreated,
+    command::{precondition::ZkappPreconditionSummary, *},
+    ledger::token::TokenAddress,
+    mina_blocks::v2::{
+        self,
+        staged_ledger_diff::{AuthorizationKind, Elt, UserCommandData},
+    },
     proof_systems::signer::signature::Signature,
     protocol::{
         bin_prot,
@@ -49,6 +54,23 @@ pub struct SignedCommandWithData {
     pub date_time: u64,
     pub nonce: Nonce,
     pub global_slot_since_genesis: u32,
+
+    /// Accounts created by the command
+    #[serde(default)]
+    pub accounts_created: Vec<AccountCreated>,
+
+    /// Index of the command in the block's ledger application order, see
+    /// [PrecomputedBlock::commands]
+    #[serde(default)]
+    pub index_in_block: u32,
+
+    /// Status of each zkapp account update, depth-first
+    #[serde(default)]
+    pub account_update_statuses: Vec<AccountUpdateStatus>,
+
+    /// Preconditions checked by a zkapp command
+    #[serde(default)]
+    pub precondition_summary: Option<ZkappPreconditionSummary>,
 }
 
 impl SignedCommand {
@@ -198,6 +220,29 @@ impl SignedCommand {
         }
     }
 
+    /// Accounts the command can create, in application order
+    ///
+    /// Zkapp account updates are visited depth-first
+    pub fn receiver_accounts(&self) -> Vec<(PublicKey, TokenAddress)> {
+        match self {
+            Self::V2(UserCommandData::ZkappCommandData(data)) => {
+                let mut accounts = vec![];
+                for update in data.account_updates.iter() {
+                    zkapp_update_accounts(&update.elt, &mut accounts);
+                }
+                accounts
+            }
+            _ => match self.kind() {
+                CommandType::Payment => self
+                    .receiver_pk()
+                    .into_iter()
+                    .map(|pk| (pk, TokenAddress::default()))
+                    .collect(),
+                _ => vec![],
+            },
+        }
+    }
+
     pub fn source_pk(&self) -> PublicKey {
         match self {
             Self::V1(v1) => {
@@ -248,6 +293,25 @@ impl SignedCommand {
         }
     }
 
+    /// Kind of the command's signature, if present
+    ///
+    /// For zkapp commands, this is the fee payer's authorization
+    pub fn signature_kind(&self) -> Option<AuthorizationKind> {
+        let is_signed = match self {
+            Self::V1(_) => true,
+            Self::V2(v2) => match v2 {
+                UserCommandData::SignedCommandData(data) => !data.signature.is_empty(),
+                UserCommandData::ZkappCommandData(data) => data
+                    .fee_payer
+                    .authorization
+                    .as_ref()
+                    .is_some_and(|sig| !sig.is_empty()),
+            },
+        };
+
+        is_signed.then_some(AuthorizationKind::Signature)
+    }
+
     pub fn all_command_public_keys(&self) -> Vec<PublicKey> {
         let mut pks = self.receiver_pk();
 
@@ -286,6 +350,10 @@ impl SignedCommand {
         matches!(self.kind(), CommandType::Delegation)
     }
 
+    pub fn is_zkapp_command(&self) -> bool {
+        matches!(self.kind(), CommandType::Zkapp)
+    }
+
     pub fn from_user_command(uc: UserCommandWithStatus) -> Self {
         match uc {
             UserCommandWithStatus::V1(v1) => match v1.t.data.t.t {
@@ -346,6 +414,16 @@ impl SignedCommand {
     }
 }
 
+/// Pushes the account update's `(public key, token)` followed by its calls
+fn zkapp_update_accounts(elt: &Elt, accounts: &mut Vec<(PublicKey, TokenAddress)>) {
+    let body = &elt.account_update.body;
+    accounts.push((body.public_key.to_owned(), body.token_id.to_owned()));
+
+    for call in elt.calls.iter() {
+        zkapp_update_accounts(&call.elt, accounts);
+    }
+}
+
 /// Hash a V2 transaction JSON string via `mina_txn_hasher.exe`
 fn hash_signed_command_v2(cmd_str: String) -> anyhow::Result<TxnHash> {
     let mut proc = std::process::Command::new("mina_txn_hasher.exe");
@@ -402,15 +480,21 @@ impl SignedCommandWithData {
         blockchain_length: u32,
         date_time: u64,
         global_slot_since_genesis: u32,
+        accounts_created: Vec<AccountCreated>,
+        index_in_block: u32,
     ) -> Self {
         let command = SignedCommand::from(user_cmd.clone());
         Self {
             date_time,
             blockchain_length,
             global_slot_since_genesis,
+            accounts_created,
+            index_in_block,
             nonce: command.nonce(),
             state_hash: state_hash.into(),
             status: user_cmd.status_data(),
+            account_update_statuses: user_cmd.account_update_statuses(),
+            precondition_summary: ZkappPreconditionSummary::from_user_command(user_cmd),
             tx_hash: command
                 .hash_signed_command()
                 .expect("valid transaction hash"),
@@ -422,17 +506,39 @@ impl SignedCommandWithData {
         block
             .commands()
             .iter()
-            .map(|cmd| {
+            .zip(block.command_accounts_created())
+            .enumerate()
+            .map(|(index, (cmd, accounts_created))| {
                 Self::from(
                     cmd,
                     &block.state_hash().0,
                     block.blockchain_length(),
                     block.timestamp(),
                     block.global_slot_since_genesis(),
+                    accounts_created,
+                    index as u32,
                 )
             })
             .collect()
     }
+
+    /// Creation fee paid for the payment's receiver account, if the payment
+    /// created it
+    pub fn receiver_account_creation_fee(&self) -> Option<Amount> {
+        if self.command.kind() != CommandType::Payment {
+            return None;
+        }
+
+        let receiver = self.command.receiver_pk();
+        let receiver = receiver.first()?;
+        self.accounts_created
+            .iter()
+            .find(|created| {
+                created.public_key == *receiver && created.token == TokenAddress::default()
+            })
+            .map(|created| created.creation_fee)
+            .or_else(|| self.status.receiver_account_creation_fee_paid().map(Amount))
+    }
 }
 
 /////////////////
@@ -597,8 +703,14 @@ impl From<SignedCommandWithKind> for serde_json::Value {
     fn from(value: SignedCommandWithKind) -> Self {
         use serde_json::*;
 
+        let kind = if value.0.is_zkapp_command() {
+            "Zkapp_command"
+        } else {
+            "Signed_command"
+        };
+
         if let Value::Object(mut obj) = value.0.into() {
-            obj.insert("kind".into(), Value::String("Signed_command".into()));
+            obj.insert("kind".into(), Value::String(kind.into()));
             Value::Object(obj)
         } else {
             Value::Null
@@ -616,12 +728,14 @@ impl From<SignedCommandWithData> for serde_json::Value {
         let command = value.command.into();
         let status = value.status.into();
         let blockchain_length = value.blockchain_length.into();
+        let index_in_block = value.index_in_block.into();
 
         obj.insert("tx_hash".into(), tx_hash);
         obj.insert("command".into(), command);
         obj.insert("status".into(), status);
         obj.insert("state_hash".into(), state_hash);
         obj.insert("blockchain_length".into(), blockchain_length);
+        obj.insert("index_in_block".into(), index_in_block);
 
         Value::Object(obj)
     }
@@ -636,8 +750,8 @@ impl From<SignedCommandWithData> for SignedCommand {
 impl From<SignedCommandWithData> for Command {
     fn from(value: SignedCommandWithData) -> Self {
         SignedCommandWithCreationData {
+            is_new_receiver_account: value.receiver_account_creation_fee().is_some(),
             signed_command: value.command,
-            is_new_receiver_account: value.status.receiver_account_creation_fee_paid().is_some(),
         }
         .into()
     }
@@ -1,13 +1,33 @@
 use crate::{
-    base::{public_key::PublicKey, state_hash::StateHash},
+    base::{nonce::Nonce, public_key::PublicKey, state_hash::StateHash},
     block::{precomputed::PrecomputedBlock, store::DbBlockUpdate},
     command::{
-        signed::{SignedCommandWithData, TxnHash},
-        UserCommandWithStatus,
+        signed::{SignedCommand, SignedCommandWithData, TxnHash},
+        UserCommandWithStatus, UserCommandWithStatusT,
     },
+    ledger::{diff::account::AccountDiff, token::TokenAddress},
 };
+use serde::{Deserialize, Serialize};
 use speedb::{DBIterator, IteratorMode, WriteBatch};
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// Delegate change of an account by an applied user command, i.e. a stake
+/// delegation or a zkapp account update
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DelegationChange {
+    pub delegator: PublicKey,
+    pub delegate: PublicKey,
+
+    /// Delegator's nonce after the change
+    pub nonce: Nonce,
+
+    pub blockchain_length: u32,
+    pub state_hash: StateHash,
+
+    /// Index of the command in the block
+    pub index_in_block: u32,
+    pub txn_hash: TxnHash,
+}
 
 /// Store for user commands
 pub trait UserCommandStore {
@@ -19,6 +39,15 @@ pub trait UserCommandStore {
         batch: &mut WriteBatch,
     ) -> anyhow::Result<()>;
 
+    /// Remove the given blocks' user commands & their block, sort, memo,
+    /// token, failure & delegation indexes. Per account lists & counts are
+    /// kept
+    fn remove_user_commands_batch(
+        &self,
+        blocks: &[PrecomputedBlock],
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()>;
+
     /// Set user commands for the given block
     fn set_block_user_commands_batch(
         &self,
@@ -70,6 +99,52 @@ pub trait UserCommandStore {
         batch: &mut WriteBatch,
     ) -> anyhow::Result<()>;
 
+    /// Index the command's decoded memo & fee for memo search (commands with
+    /// empty memos aren't indexed)
+    fn set_user_command_memo_batch(
+        &self,
+        block_height: u32,
+        txn_hash: &TxnHash,
+        state_hash: &StateHash,
+        command: &SignedCommand,
+        batch: &mut WriteBatch,
+    );
+
+    /// Index the command by the custom tokens it involves (MINA isn't
+    /// indexed, see [Self::user_commands_height_iterator])
+    fn set_user_command_tokens_batch(
+        &self,
+        block_height: u32,
+        txn_hash: &TxnHash,
+        state_hash: &StateHash,
+        tokens: &[TokenAddress],
+        batch: &mut WriteBatch,
+    );
+
+    /// Index a failed command by the kind of its failure
+    fn set_user_command_failure_batch(
+        &self,
+        block_height: u32,
+        txn_hash: &TxnHash,
+        state_hash: &StateHash,
+        failure_kind: &str,
+        batch: &mut WriteBatch,
+    );
+
+    /// Index the block's delegation changes by delegator
+    fn set_block_delegations_batch(
+        &self,
+        block: &PrecomputedBlock,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()>;
+
+    /// Get the delegator's delegation changes in canonical blocks, sorted by
+    /// block height
+    fn get_delegation_history(
+        &self,
+        delegator: &PublicKey,
+    ) -> anyhow::Result<Vec<DelegationChange>>;
+
     /// Get state hashes of blocks containing `txn_hash` in block sorted order
     fn get_user_command_state_hashes(
         &self,
@@ -82,11 +157,13 @@ pub trait UserCommandStore {
         txn_hash: &TxnHash,
     ) -> anyhow::Result<Option<u32>>;
 
-    /// Write the account's user commands to a CSV file
+    /// Write the account's user commands to a CSV file, optionally
+    /// restricted to the canonical chain as of `as_of_height`
     fn write_user_commands_csv(
         &self,
         pk: &PublicKey,
         path: Option<PathBuf>,
+        as_of_height: Option<u32>,
     ) -> anyhow::Result<PathBuf>;
 
     ///////////////
@@ -99,6 +176,17 @@ pub trait UserCommandStore {
     /// Iterator for user commands via blockchain length
     fn user_commands_height_iterator(&self, mode: IteratorMode) -> DBIterator<'_>;
 
+    /// Iterator for user commands with memos via blockchain length
+    fn user_commands_memo_iterator(&self, mode: IteratorMode) -> DBIterator<'_>;
+
+    /// Iterator for user commands via involved custom token & blockchain
+    /// length
+    fn user_commands_token_iterator(&self, mode: IteratorMode) -> DBIterator<'_>;
+
+    /// Iterator for failed user commands via failure kind & blockchain
+    /// length
+    fn user_commands_failure_iterator(&self, mode: IteratorMode) -> DBIterator<'_>;
+
     /// Iterator for user commands by sender via block height
     fn txn_from_height_iterator(&self, mode: IteratorMode) -> DBIterator<'_>;
 
@@ -174,6 +262,15 @@ pub trait UserCommandStore {
     /// Get failed user commands count
     fn get_failed_user_commands_count(&self) -> anyhow::Result<u32>;
 
+    /// Get the number of failed user commands per failure kind in blocks of
+    /// the inclusive height range, optionally of the given canonicity
+    fn get_user_command_failure_counts(
+        &self,
+        from_height: u32,
+        to_height: u32,
+        canonical: Option<bool>,
+    ) -> anyhow::Result<BTreeMap<String, u32>>;
+
     /// Increment applied user commands count
     fn increment_applied_user_commands_count(&self, incr: u32) -> anyhow::Result<()>;
 
@@ -216,3 +313,37 @@ pub trait UserCommandStore {
     /// Update user commands from DbBlockUpdate
     fn update_user_commands(&self, block: &DbBlockUpdate) -> anyhow::Result<()>;
 }
+
+impl DelegationChange {
+    /// Delegation changes of the block's applied user commands
+    pub fn from_precomputed(block: &PrecomputedBlock) -> Vec<Self> {
+        let state_hash = block.state_hash();
+        let blockchain_length = block.blockchain_length();
+
+        block
+            .commands()
+            .into_iter()
+            .zip(block.command_hashes())
+            .enumerate()
+            .filter(|(_, (command, _))| command.is_applied())
+            .flat_map(|(index, (command, txn_hash))| {
+                AccountDiff::expand(AccountDiff::from_command(command.to_command()))
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|diff| match diff {
+                        AccountDiff::Delegation(diff) => Some(Self {
+                            delegator: diff.delegator,
+                            delegate: diff.delegate,
+                            nonce: diff.nonce,
+                            blockchain_length,
+                            state_hash: state_hash.clone(),
+                            index_in_block: index as u32,
+                            txn_hash: txn_hash.clone(),
+                        }),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
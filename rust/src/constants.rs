@@ -13,9 +13,24 @@ pub const VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "-", env!("GIT_COMM
 pub const BLOCK_REPORTING_FREQ_NUM: u32 = 1000;
 pub const BLOCK_REPORTING_FREQ_SEC: u64 = 180;
 pub const LEDGER_CADENCE: u32 = 100;
+pub const INGESTION_QUEUE_DEPTH: u32 = 64;
+pub const INGESTION_QUEUE_BYTES: u64 = 256 * 1024 * 1024;
+pub const BLOCKS_DIR_MAX_DEPTH: u32 = 4;
+pub const BLOCK_PARSER_PARSE_AHEAD: usize = 16;
 pub const CANONICAL_UPDATE_THRESHOLD: u32 = PRUNE_INTERVAL_DEFAULT / 5;
 pub const MAINNET_CANONICAL_THRESHOLD: u32 = 10;
 pub const PRUNE_INTERVAL_DEFAULT: u32 = 10;
+pub const SUMMARY_MISSING_BLOCKS: usize = 10;
+pub const MISSING_BLOCK_RECOVERY_LIMIT: usize = 100;
+
+// maintenance constants
+
+pub const MAINTENANCE_LATENCY_BUDGET_MS: u64 = 50;
+pub const MAINTENANCE_YIELD_MS: u64 = 100;
+pub const MAINTENANCE_IDLE_POLL_MS: u64 = 1000;
+pub const MAINTENANCE_MAX_BATCH_SIZE: u32 = 10_000;
+pub const MAINTENANCE_INITIAL_BATCH_SIZE: u32 = 100;
+pub const EVENT_LOG_COMPACTION_MINUTE_OF_DAY: u32 = 3 * 60;
 
 // mina constants
 
@@ -71,6 +86,11 @@ pub const HARDFORK_GENESIS_BLOCKCHAIN_LENGTH: u32 = 359605;
 pub const HARDFORK_GENESIS_GLOBAL_SLOT: u32 = 564480;
 pub const HARDFORK_GENESIS_HASH: &str = "3NK4BpDSekaqsG6tx8Nse2zJchRft2JpnbvMiog55WCr5xJZaKeP";
 pub const HARDFORK_GENESIS_TIMESTAMP: u64 = 1717545600000;
+
+/// Flattened epoch number of the first post hardfork epoch, i.e. the
+/// hardfork happened during the last pre hardfork epoch
+pub const HARDFORK_GENESIS_EPOCH_OFFSET: u32 =
+    HARDFORK_GENESIS_GLOBAL_SLOT / MAINNET_EPOCH_SLOT_COUNT + 1;
 pub const HARDFORK_GENESIS_PREV_STATE_HASH: &str =
     "3NLRTfY4kZyJtvaP4dFenDcxfoMfT3uEpkWS913KkeXLtziyVd15";
 pub const HARDFORK_GENESIS_LEDGER_HASH: &str =
@@ -118,6 +138,11 @@ pub fn millis_to_global_slot(millis: i64) -> u32 {
     (millis_since_genesis / MAINNET_BLOCK_SLOT_TIME_MILLIS) as u32
 }
 
+/// Convert global slot number to the slot's nominal start in epoch milliseconds
+pub fn global_slot_to_millis(global_slot: u32) -> u64 {
+    MAINNET_GENESIS_TIMESTAMP + global_slot as u64 * MAINNET_BLOCK_SLOT_TIME_MILLIS
+}
+
 pub mod berkeley {
     pub const BERKELEY_GENESIS_STATE_HASH: &str =
         "3NK512ryRJvj1TUKGgPoGZeHSNbn37e9BbnpyeqHL9tvKLeD8yrY";
@@ -126,3 +151,67 @@ pub mod berkeley {
 
 pub const DEFAULT_WEB_HOSTNAME: &str = "localhost";
 pub const DEFAULT_WEB_PORT: u16 = 8080;
+
+/// Max size (bytes) of a precomputed block posted to the HTTP ingestion
+/// endpoint
+pub const INGEST_HTTP_MAX_BLOCK_BYTES: usize = 64 * 1024 * 1024;
+
+/// Requests allowed per client IP per minute in public API mode
+pub const PUBLIC_API_RATE_LIMIT_PER_MIN: u32 = 600;
+
+/// Requests a client IP can make in a burst in public API mode
+pub const PUBLIC_API_RATE_LIMIT_BURST: u32 = 60;
+
+/// Max size (bytes) of a request body in public API mode
+pub const PUBLIC_API_MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+/// Max GraphQL query depth in public API mode
+pub const PUBLIC_API_MAX_QUERY_DEPTH: usize = 16;
+
+/// Max GraphQL query complexity in public API mode
+pub const PUBLIC_API_MAX_QUERY_COMPLEXITY: usize = 1000;
+
+/// Precomputed block URL template of o1Labs' public block archive bucket
+pub const BLOCK_ARCHIVE_URL_TEMPLATE: &str =
+    "https://storage.googleapis.com/mina_network_block_data/{network}-{height}-{state_hash}.json";
+
+/// Object listing URL template of o1Labs' public block archive bucket, used to
+/// find the state hashes of blocks at a height
+pub const BLOCK_ARCHIVE_LIST_URL_TEMPLATE: &str =
+    "https://storage.googleapis.com/storage/v1/b/mina_network_block_data/o?prefix={network}-{height}-";
+
+/// Delay (sec) in between block archive backfill attempts
+pub const BLOCK_BACKFILL_DELAY_SECS: u64 = 180;
+
+/// Max number of missing heights backfilled per attempt
+pub const BLOCK_BACKFILL_MAX_HEIGHTS: usize = 100;
+
+/// Number of most recent events validated after an unclean shutdown
+pub const UNCLEAN_SHUTDOWN_EVENT_TAIL: u32 = 1000;
+
+/// Max number of staking ledger sort index entries scanned by a stakes query
+pub const STAKES_QUERY_MAX_SCAN: usize = 50_000;
+
+/// Number of staking ledger accounts written per write batch
+pub const STAKING_LEDGER_BATCH_SIZE: usize = 10_000;
+
+/// Max size (bytes) of a raw block JSON selection returned via GraphQL
+pub const RAW_BLOCK_JSON_MAX_BYTES: usize = 64 * 1024;
+
+/// Delay (sec) in between read-only replica catch-ups with the primary
+pub const REPLICA_CATCH_UP_INTERVAL_SECS: u64 = 5;
+
+/// Max number of blocks a read-only replica can lag its primary & be ready
+pub const REPLICA_MAX_LAG_BLOCKS: u32 = 10;
+
+/// Delay (ms) in between event log polls of `events --follow`
+pub const EVENTS_FOLLOW_POLL_MS: u64 = 1000;
+
+/// Max number of events returned per event log page
+pub const EVENTS_MAX_PAGE_SIZE: usize = 1000;
+
+/// Max number of blocks or delegators returned per gRPC list response
+pub const GRPC_MAX_PAGE_SIZE: usize = 1000;
+
+/// Min number of account diffs in a block to apply them in parallel
+pub const PARALLEL_DIFFS_MIN_ACCOUNT_DIFFS: usize = 256;
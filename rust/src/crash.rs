@@ -0,0 +1,255 @@
+//! Crash context report
+//!
+//! Tracks the block currently being ingested & the most recent log lines so
+//! an unexpected panic can be written to a crash-context file. Panics caught
+//! by [catch_unwind], e.g. while applying a block, aren't crashes & don't
+//! write one, they only stop ingestion, see [IndexerState::add_blocks]
+//!
+//! [IndexerState::add_blocks]: crate::state::IndexerState::add_blocks
+
+use crate::{base::state_hash::StateHash, ingestion::IngestionPhase};
+use log::{Log, Metadata, Record};
+use std::{
+    any::Any,
+    cell::Cell,
+    collections::VecDeque,
+    io::Write,
+    panic::UnwindSafe,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Number of recent log lines kept for the crash-context file
+pub const CRASH_LOG_LINES: usize = 50;
+
+/// Crash-context file name prefix, suffixed by the crash time in millis
+pub const CRASH_CONTEXT_PREFIX: &str = "crash-context";
+
+static CURRENT_BLOCK: Mutex<Option<(StateHash, u32)>> = Mutex::new(None);
+static CURRENT_PHASE: Mutex<Option<IngestionPhase>> = Mutex::new(None);
+static LOG_LINES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+thread_local! {
+    /// Number of nested [catch_unwind] calls on this thread
+    static CATCHING: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Snapshot of the ingestion state at the time of a crash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashContext {
+    pub state_hash: Option<StateHash>,
+    pub blockchain_length: Option<u32>,
+    pub phase: Option<IngestionPhase>,
+    pub log_lines: Vec<String>,
+}
+
+/// Wraps a logger to record the last [CRASH_LOG_LINES] log lines
+pub struct CrashContextLogger<L: Log> {
+    logger: L,
+}
+
+/// Poisoned locks still hold valid context, we may be panicking
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Sets the block currently being ingested
+pub fn set_current_block(state_hash: &StateHash, blockchain_length: u32) {
+    *lock(&CURRENT_BLOCK) = Some((state_hash.clone(), blockchain_length));
+}
+
+/// Sets the current ingestion phase
+pub fn set_phase(phase: IngestionPhase) {
+    *lock(&CURRENT_PHASE) = Some(phase);
+}
+
+/// Clears the current block & phase
+pub fn clear() {
+    *lock(&CURRENT_BLOCK) = None;
+    *lock(&CURRENT_PHASE) = None;
+}
+
+/// Records a log line, dropping the oldest beyond [CRASH_LOG_LINES]
+pub fn record_log_line(line: String) {
+    let mut log_lines = lock(&LOG_LINES);
+    if log_lines.len() >= CRASH_LOG_LINES {
+        log_lines.pop_front();
+    }
+    log_lines.push_back(line);
+}
+
+/// Current crash context
+pub fn crash_context() -> CrashContext {
+    let (state_hash, blockchain_length) = lock(&CURRENT_BLOCK).clone().unzip();
+    CrashContext {
+        state_hash,
+        blockchain_length,
+        phase: *lock(&CURRENT_PHASE),
+        log_lines: lock(&LOG_LINES).iter().cloned().collect(),
+    }
+}
+
+/// Installs a panic hook which writes the crash context to a file in `dir`,
+/// unless the panic is caught by [catch_unwind], then runs the previous hook
+pub fn install_panic_hook(dir: &Path) {
+    let dir = dir.to_path_buf();
+    let prev_hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        if !is_catching() {
+            match crash_context().write(&dir, &info.to_string()) {
+                Ok(path) => eprintln!("Crash context written to {}", path.display()),
+                Err(e) => eprintln!("Unable to write crash context to {}: {e}", dir.display()),
+            }
+        }
+
+        prev_hook(info);
+    }));
+}
+
+/// [std::panic::catch_unwind] whose panics don't write a crash-context file
+pub fn catch_unwind<F: FnOnce() -> R + UnwindSafe, R>(f: F) -> std::thread::Result<R> {
+    CATCHING.with(|catching| catching.set(catching.get() + 1));
+    let result = std::panic::catch_unwind(f);
+    CATCHING.with(|catching| catching.set(catching.get() - 1));
+    result
+}
+
+/// Whether a panic on this thread would be caught by [catch_unwind]
+fn is_catching() -> bool {
+    CATCHING.with(|catching| catching.get() > 0)
+}
+
+/// Message of a caught panic
+pub fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(msg) => format!("panicked: {msg}"),
+        Err(panic) => match panic.downcast::<&str>() {
+            Ok(msg) => format!("panicked: {msg}"),
+            Err(_) => "panicked".to_string(),
+        },
+    }
+}
+
+impl CrashContext {
+    /// Writes the crash context to a new file in `dir`, returning its path
+    pub fn write(&self, dir: &Path, panic_msg: &str) -> std::io::Result<PathBuf> {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let path = dir.join(format!("{CRASH_CONTEXT_PREFIX}-{millis}.txt"));
+
+        std::fs::create_dir_all(dir)?;
+        let mut file = std::fs::File::create(&path)?;
+        file.write_all(self.report(panic_msg).as_bytes())?;
+        Ok(path)
+    }
+
+    fn report(&self, panic_msg: &str) -> String {
+        let unknown = || "unknown".to_string();
+        let mut report = format!(
+            "mina-indexer {}\n\
+             panic: {panic_msg}\n\
+             state hash: {}\n\
+             blockchain length: {}\n\
+             phase: {}\n\n\
+             last {} log lines:\n",
+            env!("CARGO_PKG_VERSION"),
+            self.state_hash
+                .as_ref()
+                .map_or_else(unknown, ToString::to_string),
+            self.blockchain_length
+                .map_or_else(unknown, |length| length.to_string()),
+            self.phase.map_or_else(unknown, |phase| phase.to_string()),
+            self.log_lines.len(),
+        );
+
+        for line in self.log_lines.iter() {
+            report.push_str(line);
+            report.push('\n');
+        }
+        report
+    }
+}
+
+impl<L: Log> CrashContextLogger<L> {
+    pub fn new(logger: L) -> Self {
+        Self { logger }
+    }
+}
+
+impl<L: Log> Log for CrashContextLogger<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.logger.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            record_log_line(format!(
+                "{} {} {}",
+                record.level(),
+                record.target(),
+                record.args()
+            ));
+        }
+        self.logger.log(record);
+    }
+
+    fn flush(&self) {
+        self.logger.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_crash_context() -> anyhow::Result<()> {
+        let dir = tempfile::TempDir::with_prefix("crash-context")?;
+        let state_hash: StateHash = "3NKeMoncuHab5ScarV5ViyF16cJPT4taWNSaTLS64Dp67wuXigPZ".into();
+
+        set_current_block(&state_hash, 2);
+        set_phase(IngestionPhase::Apply);
+        for n in 0..CRASH_LOG_LINES + 10 {
+            record_log_line(format!("line {n}"));
+        }
+
+        let context = crash_context();
+        assert_eq!(context.state_hash, Some(state_hash.clone()));
+        assert_eq!(context.blockchain_length, Some(2));
+        assert_eq!(context.phase, Some(IngestionPhase::Apply));
+
+        // only the last lines are kept
+        assert_eq!(context.log_lines.len(), CRASH_LOG_LINES);
+        assert_eq!(context.log_lines[0], "line 10");
+
+        let path = context.write(dir.path(), "boom")?;
+        let report = std::fs::read_to_string(path)?;
+        assert!(report.contains("panic: boom"));
+        assert!(report.contains(&format!("state hash: {state_hash}")));
+        assert!(report.contains("blockchain length: 2"));
+        assert!(report.contains("phase: apply"));
+        assert!(report.ends_with(&format!("line {}\n", CRASH_LOG_LINES + 9)));
+
+        clear();
+        assert_eq!(crash_context().state_hash, None);
+        Ok(())
+    }
+
+    #[test]
+    fn caught_panics() {
+        assert!(!is_catching());
+
+        let panic = catch_unwind(|| {
+            assert!(is_catching());
+            panic!("boom")
+        })
+        .unwrap_err();
+
+        assert!(!is_catching());
+        assert_eq!(panic_message(panic), "panicked: boom");
+    }
+}
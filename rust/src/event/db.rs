@@ -1,4 +1,6 @@
-use crate::{base::state_hash::StateHash, ledger::LedgerHash};
+use crate::{
+    base::state_hash::StateHash, ledger::LedgerHash, store::constants_snapshot::Reindexable,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
@@ -7,6 +9,7 @@ pub enum DbEvent {
     Canonicity(DbCanonicityEvent),
     Ledger(DbLedgerEvent),
     StakingLedger(DbStakingLedgerEvent),
+    Constants(DbConstantsEvent),
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
@@ -19,6 +22,25 @@ pub enum DbBlockEvent {
         state_hash: StateHash,
         blockchain_length: u32,
     },
+
+    /// The best tip switched branches
+    Reorg(ReorgEvent),
+}
+
+/// Best tip branch switch
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReorgEvent {
+    pub old_tip: StateHash,
+    pub old_tip_height: u32,
+    pub new_tip: StateHash,
+    pub new_tip_height: u32,
+
+    /// Last block shared by the old & new best chains
+    pub common_ancestor: StateHash,
+    pub common_ancestor_height: u32,
+
+    /// Old best chain blocks above the common ancestor, highest first
+    pub orphaned_blocks: Vec<StateHash>,
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
@@ -41,6 +63,12 @@ pub enum DbStakingLedgerEvent {
         epoch: u32,
         genesis_state_hash: StateHash,
     },
+    ReplaceStakingLedger {
+        epoch: u32,
+        old_ledger_hash: LedgerHash,
+        ledger_hash: LedgerHash,
+        genesis_state_hash: StateHash,
+    },
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
@@ -49,6 +77,30 @@ pub enum DbCanonicityEvent {
         state_hash: StateHash,
         blockchain_length: u32,
     },
+
+    /// Canonicity of the heights was verified against the best tip's
+    /// ancestors
+    CanonicityVerified {
+        from_height: u32,
+        to_height: u32,
+        num_mismatches: u32,
+    },
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum DbConstantsEvent {
+    /// Derivation-affecting constants changed & the change was accepted
+    ConstantsChanged {
+        changed: Vec<String>,
+        reindex: Vec<Reindexable>,
+    },
+}
+
+impl ReorgEvent {
+    /// Number of orphaned blocks
+    pub fn depth(&self) -> u32 {
+        self.orphaned_blocks.len() as u32
+    }
 }
 
 impl DbEvent {
@@ -64,6 +116,7 @@ impl std::fmt::Debug for DbEvent {
             Self::Canonicity(db_canonicity_event) => write!(f, "{:?}", db_canonicity_event),
             Self::Ledger(db_ledger_event) => write!(f, "{:?}", db_ledger_event),
             Self::StakingLedger(db_ledger_event) => write!(f, "{:?}", db_ledger_event),
+            Self::Constants(db_constants_event) => write!(f, "{:?}", db_constants_event),
         }
     }
 }
@@ -89,6 +142,15 @@ impl std::fmt::Debug for DbBlockEvent {
                     blockchain_length, state_hash
                 )
             }
+            Self::Reorg(reorg) => write!(
+                f,
+                "db reorg (depth {}): {} -> {} (common ancestor (length {}): {})",
+                reorg.depth(),
+                reorg.old_tip,
+                reorg.new_tip,
+                reorg.common_ancestor_height,
+                reorg.common_ancestor
+            ),
         }
     }
 }
@@ -104,6 +166,15 @@ impl std::fmt::Debug for DbCanonicityEvent {
                 "db new canonical block (length {}): {}",
                 blockchain_length, state_hash
             ),
+            Self::CanonicityVerified {
+                from_height,
+                to_height,
+                num_mismatches,
+            } => write!(
+                f,
+                "db canonicity verified (lengths {}..={}): {} mismatches",
+                from_height, to_height, num_mismatches
+            ),
         }
     }
 }
@@ -142,6 +213,33 @@ impl std::fmt::Debug for DbStakingLedgerEvent {
             } => {
                 write!(f, "db aggregated delegations epoch {}", epoch)
             }
+            Self::ReplaceStakingLedger {
+                epoch,
+                old_ledger_hash,
+                ledger_hash,
+                genesis_state_hash: _,
+            } => write!(
+                f,
+                "db replaced staking ledger (epoch {}): {} -> {}",
+                epoch, old_ledger_hash, ledger_hash
+            ),
+        }
+    }
+}
+
+impl std::fmt::Debug for DbConstantsEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ConstantsChanged { changed, reindex } => write!(
+                f,
+                "db constants changed {:?}, reindex: {}",
+                changed,
+                reindex
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
         }
     }
 }
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 pub mod block;
 pub mod db;
 pub mod ledger;
+pub mod page;
 pub mod store;
 pub mod witness_tree;
 
@@ -32,6 +33,7 @@ impl IndexerEvent {
     pub const NEW_BLOCK_KIND: u8 = 0;
     pub const NEW_BEST_TIP_KIND: u8 = 1;
     pub const NEW_CANONICAL_BLOCK_KIND: u8 = 2;
+    pub const REORG_KIND: u8 = 4;
 
     pub fn kind(&self) -> u8 {
         use db::*;
@@ -41,6 +43,7 @@ impl IndexerEvent {
             Self::Db(DbEvent::Canonicity(DbCanonicityEvent::NewCanonicalBlock { .. })) => {
                 Self::NEW_CANONICAL_BLOCK_KIND
             }
+            Self::Db(DbEvent::Block(DbBlockEvent::Reorg(_))) => Self::REORG_KIND,
             _ => 3,
         }
     }
@@ -0,0 +1,189 @@
+//! Event log pages
+//!
+//! Decodes paged, kind-filtered ranges of the event log for operators &
+//! external consumers. Events written by other versions which fail to decode
+//! are surfaced as their raw JSON.
+
+use super::{store::EventStore, IndexerEvent};
+use crate::{store::IndexerStore, utility::store::common::u32_from_be_bytes};
+use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use speedb::{Direction, IteratorMode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Encode, Decode, Serialize, Deserialize)]
+pub enum EventKind {
+    NewBlock,
+    NewBestTip,
+    NewCanonical,
+    Ledger,
+    Staking,
+    Reorg,
+
+    /// Any other event, e.g. constants changes
+    Other,
+}
+
+/// Event log entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    pub seq_num: u32,
+
+    /// Blockchain length prefix of block & reorg events, otherwise 0
+    pub height: u32,
+
+    pub kind: EventKind,
+
+    /// Decoded event description, `None` if the event fails to decode
+    pub description: Option<String>,
+
+    /// Event as written
+    pub json: Value,
+}
+
+/// Page of the event log
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EventPage {
+    pub events: Vec<LoggedEvent>,
+
+    /// Sequence number to request the next page from
+    pub next_seq_num: u32,
+}
+
+impl EventKind {
+    /// Kind of the event's JSON, tolerating unknown variants
+    pub fn of_json(json: &Value) -> Self {
+        let db_event = &json["Db"];
+        if db_event["Block"].get("NewBlock").is_some() {
+            Self::NewBlock
+        } else if db_event["Block"].get("NewBestTip").is_some() {
+            Self::NewBestTip
+        } else if db_event["Block"].get("Reorg").is_some() {
+            Self::Reorg
+        } else if db_event["Canonicity"].get("NewCanonicalBlock").is_some() {
+            Self::NewCanonical
+        } else if db_event.get("Ledger").is_some() {
+            Self::Ledger
+        } else if db_event.get("StakingLedger").is_some() {
+            Self::Staking
+        } else {
+            Self::Other
+        }
+    }
+}
+
+impl LoggedEvent {
+    /// Decodes an event log entry (`None` if the value isn't an event)
+    pub fn decode(key: &[u8], value: &[u8]) -> anyhow::Result<Option<Self>> {
+        if value.len() < 5 {
+            return Ok(None);
+        }
+
+        let Ok(json) = serde_json::from_slice::<Value>(&value[5..]) else {
+            return Ok(None);
+        };
+
+        let description = serde_json::from_value::<IndexerEvent>(json.clone())
+            .ok()
+            .map(|event| format!("{event:?}"));
+
+        Ok(Some(Self {
+            seq_num: u32_from_be_bytes(key)?,
+            height: u32_from_be_bytes(&value[..4])?,
+            kind: EventKind::of_json(&json),
+            description,
+            json,
+        }))
+    }
+}
+
+impl IndexerStore {
+    /// Up to `limit` events of the given kinds (all if empty), from sequence
+    /// number `from_seq_num` in sequence order
+    pub fn get_event_page(
+        &self,
+        kinds: &[EventKind],
+        from_seq_num: u32,
+        limit: usize,
+    ) -> anyhow::Result<EventPage> {
+        let mut page = EventPage {
+            events: vec![],
+            next_seq_num: from_seq_num,
+        };
+
+        let start = from_seq_num.to_be_bytes();
+        for (key, value) in self
+            .event_log_iterator(IteratorMode::From(&start, Direction::Forward))
+            .flatten()
+        {
+            if page.events.len() >= limit {
+                break;
+            }
+
+            page.next_seq_num = u32_from_be_bytes(&key)? + 1;
+            if let Some(event) = LoggedEvent::decode(&key, &value)? {
+                if kinds.is_empty() || kinds.contains(&event.kind) {
+                    page.events.push(event);
+                }
+            }
+        }
+
+        Ok(page)
+    }
+}
+
+impl std::str::FromStr for EventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().replace('-', "_").as_str() {
+            "NEW_BLOCK" => Ok(Self::NewBlock),
+            "NEW_BEST_TIP" => Ok(Self::NewBestTip),
+            "NEW_CANONICAL" => Ok(Self::NewCanonical),
+            "LEDGER" => Ok(Self::Ledger),
+            "STAKING" => Ok(Self::Staking),
+            "REORG" => Ok(Self::Reorg),
+            "OTHER" => Ok(Self::Other),
+            _ => Err(format!("Invalid event kind: {s}")),
+        }
+    }
+}
+
+impl std::fmt::Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            Self::NewBlock => "NEW_BLOCK",
+            Self::NewBestTip => "NEW_BEST_TIP",
+            Self::NewCanonical => "NEW_CANONICAL",
+            Self::Ledger => "LEDGER",
+            Self::Staking => "STAKING",
+            Self::Reorg => "REORG",
+            Self::Other => "OTHER",
+        };
+        write!(f, "{kind}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn decode_unknown_variant() -> anyhow::Result<()> {
+        let mut value = 42u32.to_be_bytes().to_vec();
+        value.push(3);
+        value.append(&mut serde_json::to_vec(&json!({
+            "Db": { "Ledger": { "FutureLedgerEvent": { "epoch": 1 } } }
+        }))?);
+
+        let event = LoggedEvent::decode(&7u32.to_be_bytes(), &value)?.unwrap();
+        assert_eq!(event.seq_num, 7);
+        assert_eq!(event.height, 42);
+        assert_eq!(event.kind, EventKind::Ledger);
+        assert_eq!(event.description, None);
+        assert_eq!(event.json["Db"]["Ledger"]["FutureLedgerEvent"]["epoch"], 1);
+
+        Ok(())
+    }
+}
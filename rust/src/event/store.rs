@@ -1,5 +1,9 @@
-use crate::event::IndexerEvent;
-use speedb::{DBIterator, IteratorMode};
+use crate::{
+    base::state_hash::StateHash,
+    event::{db::ReorgEvent, IndexerEvent},
+};
+use speedb::{DBIterator, IteratorMode, WriteBatch};
+use std::collections::HashSet;
 
 pub trait EventStore {
     /// Add event to db and return the next sequence number
@@ -7,6 +11,24 @@ pub trait EventStore {
     /// `WitnessTree` events are not recorded
     fn add_event(&self, event: &IndexerEvent) -> anyhow::Result<u32>;
 
+    /// Add events to the batch and return the next sequence number, so they
+    /// are recorded atomically with the batch's other writes
+    ///
+    /// `WitnessTree` events are not recorded
+    fn add_events_batch(
+        &self,
+        events: &[IndexerEvent],
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<u32>;
+
+    /// Remove the `NewBlock` & `NewBestTip` events of the given blocks from
+    /// the log, returning the number of events removed
+    fn remove_block_events_batch(
+        &self,
+        state_hashes: &HashSet<StateHash>,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<u32>;
+
     /// Get the event from the log
     fn get_event(&self, seq_num: u32) -> anyhow::Result<Option<IndexerEvent>>;
 
@@ -16,6 +38,13 @@ pub trait EventStore {
     /// Returns the event log
     fn get_event_log(&self) -> anyhow::Result<Vec<IndexerEvent>>;
 
+    /// Compact the event log between the sequence numbers `from` (inclusive)
+    /// & `to` (exclusive)
+    fn compact_event_log(&self, from: u32, to: u32);
+
+    /// Get the `limit` most recent reorgs, most recent first
+    fn get_reorgs(&self, limit: usize) -> anyhow::Result<Vec<ReorgEvent>>;
+
     /// Returns the event log iterator
     fn event_log_iterator(&self, mode: IteratorMode) -> DBIterator<'_>;
 }
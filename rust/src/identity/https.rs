@@ -0,0 +1,168 @@
+//! External identity provider
+//!
+//! URL templates substitute `{username}` & `{public_key}`. Resolutions are
+//! JSON objects, `{"publicKeys": ["B62q..."]}` & `{"username": "..."}`, & a
+//! `404` means the provider doesn't know the username or public key. A
+//! `file://` template reads from a local directory, e.g. for testing, HTTPS
+//! templates require the `http_client` feature.
+
+use super::IdentityProvider;
+use crate::{
+    base::public_key::PublicKey,
+    ledger::username::Username,
+    utility::http::{fetch_optional, HttpClient, HTTP_CLIENT_ENABLED},
+};
+use anyhow::bail;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HttpsIdentityConfig {
+    /// Username resolution URL template
+    pub resolve_url: String,
+
+    /// Public key lookup URL template
+    pub lookup_url: String,
+}
+
+pub struct HttpsIdentityProvider {
+    config: HttpsIdentityConfig,
+    client: HttpClient,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResolveResponse {
+    #[serde(default)]
+    public_keys: Vec<PublicKey>,
+}
+
+#[derive(Deserialize)]
+struct LookupResponse {
+    username: Option<String>,
+}
+
+impl HttpsIdentityConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for url in [&self.resolve_url, &self.lookup_url] {
+            if !url.starts_with("https://") && !url.starts_with("file://") {
+                bail!("Identity provider URL must be https:// or file://: {url}")
+            }
+
+            if url.starts_with("https://") && !HTTP_CLIENT_ENABLED {
+                bail!("Identity provider URL requires the http_client feature: {url}")
+            }
+        }
+
+        if !self.resolve_url.contains("{username}") {
+            bail!("Identity provider resolve URL must contain {{username}}")
+        }
+
+        if !self.lookup_url.contains("{public_key}") {
+            bail!("Identity provider lookup URL must contain {{public_key}}")
+        }
+        Ok(())
+    }
+}
+
+impl HttpsIdentityProvider {
+    pub fn new(config: HttpsIdentityConfig) -> Self {
+        Self {
+            config,
+            client: HttpClient::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for HttpsIdentityProvider {
+    fn name(&self) -> &'static str {
+        "https"
+    }
+
+    async fn resolve_username(&self, username: &str) -> anyhow::Result<Vec<PublicKey>> {
+        let url = self
+            .config
+            .resolve_url
+            .replace("{username}", &percent_encode(username));
+
+        let Some(body) = fetch_optional(&self.client, &url).await? else {
+            return Ok(vec![]);
+        };
+
+        let response: ResolveResponse = serde_json::from_slice(&body)?;
+        for pk in response.public_keys.iter() {
+            if !PublicKey::is_valid(&pk.0) {
+                bail!("Invalid public key {pk} from {url}")
+            }
+        }
+        Ok(response.public_keys)
+    }
+
+    async fn lookup_username(&self, pk: &PublicKey) -> anyhow::Result<Option<Username>> {
+        let url = self.config.lookup_url.replace("{public_key}", &pk.0);
+        let Some(body) = fetch_optional(&self.client, &url).await? else {
+            return Ok(None);
+        };
+
+        let response: LookupResponse = serde_json::from_slice(&body)?;
+        Ok(response
+            .username
+            .filter(|username| !username.is_empty())
+            .map(Username))
+    }
+}
+
+/// Percent-encodes all but the URL unreserved characters
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_encode_username() {
+        assert_eq!(percent_encode("Mina Foundation"), "Mina%20Foundation");
+        assert_eq!(percent_encode("a/b?c"), "a%2Fb%3Fc");
+        assert_eq!(percent_encode("fée"), "f%C3%A9e");
+    }
+
+    #[test]
+    fn validate_config() {
+        let config = HttpsIdentityConfig {
+            resolve_url: "https://names.example/resolve/{username}".to_string(),
+            lookup_url: "https://names.example/lookup/{public_key}".to_string(),
+        };
+        assert_eq!(config.validate().is_ok(), HTTP_CLIENT_ENABLED);
+
+        let file = HttpsIdentityConfig {
+            resolve_url: "file:///names/resolve/{username}".to_string(),
+            lookup_url: "file:///names/lookup/{public_key}".to_string(),
+        };
+        assert!(file.validate().is_ok());
+
+        let http = HttpsIdentityConfig {
+            resolve_url: "http://names.example/resolve/{username}".to_string(),
+            ..config.clone()
+        };
+        assert!(http.validate().is_err());
+
+        let no_template = HttpsIdentityConfig {
+            lookup_url: "https://names.example/lookup".to_string(),
+            ..config
+        };
+        assert!(no_template.validate().is_err());
+    }
+}
@@ -0,0 +1,157 @@
+//! Username/identity resolution
+//!
+//! Usernames are resolved to public keys & back by a chain of
+//! [IdentityProvider]s, the first provider with an answer wins:
+//! - [OnChainIdentityProvider] - name service memo registrations indexed by
+//!   the [UsernameStore]
+//! - [HttpsIdentityProvider] - an external service, configured via the
+//!   runtime config's `identity.https`
+
+pub mod https;
+
+use crate::{
+    base::public_key::PublicKey, ledger::username::Username, store::username::UsernameStore,
+    store::IndexerStore,
+};
+use anyhow::bail;
+use async_trait::async_trait;
+use https::{HttpsIdentityConfig, HttpsIdentityProvider};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Resolves usernames to public keys & back
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    /// Name of the provider, reported with its resolutions
+    fn name(&self) -> &'static str;
+
+    /// Public keys registered with the username
+    async fn resolve_username(&self, username: &str) -> anyhow::Result<Vec<PublicKey>>;
+
+    /// Username registered by the public key
+    async fn lookup_username(&self, pk: &PublicKey) -> anyhow::Result<Option<Username>>;
+}
+
+/// Identity provider settings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IdentityConfig {
+    /// External provider, consulted after the on-chain registrations
+    pub https: Option<HttpsIdentityConfig>,
+}
+
+/// Username & the public keys registered with it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    pub username: Username,
+    pub public_keys: Vec<PublicKey>,
+
+    /// Name of the resolving provider
+    pub provider: &'static str,
+}
+
+/// Name service memo registrations
+pub struct OnChainIdentityProvider {
+    store: Arc<IndexerStore>,
+}
+
+/// Chain of identity providers
+pub struct IdentityResolver {
+    providers: Vec<Box<dyn IdentityProvider>>,
+}
+
+impl IdentityConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(https) = self.https.as_ref() {
+            https.validate()?;
+        }
+        Ok(())
+    }
+}
+
+impl OnChainIdentityProvider {
+    pub fn new(store: Arc<IndexerStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for OnChainIdentityProvider {
+    fn name(&self) -> &'static str {
+        "on-chain"
+    }
+
+    async fn resolve_username(&self, username: &str) -> anyhow::Result<Vec<PublicKey>> {
+        self.store.get_username_pks(username)
+    }
+
+    async fn lookup_username(&self, pk: &PublicKey) -> anyhow::Result<Option<Username>> {
+        self.store.get_username(pk)
+    }
+}
+
+impl IdentityResolver {
+    pub fn new(providers: Vec<Box<dyn IdentityProvider>>) -> Self {
+        Self { providers }
+    }
+
+    /// On-chain registrations, then the configured providers
+    pub fn from_config(store: Arc<IndexerStore>, config: &IdentityConfig) -> Self {
+        let mut providers: Vec<Box<dyn IdentityProvider>> =
+            vec![Box::new(OnChainIdentityProvider::new(store))];
+        if let Some(https) = config.https.clone() {
+            providers.push(Box::new(HttpsIdentityProvider::new(https)));
+        }
+
+        Self::new(providers)
+    }
+
+    /// Public keys registered with the username by the first provider which
+    /// knows it, failing providers are skipped
+    pub async fn resolve_username(&self, username: &str) -> anyhow::Result<Option<Identity>> {
+        if username.is_empty() || username.len() > Username::MAX_LEN {
+            bail!("Invalid username: {username}")
+        }
+
+        for provider in self.providers.iter() {
+            match provider.resolve_username(username).await {
+                Ok(public_keys) if !public_keys.is_empty() => {
+                    return Ok(Some(Identity {
+                        username: Username(username.to_string()),
+                        public_keys,
+                        provider: provider.name(),
+                    }))
+                }
+                Ok(_) => continue,
+                Err(e) => warn!(
+                    "{} identity provider failed to resolve {username}: {e}",
+                    provider.name()
+                ),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Username registered by the public key with the first provider which
+    /// knows it, failing providers are skipped
+    pub async fn lookup_username(&self, pk: &PublicKey) -> anyhow::Result<Option<Identity>> {
+        for provider in self.providers.iter() {
+            match provider.lookup_username(pk).await {
+                Ok(Some(username)) => {
+                    return Ok(Some(Identity {
+                        username,
+                        public_keys: vec![pk.clone()],
+                        provider: provider.name(),
+                    }))
+                }
+                Ok(None) => continue,
+                Err(e) => warn!(
+                    "{} identity provider failed to look up {pk}: {e}",
+                    provider.name()
+                ),
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,362 @@
+//! Block archive backfill
+//!
+//! Missing precomputed blocks are downloaded from a block archive, by default
+//! o1Labs' public GCS bucket, & fed to the network's indexer like blocks
+//! posted to the HTTP ingestion endpoint. Missing blocks are
+//! - parents of dangling branch roots & stored blocks, their state hashes are
+//!   known
+//! - heights skipped by the blocks dir's block files, their state hashes are
+//!   listed from the archive
+//!
+//! URL templates substitute `{network}`, `{height}` & `{state_hash}`. A
+//! `file://` template reads from a local mirror & its listing globs the
+//! rendered path prefix. HTTP(S) templates require the `backfill` feature, see
+//! [crate::utility::http].
+
+use crate::{
+    base::state_hash::StateHash,
+    block::{extract_network_height_hash, get_block_file_paths, store::BlockStore},
+    chain::Network,
+    constants::*,
+    server::{ingest_block, BlockIngestOutcome},
+    state::IndexerState,
+    utility::{
+        functions::is_valid_file_name,
+        http::{fetch, HttpClient},
+    },
+};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::sync::RwLock;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockBackfillConfig {
+    /// Precomputed block URL template
+    pub url_template: String,
+
+    /// Archive listing URL template, for heights without known state hashes
+    pub list_url_template: String,
+
+    /// Delay (sec) in between backfill attempts
+    pub delay: u64,
+}
+
+/// Block missing from the witness tree, the store, or the blocks dir
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BackfillTarget {
+    pub blockchain_length: u32,
+
+    /// Unknown for heights skipped by the blocks dir
+    pub state_hash: Option<StateHash>,
+}
+
+/// Heights of the network's block files in the blocks dir, scanned once &
+/// updated with the block files written since
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlocksDirHeights {
+    network: Network,
+    heights: BTreeSet<u32>,
+}
+
+/// Outcome of a backfill attempt
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BackfillReport {
+    pub num_fetched: u32,
+    pub num_added: u32,
+    pub failed: Vec<BackfillTarget>,
+}
+
+impl BlockBackfillConfig {
+    pub fn block_url(&self, network: &Network, height: u32, state_hash: &StateHash) -> String {
+        render_template(&self.url_template, network, height).replace("{state_hash}", &state_hash.0)
+    }
+
+    pub fn list_url(&self, network: &Network, height: u32) -> String {
+        render_template(&self.list_url_template, network, height)
+    }
+}
+
+impl std::default::Default for BlockBackfillConfig {
+    fn default() -> Self {
+        Self {
+            url_template: BLOCK_ARCHIVE_URL_TEMPLATE.to_string(),
+            list_url_template: BLOCK_ARCHIVE_LIST_URL_TEMPLATE.to_string(),
+            delay: BLOCK_BACKFILL_DELAY_SECS,
+        }
+    }
+}
+
+fn render_template(template: &str, network: &Network, height: u32) -> String {
+    template
+        .replace("{network}", &network.to_string())
+        .replace("{height}", &height.to_string())
+}
+
+impl BlocksDirHeights {
+    /// Scans the network's block files in `blocks_dir`
+    pub fn scan(blocks_dir: &Path, network: &Network, recursive: bool) -> anyhow::Result<Self> {
+        let mut heights = Self {
+            network: network.clone(),
+            heights: BTreeSet::new(),
+        };
+
+        for path in get_block_file_paths(blocks_dir, recursive)? {
+            heights.insert_file(&path);
+        }
+        Ok(heights)
+    }
+
+    pub fn insert(&mut self, height: u32) {
+        self.heights.insert(height);
+    }
+
+    /// Records the height of the block file, if it's one of the network's
+    pub fn insert_file(&mut self, path: &Path) {
+        if let Ok((network, height, _)) = extract_network_height_hash(path) {
+            if network == self.network {
+                self.insert(height);
+            }
+        }
+    }
+
+    /// Heights in between the lowest & highest block files without a block
+    /// file
+    pub fn gaps(&self) -> Vec<u32> {
+        match (self.heights.first(), self.heights.last()) {
+            (Some(&min), Some(&max)) => (min..max).filter(|h| !self.heights.contains(h)).collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// Missing parents of dangling branch roots & stored blocks, as well as
+/// unstored heights skipped by the blocks dir, lowest first & limited to
+/// [BLOCK_BACKFILL_MAX_HEIGHTS] heights
+pub fn backfill_targets(
+    state: &IndexerState,
+    blocks_dir_heights: Option<&BlocksDirHeights>,
+) -> anyhow::Result<Vec<BackfillTarget>> {
+    let mut targets: BTreeSet<_> = state
+        .dangling_branches
+        .iter()
+        .map(|branch| {
+            let root = branch.root_block();
+            BackfillTarget {
+                blockchain_length: root.blockchain_length.saturating_sub(1),
+                state_hash: Some(root.parent_hash.clone()),
+            }
+        })
+        .collect();
+
+    if let Some(store) = state.indexer_store.as_ref() {
+        for missing in store.get_missing_blocks(MISSING_BLOCK_RECOVERY_LIMIT)? {
+            targets.insert(BackfillTarget {
+                blockchain_length: missing.blockchain_length,
+                state_hash: Some(missing.state_hash),
+            });
+        }
+
+        // heights with known missing blocks aren't listed
+        if let Some(blocks_dir_heights) = blocks_dir_heights {
+            let known_heights: BTreeSet<u32> = targets
+                .iter()
+                .map(|target| target.blockchain_length)
+                .collect();
+
+            for height in blocks_dir_heights.gaps() {
+                if !known_heights.contains(&height) && store.get_num_blocks_at_height(height)? == 0
+                {
+                    targets.insert(BackfillTarget {
+                        blockchain_length: height,
+                        state_hash: None,
+                    });
+                }
+            }
+        }
+    }
+
+    let heights: BTreeSet<u32> = targets
+        .iter()
+        .map(|target| target.blockchain_length)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .take(BLOCK_BACKFILL_MAX_HEIGHTS)
+        .collect();
+    Ok(targets
+        .into_iter()
+        .filter(|target| heights.contains(&target.blockchain_length))
+        .collect())
+}
+
+/// Downloads the missing blocks from the block archive & adds them to the
+/// witness tree, lowest first so parents precede their children
+///
+/// Fetched blocks are also written to `blocks_dir`, if configured, to keep it
+/// complete & their heights recorded in `blocks_dir_heights`
+pub async fn backfill_missing_blocks(
+    state: &Arc<RwLock<IndexerState>>,
+    config: &BlockBackfillConfig,
+    blocks_dir: Option<&Path>,
+    mut blocks_dir_heights: Option<&mut BlocksDirHeights>,
+) -> anyhow::Result<BackfillReport> {
+    let (network, targets) = {
+        let state = state.read().await;
+        let targets = backfill_targets(&state, blocks_dir_heights.as_deref())?;
+        (state.version.network.clone(), targets)
+    };
+
+    let mut report = BackfillReport::default();
+    if targets.is_empty() {
+        return Ok(report);
+    }
+
+    info!(
+        "Backfilling {} missing blocks from the block archive",
+        targets.len()
+    );
+    let client = HttpClient::default();
+
+    for target in targets {
+        let height = target.blockchain_length;
+        let state_hashes = match target.state_hash.clone() {
+            Some(state_hash) => vec![state_hash],
+            None => match list_state_hashes(&client, config, &network, height).await {
+                Ok(state_hashes) if !state_hashes.is_empty() => state_hashes,
+                Ok(_) => {
+                    warn!("No {network} blocks at height {height} in the block archive");
+                    report.failed.push(target);
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Failed to list {network} blocks at height {height}: {e}");
+                    report.failed.push(target);
+                    continue;
+                }
+            },
+        };
+
+        for state_hash in state_hashes {
+            match backfill_block(
+                state,
+                &client,
+                config,
+                &network,
+                height,
+                &state_hash,
+                blocks_dir,
+            )
+            .await
+            {
+                Ok(outcome) => {
+                    if let (Some(_), Some(heights)) = (blocks_dir, blocks_dir_heights.as_mut()) {
+                        heights.insert(height);
+                    }
+
+                    report.num_fetched += 1;
+                    if outcome == BlockIngestOutcome::Added {
+                        report.num_added += 1;
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to backfill {network} block {state_hash} at height {height}: {e}"
+                    );
+                    report.failed.push(BackfillTarget {
+                        blockchain_length: height,
+                        state_hash: Some(state_hash),
+                    });
+                }
+            }
+        }
+    }
+
+    info!(
+        "Backfilled {} blocks ({} added), {} failed",
+        report.num_fetched,
+        report.num_added,
+        report.failed.len()
+    );
+    Ok(report)
+}
+
+async fn backfill_block(
+    state: &Arc<RwLock<IndexerState>>,
+    client: &HttpClient,
+    config: &BlockBackfillConfig,
+    network: &Network,
+    height: u32,
+    state_hash: &StateHash,
+    blocks_dir: Option<&Path>,
+) -> anyhow::Result<BlockIngestOutcome> {
+    let url = config.block_url(network, height, state_hash);
+    debug!("Fetching block {url}");
+
+    let contents = fetch(client, &url).await?;
+    let file_name = PathBuf::from(format!("{network}-{height}-{state_hash}.json"));
+    let block_bytes = contents.len() as u64;
+    let block = IndexerState::parse_contents(state, &file_name, contents.clone()).await?;
+
+    // only valid blocks are written, atomically so the blocks dir watcher
+    // never sees a partial file
+    if let Some(blocks_dir) = blocks_dir {
+        let path = blocks_dir.join(&file_name);
+        let tmp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, &contents).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+    }
+
+    // archived blocks have no meaningful receipt time
+    ingest_block(state, &block, block_bytes, None).await
+}
+
+/// State hashes of the archive's blocks at `height`
+async fn list_state_hashes(
+    client: &HttpClient,
+    config: &BlockBackfillConfig,
+    network: &Network,
+    height: u32,
+) -> anyhow::Result<Vec<StateHash>> {
+    #[derive(Deserialize)]
+    struct Listing {
+        #[serde(default)]
+        items: Vec<ListingItem>,
+    }
+
+    #[derive(Deserialize)]
+    struct ListingItem {
+        name: String,
+    }
+
+    let url = config.list_url(network, height);
+    debug!("Listing blocks {url}");
+
+    let names: Vec<String> = if let Some(prefix) = url.strip_prefix("file://") {
+        glob::glob(&format!("{prefix}*"))?
+            .flatten()
+            .filter_map(|path| path.file_name()?.to_str().map(ToString::to_string))
+            .collect()
+    } else {
+        let listing: Listing = serde_json::from_slice(&fetch(client, &url).await?)?;
+        listing.items.into_iter().map(|item| item.name).collect()
+    };
+
+    let mut state_hashes: Vec<_> = names
+        .iter()
+        .map(Path::new)
+        .filter(|name| is_valid_file_name(name, &StateHash::is_valid))
+        .filter_map(|name| extract_network_height_hash(name).ok())
+        .filter(|(block_network, block_height, _)| {
+            block_network == network && *block_height == height
+        })
+        .map(|(_, _, state_hash)| state_hash)
+        .collect();
+
+    state_hashes.sort();
+    state_hashes.dedup();
+    Ok(state_hashes)
+}
@@ -0,0 +1,117 @@
+//! HTTP block ingestion
+//!
+//! Nodes & block producers POST precomputed block JSON to
+//! `/blocks/{file_name}`, where `file_name` is the block's precomputed block
+//! file name, e.g.
+//! `mainnet-2-3NLyWnjZqUECniE1q719CoLmes6WDQAod4vrTeLfN7XXJbHv6EHH.json`.
+//!
+//! Posted blocks are routed to their network's indexer & feed the same block
+//! pipeline as block files seen by the filesystem watcher, so deployments
+//! don't need to sync a blocks dir.
+
+use crate::{
+    base::state_hash::StateHash,
+    block::{extract_network_height_hash, receipt::BlockReceipt},
+    constants::INGEST_HTTP_MAX_BLOCK_BYTES,
+    maintenance::{Clock, SystemClock},
+    server::{ingest_block, BlockIngestOutcome, NetworkIndexers},
+    state::IndexerState,
+    utility::functions::is_valid_file_name,
+};
+use actix_web::{
+    error::{ErrorBadRequest, ErrorInternalServerError, ErrorNotFound},
+    middleware, post,
+    web::{self, Bytes, Data},
+    App, HttpResponse, HttpServer,
+};
+use log::{debug, error};
+use serde::Serialize;
+use std::{net, path::Path};
+use tokio_graceful_shutdown::{FutureExt, SubsystemHandle};
+
+/// Posted block & its ingestion outcome
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestedBlock {
+    pub state_hash: StateHash,
+    pub outcome: BlockIngestOutcome,
+}
+
+/// Serves the HTTP block ingestion endpoint for the process' network
+/// indexers, until shutdown
+pub async fn start_ingest_http_server<A: net::ToSocketAddrs>(
+    subsys: SubsystemHandle,
+    indexers: NetworkIndexers,
+    addrs: A,
+) -> anyhow::Result<()> {
+    let _ = HttpServer::new(move || {
+        App::new()
+            .app_data(Data::new(indexers.clone()))
+            .app_data(web::PayloadConfig::new(INGEST_HTTP_MAX_BLOCK_BYTES))
+            .service(post_block)
+            .wrap(middleware::Logger::default())
+    })
+    .bind(addrs)?
+    .run()
+    .cancel_on_shutdown(&subsys)
+    .await;
+
+    Ok(())
+}
+
+#[post("/blocks/{file_name}")]
+async fn post_block(
+    indexers: Data<NetworkIndexers>,
+    file_name: web::Path<String>,
+    body: Bytes,
+) -> actix_web::Result<HttpResponse> {
+    let ingested = ingest_posted_block(&indexers, &file_name, body.to_vec(), &SystemClock).await?;
+    Ok(HttpResponse::Ok().json(ingested))
+}
+
+/// Parses the posted precomputed block file contents & adds the block to its
+/// network's witness tree
+pub async fn ingest_posted_block(
+    indexers: &NetworkIndexers,
+    file_name: &str,
+    contents: Vec<u8>,
+    clock: &impl Clock,
+) -> actix_web::Result<IngestedBlock> {
+    let received_at = clock.now_millis();
+    let file_name = Path::new(file_name);
+    debug!("Received precomputed block {}", file_name.display());
+
+    if !is_valid_file_name(file_name, &StateHash::is_valid) {
+        return Err(ErrorBadRequest(format!(
+            "Invalid block file name {}",
+            file_name.display()
+        )));
+    }
+
+    let (network, _, state_hash) =
+        extract_network_height_hash(file_name).map_err(ErrorBadRequest)?;
+    let state = indexers
+        .read()
+        .expect("network indexers lock")
+        .get(&network)
+        .cloned()
+        .ok_or_else(|| ErrorNotFound(format!("Network {network} is not indexed")))?;
+
+    let block_bytes = contents.len() as u64;
+    let block = IndexerState::parse_contents(&state, file_name, contents)
+        .await
+        .map_err(|e| ErrorBadRequest(format!("Error parsing precomputed block: {e}")))?;
+
+    let receipt = BlockReceipt::from_http(received_at);
+    let outcome = ingest_block(&state, &block, block_bytes, Some(receipt))
+        .await
+        .map_err(|e| {
+            error!("Error adding block: {e}");
+            ErrorInternalServerError(format!("Error adding block: {e}"))
+        })?;
+
+    Ok(IngestedBlock {
+        state_hash,
+        outcome,
+    })
+}
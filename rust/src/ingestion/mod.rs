@@ -0,0 +1,423 @@
+//! Durable ingestion queue
+//!
+//! Parsed blocks are journaled in the store with a sequence number before
+//! they are applied to the indexer state. The applier consumes the journal
+//! in order and marks each entry applied. On restart, unapplied entries are
+//! re-parsed from their file paths and re-applied.
+
+pub mod backfill;
+pub mod http;
+pub mod store;
+
+use crate::{
+    base::state_hash::StateHash,
+    block::{
+        parser::{BlockParseError, BlockParser, ParsedBlock},
+        precomputed::{PcbVersion, PrecomputedBlock},
+    },
+    constants::{INGESTION_QUEUE_BYTES, INGESTION_QUEUE_DEPTH},
+    store::IndexerStore,
+};
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc};
+use store::IngestionQueueStore;
+use tokio::{
+    sync::{mpsc, OwnedSemaphorePermit, Semaphore},
+    task::JoinHandle,
+};
+
+/// Journal entry for a parsed block awaiting application
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedBlock {
+    pub path: PathBuf,
+    pub state_hash: StateHash,
+    pub blockchain_length: u32,
+    pub block_bytes: u64,
+    pub version: PcbVersion,
+    pub orphaned: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IngestionQueueConfig {
+    /// Max number of parsed blocks awaiting application
+    pub max_depth: u32,
+
+    /// Max number of parsed block bytes awaiting application
+    pub max_bytes: u64,
+}
+
+/// Bounded queue of journaled blocks between the parser & the applier
+///
+/// A producer task parses & journals blocks ahead of the applier. The
+/// channel bounds the number of blocks awaiting application & the semaphore
+/// their bytes.
+pub struct IngestionQueue {
+    rx: mpsc::Receiver<QueueItem>,
+    producer: JoinHandle<anyhow::Result<BlockParser>>,
+}
+
+/// Item handed from the producer task to the applier
+#[derive(Debug)]
+pub enum QueueItem {
+    /// Journaled block, holds its bytes' permits until it's applied
+    Block {
+        seq_num: u32,
+        entry: QueuedBlock,
+        parsed_block: ParsedBlock,
+        permit: OwnedSemaphorePermit,
+    },
+
+    /// Block file which could not be read or parsed
+    Skipped(SkippedBlock),
+}
+
+/// Blocks rejected or deferred during ingestion
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IngestionReport {
+    pub parent_linkage_errors: Vec<ParentLinkageError>,
+    pub genesis_mismatches: Vec<GenesisMismatch>,
+    pub skipped_blocks: Vec<SkippedBlock>,
+    pub future_blocks_deferred: Vec<FutureBlockDeferred>,
+}
+
+/// Block skipped because of an error, rather than aborting ingestion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedBlock {
+    /// Unknown if the block was read from the store
+    pub path: Option<PathBuf>,
+
+    /// Unknown if the block could not be parsed
+    pub state_hash: Option<StateHash>,
+    pub phase: IngestionPhase,
+    pub error: String,
+}
+
+/// Ingestion step in which a block was skipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IngestionPhase {
+    Parse,
+    Apply,
+    Sync,
+}
+
+/// Block whose claimed parent is inconsistent with the stored parent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParentLinkageError {
+    pub state_hash: StateHash,
+    pub blockchain_length: u32,
+
+    /// Claimed parent state hash
+    pub parent_hash: StateHash,
+
+    /// Stored parent's state hash
+    pub stored_parent_hash: StateHash,
+
+    /// Stored parent's height
+    pub stored_parent_length: u32,
+}
+
+/// Block whose genesis state hash doesn't belong to the store's network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenesisMismatch {
+    pub state_hash: StateHash,
+    pub blockchain_length: u32,
+    pub version: PcbVersion,
+
+    /// Block's genesis state hash
+    pub genesis_state_hash: StateHash,
+
+    /// Genesis state hash of the block's PCB version, if known
+    pub expected_genesis_state_hash: Option<StateHash>,
+}
+
+/// Stored block too far above the best tip to be added to the witness tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FutureBlockDeferred {
+    pub state_hash: StateHash,
+    pub blockchain_length: u32,
+
+    /// Best tip length when the block was deferred
+    pub best_tip_length: u32,
+
+    /// Max number of blocks above the best tip
+    pub horizon: u32,
+}
+
+impl QueuedBlock {
+    pub fn new(path: PathBuf, parsed_block: &ParsedBlock, block_bytes: u64) -> Self {
+        let (block, orphaned) = match parsed_block {
+            ParsedBlock::DeepCanonical(block) | ParsedBlock::Recent(block) => (block, false),
+            ParsedBlock::Orphaned(block) => (block, true),
+        };
+
+        Self {
+            path,
+            block_bytes,
+            orphaned,
+            state_hash: block.state_hash(),
+            blockchain_length: block.blockchain_length(),
+            version: block.version(),
+        }
+    }
+
+    /// Re-parse the journaled block from its file
+    pub fn parse(&self) -> anyhow::Result<ParsedBlock> {
+        let block = PrecomputedBlock::parse_file(&self.path, self.version.clone())?;
+
+        if block.state_hash() != self.state_hash {
+            anyhow::bail!(
+                "Journaled block {} does not match {}",
+                self.state_hash,
+                self.path.display()
+            )
+        }
+
+        Ok(if self.orphaned {
+            ParsedBlock::Orphaned(block)
+        } else {
+            ParsedBlock::Recent(block)
+        })
+    }
+}
+
+impl IngestionQueueConfig {
+    pub fn default_max_depth() -> u32 {
+        INGESTION_QUEUE_DEPTH
+    }
+
+    pub fn default_max_bytes() -> u64 {
+        INGESTION_QUEUE_BYTES
+    }
+}
+
+impl IngestionQueue {
+    /// Spawns the producer task which parses & journals the parser's blocks
+    pub fn spawn(
+        config: IngestionQueueConfig,
+        mut block_parser: BlockParser,
+        indexer_store: Option<Arc<IndexerStore>>,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel(config.max_depth.max(1) as usize);
+        let max_bytes = config.max_bytes.clamp(1, u32::MAX as u64) as u32;
+        let bytes = Arc::new(Semaphore::new(max_bytes as usize));
+
+        let producer = tokio::spawn(async move {
+            loop {
+                let item = match block_parser.next_block_with_path().await {
+                    Ok(Some((parsed_block, block_bytes, path))) => {
+                        // wait for room in the queue before journaling, oversized
+                        // blocks take all the permits so they still make progress
+                        let permits = block_bytes.clamp(1, max_bytes as u64) as u32;
+                        let permit = bytes.clone().acquire_many_owned(permits).await?;
+                        let Ok(slot) = tx.reserve().await else {
+                            // the applier stopped, unapplied blocks are
+                            // recovered on restart
+                            break;
+                        };
+
+                        let entry = QueuedBlock::new(path, &parsed_block, block_bytes);
+                        let seq_num = match indexer_store.as_ref() {
+                            Some(indexer_store) => indexer_store.enqueue_block(&entry)?,
+                            // nothing to recover without a store
+                            None => 0,
+                        };
+
+                        slot.send(QueueItem::Block {
+                            seq_num,
+                            entry,
+                            parsed_block,
+                            permit,
+                        });
+                        continue;
+                    }
+                    Ok(None) => break,
+                    Err(e) => match e.downcast::<BlockParseError>() {
+                        // the parser has moved past the bad file
+                        Ok(parse_error) => QueueItem::Skipped(SkippedBlock {
+                            path: Some(parse_error.path),
+                            state_hash: None,
+                            phase: IngestionPhase::Parse,
+                            error: format!("{:#}", parse_error.error),
+                        }),
+                        Err(e) => {
+                            error!("Block ingestion error: {e}");
+                            break;
+                        }
+                    },
+                };
+
+                if tx.send(item).await.is_err() {
+                    break;
+                }
+            }
+
+            Ok::<_, anyhow::Error>(block_parser)
+        });
+
+        Self { rx, producer }
+    }
+
+    /// Number of blocks awaiting application
+    pub fn depth(&self) -> u32 {
+        self.rx.len() as u32
+    }
+
+    /// Receive the next item in sequence order, [None] once the producer is
+    /// done
+    pub async fn recv(&mut self) -> Option<QueueItem> {
+        self.rx.recv().await
+    }
+
+    /// Wait for the producer task & get the block parser back
+    pub async fn finish(self) -> anyhow::Result<BlockParser> {
+        drop(self.rx);
+        self.producer.await?
+    }
+}
+
+impl IngestionReport {
+    pub fn is_empty(&self) -> bool {
+        self.parent_linkage_errors.is_empty()
+            && self.genesis_mismatches.is_empty()
+            && self.skipped_blocks.is_empty()
+            && self.future_blocks_deferred.is_empty()
+    }
+}
+
+impl std::fmt::Display for IngestionPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse => write!(f, "parse"),
+            Self::Apply => write!(f, "apply"),
+            Self::Sync => write!(f, "sync"),
+        }
+    }
+}
+
+impl std::fmt::Display for SkippedBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Skipped block")?;
+        if let Some(state_hash) = self.state_hash.as_ref() {
+            write!(f, " {state_hash}")?;
+        }
+        if let Some(path) = self.path.as_ref() {
+            write!(f, " ({})", path.display())?;
+        }
+        write!(f, " during {}: {}", self.phase, self.error)
+    }
+}
+
+impl std::fmt::Display for ParentLinkageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Block {} (length {}) claims parent {}, stored parent is {} (length {})",
+            self.state_hash,
+            self.blockchain_length,
+            self.parent_hash,
+            self.stored_parent_hash,
+            self.stored_parent_length,
+        )
+    }
+}
+
+impl std::error::Error for ParentLinkageError {}
+
+impl std::fmt::Display for GenesisMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Block {} (length {}) has {} genesis state hash {}",
+            self.state_hash, self.blockchain_length, self.version, self.genesis_state_hash,
+        )?;
+        match self.expected_genesis_state_hash.as_ref() {
+            Some(expected) => write!(f, ", expected {expected}"),
+            None => write!(f, ", no {} genesis is known", self.version),
+        }
+    }
+}
+
+impl std::error::Error for GenesisMismatch {}
+
+impl std::fmt::Display for FutureBlockDeferred {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Block {} (length {}) is more than {} blocks above the best tip (length {})",
+            self.state_hash, self.blockchain_length, self.horizon, self.best_tip_length,
+        )
+    }
+}
+
+impl std::default::Default for IngestionQueueConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: Self::default_max_depth(),
+            max_bytes: Self::default_max_bytes(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts the producer stops at one queued block, then drains the queue
+    async fn assert_one_queued(config: IngestionQueueConfig) -> anyhow::Result<()> {
+        let blocks_dir = PathBuf::from("./tests/data/sequential_blocks");
+        let block_parser = BlockParser::new_testing(&blocks_dir)?;
+        let num_blocks = block_parser.total_num_blocks;
+        assert!(num_blocks > 1);
+
+        let mut queue = IngestionQueue::spawn(config, block_parser, None);
+        while queue.depth() == 0 {
+            tokio::task::yield_now().await;
+        }
+        for _ in 0..100 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(queue.depth(), 1);
+
+        let mut num_received = 0;
+        while let Some(item) = queue.recv().await {
+            assert!(matches!(item, QueueItem::Block { .. }));
+            num_received += 1;
+        }
+        assert_eq!(num_received, num_blocks);
+
+        let block_parser = queue.finish().await?;
+        assert_eq!(block_parser.blocks_processed, num_blocks);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn bounded_by_depth_and_bytes() -> anyhow::Result<()> {
+        // depth limit
+        assert_one_queued(IngestionQueueConfig {
+            max_depth: 1,
+            max_bytes: u64::MAX,
+        })
+        .await?;
+
+        // byte limit, oversized blocks are still queued
+        assert_one_queued(IngestionQueueConfig {
+            max_depth: u32::MAX,
+            max_bytes: 1,
+        })
+        .await
+    }
+
+    #[test]
+    fn journaled_block_reparses() -> anyhow::Result<()> {
+        let path: PathBuf = "./tests/data/non_sequential_blocks/mainnet-111-3NL33j16AWm3Jhjj1Ud25E54hu7HpUq4WBQcAiijEKMfXqwFJwzK.json".into();
+        let block = PrecomputedBlock::parse_file(&path, PcbVersion::V1)?;
+        let block_bytes = path.metadata()?.len();
+        let parsed_block = ParsedBlock::Recent(block);
+        let entry = QueuedBlock::new(path, &parsed_block, block_bytes);
+
+        // journaled blocks are re-parsed from their path
+        assert_eq!(entry.parse()?, parsed_block);
+        Ok(())
+    }
+}
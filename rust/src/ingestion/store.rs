@@ -0,0 +1,18 @@
+use super::QueuedBlock;
+
+pub trait IngestionQueueStore {
+    /// Journal the parsed block and return its sequence number
+    fn enqueue_block(&self, entry: &QueuedBlock) -> anyhow::Result<u32>;
+
+    /// Mark the journaled block applied, removing it from the queue
+    fn mark_block_applied(&self, seq_num: u32) -> anyhow::Result<()>;
+
+    /// Get the unapplied journaled blocks in sequence order
+    fn get_unapplied_blocks(&self) -> anyhow::Result<Vec<(u32, QueuedBlock)>>;
+
+    /// Get the number of unapplied journaled blocks and their total bytes
+    fn get_ingestion_queue_size(&self) -> anyhow::Result<(u32, u64)>;
+
+    /// Get the next ingestion sequence number
+    fn get_next_ingestion_seq_num(&self) -> anyhow::Result<u32>;
+}
@@ -15,10 +15,15 @@ use super::{
     username::Username,
 };
 use crate::{
-    base::{amount::Amount, nonce::Nonce, public_key::PublicKey, state_hash::StateHash},
+    base::{
+        amount::{Amount, AmountError},
+        nonce::Nonce,
+        public_key::PublicKey,
+        state_hash::StateHash,
+    },
     block::genesis::GenesisBlock,
     constants::MAINNET_ACCOUNT_CREATION_FEE,
-    ledger::diff::account::PaymentDiff,
+    ledger::{anomaly::AmountAnomaly, diff::account::PaymentDiff},
     mina_blocks::v2::{self, ZkappAccount},
 };
 use log::error;
@@ -97,7 +102,7 @@ impl Account {
     /// Display view of account, removes non-genesis account creation fee
     pub fn display(self) -> Self {
         Self {
-            balance: self.balance - MAINNET_ACCOUNT_CREATION_FEE,
+            balance: self.balance.saturating_sub(MAINNET_ACCOUNT_CREATION_FEE),
             ..self
         }
     }
@@ -105,16 +110,9 @@ impl Account {
     /// Time-locked balance (subtracted from circulating supply)
     /// as per https://docs.minaprotocol.com/mina-protocol/time-locked-accounts
     pub fn current_minimum_balance(&self, curr_global_slot: u32) -> u64 {
-        self.timing.as_ref().map_or(0, |t| {
-            if curr_global_slot < t.cliff_time.0 {
-                t.initial_minimum_balance.0
-            } else {
-                t.initial_minimum_balance.0.saturating_sub(
-                    ((curr_global_slot - t.cliff_time.0) / t.vesting_period.0) as u64
-                        * t.vesting_increment.0,
-                )
-            }
-        })
+        self.timing
+            .as_ref()
+            .map_or(0, |t| t.current_minimum_balance(curr_global_slot))
     }
 
     /// Creates a new empty account with the specified public key.
@@ -160,7 +158,7 @@ impl Account {
     /// A new `Account` instance with the updated balance.
     pub fn coinbase(self, amount: Amount) -> Self {
         Self {
-            balance: self.balance + amount,
+            balance: self.balance.saturating_add(amount),
             ..self
         }
     }
@@ -168,7 +166,7 @@ impl Account {
     /// Unapply a coinbase
     pub fn coinbase_unapply(self, diff: &CoinbaseDiff) -> Self {
         Self {
-            balance: self.balance - diff.amount,
+            balance: self.balance.saturating_sub(diff.amount),
             ..self
         }
     }
@@ -204,11 +202,11 @@ impl Account {
     pub fn payment_unapply(self, diff: &PaymentDiff) -> Self {
         match diff.update_type {
             UpdateType::Credit => Self {
-                balance: self.balance - diff.amount,
+                balance: self.balance.saturating_sub(diff.amount),
                 ..self
             },
             UpdateType::Debit(nonce) => Self {
-                balance: self.balance + diff.amount,
+                balance: self.balance.saturating_add(diff.amount),
                 nonce: nonce.map_or(self.nonce, |nonce| {
                     if self.nonce.map(|n| n.0) == Some(0) {
                         None
@@ -238,7 +236,7 @@ impl Account {
     /// balance.
     fn debit(self, amount: Amount, nonce: Option<Nonce>) -> Self {
         Self {
-            balance: self.balance - amount,
+            balance: self.balance.saturating_sub(amount),
             nonce: nonce.or(self.nonce),
             ..self
         }
@@ -257,7 +255,7 @@ impl Account {
     /// A new `Account` instance with the updated balance.
     fn credit(self, amount: Amount) -> Self {
         Self {
-            balance: self.balance + amount,
+            balance: self.balance.saturating_add(amount),
             ..self
         }
     }
@@ -283,6 +281,12 @@ impl Account {
         }
     }
 
+    /// Whether the account delegates to itself, as accounts which never
+    /// delegated do
+    pub fn is_self_delegated(&self) -> bool {
+        self.delegate == self.public_key
+    }
+
     /// Unapply a delegation
     pub fn delegation_unapply(self, diff: &DelegationDiff) -> Self {
         Self {
@@ -437,17 +441,16 @@ impl Account {
     }
 
     /// Apply zkapp actions diff
-    fn zkapp_actions(self, diff: &ZkappActionsDiff) -> Self {
+    pub fn zkapp_actions(self, diff: &ZkappActionsDiff) -> Self {
         self.checks(&diff.public_key, &diff.token);
 
         let mut zkapp = self
             .zkapp
             .unwrap_or(ZkappAccount::from_proved_state(diff.proved_state));
 
-        // modify action state
-        let n = zkapp.action_state.len();
-        for (idx, action_state) in diff.actions.iter().enumerate() {
-            zkapp.action_state[idx % n] = action_state.to_owned();
+        // action state commitments after the block
+        if let Some(action_state) = diff.action_state.as_ref() {
+            zkapp.action_state = action_state.to_owned();
         }
 
         // modify proved if necessary
@@ -483,11 +486,32 @@ impl Account {
         assert_eq!(diff.amount, MAINNET_ACCOUNT_CREATION_FEE);
 
         Self {
-            balance: self.balance + diff.amount,
+            balance: self.balance.saturating_add(diff.amount),
             ..self
         }
     }
 
+    /// Checks that applying the account diff neither overflows nor
+    /// underflows the account's balance. Balance updates saturate, so the
+    /// diff's other updates (e.g. nonce) still apply
+    pub fn check_account_diff(&self, diff: &AccountDiff) -> Result<(), AmountError> {
+        use AccountDiff::*;
+
+        let balance = match diff {
+            Payment(diff) | FeeTransfer(diff) | FeeTransferViaCoinbase(diff) => {
+                match diff.update_type {
+                    UpdateType::Credit => self.balance + diff.amount,
+                    UpdateType::Debit(_) => self.balance - diff.amount,
+                }
+            }
+            Coinbase(diff) => self.balance + diff.amount,
+            ZkappAccountCreationFee(diff) => self.balance + diff.amount,
+            _ => return Ok(()),
+        };
+
+        balance.map(|_| ())
+    }
+
     /// Apply an account diff to an account
     pub fn apply_account_diff(self, diff: &AccountDiff) -> Self {
         use AccountDiff::*;
@@ -547,17 +571,27 @@ impl Account {
     }
 
     /// Apply a ledger diff to an account
-    pub fn apply_ledger_diff(self, diff: &LedgerDiff) -> Self {
+    ///
+    /// Account diffs which overflow or underflow the balance are applied with
+    /// a saturated balance & returned as anomalies
+    pub fn apply_ledger_diff(self, diff: &LedgerDiff) -> (Self, Vec<AmountAnomaly>) {
         let pk = self.public_key.clone();
         let mut acct = self;
+        let mut anomalies = vec![];
 
         for acct_diff in diff.account_diffs.iter().flatten() {
             if acct_diff.public_key() == pk {
+                if let Err(e) = acct.check_account_diff(acct_diff) {
+                    let anomaly = AmountAnomaly::new(&diff.state_hash, acct_diff, e);
+                    error!("Saturating account diff {anomaly}");
+                    anomalies.push(anomaly);
+                }
+
                 acct = acct.apply_account_diff(acct_diff);
             }
         }
 
-        acct
+        (acct, anomalies)
     }
 
     /// Checks application to the expected token account
@@ -632,7 +666,7 @@ impl From<GenesisBlock> for Account {
 impl std::fmt::Display for Account {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let deducted = Self {
-            balance: self.balance - MAINNET_ACCOUNT_CREATION_FEE,
+            balance: self.balance.saturating_sub(MAINNET_ACCOUNT_CREATION_FEE),
             ..self.clone()
         };
 
@@ -729,7 +763,7 @@ mod tests {
         assert_eq!(
             after,
             Account {
-                balance: before.balance - amount,
+                balance: (before.balance - amount).unwrap(),
                 nonce: Some(185.into()),
                 ..before
             }
@@ -16,3 +16,18 @@ pub struct Timing {
     pub vesting_increment: Balance,
     pub initial_minimum_balance: Balance,
 }
+
+impl Timing {
+    /// Time-locked balance at the global slot
+    /// as per https://docs.minaprotocol.com/mina-protocol/time-locked-accounts
+    pub fn current_minimum_balance(&self, curr_global_slot: u32) -> u64 {
+        if curr_global_slot < self.cliff_time.0 {
+            self.initial_minimum_balance.0
+        } else {
+            self.initial_minimum_balance.0.saturating_sub(
+                ((curr_global_slot - self.cliff_time.0) / self.vesting_period.0) as u64
+                    * self.vesting_increment.0,
+            )
+        }
+    }
+}
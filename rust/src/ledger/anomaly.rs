@@ -0,0 +1,40 @@
+//! Ledger amount anomalies
+
+use crate::{
+    base::{amount::AmountError, public_key::PublicKey, state_hash::StateHash},
+    ledger::{diff::account::AccountDiff, token::TokenAddress},
+};
+use serde::{Deserialize, Serialize};
+
+/// An account diff whose application overflowed or underflowed the
+/// account's balance, which saturated, or a per account fee aggregate which
+/// would overflow & was split
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AmountAnomaly {
+    /// Block being applied when the anomaly was detected
+    pub state_hash: StateHash,
+    pub public_key: PublicKey,
+    pub token: TokenAddress,
+    pub error: AmountError,
+}
+
+impl AmountAnomaly {
+    pub fn new(state_hash: &StateHash, diff: &AccountDiff, error: AmountError) -> Self {
+        Self {
+            state_hash: state_hash.clone(),
+            public_key: diff.public_key(),
+            token: diff.token_address(),
+            error,
+        }
+    }
+}
+
+impl std::fmt::Display for AmountAnomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (token {}) in block {}: {}",
+            self.public_key, self.token, self.state_hash, self.error
+        )
+    }
+}
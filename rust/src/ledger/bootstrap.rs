@@ -0,0 +1,238 @@
+//! Bootstrap of downstream replicas from the canonical root ledger
+//!
+//! A bootstrap is serialized as newline-delimited JSON: the
+//! [BootstrapManifest] followed by `num_chunks` [BootstrapChunk]s. Consumers
+//! load the ledger & then follow the event log from `next_seq_num`, applying
+//! the ledger diffs of new canonical blocks above `root_height`.
+
+use super::{account::Account, store::staged::StagedLedgerStore, token::TokenAddress, Ledger};
+use crate::{
+    base::state_hash::StateHash,
+    event::{
+        db::{DbCanonicityEvent, DbEvent},
+        store::EventStore,
+        IndexerEvent,
+    },
+    store::IndexerStore,
+};
+use anyhow::{bail, Context};
+use log::trace;
+use serde::{Deserialize, Serialize};
+use speedb::{Direction, IteratorMode};
+use std::io::{BufRead, Write};
+
+/// First line of a bootstrap
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BootstrapManifest {
+    /// Canonical root block height
+    pub root_height: u32,
+
+    /// Canonical root block state hash
+    pub root_state_hash: StateHash,
+
+    /// Event sequence number to resume from
+    pub next_seq_num: u32,
+
+    /// Number of (token) accounts in the root ledger
+    pub num_accounts: u32,
+
+    /// Max number of accounts per chunk
+    pub chunk_size: u32,
+
+    /// Number of chunks following the manifest
+    pub num_chunks: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BootstrapAccount {
+    pub token: TokenAddress,
+    pub account: Account,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BootstrapChunk {
+    pub index: u32,
+    pub accounts: Vec<BootstrapAccount>,
+}
+
+/// Canonical root ledger, its block & the event sequence number to resume from
+pub struct Bootstrap {
+    pub manifest: BootstrapManifest,
+    accounts: Vec<BootstrapAccount>,
+}
+
+impl Bootstrap {
+    pub const DEFAULT_CHUNK_SIZE: u32 = 1000;
+
+    /// Assembles the canonical root ledger from the nearest persisted staged
+    /// ledger at or below the root & the canonical diffs to reach it
+    ///
+    /// The root is the most recent new canonical block event preceding
+    /// `next_seq_num`, so every later canonical block has a subsequent event
+    pub fn new(store: &IndexerStore, chunk_size: u32) -> anyhow::Result<Option<Self>> {
+        if chunk_size == 0 {
+            bail!("Bootstrap chunk size must be positive");
+        }
+
+        // read the sequence number first, events below it are already written
+        let next_seq_num = store.get_next_seq_num()?;
+        let Some((root_state_hash, root_height)) = canonical_root(store, next_seq_num)? else {
+            return Ok(None);
+        };
+
+        trace!("Bootstrapping from canonical root (length {root_height}): {root_state_hash}");
+        let ledger = store
+            .get_staged_ledger_at_state_hash(&root_state_hash, false)?
+            .with_context(|| format!("Canonical root ledger {root_state_hash}"))?;
+
+        // sort for deterministic chunks
+        let mut accounts: Vec<BootstrapAccount> = ledger
+            .tokens
+            .into_iter()
+            .flat_map(|(token, token_ledger)| {
+                token_ledger
+                    .accounts
+                    .into_values()
+                    .map(move |account| BootstrapAccount {
+                        token: token.clone(),
+                        account,
+                    })
+            })
+            .collect();
+        accounts.sort_by(|a, b| {
+            (&a.token, &a.account.public_key).cmp(&(&b.token, &b.account.public_key))
+        });
+
+        let num_accounts = accounts.len() as u32;
+        Ok(Some(Self {
+            manifest: BootstrapManifest {
+                root_height,
+                root_state_hash,
+                next_seq_num,
+                num_accounts,
+                chunk_size,
+                num_chunks: num_accounts.div_ceil(chunk_size),
+            },
+            accounts,
+        }))
+    }
+
+    /// The `index`-th chunk of accounts
+    pub fn chunk(&self, index: u32) -> Option<BootstrapChunk> {
+        if index >= self.manifest.num_chunks {
+            return None;
+        }
+
+        let chunk_size = self.manifest.chunk_size as usize;
+        Some(BootstrapChunk {
+            index,
+            accounts: self
+                .accounts
+                .iter()
+                .skip(index as usize * chunk_size)
+                .take(chunk_size)
+                .cloned()
+                .collect(),
+        })
+    }
+
+    /// Newline-delimited JSON line of the manifest (`index == 0`) or the
+    /// `index - 1`-th chunk
+    pub fn line(&self, index: u32) -> anyhow::Result<Option<String>> {
+        let mut line = if index == 0 {
+            serde_json::to_string(&self.manifest)?
+        } else if let Some(chunk) = self.chunk(index - 1) {
+            serde_json::to_string(&chunk)?
+        } else {
+            return Ok(None);
+        };
+
+        line.push('\n');
+        Ok(Some(line))
+    }
+
+    /// Writes the manifest followed by the chunks
+    pub fn write<W: Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        let mut index = 0;
+        while let Some(line) = self.line(index)? {
+            writer.write_all(line.as_bytes())?;
+            index += 1;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a bootstrap, checking its chunks against the manifest
+    pub fn read<R: BufRead>(reader: R) -> anyhow::Result<(BootstrapManifest, Ledger)> {
+        let mut lines = reader.lines();
+        let manifest: BootstrapManifest = match lines.next() {
+            Some(line) => serde_json::from_str(&line?).context("Bootstrap manifest")?,
+            None => bail!("Bootstrap manifest missing"),
+        };
+
+        let mut ledger = Ledger::new();
+        let mut num_accounts = 0;
+        for index in 0..manifest.num_chunks {
+            let chunk: BootstrapChunk = match lines.next() {
+                Some(line) => serde_json::from_str(&line?)
+                    .with_context(|| format!("Bootstrap chunk {index}"))?,
+                None => bail!("Bootstrap chunk {index} missing"),
+            };
+
+            if chunk.index != index {
+                bail!(
+                    "Bootstrap chunk {} out of order, expected {index}",
+                    chunk.index
+                );
+            }
+
+            num_accounts += chunk.accounts.len() as u32;
+            for BootstrapAccount { token, account } in chunk.accounts {
+                ledger.insert_account(account, &token);
+            }
+        }
+
+        if num_accounts != manifest.num_accounts {
+            bail!(
+                "Bootstrap has {num_accounts} accounts, manifest expects {}",
+                manifest.num_accounts
+            );
+        }
+
+        Ok((manifest, ledger))
+    }
+}
+
+/// Most recent new canonical block preceding `next_seq_num`
+fn canonical_root(
+    store: &IndexerStore,
+    next_seq_num: u32,
+) -> anyhow::Result<Option<(StateHash, u32)>> {
+    for (key, bytes) in store
+        .event_log_iterator(IteratorMode::From(
+            &next_seq_num.to_be_bytes(),
+            Direction::Reverse,
+        ))
+        .flatten()
+    {
+        // skip events added since reading the sequence number
+        if key.as_ref() >= next_seq_num.to_be_bytes().as_slice() {
+            continue;
+        }
+
+        if bytes.len() < 5 || bytes[4] != IndexerEvent::NEW_CANONICAL_BLOCK_KIND {
+            continue;
+        }
+
+        if let IndexerEvent::Db(DbEvent::Canonicity(DbCanonicityEvent::NewCanonicalBlock {
+            state_hash,
+            blockchain_length,
+        })) = serde_json::from_slice(&bytes[5..])?
+        {
+            return Ok(Some((state_hash, blockchain_length)));
+        }
+    }
+
+    Ok(None)
+}
@@ -1,10 +1,11 @@
 use crate::{
-    base::{nonce::Nonce, state_hash::StateHash},
+    base::{amount::AmountError, nonce::Nonce, state_hash::StateHash},
     block::precomputed::PrecomputedBlock,
     command::{Command, UserCommandWithStatus, UserCommandWithStatusT},
     constants::ZKAPP_STATE_FIELD_ELEMENTS_NUM,
     ledger::{
         account::{Permissions, Timing},
+        anomaly::AmountAnomaly,
         coinbase::Coinbase,
         token::{TokenAddress, TokenSymbol},
         Amount, PublicKey,
@@ -12,7 +13,7 @@ use crate::{
     mina_blocks::v2::{
         self,
         protocol_state::SupplyAdjustmentSign,
-        staged_ledger_diff::{Authorization, Elt, UpdateKind},
+        staged_ledger_diff::{AuthorizationKind, Elt, UpdateKind},
         ActionState, AppState, VerificationKey, ZkappEvent, ZkappUri,
     },
     snark_work::SnarkWorkSummary,
@@ -140,6 +141,11 @@ pub struct ZkappActionsDiff {
     pub public_key: PublicKey,
     pub proved_state: bool,
     pub actions: Vec<ActionState>,
+
+    /// Account's action state commitments after the block, only set on the
+    /// account's last actions diff in the block
+    #[serde(default)]
+    pub action_state: Option<[ActionState; 5]>,
 }
 
 #[derive(Default, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash, Serialize, Deserialize)]
@@ -369,43 +375,94 @@ impl AccountDiff {
         }
     }
 
+    /// Aggregates fees per public key. An aggregate fee which would overflow
+    /// is split into multiple fees instead, the overflows are returned too
+    fn aggregate_fees<I>(fees: I) -> (Vec<(PublicKey, Amount)>, Vec<(PublicKey, AmountError)>)
+    where
+        I: IntoIterator<Item = (PublicKey, Amount)>,
+    {
+        let mut fee_map: HashMap<PublicKey, Vec<Amount>> = HashMap::new();
+        let mut overflows = vec![];
+
+        for (pk, fee) in fees {
+            let totals = fee_map.entry(pk.clone()).or_default();
+
+            match totals.last().map(|total| *total + fee) {
+                Some(Ok(sum)) => *totals.last_mut().expect("aggregate fee") = sum,
+                Some(Err(e)) => {
+                    overflows.push((pk, e));
+                    totals.push(fee);
+                }
+                None => totals.push(fee),
+            }
+        }
+
+        let fees = fee_map
+            .into_iter()
+            .flat_map(|(pk, totals)| totals.into_iter().map(move |total| (pk.clone(), total)))
+            .collect();
+        (fees, overflows)
+    }
+
+    /// Per public key user command & SNARK work fee aggregates of the block
+    /// which would overflow & are split
+    pub fn block_fee_anomalies(precomputed_block: &PrecomputedBlock) -> Vec<AmountAnomaly> {
+        let state_hash = precomputed_block.state_hash();
+        let user_cmd_fees = |user_cmds: Vec<UserCommandWithStatus>| {
+            user_cmds
+                .iter()
+                .map(|user_cmd| (user_cmd.fee_payer_pk(), user_cmd.fee().into()))
+                .collect::<Vec<_>>()
+        };
+        let snark_fees = SnarkWorkSummary::from_precomputed(precomputed_block)
+            .into_iter()
+            .map(|snark| (snark.prover, snark.fee))
+            .collect();
+
+        [
+            user_cmd_fees(precomputed_block.commands_pre_diff()),
+            user_cmd_fees(precomputed_block.commands_post_diff()),
+            snark_fees,
+        ]
+        .into_iter()
+        .flat_map(|fees| Self::aggregate_fees(fees).1)
+        .map(|(public_key, error)| AmountAnomaly {
+            state_hash: state_hash.clone(),
+            public_key,
+            token: TokenAddress::default(), // always MINA
+            error,
+        })
+        .collect()
+    }
+
     fn transaction_fees(
         coinbase_receiver: &PublicKey,
         user_cmds: Vec<UserCommandWithStatus>,
     ) -> Vec<Vec<Self>> {
-        let mut fee_map = HashMap::new();
-
-        for user_cmd in user_cmds.iter() {
-            let fee = user_cmd.fee();
-            fee_map
-                .entry(user_cmd.fee_payer_pk())
-                .and_modify(|acc| *acc += fee)
-                .or_insert(fee);
-        }
-
-        fee_map
-            .iter()
-            .flat_map(|(pk, fee)| {
-                let mut res = vec![];
-
-                if *fee > 0 {
-                    res.push(vec![
-                        Self::FeeTransfer(PaymentDiff {
-                            public_key: coinbase_receiver.clone(),
-                            amount: (*fee).into(),
-                            update_type: UpdateType::Credit,
-                            token: TokenAddress::default(), // always MINA
-                        }),
-                        Self::FeeTransfer(PaymentDiff {
-                            public_key: pk.clone(),
-                            amount: (*fee).into(),
-                            update_type: UpdateType::Debit(None),
-                            token: TokenAddress::default(), // always MINA
-                        }),
-                    ]);
-                }
+        // overflowing aggregates are recorded via [Self::block_fee_anomalies]
+        let (fees, _) = Self::aggregate_fees(
+            user_cmds
+                .iter()
+                .map(|user_cmd| (user_cmd.fee_payer_pk(), user_cmd.fee().into())),
+        );
 
-                res
+        fees.into_iter()
+            .filter(|(_, fee)| fee.0 > 0)
+            .map(|(pk, fee)| {
+                vec![
+                    Self::FeeTransfer(PaymentDiff {
+                        public_key: coinbase_receiver.clone(),
+                        amount: fee,
+                        update_type: UpdateType::Credit,
+                        token: TokenAddress::default(), // always MINA
+                    }),
+                    Self::FeeTransfer(PaymentDiff {
+                        public_key: pk,
+                        amount: fee,
+                        update_type: UpdateType::Debit(None),
+                        token: TokenAddress::default(), // always MINA
+                    }),
+                ]
             })
             .collect()
     }
@@ -427,42 +484,32 @@ impl AccountDiff {
     /// Fees for SNARK work, aggregated per public key
     pub fn from_snark_fees(precomputed_block: &PrecomputedBlock) -> Vec<Vec<Self>> {
         let snarks = SnarkWorkSummary::from_precomputed(precomputed_block);
-        let mut fee_map = HashMap::new();
 
         // SNARK work fees aggregated per public key
-        for snark in snarks {
-            fee_map
-                .entry(snark.prover.clone())
-                .and_modify(|agg_fee| *agg_fee += snark.fee)
-                .or_insert(snark.fee);
-        }
-
-        fee_map
-            .iter()
-            .flat_map(|(prover, total_fee)| {
-                let mut res = vec![];
+        let (fees, _) =
+            Self::aggregate_fees(snarks.into_iter().map(|snark| (snark.prover, snark.fee)));
 
+        fees.into_iter()
+            .filter_map(|(prover, total_fee)| {
                 // No need to issue Debits and Credits if the fee is 0
-                if total_fee.0 > 0 {
-                    res.push(vec![
+                (total_fee.0 > 0).then(|| {
+                    vec![
                         AccountDiff::FeeTransfer(PaymentDiff {
-                            public_key: prover.clone(),
-                            amount: *total_fee,
+                            public_key: prover,
+                            amount: total_fee,
                             update_type: UpdateType::Credit,
                             token: TokenAddress::default(), // always MINA
                         }),
                         AccountDiff::FeeTransfer(PaymentDiff {
                             public_key: precomputed_block.coinbase_receiver(),
-                            amount: *total_fee,
+                            amount: total_fee,
                             update_type: UpdateType::Debit(None),
                             token: TokenAddress::default(), // always MINA
                         }),
-                    ]);
-                }
-
-                res
+                    ]
+                })
             })
-            .collect::<Vec<_>>()
+            .collect()
     }
 
     /// User command + SNARK work fees, aggregated per public key
@@ -501,6 +548,32 @@ impl AccountDiff {
         }
     }
 
+    /// Short name of the diff kind
+    pub fn kind(&self) -> &'static str {
+        use AccountDiff::*;
+
+        match self {
+            Payment(_) => "payment",
+            Delegation(_) => "delegation",
+            Coinbase(_) => "coinbase",
+            FeeTransfer(_) => "fee_transfer",
+            FeeTransferViaCoinbase(_) => "fee_transfer_via_coinbase",
+            FailedTransactionNonce(_) => "failed_transaction_nonce",
+            Zkapp(_) => "zkapp",
+            ZkappStateDiff(_) => "zkapp_state",
+            ZkappPermissionsDiff(_) => "zkapp_permissions",
+            ZkappVerificationKeyDiff(_) => "zkapp_verification_key",
+            ZkappUriDiff(_) => "zkapp_uri",
+            ZkappTokenSymbolDiff(_) => "zkapp_token_symbol",
+            ZkappTimingDiff(_) => "zkapp_timing",
+            ZkappVotingForDiff(_) => "zkapp_voting_for",
+            ZkappActionsDiff(_) => "zkapp_actions",
+            ZkappEventsDiff(_) => "zkapp_events",
+            ZkappIncrementNonce(_) => "zkapp_increment_nonce",
+            ZkappAccountCreationFee(_) => "zkapp_account_creation_fee",
+        }
+    }
+
     pub fn from(
         sender: &str,
         receiver: &str,
@@ -834,6 +907,7 @@ impl ZkappDiff {
                 public_key: pk,
                 proved_state,
                 actions,
+                action_state: None,
             }));
         }
     }
@@ -1089,10 +1163,8 @@ impl From<(PublicKey, Nonce, &Elt)> for AccountDiff {
                     UpdateKind::Set((_, state)) => Some(state.into()),
                 });
 
-        let proved_state = matches!(
-            value.2.account_update.body.authorization_kind,
-            Authorization::Proof(_) | Authorization::Proof_(_)
-        );
+        let proved_state =
+            value.2.account_update.body.authorization_kind.kind == AuthorizationKind::Proof;
 
         Self::Zkapp(Box::new(ZkappDiff {
             token,
@@ -1215,6 +1287,42 @@ mod tests {
     };
     use std::path::PathBuf;
 
+    #[test]
+    fn aggregate_fees_split_on_overflow() {
+        let pk0 = PublicKey::new("B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy");
+        let pk1 = PublicKey::new("B62qmMypEDCchUgPD6RU99gVKXJcY46urKdjbFmG5cYtaVpfKysXTz6");
+
+        let (mut fees, overflows) = AccountDiff::aggregate_fees([
+            (pk0.clone(), Amount(u64::MAX - 1)),
+            (pk0.clone(), Amount(1)),
+            (pk0.clone(), Amount(1)),
+            (pk1.clone(), Amount(2)),
+            (pk1.clone(), Amount(3)),
+        ]);
+        fees.sort();
+
+        // the fee which would overflow starts a new aggregate
+        let mut expected = vec![
+            (pk0.clone(), Amount(1)),
+            (pk0.clone(), Amount(u64::MAX)),
+            (pk1, Amount(5)),
+        ];
+        expected.sort();
+        assert_eq!(fees, expected);
+
+        // & the overflow is surfaced
+        assert_eq!(
+            overflows,
+            vec![(
+                pk0,
+                AmountError::Overflow {
+                    lhs: u64::MAX,
+                    rhs: 1
+                }
+            )]
+        );
+    }
+
     #[test]
     fn test_amount() {
         let credit_amount = Amount(1000);
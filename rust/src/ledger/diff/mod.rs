@@ -9,7 +9,7 @@ use crate::{
 };
 use account::ZkappAccountCreationFee;
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct LedgerDiff {
@@ -96,12 +96,41 @@ impl LedgerDiff {
             account_diffs.insert(0, zkapp_token_accounts_created)
         }
 
+        Self::set_action_states(block, &mut account_diffs);
+
         Self {
             account_diffs,
             ..unexpanded
         }
     }
 
+    /// Attach the post-block action state commitments of the accessed zkapp
+    /// accounts to each account's last actions diff
+    ///
+    /// The commitments are computed by the daemon, we only record them
+    fn set_action_states(block: &PrecomputedBlock, account_diffs: &mut [Vec<AccountDiff>]) {
+        let mut action_states: HashMap<_, _> = block
+            .accounts_accessed()
+            .into_iter()
+            .filter_map(|accessed| {
+                let account = accessed.account;
+                account.zkapp.map(|zkapp| {
+                    (
+                        (account.public_key, account.token.unwrap_or_default()),
+                        zkapp.action_state,
+                    )
+                })
+            })
+            .collect();
+
+        for diff in account_diffs.iter_mut().flatten().rev() {
+            if let AccountDiff::ZkappActionsDiff(diff) = diff {
+                let key = (diff.public_key.to_owned(), diff.token.to_owned());
+                diff.action_state = action_states.remove(&key);
+            }
+        }
+    }
+
     /// Compute a ledger diff from the given precomputed block, without
     /// expanding zkapp diffs
     pub fn from_precomputed_unexpanded(block: &PrecomputedBlock) -> Self {
@@ -135,6 +164,7 @@ impl LedgerDiff {
         }
 
         // apply in order: user commands, coinbase, fees
+        // (matches the commands' index in block)
         account_diffs.append(&mut account_diff_txns);
         if coinbase.is_coinbase_applied() {
             account_diffs.push(coinbase.as_account_diff()[0].clone());
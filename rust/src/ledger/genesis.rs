@@ -299,8 +299,9 @@ impl From<GenesisLedger> for TokenLedger {
                     (
                         pk,
                         Account {
-                            // add display fee
-                            balance: acct.balance + MAINNET_ACCOUNT_CREATION_FEE,
+                            // add display fee, genesis balances are bounded by the
+                            // total currency
+                            balance: acct.balance.saturating_add(MAINNET_ACCOUNT_CREATION_FEE),
                             ..acct
                         },
                     )
@@ -1,23 +1,37 @@
 pub mod account;
+pub mod anomaly;
+pub mod bootstrap;
 pub mod coinbase;
 pub mod diff;
 pub mod genesis;
 pub mod hash;
+pub mod overlay;
+#[cfg(feature = "parallel_diffs")]
+pub mod parallel;
 pub mod staking;
 pub mod store;
 pub mod token;
+pub mod trace;
 pub mod username;
+pub mod verify;
+pub mod view;
 
 use crate::{
-    base::{amount::Amount, nonce::Nonce, public_key::PublicKey},
+    base::{
+        amount::{Amount, AmountError},
+        nonce::Nonce,
+        public_key::PublicKey,
+    },
     block::precomputed::PrecomputedBlock,
     constants::MAINNET_ACCOUNT_CREATION_FEE,
     ledger::{
         account::Account,
+        anomaly::AmountAnomaly,
         diff::{account::AccountDiff, LedgerDiff},
         token::TokenAddress,
     },
 };
+use log::error;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, str::FromStr};
 
@@ -110,6 +124,15 @@ impl Ledger {
         }
     }
 
+    /// Only keep the given public keys' accounts, in every token ledger
+    pub fn retain_accounts(&mut self, pks: &[PublicKey]) {
+        for token_ledger in self.tokens.values_mut() {
+            token_ledger.accounts.retain(|pk, _| pks.contains(pk));
+        }
+        self.tokens
+            .retain(|_, token_ledger| !token_ledger.accounts.is_empty());
+    }
+
     /// Apply the ledger diff from a PCB
     pub fn apply_diff_from_precomputed(self, block: &PrecomputedBlock) -> anyhow::Result<Self> {
         let diff = LedgerDiff::from_precomputed(block);
@@ -125,15 +148,24 @@ impl Ledger {
     }
 
     /// Apply a ledger diff to a mutable ledger
-    pub fn _apply_diff(&mut self, diff: &LedgerDiff) -> anyhow::Result<()> {
+    ///
+    /// Account diffs which overflow or underflow balances are applied with
+    /// saturated balances & returned as anomalies
+    pub fn _apply_diff(&mut self, diff: &LedgerDiff) -> anyhow::Result<Vec<AmountAnomaly>> {
+        let mut anomalies = vec![];
         for acct_diff in diff.account_diffs.iter().flatten() {
-            self._apply_account_diff(acct_diff)?;
+            if let Err(e) = self._apply_account_diff(acct_diff) {
+                let anomaly = AmountAnomaly::new(&diff.state_hash, acct_diff, e);
+                error!("Saturating account diff {anomaly}");
+                anomalies.push(anomaly);
+            }
         }
-        Ok(())
+        Ok(anomalies)
     }
 
-    /// Apply an account diff to a mutable ledger
-    pub fn _apply_account_diff(&mut self, acct_diff: &AccountDiff) -> anyhow::Result<()> {
+    /// Apply an account diff to a mutable ledger, erroring if it overflows or
+    /// underflows the account's (saturated) balance
+    pub fn _apply_account_diff(&mut self, acct_diff: &AccountDiff) -> Result<(), AmountError> {
         let pk = acct_diff.public_key();
         let token = acct_diff.token_address();
 
@@ -143,7 +175,11 @@ impl Ledger {
             .and_then(|token_ledger| token_ledger.accounts.remove(&pk))
             .or(Some(Account::empty(pk, token.to_owned())))
         {
-            self.insert_account(account.apply_account_diff(acct_diff), &token);
+            let check = account.check_account_diff(acct_diff);
+            let account = account.apply_account_diff(acct_diff);
+
+            self.insert_account(account, &token);
+            return check;
         }
 
         Ok(())
@@ -178,21 +214,10 @@ impl Ledger {
     }
 
     pub fn time_locked_amount(&self, curr_global_slot: u32) -> Amount {
-        Amount(
-            self.tokens
-                .get(&TokenAddress::default())
-                .map(|mina_ledger| {
-                    {
-                        mina_ledger.accounts.values().filter_map(|acct| {
-                            acct.timing
-                                .as_ref()
-                                .map(|_| acct.current_minimum_balance(curr_global_slot))
-                        })
-                    }
-                    .sum()
-                })
-                .expect("MINA ledger exists"),
-        )
+        self.tokens
+            .get(&TokenAddress::default())
+            .map(|mina_ledger| mina_ledger.time_locked_amount(curr_global_slot))
+            .expect("MINA ledger exists")
     }
 
     pub fn from(value: Vec<(&str, u64, Option<u32>, Option<&str>)>) -> anyhow::Result<Self> {
@@ -301,15 +326,24 @@ impl TokenLedger {
     }
 
     /// Apply a ledger diff to a mutable ledger
-    pub fn _apply_diff(&mut self, diff: &LedgerDiff) -> anyhow::Result<()> {
+    ///
+    /// Account diffs which overflow or underflow balances are applied with
+    /// saturated balances & returned as anomalies
+    pub fn _apply_diff(&mut self, diff: &LedgerDiff) -> anyhow::Result<Vec<AmountAnomaly>> {
+        let mut anomalies = vec![];
         for acct_diff in diff.account_diffs.iter().flatten() {
-            self._apply_account_diff(acct_diff)?;
+            if let Err(e) = self._apply_account_diff(acct_diff) {
+                let anomaly = AmountAnomaly::new(&diff.state_hash, acct_diff, e);
+                error!("Saturating account diff {anomaly}");
+                anomalies.push(anomaly);
+            }
         }
-        Ok(())
+        Ok(anomalies)
     }
 
-    /// Apply an account diff to a mutable ledger
-    pub fn _apply_account_diff(&mut self, acct_diff: &AccountDiff) -> anyhow::Result<()> {
+    /// Apply an account diff to a mutable ledger, erroring if it overflows or
+    /// underflows the account's (saturated) balance
+    pub fn _apply_account_diff(&mut self, acct_diff: &AccountDiff) -> Result<(), AmountError> {
         let pk = acct_diff.public_key();
         let token = acct_diff.token_address();
 
@@ -318,8 +352,11 @@ impl TokenLedger {
             .remove(&pk)
             .or(Some(Account::empty(pk.clone(), token)))
         {
-            self.accounts
-                .insert(pk, account.apply_account_diff(acct_diff));
+            let check = account.check_account_diff(acct_diff);
+            let account = account.apply_account_diff(acct_diff);
+
+            self.accounts.insert(pk, account);
+            return check;
         }
         Ok(())
     }
@@ -347,17 +384,13 @@ impl TokenLedger {
         Ok(())
     }
 
+    /// Total time-locked balance, saturating at [u64::MAX]
     pub fn time_locked_amount(&self, curr_global_slot: u32) -> Amount {
-        Amount(
-            self.accounts
-                .values()
-                .filter_map(|acct| {
-                    acct.timing
-                        .as_ref()
-                        .map(|_| acct.current_minimum_balance(curr_global_slot))
-                })
-                .sum(),
-        )
+        Amount::saturating_sum(self.accounts.values().filter_map(|acct| {
+            acct.timing
+                .as_ref()
+                .map(|_| Amount(acct.current_minimum_balance(curr_global_slot)))
+        }))
     }
 
     pub fn from(value: Vec<(&str, u64, Option<u32>, Option<&str>)>) -> anyhow::Result<Self> {
@@ -410,7 +443,7 @@ impl FromStr for TokenLedger {
                 pk.into(),
                 Account {
                     // compensate for display deduction
-                    balance: acct.balance + MAINNET_ACCOUNT_CREATION_FEE,
+                    balance: (acct.balance + MAINNET_ACCOUNT_CREATION_FEE)?,
                     ..acct.clone()
                 },
             );
@@ -477,6 +510,7 @@ mod tests {
         constants::MINA_SCALE,
         ledger::{token::TokenAddress, TokenLedger},
     };
+    use quickcheck::QuickCheck;
     use std::collections::{BTreeMap, HashMap};
 
     #[test]
@@ -529,7 +563,7 @@ mod tests {
         assert_eq!(
             *account_after,
             Account {
-                balance: account_before.balance + amount,
+                balance: (account_before.balance + amount).unwrap(),
                 ..account_before
             }
         );
@@ -571,4 +605,122 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn apply_diff_underflowing_debit_advances_nonce() {
+        let prev_nonce = Nonce(42);
+        let public_key = PublicKey::new("B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy");
+        let account_before = Account {
+            balance: Amount(1),
+            nonce: Some(prev_nonce),
+            ..Account::empty(public_key.clone(), TokenAddress::default())
+        };
+
+        let mut accounts = HashMap::new();
+        accounts.insert(public_key.clone(), account_before.clone());
+
+        let ledger_diff = LedgerDiff {
+            blockchain_length: 0,
+            state_hash: StateHash::default(),
+            new_pk_balances: BTreeMap::new(),
+            new_coinbase_receiver: None,
+            staged_ledger_hash: LedgerHash::default(),
+            public_keys_seen: vec![],
+            account_diffs: vec![vec![AccountDiff::Payment(PaymentDiff {
+                amount: Amount(2),
+                public_key: public_key.clone(),
+                update_type: UpdateType::Debit(Some(prev_nonce + 1)),
+                token: TokenAddress::default(),
+            })]],
+        };
+
+        let mut ledger = TokenLedger { accounts };
+        let anomalies = ledger._apply_diff(&ledger_diff).unwrap();
+        assert_eq!(anomalies.len(), 1);
+
+        // the balance saturates, the nonce is still applied
+        let account_after = ledger.accounts.get(&public_key).unwrap();
+        assert_eq!(
+            *account_after,
+            Account {
+                balance: Amount(0),
+                nonce: Some(prev_nonce + 1),
+                ..account_before.clone()
+            }
+        );
+
+        // the account's diff application agrees
+        let (account, anomalies) = account_before.clone().apply_ledger_diff(&ledger_diff);
+        assert_eq!(account, *account_after);
+        assert_eq!(anomalies.len(), 1);
+    }
+
+    /// Applies the credits & debits, `(is_credit, amount, extreme)`, where
+    /// every third `extreme` replaces the amount with an extreme one
+    fn apply_diff_extreme_amounts_prop(ops: Vec<(bool, u64, u8)>) -> bool {
+        const EXTREMES: [u64; 6] = [0, 1, u64::MAX / 2, u64::MAX / 2 + 1, u64::MAX - 1, u64::MAX];
+
+        let public_key = PublicKey::new("B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy");
+        let mut ledger = TokenLedger::new();
+        let mut expected = 0u64;
+
+        for (is_credit, amount, extreme) in ops {
+            let amount = if extreme % 3 == 0 {
+                EXTREMES[extreme as usize % EXTREMES.len()]
+            } else {
+                amount
+            };
+            let ledger_diff = LedgerDiff {
+                blockchain_length: 0,
+                state_hash: StateHash::default(),
+                new_pk_balances: BTreeMap::new(),
+                new_coinbase_receiver: None,
+                staged_ledger_hash: LedgerHash::default(),
+                public_keys_seen: vec![],
+                account_diffs: vec![vec![AccountDiff::Payment(PaymentDiff {
+                    amount: Amount(amount),
+                    public_key: public_key.clone(),
+                    update_type: if is_credit {
+                        UpdateType::Credit
+                    } else {
+                        UpdateType::Debit(None)
+                    },
+                    token: TokenAddress::default(),
+                })]],
+            };
+
+            let anomalies = ledger._apply_diff(&ledger_diff).unwrap();
+            let balance = if is_credit {
+                expected.checked_add(amount)
+            } else {
+                expected.checked_sub(amount)
+            };
+            match balance {
+                Some(balance) => {
+                    expected = balance;
+                    assert!(anomalies.is_empty());
+                }
+                None => {
+                    // overflowing credits & underflowing debits saturate,
+                    // never wrap, & are recorded
+                    expected = if is_credit { u64::MAX } else { 0 };
+                    assert_eq!(anomalies.len(), 1);
+                    assert_eq!(anomalies[0].public_key, public_key);
+                }
+            }
+            assert_eq!(
+                ledger.accounts.get(&public_key).unwrap().balance.0,
+                expected
+            );
+        }
+
+        true
+    }
+
+    #[test]
+    fn apply_diff_extreme_amounts() {
+        QuickCheck::new()
+            .tests(500)
+            .quickcheck(apply_diff_extreme_amounts_prop as fn(_) -> bool);
+    }
 }
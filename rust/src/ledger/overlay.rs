@@ -0,0 +1,184 @@
+//! Best tip ledger overlay
+//!
+//! The canonical ledger is left untouched, the overlay only holds the accounts
+//! modified by the best chain blocks above the canonical root
+
+use super::{account::Account, diff::LedgerDiff, token::TokenAddress, view::LedgerView, Ledger};
+use crate::base::{public_key::PublicKey, state_hash::StateHash};
+use std::collections::HashMap;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LedgerOverlay {
+    /// State hash of the block the overlay has been applied up to
+    pub tip: StateHash,
+
+    /// Modified accounts along with the length of the last block modifying
+    /// them
+    accounts: HashMap<(PublicKey, TokenAddress), (u32, Account)>,
+}
+
+/// Ledger view which reads the overlay, then the base ledger
+#[derive(Debug, Clone, Copy)]
+pub struct OverlayLedger<'a> {
+    pub base: &'a Ledger,
+    pub overlay: &'a LedgerOverlay,
+}
+
+impl LedgerOverlay {
+    /// Creates an empty overlay on top of the `tip` block's ledger
+    pub fn new(tip: StateHash) -> Self {
+        Self {
+            tip,
+            accounts: HashMap::new(),
+        }
+    }
+
+    /// Number of overlaid accounts
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// View of the overlay on top of the `base` ledger
+    pub fn view<'a>(&'a self, base: &'a Ledger) -> OverlayLedger<'a> {
+        OverlayLedger {
+            base,
+            overlay: self,
+        }
+    }
+
+    /// Get a mutable overlaid token account
+    pub fn get_mut_account(
+        &mut self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+    ) -> Option<&mut Account> {
+        self.accounts
+            .get_mut(&(pk.clone(), token.clone()))
+            .map(|(_, account)| account)
+    }
+
+    /// Copy of the `base` ledger with the overlaid accounts applied
+    pub fn materialize(&self, base: &Ledger) -> Ledger {
+        let mut ledger = base.clone();
+        for ((_, token), (_, account)) in self.accounts.iter() {
+            ledger.insert_account(account.clone(), token);
+        }
+
+        ledger
+    }
+
+    /// Apply the best chain extension's ledger diff to the overlay
+    pub fn apply_diff(&mut self, base: &Ledger, diff: &LedgerDiff) {
+        for acct_diff in diff.account_diffs.iter().flatten() {
+            let pk = acct_diff.public_key();
+            let token = acct_diff.token_address();
+            let account = self
+                .view(base)
+                .account(&pk, &token)
+                .cloned()
+                .unwrap_or_else(|| Account::empty(pk.clone(), token.clone()));
+
+            // overflowing & underflowing diffs saturate & are recorded as
+            // anomalies when the best ledger is updated in the store
+            let account = account.apply_account_diff(acct_diff);
+
+            self.accounts
+                .insert((pk, token), (diff.blockchain_length, account));
+        }
+
+        self.tip = diff.state_hash.clone();
+    }
+
+    /// Discard the overlay and reapply the best chain diffs above the
+    /// canonical root, lowest to highest
+    pub fn rebuild<'a, I>(&mut self, base: &Ledger, tip: StateHash, diffs: I)
+    where
+        I: IntoIterator<Item = &'a LedgerDiff>,
+    {
+        self.accounts.clear();
+
+        for diff in diffs {
+            self.apply_diff(base, diff);
+        }
+
+        self.tip = tip;
+    }
+
+    /// Drop the accounts which were last modified at or below the canonical
+    /// root, these modifications have already been applied to the base ledger
+    pub fn prune(&mut self, canonical_root_length: u32) {
+        self.accounts
+            .retain(|_, (length, _)| *length > canonical_root_length);
+    }
+}
+
+impl LedgerView for OverlayLedger<'_> {
+    fn account(&self, pk: &PublicKey, token: &TokenAddress) -> Option<&Account> {
+        self.overlay
+            .accounts
+            .get(&(pk.clone(), token.clone()))
+            .map(|(_, account)| account)
+            .or_else(|| self.base.get_account(pk, token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        base::amount::Amount,
+        ledger::diff::account::{AccountDiff, PaymentDiff, UpdateType},
+    };
+
+    fn payment_diff(blockchain_length: u32, state_hash: &str, pk: &PublicKey) -> LedgerDiff {
+        LedgerDiff {
+            blockchain_length,
+            state_hash: state_hash.into(),
+            account_diffs: vec![vec![AccountDiff::Payment(PaymentDiff {
+                amount: Amount(1),
+                public_key: pk.clone(),
+                update_type: UpdateType::Credit,
+                token: TokenAddress::default(),
+            })]],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn overlay_reads_through_to_base() -> anyhow::Result<()> {
+        let pk0 = "B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy";
+        let pk1 = "B62qmMypEDCchUgPD6RU99gVKXJcY46urKdjbFmG5cYtaVpfKysXTz6";
+        let base = Ledger::from(vec![(pk0, 10, None, None), (pk1, 20, None, None)])?;
+        let (pk0, pk1) = (PublicKey::new(pk0), PublicKey::new(pk1));
+        let mina = TokenAddress::default();
+
+        let mut overlay = LedgerOverlay::default();
+        overlay.apply_diff(&base, &payment_diff(1, "tip", &pk0));
+
+        // overlaid account is modified, the base is untouched
+        assert_eq!(overlay.len(), 1);
+        assert_eq!(overlay.tip, "tip".into());
+        assert_eq!(overlay.view(&base).balance(&pk0, &mina), Some(Amount(11)));
+        assert_eq!(base.balance(&pk0, &mina), Some(Amount(10)));
+
+        // non-overlaid account is read from the base
+        assert_eq!(overlay.view(&base).balance(&pk1, &mina), Some(Amount(20)));
+
+        // materialized ledger matches the view
+        let best_ledger = overlay.materialize(&base);
+        assert_eq!(best_ledger.balance(&pk0, &mina), Some(Amount(11)));
+        assert_eq!(best_ledger.balance(&pk1, &mina), Some(Amount(20)));
+
+        // pruning at or above the modifying block's length drops the account
+        overlay.prune(0);
+        assert_eq!(overlay.len(), 1);
+
+        overlay.prune(1);
+        assert!(overlay.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+//! Parallel application of a block's account diffs
+//!
+//! Account diffs only touch their own (pk, token) account, so diffs of
+//! disjoint accounts commute. A block's expanded account diffs are
+//! partitioned by account & the partitions are applied on worker threads.
+//! Each account's diffs are applied in block order within its partition, so
+//! accounts touched by multiple diff groups (e.g. a fee payer's nonce
+//! increment before its later account updates, or a coinbase receiver which
+//! also pays a fee transfer) see the same sequence as the sequential path.
+
+use super::{
+    account::Account,
+    anomaly::AmountAnomaly,
+    diff::{account::AccountDiff, LedgerDiff},
+    token::TokenAddress,
+    Ledger,
+};
+use crate::{
+    base::{amount::AmountError, public_key::PublicKey},
+    constants::PARALLEL_DIFFS_MIN_ACCOUNT_DIFFS,
+};
+use log::error;
+use std::{collections::HashMap, thread};
+
+/// All of an account's diffs in a block, in block order
+struct AccountPartition<'a> {
+    token: TokenAddress,
+    account: Account,
+
+    /// (block order index, diff)
+    diffs: Vec<(usize, &'a AccountDiff)>,
+}
+
+impl Ledger {
+    /// Apply a ledger diff to a mutable ledger, applying the diffs of each
+    /// account in parallel when there are enough of them
+    ///
+    /// Equivalent to [Ledger::_apply_diff]
+    pub fn _apply_diff_parallel(
+        &mut self,
+        diff: &LedgerDiff,
+    ) -> anyhow::Result<Vec<AmountAnomaly>> {
+        let num_workers = thread::available_parallelism().map_or(1, |n| n.get());
+        let num_diffs = diff.account_diffs.iter().map(Vec::len).sum::<usize>();
+
+        if num_workers < 2 || num_diffs < PARALLEL_DIFFS_MIN_ACCOUNT_DIFFS {
+            return self._apply_diff(diff);
+        }
+
+        self._apply_diff_with_workers(diff, num_workers)
+    }
+
+    /// Apply a ledger diff to a mutable ledger, partitioning its account
+    /// diffs by account across `num_workers` threads
+    pub fn _apply_diff_with_workers(
+        &mut self,
+        diff: &LedgerDiff,
+        num_workers: usize,
+    ) -> anyhow::Result<Vec<AmountAnomaly>> {
+        let mut partitions = self.partition_account_diffs(diff);
+
+        // round-robin the partitions (in first-seen order) across the workers
+        let num_workers = num_workers.clamp(1, partitions.len().max(1));
+        let mut shards: Vec<Vec<&mut AccountPartition>> =
+            (0..num_workers).map(|_| vec![]).collect();
+        for (n, partition) in partitions.iter_mut().enumerate() {
+            shards[n % num_workers].push(partition);
+        }
+
+        let mut failures = thread::scope(|scope| {
+            let handles: Vec<_> = shards
+                .into_iter()
+                .map(|shard| scope.spawn(move || apply_partitions(shard)))
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("account diff worker panicked"))
+                .collect::<Vec<_>>()
+        });
+
+        for partition in partitions {
+            self.insert_account(partition.account, &partition.token);
+        }
+
+        // report anomalies in block order, like the sequential path
+        failures.sort_by_key(|(idx, ..)| *idx);
+
+        let anomalies = failures
+            .into_iter()
+            .map(|(_, acct_diff, e)| {
+                let anomaly = AmountAnomaly::new(&diff.state_hash, acct_diff, e);
+                error!("Saturating account diff {anomaly}");
+                anomaly
+            })
+            .collect();
+
+        Ok(anomalies)
+    }
+
+    /// Removes each account touched by the diff from the ledger, grouping it
+    /// with its diffs in block order
+    fn partition_account_diffs<'a>(&mut self, diff: &'a LedgerDiff) -> Vec<AccountPartition<'a>> {
+        let mut partitions: Vec<AccountPartition> = vec![];
+        let mut partition_idxs: HashMap<(TokenAddress, PublicKey), usize> = HashMap::new();
+
+        for (idx, acct_diff) in diff.account_diffs.iter().flatten().enumerate() {
+            let pk = acct_diff.public_key();
+            let token = acct_diff.token_address();
+
+            if let Some(&n) = partition_idxs.get(&(token.clone(), pk.clone())) {
+                partitions[n].diffs.push((idx, acct_diff));
+                continue;
+            }
+
+            let account = self
+                .tokens
+                .get_mut(&token)
+                .and_then(|token_ledger| token_ledger.accounts.remove(&pk))
+                .unwrap_or_else(|| Account::empty(pk.clone(), token.clone()));
+
+            partition_idxs.insert((token.clone(), pk), partitions.len());
+            partitions.push(AccountPartition {
+                token,
+                account,
+                diffs: vec![(idx, acct_diff)],
+            });
+        }
+
+        partitions
+    }
+}
+
+/// Applies each partition's diffs to its account in order, collecting those
+/// which overflow or underflow the (saturated) balance
+fn apply_partitions<'a>(
+    partitions: Vec<&mut AccountPartition<'a>>,
+) -> Vec<(usize, &'a AccountDiff, AmountError)> {
+    let mut failures = vec![];
+
+    for partition in partitions {
+        for &(idx, acct_diff) in partition.diffs.iter() {
+            if let Err(e) = partition.account.check_account_diff(acct_diff) {
+                failures.push((idx, acct_diff, e));
+            }
+
+            let account = std::mem::take(&mut partition.account);
+            partition.account = account.apply_account_diff(acct_diff);
+        }
+    }
+
+    failures
+}
@@ -1,13 +1,15 @@
 pub mod parser;
 pub mod permissions;
+pub mod rewards;
 
 use crate::{
-    base::{nonce::Nonce, public_key::PublicKey, state_hash::StateHash},
+    base::{amount::Amount, nonce::Nonce, public_key::PublicKey, state_hash::StateHash},
     block::{extract_height_and_hash, extract_network},
     chain::Network,
     constants::MINA_SCALE_DEC,
     ledger::{
         account::{ReceiptChainHash, Timing},
+        token::{TokenAddress, TokenId},
         LedgerHash,
     },
     mina_blocks::v2::ZkappAccount,
@@ -17,6 +19,7 @@ use log::trace;
 use permissions::StakingPermissions;
 use rust_decimal::{prelude::ToPrimitive, Decimal};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::{
     collections::{HashMap, HashSet},
     path::Path,
@@ -38,7 +41,10 @@ pub struct StakingAccount {
     pub balance: u64,
     pub delegate: PublicKey,
     pub username: Option<String>,
-    pub token: Option<u64>,
+
+    /// Pre-hardfork staking ledgers use numeric token ids
+    #[serde(deserialize_with = "deserialize_staking_token")]
+    pub token: TokenAddress,
     pub permissions: StakingPermissions,
     pub receipt_chain_hash: ReceiptChainHash,
     pub voting_for: StateHash,
@@ -51,8 +57,13 @@ pub struct StakingAccount {
 pub struct StakingAccountJson {
     pub pk: PublicKey,
     pub balance: String,
-    pub delegate: PublicKey,
+
+    /// Accounts without a delegate self-delegate
+    #[serde(default)]
+    pub delegate: Option<PublicKey>,
     pub username: Option<String>,
+
+    /// Numeric token id (pre-hardfork) or token address (post-hardfork)
     pub token: String,
     pub permissions: StakingPermissions,
     pub receipt_chain_hash: ReceiptChainHash,
@@ -99,9 +110,72 @@ pub struct AggregatedEpochStakeDelegation {
     pub delegates: Vec<PublicKey>,
 }
 
+/// Delegator's share of its delegate's epoch stake
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpochDelegator {
+    pub pk: PublicKey,
+    pub epoch: u32,
+    pub balance: u64,
+    pub delegate: PublicKey,
+
+    /// Delegate's total delegated stake
+    pub total_delegated: u64,
+
+    /// Delegator's delegate in the previous epoch's staking ledger
+    pub prev_delegate: Option<PublicKey>,
+
+    /// Whether the delegation changed since the previous epoch's staking
+    /// ledger, always `false` if the previous staking ledger is unknown
+    pub delegation_changed: bool,
+}
+
+/// Changes between a stored staking ledger & its replacement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StakingLedgerDiff {
+    pub epoch: u32,
+    pub old_ledger_hash: LedgerHash,
+    pub new_ledger_hash: LedgerHash,
+    pub accounts_added: Vec<PublicKey>,
+    pub accounts_removed: Vec<PublicKey>,
+
+    /// Number of accounts present in both ledgers which differ
+    pub accounts_changed: u32,
+
+    /// Number of accounts present in both ledgers with different balances
+    pub balance_changes: u32,
+
+    /// Number of accounts present in both ledgers with different delegates
+    pub delegate_changes: u32,
+
+    /// Net change in total currency
+    pub total_currency_delta: i128,
+}
+
+impl StakingAccount {
+    /// Whether the account delegates to itself, as accounts without a
+    /// delegate do
+    pub fn is_self_delegated(&self) -> bool {
+        self.delegate == self.pk
+    }
+}
+
+impl EpochDelegator {
+    /// Percentage of the delegate's total delegated stake
+    pub fn stake_percentage(&self) -> Decimal {
+        if self.total_delegated == 0 {
+            return Decimal::ZERO;
+        }
+
+        Decimal::from(self.balance) / Decimal::from(self.total_delegated) * Decimal::from(100)
+    }
+}
+
 impl From<StakingAccountJson> for StakingAccount {
     fn from(value: StakingAccountJson) -> Self {
-        let token = Some(value.token.parse().expect("token is u32"));
+        let token = match value.token.parse::<TokenId>() {
+            Ok(token_id) => token_id.into(),
+            Err(_) => TokenAddress::new(value.token).expect("valid token address"),
+        };
         let nonce = value.nonce.map(Into::into);
         let balance = match value.balance.parse::<Decimal>() {
             Ok(amt) => (amt * MINA_SCALE_DEC)
@@ -133,8 +207,8 @@ impl From<StakingAccountJson> for StakingAccount {
             token,
             timing,
             balance,
+            delegate: value.delegate.unwrap_or_else(|| value.pk.clone()),
             pk: value.pk,
-            delegate: value.delegate,
             username: value.username,
             voting_for: value.voting_for,
             permissions: value.permissions,
@@ -144,6 +218,24 @@ impl From<StakingAccountJson> for StakingAccount {
     }
 }
 
+/// Staking accounts stored before token addresses hold a numeric token id
+fn deserialize_staking_token<'de, D>(deserializer: D) -> Result<TokenAddress, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredToken {
+        Address(TokenAddress),
+        Legacy(Option<u64>),
+    }
+
+    Ok(match StoredToken::deserialize(deserializer)? {
+        StoredToken::Address(token) => token,
+        StoredToken::Legacy(token_id) => token_id.map(TokenId).unwrap_or_default().into(),
+    })
+}
+
 pub fn is_valid_ledger_file(path: &Path) -> bool {
     crate::utility::functions::is_valid_file_name(path, &LedgerHash::is_valid)
 }
@@ -171,7 +263,13 @@ impl StakingLedger {
             .map(|acct| (acct.pk.clone(), acct.into()))
             .collect();
         let (network, epoch, ledger_hash) = split_ledger_path(path);
-        let total_currency: u64 = staking_ledger.values().map(|account| account.balance).sum();
+        let total_currency = Amount::checked_sum(
+            staking_ledger
+                .values()
+                .map(|account| Amount(account.balance)),
+        )
+        .with_context(|| format!("Invalid total currency {}", path.display()))?
+        .0;
         Ok(Self {
             epoch,
             network,
@@ -192,7 +290,7 @@ impl StakingLedger {
                 let balance = staking_account.balance;
                 let delegate = staking_account.delegate.clone();
 
-                if *pk != delegate {
+                if !staking_account.is_self_delegated() {
                     delegations.insert(pk.clone(), None);
                 }
                 match delegations.insert(
@@ -250,12 +348,100 @@ impl StakingLedger {
         })
     }
 
+    /// Time-locked MINA balance of the timed accounts at the global slot
+    pub fn locked_supply(&self, global_slot: u32) -> u64 {
+        let mina = TokenAddress::default();
+        self.staking_ledger
+            .values()
+            .filter(|account| account.token == mina)
+            .filter_map(|account| {
+                account.timing.as_ref().map(|timing| {
+                    timing
+                        .current_minimum_balance(global_slot)
+                        .min(account.balance)
+                })
+            })
+            .sum()
+    }
+
     pub fn summary(&self) -> String {
         format!(
             "{} (epoch {}): {}",
             self.network, self.epoch, self.ledger_hash
         )
     }
+
+    /// Hex-encoded SHA-256 digest of the staking ledger file's contents
+    pub fn content_hash(path: &Path) -> anyhow::Result<String> {
+        Ok(hex::encode(Sha256::digest(std::fs::read(path)?)))
+    }
+
+    /// Changes from `self` to `other`
+    pub fn diff(&self, other: &Self) -> StakingLedgerDiff {
+        let mut accounts_added: Vec<PublicKey> = other
+            .staking_ledger
+            .keys()
+            .filter(|pk| !self.staking_ledger.contains_key(pk))
+            .cloned()
+            .collect();
+        let mut accounts_removed: Vec<PublicKey> = self
+            .staking_ledger
+            .keys()
+            .filter(|pk| !other.staking_ledger.contains_key(pk))
+            .cloned()
+            .collect();
+        accounts_added.sort();
+        accounts_removed.sort();
+
+        let mut accounts_changed = 0;
+        let mut balance_changes = 0;
+        let mut delegate_changes = 0;
+        for (pk, account) in self.staking_ledger.iter() {
+            if let Some(other_account) = other.staking_ledger.get(pk) {
+                accounts_changed += (account != other_account) as u32;
+                balance_changes += (account.balance != other_account.balance) as u32;
+                delegate_changes += (account.delegate != other_account.delegate) as u32;
+            }
+        }
+
+        StakingLedgerDiff {
+            epoch: other.epoch,
+            old_ledger_hash: self.ledger_hash.clone(),
+            new_ledger_hash: other.ledger_hash.clone(),
+            accounts_added,
+            accounts_removed,
+            accounts_changed,
+            balance_changes,
+            delegate_changes,
+            total_currency_delta: other.total_currency as i128 - self.total_currency as i128,
+        }
+    }
+}
+
+impl StakingLedgerDiff {
+    /// Whether the ledgers are identical
+    pub fn is_empty(&self) -> bool {
+        self.old_ledger_hash == self.new_ledger_hash
+            && self.accounts_added.is_empty()
+            && self.accounts_removed.is_empty()
+            && self.accounts_changed == 0
+            && self.total_currency_delta == 0
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "epoch {} {} -> {}: {} accounts added, {} removed, {} changed ({} balance changes, {} delegate changes), total currency delta {} nanomina",
+            self.epoch,
+            self.old_ledger_hash,
+            self.new_ledger_hash,
+            self.accounts_added.len(),
+            self.accounts_removed.len(),
+            self.accounts_changed,
+            self.balance_changes,
+            self.delegate_changes,
+            self.total_currency_delta,
+        )
+    }
 }
 
 impl From<String> for LedgerHash {
@@ -0,0 +1,275 @@
+//! Epoch reward & payout report
+//!
+//! Rolls up a delegate's canonical blocks of an epoch & splits the income
+//! among the delegators of the epoch's staking ledger, pro rata by balance:
+//! - income is the coinbase & transaction fees, less SNARK fees
+//! - the supercharged half of a supercharged coinbase is only shared among
+//!   the delegators unlocked at the block's global slot (all delegators if
+//!   none are)
+//!
+//! Payouts are rounded down, so their sum may fall short of the income by a
+//! few nanomina.
+
+use crate::{
+    base::{public_key::PublicKey, state_hash::StateHash},
+    block::store::BlockStore,
+    canonicity::store::CanonicityStore,
+    ledger::{coinbase::Coinbase, store::staking::StakingLedgerStore},
+    store::IndexerStore,
+};
+use serde::{Deserialize, Serialize};
+use speedb::Direction;
+
+/// Delegate's rewards of the epoch's canonical blocks (nanomina)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EpochRewards {
+    pub epoch: u32,
+    pub delegate: PublicKey,
+    pub num_blocks: u32,
+    pub num_supercharged: u32,
+
+    /// Coinbase, less the supercharged halves
+    pub coinbase: u64,
+
+    /// Supercharged halves of the supercharged coinbases
+    pub supercharged_coinbase: u64,
+
+    pub tx_fees: u64,
+    pub snark_fees: u64,
+
+    /// Coinbase & transaction fees, less SNARK fees
+    pub total_income: u64,
+
+    /// Delegate's total delegated stake, 0 without the epoch's staking ledger
+    pub total_delegated: u64,
+
+    pub blocks: Vec<BlockReward>,
+
+    /// Delegators' payouts, by descending balance
+    pub delegators: Vec<DelegatorPayout>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockReward {
+    pub state_hash: StateHash,
+    pub blockchain_length: u32,
+    pub global_slot: u32,
+    pub coinbase: u64,
+    pub supercharged: bool,
+    pub tx_fees: u64,
+    pub snark_fees: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DelegatorPayout {
+    pub pk: PublicKey,
+    pub balance: u64,
+
+    /// Fraction of the delegate's total delegated stake
+    pub share: f64,
+
+    /// Total payout, including the supercharged payout
+    pub payout: u64,
+
+    /// Share of the supercharged coinbases
+    pub supercharged_payout: u64,
+}
+
+impl IndexerStore {
+    /// Reward & payout report of the delegate's canonical blocks in the epoch
+    pub fn get_epoch_rewards(
+        &self,
+        epoch: u32,
+        delegate: &PublicKey,
+        genesis_state_hash: Option<&StateHash>,
+    ) -> anyhow::Result<EpochRewards> {
+        let mut rewards = EpochRewards {
+            epoch,
+            delegate: delegate.clone(),
+            num_blocks: 0,
+            num_supercharged: 0,
+            coinbase: 0,
+            supercharged_coinbase: 0,
+            tx_fees: 0,
+            snark_fees: 0,
+            total_income: 0,
+            total_delegated: 0,
+            blocks: vec![],
+            delegators: vec![],
+        };
+
+        // delegate's canonical blocks
+        let heights = self
+            .get_epoch_supply(epoch)?
+            .map(|supply| supply.heights)
+            .unwrap_or_default();
+        for height in heights {
+            let Some(state_hash) = self.get_canonical_hash_at_height(height)? else {
+                continue;
+            };
+
+            if self.get_block_creator(&state_hash)?.as_ref() != Some(delegate) {
+                continue;
+            }
+
+            let Some((block, _)) = self.get_block(&state_hash)? else {
+                continue;
+            };
+
+            let coinbase = Coinbase::from_precomputed(&block);
+            let (coinbase, supercharged) = if coinbase.is_coinbase_applied() {
+                (coinbase.amount(), coinbase.supercharge)
+            } else {
+                (0, false)
+            };
+
+            rewards.blocks.push(BlockReward {
+                state_hash,
+                blockchain_length: height,
+                global_slot: block.global_slot_since_genesis(),
+                coinbase,
+                supercharged,
+                tx_fees: block.tx_fees(),
+                snark_fees: block.snark_fees(),
+            });
+        }
+
+        for block in rewards.blocks.iter() {
+            let bonus = supercharged_half(block);
+            rewards.num_blocks += 1;
+            rewards.num_supercharged += block.supercharged as u32;
+            rewards.coinbase += block.coinbase - bonus;
+            rewards.supercharged_coinbase += bonus;
+            rewards.tx_fees += block.tx_fees;
+            rewards.snark_fees += block.snark_fees;
+        }
+        rewards.total_income = (rewards.coinbase + rewards.supercharged_coinbase + rewards.tx_fees)
+            .saturating_sub(rewards.snark_fees);
+
+        // delegators of the epoch's staking ledger
+        let delegators = self.get_epoch_delegators(
+            delegate,
+            epoch,
+            genesis_state_hash,
+            None,
+            Direction::Reverse,
+            usize::MAX,
+        )?;
+        let mut timings = Vec::with_capacity(delegators.len());
+        for delegator in delegators.iter() {
+            timings.push(
+                self.get_staking_account(&delegator.pk, epoch, genesis_state_hash)?
+                    .and_then(|account| account.timing),
+            );
+        }
+
+        rewards.total_delegated = delegators.iter().map(|delegator| delegator.balance).sum();
+        rewards.delegators = delegators
+            .iter()
+            .map(|delegator| DelegatorPayout {
+                pk: delegator.pk.clone(),
+                balance: delegator.balance,
+                share: if rewards.total_delegated == 0 {
+                    0.0
+                } else {
+                    delegator.balance as f64 / rewards.total_delegated as f64
+                },
+                payout: 0,
+                supercharged_payout: 0,
+            })
+            .collect();
+
+        for block in rewards.blocks.iter() {
+            let bonus = supercharged_half(block);
+            let income = (block.coinbase - bonus + block.tx_fees).saturating_sub(block.snark_fees);
+
+            // supercharged halves go to the delegators unlocked at the block
+            let unlocked: Vec<bool> = timings
+                .iter()
+                .map(|timing| {
+                    timing.as_ref().map_or(true, |timing| {
+                        timing.current_minimum_balance(block.global_slot) == 0
+                    })
+                })
+                .collect();
+            let unlocked_stake: u64 = rewards
+                .delegators
+                .iter()
+                .zip(unlocked.iter())
+                .filter_map(|(delegator, unlocked)| unlocked.then_some(delegator.balance))
+                .sum();
+
+            for (delegator, unlocked) in rewards.delegators.iter_mut().zip(unlocked) {
+                delegator.payout += pro_rata(income, delegator.balance, rewards.total_delegated);
+
+                let bonus = if unlocked_stake == 0 {
+                    pro_rata(bonus, delegator.balance, rewards.total_delegated)
+                } else if unlocked {
+                    pro_rata(bonus, delegator.balance, unlocked_stake)
+                } else {
+                    0
+                };
+                delegator.payout += bonus;
+                delegator.supercharged_payout += bonus;
+            }
+        }
+
+        Ok(rewards)
+    }
+}
+
+impl EpochRewards {
+    /// Delegator payouts as CSV
+    pub fn delegators_csv(&self) -> anyhow::Result<String> {
+        let mut csv_writer = csv::WriterBuilder::new().from_writer(vec![]);
+        csv_writer.write_record([
+            "public_key",
+            "balance",
+            "share",
+            "payout",
+            "supercharged_payout",
+        ])?;
+
+        for delegator in self.delegators.iter() {
+            csv_writer.write_record([
+                delegator.pk.0.clone(),
+                delegator.balance.to_string(),
+                delegator.share.to_string(),
+                delegator.payout.to_string(),
+                delegator.supercharged_payout.to_string(),
+            ])?;
+        }
+
+        Ok(String::from_utf8(csv_writer.into_inner()?)?)
+    }
+}
+
+/// Supercharged half of the block's coinbase, 0 if not supercharged
+fn supercharged_half(block: &BlockReward) -> u64 {
+    if block.supercharged {
+        block.coinbase / 2
+    } else {
+        0
+    }
+}
+
+/// `amount * stake / total`, rounded down
+fn pro_rata(amount: u64, stake: u64, total: u64) -> u64 {
+    if total == 0 {
+        return 0;
+    }
+    (amount as u128 * stake as u128 / total as u128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pro_rata_rounds_down() {
+        assert_eq!(pro_rata(720_000_000_000, 1, 3), 240_000_000_000);
+        assert_eq!(pro_rata(10, 1, 3), 3);
+        assert_eq!(pro_rata(10, 1, 0), 0);
+        assert_eq!(pro_rata(u64::MAX, u64::MAX, u64::MAX), u64::MAX);
+    }
+}
@@ -0,0 +1,16 @@
+//! Store of ledger amount anomalies
+
+use crate::{base::state_hash::StateHash, ledger::anomaly::AmountAnomaly};
+
+pub trait AmountAnomalyStore {
+    /// Record an amount anomaly, after the block's other anomalies. Recording
+    /// the same anomaly again is a no-op
+    fn add_amount_anomaly(&self, anomaly: &AmountAnomaly) -> anyhow::Result<()>;
+
+    /// Get the recorded amount anomalies, optionally only those of the given
+    /// block
+    fn get_amount_anomalies(
+        &self,
+        state_hash: Option<&StateHash>,
+    ) -> anyhow::Result<Vec<AmountAnomaly>>;
+}
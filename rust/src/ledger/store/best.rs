@@ -3,9 +3,15 @@
 use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
     block::store::DbBlockUpdate,
-    ledger::{account::Account, diff::account::AccountDiff, token::TokenAddress, Ledger},
+    ledger::{
+        account::Account,
+        diff::account::AccountDiff,
+        token::{TokenAddress, TokenSymbol},
+        Ledger,
+    },
     store::{DbUpdate, Result},
 };
+use serde::{Deserialize, Serialize};
 use speedb::{DBIterator, IteratorMode};
 use std::collections::HashSet;
 
@@ -23,6 +29,10 @@ pub trait BestLedgerStore {
         token: &TokenAddress,
     ) -> Result<Option<Account>>;
 
+    /// Mark the best ledger accounts created by the canonical block as no
+    /// longer pending, see [AccountStatus]
+    fn set_best_accounts_canonical(&self, state_hash: &StateHash) -> Result<()>;
+
     /// Update the best ledger token account
     fn update_best_account(
         &self,
@@ -42,6 +52,13 @@ pub trait BestLedgerStore {
         blocks: &DbBlockUpdate,
     ) -> Result<()>;
 
+    /// Unapply the best block from the best ledger & the rest of the
+    /// best chain data, making its parent the best block
+    ///
+    /// Returns the parent's state hash. Errors if the block isn't the best
+    /// block.
+    fn unapply_block(&self, state_hash: &StateHash) -> Result<StateHash>;
+
     /// Remove a delegation
     fn remove_pk_delegate(&self, pk: PublicKey) -> Result<()>;
 
@@ -54,6 +71,10 @@ pub trait BestLedgerStore {
     /// Get the `idx`-th delegation
     fn get_pk_delegation(&self, pk: &PublicKey, idx: u32) -> Result<Option<PublicKey>>;
 
+    /// Get the account's delegation history, oldest first (empty if the
+    /// account never delegated)
+    fn get_pk_delegations(&self, pk: &PublicKey) -> Result<Vec<PublicKey>>;
+
     /// Update the count of best ledger accounts
     fn update_num_accounts(&self, adjust: i32) -> Result<()>;
 
@@ -66,6 +87,147 @@ pub trait BestLedgerStore {
     /// Get the best ledger
     fn get_best_ledger(&self, memoize: bool) -> Result<Option<Ledger>>;
 
+    /// Set the token account's balance change applied by the best chain
+    /// block at `height`
+    fn set_best_account_balance_history(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+        entry: &BalanceHistoryEntry,
+    ) -> Result<()>;
+
+    /// Remove the token account's balance change applied by the best chain
+    /// block at `height`
+    fn remove_best_account_balance_history(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<()>;
+
+    /// Get the token account's balance change applied by the best chain
+    /// block at `height`
+    fn get_best_account_balance_history(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<BalanceHistoryEntry>>;
+
+    /// Get up to `limit` of the token account's balance changes applied by
+    /// the best chain blocks from `start_height` to `end_height`
+    /// (inclusive), ascending by height
+    ///
+    /// Blocks which didn't change the account's balance have no entry
+    fn get_best_account_balance_history_range(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        start_height: u32,
+        end_height: u32,
+        limit: usize,
+    ) -> Result<Vec<(u32, BalanceHistoryEntry)>>;
+
+    /// Get the token account's balance as of the best chain block at
+    /// `height` (`None` if the account didn't exist)
+    ///
+    /// Uses the latest balance history entry at or below `height`. Without
+    /// one, the account's balance before its earliest later change applies &
+    /// accounts without balance history are unchanged since genesis.
+    fn get_best_account_balance_at(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<u64>>;
+
+    /// Set the token account's nonce change applied by the best chain block
+    /// at `height`
+    fn set_best_account_nonce_history(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+        entry: &NonceHistoryEntry,
+    ) -> Result<()>;
+
+    /// Remove the token account's nonce change applied by the best chain
+    /// block at `height`
+    fn remove_best_account_nonce_history(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<()>;
+
+    /// Get the token account's nonce change applied by the best chain block
+    /// at `height`
+    fn get_best_account_nonce_history(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<NonceHistoryEntry>>;
+
+    /// Get the token account's nonce as of the best chain block at `height`
+    /// (`None` if the account didn't exist)
+    ///
+    /// Resolved from the nonce history like
+    /// [Self::get_best_account_balance_at]
+    fn get_best_account_nonce_at(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<u32>>;
+
+    /// Get the token account's balance as of the last canonical block of
+    /// each epoch from `from_epoch` to `to_epoch` (inclusive, up to the best
+    /// tip's epoch)
+    ///
+    /// Balances are resolved like [Self::get_best_account_balance_at], in a
+    /// single pass over the account's balance history
+    fn get_best_account_epoch_balances(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        from_epoch: u32,
+        to_epoch: u32,
+    ) -> Result<Vec<EpochBalance>>;
+
+    /// Get the token's holders with balances of at least `min_balance`,
+    /// ordered by public key & starting after `cursor`
+    ///
+    /// With `at_height`, balances are as of the best chain block at that
+    /// height, otherwise they're the current balances
+    fn get_token_holders(
+        &self,
+        token: &TokenAddress,
+        at_height: Option<u32>,
+        min_balance: u64,
+        cursor: Option<&PublicKey>,
+        limit: usize,
+    ) -> Result<Vec<(PublicKey, u64)>>;
+
+    /// Get up to `limit` of the custom token's transfers, i.e. its accounts'
+    /// balance changes applied by best chain blocks, by block height &
+    /// public key
+    ///
+    /// MINA transfers aren't recorded, see the user command store
+    fn get_token_transfers(
+        &self,
+        token: &TokenAddress,
+        descending: bool,
+        limit: usize,
+    ) -> Result<Vec<(u32, PublicKey, BalanceHistoryEntry)>>;
+
+    /// Get the symbols of the given tokens, in order
+    ///
+    /// The default token is always MINA. Other tokens use their owner's best
+    /// ledger account symbol & are `None` if it was never set.
+    fn get_token_symbols(&self, tokens: &[TokenAddress]) -> Result<Vec<Option<TokenSymbol>>>;
+
     ///////////////
     // Iterators //
     ///////////////
@@ -89,6 +251,90 @@ pub trait BestLedgerStore {
     fn zkapp_best_ledger_account_balance_iterator(&self, mode: IteratorMode) -> DBIterator<'_>;
 }
 
+/// Point reads used to resolve an [AccountStatus]
+pub trait AccountStatusReads {
+    /// Get whether the best ledger token account's creation is pending, i.e.
+    /// it was created by a best chain block above the canonical root, without
+    /// deserializing the account (`None` if the account doesn't exist)
+    fn get_best_account_pending(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+    ) -> Result<Option<bool>>;
+
+    /// Whether the best ledger token account is a zkapp account, i.e. it has a
+    /// verification key
+    fn is_best_account_zkapp(&self, pk: &PublicKey, token: &TokenAddress) -> Result<bool>;
+}
+
+/// Best tip existence & zkapp status of a token account
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AccountStatus {
+    pub exists: bool,
+    pub is_zkapp: bool,
+
+    /// The account only exists due to best chain blocks above the canonical
+    /// root
+    pub pending_only: bool,
+}
+
+impl AccountStatus {
+    /// Resolves the account status with at most two point reads
+    pub fn resolve<S>(store: &S, pk: &PublicKey, token: &TokenAddress) -> Result<Self>
+    where
+        S: AccountStatusReads + ?Sized,
+    {
+        let pending = match store.get_best_account_pending(pk, token)? {
+            Some(pending) => pending,
+            None => return Ok(Self::default()),
+        };
+
+        Ok(Self {
+            exists: true,
+            is_zkapp: store.is_best_account_zkapp(pk, token)?,
+            pending_only: pending,
+        })
+    }
+
+    /// Sending to a non-existent account charges the account creation fee
+    pub fn will_charge_creation_fee(&self) -> bool {
+        !self.exists
+    }
+}
+
+/// Stored balance history of a token account at a best chain block height
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceHistoryEntry {
+    /// Balance before the block (`None` if the block created the account)
+    pub before: Option<u64>,
+
+    /// Balance after the block
+    pub after: u64,
+}
+
+/// Token account nonce change applied by a best chain block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonceHistoryEntry {
+    /// Nonce before the block (`None` if the block created the account)
+    pub before: Option<u32>,
+
+    /// Nonce after the block
+    pub after: u32,
+}
+
+/// Token account balance as of the last canonical block of an epoch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpochBalance {
+    pub epoch: u32,
+
+    /// Height & state hash of the epoch's last canonical block (`None` if the
+    /// epoch has no canonical blocks)
+    pub boundary: Option<(u32, StateHash)>,
+
+    /// Balance at the boundary block (`None` if the account didn't exist)
+    pub balance: Option<u64>,
+}
+
 /// Applied & unapplied block account diffs & new block accounts
 type AccountUpdate = (Vec<AccountDiff>, HashSet<(PublicKey, TokenAddress)>);
 pub type DbAccountUpdate = DbUpdate<AccountUpdate>;
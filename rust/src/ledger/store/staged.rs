@@ -56,6 +56,14 @@ pub trait StagedLedgerStore {
         memoize: bool,
     ) -> anyhow::Result<Option<Ledger>>;
 
+    /// Get the (canonical) ledger at a specified global slot, i.e. the ledger
+    /// of the highest canonical block at or before the slot
+    fn get_staged_ledger_at_slot(
+        &self,
+        global_slot: u32,
+        memoize: bool,
+    ) -> anyhow::Result<Option<Ledger>>;
+
     /// Set `pk`'s `state_hash` staged ledger `account` & balance-sort data
     fn set_staged_account(
         &self,
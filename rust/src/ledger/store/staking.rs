@@ -4,12 +4,13 @@ use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
     ledger::{
         staking::{
-            AggregatedEpochStakeDelegations, EpochStakeDelegation, StakingAccount, StakingLedger,
+            AggregatedEpochStakeDelegations, EpochDelegator, EpochStakeDelegation, StakingAccount,
+            StakingLedger, StakingLedgerDiff,
         },
         LedgerHash,
     },
 };
-use speedb::{DBIterator, Direction, IteratorMode};
+use speedb::{DBIterator, Direction, IteratorMode, WriteBatch};
 
 pub trait StakingLedgerStore {
     /// Get `pk`'s `epoch` staking ledger account
@@ -30,13 +31,86 @@ pub trait StakingLedgerStore {
         staking_account_with_delegation: StakingAccountWithEpochDelegation,
     ) -> anyhow::Result<()>;
 
-    /// Add a staking ledger
+    /// Batch set `pk`'s staking ledger account
+    fn set_staking_account_batch(
+        &self,
+        pk: &PublicKey,
+        epoch: u32,
+        ledger_hash: &LedgerHash,
+        genesis_state_hash: &StateHash,
+        staking_account_with_delegation: &StakingAccountWithEpochDelegation,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()>;
+
+    /// Add a staking ledger, resuming its interrupted ingestion from the
+    /// epoch's checkpoint
     fn add_staking_ledger(
         &self,
         staking_ledger: StakingLedger,
         genesis_state_hash: &StateHash,
     ) -> anyhow::Result<()>;
 
+    /// Atomically replace the stored staking ledger for the same epoch with
+    /// `staking_ledger`, rewriting all accounts, aggregated delegations &
+    /// the file record along with the replacement events
+    ///
+    /// Returns the diff from the stored ledger to `staking_ledger`
+    fn replace_staking_ledger(
+        &self,
+        staking_ledger: StakingLedger,
+        genesis_state_hash: &StateHash,
+        content_hash: &str,
+        file: &StakingLedgerFile,
+    ) -> anyhow::Result<StakingLedgerDiff>;
+
+    /// Set the content hash of the file the epoch's staking ledger was
+    /// ingested from
+    fn set_staking_ledger_content_hash(
+        &self,
+        epoch: u32,
+        genesis_state_hash: &StateHash,
+        content_hash: &str,
+    ) -> anyhow::Result<()>;
+
+    /// Get the content hash of the file the epoch's staking ledger was
+    /// ingested from
+    fn get_staking_ledger_content_hash(
+        &self,
+        epoch: u32,
+        genesis_state_hash: &StateHash,
+    ) -> anyhow::Result<Option<String>>;
+
+    /// Set the size & modification time of the file the epoch's staking
+    /// ledger was ingested from
+    fn set_staking_ledger_file(
+        &self,
+        epoch: u32,
+        genesis_state_hash: &StateHash,
+        file: &StakingLedgerFile,
+    ) -> anyhow::Result<()>;
+
+    /// Get the size & modification time of the file the epoch's staking
+    /// ledger was ingested from
+    fn get_staking_ledger_file(
+        &self,
+        epoch: u32,
+        genesis_state_hash: &StateHash,
+    ) -> anyhow::Result<Option<StakingLedgerFile>>;
+
+    /// Get the epoch's staking ledger ingestion checkpoint, only present
+    /// while its ingestion is incomplete
+    fn get_staking_ledger_checkpoint(
+        &self,
+        epoch: u32,
+        genesis_state_hash: &StateHash,
+    ) -> anyhow::Result<Option<StakingLedgerCheckpoint>>;
+
+    /// Get the highest epoch with a fully ingested staking ledger
+    fn get_last_staking_ledger_epoch(
+        &self,
+        genesis_state_hash: &StateHash,
+    ) -> anyhow::Result<Option<u32>>;
+
     /// Get the staking ledger with the given hash & epoch
     fn get_staking_ledger(
         &self,
@@ -55,6 +129,22 @@ pub trait StakingLedgerStore {
         genesis_state_hash: Option<&StateHash>,
     ) -> anyhow::Result<Option<EpochStakeDelegation>>;
 
+    /// Get `delegate`'s delegators for the given epoch, sorted by balance,
+    /// starting after the `cursor` delegator
+    ///
+    /// Each delegator's delegation is compared to the previous epoch's
+    /// staking ledger. If no genesis state hash is provided, default to
+    /// current network
+    fn get_epoch_delegators(
+        &self,
+        delegate: &PublicKey,
+        epoch: u32,
+        genesis_state_hash: Option<&StateHash>,
+        cursor: Option<&PublicKey>,
+        direction: Direction,
+        limit: usize,
+    ) -> anyhow::Result<Vec<EpochDelegator>>;
+
     /// Set the epoch number corresponding to the given staking ledger hash
     fn set_staking_ledger_hash_epoch_pair(
         &self,
@@ -128,23 +218,34 @@ pub trait StakingLedgerStore {
     // Iterators //
     ///////////////
 
-    /// Per epoch staking ledger account iterator via balance
+    /// Per flattened epoch staking ledger account iterator via balance
     /// ```
     /// key: [staking_ledger_sort_key]
     /// val: b""
     fn staking_ledger_account_balance_iterator(
         &self,
-        epoch: u32,
+        flat_epoch: u32,
         direction: Direction,
     ) -> DBIterator<'_>;
 
-    /// Per epoch staking ledger account iterator via stake (total delegations)
+    /// Per flattened epoch staking ledger account iterator via stake (total delegations)
     /// ```
     /// key: [staking_ledger_sort_key]
     /// val: b""
     fn staking_ledger_account_stake_iterator(
         &self,
-        epoch: u32,
+        flat_epoch: u32,
+        direction: Direction,
+    ) -> DBIterator<'_>;
+
+    /// Per flattened epoch delegate's staking ledger delegator iterator via balance
+    /// ```
+    /// key: [staking_ledger_delegate_sort_key]
+    /// val: b""
+    fn staking_ledger_delegate_balance_iterator(
+        &self,
+        flat_epoch: u32,
+        delegate: &PublicKey,
         direction: Direction,
     ) -> DBIterator<'_>;
 
@@ -160,3 +261,51 @@ pub struct StakingAccountWithEpochDelegation {
     pub account: StakingAccount,
     pub delegation: EpochStakeDelegation,
 }
+
+/// Size & modification time of the file an epoch's staking ledger was
+/// ingested from, the file is only content hashed when these change
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct StakingLedgerFile {
+    pub len: u64,
+
+    /// Milliseconds since the Unix epoch
+    pub modified: u64,
+}
+
+impl StakingLedgerFile {
+    pub fn new(path: &std::path::Path) -> anyhow::Result<Self> {
+        let metadata = std::fs::metadata(path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis() as u64;
+
+        Ok(Self {
+            len: metadata.len(),
+            modified,
+        })
+    }
+}
+
+/// How a staking ledger file compares to the one its epoch's stored ledger
+/// was ingested from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StakingLedgerFileStatus {
+    /// The epoch has no stored staking ledger
+    New,
+
+    /// The epoch's staking ledger was ingested from the same file contents
+    Unchanged,
+
+    /// The epoch's staking ledger was ingested from different file contents
+    Changed { content_hash: String },
+}
+
+/// Progress of an incomplete staking ledger ingestion
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct StakingLedgerCheckpoint {
+    pub ledger_hash: LedgerHash,
+
+    /// Number of accounts written, in public key order
+    pub num_accounts: u32,
+}
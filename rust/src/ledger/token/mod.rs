@@ -1,9 +1,11 @@
-//! Token address, id, & symbol
+//! Token address, id, symbol, & provenance
 
 mod address;
 mod id;
 mod symbol;
 
+pub mod provenance;
+
 use crate::base::{amount::Amount, public_key::PublicKey};
 use serde::{Deserialize, Serialize};
 
@@ -0,0 +1,26 @@
+//! Provenance of the tokens used in ingested blocks
+
+use super::TokenAddress;
+use crate::base::state_hash::StateHash;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProvenanceStatus {
+    /// The token's owner is known
+    Known,
+
+    /// The token was used before its owner was seen, e.g. the owner was
+    /// created in an orphaned block or outside the ingested range
+    Unknown,
+}
+
+/// Registry entry of a token, recorded when it's first used
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenProvenance {
+    pub token: TokenAddress,
+    pub provenance: ProvenanceStatus,
+
+    /// Block the token was first used in
+    pub first_seen_state_hash: StateHash,
+    pub first_seen_blockchain_length: u32,
+}
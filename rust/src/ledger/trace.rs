@@ -0,0 +1,86 @@
+//! Tracing of the canonical ledger diffs which touch specific accounts
+
+use super::{
+    diff::{account::AccountDiff, LedgerDiff},
+    Ledger,
+};
+use crate::base::public_key::PublicKey;
+use log::info;
+use std::collections::HashSet;
+
+/// Public keys whose canonical ledger diffs are logged
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TracedAccounts(HashSet<PublicKey>);
+
+impl TracedAccounts {
+    /// Parses & validates the traced public keys
+    pub fn new<S: AsRef<str>>(pks: &[S]) -> anyhow::Result<Option<Self>> {
+        let mut traced = HashSet::new();
+        for pk in pks.iter().map(AsRef::as_ref).map(str::trim) {
+            if pk.is_empty() {
+                continue;
+            }
+            if !PublicKey::is_valid(pk) {
+                anyhow::bail!("Invalid traced account public key: {pk}");
+            }
+            traced.insert(PublicKey::from(pk));
+        }
+
+        Ok((!traced.is_empty()).then_some(Self(traced)))
+    }
+
+    pub fn contains(&self, pk: &PublicKey) -> bool {
+        self.0.contains(pk)
+    }
+
+    /// Trace lines of the traced account diffs of `diff`, with the balances
+    /// of `ledger` (the ledger after application)
+    pub fn traces(&self, diff: &LedgerDiff, ledger: &Ledger) -> Vec<String> {
+        diff.account_diffs
+            .iter()
+            .flatten()
+            .filter_map(|account_diff| {
+                let pk = account_diff.public_key();
+                if !self.contains(&pk) {
+                    return None;
+                }
+
+                let token = account_diff.token_address();
+                let balance = ledger
+                    .get_account(&pk, &token)
+                    .map_or("none".to_string(), |account| account.balance.0.to_string());
+
+                Some(format!(
+                    "trace pk={pk} height={} state_hash={} diff={} amount={} balance={balance} token={token}",
+                    diff.blockchain_length,
+                    diff.state_hash,
+                    account_diff.kind(),
+                    amount(account_diff),
+                ))
+            })
+            .collect()
+    }
+
+    /// Logs the traced account diffs of `diff`
+    pub fn log(&self, diff: &LedgerDiff, ledger: &Ledger) {
+        for trace in self.traces(diff, ledger) {
+            info!("{trace}");
+        }
+    }
+}
+
+/// Signed amount of the diff, zkapp account updates have none
+fn amount(diff: &AccountDiff) -> i64 {
+    use AccountDiff::*;
+
+    match diff {
+        Payment(_)
+        | Delegation(_)
+        | Coinbase(_)
+        | FeeTransfer(_)
+        | FeeTransferViaCoinbase(_)
+        | FailedTransactionNonce(_)
+        | ZkappAccountCreationFee(_) => diff.amount(),
+        _ => 0,
+    }
+}
@@ -0,0 +1,220 @@
+//! Replay verification of the canonical chain's staged ledgers
+//!
+//! Recomputes the staged ledgers by applying each canonical block's ledger
+//! diff, derived from the block itself, without writing to the store. At each
+//! ledger cadence point the block-declared staged ledger hash is the
+//! checkpoint: the store must associate it with the block & the store's
+//! staged ledger for the block must match the replayed one.
+//!
+//! The indexer doesn't compute ledger merkle hashes, so checkpoint ledgers
+//! are compared account by account.
+
+use super::{
+    diff::LedgerDiff, hash::LedgerHash, store::staged::StagedLedgerStore, token::TokenAddress,
+    Ledger,
+};
+use crate::{
+    base::{public_key::PublicKey, state_hash::StateHash},
+    block::store::BlockStore,
+    canonicity::store::CanonicityStore,
+    crash,
+    store::IndexerStore,
+};
+use anyhow::{bail, Context};
+use log::{info, trace};
+use std::panic::AssertUnwindSafe;
+
+/// Replayed block which disagrees with the store
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayMismatch {
+    /// Applying the block's ledger diff failed, the replay stops here
+    ApplyFailed {
+        height: u32,
+        state_hash: StateHash,
+        error: String,
+    },
+
+    /// Store's staged ledger hash for the block isn't the block-declared one
+    LedgerHashMismatch {
+        height: u32,
+        state_hash: StateHash,
+        declared: LedgerHash,
+        stored: Option<LedgerHash>,
+    },
+
+    /// Store has no staged ledger for the block
+    MissingLedger { height: u32, state_hash: StateHash },
+
+    /// Store's staged ledger for the block differs from the replayed one
+    LedgerMismatch {
+        height: u32,
+        state_hash: StateHash,
+        ledger_hash: LedgerHash,
+
+        /// Accounts which differ or are missing from either ledger
+        accounts: Vec<(TokenAddress, PublicKey)>,
+    },
+}
+
+/// Summary of a staged ledger replay verification
+#[derive(Debug, Default)]
+pub struct ReplayVerificationReport {
+    pub num_blocks: u32,
+    pub num_checkpoints: u32,
+    pub mismatches: Vec<ReplayMismatch>,
+}
+
+impl IndexerStore {
+    /// Replay the canonical chain above `from_height`, starting from the
+    /// store's staged ledger at `from_height` & verifying every
+    /// `ledger_cadence` heights
+    pub fn verify_replay(
+        &self,
+        from_height: u32,
+        ledger_cadence: u32,
+    ) -> anyhow::Result<ReplayVerificationReport> {
+        if ledger_cadence == 0 {
+            bail!("Invalid ledger cadence 0")
+        }
+
+        let mut report = ReplayVerificationReport::default();
+        let mut ledger = self
+            .get_staged_ledger_at_block_height(from_height, false)?
+            .with_context(|| format!("Missing staged ledger at height {from_height}"))?;
+
+        let mut height = from_height + 1;
+        while let Some(state_hash) = self.get_canonical_hash_at_height(height)? {
+            let (block, _) = self
+                .get_block(&state_hash)?
+                .with_context(|| format!("Block missing from store {state_hash}"))?;
+
+            // apply failures are often panics deep in the diff application
+            let diff = LedgerDiff::from_precomputed(&block);
+            let applied = match crash::catch_unwind(AssertUnwindSafe(|| ledger._apply_diff(&diff)))
+            {
+                Ok(result) => result.map_err(|e| e.to_string()),
+                Err(panic) => Err(panic_message(panic)),
+            };
+
+            report.num_blocks += 1;
+            if let Err(error) = applied {
+                report.mismatches.push(ReplayMismatch::ApplyFailed {
+                    height,
+                    state_hash,
+                    error,
+                });
+                break;
+            }
+
+            if height % ledger_cadence == 0 {
+                trace!("Verifying staged ledger checkpoint {state_hash} at height {height}");
+                report.num_checkpoints += 1;
+
+                let declared = block.staged_ledger_hash();
+                let stored = self.get_block_staged_ledger_hash(&state_hash)?;
+                if stored.as_ref() != Some(&declared) {
+                    report.mismatches.push(ReplayMismatch::LedgerHashMismatch {
+                        height,
+                        state_hash: state_hash.clone(),
+                        declared: declared.clone(),
+                        stored,
+                    });
+                }
+
+                match self.get_staged_ledger_at_state_hash(&state_hash, false)? {
+                    Some(stored_ledger) => {
+                        let accounts = ledger_differences(&ledger, &stored_ledger);
+                        if !accounts.is_empty() {
+                            report.mismatches.push(ReplayMismatch::LedgerMismatch {
+                                height,
+                                state_hash,
+                                ledger_hash: declared,
+                                accounts,
+                            });
+                        }
+                    }
+                    None => report
+                        .mismatches
+                        .push(ReplayMismatch::MissingLedger { height, state_hash }),
+                }
+            }
+
+            height += 1;
+        }
+
+        info!(
+            "Replayed {} canonical blocks above height {from_height}",
+            report.num_blocks
+        );
+        Ok(report)
+    }
+}
+
+/// Accounts which differ between the ledgers, sorted
+fn ledger_differences(replayed: &Ledger, stored: &Ledger) -> Vec<(TokenAddress, PublicKey)> {
+    let mut accounts = vec![];
+    for (a, b) in [(replayed, stored), (stored, replayed)] {
+        for (token, token_ledger) in a.tokens.iter() {
+            for (pk, account) in token_ledger.accounts.iter() {
+                if b.get_account(pk, token) != Some(account) {
+                    accounts.push((token.clone(), pk.clone()));
+                }
+            }
+        }
+    }
+
+    accounts.sort();
+    accounts.dedup();
+    accounts
+}
+
+impl std::fmt::Display for ReplayMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ApplyFailed {
+                height,
+                state_hash,
+                error,
+            } => write!(
+                f,
+                "Failed to apply block {state_hash} at height {height}: {error}"
+            ),
+            Self::LedgerHashMismatch {
+                height,
+                state_hash,
+                declared,
+                stored,
+            } => match stored {
+                Some(stored) => write!(
+                    f,
+                    "Block {state_hash} at height {height} declares staged ledger {declared}, stored {stored}"
+                ),
+                None => write!(
+                    f,
+                    "Block {state_hash} at height {height} declares staged ledger {declared}, none stored"
+                ),
+            },
+            Self::MissingLedger { height, state_hash } => write!(
+                f,
+                "Missing staged ledger of block {state_hash} at height {height}"
+            ),
+            Self::LedgerMismatch {
+                height,
+                state_hash,
+                ledger_hash,
+                accounts,
+            } => {
+                write!(
+                    f,
+                    "Staged ledger {ledger_hash} of block {state_hash} at height {height} differs in {} accounts",
+                    accounts.len()
+                )?;
+
+                if let Some((token, pk)) = accounts.first() {
+                    write!(f, " (first {pk} token {token})")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
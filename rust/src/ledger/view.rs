@@ -0,0 +1,35 @@
+//! Read-only ledger access
+
+use super::{account::Account, token::TokenAddress, Ledger};
+use crate::base::{amount::Amount, nonce::Nonce, public_key::PublicKey};
+
+/// Read-only view of ledger accounts
+///
+/// Implemented by both the plain [Ledger] and the best tip
+/// [super::overlay::OverlayLedger], query code can be agnostic to which
+pub trait LedgerView {
+    /// Get the token account
+    fn account(&self, pk: &PublicKey, token: &TokenAddress) -> Option<&Account>;
+
+    /// Get the token account balance
+    fn balance(&self, pk: &PublicKey, token: &TokenAddress) -> Option<Amount> {
+        self.account(pk, token).map(|account| account.balance)
+    }
+
+    /// Get the token account nonce
+    fn nonce(&self, pk: &PublicKey, token: &TokenAddress) -> Option<Nonce> {
+        self.account(pk, token).and_then(|account| account.nonce)
+    }
+
+    /// Get the MINA account if it's a zkapp account
+    fn zkapp_account(&self, pk: &PublicKey) -> Option<&Account> {
+        self.account(pk, &TokenAddress::default())
+            .filter(|account| account.is_zkapp_account())
+    }
+}
+
+impl LedgerView for Ledger {
+    fn account(&self, pk: &PublicKey, token: &TokenAddress) -> Option<&Account> {
+        self.get_account(pk, token)
+    }
+}
@@ -8,8 +8,12 @@ pub mod cli;
 pub mod client;
 pub mod command;
 pub mod constants;
+pub mod crash;
 pub mod event;
+pub mod identity;
+pub mod ingestion;
 pub mod ledger;
+pub mod maintenance;
 pub mod mina_blocks;
 pub mod proof_systems;
 pub mod protocol;
@@ -19,6 +23,7 @@ pub mod state;
 pub mod store;
 pub mod unix_socket_server;
 pub mod utility;
+pub mod watchlist;
 pub mod web;
 
 #[cfg(target_family = "unix")]
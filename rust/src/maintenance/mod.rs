@@ -0,0 +1,801 @@
+//! Background maintenance scheduler
+//!
+//! Registered maintenance tasks run inside the server, one at a time, in
+//! bounded batches. Batch sizes adapt to a latency budget & the scheduler
+//! sleeps between batches so foreground ingestion & queries stay responsive.
+
+pub mod tasks;
+
+use crate::constants::*;
+use log::{error, info, trace};
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio_graceful_shutdown::SubsystemHandle;
+
+const MILLIS_PER_MINUTE: u64 = 60 * 1000;
+const MILLIS_PER_DAY: u64 = 24 * 60 * MILLIS_PER_MINUTE;
+
+/// Shared handle to the server's maintenance scheduler
+pub type MaintenanceHandle = Arc<Mutex<MaintenanceScheduler>>;
+
+/// Source of the current time (millis since the Unix epoch)
+pub trait Clock: Send + Sync {
+    fn now_millis(&self) -> u64;
+}
+
+/// Wall clock
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+/// Manually advanced clock
+#[derive(Debug, Default)]
+pub struct FakeClock(AtomicU64);
+
+/// When a maintenance task runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Schedule {
+    /// Run each interval after the previous run finishes
+    Interval { millis: u64 },
+
+    /// Run daily at the given minute of the day (UTC)
+    Daily { minute_of_day: u32 },
+}
+
+/// Progress of a single batch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchProgress {
+    pub num_processed: u32,
+    pub done: bool,
+}
+
+/// Maintenance job run under the scheduler
+pub trait MaintenanceTask: Send {
+    fn name(&self) -> &str;
+
+    fn schedule(&self) -> Schedule;
+
+    /// Prepare a new run
+    fn start(&mut self) -> anyhow::Result<()>;
+
+    /// Process at most `batch_size` items of the current run
+    fn run_batch(&mut self, batch_size: u32) -> anyhow::Result<BatchProgress>;
+}
+
+/// Maintenance scheduler settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceConfig {
+    /// Target duration (ms) of a single batch
+    pub latency_budget_ms: u64,
+
+    /// Sleep (ms) in between batches
+    pub yield_ms: u64,
+
+    /// Upper bound on the batch size
+    pub max_batch_size: u32,
+
+    /// Start with maintenance paused
+    pub paused: bool,
+
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+}
+
+/// Retention of settled orphaned blocks' data, nothing is pruned by default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Prune orphaned blocks this many blocks below the best tip
+    pub prune_orphaned_after: Option<u32>,
+
+    /// Prune orphaned blocks below this height
+    pub prune_noncanonical_below_height: Option<u32>,
+}
+
+/// Status of the last finished run of a task
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastRun {
+    pub started_millis: u64,
+    pub finished_millis: u64,
+    pub num_processed: u64,
+    pub error: Option<String>,
+}
+
+/// Status of a registered maintenance task
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceTaskStatus {
+    pub name: String,
+    pub schedule: Schedule,
+    pub running: bool,
+
+    /// Items processed by the current run
+    pub num_processed: u64,
+    pub next_run_millis: u64,
+    pub last_run: Option<LastRun>,
+}
+
+/// Maintenance scheduler summary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceSummary {
+    pub paused: bool,
+    pub batch_size: u32,
+    pub tasks: Vec<MaintenanceTaskStatus>,
+}
+
+/// Outcome of a scheduler step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepOutcome {
+    Paused,
+    Idle,
+    Ran { task: String, done: bool },
+}
+
+struct Run {
+    started_millis: u64,
+    num_processed: u64,
+}
+
+struct Entry {
+    task: Box<dyn MaintenanceTask>,
+    next_run_millis: u64,
+    run: Option<Run>,
+    last_run: Option<LastRun>,
+}
+
+/// Runs registered maintenance tasks one batch at a time
+///
+/// At most one task is running at any time; a started run is continued
+/// until it finishes before another task is started
+pub struct MaintenanceScheduler {
+    config: MaintenanceConfig,
+    clock: Arc<dyn Clock>,
+    entries: Vec<Entry>,
+    batch_size: u32,
+    paused: bool,
+}
+
+///////////
+// impls //
+///////////
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64)
+    }
+}
+
+impl FakeClock {
+    pub fn new(millis: u64) -> Self {
+        Self(AtomicU64::new(millis))
+    }
+
+    pub fn advance(&self, millis: u64) {
+        self.0.fetch_add(millis, Ordering::SeqCst);
+    }
+}
+
+impl Clock for FakeClock {
+    fn now_millis(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl Schedule {
+    pub fn hourly() -> Self {
+        Self::Interval {
+            millis: 60 * MILLIS_PER_MINUTE,
+        }
+    }
+
+    /// Next run strictly after `after`
+    pub fn next_run(&self, after: u64) -> u64 {
+        match self {
+            Self::Interval { millis } => after + (*millis).max(1),
+            Self::Daily { minute_of_day } => {
+                let offset = (*minute_of_day as u64 * MILLIS_PER_MINUTE) % MILLIS_PER_DAY;
+                let run = after - after % MILLIS_PER_DAY + offset;
+                if run > after {
+                    run
+                } else {
+                    run + MILLIS_PER_DAY
+                }
+            }
+        }
+    }
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            latency_budget_ms: MAINTENANCE_LATENCY_BUDGET_MS,
+            yield_ms: MAINTENANCE_YIELD_MS,
+            max_batch_size: MAINTENANCE_MAX_BATCH_SIZE,
+            paused: false,
+            retention: RetentionPolicy::default(),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.prune_orphaned_after.is_some() || self.prune_noncanonical_below_height.is_some()
+    }
+
+    /// Height up to which orphaned blocks are pruned, given the best tip's
+    /// height
+    ///
+    /// Only settled heights are pruned, see
+    /// [BlockStore::prune_orphaned_blocks](crate::block::store::BlockStore::prune_orphaned_blocks)
+    pub fn prune_height(&self, best_height: u32) -> Option<u32> {
+        let orphaned_after = self
+            .prune_orphaned_after
+            .map(|num_blocks| best_height.saturating_sub(num_blocks));
+        let below_height = self
+            .prune_noncanonical_below_height
+            .map(|height| height.saturating_sub(1));
+
+        orphaned_after.max(below_height)
+    }
+}
+
+impl MaintenanceScheduler {
+    pub fn new(config: MaintenanceConfig, clock: Arc<dyn Clock>) -> Self {
+        let batch_size = config
+            .max_batch_size
+            .clamp(1, MAINTENANCE_INITIAL_BATCH_SIZE);
+        let paused = config.paused;
+
+        Self {
+            config,
+            clock,
+            entries: vec![],
+            batch_size,
+            paused,
+        }
+    }
+
+    /// Register a task, first run at its next scheduled time
+    pub fn register(&mut self, task: Box<dyn MaintenanceTask>) {
+        let next_run_millis = task.schedule().next_run(self.clock.now_millis());
+        info!("Registering maintenance task {}", task.name());
+
+        self.entries.push(Entry {
+            task,
+            next_run_millis,
+            run: None,
+            last_run: None,
+        });
+    }
+
+    /// Stop running batches, a started run continues on resume
+    pub fn pause(&mut self) {
+        info!("Pausing maintenance");
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        info!("Resuming maintenance");
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn batch_size(&self) -> u32 {
+        self.batch_size
+    }
+
+    pub fn config(&self) -> &MaintenanceConfig {
+        &self.config
+    }
+
+    pub fn statuses(&self) -> Vec<MaintenanceTaskStatus> {
+        self.entries
+            .iter()
+            .map(|entry| MaintenanceTaskStatus {
+                name: entry.task.name().to_string(),
+                schedule: entry.task.schedule(),
+                running: entry.run.is_some(),
+                num_processed: entry.run.as_ref().map_or(0, |run| run.num_processed),
+                next_run_millis: entry.next_run_millis,
+                last_run: entry.last_run.clone(),
+            })
+            .collect()
+    }
+
+    pub fn summary(&self) -> MaintenanceSummary {
+        MaintenanceSummary {
+            paused: self.paused,
+            batch_size: self.batch_size,
+            tasks: self.statuses(),
+        }
+    }
+
+    /// Run a single batch of the running task, or of the most overdue task
+    pub fn step(&mut self) -> StepOutcome {
+        if self.paused {
+            return StepOutcome::Paused;
+        }
+
+        let now = self.clock.now_millis();
+        let idx = match self.entries.iter().position(|entry| entry.run.is_some()) {
+            Some(idx) => idx,
+            None => match self
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.next_run_millis <= now)
+                .min_by_key(|(_, entry)| entry.next_run_millis)
+            {
+                Some((idx, _)) => idx,
+                None => return StepOutcome::Idle,
+            },
+        };
+
+        let entry = &mut self.entries[idx];
+        let task = entry.task.name().to_string();
+        if entry.run.is_none() {
+            trace!("Starting maintenance task {task}");
+            entry.run = Some(Run {
+                started_millis: now,
+                num_processed: 0,
+            });
+
+            if let Err(e) = entry.task.start() {
+                self.finish(idx, Some(e.to_string()));
+                return StepOutcome::Ran { task, done: true };
+            }
+        }
+
+        let batch_start = self.clock.now_millis();
+        let result = self.entries[idx].task.run_batch(self.batch_size);
+        self.adapt_batch_size(self.clock.now_millis().saturating_sub(batch_start));
+
+        let done = match result {
+            Ok(progress) => {
+                if let Some(run) = self.entries[idx].run.as_mut() {
+                    run.num_processed += progress.num_processed as u64;
+                }
+                if progress.done {
+                    self.finish(idx, None);
+                }
+                progress.done
+            }
+            Err(e) => {
+                self.finish(idx, Some(e.to_string()));
+                true
+            }
+        };
+
+        StepOutcome::Ran { task, done }
+    }
+
+    fn finish(&mut self, idx: usize, error: Option<String>) {
+        let now = self.clock.now_millis();
+        let entry = &mut self.entries[idx];
+        let run = entry.run.take().unwrap_or(Run {
+            started_millis: now,
+            num_processed: 0,
+        });
+
+        match error.as_ref() {
+            Some(e) => error!("Maintenance task {} failed: {e}", entry.task.name()),
+            None => info!(
+                "Maintenance task {} finished, processed {}",
+                entry.task.name(),
+                run.num_processed
+            ),
+        }
+
+        entry.next_run_millis = entry.task.schedule().next_run(now);
+        entry.last_run = Some(LastRun {
+            started_millis: run.started_millis,
+            finished_millis: now,
+            num_processed: run.num_processed,
+            error,
+        });
+    }
+
+    /// Halve the batch size when over budget, double it when well under
+    fn adapt_batch_size(&mut self, elapsed_millis: u64) {
+        let budget = self.config.latency_budget_ms;
+        if elapsed_millis > budget {
+            self.batch_size = (self.batch_size / 2).max(1);
+        } else if elapsed_millis * 2 < budget {
+            self.batch_size = self
+                .batch_size
+                .saturating_mul(2)
+                .min(self.config.max_batch_size.max(1));
+        }
+    }
+}
+
+/// Drive the scheduler until shutdown
+pub async fn run_maintenance(
+    subsys: SubsystemHandle,
+    maintenance: MaintenanceHandle,
+) -> anyhow::Result<()> {
+    loop {
+        let handle = maintenance.clone();
+        let (outcome, yield_ms) = tokio::task::spawn_blocking(move || {
+            let mut scheduler = handle.lock().unwrap();
+            (scheduler.step(), scheduler.config.yield_ms)
+        })
+        .await?;
+
+        let delay = match outcome {
+            StepOutcome::Ran { .. } => yield_ms,
+            StepOutcome::Paused | StepOutcome::Idle => MAINTENANCE_IDLE_POLL_MS,
+        };
+
+        tokio::select! {
+            _ = subsys.on_shutdown_requested() => {
+                break;
+            }
+
+            _ = tokio::time::sleep(Duration::from_millis(delay)) => {}
+        }
+    }
+
+    info!("Maintenance scheduler successfully shutdown");
+    Ok(())
+}
+
+impl std::fmt::Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Interval { millis } => write!(f, "every {}s", millis / 1000),
+            Self::Daily { minute_of_day } => write!(
+                f,
+                "daily at {:02}:{:02} UTC",
+                minute_of_day / 60,
+                minute_of_day % 60
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUDGET: u64 = 100;
+
+    /// Records its batches in a shared log & takes `millis_per_item` per item
+    struct FakeTask {
+        name: &'static str,
+        schedule: Schedule,
+        items: u32,
+        remaining: u32,
+        millis_per_item: u64,
+        clock: Arc<FakeClock>,
+        log: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl MaintenanceTask for FakeTask {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn schedule(&self) -> Schedule {
+            self.schedule
+        }
+
+        fn start(&mut self) -> anyhow::Result<()> {
+            self.remaining = self.items;
+            Ok(())
+        }
+
+        fn run_batch(&mut self, batch_size: u32) -> anyhow::Result<BatchProgress> {
+            let num_processed = batch_size.min(self.remaining);
+            self.remaining -= num_processed;
+            self.clock
+                .advance(num_processed as u64 * self.millis_per_item);
+            self.log.lock().unwrap().push(self.name);
+
+            Ok(BatchProgress {
+                num_processed,
+                done: self.remaining == 0,
+            })
+        }
+    }
+
+    fn setup(
+        max_batch_size: u32,
+    ) -> (
+        MaintenanceScheduler,
+        Arc<FakeClock>,
+        Arc<Mutex<Vec<&'static str>>>,
+    ) {
+        let clock = Arc::new(FakeClock::new(0));
+        let config = MaintenanceConfig {
+            latency_budget_ms: BUDGET,
+            yield_ms: 10,
+            max_batch_size,
+            paused: false,
+            retention: RetentionPolicy::default(),
+        };
+
+        (
+            MaintenanceScheduler::new(config, clock.clone()),
+            clock,
+            Default::default(),
+        )
+    }
+
+    fn task(
+        name: &'static str,
+        schedule: Schedule,
+        items: u32,
+        clock: &Arc<FakeClock>,
+        log: &Arc<Mutex<Vec<&'static str>>>,
+    ) -> Box<FakeTask> {
+        Box::new(FakeTask {
+            name,
+            schedule,
+            items,
+            remaining: 0,
+            millis_per_item: 0,
+            clock: clock.clone(),
+            log: log.clone(),
+        })
+    }
+
+    #[test]
+    fn schedule_next_run() {
+        assert_eq!(Schedule::Interval { millis: 500 }.next_run(1000), 1500);
+
+        // 03:30 UTC
+        let daily = Schedule::Daily {
+            minute_of_day: 3 * 60 + 30,
+        };
+        let at = 210 * MILLIS_PER_MINUTE;
+        assert_eq!(daily.next_run(0), at);
+        assert_eq!(daily.next_run(at - 1), at);
+        assert_eq!(daily.next_run(at), at + MILLIS_PER_DAY);
+        assert_eq!(
+            daily.next_run(5 * MILLIS_PER_DAY + 1),
+            5 * MILLIS_PER_DAY + at
+        );
+    }
+
+    #[test]
+    fn retention_prune_height() {
+        assert!(!RetentionPolicy::default().is_enabled());
+        assert_eq!(RetentionPolicy::default().prune_height(1000), None);
+
+        let orphaned_after = RetentionPolicy {
+            prune_orphaned_after: Some(290),
+            ..Default::default()
+        };
+        assert!(orphaned_after.is_enabled());
+        assert_eq!(orphaned_after.prune_height(1000), Some(710));
+        assert_eq!(orphaned_after.prune_height(100), Some(0));
+
+        let below_height = RetentionPolicy {
+            prune_noncanonical_below_height: Some(500),
+            ..Default::default()
+        };
+        assert_eq!(below_height.prune_height(1000), Some(499));
+
+        // the higher of the two
+        let both = RetentionPolicy {
+            prune_orphaned_after: Some(290),
+            prune_noncanonical_below_height: Some(500),
+        };
+        assert_eq!(both.prune_height(1000), Some(710));
+        assert_eq!(both.prune_height(600), Some(499));
+    }
+
+    #[test]
+    fn runs_tasks_when_due() {
+        let (mut scheduler, clock, log) = setup(10);
+        scheduler.register(task(
+            "interval",
+            Schedule::Interval { millis: 1000 },
+            5,
+            &clock,
+            &log,
+        ));
+
+        // not yet due
+        assert_eq!(scheduler.step(), StepOutcome::Idle);
+        clock.advance(999);
+        assert_eq!(scheduler.step(), StepOutcome::Idle);
+
+        // due
+        clock.advance(1);
+        assert_eq!(
+            scheduler.step(),
+            StepOutcome::Ran {
+                task: "interval".into(),
+                done: true
+            }
+        );
+
+        let status = &scheduler.statuses()[0];
+        assert!(!status.running);
+        assert_eq!(status.next_run_millis, 2000);
+        assert_eq!(
+            status.last_run,
+            Some(LastRun {
+                started_millis: 1000,
+                finished_millis: 1000,
+                num_processed: 5,
+                error: None,
+            })
+        );
+
+        // next run is an interval after the previous one finished
+        clock.advance(999);
+        assert_eq!(scheduler.step(), StepOutcome::Idle);
+        clock.advance(1);
+        assert!(matches!(scheduler.step(), StepOutcome::Ran { .. }));
+        assert_eq!(*log.lock().unwrap(), vec!["interval", "interval"]);
+    }
+
+    #[test]
+    fn one_task_at_a_time() {
+        let (mut scheduler, clock, log) = setup(2);
+        let schedule = Schedule::Interval { millis: 1000 };
+        scheduler.register(task("a", schedule, 6, &clock, &log));
+        scheduler.register(task("b", schedule, 4, &clock, &log));
+
+        // both are due, each takes several batches
+        clock.advance(1000);
+        while scheduler.step() != StepOutcome::Idle {
+            let num_running = scheduler
+                .statuses()
+                .iter()
+                .filter(|status| status.running)
+                .count();
+            assert!(num_running <= 1);
+        }
+
+        // runs never interleave
+        assert_eq!(*log.lock().unwrap(), vec!["a", "a", "a", "b", "b"]);
+
+        let statuses = scheduler.statuses();
+        assert_eq!(statuses[0].last_run.as_ref().unwrap().num_processed, 6);
+        assert_eq!(statuses[1].last_run.as_ref().unwrap().num_processed, 4);
+    }
+
+    #[test]
+    fn pause_and_resume() {
+        let (mut scheduler, clock, log) = setup(2);
+        scheduler.register(task(
+            "a",
+            Schedule::Interval { millis: 1000 },
+            4,
+            &clock,
+            &log,
+        ));
+
+        clock.advance(1000);
+        assert_eq!(
+            scheduler.step(),
+            StepOutcome::Ran {
+                task: "a".into(),
+                done: false
+            }
+        );
+
+        // no batches run while paused, the started run is kept
+        scheduler.pause();
+        assert!(scheduler.is_paused());
+        clock.advance(10_000);
+        assert_eq!(scheduler.step(), StepOutcome::Paused);
+        assert_eq!(log.lock().unwrap().len(), 1);
+
+        let status = &scheduler.statuses()[0];
+        assert!(status.running);
+        assert_eq!(status.num_processed, 2);
+
+        // the run continues where it left off
+        scheduler.resume();
+        assert_eq!(
+            scheduler.step(),
+            StepOutcome::Ran {
+                task: "a".into(),
+                done: true
+            }
+        );
+        assert_eq!(
+            scheduler.statuses()[0]
+                .last_run
+                .as_ref()
+                .unwrap()
+                .num_processed,
+            4
+        );
+    }
+
+    #[test]
+    fn start_paused() {
+        let clock = Arc::new(FakeClock::new(0));
+        let log = Default::default();
+        let mut scheduler = MaintenanceScheduler::new(
+            MaintenanceConfig {
+                paused: true,
+                ..Default::default()
+            },
+            clock.clone(),
+        );
+        scheduler.register(task("a", Schedule::hourly(), 1, &clock, &log));
+
+        clock.advance(MILLIS_PER_DAY);
+        assert_eq!(scheduler.step(), StepOutcome::Paused);
+        assert!(log.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn batch_size_adapts_to_latency_budget() {
+        let (mut scheduler, clock, log) = setup(64);
+        let mut slow = task(
+            "slow",
+            Schedule::Interval { millis: 1000 },
+            u32::MAX,
+            &clock,
+            &log,
+        );
+        slow.millis_per_item = 10;
+        scheduler.register(slow);
+
+        clock.advance(1000);
+        let initial = scheduler.batch_size();
+        assert!(initial as u64 * 10 > BUDGET);
+
+        // over budget batches shrink until a batch fits the budget
+        for _ in 0..10 {
+            scheduler.step();
+            assert!(scheduler.batch_size() >= 1);
+        }
+        assert!(scheduler.batch_size() as u64 * 10 <= BUDGET);
+        assert!(scheduler.batch_size() < initial);
+    }
+
+    #[test]
+    fn failed_run_is_reported() {
+        struct Failing;
+
+        impl MaintenanceTask for Failing {
+            fn name(&self) -> &str {
+                "failing"
+            }
+
+            fn schedule(&self) -> Schedule {
+                Schedule::Interval { millis: 1 }
+            }
+
+            fn start(&mut self) -> anyhow::Result<()> {
+                Ok(())
+            }
+
+            fn run_batch(&mut self, _batch_size: u32) -> anyhow::Result<BatchProgress> {
+                anyhow::bail!("boom")
+            }
+        }
+
+        let (mut scheduler, clock, _) = setup(2);
+        scheduler.register(Box::new(Failing));
+
+        clock.advance(1);
+        assert_eq!(
+            scheduler.step(),
+            StepOutcome::Ran {
+                task: "failing".into(),
+                done: true
+            }
+        );
+
+        let last_run = scheduler.statuses()[0].last_run.clone().unwrap();
+        assert_eq!(last_run.error, Some("boom".into()));
+    }
+}
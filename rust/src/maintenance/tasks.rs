@@ -0,0 +1,192 @@
+//! Maintenance tasks run under the scheduler
+
+use super::{BatchProgress, MaintenanceTask, RetentionPolicy, Schedule};
+use crate::{
+    block::store::BlockStore, constants::*, event::store::EventStore, store::IndexerStore,
+};
+use log::debug;
+use std::sync::Arc;
+
+/// Drops the ledger diffs of settled orphaned blocks
+///
+/// Each batch covers at most `batch_size` heights, resuming from the stored
+/// pruned height
+pub struct OrphanPruningTask {
+    store: Arc<IndexerStore>,
+    pruned_height: u32,
+    settled_height: u32,
+}
+
+/// Drops the stored data of settled orphaned blocks per the retention policy
+///
+/// Each batch covers at most `batch_size` heights, resuming from the stored
+/// pruned height
+pub struct BlockRetentionTask {
+    store: Arc<IndexerStore>,
+    policy: RetentionPolicy,
+    pruned_height: u32,
+    prune_height: u32,
+}
+
+/// Compacts the event log
+///
+/// Each batch covers at most `batch_size` events
+pub struct EventLogCompactionTask {
+    store: Arc<IndexerStore>,
+    seq_num: u32,
+    next_seq_num: u32,
+}
+
+impl OrphanPruningTask {
+    pub const NAME: &'static str = "orphan-pruning";
+
+    pub fn new(store: Arc<IndexerStore>) -> Self {
+        Self {
+            store,
+            pruned_height: 0,
+            settled_height: 0,
+        }
+    }
+}
+
+impl MaintenanceTask for OrphanPruningTask {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::hourly()
+    }
+
+    fn start(&mut self) -> anyhow::Result<()> {
+        self.pruned_height = self.store.get_orphans_pruned_height()?;
+        self.settled_height = self.store.get_orphans_settled_height()?;
+        Ok(())
+    }
+
+    fn run_batch(&mut self, batch_size: u32) -> anyhow::Result<BatchProgress> {
+        let height = self
+            .pruned_height
+            .saturating_add(batch_size)
+            .min(self.settled_height);
+        let num_processed = height.saturating_sub(self.pruned_height);
+
+        if num_processed > 0 {
+            let num_pruned = self.store.prune_orphaned_ledger_diffs(height)?;
+            debug!(
+                "Pruned {num_pruned} orphaned block ledger diffs (length {} -> {height})",
+                self.pruned_height
+            );
+            self.pruned_height = height;
+        }
+
+        Ok(BatchProgress {
+            num_processed,
+            done: self.pruned_height >= self.settled_height,
+        })
+    }
+}
+
+impl BlockRetentionTask {
+    pub const NAME: &'static str = "block-retention";
+
+    pub fn new(store: Arc<IndexerStore>, policy: RetentionPolicy) -> Self {
+        Self {
+            store,
+            policy,
+            pruned_height: 0,
+            prune_height: 0,
+        }
+    }
+}
+
+impl MaintenanceTask for BlockRetentionTask {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::hourly()
+    }
+
+    fn start(&mut self) -> anyhow::Result<()> {
+        let best_height = self.store.get_best_block_height()?.unwrap_or_default();
+        self.pruned_height = self.store.get_orphaned_blocks_pruned_height()?;
+        self.prune_height = self
+            .policy
+            .prune_height(best_height)
+            .unwrap_or_default()
+            .min(self.store.get_orphans_settled_height()?);
+        Ok(())
+    }
+
+    fn run_batch(&mut self, batch_size: u32) -> anyhow::Result<BatchProgress> {
+        let height = self
+            .pruned_height
+            .saturating_add(batch_size)
+            .min(self.prune_height);
+        let num_processed = height.saturating_sub(self.pruned_height);
+
+        if num_processed > 0 {
+            let num_pruned = self.store.prune_orphaned_blocks(height)?;
+            debug!(
+                "Pruned {num_pruned} orphaned blocks (length {} -> {height})",
+                self.pruned_height
+            );
+            self.pruned_height = height;
+        }
+
+        Ok(BatchProgress {
+            num_processed,
+            done: self.pruned_height >= self.prune_height,
+        })
+    }
+}
+
+impl EventLogCompactionTask {
+    pub const NAME: &'static str = "event-log-compaction";
+
+    pub fn new(store: Arc<IndexerStore>) -> Self {
+        Self {
+            store,
+            seq_num: 0,
+            next_seq_num: 0,
+        }
+    }
+}
+
+impl MaintenanceTask for EventLogCompactionTask {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn schedule(&self) -> Schedule {
+        Schedule::Daily {
+            minute_of_day: EVENT_LOG_COMPACTION_MINUTE_OF_DAY,
+        }
+    }
+
+    fn start(&mut self) -> anyhow::Result<()> {
+        self.seq_num = 0;
+        self.next_seq_num = self.store.get_next_seq_num()?;
+        Ok(())
+    }
+
+    fn run_batch(&mut self, batch_size: u32) -> anyhow::Result<BatchProgress> {
+        let to = self
+            .seq_num
+            .saturating_add(batch_size)
+            .min(self.next_seq_num);
+        let num_processed = to.saturating_sub(self.seq_num);
+
+        if num_processed > 0 {
+            self.store.compact_event_log(self.seq_num, to);
+            self.seq_num = to;
+        }
+
+        Ok(BatchProgress {
+            num_processed,
+            done: self.seq_num >= self.next_seq_num,
+        })
+    }
+}
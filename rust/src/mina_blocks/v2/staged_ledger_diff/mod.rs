@@ -71,7 +71,26 @@ impl Eq for UserCommand {}
 #[serde(untagged)]
 pub enum Status {
     Status((StatusKind,)),
-    StatusAndFailure(StatusKind, (((TransactionStatusFailedType,),),)),
+    StatusAndFailure(StatusKind, Vec<Vec<(TransactionStatusFailedType,)>>),
+}
+
+impl Status {
+    /// Index-mapped failures, one list per account update (zkapp commands
+    /// list the fee payer first)
+    pub fn failures(&self) -> Vec<Vec<TransactionStatusFailedType>> {
+        match self {
+            Self::Status(_) => vec![],
+            Self::StatusAndFailure(_, failures) => failures
+                .iter()
+                .map(|update_failures| {
+                    update_failures
+                        .iter()
+                        .map(|(failure,)| failure.to_owned())
+                        .collect()
+                })
+                .collect(),
+        }
+    }
 }
 
 /// User command
@@ -188,25 +207,35 @@ pub struct AccountUpdate {
     pub authorization: Authorization,
 }
 
+// see https://github.com/MinaProtocol/mina/blob/compatible/src/lib/mina_base/account_update.ml#L24-L28
+//
+// Mina's JSON has both a bare (`["Proof"]`) and a data carrying (`["Proof",
+// data]`) shape for each kind, both are normalized to the same kind & the
+// data is kept for exact round-trips
 #[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
-pub enum ProofOrSignature {
+#[serde(from = "AuthorizationJson", into = "AuthorizationJson")]
+pub struct Authorization {
+    pub kind: AuthorizationKind,
+
+    /// Proof, signature, or verification key hash accompanying the kind
+    pub data: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
+pub enum AuthorizationKind {
+    #[serde(rename = "None_given")]
+    NoneGiven,
+    Either,
     Proof,
     Signature,
 }
 
-// see https://github.com/MinaProtocol/mina/blob/compatible/src/lib/mina_base/account_update.ml#L24-L28
-#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+/// Authorization JSON shapes
+#[derive(Clone, Serialize, Deserialize)]
 #[serde(untagged)]
-pub enum Authorization {
-    #[serde(rename = "None_given")]
-    NoneGiven((String,)),
-    Either((String,)),
-    #[serde(rename = "Proof")]
-    Proof_((ProofOrSignature, String)),
-    Proof((String,)),
-    #[serde(rename = "Signature")]
-    Signature_((ProofOrSignature, String)),
-    Signature((String,)),
+enum AuthorizationJson {
+    Bare((AuthorizationKind,)),
+    WithData((AuthorizationKind, String)),
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
@@ -421,6 +450,38 @@ where
     }
 }
 
+impl From<AuthorizationJson> for Authorization {
+    fn from(value: AuthorizationJson) -> Self {
+        match value {
+            AuthorizationJson::Bare((kind,)) => Self { kind, data: None },
+            AuthorizationJson::WithData((kind, data)) => Self {
+                kind,
+                data: Some(data),
+            },
+        }
+    }
+}
+
+impl From<Authorization> for AuthorizationJson {
+    fn from(value: Authorization) -> Self {
+        match value.data {
+            None => Self::Bare((value.kind,)),
+            Some(data) => Self::WithData((value.kind, data)),
+        }
+    }
+}
+
+impl std::fmt::Display for AuthorizationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoneGiven => write!(f, "None_given"),
+            Self::Either => write!(f, "Either"),
+            Self::Proof => write!(f, "Proof"),
+            Self::Signature => write!(f, "Signature"),
+        }
+    }
+}
+
 impl From<UpdateVerificationKey> for Option<VerificationKey> {
     fn from(value: UpdateVerificationKey) -> Self {
         match value {
@@ -453,10 +514,41 @@ mod tests {
     use crate::{
         block::precomputed::{PcbVersion, PrecomputedBlock},
         command::{signed::SignedCommand, to_mina_json, to_zkapp_json, UserCommandWithStatusT},
-        mina_blocks::v2::staged_ledger_diff::UserCommandData,
+        mina_blocks::v2::staged_ledger_diff::{Authorization, AuthorizationKind, UserCommandData},
     };
     use std::path::PathBuf;
 
+    #[test]
+    fn authorization_normalization() -> anyhow::Result<()> {
+        for (json, kind, data) in [
+            (r#"["None_given"]"#, AuthorizationKind::NoneGiven, None),
+            (r#"["Either"]"#, AuthorizationKind::Either, None),
+            (r#"["Signature"]"#, AuthorizationKind::Signature, None),
+            (
+                r#"["Signature","7mX"]"#,
+                AuthorizationKind::Signature,
+                Some("7mX"),
+            ),
+            (r#"["Proof"]"#, AuthorizationKind::Proof, None),
+            (
+                r#"["Proof","0x1C"]"#,
+                AuthorizationKind::Proof,
+                Some("0x1C"),
+            ),
+        ] {
+            let auth: Authorization = serde_json::from_str(json)?;
+
+            // both shapes normalize to the same kind
+            assert_eq!(auth.kind, kind);
+            assert_eq!(auth.data.as_deref(), data);
+
+            // original shape is preserved
+            assert_eq!(serde_json::to_string(&auth)?, json);
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn v2_signed_command_to_mina_json() -> anyhow::Result<()> {
         let block_file = PathBuf::from("./tests/data/hardfork/mainnet-359606-3NKvvtFwjEtQLswWJzXBSxxiKuYVbLJrKXCnmhp6jctYMqAWcftg.json");
@@ -854,4 +946,43 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn zkapp_command_proof_authorization_roundtrip() -> anyhow::Result<()> {
+        use serde_json::*;
+
+        let block_file = PathBuf::from("./tests/data/misc_blocks/mainnet-397612-3NLh3tvZpMPXxUhCLz1898BDV6CwtExJqDWpzcZQebVCsZxghoXK.json");
+        let precomputed_block = PrecomputedBlock::parse_file(&block_file, PcbVersion::V2).unwrap();
+        let zkapp_cmds = precomputed_block
+            .commands()
+            .into_iter()
+            .filter_map(|cmd| {
+                if let SignedCommand::V2(UserCommandData::ZkappCommandData(data)) = cmd.into() {
+                    return Some(data);
+                }
+
+                None
+            })
+            .collect::<Vec<_>>();
+
+        // 6th "post-diff" user command contains proof authorizations
+        let account_update = &zkapp_cmds[1].account_updates[1].elt.account_update;
+        assert_eq!(account_update.authorization.kind, AuthorizationKind::Proof);
+        assert_eq!(
+            account_update.body.authorization_kind.kind,
+            AuthorizationKind::Proof
+        );
+
+        // re-serializes byte-identically despite the normalization
+        let contents = std::fs::read(block_file)?;
+        let mina_json: Value = from_slice::<Value>(&contents)?["data"]["staged_ledger_diff"]
+            ["diff"][1]["commands"][5]["data"][1]
+            .clone();
+
+        assert_eq!(
+            to_string(&to_mina_json(to_value(&zkapp_cmds[1])?))?,
+            to_string(&mina_json)?
+        );
+        Ok(())
+    }
 }
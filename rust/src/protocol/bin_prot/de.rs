@@ -15,7 +15,10 @@ use serde::{
     de::{self, value::U8Deserializer, EnumAccess, IntoDeserializer, Visitor},
     Deserialize,
 };
-use std::io::{BufReader, Read};
+use std::{
+    borrow::Cow,
+    io::{BufReader, Read},
+};
 
 /// the modes of operation for the deserializer
 pub struct StronglyTyped;
@@ -29,21 +32,69 @@ pub struct LooselyTyped {
 /// Can operate in strong or loose deserialization mode
 pub struct Deserializer<R: Read, Mode> {
     /// BufReader to read the bytes from
-    pub rdr: BufReader<R>,
+    pub rdr: BufReader<OffsetReader<R>>,
     /// Deserialization mode, StronglyTyped or LooselyTyped
     pub mode: Mode,
+    /// Types & fields being read, outermost first
+    path: Vec<Cow<'static, str>>,
+}
+
+/// Reader which counts the bytes read from the underlying reader
+pub struct OffsetReader<R> {
+    inner: R,
+    offset: u64,
+}
+
+impl<R: Read> Read for OffsetReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.offset += n as u64;
+        Ok(n)
+    }
 }
 
 impl<R: Read> Deserializer<R, StronglyTyped> {
     /// Create a BinProt deserializer from a reader
     pub fn from_reader(rdr: R) -> Self {
         Self {
-            rdr: BufReader::new(rdr),
+            rdr: BufReader::new(OffsetReader {
+                inner: rdr,
+                offset: 0,
+            }),
             mode: StronglyTyped,
+            path: vec![],
         }
     }
 }
 
+impl<R: Read, Mode> Deserializer<R, Mode> {
+    /// Number of bytes consumed by the deserializer
+    pub fn offset(&self) -> u64 {
+        self.rdr.get_ref().offset - self.rdr.buffer().len() as u64
+    }
+
+    /// Reads the named type or field, attaching the byte offset & the path
+    /// of types being read to the innermost error
+    fn in_context<T>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        read: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        self.path.push(name.into());
+        let result = read(self).map_err(|error| match error {
+            Error::Context { .. } => error,
+            error => Error::Context {
+                offset: self.offset(),
+                path: self.path.iter().map(ToString::to_string).collect(),
+                source: Box::new(error),
+            },
+        });
+
+        self.path.pop();
+        result
+    }
+}
+
 #[cfg(feature = "loose_deserialization")]
 impl<R: Read> Deserializer<R, StronglyTyped> {
     /// Converts a strong type deserializer into a loose type deserializer by
@@ -54,6 +105,7 @@ impl<R: Read> Deserializer<R, StronglyTyped> {
             mode: LooselyTyped {
                 layout_iter: layout.clone().into_iter(),
             },
+            path: self.path,
         }
     }
 }
@@ -62,7 +114,7 @@ impl<R: Read> Deserializer<R, StronglyTyped> {
 /// then read from it
 pub fn from_reader<'de, R: Read, T: Deserialize<'de>>(rdr: R) -> Result<T> {
     let mut de = Deserializer::from_reader(rdr);
-    let value = Deserialize::deserialize(&mut de)?;
+    let value = de.in_context(short_type_name::<T>(), |de| T::deserialize(de))?;
     Ok(value)
 }
 
@@ -71,13 +123,21 @@ pub fn from_reader<'de, R: Read, T: Deserialize<'de>>(rdr: R) -> Result<T> {
 /// This method also ensures the input byte stream is fully consumed.
 pub fn from_reader_strict<'de, R: Read, T: Deserialize<'de>>(rdr: R) -> Result<T> {
     let mut de = Deserializer::from_reader(rdr);
-    let value = Deserialize::deserialize(&mut de)?;
+    let value = de.in_context(short_type_name::<T>(), |de| T::deserialize(de))?;
     match de.rdr.buffer().len() {
         0 => Ok(value),
         unconsumed => Err(Error::StreamNotFullyConsumed(unconsumed)),
     }
 }
 
+/// Type name without module paths, e.g. `Versioned<HashV1, 1>`
+fn short_type_name<T>() -> String {
+    std::any::type_name::<T>()
+        .split_inclusive(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+        .map(|segment| segment.rsplit("::").next().unwrap_or(segment))
+        .collect()
+}
+
 // In the loosely typed case we want to use deserialize_any for every field
 // This includes the hybrid strong/loose case
 #[cfg(feature = "loose_deserialization")]
@@ -255,11 +315,11 @@ impl<'de, 'a, R: Read> de::Deserializer<'de> for &'a mut Deserializer<R, Strongl
     // As is done here, serializers are encouraged to treat newtype structs as
     // insignificant wrappers around the data they contain. That means not
     // parsing anything other than the contained value.
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        self.in_context(name, |de| visitor.visit_newtype_struct(de))
     }
 
     // Parsing an unknown length seq (e.g array, list) involves
@@ -283,14 +343,14 @@ impl<'de, 'a, R: Read> de::Deserializer<'de> for &'a mut Deserializer<R, Strongl
     // Tuple structs look just like sequences
     fn deserialize_tuple_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         len: usize,
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_seq(SeqAccess::new(self, len))
+        self.in_context(name, |de| visitor.visit_seq(SeqAccess::new(de, len)))
     }
 
     // Much like `deserialize_seq` but calls the visitors `visit_map` method
@@ -311,14 +371,20 @@ impl<'de, 'a, R: Read> de::Deserializer<'de> for &'a mut Deserializer<R, Strongl
     // Structs look just like sequences
     fn deserialize_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_seq(SeqAccess::new(self, fields.len()))
+        if name.is_empty() {
+            // struct variant, already named by its enum
+            return visitor.visit_seq(SeqAccess::new_struct(self, fields));
+        }
+        self.in_context(name, |de| {
+            visitor.visit_seq(SeqAccess::new_struct(de, fields))
+        })
     }
 
     fn deserialize_enum<V>(
@@ -332,16 +398,16 @@ impl<'de, 'a, R: Read> de::Deserializer<'de> for &'a mut Deserializer<R, Strongl
     {
         // Deserialize polyvar enum using serde container attribute #[serde(rename =
         // "Polyvar")] Refer tests/polyvar.rs for more info
-        match name {
+        self.in_context(name, |de| match name {
             "Polyvar" => {
-                let hash = self.rdr.bin_read_polyvar_tag()?;
-                visitor.visit_enum(PolyvarEnum::new(self, hash, variants))
+                let hash = de.rdr.bin_read_polyvar_tag()?;
+                visitor.visit_enum(PolyvarEnum::new(de, hash, variants))
             }
             _ => {
-                let index = self.rdr.bin_read_variant_index()?;
-                visitor.visit_enum(Enum::new(self, index))
+                let index = de.rdr.bin_read_variant_index()?;
+                visitor.visit_enum(Enum::new(de, index))
             }
-        }
+        })
     }
 
     fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value>
@@ -558,6 +624,7 @@ pub(crate) struct SeqAccess<'a, R: Read + 'a, Mode> {
     total_len: usize,
     len: usize,
     is_list: bool,
+    fields: &'static [&'static str],
 }
 
 impl<'a, R: Read + 'a, Mode> SeqAccess<'a, R, Mode> {
@@ -567,16 +634,23 @@ impl<'a, R: Read + 'a, Mode> SeqAccess<'a, R, Mode> {
             len,
             total_len: len,
             is_list: false,
+            fields: &[],
+        }
+    }
+
+    /// Struct fields are read in order, named in the error context
+    pub fn new_struct(de: &'a mut Deserializer<R, Mode>, fields: &'static [&'static str]) -> Self {
+        Self {
+            fields,
+            ..Self::new(de, fields.len())
         }
     }
 
     #[cfg(feature = "loose_deserialization")]
     pub fn new_list(de: &'a mut Deserializer<R, Mode>, len: usize) -> Self {
         Self {
-            de,
-            len,
-            total_len: len,
             is_list: true,
+            ..Self::new(de, len)
         }
     }
 }
@@ -591,8 +665,15 @@ macro_rules! impl_seq_access {
                 seed: T,
             ) -> Result<Option<T::Value>> {
                 if self.len > 0 {
+                    let index = self.total_len - self.len;
                     self.len -= 1;
-                    seed.deserialize(&mut *self.de).map(Some)
+                    match self.fields.get(index) {
+                        Some(field) => self
+                            .de
+                            .in_context(*field, |de| seed.deserialize(de))
+                            .map(Some),
+                        None => seed.deserialize(&mut *self.de).map(Some),
+                    }
                 } else {
                     Ok(None)
                 }
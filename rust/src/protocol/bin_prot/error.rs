@@ -116,6 +116,17 @@ pub enum Error {
     #[error("Input stream has {0} unconsumed bytes")]
     StreamNotFullyConsumed(usize),
 
+    /// Where the innermost error occurred
+    #[error("{source} at byte offset {offset} reading {}", .path.join(" > "))]
+    Context {
+        /// Number of bytes consumed before the error
+        offset: u64,
+        /// Types & fields being read, outermost first
+        path: Vec<String>,
+        /// The innermost error
+        source: Box<Error>,
+    },
+
     //////////////////////////////////
     /// Some user-defined error occurred.
     #[error("{message}")]
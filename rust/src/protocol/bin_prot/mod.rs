@@ -16,7 +16,7 @@ mod ser;
 pub mod value;
 mod write_ext;
 
-pub use de::{from_reader, from_reader_strict, Deserializer};
+pub use de::{from_reader, from_reader_strict, Deserializer, OffsetReader};
 pub use polyvar::{caml_hash_variant, VariantHash};
 pub use read_ext::ReadBinProtExt;
 pub use ser::{to_writer, Serializer};
@@ -1086,6 +1086,23 @@ pub struct FeeTransferBalanceDataJson {
     pub receiver2_balance: Option<U64Json>,
 }
 
+impl TransactionStatusFailedType {
+    /// Name of the failure, less its data
+    pub fn kind(&self) -> String {
+        match serde_json::to_value(self).unwrap() {
+            serde_json::Value::Object(obj) => obj.keys().next().cloned().unwrap_or_default(),
+            value => value.as_str().unwrap_or_default().to_string(),
+        }
+    }
+
+    /// Parse a failure from its [Display](std::fmt::Display) string
+    pub fn from_reason(reason: &str) -> Option<Self> {
+        serde_json::from_str(reason)
+            .or_else(|_| serde_json::from_value(reason.into()))
+            .ok()
+    }
+}
+
 impl std::fmt::Display for TransactionStatusFailedType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = serde_json::to_string(self).unwrap();
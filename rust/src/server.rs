@@ -2,25 +2,43 @@
 
 use crate::{
     base::state_hash::StateHash,
-    block::{self, parser::BlockParser, precomputed::PcbVersion, vrf_output::VrfOutput},
-    chain::{ChainId, Network},
-    cli::server::ServerArgsJson,
+    block::{
+        self,
+        parser::BlockParser,
+        precomputed::{PcbVersion, PrecomputedBlock},
+        receipt::BlockReceipt,
+        store::BlockStore,
+        vrf_output::VrfOutput,
+    },
+    chain::{store::ChainStore, ChainConstants, ChainId, Network},
+    cli::server::{GenesisConfig, ServerArgsJson},
     constants::*,
+    ingestion::{
+        backfill::{backfill_missing_blocks, BlockBackfillConfig, BlocksDirHeights},
+        IngestionQueueConfig,
+    },
     ledger::{
         genesis::GenesisLedger,
         staking::{self, StakingLedger},
-        store::staking::StakingLedgerStore,
+        store::staking::StakingLedgerFileStatus,
+        trace::TracedAccounts,
+    },
+    maintenance::{
+        run_maintenance,
+        tasks::{BlockRetentionTask, EventLogCompactionTask, OrphanPruningTask},
+        Clock, MaintenanceConfig, MaintenanceScheduler, RetentionPolicy, SystemClock,
     },
     state::{IndexerState, IndexerStateConfig},
     store::{fixed_keys::FixedKeys, IndexerStore},
     unix_socket_server::{create_socket_listener, handle_connection},
+    watchlist::{store::WatchlistStore, webhook::run_webhook, WatchedAccount},
 };
 use log::{debug, error, info, trace, warn};
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
 use speedb::checkpoint::Checkpoint;
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fs,
     os::unix::fs::MetadataExt,
     path::{Path, PathBuf},
@@ -34,6 +52,10 @@ use tokio::{
 };
 use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle};
 
+/// States of the networks indexed by the process, each in its own store
+/// namespace
+pub type NetworkIndexers = Arc<std::sync::RwLock<HashMap<Network, Arc<RwLock<IndexerState>>>>>;
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct IndexerVersion {
     pub network: Network,
@@ -63,13 +85,53 @@ pub struct IndexerConfiguration {
     pub initialization_mode: InitializationMode,
     pub ledger_cadence: u32,
     pub reporting_freq: u32,
+
+    #[serde(default)]
+    pub ingestion_queue: IngestionQueueConfig,
+
     pub domain_socket_path: PathBuf,
     pub do_not_ingest_orphan_blocks: bool,
+
+    #[serde(default)]
+    pub no_recursive: bool,
+
+    #[serde(default)]
+    pub replace_staking_ledger: bool,
     pub fetch_new_blocks_exe: Option<PathBuf>,
     pub fetch_new_blocks_delay: Option<u64>,
     pub missing_block_recovery_exe: Option<PathBuf>,
     pub missing_block_recovery_delay: Option<u64>,
     pub missing_block_recovery_batch: bool,
+
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+
+    #[serde(default)]
+    pub trace_accounts: Vec<String>,
+
+    #[serde(default)]
+    pub validate_blocks: bool,
+
+    #[serde(default)]
+    pub future_block_horizon: Option<u32>,
+
+    #[serde(default)]
+    pub staking_ledger_batch_size: Option<usize>,
+
+    #[serde(default)]
+    pub staking_ledgers_from_epoch: Option<u32>,
+
+    #[serde(default)]
+    pub chain_constants: ChainConstants,
+
+    #[serde(default)]
+    pub block_backfill: Option<BlockBackfillConfig>,
+
+    #[serde(default)]
+    pub watch_accounts: Vec<String>,
+
+    #[serde(default)]
+    pub watch_webhook: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -99,6 +161,7 @@ impl IndexerConfiguration {
         });
 
         if let Some(indexer_store) = state.indexer_store.as_ref() {
+            indexer_store.clean_shutdown()?;
             indexer_store.database.cancel_all_background_work(true);
         }
 
@@ -114,6 +177,9 @@ impl IndexerConfiguration {
         info!("Initializing mina indexer database");
         let db_path = store.db_path.clone();
 
+        // the network's store namespace
+        let store = &store.network_store(&self.version.network)?;
+
         // read the config from the store if it exists or write it
         let IndexerConfiguration {
             genesis_ledger,
@@ -125,20 +191,44 @@ impl IndexerConfiguration {
             initialization_mode,
             ledger_cadence,
             reporting_freq,
+            ingestion_queue,
             version,
             do_not_ingest_orphan_blocks,
+            no_recursive,
+            replace_staking_ledger,
+            trace_accounts,
+            validate_blocks,
+            future_block_horizon,
+            staking_ledger_batch_size,
+            staking_ledgers_from_epoch,
+            chain_constants,
             ..
         } = if reuse {
             self
         } else {
             debug!("Persisting mina indexer config");
-            store
-                .database
-                .put(IndexerStore::INDEXER_CONFIG_KEY, serde_json::to_vec(&self)?)?;
+            store.database.put_cf(
+                store.default_cf(),
+                IndexerStore::INDEXER_CONFIG_KEY,
+                serde_json::to_vec(&self)?,
+            )?;
 
             self
         };
 
+        if !trace_accounts.is_empty() {
+            info!("Tracing canonical ledger diffs of {trace_accounts:?}");
+        }
+        let trace_accounts = TracedAccounts::new(&trace_accounts)?;
+        if validate_blocks {
+            info!("Validating canonical blocks' created & accessed accounts");
+        }
+        let future_block_horizon = future_block_horizon.unwrap_or(2 * chain_constants.k);
+        store.set_chain_constants(&chain_constants)?;
+        store.set_staking_ledger_batch_size(
+            staking_ledger_batch_size.unwrap_or(STAKING_LEDGER_BATCH_SIZE),
+        );
+
         // blocks dir
         if let Some(ref blocks_dir) = blocks_dir {
             if let Err(e) = fs::create_dir_all(blocks_dir) {
@@ -181,15 +271,29 @@ impl IndexerConfiguration {
             indexer_store: store.clone(),
             version: version.clone(),
             genesis_ledger: genesis_ledger.clone(),
-            transition_frontier_length: MAINNET_TRANSITION_FRONTIER_K,
+            transition_frontier_length: chain_constants.k,
             do_not_ingest_orphan_blocks,
             prune_interval,
             canonical_threshold,
             canonical_update_threshold,
             ledger_cadence,
             reporting_freq,
+            ingestion_queue,
+            replace_staking_ledger,
+            trace_accounts: trace_accounts.clone(),
+            validate_blocks,
+            future_block_horizon,
         };
 
+        // in-flight writes of an unclean shutdown may have been torn
+        if !matches!(initialization_mode, InitializationMode::BuildDB)
+            && !store.is_clean_shutdown()?
+        {
+            warn!("Unclean shutdown detected, validating the event log tail");
+            store.recover_unclean_shutdown(UNCLEAN_SHUTDOWN_EVENT_TAIL)?;
+        }
+        store.set_running()?;
+
         let mut state = match initialization_mode {
             InitializationMode::BuildDB => {
                 log_dirs_msg(blocks_dir.as_ref(), staking_ledgers_dir.as_ref());
@@ -208,7 +312,10 @@ impl IndexerConfiguration {
         // ingest staking ledgers
         if let Some(ref staking_ledgers_dir) = staking_ledgers_dir {
             if let Err(e) = state
-                .add_startup_staking_ledgers_to_store(staking_ledgers_dir)
+                .add_startup_staking_ledgers_to_store_from(
+                    staking_ledgers_dir,
+                    staking_ledgers_from_epoch,
+                )
                 .await
             {
                 error!("Failed to ingest staking ledger {staking_ledgers_dir:#?}: {e}");
@@ -225,6 +332,7 @@ impl IndexerConfiguration {
                         canonical_threshold,
                         do_not_ingest_orphan_blocks,
                         reporting_freq,
+                        !no_recursive,
                     )
                     .await
                     .unwrap_or_else(|e| panic!("Obtaining block parser failed: {e}"));
@@ -239,21 +347,29 @@ impl IndexerConfiguration {
                         indexer_store: store.clone(),
                         version,
                         genesis_ledger,
-                        transition_frontier_length: MAINNET_TRANSITION_FRONTIER_K,
+                        transition_frontier_length: chain_constants.k,
                         prune_interval,
                         canonical_threshold,
                         canonical_update_threshold,
                         ledger_cadence,
                         reporting_freq,
+                        ingestion_queue,
                         do_not_ingest_orphan_blocks,
+                        replace_staking_ledger,
+                        trace_accounts,
+                        validate_blocks,
+                        future_block_horizon,
                     })
                 {
                     let min_length_filter = state.replay_events(replay_state)?;
+                    state.recover_ingestion_queue()?;
+
                     if let Some(ref blocks_dir) = blocks_dir {
                         let mut block_parser = BlockParser::new_length_sorted_min_filtered(
                             blocks_dir,
                             pcb_version,
                             min_length_filter,
+                            !no_recursive,
                         )?;
 
                         if block_parser.total_num_blocks > 0 {
@@ -264,12 +380,14 @@ impl IndexerConfiguration {
                 }
             }
             InitializationMode::Sync => {
-                let min_length_filter = state.sync_from_db()?;
+                let min_length_filter = state.startup_from_db()?;
+
                 if let Some(ref blocks_dir) = blocks_dir {
                     let mut block_parser = BlockParser::new_length_sorted_min_filtered(
                         blocks_dir,
                         pcb_version,
                         min_length_filter,
+                        !no_recursive,
                     )?;
 
                     if block_parser.total_num_blocks > 0 {
@@ -284,7 +402,7 @@ impl IndexerConfiguration {
         let store = state.indexer_store.as_ref().unwrap();
         let temp_checkpoint_dir = store.db_path.join("tmp-checkpoint");
 
-        Checkpoint::new(&store.database)?.create_checkpoint(&temp_checkpoint_dir)?;
+        Checkpoint::new(store.database.as_ref())?.create_checkpoint(&temp_checkpoint_dir)?;
         fs::remove_dir_all(&temp_checkpoint_dir)?;
 
         Ok(state)
@@ -297,6 +415,25 @@ impl IndexerConfiguration {
         subsys: SubsystemHandle,
         store: Arc<IndexerStore>,
     ) -> anyhow::Result<()> {
+        self.start_network_indexer(subsys, store, NetworkIndexers::default(), true)
+            .await
+    }
+
+    /// Initializes witness tree, connects the database namespace of the
+    /// config's network & runs the indexer, registering its state with the
+    /// other networks' indexers
+    ///
+    /// Only one of the process' indexers serves the UDS, other networks'
+    /// summaries are selected with the client's `--network`
+    pub async fn start_network_indexer(
+        self,
+        subsys: SubsystemHandle,
+        store: Arc<IndexerStore>,
+        indexers: NetworkIndexers,
+        serve_uds: bool,
+    ) -> anyhow::Result<()> {
+        let network = self.version.network.clone();
+        let store = store.network_store(&network)?;
         let blocks_dir = self.blocks_dir.clone();
         let staking_ledgers_dir = self.staking_ledgers_dir.clone();
         let fetch_new_blocks_delay = self.fetch_new_blocks_delay;
@@ -304,7 +441,20 @@ impl IndexerConfiguration {
         let missing_block_recovery_delay = self.missing_block_recovery_delay;
         let missing_block_recovery_exe = self.missing_block_recovery_exe.clone();
         let missing_block_recovery_batch = self.missing_block_recovery_batch;
+        let block_backfill = self.block_backfill.clone();
         let domain_socket_path = self.domain_socket_path.clone();
+        let no_recursive = self.no_recursive;
+        let maintenance_config = self.maintenance.clone();
+
+        // address watchlist
+        for account in WatchedAccount::parse_all(&self.watch_accounts)? {
+            if store.add_watched_account(&account)? {
+                info!("Watching account {}", account.public_key);
+            }
+        }
+        if let Some(url) = self.watch_webhook.clone() {
+            tokio::spawn(run_webhook(store.subscribe_watch_notifications(), url));
+        }
 
         // initialize witness tree & connect database
         let state = Arc::new(RwLock::new(
@@ -314,8 +464,17 @@ impl IndexerConfiguration {
             }),
         ));
 
+        // background maintenance
+        start_maintenance(&subsys, &state, &store, maintenance_config).await;
+
         // read-only state
-        start_uds_server(&subsys, state.clone(), &domain_socket_path).await?;
+        indexers
+            .write()
+            .expect("network indexers lock")
+            .insert(network, state.clone());
+        if serve_uds {
+            start_uds_server(&subsys, state.clone(), indexers, &domain_socket_path).await?;
+        }
 
         // modifies the state
         let missing_block_recovery =
@@ -335,6 +494,8 @@ impl IndexerConfiguration {
             staking_ledgers_dir,
             missing_block_recovery,
             fetch_new_blocks,
+            block_backfill,
+            no_recursive,
             state.clone(),
         )
         .await?;
@@ -344,7 +505,9 @@ impl IndexerConfiguration {
 
     /// Read the indexer config from the given store or panic
     pub fn read_indexer_config(store: &Arc<IndexerStore>) -> anyhow::Result<Self> {
-        if let Some(config_bytes) = store.database.get(IndexerStore::INDEXER_CONFIG_KEY)? {
+        if let Some(config_bytes) =
+            store.get_cf(store.default_cf(), IndexerStore::INDEXER_CONFIG_KEY)?
+        {
             debug!("Reading mina indexer config from store");
             Ok(serde_json::from_slice(&config_bytes)?)
         } else {
@@ -353,16 +516,43 @@ impl IndexerConfiguration {
     }
 }
 
+/// Starts the maintenance scheduler with the orphan pruning & event log
+/// compaction tasks, as well as block retention if a retention policy is
+/// configured
+async fn start_maintenance(
+    subsys: &SubsystemHandle,
+    state: &Arc<RwLock<IndexerState>>,
+    store: &Arc<IndexerStore>,
+    config: MaintenanceConfig,
+) {
+    let retention = config.retention;
+    let mut scheduler = MaintenanceScheduler::new(config, Arc::new(SystemClock));
+    scheduler.register(Box::new(OrphanPruningTask::new(store.clone())));
+    scheduler.register(Box::new(EventLogCompactionTask::new(store.clone())));
+
+    if retention.is_enabled() {
+        scheduler.register(Box::new(BlockRetentionTask::new(store.clone(), retention)));
+    }
+
+    let maintenance = Arc::new(std::sync::Mutex::new(scheduler));
+    state.write().await.maintenance = Some(maintenance.clone());
+
+    subsys.start(SubsystemBuilder::new("Maintenance", {
+        move |subsys| run_maintenance(subsys, maintenance)
+    }));
+}
+
 /// Starts UDS server with read-only state for summary
 async fn start_uds_server(
     subsys: &SubsystemHandle,
     state: Arc<RwLock<IndexerState>>,
+    indexers: NetworkIndexers,
     domain_socket_path: &Path,
 ) -> anyhow::Result<()> {
     let listener = create_socket_listener(domain_socket_path);
 
     subsys.start(SubsystemBuilder::new("Socket Listener", {
-        move |subsys| handle_connection(listener, state, subsys)
+        move |subsys| handle_connection(listener, state, indexers, subsys)
     }));
 
     Ok(())
@@ -415,6 +605,8 @@ async fn run_indexer<P: AsRef<Path>>(
     staking_ledgers_dir: Option<P>,
     missing_block_recovery: Option<MissingBlockRecoveryOptions>,
     fetch_new_blocks_opts: Option<FetchNewBlocksOptions>,
+    block_backfill: Option<BlockBackfillConfig>,
+    no_recursive: bool,
     state: Arc<RwLock<IndexerState>>,
 ) -> anyhow::Result<()> {
     // setup fs-based precomputed block & staking ledger watchers
@@ -434,7 +626,13 @@ async fn run_indexer<P: AsRef<Path>>(
     )?;
 
     if let Some(ref blocks_dir) = blocks_dir {
-        watcher.watch(blocks_dir.as_ref(), RecursiveMode::NonRecursive)?;
+        let mode = if no_recursive {
+            RecursiveMode::NonRecursive
+        } else {
+            RecursiveMode::Recursive
+        };
+
+        watcher.watch(blocks_dir.as_ref(), mode)?;
         info!(
             "Watching for precomputed blocks in directory: {:#?}",
             blocks_dir.as_ref()
@@ -454,6 +652,21 @@ async fn run_indexer<P: AsRef<Path>>(
     let missing_block_recovery_delay = missing_block_recovery.as_ref().map(|m| m.delay);
     let missing_block_recovery_exe = missing_block_recovery.as_ref().map(|m| m.exe.clone());
     let missing_block_recovery_batch = missing_block_recovery.map_or(false, |m| m.batch);
+    let block_backfill_delay = block_backfill.as_ref().map(|b| b.delay);
+
+    // scanned once, then kept up to date with the watched block files
+    let mut blocks_dir_heights = match (block_backfill.as_ref(), blocks_dir.as_ref()) {
+        (Some(_), Some(blocks_dir)) => {
+            let network = state.read().await.version.network.clone();
+            Some(BlocksDirHeights::scan(
+                blocks_dir.as_ref(),
+                &network,
+                !no_recursive,
+            )?)
+        }
+        _ => None,
+    };
+
     loop {
         tokio::select! {
             // watch for shutdown signals
@@ -464,7 +677,14 @@ async fn run_indexer<P: AsRef<Path>>(
             // watch for precomputed blocks & staking ledgers
             Some(res) = rx.recv() => {
                 match res {
-                    Ok(event) => process_event(event, &state).await?,
+                    Ok(event) => {
+                        if let Some(heights) = blocks_dir_heights.as_mut() {
+                            if matches_event_kind(event.kind) {
+                                event.paths.iter().for_each(|path| heights.insert_file(path));
+                            }
+                        }
+                        process_event(event, &state, &SystemClock).await?
+                    }
                     Err(e) => {
                         error!("Filesystem watcher error: {e}");
                         break;
@@ -489,11 +709,26 @@ async fn run_indexer<P: AsRef<Path>>(
                     }
                 }
             }
+
+            // backfill missing blocks from the block archive
+            _ = tokio::time::sleep(std::time::Duration::from_secs(block_backfill_delay.unwrap_or(BLOCK_BACKFILL_DELAY_SECS))) => {
+                if let Some(ref block_backfill) = block_backfill {
+                    let blocks_dir = blocks_dir.as_ref().map(|dir| dir.as_ref());
+                    if let Err(e) = backfill_missing_blocks(&state, block_backfill, blocks_dir, blocks_dir_heights.as_mut()).await {
+                        error!("Error backfilling missing blocks: {e}")
+                    }
+                }
+            }
         }
     }
 
+    // the write lock waits for in-flight ingestion
     let state = state.write().await;
     if let Some(store) = state.indexer_store.as_ref() {
+        if let Err(e) = store.clean_shutdown() {
+            error!("Failed to cleanly shutdown the indexer store: {e}");
+        }
+
         info!("Canceling db background work");
         store.database.cancel_all_background_work(true)
     }
@@ -519,7 +754,11 @@ async fn retry_parse_staking_ledger(
 }
 
 /// Precomputed block & staking ledger event handler
-async fn process_event(event: Event, state: &Arc<RwLock<IndexerState>>) -> anyhow::Result<()> {
+async fn process_event(
+    event: Event,
+    state: &Arc<RwLock<IndexerState>>,
+    clock: &impl Clock,
+) -> anyhow::Result<()> {
     trace!("Event: {event:?}");
     if matches_event_kind(event.kind) {
         for path in event.paths {
@@ -537,56 +776,42 @@ async fn process_event(event: Event, state: &Arc<RwLock<IndexerState>>) -> anyho
                 continue;
             }
             if block::is_valid_block_file(&path) {
-                debug!("Valid precomputed block file: {}", path.display());
-                match IndexerState::parse_file(state, &path).await {
-                    Ok(block) => {
-                        // Acquire write lock
-                        let mut state = state.write().await;
-
-                        // check if the block is already in the witness tree
-                        if state.diffs_map.contains_key(&block.state_hash()) {
-                            return Ok(info!(
-                                "Block is already present in the witness tree {}",
-                                block.summary()
-                            ));
-                        }
-
-                        // if the block isn't in the witness tree, pipeline it
-                        match state.block_pipeline(&block, path.metadata()?.len()) {
-                            Ok(is_added) => {
-                                if is_added {
-                                    info!("Added block {}", block.summary())
-                                }
-                            }
-                            Err(e) => error!("Error adding block: {e}"),
-                        }
-                    }
-                    Err(e) => error!("Error parsing precomputed block: {e}"),
-                }
+                process_block_file(&path, state, clock).await?;
             } else if staking::is_valid_ledger_file(&path) {
                 // acquire state write lock
                 let version = state.read().await.version.clone();
                 let state = state.write().await;
                 if let Some(store) = state.indexer_store.as_ref() {
-                    match retry_parse_staking_ledger(&path, version.genesis.state_hash.clone())
-                        .await
-                    {
+                    let genesis_state_hash = &version.genesis.state_hash;
+                    let content_hash = match IndexerState::staking_ledger_file_status(
+                        store,
+                        &path,
+                        genesis_state_hash,
+                    )? {
+                        StakingLedgerFileStatus::Unchanged => {
+                            info!("Staking ledger already present {}", path.display());
+                            continue;
+                        }
+                        StakingLedgerFileStatus::New => None,
+                        StakingLedgerFileStatus::Changed { content_hash } => Some(content_hash),
+                    };
+
+                    match retry_parse_staking_ledger(&path, genesis_state_hash.clone()).await {
                         Ok(staking_ledger) => {
-                            let epoch = staking_ledger.epoch;
-                            let ledger_hash = staking_ledger.ledger_hash.clone();
                             let ledger_summary = staking_ledger.summary();
 
-                            info!("Adding staking ledger {ledger_summary}");
-                            store
-                                .add_staking_ledger(
-                                    staking_ledger,
-                                    &state.version.genesis.state_hash,
-                                )
-                                .unwrap_or_else(|e| {
-                                    error!("Error adding staking ledger {ledger_summary} {e}")
-                                });
-                            let mut staking_ledgers = state.staking_ledgers.lock().unwrap();
-                            staking_ledgers.insert(epoch, ledger_hash);
+                            IndexerState::ingest_staking_ledger(
+                                staking_ledger,
+                                &path,
+                                content_hash,
+                                store,
+                                &state.staking_ledgers,
+                                genesis_state_hash,
+                                state.replace_staking_ledger,
+                            )
+                            .unwrap_or_else(|e| {
+                                error!("Error adding staking ledger {ledger_summary} {e}")
+                            });
                         }
                         Err(e) => {
                             error!("Error parsing staking ledger: {e}")
@@ -602,6 +827,78 @@ async fn process_event(event: Event, state: &Arc<RwLock<IndexerState>>) -> anyho
     Ok(())
 }
 
+/// Outcome of adding a received block to the witness tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BlockIngestOutcome {
+    Added,
+    AlreadyPresent,
+    NotAdded,
+}
+
+/// Adds the precomputed block file seen by the watcher to the witness tree &
+/// records its receipt
+pub async fn process_block_file(
+    path: &Path,
+    state: &Arc<RwLock<IndexerState>>,
+    clock: &impl Clock,
+) -> anyhow::Result<()> {
+    debug!("Valid precomputed block file: {}", path.display());
+    let seen_at = clock.now_millis();
+
+    match IndexerState::parse_file(state, path).await {
+        Ok(block) => {
+            let receipt = BlockReceipt::from_watcher(path, seen_at)
+                .map_err(|e| warn!("Unable to read block file receipt: {e}"))
+                .ok();
+
+            if let Err(e) =
+                ingest_block(state, &block, block::block_file_size(path)?, receipt).await
+            {
+                error!("Error adding block: {e}")
+            }
+        }
+        Err(e) => error!("Error parsing precomputed block: {e}"),
+    }
+
+    Ok(())
+}
+
+/// Adds the received block to the witness tree, if it isn't already present,
+/// & records its receipt
+pub async fn ingest_block(
+    state: &Arc<RwLock<IndexerState>>,
+    block: &PrecomputedBlock,
+    block_bytes: u64,
+    receipt: Option<BlockReceipt>,
+) -> anyhow::Result<BlockIngestOutcome> {
+    // Acquire write lock
+    let mut state = state.write().await;
+
+    // check if the block is already in the witness tree
+    if state.diffs_map.contains_key(&block.state_hash()) {
+        info!(
+            "Block is already present in the witness tree {}",
+            block.summary()
+        );
+        return Ok(BlockIngestOutcome::AlreadyPresent);
+    }
+
+    // if the block isn't in the witness tree, pipeline it
+    let outcome = if state.block_pipeline(block, block_bytes)? {
+        info!("Added block {}", block.summary());
+        BlockIngestOutcome::Added
+    } else {
+        BlockIngestOutcome::NotAdded
+    };
+
+    if let Some(receipt) = receipt {
+        state.record_block_receipt(&block.state_hash(), &receipt)?;
+    }
+
+    Ok(outcome)
+}
+
 /// Fetch new blocks
 async fn fetch_new_blocks(
     state: &Arc<RwLock<IndexerState>>,
@@ -642,11 +939,23 @@ async fn recover_missing_blocks(
 ) {
     let state = state.read().await;
     let network = state.version.network.clone();
-    let missing_parent_lengths: HashSet<u32> = state
+    let mut missing_parent_lengths: HashSet<u32> = state
         .dangling_branches
         .iter()
         .map(|b| b.root_block().blockchain_length.saturating_sub(1))
         .collect();
+
+    // parents referenced by stored blocks, but never seen
+    if let Some(store) = state.indexer_store.as_ref() {
+        match store.get_missing_blocks(MISSING_BLOCK_RECOVERY_LIMIT) {
+            Ok(missing_blocks) => missing_parent_lengths.extend(
+                missing_blocks
+                    .into_iter()
+                    .map(|missing| missing.blockchain_length),
+            ),
+            Err(e) => error!("Error getting missing blocks: {e}"),
+        }
+    }
     if missing_parent_lengths.is_empty() {
         return;
     }
@@ -740,20 +1049,46 @@ impl IndexerVersion {
             genesis: GenesisVersion::v2(),
         }
     }
+
+    /// Overrides the genesis with the config file's genesis section
+    pub fn with_genesis_config(mut self, config: &GenesisConfig) -> Self {
+        let genesis = &mut self.genesis;
+        if let Some(state_hash) = config.state_hash.clone() {
+            genesis.state_hash = state_hash;
+        }
+        if let Some(prev_hash) = config.prev_state_hash.clone() {
+            genesis.prev_hash = prev_hash;
+        }
+        if let Some(blockchain_length) = config.blockchain_length {
+            genesis.blockchain_lenth = blockchain_length;
+        }
+        if let Some(global_slot) = config.global_slot {
+            genesis.global_slot = global_slot;
+        }
+        if let Some(last_vrf_output) = config.last_vrf_output.clone() {
+            genesis.last_vrf_output = last_vrf_output;
+        }
+        self
+    }
 }
 
 impl From<(ServerArgsJson, PathBuf)> for IndexerConfiguration {
     fn from(value: (ServerArgsJson, PathBuf)) -> Self {
-        let genesis_ledger = if value.0.genesis_hash == HARDFORK_GENESIS_HASH {
+        let genesis = value.0.genesis.clone().unwrap_or_default();
+        let genesis_ledger = if let Some(path) = genesis.ledger.as_ref() {
+            GenesisLedger::parse_file(path).expect("configured genesis ledger")
+        } else if value.0.genesis_hash == HARDFORK_GENESIS_HASH {
             GenesisLedger::new_v2().expect("v2 genesis ledger")
         } else {
             GenesisLedger::new_v1().expect("v1 genesis ledger")
         };
-        let version = if value.0.genesis_hash == HARDFORK_GENESIS_HASH {
+        let mut version = if value.0.genesis_hash == HARDFORK_GENESIS_HASH {
             IndexerVersion::v2()
         } else {
             IndexerVersion::v1()
-        };
+        }
+        .with_genesis_config(&genesis);
+        version.network = (&value.0.network as &str).into();
 
         Self {
             version,
@@ -767,12 +1102,55 @@ impl From<(ServerArgsJson, PathBuf)> for IndexerConfiguration {
             initialization_mode: InitializationMode::Sync,
             ledger_cadence: value.0.ledger_cadence,
             reporting_freq: value.0.reporting_freq,
+            ingestion_queue: IngestionQueueConfig {
+                max_depth: value.0.ingestion_queue_depth,
+                max_bytes: value.0.ingestion_queue_bytes,
+            },
             do_not_ingest_orphan_blocks: value.0.do_not_ingest_orphan_blocks,
+            no_recursive: value.0.no_recursive,
+            replace_staking_ledger: value.0.replace_staking_ledger,
             fetch_new_blocks_exe: value.0.fetch_new_blocks_exe.map(Into::into),
             fetch_new_blocks_delay: value.0.fetch_new_blocks_delay,
             missing_block_recovery_exe: value.0.missing_block_recovery_exe.map(Into::into),
             missing_block_recovery_delay: value.0.missing_block_recovery_delay,
             missing_block_recovery_batch: value.0.missing_block_recovery_batch.unwrap_or_default(),
+            maintenance: MaintenanceConfig {
+                latency_budget_ms: value
+                    .0
+                    .maintenance_latency_budget
+                    .unwrap_or(MAINTENANCE_LATENCY_BUDGET_MS),
+                yield_ms: value.0.maintenance_yield.unwrap_or(MAINTENANCE_YIELD_MS),
+                paused: value.0.maintenance_paused.unwrap_or_default(),
+                retention: RetentionPolicy {
+                    prune_orphaned_after: value.0.prune_orphaned_after,
+                    prune_noncanonical_below_height: value.0.prune_noncanonical_below_height,
+                },
+                ..Default::default()
+            },
+            trace_accounts: value.0.trace_accounts,
+            validate_blocks: value.0.validate_blocks,
+            future_block_horizon: value.0.future_block_horizon,
+            staking_ledger_batch_size: value.0.staking_ledger_batch_size,
+            staking_ledgers_from_epoch: value.0.staking_ledgers_from_epoch,
+            chain_constants: genesis.chain_constants(),
+            block_backfill: value.0.block_backfill.unwrap_or_default().then(|| {
+                BlockBackfillConfig {
+                    url_template: value
+                        .0
+                        .block_archive_url
+                        .unwrap_or_else(|| BLOCK_ARCHIVE_URL_TEMPLATE.to_string()),
+                    list_url_template: value
+                        .0
+                        .block_archive_list_url
+                        .unwrap_or_else(|| BLOCK_ARCHIVE_LIST_URL_TEMPLATE.to_string()),
+                    delay: value
+                        .0
+                        .block_backfill_delay
+                        .unwrap_or(BLOCK_BACKFILL_DELAY_SECS),
+                }
+            }),
+            watch_accounts: value.0.watch_accounts,
+            watch_webhook: value.0.watch_webhook,
         }
     }
 }
@@ -5,7 +5,7 @@ use crate::{
     store::DbUpdate,
 };
 use serde::{Deserialize, Serialize};
-use speedb::{DBIterator, Direction, IteratorMode};
+use speedb::{DBIterator, Direction, IteratorMode, WriteBatch};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +22,24 @@ pub struct SnarkUpdate {
     pub works: Vec<SnarkWorkSummary>,
 }
 
+/// SNARK job sold in a best chain block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnarkProverJob {
+    pub block_height: u32,
+    pub state_hash: StateHash,
+
+    /// Index of the job in the block's SNARK work
+    pub index: u32,
+    pub fee: u64,
+}
+
+/// Prover's best chain SNARK job count & fees
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SnarkProverJobTotals {
+    pub num_jobs: u32,
+    pub total_fees: u64,
+}
+
 pub type DbSnarkUpdate = DbUpdate<SnarkUpdate>;
 
 pub enum SnarkApplication {
@@ -33,6 +51,14 @@ pub trait SnarkStore {
     /// Add SNARK work in a precomputed block
     fn add_snark_work(&self, block: &PrecomputedBlock) -> anyhow::Result<()>;
 
+    /// Remove the given blocks' SNARK work & its block & fee sort indexes.
+    /// Per prover lists & counts are kept
+    fn remove_snark_work_batch(
+        &self,
+        blocks: &[PrecomputedBlock],
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()>;
+
     /// Get SNARK work in a given block
     fn get_block_snark_work(
         &self,
@@ -207,6 +233,34 @@ pub trait SnarkStore {
         epoch: Option<u32>,
     ) -> anyhow::Result<Option<Vec<u8>>>;
 
+    /////////////////
+    // Prover jobs //
+    /////////////////
+
+    /// Add (or remove) the block's SNARK jobs to (or from) the prover job
+    /// indices
+    fn update_snark_prover_jobs(
+        &self,
+        update: &SnarkUpdate,
+        apply: &SnarkApplication,
+    ) -> anyhow::Result<()>;
+
+    /// Get the prover's best chain SNARK jobs in blocks `from_height` to
+    /// `to_height` (inclusive), sorted by block height & index
+    fn get_snark_prover_jobs(
+        &self,
+        prover: &PublicKey,
+        from_height: u32,
+        to_height: u32,
+    ) -> anyhow::Result<Vec<SnarkProverJob>>;
+
+    /// Get the prover's best chain SNARK job count & fees in the epoch
+    fn get_snark_prover_epoch_jobs(
+        &self,
+        prover: &PublicKey,
+        epoch: u32,
+    ) -> anyhow::Result<SnarkProverJobTotals>;
+
     ///////////////
     // Iterators //
     ///////////////
@@ -247,6 +301,9 @@ pub trait SnarkStore {
         direction: Direction,
     ) -> DBIterator<'_>;
 
+    /// Iterator over SNARK provers per epoch by best chain job fees
+    fn snark_prover_jobs_epoch_iterator(&self, epoch: u32, direction: Direction) -> DBIterator<'_>;
+
     /// Iterator over SNARKs by prover, sorted by block height & index
     fn snark_prover_block_height_iterator(&self, mode: IteratorMode) -> DBIterator<'_>;
 
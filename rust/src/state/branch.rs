@@ -80,30 +80,9 @@ impl Branch {
 
     /// Returns the node id of the best tip
     pub fn best_tip_id(&self) -> NodeId {
-        let mut best_tip_id = self
-            .branches
-            .root_node_id()
+        self.best_tip_with_id()
+            .map(|(node_id, _)| node_id)
             .expect("branch always has root node")
-            .clone();
-
-        for node_id in self.traverse_level_order_ids() {
-            let best_tip = self
-                .branches
-                .get(&best_tip_id)
-                .expect("branch always has root node")
-                .data()
-                .clone();
-            let node = self
-                .branches
-                .get(&node_id)
-                .expect("node_id from iterator")
-                .data()
-                .clone();
-            if best_tip.height < node.height {
-                best_tip_id = node_id.clone();
-            }
-        }
-        best_tip_id
     }
 
     /// Returns the node id of the canonical root, if it exists
@@ -370,10 +349,16 @@ impl Branch {
     }
 
     // Always returns some for a non-empty tree
+    //
+    // The best of the top leaves, independent of their insertion order
     pub fn best_tip_with_id(&self) -> Option<(NodeId, Block)> {
-        let mut leaves = self.top_leaves_with_id();
-        leaves.sort_by(|(_, x), (_, y)| x.cmp(y).reverse());
-        leaves.first().cloned()
+        self.top_leaves_with_id().into_iter().reduce(|best, leaf| {
+            if leaf.1.better_than(&best.1) {
+                leaf
+            } else {
+                best
+            }
+        })
     }
 
     /// Returns the `StateHash`es of the longest chain in the branch,
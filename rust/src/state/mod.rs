@@ -1,41 +1,69 @@
 pub mod branch;
 pub mod summary;
+pub mod tree_graph;
 
 use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
     block::{
+        extract_network_height_hash,
         genesis::GenesisBlock,
         genesis_state_hash::GenesisStateHash,
+        missing::MissingBlock,
         parser::{BlockParser, ParsedBlock},
         precomputed::{PcbVersion, PrecomputedBlock},
+        receipt::BlockReceipt,
         store::BlockStore,
+        validation::{
+            check_accounts_accessed, check_accounts_created, store::ValidationStore,
+            ValidationError,
+        },
         Block, BlockWithoutHeight,
     },
     canonicity::{store::CanonicityStore, Canonicity},
-    chain::{store::ChainStore, ChainData},
+    chain::{store::ChainStore, ChainData, GenesisRegistry},
     constants::*,
+    crash,
     event::{db::*, store::*, witness_tree::*, IndexerEvent},
+    ingestion::{
+        store::IngestionQueueStore, FutureBlockDeferred, GenesisMismatch, IngestionPhase,
+        IngestionQueue, IngestionQueueConfig, IngestionReport, ParentLinkageError, QueueItem,
+        QueuedBlock, SkippedBlock,
+    },
     ledger::{
+        anomaly::AmountAnomaly,
         diff::LedgerDiff,
         genesis::GenesisLedger,
+        overlay::{LedgerOverlay, OverlayLedger},
         staking::{
             parser::{extract_epoch_hash, StakingLedgerParser},
             StakingLedger,
         },
-        store::{staged::StagedLedgerStore, staking::StakingLedgerStore},
+        store::{
+            anomaly::AmountAnomalyStore,
+            best::BestLedgerStore,
+            staged::StagedLedgerStore,
+            staking::{StakingLedgerFile, StakingLedgerFileStatus, StakingLedgerStore},
+        },
         token::TokenAddress,
+        trace::TracedAccounts,
         username::Username,
         Ledger, LedgerHash,
     },
+    maintenance::{MaintenanceHandle, MaintenanceSummary},
     server::IndexerVersion,
     state::{
         branch::Branch,
         summary::{
-            DbStats, SummaryShort, SummaryVerbose, WitnessTreeSummaryShort,
+            DbStats, IngestionQueueSummary, SummaryShort, SummaryVerbose, WitnessTreeSummaryShort,
             WitnessTreeSummaryVerbose,
         },
     },
-    store::{fixed_keys::FixedKeys, username::UsernameStore, IndexerStore},
+    store::{
+        block_subscriptions::{BlockNotification, BlockNotificationKind},
+        fixed_keys::FixedKeys,
+        username::UsernameStore,
+        IndexerStore,
+    },
     utility::{
         functions::pretty_print_duration,
         store::{
@@ -44,11 +72,12 @@ use crate::{
         },
     },
 };
-use anyhow::bail;
-use id_tree::NodeId;
-use log::{debug, error, info, trace};
+use anyhow::{bail, ensure, Context};
+use id_tree::{NodeId, RemoveBehavior::DropChildren};
+use log::{debug, error, info, trace, warn};
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
+    panic::AssertUnwindSafe,
     path::Path,
     str::FromStr,
     sync::{Arc, Mutex},
@@ -70,6 +99,9 @@ pub struct IndexerState {
     /// Ledger corresponding to the canonical root
     pub ledger: Ledger,
 
+    /// Best chain modifications on top of the canonical root ledger
+    pub ledger_overlay: LedgerOverlay,
+
     /// Cadence for computing and storing new ledgers
     pub ledger_cadence: u32,
 
@@ -86,7 +118,8 @@ pub struct IndexerState {
     /// Underlying database
     pub indexer_store: Option<Arc<IndexerStore>>,
 
-    /// Staking ledger epochs and ledger hashes
+    /// Staking ledger flattened epochs (see [GenesisRegistry::flat_epoch])
+    /// and ledger hashes
     pub staking_ledgers: Arc<Mutex<HashMap<u32, LedgerHash>>>,
 
     /// Threshold amount of confirmations to trigger a pruning event
@@ -98,6 +131,31 @@ pub struct IndexerState {
     /// Frequency to report
     pub reporting_freq: u32,
 
+    /// Limits on parsed blocks awaiting application
+    pub ingestion_queue: IngestionQueueConfig,
+
+    /// Blocks rejected or deferred during ingestion
+    pub ingestion_report: IngestionReport,
+
+    /// Max number of blocks above the best tip to add to the witness tree
+    pub future_block_horizon: u32,
+
+    /// Stored blocks above the future block horizon, by length
+    pub deferred_blocks: BTreeSet<(u32, StateHash)>,
+
+    /// Background maintenance scheduler (server only)
+    pub maintenance: Option<MaintenanceHandle>,
+
+    /// Replace stored staking ledgers whose files have changed
+    pub replace_staking_ledger: bool,
+
+    /// Accounts whose canonical ledger diffs are logged
+    pub trace_accounts: Option<TracedAccounts>,
+
+    /// Cross-check canonical blocks' created & accessed accounts against the
+    /// ledger, see [crate::block::validation]
+    pub validate_blocks: bool,
+
     /// Threshold for updating the canonical root and db ledger
     pub canonical_update_threshold: u32,
 
@@ -165,7 +223,12 @@ pub struct IndexerStateConfig {
     pub canonical_update_threshold: u32,
     pub ledger_cadence: u32,
     pub reporting_freq: u32,
+    pub ingestion_queue: IngestionQueueConfig,
     pub do_not_ingest_orphan_blocks: bool,
+    pub replace_staking_ledger: bool,
+    pub trace_accounts: Option<TracedAccounts>,
+    pub validate_blocks: bool,
+    pub future_block_horizon: u32,
 }
 
 impl IndexerStateConfig {
@@ -188,8 +251,19 @@ impl IndexerStateConfig {
             canonical_update_threshold: CANONICAL_UPDATE_THRESHOLD,
             ledger_cadence: LEDGER_CADENCE,
             reporting_freq: BLOCK_REPORTING_FREQ_NUM,
+            ingestion_queue: IngestionQueueConfig::default(),
+            replace_staking_ledger: false,
+            trace_accounts: None,
+            validate_blocks: false,
+            future_block_horizon: 2 * transition_frontier_length,
         }
     }
+
+    /// Selects the version network's namespace of the store
+    fn with_network_store(mut self) -> anyhow::Result<Self> {
+        self.indexer_store = self.indexer_store.network_store(&self.version.network)?;
+        Ok(self)
+    }
 }
 
 impl IndexerState {
@@ -258,6 +332,35 @@ impl IndexerState {
         path: &Path,
     ) -> anyhow::Result<PrecomputedBlock> {
         let genesis_state_hash = GenesisStateHash::from_path(path)?;
+        let pcb_version = Self::switch_pcb_version(state, genesis_state_hash).await?;
+        PrecomputedBlock::parse_file(path, pcb_version)
+    }
+
+    /// Parses the precomputed block file contents, received under the block
+    /// file name `file_name`, e.g. via the HTTP ingestion endpoint
+    pub async fn parse_contents(
+        state: &Arc<tokio::sync::RwLock<Self>>,
+        file_name: &Path,
+        contents: Vec<u8>,
+    ) -> anyhow::Result<PrecomputedBlock> {
+        let (network, blockchain_length, state_hash) = extract_network_height_hash(file_name)?;
+        let genesis_state_hash = GenesisStateHash::from_bytes(&contents)?;
+        let pcb_version = Self::switch_pcb_version(state, genesis_state_hash).await?;
+        PrecomputedBlock::new(
+            &network.to_string(),
+            blockchain_length,
+            &state_hash.0,
+            contents,
+            pcb_version,
+        )
+    }
+
+    /// PCB version of blocks descending from `genesis_state_hash`, switching
+    /// the indexer's version quantities if it changed
+    async fn switch_pcb_version(
+        state: &Arc<tokio::sync::RwLock<Self>>,
+        genesis_state_hash: StateHash,
+    ) -> anyhow::Result<PcbVersion> {
         let read_state = state.read().await;
         let curr_pcb_version = read_state.version.version.clone();
         let (new_pcb_version, new_chain_id) = read_state
@@ -265,7 +368,8 @@ impl IndexerState {
             .0
             .get(&genesis_state_hash)
             .cloned()
-            .expect("PCB version");
+            .with_context(|| format!("Unknown genesis state hash {genesis_state_hash}"))?;
+        drop(read_state);
 
         // if the PCB version changed, change state version quantities
         if curr_pcb_version != new_pcb_version {
@@ -279,15 +383,24 @@ impl IndexerState {
             }
             state.write().await.version.chain_id = new_chain_id;
         }
-        PrecomputedBlock::parse_file(path, new_pcb_version)
+        Ok(new_pcb_version)
     }
 
     /// Creates a new indexer state from the genesis ledger
     pub fn new_from_config(config: IndexerStateConfig) -> anyhow::Result<Self> {
+        let config = config.with_network_store()?;
+
         // set chain id
         config
             .indexer_store
             .set_chain_id_for_network(&config.version.chain_id, &config.version.network)?;
+        // the configured genesis of non-mainnet networks is its own era
+        config.indexer_store.set_genesis_registry(
+            &GenesisRegistry::from(&ChainData::default()).with_genesis(
+                config.version.genesis.state_hash.clone(),
+                config.version.version.clone(),
+            ),
+        )?;
 
         let genesis_block = match config.version.version {
             PcbVersion::V1 => GenesisBlock::new_v1()?,
@@ -340,6 +453,7 @@ impl IndexerState {
                 genesis_block.state_hash(),
                 LedgerDiff::from_precomputed(&genesis_block),
             )]),
+            ledger_overlay: LedgerOverlay::new(tip.state_hash.clone()),
             canonical_root: tip.clone(),
             best_tip: tip,
             root_branch,
@@ -356,6 +470,14 @@ impl IndexerState {
             init_time: Instant::now(),
             ledger_cadence: config.ledger_cadence,
             reporting_freq: config.reporting_freq,
+            ingestion_queue: config.ingestion_queue,
+            ingestion_report: IngestionReport::default(),
+            maintenance: None,
+            replace_staking_ledger: config.replace_staking_ledger,
+            trace_accounts: config.trace_accounts,
+            validate_blocks: config.validate_blocks,
+            future_block_horizon: config.future_block_horizon,
+            deferred_blocks: BTreeSet::new(),
             staking_ledgers: Arc::new(Mutex::new(HashMap::new())),
             chain_data: ChainData::default(),
         })
@@ -363,6 +485,7 @@ impl IndexerState {
 
     /// Creates a new indexer state without genesis events
     pub fn new_without_genesis_events(config: IndexerStateConfig) -> anyhow::Result<Self> {
+        let config = config.with_network_store()?;
         let root_branch = Branch::new_genesis(
             config.version.genesis.state_hash.to_owned(),
             config.version.genesis.prev_hash.to_owned(),
@@ -378,6 +501,7 @@ impl IndexerState {
         Ok(Self {
             ledger: config.genesis_ledger.into(),
             diffs_map: HashMap::new(),
+            ledger_overlay: LedgerOverlay::new(tip.state_hash.clone()),
             canonical_root: tip.clone(),
             best_tip: tip,
             root_branch,
@@ -394,6 +518,14 @@ impl IndexerState {
             init_time: Instant::now(),
             ledger_cadence: config.ledger_cadence,
             reporting_freq: config.reporting_freq,
+            ingestion_queue: config.ingestion_queue,
+            ingestion_report: IngestionReport::default(),
+            maintenance: None,
+            replace_staking_ledger: config.replace_staking_ledger,
+            trace_accounts: config.trace_accounts,
+            validate_blocks: config.validate_blocks,
+            future_block_horizon: config.future_block_horizon,
+            deferred_blocks: BTreeSet::new(),
             staking_ledgers: Arc::new(Mutex::new(HashMap::new())),
             chain_data: ChainData::default(),
         })
@@ -432,6 +564,9 @@ impl IndexerState {
             node_id: root_branch.root.clone(),
         };
 
+        let transition_frontier_length =
+            transition_frontier_length.unwrap_or(MAINNET_TRANSITION_FRONTIER_K);
+
         // apply root block to root ledger and keep its ledger diff
         Ok(Self {
             ledger: root_ledger
@@ -441,13 +576,15 @@ impl IndexerState {
                 root_block.state_hash(),
                 LedgerDiff::from_precomputed(root_block),
             )]),
+            ledger_overlay: LedgerOverlay::new(tip.state_hash.clone()),
             canonical_root: tip.clone(),
             best_tip: tip,
             root_branch,
             dangling_branches: Vec::new(),
             indexer_store: indexer_store.map(Arc::new),
-            transition_frontier_length: transition_frontier_length
-                .unwrap_or(MAINNET_TRANSITION_FRONTIER_K),
+            transition_frontier_length,
+            future_block_horizon: 2 * transition_frontier_length,
+            deferred_blocks: BTreeSet::new(),
             prune_interval: PRUNE_INTERVAL_DEFAULT,
             canonical_threshold: MAINNET_CANONICAL_THRESHOLD,
             canonical_update_threshold: CANONICAL_UPDATE_THRESHOLD,
@@ -457,6 +594,12 @@ impl IndexerState {
             init_time: Instant::now(),
             ledger_cadence: ledger_cadence.unwrap_or(LEDGER_CADENCE),
             reporting_freq: reporting_freq.unwrap_or(BLOCK_REPORTING_FREQ_NUM),
+            ingestion_queue: IngestionQueueConfig::default(),
+            ingestion_report: IngestionReport::default(),
+            maintenance: None,
+            replace_staking_ledger: false,
+            trace_accounts: None,
+            validate_blocks: false,
             staking_ledgers: Arc::new(Mutex::new(HashMap::new())),
             version: IndexerVersion::default(),
             chain_data: ChainData::default(),
@@ -502,6 +645,11 @@ impl IndexerState {
 
                     indexer_store.add_block(&block, block_bytes)?;
                     indexer_store.set_best_block(&block.state_hash())?;
+                    indexer_store.settle_orphaned_blocks(
+                        block
+                            .blockchain_length()
+                            .saturating_sub(self.canonical_threshold),
+                    )?;
                     indexer_store.add_canonical_block(
                         block.blockchain_length(),
                         block.global_slot_since_genesis(),
@@ -513,7 +661,7 @@ impl IndexerState {
                     // compute and store ledger at specified cadence
                     if self.blocks_processed % self.ledger_cadence == 0 {
                         for diff in ledger_diffs.iter() {
-                            self.ledger._apply_diff(diff)?;
+                            self.apply_canonical_diff(diff)?;
                         }
 
                         ledger_diffs.clear();
@@ -527,12 +675,14 @@ impl IndexerState {
                     // update root branch on last deep canonical block
                     if self.blocks_processed > block_parser.num_deep_canonical_blocks {
                         self.root_branch = Branch::new(&block)?;
-                        self.ledger._apply_diff(&diff)?;
+
+                        self.apply_canonical_diff(&diff)?;
                         self.best_tip = Tip {
                             state_hash: self.root_branch.root_block().state_hash.clone(),
                             node_id: self.root_branch.root.clone(),
                         };
                         self.canonical_root = self.best_tip.clone();
+                        self.ledger_overlay = LedgerOverlay::new(self.best_tip.state_hash.clone());
                     }
                 } else {
                     bail!("Block unexpectedly missing");
@@ -555,7 +705,8 @@ impl IndexerState {
     }
 
     /// Adds blocks to the state according to `block_parser` then changes phase
-    /// to Watching
+    /// to Watching. Blocks failing to apply are skipped & reported, a block
+    /// panicking mid-apply stops ingestion & stays unapplied in the journal
     pub async fn add_blocks(&mut self, block_parser: &mut BlockParser) -> anyhow::Result<()> {
         self.add_blocks_with_time(block_parser, None).await
     }
@@ -575,46 +726,325 @@ impl IndexerState {
             );
         }
 
+        // the producer task owns the parser until it's done, progress reports
+        // only need its totals
+        let parser = std::mem::take(block_parser);
+        block_parser.total_num_blocks = parser.total_num_blocks;
+        block_parser.total_num_bytes = parser.total_num_bytes;
+        block_parser.num_deep_canonical_blocks = parser.num_deep_canonical_blocks;
+
+        let mut queue =
+            IngestionQueue::spawn(self.ingestion_queue, parser, self.indexer_store.clone());
+
         loop {
-            tokio::select! {
-                // wait for SIGINT
+            // wait for the producer to parse & journal the next block
+            crash::set_phase(IngestionPhase::Parse);
+            let item = tokio::select! {
+                // wait for SIGINT, journaled blocks are recovered on restart
                 _ = tokio::signal::ctrl_c() => {
                     info!("SIGINT received");
-                    break;
+                    return Ok(());
                 }
 
-                // parse the next precomputed block
-                res = block_parser.next_block() => {
-                    match res {
-                        Ok(Some((parsed_block, block_bytes))) => {
-                            self.report_progress(block_parser, step_time, total_time)?;
-                            step_time = Instant::now();
-
-                            match parsed_block {
-                                ParsedBlock::DeepCanonical(block) | ParsedBlock::Recent(block) => {
-                                    info!("Adding block to witness tree {}", block.summary());
-                                    self.block_pipeline(&block, block_bytes)?;
-                                }
-                                ParsedBlock::Orphaned(block) => {
-                                    trace!("Adding orphaned block to store {}", block.summary());
-                                    self.add_block_to_store(&block, block_bytes, true)?;
-                                }
-                            }
-                        }
-                        Ok(None) => {
-                            info!(
-                                "Finished ingesting and applying {} blocks ({}) to the witness tree in {}",
-                                self.blocks_processed,
-                                bytesize::ByteSize::b(self.bytes_processed),
-                                pretty_print_duration(total_time.elapsed()),
-                            );
-                            break;
-                        }
-                        Err(e) => {
-                            error!("Block ingestion error: {e}");
-                            break;
+                item = queue.recv() => item,
+            };
+
+            // apply the next journaled block
+            match item {
+                Some(QueueItem::Block {
+                    seq_num,
+                    entry,
+                    parsed_block,
+                    permit,
+                }) => {
+                    self.report_progress(block_parser, step_time, total_time)?;
+                    step_time = Instant::now();
+
+                    crash::set_current_block(&entry.state_hash, entry.blockchain_length);
+                    crash::set_phase(IngestionPhase::Apply);
+                    let applied = crash::catch_unwind(AssertUnwindSafe(|| {
+                        self.apply_queued_block(seq_num, &entry, parsed_block)
+                    }));
+
+                    // the witness tree may be partially updated, stop ingesting
+                    // & leave the block unapplied to be retried on restart
+                    let applied = match applied {
+                        Ok(applied) => applied,
+                        Err(panic) => bail!(
+                            "Panicked applying block (length {}): {}, {}",
+                            entry.blockchain_length,
+                            entry.state_hash,
+                            crash::panic_message(panic)
+                        ),
+                    };
+
+                    if let Err(e) = applied {
+                        // don't retry the block on restart
+                        if let Some(indexer_store) = self.indexer_store.as_ref() {
+                            indexer_store.mark_block_applied(seq_num)?;
                         }
+
+                        self.skip_block(SkippedBlock {
+                            path: Some(entry.path),
+                            state_hash: Some(entry.state_hash),
+                            phase: IngestionPhase::Apply,
+                            error: format!("{e:#}"),
+                        });
                     }
+
+                    // release the block's bytes
+                    drop(permit);
+                }
+                Some(QueueItem::Skipped(skipped_block)) => self.skip_block(skipped_block),
+                None => break,
+            }
+        }
+        *block_parser = queue.finish().await?;
+        crash::clear();
+
+        info!(
+            "Finished ingesting and applying {} blocks ({}) to the witness tree in {}",
+            self.blocks_processed,
+            bytesize::ByteSize::b(self.bytes_processed),
+            pretty_print_duration(total_time.elapsed()),
+        );
+
+        if !self.ingestion_report.is_empty() {
+            warn!(
+                "Rejected {} blocks with parent linkage errors & {} blocks from other networks, skipped {} blocks with errors, deferred {} future blocks",
+                self.ingestion_report.parent_linkage_errors.len(),
+                self.ingestion_report.genesis_mismatches.len(),
+                self.ingestion_report.skipped_blocks.len(),
+                self.ingestion_report.future_blocks_deferred.len(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Records the skipped block in the ingestion report
+    fn skip_block(&mut self, skipped_block: SkippedBlock) {
+        error!("{skipped_block}");
+        self.ingestion_report.skipped_blocks.push(skipped_block);
+    }
+
+    /// Applies the journaled block to the state & marks it applied
+    pub fn apply_queued_block(
+        &mut self,
+        seq_num: u32,
+        entry: &QueuedBlock,
+        parsed_block: ParsedBlock,
+    ) -> anyhow::Result<()> {
+        // blocks dir files may come from a historical mirror, so their
+        // receipt times are unreliable
+        match BlockReceipt::from_file(&entry.path) {
+            Ok(receipt) => self.record_block_receipt(&entry.state_hash, &receipt)?,
+            Err(e) => warn!("Unable to read block file receipt {:#?}: {e}", entry.path),
+        }
+
+        match parsed_block {
+            ParsedBlock::DeepCanonical(block) | ParsedBlock::Recent(block) => {
+                info!("Adding block to witness tree {}", block.summary());
+                self.block_pipeline(&block, entry.block_bytes)?;
+            }
+            ParsedBlock::Orphaned(block) => {
+                if self.verify_genesis_lineage(&block) && self.verify_parent_linkage(&block)? {
+                    trace!("Adding orphaned block to store {}", block.summary());
+                    self.add_block_to_store(&block, entry.block_bytes, true)?;
+                }
+            }
+        }
+
+        if let Some(indexer_store) = self.indexer_store.as_ref() {
+            indexer_store.mark_block_applied(seq_num)?;
+        }
+        Ok(())
+    }
+
+    /// Verifies the block's claimed parent against the stored parent, if
+    /// present. Mismatched blocks are rejected & recorded in the ingestion
+    /// report
+    pub fn verify_parent_linkage(&mut self, block: &PrecomputedBlock) -> anyhow::Result<bool> {
+        let indexer_store = match self.indexer_store.as_ref() {
+            Some(indexer_store) => indexer_store,
+            None => return Ok(true),
+        };
+
+        let parent_hash = block.previous_state_hash();
+        if let Some(parent) = indexer_store.get_block_comparison(&parent_hash)? {
+            if parent.state_hash != parent_hash
+                || parent.blockchain_length + 1 != block.blockchain_length()
+            {
+                let linkage_error = ParentLinkageError {
+                    state_hash: block.state_hash(),
+                    blockchain_length: block.blockchain_length(),
+                    parent_hash,
+                    stored_parent_hash: parent.state_hash,
+                    stored_parent_length: parent.blockchain_length,
+                };
+
+                error!("Rejecting block: {linkage_error}");
+                self.ingestion_report
+                    .parent_linkage_errors
+                    .push(linkage_error);
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Verifies the block's genesis state hash is the store's genesis for the
+    /// block's PCB version (the hardfork genesis for v2 blocks of a v1 store).
+    /// Mismatched blocks are rejected & recorded in the ingestion report
+    pub fn verify_genesis_lineage(&mut self, block: &PrecomputedBlock) -> bool {
+        let version = block.version();
+        let genesis_state_hash = block.genesis_state_hash();
+        let expected_genesis_state_hash = if version == self.version.version {
+            Some(&self.version.genesis.state_hash)
+        } else {
+            self.chain_data.genesis_state_hash(&version)
+        };
+
+        if expected_genesis_state_hash == Some(&genesis_state_hash) {
+            return true;
+        }
+
+        let mismatch = GenesisMismatch {
+            state_hash: block.state_hash(),
+            blockchain_length: block.blockchain_length(),
+            expected_genesis_state_hash: expected_genesis_state_hash.cloned(),
+            genesis_state_hash,
+            version,
+        };
+
+        error!("Rejecting block: {mismatch}");
+        self.ingestion_report.genesis_mismatches.push(mismatch);
+        false
+    }
+
+    /// Records the block's receipt times, keeping the first ones recorded so
+    /// reingesting a block doesn't clobber the watcher's times
+    pub fn record_block_receipt(
+        &self,
+        state_hash: &StateHash,
+        receipt: &BlockReceipt,
+    ) -> anyhow::Result<()> {
+        if let Some(indexer_store) = self.indexer_store.as_ref() {
+            if indexer_store.get_block_receipt(state_hash)?.is_none() {
+                indexer_store.set_block_receipt(state_hash, receipt)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Publishes the witness tree update to the store's block subscribers
+    fn publish_block_notification(&self, notification: BlockNotification) {
+        if let Some(indexer_store) = self.indexer_store.as_ref() {
+            indexer_store.publish_block_notification(notification);
+        }
+    }
+
+    /// Applies a canonical block's diff to the ledger, recording anomalies,
+    /// logging traced accounts & validating the block
+    pub fn apply_canonical_diff(&mut self, diff: &LedgerDiff) -> anyhow::Result<()> {
+        let block = self.block_to_validate(&diff.state_hash)?;
+        let mut validation_errors = block
+            .as_ref()
+            .map_or(vec![], |block| check_accounts_created(block, &self.ledger));
+
+        #[cfg(feature = "parallel_diffs")]
+        let anomalies = self.ledger._apply_diff_parallel(diff)?;
+        #[cfg(not(feature = "parallel_diffs"))]
+        let anomalies = self.ledger._apply_diff(diff)?;
+        self.record_amount_anomalies(&anomalies)?;
+
+        if let Some(trace_accounts) = self.trace_accounts.as_ref() {
+            trace_accounts.log(diff, &self.ledger);
+        }
+
+        if let Some(block) = block {
+            validation_errors.extend(check_accounts_accessed(&block, &self.ledger));
+            self.record_validation_errors(&block, &validation_errors)?;
+        }
+        Ok(())
+    }
+
+    /// The post-hardfork block to validate, if validating blocks
+    fn block_to_validate(
+        &self,
+        state_hash: &StateHash,
+    ) -> anyhow::Result<Option<PrecomputedBlock>> {
+        if !self.validate_blocks {
+            return Ok(None);
+        }
+
+        let Some(indexer_store) = self.indexer_store.as_ref() else {
+            return Ok(None);
+        };
+
+        match indexer_store.get_block(state_hash)? {
+            Some((block @ PrecomputedBlock::V2(_), _)) => Ok(Some(block)),
+            Some(_) => Ok(None),
+            None => {
+                warn!("Cannot validate block missing from the store {state_hash}");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Records the block's validation errors, replacing any recorded before
+    pub fn record_validation_errors(
+        &self,
+        block: &PrecomputedBlock,
+        errors: &[ValidationError],
+    ) -> anyhow::Result<()> {
+        for error in errors {
+            warn!("Block validation error: {error}");
+        }
+
+        if let Some(indexer_store) = self.indexer_store.as_ref() {
+            indexer_store.set_validation_errors(
+                &block.state_hash(),
+                block.blockchain_length(),
+                errors,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Records anomalies of account diffs saturated by the ledger
+    pub fn record_amount_anomalies(&self, anomalies: &[AmountAnomaly]) -> anyhow::Result<()> {
+        if let Some(indexer_store) = self.indexer_store.as_ref() {
+            for anomaly in anomalies {
+                indexer_store.add_amount_anomaly(anomaly)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-applies journaled blocks which were not applied before shutdown
+    ///
+    /// Blocks already in the store are skipped by the block pipeline
+    pub fn recover_ingestion_queue(&mut self) -> anyhow::Result<()> {
+        let indexer_store = match self.indexer_store.clone() {
+            Some(indexer_store) => indexer_store,
+            None => return Ok(()),
+        };
+
+        let unapplied_blocks = indexer_store.get_unapplied_blocks()?;
+        if !unapplied_blocks.is_empty() {
+            info!(
+                "Recovering {} journaled blocks from the ingestion queue",
+                unapplied_blocks.len()
+            );
+        }
+
+        for (seq_num, entry) in unapplied_blocks {
+            match entry.parse() {
+                Ok(parsed_block) => self.apply_queued_block(seq_num, &entry, parsed_block)?,
+                Err(e) => {
+                    error!("Dropping journaled block {seq_num} {:#?}: {e}", entry.path);
+                    indexer_store.mark_block_applied(seq_num)?;
                 }
             }
         }
@@ -622,6 +1052,7 @@ impl IndexerState {
     }
 
     /// **Block pipeline**
+    /// - verify the block's parent linkage
     /// - add block to
     ///     - block store
     ///     - witness tree
@@ -633,42 +1064,142 @@ impl IndexerState {
         block: &PrecomputedBlock,
         block_bytes: u64,
     ) -> anyhow::Result<bool> {
+        if !self.verify_genesis_lineage(block) || !self.verify_parent_linkage(block)? {
+            return Ok(false);
+        }
+
         if let Some(db_event) = self.add_block_to_store(block, block_bytes, false)? {
             self.bytes_processed += block_bytes;
 
-            let (best_tip, new_canonical_blocks) = if db_event.is_new_block_event() {
-                if let Some(wt_event) = self.add_block_to_witness_tree(block, true, true)?.1 {
-                    match wt_event {
-                        WitnessTreeEvent::UpdateBestTip {
-                            best_tip,
-                            canonical_blocks,
-                        } => (best_tip, canonical_blocks),
-                    }
-                } else {
-                    return Ok(true);
-                }
-            } else {
+            if !db_event.is_new_block_event() {
                 debug!("Block not added: {db_event:?}");
                 return Ok(false);
-            };
+            }
 
-            if let Some(username_updates) = self.update_best_block_in_store(&best_tip.state_hash)? {
-                for (pk, username) in username_updates.iter() {
-                    // only use MINA token
-                    if let Some(account) = self.ledger.get_mut_account(pk, &TokenAddress::default())
-                    {
-                        account.username = Some(username.clone());
-                    }
+            self.witness_tree_pipeline(block, true)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Adds the stored block to the witness tree & updates the store's best
+    /// block & canonical blocks, then adds any deferred future blocks now
+    /// within the horizon
+    ///
+    /// The witness tree updates are published to the store's block
+    /// subscribers
+    fn witness_tree_pipeline(
+        &mut self,
+        block: &PrecomputedBlock,
+        increment_blocks: bool,
+    ) -> anyhow::Result<()> {
+        let witness_tree_event = self
+            .add_block_to_witness_tree(block, increment_blocks, true)?
+            .1;
+        self.publish_block_notification(BlockNotification::from_precomputed(
+            BlockNotificationKind::NewBlock,
+            block,
+        ));
+
+        let Some(WitnessTreeEvent::UpdateBestTip {
+            best_tip,
+            canonical_blocks: new_canonical_blocks,
+        }) = witness_tree_event
+        else {
+            return Ok(());
+        };
+
+        if let Some(username_updates) = self.update_best_block_in_store(&best_tip.state_hash)? {
+            for (pk, username) in username_updates.iter() {
+                // only use MINA token
+                if let Some(account) = self.ledger.get_mut_account(pk, &TokenAddress::default()) {
+                    account.username = Some(username.clone());
+                }
+                if let Some(account) = self
+                    .ledger_overlay
+                    .get_mut_account(pk, &TokenAddress::default())
+                {
+                    account.username = Some(username.clone());
                 }
             }
+        }
 
-            new_canonical_blocks.iter().for_each(|block| {
-                self.add_canonical_block_to_store(block, &block.genesis_state_hash, None)
-                    .unwrap()
-            });
+        self.publish_block_notification(BlockNotification::new(
+            BlockNotificationKind::BestTipChanged,
+            &best_tip,
+        ));
+
+        new_canonical_blocks.iter().for_each(|block| {
+            self.add_canonical_block_to_store(block, &block.genesis_state_hash, None)
+                .unwrap();
+            self.publish_block_notification(BlockNotification::new(
+                BlockNotificationKind::NewCanonicalBlock,
+                block,
+            ));
+        });
+
+        for block in self.promote_deferred_blocks()? {
+            self.witness_tree_pipeline(&block, false)?;
         }
 
-        Ok(true)
+        Ok(())
+    }
+
+    /// Whether the block is too far above the best tip to be added to the
+    /// witness tree
+    ///
+    /// Only stored blocks are deferred, so they can be added later
+    fn is_future_block(&self, block: &PrecomputedBlock) -> bool {
+        self.indexer_store.is_some()
+            && block.blockchain_length()
+                > self
+                    .best_tip_block()
+                    .blockchain_length
+                    .saturating_add(self.future_block_horizon)
+    }
+
+    fn defer_future_block(&mut self, block: &PrecomputedBlock) {
+        let deferred = FutureBlockDeferred {
+            state_hash: block.state_hash(),
+            blockchain_length: block.blockchain_length(),
+            best_tip_length: self.best_tip_block().blockchain_length,
+            horizon: self.future_block_horizon,
+        };
+
+        warn!("Deferring future block: {deferred}");
+        self.deferred_blocks
+            .insert((deferred.blockchain_length, deferred.state_hash.clone()));
+        self.ingestion_report.future_blocks_deferred.push(deferred);
+    }
+
+    /// Removes the deferred blocks now within the future block horizon &
+    /// reads them from the store, lowest first
+    pub fn promote_deferred_blocks(&mut self) -> anyhow::Result<Vec<PrecomputedBlock>> {
+        let max_length = self
+            .best_tip_block()
+            .blockchain_length
+            .saturating_add(self.future_block_horizon);
+        let Some(indexer_store) = self.indexer_store.as_ref() else {
+            return Ok(vec![]);
+        };
+
+        let mut blocks = vec![];
+        while self
+            .deferred_blocks
+            .first()
+            .is_some_and(|(length, _)| *length <= max_length)
+        {
+            let (length, state_hash) = self.deferred_blocks.pop_first().expect("deferred block");
+            match indexer_store.get_block(&state_hash)? {
+                Some((block, _)) => {
+                    info!("Promoting deferred block (length {length}): {state_hash}");
+                    blocks.push(block);
+                }
+                None => error!("Deferred block missing from store (length {length}): {state_hash}"),
+            }
+        }
+
+        Ok(blocks)
     }
 
     /// Adds the block to the witness tree & skips store operations
@@ -687,6 +1218,16 @@ impl IndexerState {
             return Ok((ExtensionType::BlockNotAdded, None));
         }
 
+        if increment_blocks {
+            self.blocks_processed += 1;
+        }
+
+        // blocks far above the best tip are added once it catches up
+        if self.is_future_block(precomputed_block) {
+            self.defer_future_block(precomputed_block);
+            return Ok((ExtensionType::BlockNotAdded, None));
+        }
+
         // put the pcb's ledger diff in the map
         if insert_diff {
             self.diffs_map.insert(
@@ -695,10 +1236,6 @@ impl IndexerState {
             );
         }
 
-        if increment_blocks {
-            self.blocks_processed += 1;
-        }
-
         // forward extension on root branch
         if self.is_length_within_root_bounds(precomputed_block) {
             if let Some(root_extension) = self.root_extension(precomputed_block)? {
@@ -707,11 +1244,14 @@ impl IndexerState {
                     ExtensionType::RootComplex(block) => block.clone(),
                     _ => unreachable!(),
                 };
+                let canonical_blocks = self.prune_root_branch()?;
+
+                self.update_ledger_overlay()?;
                 return Ok((
                     root_extension,
                     Some(WitnessTreeEvent::UpdateBestTip {
                         best_tip,
-                        canonical_blocks: self.prune_root_branch()?,
+                        canonical_blocks,
                     }),
                 ));
             }
@@ -760,13 +1300,12 @@ impl IndexerState {
                 }
             }
 
-            // check if the best chain is extended
-            let best_tip_id = merged_tip_ids.iter().min_by(|a, b| {
-                let a_best_block = self.root_branch.branches.get(a).unwrap().data();
-                let b_best_block = self.root_branch.branches.get(b).unwrap().data();
-                a_best_block.cmp(b_best_block)
-            });
-            if let Some(merged_tip_id) = best_tip_id {
+            // check if the best chain is extended, the merged tips are sorted
+            // better first (see [Block::better_than]) so the best tip doesn't
+            // depend on the dangling branches' order
+            let block = |node_id: &NodeId| self.root_branch.branches.get(node_id).unwrap().data();
+            merged_tip_ids.sort_by(|a, b| block(a).cmp(block(b)));
+            if let Some(merged_tip_id) = merged_tip_ids.first() {
                 let merged_tip_block = self
                     .root_branch
                     .branches
@@ -798,43 +1337,50 @@ impl IndexerState {
     }
 
     /// Extends an existing dangling branch either forwards or backwards
+    ///
+    /// Forward extensions take precedence so a block joining two dangling
+    /// branches always extends its parent's branch & the child branches are
+    /// then merged onto it, regardless of the branches' order
     fn dangling_extension(
         &mut self,
         precomputed_block: &PrecomputedBlock,
     ) -> anyhow::Result<Option<(usize, NodeId, ExtensionDirection)>> {
-        let mut extension = None;
-        for (index, dangling_branch) in self.dangling_branches.iter_mut().enumerate() {
+        let incoming_length = precomputed_block.blockchain_length();
+        let within_bounds = |dangling_branch: &Branch| {
             let min_length = dangling_branch.root_block().blockchain_length;
             let max_length = dangling_branch.best_tip().unwrap().blockchain_length;
+            max_length + 1 >= incoming_length && incoming_length + 1 >= min_length
+        };
 
-            // check incoming block is within the length bounds
-            if max_length + 1 >= precomputed_block.blockchain_length()
-                && precomputed_block.blockchain_length() + 1 >= min_length
-            {
-                // simple reverse
-                if is_reverse_extension(dangling_branch, precomputed_block) {
-                    dangling_branch.new_root(precomputed_block);
-                    extension = Some((
-                        index,
-                        dangling_branch
-                            .branches
-                            .root_node_id()
-                            .expect("has root")
-                            .clone(),
-                        ExtensionDirection::Reverse,
-                    ));
-                    break;
-                }
-
-                // simple forward
+        // simple forward
+        for (index, dangling_branch) in self.dangling_branches.iter_mut().enumerate() {
+            if within_bounds(dangling_branch) {
                 if let Some((new_node_id, _)) = dangling_branch.simple_extension(precomputed_block)
                 {
-                    extension = Some((index, new_node_id, ExtensionDirection::Forward));
-                    break;
+                    return Ok(Some((index, new_node_id, ExtensionDirection::Forward)));
                 }
             }
         }
-        Ok(extension)
+
+        // simple reverse
+        for (index, dangling_branch) in self.dangling_branches.iter_mut().enumerate() {
+            if within_bounds(dangling_branch)
+                && is_reverse_extension(dangling_branch, precomputed_block)
+            {
+                dangling_branch.new_root(precomputed_block);
+                return Ok(Some((
+                    index,
+                    dangling_branch
+                        .branches
+                        .root_node_id()
+                        .expect("has root")
+                        .clone(),
+                    ExtensionDirection::Reverse,
+                )));
+            }
+        }
+
+        Ok(None)
     }
 
     /// Updates an existing dangling branch in the witness tree
@@ -894,7 +1440,7 @@ impl IndexerState {
     /// Update the best tip of the root branch if the incoming block is better
     fn update_best_tip(&mut self, incoming_block: &Block, node_id: &NodeId) {
         let old_best_tip = self.best_tip_block();
-        if incoming_block < old_best_tip {
+        if incoming_block.better_than(old_best_tip) {
             info!(
                 "Update best tip\n    old: {}\n    new: {}",
                 old_best_tip.summary(),
@@ -929,16 +1475,23 @@ impl IndexerState {
     /// The highest known canonical block
     pub fn canonical_root_block(&self) -> &Block {
         self.get_block_from_id(&self.canonical_root.node_id)
+            .expect("canonical root is in the root branch")
     }
 
     /// The highest block known to be a descendant of the root block
     pub fn best_tip_block(&self) -> &Block {
         self.get_block_from_id(&self.best_tip.node_id)
+            .expect("best tip is in the root branch")
     }
 
     /// Only works with blocks in the root branch
-    fn get_block_from_id(&self, node_id: &NodeId) -> &Block {
-        self.root_branch.branches.get(node_id).unwrap().data()
+    fn get_block_from_id(&self, node_id: &NodeId) -> anyhow::Result<&Block> {
+        Ok(self
+            .root_branch
+            .branches
+            .get(node_id)
+            .with_context(|| format!("Node {node_id:?} not in the root branch"))?
+            .data())
     }
 
     /// Updates the canonical root if the precondition is met
@@ -989,40 +1542,179 @@ impl IndexerState {
         best_chain
     }
 
-    /// Returns the best ledger
-    pub fn best_ledger(&self) -> Ledger {
+    /// Rolls the best tip back to the best chain block, unapplying the
+    /// blocks above it from the store & dropping them (with their
+    /// descendants) from the witness tree
+    ///
+    /// Only blocks above the canonical root can be rolled back. Rolled back
+    /// blocks remain in the block store, as orphans. Returns the rolled back
+    /// best chain blocks, highest first.
+    pub fn rollback_to(&mut self, state_hash: &StateHash) -> anyhow::Result<Vec<Block>> {
+        let mut best_chain_ids = vec![self.best_tip.node_id.clone()];
+        best_chain_ids.extend(
+            self.root_branch
+                .branches
+                .ancestor_ids(&self.best_tip.node_id)
+                .expect("best tip is in the root branch")
+                .cloned(),
+        );
+
+        let best_chain = self.best_chain();
+        let Some(index) = best_chain
+            .iter()
+            .position(|block| block.state_hash == *state_hash)
+        else {
+            bail!(
+                "Cannot roll back to {state_hash}, it's not a best chain block in the witness tree"
+            );
+        };
+
+        let target = best_chain[index].clone();
+        let canonical_root = self.canonical_root_block();
+        if target.blockchain_length < canonical_root.blockchain_length {
+            bail!(
+                "Cannot roll back to {}, it's below the canonical root {}",
+                target.summary(),
+                canonical_root.summary()
+            );
+        }
+
+        let rolled_back = best_chain[..index].to_vec();
+        if rolled_back.is_empty() {
+            return Ok(rolled_back);
+        }
+
+        // unapply the blocks from the store, highest first
+        if let Some(indexer_store) = self.indexer_store.as_ref() {
+            for block in rolled_back.iter() {
+                info!("Unapplying block {}", block.summary());
+                indexer_store.unapply_block(&block.state_hash)?;
+            }
+        }
+
+        // drop the lowest rolled back block's subtree from the witness tree
+        let subtree_root_id = best_chain_ids[index - 1].clone();
+        let dropped: Vec<_> = self
+            .root_branch
+            .branches
+            .traverse_pre_order(&subtree_root_id)
+            .expect("rolled back block is in the root branch")
+            .map(|node| node.data().state_hash.clone())
+            .collect();
+        self.root_branch
+            .branches
+            .remove_node(subtree_root_id, DropChildren)
+            .expect("rolled back block is in the root branch");
+
+        for state_hash in dropped.iter() {
+            self.diffs_map.remove(state_hash);
+        }
+
+        info!(
+            "Rolled back {} blocks to {}",
+            rolled_back.len(),
+            target.summary()
+        );
+        self.best_tip = Tip {
+            state_hash: target.state_hash.clone(),
+            node_id: best_chain_ids[index].clone(),
+        };
+        self.rebuild_ledger_overlay()?;
+        self.publish_block_notification(BlockNotification::new(
+            BlockNotificationKind::BestTipChanged,
+            &target,
+        ));
+
+        Ok(rolled_back)
+    }
+
+    /// Returns an owned copy of the best ledger
+    ///
+    /// Account lookups should go through [Self::best_ledger_view] instead,
+    /// which doesn't clone the canonical root ledger
+    pub fn best_ledger(&self) -> anyhow::Result<Ledger> {
+        if self.ledger_overlay.tip == self.best_tip.state_hash {
+            return Ok(self.ledger_overlay.materialize(&self.ledger));
+        }
+
+        // stale overlay, apply each best chain block's ledger diff
+        // skip canonical blocks since they've already modified the ledger
         let mut best_ledger = self.ledger.to_owned();
+        for ledger_diff in self.best_chain_ledger_diffs()? {
+            best_ledger
+                ._apply_diff(&ledger_diff)
+                .context("Error applying ledger diff")?;
+        }
+
+        Ok(best_ledger)
+    }
 
+    /// Returns a view of the best ledger without cloning the canonical root
+    /// ledger
+    pub fn best_ledger_view(&self) -> OverlayLedger<'_> {
+        self.ledger_overlay.view(&self.ledger)
+    }
+
+    /// Ledger diffs of the best chain blocks above the canonical root, lowest
+    /// to highest
+    fn best_chain_ledger_diffs(&self) -> anyhow::Result<Vec<LedgerDiff>> {
         let mut best_chain = self.best_chain();
         best_chain.reverse();
 
-        // traverse best chain applying each block's ledger diff
-        // skip canonical blocks since they've already modified the ledger
-        for block in best_chain
+        best_chain
             .iter()
             .skip_while(|b| *b != self.canonical_root_block())
             .skip(1)
-        {
-            if let Some(ledger_diff) =
-                self.diffs_map.get(&block.state_hash).cloned().or_else(|| {
-                    if let Some(store) = self.indexer_store.as_ref() {
-                        if let Ok(diff) = store.get_block_ledger_diff(&block.state_hash) {
-                            return diff;
-                        }
-                    }
+            .map(|block| {
+                self.get_ledger_diff(&block.state_hash)
+                    .with_context(|| format!("Missing block from diffs map {}", block.summary()))
+            })
+            .collect()
+    }
 
-                    None
-                })
-            {
-                if let Err(err) = best_ledger._apply_diff(&ledger_diff) {
-                    panic!("Error applying ledger diff: {err}");
+    /// Get the block's ledger diff from the diffs map, or the store
+    fn get_ledger_diff(&self, state_hash: &StateHash) -> Option<LedgerDiff> {
+        self.diffs_map.get(state_hash).cloned().or_else(|| {
+            if let Some(store) = self.indexer_store.as_ref() {
+                if let Ok(diff) = store.get_block_ledger_diff(state_hash) {
+                    return diff;
                 }
-            } else {
-                panic!("Missing block from diffs map {}", block.summary());
+            }
+
+            None
+        })
+    }
+
+    /// Keeps the ledger overlay in sync with the best tip
+    ///
+    /// Best chain extensions are applied incrementally, reorgs only rebuild
+    /// the overlay
+    fn update_ledger_overlay(&mut self) -> anyhow::Result<()> {
+        let canonical_root_length = self.canonical_root_block().blockchain_length;
+        self.ledger_overlay.prune(canonical_root_length);
+
+        let best_tip = self.best_tip_block();
+        if best_tip.state_hash == self.ledger_overlay.tip {
+            return Ok(());
+        }
+
+        if best_tip.parent_hash == self.ledger_overlay.tip {
+            if let Some(diff) = self.get_ledger_diff(&best_tip.state_hash) {
+                self.ledger_overlay.apply_diff(&self.ledger, &diff);
+                return Ok(());
             }
         }
 
-        best_ledger
+        self.rebuild_ledger_overlay()
+    }
+
+    /// Rebuilds the ledger overlay from the best chain above the canonical
+    /// root
+    pub fn rebuild_ledger_overlay(&mut self) -> anyhow::Result<()> {
+        let diffs = self.best_chain_ledger_diffs()?;
+        self.ledger_overlay
+            .rebuild(&self.ledger, self.best_tip.state_hash.clone(), &diffs);
+        Ok(())
     }
 
     /// Get the canonical block at the given height
@@ -1054,6 +1746,17 @@ impl IndexerState {
     pub async fn add_startup_staking_ledgers_to_store(
         &mut self,
         ledgers_dir: &Path,
+    ) -> anyhow::Result<()> {
+        self.add_startup_staking_ledgers_to_store_from(ledgers_dir, None)
+            .await
+    }
+
+    /// Add the staking ledgers in `ledgers_dir` of epochs from `from_epoch`
+    /// on (all epochs by default), resuming interrupted ingestions
+    pub async fn add_startup_staking_ledgers_to_store_from(
+        &mut self,
+        ledgers_dir: &Path,
+        from_epoch: Option<u32>,
     ) -> anyhow::Result<()> {
         match std::fs::read_dir(ledgers_dir) {
             Ok(dir) => {
@@ -1068,19 +1771,30 @@ impl IndexerState {
         let ledger_parser = StakingLedgerParser::new(ledgers_dir)?;
 
         if let Some(indexer_store) = self.indexer_store.as_ref() {
+            let genesis_state_hash = &self.version.genesis.state_hash;
+            if let Some(epoch) = indexer_store.get_last_staking_ledger_epoch(genesis_state_hash)? {
+                info!("Last fully ingested staking ledger epoch: {epoch}");
+            }
+            if let Some(from_epoch) = from_epoch {
+                info!("Skipping staking ledgers before epoch {from_epoch}");
+            }
+
             // Create tasks for each file to be opened and processed concurrently
             let tasks: Vec<_> = ledger_parser
                 .ledger_paths
+                .filter(|path| from_epoch.map_or(true, |from| extract_epoch_hash(path).0 >= from))
                 .map(|path| {
                     let staking_ledgers = self.staking_ledgers.clone();
                     let genesis_state_hash = self.version.genesis.state_hash.clone();
                     let indexer_store = indexer_store.clone();
+                    let replace_staking_ledger = self.replace_staking_ledger;
                     tokio::task::spawn(async move {
                         Self::process_staking_ledger(
                             &path,
                             &indexer_store,
                             &staking_ledgers,
                             &genesis_state_hash,
+                            replace_staking_ledger,
                         )
                         .await
                     })
@@ -1099,19 +1813,144 @@ impl IndexerState {
         store: &Arc<IndexerStore>,
         staking_ledgers: &Arc<Mutex<HashMap<u32, LedgerHash>>>,
         genesis_state_hash: &StateHash,
+        replace_staking_ledger: bool,
     ) -> anyhow::Result<()> {
-        let (epoch, hash) = extract_epoch_hash(path);
-        if store.get_staking_ledger_hash_by_epoch(epoch, None)? != Some(hash) {
-            let staking_ledger =
-                StakingLedger::parse_file(path, genesis_state_hash.clone()).await?;
-            let summary = staking_ledger.summary();
-            let mut staking_ledgers = staking_ledgers.lock().unwrap();
+        let content_hash = match Self::staking_ledger_file_status(store, path, genesis_state_hash)?
+        {
+            StakingLedgerFileStatus::Unchanged => {
+                debug!("Staking ledger already present {}", path.display());
+                return Ok(());
+            }
+            StakingLedgerFileStatus::New => None,
+            StakingLedgerFileStatus::Changed { content_hash } => Some(content_hash),
+        };
 
-            staking_ledgers.insert(staking_ledger.epoch, staking_ledger.ledger_hash.clone());
-            store.add_staking_ledger(staking_ledger, genesis_state_hash)?;
-            info!("Added staking ledger {summary}");
+        let staking_ledger = StakingLedger::parse_file(path, genesis_state_hash.clone()).await?;
+        Self::ingest_staking_ledger(
+            staking_ledger,
+            path,
+            content_hash,
+            store,
+            staking_ledgers,
+            genesis_state_hash,
+            replace_staking_ledger,
+        )
+    }
+
+    /// Compare the staking ledger file to the one its epoch's stored ledger
+    /// was ingested from. The file is only content hashed when its epoch is
+    /// stored & its size or modification time differ, a touched file with the
+    /// same contents has its new modification time recorded
+    pub fn staking_ledger_file_status(
+        store: &IndexerStore,
+        path: &Path,
+        genesis_state_hash: &StateHash,
+    ) -> anyhow::Result<StakingLedgerFileStatus> {
+        let (epoch, _) = extract_epoch_hash(path);
+        let file = StakingLedgerFile::new(path)?;
+
+        if store
+            .get_staking_ledger_file(epoch, genesis_state_hash)?
+            .is_some_and(|stored| stored == file)
+        {
+            return Ok(StakingLedgerFileStatus::Unchanged);
         }
-        Ok(())
+
+        if store
+            .get_staking_ledger_hash_by_epoch(epoch, Some(genesis_state_hash))?
+            .is_none()
+        {
+            return Ok(StakingLedgerFileStatus::New);
+        }
+
+        let content_hash = StakingLedger::content_hash(path)?;
+        if store
+            .get_staking_ledger_content_hash(epoch, genesis_state_hash)?
+            .is_some_and(|stored| stored == content_hash)
+        {
+            store.set_staking_ledger_file(epoch, genesis_state_hash, &file)?;
+            return Ok(StakingLedgerFileStatus::Unchanged);
+        }
+
+        Ok(StakingLedgerFileStatus::Changed { content_hash })
+    }
+
+    /// Add a staking ledger for a new epoch. If the epoch's staking ledger is
+    /// already stored & differs, it is only replaced if
+    /// `replace_staking_ledger`
+    pub fn ingest_staking_ledger(
+        staking_ledger: StakingLedger,
+        path: &Path,
+        content_hash: Option<String>,
+        store: &IndexerStore,
+        staking_ledgers: &Arc<Mutex<HashMap<u32, LedgerHash>>>,
+        genesis_state_hash: &StateHash,
+        replace_staking_ledger: bool,
+    ) -> anyhow::Result<()> {
+        let epoch = staking_ledger.epoch;
+        let ledger_hash = staking_ledger.ledger_hash.clone();
+        let summary = staking_ledger.summary();
+        let file = StakingLedgerFile::new(path)?;
+
+        // an interrupted ingestion is resumed, its partial ledger isn't
+        // compared to the file's
+        let is_checkpointed = store
+            .get_staking_ledger_checkpoint(epoch, genesis_state_hash)?
+            .is_some_and(|checkpoint| checkpoint.ledger_hash == ledger_hash);
+
+        let stored = if is_checkpointed {
+            None
+        } else {
+            store.build_staking_ledger(epoch, Some(genesis_state_hash))?
+        };
+
+        match stored {
+            None => {
+                store.add_staking_ledger(staking_ledger, genesis_state_hash)?;
+                if is_checkpointed {
+                    info!("Resumed staking ledger {summary}");
+                } else {
+                    info!("Added staking ledger {summary}");
+                }
+            }
+            Some(stored) => {
+                let diff = stored.diff(&staking_ledger);
+
+                if diff.is_empty() {
+                    debug!("Staking ledger {summary} unchanged");
+                } else if !replace_staking_ledger {
+                    warn!(
+                        "Skipping changed staking ledger (use --replace-staking-ledger to replace it): {}",
+                        diff.summary()
+                    );
+                    return Ok(());
+                } else {
+                    let content_hash = match content_hash.as_ref() {
+                        Some(content_hash) => content_hash.clone(),
+                        None => StakingLedger::content_hash(path)?,
+                    };
+                    let diff = store.replace_staking_ledger(
+                        staking_ledger,
+                        genesis_state_hash,
+                        &content_hash,
+                        &file,
+                    )?;
+                    info!("Replaced staking ledger {}", diff.summary());
+                }
+            }
+        }
+
+        // keyed by flattened epoch since epochs restart at each genesis
+        let flat_epoch = store.get_flat_epoch(genesis_state_hash, epoch)?;
+        staking_ledgers
+            .lock()
+            .unwrap()
+            .insert(flat_epoch, ledger_hash);
+
+        if let Some(content_hash) = content_hash {
+            store.set_staking_ledger_content_hash(epoch, genesis_state_hash, &content_hash)?;
+        }
+        store.set_staking_ledger_file(epoch, genesis_state_hash, &file)
     }
 
     /// Add block to the underlying block store
@@ -1154,12 +1993,51 @@ impl IndexerState {
         state_hash: &StateHash,
     ) -> anyhow::Result<Option<HashMap<PublicKey, Username>>> {
         if let Some(indexer_store) = self.indexer_store.as_ref() {
+            let reorg_depth = match indexer_store.get_best_block_hash()? {
+                Some(old) if old != *state_hash => {
+                    indexer_store.reorg_blocks(&old, state_hash)?.unapply.len() as u32
+                }
+                _ => 0,
+            };
             indexer_store.set_best_block(state_hash)?;
+
+            // blocks `canonical_threshold` below the best tip are settled
+            if let Some(height) = indexer_store.get_block_height(state_hash)? {
+                indexer_store
+                    .settle_orphaned_blocks(height.saturating_sub(self.canonical_threshold))?;
+
+                // deep reorgs may have left inconsistent canonicity behind
+                if reorg_depth > self.canonical_threshold {
+                    indexer_store.record_canonicity_verification(
+                        height.saturating_sub(reorg_depth + self.canonical_threshold),
+                    )?;
+                }
+            }
             return indexer_store.get_block_username_updates(state_hash);
         }
         Ok(None)
     }
 
+    /// Starts a state created by [Self::new_without_genesis_events] from its
+    /// store, syncing the witness tree & applying any journaled blocks
+    ///
+    /// Returns the min length of blocks to ingest from the blocks dir
+    pub fn startup_from_db(&mut self) -> anyhow::Result<Option<u32>> {
+        let min_length_filter = self.sync_from_db()?;
+        self.recover_ingestion_queue()?;
+        Ok(min_length_filter)
+    }
+
+    /// Flushes the store, so a new state can start from it, & returns it
+    pub fn shutdown(self) -> anyhow::Result<Option<Arc<IndexerStore>>> {
+        let indexer_store = self.indexer_store;
+        if let Some(store) = indexer_store.as_ref() {
+            store.clean_shutdown()?;
+        }
+
+        Ok(indexer_store)
+    }
+
     /// Sync from an existing db
     ///
     /// Short-circuits adding all blocks to the witness tree by rooting the
@@ -1167,7 +2045,9 @@ impl IndexerState {
     pub fn sync_from_db(&mut self) -> anyhow::Result<Option<u32>> {
         let mut min_length_filter = None;
         let mut witness_tree_blocks = vec![];
+        let mut skipped_blocks = vec![];
         let mut staking_ledgers = HashMap::new();
+        crash::set_phase(IngestionPhase::Sync);
         if let Some(indexer_store) = self.indexer_store.as_ref() {
             debug!("Looking for witness tree root block");
             let next_seq_num = indexer_store.get_next_seq_num()?;
@@ -1180,11 +2060,12 @@ impl IndexerState {
                 .flatten()
                 .find_map(|(_, bytes)| {
                     // value prefix == best block height or 0 BE bytes
-                    let height = u32::from_be_bytes(
-                        bytes[..4]
-                            .try_into()
-                            .expect("Byte length is incorrect. Unable to parse block height"),
-                    );
+                    if bytes.len() < 5 {
+                        warn!("Sync: skipping malformed event ({} bytes)", bytes.len());
+                        return None;
+                    }
+
+                    let height = u32::from_be_bytes(bytes[..4].try_into().ok()?);
                     if bytes[4] == IndexerEvent::NEW_BEST_TIP_KIND
                         && height
                             == 1.max(best_block_height.saturating_sub(self.canonical_threshold))
@@ -1212,87 +2093,99 @@ impl IndexerState {
                         tip.state_hash.clone(),
                         LedgerDiff::from_precomputed(&root_block),
                     );
+                    self.ledger_overlay = LedgerOverlay::new(tip.state_hash.clone());
                     self.canonical_root = tip.clone();
                     self.best_tip = tip;
                     debug!("Witness tree root block (length {root_block_height}): {state_hash}");
 
                     // collect witness tree blocks
-                    indexer_store
+                    for (key, _) in indexer_store
                         .blocks_height_iterator(speedb::IteratorMode::From(
                             &root_block.blockchain_length().to_be_bytes(),
                             speedb::Direction::Forward,
                         ))
                         .flatten()
-                        .for_each(|(key, _)| {
-                            if let (Ok(height), Ok(state_hash)) = (block_u32_prefix_from_key(&key), state_hash_suffix(&key)) {
-                                if height <= 1 || state_hash == root_block.state_hash() {
-                                    return;
-                                }
-                                if let Ok(Some((block, _))) = indexer_store.get_block(&state_hash) {
-                                    witness_tree_blocks.push(block);
-                                } else {
-                                    panic!(
-                                        "Fatal sync error: block missing from db (length {height}): {state_hash}"
-                                    )
-                                }
+                    {
+                        if let (Ok(height), Ok(state_hash)) =
+                            (block_u32_prefix_from_key(&key), state_hash_suffix(&key))
+                        {
+                            if height <= 1 || state_hash == root_block.state_hash() {
+                                continue;
                             }
-                        });
+                            match sync_block(indexer_store, &state_hash, height) {
+                                Ok(block) => witness_tree_blocks.push(block),
+                                Err(skipped_block) => skipped_blocks.push(skipped_block),
+                            }
+                        }
+                    }
 
-                    // collect staking ledger data
+                    // collect staking ledger data of all registered eras
+                    let registry = indexer_store.get_genesis_registry()?;
                     for (key, _) in indexer_store
                         .staking_ledger_epoch_iterator(speedb::IteratorMode::End)
                         .flatten()
                     {
                         let (genesis_state_hash, epoch, ledger_hash) =
                             split_staking_ledger_epoch_key(&key)?;
-                        if genesis_state_hash.0 == MAINNET_GENESIS_HASH
-                            || genesis_state_hash.0 == HARDFORK_GENESIS_HASH
-                        {
-                            staking_ledgers.insert(epoch, ledger_hash);
+                        if registry.contains(&genesis_state_hash) {
+                            staking_ledgers.insert(
+                                registry.flat_epoch(&genesis_state_hash, epoch),
+                                ledger_hash,
+                            );
                         } else {
-                            error!("Unrecognized genesis state hash");
+                            warn!("Skipping staking ledger (epoch {epoch}) of unregistered genesis state hash {genesis_state_hash}: {ledger_hash}");
                         }
                     }
                 } else {
-                    panic!("Fatal sync error: block missing from db {state_hash}")
+                    bail!("Sync error: witness tree root block missing from db {state_hash}")
                 }
 
                 // return after adding succesive blocks
                 min_length_filter = Some(*root_block_height);
             } else {
                 // add all blocks to the witness tree
-                indexer_store
-                    .blocks_height_iterator(speedb::IteratorMode::From(&1u32.to_be_bytes(), speedb::Direction::Reverse))
+                for (key, _) in indexer_store
+                    .blocks_height_iterator(speedb::IteratorMode::From(
+                        &1u32.to_be_bytes(),
+                        speedb::Direction::Reverse,
+                    ))
                     .flatten()
-                    .for_each(|(key, _)| {
-                        if let (Ok(height), Ok(state_hash)) = (block_u32_prefix_from_key(&key), state_hash_suffix(&key)) {
-                            if let Ok(Some((block, _))) = indexer_store.get_block(&state_hash) {
-                                witness_tree_blocks.push(block);
-                            } else {
-                                panic!(
-                                    "Fatal sync error: block missing from db (length {height}): {state_hash}"
-                                )
-                            }
+                {
+                    if let (Ok(height), Ok(state_hash)) =
+                        (block_u32_prefix_from_key(&key), state_hash_suffix(&key))
+                    {
+                        match sync_block(indexer_store, &state_hash, height) {
+                            Ok(block) => witness_tree_blocks.push(block),
+                            Err(skipped_block) => skipped_blocks.push(skipped_block),
                         }
-                    });
+                    }
+                }
             }
             self.blocks_processed = indexer_store.get_block_production_total_count()?;
             self.bytes_processed = indexer_store
-                .database
-                .get(IndexerStore::NUM_BLOCK_BYTES_PROCESSED)?
-                .map_or(self.genesis_bytes, |bytes| {
-                    u64_from_be_bytes(&bytes).expect("num blocks processed u64 BE bytes")
-                });
+                .get_cf(
+                    indexer_store.default_cf(),
+                    IndexerStore::NUM_BLOCK_BYTES_PROCESSED,
+                )?
+                .map_or(Ok(self.genesis_bytes), |bytes| u64_from_be_bytes(&bytes))?;
         } else {
-            panic!("Fatal sync error: no indexer store");
+            bail!("Sync error: no indexer store");
         };
 
+        // blocks missing from the store are skipped & reported
+        for skipped_block in skipped_blocks {
+            self.skip_block(skipped_block);
+        }
+
         // update witness tree blocks/staking ledgers
         self.staking_ledgers = Arc::new(Mutex::new(staking_ledgers));
         for block in witness_tree_blocks {
             debug!("Sync: add block {}", block.summary());
+            crash::set_current_block(&block.state_hash(), block.blockchain_length());
             self.add_block_to_witness_tree(&block, false, true)?;
         }
+
+        crash::clear();
         Ok(min_length_filter)
     }
 
@@ -1335,16 +2228,14 @@ impl IndexerState {
                         let block_summary = format!("(length {blockchain_length}): {state_hash}");
                         info!("Replaying new best tip {block_summary}");
 
-                        if let Some((block, _)) = indexer_store.get_block(state_hash)? {
-                            assert_eq!(block.state_hash(), *state_hash);
-                            assert_eq!(block.blockchain_length(), *blockchain_length);
-                            assert_eq!(
-                                indexer_store.get_block_height(state_hash)?,
-                                Some(*blockchain_length),
-                            );
-                            return Ok(());
-                        }
-                        panic!("Fatal: block not in store {block_summary}");
+                        let Some((block, _)) = indexer_store.get_block(state_hash)? else {
+                            if is_pruned_block(indexer_store, state_hash, *blockchain_length)? {
+                                info!("Skipping pruned best tip block {block_summary}");
+                                return Ok(());
+                            }
+                            bail!("Best tip block missing from store {block_summary}");
+                        };
+                        check_replayed_block(indexer_store, &block, state_hash, *blockchain_length)
                     }
                     DbBlockEvent::NewBlock {
                         blockchain_length,
@@ -1355,17 +2246,31 @@ impl IndexerState {
                         let block_summary = format!("(length {blockchain_length}): {state_hash}");
                         info!("Replaying db new block {block_summary}");
 
-                        if let Ok(Some((block, _))) = indexer_store.get_block(state_hash) {
-                            assert_eq!(block.state_hash(), *state_hash);
-                            assert_eq!(block.blockchain_length(), *blockchain_length);
-                            assert_eq!(
-                                indexer_store.get_block_height(state_hash)?,
-                                Some(*blockchain_length),
-                            );
-                            self.add_block_to_witness_tree(&block, true, true)?;
-                            return Ok(());
-                        }
-                        panic!("Fatal: block missing from store {block_summary}")
+                        let Some((block, _)) = indexer_store.get_block(state_hash)? else {
+                            if is_pruned_block(indexer_store, state_hash, *blockchain_length)? {
+                                info!("Skipping pruned block {block_summary}");
+                                return Ok(());
+                            }
+                            bail!("Block missing from store {block_summary}");
+                        };
+                        check_replayed_block(
+                            indexer_store,
+                            &block,
+                            state_hash,
+                            *blockchain_length,
+                        )?;
+
+                        self.add_block_to_witness_tree(&block, true, true)?;
+                        Ok(())
+                    }
+                    DbBlockEvent::Reorg(reorg) => {
+                        // nothing to check, the new best tip is replayed separately
+                        info!(
+                            "Replay reorg (depth {}) to best tip {}",
+                            reorg.depth(),
+                            reorg.new_tip
+                        );
+                        Ok(())
                     }
                 },
                 DbEvent::Ledger(DbLedgerEvent::NewLedger {
@@ -1378,30 +2283,66 @@ impl IndexerState {
 
                     // check ledger & block are in the store
                     let indexer_store = self.indexer_store_or_panic();
-                    if let Some(_ledger) =
-                        indexer_store.get_staged_ledger_at_state_hash(state_hash, false)?
+                    if indexer_store
+                        .get_staged_ledger_at_state_hash(state_hash, false)?
+                        .is_none()
                     {
-                        if let Some((block, _)) = indexer_store.get_block(state_hash)? {
-                            assert_eq!(block.state_hash(), *state_hash);
-                            return Ok(());
-                        }
-                        if state_hash.0 == MAINNET_GENESIS_PREV_STATE_HASH {
-                            return Ok(());
-                        } else {
-                            panic!("Fatal: block missing from store {block_summary}");
+                        bail!("Staged ledger missing from store {ledger_hash} for block {block_summary}");
+                    }
+
+                    match indexer_store.get_block(state_hash)? {
+                        Some((block, _)) => {
+                            ensure!(
+                                block.state_hash() == *state_hash,
+                                "Stored block state hash mismatch {block_summary}"
+                            );
+                            Ok(())
                         }
+                        None if state_hash.0 == MAINNET_GENESIS_PREV_STATE_HASH => Ok(()),
+                        None => bail!("Block missing from store {block_summary}"),
                     }
-                    panic!(
-                        "Fatal: staged ledger missing from store {ledger_hash} for block {block_summary}",
-                    );
                 }
                 DbEvent::StakingLedger(DbStakingLedgerEvent::NewStakingLedger {
                     epoch,
-                    genesis_state_hash: _,
+                    genesis_state_hash,
                     ledger_hash,
                 }) => {
+                    // the ledger may have been replaced by a later event
+                    let indexer_store = self.indexer_store_or_panic();
+                    if indexer_store
+                        .get_staking_ledger_hash_by_epoch(*epoch, Some(genesis_state_hash))?
+                        .as_ref()
+                        != Some(ledger_hash)
+                    {
+                        info!("Skipping replaced staking ledger (epoch {epoch}): {ledger_hash}");
+                        return Ok(());
+                    }
+
+                    let flat_epoch = indexer_store.get_flat_epoch(genesis_state_hash, *epoch)?;
                     let mut staking_ledgers = self.staking_ledgers.lock().unwrap();
-                    staking_ledgers.insert(*epoch, ledger_hash.clone());
+                    staking_ledgers.insert(flat_epoch, ledger_hash.clone());
+                    self.replay_staking_ledger(epoch, ledger_hash)
+                }
+                DbEvent::StakingLedger(DbStakingLedgerEvent::ReplaceStakingLedger {
+                    epoch,
+                    old_ledger_hash,
+                    ledger_hash,
+                    genesis_state_hash,
+                }) => {
+                    let indexer_store = self.indexer_store_or_panic();
+                    if indexer_store
+                        .get_staking_ledger_hash_by_epoch(*epoch, Some(genesis_state_hash))?
+                        .as_ref()
+                        != Some(ledger_hash)
+                    {
+                        info!("Skipping replaced staking ledger (epoch {epoch}): {ledger_hash}");
+                        return Ok(());
+                    }
+
+                    info!("Replaying staking ledger replacement (epoch {epoch}): {old_ledger_hash} -> {ledger_hash}");
+                    let flat_epoch = indexer_store.get_flat_epoch(genesis_state_hash, *epoch)?;
+                    let mut staking_ledgers = self.staking_ledgers.lock().unwrap();
+                    staking_ledgers.insert(flat_epoch, ledger_hash.clone());
                     self.replay_staking_ledger(epoch, ledger_hash)
                 }
                 DbEvent::StakingLedger(DbStakingLedgerEvent::AggregateDelegations {
@@ -1410,22 +2351,23 @@ impl IndexerState {
                 }) => {
                     info!("Replaying aggregate delegations epoch {epoch}");
                     let indexer_store = self.indexer_store_or_panic();
-                    if let Some(aggregated_delegations) = indexer_store
+                    let Some(aggregated_delegations) = indexer_store
                         .build_aggregated_delegations(*epoch, Some(genesis_state_hash))?
-                    {
-                        if let Some(staking_ledger) =
-                            indexer_store.build_staking_ledger(*epoch, Some(genesis_state_hash))?
-                        {
-                            // check delegation calculations
-                            assert_eq!(
-                                aggregated_delegations,
-                                staking_ledger.aggregate_delegations()?
-                            );
-                            return Ok(());
-                        }
-                        panic!("Fatal: no staking ledger epoch {epoch}");
-                    }
-                    panic!("Fatal: aggregate delegations epoch {epoch}");
+                    else {
+                        bail!("Aggregate delegations missing from store epoch {epoch}");
+                    };
+                    let Some(staking_ledger) =
+                        indexer_store.build_staking_ledger(*epoch, Some(genesis_state_hash))?
+                    else {
+                        bail!("Staking ledger missing from store epoch {epoch}");
+                    };
+
+                    // check delegation calculations
+                    ensure!(
+                        aggregated_delegations == staking_ledger.aggregate_delegations()?,
+                        "Aggregate delegations mismatch epoch {epoch}"
+                    );
+                    Ok(())
                 }
                 DbEvent::Canonicity(DbCanonicityEvent::NewCanonicalBlock {
                     state_hash,
@@ -1436,22 +2378,34 @@ impl IndexerState {
                     info!("Replay new canonical block {block_summary}");
 
                     // check canonicity & block store
-                    if let Some(canonical_hash) =
+                    let Some(canonical_hash) =
                         indexer_store.get_canonical_hash_at_height(*blockchain_length)?
-                    {
-                        assert_eq!(canonical_hash, *state_hash);
-                        if let Some((block, _)) = indexer_store.get_block(state_hash)? {
-                            assert_eq!(block.state_hash(), *state_hash);
-                            assert_eq!(block.blockchain_length(), *blockchain_length);
-                            assert_eq!(
-                                indexer_store.get_block_height(state_hash)?,
-                                Some(*blockchain_length),
-                            );
-                            return Ok(());
-                        }
-                        panic!("Fatal: block not in store {block_summary}");
-                    }
-                    panic!("Fatal: canonical block not in store {block_summary}");
+                    else {
+                        bail!("Canonical block missing from store {block_summary}");
+                    };
+                    ensure!(
+                        canonical_hash == *state_hash,
+                        "Canonical block mismatch {block_summary}, stored {canonical_hash}"
+                    );
+
+                    let Some((block, _)) = indexer_store.get_block(state_hash)? else {
+                        bail!("Block missing from store {block_summary}");
+                    };
+                    check_replayed_block(indexer_store, &block, state_hash, *blockchain_length)
+                }
+                DbEvent::Canonicity(DbCanonicityEvent::CanonicityVerified {
+                    from_height,
+                    to_height,
+                    num_mismatches,
+                }) => {
+                    // nothing to check, the verification is rerun on demand
+                    info!("Replay canonicity verified (lengths {from_height}..={to_height}): {num_mismatches} mismatches");
+                    Ok(())
+                }
+                DbEvent::Constants(DbConstantsEvent::ConstantsChanged { changed, reindex }) => {
+                    // nothing to check, the derived data is reindexed separately
+                    info!("Replay constants changed {changed:?}, reindex: {reindex:?}");
+                    Ok(())
                 }
             },
             IndexerEvent::WitnessTree(_) => unreachable!("Replay witness tree event"),
@@ -1486,10 +2440,10 @@ impl IndexerState {
                 return Ok(());
             }
 
-            panic!("Fatal: no staking ledger at epoch {epoch} in store");
+            bail!("Fatal: no staking ledger at epoch {epoch} in store");
         }
 
-        panic!("Fatal: no staking ledger with hash {ledger_hash} in store");
+        bail!("Fatal: no staking ledger with hash {ledger_hash} in store");
     }
 
     fn indexer_store_or_panic(&self) -> &Arc<IndexerStore> {
@@ -1508,13 +2462,12 @@ impl IndexerState {
         for ancestor_id in self
             .root_branch
             .branches
-            .ancestor_ids(&self.best_tip.node_id)
-            .unwrap()
+            .ancestor_ids(&self.best_tip.node_id)?
             .skip(MAINNET_CANONICAL_THRESHOLD.saturating_sub(1) as usize)
         {
             // only add blocks between the old_canonical_root and the new one
             if ancestor_id != old_canonical_root_id {
-                let ancestor_block = self.get_block_from_id(ancestor_id).clone();
+                let ancestor_block = self.get_block_from_id(ancestor_id)?.clone();
                 if canonical_blocks.is_empty() {
                     // update canonical root
                     self.canonical_root.node_id = ancestor_id.clone();
@@ -1533,6 +2486,22 @@ impl IndexerState {
 
     /// Add new canonical diffs to the ledger
     fn update_ledger(&mut self, canonical_blocks: &Vec<Block>) -> anyhow::Result<()> {
+        // traced & validated diffs are applied block by block to check each
+        // resulting ledger
+        if self.trace_accounts.is_some() || self.validate_blocks {
+            for canonical_block in canonical_blocks {
+                if let Some(diff) = self.diffs_map.get(&canonical_block.state_hash).cloned() {
+                    self.apply_canonical_diff(&diff)?;
+                } else {
+                    error!(
+                        "Block not in diffs map (length {}): {}",
+                        canonical_block.blockchain_length, canonical_block.state_hash
+                    );
+                }
+            }
+            return Ok(());
+        }
+
         // apply the new canonical diffs and store each nth resulting ledger
         let mut ledger_diff = LedgerDiff::default();
         for canonical_block in canonical_blocks {
@@ -1547,7 +2516,11 @@ impl IndexerState {
         }
 
         if !ledger_diff.account_diffs.is_empty() {
-            self.ledger._apply_diff(&ledger_diff)?;
+            #[cfg(feature = "parallel_diffs")]
+            let anomalies = self.ledger._apply_diff_parallel(&ledger_diff)?;
+            #[cfg(not(feature = "parallel_diffs"))]
+            let anomalies = self.ledger._apply_diff(&ledger_diff)?;
+            self.record_amount_anomalies(&anomalies)?;
         }
         Ok(())
     }
@@ -1574,10 +2547,9 @@ impl IndexerState {
         for node_id in self
             .root_branch
             .branches
-            .traverse_level_order_ids(old_canonical_root_id)
-            .unwrap()
+            .traverse_level_order_ids(old_canonical_root_id)?
         {
-            let block = self.get_block_from_id(&node_id);
+            let block = self.get_block_from_id(&node_id)?;
             if block != self.canonical_root_block()
                 && block.height <= self.canonical_root_block().height
             {
@@ -1587,6 +2559,34 @@ impl IndexerState {
         Ok(())
     }
 
+    /// Number of missing blocks & the lowest ones
+    fn missing_blocks_summary(&self) -> (u32, Vec<MissingBlock>) {
+        self.indexer_store
+            .as_ref()
+            .map(|store| {
+                (
+                    store.get_num_missing_blocks().unwrap_or_default(),
+                    store
+                        .get_missing_blocks(SUMMARY_MISSING_BLOCKS)
+                        .unwrap_or_default(),
+                )
+            })
+            .unwrap_or_default()
+    }
+
+    /// Number of tokens used without a known owner, i.e. the ingested range
+    /// is incomplete
+    fn num_unknown_provenance_tokens(&self) -> u32 {
+        self.indexer_store
+            .as_ref()
+            .map(|store| {
+                store
+                    .get_num_unknown_provenance_tokens()
+                    .unwrap_or_default()
+            })
+            .unwrap_or_default()
+    }
+
     pub fn summary_short(&self) -> SummaryShort {
         let mut max_dangling_height = 0;
         let mut max_dangling_length = 0;
@@ -1616,13 +2616,17 @@ impl IndexerState {
             root_length: self.root_branch.len(),
             num_leaves: self.root_branch.leaves().len() as u32,
             num_dangling: self.dangling_branches.len() as u32,
+            num_deferred: self.deferred_blocks.len() as u32,
             max_dangling_height,
             max_dangling_length,
         };
+        let (num_missing_blocks, missing_blocks) = self.missing_blocks_summary();
+        let ingestion_queue = self.ingestion_queue_summary();
         let staking_ledgers = self.staking_ledgers.lock().unwrap();
         let max_staking_ledger_epoch = staking_ledgers.keys().max().cloned();
         SummaryShort {
             witness_tree,
+            ingestion_queue,
             max_staking_ledger_epoch,
             uptime: Instant::now() - self.init_time,
             blocks_processed: self.blocks_processed,
@@ -1631,6 +2635,14 @@ impl IndexerState {
                 .cloned()
                 .map(|h| h.0),
             db_stats: db_stats_str.map(|s| DbStats::from_str(&format!("{mem}\n{s}")).unwrap()),
+            canonical_hash_cache: self
+                .indexer_store
+                .as_ref()
+                .map(|db| db.canonical_hash_cache.stats()),
+            maintenance: self.maintenance_summary(),
+            num_missing_blocks,
+            missing_blocks,
+            num_unknown_provenance_tokens: self.num_unknown_provenance_tokens(),
         }
     }
 
@@ -1663,14 +2675,18 @@ impl IndexerState {
             root_length: self.root_branch.len(),
             num_leaves: self.root_branch.leaves().len() as u32,
             num_dangling: self.dangling_branches.len() as u32,
+            num_deferred: self.deferred_blocks.len() as u32,
             max_dangling_height,
             max_dangling_length,
             witness_tree: format!("{self}"),
         };
+        let (num_missing_blocks, missing_blocks) = self.missing_blocks_summary();
+        let ingestion_queue = self.ingestion_queue_summary();
         let staking_ledgers = self.staking_ledgers.lock().unwrap();
         let max_staking_ledger_epoch = staking_ledgers.keys().max().cloned();
         SummaryVerbose {
             witness_tree,
+            ingestion_queue,
             max_staking_ledger_epoch,
             uptime: Instant::now() - self.init_time,
             blocks_processed: self.blocks_processed,
@@ -1679,6 +2695,35 @@ impl IndexerState {
                 .cloned()
                 .map(|h| h.0),
             db_stats: db_stats_str.map(|s| DbStats::from_str(&format!("{mem}\n{s}")).unwrap()),
+            canonical_hash_cache: self
+                .indexer_store
+                .as_ref()
+                .map(|db| db.canonical_hash_cache.stats()),
+            maintenance: self.maintenance_summary(),
+            num_missing_blocks,
+            missing_blocks,
+            num_unknown_provenance_tokens: self.num_unknown_provenance_tokens(),
+        }
+    }
+
+    fn maintenance_summary(&self) -> Option<MaintenanceSummary> {
+        self.maintenance
+            .as_ref()
+            .map(|maintenance| maintenance.lock().unwrap().summary())
+    }
+
+    fn ingestion_queue_summary(&self) -> IngestionQueueSummary {
+        let (depth, num_bytes) = self
+            .indexer_store
+            .as_ref()
+            .and_then(|db| db.get_ingestion_queue_size().ok())
+            .unwrap_or_default();
+
+        IngestionQueueSummary {
+            depth,
+            num_bytes,
+            max_depth: self.ingestion_queue.max_depth,
+            max_bytes: self.ingestion_queue.max_bytes,
         }
     }
 
@@ -1772,6 +2817,59 @@ fn is_reverse_extension(branch: &Branch, precomputed_block: &PrecomputedBlock) -
     precomputed_block.state_hash() == branch.root_block().parent_hash
 }
 
+/// Gets the block to sync from the store, skipping it if missing or
+/// unreadable
+fn sync_block(
+    indexer_store: &IndexerStore,
+    state_hash: &StateHash,
+    height: u32,
+) -> Result<PrecomputedBlock, SkippedBlock> {
+    let error = match indexer_store.get_block(state_hash) {
+        Ok(Some((block, _))) => return Ok(block),
+        Ok(None) => format!("block missing from db (length {height})"),
+        Err(e) => format!("block unreadable from db (length {height}): {e:#}"),
+    };
+
+    Err(SkippedBlock {
+        path: None,
+        state_hash: Some(state_hash.clone()),
+        phase: IngestionPhase::Sync,
+        error,
+    })
+}
+
+/// Checks the replayed event's block against the stored block & height
+fn check_replayed_block(
+    indexer_store: &IndexerStore,
+    block: &PrecomputedBlock,
+    state_hash: &StateHash,
+    blockchain_length: u32,
+) -> anyhow::Result<()> {
+    let block_summary = format!("(length {blockchain_length}): {state_hash}");
+    ensure!(
+        block.state_hash() == *state_hash && block.blockchain_length() == blockchain_length,
+        "Stored block mismatch {block_summary}, stored {}",
+        block.summary()
+    );
+    ensure!(
+        indexer_store.get_block_height(state_hash)? == Some(blockchain_length),
+        "Stored block height mismatch {block_summary}"
+    );
+    Ok(())
+}
+
+/// Whether the block was dropped by orphaned block pruning
+fn is_pruned_block(
+    indexer_store: &IndexerStore,
+    state_hash: &StateHash,
+    blockchain_length: u32,
+) -> anyhow::Result<bool> {
+    Ok(
+        indexer_store.get_block_canonicity(state_hash)? == Some(Canonicity::Orphaned)
+            && blockchain_length <= indexer_store.get_orphaned_blocks_pruned_height()?,
+    )
+}
+
 impl std::fmt::Display for IndexerState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "=== Root branch ===")?;
@@ -1,3 +1,7 @@
+use crate::{
+    block::missing::MissingBlock, canonicity::cache::CanonicalHashCacheStats,
+    constants::millis_to_iso_date_string, maintenance::MaintenanceSummary,
+};
 use bytesize::ByteSize;
 use serde::{Deserialize, Serialize};
 use std::str::Lines;
@@ -18,7 +22,14 @@ pub trait Summary {
     fn num_dangling(&self) -> u32;
     fn max_dangling_height(&self) -> u32;
     fn max_dangling_length(&self) -> u32;
+    fn num_deferred(&self) -> u32;
+    fn ingestion_queue(&self) -> IngestionQueueSummary;
     fn db_stats(&self) -> DbStats;
+    fn canonical_hash_cache(&self) -> Option<CanonicalHashCacheStats>;
+    fn maintenance(&self) -> Option<MaintenanceSummary>;
+    fn num_missing_blocks(&self) -> u32;
+    fn missing_blocks(&self) -> Vec<MissingBlock>;
+    fn num_unknown_provenance_tokens(&self) -> u32;
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,7 +39,23 @@ pub struct SummaryShort {
     pub max_staking_ledger_epoch: Option<u32>,
     pub max_staking_ledger_hash: Option<String>,
     pub witness_tree: WitnessTreeSummaryShort,
+    pub ingestion_queue: IngestionQueueSummary,
     pub db_stats: Option<DbStats>,
+    pub canonical_hash_cache: Option<CanonicalHashCacheStats>,
+
+    #[serde(default)]
+    pub maintenance: Option<MaintenanceSummary>,
+
+    #[serde(default)]
+    pub num_missing_blocks: u32,
+
+    /// Lowest missing blocks
+    #[serde(default)]
+    pub missing_blocks: Vec<MissingBlock>,
+
+    /// Tokens used without a known owner
+    #[serde(default)]
+    pub num_unknown_provenance_tokens: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,7 +65,23 @@ pub struct SummaryVerbose {
     pub max_staking_ledger_epoch: Option<u32>,
     pub max_staking_ledger_hash: Option<String>,
     pub witness_tree: WitnessTreeSummaryVerbose,
+    pub ingestion_queue: IngestionQueueSummary,
     pub db_stats: Option<DbStats>,
+    pub canonical_hash_cache: Option<CanonicalHashCacheStats>,
+
+    #[serde(default)]
+    pub maintenance: Option<MaintenanceSummary>,
+
+    #[serde(default)]
+    pub num_missing_blocks: u32,
+
+    /// Lowest missing blocks
+    #[serde(default)]
+    pub missing_blocks: Vec<MissingBlock>,
+
+    /// Tokens used without a known owner
+    #[serde(default)]
+    pub num_unknown_provenance_tokens: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +97,9 @@ pub struct WitnessTreeSummaryShort {
     pub num_dangling: u32,
     pub max_dangling_height: u32,
     pub max_dangling_length: u32,
+
+    #[serde(default)]
+    pub num_deferred: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,9 +115,20 @@ pub struct WitnessTreeSummaryVerbose {
     pub num_dangling: u32,
     pub max_dangling_height: u32,
     pub max_dangling_length: u32,
+
+    #[serde(default)]
+    pub num_deferred: u32,
     pub witness_tree: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestionQueueSummary {
+    pub depth: u32,
+    pub num_bytes: u64,
+    pub max_depth: u32,
+    pub max_bytes: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DbStats {
     memory: u64,
@@ -107,7 +164,13 @@ impl From<SummaryVerbose> for SummaryShort {
             max_staking_ledger_epoch: value.max_staking_ledger_epoch,
             max_staking_ledger_hash: value.max_staking_ledger_hash,
             witness_tree: value.witness_tree.into(),
+            ingestion_queue: value.ingestion_queue,
             db_stats: value.db_stats,
+            canonical_hash_cache: value.canonical_hash_cache,
+            maintenance: value.maintenance,
+            num_missing_blocks: value.num_missing_blocks,
+            missing_blocks: value.missing_blocks,
+            num_unknown_provenance_tokens: value.num_unknown_provenance_tokens,
         }
     }
 }
@@ -124,6 +187,7 @@ impl From<WitnessTreeSummaryVerbose> for WitnessTreeSummaryShort {
             root_length: value.root_length,
             num_leaves: value.num_leaves,
             num_dangling: value.num_dangling,
+            num_deferred: value.num_deferred,
             max_dangling_height: value.max_dangling_height,
             max_dangling_length: value.max_dangling_length,
         }
@@ -161,6 +225,42 @@ fn summary_short(state: &impl Summary, f: &mut std::fmt::Formatter<'_>) -> std::
         writeln!(f, "  Max length: {}", state.max_dangling_height())?;
     }
 
+    if state.num_missing_blocks() > 0 {
+        writeln!(f, "\n=== Missing blocks ===")?;
+        writeln!(f, "  Num: {}", state.num_missing_blocks())?;
+        for missing in state.missing_blocks() {
+            writeln!(
+                f,
+                "  {} {} (referenced by {})",
+                missing.blockchain_length, missing.state_hash, missing.referenced_by
+            )?;
+        }
+    }
+
+    if state.num_unknown_provenance_tokens() > 0 {
+        writeln!(f, "\n=== Unknown provenance tokens ===")?;
+        writeln!(f, "  Num: {}", state.num_unknown_provenance_tokens())?;
+    }
+
+    if state.num_deferred() > 0 {
+        writeln!(f, "\n=== Deferred future blocks ===")?;
+        writeln!(f, "  Num: {}", state.num_deferred())?;
+    }
+
+    let ingestion_queue = state.ingestion_queue();
+    writeln!(f, "\n=== Ingestion queue ===")?;
+    writeln!(
+        f,
+        "  Depth: {}/{}",
+        ingestion_queue.depth, ingestion_queue.max_depth
+    )?;
+    writeln!(
+        f,
+        "  Bytes: {}/{}",
+        ByteSize::b(ingestion_queue.num_bytes),
+        ByteSize::b(ingestion_queue.max_bytes)
+    )?;
+
     // let db_stats = state.db_stats.as_ref().unwrap();
     writeln!(f, "\n=== DB stats ===")?;
     writeln!(
@@ -176,6 +276,44 @@ fn summary_short(state: &impl Summary, f: &mut std::fmt::Formatter<'_>) -> std::
     writeln!(f, "  Interval WAL:      {}", state.db_stats().int_wal)?;
     writeln!(f, "  Interval stall:    {}", state.db_stats().int_stall)?;
 
+    if let Some(cache) = state.canonical_hash_cache() {
+        writeln!(f, "\n=== Canonical hash cache ===")?;
+        writeln!(f, "  Loaded:       {}", cache.loaded)?;
+        writeln!(f, "  Start height: {}", cache.start_height)?;
+        writeln!(f, "  Num heights:  {}", cache.len)?;
+        writeln!(f, "  Hits:         {}", cache.hits)?;
+        writeln!(f, "  Misses:       {}", cache.misses)?;
+    }
+
+    if let Some(maintenance) = state.maintenance() {
+        writeln!(f, "\n=== Maintenance ===")?;
+        writeln!(f, "  Paused:     {}", maintenance.paused)?;
+        writeln!(f, "  Batch size: {}", maintenance.batch_size)?;
+
+        for task in maintenance.tasks {
+            writeln!(f, "  {} ({})", task.name, task.schedule)?;
+            if task.running {
+                writeln!(f, "    Running:   {} processed", task.num_processed)?;
+            }
+            writeln!(
+                f,
+                "    Next run:  {}",
+                millis_to_iso_date_string(task.next_run_millis as i64)
+            )?;
+            if let Some(last_run) = task.last_run {
+                writeln!(
+                    f,
+                    "    Last run:  {} ({} processed)",
+                    millis_to_iso_date_string(last_run.finished_millis as i64),
+                    last_run.num_processed
+                )?;
+                if let Some(error) = last_run.error {
+                    writeln!(f, "    Error:     {error}")?;
+                }
+            }
+        }
+    }
+
     Ok(())
 }
 
@@ -208,10 +346,34 @@ impl Summary for SummaryShort {
         self.witness_tree.canonical_root_length
     }
 
+    fn ingestion_queue(&self) -> IngestionQueueSummary {
+        self.ingestion_queue.clone()
+    }
+
     fn db_stats(&self) -> DbStats {
         self.db_stats.as_ref().unwrap().clone()
     }
 
+    fn canonical_hash_cache(&self) -> Option<CanonicalHashCacheStats> {
+        self.canonical_hash_cache.clone()
+    }
+
+    fn maintenance(&self) -> Option<MaintenanceSummary> {
+        self.maintenance.clone()
+    }
+
+    fn num_missing_blocks(&self) -> u32 {
+        self.num_missing_blocks
+    }
+
+    fn missing_blocks(&self) -> Vec<MissingBlock> {
+        self.missing_blocks.clone()
+    }
+
+    fn num_unknown_provenance_tokens(&self) -> u32 {
+        self.num_unknown_provenance_tokens
+    }
+
     fn max_dangling_height(&self) -> u32 {
         self.witness_tree.max_dangling_height
     }
@@ -224,6 +386,10 @@ impl Summary for SummaryShort {
         self.witness_tree.num_dangling
     }
 
+    fn num_deferred(&self) -> u32 {
+        self.witness_tree.num_deferred
+    }
+
     fn num_leaves(&self) -> u32 {
         self.witness_tree.num_leaves
     }
@@ -274,10 +440,34 @@ impl Summary for SummaryVerbose {
         self.witness_tree.canonical_root_length
     }
 
+    fn ingestion_queue(&self) -> IngestionQueueSummary {
+        self.ingestion_queue.clone()
+    }
+
     fn db_stats(&self) -> DbStats {
         self.db_stats.as_ref().unwrap().clone()
     }
 
+    fn canonical_hash_cache(&self) -> Option<CanonicalHashCacheStats> {
+        self.canonical_hash_cache.clone()
+    }
+
+    fn maintenance(&self) -> Option<MaintenanceSummary> {
+        self.maintenance.clone()
+    }
+
+    fn num_missing_blocks(&self) -> u32 {
+        self.num_missing_blocks
+    }
+
+    fn missing_blocks(&self) -> Vec<MissingBlock> {
+        self.missing_blocks.clone()
+    }
+
+    fn num_unknown_provenance_tokens(&self) -> u32 {
+        self.num_unknown_provenance_tokens
+    }
+
     fn max_dangling_height(&self) -> u32 {
         self.witness_tree.max_dangling_height
     }
@@ -290,6 +480,10 @@ impl Summary for SummaryVerbose {
         self.witness_tree.num_dangling
     }
 
+    fn num_deferred(&self) -> u32 {
+        self.witness_tree.num_deferred
+    }
+
     fn num_leaves(&self) -> u32 {
         self.witness_tree.num_leaves
     }
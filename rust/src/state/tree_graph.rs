@@ -0,0 +1,220 @@
+//! Witness tree graph
+//!
+//! The root & dangling branches of the witness tree as a structured graph,
+//! for Graphviz DOT or JSON output. Each node's canonicity is relative to the
+//! root branch's best chain:
+//! - canonical: best chain ancestors of the canonical root, inclusive
+//! - orphaned: other blocks no higher than the canonical root
+//! - pending: all others, including dangling branches' blocks
+
+use super::{branch::Branch, IndexerState};
+use crate::{base::state_hash::StateHash, canonicity::Canonicity};
+use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt::Write};
+
+/// Output format of the witness tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+pub enum TreeFormat {
+    Dot,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WitnessTreeGraph {
+    pub root_branch: BranchGraph,
+    pub dangling_branches: Vec<BranchGraph>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BranchGraph {
+    pub root_hash: StateHash,
+
+    /// Nodes in level order, the root first
+    pub nodes: Vec<TreeNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TreeNode {
+    pub state_hash: StateHash,
+    pub parent_hash: StateHash,
+    pub blockchain_length: u32,
+    pub global_slot_since_genesis: u32,
+    pub canonicity: Canonicity,
+
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub best_tip: bool,
+
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub canonical_root: bool,
+}
+
+impl IndexerState {
+    /// Graph of the witness tree's root & dangling branches
+    pub fn witness_tree_graph(&self) -> WitnessTreeGraph {
+        let best_tip = &self.best_tip_block().state_hash;
+        let canonical_root = self.canonical_root_block();
+        let canonical: HashSet<_> = self
+            .root_branch
+            .branches
+            .ancestors(&self.canonical_root.node_id)
+            .into_iter()
+            .flatten()
+            .map(|node| node.data().state_hash.clone())
+            .chain(std::iter::once(canonical_root.state_hash.clone()))
+            .collect();
+
+        let canonicity = |state_hash: &StateHash, blockchain_length: u32| {
+            if canonical.contains(state_hash) {
+                Canonicity::Canonical
+            } else if blockchain_length <= canonical_root.blockchain_length {
+                Canonicity::Orphaned
+            } else {
+                Canonicity::Pending
+            }
+        };
+
+        WitnessTreeGraph {
+            root_branch: BranchGraph::new(&self.root_branch, |mut node| {
+                node.canonicity = canonicity(&node.state_hash, node.blockchain_length);
+                node.best_tip = node.state_hash == *best_tip;
+                node.canonical_root = node.state_hash == canonical_root.state_hash;
+                node
+            }),
+            dangling_branches: self
+                .dangling_branches
+                .iter()
+                .map(|branch| BranchGraph::new(branch, |node| node))
+                .collect(),
+        }
+    }
+}
+
+impl BranchGraph {
+    /// Nodes of the branch, pending unless mapped by `f`
+    fn new(branch: &Branch, f: impl Fn(TreeNode) -> TreeNode) -> Self {
+        let root_id = branch.branches.root_node_id().expect("root node id");
+        let nodes = branch
+            .branches
+            .traverse_level_order(root_id)
+            .expect("traverse level order")
+            .map(|node| {
+                let block = node.data();
+                f(TreeNode {
+                    state_hash: block.state_hash.clone(),
+                    parent_hash: block.parent_hash.clone(),
+                    blockchain_length: block.blockchain_length,
+                    global_slot_since_genesis: block.global_slot_since_genesis,
+                    canonicity: Canonicity::Pending,
+                    best_tip: false,
+                    canonical_root: false,
+                })
+            })
+            .collect();
+
+        Self {
+            root_hash: branch.root_block().state_hash.clone(),
+            nodes,
+        }
+    }
+
+    fn write_dot(&self, name: &str, label: &str, dot: &mut String) -> std::fmt::Result {
+        writeln!(dot, "  subgraph \"cluster_{name}\" {{")?;
+        writeln!(dot, "    label=\"{label}\";")?;
+
+        for node in self.nodes.iter() {
+            let (color, style) = match node.canonicity {
+                Canonicity::Canonical => ("forestgreen", "filled"),
+                Canonicity::Orphaned => ("firebrick", "filled"),
+                Canonicity::Pending => ("gray", "solid"),
+            };
+            let peripheries = if node.best_tip || node.canonical_root {
+                2
+            } else {
+                1
+            };
+
+            writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\\n{}\", color={color}, style={style}, peripheries={peripheries}];",
+                node.state_hash,
+                node.blockchain_length,
+                short_hash(&node.state_hash),
+            )?;
+        }
+
+        // the branch root's parent is outside the branch
+        for node in self.nodes.iter().skip(1) {
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\";",
+                node.parent_hash, node.state_hash
+            )?;
+        }
+        writeln!(dot, "  }}")
+    }
+}
+
+impl WitnessTreeGraph {
+    /// Graphviz DOT digraph, each branch in its own cluster & edges from
+    /// parent to child
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        self.write_dot(&mut dot).expect("write to string");
+        dot
+    }
+
+    fn write_dot(&self, dot: &mut String) -> std::fmt::Result {
+        writeln!(dot, "digraph witness_tree {{")?;
+        writeln!(dot, "  rankdir=BT;")?;
+        writeln!(dot, "  node [shape=box, fontname=monospace];")?;
+
+        self.root_branch.write_dot("root", "Root branch", dot)?;
+        for (n, branch) in self.dangling_branches.iter().enumerate() {
+            branch.write_dot(
+                &format!("dangling_{n}"),
+                &format!("Dangling branch {n}"),
+                dot,
+            )?;
+        }
+        writeln!(dot, "}}")
+    }
+
+    /// Graph in the given format
+    pub fn format(&self, format: TreeFormat) -> anyhow::Result<String> {
+        match format {
+            TreeFormat::Dot => Ok(self.to_dot()),
+            TreeFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+        }
+    }
+}
+
+/// Leading & trailing characters of the state hash
+fn short_hash(state_hash: &StateHash) -> String {
+    let hash = &state_hash.0;
+    if hash.len() <= 12 {
+        return hash.clone();
+    }
+    format!("{}..{}", &hash[..6], &hash[hash.len() - 6..])
+}
+
+impl std::str::FromStr for TreeFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dot" => Ok(Self::Dot),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("Invalid tree format: {s}")),
+        }
+    }
+}
+
+impl std::fmt::Display for TreeFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dot => write!(f, "dot"),
+            Self::Json => write!(f, "json"),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use super::column_families::ColumnFamilyHelpers;
+use crate::{
+    base::state_hash::StateHash,
+    ledger::{anomaly::AmountAnomaly, store::anomaly::AmountAnomalyStore},
+    store::{sub_store::SubStore, IndexerStore},
+    utility::store::ledger::anomaly::amount_anomaly_key,
+};
+use log::trace;
+use speedb::{Direction, IteratorMode, WriteBatch};
+
+/// Add the corresponding CF helpers to [ColumnFamilyHelpers] & bump the
+/// sub-store version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "amount-anomalies",
+    version: 2,
+    column_families: &["amount-anomalies"],
+    dependencies: &[],
+    migrate,
+};
+
+fn migrate(store: &IndexerStore, from_version: u32) -> anyhow::Result<()> {
+    match from_version {
+        1 => migrate_sequence_keys(store),
+        _ => SubStore::no_migration(store, from_version),
+    }
+}
+
+/// Re-keys anomalies by their per block sequence number, keys of the same
+/// account in the same block collided
+fn migrate_sequence_keys(store: &IndexerStore) -> anyhow::Result<()> {
+    let mut batch = WriteBatch::default();
+    let mut block_seq: Option<(StateHash, u32)> = None;
+
+    for (key, value) in store
+        .iterator_cf(store.amount_anomalies_cf(), IteratorMode::Start)
+        .flatten()
+    {
+        let anomaly: AmountAnomaly = serde_json::from_slice(&value)?;
+        let seq = match block_seq.as_mut() {
+            Some((state_hash, seq)) if *state_hash == anomaly.state_hash => {
+                *seq += 1;
+                *seq
+            }
+            _ => 0,
+        };
+
+        batch.delete_cf(store.amount_anomalies_cf(), key);
+        batch.put_cf(
+            store.amount_anomalies_cf(),
+            amount_anomaly_key(&anomaly.state_hash, seq),
+            value,
+        );
+
+        if seq == 0 {
+            block_seq = Some((anomaly.state_hash, 0));
+        }
+    }
+
+    Ok(store.database.write(batch)?)
+}
+
+impl AmountAnomalyStore for IndexerStore {
+    /// Key: [amount_anomaly_key]
+    /// Value: anomaly (serialized with [serde_json::to_vec])
+    fn add_amount_anomaly(&self, anomaly: &AmountAnomaly) -> anyhow::Result<()> {
+        let anomalies = self.get_amount_anomalies(Some(&anomaly.state_hash))?;
+        if anomalies.contains(anomaly) {
+            trace!("Amount anomaly already recorded {anomaly}");
+            return Ok(());
+        }
+
+        trace!("Recording amount anomaly {anomaly}");
+        self.database.put_cf(
+            self.amount_anomalies_cf(),
+            amount_anomaly_key(&anomaly.state_hash, anomalies.len() as u32),
+            serde_json::to_vec(anomaly)?,
+        )?;
+        Ok(())
+    }
+
+    fn get_amount_anomalies(
+        &self,
+        state_hash: Option<&StateHash>,
+    ) -> anyhow::Result<Vec<AmountAnomaly>> {
+        trace!("Getting amount anomalies {state_hash:?}");
+        let mode = match state_hash {
+            Some(state_hash) => IteratorMode::From(state_hash.0.as_bytes(), Direction::Forward),
+            None => IteratorMode::Start,
+        };
+
+        let mut anomalies = vec![];
+        for (key, value) in self.iterator_cf(self.amount_anomalies_cf(), mode).flatten() {
+            if state_hash.is_some_and(|state_hash| !key.starts_with(state_hash.0.as_bytes())) {
+                // no longer the block of interest
+                break;
+            }
+            anomalies.push(serde_json::from_slice(&value)?);
+        }
+        Ok(anomalies)
+    }
+}
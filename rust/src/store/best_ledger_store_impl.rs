@@ -1,35 +1,155 @@
-use super::{column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, DbUpdate, IndexerStore};
+use super::{
+    column_families::ColumnFamilyHelpers,
+    fixed_keys::FixedKeys,
+    replay::{DerivedData, ReplayRange},
+    sub_store::SubStore,
+    DbUpdate, IndexerStore,
+};
 use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
     block::store::{BlockStore, BlockUpdate, DbBlockUpdate},
+    chain::store::ChainStore,
     ledger::{
         account::Account,
+        anomaly::AmountAnomaly,
         diff::account::AccountDiff,
         store::{
-            best::{BestLedgerStore, DbAccountUpdate},
+            anomaly::AmountAnomalyStore,
+            best::{
+                AccountStatusReads, BalanceHistoryEntry, BestLedgerStore, DbAccountUpdate,
+                EpochBalance, NonceHistoryEntry,
+            },
             staged::StagedLedgerStore,
         },
-        token::TokenAddress,
+        token::{TokenAddress, TokenSymbol},
         Ledger, TokenLedger,
     },
+    mina_blocks::v2::ActionState,
     store::{
-        zkapp::{actions::ZkappActionStore, events::ZkappEventStore},
+        zkapp::{
+            actions::{ActionStateTransition, ZkappActionStore},
+            events::{ZkappBlockEvents, ZkappEventStore},
+        },
         Result,
     },
     utility::store::{
-        common::{from_be_bytes, pk_index_key},
+        common::{from_be_bytes, pk_index_key, pk_key_prefix, u32_from_be_bytes, U32_LEN},
         ledger::best::*,
     },
 };
-use log::trace;
-use speedb::{DBIterator, IteratorMode};
-use std::collections::HashSet;
+use anyhow::{bail, Context};
+use log::{error, trace};
+use speedb::{DBIterator, Direction, IteratorMode, WriteBatch};
+use std::collections::{BTreeSet, HashSet};
+
+/// Add the corresponding CF helpers to [ColumnFamilyHelpers] & bump the
+/// sub-store version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "best-ledger",
+    version: 5,
+    column_families: &[
+        "best-ledger-accounts",
+        "best-ledger-account-balance-sort",
+        "best-ledger-account-num-delegations",
+        "best-ledger-account-delegations",
+        "zkapp-best-ledger-accounts",
+        "zkapp-best-ledger-account-balance-sort",
+        "best-ledger-account-status",
+        "best-ledger-account-balance-history",
+        "best-ledger-token-transfers",
+        "best-ledger-account-nonce-history",
+    ],
+    dependencies: &["canonicity", "staged-ledger"],
+    migrate,
+};
+
+/// Best ledger account status bytes, see
+/// [AccountStatus](crate::ledger::store::best::AccountStatus)
+const CANONICAL: u8 = 0;
+const PENDING: u8 = 1;
+
+/// Version 1 stores don't record best ledger account statuses, all existing
+/// accounts are considered canonical
+///
+/// Version 2 stores don't record best ledger account balance history, it's
+/// backfilled by replaying the canonical chain, see [BalanceHistoryData]
+///
+/// Version 3 stores don't record custom token transfers, they're backfilled
+/// from the balance history
+///
+/// Version 4 stores don't record best ledger account nonce history, it's
+/// backfilled by replaying the canonical chain, see [NonceHistoryData]
+fn migrate(store: &IndexerStore, from_version: u32) -> anyhow::Result<()> {
+    match from_version {
+        1 => migrate_account_statuses(store),
+        2 => migrate_balance_history(store),
+        3 => migrate_token_transfers(store),
+        4 => migrate_nonce_history(store),
+        _ => SubStore::no_migration(store, from_version),
+    }
+}
+
+fn migrate_balance_history(store: &IndexerStore) -> anyhow::Result<()> {
+    store.replay_canonical_chain(&[&BalanceHistoryData])?;
+    Ok(())
+}
+
+fn migrate_nonce_history(store: &IndexerStore) -> anyhow::Result<()> {
+    store.replay_canonical_chain(&[&NonceHistoryData])?;
+    Ok(())
+}
+
+fn migrate_token_transfers(store: &IndexerStore) -> anyhow::Result<()> {
+    let default_token = TokenAddress::default();
+    let mut batch = WriteBatch::default();
+    for (key, value) in store
+        .iterator_cf(
+            store.best_ledger_accounts_balance_history_cf(),
+            IteratorMode::Start,
+        )
+        .flatten()
+    {
+        let token = TokenAddress::from_bytes(key[..TokenAddress::LEN].to_vec())?;
+        if token == default_token {
+            continue;
+        }
+
+        let pk = PublicKey::from_bytes(&key[TokenAddress::LEN..][..PublicKey::LEN])?;
+        let height = u32_from_be_bytes(&key[TokenAddress::LEN..][PublicKey::LEN..])?;
+        batch.put_cf(
+            store.best_ledger_token_transfers_cf(),
+            token_transfer_key(&token, height, &pk),
+            value,
+        );
+
+        if batch.len() >= 1000 {
+            store.database.write(std::mem::take(&mut batch))?;
+        }
+    }
+
+    Ok(store.database.write(batch)?)
+}
+
+fn migrate_account_statuses(store: &IndexerStore) -> anyhow::Result<()> {
+    let mut batch = WriteBatch::default();
+    for (key, _) in store
+        .iterator_cf(store.best_ledger_accounts_cf(), IteratorMode::Start)
+        .flatten()
+    {
+        batch.put_cf(store.best_ledger_accounts_status_cf(), key, [CANONICAL]);
+
+        if batch.len() >= 1000 {
+            store.database.write(std::mem::take(&mut batch))?;
+        }
+    }
+
+    Ok(store.database.write(batch)?)
+}
 
 impl BestLedgerStore for IndexerStore {
     fn get_best_account(&self, pk: &PublicKey, token: &TokenAddress) -> Result<Option<Account>> {
         trace!("Getting best ledger account {pk}");
         Ok(self
-            .database
             .get_cf(self.best_ledger_accounts_cf(), best_account_key(token, pk))?
             .map(|bytes| {
                 serde_json::from_slice::<Account>(&bytes)
@@ -87,6 +207,9 @@ impl BestLedgerStore for IndexerStore {
                 self.database
                     .delete_cf(self.best_ledger_accounts_balance_sort_cf(), sort_key)?;
 
+                self.database
+                    .delete_cf(self.best_ledger_accounts_status_cf(), account_key)?;
+
                 // zkapp account
                 if before.0 {
                     self.database
@@ -136,6 +259,15 @@ impl BestLedgerStore for IndexerStore {
             serde_json::to_vec(&after)?,
         )?;
 
+        // new accounts are canonical unless created by a best chain block
+        if before.is_none() {
+            self.database.put_cf(
+                self.best_ledger_accounts_status_cf(),
+                account_key,
+                [CANONICAL],
+            )?;
+        }
+
         if after.is_zkapp_account() {
             // store
             self.database.put_cf(
@@ -162,35 +294,152 @@ impl BestLedgerStore for IndexerStore {
         state_hash: &StateHash,
         blocks: &DbBlockUpdate,
     ) -> Result<()> {
-        let account_updates = DbUpdate {
-            apply: blocks
-                .apply
-                .iter()
-                .flat_map(|BlockUpdate { state_hash: a, .. }| {
-                    let diff = self.get_block_ledger_diff(a).unwrap();
-                    diff.map(|d| {
-                        (
-                            d.account_diffs.into_iter().flatten().collect(),
-                            update_token_accounts(d.new_pk_balances),
-                        )
-                    })
-                })
-                .collect(),
-            unapply: blocks
-                .unapply
-                .iter()
-                .flat_map(|BlockUpdate { state_hash: u, .. }| {
-                    let diff = self.get_block_ledger_diff(u).unwrap();
-                    diff.map(|d| {
-                        (
-                            d.account_diffs.into_iter().flatten().collect(),
-                            update_token_accounts(d.new_pk_balances),
-                        )
-                    })
-                })
-                .collect(),
-        };
-        self.update_best_accounts(state_hash, account_updates)
+        // unapply & drop the unapplied blocks' balance history, action state
+        // transitions & events
+        let mut unapply = Vec::with_capacity(blocks.unapply.len());
+        let mut action_states = vec![];
+        for BlockUpdate {
+            state_hash: u,
+            blockchain_length,
+            ..
+        } in blocks.unapply.iter()
+        {
+            if let Some(update) = self.get_block_account_update(u)? {
+                for (pk, token) in touched_token_accounts(&update) {
+                    self.remove_best_account_balance_history(&pk, &token, *blockchain_length)?;
+                    self.remove_best_account_nonce_history(&pk, &token, *blockchain_length)?;
+                }
+
+                for (pk, token) in block_num_actions(&update).into_keys() {
+                    if let Some(transition) =
+                        self.remove_action_state_transition(&pk, &token, *blockchain_length)?
+                    {
+                        action_states.push((pk, token, transition.before));
+                    }
+                }
+
+                for (pk, token) in block_num_events(&update).into_keys() {
+                    self.remove_block_events(&pk, &token, *blockchain_length)?;
+                }
+
+                unapply.push(update);
+            }
+        }
+
+        if !unapply.is_empty() {
+            self.update_best_accounts(
+                state_hash,
+                DbUpdate {
+                    apply: vec![],
+                    unapply,
+                },
+            )?;
+        }
+
+        // restore the action states, blocks are unapplied in descending order
+        for (pk, token, action_state) in action_states {
+            if let Some(mut account) = self.get_best_account(&pk, &token)? {
+                if let Some(zkapp) = account.zkapp.as_mut() {
+                    zkapp.action_state = action_state;
+
+                    let before = Some((account.is_zkapp_account(), account.balance.0));
+                    self.update_best_account(&pk, &token, before, Some(account))?;
+                }
+            }
+        }
+
+        // apply block by block, recording the balance changes
+        for BlockUpdate {
+            state_hash: a,
+            blockchain_length,
+            ..
+        } in blocks.apply.iter()
+        {
+            if let Some(update) = self.get_block_account_update(a)? {
+                let mut balances = vec![];
+                for (pk, token) in touched_token_accounts(&update) {
+                    let before = self
+                        .get_best_account(&pk, &token)?
+                        .map(|acct| (acct.balance.0, account_nonce(&acct)));
+                    balances.push((pk, token, before));
+                }
+
+                let mut action_states = vec![];
+                for ((pk, token), num_actions) in block_num_actions(&update) {
+                    let first_action_index = self.get_num_actions(&pk, &token)?.unwrap_or_default();
+                    let before = best_action_state(self, &pk, &token)?;
+                    action_states.push((pk, token, first_action_index, num_actions, before));
+                }
+
+                let mut block_events = vec![];
+                for ((pk, token), num_events) in block_num_events(&update) {
+                    let first_event_index = self.get_num_events(&pk, &token)?.unwrap_or_default();
+                    block_events.push((pk, token, first_event_index, num_events));
+                }
+
+                self.update_best_accounts(
+                    state_hash,
+                    DbUpdate {
+                        apply: vec![update],
+                        unapply: vec![],
+                    },
+                )?;
+
+                for (pk, token, first_action_index, num_actions, before) in action_states {
+                    let transition = ActionStateTransition {
+                        state_hash: a.to_owned(),
+                        blockchain_length: *blockchain_length,
+                        first_action_index,
+                        num_actions,
+                        before,
+                        after: best_action_state(self, &pk, &token)?,
+                    };
+                    self.set_action_state_transition(&pk, &token, &transition)?;
+                }
+
+                for (pk, token, first_event_index, num_events) in block_events {
+                    let block_events = ZkappBlockEvents {
+                        state_hash: a.to_owned(),
+                        blockchain_length: *blockchain_length,
+                        first_event_index,
+                        num_events,
+                    };
+                    self.set_block_events(&pk, &token, &block_events)?;
+                }
+
+                for (pk, token, before) in balances {
+                    if let Some(after) = self.get_best_account(&pk, &token)? {
+                        let balance_before = before.map(|(balance, _)| balance);
+                        if balance_before != Some(after.balance.0) {
+                            self.set_best_account_balance_history(
+                                &pk,
+                                &token,
+                                *blockchain_length,
+                                &BalanceHistoryEntry {
+                                    before: balance_before,
+                                    after: after.balance.0,
+                                },
+                            )?;
+                        }
+
+                        let nonce_before = before.map(|(_, nonce)| nonce);
+                        if nonce_before != Some(account_nonce(&after)) {
+                            self.set_best_account_nonce_history(
+                                &pk,
+                                &token,
+                                *blockchain_length,
+                                &NonceHistoryEntry {
+                                    before: nonce_before,
+                                    after: account_nonce(&after),
+                                },
+                            )?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
     }
 
     fn update_best_accounts(&self, state_hash: &StateHash, updates: DbAccountUpdate) -> Result<()> {
@@ -228,7 +477,19 @@ impl BestLedgerStore for IndexerStore {
                         Coinbase(diff) => after.coinbase_unapply(diff),
                         Delegation(diff) => {
                             self.remove_pk_delegate(pk.clone())?;
-                            after.delegation_unapply(diff)
+
+                            // restore the previous delegate, self if none
+                            let num = self.get_num_pk_delegations(&pk)?;
+                            let delegate = match num.checked_sub(1) {
+                                Some(idx) => self.get_pk_delegation(&pk, idx)?,
+                                None => None,
+                            };
+
+                            let after = after.delegation_unapply(diff);
+                            Account {
+                                delegate: delegate.unwrap_or_else(|| after.public_key.clone()),
+                                ..after
+                            }
                         }
                         FailedTransactionNonce(diff) => after.failed_transaction_unapply(diff),
 
@@ -261,12 +522,15 @@ impl BestLedgerStore for IndexerStore {
 
             // remove accounts
             for (pk, token) in remove_pks.iter() {
-                self.update_best_account(pk, token, None, None)?;
+                let before = self
+                    .get_best_account(pk, token)?
+                    .map(|a| (a.is_zkapp_account(), a.balance.0));
+                self.update_best_account(pk, token, before, None)?;
             }
         }
 
         // apply
-        for (block_apply_diffs, _) in updates.apply.into_iter() {
+        for (block_apply_diffs, new_accounts) in updates.apply.into_iter() {
             let token_account_diffs = aggregate_token_account_diffs(block_apply_diffs);
 
             for ((pk, token), diffs) in token_account_diffs {
@@ -277,12 +541,21 @@ impl BestLedgerStore for IndexerStore {
                 );
 
                 for diff in diffs.iter() {
+                    if let Err(e) = after.check_account_diff(diff) {
+                        let anomaly = AmountAnomaly::new(state_hash, diff, e);
+                        error!("Saturating best ledger account diff {anomaly}");
+                        self.add_amount_anomaly(&anomaly)?;
+                    }
+
                     after = match diff {
                         Payment(diff) | FeeTransfer(diff) | FeeTransferViaCoinbase(diff) => {
                             after.payment(diff)
                         }
                         Coinbase(diff) => after.coinbase(diff.amount),
-                        Delegation(diff) => after.delegation(diff.delegate.clone(), diff.nonce),
+                        Delegation(diff) => {
+                            self.add_pk_delegate(&pk, &diff.delegate)?;
+                            after.delegation(diff.delegate.clone(), diff.nonce)
+                        }
                         FailedTransactionNonce(diff) => after.failed_transaction(diff.nonce),
                         ZkappStateDiff(diff) => after.zkapp_state(diff),
                         ZkappPermissionsDiff(diff) => after.zkapp_permissions(diff),
@@ -293,12 +566,12 @@ impl BestLedgerStore for IndexerStore {
                         ZkappVotingForDiff(diff) => after.zkapp_voting_for(diff),
                         ZkappIncrementNonce(diff) => after.zkapp_nonce(diff),
                         ZkappAccountCreationFee(diff) => after.zkapp_account_creation(diff),
-
-                        // these diffs do not modify the account
                         ZkappActionsDiff(diff) => {
                             self.add_actions(&diff.public_key, &diff.token, &diff.actions)?;
-                            after
+                            after.zkapp_actions(diff)
                         }
+
+                        // these diffs do not modify the account
                         ZkappEventsDiff(diff) => {
                             self.add_events(&diff.public_key, &diff.token, &diff.events)?;
                             after
@@ -309,6 +582,499 @@ impl BestLedgerStore for IndexerStore {
 
                 self.update_best_account(&pk, &token, before_values, Some(after))?;
             }
+
+            // accounts created by best chain blocks are pending until the
+            // block is canonical
+            for (pk, token) in new_accounts.iter() {
+                self.database.put_cf(
+                    self.best_ledger_accounts_status_cf(),
+                    best_account_key(token, pk),
+                    [PENDING],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_best_account_balance_history(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+        entry: &BalanceHistoryEntry,
+    ) -> Result<()> {
+        trace!("Setting best ledger account {pk} balance history at height {height}");
+        let value = serde_json::to_vec(entry)?;
+        self.database.put_cf(
+            self.best_ledger_accounts_balance_history_cf(),
+            best_account_balance_history_key(token, pk, height),
+            &value,
+        )?;
+
+        // custom token transfer
+        if *token != TokenAddress::default() {
+            self.database.put_cf(
+                self.best_ledger_token_transfers_cf(),
+                token_transfer_key(token, height, pk),
+                value,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn remove_best_account_balance_history(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<()> {
+        trace!("Removing best ledger account {pk} balance history at height {height}");
+        self.database.delete_cf(
+            self.best_ledger_accounts_balance_history_cf(),
+            best_account_balance_history_key(token, pk, height),
+        )?;
+
+        if *token != TokenAddress::default() {
+            self.database.delete_cf(
+                self.best_ledger_token_transfers_cf(),
+                token_transfer_key(token, height, pk),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn get_best_account_balance_history(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<BalanceHistoryEntry>> {
+        trace!("Getting best ledger account {pk} balance history at height {height}");
+        Ok(self
+            .get_cf(
+                self.best_ledger_accounts_balance_history_cf(),
+                best_account_balance_history_key(token, pk, height),
+            )?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+
+    fn get_best_account_balance_history_range(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        start_height: u32,
+        end_height: u32,
+        limit: usize,
+    ) -> Result<Vec<(u32, BalanceHistoryEntry)>> {
+        trace!("Getting best ledger account {pk} balance history at heights {start_height}..={end_height}");
+        let account_key = best_account_key(token, pk);
+        let start = best_account_balance_history_key(token, pk, start_height);
+
+        let mut history = vec![];
+        for (key, value) in self
+            .iterator_cf(
+                self.best_ledger_accounts_balance_history_cf(),
+                IteratorMode::From(&start, Direction::Forward),
+            )
+            .flatten()
+        {
+            if !key.starts_with(&account_key) || history.len() >= limit {
+                break;
+            }
+
+            let height = u32_from_be_bytes(&key[account_key.len()..])?;
+            if height > end_height {
+                break;
+            }
+
+            history.push((height, serde_json::from_slice(&value)?));
+        }
+
+        Ok(history)
+    }
+
+    fn get_best_account_balance_at(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<u64>> {
+        trace!("Getting best ledger account {pk} balance at height {height}");
+        let account_key = best_account_key(token, pk);
+        let key = best_account_balance_history_key(token, pk, height);
+
+        // latest change at or below the height
+        if let Some((key, value)) = self
+            .iterator_cf(
+                self.best_ledger_accounts_balance_history_cf(),
+                IteratorMode::From(&key, Direction::Reverse),
+            )
+            .flatten()
+            .next()
+        {
+            if key.starts_with(&account_key) {
+                let entry: BalanceHistoryEntry = serde_json::from_slice(&value)?;
+                return Ok(Some(entry.after));
+            }
+        }
+
+        // earliest change above the height
+        if let Some((key, value)) = self
+            .iterator_cf(
+                self.best_ledger_accounts_balance_history_cf(),
+                IteratorMode::From(&key, Direction::Forward),
+            )
+            .flatten()
+            .next()
+        {
+            if key.starts_with(&account_key) {
+                let entry: BalanceHistoryEntry = serde_json::from_slice(&value)?;
+                return Ok(entry.before);
+            }
+        }
+
+        // unchanged since genesis
+        Ok(self.get_best_account(pk, token)?.map(|a| a.balance.0))
+    }
+
+    fn set_best_account_nonce_history(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+        entry: &NonceHistoryEntry,
+    ) -> Result<()> {
+        trace!("Setting best ledger account {pk} nonce history at height {height}");
+        Ok(self.database.put_cf(
+            self.best_ledger_accounts_nonce_history_cf(),
+            best_account_balance_history_key(token, pk, height),
+            serde_json::to_vec(entry)?,
+        )?)
+    }
+
+    fn remove_best_account_nonce_history(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<()> {
+        trace!("Removing best ledger account {pk} nonce history at height {height}");
+        Ok(self.database.delete_cf(
+            self.best_ledger_accounts_nonce_history_cf(),
+            best_account_balance_history_key(token, pk, height),
+        )?)
+    }
+
+    fn get_best_account_nonce_history(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<NonceHistoryEntry>> {
+        trace!("Getting best ledger account {pk} nonce history at height {height}");
+        Ok(self
+            .get_cf(
+                self.best_ledger_accounts_nonce_history_cf(),
+                best_account_balance_history_key(token, pk, height),
+            )?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+
+    fn get_best_account_nonce_at(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<u32>> {
+        trace!("Getting best ledger account {pk} nonce at height {height}");
+        let account_key = best_account_key(token, pk);
+        let key = best_account_balance_history_key(token, pk, height);
+
+        // latest change at or below the height
+        if let Some((key, value)) = self
+            .iterator_cf(
+                self.best_ledger_accounts_nonce_history_cf(),
+                IteratorMode::From(&key, Direction::Reverse),
+            )
+            .flatten()
+            .next()
+        {
+            if key.starts_with(&account_key) {
+                let entry: NonceHistoryEntry = serde_json::from_slice(&value)?;
+                return Ok(Some(entry.after));
+            }
+        }
+
+        // earliest change above the height
+        if let Some((key, value)) = self
+            .iterator_cf(
+                self.best_ledger_accounts_nonce_history_cf(),
+                IteratorMode::From(&key, Direction::Forward),
+            )
+            .flatten()
+            .next()
+        {
+            if key.starts_with(&account_key) {
+                let entry: NonceHistoryEntry = serde_json::from_slice(&value)?;
+                return Ok(entry.before);
+            }
+        }
+
+        // unchanged since genesis
+        Ok(self
+            .get_best_account(pk, token)?
+            .map(|acct| account_nonce(&acct)))
+    }
+
+    fn get_best_account_epoch_balances(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        from_epoch: u32,
+        to_epoch: u32,
+    ) -> Result<Vec<EpochBalance>> {
+        trace!("Getting best ledger account {pk} balances of epochs {from_epoch}..={to_epoch}");
+        let constants = self.get_chain_constants()?;
+        let best_epoch = match self.get_best_block_global_slot()? {
+            Some(global_slot) => constants.epoch(global_slot),
+            None => return Ok(vec![]),
+        };
+
+        // last canonical block of each epoch
+        let mut boundaries = vec![];
+        for epoch in from_epoch..=to_epoch.min(best_epoch) {
+            let first_slot = epoch * constants.slots_per_epoch;
+            let last_slot = first_slot + constants.slots_per_epoch - 1;
+
+            let mut boundary = None;
+            if let Some((key, value)) = self
+                .iterator_cf(
+                    self.canonicity_slot_cf(),
+                    IteratorMode::From(&last_slot.to_be_bytes(), Direction::Reverse),
+                )
+                .flatten()
+                .next()
+            {
+                if u32_from_be_bytes(&key)? >= first_slot {
+                    let state_hash = StateHash::from_bytes(&value)?;
+                    let height = self
+                        .get_block_height(&state_hash)?
+                        .with_context(|| format!("block height {state_hash}"))?;
+                    boundary = Some((height, state_hash));
+                }
+            }
+            boundaries.push((epoch, boundary));
+        }
+
+        // single pass over the account's ascending balance history
+        let account_key = best_account_key(token, pk);
+        let mut history = self
+            .iterator_cf(
+                self.best_ledger_accounts_balance_history_cf(),
+                IteratorMode::From(&account_key, Direction::Forward),
+            )
+            .flatten()
+            .take_while(|(key, _)| key.starts_with(&account_key))
+            .map(|(key, value)| -> Result<(u32, BalanceHistoryEntry)> {
+                let height = u32_from_be_bytes(&key[account_key.len()..])?;
+                Ok((height, serde_json::from_slice(&value)?))
+            });
+
+        let mut latest: Option<BalanceHistoryEntry> = None;
+        let mut next = history.next().transpose()?;
+        let mut unchanged = None;
+
+        let mut balances = Vec::with_capacity(boundaries.len());
+        for (epoch, boundary) in boundaries {
+            let balance = match boundary.as_ref() {
+                None => None,
+                Some((height, _)) => {
+                    while let Some((_, entry)) = next.filter(|(h, _)| h <= height) {
+                        latest = Some(entry);
+                        next = history.next().transpose()?;
+                    }
+
+                    match (latest, next) {
+                        // latest change at or below the height
+                        (Some(entry), _) => Some(entry.after),
+                        // earliest change above the height
+                        (None, Some((_, entry))) => entry.before,
+                        // unchanged since genesis
+                        (None, None) => {
+                            if unchanged.is_none() {
+                                unchanged =
+                                    Some(self.get_best_account(pk, token)?.map(|a| a.balance.0));
+                            }
+                            unchanged.flatten()
+                        }
+                    }
+                }
+            };
+
+            balances.push(EpochBalance {
+                epoch,
+                boundary,
+                balance,
+            });
+        }
+
+        Ok(balances)
+    }
+
+    fn get_token_holders(
+        &self,
+        token: &TokenAddress,
+        at_height: Option<u32>,
+        min_balance: u64,
+        cursor: Option<&PublicKey>,
+        limit: usize,
+    ) -> Result<Vec<(PublicKey, u64)>> {
+        trace!("Getting token {token} holders at height {at_height:?}");
+        let mut holders = Vec::with_capacity(limit.min(1000));
+        if limit == 0 {
+            return Ok(holders);
+        }
+
+        // accounts are keyed by token, then public key
+        let start = match cursor {
+            Some(pk) => best_account_key(token, pk).to_vec(),
+            None => token.0.as_bytes().to_vec(),
+        };
+
+        for (key, value) in self
+            .iterator_cf(
+                self.best_ledger_accounts_cf(),
+                IteratorMode::From(&start, Direction::Forward),
+            )
+            .flatten()
+        {
+            if !key.starts_with(token.0.as_bytes()) {
+                break;
+            }
+
+            let pk = pk_key_prefix(&key[TokenAddress::LEN..]);
+            if cursor == Some(&pk) {
+                continue;
+            }
+
+            let balance = match at_height {
+                None => serde_json::from_slice::<Account>(&value)?.balance.0,
+                Some(height) => match self.get_best_account_balance_at(&pk, token, height)? {
+                    Some(balance) => balance,
+                    None => continue,
+                },
+            };
+
+            if balance >= min_balance {
+                holders.push((pk, balance));
+
+                if holders.len() >= limit {
+                    break;
+                }
+            }
+        }
+
+        Ok(holders)
+    }
+
+    fn get_token_transfers(
+        &self,
+        token: &TokenAddress,
+        descending: bool,
+        limit: usize,
+    ) -> Result<Vec<(u32, PublicKey, BalanceHistoryEntry)>> {
+        trace!("Getting token {token} transfers");
+        let mode = if descending {
+            // past the token's last key
+            let mut upper = token_transfer_key(token, u32::MAX, &PublicKey::default());
+            upper[TokenAddress::LEN..].fill(u8::MAX);
+            IteratorMode::From(&upper, Direction::Reverse)
+        } else {
+            IteratorMode::From(token.0.as_bytes(), Direction::Forward)
+        };
+
+        let mut transfers = vec![];
+        for (key, value) in self
+            .iterator_cf(self.best_ledger_token_transfers_cf(), mode)
+            .flatten()
+        {
+            if !key.starts_with(token.0.as_bytes()) || transfers.len() >= limit {
+                break;
+            }
+
+            let height = u32_from_be_bytes(&key[TokenAddress::LEN..][..U32_LEN])?;
+            let pk = PublicKey::from_bytes(&key[TokenAddress::LEN..][U32_LEN..])?;
+            transfers.push((height, pk, serde_json::from_slice(&value)?));
+        }
+
+        Ok(transfers)
+    }
+
+    fn get_token_symbols(&self, tokens: &[TokenAddress]) -> Result<Vec<Option<TokenSymbol>>> {
+        trace!("Getting {} token symbols", tokens.len());
+        let mut symbols = vec![None; tokens.len()];
+
+        // the default token is always MINA
+        let mut idxs = Vec::with_capacity(tokens.len());
+        let mut others = Vec::with_capacity(tokens.len());
+        for (idx, token) in tokens.iter().enumerate() {
+            if *token == TokenAddress::default() {
+                symbols[idx] = Some(TokenSymbol::default());
+            } else {
+                idxs.push(idx);
+                others.push(token.clone());
+            }
+        }
+
+        if others.is_empty() {
+            return Ok(symbols);
+        }
+
+        // other tokens take their owner account's symbol
+        let mut owned = Vec::with_capacity(others.len());
+        let mut owner_keys = Vec::with_capacity(others.len());
+        for (idx, owner) in idxs.into_iter().zip(self.get_token_owners(&others)?) {
+            if let Some((owner, parent)) = owner {
+                owned.push(idx);
+                owner_keys.push(best_account_key(&parent, &owner));
+            }
+        }
+
+        for (idx, value) in owned.into_iter().zip(
+            self.multi_get_cf(
+                owner_keys
+                    .iter()
+                    .map(|key| (self.best_ledger_accounts_cf(), key)),
+            ),
+        ) {
+            if let Some(bytes) = value? {
+                symbols[idx] = serde_json::from_slice::<Account>(&bytes)?
+                    .token_symbol
+                    .filter(|symbol| !symbol.0.is_empty());
+            }
+        }
+
+        Ok(symbols)
+    }
+
+    fn set_best_accounts_canonical(&self, state_hash: &StateHash) -> Result<()> {
+        trace!("Setting best ledger accounts created by block {state_hash} canonical");
+        if let Some(diff) = self.get_block_ledger_diff(state_hash)? {
+            for (pk, token) in update_token_accounts(diff.new_pk_balances) {
+                let account_key = best_account_key(&token, &pk);
+                if self
+                    .get_pinned_cf(self.best_ledger_accounts_status_cf(), account_key)?
+                    .is_some()
+                {
+                    self.database.put_cf(
+                        self.best_ledger_accounts_status_cf(),
+                        account_key,
+                        [CANONICAL],
+                    )?;
+                }
+            }
         }
         Ok(())
     }
@@ -335,7 +1101,6 @@ impl BestLedgerStore for IndexerStore {
 
     fn get_num_pk_delegations(&self, pk: &PublicKey) -> Result<u32> {
         Ok(self
-            .database
             .get_cf(
                 self.best_ledger_accounts_num_delegations_cf(),
                 pk.0.as_bytes(),
@@ -346,7 +1111,6 @@ impl BestLedgerStore for IndexerStore {
     fn get_pk_delegation(&self, pk: &PublicKey, idx: u32) -> Result<Option<PublicKey>> {
         trace!("Getting pk {pk} delegation index {idx}");
         Ok(self
-            .database
             .get_cf(
                 self.best_ledger_accounts_delegations_cf(),
                 pk_index_key(pk, idx),
@@ -354,6 +1118,36 @@ impl BestLedgerStore for IndexerStore {
             .and_then(|bytes| PublicKey::from_bytes(&bytes).ok()))
     }
 
+    fn get_pk_delegations(&self, pk: &PublicKey) -> Result<Vec<PublicKey>> {
+        let mut delegations = vec![];
+        for idx in 0..self.get_num_pk_delegations(pk)? {
+            if let Some(delegate) = self.get_pk_delegation(pk, idx)? {
+                delegations.push(delegate);
+            }
+        }
+
+        Ok(delegations)
+    }
+
+    fn unapply_block(&self, state_hash: &StateHash) -> Result<StateHash> {
+        trace!("Unapplying best block {state_hash}");
+        let best_block_hash = self.get_best_block_hash()?;
+        if best_block_hash.as_ref() != Some(state_hash) {
+            bail!("Only the best block can be unapplied, {state_hash} is not the best block");
+        }
+
+        let parent_hash = self
+            .get_block_parent_hash(state_hash)?
+            .with_context(|| format!("Block missing parent: {state_hash}"))?;
+        if self.get_block_height(&parent_hash)?.is_none() {
+            bail!("Parent block missing from store: {parent_hash}");
+        }
+
+        // the reorg from the block to its parent unapplies the block
+        self.set_best_block(&parent_hash)?;
+        Ok(parent_hash)
+    }
+
     fn remove_pk_delegate(&self, pk: PublicKey) -> Result<()> {
         trace!("Removing pk {pk} delegate");
         let idx = self.get_num_pk_delegations(&pk)?;
@@ -380,14 +1174,16 @@ impl BestLedgerStore for IndexerStore {
             Equal => (),
             Greater => {
                 let old = self.get_num_accounts().ok().flatten().unwrap_or(0);
-                self.database.put(
+                self.database.put_cf(
+                    self.default_cf(),
                     Self::TOTAL_NUM_ACCOUNTS_KEY,
                     old.saturating_add(adjust.unsigned_abs()).to_be_bytes(),
                 )?;
             }
             Less => {
                 let old = self.get_num_accounts().ok().flatten().unwrap_or(0);
-                self.database.put(
+                self.database.put_cf(
+                    self.default_cf(),
                     Self::TOTAL_NUM_ACCOUNTS_KEY,
                     old.saturating_sub(adjust.unsigned_abs()).to_be_bytes(),
                 )?;
@@ -398,8 +1194,7 @@ impl BestLedgerStore for IndexerStore {
 
     fn get_num_accounts(&self) -> Result<Option<u32>> {
         Ok(self
-            .database
-            .get(Self::TOTAL_NUM_ACCOUNTS_KEY)?
+            .get_cf(self.default_cf(), Self::TOTAL_NUM_ACCOUNTS_KEY)?
             .map(from_be_bytes))
     }
 
@@ -429,13 +1224,337 @@ impl BestLedgerStore for IndexerStore {
     ///////////////
 
     fn best_ledger_account_balance_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.best_ledger_accounts_balance_sort_cf(), mode)
+        self.iterator_cf(self.best_ledger_accounts_balance_sort_cf(), mode)
     }
 
     fn zkapp_best_ledger_account_balance_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.zkapp_best_ledger_accounts_balance_sort_cf(), mode)
+        self.iterator_cf(self.zkapp_best_ledger_accounts_balance_sort_cf(), mode)
+    }
+}
+
+impl IndexerStore {
+    /// Get the block's account diffs & new accounts
+    fn get_block_account_update(
+        &self,
+        state_hash: &StateHash,
+    ) -> Result<Option<(Vec<AccountDiff>, HashSet<(PublicKey, TokenAddress)>)>> {
+        Ok(self.get_block_ledger_diff(state_hash)?.map(|diff| {
+            (
+                diff.account_diffs.into_iter().flatten().collect(),
+                update_token_accounts(diff.new_pk_balances),
+            )
+        }))
+    }
+}
+
+/// Best ledger account balance history, see [DerivedData]
+pub struct BalanceHistoryData;
+
+impl DerivedData for BalanceHistoryData {
+    fn name(&self) -> &'static str {
+        "balance history"
+    }
+
+    fn rewrite_range(&self, store: &IndexerStore, range: &ReplayRange) -> anyhow::Result<u32> {
+        let mut num_rewritten = 0;
+        let mut ledger = range.start_ledger.clone();
+
+        for (height, state_hash) in (range.from_height..).zip(range.state_hashes.iter()) {
+            let diff = store
+                .get_block_ledger_diff(state_hash)?
+                .with_context(|| format!("Block ledger diff missing from store {state_hash}"))?;
+
+            let accounts = touched_token_accounts(&(
+                diff.account_diffs.iter().flatten().cloned().collect(),
+                update_token_accounts(diff.new_pk_balances.clone()),
+            ));
+
+            let mut balances = vec![];
+            for (pk, token) in accounts {
+                let before = ledger.get_account(&pk, &token).map(|a| a.balance.0);
+                balances.push((pk, token, before));
+            }
+
+            ledger._apply_diff(&diff)?;
+
+            for (pk, token, before) in balances {
+                let expected = ledger
+                    .get_account(&pk, &token)
+                    .map(|a| a.balance.0)
+                    .filter(|after| before != Some(*after))
+                    .map(|after| BalanceHistoryEntry { before, after });
+                let stored = store.get_best_account_balance_history(&pk, &token, height)?;
+
+                if stored == expected {
+                    continue;
+                }
+
+                trace!("Rewriting best ledger account {pk} balance history at height {height}");
+                match expected {
+                    Some(entry) => {
+                        store.set_best_account_balance_history(&pk, &token, height, &entry)?
+                    }
+                    None => store.remove_best_account_balance_history(&pk, &token, height)?,
+                }
+
+                num_rewritten += 1;
+            }
+        }
+
+        Ok(num_rewritten)
+    }
+}
+
+/// Best ledger account nonce history, see [DerivedData]
+pub struct NonceHistoryData;
+
+impl DerivedData for NonceHistoryData {
+    fn name(&self) -> &'static str {
+        "nonce history"
+    }
+
+    fn rewrite_range(&self, store: &IndexerStore, range: &ReplayRange) -> anyhow::Result<u32> {
+        let mut num_rewritten = 0;
+        let mut ledger = range.start_ledger.clone();
+
+        for (height, state_hash) in (range.from_height..).zip(range.state_hashes.iter()) {
+            let diff = store
+                .get_block_ledger_diff(state_hash)?
+                .with_context(|| format!("Block ledger diff missing from store {state_hash}"))?;
+
+            let accounts = touched_token_accounts(&(
+                diff.account_diffs.iter().flatten().cloned().collect(),
+                update_token_accounts(diff.new_pk_balances.clone()),
+            ));
+
+            let mut nonces = vec![];
+            for (pk, token) in accounts {
+                let before = ledger.get_account(&pk, &token).map(account_nonce);
+                nonces.push((pk, token, before));
+            }
+
+            ledger._apply_diff(&diff)?;
+
+            for (pk, token, before) in nonces {
+                let expected = ledger
+                    .get_account(&pk, &token)
+                    .map(account_nonce)
+                    .filter(|after| before != Some(*after))
+                    .map(|after| NonceHistoryEntry { before, after });
+                let stored = store.get_best_account_nonce_history(&pk, &token, height)?;
+
+                if stored == expected {
+                    continue;
+                }
+
+                trace!("Rewriting best ledger account {pk} nonce history at height {height}");
+                match expected {
+                    Some(entry) => {
+                        store.set_best_account_nonce_history(&pk, &token, height, &entry)?
+                    }
+                    None => store.remove_best_account_nonce_history(&pk, &token, height)?,
+                }
+
+                num_rewritten += 1;
+            }
+        }
+
+        Ok(num_rewritten)
+    }
+}
+
+/// Zkapp action state transitions of the best chain blocks, see
+/// [DerivedData]
+pub struct ActionStateData;
+
+impl DerivedData for ActionStateData {
+    fn name(&self) -> &'static str {
+        "action state transition"
+    }
+
+    fn rewrite_range(&self, store: &IndexerStore, range: &ReplayRange) -> anyhow::Result<u32> {
+        let mut num_rewritten = 0;
+        let mut ledger = range.start_ledger.clone();
+
+        // number of each account's actions before the current block
+        let mut num_actions_before = BTreeMap::new();
+
+        for (height, state_hash) in (range.from_height..).zip(range.state_hashes.iter()) {
+            let diff = store
+                .get_block_ledger_diff(state_hash)?
+                .with_context(|| format!("Block ledger diff missing from store {state_hash}"))?;
+
+            let block_actions = block_num_actions(&(
+                diff.account_diffs.iter().flatten().cloned().collect(),
+                HashSet::new(),
+            ));
+
+            let mut action_states = vec![];
+            for (pk, token) in block_actions.keys() {
+                action_states.push(ledger_action_state(&ledger, pk, token));
+            }
+
+            ledger._apply_diff(&diff)?;
+
+            for (((pk, token), num_actions), before) in block_actions.into_iter().zip(action_states)
+            {
+                let first_action_index = match num_actions_before.get(&(pk.clone(), token.clone()))
+                {
+                    Some(num) => *num,
+                    None => num_actions_before_range(store, &pk, &token, range.from_height)?,
+                };
+
+                let expected = ActionStateTransition {
+                    state_hash: state_hash.to_owned(),
+                    blockchain_length: height,
+                    first_action_index,
+                    num_actions,
+                    before,
+                    after: ledger_action_state(&ledger, &pk, &token),
+                };
+                num_actions_before.insert(
+                    (pk.clone(), token.clone()),
+                    first_action_index + num_actions,
+                );
+
+                if store
+                    .get_action_state_transition(&pk, &token, height)?
+                    .as_ref()
+                    == Some(&expected)
+                {
+                    continue;
+                }
+
+                trace!("Rewriting zkapp account {pk} action state transition at height {height}");
+                store.set_action_state_transition(&pk, &token, &expected)?;
+                num_rewritten += 1;
+            }
+        }
+
+        Ok(num_rewritten)
+    }
+}
+
+/// Zkapp events emitted by the best chain blocks, see [DerivedData]
+pub struct BlockEventsData;
+
+impl DerivedData for BlockEventsData {
+    fn name(&self) -> &'static str {
+        "block events"
+    }
+
+    fn rewrite_range(&self, store: &IndexerStore, range: &ReplayRange) -> anyhow::Result<u32> {
+        let mut num_rewritten = 0;
+
+        // number of each account's events before the current block
+        let mut num_events_before = BTreeMap::new();
+
+        for (height, state_hash) in (range.from_height..).zip(range.state_hashes.iter()) {
+            let diff = store
+                .get_block_ledger_diff(state_hash)?
+                .with_context(|| format!("Block ledger diff missing from store {state_hash}"))?;
+
+            let block_events = block_num_events(&(
+                diff.account_diffs.into_iter().flatten().collect(),
+                HashSet::new(),
+            ));
+
+            for ((pk, token), num_events) in block_events {
+                let first_event_index = match num_events_before.get(&(pk.clone(), token.clone())) {
+                    Some(num) => *num,
+                    None => num_events_before_range(store, &pk, &token, range.from_height)?,
+                };
+
+                let expected = ZkappBlockEvents {
+                    state_hash: state_hash.to_owned(),
+                    blockchain_length: height,
+                    first_event_index,
+                    num_events,
+                };
+                num_events_before
+                    .insert((pk.clone(), token.clone()), first_event_index + num_events);
+
+                if store.get_block_events(&pk, &token, height)?.as_ref() == Some(&expected) {
+                    continue;
+                }
+
+                trace!("Rewriting zkapp account {pk} block events at height {height}");
+                store.set_block_events(&pk, &token, &expected)?;
+                num_rewritten += 1;
+            }
+        }
+
+        Ok(num_rewritten)
+    }
+}
+
+/// Ledger account's action state commitments (empty for non-zkapp accounts)
+fn ledger_action_state(ledger: &Ledger, pk: &PublicKey, token: &TokenAddress) -> [ActionState; 5] {
+    ledger
+        .get_account(pk, token)
+        .and_then(|account| account.zkapp.as_ref())
+        .map(|zkapp| zkapp.action_state.clone())
+        .unwrap_or_default()
+}
+
+/// Number of the account's actions in best chain blocks below `height`
+fn num_actions_before_range(
+    store: &IndexerStore,
+    pk: &PublicKey,
+    token: &TokenAddress,
+    height: u32,
+) -> Result<u32> {
+    Ok(store
+        .get_action_state_transitions_range(pk, token, 0, height - 1, usize::MAX)?
+        .last()
+        .map_or(0, |transition| {
+            transition.first_action_index + transition.num_actions
+        }))
+}
+
+/// Number of the account's events in best chain blocks below `height`
+fn num_events_before_range(
+    store: &IndexerStore,
+    pk: &PublicKey,
+    token: &TokenAddress,
+    height: u32,
+) -> Result<u32> {
+    Ok(store
+        .get_block_events_range(pk, token, 0, height - 1, usize::MAX)?
+        .last()
+        .map_or(0, |block_events| {
+            block_events.first_event_index + block_events.num_events
+        }))
+}
+
+/// Account nonce, 0 if unset
+fn account_nonce(account: &Account) -> u32 {
+    account.nonce.unwrap_or_default().0
+}
+
+impl AccountStatusReads for IndexerStore {
+    fn get_best_account_pending(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+    ) -> Result<Option<bool>> {
+        trace!("Getting best ledger account {pk} status");
+        Ok(self
+            .get_pinned_cf(
+                self.best_ledger_accounts_status_cf(),
+                best_account_key(token, pk),
+            )?
+            .map(|bytes| bytes.first() == Some(&PENDING)))
+    }
+
+    fn is_best_account_zkapp(&self, pk: &PublicKey, token: &TokenAddress) -> Result<bool> {
+        trace!("Checking best ledger zkapp account {pk}");
+        Ok(self
+            .get_pinned_cf(
+                self.zkapp_best_ledger_accounts_cf(),
+                best_account_key(token, pk),
+            )?
+            .is_some())
     }
 }
 
@@ -464,6 +1583,63 @@ fn aggregate_token_account_diffs(
 
 use std::collections::BTreeMap;
 
+/// Token accounts touched by the block's account diffs or created by it
+fn touched_token_accounts(
+    (account_diffs, new_accounts): &(Vec<AccountDiff>, HashSet<(PublicKey, TokenAddress)>),
+) -> BTreeSet<(PublicKey, TokenAddress)> {
+    account_diffs
+        .iter()
+        .map(|diff| (diff.public_key(), diff.token_address()))
+        .chain(new_accounts.iter().cloned())
+        .collect()
+}
+
+/// Number of actions per token account in the block
+fn block_num_actions(
+    (account_diffs, _): &(Vec<AccountDiff>, HashSet<(PublicKey, TokenAddress)>),
+) -> BTreeMap<(PublicKey, TokenAddress), u32> {
+    let mut num_actions = BTreeMap::new();
+    for diff in account_diffs.iter() {
+        if let AccountDiff::ZkappActionsDiff(diff) = diff {
+            *num_actions
+                .entry((diff.public_key.to_owned(), diff.token.to_owned()))
+                .or_default() += diff.actions.len() as u32;
+        }
+    }
+
+    num_actions
+}
+
+/// Number of events per token account in the block
+fn block_num_events(
+    (account_diffs, _): &(Vec<AccountDiff>, HashSet<(PublicKey, TokenAddress)>),
+) -> BTreeMap<(PublicKey, TokenAddress), u32> {
+    let mut num_events = BTreeMap::new();
+    for diff in account_diffs.iter() {
+        if let AccountDiff::ZkappEventsDiff(diff) = diff {
+            *num_events
+                .entry((diff.public_key.to_owned(), diff.token.to_owned()))
+                .or_default() += diff.events.len() as u32;
+        }
+    }
+
+    num_events
+}
+
+/// Best ledger account's action state commitments (empty for non-zkapp
+/// accounts)
+fn best_action_state(
+    store: &IndexerStore,
+    pk: &PublicKey,
+    token: &TokenAddress,
+) -> Result<[ActionState; 5]> {
+    Ok(store
+        .get_best_account(pk, token)?
+        .and_then(|account| account.zkapp)
+        .map(|zkapp| zkapp.action_state)
+        .unwrap_or_default())
+}
+
 fn update_token_accounts(
     new_pk_balances: BTreeMap<PublicKey, BTreeMap<TokenAddress, u64>>,
 ) -> HashSet<(PublicKey, TokenAddress)> {
@@ -1,22 +1,37 @@
 use super::{
-    column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, username::UsernameStore, DbUpdate,
-    IndexerStore,
+    column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, sub_store::SubStore,
+    username::UsernameStore, DbUpdate, IndexerStore,
 };
 use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
     block::{
+        activity::{BlockActivity, DailyActivity},
+        fee_stats::BlockFeeStats,
+        missing::MissingBlock,
         precomputed::{PcbVersion, PrecomputedBlock},
+        receipt::BlockReceipt,
         store::{BlockStore, BlockUpdate, DbBlockUpdate},
+        supply::{BlockSupply, EpochSupply, Supply},
         BlockComparison,
     },
     canonicity::{store::CanonicityStore, Canonicity},
-    command::{internal::store::InternalCommandStore, store::UserCommandStore},
+    chain::store::ChainStore,
+    command::{
+        internal::store::InternalCommandStore, store::UserCommandStore, UserCommandWithStatusT,
+    },
     constants::*,
     event::{db::*, store::EventStore, IndexerEvent},
     ledger::{
         coinbase::Coinbase,
         diff::{account::AccountDiff, LedgerDiff},
-        store::{best::BestLedgerStore, staged::StagedLedgerStore},
+        store::{
+            anomaly::AmountAnomalyStore, best::BestLedgerStore, staged::StagedLedgerStore,
+            staking::StakingLedgerStore,
+        },
+        token::{
+            provenance::{ProvenanceStatus, TokenProvenance},
+            TokenAddress,
+        },
     },
     snark_work::store::SnarkStore,
     utility::store::{
@@ -26,11 +41,266 @@ use crate::{
             state_hash_suffix, u32_from_be_bytes, u32_prefix_key, u64_from_be_bytes, U32_LEN,
             U64_LEN,
         },
+        ledger::best::best_account_key,
     },
 };
 use anyhow::{bail, Context};
-use log::{error, trace};
+use log::{error, info, trace, warn};
 use speedb::{DBIterator, Direction, IteratorMode, WriteBatch};
+use std::collections::HashSet;
+
+/// Add the corresponding CF helpers to [ColumnFamilyHelpers] & bump the
+/// sub-store version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "blocks",
+    version: 8,
+    column_families: &[
+        "blocks",
+        "blocks-state-hash",
+        "blocks-version",
+        "blocks-at-length",
+        "blocks-at-slot",
+        "blocks-height",
+        "blocks-global-slot",
+        "blocks-parent-hash",
+        "blocks-date-time",
+        "blocks-epoch",
+        "blocks-genesis-hash",
+        "blocks-height-to-slots",
+        "blocks-slot-to-heights",
+        "blocks-height-sort",
+        "blocks-global-slot-sort",
+        "blocks-comparison",
+        "blocks-receipt",
+        "blocks-fee-stats",
+        "blocks-activity",
+        "blocks-daily-activity",
+        "blocks-supply",
+        "blocks-epoch-supply",
+        "blocks-missing",
+        "blocks-token-owner",
+        "blocks-token-provenance",
+        "blocks-coinbase-receiver",
+        "blocks-creator",
+        "block-creator-height-sort",
+        "block-creator-slot-sort",
+        "coinbase-receiver-height-sort",
+        "coinbase-receiver-slot-sort",
+        "block-epoch-slots-produced",
+        "block-pk-epoch-slots-produced",
+        "blocks-pk-count",
+        // block counts
+        "block-production-pk-epoch",
+        "block-production-pk-canonical-epoch",
+        "block-production-pk-canonical-epoch-sort",
+        "block-production-pk-supercharged-epoch",
+        "block-production-pk-total",
+        "block-production-pk-canonical-total",
+        "block-production-pk-supercharged-total",
+        "block-production-epoch",
+        "block-production-canonical-epoch",
+        "block-production-supercharged-epoch",
+        "block-production-pk-orphaned-epoch",
+        "block-production-orphaned-epoch",
+        "block-user-command-counts",
+        "block-internal-command-counts",
+        // slot counts
+        "block-epoch-slots-produced-count",
+        "block-pk-epoch-slots-produced-count",
+        "block-pk-epoch-slots-produced-count-sort",
+    ],
+    dependencies: &[],
+    migrate,
+};
+
+/// - v1 -> v2: index existing blocks' fee stats
+/// - v2 -> v3: settle existing orphaned blocks up to the canonical tip
+/// - v3 -> v4: index existing v2 blocks' token owners
+/// - v4 -> v5: index existing blocks' activity & roll up the canonical ones
+/// - v5 -> v6: record the missing parents of existing blocks
+/// - v6 -> v7: record the provenance of existing blocks' tokens
+/// - v7 -> v8: index existing blocks' supply & roll up the canonical ones
+fn migrate(store: &IndexerStore, from_version: u32) -> anyhow::Result<()> {
+    match from_version {
+        1 => migrate_fee_stats(store),
+        2 => migrate_orphan_counts(store),
+        3 => migrate_token_owners(store),
+        4 => migrate_activity(store),
+        5 => migrate_missing_blocks(store),
+        6 => migrate_token_provenance(store),
+        7 => migrate_supply(store),
+        _ => SubStore::no_migration(store, from_version),
+    }
+}
+
+fn migrate_fee_stats(store: &IndexerStore) -> anyhow::Result<()> {
+    let mut batch = WriteBatch::default();
+    for (key, value) in store
+        .iterator_cf(store.blocks_cf(), IteratorMode::Start)
+        .flatten()
+    {
+        let state_hash = StateHash::from_bytes(&key)?;
+        let block: PrecomputedBlock = serde_json::from_slice(&value[U64_LEN..])?;
+        store.set_block_fee_stats_batch(
+            &state_hash,
+            &BlockFeeStats::from_precomputed(&block),
+            &mut batch,
+        )?;
+
+        if batch.len() >= 1000 {
+            store.database.write(std::mem::take(&mut batch))?;
+        }
+    }
+
+    Ok(store.database.write(batch)?)
+}
+
+fn migrate_orphan_counts(store: &IndexerStore) -> anyhow::Result<()> {
+    if let Some((key, _)) = store
+        .iterator_cf(store.canonicity_length_cf(), IteratorMode::End)
+        .flatten()
+        .next()
+    {
+        store.settle_orphaned_blocks(u32_from_be_bytes(&key)?)?;
+    }
+
+    Ok(())
+}
+
+fn migrate_token_owners(store: &IndexerStore) -> anyhow::Result<()> {
+    let mut batch = WriteBatch::default();
+    for (key, value) in store
+        .iterator_cf(store.block_version_cf(), IteratorMode::Start)
+        .flatten()
+    {
+        // only post-hardfork blocks use tokens
+        if serde_json::from_slice::<PcbVersion>(&value)? != PcbVersion::V2 {
+            continue;
+        }
+
+        let state_hash = StateHash::from_bytes(&key)?;
+        if let Some((block, _)) = store.get_block(&state_hash)? {
+            store.set_token_owners_batch(&block, &mut batch)?;
+        }
+
+        if batch.len() >= 1000 {
+            store.database.write(std::mem::take(&mut batch))?;
+        }
+    }
+
+    Ok(store.database.write(batch)?)
+}
+
+fn migrate_activity(store: &IndexerStore) -> anyhow::Result<()> {
+    let mut batch = WriteBatch::default();
+    for (key, value) in store
+        .iterator_cf(store.blocks_cf(), IteratorMode::Start)
+        .flatten()
+    {
+        // pruned orphaned block diffs are rebuilt from the block
+        let state_hash = StateHash::from_bytes(&key)?;
+        let block: PrecomputedBlock = serde_json::from_slice(&value[U64_LEN..])?;
+        store.set_block_activity_batch(
+            &state_hash,
+            &BlockActivity::new(&block, &LedgerDiff::from_precomputed(&block)),
+            &mut batch,
+        )?;
+
+        if batch.len() >= 1000 {
+            store.database.write(std::mem::take(&mut batch))?;
+        }
+    }
+    store.database.write(batch)?;
+
+    // daily rollups of the canonical blocks
+    for (key, value) in store
+        .iterator_cf(store.canonicity_length_cf(), IteratorMode::Start)
+        .flatten()
+    {
+        store.apply_block_activity(&StateHash::from_bytes(&value)?, u32_from_be_bytes(&key)?)?;
+    }
+
+    Ok(())
+}
+
+fn migrate_missing_blocks(store: &IndexerStore) -> anyhow::Result<()> {
+    let mut batch = WriteBatch::default();
+    let genesis_registry = store.get_genesis_registry()?;
+    for (key, value) in store
+        .iterator_cf(store.block_parent_hash_cf(), IteratorMode::Start)
+        .flatten()
+    {
+        let state_hash = StateHash::from_bytes(&key)?;
+        let parent_hash = StateHash::from_bytes(&value)?;
+        if genesis_registry.contains(&state_hash)
+            || store
+                .get_pinned_cf(store.blocks_state_hash_cf(), parent_hash.0.as_bytes())?
+                .is_some()
+        {
+            continue;
+        }
+
+        if let Some(height) = store.get_block_height(&state_hash)? {
+            batch.put_cf(
+                store.blocks_missing_cf(),
+                missing_block_key(height.saturating_sub(1), &parent_hash),
+                state_hash.0.as_bytes(),
+            );
+        }
+    }
+
+    store.database.write(batch)?;
+    Ok(())
+}
+
+fn migrate_token_provenance(store: &IndexerStore) -> anyhow::Result<()> {
+    // tokens are first seen in height order & each block's provenance
+    // depends on the previous blocks'
+    for (key, _) in store
+        .iterator_cf(store.blocks_height_sort_cf(), IteratorMode::Start)
+        .flatten()
+    {
+        let state_hash = state_hash_suffix(&key)?;
+        if store.get_block_version(&state_hash)? != Some(PcbVersion::V2) {
+            continue;
+        }
+
+        if let Some((block, _)) = store.get_block(&state_hash)? {
+            let mut batch = WriteBatch::default();
+            store.update_token_provenance_batch(&block, &mut batch)?;
+            store.database.write(batch)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn migrate_supply(store: &IndexerStore) -> anyhow::Result<()> {
+    let mut batch = WriteBatch::default();
+    for (key, value) in store
+        .iterator_cf(store.blocks_cf(), IteratorMode::Start)
+        .flatten()
+    {
+        let state_hash = StateHash::from_bytes(&key)?;
+        let block: PrecomputedBlock = serde_json::from_slice(&value[U64_LEN..])?;
+        store.set_block_supply_batch(&state_hash, &BlockSupply::new(&block), &mut batch)?;
+
+        if batch.len() >= 1000 {
+            store.database.write(std::mem::take(&mut batch))?;
+        }
+    }
+    store.database.write(batch)?;
+
+    // epoch rollups of the canonical blocks
+    for (key, value) in store
+        .iterator_cf(store.canonicity_length_cf(), IteratorMode::Start)
+        .flatten()
+    {
+        store.apply_block_supply(&StateHash::from_bytes(&value)?, u32_from_be_bytes(&key)?)?;
+    }
+
+    Ok(())
+}
 
 impl BlockStore for IndexerStore {
     /// Add the given block at its indices and record a db event
@@ -47,8 +317,7 @@ impl BlockStore for IndexerStore {
         value.append(&mut serde_json::to_vec(block)?);
 
         if matches!(
-            self.database
-                .get_cf(self.blocks_state_hash_cf(), state_hash.0.as_bytes()),
+            self.get_cf(self.blocks_state_hash_cf(), state_hash.0.as_bytes()),
             Ok(Some(_))
         ) {
             trace!("Block already present {}", block.summary());
@@ -60,18 +329,38 @@ impl BlockStore for IndexerStore {
         batch.put_cf(self.blocks_cf(), state_hash.0.as_bytes(), value);
 
         // add to ledger diff index
-        self.set_block_ledger_diff_batch(
+        let ledger_diff = LedgerDiff::from_precomputed(block);
+        self.set_block_ledger_diff_batch(&state_hash, &ledger_diff, &mut batch)?;
+
+        // record fee aggregates which overflowed & were split
+        for anomaly in AccountDiff::block_fee_anomalies(block) {
+            error!("Splitting aggregate fee {anomaly}");
+            self.add_amount_anomaly(&anomaly)?;
+        }
+
+        // add account activity
+        self.set_block_activity_batch(
             &state_hash,
-            &LedgerDiff::from_precomputed(block),
+            &BlockActivity::new(block, &ledger_diff),
             &mut batch,
         )?;
 
+        // add supply data
+        self.set_block_supply_batch(&state_hash, &BlockSupply::new(block), &mut batch)?;
+
         // add to epoch index before setting other indices
         self.set_block_epoch_batch(&state_hash, block.epoch_count(), &mut batch)?;
 
         // increment block production counts
         self.increment_block_production_count_batch(block, &mut batch)?;
 
+        // add user command fee stats
+        self.set_block_fee_stats_batch(
+            &state_hash,
+            &BlockFeeStats::from_precomputed(block),
+            &mut batch,
+        )?;
+
         // add comparison data before user commands, SNARKs, and internal commands
         self.set_block_comparison_batch(&state_hash, &BlockComparison::from(block))?;
 
@@ -99,7 +388,7 @@ impl BlockStore for IndexerStore {
         )?;
 
         // add to genesis state hash index
-        if state_hash.0 == MAINNET_GENESIS_HASH || state_hash.0 == HARDFORK_GENESIS_HASH {
+        if self.is_genesis_hash(&state_hash)? {
             self.set_block_genesis_state_hash_batch(&state_hash, &state_hash, &mut batch)?;
         } else {
             let genesis_state_hash = block.genesis_state_hash();
@@ -119,6 +408,12 @@ impl BlockStore for IndexerStore {
         // add to coinbase receiver index
         self.set_coinbase_receiver_batch(block, &mut batch)?;
 
+        // add to token owner index
+        self.set_token_owners_batch(block, &mut batch)?;
+
+        // record the provenance of the block's tokens
+        self.update_token_provenance_batch(block, &mut batch)?;
+
         // add block height/global slot for sorting
         batch.put_cf(self.blocks_height_sort_cf(), block_height_key(block), b"");
         batch.put_cf(
@@ -141,6 +436,9 @@ impl BlockStore for IndexerStore {
         // add pcb's version
         self.set_block_version_batch(&state_hash, block.version(), &mut batch)?;
 
+        // update the missing blocks
+        self.update_missing_blocks_batch(block, &mut batch)?;
+
         // add block user commands
         self.add_user_commands_batch(block, &mut batch)?;
 
@@ -157,21 +455,27 @@ impl BlockStore for IndexerStore {
         // add epoch produced slot
         self.add_epoch_slots_produced(
             block.epoch_count(),
-            block.global_slot_since_genesis() % MAINNET_EPOCH_SLOT_COUNT,
+            self.get_chain_constants()?
+                .epoch_slot(block.global_slot_since_genesis()),
             &block.block_creator(),
         )?;
 
         // add block SNARK work
         self.add_snark_work(block)?;
 
+        // blocks arriving at already settled heights are orphaned
+        if block.blockchain_length() <= self.get_orphans_settled_height()? {
+            self.increment_block_orphaned_production_count(&state_hash)?;
+        }
+
         // increment bytes processed
         let bytes_processed = self
-            .database
-            .get(Self::NUM_BLOCK_BYTES_PROCESSED)?
+            .get_cf(self.default_cf(), Self::NUM_BLOCK_BYTES_PROCESSED)?
             .map_or(0, |bytes| {
                 u64_from_be_bytes(&bytes).expect("bytes processed u64 BE bytes")
             });
-        self.database.put(
+        self.database.put_cf(
+            self.default_cf(),
             Self::NUM_BLOCK_BYTES_PROCESSED,
             (bytes_processed + num_block_bytes).to_be_bytes(),
         )?;
@@ -188,18 +492,13 @@ impl BlockStore for IndexerStore {
     fn get_block(&self, state_hash: &StateHash) -> anyhow::Result<Option<(PrecomputedBlock, u64)>> {
         trace!("Getting block {state_hash}");
         Ok(self
-            .database
             .get_pinned_cf(self.blocks_cf(), state_hash.0.as_bytes())?
             .and_then(|bytes| {
+                let block_bytes = u64_from_be_bytes(bytes.get(..U64_LEN)?).ok()?;
                 serde_json::from_slice::<PrecomputedBlock>(&bytes[U64_LEN..])
                     .with_context(|| format!("{:?}", bytes.to_vec()))
                     .ok()
-                    .map(|block| {
-                        (
-                            block,
-                            u64_from_be_bytes(&bytes[..U64_LEN]).expect("block bytes u64 BE bytes"),
-                        )
-                    })
+                    .map(|block| (block, block_bytes))
             }))
     }
 
@@ -218,8 +517,7 @@ impl BlockStore for IndexerStore {
     fn get_best_block_hash(&self) -> anyhow::Result<Option<StateHash>> {
         trace!("Getting best block state hash");
         Ok(self
-            .database
-            .get(Self::BEST_TIP_STATE_HASH_KEY)?
+            .get_cf(self.default_cf(), Self::BEST_TIP_STATE_HASH_KEY)?
             .and_then(|bytes| StateHash::from_bytes(&bytes).ok()))
     }
 
@@ -248,6 +546,7 @@ impl BlockStore for IndexerStore {
 
     fn set_best_block(&self, state_hash: &StateHash) -> anyhow::Result<()> {
         trace!("Setting best block {state_hash}");
+        let mut reorg = None;
         if let Some(old) = self.get_best_block_hash()? {
             if old == *state_hash {
                 return Ok(());
@@ -261,11 +560,29 @@ impl BlockStore for IndexerStore {
             self.update_block_usernames(&reorg_blocks)?;
             self.update_internal_commands(&reorg_blocks)?;
             self.update_user_commands(&reorg_blocks)?;
+            self.update_sql_mirror(&reorg_blocks)?;
+            reorg = reorg_event(self, &old, state_hash, &reorg_blocks)?;
+        } else if let (Some(blockchain_length), Some(global_slot_since_genesis)) = (
+            self.get_block_height(state_hash)?,
+            self.get_block_global_slot(state_hash)?,
+        ) {
+            // first best block
+            self.update_sql_mirror(&DbBlockUpdate {
+                apply: vec![BlockUpdate {
+                    state_hash: state_hash.clone(),
+                    blockchain_length,
+                    global_slot_since_genesis,
+                }],
+                unapply: vec![],
+            })?;
         }
 
         // set new best tip
-        self.database
-            .put(Self::BEST_TIP_STATE_HASH_KEY, state_hash.0.as_bytes())?;
+        self.database.put_cf(
+            self.default_cf(),
+            Self::BEST_TIP_STATE_HASH_KEY,
+            state_hash.0.as_bytes(),
+        )?;
 
         // record new best tip event
         match self.get_block_height(state_hash)? {
@@ -276,9 +593,27 @@ impl BlockStore for IndexerStore {
                         blockchain_length,
                     },
                 )))?;
+
+                // replicas measure their lag against the heartbeat
+                if let Err(e) = self.write_primary_heartbeat(blockchain_length) {
+                    error!("Failed to write primary heartbeat: {e}");
+                }
             }
             None => error!("Block missing from store: {state_hash}"),
         }
+
+        // record the branch switch
+        if let Some(reorg) = reorg {
+            info!(
+                "Reorg (depth {}) to best tip {} from {}",
+                reorg.depth(),
+                reorg.new_tip,
+                reorg.old_tip
+            );
+            self.add_event(&IndexerEvent::Db(DbEvent::Block(DbBlockEvent::Reorg(
+                reorg,
+            ))))?;
+        }
         Ok(())
     }
 
@@ -293,6 +628,8 @@ impl BlockStore for IndexerStore {
             new_best_tip
         );
 
+        let genesis_registry = self.get_genesis_registry()?;
+
         // follows the old best tip back to the common ancestor
         let mut a = old_best_tip.clone();
         let mut unapply = vec![];
@@ -300,18 +637,19 @@ impl BlockStore for IndexerStore {
         // follows the new best tip back to the common ancestor
         let mut b = new_best_tip.clone();
         let mut apply = vec![];
+        let a_length = self.get_block_height(&a)?.expect("a has length");
         let b_length = self.get_block_height(&b)?.expect("b has length");
 
         // bring b back to the same height as a
-        for _ in 0..b_length.saturating_sub(self.get_block_height(&a)?.expect("a has length")) {
+        for _ in 0..b_length.saturating_sub(a_length) {
             // check if there's a previous block
-            if b.0 == MAINNET_GENESIS_HASH || b.0 == HARDFORK_GENESIS_HASH {
+            if genesis_registry.contains(&b) {
                 break;
             }
 
             apply.push(BlockUpdate {
                 state_hash: b.clone(),
-                blockchain_length: b_length,
+                blockchain_length: self.get_block_height(&b)?.expect("b has length"),
                 global_slot_since_genesis: self
                     .get_block_global_slot(&b)?
                     .expect("b has global slot"),
@@ -320,11 +658,30 @@ impl BlockStore for IndexerStore {
             b = self.get_block_parent_hash(&b)?.expect("b has a parent");
         }
 
+        // bring a back to the same height as b, i.e. roll back to an ancestor
+        for _ in 0..a_length.saturating_sub(b_length) {
+            unapply.push(BlockUpdate {
+                state_hash: a.clone(),
+                blockchain_length: self.get_block_height(&a)?.expect("a has length"),
+                global_slot_since_genesis: self
+                    .get_block_global_slot(&a)?
+                    .expect("a has global slot"),
+            });
+
+            a = self.get_block_parent_hash(&a)?.expect("a has a parent");
+        }
+
+        // one best tip is an ancestor of the other
+        if a == b {
+            apply.reverse();
+            return Ok(DbUpdate { apply, unapply });
+        }
+
         // find the common ancestor
         let mut a_prev = self.get_block_parent_hash(&a)?.expect("a has a parent");
         let mut b_prev = self.get_block_parent_hash(&b)?.expect("b has a parent");
 
-        while a != b && (a.0 != MAINNET_GENESIS_HASH || a.0 != HARDFORK_GENESIS_HASH) {
+        while a != b && !genesis_registry.contains(&a) {
             // add blocks to appropriate collection
             let a_length = self.get_block_height(&a)?.expect("a has length");
             let b_length = self.get_block_height(&b)?.expect("b has length");
@@ -385,16 +742,21 @@ impl BlockStore for IndexerStore {
 
     fn get_block_ledger_diff(&self, state_hash: &StateHash) -> anyhow::Result<Option<LedgerDiff>> {
         trace!("Getting block ledger diff {state_hash}");
+        if let Some(bytes) =
+            self.get_pinned_cf(self.block_ledger_diff_cf(), state_hash.0.as_bytes())?
+        {
+            return Ok(serde_json::from_slice(&bytes).ok());
+        }
+
+        // pruned orphaned block diffs are rebuilt from the block
         Ok(self
-            .database
-            .get_pinned_cf(self.block_ledger_diff_cf(), state_hash.0.as_bytes())?
-            .and_then(|bytes| serde_json::from_slice(&bytes).ok()))
+            .get_block(state_hash)?
+            .map(|(block, _)| LedgerDiff::from_precomputed(&block)))
     }
 
     fn get_block_parent_hash(&self, state_hash: &StateHash) -> anyhow::Result<Option<StateHash>> {
         trace!("Getting block's parent hash {state_hash}");
         Ok(self
-            .database
             .get_cf(self.block_parent_hash_cf(), state_hash.0.as_bytes())?
             .and_then(|bytes| StateHash::from_bytes(&bytes).ok()))
     }
@@ -417,7 +779,6 @@ impl BlockStore for IndexerStore {
     fn get_block_date_time(&self, state_hash: &StateHash) -> anyhow::Result<Option<i64>> {
         trace!("Getting block date time {state_hash}");
         Ok(self
-            .database
             .get_cf(self.block_date_time_cf(), state_hash.0.as_bytes())?
             .and_then(|bytes| i64_from_be_bytes(&bytes).ok()))
     }
@@ -449,7 +810,6 @@ impl BlockStore for IndexerStore {
         }
 
         Ok(self
-            .database
             .get_cf(self.block_height_cf(), state_hash.0.as_bytes())?
             .map(from_be_bytes))
     }
@@ -472,7 +832,6 @@ impl BlockStore for IndexerStore {
     fn get_block_global_slot(&self, state_hash: &StateHash) -> anyhow::Result<Option<u32>> {
         trace!("Getting block global slot {state_hash}");
         Ok(self
-            .database
             .get_cf(self.block_global_slot_cf(), state_hash.0.as_bytes())?
             .map(from_be_bytes))
     }
@@ -495,7 +854,6 @@ impl BlockStore for IndexerStore {
     fn get_block_creator(&self, state_hash: &StateHash) -> anyhow::Result<Option<PublicKey>> {
         trace!("Getting block creator {state_hash}");
         Ok(self
-            .database
             .get_cf(self.block_creator_cf(), state_hash.0.as_bytes())?
             .and_then(|bytes| PublicKey::from_bytes(&bytes).ok()))
     }
@@ -539,7 +897,6 @@ impl BlockStore for IndexerStore {
     fn get_coinbase_receiver(&self, state_hash: &StateHash) -> anyhow::Result<Option<PublicKey>> {
         trace!("Getting coinbase receiver for {state_hash}");
         Ok(self
-            .database
             .get_cf(self.block_coinbase_receiver_cf(), state_hash.0.as_bytes())?
             .and_then(|bytes| PublicKey::from_bytes(&bytes).ok()))
     }
@@ -580,10 +937,158 @@ impl BlockStore for IndexerStore {
         Ok(())
     }
 
+    fn get_token_owners(
+        &self,
+        tokens: &[TokenAddress],
+    ) -> anyhow::Result<Vec<Option<(PublicKey, TokenAddress)>>> {
+        trace!("Getting {} token owners", tokens.len());
+
+        let mut owners = Vec::with_capacity(tokens.len());
+        for value in self.multi_get_cf(
+            tokens
+                .iter()
+                .map(|token| (self.block_token_owner_cf(), token.0.as_bytes())),
+        ) {
+            owners.push(match value? {
+                Some(bytes) => Some((
+                    PublicKey::from_bytes(&bytes[TokenAddress::LEN..])?,
+                    TokenAddress::from_bytes(bytes[..TokenAddress::LEN].to_vec())?,
+                )),
+                None => None,
+            });
+        }
+
+        Ok(owners)
+    }
+
+    fn set_token_owners_batch(
+        &self,
+        block: &PrecomputedBlock,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()> {
+        for token_used in block.tokens_used() {
+            if let (Some(owner), Some(parent)) = (token_used.token_owner, token_used.payment_token)
+            {
+                trace!(
+                    "Setting token owner: {} -> ({owner}, {parent})",
+                    token_used.used_token
+                );
+                batch.put_cf(
+                    self.block_token_owner_cf(),
+                    token_used.used_token.0.as_bytes(),
+                    best_account_key(&parent, &owner),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn update_token_provenance_batch(
+        &self,
+        block: &PrecomputedBlock,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()> {
+        // only post-hardfork blocks use tokens
+        if block.version() != PcbVersion::V2 {
+            return Ok(());
+        }
+
+        let tokens_used = block.tokens_used();
+        let mut tokens: Vec<TokenAddress> = vec![];
+        for token in tokens_used
+            .iter()
+            .map(|token_used| token_used.used_token.clone())
+            .chain(block.commands().iter().flat_map(|cmd| cmd.tokens()))
+        {
+            if token != TokenAddress::default() && !tokens.contains(&token) {
+                tokens.push(token);
+            }
+        }
+
+        let owners = self.get_token_owners(&tokens)?;
+        for (token, owner) in tokens.into_iter().zip(owners) {
+            let is_known = owner.is_some()
+                || tokens_used.iter().any(|token_used| {
+                    token_used.used_token == token && token_used.token_owner.is_some()
+                });
+
+            match self.get_token_provenance(&token)? {
+                None => {
+                    let provenance = if is_known {
+                        ProvenanceStatus::Known
+                    } else {
+                        warn!(
+                            "Token {token} used in block {} without a known owner",
+                            block.summary()
+                        );
+                        ProvenanceStatus::Unknown
+                    };
+
+                    batch.put_cf(
+                        self.block_token_provenance_cf(),
+                        token.0.as_bytes(),
+                        serde_json::to_vec(&TokenProvenance {
+                            token: token.clone(),
+                            provenance,
+                            first_seen_state_hash: block.state_hash(),
+                            first_seen_blockchain_length: block.blockchain_length(),
+                        })?,
+                    );
+                }
+                Some(mut token_provenance)
+                    if is_known && token_provenance.provenance == ProvenanceStatus::Unknown =>
+                {
+                    info!(
+                        "Resolved owner of token {token} in block {} (first seen in block {} at length {})",
+                        block.summary(),
+                        token_provenance.first_seen_state_hash,
+                        token_provenance.first_seen_blockchain_length,
+                    );
+
+                    token_provenance.provenance = ProvenanceStatus::Known;
+                    batch.put_cf(
+                        self.block_token_provenance_cf(),
+                        token.0.as_bytes(),
+                        serde_json::to_vec(&token_provenance)?,
+                    );
+                }
+                Some(_) => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_token_provenance(
+        &self,
+        token: &TokenAddress,
+    ) -> anyhow::Result<Option<TokenProvenance>> {
+        trace!("Getting token provenance {token}");
+        Ok(self
+            .get_pinned_cf(self.block_token_provenance_cf(), token.0.as_bytes())?
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok()))
+    }
+
+    fn get_num_unknown_provenance_tokens(&self) -> anyhow::Result<u32> {
+        trace!("Getting number of unknown provenance tokens");
+        let mut count = 0;
+        for (_, value) in self
+            .iterator_cf(self.block_token_provenance_cf(), IteratorMode::Start)
+            .flatten()
+        {
+            let token_provenance: TokenProvenance = serde_json::from_slice(&value)?;
+            if token_provenance.provenance == ProvenanceStatus::Unknown {
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
     fn get_num_blocks_at_height(&self, blockchain_length: u32) -> anyhow::Result<u32> {
         trace!("Getting number of blocks at height {blockchain_length}");
         Ok(self
-            .database
             .get_cf(self.blocks_at_height_cf(), blockchain_length.to_be_bytes())?
             .map_or(0, from_be_bytes))
     }
@@ -618,7 +1123,7 @@ impl BlockStore for IndexerStore {
         let mut blocks = vec![];
 
         for n in 0..num_blocks_at_height {
-            match self.database.get_cf(
+            match self.get_cf(
                 self.blocks_at_height_cf(),
                 block_num_key(blockchain_length, n),
             )? {
@@ -633,7 +1138,6 @@ impl BlockStore for IndexerStore {
     fn get_num_blocks_at_slot(&self, slot: u32) -> anyhow::Result<u32> {
         trace!("Getting number of blocks at slot {slot}");
         Ok(self
-            .database
             .get_cf(self.blocks_at_global_slot_cf(), slot.to_be_bytes())?
             .map_or(0, from_be_bytes))
     }
@@ -668,10 +1172,7 @@ impl BlockStore for IndexerStore {
         let mut blocks = vec![];
 
         for n in 0..self.get_num_blocks_at_slot(slot)? {
-            match self
-                .database
-                .get_cf(self.blocks_at_global_slot_cf(), block_num_key(slot, n))?
-            {
+            match self.get_cf(self.blocks_at_global_slot_cf(), block_num_key(slot, n))? {
                 None => break,
                 Some(bytes) => blocks.push(StateHash::from_bytes(&bytes)?),
             }
@@ -684,7 +1185,6 @@ impl BlockStore for IndexerStore {
     fn get_num_blocks_at_public_key(&self, pk: &PublicKey) -> anyhow::Result<u32> {
         trace!("Getting number of blocks at public key {pk}");
         Ok(self
-            .database
             .get_cf(self.blocks_pk_count_cf(), pk.0.as_bytes())?
             .map_or(0, from_be_bytes))
     }
@@ -719,10 +1219,7 @@ impl BlockStore for IndexerStore {
         let mut blocks = vec![];
 
         for n in 0..self.get_num_blocks_at_public_key(pk)? {
-            match self
-                .database
-                .get_cf(self.blocks_cf(), pk_index_key(pk, n))?
-            {
+            match self.get_cf(self.blocks_cf(), pk_index_key(pk, n))? {
                 None => break,
                 Some(bytes) => blocks.push(StateHash::from_bytes(&bytes)?),
             }
@@ -752,7 +1249,6 @@ impl BlockStore for IndexerStore {
         trace!("Getting block version {state_hash}");
         let key = state_hash.0.as_bytes();
         Ok(self
-            .database
             .get_pinned_cf(self.block_version_cf(), key)?
             .and_then(|bytes| serde_json::from_slice(&bytes).ok()))
     }
@@ -816,7 +1312,6 @@ impl BlockStore for IndexerStore {
     ) -> anyhow::Result<Option<Vec<u32>>> {
         trace!("Getting global slot for height {blockchain_length}");
         Ok(self
-            .database
             .get_pinned_cf(
                 self.block_height_to_global_slots_cf(),
                 blockchain_length.to_be_bytes(),
@@ -830,7 +1325,6 @@ impl BlockStore for IndexerStore {
     ) -> anyhow::Result<Option<Vec<u32>>> {
         trace!("Getting height for global slot {global_slot}");
         Ok(self
-            .database
             .get_pinned_cf(
                 self.block_global_slot_to_heights_cf(),
                 global_slot.to_be_bytes(),
@@ -856,7 +1350,6 @@ impl BlockStore for IndexerStore {
     fn get_block_epoch(&self, state_hash: &StateHash) -> anyhow::Result<Option<u32>> {
         trace!("Getting block epoch {state_hash}");
         Ok(self
-            .database
             .get_cf(self.block_epoch_cf(), state_hash.0.as_bytes())?
             .map(from_be_bytes))
     }
@@ -885,7 +1378,6 @@ impl BlockStore for IndexerStore {
         trace!("Getting block genesis state hash {state_hash}");
 
         Ok(self
-            .database
             .get_cf(self.block_genesis_state_hash_cf(), state_hash.0.as_bytes())?
             .and_then(|bytes| StateHash::from_bytes(&bytes).ok()))
     }
@@ -901,7 +1393,6 @@ impl BlockStore for IndexerStore {
         // add to total
         let key = block_num_key(epoch, epoch_slot);
         if self
-            .database
             .get_cf(self.block_epoch_slots_produced_cf(), key)?
             .is_none()
         {
@@ -921,7 +1412,6 @@ impl BlockStore for IndexerStore {
         // add to account
         let key = epoch_pk_num_key(epoch, pk, epoch_slot);
         if self
-            .database
             .get_cf(self.block_pk_epoch_slots_produced_cf(), key)?
             .is_none()
         {
@@ -942,11 +1432,11 @@ impl BlockStore for IndexerStore {
 
     fn get_next_global_slot_produced(&self, global_slot: u32) -> anyhow::Result<Option<u32>> {
         trace!("Getting next slot produced at or above {global_slot}");
-        let epoch = global_slot / MAINNET_EPOCH_SLOT_COUNT;
-        let epoch_slot = global_slot % MAINNET_EPOCH_SLOT_COUNT;
+        let constants = self.get_chain_constants()?;
+        let epoch = constants.epoch(global_slot);
+        let epoch_slot = constants.epoch_slot(global_slot);
 
         if let Some((key, _)) = self
-            .database
             .iterator_cf(
                 self.block_epoch_slots_produced_cf(),
                 IteratorMode::From(&block_num_key(epoch, epoch_slot), Direction::Forward),
@@ -956,18 +1446,18 @@ impl BlockStore for IndexerStore {
         {
             let epoch = u32_from_be_bytes(&key[..U32_LEN]).expect("epoch u32 bytes");
             let epoch_slot = u32_from_be_bytes(&key[U32_LEN..]).expect("epoch slot u32 bytes");
-            return Ok(Some(epoch * MAINNET_EPOCH_SLOT_COUNT + epoch_slot));
+            return Ok(Some(epoch * constants.slots_per_epoch + epoch_slot));
         }
         Ok(None)
     }
 
     fn get_prev_global_slot_produced(&self, global_slot: u32) -> anyhow::Result<u32> {
         trace!("Getting previous slot produced at or below {global_slot}");
-        let epoch = global_slot / MAINNET_EPOCH_SLOT_COUNT;
-        let epoch_slot = global_slot % MAINNET_EPOCH_SLOT_COUNT;
+        let constants = self.get_chain_constants()?;
+        let epoch = constants.epoch(global_slot);
+        let epoch_slot = constants.epoch_slot(global_slot);
 
         if let Some((key, _)) = self
-            .database
             .iterator_cf(
                 self.block_epoch_slots_produced_cf(),
                 IteratorMode::From(&block_num_key(epoch, epoch_slot), Direction::Reverse),
@@ -977,7 +1467,7 @@ impl BlockStore for IndexerStore {
         {
             let epoch = u32_from_be_bytes(&key[..U32_LEN]).expect("epoch u32 bytes");
             let epoch_slot = u32_from_be_bytes(&key[U32_LEN..]).expect("epoch slot u32 bytes");
-            return Ok(epoch * MAINNET_EPOCH_SLOT_COUNT + epoch_slot);
+            return Ok(epoch * constants.slots_per_epoch + epoch_slot);
         }
         Ok(0)
     }
@@ -987,33 +1477,27 @@ impl BlockStore for IndexerStore {
     ///////////////
 
     fn blocks_height_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.blocks_height_sort_cf(), mode)
+        self.iterator_cf(self.blocks_height_sort_cf(), mode)
     }
 
     fn blocks_global_slot_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.blocks_global_slot_sort_cf(), mode)
+        self.iterator_cf(self.blocks_global_slot_sort_cf(), mode)
     }
 
     fn block_creator_block_height_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.block_creator_height_sort_cf(), mode)
+        self.iterator_cf(self.block_creator_height_sort_cf(), mode)
     }
 
     fn block_creator_global_slot_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.block_creator_slot_sort_cf(), mode)
+        self.iterator_cf(self.block_creator_slot_sort_cf(), mode)
     }
 
     fn coinbase_receiver_block_height_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.block_coinbase_height_sort_cf(), mode)
+        self.iterator_cf(self.block_coinbase_height_sort_cf(), mode)
     }
 
     fn coinbase_receiver_global_slot_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.block_coinbase_slot_sort_cf(), mode)
+        self.iterator_cf(self.block_coinbase_slot_sort_cf(), mode)
     }
 
     fn canonical_epoch_blocks_produced_iterator(
@@ -1038,7 +1522,7 @@ impl BlockStore for IndexerStore {
                 start[U32_LEN..][U32_LEN..].copy_from_slice(PublicKey::upper_bound().0.as_bytes());
             }
         }
-        self.database.iterator_cf(
+        self.iterator_cf(
             self.block_production_pk_canonical_epoch_sort_cf(),
             IteratorMode::From(start.as_slice(), direction),
         )
@@ -1083,7 +1567,11 @@ impl BlockStore for IndexerStore {
 
         // increment total count
         let acc = self.get_block_production_total_count()?;
-        batch.put(Self::TOTAL_NUM_BLOCKS_KEY, (acc + 1).to_be_bytes());
+        batch.put_cf(
+            self.default_cf(),
+            Self::TOTAL_NUM_BLOCKS_KEY,
+            (acc + 1).to_be_bytes(),
+        );
 
         // supercharged counts
         if Coinbase::from_precomputed(block).supercharge {
@@ -1114,7 +1602,8 @@ impl BlockStore for IndexerStore {
 
             // total supercharged
             let acc = self.get_block_production_supercharged_total_count()?;
-            batch.put(
+            batch.put_cf(
+                self.default_cf(),
                 Self::TOTAL_NUM_BLOCKS_SUPERCHARGED_KEY,
                 (acc + 1).to_be_bytes(),
             );
@@ -1156,8 +1645,11 @@ impl BlockStore for IndexerStore {
 
         // increment total count
         let acc = self.get_block_production_total_count()?;
-        self.database
-            .put(Self::TOTAL_NUM_BLOCKS_KEY, (acc + 1).to_be_bytes())?;
+        self.database.put_cf(
+            self.default_cf(),
+            Self::TOTAL_NUM_BLOCKS_KEY,
+            (acc + 1).to_be_bytes(),
+        )?;
 
         // supercharged counts
         if supercharged {
@@ -1187,7 +1679,8 @@ impl BlockStore for IndexerStore {
 
             // total supercharged
             let acc = self.get_block_production_supercharged_total_count()?;
-            self.database.put(
+            self.database.put_cf(
+                self.default_cf(),
                 Self::TOTAL_NUM_BLOCKS_SUPERCHARGED_KEY,
                 (acc + 1).to_be_bytes(),
             )?;
@@ -1313,6 +1806,234 @@ impl BlockStore for IndexerStore {
         Ok(())
     }
 
+    fn increment_block_orphaned_production_count(
+        &self,
+        state_hash: &StateHash,
+    ) -> anyhow::Result<()> {
+        trace!("Incrementing orphaned block production count {state_hash}");
+        let creator = self.get_block_creator(state_hash)?.expect("block creator");
+        let epoch = self.get_block_epoch(state_hash)?.expect("block epoch");
+
+        // increment pk epoch count
+        let acc = self.get_block_production_pk_orphaned_epoch_count(&creator, Some(epoch))?;
+        self.database.put_cf(
+            self.block_production_pk_orphaned_epoch_cf(),
+            u32_prefix_key(epoch, &creator),
+            (acc + 1).to_be_bytes(),
+        )?;
+
+        // increment epoch count
+        let acc = self.get_block_production_orphaned_epoch_count(Some(epoch))?;
+        self.database.put_cf(
+            self.block_production_orphaned_epoch_cf(),
+            epoch.to_be_bytes(),
+            (acc + 1).to_be_bytes(),
+        )?;
+        Ok(())
+    }
+
+    fn decrement_block_orphaned_production_count(
+        &self,
+        state_hash: &StateHash,
+    ) -> anyhow::Result<()> {
+        trace!("Decrementing orphaned block production count {state_hash}");
+        let creator = self.get_block_creator(state_hash)?.expect("block creator");
+        let epoch = self.get_block_epoch(state_hash)?.expect("block epoch");
+
+        // decrement pk epoch count
+        let acc = self.get_block_production_pk_orphaned_epoch_count(&creator, Some(epoch))?;
+        assert!(acc > 0);
+        self.database.put_cf(
+            self.block_production_pk_orphaned_epoch_cf(),
+            u32_prefix_key(epoch, &creator),
+            (acc - 1).to_be_bytes(),
+        )?;
+
+        // decrement epoch count
+        let acc = self.get_block_production_orphaned_epoch_count(Some(epoch))?;
+        assert!(acc > 0);
+        self.database.put_cf(
+            self.block_production_orphaned_epoch_cf(),
+            epoch.to_be_bytes(),
+            (acc - 1).to_be_bytes(),
+        )?;
+        Ok(())
+    }
+
+    fn settle_orphaned_blocks(&self, settled_height: u32) -> anyhow::Result<()> {
+        let prev_settled_height = self.get_orphans_settled_height()?;
+        if settled_height <= prev_settled_height {
+            return Ok(());
+        }
+
+        trace!("Settling orphaned blocks (length {prev_settled_height} -> {settled_height})");
+        for height in prev_settled_height + 1..=settled_height {
+            for state_hash in self.get_blocks_at_height(height)? {
+                if let Some(Canonicity::Orphaned) = self.get_block_canonicity(&state_hash)? {
+                    self.increment_block_orphaned_production_count(&state_hash)?;
+                }
+            }
+        }
+
+        self.database.put_cf(
+            self.default_cf(),
+            Self::ORPHANS_SETTLED_HEIGHT_KEY,
+            settled_height.to_be_bytes(),
+        )?;
+        Ok(())
+    }
+
+    fn get_orphans_settled_height(&self) -> anyhow::Result<u32> {
+        trace!("Getting orphans settled height");
+        Ok(self
+            .get_cf(self.default_cf(), Self::ORPHANS_SETTLED_HEIGHT_KEY)?
+            .map_or(0, from_be_bytes))
+    }
+
+    fn prune_orphaned_ledger_diffs(&self, height: u32) -> anyhow::Result<u32> {
+        let prev_pruned_height = self.get_orphans_pruned_height()?;
+        let height = height.min(self.get_orphans_settled_height()?);
+        if height <= prev_pruned_height {
+            return Ok(0);
+        }
+
+        trace!("Pruning orphaned block ledger diffs (length {prev_pruned_height} -> {height})");
+        let mut batch = WriteBatch::default();
+        let mut num_pruned = 0;
+        for height in prev_pruned_height + 1..=height {
+            for state_hash in self.get_blocks_at_height(height)? {
+                if let Some(Canonicity::Orphaned) = self.get_block_canonicity(&state_hash)? {
+                    batch.delete_cf(self.block_ledger_diff_cf(), state_hash.0.as_bytes());
+                    num_pruned += 1;
+                }
+            }
+        }
+
+        batch.put_cf(
+            self.default_cf(),
+            Self::ORPHANS_PRUNED_HEIGHT_KEY,
+            height.to_be_bytes(),
+        );
+        self.database.write(batch)?;
+        Ok(num_pruned)
+    }
+
+    fn get_orphans_pruned_height(&self) -> anyhow::Result<u32> {
+        trace!("Getting orphans pruned height");
+        Ok(self
+            .get_cf(self.default_cf(), Self::ORPHANS_PRUNED_HEIGHT_KEY)?
+            .map_or(0, from_be_bytes))
+    }
+
+    fn prune_orphaned_blocks(&self, height: u32) -> anyhow::Result<u32> {
+        let prev_pruned_height = self.get_orphaned_blocks_pruned_height()?;
+        let height = height.min(self.get_orphans_settled_height()?);
+        if height <= prev_pruned_height {
+            return Ok(0);
+        }
+
+        trace!("Pruning orphaned blocks (length {prev_pruned_height} -> {height})");
+        let mut batch = WriteBatch::default();
+        let mut num_pruned = 0;
+        let mut pruned_hashes = HashSet::new();
+        let mut pruned_blocks = vec![];
+        for height in prev_pruned_height + 1..=height {
+            let mut kept = vec![];
+            for state_hash in self.get_blocks_at_height(height)? {
+                if let Some(Canonicity::Orphaned) = self.get_block_canonicity(&state_hash)? {
+                    // the block's commands & SNARKs are indexed from its data
+                    if let Some((block, _)) = self.get_block(&state_hash)? {
+                        pruned_blocks.push(block);
+                    }
+
+                    let key = state_hash.0.as_bytes();
+                    batch.delete_cf(self.blocks_cf(), key);
+                    batch.delete_cf(self.block_ledger_diff_cf(), key);
+
+                    // sort indexes
+                    let slot = self.get_block_global_slot(&state_hash)?.unwrap_or_default();
+                    batch.delete_cf(
+                        self.blocks_height_sort_cf(),
+                        block_sort_key(height, &state_hash),
+                    );
+                    batch.delete_cf(
+                        self.blocks_global_slot_sort_cf(),
+                        block_sort_key(slot, &state_hash),
+                    );
+
+                    if let Some(block_creator) = self.get_block_creator(&state_hash)? {
+                        batch.delete_cf(
+                            self.block_creator_height_sort_cf(),
+                            pk_block_sort_key(&block_creator, height, &state_hash),
+                        );
+                        batch.delete_cf(
+                            self.block_creator_slot_sort_cf(),
+                            pk_block_sort_key(&block_creator, slot, &state_hash),
+                        );
+                    }
+
+                    if let Some(coinbase_receiver) = self.get_coinbase_receiver(&state_hash)? {
+                        batch.delete_cf(
+                            self.block_coinbase_height_sort_cf(),
+                            pk_block_sort_key(&coinbase_receiver, height, &state_hash),
+                        );
+                        batch.delete_cf(
+                            self.block_coinbase_slot_sort_cf(),
+                            pk_block_sort_key(&coinbase_receiver, slot, &state_hash),
+                        );
+                    }
+
+                    pruned_hashes.insert(state_hash);
+                    num_pruned += 1;
+                } else {
+                    kept.push(state_hash);
+                }
+            }
+
+            // rewrite the height's remaining blocks
+            let num_blocks_at_height = self.get_num_blocks_at_height(height)?;
+            if (kept.len() as u32) < num_blocks_at_height {
+                batch.put_cf(
+                    self.blocks_at_height_cf(),
+                    height.to_be_bytes(),
+                    (kept.len() as u32).to_be_bytes(),
+                );
+
+                for (n, state_hash) in kept.iter().enumerate() {
+                    batch.put_cf(
+                        self.blocks_at_height_cf(),
+                        block_num_key(height, n as u32),
+                        state_hash.0.as_bytes(),
+                    );
+                }
+                for n in kept.len() as u32..num_blocks_at_height {
+                    batch.delete_cf(self.blocks_at_height_cf(), block_num_key(height, n));
+                }
+            }
+        }
+
+        // events & command/SNARK indexes of the pruned blocks
+        self.remove_block_events_batch(&pruned_hashes, &mut batch)?;
+        self.remove_user_commands_batch(&pruned_blocks, &mut batch)?;
+        self.remove_internal_commands_batch(&pruned_blocks, &mut batch)?;
+        self.remove_snark_work_batch(&pruned_blocks, &mut batch)?;
+
+        batch.put_cf(
+            self.default_cf(),
+            Self::ORPHANED_BLOCKS_PRUNED_HEIGHT_KEY,
+            height.to_be_bytes(),
+        );
+        self.database.write(batch)?;
+        Ok(num_pruned)
+    }
+
+    fn get_orphaned_blocks_pruned_height(&self) -> anyhow::Result<u32> {
+        trace!("Getting orphaned blocks pruned height");
+        Ok(self
+            .get_cf(self.default_cf(), Self::ORPHANED_BLOCKS_PRUNED_HEIGHT_KEY)?
+            .map_or(0, from_be_bytes))
+    }
+
     fn get_block_production_pk_epoch_count(
         &self,
         pk: &PublicKey,
@@ -1321,7 +2042,6 @@ impl BlockStore for IndexerStore {
         let epoch = epoch.unwrap_or(self.get_current_epoch()?);
         trace!("Getting pk epoch {epoch} block production count {pk}");
         Ok(self
-            .database
             .get_cf(
                 self.block_production_pk_epoch_cf(),
                 u32_prefix_key(epoch, pk),
@@ -1337,7 +2057,6 @@ impl BlockStore for IndexerStore {
         let epoch = epoch.unwrap_or(self.get_current_epoch()?);
         trace!("Getting pk epoch {epoch} canonical block production count {pk}");
         Ok(self
-            .database
             .get_cf(
                 self.block_production_pk_canonical_epoch_cf(),
                 u32_prefix_key(epoch, pk),
@@ -1353,7 +2072,6 @@ impl BlockStore for IndexerStore {
         let epoch = epoch.unwrap_or(self.get_current_epoch()?);
         trace!("Getting pk epoch {epoch} supercharged block production count {pk}");
         Ok(self
-            .database
             .get_cf(
                 self.block_production_pk_supercharged_epoch_cf(),
                 u32_prefix_key(epoch, pk),
@@ -1361,10 +2079,24 @@ impl BlockStore for IndexerStore {
             .map_or(0, from_be_bytes))
     }
 
+    fn get_block_production_pk_orphaned_epoch_count(
+        &self,
+        pk: &PublicKey,
+        epoch: Option<u32>,
+    ) -> anyhow::Result<u32> {
+        let epoch = epoch.unwrap_or(self.get_current_epoch()?);
+        trace!("Getting pk epoch {epoch} orphaned block production count {pk}");
+        Ok(self
+            .get_cf(
+                self.block_production_pk_orphaned_epoch_cf(),
+                u32_prefix_key(epoch, pk),
+            )?
+            .map_or(0, from_be_bytes))
+    }
+
     fn get_block_production_pk_total_count(&self, pk: &PublicKey) -> anyhow::Result<u32> {
         trace!("Getting pk total block production count {pk}");
         Ok(self
-            .database
             .get_cf(self.block_production_pk_total_cf(), pk.0.as_bytes())?
             .map_or(0, from_be_bytes))
     }
@@ -1372,7 +2104,6 @@ impl BlockStore for IndexerStore {
     fn get_block_production_pk_canonical_total_count(&self, pk: &PublicKey) -> anyhow::Result<u32> {
         trace!("Getting pk total canonical block production count {pk}");
         Ok(self
-            .database
             .get_cf(
                 self.block_production_pk_canonical_total_cf(),
                 pk.0.as_bytes(),
@@ -1386,7 +2117,6 @@ impl BlockStore for IndexerStore {
     ) -> anyhow::Result<u32> {
         trace!("Getting pk total supercharged block production count {pk}");
         Ok(self
-            .database
             .get_cf(
                 self.block_production_pk_supercharged_total_cf(),
                 pk.0.as_bytes(),
@@ -1398,7 +2128,6 @@ impl BlockStore for IndexerStore {
         let epoch = epoch.unwrap_or(self.get_current_epoch()?);
         trace!("Getting epoch block production count {epoch}");
         Ok(self
-            .database
             .get_cf(self.block_production_epoch_cf(), epoch.to_be_bytes())?
             .map_or(0, from_be_bytes))
     }
@@ -1410,7 +2139,6 @@ impl BlockStore for IndexerStore {
         let epoch = epoch.unwrap_or(self.get_current_epoch()?);
         trace!("Getting epoch canonical block production count {epoch}");
         Ok(self
-            .database
             .get_cf(
                 self.block_production_canonical_epoch_cf(),
                 epoch.to_be_bytes(),
@@ -1425,7 +2153,6 @@ impl BlockStore for IndexerStore {
         let epoch = epoch.unwrap_or(self.get_current_epoch()?);
         trace!("Getting epoch supercharged block production count {epoch}");
         Ok(self
-            .database
             .get_cf(
                 self.block_production_supercharged_epoch_cf(),
                 epoch.to_be_bytes(),
@@ -1433,11 +2160,21 @@ impl BlockStore for IndexerStore {
             .map_or(0, from_be_bytes))
     }
 
+    fn get_block_production_orphaned_epoch_count(&self, epoch: Option<u32>) -> anyhow::Result<u32> {
+        let epoch = epoch.unwrap_or(self.get_current_epoch()?);
+        trace!("Getting epoch orphaned block production count {epoch}");
+        Ok(self
+            .get_cf(
+                self.block_production_orphaned_epoch_cf(),
+                epoch.to_be_bytes(),
+            )?
+            .map_or(0, from_be_bytes))
+    }
+
     fn get_block_production_total_count(&self) -> anyhow::Result<u32> {
         trace!("Getting total block production count");
         Ok(self
-            .database
-            .get(Self::TOTAL_NUM_BLOCKS_KEY)?
+            .get_cf(self.default_cf(), Self::TOTAL_NUM_BLOCKS_KEY)?
             .map_or(0, from_be_bytes))
     }
 
@@ -1450,8 +2187,7 @@ impl BlockStore for IndexerStore {
     fn get_block_production_supercharged_total_count(&self) -> anyhow::Result<u32> {
         trace!("Getting total supercharged block production count");
         Ok(self
-            .database
-            .get(Self::TOTAL_NUM_BLOCKS_SUPERCHARGED_KEY)?
+            .get_cf(self.default_cf(), Self::TOTAL_NUM_BLOCKS_SUPERCHARGED_KEY)?
             .map_or(0, from_be_bytes))
     }
 
@@ -1463,7 +2199,6 @@ impl BlockStore for IndexerStore {
         let epoch = epoch.unwrap_or(self.get_current_epoch()?);
         trace!("Getting epoch {epoch} pk {pk} slots produced count");
         Ok(self
-            .database
             .get_cf(
                 self.block_pk_epoch_slots_produced_count_cf(),
                 epoch_pk_key(epoch, pk),
@@ -1475,7 +2210,6 @@ impl BlockStore for IndexerStore {
         let epoch = epoch.unwrap_or(self.get_current_epoch()?);
         trace!("Getting epoch {epoch} slots produced count");
         Ok(self
-            .database
             .get_cf(
                 self.block_epoch_slots_produced_count_cf(),
                 epoch.to_be_bytes(),
@@ -1502,23 +2236,361 @@ impl BlockStore for IndexerStore {
     ) -> anyhow::Result<Option<BlockComparison>> {
         trace!("Getting block comparison {state_hash}");
         Ok(self
-            .database
             .get_pinned_cf(self.block_comparison_cf(), state_hash.0.as_bytes())?
             .and_then(|bytes| serde_json::from_slice(&bytes).ok()))
     }
 
+    fn set_block_receipt(
+        &self,
+        state_hash: &StateHash,
+        receipt: &BlockReceipt,
+    ) -> anyhow::Result<()> {
+        trace!("Setting block receipt {state_hash}");
+        Ok(self.database.put_cf(
+            self.block_receipt_cf(),
+            state_hash.0.as_bytes(),
+            serde_json::to_vec(receipt)?,
+        )?)
+    }
+
+    fn get_block_receipt(&self, state_hash: &StateHash) -> anyhow::Result<Option<BlockReceipt>> {
+        trace!("Getting block receipt {state_hash}");
+        Ok(self
+            .get_pinned_cf(self.block_receipt_cf(), state_hash.0.as_bytes())?
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok()))
+    }
+
+    fn set_block_fee_stats_batch(
+        &self,
+        state_hash: &StateHash,
+        fee_stats: &BlockFeeStats,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()> {
+        trace!("Setting block fee stats {state_hash}");
+        batch.put_cf(
+            self.block_fee_stats_cf(),
+            state_hash.0.as_bytes(),
+            serde_json::to_vec(fee_stats)?,
+        );
+        Ok(())
+    }
+
+    fn get_block_fee_stats(&self, state_hash: &StateHash) -> anyhow::Result<Option<BlockFeeStats>> {
+        trace!("Getting block fee stats {state_hash}");
+        Ok(self
+            .get_pinned_cf(self.block_fee_stats_cf(), state_hash.0.as_bytes())?
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok()))
+    }
+
+    fn set_block_activity_batch(
+        &self,
+        state_hash: &StateHash,
+        activity: &BlockActivity,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()> {
+        trace!("Setting block activity {state_hash}");
+        batch.put_cf(
+            self.block_activity_cf(),
+            state_hash.0.as_bytes(),
+            serde_json::to_vec(activity)?,
+        );
+        Ok(())
+    }
+
+    fn update_missing_blocks_batch(
+        &self,
+        block: &PrecomputedBlock,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()> {
+        let state_hash = block.state_hash();
+        let blockchain_length = block.blockchain_length();
+        batch.delete_cf(
+            self.blocks_missing_cf(),
+            missing_block_key(blockchain_length, &state_hash),
+        );
+
+        // genesis parents are never stored
+        let parent_hash = block.previous_state_hash();
+        if self.is_genesis_hash(&state_hash)?
+            || self
+                .get_pinned_cf(self.blocks_state_hash_cf(), parent_hash.0.as_bytes())?
+                .is_some()
+        {
+            return Ok(());
+        }
+
+        // keep the first referencing block
+        let key = missing_block_key(blockchain_length.saturating_sub(1), &parent_hash);
+        if self.get_pinned_cf(self.blocks_missing_cf(), key)?.is_none() {
+            trace!("Missing parent {parent_hash} of block {}", block.summary());
+            batch.put_cf(self.blocks_missing_cf(), key, state_hash.0.as_bytes());
+        }
+
+        Ok(())
+    }
+
+    fn get_missing_blocks(&self, limit: usize) -> anyhow::Result<Vec<MissingBlock>> {
+        trace!("Getting {limit} missing blocks");
+        let mut missing_blocks = vec![];
+        for (key, value) in self
+            .iterator_cf(self.blocks_missing_cf(), IteratorMode::Start)
+            .flatten()
+            .take(limit)
+        {
+            missing_blocks.push(MissingBlock {
+                state_hash: state_hash_suffix(&key)?,
+                blockchain_length: block_u32_prefix_from_key(&key)?,
+                referenced_by: StateHash::from_bytes(&value)?,
+            });
+        }
+
+        Ok(missing_blocks)
+    }
+
+    fn get_num_missing_blocks(&self) -> anyhow::Result<u32> {
+        trace!("Getting number of missing blocks");
+        Ok(self
+            .iterator_cf(self.blocks_missing_cf(), IteratorMode::Start)
+            .flatten()
+            .count() as u32)
+    }
+
+    fn get_block_activity(&self, state_hash: &StateHash) -> anyhow::Result<Option<BlockActivity>> {
+        trace!("Getting block activity {state_hash}");
+        Ok(self
+            .get_pinned_cf(self.block_activity_cf(), state_hash.0.as_bytes())?
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok()))
+    }
+
+    fn apply_block_activity(&self, state_hash: &StateHash, height: u32) -> anyhow::Result<()> {
+        let Some(activity) = self.get_block_activity(state_hash)? else {
+            trace!("No block activity to apply {state_hash}");
+            return Ok(());
+        };
+
+        let day = activity.day();
+        let mut daily = self.get_daily_activity(day)?.unwrap_or_default();
+        if daily.apply(height, &activity) {
+            trace!("Applying block activity to day {day} {state_hash}");
+            self.database.put_cf(
+                self.block_daily_activity_cf(),
+                day.to_be_bytes(),
+                serde_json::to_vec(&daily)?,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn unapply_block_activity(&self, state_hash: &StateHash, height: u32) -> anyhow::Result<()> {
+        let Some(activity) = self.get_block_activity(state_hash)? else {
+            trace!("No block activity to unapply {state_hash}");
+            return Ok(());
+        };
+
+        let day = activity.day();
+        let Some(mut daily) = self.get_daily_activity(day)? else {
+            return Ok(());
+        };
+
+        if daily.unapply(height, &activity) {
+            trace!("Unapplying block activity from day {day} {state_hash}");
+            if daily.is_empty() {
+                self.database
+                    .delete_cf(self.block_daily_activity_cf(), day.to_be_bytes())?;
+            } else {
+                self.database.put_cf(
+                    self.block_daily_activity_cf(),
+                    day.to_be_bytes(),
+                    serde_json::to_vec(&daily)?,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn get_daily_activity(&self, day: u32) -> anyhow::Result<Option<DailyActivity>> {
+        trace!("Getting daily activity {day}");
+        Ok(self
+            .get_pinned_cf(self.block_daily_activity_cf(), day.to_be_bytes())?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+
+    fn get_daily_activities(
+        &self,
+        start_day: u32,
+        end_day: u32,
+        limit: usize,
+    ) -> anyhow::Result<Vec<(u32, DailyActivity)>> {
+        trace!("Getting daily activities {start_day} -> {end_day}");
+        let mut activities = vec![];
+        for (key, value) in self
+            .iterator_cf(
+                self.block_daily_activity_cf(),
+                IteratorMode::From(&start_day.to_be_bytes(), Direction::Forward),
+            )
+            .flatten()
+        {
+            let day = u32_from_be_bytes(&key)?;
+            if day > end_day || activities.len() >= limit {
+                break;
+            }
+            activities.push((day, serde_json::from_slice(&value)?));
+        }
+        Ok(activities)
+    }
+
+    fn get_canonical_block_activities(
+        &self,
+        start: i64,
+        end: i64,
+        limit: usize,
+    ) -> anyhow::Result<Vec<(u32, StateHash, BlockActivity)>> {
+        trace!("Getting canonical block activities {start} -> {end}");
+        let start_key = canonical_date_time_key(start.max(0) as u64, 0);
+
+        let mut activities = vec![];
+        for (key, value) in self
+            .iterator_cf(
+                self.canonicity_date_time_cf(),
+                IteratorMode::From(&start_key, Direction::Forward),
+            )
+            .flatten()
+        {
+            if u64_from_be_bytes(&key[..U64_LEN])? as i64 > end || activities.len() >= limit {
+                break;
+            }
+
+            let state_hash = StateHash::from_bytes(&value)?;
+            if let Some(activity) = self.get_block_activity(&state_hash)? {
+                let height = u32_from_be_bytes(&key[U64_LEN..])?;
+                activities.push((height, state_hash, activity));
+            }
+        }
+        Ok(activities)
+    }
+
+    fn set_block_supply_batch(
+        &self,
+        state_hash: &StateHash,
+        supply: &BlockSupply,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()> {
+        trace!("Setting block supply {state_hash}");
+        batch.put_cf(
+            self.block_supply_cf(),
+            state_hash.0.as_bytes(),
+            serde_json::to_vec(supply)?,
+        );
+        Ok(())
+    }
+
+    fn get_block_supply(&self, state_hash: &StateHash) -> anyhow::Result<Option<BlockSupply>> {
+        trace!("Getting block supply {state_hash}");
+        Ok(self
+            .get_pinned_cf(self.block_supply_cf(), state_hash.0.as_bytes())?
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok()))
+    }
+
+    fn apply_block_supply(&self, state_hash: &StateHash, height: u32) -> anyhow::Result<()> {
+        let Some(supply) = self.get_block_supply(state_hash)? else {
+            trace!("No block supply to apply {state_hash}");
+            return Ok(());
+        };
+
+        let epoch = supply.epoch;
+        let mut epoch_supply = self.get_epoch_supply(epoch)?.unwrap_or_default();
+        if epoch_supply.apply(height, &supply) {
+            trace!("Applying block supply to epoch {epoch} {state_hash}");
+            self.database.put_cf(
+                self.block_epoch_supply_cf(),
+                epoch.to_be_bytes(),
+                serde_json::to_vec(&epoch_supply)?,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn unapply_block_supply(&self, state_hash: &StateHash, height: u32) -> anyhow::Result<()> {
+        let Some(supply) = self.get_block_supply(state_hash)? else {
+            trace!("No block supply to unapply {state_hash}");
+            return Ok(());
+        };
+
+        let epoch = supply.epoch;
+        let Some(mut epoch_supply) = self.get_epoch_supply(epoch)? else {
+            return Ok(());
+        };
+
+        // the next highest canonical block's total currency
+        let prev = match epoch_supply.prev_height(height) {
+            Some(prev_height) => match self.get_canonical_hash_at_height(prev_height)? {
+                Some(prev_hash) => self.get_block_supply(&prev_hash)?,
+                None => None,
+            },
+            None => None,
+        };
+
+        if epoch_supply.unapply(height, &supply, prev.as_ref()) {
+            trace!("Unapplying block supply from epoch {epoch} {state_hash}");
+            if epoch_supply.is_empty() {
+                self.database
+                    .delete_cf(self.block_epoch_supply_cf(), epoch.to_be_bytes())?;
+            } else {
+                self.database.put_cf(
+                    self.block_epoch_supply_cf(),
+                    epoch.to_be_bytes(),
+                    serde_json::to_vec(&epoch_supply)?,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn get_epoch_supply(&self, epoch: u32) -> anyhow::Result<Option<EpochSupply>> {
+        trace!("Getting epoch supply {epoch}");
+        Ok(self
+            .get_pinned_cf(self.block_epoch_supply_cf(), epoch.to_be_bytes())?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+
+    fn get_supply(&self, epoch: Option<u32>) -> anyhow::Result<Option<Supply>> {
+        trace!("Getting supply {epoch:?}");
+        let epoch_supply = match epoch {
+            Some(epoch) => self
+                .get_epoch_supply(epoch)?
+                .map(|epoch_supply| (epoch, epoch_supply)),
+            None => self
+                .iterator_cf(self.block_epoch_supply_cf(), IteratorMode::End)
+                .flatten()
+                .next()
+                .map(|(key, value)| -> anyhow::Result<_> {
+                    Ok((u32_from_be_bytes(&key)?, serde_json::from_slice(&value)?))
+                })
+                .transpose()?,
+        };
+
+        let Some((epoch, epoch_supply)) = epoch_supply else {
+            return Ok(None);
+        };
+
+        // locked supply from the epoch's staking ledger account timings
+        let staking_ledger = match self.get_staking_ledger_hash_by_epoch(epoch, None)? {
+            Some(ledger_hash) => self.get_staking_ledger(&ledger_hash, Some(epoch), None)?,
+            None => None,
+        };
+        Ok(Some(epoch_supply.supply(epoch, staking_ledger.as_ref())))
+    }
+
     fn block_cmp(
         &self,
         block: &StateHash,
         other: &StateHash,
     ) -> anyhow::Result<Option<std::cmp::Ordering>> {
         // get stored block comparisons
-        let res1 = self
-            .database
-            .get_cf(self.block_comparison_cf(), block.0.as_bytes());
-        let res2 = self
-            .database
-            .get_cf(self.block_comparison_cf(), other.0.as_bytes());
+        let res1 = self.get_cf(self.block_comparison_cf(), block.0.as_bytes());
+        let res2 = self.get_cf(self.block_comparison_cf(), other.0.as_bytes());
 
         // compare stored block comparisons
         if let (Ok(Some(bytes1)), Ok(Some(bytes2))) = (res1, res2) {
@@ -1599,6 +2671,41 @@ impl BlockStore for IndexerStore {
     }
 }
 
+/// Reorg event of the best tip update, `None` if the new best tip extends the
+/// old one
+fn reorg_event(
+    db: &IndexerStore,
+    old_tip: &StateHash,
+    new_tip: &StateHash,
+    reorg_blocks: &DbBlockUpdate,
+) -> anyhow::Result<Option<ReorgEvent>> {
+    let (Some(highest), Some(lowest)) = (reorg_blocks.unapply.first(), reorg_blocks.unapply.last())
+    else {
+        return Ok(None);
+    };
+
+    let common_ancestor = db
+        .get_block_parent_hash(&lowest.state_hash)?
+        .with_context(|| format!("block missing parent: {}", lowest.state_hash))?;
+    let new_tip_height = db
+        .get_block_height(new_tip)?
+        .with_context(|| format!("block missing from store: {new_tip}"))?;
+
+    Ok(Some(ReorgEvent {
+        old_tip: old_tip.clone(),
+        old_tip_height: highest.blockchain_length,
+        new_tip: new_tip.clone(),
+        new_tip_height,
+        common_ancestor,
+        common_ancestor_height: lowest.blockchain_length.saturating_sub(1),
+        orphaned_blocks: reorg_blocks
+            .unapply
+            .iter()
+            .map(|block| block.state_hash.clone())
+            .collect(),
+    }))
+}
+
 fn block_cmp(db: &IndexerStore, a: &StateHash, b: &StateHash) -> std::cmp::Ordering {
     use std::cmp::Ordering;
     let a_canonicity = db.get_block_canonicity(a).ok().flatten();
@@ -0,0 +1,130 @@
+//! New block, canonical block & best tip subscriptions
+//!
+//! As blocks are ingested, the indexer state publishes the witness tree's
+//! updates via the primary store: each block added to the witness tree, each
+//! block which becomes canonical & each best tip change. Subscribers filter
+//! the published notifications by kind.
+
+use crate::{
+    base::state_hash::StateHash,
+    block::{precomputed::PrecomputedBlock, Block},
+    store::IndexerStore,
+};
+use log::warn;
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError, error::TryRecvError};
+
+/// Max number of published notifications buffered for slow subscribers
+pub const BLOCK_NOTIFICATIONS_CAPACITY: usize = 1024;
+
+pub type BlockNotificationSender = broadcast::Sender<Arc<BlockNotification>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlockNotificationKind {
+    /// Block added to the witness tree
+    NewBlock,
+
+    /// Block which became canonical
+    NewCanonicalBlock,
+
+    /// Block which became the best tip
+    BestTipChanged,
+}
+
+/// Witness tree update of a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockNotification {
+    pub kind: BlockNotificationKind,
+    pub state_hash: StateHash,
+    pub parent_hash: StateHash,
+    pub blockchain_length: u32,
+    pub global_slot_since_genesis: u32,
+}
+
+/// Subscription to kinds of block notifications
+pub struct BlockSubscription {
+    kinds: Vec<BlockNotificationKind>,
+    receiver: broadcast::Receiver<Arc<BlockNotification>>,
+}
+
+impl BlockNotification {
+    pub fn new(kind: BlockNotificationKind, block: &Block) -> Self {
+        Self {
+            kind,
+            state_hash: block.state_hash.clone(),
+            parent_hash: block.parent_hash.clone(),
+            blockchain_length: block.blockchain_length,
+            global_slot_since_genesis: block.global_slot_since_genesis,
+        }
+    }
+
+    pub fn from_precomputed(kind: BlockNotificationKind, block: &PrecomputedBlock) -> Self {
+        Self {
+            kind,
+            state_hash: block.state_hash(),
+            parent_hash: block.previous_state_hash(),
+            blockchain_length: block.blockchain_length(),
+            global_slot_since_genesis: block.global_slot_since_genesis(),
+        }
+    }
+}
+
+impl BlockSubscription {
+    /// Next notification of the subscribed kinds, waits for one to be
+    /// published (`None` once the store is dropped)
+    pub async fn next(&mut self) -> Option<Arc<BlockNotification>> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(notification) => {
+                    if self.kinds.contains(&notification.kind) {
+                        return Some(notification);
+                    }
+                }
+                Err(RecvError::Lagged(num)) => {
+                    warn!("{:?} subscription missed {num} notifications", self.kinds);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Next already published notification of the subscribed kinds, if any
+    pub fn try_next(&mut self) -> Option<Arc<BlockNotification>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(notification) => {
+                    if self.kinds.contains(&notification.kind) {
+                        return Some(notification);
+                    }
+                }
+                Err(TryRecvError::Lagged(_)) => continue,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+impl IndexerStore {
+    /// Subscribe to the kind of block notification
+    pub fn subscribe_blocks(&self, kind: BlockNotificationKind) -> BlockSubscription {
+        self.subscribe_block_kinds(&[kind])
+    }
+
+    /// Subscribe to the kinds of block notifications, in publication order
+    pub fn subscribe_block_kinds(&self, kinds: &[BlockNotificationKind]) -> BlockSubscription {
+        BlockSubscription {
+            kinds: kinds.to_vec(),
+            receiver: self.block_notifications.subscribe(),
+        }
+    }
+
+    /// Publishes the block notification to any subscribers
+    pub(crate) fn publish_block_notification(&self, notification: BlockNotification) {
+        // no subscribers
+        if self.block_notifications.receiver_count() == 0 {
+            return;
+        }
+
+        self.block_notifications.send(Arc::new(notification)).ok();
+    }
+}
@@ -1,13 +1,71 @@
-use super::{column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, DbUpdate, IndexerStore};
+use super::{
+    column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, sub_store::SubStore, DbUpdate,
+    IndexerStore,
+};
 use crate::{
     base::state_hash::StateHash,
     block::store::{BlockStore, BlockUpdate, DbBlockUpdate},
-    canonicity::{store::CanonicityStore, Canonicity, CanonicityDiff, CanonicityUpdate},
+    canonicity::{
+        store::CanonicityStore, CanonicalBlockAt, Canonicity, CanonicityDiff, CanonicityUpdate,
+    },
     command::internal::{store::InternalCommandStore, DbInternalCommandWithData},
     constants::MAINNET_COINBASE_REWARD,
     event::{db::*, store::EventStore, IndexerEvent},
+    ledger::store::best::BestLedgerStore,
+    utility::store::{
+        block::canonical_date_time_key,
+        common::{u32_from_be_bytes, u64_from_be_bytes, U64_LEN},
+    },
+    watchlist::store::WatchlistStore,
+};
+use anyhow::Context;
+use log::{error, trace};
+use speedb::{Direction, IteratorMode};
+
+/// Add the corresponding CF helpers to [ColumnFamilyHelpers] & bump the
+/// sub-store version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "canonicity",
+    version: 1,
+    column_families: &[
+        "canonicity-length",
+        "canonicity-slot",
+        "canonicity-date-time",
+    ],
+    dependencies: &["blocks"],
+    migrate: SubStore::no_migration,
 };
-use log::trace;
+
+impl IndexerStore {
+    /// Index the canonical block by date time, if the block is present
+    fn set_canonical_date_time(&self, height: u32, state_hash: &StateHash) -> anyhow::Result<()> {
+        if let Some(date_time) = self.get_block_date_time(state_hash)? {
+            self.database.put_cf(
+                self.canonicity_date_time_cf(),
+                canonical_date_time_key(date_time as u64, height),
+                state_hash.0.as_bytes(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Lazily load the canonical hash cache from the canonicity CF
+    fn load_canonical_hash_cache(&self) -> anyhow::Result<()> {
+        if self.canonical_hash_cache.is_loaded() {
+            return Ok(());
+        }
+
+        trace!("Loading canonical hash cache");
+        self.canonical_hash_cache.load_with(|| {
+            Ok(self
+                .iterator_cf(self.canonicity_length_cf(), IteratorMode::Start)
+                .map(|entry| -> anyhow::Result<(u32, StateHash)> {
+                    let (key, value) = entry?;
+                    Ok((u32_from_be_bytes(&key)?, StateHash::from_bytes(&value)?))
+                }))
+        })
+    }
+}
 
 impl CanonicityStore for IndexerStore {
     fn add_canonical_block(
@@ -45,6 +103,7 @@ impl CanonicityStore for IndexerStore {
             height.to_be_bytes(),
             state_hash.0.as_bytes(),
         )?;
+        self.canonical_hash_cache.set(height, state_hash);
 
         // slot -> state hash
         self.database.put_cf(
@@ -53,6 +112,17 @@ impl CanonicityStore for IndexerStore {
             state_hash.0.as_bytes(),
         )?;
 
+        // date time -> state hash
+        self.set_canonical_date_time(height, state_hash)?;
+        self.apply_block_activity(state_hash, height)?;
+        self.apply_block_supply(state_hash, height)?;
+
+        // record & publish notifications of the block's watched accounts
+        self.add_watch_notifications(state_hash, height, global_slot)?;
+
+        // accounts created by the block are no longer pending
+        self.set_best_accounts_canonical(state_hash)?;
+
         // record new genesis/prev state hashes
         if let Some(genesis_prev_state_hash) = genesis_prev_state_hash {
             let (mut genesis_state_hashes, mut genesis_prev_state_hashes) = (
@@ -65,14 +135,16 @@ impl CanonicityStore for IndexerStore {
                 // if not
                 // add genesis state hash
                 genesis_state_hashes.push(genesis_state_hash.clone());
-                self.database.put(
+                self.database.put_cf(
+                    self.default_cf(),
                     Self::KNOWN_GENESIS_STATE_HASHES_KEY,
                     serde_json::to_vec(&genesis_state_hashes)?,
                 )?;
 
                 // add genesis prev state hash
                 genesis_prev_state_hashes.push(genesis_prev_state_hash.clone());
-                self.database.put(
+                self.database.put_cf(
+                    self.default_cf(),
                     Self::KNOWN_GENESIS_PREV_STATE_HASHES_KEY,
                     serde_json::to_vec(&genesis_prev_state_hashes)?,
                 )?;
@@ -92,8 +164,7 @@ impl CanonicityStore for IndexerStore {
     fn get_known_genesis_state_hashes(&self) -> anyhow::Result<Vec<StateHash>> {
         trace!("Getting known genesis state hashes");
         Ok(self
-            .database
-            .get_pinned(Self::KNOWN_GENESIS_STATE_HASHES_KEY)?
+            .get_pinned_cf(self.default_cf(), Self::KNOWN_GENESIS_STATE_HASHES_KEY)?
             .map_or(vec![], |bytes| {
                 serde_json::from_slice(&bytes).expect("known genesis state hashes")
             }))
@@ -102,8 +173,7 @@ impl CanonicityStore for IndexerStore {
     fn get_known_genesis_prev_state_hashes(&self) -> anyhow::Result<Vec<StateHash>> {
         trace!("Getting known genesis prev state hashes");
         Ok(self
-            .database
-            .get_pinned(Self::KNOWN_GENESIS_PREV_STATE_HASHES_KEY)?
+            .get_pinned_cf(self.default_cf(), Self::KNOWN_GENESIS_PREV_STATE_HASHES_KEY)?
             .map_or(vec![], |bytes| {
                 serde_json::from_slice(&bytes).expect("known genesis prev state hashes")
             }))
@@ -111,8 +181,16 @@ impl CanonicityStore for IndexerStore {
 
     fn get_canonical_hash_at_height(&self, height: u32) -> anyhow::Result<Option<StateHash>> {
         trace!("Getting canonical state hash at height {height}");
+
+        // the cache tracks the live canonical chain
+        if self.is_primary && !self.is_pinned() {
+            self.load_canonical_hash_cache()?;
+            if let Some(state_hash) = self.canonical_hash_cache.get(height) {
+                return Ok(Some(state_hash));
+            }
+        }
+
         Ok(self
-            .database
             .get_pinned_cf(&self.canonicity_length_cf(), height.to_be_bytes())?
             .and_then(|bytes| StateHash::from_bytes(&bytes).ok()))
     }
@@ -120,11 +198,87 @@ impl CanonicityStore for IndexerStore {
     fn get_canonical_hash_at_slot(&self, global_slot: u32) -> anyhow::Result<Option<StateHash>> {
         trace!("Getting canonical state hash at slot {global_slot}");
         Ok(self
-            .database
             .get_pinned_cf(&self.canonicity_slot_cf(), global_slot.to_be_bytes())?
             .and_then(|bytes| StateHash::from_bytes(&bytes).ok()))
     }
 
+    fn get_canonical_hash_at_or_before_slot(
+        &self,
+        global_slot: u32,
+    ) -> anyhow::Result<Option<StateHash>> {
+        trace!("Getting canonical state hash at or before slot {global_slot}");
+        let start = global_slot.to_be_bytes();
+        Ok(self
+            .iterator_cf(
+                self.canonicity_slot_cf(),
+                IteratorMode::From(&start, Direction::Reverse),
+            )
+            .flatten()
+            .next()
+            .and_then(|(_, value)| StateHash::from_bytes(&value).ok()))
+    }
+
+    fn get_canonical_block_at(&self, date_time: i64) -> anyhow::Result<CanonicalBlockAt> {
+        trace!("Getting canonical block at date time {date_time}");
+
+        // canonical tip has the latest date time
+        let tip_date_time = match self
+            .iterator_cf(self.canonicity_date_time_cf(), IteratorMode::End)
+            .flatten()
+            .next()
+        {
+            Some((key, _)) => u64_from_be_bytes(&key[..U64_LEN])?,
+            None => return Ok(CanonicalBlockAt::BeforeGenesis),
+        };
+
+        if date_time < 0 {
+            return Ok(CanonicalBlockAt::BeforeGenesis);
+        }
+        if date_time as u64 > tip_date_time {
+            return Ok(CanonicalBlockAt::AfterTip);
+        }
+
+        // latest canonical block at or before the date time
+        let start = canonical_date_time_key(date_time as u64, u32::MAX);
+        let mut height = match self
+            .iterator_cf(
+                self.canonicity_date_time_cf(),
+                IteratorMode::From(&start, Direction::Reverse),
+            )
+            .flatten()
+            .next()
+        {
+            Some((key, _)) => u32_from_be_bytes(&key[U64_LEN..])?,
+            None => return Ok(CanonicalBlockAt::BeforeGenesis),
+        };
+
+        // a higher block may have an earlier date time
+        while let Some(next_date_time) = self
+            .get_canonical_hash_at_height(height + 1)?
+            .map(|state_hash| self.get_block_date_time(&state_hash))
+            .transpose()?
+            .flatten()
+        {
+            if next_date_time > date_time {
+                break;
+            }
+            height += 1;
+        }
+
+        let state_hash = self
+            .get_canonical_hash_at_height(height)?
+            .with_context(|| format!("canonical block at height {height}"))?;
+        let date_time = self
+            .get_block_date_time(&state_hash)?
+            .with_context(|| format!("block date time {state_hash}"))?;
+
+        Ok(CanonicalBlockAt::Block {
+            height,
+            state_hash,
+            date_time,
+        })
+    }
+
     fn get_block_canonicity(&self, state_hash: &StateHash) -> anyhow::Result<Option<Canonicity>> {
         trace!("Getting canonicity of block {state_hash}");
         if let Ok(Some(height)) = self.get_block_height(state_hash) {
@@ -179,6 +333,8 @@ impl CanonicityStore for IndexerStore {
 
     fn update_canonicity(&self, updates: CanonicityUpdate) -> anyhow::Result<()> {
         trace!("Updating block canonicities: {updates:?}");
+        let orphans_settled_height = self.get_orphans_settled_height()?;
+
         // unapply canonicities
         for unapply in updates.unapply.iter() {
             // remove from canonicity sets
@@ -186,9 +342,24 @@ impl CanonicityStore for IndexerStore {
                 self.canonicity_length_cf(),
                 unapply.blockchain_length.to_be_bytes(),
             )?;
+            self.canonical_hash_cache
+                .truncate(unapply.blockchain_length);
             self.database
                 .delete_cf(self.canonicity_slot_cf(), unapply.global_slot.to_be_bytes())?;
+            if let Some(date_time) = self.get_block_date_time(&unapply.state_hash)? {
+                self.database.delete_cf(
+                    self.canonicity_date_time_cf(),
+                    canonical_date_time_key(date_time as u64, unapply.blockchain_length),
+                )?;
+            }
             self.decrement_block_canonical_production_count(&unapply.state_hash)?;
+            self.unapply_block_activity(&unapply.state_hash, unapply.blockchain_length)?;
+            self.unapply_block_supply(&unapply.state_hash, unapply.blockchain_length)?;
+
+            // deep reorgs orphan settled blocks
+            if unapply.blockchain_length <= orphans_settled_height {
+                self.increment_block_orphaned_production_count(&unapply.state_hash)?;
+            }
         }
 
         // apply canonicities
@@ -199,12 +370,26 @@ impl CanonicityStore for IndexerStore {
                 apply.blockchain_length.to_be_bytes(),
                 apply.state_hash.0.as_bytes(),
             )?;
+            self.canonical_hash_cache
+                .set(apply.blockchain_length, &apply.state_hash);
             self.database.put_cf(
                 self.canonicity_slot_cf(),
                 apply.global_slot.to_be_bytes(),
                 apply.state_hash.0.as_bytes(),
             )?;
+            self.set_canonical_date_time(apply.blockchain_length, &apply.state_hash)?;
             self.increment_block_canonical_production_count(&apply.state_hash)?;
+            self.apply_block_activity(&apply.state_hash, apply.blockchain_length)?;
+            self.apply_block_supply(&apply.state_hash, apply.blockchain_length)?;
+
+            if apply.blockchain_length <= orphans_settled_height {
+                self.decrement_block_orphaned_production_count(&apply.state_hash)?;
+            }
+        }
+
+        // notify zkapp subscribers
+        if let Err(e) = self.publish_zkapp_notifications(&updates) {
+            error!("Failed to publish zkapp notifications: {e}");
         }
         Ok(())
     }
@@ -1,7 +1,22 @@
-use super::{column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, IndexerStore};
-use crate::chain::{store::ChainStore, ChainId, Network};
+use super::{
+    column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, sub_store::SubStore, IndexerStore,
+};
+use crate::{
+    base::state_hash::StateHash,
+    chain::{store::ChainStore, ChainConstants, ChainId, GenesisRegistry, Network},
+};
 use log::trace;
 
+/// Add the corresponding CF helpers to [ColumnFamilyHelpers] & bump the
+/// sub-store version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "chain",
+    version: 1,
+    column_families: &["chain-id-to-network"],
+    dependencies: &[],
+    migrate: SubStore::no_migration,
+};
+
 impl ChainStore for IndexerStore {
     fn set_chain_id_for_network(
         &self,
@@ -24,15 +39,15 @@ impl ChainStore for IndexerStore {
         )?;
 
         // update current chain_id
-        self.database.put(Self::CHAIN_ID_KEY, chain_bytes)?;
+        self.database
+            .put_cf(self.default_cf(), Self::CHAIN_ID_KEY, chain_bytes)?;
         Ok(())
     }
 
     fn get_network(&self, chain_id: &ChainId) -> anyhow::Result<Network> {
         trace!("Getting network for chain id: {}", chain_id);
         Ok(Network::from(
-            self.database
-                .get_pinned_cf(self.chain_id_to_network_cf(), chain_id.0.as_bytes())?
+            self.get_pinned_cf(self.chain_id_to_network_cf(), chain_id.0.as_bytes())?
                 .expect("network should exist in database")
                 .to_vec(),
         ))
@@ -46,9 +61,53 @@ impl ChainStore for IndexerStore {
     fn get_chain_id(&self) -> anyhow::Result<ChainId> {
         trace!("Getting chain id");
         Ok(ChainId::from(
-            self.database
-                .get(Self::CHAIN_ID_KEY)?
+            self.get_cf(self.default_cf(), Self::CHAIN_ID_KEY)?
                 .expect("chain id should exist in database"),
         ))
     }
+
+    fn set_genesis_registry(&self, registry: &GenesisRegistry) -> anyhow::Result<()> {
+        trace!("Setting genesis registry {registry:?}");
+        Ok(self.database.put_cf(
+            self.default_cf(),
+            Self::GENESIS_REGISTRY_KEY,
+            serde_json::to_vec(registry)?,
+        )?)
+    }
+
+    fn get_genesis_registry(&self) -> anyhow::Result<GenesisRegistry> {
+        trace!("Getting genesis registry");
+        Ok(self
+            .get_pinned_cf(self.default_cf(), Self::GENESIS_REGISTRY_KEY)?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?
+            .unwrap_or_default())
+    }
+
+    fn is_genesis_hash(&self, state_hash: &StateHash) -> anyhow::Result<bool> {
+        Ok(self.get_genesis_registry()?.contains(state_hash))
+    }
+
+    fn get_flat_epoch(&self, genesis_state_hash: &StateHash, epoch: u32) -> anyhow::Result<u32> {
+        Ok(self
+            .get_genesis_registry()?
+            .flat_epoch(genesis_state_hash, epoch))
+    }
+
+    fn set_chain_constants(&self, constants: &ChainConstants) -> anyhow::Result<()> {
+        trace!("Setting chain constants {constants:?}");
+        Ok(self.database.put_cf(
+            self.default_cf(),
+            Self::CHAIN_CONSTANTS_KEY,
+            serde_json::to_vec(constants)?,
+        )?)
+    }
+
+    fn get_chain_constants(&self) -> anyhow::Result<ChainConstants> {
+        Ok(self
+            .get_pinned_cf(self.default_cf(), Self::CHAIN_CONSTANTS_KEY)?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?
+            .unwrap_or_default())
+    }
 }
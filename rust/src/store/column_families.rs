@@ -46,6 +46,33 @@ pub trait ColumnFamilyHelpers {
     /// CF for storing block comparison data
     fn block_comparison_cf(&self) -> &ColumnFamily;
 
+    /// CF for storing block receipt times
+    fn block_receipt_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing block user command fee stats
+    fn block_fee_stats_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing block account activity
+    fn block_activity_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing daily canonical block activity rollups
+    fn block_daily_activity_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing block supply data
+    fn block_supply_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing per epoch canonical block supply rollups
+    fn block_epoch_supply_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing blocks referenced as a parent, but not in the store
+    fn blocks_missing_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing token owners
+    fn block_token_owner_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing the provenance of tokens used in blocks
+    fn block_token_provenance_cf(&self) -> &ColumnFamily;
+
     /// CF for storing `height -> global slots`
     fn block_height_to_global_slots_cf(&self) -> &ColumnFamily;
 
@@ -97,6 +124,9 @@ pub trait ColumnFamilyHelpers {
     /// CF for storing canonical state hashes by global slot
     fn canonicity_slot_cf(&self) -> &ColumnFamily;
 
+    /// CF for storing canonical state hashes by date time & blockchain length
+    fn canonicity_date_time_cf(&self) -> &ColumnFamily;
+
     ////////////////////////////
     // User command store CFs //
     ////////////////////////////
@@ -134,6 +164,18 @@ pub trait ColumnFamilyHelpers {
     /// CF for sorting user commands by global slot
     fn user_commands_slot_sort_cf(&self) -> &ColumnFamily;
 
+    /// CF for searching user commands by memo & fee
+    fn user_commands_memo_cf(&self) -> &ColumnFamily;
+
+    /// CF for user commands by involved custom token
+    fn user_commands_token_cf(&self) -> &ColumnFamily;
+
+    /// CF for failed user commands by failure kind
+    fn user_commands_failure_cf(&self) -> &ColumnFamily;
+
+    /// CF for delegation changes by delegator
+    fn user_commands_delegations_cf(&self) -> &ColumnFamily;
+
     /// CF for sorting user commands by sender public key
     fn txn_from_slot_sort_cf(&self) -> &ColumnFamily;
 
@@ -162,6 +204,12 @@ pub trait ColumnFamilyHelpers {
     /// CF for storing a zkapp account's current event num
     fn zkapp_events_pk_num_cf(&self) -> &ColumnFamily;
 
+    /// CF for storing zkapp action state transitions
+    fn zkapp_action_states_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing zkapp events per best chain block
+    fn zkapp_events_height_cf(&self) -> &ColumnFamily;
+
     ////////////////////////////////
     // Internal command store CFs //
     ////////////////////////////////
@@ -206,6 +254,24 @@ pub trait ColumnFamilyHelpers {
     /// CF for sorting zkapp best ledger accounts by balance
     fn zkapp_best_ledger_accounts_balance_sort_cf(&self) -> &ColumnFamily;
 
+    /// CF for storing best ledger account creation statuses
+    fn best_ledger_accounts_status_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing best ledger account balance history
+    fn best_ledger_accounts_balance_history_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing best ledger account nonce history
+    fn best_ledger_accounts_nonce_history_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing custom token transfers by block height
+    fn best_ledger_token_transfers_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing account diffs which overflow or underflow balances
+    fn amount_anomalies_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing block validation errors
+    fn validation_errors_cf(&self) -> &ColumnFamily;
+
     /// CF for storing number of best ledger delegations
     fn best_ledger_accounts_num_delegations_cf(&self) -> &ColumnFamily;
 
@@ -272,9 +338,21 @@ pub trait ColumnFamilyHelpers {
     /// CF for sorting staking ledger accounts by stake (total delegations)
     fn staking_ledger_stake_sort_cf(&self) -> &ColumnFamily;
 
+    /// CF for sorting each delegate's staking ledger delegators by balance
+    fn staking_ledger_delegate_balance_sort_cf(&self) -> &ColumnFamily;
+
     /// CF for per epoch staking account totals
     fn staking_ledger_accounts_count_epoch_cf(&self) -> &ColumnFamily;
 
+    /// CF for per epoch staking ledger file content hashes
+    fn staking_ledger_content_hash_cf(&self) -> &ColumnFamily;
+
+    /// CF for per epoch staking ledger ingestion checkpoints
+    fn staking_ledger_checkpoints_cf(&self) -> &ColumnFamily;
+
+    /// CF for per epoch staking ledger file sizes & modification times
+    fn staking_ledger_files_cf(&self) -> &ColumnFamily;
+
     /////////////////////
     // SNARK store CFs //
     /////////////////////
@@ -335,6 +413,15 @@ pub trait ColumnFamilyHelpers {
     /// CF for sorting SNARKS by prover & global slot
     fn snark_prover_global_slot_sort_cf(&self) -> &ColumnFamily;
 
+    /// CF for storing best chain SNARK jobs by prover & block height
+    fn snark_prover_jobs_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing per epoch SNARK prover best chain job counts & fees
+    fn snark_prover_jobs_epoch_cf(&self) -> &ColumnFamily;
+
+    /// CF for sorting per epoch SNARK provers by best chain job fees
+    fn snark_prover_jobs_epoch_sort_cf(&self) -> &ColumnFamily;
+
     /// CF for sorting SNARK work fees & block height
     fn snark_work_fees_block_height_sort_cf(&self) -> &ColumnFamily;
 
@@ -354,6 +441,9 @@ pub trait ColumnFamilyHelpers {
     /// CF for storing state hash -> usernames
     fn usernames_per_block_cf(&self) -> &ColumnFamily;
 
+    /// CF for storing current username -> pks
+    fn username_name_pk_cf(&self) -> &ColumnFamily;
+
     /////////////////
     // Data counts //
     /////////////////
@@ -370,6 +460,9 @@ pub trait ColumnFamilyHelpers {
     /// CF for per epoch per account supercharged block prodution info
     fn block_production_pk_supercharged_epoch_cf(&self) -> &ColumnFamily;
 
+    /// CF for per epoch per account orphaned block prodution info
+    fn block_production_pk_orphaned_epoch_cf(&self) -> &ColumnFamily;
+
     /// CF for per account total block prodution info
     fn block_production_pk_total_cf(&self) -> &ColumnFamily;
 
@@ -388,6 +481,9 @@ pub trait ColumnFamilyHelpers {
     /// CF for per epoch supercharged block production totals
     fn block_production_supercharged_epoch_cf(&self) -> &ColumnFamily;
 
+    /// CF for per epoch orphaned block production totals
+    fn block_production_orphaned_epoch_cf(&self) -> &ColumnFamily;
+
     /// CF for per block SNARK counts
     fn block_snark_counts_cf(&self) -> &ColumnFamily;
 
@@ -446,4 +542,28 @@ pub trait ColumnFamilyHelpers {
 
     /// CF for storing indexer store events by sequence number
     fn events_cf(&self) -> &ColumnFamily;
+
+    /////////////////////////
+    // Watchlist store CFs //
+    /////////////////////////
+
+    /// CF for storing watched accounts
+    fn watchlist_cf(&self) -> &ColumnFamily;
+
+    /// CF for storing watched account notifications by block height
+    fn watchlist_notifications_cf(&self) -> &ColumnFamily;
+
+    ///////////////////////////////
+    // Ingestion queue store CFs //
+    ///////////////////////////////
+
+    /// CF for journaling parsed blocks by ingestion sequence number
+    fn ingestion_queue_cf(&self) -> &ColumnFamily;
+
+    //////////////////////////
+    // Sub-store version CF //
+    //////////////////////////
+
+    /// CF for storing each sub-store's recorded version
+    fn sub_store_versions_cf(&self) -> &ColumnFamily;
 }
@@ -11,9 +11,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// key: [StateHash] bytes
     /// val: {num block bytes BE u64 bytes}{serde_json block bytes}
     fn blocks_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks")
-            .expect("blocks column family exists")
+        self.namespaced_cf("blocks")
     }
 
     /// CF for storing block state hashes
@@ -21,9 +19,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// key: [StateHash] bytes
     /// val: {num block bytes BE u64 bytes}{serde_json block bytes}
     fn blocks_state_hash_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-state-hash")
-            .expect("blocks-state-hash column family exists")
+        self.namespaced_cf("blocks-state-hash")
     }
 
     /// CF for storing PCB versions
@@ -31,9 +27,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// key: [StateHash] bytes
     /// val: [PcbVersion] serde bytes
     fn block_version_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-version")
-            .expect("blocks-version column family exists")
+        self.namespaced_cf("blocks-version")
     }
 
     /// CF for sorting blocks by global slot
@@ -44,9 +38,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - global_slot: [u32] BE bytes
     /// - state_hash:  [StateHash] bytes
     fn blocks_global_slot_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-global-slot-sort")
-            .expect("blocks-global-slot-sort column family exists")
+        self.namespaced_cf("blocks-global-slot-sort")
     }
 
     /// CF for sorting blocks by block height
@@ -57,93 +49,63 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - block_height: [u32] BE bytes
     /// - state_hash:   [StateHash] bytes
     fn blocks_height_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-height-sort")
-            .expect("blocks-height-sort column family exists")
+        self.namespaced_cf("blocks-height-sort")
     }
 
     fn block_height_to_global_slots_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-height-to-slots")
-            .expect("blocks-height-to-slots column family exists")
+        self.namespaced_cf("blocks-height-to-slots")
     }
 
     fn block_global_slot_to_heights_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-slot-to-heights")
-            .expect("blocks-slot-to-heights column family exists")
+        self.namespaced_cf("blocks-slot-to-heights")
     }
 
     fn block_date_time_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-date-time")
-            .expect("blocks-date-time column family exists")
+        self.namespaced_cf("blocks-date-time")
     }
 
     fn block_parent_hash_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-parent-hash")
-            .expect("blocks-parent-hash column family exists")
+        self.namespaced_cf("blocks-parent-hash")
     }
 
     fn block_height_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-height")
-            .expect("blocks-height column family exists")
+        self.namespaced_cf("blocks-height")
     }
 
     fn block_global_slot_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-global-slot")
-            .expect("blocks-global-slot column family exists")
+        self.namespaced_cf("blocks-global-slot")
     }
 
     fn block_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-epoch")
-            .expect("blocks-epoch column family exists")
+        self.namespaced_cf("blocks-epoch")
     }
 
     fn block_genesis_state_hash_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-genesis-hash")
-            .expect("blocks-genesis-hash column family exists")
+        self.namespaced_cf("blocks-genesis-hash")
     }
 
     fn block_creator_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-creator")
-            .expect("blocks-creator column family exists")
+        self.namespaced_cf("blocks-creator")
     }
 
     fn block_coinbase_receiver_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-coinbase-receiver")
-            .expect("blocks-coinbase-receiver column family exists")
+        self.namespaced_cf("blocks-coinbase-receiver")
     }
 
     fn block_coinbase_height_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("coinbase-receiver-height-sort")
-            .expect("coinbase-receiver-height-sort column family exists")
+        self.namespaced_cf("coinbase-receiver-height-sort")
     }
 
     fn block_coinbase_slot_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("coinbase-receiver-slot-sort")
-            .expect("coinbase-receiver-slot-sort column family exists")
+        self.namespaced_cf("coinbase-receiver-slot-sort")
     }
 
     fn block_creator_height_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-creator-height-sort")
-            .expect("block-creator-height-sort column family exists")
+        self.namespaced_cf("block-creator-height-sort")
     }
 
     fn block_creator_slot_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-creator-slot-sort")
-            .expect("block-creator-slot-sort column family exists")
+        self.namespaced_cf("block-creator-slot-sort")
     }
 
     /// CF for storing blocks at a fixed height:
@@ -151,9 +113,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     ///
     /// - `list of state hashes at height`: sorted from best to worst
     fn blocks_at_height_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-at-length")
-            .expect("blocks-at-length column family exists")
+        self.namespaced_cf("blocks-at-length")
     }
 
     /// CF for storing blocks at a fixed global slot:
@@ -161,15 +121,81 @@ impl ColumnFamilyHelpers for IndexerStore {
     ///
     /// - `list of state hashes at slot`: sorted from best to worst
     fn blocks_at_global_slot_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-at-slot")
-            .expect("blocks-at-slot column family exists")
+        self.namespaced_cf("blocks-at-slot")
     }
 
     fn block_comparison_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-comparison")
-            .expect("blocks-comparison column family exists")
+        self.namespaced_cf("blocks-comparison")
+    }
+
+    fn block_receipt_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("blocks-receipt")
+    }
+
+    fn block_fee_stats_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("blocks-fee-stats")
+    }
+
+    /// CF for storing block account activity
+    /// ```
+    /// - key: state hash
+    /// - value: [BlockActivity] serde bytes
+    fn block_activity_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("blocks-activity")
+    }
+
+    /// CF for storing daily canonical block activity rollups
+    /// ```
+    /// - key: day since the Unix epoch (u32 BE bytes)
+    /// - value: [DailyActivity] serde bytes
+    fn block_daily_activity_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("blocks-daily-activity")
+    }
+
+    /// CF for storing block supply data
+    /// ```
+    /// - key: state hash
+    /// - value: [BlockSupply] serde bytes
+    fn block_supply_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("blocks-supply")
+    }
+
+    /// CF for storing per epoch canonical block supply rollups
+    /// ```
+    /// - key: epoch (u32 BE bytes)
+    /// - value: [EpochSupply] serde bytes
+    fn block_epoch_supply_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("blocks-epoch-supply")
+    }
+
+    /// CF for storing blocks referenced as a parent, but not in the store
+    /// ```
+    /// - key: {height}{state_hash}
+    /// - value: referencing block's state hash bytes
+    /// where
+    /// - height:     [u32] BE bytes
+    /// - state_hash: [StateHash::LEN] bytes
+    fn blocks_missing_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("blocks-missing")
+    }
+
+    /// CF for storing token owners
+    /// ```
+    /// key: [TokenAddress] bytes
+    /// val: {parent}{owner}
+    /// where
+    /// - parent: [TokenAddress::LEN] bytes
+    /// - owner:  [PublicKey::LEN] bytes
+    fn block_token_owner_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("blocks-token-owner")
+    }
+
+    /// CF for storing the provenance of tokens used in blocks
+    /// ```
+    /// key: [TokenAddress] bytes
+    /// val: [TokenProvenance] serde bytes
+    fn block_token_provenance_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("blocks-token-provenance")
     }
 
     /// CF for storing per epoch slots produced
@@ -180,9 +206,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - epoch: [u32] BE bytes
     /// - slot:  [u32] BE bytes
     fn block_epoch_slots_produced_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-epoch-slots-produced")
-            .expect("block-epoch-slots-produced column family exists")
+        self.namespaced_cf("block-epoch-slots-produced")
     }
 
     /// CF for storing per epoch per account slots produced
@@ -196,9 +220,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// ```
     /// Use [epoch_pk_num_key]
     fn block_pk_epoch_slots_produced_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-pk-epoch-slots-produced")
-            .expect("block-pk-epoch-slots-produced column family exists")
+        self.namespaced_cf("block-pk-epoch-slots-produced")
     }
 
     /// CF for storing the number of blocks for a specified public key
@@ -209,9 +231,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - pk:    [PublicKey] bytes
     /// - count: [u32] BE bytes
     fn blocks_pk_count_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-pk-count")
-            .expect("blocks-pk-count column family exists")
+        self.namespaced_cf("blocks-pk-count")
     }
 
     ////////////////////////////
@@ -219,45 +239,31 @@ impl ColumnFamilyHelpers for IndexerStore {
     ////////////////////////////
 
     fn user_commands_pk_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("user-commands-pk")
-            .expect("user-commands-pk column family exists")
+        self.namespaced_cf("user-commands-pk")
     }
 
     fn user_commands_pk_num_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("user-commands-pk-num")
-            .expect("user-commands-pk-num column family exists")
+        self.namespaced_cf("user-commands-pk-num")
     }
 
     fn user_command_state_hashes_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("user-command-state-hashes")
-            .expect("user-command-state-hashes column family exists")
+        self.namespaced_cf("user-command-state-hashes")
     }
 
     fn user_commands_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("user-commands")
-            .expect("user-commands column family exists")
+        self.namespaced_cf("user-commands")
     }
 
     fn user_commands_per_block_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("user-commands-block")
-            .expect("user-commands-block column family exists")
+        self.namespaced_cf("user-commands-block")
     }
 
     fn user_commands_block_order_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("user-commands-block-order")
-            .expect("user-commands-block-order column family exists")
+        self.namespaced_cf("user-commands-block-order")
     }
 
     fn user_commands_num_containing_blocks_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("user-commands-num-blocks")
-            .expect("user-commands-num-blocks column family exists")
+        self.namespaced_cf("user-commands-num-blocks")
     }
 
     /// Key-value pairs
@@ -269,9 +275,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - txn_hash:   [TxnHash::V1_LEN] bytes
     /// - state_hash: [StateHash] bytes
     fn user_commands_height_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("user-commands-height-sort")
-            .expect("user-commands-height-sort column family exists")
+        self.namespaced_cf("user-commands-height-sort")
     }
 
     /// Key-value pairs
@@ -283,9 +287,60 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - txn_hash:   [TxnHash::V1_LEN] bytes
     /// - state_hash: [StateHash] bytes
     fn user_commands_slot_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("user-commands-slot-sort")
-            .expect("user-commands-slot-sort column family exists")
+        self.namespaced_cf("user-commands-slot-sort")
+    }
+
+    /// Key-value pairs (commands with non-empty memos only)
+    /// ```
+    /// - key: {height}{txn_hash}{state_hash}
+    /// - val: {fee}{memo}
+    /// where
+    /// - height:     [u32] BE bytes
+    /// - txn_hash:   [TxnHash::V1_LEN] bytes
+    /// - state_hash: [StateHash] bytes
+    /// - fee:        [u64] BE bytes
+    /// - memo:       decoded memo bytes
+    fn user_commands_memo_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("user-commands-memo")
+    }
+
+    /// Key-value pairs (custom tokens only)
+    /// ```
+    /// - key: {token}{height}{txn_hash}{state_hash}
+    /// - val: b""
+    /// where
+    /// - token:      [TokenAddress::LEN] bytes
+    /// - height:     [u32] BE bytes
+    /// - txn_hash:   [TxnHash::V1_LEN] bytes
+    /// - state_hash: [StateHash] bytes
+    fn user_commands_token_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("user-commands-token")
+    }
+
+    /// Key-value pairs (failed commands only)
+    /// ```
+    /// - key: {kind}\0{height}{txn_hash}{state_hash}
+    /// - val: b""
+    /// where
+    /// - kind:       failure kind bytes
+    /// - height:     [u32] BE bytes
+    /// - txn_hash:   [TxnHash::V1_LEN] bytes
+    /// - state_hash: [StateHash] bytes
+    fn user_commands_failure_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("user-commands-failure")
+    }
+
+    /// Key-value pairs
+    /// ```
+    /// - key: {delegator}{height}{state_hash}{index}
+    /// - val: [DelegationChange] serde bytes
+    /// where
+    /// - delegator:  [PublicKey] bytes
+    /// - height:     [u32] BE bytes
+    /// - state_hash: [StateHash] bytes
+    /// - index:      [u32] BE bytes (command's index in block)
+    fn user_commands_delegations_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("user-commands-delegations")
     }
 
     /// Key-value pairs
@@ -296,9 +351,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - txn_hash:     [TxnHash::V1_LEN] bytes
     /// - block_height: [u32] BE bytes
     fn user_commands_txn_hash_to_block_height_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("user-commands-to-block-height")
-            .expect("user-commands-to-block-height column family exists")
+        self.namespaced_cf("user-commands-to-block-height")
     }
 
     /// Key-value pairs
@@ -309,9 +362,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - txn_hash:    [TxnHash::V1_LEN] bytes
     /// - global_slot: [u32] BE bytes
     fn user_commands_txn_hash_to_global_slot_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("user-commands-to-global-slot")
-            .expect("user-commands-to-global-slot column family exists")
+        self.namespaced_cf("user-commands-to-global-slot")
     }
 
     /// Key-value pairs
@@ -325,9 +376,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - state_hash:  [StateHash] bytes
     /// - amount:      [u64] BE bytes
     fn txn_from_slot_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("txn-from-slot-sort")
-            .expect("txn-from-slot-sort column family exists")
+        self.namespaced_cf("txn-from-slot-sort")
     }
 
     /// Key-value pairs
@@ -342,9 +391,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - state_hash:   [StateHash] bytes
     /// - amount:       [u64] BE bytes
     fn txn_from_height_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("txn-from-height-sort")
-            .expect("txn-from-height-sort column family exists")
+        self.namespaced_cf("txn-from-height-sort")
     }
 
     /// Key-value pairs
@@ -358,9 +405,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - state_hash:  [StateHash] bytes
     /// - amount:      [u64] BE bytes
     fn txn_to_slot_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("txn-to-slot-sort")
-            .expect("txn-to-slot-sort column family exists")
+        self.namespaced_cf("txn-to-slot-sort")
     }
 
     /// Key-value pairs
@@ -374,9 +419,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - state_hash:   [StateHash] bytes
     /// - amount:       [u64] BE bytes
     fn txn_to_height_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("txn-to-height-sort")
-            .expect("txn-to-height-sort column family exists")
+        self.namespaced_cf("txn-to-height-sort")
     }
 
     /////////////////////
@@ -392,9 +435,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - pk:    [PublicKey] bytes
     /// - num:   [u32] BE bytes
     fn zkapp_actions_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("zkapp-actions")
-            .expect("zkapp-actions column family exists")
+        self.namespaced_cf("zkapp-actions")
     }
 
     /// Key-value pairs
@@ -405,9 +446,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - token: [TokenAddress] bytes
     /// - pk:    [PublicKey] bytes
     fn zkapp_actions_pk_num_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("zkapp-actions-pk-num")
-            .expect("zkapp-actions-pk-num column family exists")
+        self.namespaced_cf("zkapp-actions-pk-num")
     }
 
     /// Key-value pairs
@@ -419,9 +458,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - pk:    [PublicKey] bytes
     /// - num:   [u32] BE bytes
     fn zkapp_events_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("zkapp-events")
-            .expect("zkapp-events column family exists")
+        self.namespaced_cf("zkapp-events")
     }
 
     /// Key-value pairs
@@ -432,9 +469,23 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - token: [TokenAddress] bytes
     /// - pk:    [PublicKey] bytes
     fn zkapp_events_pk_num_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("zkapp-events-pk-num")
-            .expect("zkapp-events-pk-num column family exists")
+        self.namespaced_cf("zkapp-events-pk-num")
+    }
+
+    /// Key-value pairs
+    /// ```
+    /// key: [zkapp_action_state_key]
+    /// val: [ActionStateTransition] serde bytes
+    fn zkapp_action_states_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("zkapp-action-states")
+    }
+
+    /// Key-value pairs
+    /// ```
+    /// key: [zkapp_events_height_key]
+    /// val: [ZkappBlockEvents] serde bytes
+    fn zkapp_events_height_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("zkapp-events-height")
     }
 
     ////////////////////////////////
@@ -449,9 +500,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - state_hash: [StateHash] bytes
     /// - index:      [u32] BE bytes
     fn internal_commands_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("internal-commands")
-            .expect("internal-commands column family exists")
+        self.namespaced_cf("internal-commands")
     }
 
     /// Key-value pairs
@@ -459,9 +508,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: [StateHash] bytes
     /// - val: [u32] BE bytes
     fn internal_commands_block_num_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("internal-commands-block-num")
-            .expect("internal-commands-block-num column family exists")
+        self.namespaced_cf("internal-commands-block-num")
     }
 
     /// Key-value pairs
@@ -472,9 +519,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - receiver: [PublicKey] bytes
     /// - index:    [u32] BE bytes
     fn internal_commands_pk_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("internal-commands-pk")
-            .expect("internal-commands-pk column family exists")
+        self.namespaced_cf("internal-commands-pk")
     }
 
     /// Key-value pairs
@@ -482,9 +527,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: [PublicKey] bytes
     /// - val: [u32] BE bytes
     fn internal_commands_pk_num_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("internal-commands-pk-num")
-            .expect("internal-commands-pk-num column family exists")
+        self.namespaced_cf("internal-commands-pk-num")
     }
 
     /// Key-value pairs
@@ -497,9 +540,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - index:        [u32] BE bytes
     /// - kind:         0, 1, or 2
     fn internal_commands_block_height_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("internal-commands-block-height-sort")
-            .expect("internal-commands-block-height-sort column family exists")
+        self.namespaced_cf("internal-commands-block-height-sort")
     }
 
     /// Key-value pairs
@@ -512,9 +553,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - index:       [u32] BE bytes
     /// - kind:        0, 1, or 2
     fn internal_commands_global_slot_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("internal-commands-global-slot-sort")
-            .expect("internal-commands-global-slot-sort column family exists")
+        self.namespaced_cf("internal-commands-global-slot-sort")
     }
 
     /// Key-value pairs
@@ -527,9 +566,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - state_hash:   [StateHash] bytes
     /// - index:        [u32] BE bytes
     fn internal_commands_pk_block_height_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("internal-commands-pk-block-height-sort")
-            .expect("internal-commands-pk-block-height-sort column family exists")
+        self.namespaced_cf("internal-commands-pk-block-height-sort")
     }
 
     /// Key-value pairs
@@ -542,9 +579,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - state_hash:  [StateHash] bytes
     /// - index:       [u32] BE bytes
     fn internal_commands_pk_global_slot_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("internal-commands-pk-global-slot-sort")
-            .expect("internal-commands-pk-global-slot-sort column family exists")
+        self.namespaced_cf("internal-commands-pk-global-slot-sort")
     }
 
     //////////////////////////
@@ -552,15 +587,15 @@ impl ColumnFamilyHelpers for IndexerStore {
     //////////////////////////
 
     fn canonicity_length_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("canonicity-length")
-            .expect("canonicity-length column family exists")
+        self.namespaced_cf("canonicity-length")
     }
 
     fn canonicity_slot_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("canonicity-slot")
-            .expect("canonicity-slot column family exists")
+        self.namespaced_cf("canonicity-slot")
+    }
+
+    fn canonicity_date_time_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("canonicity-date-time")
     }
 
     ///////////////////////////
@@ -575,9 +610,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - token:   [TokenAddress] bytes
     /// - pk:      [PublicKey] bytes
     fn best_ledger_accounts_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("best-ledger-accounts")
-            .expect("best-ledger-accounts column family exists")
+        self.namespaced_cf("best-ledger-accounts")
     }
 
     /// CF for sorting best ledger accounts by balance
@@ -589,9 +622,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - balance: [u64] BE bytes
     /// - pk:      [PublicKey] bytes
     fn best_ledger_accounts_balance_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("best-ledger-account-balance-sort")
-            .expect("best-ledger-account-balance-sort column family exists")
+        self.namespaced_cf("best-ledger-account-balance-sort")
     }
 
     /// CF for storing zkapp best ledger accounts
@@ -602,9 +633,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - token:   [TokenAddress] bytes
     /// - pk:      [PublicKey] bytes
     fn zkapp_best_ledger_accounts_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("zkapp-best-ledger-accounts")
-            .expect("zkapp-best-ledger-accounts column family exists")
+        self.namespaced_cf("zkapp-best-ledger-accounts")
     }
 
     /// CF for sorting zkapp best ledger accounts by balance
@@ -616,9 +645,60 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - balance: [u64] BE bytes
     /// - pk:      [PublicKey] bytes
     fn zkapp_best_ledger_accounts_balance_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("zkapp-best-ledger-account-balance-sort")
-            .expect("zkapp-best-ledger-account-balance-sort column family exists")
+        self.namespaced_cf("zkapp-best-ledger-account-balance-sort")
+    }
+
+    /// CF for storing best ledger account creation statuses
+    /// ```
+    /// key: {token}{pk}
+    /// val: pending byte (1 if created above the canonical root, else 0)
+    /// where
+    /// - token: [TokenAddress] bytes
+    /// - pk:    [PublicKey] bytes
+    fn best_ledger_accounts_status_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("best-ledger-account-status")
+    }
+
+    /// CF for storing best ledger account balance history
+    /// ```
+    /// key: [best_account_balance_history_key]
+    /// val: [BalanceHistoryEntry] serde bytes
+    fn best_ledger_accounts_balance_history_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("best-ledger-account-balance-history")
+    }
+
+    /// CF for storing best ledger account nonce history
+    /// ```
+    /// key: [best_account_balance_history_key]
+    /// val: [NonceHistoryEntry] serde bytes
+    fn best_ledger_accounts_nonce_history_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("best-ledger-account-nonce-history")
+    }
+
+    /// CF for storing custom token transfers, i.e. the balance history of
+    /// non-MINA token accounts by block height
+    /// ```
+    /// key: [token_transfer_key]
+    /// val: [BalanceHistoryEntry] serde bytes
+    fn best_ledger_token_transfers_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("best-ledger-token-transfers")
+    }
+
+    /// CF for storing account diffs which overflow or underflow balances &
+    /// overflowing fee aggregates
+    /// ```
+    /// key: [amount_anomaly_key]
+    /// val: [AmountAnomaly] serde bytes
+    fn amount_anomalies_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("amount-anomalies")
+    }
+
+    /// CF for storing block validation errors
+    /// ```
+    /// key: [validation_error_key]
+    /// val: [ValidationError] serde bytes
+    fn validation_errors_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("validation-errors")
     }
 
     /// CF for storing number of best ledger account delegations
@@ -628,9 +708,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - pk:  [PublicKey] bytes
     /// - num: [u32] BE bytes
     fn best_ledger_accounts_num_delegations_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("best-ledger-account-num-delegations")
-            .expect("best-ledger-account-num-delegations column family exists")
+        self.namespaced_cf("best-ledger-account-num-delegations")
     }
 
     /// CF for storing best ledger account delegations
@@ -640,9 +718,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - pk:  [PublicKey] bytes
     /// - num: [u32] BE bytes
     fn best_ledger_accounts_delegations_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("best-ledger-account-delegations")
-            .expect("best-ledger-account-delegations column family exists")
+        self.namespaced_cf("best-ledger-account-delegations")
     }
 
     /////////////////////////////
@@ -658,9 +734,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - pk:         [PublicKey] bytes
     /// - account:    [Account] serde bytes
     fn staged_ledger_accounts_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staged-ledger-accounts")
-            .expect("staged-ledger-accounts column family exists")
+        self.namespaced_cf("staged-ledger-accounts")
     }
 
     /// CF for sorting staged ledger accounts by balance
@@ -672,9 +746,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - balance:    [u64] BE bytes
     /// - pk:         [PublicKey] bytes
     fn staged_ledger_account_balance_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staged-ledger-account-balance-sort")
-            .expect("staged-ledger-account-balance-sort column family exists")
+        self.namespaced_cf("staged-ledger-account-balance-sort")
     }
 
     /// CF for storing number of staged ledger account delegations
@@ -685,9 +757,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - pk:         [PublicKey] bytes
     /// - num:        [u32] BE bytes
     fn staged_ledger_account_num_delegations_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staged-ledger-account-num-delegations")
-            .expect("staged-ledger-account-num-delegations column family exists")
+        self.namespaced_cf("staged-ledger-account-num-delegations")
     }
 
     /// CF for storing staged ledger account delegations
@@ -699,30 +769,22 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - num:        [u32] BE bytes
     /// - delegate:   [PublicKey] bytes
     fn staged_ledger_account_delegations_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staged-ledger-account-delegations")
-            .expect("staged-ledger-account-delegations column family exists")
+        self.namespaced_cf("staged-ledger-account-delegations")
     }
 
     /// CF for storing staged ledger hash -> block state hash
     fn staged_ledger_hash_to_block_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staged-ledger-hash-to-block")
-            .expect("staged-ledger-hash-to-block column family exists")
+        self.namespaced_cf("staged-ledger-hash-to-block")
     }
 
     /// CF for keeping track of which staged ledgers have been persisted
     fn staged_ledgers_persisted_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staged-ledger-persisted")
-            .expect("staged-ledger-persisted column family exists")
+        self.namespaced_cf("staged-ledger-persisted")
     }
 
     /// CF for tracking when an account was added to the staged ledger
     fn staged_ledger_accounts_min_block_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staged-ledger-accounts-min-block")
-            .expect("staged-ledger-accounts-min-block column family exists")
+        self.namespaced_cf("staged-ledger-accounts-min-block")
     }
 
     /// CF for storing block ledger diffs
@@ -730,15 +792,11 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// key: [StateHash] bytes
     /// val: [LedgerDiff] serde bytes
     fn block_ledger_diff_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-ledger-diff")
-            .expect("blocks-ledger-diff column family exists")
+        self.namespaced_cf("blocks-ledger-diff")
     }
 
     fn block_staged_ledger_hash_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("blocks-staged-ledger-hash")
-            .expect("blocks-staged-ledger-hash column family exists")
+        self.namespaced_cf("blocks-staged-ledger-hash")
     }
 
     //////////////////////////////
@@ -750,9 +808,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: [staking_ledger_account_key]
     /// - val: [Account] serde bytes
     fn staking_ledger_accounts_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staking-ledger-accounts")
-            .expect("staking-ledger-accounts column family exists")
+        self.namespaced_cf("staking-ledger-accounts")
     }
 
     /// CF for storing aggregated staking delegations
@@ -760,9 +816,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: [staking_ledger_account_key]
     /// - val: aggregated epoch delegations serde bytes
     fn staking_delegations_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staking-ledger-delegations")
-            .expect("staking-ledger-delegations column family exists")
+        self.namespaced_cf("staking-ledger-delegations")
     }
 
     /// CF for storing aggregated staking delegations
@@ -770,9 +824,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: [staking_ledger_epoch_key]
     /// - val: b""
     fn staking_ledger_persisted_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staking-ledger-persisted")
-            .expect("staking-ledger-persisted column family exists")
+        self.namespaced_cf("staking-ledger-persisted")
     }
 
     /// CF for storing staking ledger hashes
@@ -780,9 +832,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: [staking_ledger_epoch_key_prefix]
     /// - val: [LedgerHash] bytes
     fn staking_ledger_epoch_to_hash_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staking-ledger-epoch-to-hash")
-            .expect("staking-ledger-epoch-to-hash column family exists")
+        self.namespaced_cf("staking-ledger-epoch-to-hash")
     }
 
     /// CF for storing staking ledger epochs
@@ -790,9 +840,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: [LedgerHash] bytes
     /// - val: epoch ([u32] BE bytes)
     fn staking_ledger_hash_to_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staking-ledger-hash-to-epoch")
-            .expect("staking-ledger-hash-to-epoch column family exists")
+        self.namespaced_cf("staking-ledger-hash-to-epoch")
     }
 
     /// CF for storing staking ledger genesis state hashes
@@ -800,9 +848,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: [LedgerHash] bytes
     /// - val: [StateHash] bytes
     fn staking_ledger_genesis_hash_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staking-ledger-genesis-hash")
-            .expect("staking-ledger-genesis-hash column family exists")
+        self.namespaced_cf("staking-ledger-genesis-hash")
     }
 
     /// CF for storing staking ledger total currencies
@@ -810,9 +856,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: [LedgerHash] bytes
     /// - val: [u64] BE bytes
     fn staking_ledger_total_currency_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staking-ledger-total-currency")
-            .expect("staking-ledger-total-currency column family exists")
+        self.namespaced_cf("staking-ledger-total-currency")
     }
 
     /// CF for sorting staking ledger accounts by balance
@@ -820,9 +864,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: [staking_ledger_sort_key]
     /// - val: b""
     fn staking_ledger_balance_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staking-ledger-balance-sort")
-            .expect("staking-ledger-balance-sort column family exists")
+        self.namespaced_cf("staking-ledger-balance-sort")
     }
 
     /// CF for sorting staking ledger accounts by stake (i.e. total delegations)
@@ -830,9 +872,15 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: [staking_ledger_sort_key]
     /// - val: b""
     fn staking_ledger_stake_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staking-ledger-stake-sort")
-            .expect("staking-ledger-stake-sort column family exists")
+        self.namespaced_cf("staking-ledger-stake-sort")
+    }
+
+    /// CF for sorting each delegate's staking ledger delegators by balance
+    /// ```
+    /// - key: [staking_ledger_delegate_sort_key]
+    /// - val: b""
+    fn staking_ledger_delegate_balance_sort_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("staking-ledger-delegate-balance-sort")
     }
 
     /// CF for storing per epoch total number of staking ledger accounts
@@ -840,9 +888,32 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: epoch ([u32] BE bytes)
     /// - value: number of staking ledger accounts in epoch ([u32] BE bytes)
     fn staking_ledger_accounts_count_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("staking-ledger-accounts-count-epoch")
-            .expect("staking-ledger-accounts-count-epoch column family exists")
+        self.namespaced_cf("staking-ledger-accounts-count-epoch")
+    }
+
+    /// CF for storing per epoch staking ledger file content hashes
+    /// ```
+    /// - key: [staking_ledger_epoch_key_prefix]
+    /// - value: hex-encoded SHA-256 digest of the staking ledger file
+    fn staking_ledger_content_hash_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("staking-ledger-content-hash")
+    }
+
+    /// CF for storing incomplete staking ledger ingestions' checkpoints
+    /// ```
+    /// - key: [staking_ledger_epoch_key_prefix]
+    /// - value: [StakingLedgerCheckpoint] (serialized with [serde_json::to_vec])
+    fn staking_ledger_checkpoints_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("staking-ledger-checkpoints")
+    }
+
+    /// CF for storing the size & modification time of each epoch's staking
+    /// ledger file
+    /// ```
+    /// - key: [staking_ledger_epoch_key_prefix]
+    /// - value: [StakingLedgerFile] (serialized with [serde_json::to_vec])
+    fn staking_ledger_files_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("staking-ledger-files")
     }
 
     /////////////////////
@@ -858,9 +929,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - index: [u32] BE bytes
     /// - snark: [SnarkWorkSummary] serde bytes
     fn snarks_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snarks")
-            .expect("snarks column family exists")
+        self.namespaced_cf("snarks")
     }
 
     /// CF for storing SNARKs by prover
@@ -872,9 +941,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - index:  [u32] BE bytes
     /// - snark:  [SnarkWorkSummaryWithStateHash] serde bytes
     fn snarks_prover_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snarks-prover")
-            .expect("snarks-prover column family exists")
+        self.namespaced_cf("snarks-prover")
     }
 
     /// CF for storing SNARK total fees by prover
@@ -885,9 +952,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - prover: [PublicKey] bytes
     /// - fees:   [u64] BE bytes
     fn snark_prover_fees_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-fees")
-            .expect("snark-prover-fees column family exists")
+        self.namespaced_cf("snark-prover-fees")
     }
 
     /// CF for storing per epoch SNARK total fees by prover
@@ -899,9 +964,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - prover: [PublicKey] bytes
     /// - fees:   [u64] BE bytes
     fn snark_prover_fees_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-fees-epoch")
-            .expect("snark-prover-fees-epoch column family exists")
+        self.namespaced_cf("snark-prover-fees-epoch")
     }
 
     /// CF for storing historical SNARK all-time fee updates
@@ -912,9 +975,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - prover: [PublicKey] bytes
     /// - height: [u32] BE bytes
     fn snark_prover_fees_historical_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-fees-historical")
-            .expect("snark-prover-fees-historical column family exists")
+        self.namespaced_cf("snark-prover-fees-historical")
     }
 
     /// CF for storing historical SNARK epoch fee updates
@@ -925,9 +986,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - prover: [PublicKey] bytes
     /// - height: [u32] BE bytes
     fn snark_prover_fees_epoch_historical_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-fees-epoch-historical")
-            .expect("snark-prover-fees-epoch-historical column family exists")
+        self.namespaced_cf("snark-prover-fees-epoch-historical")
     }
 
     /// CF for sorting SNARK provers by total fees
@@ -938,9 +997,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - fees:   [u64] BE bytes
     /// - prover: [PublicKey] bytes
     fn snark_prover_total_fees_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-total-fees-sort")
-            .expect("snark-prover-total-fees-sort column family exists")
+        self.namespaced_cf("snark-prover-total-fees-sort")
     }
 
     /// CF for sorting per epoch SNARK provers by total fees
@@ -952,9 +1009,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - fees:   [u64] BE bytes
     /// - prover: [PublicKey] bytes
     fn snark_prover_total_fees_epoch_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-total-fees-epoch-sort")
-            .expect("snark-prover-total-fees-epoch-sort column family exists")
+        self.namespaced_cf("snark-prover-total-fees-epoch-sort")
     }
 
     /// CF for storing SNARK prover max fees
@@ -965,9 +1020,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - prover: [PublicKey] bytes
     /// - fee:    [u64] BE bytes
     fn snark_prover_max_fee_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-max-fee")
-            .expect("snark-prover-max-fee column family exists")
+        self.namespaced_cf("snark-prover-max-fee")
     }
 
     /// CF for storing per epoch SNARK prover max fees
@@ -979,9 +1032,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - prover: [PublicKey] bytes
     /// - fee:    [u64] BE bytes
     fn snark_prover_max_fee_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-max-fee-epoch")
-            .expect("snark-prover-max-fee-epoch column family exists")
+        self.namespaced_cf("snark-prover-max-fee-epoch")
     }
 
     /// CF for sorting SNARK provers by max fee
@@ -994,9 +1045,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// ```
     /// Use [snark_fee_sort_key]
     fn snark_prover_max_fee_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-max-fee-sort")
-            .expect("snark-prover-max-fee-sort column family exists")
+        self.namespaced_cf("snark-prover-max-fee-sort")
     }
 
     /// CF for sorting per epoch SNARK provers by max fee
@@ -1010,9 +1059,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// ```
     /// Use [snark_fee_epoch_sort_key]
     fn snark_prover_max_fee_epoch_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-max-fee-epoch-sort")
-            .expect("snark-prover-max-fee-epoch-sort column family exists")
+        self.namespaced_cf("snark-prover-max-fee-epoch-sort")
     }
 
     /// CF for storing SNARK prover min fees
@@ -1023,9 +1070,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - prover: [PublicKey] bytes
     /// - fee:    [u64] BE bytes
     fn snark_prover_min_fee_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-min-fee")
-            .expect("snark-prover-min-fee column family exists")
+        self.namespaced_cf("snark-prover-min-fee")
     }
 
     /// CF for storing per epoch SNARK prover min fees
@@ -1037,9 +1082,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - prover: [PublicKey] bytes
     /// - fee:    [u64] BE bytes
     fn snark_prover_min_fee_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-min-fee-epoch")
-            .expect("snark-prover-min-fee-epoch column family exists")
+        self.namespaced_cf("snark-prover-min-fee-epoch")
     }
 
     /// CF for sorting SNARK provers by min fee
@@ -1052,9 +1095,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// ```
     /// Use [snark_fee_sort_key]
     fn snark_prover_min_fee_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-min-fee-sort")
-            .expect("snark-prover-min-fee-sort column family exists")
+        self.namespaced_cf("snark-prover-min-fee-sort")
     }
 
     /// CF for sorting per epoch SNARK provers by min fee
@@ -1068,9 +1109,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// ```
     /// Use [snark_fee_epoch_sort_key]
     fn snark_prover_min_fee_epoch_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-min-fee-epoch-sort")
-            .expect("snark-prover-min-fee-epoch-sort column family exists")
+        self.namespaced_cf("snark-prover-min-fee-epoch-sort")
     }
 
     /// CF for storing/sorting SNARKs by prover & block height
@@ -1083,9 +1122,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - index:        [u32] BE bytes
     /// - snark:        [SnarkWorkSummary] serde bytes
     fn snark_prover_block_height_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-block-height-sort")
-            .expect("snark-prover-block-height-sort column family exists")
+        self.namespaced_cf("snark-prover-block-height-sort")
     }
 
     /// CF for storing/sorting SNARKs by prover & global slot
@@ -1098,9 +1135,51 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - index:       [u32] BE bytes
     /// - snark:       [SnarkWorkSummary] serde bytes
     fn snark_prover_global_slot_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-prover-global-slot-sort")
-            .expect("snark-prover-global-slot-sort column family exists")
+        self.namespaced_cf("snark-prover-global-slot-sort")
+    }
+
+    /// CF for storing best chain SNARK jobs by prover & block height
+    /// ```
+    /// key: {prover}{block_height}{state_hash}{index}
+    /// val: fee
+    /// where
+    /// - prover:       [PublicKey] bytes
+    /// - block_height: [u32] BE bytes
+    /// - state_hash:   [StateHash] bytes
+    /// - index:        [u32] BE bytes
+    /// - fee:          [u64] BE bytes
+    /// ```
+    /// Use [snark_prover_job_key]
+    fn snark_prover_jobs_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("snark-prover-jobs")
+    }
+
+    /// CF for storing per epoch SNARK prover best chain job counts & fees
+    /// ```
+    /// key: {epoch}{prover}
+    /// val: {num_jobs}{total_fees}
+    /// where
+    /// - epoch:      [u32] BE bytes
+    /// - prover:     [PublicKey] bytes
+    /// - num_jobs:   [u32] BE bytes
+    /// - total_fees: [u64] BE bytes
+    /// ```
+    /// Use [snark_epoch_key]
+    fn snark_prover_jobs_epoch_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("snark-prover-jobs-epoch")
+    }
+
+    /// CF for sorting per epoch SNARK provers by best chain job fees
+    /// ```
+    /// {epoch}{total_fees}{prover}
+    /// where
+    /// - epoch:      [u32] BE bytes
+    /// - total_fees: [u64] BE bytes
+    /// - prover:     [PublicKey] bytes
+    /// ```
+    /// Use [snark_fee_epoch_sort_key]
+    fn snark_prover_jobs_epoch_sort_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("snark-prover-jobs-epoch-sort")
     }
 
     /// CF for sorting snark fees by block height
@@ -1115,9 +1194,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// ```
     /// Use [snark_fee_sort_key]
     fn snark_work_fees_block_height_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-work-fees-block-height-sort")
-            .expect("snark-work-fees-block-height-sort column family exists")
+        self.namespaced_cf("snark-work-fees-block-height-sort")
     }
 
     /// CF for sorting snark fees by global slot
@@ -1132,9 +1209,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// ```
     /// Use [snark_fee_sort_key]
     fn snark_work_fees_global_slot_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snark-work-fees-global-slot-sort")
-            .expect("snark-work-fees-global-slot-sort column family exists")
+        self.namespaced_cf("snark-work-fees-global-slot-sort")
     }
 
     ////////////////////////
@@ -1142,22 +1217,24 @@ impl ColumnFamilyHelpers for IndexerStore {
     ////////////////////////
 
     fn username_pk_num_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("username-pk-num")
-            .expect("username-pk-num column family exists")
+        self.namespaced_cf("username-pk-num")
     }
 
     fn username_pk_index_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("username-pk-index")
-            .expect("username-pk-index column family exists")
+        self.namespaced_cf("username-pk-index")
     }
 
     /// CF for storing state hash -> usernames
     fn usernames_per_block_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("usernames-per-block")
-            .expect("usernames-per-block column family exists")
+        self.namespaced_cf("usernames-per-block")
+    }
+
+    /// CF for storing current username -> pks
+    /// ```
+    /// - key: {username len u8}{username}{pk}
+    /// - value: empty byte
+    fn username_name_pk_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("username-name-pk")
     }
 
     /////////////////////
@@ -1165,9 +1242,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /////////////////////
 
     fn chain_id_to_network_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("chain-id-to-network")
-            .expect("chain-id-to-network column family exists")
+        self.namespaced_cf("chain-id-to-network")
     }
 
     /////////////////////
@@ -1175,9 +1250,47 @@ impl ColumnFamilyHelpers for IndexerStore {
     /////////////////////
 
     fn events_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("events")
-            .expect("events column family exists")
+        self.namespaced_cf("events")
+    }
+
+    /////////////////////////
+    // Watchlist store CFs //
+    /////////////////////////
+
+    /// CF for storing watched accounts
+    /// ```
+    /// - key: [PublicKey] bytes
+    /// - val: [WatchedAccount] serde bytes
+    fn watchlist_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("watchlist")
+    }
+
+    /// CF for storing watched account notifications by block height
+    /// ```
+    /// - key: [watch_notification_key]
+    /// - val: [WatchNotification] serde bytes
+    fn watchlist_notifications_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("watchlist-notifications")
+    }
+
+    ///////////////////////////////
+    // Ingestion queue store CFs //
+    ///////////////////////////////
+
+    fn ingestion_queue_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("ingestion-queue")
+    }
+
+    //////////////////////////
+    // Sub-store version CF //
+    //////////////////////////
+
+    /// CF for storing each sub-store's recorded version
+    /// ```
+    /// - key: sub-store name
+    /// - value: version ([u32] BE bytes)
+    fn sub_store_versions_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("sub-store-versions")
     }
 
     ////////////////////
@@ -1189,9 +1302,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: {epoch BE bytes}{pk}
     /// - value: number of blocks produced by pk in epoch
     fn block_production_pk_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-production-pk-epoch")
-            .expect("block-production-pk-epoch column family exists")
+        self.namespaced_cf("block-production-pk-epoch")
     }
 
     /// CF for storing per epoch per account canonical block prodution info
@@ -1199,9 +1310,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: {epoch BE bytes}{pk}
     /// - value: number of canonical blocks produced by pk in epoch
     fn block_production_pk_canonical_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-production-pk-canonical-epoch")
-            .expect("block-production-pk-canonical-epoch column family exists")
+        self.namespaced_cf("block-production-pk-canonical-epoch")
     }
 
     /// CF for sorting per epoch per account canonical block prodution info
@@ -1211,9 +1320,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// ```
     /// Use [epoch_block_num_key]
     fn block_production_pk_canonical_epoch_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-production-pk-canonical-epoch-sort")
-            .expect("block-production-pk-canonical-epoch-sort column family exists")
+        self.namespaced_cf("block-production-pk-canonical-epoch-sort")
     }
 
     /// CF for storing per epoch per account supercharged block prodution info
@@ -1221,9 +1328,15 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: {epoch BE bytes}{pk}
     /// - value: number of supercharged blocks produced by pk in epoch
     fn block_production_pk_supercharged_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-production-pk-supercharged-epoch")
-            .expect("block-production-pk-supercharged-epoch column family exists")
+        self.namespaced_cf("block-production-pk-supercharged-epoch")
+    }
+
+    /// CF for storing per epoch per account orphaned block prodution info
+    /// ```
+    /// - key: {epoch BE bytes}{pk}
+    /// - value: number of orphaned blocks produced by pk in epoch
+    fn block_production_pk_orphaned_epoch_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("block-production-pk-orphaned-epoch")
     }
 
     /// CF for storing per account total block prodution info
@@ -1231,9 +1344,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: pk
     /// - value: total number of blocks produced by pk
     fn block_production_pk_total_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-production-pk-total")
-            .expect("block-production-pk-total column family exists")
+        self.namespaced_cf("block-production-pk-total")
     }
 
     /// CF for storing per account total canonical block prodution info
@@ -1241,9 +1352,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: pk
     /// - value: total number of canonical blocks produced by pk
     fn block_production_pk_canonical_total_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-production-pk-canonical-total")
-            .expect("block-production-pk-canonical-total column family exists")
+        self.namespaced_cf("block-production-pk-canonical-total")
     }
 
     /// CF for storing per account total supercharged block prodution info
@@ -1251,9 +1360,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: pk
     /// - value: total number of supercharged blocks produced by pk
     fn block_production_pk_supercharged_total_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-production-pk-supercharged-total")
-            .expect("block-production-pk-supercharged-total column family exists")
+        self.namespaced_cf("block-production-pk-supercharged-total")
     }
 
     /// CF for storing per epoch block production totals
@@ -1261,9 +1368,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: epoch
     /// - value: number of blocks produced in epoch
     fn block_production_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-production-epoch")
-            .expect("block-production-epoch column family exists")
+        self.namespaced_cf("block-production-epoch")
     }
 
     /// CF for storing per epoch canonical block production totals
@@ -1271,9 +1376,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: epoch
     /// - value: number of canonical blocks produced in epoch
     fn block_production_canonical_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-production-canonical-epoch")
-            .expect("block-production-canonical-epoch column family exists")
+        self.namespaced_cf("block-production-canonical-epoch")
     }
 
     /// CF for storing per epoch supercharged block production totals
@@ -1281,18 +1384,22 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: epoch
     /// - value: number of supercharged blocks produced in epoch
     fn block_production_supercharged_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-production-supercharged-epoch")
-            .expect("block-production-supercharged-epoch column family exists")
+        self.namespaced_cf("block-production-supercharged-epoch")
+    }
+
+    /// CF for storing per epoch orphaned block production totals
+    /// ```
+    /// - key: epoch
+    /// - value: number of orphaned blocks produced in epoch
+    fn block_production_orphaned_epoch_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf("block-production-orphaned-epoch")
     }
 
     /// CF for storing per block SNARK counts
     /// - key: state hash
     /// - value: number of SNARKs in block
     fn block_snark_counts_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-snark-counts")
-            .expect("block-snark-counts column family exists")
+        self.namespaced_cf("block-snark-counts")
     }
 
     /// CF for stoing per block user command counts
@@ -1300,9 +1407,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: state hash
     /// - value: number of user commands in block
     fn block_user_command_counts_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-user-command-counts")
-            .expect("block-user-command-counts column family exists")
+        self.namespaced_cf("block-user-command-counts")
     }
 
     /// CF for storing per block internal command counts
@@ -1310,9 +1415,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: state hash
     /// - value: number of internal commands in block
     fn block_internal_command_counts_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-internal-command-counts")
-            .expect("block-internal-command-counts column family exists")
+        self.namespaced_cf("block-internal-command-counts")
     }
 
     /// CF for storing per epoch slots produced counts
@@ -1320,9 +1423,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// key: epoch ([u32] BE bytes)
     /// val: number of slots produced in epoch ([u32] BE bytes)
     fn block_epoch_slots_produced_count_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-epoch-slots-produced-count")
-            .expect("block-epoch-slots-produced-count column family exists")
+        self.namespaced_cf("block-epoch-slots-produced-count")
     }
 
     /// CF for storing per epoch per account slots produced counts
@@ -1336,9 +1437,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// ```
     /// Use [epoch_pk_key]
     fn block_pk_epoch_slots_produced_count_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-pk-epoch-slots-produced-count")
-            .expect("block-pk-epoch-slots-produced-count column family exists")
+        self.namespaced_cf("block-pk-epoch-slots-produced-count")
     }
 
     /// CF for storing per epoch per account slots produced counts
@@ -1351,9 +1450,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// ```
     /// Use [epoch_block_num_key]
     fn block_pk_epoch_slots_produced_count_sort_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("block-pk-epoch-slots-produced-count-sort")
-            .expect("block-pk-epoch-slots-produced-count-sort column family exists")
+        self.namespaced_cf("block-pk-epoch-slots-produced-count-sort")
     }
 
     /// CF for storing per epoch per account user commands
@@ -1361,9 +1458,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: {epoch BE bytes}{pk}
     /// - value: number of pk user commands in epoch
     fn user_commands_pk_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("user-commands-pk-epoch")
-            .expect("user-commands-pk-epoch column family exists")
+        self.namespaced_cf("user-commands-pk-epoch")
     }
 
     /// CF for storing per account total user commands
@@ -1371,9 +1466,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: pk
     /// - value: total number of pk user commands
     fn user_commands_pk_total_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("user-commands-pk-total")
-            .expect("user-commands-pk-total column family exists")
+        self.namespaced_cf("user-commands-pk-total")
     }
 
     /// CF for per epoch total user commands
@@ -1381,9 +1474,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: epoch
     /// - value: number of user commands in epoch
     fn user_commands_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("user-commands-epoch")
-            .expect("user-commands-epoch column family exists")
+        self.namespaced_cf("user-commands-epoch")
     }
 
     /// CF for storing per epoch per account internal commands
@@ -1391,9 +1482,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: {epoch BE bytes}{pk}
     /// - value: number of pk internal commands in epoch
     fn internal_commands_pk_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("internal-commands-pk-epoch")
-            .expect("internal-commands-pk-epoch column family exists")
+        self.namespaced_cf("internal-commands-pk-epoch")
     }
 
     /// CF for storing per account total internal commands
@@ -1401,9 +1490,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: pk
     /// - value: total number of pk internal commands
     fn internal_commands_pk_total_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("internal-commands-pk-total")
-            .expect("internal-commands-pk-total column family exists")
+        self.namespaced_cf("internal-commands-pk-total")
     }
 
     /// CF for storing per epoch total internal commands
@@ -1411,9 +1498,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: epoch
     /// - value: number of internal commands in epoch
     fn internal_commands_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("internal-commands-epoch")
-            .expect("internal-commands-epoch column family exists")
+        self.namespaced_cf("internal-commands-epoch")
     }
 
     /// CF for storing per epoch per account SNARK counts
@@ -1421,9 +1506,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: {epoch BE bytes}{pk}
     /// - value: number of pk SNARKs in epoch
     fn snarks_pk_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snarks-pk-epoch")
-            .expect("snarks-pk-epoch column family exists")
+        self.namespaced_cf("snarks-pk-epoch")
     }
 
     /// CF for storing per account SNARK counts
@@ -1431,9 +1514,7 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: pk
     /// - value: total number of pk SNARKs
     fn snarks_pk_total_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snarks-pk-total")
-            .expect("snarks-pk-total column family exists")
+        self.namespaced_cf("snarks-pk-total")
     }
 
     /// CF for storing per epoch SNARK counts
@@ -1441,8 +1522,6 @@ impl ColumnFamilyHelpers for IndexerStore {
     /// - key: epoch
     /// - value: number of SNARKs in epoch
     fn snarks_epoch_cf(&self) -> &ColumnFamily {
-        self.database
-            .cf_handle("snarks-epoch")
-            .expect("snarks-epoch column family exists")
+        self.namespaced_cf("snarks-epoch")
     }
 }
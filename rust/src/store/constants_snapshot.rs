@@ -0,0 +1,296 @@
+//! Snapshot of the compile-time constants the store's data is derived with
+//!
+//! The snapshot is persisted when the store is created & compared with the
+//! binary's constants each time the store is opened, see
+//! [ConstantsSnapshotStore::check_constants_snapshot]
+
+use crate::constants::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub trait ConstantsSnapshotStore {
+    /// Persist the constants snapshot
+    fn set_constants_snapshot(&self, snapshot: &ConstantsSnapshot) -> anyhow::Result<()>;
+
+    /// Get the persisted constants snapshot
+    fn get_constants_snapshot(&self) -> anyhow::Result<Option<ConstantsSnapshot>>;
+
+    /// Compare the persisted snapshot with `current`
+    ///
+    /// - identical: proceed
+    /// - changes which don't affect derived data: warn
+    /// - changes which affect derived data: error, unless `accept_change`, in
+    ///   which case an event listing the derived data which may need
+    ///   reindexing is recorded
+    ///
+    /// Stores without a persisted snapshot persist `current`
+    fn check_constants_snapshot(
+        &self,
+        current: &ConstantsSnapshot,
+        accept_change: bool,
+    ) -> anyhow::Result<ConstantsChange>;
+}
+
+/// Derived data which can be reindexed, named after the owning sub-store
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Reindexable {
+    Blocks,
+    Canonicity,
+    UserCommands,
+    InternalCommands,
+    Snarks,
+    StagedLedgers,
+    BestLedger,
+    StakingLedgers,
+}
+
+/// Values of the binary's constants by name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConstantsSnapshot {
+    pub values: BTreeMap<String, String>,
+}
+
+/// Differences between a persisted & the current constants snapshot
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConstantsChange {
+    /// Changed constants which affect derived data
+    pub derivation_affecting: Vec<String>,
+
+    /// Changed constants which don't affect derived data
+    pub other: Vec<String>,
+
+    /// Derived data which may need reindexing
+    pub reindex: Vec<Reindexable>,
+}
+
+/// Constants which affect derived data & the data derived with them
+const DERIVATION_AFFECTING: &[(&str, &[Reindexable])] = {
+    use Reindexable::*;
+    &[
+        ("MINA_SCALE", &[StagedLedgers, BestLedger, StakingLedgers]),
+        (
+            "MAINNET_ACCOUNT_CREATION_FEE",
+            &[UserCommands, StagedLedgers, BestLedger],
+        ),
+        (
+            "MAINNET_COINBASE_REWARD",
+            &[
+                Blocks,
+                Canonicity,
+                InternalCommands,
+                StagedLedgers,
+                BestLedger,
+            ],
+        ),
+        ("MAINNET_EPOCH_SLOT_COUNT", &[Blocks, Snarks]),
+        ("MAINNET_BLOCK_SLOT_TIME_MILLIS", &[Blocks]),
+        ("MAINNET_GENESIS_TIMESTAMP", &[Blocks]),
+        (
+            "MAINNET_GENESIS_HASH",
+            &[Blocks, Canonicity, StagedLedgers, BestLedger],
+        ),
+        (
+            "MAINNET_GENESIS_PREV_STATE_HASH",
+            &[Blocks, Canonicity, StagedLedgers],
+        ),
+        ("MAINNET_GENESIS_LEDGER_HASH", &[StagedLedgers]),
+        ("MAINNET_TRANSITION_FRONTIER_K", &[Canonicity]),
+        (
+            "HARDFORK_GENESIS_BLOCKCHAIN_LENGTH",
+            &[Blocks, Canonicity, StagedLedgers],
+        ),
+        ("HARDFORK_GENESIS_GLOBAL_SLOT", &[Blocks]),
+        ("HARDFORK_GENESIS_TIMESTAMP", &[Blocks]),
+        (
+            "HARDFORK_GENESIS_HASH",
+            &[Blocks, Canonicity, StagedLedgers, BestLedger],
+        ),
+        (
+            "HARDFORK_GENESIS_PREV_STATE_HASH",
+            &[Blocks, Canonicity, StagedLedgers],
+        ),
+        ("HARDFORK_GENESIS_LEDGER_HASH", &[StagedLedgers]),
+    ]
+};
+
+macro_rules! snapshot_values {
+    ($($name:ident),* $(,)?) => {
+        BTreeMap::from([$((stringify!($name).to_string(), $name.to_string())),*])
+    };
+}
+
+impl Reindexable {
+    /// Name of the sub-store owning the derived data
+    pub fn sub_store(&self) -> &'static str {
+        match self {
+            Self::Blocks => "blocks",
+            Self::Canonicity => "canonicity",
+            Self::UserCommands => "user-commands",
+            Self::InternalCommands => "internal-commands",
+            Self::Snarks => "snarks",
+            Self::StagedLedgers => "staged-ledger",
+            Self::BestLedger => "best-ledger",
+            Self::StakingLedgers => "staking-ledger",
+        }
+    }
+}
+
+impl ConstantsSnapshot {
+    /// Snapshot of this binary's constants
+    pub fn current() -> Self {
+        Self {
+            values: snapshot_values![
+                // derivation affecting
+                MINA_SCALE,
+                MAINNET_ACCOUNT_CREATION_FEE,
+                MAINNET_COINBASE_REWARD,
+                MAINNET_EPOCH_SLOT_COUNT,
+                MAINNET_BLOCK_SLOT_TIME_MILLIS,
+                MAINNET_GENESIS_TIMESTAMP,
+                MAINNET_GENESIS_HASH,
+                MAINNET_GENESIS_PREV_STATE_HASH,
+                MAINNET_GENESIS_LEDGER_HASH,
+                MAINNET_TRANSITION_FRONTIER_K,
+                HARDFORK_GENESIS_BLOCKCHAIN_LENGTH,
+                HARDFORK_GENESIS_GLOBAL_SLOT,
+                HARDFORK_GENESIS_TIMESTAMP,
+                HARDFORK_GENESIS_HASH,
+                HARDFORK_GENESIS_PREV_STATE_HASH,
+                HARDFORK_GENESIS_LEDGER_HASH,
+                // other
+                MAINNET_CANONICAL_THRESHOLD,
+                CANONICAL_UPDATE_THRESHOLD,
+                PRUNE_INTERVAL_DEFAULT,
+                LEDGER_CADENCE,
+                BLOCK_REPORTING_FREQ_NUM,
+                INGESTION_QUEUE_DEPTH,
+                INGESTION_QUEUE_BYTES,
+                BLOCKS_DIR_MAX_DEPTH,
+            ],
+        }
+    }
+
+    /// Changes from the `stored` snapshot to `self`
+    ///
+    /// Constants missing from `stored` were added to the snapshot after it
+    /// was persisted & are assumed unchanged
+    pub fn changes_from(&self, stored: &Self) -> ConstantsChange {
+        let mut change = ConstantsChange::default();
+        for (name, value) in self.values.iter() {
+            if stored
+                .values
+                .get(name)
+                .map_or(true, |stored| stored == value)
+            {
+                continue;
+            }
+
+            match DERIVATION_AFFECTING
+                .iter()
+                .find(|(affecting, _)| affecting == name)
+            {
+                Some((_, reindex)) => {
+                    change.derivation_affecting.push(name.clone());
+                    change.reindex.extend_from_slice(reindex);
+                }
+                None => change.other.push(name.clone()),
+            }
+        }
+
+        change.reindex.sort();
+        change.reindex.dedup();
+        change
+    }
+}
+
+impl ConstantsChange {
+    pub fn is_empty(&self) -> bool {
+        self.derivation_affecting.is_empty() && self.other.is_empty()
+    }
+
+    pub fn affects_derived_data(&self) -> bool {
+        !self.derivation_affecting.is_empty()
+    }
+}
+
+impl std::fmt::Display for Reindexable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.sub_store())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::IndexerStore;
+
+    #[test]
+    fn derivation_affecting_constants_are_snapshot() {
+        let current = ConstantsSnapshot::current();
+        for (name, reindex) in DERIVATION_AFFECTING {
+            assert!(current.values.contains_key(*name), "{name} missing");
+            assert!(!reindex.is_empty());
+        }
+    }
+
+    #[test]
+    fn reindexable_sub_stores_exist() {
+        use Reindexable::*;
+        for reindexable in [
+            Blocks,
+            Canonicity,
+            UserCommands,
+            InternalCommands,
+            Snarks,
+            StagedLedgers,
+            BestLedger,
+            StakingLedgers,
+        ] {
+            assert!(IndexerStore::SUB_STORES
+                .iter()
+                .any(|sub_store| sub_store.name == reindexable.sub_store()));
+        }
+    }
+
+    #[test]
+    fn changes() {
+        let current = ConstantsSnapshot::current();
+        assert!(current.changes_from(&current).is_empty());
+
+        // constants added to the snapshot later are assumed unchanged
+        let mut stored = current.clone();
+        stored.values.remove("MAINNET_COINBASE_REWARD");
+        assert!(current.changes_from(&stored).is_empty());
+
+        // non-derivation affecting change
+        let mut stored = current.clone();
+        stored.values.insert("LEDGER_CADENCE".into(), "1".into());
+
+        let change = current.changes_from(&stored);
+        assert!(!change.affects_derived_data());
+        assert_eq!(change.other, vec!["LEDGER_CADENCE".to_string()]);
+        assert!(change.reindex.is_empty());
+
+        // derivation affecting changes
+        stored
+            .values
+            .insert("MAINNET_EPOCH_SLOT_COUNT".into(), "1".into());
+        stored
+            .values
+            .insert("MAINNET_GENESIS_TIMESTAMP".into(), "1".into());
+
+        let change = current.changes_from(&stored);
+        assert!(change.affects_derived_data());
+        assert_eq!(
+            change.derivation_affecting,
+            vec![
+                "MAINNET_EPOCH_SLOT_COUNT".to_string(),
+                "MAINNET_GENESIS_TIMESTAMP".to_string()
+            ]
+        );
+        assert_eq!(
+            change.reindex,
+            vec![Reindexable::Blocks, Reindexable::Snarks]
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use super::{
+    constants_snapshot::{ConstantsChange, ConstantsSnapshot, ConstantsSnapshotStore},
+    fixed_keys::FixedKeys,
+    IndexerStore,
+};
+use crate::event::{
+    db::{DbConstantsEvent, DbEvent},
+    store::EventStore,
+    IndexerEvent,
+};
+use anyhow::bail;
+use log::{trace, warn};
+
+impl ConstantsSnapshotStore for IndexerStore {
+    fn set_constants_snapshot(&self, snapshot: &ConstantsSnapshot) -> anyhow::Result<()> {
+        trace!("Setting constants snapshot");
+        self.database.put_cf(
+            self.default_cf(),
+            Self::INDEXER_CONSTANTS_KEY,
+            serde_json::to_vec(snapshot)?,
+        )?;
+        Ok(())
+    }
+
+    fn get_constants_snapshot(&self) -> anyhow::Result<Option<ConstantsSnapshot>> {
+        trace!("Getting constants snapshot");
+        Ok(self
+            .get_cf(self.default_cf(), Self::INDEXER_CONSTANTS_KEY)?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+
+    fn check_constants_snapshot(
+        &self,
+        current: &ConstantsSnapshot,
+        accept_change: bool,
+    ) -> anyhow::Result<ConstantsChange> {
+        let stored = match self.get_constants_snapshot()? {
+            Some(stored) => stored,
+            None => {
+                self.set_constants_snapshot(current)?;
+                return Ok(ConstantsChange::default());
+            }
+        };
+
+        let change = current.changes_from(&stored);
+        if change.is_empty() {
+            return Ok(change);
+        }
+
+        if !change.other.is_empty() {
+            warn!(
+                "Constants changed since the store was created: {}",
+                change.other.join(", ")
+            );
+        }
+
+        if change.affects_derived_data() {
+            let reindex = change
+                .reindex
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            if !accept_change {
+                bail!(
+                    "Derivation-affecting constants changed since the store was created: {}. Affected derived data: {reindex}. Restart with --accept-constants-change to accept the change",
+                    change.derivation_affecting.join(", "),
+                )
+            }
+
+            warn!(
+                "Accepted derivation-affecting constants change: {}. Affected derived data: {reindex}",
+                change.derivation_affecting.join(", "),
+            );
+            self.add_event(&IndexerEvent::Db(DbEvent::Constants(
+                DbConstantsEvent::ConstantsChanged {
+                    changed: change.derivation_affecting.clone(),
+                    reindex: change.reindex.clone(),
+                },
+            )))?;
+        }
+
+        self.set_constants_snapshot(current)?;
+        Ok(change)
+    }
+}
@@ -0,0 +1,360 @@
+//! Deterministic dataset export for diffing indexer versions
+//!
+//! All user-visible derived data is dumped as NDJSON tables. Rows are
+//! sorted & object keys are serialized in sorted order, so exports of the
+//! same blocks by two indexer versions are byte-identical unless their
+//! derived data differs. Nothing depending on ingestion (e.g. receipt times)
+//! is exported.
+
+use super::IndexerStore;
+use crate::{
+    base::{public_key::PublicKey, state_hash::StateHash},
+    block::store::BlockStore,
+    canonicity::store::CanonicityStore,
+    command::{
+        internal::store::InternalCommandStore, signed::SignedCommandWithData,
+        store::UserCommandStore, AccountUpdateStatus, CommandStatusData,
+    },
+    ledger::store::best::BestLedgerStore,
+};
+use anyhow::{bail, Context};
+use log::info;
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+/// Exported tables, in export order
+pub const DATASET_TABLES: [&str; 5] = [
+    "canonical_chain",
+    "user_commands",
+    "internal_commands",
+    "ledger_accounts",
+    "epoch_aggregates",
+];
+
+#[derive(Debug, Serialize)]
+struct CanonicalBlockRow {
+    blockchain_length: u32,
+    state_hash: StateHash,
+    parent_hash: Option<StateHash>,
+    global_slot_since_genesis: Option<u32>,
+    epoch: Option<u32>,
+    date_time: Option<i64>,
+    creator: Option<PublicKey>,
+    coinbase_receiver: Option<PublicKey>,
+}
+
+#[derive(Debug, Serialize)]
+struct UserCommandRow {
+    blockchain_length: u32,
+    state_hash: StateHash,
+    index_in_block: u32,
+    txn_hash: String,
+    kind: String,
+    sender: PublicKey,
+    receivers: Vec<PublicKey>,
+    amount: u64,
+    fee: u64,
+    nonce: u32,
+    status: String,
+    failures: Vec<String>,
+    account_update_statuses: Vec<AccountUpdateStatus>,
+}
+
+#[derive(Debug, Serialize)]
+struct EpochAggregateRow {
+    epoch: u32,
+    num_blocks: u32,
+    num_canonical_blocks: u32,
+    num_supercharged_blocks: u32,
+    num_orphaned_blocks: u32,
+    num_slots_produced: u32,
+    num_user_commands: u32,
+    num_internal_commands: u32,
+}
+
+/// Number of rows of each exported table
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DatasetExport {
+    pub num_rows: Vec<(&'static str, usize)>,
+}
+
+/// Differences of a table between two datasets
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TableComparison {
+    pub table: String,
+    pub num_rows_a: usize,
+    pub num_rows_b: usize,
+    pub num_differing_rows: usize,
+
+    /// First differing rows, as `(line number, row in A, row in B)`
+    pub first_differences: Vec<(usize, Option<String>, Option<String>)>,
+}
+
+/// Per table differences between two datasets
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DatasetComparison {
+    pub tables: Vec<TableComparison>,
+}
+
+impl IndexerStore {
+    /// Export the dataset as sorted NDJSON tables to `dir`
+    pub fn export_dataset(&self, dir: &Path) -> anyhow::Result<DatasetExport> {
+        std::fs::create_dir_all(dir)?;
+
+        let canonical_hashes = self.canonical_hashes()?;
+        let tables = [
+            self.canonical_chain_rows(&canonical_hashes)?,
+            self.user_command_rows(&canonical_hashes)?,
+            self.internal_command_rows(&canonical_hashes)?,
+            self.ledger_account_rows()?,
+            self.epoch_aggregate_rows()?,
+        ];
+
+        let mut export = DatasetExport::default();
+        for (table, rows) in DATASET_TABLES.into_iter().zip(tables) {
+            let path = dir.join(format!("{table}.ndjson"));
+            let mut writer = BufWriter::new(File::create(&path)?);
+
+            for row in rows.iter() {
+                writeln!(writer, "{row}")?;
+            }
+
+            writer.flush()?;
+            info!("Exported {} {table} rows to {path:#?}", rows.len());
+            export.num_rows.push((table, rows.len()));
+        }
+
+        Ok(export)
+    }
+
+    /// Canonical state hashes, ascending by height
+    fn canonical_hashes(&self) -> anyhow::Result<Vec<(u32, StateHash)>> {
+        let mut canonical_hashes = vec![];
+        for height in 1..=self.get_best_block_height()?.unwrap_or_default() {
+            if let Some(state_hash) = self.get_canonical_hash_at_height(height)? {
+                canonical_hashes.push((height, state_hash));
+            }
+        }
+
+        Ok(canonical_hashes)
+    }
+
+    fn canonical_chain_rows(
+        &self,
+        canonical_hashes: &[(u32, StateHash)],
+    ) -> anyhow::Result<Vec<String>> {
+        let mut rows = vec![];
+        for (height, state_hash) in canonical_hashes {
+            rows.push(to_row(&CanonicalBlockRow {
+                blockchain_length: *height,
+                state_hash: state_hash.clone(),
+                parent_hash: self.get_block_parent_hash(state_hash)?,
+                global_slot_since_genesis: self.get_block_global_slot(state_hash)?,
+                epoch: self.get_block_epoch(state_hash)?,
+                date_time: self.get_block_date_time(state_hash)?,
+                creator: self.get_block_creator(state_hash)?,
+                coinbase_receiver: self.get_coinbase_receiver(state_hash)?,
+            })?);
+        }
+
+        Ok(rows)
+    }
+
+    fn user_command_rows(
+        &self,
+        canonical_hashes: &[(u32, StateHash)],
+    ) -> anyhow::Result<Vec<String>> {
+        let mut rows = vec![];
+        for (_, state_hash) in canonical_hashes {
+            let (block, _) = self
+                .get_block(state_hash)?
+                .with_context(|| format!("canonical block missing from store {state_hash}"))?;
+
+            let mut commands = SignedCommandWithData::from_precomputed(&block);
+            commands.sort_by_key(|cmd| cmd.index_in_block);
+
+            for cmd in commands {
+                let (status, failures) = match &cmd.status {
+                    CommandStatusData::Applied { .. } => ("Applied", vec![]),
+                    CommandStatusData::Failed(failures, _) => (
+                        "Failed",
+                        failures.iter().map(|failure| failure.to_string()).collect(),
+                    ),
+                };
+
+                rows.push(to_row(&UserCommandRow {
+                    blockchain_length: cmd.blockchain_length,
+                    state_hash: cmd.state_hash,
+                    index_in_block: cmd.index_in_block,
+                    txn_hash: cmd.tx_hash.to_string(),
+                    kind: cmd.command.kind().to_string(),
+                    sender: cmd.command.source_pk(),
+                    receivers: cmd.command.receiver_pk(),
+                    amount: cmd.command.amount(),
+                    fee: cmd.command.fee(),
+                    nonce: cmd.nonce.0,
+                    status: status.to_string(),
+                    failures,
+                    account_update_statuses: cmd.account_update_statuses,
+                })?);
+            }
+        }
+
+        Ok(rows)
+    }
+
+    fn internal_command_rows(
+        &self,
+        canonical_hashes: &[(u32, StateHash)],
+    ) -> anyhow::Result<Vec<String>> {
+        let mut rows = vec![];
+        for (_, state_hash) in canonical_hashes {
+            let mut block_rows = self
+                .get_internal_commands(state_hash)?
+                .iter()
+                .map(to_row)
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            block_rows.sort();
+            rows.append(&mut block_rows);
+        }
+
+        Ok(rows)
+    }
+
+    fn ledger_account_rows(&self) -> anyhow::Result<Vec<String>> {
+        let Some(ledger) = self.get_best_ledger(false)? else {
+            return Ok(vec![]);
+        };
+
+        let mut accounts: Vec<_> = ledger
+            .tokens
+            .iter()
+            .flat_map(|(token, token_ledger)| {
+                token_ledger
+                    .accounts
+                    .iter()
+                    .map(move |(pk, account)| ((token.clone(), pk.clone()), account))
+            })
+            .collect();
+        accounts.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        accounts
+            .into_iter()
+            .map(|(_, account)| to_row(account))
+            .collect()
+    }
+
+    fn epoch_aggregate_rows(&self) -> anyhow::Result<Vec<String>> {
+        if self.get_best_block_height()?.is_none() {
+            return Ok(vec![]);
+        }
+
+        let mut rows = vec![];
+        for epoch in 0..=self.get_current_epoch()? {
+            rows.push(to_row(&EpochAggregateRow {
+                epoch,
+                num_blocks: self.get_block_production_epoch_count(Some(epoch))?,
+                num_canonical_blocks: self
+                    .get_block_production_canonical_epoch_count(Some(epoch))?,
+                num_supercharged_blocks: self
+                    .get_block_production_supercharged_epoch_count(Some(epoch))?,
+                num_orphaned_blocks: self.get_block_production_orphaned_epoch_count(Some(epoch))?,
+                num_slots_produced: self.get_epoch_slots_produced_count(Some(epoch))?,
+                num_user_commands: self.get_user_commands_epoch_count(Some(epoch))?,
+                num_internal_commands: self.get_internal_commands_epoch_count(Some(epoch))?,
+            })?);
+        }
+
+        Ok(rows)
+    }
+}
+
+/// Compare the datasets exported to `dir_a` & `dir_b`, keeping up to
+/// `max_differences` differing rows per table
+///
+/// Rows are compared by line number since the tables are sorted
+pub fn compare_datasets(
+    dir_a: &Path,
+    dir_b: &Path,
+    max_differences: usize,
+) -> anyhow::Result<DatasetComparison> {
+    let mut comparison = DatasetComparison::default();
+    for table in DATASET_TABLES {
+        let rows_a = read_rows(&dir_a.join(format!("{table}.ndjson")))?;
+        let rows_b = read_rows(&dir_b.join(format!("{table}.ndjson")))?;
+
+        let mut table_comparison = TableComparison {
+            table: table.to_string(),
+            num_rows_a: rows_a.len(),
+            num_rows_b: rows_b.len(),
+            ..Default::default()
+        };
+
+        for idx in 0..rows_a.len().max(rows_b.len()) {
+            let (a, b) = (rows_a.get(idx), rows_b.get(idx));
+            if a == b {
+                continue;
+            }
+
+            table_comparison.num_differing_rows += 1;
+            if table_comparison.first_differences.len() < max_differences {
+                table_comparison
+                    .first_differences
+                    .push((idx + 1, a.cloned(), b.cloned()));
+            }
+        }
+
+        comparison.tables.push(table_comparison);
+    }
+
+    Ok(comparison)
+}
+
+impl DatasetComparison {
+    /// Whether the datasets are identical
+    pub fn is_identical(&self) -> bool {
+        self.tables
+            .iter()
+            .all(|table| table.num_differing_rows == 0)
+    }
+}
+
+/// Serialize the row with sorted object keys
+fn to_row<T: Serialize>(row: &T) -> anyhow::Result<String> {
+    Ok(serde_json::to_value(row)?.to_string())
+}
+
+fn read_rows(path: &Path) -> anyhow::Result<Vec<String>> {
+    if !path.exists() {
+        bail!("Dataset table missing {path:#?}")
+    }
+
+    Ok(BufReader::new(File::open(path)?)
+        .lines()
+        .collect::<Result<_, _>>()?)
+}
+
+impl std::fmt::Display for DatasetComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for table in self.tables.iter() {
+            writeln!(
+                f,
+                "{}: {} rows in A, {} rows in B, {} differing",
+                table.table, table.num_rows_a, table.num_rows_b, table.num_differing_rows
+            )?;
+
+            for (line, a, b) in table.first_differences.iter() {
+                writeln!(f, "  line {line}")?;
+                writeln!(f, "    A: {}", a.as_deref().unwrap_or("<missing>"))?;
+                writeln!(f, "    B: {}", b.as_deref().unwrap_or("<missing>"))?;
+            }
+        }
+
+        Ok(())
+    }
+}
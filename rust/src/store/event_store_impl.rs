@@ -1,7 +1,8 @@
-use super::{column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys};
+use super::{column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, sub_store::SubStore};
 use crate::{
+    base::state_hash::StateHash,
     event::{
-        db::{DbBlockEvent, DbEvent},
+        db::{DbBlockEvent, DbEvent, ReorgEvent},
         store::EventStore,
         witness_tree::WitnessTreeEvent,
         IndexerEvent,
@@ -10,49 +11,114 @@ use crate::{
     utility::store::common::from_be_bytes,
 };
 use log::trace;
+use speedb::{Direction, IteratorMode, WriteBatch};
+use std::collections::HashSet;
+
+/// Add the corresponding CF helpers to [ColumnFamilyHelpers] & bump the
+/// sub-store version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "events",
+    version: 1,
+    column_families: &["events"],
+    dependencies: &[],
+    migrate: SubStore::no_migration,
+};
 
 impl EventStore for IndexerStore {
     fn add_event(&self, event: &IndexerEvent) -> anyhow::Result<u32> {
-        let seq_num = self.get_next_seq_num()?;
-        trace!("Adding event {seq_num}: {event:?}");
-
-        if matches!(
-            event,
-            IndexerEvent::WitnessTree(WitnessTreeEvent::UpdateBestTip { .. })
-        ) {
-            return Ok(seq_num);
-        }
+        let mut batch = WriteBatch::default();
+        let next_seq_num = self.add_events_batch(std::slice::from_ref(event), &mut batch)?;
 
-        // add prefixed event to db
-        let mut value = match event {
-            IndexerEvent::Db(DbEvent::Block(
-                DbBlockEvent::NewBestTip {
-                    blockchain_length, ..
+        self.database.write(batch)?;
+        Ok(next_seq_num)
+    }
+
+    fn add_events_batch(
+        &self,
+        events: &[IndexerEvent],
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<u32> {
+        let mut seq_num = self.get_next_seq_num()?;
+
+        for event in events {
+            trace!("Adding event {seq_num}: {event:?}");
+
+            if matches!(
+                event,
+                IndexerEvent::WitnessTree(WitnessTreeEvent::UpdateBestTip { .. })
+            ) {
+                continue;
+            }
+
+            // add prefixed event to db
+            let mut value = match event {
+                IndexerEvent::Db(DbEvent::Block(
+                    DbBlockEvent::NewBestTip {
+                        blockchain_length, ..
+                    }
+                    | DbBlockEvent::NewBlock {
+                        blockchain_length, ..
+                    },
+                )) => (*blockchain_length).to_be_bytes().to_vec(),
+                IndexerEvent::Db(DbEvent::Block(DbBlockEvent::Reorg(reorg))) => {
+                    reorg.new_tip_height.to_be_bytes().to_vec()
                 }
-                | DbBlockEvent::NewBlock {
-                    blockchain_length, ..
-                },
-            )) => (*blockchain_length).to_be_bytes().to_vec(),
-            _ => 0u32.to_be_bytes().to_vec(),
-        };
-        value.push(event.kind());
-        value.append(&mut serde_json::to_vec(&event)?);
-        self.database
-            .put_cf(self.events_cf(), seq_num.to_be_bytes(), value)?;
+                _ => 0u32.to_be_bytes().to_vec(),
+            };
+            value.push(event.kind());
+            value.append(&mut serde_json::to_vec(&event)?);
+            batch.put_cf(self.events_cf(), seq_num.to_be_bytes(), value);
+            seq_num += 1;
+        }
 
         // increment event sequence number
-        let next_seq_num = seq_num + 1;
-        self.database
-            .put(Self::NEXT_EVENT_SEQ_NUM_KEY, next_seq_num.to_be_bytes())?;
+        batch.put_cf(
+            self.default_cf(),
+            Self::NEXT_EVENT_SEQ_NUM_KEY,
+            seq_num.to_be_bytes(),
+        );
 
         // return next event sequence number
-        Ok(next_seq_num)
+        Ok(seq_num)
+    }
+
+    fn remove_block_events_batch(
+        &self,
+        state_hashes: &HashSet<StateHash>,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<u32> {
+        trace!("Removing events of {} blocks", state_hashes.len());
+        let mut num_removed = 0;
+        if state_hashes.is_empty() {
+            return Ok(num_removed);
+        }
+
+        for (key, value) in self.event_log_iterator(IteratorMode::Start).flatten() {
+            // avoid deserializing other events
+            if value.len() < 5
+                || (value[4] != IndexerEvent::NEW_BLOCK_KIND
+                    && value[4] != IndexerEvent::NEW_BEST_TIP_KIND)
+            {
+                continue;
+            }
+
+            if let Ok(IndexerEvent::Db(DbEvent::Block(
+                DbBlockEvent::NewBlock { state_hash, .. }
+                | DbBlockEvent::NewBestTip { state_hash, .. },
+            ))) = serde_json::from_slice(&value[5..])
+            {
+                if state_hashes.contains(&state_hash) {
+                    batch.delete_cf(self.events_cf(), key);
+                    num_removed += 1;
+                }
+            }
+        }
+        Ok(num_removed)
     }
 
     fn get_event(&self, seq_num: u32) -> anyhow::Result<Option<IndexerEvent>> {
         trace!("Getting event {seq_num}");
         Ok(self
-            .database
             .get_pinned_cf(self.events_cf(), seq_num.to_be_bytes())?
             .and_then(|bytes| serde_json::from_slice(&bytes[5..]).ok()))
     }
@@ -60,11 +126,19 @@ impl EventStore for IndexerStore {
     fn get_next_seq_num(&self) -> anyhow::Result<u32> {
         trace!("Getting next event sequence number");
         Ok(self
-            .database
-            .get(Self::NEXT_EVENT_SEQ_NUM_KEY)?
+            .get_cf(self.default_cf(), Self::NEXT_EVENT_SEQ_NUM_KEY)?
             .map_or(0, from_be_bytes))
     }
 
+    fn compact_event_log(&self, from: u32, to: u32) {
+        trace!("Compacting event log {from}..{to}");
+        self.database.compact_range_cf(
+            self.events_cf(),
+            Some(from.to_be_bytes()),
+            Some(to.to_be_bytes()),
+        );
+    }
+
     fn get_event_log(&self) -> anyhow::Result<Vec<IndexerEvent>> {
         trace!("Getting event log");
         let mut events = vec![];
@@ -76,9 +150,42 @@ impl EventStore for IndexerStore {
         Ok(events)
     }
 
+    fn get_reorgs(&self, limit: usize) -> anyhow::Result<Vec<ReorgEvent>> {
+        trace!("Getting {limit} most recent reorgs");
+        let mut reorgs = vec![];
+        if limit == 0 {
+            return Ok(reorgs);
+        }
+
+        let next_seq_num = self.get_next_seq_num()?;
+        for (_, value) in self
+            .event_log_iterator(IteratorMode::From(
+                &next_seq_num.to_be_bytes(),
+                Direction::Reverse,
+            ))
+            .flatten()
+        {
+            // avoid deserializing other events
+            if value.len() < 5 || value[4] != IndexerEvent::REORG_KIND {
+                continue;
+            }
+
+            if let Ok(IndexerEvent::Db(DbEvent::Block(DbBlockEvent::Reorg(reorg)))) =
+                serde_json::from_slice(&value[5..])
+            {
+                reorgs.push(reorg);
+
+                if reorgs.len() >= limit {
+                    break;
+                }
+            }
+        }
+        Ok(reorgs)
+    }
+
     /// Key: sequence number ([u32] BE bytes)
     /// Value: event (serialized with [serde_json::to_vec])
     fn event_log_iterator(&self, mode: speedb::IteratorMode) -> speedb::DBIterator<'_> {
-        self.database.iterator_cf(self.events_cf(), mode)
+        self.iterator_cf(self.events_cf(), mode)
     }
 }
@@ -0,0 +1,502 @@
+//! Canonical chain export to CSV or Parquet files
+//!
+//! Canonical blocks, user commands, internal commands & account diffs of a
+//! height range are written to one file per table, with the same columns as
+//! the SQL mirror's tables (see `sql_mirror/schema.sql`). Parquet files
+//! require the parquet_export feature.
+
+use super::IndexerStore;
+use crate::{
+    base::state_hash::StateHash,
+    block::store::BlockStore,
+    canonicity::store::CanonicityStore,
+    command::{internal::DbInternalCommandWithData, signed::SignedCommandWithData},
+    ledger::diff::account::AccountDiff,
+};
+use anyhow::{bail, Context};
+use log::info;
+use std::{fs::File, path::Path};
+
+/// Exported tables, in export order
+pub const EXPORT_TABLES: [&str; 4] = [
+    "blocks",
+    "user_commands",
+    "internal_commands",
+    "account_diffs",
+];
+
+const BLOCK_COLUMNS: &[(&str, ColumnType)] = &[
+    ("height", ColumnType::U32),
+    ("state_hash", ColumnType::Str),
+    ("parent_hash", ColumnType::Str),
+    ("global_slot", ColumnType::U32),
+    ("epoch", ColumnType::U32),
+    ("creator", ColumnType::Str),
+    ("coinbase_receiver", ColumnType::Str),
+    ("timestamp", ColumnType::U64),
+];
+
+const USER_COMMAND_COLUMNS: &[(&str, ColumnType)] = &[
+    ("height", ColumnType::U32),
+    ("index_in_block", ColumnType::U32),
+    ("txn_hash", ColumnType::Str),
+    ("kind", ColumnType::Str),
+    ("sender", ColumnType::Str),
+    ("receiver", ColumnType::Str),
+    ("amount", ColumnType::U64),
+    ("fee", ColumnType::U64),
+    ("nonce", ColumnType::U32),
+    ("memo", ColumnType::Str),
+    ("applied", ColumnType::Bool),
+];
+
+const INTERNAL_COMMAND_COLUMNS: &[(&str, ColumnType)] = &[
+    ("height", ColumnType::U32),
+    ("index_in_block", ColumnType::U32),
+    ("kind", ColumnType::Str),
+    ("receiver", ColumnType::Str),
+    ("amount", ColumnType::U64),
+];
+
+const ACCOUNT_DIFF_COLUMNS: &[(&str, ColumnType)] = &[
+    ("height", ColumnType::U32),
+    ("index_in_block", ColumnType::U32),
+    ("kind", ColumnType::Str),
+    ("public_key", ColumnType::Str),
+    ("token", ColumnType::Str),
+    ("amount", ColumnType::I64),
+];
+
+/// Export file format
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Parquet,
+}
+
+/// Number of rows exported per table
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ChainExport {
+    pub start_height: u32,
+    pub end_height: u32,
+    pub num_rows: Vec<(&'static str, usize)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    U32,
+    U64,
+    I64,
+    Bool,
+    Str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ExportValue {
+    U32(u32),
+    U64(u64),
+    I64(i64),
+    Bool(bool),
+    Str(String),
+}
+
+type Row = Vec<ExportValue>;
+
+trait TableWriter {
+    fn write_row(&mut self, row: Row) -> anyhow::Result<()>;
+
+    /// Flushes the remaining rows & completes the file
+    fn finish(self: Box<Self>) -> anyhow::Result<()>;
+}
+
+impl IndexerStore {
+    /// Export the canonical chain from `start_height` through `end_height`
+    /// (defaults to the best block height) to `dir`
+    pub fn export_canonical_chain(
+        &self,
+        dir: &Path,
+        format: ExportFormat,
+        start_height: u32,
+        end_height: Option<u32>,
+    ) -> anyhow::Result<ChainExport> {
+        let end_height = match end_height {
+            Some(end_height) => end_height,
+            None => self.get_best_block_height()?.unwrap_or_default(),
+        };
+        if start_height > end_height {
+            bail!("Invalid export height range {start_height}..={end_height}")
+        }
+
+        std::fs::create_dir_all(dir)?;
+        let columns = [
+            BLOCK_COLUMNS,
+            USER_COMMAND_COLUMNS,
+            INTERNAL_COMMAND_COLUMNS,
+            ACCOUNT_DIFF_COLUMNS,
+        ];
+
+        let mut writers = vec![];
+        for (table, columns) in EXPORT_TABLES.into_iter().zip(columns) {
+            writers.push(table_writer(dir, table, columns, format)?);
+        }
+
+        let mut num_rows = [0; EXPORT_TABLES.len()];
+        for height in start_height..=end_height {
+            let Some(state_hash) = self.get_canonical_hash_at_height(height)? else {
+                continue;
+            };
+
+            for (idx, rows) in self
+                .canonical_block_rows(&state_hash)?
+                .into_iter()
+                .enumerate()
+            {
+                num_rows[idx] += rows.len();
+                for row in rows {
+                    writers[idx].write_row(row)?;
+                }
+            }
+        }
+
+        for writer in writers {
+            writer.finish()?;
+        }
+
+        info!("Exported canonical chain {start_height}..={end_height} to {dir:#?}");
+        Ok(ChainExport {
+            start_height,
+            end_height,
+            num_rows: EXPORT_TABLES.into_iter().zip(num_rows).collect(),
+        })
+    }
+
+    /// Rows of each export table for the canonical block
+    fn canonical_block_rows(&self, state_hash: &StateHash) -> anyhow::Result<[Vec<Row>; 4]> {
+        use ExportValue::*;
+
+        let (block, _) = self
+            .get_block(state_hash)?
+            .with_context(|| format!("canonical block missing from store {state_hash}"))?;
+        let height = block.blockchain_length();
+
+        let blocks = vec![vec![
+            U32(height),
+            Str(state_hash.to_string()),
+            Str(block.previous_state_hash().0),
+            U32(block.global_slot_since_genesis()),
+            U32(block.epoch_count()),
+            Str(block.block_creator().0),
+            Str(block.coinbase_receiver().0),
+            U64(block.timestamp()),
+        ]];
+
+        // user commands
+        let mut commands = SignedCommandWithData::from_precomputed(&block);
+        commands.sort_by_key(|cmd| cmd.index_in_block);
+
+        let user_commands = commands
+            .into_iter()
+            .map(|cmd| {
+                let command = &cmd.command;
+                vec![
+                    U32(height),
+                    U32(cmd.index_in_block),
+                    Str(cmd.tx_hash.to_string()),
+                    Str(command.kind().to_string()),
+                    Str(command.source_pk().0),
+                    Str(command
+                        .receiver_pk()
+                        .first()
+                        .map(|pk| pk.0.to_owned())
+                        .unwrap_or_default()),
+                    U64(command.amount()),
+                    U64(command.fee()),
+                    U32(cmd.nonce.0),
+                    Str(command.memo()),
+                    Bool(cmd.status.is_applied()),
+                ]
+            })
+            .collect();
+
+        // internal commands
+        let internal_commands = DbInternalCommandWithData::from_precomputed(&block)
+            .into_iter()
+            .map(|cmd| {
+                let (DbInternalCommandWithData::Coinbase {
+                    receiver,
+                    amount,
+                    kind,
+                    index_in_block,
+                    ..
+                }
+                | DbInternalCommandWithData::FeeTransfer {
+                    receiver,
+                    amount,
+                    kind,
+                    index_in_block,
+                    ..
+                }) = cmd;
+
+                vec![
+                    U32(height),
+                    U32(index_in_block),
+                    Str(kind.to_string()),
+                    Str(receiver.0),
+                    U64(amount),
+                ]
+            })
+            .collect();
+
+        // account diffs
+        let diff = self
+            .get_block_ledger_diff(state_hash)?
+            .with_context(|| format!("Block ledger diff missing from store {state_hash}"))?;
+
+        let account_diffs = diff
+            .account_diffs
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(index, diff)| {
+                vec![
+                    U32(height),
+                    U32(index as u32),
+                    Str(diff.kind().to_string()),
+                    Str(diff.public_key().0),
+                    Str(diff.token_address().0),
+                    I64(balance_change(diff)),
+                ]
+            })
+            .collect();
+
+        Ok([blocks, user_commands, internal_commands, account_diffs])
+    }
+}
+
+/// Signed balance change of payments, fee transfers & coinbases
+fn balance_change(diff: &AccountDiff) -> i64 {
+    use AccountDiff::*;
+
+    match diff {
+        Payment(_) | FeeTransfer(_) | FeeTransferViaCoinbase(_) | Coinbase(_) => diff.amount(),
+        _ => 0,
+    }
+}
+
+fn table_writer(
+    dir: &Path,
+    table: &str,
+    columns: &'static [(&'static str, ColumnType)],
+    format: ExportFormat,
+) -> anyhow::Result<Box<dyn TableWriter>> {
+    let path = dir.join(format!("{table}.{}", format.extension()));
+    let file = File::create(&path).with_context(|| format!("Failed to create {path:#?}"))?;
+
+    match format {
+        ExportFormat::Csv => Ok(Box::new(CsvTableWriter::new(file, columns)?)),
+        ExportFormat::Parquet => parquet_writer::table_writer(file, columns),
+    }
+}
+
+/////////
+// CSV //
+/////////
+
+struct CsvTableWriter {
+    writer: csv::Writer<File>,
+}
+
+impl CsvTableWriter {
+    fn new(file: File, columns: &[(&str, ColumnType)]) -> anyhow::Result<Self> {
+        let mut writer = csv::WriterBuilder::new().from_writer(file);
+        writer.write_record(columns.iter().map(|(name, _)| name))?;
+
+        Ok(Self { writer })
+    }
+}
+
+impl TableWriter for CsvTableWriter {
+    fn write_row(&mut self, row: Row) -> anyhow::Result<()> {
+        Ok(self
+            .writer
+            .write_record(row.iter().map(ExportValue::to_string))?)
+    }
+
+    fn finish(mut self: Box<Self>) -> anyhow::Result<()> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+/////////////
+// Parquet //
+/////////////
+
+#[cfg(feature = "parquet_export")]
+mod parquet_writer {
+    use super::{ColumnType, ExportValue, Row, TableWriter};
+    use arrow::{
+        array::{ArrayRef, BooleanArray, Int64Array, StringArray, UInt32Array, UInt64Array},
+        datatypes::{DataType, Field, Schema, SchemaRef},
+        record_batch::RecordBatch,
+    };
+    use parquet::arrow::ArrowWriter;
+    use std::{fs::File, sync::Arc};
+
+    /// Number of rows per record batch
+    const BATCH_SIZE: usize = 8192;
+
+    struct ParquetTableWriter {
+        schema: SchemaRef,
+        columns: &'static [(&'static str, ColumnType)],
+        writer: ArrowWriter<File>,
+        rows: Vec<Row>,
+    }
+
+    pub(super) fn table_writer(
+        file: File,
+        columns: &'static [(&'static str, ColumnType)],
+    ) -> anyhow::Result<Box<dyn TableWriter>> {
+        let fields: Vec<_> = columns
+            .iter()
+            .map(|(name, ty)| Field::new(*name, ty.data_type(), false))
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+
+        Ok(Box::new(ParquetTableWriter {
+            writer: ArrowWriter::try_new(file, schema.clone(), None)?,
+            schema,
+            columns,
+            rows: Vec::with_capacity(BATCH_SIZE),
+        }))
+    }
+
+    impl ColumnType {
+        fn data_type(&self) -> DataType {
+            match self {
+                Self::U32 => DataType::UInt32,
+                Self::U64 => DataType::UInt64,
+                Self::I64 => DataType::Int64,
+                Self::Bool => DataType::Boolean,
+                Self::Str => DataType::Utf8,
+            }
+        }
+    }
+
+    impl ParquetTableWriter {
+        /// Writes the buffered rows as a record batch
+        fn write_batch(&mut self) -> anyhow::Result<()> {
+            if self.rows.is_empty() {
+                return Ok(());
+            }
+
+            let rows = std::mem::take(&mut self.rows);
+            let arrays = self
+                .columns
+                .iter()
+                .enumerate()
+                .map(|(idx, (_, ty))| column_array(&rows, idx, *ty))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            self.writer
+                .write(&RecordBatch::try_new(self.schema.clone(), arrays)?)?;
+            Ok(())
+        }
+    }
+
+    impl TableWriter for ParquetTableWriter {
+        fn write_row(&mut self, row: Row) -> anyhow::Result<()> {
+            self.rows.push(row);
+            if self.rows.len() >= BATCH_SIZE {
+                self.write_batch()?;
+            }
+
+            Ok(())
+        }
+
+        fn finish(mut self: Box<Self>) -> anyhow::Result<()> {
+            self.write_batch()?;
+            self.writer.close()?;
+            Ok(())
+        }
+    }
+
+    fn column_array(rows: &[Row], idx: usize, ty: ColumnType) -> anyhow::Result<ArrayRef> {
+        macro_rules! array {
+            ($array:ident, $variant:ident) => {{
+                let values = rows
+                    .iter()
+                    .map(|row| match &row[idx] {
+                        ExportValue::$variant(value) => Ok(value.to_owned()),
+                        value => anyhow::bail!("Expected {:?} column value, got {value:?}", ty),
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                Arc::new($array::from(values)) as ArrayRef
+            }};
+        }
+
+        Ok(match ty {
+            ColumnType::U32 => array!(UInt32Array, U32),
+            ColumnType::U64 => array!(UInt64Array, U64),
+            ColumnType::I64 => array!(Int64Array, I64),
+            ColumnType::Bool => array!(BooleanArray, Bool),
+            ColumnType::Str => array!(StringArray, Str),
+        })
+    }
+}
+
+#[cfg(not(feature = "parquet_export"))]
+mod parquet_writer {
+    use super::{ColumnType, TableWriter};
+    use std::fs::File;
+
+    pub(super) fn table_writer(
+        _file: File,
+        _columns: &'static [(&'static str, ColumnType)],
+    ) -> anyhow::Result<Box<dyn TableWriter>> {
+        anyhow::bail!("Cannot export Parquet files, built without the parquet_export feature")
+    }
+}
+
+///////////
+// Other //
+///////////
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "parquet" => Ok(Self::Parquet),
+            _ => Err(format!("Invalid export format: {s}")),
+        }
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+impl std::fmt::Display for ExportValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::U32(value) => write!(f, "{value}"),
+            Self::U64(value) => write!(f, "{value}"),
+            Self::I64(value) => write!(f, "{value}"),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Str(value) => write!(f, "{value}"),
+        }
+    }
+}
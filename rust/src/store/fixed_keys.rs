@@ -2,15 +2,27 @@ pub trait FixedKeys {
     const CHAIN_ID_KEY: &'static [u8] = "current_chain_id".as_bytes();
     const BEST_TIP_STATE_HASH_KEY: &'static [u8] = "best_tip_state_hash".as_bytes();
     const NEXT_EVENT_SEQ_NUM_KEY: &'static [u8] = "next_event_seq_num".as_bytes();
+    const NEXT_INGESTION_SEQ_NUM_KEY: &'static [u8] = "next_ingestion_seq_num".as_bytes();
     const MAX_CANONICAL_KEY: &'static [u8] = "max_canonical_blockchain_length".as_bytes();
+    const ORPHANS_SETTLED_HEIGHT_KEY: &'static [u8] = "orphans_settled_height".as_bytes();
+    const ORPHANS_PRUNED_HEIGHT_KEY: &'static [u8] = "orphans_pruned_height".as_bytes();
+    const ORPHANED_BLOCKS_PRUNED_HEIGHT_KEY: &'static [u8] =
+        "orphaned_blocks_pruned_height".as_bytes();
     const KNOWN_GENESIS_STATE_HASHES_KEY: &'static [u8] = "genesis_state_hashes".as_bytes();
     const KNOWN_GENESIS_PREV_STATE_HASHES_KEY: &'static [u8] =
         "genesis_prev_state_hashes".as_bytes();
+    const GENESIS_REGISTRY_KEY: &'static [u8] = "genesis_registry".as_bytes();
+    const CHAIN_CONSTANTS_KEY: &'static [u8] = "chain_constants".as_bytes();
     const NUM_BLOCK_BYTES_PROCESSED: &'static [u8] = "num_block_bytes_processed".as_bytes();
+    const CLEAN_SHUTDOWN_KEY: &'static [u8] = "clean_shutdown".as_bytes();
+
+    // network namespaces
+    const LEGACY_NAMESPACE_NETWORK_KEY: &'static [u8] = "legacy_namespace_network".as_bytes();
 
     // version info
     const INDEXER_STORE_VERSION_KEY: &'static [u8] = "indexer_store_version".as_bytes();
     const INDEXER_CONFIG_KEY: &'static [u8] = "indexer_config".as_bytes();
+    const INDEXER_CONSTANTS_KEY: &'static [u8] = "indexer_constants".as_bytes();
 
     // indexed totals
     const TOTAL_NUM_ACCOUNTS_KEY: &'static [u8] = "total_num_accounts".as_bytes();
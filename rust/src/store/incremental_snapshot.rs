@@ -0,0 +1,287 @@
+//! Incremental store snapshots
+//!
+//! A snapshot dir holds any number of compressed, consistent snapshots of the
+//! store, each taken from a speedb checkpoint (including the event log)
+//!
+//! ```text
+//! {snapshot_dir}/
+//!   tables/{table}-{size}.sst.gz  table files, shared by the snapshots
+//!   snapshots/{name}/{file}.gz    each snapshot's other files
+//!   snapshots/{name}.json         each snapshot's manifest
+//! ```
+//!
+//! Table files are immutable, so a snapshot only adds the tables written since
+//! the previous snapshot. Manifests are written last, so interrupted snapshots
+//! are never restored.
+
+use super::{persist_indexer_version, version::IndexerStoreVersion, IndexerStore};
+use crate::{base::state_hash::StateHash, block::store::BlockStore};
+use anyhow::{bail, Context};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use speedb::checkpoint::Checkpoint;
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const TABLES_DIR: &str = "tables";
+const SNAPSHOTS_DIR: &str = "snapshots";
+const CHECKPOINT_DIR: &str = "tmp-checkpoint";
+
+/// Snapshot manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub name: String,
+
+    /// Milliseconds since the Unix epoch
+    pub created_at: u64,
+    pub best_tip: Option<StateHash>,
+    pub best_height: Option<u32>,
+    pub files: Vec<SnapshotFile>,
+}
+
+/// Checkpoint file of a snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    /// Name in the database dir
+    pub name: String,
+
+    /// Uncompressed size
+    pub size: u64,
+
+    /// Path of the compressed file, relative to the snapshot dir
+    pub path: PathBuf,
+}
+
+/// Outcome of adding a snapshot to a snapshot dir
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncrementalSnapshot {
+    pub manifest: SnapshotManifest,
+
+    /// Number of files compressed into the snapshot dir
+    pub files_added: usize,
+
+    /// Number of table files shared with previous snapshots
+    pub files_reused: usize,
+}
+
+impl IndexerStore {
+    /// Adds a compressed snapshot of the store to `snapshot_dir`, reusing the
+    /// table files of its previous snapshots
+    pub fn create_incremental_snapshot(
+        &self,
+        snapshot_dir: &Path,
+    ) -> anyhow::Result<IncrementalSnapshot> {
+        fs::create_dir_all(snapshot_dir.join(TABLES_DIR))?;
+        fs::create_dir_all(snapshot_dir.join(SNAPSHOTS_DIR))?;
+
+        // leftover from an interrupted snapshot
+        let checkpoint_dir = snapshot_dir.join(CHECKPOINT_DIR);
+        if checkpoint_dir.exists() {
+            fs::remove_dir_all(&checkpoint_dir)?;
+        }
+
+        Checkpoint::new(self.database.as_ref())?
+            .create_checkpoint(&checkpoint_dir)
+            .map_err(|e| anyhow::anyhow!("Error creating database checkpoint: {e}"))?;
+        persist_indexer_version(&IndexerStoreVersion::default(), &checkpoint_dir)?;
+
+        let best_tip = self.get_best_block_hash()?;
+        let best_height = self.get_best_block_height()?;
+
+        // names are unique & ordered by creation
+        let mut created_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+        let mut name = format!("{created_at}-{}", best_height.unwrap_or_default());
+        while manifest_path(snapshot_dir, &name).exists() {
+            created_at += 1;
+            name = format!("{created_at}-{}", best_height.unwrap_or_default());
+        }
+
+        let mut files = vec![];
+        let (mut files_added, mut files_reused) = (0, 0);
+        for entry in fs::read_dir(&checkpoint_dir)?.flatten() {
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let size = entry.metadata()?.len();
+            let path = match file_name.strip_suffix(".sst") {
+                Some(table) => Path::new(TABLES_DIR).join(format!("{table}-{size}.sst.gz")),
+                None => Path::new(SNAPSHOTS_DIR)
+                    .join(&name)
+                    .join(format!("{file_name}.gz")),
+            };
+
+            if path.starts_with(TABLES_DIR) && snapshot_dir.join(&path).exists() {
+                files_reused += 1;
+            } else {
+                compress_file(&entry.path(), &snapshot_dir.join(&path))?;
+                files_added += 1;
+            }
+
+            files.push(SnapshotFile {
+                name: file_name,
+                size,
+                path,
+            });
+        }
+
+        let manifest = SnapshotManifest {
+            name,
+            created_at,
+            best_tip,
+            best_height,
+            files,
+        };
+        write_manifest(snapshot_dir, &manifest)?;
+        fs::remove_dir_all(&checkpoint_dir)?;
+
+        info!(
+            "Snapshot {} added to {} ({files_added} files added, {files_reused} reused)",
+            manifest.name,
+            snapshot_dir.display()
+        );
+        Ok(IncrementalSnapshot {
+            manifest,
+            files_added,
+            files_reused,
+        })
+    }
+}
+
+/// Manifests of the snapshot dir's complete snapshots, oldest first
+pub fn snapshot_manifests(snapshot_dir: &Path) -> anyhow::Result<Vec<SnapshotManifest>> {
+    let snapshots_dir = snapshot_dir.join(SNAPSHOTS_DIR);
+    if !snapshots_dir.is_dir() {
+        bail!("No snapshots in {}", snapshot_dir.display())
+    }
+
+    let mut manifests = vec![];
+    for entry in fs::read_dir(&snapshots_dir)?.flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            let manifest: SnapshotManifest = serde_json::from_slice(&fs::read(&path)?)
+                .with_context(|| format!("Invalid snapshot manifest {}", path.display()))?;
+            manifests.push(manifest);
+        }
+    }
+
+    manifests.sort_by_key(|manifest| manifest.created_at);
+    Ok(manifests)
+}
+
+/// Restores the named (or latest) snapshot of the snapshot dir to
+/// `restore_dir`, which must not exist
+pub fn restore_incremental_snapshot(
+    snapshot_dir: &Path,
+    name: Option<&str>,
+    restore_dir: &Path,
+) -> anyhow::Result<SnapshotManifest> {
+    if restore_dir.exists() {
+        bail!("Restore dir {restore_dir:#?} must not exist")
+    }
+
+    let manifests = snapshot_manifests(snapshot_dir)?;
+    let manifest = match name {
+        Some(name) => manifests
+            .into_iter()
+            .find(|manifest| manifest.name == name)
+            .with_context(|| format!("No snapshot {name} in {}", snapshot_dir.display()))?,
+        None => manifests
+            .into_iter()
+            .last()
+            .with_context(|| format!("No snapshots in {}", snapshot_dir.display()))?,
+    };
+
+    fs::create_dir_all(restore_dir)?;
+    for file in manifest.files.iter() {
+        let output_file = restore_dir.join(&file.name);
+        decompress_file(&snapshot_dir.join(&file.path), &output_file)?;
+
+        let size = output_file.metadata()?.len();
+        if size != file.size {
+            bail!(
+                "Snapshot {} file {} has {size} bytes, expected {}",
+                manifest.name,
+                file.name,
+                file.size
+            )
+        }
+    }
+
+    info!(
+        "Snapshot {} successfully restored. Start mina indexer using `mina-indexer server start --database-dir {}`",
+        manifest.name,
+        restore_dir.display()
+    );
+    Ok(manifest)
+}
+
+impl std::fmt::Display for IncrementalSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Snapshot {} created at height {} ({} files added, {} reused)",
+            self.manifest.name,
+            self.manifest.best_height.unwrap_or_default(),
+            self.files_added,
+            self.files_reused
+        )
+    }
+}
+
+/// Writes the manifest, completing the snapshot
+fn write_manifest(snapshot_dir: &Path, manifest: &SnapshotManifest) -> anyhow::Result<()> {
+    let manifest_path = manifest_path(snapshot_dir, &manifest.name);
+    let tmp_path = manifest_path.with_extension("json.tmp");
+
+    fs::write(&tmp_path, serde_json::to_vec_pretty(manifest)?)?;
+    Ok(fs::rename(tmp_path, manifest_path)?)
+}
+
+fn manifest_path(snapshot_dir: &Path, name: &str) -> PathBuf {
+    snapshot_dir
+        .join(SNAPSHOTS_DIR)
+        .join(format!("{name}.json"))
+}
+
+/// Gzip compresses the file, the output file only exists once it's complete
+fn compress_file(input_file: &Path, output_file: &Path) -> io::Result<()> {
+    debug!(
+        "Compressing {} to {}",
+        input_file.display(),
+        output_file.display()
+    );
+
+    if let Some(parent) = output_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let tmp_file = output_file.with_extension("gz.tmp");
+    let mut encoder = GzEncoder::new(
+        BufWriter::new(File::create(&tmp_file)?),
+        Compression::default(),
+    );
+    io::copy(&mut BufReader::new(File::open(input_file)?), &mut encoder)?;
+    encoder.finish()?;
+
+    fs::rename(tmp_file, output_file)
+}
+
+fn decompress_file(input_file: &Path, output_file: &Path) -> io::Result<()> {
+    debug!(
+        "Decompressing {} to {}",
+        input_file.display(),
+        output_file.display()
+    );
+
+    let mut decoder = GzDecoder::new(BufReader::new(File::open(input_file)?));
+    let mut output = BufWriter::new(File::create(output_file)?);
+    io::copy(&mut decoder, &mut output)?;
+    Ok(())
+}
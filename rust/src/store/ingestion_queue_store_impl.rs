@@ -0,0 +1,83 @@
+use super::{column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, sub_store::SubStore};
+use crate::{
+    ingestion::{store::IngestionQueueStore, QueuedBlock},
+    store::IndexerStore,
+    utility::store::common::{from_be_bytes, u32_from_be_bytes},
+};
+use log::trace;
+use speedb::{IteratorMode, WriteBatch};
+
+/// Add the corresponding CF helpers to [ColumnFamilyHelpers] & bump the
+/// sub-store version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "ingestion-queue",
+    version: 1,
+    column_families: &["ingestion-queue"],
+    dependencies: &[],
+    migrate: SubStore::no_migration,
+};
+
+impl IngestionQueueStore for IndexerStore {
+    /// Key: sequence number ([u32] BE bytes)
+    /// Value: journaled block (serialized with [serde_json::to_vec])
+    fn enqueue_block(&self, entry: &QueuedBlock) -> anyhow::Result<u32> {
+        let seq_num = self.get_next_ingestion_seq_num()?;
+        trace!(
+            "Journaling block {seq_num}: {} {}",
+            entry.blockchain_length,
+            entry.state_hash
+        );
+
+        // journal the entry & increment the sequence number atomically
+        let mut batch = WriteBatch::default();
+        batch.put_cf(
+            self.ingestion_queue_cf(),
+            seq_num.to_be_bytes(),
+            serde_json::to_vec(entry)?,
+        );
+        batch.put_cf(
+            self.default_cf(),
+            Self::NEXT_INGESTION_SEQ_NUM_KEY,
+            (seq_num + 1).to_be_bytes(),
+        );
+        self.database.write(batch)?;
+
+        Ok(seq_num)
+    }
+
+    fn mark_block_applied(&self, seq_num: u32) -> anyhow::Result<()> {
+        trace!("Marking journaled block {seq_num} applied");
+        self.database
+            .delete_cf(self.ingestion_queue_cf(), seq_num.to_be_bytes())?;
+        Ok(())
+    }
+
+    fn get_unapplied_blocks(&self) -> anyhow::Result<Vec<(u32, QueuedBlock)>> {
+        trace!("Getting unapplied journaled blocks");
+        let mut entries = vec![];
+        for (key, value) in self
+            .iterator_cf(self.ingestion_queue_cf(), IteratorMode::Start)
+            .flatten()
+        {
+            entries.push((u32_from_be_bytes(&key)?, serde_json::from_slice(&value)?));
+        }
+        Ok(entries)
+    }
+
+    fn get_ingestion_queue_size(&self) -> anyhow::Result<(u32, u64)> {
+        trace!("Getting ingestion queue size");
+        Ok(self
+            .get_unapplied_blocks()?
+            .iter()
+            .fold((0, 0), |(depth, num_bytes), (_, entry)| {
+                (depth + 1, num_bytes + entry.block_bytes)
+            }))
+    }
+
+    fn get_next_ingestion_seq_num(&self) -> anyhow::Result<u32> {
+        trace!("Getting next ingestion sequence number");
+        Ok(self
+            .get_cf(self.default_cf(), Self::NEXT_INGESTION_SEQ_NUM_KEY)?
+            .map_or(0, from_be_bytes))
+    }
+}
@@ -1,24 +1,52 @@
-use super::{column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, IndexerStore};
+use super::{
+    column_families::ColumnFamilyHelpers,
+    fixed_keys::FixedKeys,
+    replay::{DerivedData, ReplayRange},
+    sub_store::SubStore,
+    IndexerStore,
+};
 use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
     block::{
         precomputed::PrecomputedBlock,
         store::{BlockStore, DbBlockUpdate},
     },
-    command::internal::{
-        store::InternalCommandStore, DbInternalCommand, DbInternalCommandWithData,
-    },
+    canonicity::as_of::CanonicalChainAsOf,
+    command::internal::{store::InternalCommandStore, DbInternalCommandWithData},
     constants::millis_to_iso_date_string,
     utility::store::{
         command::internal::*,
         common::{from_be_bytes, pk_key_prefix, pk_txn_sort_key_sort, u32_prefix_key, U32_LEN},
     },
 };
-use anyhow::bail;
+use anyhow::{bail, Context};
 use log::trace;
 use speedb::{DBIterator, Direction, IteratorMode, WriteBatch};
 use std::path::PathBuf;
 
+/// Add the corresponding CF helpers to [ColumnFamilyHelpers] & bump the
+/// sub-store version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "internal-commands",
+    version: 1,
+    column_families: &[
+        "internal-commands",
+        "internal-commands-block-num",
+        "internal-commands-global-slot-sort",
+        "internal-commands-block-height-sort",
+        "internal-commands-pk",
+        "internal-commands-pk-num",
+        "internal-commands-pk-global-slot-sort",
+        "internal-commands-pk-block-height-sort",
+        // internal command counts
+        "internal-commands-epoch",
+        "internal-commands-pk-epoch",
+        "internal-commands-pk-total",
+    ],
+    dependencies: &["blocks"],
+    migrate: SubStore::no_migration,
+};
+
 impl InternalCommandStore for IndexerStore {
     /// Index internal commands on public keys & state hash
     fn add_internal_commands_batch(
@@ -30,22 +58,10 @@ impl InternalCommandStore for IndexerStore {
         let state_hash = block.state_hash();
         let global_slot = block.global_slot_since_genesis();
         let block_height = block.blockchain_length();
-        let date_time = block.timestamp() as i64;
         trace!("Adding internal commands for block {}", block.summary());
 
         // add cmds with data to public keys
-        let internal_cmds_with_data: Vec<DbInternalCommandWithData> =
-            DbInternalCommand::from_precomputed(block)
-                .into_iter()
-                .map(|c| {
-                    DbInternalCommandWithData::from_internal_cmd(
-                        c,
-                        state_hash.clone(),
-                        block_height,
-                        date_time,
-                    )
-                })
-                .collect();
+        let internal_cmds_with_data = DbInternalCommandWithData::from_precomputed(block);
 
         // per block internal command count
         self.set_block_internal_commands_count_batch(
@@ -95,6 +111,70 @@ impl InternalCommandStore for IndexerStore {
         Ok(())
     }
 
+    fn remove_internal_commands_batch(
+        &self,
+        blocks: &[PrecomputedBlock],
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()> {
+        for block in blocks {
+            trace!("Removing internal commands for block {}", block.summary());
+            let state_hash = block.state_hash();
+            let global_slot = block.global_slot_since_genesis();
+            let block_height = block.blockchain_length();
+
+            batch.delete_cf(
+                self.block_internal_command_counts_cf(),
+                state_hash.0.as_bytes(),
+            );
+            batch.delete_cf(
+                self.internal_commands_block_num_cf(),
+                state_hash.0.as_bytes(),
+            );
+
+            for (i, int_cmd) in DbInternalCommandWithData::from_precomputed(block)
+                .iter()
+                .enumerate()
+            {
+                let index = i as u32;
+                let pk = int_cmd.recipient();
+
+                batch.delete_cf(
+                    self.internal_commands_cf(),
+                    internal_commmand_block_key(&state_hash, index),
+                );
+                batch.delete_cf(
+                    self.internal_commands_block_height_sort_cf(),
+                    internal_commmand_sort_key(block_height, &state_hash, index),
+                );
+                batch.delete_cf(
+                    self.internal_commands_global_slot_sort_cf(),
+                    internal_commmand_sort_key(global_slot, &state_hash, index),
+                );
+                batch.delete_cf(
+                    self.internal_commands_pk_block_height_sort_cf(),
+                    internal_commmand_pk_sort_key(
+                        &pk,
+                        block_height,
+                        &state_hash,
+                        index,
+                        int_cmd.kind(),
+                    ),
+                );
+                batch.delete_cf(
+                    self.internal_commands_pk_global_slot_sort_cf(),
+                    internal_commmand_pk_sort_key(
+                        &pk,
+                        global_slot,
+                        &state_hash,
+                        index,
+                        int_cmd.kind(),
+                    ),
+                );
+            }
+        }
+        Ok(())
+    }
+
     fn set_block_internal_command(
         &self,
         block: &PrecomputedBlock,
@@ -131,7 +211,6 @@ impl InternalCommandStore for IndexerStore {
     ) -> anyhow::Result<Option<DbInternalCommandWithData>> {
         trace!("Getting internal command block {state_hash} index {index}");
         Ok(self
-            .database
             .get_cf(
                 self.internal_commands_cf(),
                 internal_commmand_block_key(state_hash, index),
@@ -165,7 +244,6 @@ impl InternalCommandStore for IndexerStore {
     ) -> anyhow::Result<Option<DbInternalCommandWithData>> {
         trace!("Getting internal command pk {pk} index {index}");
         Ok(self
-            .database
             .get_cf(
                 self.internal_commands_pk_cf(),
                 internal_commmand_pk_key(pk, index),
@@ -215,7 +293,6 @@ impl InternalCommandStore for IndexerStore {
     fn get_pk_num_internal_commands(&self, pk: &PublicKey) -> anyhow::Result<Option<u32>> {
         trace!("Getting pk num internal commands {pk}");
         Ok(self
-            .database
             .get_cf(self.internal_commands_pk_num_cf(), pk.0.as_bytes())?
             .map(from_be_bytes))
     }
@@ -224,7 +301,12 @@ impl InternalCommandStore for IndexerStore {
         &self,
         pk: PublicKey,
         path: Option<PathBuf>,
+        as_of_height: Option<u32>,
     ) -> anyhow::Result<PathBuf> {
+        let mut as_of = as_of_height
+            .map(|height| CanonicalChainAsOf::new(self, height))
+            .transpose()?;
+
         let mut cmds = vec![];
         for (key, _) in self
             .internal_commands_pk_block_height_iterator(pk.clone(), Direction::Reverse)
@@ -258,7 +340,13 @@ impl InternalCommandStore for IndexerStore {
         let mut csv_writer = csv::WriterBuilder::new()
             .has_headers(true)
             .from_path(path.clone())?;
-        for (_, _, index, state_hash) in cmds {
+        for (height, _, index, state_hash) in cmds {
+            if let Some(as_of) = as_of.as_mut() {
+                if !as_of.contains_at(self, &state_hash, height)? {
+                    continue;
+                }
+            }
+
             if let Some(cmd) = self
                 .get_block_internal_command(&state_hash, index)?
                 .as_ref()
@@ -278,13 +366,11 @@ impl InternalCommandStore for IndexerStore {
     ///////////////
 
     fn internal_commands_block_height_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.internal_commands_block_height_sort_cf(), mode)
+        self.iterator_cf(self.internal_commands_block_height_sort_cf(), mode)
     }
 
     fn internal_commands_global_slot_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.internal_commands_global_slot_sort_cf(), mode)
+        self.iterator_cf(self.internal_commands_global_slot_sort_cf(), mode)
     }
 
     fn internal_commands_pk_block_height_iterator(
@@ -305,8 +391,32 @@ impl InternalCommandStore for IndexerStore {
                 IteratorMode::From(&start, direction)
             }
         };
-        self.database
-            .iterator_cf(self.internal_commands_pk_block_height_sort_cf(), mode)
+        self.iterator_cf(self.internal_commands_pk_block_height_sort_cf(), mode)
+    }
+
+    fn internal_commands_block_height_iterator_from(
+        &self,
+        block_height: u32,
+        direction: Direction,
+    ) -> DBIterator<'_> {
+        let start = internal_command_sort_key_start(block_height, direction);
+        self.iterator_cf(
+            self.internal_commands_block_height_sort_cf(),
+            IteratorMode::From(&start, direction),
+        )
+    }
+
+    fn internal_commands_pk_block_height_iterator_from(
+        &self,
+        pk: &PublicKey,
+        block_height: u32,
+        direction: Direction,
+    ) -> DBIterator<'_> {
+        let start = internal_command_pk_sort_key_start(pk, block_height, direction);
+        self.iterator_cf(
+            self.internal_commands_pk_block_height_sort_cf(),
+            IteratorMode::From(&start, direction),
+        )
     }
 
     fn internal_commands_pk_global_slot_iterator(
@@ -327,8 +437,7 @@ impl InternalCommandStore for IndexerStore {
                 IteratorMode::From(&start, direction)
             }
         };
-        self.database
-            .iterator_cf(self.internal_commands_pk_global_slot_sort_cf(), mode)
+        self.iterator_cf(self.internal_commands_pk_global_slot_sort_cf(), mode)
     }
 
     /////////////////////////////
@@ -339,7 +448,6 @@ impl InternalCommandStore for IndexerStore {
         let epoch = epoch.unwrap_or(self.get_current_epoch()?);
         trace!("Getting internal command epoch {epoch}");
         Ok(self
-            .database
             .get_cf(self.internal_commands_epoch_cf(), epoch.to_be_bytes())?
             .map_or(0, from_be_bytes))
     }
@@ -357,15 +465,15 @@ impl InternalCommandStore for IndexerStore {
     fn get_internal_commands_total_count(&self) -> anyhow::Result<u32> {
         trace!("Getting internal command total");
         Ok(self
-            .database
-            .get(Self::TOTAL_NUM_FEE_TRANSFERS_KEY)?
+            .get_cf(self.default_cf(), Self::TOTAL_NUM_FEE_TRANSFERS_KEY)?
             .map_or(0, from_be_bytes))
     }
 
     fn increment_internal_commands_total_count(&self, incr: u32) -> anyhow::Result<()> {
         trace!("Incrementing internal command total");
         let old = self.get_internal_commands_total_count()?;
-        Ok(self.database.put(
+        Ok(self.database.put_cf(
+            self.default_cf(),
             Self::TOTAL_NUM_FEE_TRANSFERS_KEY,
             (old + incr).to_be_bytes(),
         )?)
@@ -379,7 +487,6 @@ impl InternalCommandStore for IndexerStore {
         let epoch = epoch.unwrap_or(self.get_current_epoch()?);
         trace!("Getting internal command epoch {epoch} num {pk}");
         Ok(self
-            .database
             .get_cf(
                 self.internal_commands_pk_epoch_cf(),
                 u32_prefix_key(epoch, pk),
@@ -404,7 +511,6 @@ impl InternalCommandStore for IndexerStore {
     fn get_internal_commands_pk_total_count(&self, pk: &PublicKey) -> anyhow::Result<u32> {
         trace!("Getting pk total internal commands count {pk}");
         Ok(self
-            .database
             .get_cf(self.internal_commands_pk_total_cf(), pk.0.as_bytes())?
             .map_or(0, from_be_bytes))
     }
@@ -425,7 +531,6 @@ impl InternalCommandStore for IndexerStore {
     ) -> anyhow::Result<Option<u32>> {
         trace!("Getting block internal command count");
         Ok(self
-            .database
             .get_cf(
                 self.block_internal_command_counts_cf(),
                 state_hash.0.as_bytes(),
@@ -467,12 +572,49 @@ impl InternalCommandStore for IndexerStore {
         self.increment_internal_commands_epoch_count(epoch)
     }
 
+    fn decrement_internal_commands_counts(
+        &self,
+        internal_command: &DbInternalCommandWithData,
+        epoch: u32,
+    ) -> anyhow::Result<()> {
+        let receiver = match internal_command {
+            DbInternalCommandWithData::Coinbase { .. } => return Ok(()),
+            DbInternalCommandWithData::FeeTransfer { receiver, .. } => receiver,
+        };
+        trace!("Decrementing internal command counts {internal_command:?}");
+
+        // receiver epoch & total
+        let pk_epoch = self.get_internal_commands_pk_epoch_count(receiver, Some(epoch))?;
+        self.database.put_cf(
+            self.internal_commands_pk_epoch_cf(),
+            u32_prefix_key(epoch, receiver),
+            pk_epoch.saturating_sub(1).to_be_bytes(),
+        )?;
+
+        let pk_total = self.get_internal_commands_pk_total_count(receiver)?;
+        self.database.put_cf(
+            self.internal_commands_pk_total_cf(),
+            receiver.0.as_bytes(),
+            pk_total.saturating_sub(1).to_be_bytes(),
+        )?;
+
+        // epoch count
+        let epoch_count = self.get_internal_commands_epoch_count(Some(epoch))?;
+        Ok(self.database.put_cf(
+            self.internal_commands_epoch_cf(),
+            epoch.to_be_bytes(),
+            epoch_count.saturating_sub(1).to_be_bytes(),
+        )?)
+    }
+
     /// get canonical internal commands count
     fn get_canonical_internal_commands_count(&self) -> anyhow::Result<u32> {
         trace!("Getting canonical internal command count");
         Ok(self
-            .database
-            .get(Self::TOTAL_NUM_CANONICAL_FEE_TRANSFERS_KEY)?
+            .get_cf(
+                self.default_cf(),
+                Self::TOTAL_NUM_CANONICAL_FEE_TRANSFERS_KEY,
+            )?
             .map_or(0, from_be_bytes))
     }
 
@@ -480,7 +622,8 @@ impl InternalCommandStore for IndexerStore {
     fn increment_canonical_internal_commands_count(&self, incr: u32) -> anyhow::Result<()> {
         trace!("Increment canonical internal commands count");
         let old = self.get_canonical_internal_commands_count()?;
-        Ok(self.database.put(
+        Ok(self.database.put_cf(
+            self.default_cf(),
             Self::TOTAL_NUM_CANONICAL_FEE_TRANSFERS_KEY,
             (old + incr).to_be_bytes(),
         )?)
@@ -490,7 +633,8 @@ impl InternalCommandStore for IndexerStore {
     fn decrement_canonical_internal_commands_count(&self, incr: u32) -> anyhow::Result<()> {
         trace!("Decrement canonical internal commands count");
         let old = self.get_canonical_internal_commands_count()?;
-        Ok(self.database.put(
+        Ok(self.database.put_cf(
+            self.default_cf(),
             Self::TOTAL_NUM_CANONICAL_FEE_TRANSFERS_KEY,
             (old.saturating_sub(incr)).to_be_bytes(),
         )?)
@@ -512,6 +656,241 @@ impl InternalCommandStore for IndexerStore {
     }
 }
 
+/// Internal command records, sort keys & counts, see [DerivedData]
+pub struct InternalCommandData;
+
+impl DerivedData for InternalCommandData {
+    fn name(&self) -> &'static str {
+        "internal command"
+    }
+
+    fn rewrite_range(&self, store: &IndexerStore, range: &ReplayRange) -> anyhow::Result<u32> {
+        let mut num_rewritten = 0;
+        for state_hash in range.state_hashes.iter() {
+            let (block, _) = store
+                .get_block(state_hash)?
+                .with_context(|| format!("Block missing from store {state_hash}"))?;
+            num_rewritten += store.rewrite_block_internal_commands(&block)?;
+        }
+
+        Ok(num_rewritten)
+    }
+}
+
+impl IndexerStore {
+    /// Rewrite the canonical block's internal commands which differ from the
+    /// recomputed ones, returning the number of rewritten commands
+    fn rewrite_block_internal_commands(&self, block: &PrecomputedBlock) -> anyhow::Result<u32> {
+        let epoch = block.epoch_count();
+        let state_hash = block.state_hash();
+        let expected = DbInternalCommandWithData::from_precomputed(block);
+
+        let num_stored = self
+            .get_block_internal_commands_count(&state_hash)?
+            .unwrap_or(0);
+        let mut stored = Vec::with_capacity(num_stored as usize);
+        for index in 0..num_stored {
+            stored.push(self.get_block_internal_command(&state_hash, index)?);
+        }
+
+        let mut num_rewritten = 0;
+        for index in 0..stored.len().max(expected.len()) {
+            let old = stored.get(index).cloned().flatten();
+            let new = expected.get(index);
+            if old.as_ref() == new {
+                continue;
+            }
+
+            trace!("Rewriting block internal command {state_hash} index {index}");
+            num_rewritten += 1;
+
+            // subtract the old contributions
+            if let Some(old) = old.as_ref() {
+                let pk = old.recipient();
+                self.decrement_internal_commands_counts(old, epoch)?;
+                self.delete_pk_internal_command_sort_keys(block, index as u32, &pk, old.kind())?;
+
+                // replace in place if the recipient is unchanged
+                let replacement = new.filter(|new| new.recipient() == pk);
+                self.replace_pk_internal_command(&pk, old, replacement)?;
+            }
+
+            // add the new contributions
+            match new {
+                Some(new) => {
+                    self.set_block_internal_command(block, index as u32, new)?;
+                    self.set_pk_internal_command_sort_keys(block, index as u32, new)?;
+
+                    // missing records are still counted
+                    if old.is_some() || index >= stored.len() {
+                        let pk = new.recipient();
+                        self.increment_internal_commands_counts(new, epoch)?;
+
+                        if old.as_ref().map(|old| old.recipient()) != Some(pk.clone()) {
+                            self.set_pk_internal_command(&pk, new)?;
+                        }
+                    }
+                }
+                None => self.delete_block_internal_command(block, index as u32)?,
+            }
+        }
+
+        // block, total & canonical counts
+        let num_expected = expected.len() as u32;
+        if num_stored != num_expected {
+            let mut batch = WriteBatch::default();
+            self.set_block_internal_commands_count_batch(&state_hash, num_expected, &mut batch)?;
+            self.database.write(batch)?;
+            self.database.put_cf(
+                self.internal_commands_block_num_cf(),
+                state_hash.0.as_bytes(),
+                num_expected.to_be_bytes(),
+            )?;
+
+            let total = self.get_internal_commands_total_count()?;
+            self.database.put_cf(
+                self.default_cf(),
+                Self::TOTAL_NUM_FEE_TRANSFERS_KEY,
+                (total + num_expected)
+                    .saturating_sub(num_stored)
+                    .to_be_bytes(),
+            )?;
+
+            if num_expected > num_stored {
+                self.increment_canonical_internal_commands_count(num_expected - num_stored)?;
+            } else {
+                self.decrement_canonical_internal_commands_count(num_stored - num_expected)?;
+            }
+        }
+
+        Ok(num_rewritten)
+    }
+
+    fn set_pk_internal_command_sort_keys(
+        &self,
+        block: &PrecomputedBlock,
+        index: u32,
+        internal_command: &DbInternalCommandWithData,
+    ) -> anyhow::Result<()> {
+        let pk = internal_command.recipient();
+        let state_hash = block.state_hash();
+        let kind = internal_command.kind();
+
+        self.database.put_cf(
+            self.internal_commands_pk_block_height_sort_cf(),
+            internal_commmand_pk_sort_key(&pk, block.blockchain_length(), &state_hash, index, kind),
+            serde_json::to_vec(internal_command)?,
+        )?;
+        self.database.put_cf(
+            self.internal_commands_pk_global_slot_sort_cf(),
+            internal_commmand_pk_sort_key(
+                &pk,
+                block.global_slot_since_genesis(),
+                &state_hash,
+                index,
+                kind,
+            ),
+            serde_json::to_vec(internal_command)?,
+        )?;
+        Ok(())
+    }
+
+    fn delete_pk_internal_command_sort_keys(
+        &self,
+        block: &PrecomputedBlock,
+        index: u32,
+        pk: &PublicKey,
+        kind: u8,
+    ) -> anyhow::Result<()> {
+        let state_hash = block.state_hash();
+        self.database.delete_cf(
+            self.internal_commands_pk_block_height_sort_cf(),
+            internal_commmand_pk_sort_key(pk, block.blockchain_length(), &state_hash, index, kind),
+        )?;
+        self.database.delete_cf(
+            self.internal_commands_pk_global_slot_sort_cf(),
+            internal_commmand_pk_sort_key(
+                pk,
+                block.global_slot_since_genesis(),
+                &state_hash,
+                index,
+                kind,
+            ),
+        )?;
+        Ok(())
+    }
+
+    fn delete_block_internal_command(
+        &self,
+        block: &PrecomputedBlock,
+        index: u32,
+    ) -> anyhow::Result<()> {
+        let state_hash = block.state_hash();
+        self.database.delete_cf(
+            self.internal_commands_cf(),
+            internal_commmand_block_key(&state_hash, index),
+        )?;
+        self.database.delete_cf(
+            self.internal_commands_block_height_sort_cf(),
+            internal_commmand_sort_key(block.blockchain_length(), &state_hash, index),
+        )?;
+        self.database.delete_cf(
+            self.internal_commands_global_slot_sort_cf(),
+            internal_commmand_sort_key(block.global_slot_since_genesis(), &state_hash, index),
+        )?;
+        Ok(())
+    }
+
+    /// Replace `old` in the pk's internal commands with `new`, or remove it
+    /// & shift the later commands down
+    fn replace_pk_internal_command(
+        &self,
+        pk: &PublicKey,
+        old: &DbInternalCommandWithData,
+        new: Option<&DbInternalCommandWithData>,
+    ) -> anyhow::Result<()> {
+        let num = self.get_pk_num_internal_commands(pk)?.unwrap_or(0);
+        let Some(position) = (0..num)
+            .find(|n| self.get_pk_internal_command(pk, *n).ok().flatten().as_ref() == Some(old))
+        else {
+            return Ok(());
+        };
+
+        if let Some(new) = new {
+            self.database.put_cf(
+                self.internal_commands_pk_cf(),
+                internal_command_pk_key(pk, position),
+                serde_json::to_vec(new)?,
+            )?;
+            return Ok(());
+        }
+
+        for n in position + 1..num {
+            if let Some(bytes) = self.get_cf(
+                self.internal_commands_pk_cf(),
+                internal_command_pk_key(pk, n),
+            )? {
+                self.database.put_cf(
+                    self.internal_commands_pk_cf(),
+                    internal_command_pk_key(pk, n - 1),
+                    bytes,
+                )?;
+            }
+        }
+
+        self.database.delete_cf(
+            self.internal_commands_pk_cf(),
+            internal_command_pk_key(pk, num - 1),
+        )?;
+        self.database.put_cf(
+            self.internal_commands_pk_num_cf(),
+            pk.0.as_bytes(),
+            (num - 1).to_be_bytes(),
+        )?;
+        Ok(())
+    }
+}
+
 #[derive(serde::Serialize)]
 #[serde(rename_all = "PascalCase")]
 struct CsvRecordInternalCommand<'a> {
@@ -534,6 +913,7 @@ impl<'a> CsvRecordInternalCommand<'a> {
                 kind,
                 date_time,
                 block_height,
+                ..
             }
             | FeeTransfer {
                 receiver,
@@ -542,6 +922,7 @@ impl<'a> CsvRecordInternalCommand<'a> {
                 kind,
                 date_time,
                 block_height,
+                ..
             } => Self {
                 amount: *amount,
                 recipient: &receiver.0,
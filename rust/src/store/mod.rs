@@ -2,29 +2,61 @@
 //! [IndexerStore]
 
 // traits
+pub mod block_subscriptions;
 pub mod column_families;
+pub mod constants_snapshot;
+pub mod dataset;
+pub mod export;
 pub mod fixed_keys;
+pub mod incremental_snapshot;
+pub mod namespace;
+pub mod replay;
+pub mod replication;
+pub mod shutdown;
+pub mod snapshot;
+pub mod sub_store;
 pub mod username;
 pub mod version;
 pub mod zkapp;
 
 // impls
+pub mod amount_anomaly_store_impl;
 pub mod best_ledger_store_impl;
 pub mod block_store_impl;
 pub mod canonicity_store_impl;
 pub mod chain_store_impl;
 pub mod column_families_impl;
+pub mod constants_snapshot_store_impl;
 pub mod event_store_impl;
+pub mod ingestion_queue_store_impl;
 pub mod internal_command_store_impl;
 pub mod snark_store_impl;
 pub mod staged_ledger_store_impl;
 pub mod staking_ledger_store_impl;
 pub mod user_command_store_impl;
 pub mod username_store_impl;
+pub mod validation_store_impl;
 pub mod version_store_impl;
+pub mod watchlist_store_impl;
 pub mod zkapp_store_impl;
 
-use self::fixed_keys::FixedKeys;
+#[cfg(feature = "sql_mirror")]
+pub mod sql_mirror;
+
+use self::{
+    block_subscriptions::BLOCK_NOTIFICATIONS_CAPACITY,
+    constants_snapshot::{ConstantsSnapshot, ConstantsSnapshotStore},
+    fixed_keys::FixedKeys,
+    namespace::NetworkNamespaces,
+    snapshot::{PinnedViews, ReadSnapshot},
+    zkapp::subscriptions::ZKAPP_NOTIFICATIONS_CAPACITY,
+};
+use crate::{
+    canonicity::cache::CanonicalHashCache,
+    chain::Network,
+    constants::STAKING_LEDGER_BATCH_SIZE,
+    watchlist::{WatchNotificationSender, WATCH_NOTIFICATIONS_CAPACITY},
+};
 use anyhow::{anyhow, bail, Context};
 use log::{debug, info};
 use serde::{Deserialize, Serialize};
@@ -33,7 +65,12 @@ use std::{
     fs::{self, read_dir, File},
     io::{self, BufReader, BufWriter, Write},
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
 };
+use sub_store::{SubStore, SUB_STORE_VERSIONS_CF};
 use version::{IndexerStoreVersion, VersionStore};
 
 pub(crate) type Result<T> = anyhow::Result<T>;
@@ -41,8 +78,56 @@ pub(crate) type Result<T> = anyhow::Result<T>;
 #[derive(Debug)]
 pub struct IndexerStore {
     pub db_path: PathBuf,
-    pub database: DB,
+    pub database: Arc<DB>,
     pub is_primary: bool,
+
+    /// Network namespace, `None` for the legacy (unprefixed) namespace, see
+    /// [namespace]
+    pub namespace: Option<Network>,
+
+    /// Networks whose namespaces were opened with the database
+    pub namespaced_networks: Vec<Network>,
+
+    /// Live stores of the database's namespaces
+    pub namespaces: Arc<NetworkNamespaces>,
+
+    /// Registered & extra sub-stores, in dependency order
+    pub sub_stores: Vec<SubStore>,
+
+    /// Snapshot the store's reads are pinned to, see [snapshot]
+    pub read_snapshot: Option<Arc<ReadSnapshot>>,
+
+    /// Recently pinned views of the store
+    pub pinned_views: PinnedViews,
+
+    /// Only used by the primary store
+    pub canonical_hash_cache: CanonicalHashCache,
+
+    /// Primary's database directory, only used by read-only stores
+    pub primary_path: Option<PathBuf>,
+
+    /// Last catch-up with the primary, only used by read-only stores
+    pub replica_catch_up: std::sync::Mutex<Option<replication::ReplicaCatchUp>>,
+
+    /// Zkapp event & action notifications, only published by the primary
+    /// store
+    pub zkapp_notifications: zkapp::subscriptions::ZkappNotificationSender,
+
+    /// New block, canonical block & best tip notifications, only published
+    /// by the primary store
+    pub block_notifications: block_subscriptions::BlockNotificationSender,
+
+    /// Watched account notifications, only published by the primary store
+    pub watch_notifications: WatchNotificationSender,
+
+    /// Number of staking ledger accounts written per write batch, see
+    /// [IndexerStore::set_staking_ledger_batch_size]
+    pub staking_ledger_batch_size: AtomicUsize,
+
+    /// Optional SQL mirror of the canonical chain, see
+    /// [IndexerStore::enable_sql_mirror]
+    #[cfg(feature = "sql_mirror")]
+    pub sql_mirror: std::sync::OnceLock<sql_mirror::SqlMirror>,
 }
 
 #[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -52,184 +137,90 @@ pub struct DbUpdate<T> {
 }
 
 impl IndexerStore {
-    /// Add the corresponding CF helper to [ColumnFamilyHelpers]
-    /// & modify [IndexerStoreVersion] as needed!
-    const COLUMN_FAMILIES: [&'static str; 129] = [
-        //////////////////////
-        // Blocks store CFs //
-        //////////////////////
-        "blocks",
-        "blocks-state-hash",
-        "blocks-version",
-        "blocks-at-length",
-        "blocks-at-slot",
-        "blocks-height",
-        "blocks-global-slot",
-        "blocks-parent-hash",
-        "blocks-date-time",
-        "blocks-epoch",
-        "blocks-genesis-hash",
-        "blocks-height-to-slots",
-        "blocks-slot-to-heights",
-        "blocks-height-sort",
-        "blocks-global-slot-sort",
-        "blocks-comparison",
-        "blocks-coinbase-receiver",
-        "blocks-creator",
-        "block-creator-height-sort",
-        "block-creator-slot-sort",
-        "coinbase-receiver-height-sort",
-        "coinbase-receiver-slot-sort",
-        "block-epoch-slots-produced",
-        "block-pk-epoch-slots-produced",
-        "blocks-pk-count",
-        //////////////////////////
-        // Canonicity store CFs //
-        //////////////////////////
-        "canonicity-length",
-        "canonicity-slot",
-        ////////////////////////////
-        // User command store CFs //
-        ////////////////////////////
-        "user-commands",
-        "user-commands-pk",
-        "user-commands-pk-num",
-        "user-commands-block",
-        "user-commands-block-order",
-        "user-commands-num-blocks",
-        "user-commands-slot-sort",
-        "user-commands-height-sort",
-        "user-commands-to-global-slot",
-        "user-commands-to-block-height",
-        "user-command-state-hashes",
-        // sorting user commands by sender/receiver
-        "txn-from-slot-sort",
-        "txn-from-height-sort",
-        "txn-to-slot-sort",
-        "txn-to-height-sort",
-        /////////////////////
-        // Zkapp store CFs //
-        /////////////////////
-        "zkapp-actions",
-        "zkapp-actions-pk-num",
-        "zkapp-events",
-        "zkapp-events-pk-num",
-        ////////////////////////////////
-        // Internal command store CFs //
-        ////////////////////////////////
-        "internal-commands",
-        "internal-commands-block-num",
-        "internal-commands-global-slot-sort",
-        "internal-commands-block-height-sort",
-        "internal-commands-pk",
-        "internal-commands-pk-num",
-        "internal-commands-pk-global-slot-sort",
-        "internal-commands-pk-block-height-sort",
-        /////////////////////
-        // SNARK store CFs //
-        /////////////////////
-        "snarks",
-        "snarks-prover",
-        "snark-prover-fees",
-        "snark-prover-fees-epoch",
-        "snark-prover-fees-historical",
-        "snark-prover-fees-epoch-historical",
-        "snark-prover-total-fees-sort",
-        "snark-prover-total-fees-epoch-sort",
-        "snark-prover-max-fee",
-        "snark-prover-max-fee-epoch",
-        "snark-prover-max-fee-sort",
-        "snark-prover-max-fee-epoch-sort",
-        "snark-prover-min-fee",
-        "snark-prover-min-fee-epoch",
-        "snark-prover-min-fee-sort",
-        "snark-prover-min-fee-epoch-sort",
-        "snark-prover-block-height-sort",
-        "snark-prover-global-slot-sort",
-        "snark-work-fees-block-height-sort",
-        "snark-work-fees-global-slot-sort",
-        /////////////////////
-        // Event store CFs //
-        /////////////////////
-        "events",
-        ///////////////////////////
-        // Best ledger store CFs //
-        ///////////////////////////
-        "best-ledger-accounts",
-        "best-ledger-account-balance-sort",
-        "best-ledger-account-num-delegations",
-        "best-ledger-account-delegations",
-        "zkapp-best-ledger-accounts",
-        "zkapp-best-ledger-account-balance-sort",
-        /////////////////////////////
-        // Staged ledger store CFs //
-        /////////////////////////////
-        "staged-ledger-accounts",
-        "staged-ledger-account-balance-sort",
-        "staged-ledger-account-num-delegations",
-        "staged-ledger-account-delegations",
-        "staged-ledger-hash-to-block",
-        "staged-ledger-persisted",
-        "staged-ledger-accounts-min-block",
-        "blocks-ledger-diff",
-        "blocks-staged-ledger-hash",
-        //////////////////////////////
-        // Staking ledger store CFs //
-        //////////////////////////////
-        "staking-ledger-accounts",
-        "staking-ledger-delegations",
-        "staking-ledger-persisted",
-        "staking-ledger-epoch-to-hash",
-        "staking-ledger-hash-to-epoch",
-        "staking-ledger-genesis-hash",
-        "staking-ledger-total-currency",
-        "staking-ledger-balance-sort",
-        "staking-ledger-stake-sort",
-        "staking-ledger-accounts-count-epoch",
-        /////////////////////
-        // Chain store CFs //
-        /////////////////////
-        "chain-id-to-network",
-        ////////////////////////
-        // Username store CFs //
-        ////////////////////////
-        "username-pk-num",
-        "username-pk-index",
-        "usernames-per-block",
-        // block counts
-        "block-production-pk-epoch",
-        "block-production-pk-canonical-epoch",
-        "block-production-pk-canonical-epoch-sort",
-        "block-production-pk-supercharged-epoch",
-        "block-production-pk-total",
-        "block-production-pk-canonical-total",
-        "block-production-pk-supercharged-total",
-        "block-production-epoch",
-        "block-production-canonical-epoch",
-        "block-production-supercharged-epoch",
-        "block-snark-counts",
-        "block-user-command-counts",
-        "block-internal-command-counts",
-        // slot counts
-        "block-epoch-slots-produced-count",
-        "block-pk-epoch-slots-produced-count",
-        "block-pk-epoch-slots-produced-count-sort",
-        // user command counts
-        "user-commands-epoch",
-        "user-commands-pk-epoch",
-        "user-commands-pk-total",
-        // internal command counts
-        "internal-commands-epoch",
-        "internal-commands-pk-epoch",
-        "internal-commands-pk-total",
-        // SNARK counts
-        "snarks-epoch",
-        "snarks-pk-epoch",
-        "snarks-pk-total",
+    /// All registered sub-stores, see [SubStore]
+    pub const SUB_STORES: [SubStore; 16] = [
+        block_store_impl::SUB_STORE,
+        canonicity_store_impl::SUB_STORE,
+        user_command_store_impl::SUB_STORE,
+        zkapp_store_impl::SUB_STORE,
+        internal_command_store_impl::SUB_STORE,
+        snark_store_impl::SUB_STORE,
+        event_store_impl::SUB_STORE,
+        ingestion_queue_store_impl::SUB_STORE,
+        best_ledger_store_impl::SUB_STORE,
+        amount_anomaly_store_impl::SUB_STORE,
+        staged_ledger_store_impl::SUB_STORE,
+        staking_ledger_store_impl::SUB_STORE,
+        chain_store_impl::SUB_STORE,
+        username_store_impl::SUB_STORE,
+        watchlist_store_impl::SUB_STORE,
+        validation_store_impl::SUB_STORE,
     ];
 
     /// Creates a new _primary_ indexer store
     pub fn new(path: &Path) -> Result<Self> {
+        Self::new_with_sub_stores(path, &[], false)
+    }
+
+    /// Creates a new _primary_ indexer store, accepting changes to
+    /// derivation-affecting constants, see [ConstantsSnapshotStore]
+    pub fn new_accept_constants_change(path: &Path) -> Result<Self> {
+        Self::open(path, &[], &[], false, true)
+    }
+
+    /// Creates a new _primary_ indexer store with the registered & extra
+    /// sub-stores, running any pending sub-store migrations
+    ///
+    /// With `dry_run`, pending migrations are logged, but neither run nor
+    /// recorded
+    pub fn new_with_sub_stores(
+        path: &Path,
+        extra_sub_stores: &[SubStore],
+        dry_run: bool,
+    ) -> Result<Self> {
+        Self::open(path, extra_sub_stores, &[], dry_run, false)
+    }
+
+    /// Creates a new _primary_ indexer store, opening (& creating, if
+    /// needed) the networks' namespaces, see [IndexerStore::network_store]
+    ///
+    /// The legacy network, or the first network of a fresh store, uses the
+    /// legacy namespace
+    pub fn new_with_networks(
+        path: &Path,
+        networks: &[Network],
+        accept_constants_change: bool,
+    ) -> Result<Self> {
+        let store = Self::open(path, &[], &[], false, accept_constants_change)?;
+        let legacy = store
+            .get_legacy_network()?
+            .or_else(|| networks.first().cloned());
+
+        let missing: Vec<_> = networks
+            .iter()
+            .filter(|network| {
+                legacy.as_ref() != Some(*network) && !store.namespaced_networks.contains(network)
+            })
+            .cloned()
+            .collect();
+        if missing.is_empty() {
+            return Ok(store);
+        }
+
+        // reopen with the missing namespaces
+        drop(store);
+        Self::open(path, &[], &missing, false, accept_constants_change)
+    }
+
+    fn open(
+        path: &Path,
+        extra_sub_stores: &[SubStore],
+        networks: &[Network],
+        dry_run: bool,
+        accept_constants_change: bool,
+    ) -> Result<Self> {
+        let sub_stores = Self::sub_stores(extra_sub_stores)?;
+
         let mut cf_opts = speedb::Options::default();
         cf_opts.set_write_buffer_size(256 * 1024 * 1024); // 256MB
         cf_opts.set_max_write_buffer_number(16); // 256Mb * 16 ~= 4GB
@@ -241,29 +232,122 @@ impl IndexerStore {
         database_opts.create_missing_column_families(true);
         database_opts.create_if_missing(true);
 
-        let column_families: Vec<ColumnFamilyDescriptor> = Self::COLUMN_FAMILIES
-            .iter()
-            .map(|cf| ColumnFamilyDescriptor::new(*cf, cf_opts.clone()))
-            .collect();
+        let mut namespaced_networks = namespace::existing_namespaces(path);
+        for network in networks {
+            if !namespaced_networks.contains(network) {
+                namespaced_networks.push(network.clone());
+            }
+        }
+
         let primary = Self {
             is_primary: true,
             db_path: path.into(),
-            database: speedb::DBWithThreadMode::open_cf_descriptors(
+            canonical_hash_cache: CanonicalHashCache::default(),
+            primary_path: None,
+            replica_catch_up: Default::default(),
+            zkapp_notifications: tokio::sync::broadcast::channel(ZKAPP_NOTIFICATIONS_CAPACITY).0,
+            block_notifications: tokio::sync::broadcast::channel(BLOCK_NOTIFICATIONS_CAPACITY).0,
+            watch_notifications: tokio::sync::broadcast::channel(WATCH_NOTIFICATIONS_CAPACITY).0,
+            staking_ledger_batch_size: AtomicUsize::new(STAKING_LEDGER_BATCH_SIZE),
+            #[cfg(feature = "sql_mirror")]
+            sql_mirror: Default::default(),
+            database: Arc::new(speedb::DBWithThreadMode::open_cf_descriptors(
                 &database_opts,
                 path,
-                column_families,
-            )?,
+                Self::column_family_descriptors(&sub_stores, &namespaced_networks, &cf_opts),
+            )?),
+            namespace: None,
+            namespaced_networks,
+            namespaces: Default::default(),
+            sub_stores,
+            read_snapshot: None,
+            pinned_views: Default::default(),
         };
 
+        primary.prepare_primary(dry_run, accept_constants_change)?;
+        if dry_run {
+            return Ok(primary);
+        }
+
+        let version = primary.get_db_version().expect("db version exists");
+        persist_indexer_version(&version, path)?;
+        Ok(primary)
+    }
+
+    /// Migrates the sub-stores, checks the derivation-affecting constants &
+    /// sets the db version of the store's namespace
+    fn prepare_primary(&self, dry_run: bool, accept_constants_change: bool) -> Result<()> {
+        // migrate sub-stores
+        self.migrate_sub_stores(&self.sub_stores, dry_run)?;
+        if dry_run {
+            return Ok(());
+        }
+
+        // check constants
+        self.check_constants_snapshot(&ConstantsSnapshot::current(), accept_constants_change)?;
+
         // set db version
-        primary.set_db_version_with_git_commit(
+        self.set_db_version_with_git_commit(
             IndexerStoreVersion::MAJOR,
             IndexerStoreVersion::MINOR,
             IndexerStoreVersion::PATCH,
-        )?;
-        let version = primary.get_db_version().expect("db version exists");
-        persist_indexer_version(&version, path)?;
-        Ok(primary)
+        )
+    }
+
+    /// Store sharing this store's database, namespace & read snapshot, with
+    /// its own caches
+    fn shared(&self) -> Self {
+        Self {
+            db_path: self.db_path.clone(),
+            database: self.database.clone(),
+            is_primary: self.is_primary,
+            canonical_hash_cache: CanonicalHashCache::default(),
+            primary_path: self.primary_path.clone(),
+            replica_catch_up: Default::default(),
+            zkapp_notifications: tokio::sync::broadcast::channel(ZKAPP_NOTIFICATIONS_CAPACITY).0,
+            block_notifications: tokio::sync::broadcast::channel(BLOCK_NOTIFICATIONS_CAPACITY).0,
+            watch_notifications: tokio::sync::broadcast::channel(WATCH_NOTIFICATIONS_CAPACITY).0,
+            staking_ledger_batch_size: AtomicUsize::new(
+                self.staking_ledger_batch_size.load(Ordering::Relaxed),
+            ),
+            #[cfg(feature = "sql_mirror")]
+            sql_mirror: Default::default(),
+            namespace: self.namespace.clone(),
+            namespaced_networks: self.namespaced_networks.clone(),
+            namespaces: self.namespaces.clone(),
+            sub_stores: self.sub_stores.clone(),
+            read_snapshot: self.read_snapshot.clone(),
+            pinned_views: Default::default(),
+        }
+    }
+
+    /// Registered & extra sub-stores, in dependency order
+    fn sub_stores(extra_sub_stores: &[SubStore]) -> Result<Vec<SubStore>> {
+        let mut sub_stores = Self::SUB_STORES.to_vec();
+        sub_stores.extend_from_slice(extra_sub_stores);
+        sub_store::sort_sub_stores(&sub_stores)
+    }
+
+    /// Names of each sub-store CF & the sub-store versions CF
+    fn column_family_names(sub_stores: &[SubStore]) -> impl Iterator<Item = &'static str> + '_ {
+        sub_stores
+            .iter()
+            .flat_map(|sub_store| sub_store.column_families.iter().copied())
+            .chain(std::iter::once(SUB_STORE_VERSIONS_CF))
+    }
+
+    /// Descriptors for each sub-store CF & the sub-store versions CF, in the
+    /// legacy & each network's namespace
+    fn column_family_descriptors(
+        sub_stores: &[SubStore],
+        networks: &[Network],
+        cf_opts: &speedb::Options,
+    ) -> Vec<ColumnFamilyDescriptor> {
+        Self::column_family_names(sub_stores)
+            .map(String::from)
+            .chain(namespace::namespaced_cf_names(sub_stores, networks))
+            .map(|cf| ColumnFamilyDescriptor::new(cf, cf_opts.clone()))
+            .collect()
     }
 
     /// Create a snapshot of the Indexer store
@@ -272,7 +356,7 @@ impl IndexerStore {
 
         let mut snapshot_temp_dir = output_file.to_path_buf();
         snapshot_temp_dir.set_extension("tmp-snapshot");
-        Checkpoint::new(&self.database)?
+        Checkpoint::new(self.database.as_ref())?
             .create_checkpoint(&snapshot_temp_dir)
             .map_err(|e| anyhow!("Error creating database snapshot: {e}"))
             .and_then(|_| {
@@ -298,22 +382,79 @@ impl IndexerStore {
         database_opts.create_missing_column_families(true);
         database_opts.create_if_missing(true);
 
-        let column_families: Vec<ColumnFamilyDescriptor> = Self::COLUMN_FAMILIES
-            .iter()
-            .map(|cf| ColumnFamilyDescriptor::new(*cf, cf_opts.clone()))
-            .collect();
+        let sub_stores = Self::sub_stores(&[])?;
+        let namespaced_networks = namespace::existing_namespaces(primary);
+        let column_families =
+            Self::column_family_descriptors(&sub_stores, &namespaced_networks, &cf_opts);
         let read_only = Self {
             is_primary: false,
             db_path: secondary.into(),
-            database: speedb::DBWithThreadMode::open_cf_descriptors_as_secondary(
+            canonical_hash_cache: CanonicalHashCache::default(),
+            primary_path: Some(primary.into()),
+            replica_catch_up: Default::default(),
+            zkapp_notifications: tokio::sync::broadcast::channel(ZKAPP_NOTIFICATIONS_CAPACITY).0,
+            block_notifications: tokio::sync::broadcast::channel(BLOCK_NOTIFICATIONS_CAPACITY).0,
+            watch_notifications: tokio::sync::broadcast::channel(WATCH_NOTIFICATIONS_CAPACITY).0,
+            staking_ledger_batch_size: AtomicUsize::new(STAKING_LEDGER_BATCH_SIZE),
+            #[cfg(feature = "sql_mirror")]
+            sql_mirror: Default::default(),
+            database: Arc::new(speedb::DBWithThreadMode::open_cf_descriptors_as_secondary(
                 &database_opts,
                 primary,
                 secondary,
                 column_families,
-            )?,
+            )?),
+            namespace: None,
+            namespaced_networks,
+            namespaces: Default::default(),
+            sub_stores,
+            read_snapshot: None,
+            pinned_views: Default::default(),
         };
+
+        read_only.record_replica_catch_up()?;
         Ok(read_only)
     }
+
+    /// Mirrors the canonical chain to the DuckDB database at `path`, syncing
+    /// it with the store first
+    #[cfg(feature = "sql_mirror")]
+    pub fn enable_sql_mirror(&self, path: &Path) -> Result<()> {
+        let mirror = sql_mirror::SqlMirror::open(path)?;
+        mirror.sync(self)?;
+
+        self.sql_mirror
+            .set(mirror)
+            .map_err(|_| anyhow!("SQL mirror already enabled"))
+    }
+
+    #[cfg(not(feature = "sql_mirror"))]
+    pub fn enable_sql_mirror(&self, path: &Path) -> Result<()> {
+        bail!(
+            "Cannot mirror to {}, built without the sql_mirror feature",
+            path.display()
+        )
+    }
+
+    /// Applies the best chain block updates to the SQL mirror, if enabled
+    #[cfg(feature = "sql_mirror")]
+    pub(crate) fn update_sql_mirror(
+        &self,
+        blocks: &crate::block::store::DbBlockUpdate,
+    ) -> Result<()> {
+        match self.sql_mirror.get() {
+            Some(mirror) => mirror.update(self, blocks),
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(not(feature = "sql_mirror"))]
+    pub(crate) fn update_sql_mirror(
+        &self,
+        _blocks: &crate::block::store::DbBlockUpdate,
+    ) -> Result<()> {
+        Ok(())
+    }
 }
 
 /// Restore a snapshot of the Indexer store
@@ -0,0 +1,213 @@
+//! Per-network namespaces of a store directory
+//!
+//! Each network's column families are prefixed with the network's name (e.g.
+//! `devnet/blocks`), so a single database can host multiple networks, each
+//! accessed through its own [IndexerStore]. The unprefixed column families
+//! belong to the _legacy_ network: the network recorded in stores created
+//! before namespacing, or the first network selected in a fresh store. This
+//! keeps existing single-network stores readable without a migration.
+//!
+//! Cross-network queries are not supported.
+
+use super::{fixed_keys::FixedKeys, sub_store::SubStore, IndexerStore, Result};
+use crate::chain::Network;
+use anyhow::{bail, Context};
+use log::info;
+use speedb::{ColumnFamily, Options, DB};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::{Arc, Mutex, Weak},
+};
+
+/// Separates a network namespace from the column family name
+pub const NAMESPACE_SEPARATOR: char = '/';
+
+/// Name of each namespace's default column family
+pub const DEFAULT_CF: &str = "default";
+
+/// Live stores of a database's network namespaces, shared by all of them
+#[derive(Debug, Default)]
+pub struct NetworkNamespaces {
+    /// `None` is the legacy (unprefixed) namespace
+    stores: Mutex<HashMap<Option<Network>, Weak<IndexerStore>>>,
+}
+
+/// Name of the column family in the network's namespace
+pub fn namespaced_cf_name<'a>(network: Option<&Network>, cf: &'a str) -> Cow<'a, str> {
+    match network {
+        None => Cow::Borrowed(cf),
+        Some(network) => Cow::Owned(format!("{network}{NAMESPACE_SEPARATOR}{cf}")),
+    }
+}
+
+/// Networks with namespaced column families in the database at `path`
+pub fn existing_namespaces(path: &Path) -> Vec<Network> {
+    let mut networks = vec![];
+    for cf in DB::list_cf(&Options::default(), path).unwrap_or_default() {
+        if let Some((network, _)) = cf.split_once(NAMESPACE_SEPARATOR) {
+            let network = Network::from(network);
+            if !networks.contains(&network) {
+                networks.push(network);
+            }
+        }
+    }
+
+    networks
+}
+
+/// Descriptor names of the sub-stores' column families in each namespace
+pub(crate) fn namespaced_cf_names(sub_stores: &[SubStore], networks: &[Network]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut names = vec![];
+    for network in networks {
+        if !seen.insert(network) {
+            continue;
+        }
+
+        for cf in IndexerStore::column_family_names(sub_stores).chain(std::iter::once(DEFAULT_CF)) {
+            names.push(namespaced_cf_name(Some(network), cf).into_owned());
+        }
+    }
+
+    names
+}
+
+impl IndexerStore {
+    /// Network namespace of the store, `None` for the legacy namespace
+    pub fn namespace(&self) -> Option<&Network> {
+        self.namespace.as_ref()
+    }
+
+    /// Name of the column family in the store's namespace
+    pub fn cf_name<'a>(&self, cf: &'a str) -> Cow<'a, str> {
+        namespaced_cf_name(self.namespace.as_ref(), cf)
+    }
+
+    /// Handle of the column family in the store's namespace
+    pub fn namespaced_cf(&self, cf: &str) -> &ColumnFamily {
+        let cf_name = self.cf_name(cf);
+        self.database
+            .cf_handle(&cf_name)
+            .unwrap_or_else(|| panic!("{cf_name} column family exists"))
+    }
+
+    /// Default column family of the store's namespace, holding its fixed keys
+    pub fn default_cf(&self) -> &ColumnFamily {
+        self.namespaced_cf(DEFAULT_CF)
+    }
+
+    /// Network owning the legacy (unprefixed) namespace, if any
+    pub fn get_legacy_network(&self) -> Result<Option<Network>> {
+        let default_cf = self
+            .database
+            .cf_handle(DEFAULT_CF)
+            .context("default column family exists")?;
+
+        if let Some(bytes) = self.get_cf(default_cf, Self::LEGACY_NAMESPACE_NETWORK_KEY)? {
+            return Ok(Some(bytes.into()));
+        }
+
+        // stores created before namespacing record their chain's network
+        if let Some(chain_id) = self.get_cf(default_cf, Self::CHAIN_ID_KEY)? {
+            let chain_id_to_network_cf = self
+                .database
+                .cf_handle("chain-id-to-network")
+                .context("chain-id-to-network column family exists")?;
+
+            return Ok(self
+                .get_cf(chain_id_to_network_cf, chain_id)?
+                .map(Into::into));
+        }
+
+        Ok(None)
+    }
+
+    /// Networks hosted by the database, the legacy network first
+    pub fn get_networks(&self) -> Result<Vec<Network>> {
+        let mut networks: Vec<Network> = self.get_legacy_network()?.into_iter().collect();
+        for network in self.namespaced_networks.iter() {
+            if !networks.contains(network) {
+                networks.push(network.clone());
+            }
+        }
+
+        Ok(networks)
+    }
+
+    /// Store of the network's namespace, sharing this store's database
+    ///
+    /// A fresh store's legacy namespace is claimed by the first selected
+    /// network. Other networks' namespaces must be opened with the store, see
+    /// [IndexerStore::new_with_networks].
+    pub fn network_store(self: &Arc<Self>, network: &Network) -> Result<Arc<Self>> {
+        if self.namespace.as_ref() == Some(network) {
+            return Ok(self.clone());
+        }
+
+        let mut stores = self.namespaces.stores.lock().expect("namespaces lock");
+
+        // legacy namespace
+        let namespace = match self.get_legacy_network()? {
+            Some(legacy) if legacy == *network => None,
+            Some(_) => Some(network.clone()),
+            None if self.is_primary => {
+                info!("Claiming the legacy store namespace for {network}");
+                self.database.put_cf(
+                    self.database
+                        .cf_handle(DEFAULT_CF)
+                        .context("default column family exists")?,
+                    Self::LEGACY_NAMESPACE_NETWORK_KEY,
+                    network.to_string().as_bytes(),
+                )?;
+                None
+            }
+            None => bail!("No {network} namespace in {:#?}", self.db_path),
+        };
+
+        if self.namespace == namespace {
+            return Ok(self.clone());
+        }
+
+        if stores
+            .get(&self.namespace)
+            .and_then(Weak::upgrade)
+            .is_none()
+        {
+            stores.insert(self.namespace.clone(), Arc::downgrade(self));
+        }
+
+        if let Some(store) = stores.get(&namespace).and_then(Weak::upgrade) {
+            return Ok(store);
+        }
+
+        if namespace
+            .as_ref()
+            .is_some_and(|network| !self.namespaced_networks.contains(network))
+        {
+            bail!(
+                "No {network} namespace in {:#?}, open the store with it",
+                self.db_path
+            )
+        }
+
+        let store = Arc::new(self.with_namespace(namespace.clone())?);
+        stores.insert(namespace, Arc::downgrade(&store));
+        Ok(store)
+    }
+
+    /// Store of the namespace, sharing this store's database
+    fn with_namespace(&self, namespace: Option<Network>) -> Result<Self> {
+        let store = Self {
+            namespace,
+            ..self.shared()
+        };
+
+        if store.is_primary {
+            store.prepare_primary(false, false)?;
+        }
+
+        Ok(store)
+    }
+}
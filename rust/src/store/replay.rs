@@ -0,0 +1,159 @@
+//! On-demand replay of canonical blocks in a height range
+//!
+//! Blocks & canonicity are the source of truth. Everything computed from
+//! them while ingesting (ledger diffs, staged ledgers, internal commands &
+//! their counts) is derived data. When an apply bug is fixed, replaying the
+//! affected range recomputes the derived data & rewrites the entries which
+//! differ, subtracting the old contributions & adding the new ones.
+
+use super::{
+    best_ledger_store_impl::{
+        ActionStateData, BalanceHistoryData, BlockEventsData, NonceHistoryData,
+    },
+    column_families::ColumnFamilyHelpers,
+    internal_command_store_impl::InternalCommandData,
+    staged_ledger_store_impl::StagedLedgerData,
+    IndexerStore, Result,
+};
+use crate::{
+    base::state_hash::StateHash,
+    block::store::BlockStore,
+    canonicity::store::CanonicityStore,
+    ledger::{store::staged::StagedLedgerStore, Ledger},
+    utility::store::common::u32_from_be_bytes,
+};
+use anyhow::{bail, Context};
+use log::info;
+use speedb::IteratorMode;
+
+/// Canonical blocks to replay
+pub struct ReplayRange {
+    pub from_height: u32,
+    pub to_height: u32,
+
+    /// Canonical state hashes of the range, ascending by height
+    pub state_hashes: Vec<StateHash>,
+
+    /// Staged ledger of the parent of the canonical block at `from_height`
+    pub start_ledger: Ledger,
+}
+
+/// Derived data which can be rewritten for a range of canonical blocks
+pub trait DerivedData {
+    /// Name of the derived data, for logging
+    fn name(&self) -> &'static str;
+
+    /// Recompute the derived data of the range & rewrite the stored entries
+    /// which differ, returning the number of rewritten entries
+    ///
+    /// Entries derived from blocks outside the range must not be modified
+    fn rewrite_range(&self, store: &IndexerStore, range: &ReplayRange) -> Result<u32>;
+}
+
+impl IndexerStore {
+    /// All derived data writers, in replay order
+    pub const DERIVED_DATA: [&'static dyn DerivedData; 6] = [
+        &StagedLedgerData,
+        &InternalCommandData,
+        &BalanceHistoryData,
+        &NonceHistoryData,
+        &ActionStateData,
+        &BlockEventsData,
+    ];
+
+    /// Replay the canonical blocks `from_height..=to_height`, rewriting all
+    /// derived data which differs from the recomputed data
+    ///
+    /// Returns the number of rewritten entries
+    pub fn replay_range(&self, from_height: u32, to_height: u32) -> Result<u32> {
+        let range = self.get_replay_range(from_height, to_height)?;
+        self.replay(&range, &Self::DERIVED_DATA)
+    }
+
+    /// Replay the whole canonical chain, rewriting the given derived data
+    ///
+    /// Used by sub-store migrations to backfill derived data recorded by
+    /// newer versions. Returns the number of rewritten entries
+    pub fn replay_canonical_chain(&self, derived_data: &[&'static dyn DerivedData]) -> Result<u32> {
+        let first = self
+            .iterator_cf(self.canonicity_length_cf(), IteratorMode::Start)
+            .flatten()
+            .next();
+        let last = self
+            .iterator_cf(self.canonicity_length_cf(), IteratorMode::End)
+            .flatten()
+            .next();
+
+        let (Some((first, _)), Some((last, _))) = (first, last) else {
+            return Ok(0);
+        };
+
+        let from_height = u32_from_be_bytes(&first)?;
+        let to_height = u32_from_be_bytes(&last)?;
+
+        // the root of a chain not ingested from genesis has no parent staged
+        // ledger, its own staged ledger starts the replay
+        let range = match self.get_replay_range(from_height, to_height) {
+            Ok(range) => range,
+            Err(_) if from_height < to_height => {
+                self.get_replay_range(from_height + 1, to_height)?
+            }
+            Err(e) => return Err(e),
+        };
+
+        self.replay(&range, derived_data)
+    }
+
+    /// Canonical blocks `from_height..=to_height` & the staged ledger of the
+    /// first block's parent (the genesis ledger when `from_height` is 1)
+    fn get_replay_range(&self, from_height: u32, to_height: u32) -> Result<ReplayRange> {
+        if from_height == 0 || from_height > to_height {
+            bail!("Invalid replay range {from_height}..={to_height}")
+        }
+
+        let mut state_hashes = vec![];
+        for height in from_height..=to_height {
+            match self.get_canonical_hash_at_height(height)? {
+                Some(state_hash) => state_hashes.push(state_hash),
+                None => bail!("Missing canonical block at height {height}"),
+            }
+        }
+
+        let parent_hash = self
+            .get_block_parent_hash(&state_hashes[0])?
+            .with_context(|| format!("Missing parent hash of block {}", state_hashes[0]))?;
+        let start_ledger = self
+            .get_staged_ledger_at_state_hash(&parent_hash, false)?
+            .with_context(|| format!("Missing staged ledger at {parent_hash}"))?;
+
+        Ok(ReplayRange {
+            from_height,
+            to_height,
+            state_hashes,
+            start_ledger,
+        })
+    }
+
+    fn replay(
+        &self,
+        range: &ReplayRange,
+        derived_data: &[&'static dyn DerivedData],
+    ) -> Result<u32> {
+        info!(
+            "Replaying canonical blocks {}..={}",
+            range.from_height, range.to_height
+        );
+
+        let mut num_rewritten = 0;
+        for derived_data in derived_data {
+            let num = derived_data
+                .rewrite_range(self, range)
+                .with_context(|| format!("Failed to rewrite {}", derived_data.name()))?;
+
+            info!("Rewrote {num} {} entries", derived_data.name());
+            num_rewritten += num;
+        }
+
+        Ok(num_rewritten)
+    }
+}
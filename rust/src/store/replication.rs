@@ -0,0 +1,174 @@
+//! Replication lag of read-only replicas
+//!
+//! The primary records its best tip height & the time it was set in a
+//! heartbeat file in its database directory. A read-only replica records the
+//! best tip height it last caught up to & when, so the lag is the difference
+//! between the two.
+
+use super::IndexerStore;
+use crate::block::store::BlockStore;
+use anyhow::Context;
+use log::{error, trace};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Name of the primary's heartbeat file, in its database directory
+pub const PRIMARY_HEARTBEAT_FILE: &str = "primary-heartbeat.json";
+
+/// Primary best tip height & when it was set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrimaryHeartbeat {
+    pub best_tip_height: u32,
+
+    /// Unix epoch millis
+    pub updated_at: u64,
+}
+
+/// Replica best tip height as of its last successful catch-up & when it
+/// happened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplicaCatchUp {
+    pub best_tip_height: u32,
+
+    /// Unix epoch millis
+    pub caught_up_at: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplicationLag {
+    /// Number of blocks the replica's best tip is behind the primary's
+    pub blocks: u32,
+
+    /// Seconds since the replica last caught up, as of the primary's last
+    /// best tip update
+    pub seconds: u64,
+}
+
+impl ReplicationLag {
+    pub fn new(primary: &PrimaryHeartbeat, replica: &ReplicaCatchUp) -> Self {
+        Self {
+            blocks: primary
+                .best_tip_height
+                .saturating_sub(replica.best_tip_height),
+            seconds: primary.updated_at.saturating_sub(replica.caught_up_at) / 1000,
+        }
+    }
+}
+
+impl IndexerStore {
+    /// Records the primary's best tip height (no-op for replicas)
+    pub fn write_primary_heartbeat(&self, best_tip_height: u32) -> anyhow::Result<()> {
+        if !self.is_primary {
+            return Ok(());
+        }
+
+        let heartbeat = PrimaryHeartbeat {
+            best_tip_height,
+            updated_at: now_millis(),
+        };
+        let path = self.db_path.join(PRIMARY_HEARTBEAT_FILE);
+        let tmp_path = path.with_extension("tmp");
+
+        // write atomically, replicas may read at any time
+        fs::write(&tmp_path, serde_json::to_vec(&heartbeat)?)?;
+        fs::rename(&tmp_path, &path)
+            .with_context(|| format!("Failed to write primary heartbeat {path:#?}"))
+    }
+
+    /// Reads the primary's heartbeat (`None` if the primary hasn't written
+    /// one yet)
+    pub fn read_primary_heartbeat(&self) -> anyhow::Result<Option<PrimaryHeartbeat>> {
+        let Some(primary_path) = self.primary_path.as_ref() else {
+            return Ok(None);
+        };
+
+        match fs::read(primary_path.join(PRIMARY_HEARTBEAT_FILE)) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Catches a read-only replica up with its primary, recording the caught
+    /// up best tip height
+    pub fn catch_up_with_primary(&self) -> anyhow::Result<()> {
+        if let Err(e) = self.database.try_catch_up_with_primary() {
+            error!("Replica failed to catch up with primary: {e}");
+            return Err(e.into());
+        }
+
+        self.record_replica_catch_up()
+    }
+
+    /// Records the replica's current best tip height as caught up
+    pub(crate) fn record_replica_catch_up(&self) -> anyhow::Result<()> {
+        let best_tip_height = self.get_best_block_height()?.unwrap_or_default();
+
+        trace!("Replica caught up to best tip height {best_tip_height}");
+        *self.replica_catch_up.lock().unwrap() = Some(ReplicaCatchUp {
+            best_tip_height,
+            caught_up_at: now_millis(),
+        });
+
+        Ok(())
+    }
+
+    /// Replica's lag behind its primary (`None` for the primary or before
+    /// either side has a best tip)
+    pub fn replication_lag(&self) -> anyhow::Result<Option<ReplicationLag>> {
+        if self.is_primary {
+            return Ok(None);
+        }
+
+        let replica = *self.replica_catch_up.lock().unwrap();
+        Ok(self
+            .read_primary_heartbeat()?
+            .zip(replica)
+            .map(|(primary, replica)| ReplicationLag::new(&primary, &replica)))
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("time went backwards")
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lag() {
+        let primary = PrimaryHeartbeat {
+            best_tip_height: 110,
+            updated_at: 75_000,
+        };
+
+        let replica = ReplicaCatchUp {
+            best_tip_height: 100,
+            caught_up_at: 45_500,
+        };
+        assert_eq!(
+            ReplicationLag::new(&primary, &replica),
+            ReplicationLag {
+                blocks: 10,
+                seconds: 29
+            }
+        );
+
+        // replica caught up after the primary's last update
+        let replica = ReplicaCatchUp {
+            best_tip_height: 110,
+            caught_up_at: 80_000,
+        };
+        assert_eq!(
+            ReplicationLag::new(&primary, &replica),
+            ReplicationLag::default()
+        );
+    }
+}
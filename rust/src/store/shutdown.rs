@@ -0,0 +1,160 @@
+//! Clean shutdown marker & unclean shutdown recovery
+//!
+//! The marker is cleared when an indexer starts & set once it stops
+//! ingesting, after its memtables & WAL are flushed. A cleared marker on
+//! startup means in-flight store writes & event log appends may have been
+//! torn, so the tail of the event log is validated & the canonical blocks it
+//! references are verified & replayed.
+
+use super::{column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, IndexerStore, Result};
+use crate::{
+    base::state_hash::StateHash,
+    event::{
+        db::{DbBlockEvent, DbCanonicityEvent, DbEvent},
+        store::EventStore,
+        IndexerEvent,
+    },
+    utility::store::common::u32_from_be_bytes,
+};
+use log::{error, info, warn};
+use speedb::{Direction, IteratorMode, WriteOptions};
+
+/// Summary of an event log tail validation
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EventLogTailReport {
+    pub from_seq_num: u32,
+    pub next_seq_num: u32,
+
+    /// Events appended past the next sequence number, removed
+    pub num_dangling: u32,
+
+    /// Sequence numbers of unreadable events & events of missing blocks
+    pub invalid: Vec<u32>,
+
+    /// Canonical heights of the tail's canonical block events
+    pub canonical_heights: Option<(u32, u32)>,
+}
+
+impl IndexerStore {
+    /// Whether the indexer last stopped with a clean shutdown
+    pub fn is_clean_shutdown(&self) -> Result<bool> {
+        Ok(self
+            .get_pinned_cf(self.default_cf(), Self::CLEAN_SHUTDOWN_KEY)?
+            .is_some_and(|bytes| bytes.first() == Some(&1)))
+    }
+
+    /// Clear the clean shutdown marker of a running indexer
+    pub fn set_running(&self) -> Result<()> {
+        self.put_clean_shutdown(false)
+    }
+
+    /// Flush the memtables & WAL, then set the clean shutdown marker
+    ///
+    /// Callers must hold the state's write lock so no writes are in flight
+    pub fn clean_shutdown(&self) -> Result<()> {
+        info!("Flushing indexer store");
+        self.database.flush_wal(true)?;
+        self.database.flush()?;
+        self.put_clean_shutdown(true)
+    }
+
+    fn put_clean_shutdown(&self, clean: bool) -> Result<()> {
+        let mut opts = WriteOptions::default();
+        opts.set_sync(true);
+
+        Ok(self.database.put_cf_opt(
+            self.default_cf(),
+            Self::CLEAN_SHUTDOWN_KEY,
+            [clean as u8],
+            &opts,
+        )?)
+    }
+
+    /// Validate the last `num_events` events of the log, removing events
+    /// appended past the next sequence number
+    pub fn validate_event_log_tail(&self, num_events: u32) -> Result<EventLogTailReport> {
+        let next_seq_num = self.get_next_seq_num()?;
+        let mut report = EventLogTailReport {
+            from_seq_num: next_seq_num.saturating_sub(num_events),
+            next_seq_num,
+            ..Default::default()
+        };
+
+        // torn appends, the sequence number wasn't incremented
+        for (key, _) in self
+            .event_log_iterator(IteratorMode::From(
+                &next_seq_num.to_be_bytes(),
+                Direction::Forward,
+            ))
+            .flatten()
+        {
+            warn!("Removing dangling event {}", u32_from_be_bytes(&key)?);
+            self.database.delete_cf(self.events_cf(), key)?;
+            report.num_dangling += 1;
+        }
+
+        for seq_num in report.from_seq_num..next_seq_num {
+            let Some(event) = self.get_event(seq_num)? else {
+                warn!("Missing or unreadable event {seq_num}");
+                report.invalid.push(seq_num);
+                continue;
+            };
+
+            let (state_hash, canonical_height) = match event {
+                IndexerEvent::Db(DbEvent::Block(
+                    DbBlockEvent::NewBlock { state_hash, .. }
+                    | DbBlockEvent::NewBestTip { state_hash, .. },
+                )) => (state_hash, None),
+                IndexerEvent::Db(DbEvent::Canonicity(DbCanonicityEvent::NewCanonicalBlock {
+                    state_hash,
+                    blockchain_length,
+                })) => (state_hash, Some(blockchain_length)),
+                _ => continue,
+            };
+
+            if !self.is_block_stored(&state_hash)? {
+                warn!("Event {seq_num} references missing block {state_hash}");
+                report.invalid.push(seq_num);
+                continue;
+            }
+
+            if let Some(height) = canonical_height {
+                report.canonical_heights = Some(match report.canonical_heights {
+                    Some((min, max)) => (min.min(height), max.max(height)),
+                    None => (height, height),
+                });
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Validate the event log tail, then verify the canonicity of & replay
+    /// the canonical blocks it references
+    pub fn recover_unclean_shutdown(&self, num_events: u32) -> Result<EventLogTailReport> {
+        let report = self.validate_event_log_tail(num_events)?;
+        info!(
+            "Validated events {}..{}: {} dangling, {} invalid",
+            report.from_seq_num,
+            report.next_seq_num,
+            report.num_dangling,
+            report.invalid.len()
+        );
+
+        if let Some((min_height, max_height)) = report.canonical_heights {
+            self.record_canonicity_verification(min_height)?;
+
+            if let Err(e) = self.replay_range(min_height, max_height) {
+                error!("Failed to replay canonical blocks {min_height}..={max_height}: {e}");
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn is_block_stored(&self, state_hash: &StateHash) -> Result<bool> {
+        Ok(self
+            .get_pinned_cf(self.blocks_state_hash_cf(), state_hash.0.as_bytes())?
+            .is_some())
+    }
+}
@@ -0,0 +1,196 @@
+//! Snapshot-consistent store views
+//!
+//! A pinned view reads the database as of a speedb snapshot, so multiple
+//! queries see a single consistent state while blocks are being ingested.
+//! Pinned views bypass the canonical hash cache, which tracks the live
+//! canonical chain.
+//!
+//! Recently pinned views are retained by their best tip (see
+//! [PINNED_VIEWS_CAPACITY] & [PINNED_VIEW_TTL]) so later requests can be
+//! pinned to the same state. Retained snapshots hold back compaction of the
+//! data they reference.
+
+use super::IndexerStore;
+use crate::{base::state_hash::StateHash, block::store::BlockStore};
+use anyhow::{bail, Context};
+use log::trace;
+use self_cell::self_cell;
+use speedb::{
+    AsColumnFamilyRef, DBIteratorWithThreadMode, DBPinnableSlice, IteratorMode, ReadOptions,
+    SnapshotWithThreadMode, DB,
+};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Max number of retained pinned views
+pub const PINNED_VIEWS_CAPACITY: usize = 16;
+
+/// Duration pinned views are retained for
+pub const PINNED_VIEW_TTL: Duration = Duration::from_secs(60);
+
+type Snapshot<'a> = SnapshotWithThreadMode<'a, DB>;
+
+self_cell!(
+    /// Snapshot of the database, keeping it alive
+    pub struct ReadSnapshot {
+        owner: Arc<DB>,
+
+        #[covariant]
+        dependent: Snapshot,
+    }
+);
+
+/// Recently pinned views, oldest first
+#[derive(Debug, Default)]
+pub struct PinnedViews {
+    views: Mutex<VecDeque<PinnedView>>,
+}
+
+#[derive(Debug)]
+struct PinnedView {
+    best_tip: StateHash,
+    pinned_at: Instant,
+    store: Arc<IndexerStore>,
+}
+
+impl ReadSnapshot {
+    pub fn snapshot(database: Arc<DB>) -> Self {
+        Self::new(database, |database| database.snapshot())
+    }
+
+    fn read_opts(&self) -> ReadOptions {
+        let mut read_opts = ReadOptions::default();
+        read_opts.set_snapshot(self.borrow_dependent());
+        read_opts
+    }
+}
+
+impl std::fmt::Debug for ReadSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReadSnapshot").finish_non_exhaustive()
+    }
+}
+
+impl IndexerStore {
+    /// Whether the store's reads are pinned to a snapshot
+    pub fn is_pinned(&self) -> bool {
+        self.read_snapshot.is_some()
+    }
+
+    /// View pinned to the database's current state, along with its best tip
+    pub fn pin(&self) -> anyhow::Result<(StateHash, Arc<Self>)> {
+        let store = Arc::new(Self {
+            read_snapshot: Some(Arc::new(ReadSnapshot::snapshot(self.database.clone()))),
+            ..self.shared()
+        });
+        let best_tip = store
+            .get_best_block_hash()?
+            .context("No best tip to pin the store to")?;
+
+        trace!("Pinned store view at {best_tip}");
+        let mut views = self.pinned_views.views.lock().expect("pinned views lock");
+        prune_pinned_views(&mut views);
+
+        views.retain(|view| view.best_tip != best_tip);
+        if views.len() >= PINNED_VIEWS_CAPACITY {
+            views.pop_front();
+        }
+
+        views.push_back(PinnedView {
+            best_tip: best_tip.clone(),
+            pinned_at: Instant::now(),
+            store: store.clone(),
+        });
+
+        Ok((best_tip, store))
+    }
+
+    /// Recently pinned view of the best tip `state_hash`, or a newly pinned
+    /// view if it's the current best tip
+    pub fn pinned_at(&self, state_hash: &StateHash) -> anyhow::Result<Arc<Self>> {
+        {
+            let mut views = self.pinned_views.views.lock().expect("pinned views lock");
+            prune_pinned_views(&mut views);
+
+            if let Some(view) = views.iter().find(|view| view.best_tip == *state_hash) {
+                return Ok(view.store.clone());
+            }
+        }
+
+        let (best_tip, store) = self.pin()?;
+        if best_tip != *state_hash {
+            bail!("No pinned view at {state_hash}, the best tip is now {best_tip}")
+        }
+
+        Ok(store)
+    }
+
+    //////////////////////////
+    // snapshot-aware reads //
+    //////////////////////////
+
+    pub fn get_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        key: K,
+    ) -> Result<Option<Vec<u8>>, speedb::Error> {
+        match self.read_snapshot.as_ref() {
+            Some(snapshot) => self.database.get_cf_opt(cf, key, &snapshot.read_opts()),
+            None => self.database.get_cf(cf, key),
+        }
+    }
+
+    pub fn get_pinned_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &impl AsColumnFamilyRef,
+        key: K,
+    ) -> Result<Option<DBPinnableSlice<'_>>, speedb::Error> {
+        match self.read_snapshot.as_ref() {
+            Some(snapshot) => self
+                .database
+                .get_pinned_cf_opt(cf, key, &snapshot.read_opts()),
+            None => self.database.get_pinned_cf(cf, key),
+        }
+    }
+
+    pub fn iterator_cf<'a: 'b, 'b>(
+        &'a self,
+        cf: &impl AsColumnFamilyRef,
+        mode: IteratorMode,
+    ) -> DBIteratorWithThreadMode<'b, DB> {
+        match self.read_snapshot.as_ref() {
+            Some(snapshot) => self
+                .database
+                .iterator_cf_opt(cf, snapshot.read_opts(), mode),
+            None => self.database.iterator_cf(cf, mode),
+        }
+    }
+
+    pub fn multi_get_cf<'a, 'b: 'a, K, I, W>(
+        &'a self,
+        keys: I,
+    ) -> Vec<Result<Option<Vec<u8>>, speedb::Error>>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (&'b W, K)>,
+        W: 'b + AsColumnFamilyRef,
+    {
+        match self.read_snapshot.as_ref() {
+            Some(snapshot) => self.database.multi_get_cf_opt(keys, &snapshot.read_opts()),
+            None => self.database.multi_get_cf(keys),
+        }
+    }
+}
+
+/// Drops expired pinned views
+fn prune_pinned_views(views: &mut VecDeque<PinnedView>) {
+    while views
+        .front()
+        .is_some_and(|view| view.pinned_at.elapsed() > PINNED_VIEW_TTL)
+    {
+        views.pop_front();
+    }
+}
@@ -1,4 +1,7 @@
-use super::{column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, DbUpdate, IndexerStore};
+use super::{
+    column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, sub_store::SubStore, DbUpdate,
+    IndexerStore,
+};
 use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
     block::{
@@ -6,9 +9,12 @@ use crate::{
         store::{BlockStore, BlockUpdate, DbBlockUpdate},
     },
     canonicity::store::CanonicityStore,
-    constants::MAINNET_EPOCH_SLOT_COUNT,
+    chain::store::ChainStore,
     snark_work::{
-        store::{DbSnarkUpdate, SnarkApplication, SnarkProverFees, SnarkStore, SnarkUpdate},
+        store::{
+            DbSnarkUpdate, SnarkApplication, SnarkProverFees, SnarkProverJob, SnarkProverJobTotals,
+            SnarkStore, SnarkUpdate,
+        },
         SnarkWorkSummary, SnarkWorkSummaryWithStateHash, SnarkWorkTotal,
     },
     utility::store::{
@@ -21,9 +27,86 @@ use crate::{
 };
 use log::trace;
 use serde::{Deserialize, Serialize};
-use speedb::{DBIterator, Direction, IteratorMode};
+use speedb::{DBIterator, Direction, IteratorMode, WriteBatch};
 use std::collections::HashMap;
 
+/// Add the corresponding CF helpers to [ColumnFamilyHelpers] & bump the
+/// sub-store version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "snarks",
+    version: 2,
+    column_families: &[
+        "snarks",
+        "snarks-prover",
+        "snark-prover-fees",
+        "snark-prover-fees-epoch",
+        "snark-prover-fees-historical",
+        "snark-prover-fees-epoch-historical",
+        "snark-prover-total-fees-sort",
+        "snark-prover-total-fees-epoch-sort",
+        "snark-prover-max-fee",
+        "snark-prover-max-fee-epoch",
+        "snark-prover-max-fee-sort",
+        "snark-prover-max-fee-epoch-sort",
+        "snark-prover-min-fee",
+        "snark-prover-min-fee-epoch",
+        "snark-prover-min-fee-sort",
+        "snark-prover-min-fee-epoch-sort",
+        "snark-prover-block-height-sort",
+        "snark-prover-global-slot-sort",
+        "snark-work-fees-block-height-sort",
+        "snark-work-fees-global-slot-sort",
+        "snark-prover-jobs",
+        "snark-prover-jobs-epoch",
+        "snark-prover-jobs-epoch-sort",
+        "block-snark-counts",
+        // SNARK counts
+        "snarks-epoch",
+        "snarks-pk-epoch",
+        "snarks-pk-total",
+    ],
+    dependencies: &["blocks"],
+    migrate,
+};
+
+/// Version 1 stores don't index SNARK jobs by prover, the best chain's jobs
+/// are backfilled from the best tip
+fn migrate(store: &IndexerStore, from_version: u32) -> anyhow::Result<()> {
+    match from_version {
+        1 => migrate_prover_jobs(store),
+        _ => SubStore::no_migration(store, from_version),
+    }
+}
+
+fn migrate_prover_jobs(store: &IndexerStore) -> anyhow::Result<()> {
+    let mut state_hash = store.get_best_block_hash()?;
+    while let Some(hash) = state_hash {
+        let (Some(blockchain_length), Some(global_slot_since_genesis)) = (
+            store.get_block_height(&hash)?,
+            store.get_block_global_slot(&hash)?,
+        ) else {
+            break;
+        };
+
+        let works = store.get_block_snark_work(&hash)?.unwrap_or_default();
+        if !works.is_empty() {
+            store.update_snark_prover_jobs(
+                &SnarkUpdate {
+                    state_hash: hash.clone(),
+                    blockchain_length,
+                    global_slot_since_genesis,
+                    works,
+                },
+                &SnarkApplication::Apply,
+            )?;
+        }
+
+        state_hash = store.get_block_parent_hash(&hash)?;
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SnarkAllTimeFees {
     pub total: u64,
@@ -112,7 +195,6 @@ impl SnarkStore for IndexerStore {
             // increment SNARK counts
             for (index, snark) in block_pk_snarks.iter().enumerate() {
                 if self
-                    .database
                     .get_pinned_cf(
                         self.snark_prover_block_height_sort_cf(),
                         snark_prover_sort_key(&pk, block_height, index as u32),
@@ -135,6 +217,50 @@ impl SnarkStore for IndexerStore {
         Ok(())
     }
 
+    fn remove_snark_work_batch(
+        &self,
+        blocks: &[PrecomputedBlock],
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()> {
+        for block in blocks {
+            trace!("Removing SNARK work from block {}", block.summary());
+            let state_hash = block.state_hash();
+            let global_slot = block.global_slot_since_genesis();
+            let block_height = block.blockchain_length();
+
+            batch.delete_cf(self.block_snark_counts_cf(), state_hash.0.as_bytes());
+
+            let mut num_prover_works: HashMap<PublicKey, u32> = HashMap::new();
+            for (index, snark) in SnarkWorkSummary::from_precomputed(block).iter().enumerate() {
+                batch.delete_cf(self.snarks_cf(), block_index_key(&state_hash, index as u32));
+
+                let prover_index = num_prover_works.entry(snark.prover.clone()).or_default();
+                batch.delete_cf(
+                    self.snark_work_fees_block_height_sort_cf(),
+                    snark_fee_sort_key(
+                        snark.fee.0,
+                        block_height,
+                        &snark.prover,
+                        &state_hash,
+                        *prover_index,
+                    ),
+                );
+                batch.delete_cf(
+                    self.snark_work_fees_global_slot_sort_cf(),
+                    snark_fee_sort_key(
+                        snark.fee.0,
+                        global_slot,
+                        &snark.prover,
+                        &state_hash,
+                        *prover_index,
+                    ),
+                );
+                *prover_index += 1;
+            }
+        }
+        Ok(())
+    }
+
     fn get_snark_work_by_public_key(
         &self,
         pk: &PublicKey,
@@ -143,8 +269,7 @@ impl SnarkStore for IndexerStore {
         let mut snarks = vec![];
         for index in 0..self.get_snarks_pk_total_count(pk)? {
             snarks.push(
-                self.database
-                    .get_pinned_cf(self.snarks_prover_cf(), pk_index_key(pk, index))?
+                self.get_pinned_cf(self.snarks_prover_cf(), pk_index_key(pk, index))?
                     .map(|bytes| {
                         serde_json::from_slice::<SnarkWorkSummaryWithStateHash>(&bytes)
                             .expect("SNARK work with state hash")
@@ -164,7 +289,6 @@ impl SnarkStore for IndexerStore {
         if let Some(num) = self.get_block_snarks_count(state_hash)? {
             for index in 0..num {
                 let snark = self
-                    .database
                     .get_pinned_cf(self.snarks_cf(), block_index_key(state_hash, index))?
                     .map(|bytes| {
                         serde_json::from_slice(&bytes).expect("SnarkWorkSummary serde bytes")
@@ -214,7 +338,7 @@ impl SnarkStore for IndexerStore {
         apply: SnarkApplication,
     ) -> anyhow::Result<()> {
         trace!("Updating SNARK prover fees");
-        let epoch = global_slot / MAINNET_EPOCH_SLOT_COUNT;
+        let epoch = self.get_chain_constants()?.epoch(global_slot);
         let block_height_opt = match apply {
             SnarkApplication::Apply => None,
             SnarkApplication::Unapply => Some(block_height),
@@ -575,7 +699,6 @@ impl SnarkStore for IndexerStore {
         trace!("Getting SNARK total fees for {pk}");
         Ok(match block_height {
             None => self
-                .database
                 .get_pinned_cf(self.snark_prover_fees_cf(), pk.0.as_bytes())?
                 .map(|bytes| u64_from_be_bytes(&bytes).expect("SNARK total fees")),
             Some(block_height) => self
@@ -598,7 +721,6 @@ impl SnarkStore for IndexerStore {
         trace!("Getting SNARK epoch {epoch} fees for {pk}");
         Ok(match block_height {
             None => self
-                .database
                 .get_pinned_cf(
                     self.snark_prover_fees_epoch_cf(),
                     snark_epoch_key(epoch, pk),
@@ -622,7 +744,6 @@ impl SnarkStore for IndexerStore {
         trace!("Getting SNARK max fee for {pk}");
         Ok(match block_height {
             None => self
-                .database
                 .get_pinned_cf(self.snark_prover_max_fee_cf(), pk.0.as_bytes())?
                 .map(|bytes| u64_from_be_bytes(&bytes).expect("SNARK max fee")),
             Some(block_height) => self
@@ -645,7 +766,6 @@ impl SnarkStore for IndexerStore {
         trace!("Getting SNARK epoch {epoch} fees for {pk}");
         Ok(match block_height {
             None => self
-                .database
                 .get_pinned_cf(
                     self.snark_prover_max_fee_epoch_cf(),
                     snark_epoch_key(epoch, pk),
@@ -669,7 +789,6 @@ impl SnarkStore for IndexerStore {
         trace!("Getting SNARK min fee for {pk}");
         Ok(match block_height {
             None => self
-                .database
                 .get_pinned_cf(self.snark_prover_min_fee_cf(), pk.0.as_bytes())?
                 .map(|bytes| u64_from_be_bytes(&bytes).expect("SNARK min fee")),
             Some(block_height) => self
@@ -692,7 +811,6 @@ impl SnarkStore for IndexerStore {
         trace!("Getting SNARK epoch {epoch} fees for {pk}");
         Ok(match block_height {
             None => self
-                .database
                 .get_pinned_cf(
                     self.snark_prover_min_fee_epoch_cf(),
                     snark_epoch_key(epoch, pk),
@@ -758,6 +876,7 @@ impl SnarkStore for IndexerStore {
         // unapply
         for snark_update in update.unapply {
             self.decrement_snarks_total_canonical_count(snark_update.works.len() as u32)?;
+            self.update_snark_prover_jobs(&snark_update, &SnarkApplication::Unapply)?;
             self.update_snark_prover_fees(
                 snark_update.blockchain_length,
                 snark_update.global_slot_since_genesis,
@@ -769,6 +888,7 @@ impl SnarkStore for IndexerStore {
         // apply
         for snark_update in update.apply {
             self.increment_snarks_total_canonical_count(snark_update.works.len() as u32)?;
+            self.update_snark_prover_jobs(&snark_update, &SnarkApplication::Apply)?;
             self.update_snark_prover_fees(
                 snark_update.blockchain_length,
                 snark_update.global_slot_since_genesis,
@@ -793,14 +913,12 @@ impl SnarkStore for IndexerStore {
         // use appropriate CF for iteration
         let mut iter = match epoch {
             None => self
-                .database
                 .iterator_cf(
                     self.snark_prover_fees_historical_cf(),
                     IteratorMode::From(&start, Direction::Reverse),
                 )
                 .flatten(),
             Some(_) => self
-                .database
                 .iterator_cf(
                     self.snark_prover_fees_epoch_historical_cf(),
                     IteratorMode::From(&start, Direction::Reverse),
@@ -832,23 +950,162 @@ impl SnarkStore for IndexerStore {
         Ok(None)
     }
 
+    /////////////////
+    // Prover jobs //
+    /////////////////
+
+    fn update_snark_prover_jobs(
+        &self,
+        update: &SnarkUpdate,
+        apply: &SnarkApplication,
+    ) -> anyhow::Result<()> {
+        trace!(
+            "Updating SNARK prover jobs of block {} (length {})",
+            update.state_hash,
+            update.blockchain_length
+        );
+        let epoch = self
+            .get_chain_constants()?
+            .epoch(update.global_slot_since_genesis);
+        let mut batch = WriteBatch::default();
+
+        // block's per prover job counts & fees
+        let mut block_totals: HashMap<PublicKey, SnarkProverJobTotals> = HashMap::new();
+        for (index, snark) in update.works.iter().enumerate() {
+            let key = snark_prover_job_key(
+                &snark.prover,
+                update.blockchain_length,
+                &update.state_hash,
+                index as u32,
+            );
+            match apply {
+                SnarkApplication::Apply => {
+                    batch.put_cf(self.snark_prover_jobs_cf(), key, snark.fee.0.to_be_bytes())
+                }
+                SnarkApplication::Unapply => batch.delete_cf(self.snark_prover_jobs_cf(), key),
+            }
+
+            let totals = block_totals.entry(snark.prover.clone()).or_default();
+            totals.num_jobs += 1;
+            totals.total_fees += snark.fee.0;
+        }
+
+        for (prover, block) in block_totals {
+            let old = self.get_snark_prover_epoch_jobs(&prover, epoch)?;
+            let new = match apply {
+                SnarkApplication::Apply => SnarkProverJobTotals {
+                    num_jobs: old.num_jobs + block.num_jobs,
+                    total_fees: old.total_fees + block.total_fees,
+                },
+                SnarkApplication::Unapply => SnarkProverJobTotals {
+                    num_jobs: old.num_jobs.saturating_sub(block.num_jobs),
+                    total_fees: old.total_fees.saturating_sub(block.total_fees),
+                },
+            };
+
+            batch.delete_cf(
+                self.snark_prover_jobs_epoch_sort_cf(),
+                snark_fee_epoch_sort_key(epoch, old.total_fees, &prover),
+            );
+
+            if new.num_jobs == 0 {
+                batch.delete_cf(
+                    self.snark_prover_jobs_epoch_cf(),
+                    snark_epoch_key(epoch, &prover),
+                );
+                continue;
+            }
+
+            let mut value = [0; U32_LEN + U64_LEN];
+            value[..U32_LEN].copy_from_slice(&new.num_jobs.to_be_bytes());
+            value[U32_LEN..].copy_from_slice(&new.total_fees.to_be_bytes());
+
+            batch.put_cf(
+                self.snark_prover_jobs_epoch_cf(),
+                snark_epoch_key(epoch, &prover),
+                value,
+            );
+            batch.put_cf(
+                self.snark_prover_jobs_epoch_sort_cf(),
+                snark_fee_epoch_sort_key(epoch, new.total_fees, &prover),
+                b"",
+            );
+        }
+
+        Ok(self.database.write(batch)?)
+    }
+
+    fn get_snark_prover_jobs(
+        &self,
+        prover: &PublicKey,
+        from_height: u32,
+        to_height: u32,
+    ) -> anyhow::Result<Vec<SnarkProverJob>> {
+        let mut start = [0; PublicKey::LEN + U32_LEN];
+        start[..PublicKey::LEN].copy_from_slice(prover.0.as_bytes());
+        start[PublicKey::LEN..].copy_from_slice(&from_height.to_be_bytes());
+
+        let mut jobs = vec![];
+        for (key, value) in self
+            .iterator_cf(
+                self.snark_prover_jobs_cf(),
+                IteratorMode::From(&start, Direction::Forward),
+            )
+            .flatten()
+        {
+            if &key[..PublicKey::LEN] != prover.0.as_bytes() {
+                break;
+            }
+
+            let block_height = u32_from_be_bytes(&key[PublicKey::LEN..][..U32_LEN])?;
+            if block_height > to_height {
+                break;
+            }
+
+            jobs.push(SnarkProverJob {
+                block_height,
+                state_hash: StateHash::from_bytes(
+                    &key[PublicKey::LEN..][U32_LEN..][..StateHash::LEN],
+                )?,
+                index: u32_from_be_bytes(&key[PublicKey::LEN..][U32_LEN..][StateHash::LEN..])?,
+                fee: u64_from_be_bytes(&value)?,
+            });
+        }
+
+        Ok(jobs)
+    }
+
+    fn get_snark_prover_epoch_jobs(
+        &self,
+        prover: &PublicKey,
+        epoch: u32,
+    ) -> anyhow::Result<SnarkProverJobTotals> {
+        match self.get_pinned_cf(
+            self.snark_prover_jobs_epoch_cf(),
+            snark_epoch_key(epoch, prover),
+        )? {
+            Some(bytes) => Ok(SnarkProverJobTotals {
+                num_jobs: u32_from_be_bytes(&bytes[..U32_LEN])?,
+                total_fees: u64_from_be_bytes(&bytes[U32_LEN..])?,
+            }),
+            None => Ok(SnarkProverJobTotals::default()),
+        }
+    }
+
     ///////////////
     // Iterators //
     ///////////////
 
     fn snark_fees_block_height_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.snark_work_fees_block_height_sort_cf(), mode)
+        self.iterator_cf(self.snark_work_fees_block_height_sort_cf(), mode)
     }
 
     fn snark_fees_global_slot_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.snark_work_fees_global_slot_sort_cf(), mode)
+        self.iterator_cf(self.snark_work_fees_global_slot_sort_cf(), mode)
     }
 
     fn snark_prover_max_fee_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.snark_prover_max_fee_sort_cf(), mode)
+        self.iterator_cf(self.snark_prover_max_fee_sort_cf(), mode)
     }
 
     fn snark_prover_max_fee_epoch_iterator(
@@ -856,15 +1113,14 @@ impl SnarkStore for IndexerStore {
         epoch: u32,
         direction: Direction,
     ) -> DBIterator<'_> {
-        self.database.iterator_cf(
+        self.iterator_cf(
             self.snark_prover_max_fee_epoch_sort_cf(),
             IteratorMode::From(&start_key(epoch, direction), direction),
         )
     }
 
     fn snark_prover_min_fee_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.snark_prover_min_fee_sort_cf(), mode)
+        self.iterator_cf(self.snark_prover_min_fee_sort_cf(), mode)
     }
 
     fn snark_prover_min_fee_epoch_iterator(
@@ -872,15 +1128,14 @@ impl SnarkStore for IndexerStore {
         epoch: u32,
         direction: Direction,
     ) -> DBIterator<'_> {
-        self.database.iterator_cf(
+        self.iterator_cf(
             self.snark_prover_min_fee_epoch_sort_cf(),
             IteratorMode::From(&start_key(epoch, direction), direction),
         )
     }
 
     fn snark_prover_total_fees_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.snark_prover_total_fees_sort_cf(), mode)
+        self.iterator_cf(self.snark_prover_total_fees_sort_cf(), mode)
     }
 
     fn snark_prover_total_fees_epoch_iterator(
@@ -888,12 +1143,19 @@ impl SnarkStore for IndexerStore {
         epoch: u32,
         direction: Direction,
     ) -> DBIterator<'_> {
-        self.database.iterator_cf(
+        self.iterator_cf(
             self.snark_prover_total_fees_epoch_sort_cf(),
             IteratorMode::From(&start_key(epoch, direction), direction),
         )
     }
 
+    fn snark_prover_jobs_epoch_iterator(&self, epoch: u32, direction: Direction) -> DBIterator<'_> {
+        self.iterator_cf(
+            self.snark_prover_jobs_epoch_sort_cf(),
+            IteratorMode::From(&start_key(epoch, direction), direction),
+        )
+    }
+
     /// Iterator over SNARKs by prover & block height
     /// ```
     /// key: {prover}{block_height}{index}{state_hash}
@@ -904,8 +1166,7 @@ impl SnarkStore for IndexerStore {
     /// - index:        u32 BE bytes
     /// - snark:        [SnarkWorkSummary] serde bytes
     fn snark_prover_block_height_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.snark_prover_block_height_sort_cf(), mode)
+        self.iterator_cf(self.snark_prover_block_height_sort_cf(), mode)
     }
 
     /// Iterator over SNARKs by prover & global slot
@@ -918,8 +1179,7 @@ impl SnarkStore for IndexerStore {
     /// - index:       u32 BE bytes
     /// - snark:       [SnarkWorkSummary] serde bytes
     fn snark_prover_global_slot_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.snark_prover_global_slot_sort_cf(), mode)
+        self.iterator_cf(self.snark_prover_global_slot_sort_cf(), mode)
     }
 
     //////////////////
@@ -930,7 +1190,6 @@ impl SnarkStore for IndexerStore {
         let epoch = epoch.unwrap_or_else(|| self.get_current_epoch().expect("current epoch"));
         trace!("Getting epoch {epoch} SNARKs count");
         Ok(self
-            .database
             .get_pinned_cf(self.snarks_epoch_cf(), epoch.to_be_bytes())?
             .map_or(0, |bytes| {
                 u32_from_be_bytes(&bytes).expect("epoch SNARK count")
@@ -950,8 +1209,7 @@ impl SnarkStore for IndexerStore {
     fn get_snarks_total_count(&self) -> anyhow::Result<u32> {
         trace!("Getting total SNARKs count");
         Ok(self
-            .database
-            .get_pinned(Self::TOTAL_NUM_SNARKS_KEY)?
+            .get_pinned_cf(self.default_cf(), Self::TOTAL_NUM_SNARKS_KEY)?
             .map_or(0, |bytes| {
                 u32_from_be_bytes(&bytes).expect("total SNARK count")
             }))
@@ -960,8 +1218,7 @@ impl SnarkStore for IndexerStore {
     fn get_snarks_total_canonical_count(&self) -> anyhow::Result<u32> {
         trace!("Getting total canonical SNARKs count");
         Ok(self
-            .database
-            .get_pinned(Self::TOTAL_NUM_CANONICAL_SNARKS_KEY)?
+            .get_pinned_cf(self.default_cf(), Self::TOTAL_NUM_CANONICAL_SNARKS_KEY)?
             .map_or(0, |bytes| {
                 u32_from_be_bytes(&bytes).expect("total canonical SNARK count")
             }))
@@ -973,7 +1230,8 @@ impl SnarkStore for IndexerStore {
             .get_snarks_total_canonical_count()
             .ok()
             .unwrap_or_default();
-        Ok(self.database.put(
+        Ok(self.database.put_cf(
+            self.default_cf(),
             Self::TOTAL_NUM_CANONICAL_SNARKS_KEY,
             (old + incr).to_be_bytes(),
         )?)
@@ -985,7 +1243,8 @@ impl SnarkStore for IndexerStore {
             .get_snarks_total_canonical_count()
             .ok()
             .unwrap_or_default();
-        Ok(self.database.put(
+        Ok(self.database.put_cf(
+            self.default_cf(),
             Self::TOTAL_NUM_CANONICAL_SNARKS_KEY,
             (old.saturating_sub(decr)).to_be_bytes(),
         )?)
@@ -994,16 +1253,17 @@ impl SnarkStore for IndexerStore {
     fn increment_snarks_total_count(&self) -> anyhow::Result<()> {
         trace!("Incrementing total SNARKs count");
         let old = self.get_snarks_total_count()?;
-        Ok(self
-            .database
-            .put(Self::TOTAL_NUM_SNARKS_KEY, (old + 1).to_be_bytes())?)
+        Ok(self.database.put_cf(
+            self.default_cf(),
+            Self::TOTAL_NUM_SNARKS_KEY,
+            (old + 1).to_be_bytes(),
+        )?)
     }
 
     fn get_snarks_pk_epoch_count(&self, pk: &PublicKey, epoch: Option<u32>) -> anyhow::Result<u32> {
         let epoch = epoch.unwrap_or_else(|| self.get_current_epoch().expect("current epoch"));
         trace!("Getting pk epoch {epoch} SNARKs count {pk}");
         Ok(self
-            .database
             .get_pinned_cf(self.snarks_pk_epoch_cf(), u32_prefix_key(epoch, pk))?
             .map_or(0, |bytes| {
                 u32_from_be_bytes(&bytes).expect("pk epoch SNARK count")
@@ -1023,7 +1283,6 @@ impl SnarkStore for IndexerStore {
     fn get_snarks_pk_total_count(&self, pk: &PublicKey) -> anyhow::Result<u32> {
         trace!("Getting pk total SNARKs count {pk}");
         Ok(self
-            .database
             .get_pinned_cf(self.snarks_pk_total_cf(), pk.0.as_bytes())?
             .map_or(0, |bytes| {
                 u32_from_be_bytes(&bytes).expect("pk total SNARK count")
@@ -1043,7 +1302,6 @@ impl SnarkStore for IndexerStore {
     fn get_block_snarks_count(&self, state_hash: &StateHash) -> anyhow::Result<Option<u32>> {
         trace!("Getting block SNARKs count {state_hash}");
         Ok(self
-            .database
             .get_pinned_cf(self.block_snark_counts_cf(), state_hash.0.as_bytes())?
             .map(|bytes| u32_from_be_bytes(&bytes).expect("block SNARK count")))
     }
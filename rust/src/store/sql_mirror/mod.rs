@@ -0,0 +1,258 @@
+//! Optional DuckDB mirror of the canonical chain for ad-hoc SQL analytics
+//!
+//! Canonical blocks, user commands, internal commands & account diffs are
+//! written to the mirror alongside the primary store's best chain updates.
+//! Each update runs in a single transaction, deleting & reinserting the rows
+//! of the affected heights, so the mirror never diverges from the store.
+
+use super::IndexerStore;
+use crate::{
+    base::state_hash::StateHash,
+    block::store::{BlockStore, BlockUpdate, DbBlockUpdate},
+    canonicity::store::CanonicityStore,
+    command::{internal::DbInternalCommandWithData, signed::SignedCommandWithData},
+    ledger::diff::account::AccountDiff,
+};
+use anyhow::Context;
+use duckdb::{params, Connection, OptionalExt, Transaction};
+use log::{debug, info};
+use std::{
+    path::Path,
+    sync::{Mutex, MutexGuard},
+};
+
+/// Mirror tables, see `schema.sql`
+pub const SQL_MIRROR_TABLES: [&str; 4] = [
+    "blocks",
+    "user_commands",
+    "internal_commands",
+    "account_diffs",
+];
+
+const SCHEMA: &str = include_str!("schema.sql");
+
+#[derive(Debug)]
+pub struct SqlMirror {
+    connection: Mutex<Connection>,
+}
+
+impl SqlMirror {
+    /// Opens (or creates) the DuckDB mirror file at `path`
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let connection = Connection::open(path)
+            .with_context(|| format!("Failed to open SQL mirror {}", path.display()))?;
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// New connection to the mirror database
+    pub fn connection(&self) -> anyhow::Result<Connection> {
+        Ok(self.lock().try_clone()?)
+    }
+
+    /// Rewrites the mirror's canonical blocks which diverge from the store's
+    /// & adds the missing ones
+    pub fn sync(&self, store: &IndexerStore) -> anyhow::Result<()> {
+        let mut connection = self.lock();
+        let tx = connection.transaction()?;
+
+        // highest height consistent with the store
+        let mut height = max_height(&tx)?;
+        while height > 0
+            && state_hash_at(&tx, height)? != store.get_canonical_hash_at_height(height)?
+        {
+            height -= 1;
+        }
+
+        let mut num_blocks = 0;
+        delete_above(&tx, height)?;
+        while let Some(state_hash) = store.get_canonical_hash_at_height(height + 1)? {
+            height += 1;
+            insert_block(&tx, store, &state_hash)?;
+            num_blocks += 1;
+        }
+
+        tx.commit()?;
+        info!("Synced {num_blocks} canonical blocks to the SQL mirror (length {height})");
+        Ok(())
+    }
+
+    /// Applies the best chain block updates
+    pub fn update(&self, store: &IndexerStore, blocks: &DbBlockUpdate) -> anyhow::Result<()> {
+        let mut connection = self.lock();
+        let tx = connection.transaction()?;
+
+        for BlockUpdate {
+            blockchain_length, ..
+        } in blocks.unapply.iter()
+        {
+            delete_height(&tx, *blockchain_length)?;
+        }
+
+        for BlockUpdate { state_hash, .. } in blocks.apply.iter() {
+            insert_block(&tx, store, state_hash)?;
+        }
+
+        tx.commit()?;
+        debug!(
+            "Updated SQL mirror: {} unapplied, {} applied blocks",
+            blocks.unapply.len(),
+            blocks.apply.len()
+        );
+        Ok(())
+    }
+
+    /// Poisoned locks are fine, uncommitted transactions roll back
+    fn lock(&self) -> MutexGuard<'_, Connection> {
+        self.connection.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn max_height(tx: &Transaction) -> anyhow::Result<u32> {
+    Ok(
+        tx.query_row("SELECT coalesce(max(height), 0) FROM blocks", [], |row| {
+            row.get(0)
+        })?,
+    )
+}
+
+fn state_hash_at(tx: &Transaction, height: u32) -> anyhow::Result<Option<StateHash>> {
+    Ok(tx
+        .query_row(
+            "SELECT state_hash FROM blocks WHERE height = ?",
+            params![height],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?
+        .map(Into::into))
+}
+
+fn delete_height(tx: &Transaction, height: u32) -> anyhow::Result<()> {
+    for table in SQL_MIRROR_TABLES {
+        tx.execute(
+            &format!("DELETE FROM {table} WHERE height = ?"),
+            params![height],
+        )?;
+    }
+    Ok(())
+}
+
+fn delete_above(tx: &Transaction, height: u32) -> anyhow::Result<()> {
+    for table in SQL_MIRROR_TABLES {
+        tx.execute(
+            &format!("DELETE FROM {table} WHERE height > ?"),
+            params![height],
+        )?;
+    }
+    Ok(())
+}
+
+fn insert_block(
+    tx: &Transaction,
+    store: &IndexerStore,
+    state_hash: &StateHash,
+) -> anyhow::Result<()> {
+    let (block, _) = store
+        .get_block(state_hash)?
+        .with_context(|| format!("Block missing from store {state_hash}"))?;
+    let height = block.blockchain_length();
+
+    // replaces the height's previous canonical block
+    delete_height(tx, height)?;
+    tx.execute(
+        "INSERT INTO blocks VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            height,
+            state_hash.0,
+            block.previous_state_hash().0,
+            block.global_slot_since_genesis(),
+            block.epoch_count(),
+            block.block_creator().0,
+            block.coinbase_receiver().0,
+            block.timestamp(),
+        ],
+    )?;
+
+    // user commands
+    let mut stmt =
+        tx.prepare("INSERT INTO user_commands VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")?;
+    for cmd in SignedCommandWithData::from_precomputed(&block) {
+        let command = &cmd.command;
+        stmt.execute(params![
+            height,
+            cmd.index_in_block,
+            cmd.tx_hash.to_string(),
+            command.kind().to_string(),
+            command.source_pk().0,
+            command
+                .receiver_pk()
+                .first()
+                .map(|pk| pk.0.to_owned())
+                .unwrap_or_default(),
+            command.amount(),
+            command.fee(),
+            cmd.nonce.0,
+            command.memo(),
+            cmd.status.is_applied(),
+        ])?;
+    }
+
+    // internal commands
+    let mut stmt = tx.prepare("INSERT INTO internal_commands VALUES (?, ?, ?, ?, ?)")?;
+    for cmd in DbInternalCommandWithData::from_precomputed(&block) {
+        let (DbInternalCommandWithData::Coinbase {
+            receiver,
+            amount,
+            kind,
+            index_in_block,
+            ..
+        }
+        | DbInternalCommandWithData::FeeTransfer {
+            receiver,
+            amount,
+            kind,
+            index_in_block,
+            ..
+        }) = cmd;
+
+        stmt.execute(params![
+            height,
+            index_in_block,
+            kind.to_string(),
+            receiver.0,
+            amount
+        ])?;
+    }
+
+    // account diffs
+    let diff = store
+        .get_block_ledger_diff(state_hash)?
+        .with_context(|| format!("Block ledger diff missing from store {state_hash}"))?;
+
+    let mut stmt = tx.prepare("INSERT INTO account_diffs VALUES (?, ?, ?, ?, ?, ?)")?;
+    for (index, diff) in diff.account_diffs.iter().flatten().enumerate() {
+        stmt.execute(params![
+            height,
+            index as u32,
+            diff.kind(),
+            diff.public_key().0,
+            diff.token_address().0,
+            balance_change(diff),
+        ])?;
+    }
+
+    Ok(())
+}
+
+/// Signed balance change of payments, fee transfers & coinbases
+fn balance_change(diff: &AccountDiff) -> i64 {
+    use AccountDiff::*;
+
+    match diff {
+        Payment(_) | FeeTransfer(_) | FeeTransferViaCoinbase(_) | Coinbase(_) => diff.amount(),
+        _ => 0,
+    }
+}
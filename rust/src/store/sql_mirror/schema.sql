@@ -0,0 +1,51 @@
+-- SQL mirror of the canonical chain
+--
+-- Every table holds the rows of a single block per height, rows are deleted
+-- & reinserted for the heights affected by a reorg
+
+CREATE TABLE IF NOT EXISTS blocks (
+    height UINTEGER NOT NULL,
+    state_hash VARCHAR NOT NULL,
+    parent_hash VARCHAR NOT NULL,
+    global_slot UINTEGER NOT NULL,
+    epoch UINTEGER NOT NULL,
+    creator VARCHAR NOT NULL,
+    coinbase_receiver VARCHAR NOT NULL,
+    -- millis since the unix epoch
+    timestamp UBIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS user_commands (
+    height UINTEGER NOT NULL,
+    index_in_block UINTEGER NOT NULL,
+    txn_hash VARCHAR NOT NULL,
+    kind VARCHAR NOT NULL,
+    sender VARCHAR NOT NULL,
+    receiver VARCHAR NOT NULL,
+    -- nanomina
+    amount UBIGINT NOT NULL,
+    fee UBIGINT NOT NULL,
+    nonce UINTEGER NOT NULL,
+    memo VARCHAR NOT NULL,
+    applied BOOLEAN NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS internal_commands (
+    height UINTEGER NOT NULL,
+    index_in_block UINTEGER NOT NULL,
+    kind VARCHAR NOT NULL,
+    receiver VARCHAR NOT NULL,
+    -- nanomina
+    amount UBIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS account_diffs (
+    height UINTEGER NOT NULL,
+    -- index in the block's ledger application order
+    index_in_block UINTEGER NOT NULL,
+    kind VARCHAR NOT NULL,
+    public_key VARCHAR NOT NULL,
+    token VARCHAR NOT NULL,
+    -- signed balance change of payments, fee transfers & coinbases
+    amount BIGINT NOT NULL
+);
@@ -1,4 +1,10 @@
-use super::{column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, IndexerStore};
+use super::{
+    column_families::ColumnFamilyHelpers,
+    fixed_keys::FixedKeys,
+    replay::{DerivedData, ReplayRange},
+    sub_store::SubStore,
+    IndexerStore,
+};
 use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
     block::store::BlockStore,
@@ -8,7 +14,7 @@ use crate::{
     ledger::{
         account::Account,
         diff::LedgerDiff,
-        store::{best::BestLedgerStore, staged::StagedLedgerStore},
+        store::{anomaly::AmountAnomalyStore, best::BestLedgerStore, staged::StagedLedgerStore},
         token::TokenAddress,
         Ledger, LedgerHash,
     },
@@ -17,6 +23,27 @@ use crate::{
 use anyhow::{bail, Context};
 use log::{error, trace};
 use speedb::{DBIterator, Direction, IteratorMode, WriteBatch};
+use std::collections::HashSet;
+
+/// Add the corresponding CF helpers to [ColumnFamilyHelpers] & bump the
+/// sub-store version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "staged-ledger",
+    version: 1,
+    column_families: &[
+        "staged-ledger-accounts",
+        "staged-ledger-account-balance-sort",
+        "staged-ledger-account-num-delegations",
+        "staged-ledger-account-delegations",
+        "staged-ledger-hash-to-block",
+        "staged-ledger-persisted",
+        "staged-ledger-accounts-min-block",
+        "blocks-ledger-diff",
+        "blocks-staged-ledger-hash",
+    ],
+    dependencies: &["blocks"],
+    migrate: SubStore::no_migration,
+};
 
 impl StagedLedgerStore for IndexerStore {
     fn get_staged_account(
@@ -44,7 +71,6 @@ impl StagedLedgerStore for IndexerStore {
         let mut curr_state_hash = state_hash.clone();
 
         while self
-            .database
             .get_cf(
                 self.staged_ledger_accounts_cf(),
                 staged_account_key(&curr_state_hash, token, pk),
@@ -62,7 +88,6 @@ impl StagedLedgerStore for IndexerStore {
         apply_block_diffs.reverse();
 
         let mut staged_account = self
-            .database
             .get_cf(
                 self.staged_ledger_accounts_cf(),
                 staged_account_key(&curr_state_hash, token, pk),
@@ -75,7 +100,11 @@ impl StagedLedgerStore for IndexerStore {
             .iter()
             .flat_map(|state_hash| self.get_block_ledger_diff(state_hash).ok().flatten())
         {
-            staged_account = staged_account.apply_ledger_diff(&diff);
+            let (account, anomalies) = staged_account.apply_ledger_diff(&diff);
+            for anomaly in anomalies.iter() {
+                self.add_amount_anomaly(anomaly)?;
+            }
+            staged_account = account;
         }
         Ok(Some(staged_account))
     }
@@ -166,7 +195,6 @@ impl StagedLedgerStore for IndexerStore {
     fn get_pk_min_staged_ledger_block(&self, pk: &PublicKey) -> anyhow::Result<Option<u32>> {
         trace!("Getting pk min staged ledger block height {pk}");
         Ok(self
-            .database
             .get_cf(self.staged_ledger_accounts_min_block_cf(), pk.0.as_bytes())?
             .map(from_be_bytes))
     }
@@ -193,7 +221,6 @@ impl StagedLedgerStore for IndexerStore {
             "Adding staged ledger hash\n  state_hash:  {state_hash}\n  ledger_hash: {ledger_hash}"
         );
         let is_new = self
-            .database
             .get_cf(self.staged_ledgers_persisted_cf(), state_hash.0.as_bytes())?
             .is_none();
 
@@ -306,7 +333,8 @@ impl StagedLedgerStore for IndexerStore {
 
         if !known_prev.contains(state_hash) {
             known_prev.push(state_hash.clone());
-            self.database.put(
+            self.database.put_cf(
+                self.default_cf(),
                 Self::KNOWN_GENESIS_PREV_STATE_HASHES_KEY,
                 serde_json::to_vec(&known_prev)?,
             )?;
@@ -336,7 +364,6 @@ impl StagedLedgerStore for IndexerStore {
         // walk chain back to a stored ledger
         // collect diffs to compute the current ledger
         while self
-            .database
             .get_cf(
                 self.staged_ledgers_persisted_cf(),
                 curr_state_hash.0.as_bytes(),
@@ -397,7 +424,6 @@ impl StagedLedgerStore for IndexerStore {
         trace!("Getting staged ledger hash {ledger_hash}");
         let key = ledger_hash.0.as_bytes();
         if let Some(state_hash) = self
-            .database
             .get_cf(self.staged_ledger_hash_to_block_cf(), key)?
             .and_then(|bytes| StateHash::from_bytes(&bytes).ok())
         {
@@ -420,6 +446,18 @@ impl StagedLedgerStore for IndexerStore {
             })
     }
 
+    fn get_staged_ledger_at_slot(
+        &self,
+        global_slot: u32,
+        memoize: bool,
+    ) -> anyhow::Result<Option<Ledger>> {
+        trace!("Getting staged ledger at slot {global_slot}");
+        self.get_canonical_hash_at_or_before_slot(global_slot)?
+            .map_or(Ok(None), |state_hash| {
+                self.get_staged_ledger_at_state_hash(&state_hash, memoize)
+            })
+    }
+
     fn set_block_ledger_diff_batch(
         &self,
         state_hash: &StateHash,
@@ -461,7 +499,6 @@ impl StagedLedgerStore for IndexerStore {
     ) -> anyhow::Result<Option<LedgerHash>> {
         trace!("Getting block staged ledger hash {state_hash}");
         Ok(self
-            .database
             .get_cf(self.block_staged_ledger_hash_cf(), state_hash.0.as_bytes())?
             .map(|bytes| LedgerHash::from_bytes(bytes).expect("ledger hash")))
     }
@@ -472,7 +509,6 @@ impl StagedLedgerStore for IndexerStore {
     ) -> anyhow::Result<Option<StateHash>> {
         trace!("Getting staged ledger {ledger_hash} block state hash");
         Ok(self
-            .database
             .get_cf(
                 self.staged_ledger_hash_to_block_cf(),
                 ledger_hash.0.as_bytes(),
@@ -531,7 +567,123 @@ impl StagedLedgerStore for IndexerStore {
             direction,
         );
 
-        self.database
-            .iterator_cf(self.staged_ledger_account_balance_sort_cf(), mode)
+        self.iterator_cf(self.staged_ledger_account_balance_sort_cf(), mode)
+    }
+}
+
+/// Block ledger diffs & persisted staged ledgers, see [DerivedData]
+pub struct StagedLedgerData;
+
+impl DerivedData for StagedLedgerData {
+    fn name(&self) -> &'static str {
+        "staged ledger"
+    }
+
+    fn rewrite_range(&self, store: &IndexerStore, range: &ReplayRange) -> anyhow::Result<u32> {
+        let mut num_rewritten = 0;
+        let mut ledger = range.start_ledger.clone();
+
+        for state_hash in range.state_hashes.iter() {
+            let (block, _) = store
+                .get_block(state_hash)?
+                .with_context(|| format!("Block missing from store {state_hash}"))?;
+
+            // block ledger diff
+            let diff = LedgerDiff::from_precomputed(&block);
+            if store.get_block_ledger_diff(state_hash)?.as_ref() != Some(&diff) {
+                trace!("Rewriting block ledger diff {state_hash}");
+                let mut batch = WriteBatch::default();
+                store.set_block_ledger_diff_batch(state_hash, &diff, &mut batch)?;
+                store.database.write(batch)?;
+                num_rewritten += 1;
+            }
+
+            ledger._apply_diff(&diff)?;
+
+            // persisted staged ledger
+            if store
+                .get_cf(store.staged_ledgers_persisted_cf(), state_hash.0.as_bytes())?
+                .is_some()
+            {
+                num_rewritten +=
+                    store.rewrite_staged_ledger(state_hash, block.blockchain_length(), &ledger)?;
+            }
+        }
+
+        Ok(num_rewritten)
+    }
+}
+
+impl IndexerStore {
+    /// Rewrite the persisted staged ledger's accounts which differ from
+    /// `ledger`, returning the number of rewritten accounts
+    fn rewrite_staged_ledger(
+        &self,
+        state_hash: &StateHash,
+        block_height: u32,
+        ledger: &Ledger,
+    ) -> anyhow::Result<u32> {
+        let mut num_rewritten = 0;
+        let mut correct = HashSet::new();
+
+        // remove stale & incorrect balance-sorted accounts
+        for (key, value) in self
+            .staged_ledger_account_balance_iterator(state_hash, Direction::Forward)
+            .flatten()
+        {
+            let Some((key_state_hash, token, balance, pk)) =
+                split_staged_account_balance_sort_key(&key)
+            else {
+                panic!("Invalid staged ledger account balance sort key")
+            };
+
+            if key_state_hash != *state_hash {
+                break;
+            }
+
+            let account_key = staged_account_key(state_hash, &token, &pk);
+            let stored: Account = serde_json::from_slice(&value)?;
+            let stored_by_key = self
+                .get_cf(self.staged_ledger_accounts_cf(), account_key)?
+                .map(|bytes| serde_json::from_slice::<Account>(&bytes))
+                .transpose()?;
+
+            match ledger.get_account(&pk, &token) {
+                Some(account)
+                    if account.balance.0 == balance
+                        && *account == stored
+                        && Some(account) == stored_by_key.as_ref() =>
+                {
+                    correct.insert((token, pk));
+                }
+                account => {
+                    self.database
+                        .delete_cf(self.staged_ledger_account_balance_sort_cf(), &key)?;
+
+                    // accounts which shouldn't exist
+                    if account.is_none() {
+                        trace!("Removing staged ledger {state_hash} account {pk}");
+                        self.database
+                            .delete_cf(self.staged_ledger_accounts_cf(), account_key)?;
+                        num_rewritten += 1;
+                    }
+                }
+            }
+        }
+
+        // (re)write the missing & incorrect accounts
+        for (token, token_ledger) in ledger.tokens.iter() {
+            for (pk, account) in token_ledger.accounts.iter() {
+                if correct.contains(&(token.clone(), pk.clone())) {
+                    continue;
+                }
+
+                trace!("Rewriting staged ledger {state_hash} account {pk}");
+                self.set_staged_account(pk, token, state_hash, block_height, account)?;
+                num_rewritten += 1;
+            }
+        }
+
+        Ok(num_rewritten)
     }
 }
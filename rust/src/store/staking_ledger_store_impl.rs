@@ -1,4 +1,4 @@
-use super::{column_families::ColumnFamilyHelpers, IndexerStore};
+use super::{column_families::ColumnFamilyHelpers, sub_store::SubStore, IndexerStore};
 use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
     block::store::BlockStore,
@@ -6,20 +6,120 @@ use crate::{
     event::{db::*, store::EventStore, IndexerEvent},
     ledger::{
         staking::{
-            AggregatedEpochStakeDelegations, EpochStakeDelegation, StakingAccount, StakingLedger,
+            AggregatedEpochStakeDelegations, EpochDelegator, EpochStakeDelegation, StakingAccount,
+            StakingLedger, StakingLedgerDiff,
+        },
+        store::staking::{
+            StakingAccountWithEpochDelegation, StakingLedgerCheckpoint, StakingLedgerFile,
+            StakingLedgerStore,
         },
-        store::staking::{StakingAccountWithEpochDelegation, StakingLedgerStore},
         LedgerHash,
     },
     utility::store::{
-        common::{from_be_bytes, u64_from_be_bytes},
+        common::{from_be_bytes, u64_from_be_bytes, U32_LEN},
         ledger::staking::*,
     },
 };
-use anyhow::Context;
-use log::{error, trace};
-use speedb::{DBIterator, Direction, IteratorMode};
-use std::collections::HashMap;
+use anyhow::{bail, Context};
+use log::{error, info, trace};
+use speedb::{DBIterator, Direction, IteratorMode, WriteBatch};
+use std::{collections::HashMap, sync::atomic::Ordering};
+
+/// Add the corresponding CF helpers to [ColumnFamilyHelpers] & bump the
+/// sub-store version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "staking-ledger",
+    version: 4,
+    column_families: &[
+        "staking-ledger-accounts",
+        "staking-ledger-delegations",
+        "staking-ledger-persisted",
+        "staking-ledger-epoch-to-hash",
+        "staking-ledger-hash-to-epoch",
+        "staking-ledger-genesis-hash",
+        "staking-ledger-total-currency",
+        "staking-ledger-balance-sort",
+        "staking-ledger-stake-sort",
+        "staking-ledger-delegate-balance-sort",
+        "staking-ledger-accounts-count-epoch",
+        "staking-ledger-content-hash",
+        "staking-ledger-checkpoints",
+        "staking-ledger-files",
+    ],
+    dependencies: &["chain"],
+    migrate,
+};
+
+/// - v1 -> v2: rebuild the sort indices keyed by flattened epoch
+/// - v2 -> v3: file sizes & modification times are recorded the first time
+///   each stored epoch's file is content hashed
+/// - v3 -> v4: checkpoints only exist for in-progress ingestions, which v3
+///   stores never interrupt, so there's nothing to backfill
+fn migrate(store: &IndexerStore, from_version: u32) -> anyhow::Result<()> {
+    match from_version {
+        1 => migrate_flat_epoch_sort_keys(store),
+        2 | 3 => Ok(()),
+        _ => SubStore::no_migration(store, from_version),
+    }
+}
+
+/// Rebuilds the balance/stake sort indices keyed by flattened epoch, the
+/// same epoch of different genesis eras collided
+fn migrate_flat_epoch_sort_keys(store: &IndexerStore) -> anyhow::Result<()> {
+    for cf in [
+        store.staking_ledger_balance_sort_cf(),
+        store.staking_ledger_stake_sort_cf(),
+        store.staking_ledger_delegate_balance_sort_cf(),
+    ] {
+        let mut batch = WriteBatch::default();
+        for (key, _) in store.iterator_cf(cf, IteratorMode::Start).flatten() {
+            batch.delete_cf(cf, key);
+        }
+        store.database.write(batch)?;
+    }
+
+    for (key, _) in store
+        .staking_ledger_epoch_iterator(IteratorMode::Start)
+        .flatten()
+    {
+        let (genesis_state_hash, epoch, ledger_hash) = split_staking_ledger_epoch_key(&key)?;
+        let prefix = staking_ledger_epoch_key(&genesis_state_hash, epoch, &ledger_hash);
+
+        let mut batch = WriteBatch::default();
+        for (key, value) in store
+            .iterator_cf(
+                store.staking_ledger_accounts_cf(),
+                IteratorMode::From(&prefix, Direction::Forward),
+            )
+            .flatten()
+        {
+            if !key.starts_with(&prefix) {
+                break;
+            }
+
+            let pk = PublicKey::from_bytes(&key[prefix.len()..])?;
+            let delegation = store
+                .get_cf(store.staking_delegations_cf(), &key)?
+                .with_context(|| format!("epoch {epoch} delegation {pk}"))?;
+
+            store.set_staking_account_batch(
+                &pk,
+                epoch,
+                &ledger_hash,
+                &genesis_state_hash,
+                &StakingAccountWithEpochDelegation {
+                    account: serde_json::from_slice(&value)?,
+                    delegation: serde_json::from_slice(&delegation)?,
+                },
+                &mut batch,
+            )?;
+        }
+
+        store.database.write(batch)?;
+    }
+
+    Ok(())
+}
 
 impl StakingLedgerStore for IndexerStore {
     fn get_staking_account(
@@ -40,7 +140,6 @@ impl StakingLedgerStore for IndexerStore {
             let key = staking_ledger_account_key(genesis_state_hash, epoch, &ledger_hash, pk);
 
             return Ok(self
-                .database
                 .get_cf(self.staking_ledger_accounts_cf(), key)?
                 .and_then(|bytes| serde_json::from_slice(&bytes).ok()));
         }
@@ -59,32 +158,60 @@ impl StakingLedgerStore for IndexerStore {
     ) -> anyhow::Result<()> {
         trace!("Setting staking account {pk} (epoch {epoch}): {ledger_hash}");
 
+        let mut batch = WriteBatch::default();
+        self.set_staking_account_batch(
+            pk,
+            epoch,
+            ledger_hash,
+            genesis_state_hash,
+            &staking_account_with_delegation,
+            &mut batch,
+        )?;
+
+        Ok(self.database.write(batch)?)
+    }
+
+    fn set_staking_account_batch(
+        &self,
+        pk: &PublicKey,
+        epoch: u32,
+        ledger_hash: &LedgerHash,
+        genesis_state_hash: &StateHash,
+        staking_account_with_delegation: &StakingAccountWithEpochDelegation,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()> {
+        let flat_epoch = self.get_flat_epoch(genesis_state_hash, epoch)?;
+
         // add staking account
-        self.database.put_cf(
+        batch.put_cf(
             self.staking_ledger_accounts_cf(),
             staking_ledger_account_key(genesis_state_hash, epoch, ledger_hash, pk),
             serde_json::to_vec(&staking_account_with_delegation.account)?,
-        )?;
+        );
 
         // add staking delegations
-        self.database.put_cf(
+        batch.put_cf(
             self.staking_delegations_cf(),
             staking_ledger_account_key(genesis_state_hash, epoch, ledger_hash, pk),
             serde_json::to_vec(&staking_account_with_delegation.delegation)?,
-        )?;
+        );
 
         // balance/stake sort
-        let account_serde_bytes = serde_json::to_vec(&staking_account_with_delegation)?;
-        self.database.put_cf(
+        let account_serde_bytes = serde_json::to_vec(staking_account_with_delegation)?;
+        batch.put_cf(
             self.staking_ledger_balance_sort_cf(),
-            staking_ledger_sort_key(epoch, staking_account_with_delegation.account.balance, pk),
+            staking_ledger_sort_key(
+                flat_epoch,
+                staking_account_with_delegation.account.balance,
+                pk,
+            ),
             &account_serde_bytes,
-        )?;
+        );
 
-        self.database.put_cf(
+        batch.put_cf(
             self.staking_ledger_stake_sort_cf(),
             staking_ledger_sort_key(
-                epoch,
+                flat_epoch,
                 staking_account_with_delegation
                     .delegation
                     .total_delegated
@@ -92,7 +219,19 @@ impl StakingLedgerStore for IndexerStore {
                 pk,
             ),
             &account_serde_bytes,
-        )?;
+        );
+
+        // delegate's delegators balance sort
+        batch.put_cf(
+            self.staking_ledger_delegate_balance_sort_cf(),
+            staking_ledger_delegate_sort_key(
+                flat_epoch,
+                &staking_account_with_delegation.account.delegate,
+                staking_account_with_delegation.account.balance,
+                pk,
+            ),
+            b"",
+        );
 
         Ok(())
     }
@@ -147,16 +286,9 @@ impl StakingLedgerStore for IndexerStore {
             &staking_ledger.ledger_hash,
         );
         let is_new = self
-            .database
             .get_cf(self.staking_ledger_persisted_cf(), key)?
             .is_none();
 
-        // persist new staking ledger
-        if is_new {
-            self.database
-                .put_cf(self.staking_ledger_persisted_cf(), key, b"")?;
-        }
-
         // additional indices
         let ledger_hash = staking_ledger.ledger_hash.clone();
         self.set_staking_ledger_hash_epoch_pair(&ledger_hash, epoch, Some(genesis_state_hash))?;
@@ -167,25 +299,67 @@ impl StakingLedgerStore for IndexerStore {
         let count = staking_ledger.staking_ledger.len();
         self.set_staking_ledger_accounts_count_epoch(epoch, genesis_state_hash, count as u32)?;
 
-        // add staking ledger accounts & per epoch balance-sorted data
+        // resume an interrupted ingestion of the same staking ledger
+        let checkpoint_key = staking_ledger_epoch_key_prefix(genesis_state_hash, epoch);
+        let num_ingested = self
+            .get_staking_ledger_checkpoint(epoch, genesis_state_hash)?
+            .filter(|checkpoint| checkpoint.ledger_hash == ledger_hash)
+            .map_or(0, |checkpoint| checkpoint.num_accounts as usize);
+        if num_ingested > 0 {
+            info!("Resuming staking ledger (epoch {epoch}) {ledger_hash} after {num_ingested} accounts");
+        }
+
+        // add staking ledger accounts & per epoch balance-sorted data in
+        // public key order, in write batches of `staking_ledger_batch_size`
+        // accounts, each checkpointing the ingestion
+        let batch_size = self.staking_ledger_batch_size.load(Ordering::Relaxed);
         let aggregated_delegations = staking_ledger.aggregate_delegations()?;
-        for (pk, account) in staking_ledger.staking_ledger {
+        let mut accounts: Vec<_> = staking_ledger.staking_ledger.into_iter().collect();
+        accounts.sort_by(|(pk0, _), (pk1, _)| pk0.cmp(pk1));
+
+        let mut batch = WriteBatch::default();
+        let mut num_batched = 0;
+        for (n, (pk, account)) in accounts.into_iter().enumerate().skip(num_ingested) {
             let delegation = aggregated_delegations
                 .delegations
                 .get(&pk)
                 .cloned()
                 .expect("delegation exists");
-            self.set_staking_account(
+            self.set_staking_account_batch(
                 &pk,
                 epoch,
                 &ledger_hash,
                 genesis_state_hash,
-                StakingAccountWithEpochDelegation {
+                &StakingAccountWithEpochDelegation {
                     account,
                     delegation,
                 },
+                &mut batch,
             )?;
+
+            num_batched += 1;
+            if batch_size > 0 && num_batched >= batch_size {
+                let checkpoint = StakingLedgerCheckpoint {
+                    ledger_hash: ledger_hash.clone(),
+                    num_accounts: n as u32 + 1,
+                };
+                batch.put_cf(
+                    self.staking_ledger_checkpoints_cf(),
+                    checkpoint_key,
+                    serde_json::to_vec(&checkpoint)?,
+                );
+
+                self.database.write(std::mem::take(&mut batch))?;
+                num_batched = 0;
+            }
+        }
+
+        // persist the complete staking ledger
+        if is_new {
+            batch.put_cf(self.staking_ledger_persisted_cf(), key, b"");
         }
+        batch.delete_cf(self.staking_ledger_checkpoints_cf(), checkpoint_key);
+        self.database.write(batch)?;
 
         if is_new {
             // add new ledger event
@@ -209,6 +383,267 @@ impl StakingLedgerStore for IndexerStore {
         Ok(())
     }
 
+    fn replace_staking_ledger(
+        &self,
+        staking_ledger: StakingLedger,
+        genesis_state_hash: &StateHash,
+        content_hash: &str,
+        file: &StakingLedgerFile,
+    ) -> anyhow::Result<StakingLedgerDiff> {
+        trace!("Replacing staking ledger {}", staking_ledger.summary());
+
+        let epoch = staking_ledger.epoch;
+        let (old_ledger, old_delegations) = match (
+            self.build_staking_ledger(epoch, Some(genesis_state_hash))?,
+            self.build_aggregated_delegations(epoch, Some(genesis_state_hash))?,
+        ) {
+            (Some(old_ledger), Some(old_delegations)) => (old_ledger, old_delegations),
+            _ => bail!("No staking ledger to replace for epoch {epoch}"),
+        };
+
+        let diff = old_ledger.diff(&staking_ledger);
+        let flat_epoch = self.get_flat_epoch(genesis_state_hash, epoch)?;
+        let old_ledger_hash = old_ledger.ledger_hash.clone();
+        let ledger_hash = staking_ledger.ledger_hash.clone();
+        let mut batch = WriteBatch::default();
+
+        // remove old staking ledger accounts & per epoch sorted data
+        for (pk, account) in old_ledger.staking_ledger.iter() {
+            let account_key =
+                staking_ledger_account_key(genesis_state_hash, epoch, &old_ledger_hash, pk);
+            let stake = old_delegations
+                .delegations
+                .get(pk)
+                .and_then(|delegation| delegation.total_delegated)
+                .unwrap_or_default();
+
+            batch.delete_cf(self.staking_ledger_accounts_cf(), account_key);
+            batch.delete_cf(self.staking_delegations_cf(), account_key);
+            batch.delete_cf(
+                self.staking_ledger_balance_sort_cf(),
+                staking_ledger_sort_key(flat_epoch, account.balance, pk),
+            );
+            batch.delete_cf(
+                self.staking_ledger_stake_sort_cf(),
+                staking_ledger_sort_key(flat_epoch, stake, pk),
+            );
+            batch.delete_cf(
+                self.staking_ledger_delegate_balance_sort_cf(),
+                staking_ledger_delegate_sort_key(
+                    flat_epoch,
+                    &account.delegate,
+                    account.balance,
+                    pk,
+                ),
+            );
+        }
+
+        // remove old staking ledger indices
+        if old_ledger_hash != ledger_hash {
+            batch.delete_cf(
+                self.staking_ledger_persisted_cf(),
+                staking_ledger_epoch_key(genesis_state_hash, epoch, &old_ledger_hash),
+            );
+            batch.delete_cf(
+                self.staking_ledger_hash_to_epoch_cf(),
+                old_ledger_hash.0.as_bytes(),
+            );
+            batch.delete_cf(
+                self.staking_ledger_genesis_hash_cf(),
+                old_ledger_hash.0.as_bytes(),
+            );
+            batch.delete_cf(
+                self.staking_ledger_total_currency_cf(),
+                old_ledger_hash.0.as_bytes(),
+            );
+        }
+
+        // persist new staking ledger & indices
+        let epoch_key = staking_ledger_epoch_key_prefix(genesis_state_hash, epoch);
+        batch.put_cf(
+            self.staking_ledger_persisted_cf(),
+            staking_ledger_epoch_key(genesis_state_hash, epoch, &ledger_hash),
+            b"",
+        );
+        batch.put_cf(
+            self.staking_ledger_epoch_to_hash_cf(),
+            epoch_key,
+            ledger_hash.0.as_bytes(),
+        );
+        batch.put_cf(
+            self.staking_ledger_hash_to_epoch_cf(),
+            ledger_hash.0.as_bytes(),
+            epoch.to_be_bytes(),
+        );
+        batch.put_cf(
+            self.staking_ledger_genesis_hash_cf(),
+            ledger_hash.0.as_bytes(),
+            genesis_state_hash.0.as_bytes(),
+        );
+        batch.put_cf(
+            self.staking_ledger_total_currency_cf(),
+            ledger_hash.0.as_bytes(),
+            staking_ledger.total_currency.to_be_bytes(),
+        );
+        batch.put_cf(
+            self.staking_ledger_accounts_count_epoch_cf(),
+            epoch_key,
+            (staking_ledger.staking_ledger.len() as u32).to_be_bytes(),
+        );
+
+        // add new staking ledger accounts & per epoch sorted data
+        let aggregated_delegations = staking_ledger.aggregate_delegations()?;
+        for (pk, account) in staking_ledger.staking_ledger {
+            let delegation = aggregated_delegations
+                .delegations
+                .get(&pk)
+                .cloned()
+                .expect("delegation exists");
+            self.set_staking_account_batch(
+                &pk,
+                epoch,
+                &ledger_hash,
+                genesis_state_hash,
+                &StakingAccountWithEpochDelegation {
+                    account,
+                    delegation,
+                },
+                &mut batch,
+            )?;
+        }
+
+        // record the replacement's file
+        batch.put_cf(
+            self.staking_ledger_content_hash_cf(),
+            epoch_key,
+            content_hash.as_bytes(),
+        );
+        batch.put_cf(
+            self.staking_ledger_files_cf(),
+            epoch_key,
+            serde_json::to_vec(file)?,
+        );
+
+        // add replaced ledger & re-aggregated delegation events
+        self.add_events_batch(
+            &[
+                IndexerEvent::Db(DbEvent::StakingLedger(
+                    DbStakingLedgerEvent::ReplaceStakingLedger {
+                        epoch,
+                        old_ledger_hash,
+                        ledger_hash,
+                        genesis_state_hash: genesis_state_hash.clone(),
+                    },
+                )),
+                IndexerEvent::Db(DbEvent::StakingLedger(
+                    DbStakingLedgerEvent::AggregateDelegations {
+                        epoch,
+                        genesis_state_hash: genesis_state_hash.clone(),
+                    },
+                )),
+            ],
+            &mut batch,
+        )?;
+
+        self.database.write(batch)?;
+        Ok(diff)
+    }
+
+    fn set_staking_ledger_content_hash(
+        &self,
+        epoch: u32,
+        genesis_state_hash: &StateHash,
+        content_hash: &str,
+    ) -> anyhow::Result<()> {
+        trace!("Setting staking ledger content hash for epoch {epoch}: {content_hash}");
+        Ok(self.database.put_cf(
+            self.staking_ledger_content_hash_cf(),
+            staking_ledger_epoch_key_prefix(genesis_state_hash, epoch),
+            content_hash.as_bytes(),
+        )?)
+    }
+
+    fn get_staking_ledger_content_hash(
+        &self,
+        epoch: u32,
+        genesis_state_hash: &StateHash,
+    ) -> anyhow::Result<Option<String>> {
+        trace!("Getting staking ledger content hash for epoch {epoch}");
+        Ok(self
+            .get_cf(
+                self.staking_ledger_content_hash_cf(),
+                staking_ledger_epoch_key_prefix(genesis_state_hash, epoch),
+            )?
+            .map(|bytes| String::from_utf8(bytes).expect("content hash bytes")))
+    }
+
+    fn set_staking_ledger_file(
+        &self,
+        epoch: u32,
+        genesis_state_hash: &StateHash,
+        file: &StakingLedgerFile,
+    ) -> anyhow::Result<()> {
+        trace!("Setting staking ledger file for epoch {epoch}: {file:?}");
+        Ok(self.database.put_cf(
+            self.staking_ledger_files_cf(),
+            staking_ledger_epoch_key_prefix(genesis_state_hash, epoch),
+            serde_json::to_vec(file)?,
+        )?)
+    }
+
+    fn get_staking_ledger_file(
+        &self,
+        epoch: u32,
+        genesis_state_hash: &StateHash,
+    ) -> anyhow::Result<Option<StakingLedgerFile>> {
+        trace!("Getting staking ledger file for epoch {epoch}");
+        Ok(self
+            .get_cf(
+                self.staking_ledger_files_cf(),
+                staking_ledger_epoch_key_prefix(genesis_state_hash, epoch),
+            )?
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok()))
+    }
+
+    fn get_staking_ledger_checkpoint(
+        &self,
+        epoch: u32,
+        genesis_state_hash: &StateHash,
+    ) -> anyhow::Result<Option<StakingLedgerCheckpoint>> {
+        trace!("Getting staking ledger checkpoint for epoch {epoch}");
+        Ok(self
+            .get_cf(
+                self.staking_ledger_checkpoints_cf(),
+                staking_ledger_epoch_key_prefix(genesis_state_hash, epoch),
+            )?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+
+    fn get_last_staking_ledger_epoch(
+        &self,
+        genesis_state_hash: &StateHash,
+    ) -> anyhow::Result<Option<u32>> {
+        trace!("Getting last staking ledger epoch of {genesis_state_hash}");
+
+        // complete staking ledgers are persisted, highest epoch first
+        let mut start = [u8::MAX; StateHash::LEN + U32_LEN + LedgerHash::LEN];
+        start[..StateHash::LEN].copy_from_slice(genesis_state_hash.0.as_bytes());
+
+        if let Some((key, _)) = self
+            .staking_ledger_epoch_iterator(IteratorMode::From(&start, Direction::Reverse))
+            .flatten()
+            .next()
+        {
+            let (key_genesis_state_hash, epoch, _) = split_staking_ledger_epoch_key(&key)?;
+            if key_genesis_state_hash == *genesis_state_hash {
+                return Ok(Some(epoch));
+            }
+        }
+
+        Ok(None)
+    }
+
     fn get_epoch_delegations(
         &self,
         pk: &PublicKey,
@@ -228,7 +663,6 @@ impl StakingLedgerStore for IndexerStore {
             .expect("staking ledger hash");
 
         Ok(self
-            .database
             .get_cf(
                 self.staking_delegations_cf(),
                 staking_ledger_account_key(genesis_state_hash, epoch, &ledger_hash, pk),
@@ -236,10 +670,106 @@ impl StakingLedgerStore for IndexerStore {
             .map(|bytes| serde_json::from_slice(&bytes).expect("epoch staking delegation bytes")))
     }
 
+    fn get_epoch_delegators(
+        &self,
+        delegate: &PublicKey,
+        epoch: u32,
+        genesis_state_hash: Option<&StateHash>,
+        cursor: Option<&PublicKey>,
+        direction: Direction,
+        limit: usize,
+    ) -> anyhow::Result<Vec<EpochDelegator>> {
+        trace!("Getting epoch {epoch} delegators of {delegate}");
+
+        if self
+            .get_staking_ledger_hash_by_epoch(epoch, genesis_state_hash)?
+            .is_none()
+        {
+            return Ok(vec![]);
+        }
+
+        let best_block_genesis_hash = self.get_best_block_genesis_hash()?;
+        let flat_epoch = self.get_flat_epoch(
+            genesis_state_hash
+                .or(best_block_genesis_hash.as_ref())
+                .expect("genesis state hash"),
+            epoch,
+        )?;
+
+        // persisted aggregated delegations
+        let total_delegated = self
+            .get_epoch_delegations(delegate, epoch, genesis_state_hash)?
+            .and_then(|delegation| delegation.total_delegated)
+            .unwrap_or_default();
+
+        // delegation changes are only known if the previous ledger is present
+        let prev_epoch = match epoch.checked_sub(1) {
+            Some(prev_epoch)
+                if self
+                    .get_staking_ledger_hash_by_epoch(prev_epoch, genesis_state_hash)?
+                    .is_some() =>
+            {
+                Some(prev_epoch)
+            }
+            _ => None,
+        };
+
+        // resume after the cursor delegator's sort key
+        let cursor_key = match cursor {
+            Some(pk) => match self.get_staking_account(pk, epoch, genesis_state_hash)? {
+                Some(account) if account.delegate == *delegate => Some(
+                    staking_ledger_delegate_sort_key(flat_epoch, delegate, account.balance, pk),
+                ),
+                _ => bail!("Cursor {pk} is not an epoch {epoch} delegator of {delegate}"),
+            },
+            None => None,
+        };
+        let iter = match cursor_key.as_ref() {
+            Some(cursor_key) => self.iterator_cf(
+                self.staking_ledger_delegate_balance_sort_cf(),
+                IteratorMode::From(cursor_key, direction),
+            ),
+            None => self.staking_ledger_delegate_balance_iterator(flat_epoch, delegate, direction),
+        };
+
+        let mut delegators = vec![];
+        for (key, _) in iter.flatten() {
+            if cursor_key.is_some_and(|cursor_key| key[..] == cursor_key[..]) {
+                continue;
+            }
+
+            let (key_epoch, key_delegate, balance, pk) =
+                split_staking_ledger_delegate_sort_key(&key)?;
+            if key_epoch != flat_epoch || key_delegate != *delegate || delegators.len() >= limit {
+                // no longer the delegate of interest
+                break;
+            }
+
+            let prev_delegate = match prev_epoch {
+                Some(prev_epoch) => self
+                    .get_staking_account(&pk, prev_epoch, genesis_state_hash)?
+                    .map(|account| account.delegate),
+                None => None,
+            };
+
+            delegators.push(EpochDelegator {
+                delegation_changed: prev_epoch.is_some()
+                    && prev_delegate.as_ref() != Some(delegate),
+                delegate: delegate.clone(),
+                pk,
+                epoch,
+                balance,
+                total_delegated,
+                prev_delegate,
+            });
+        }
+
+        Ok(delegators)
+    }
+
     fn get_epoch(&self, ledger_hash: &LedgerHash) -> anyhow::Result<Option<u32>> {
         trace!("Getting epoch for staking ledger {ledger_hash}");
         Ok(self
-            .database
             .get_cf(
                 self.staking_ledger_hash_to_epoch_cf(),
                 ledger_hash.0.as_bytes(),
@@ -260,7 +790,6 @@ impl StakingLedgerStore for IndexerStore {
             .unwrap();
 
         Ok(self
-            .database
             .get_cf(
                 self.staking_ledger_epoch_to_hash_cf(),
                 staking_ledger_epoch_key_prefix(genesis_state_hash, epoch),
@@ -315,7 +844,6 @@ impl StakingLedgerStore for IndexerStore {
     ) -> anyhow::Result<Option<StateHash>> {
         trace!("Getting genesis state hash for staking ledger {ledger_hash}");
         Ok(self
-            .database
             .get_cf(
                 self.staking_ledger_genesis_hash_cf(),
                 ledger_hash.0.as_bytes(),
@@ -339,7 +867,6 @@ impl StakingLedgerStore for IndexerStore {
     fn get_total_currency(&self, ledger_hash: &LedgerHash) -> anyhow::Result<Option<u64>> {
         trace!("Getting total currency for staking ledger {ledger_hash}");
         Ok(self
-            .database
             .get_cf(
                 self.staking_ledger_total_currency_cf(),
                 ledger_hash.0.as_bytes(),
@@ -354,7 +881,6 @@ impl StakingLedgerStore for IndexerStore {
     ) -> anyhow::Result<u32> {
         trace!("Getting staking ledger accounts count for epoch {epoch} {genesis_state_hash:?}");
         Ok(self
-            .database
             .get_cf(
                 self.staking_ledger_accounts_count_epoch_cf(),
                 staking_ledger_epoch_key_prefix(genesis_state_hash, epoch),
@@ -396,19 +922,20 @@ impl StakingLedgerStore for IndexerStore {
                     assert_eq!(genesis_hash, *genesis_state_hash);
                 }
 
+                let flat_epoch = self.get_flat_epoch(&genesis_hash, epoch)?;
                 let mut staking_ledger = HashMap::new();
                 for (key, _) in self
-                    .staking_ledger_account_balance_iterator(epoch, Direction::Reverse)
+                    .staking_ledger_account_balance_iterator(flat_epoch, Direction::Reverse)
                     .flatten()
                 {
                     let (key_epoch, balance, pk) = split_staking_ledger_sort_key(&key)?;
-                    if key_epoch != epoch {
+                    if key_epoch != flat_epoch {
                         // no longer the ledger of interest
                         break;
                     }
 
                     let account = self
-                        .get_staking_account(&pk, epoch, genesis_state_hash)?
+                        .get_staking_account(&pk, epoch, Some(&genesis_hash))?
                         .with_context(|| format!("epoch {epoch}, account {pk}"))
                         .expect("staking account exists");
                     assert_eq!(account.balance, balance);
@@ -443,14 +970,15 @@ impl StakingLedgerStore for IndexerStore {
                 self.get_genesis_state_hash(&ledger_hash)?,
             ) {
                 trace!("Staking ledger {network} (epoch {epoch}): {ledger_hash}");
+                let flat_epoch = self.get_flat_epoch(&genesis_state_hash, epoch)?;
                 let mut delegations = HashMap::new();
                 let mut total_delegations = 0;
                 for (key, _value) in self
-                    .staking_ledger_account_stake_iterator(epoch, Direction::Reverse)
+                    .staking_ledger_account_stake_iterator(flat_epoch, Direction::Reverse)
                     .flatten()
                 {
                     let (key_epoch, stake, pk) = split_staking_ledger_sort_key(&key)?;
-                    if key_epoch != epoch {
+                    if key_epoch != flat_epoch {
                         // no longer the staking ledger of interest
                         break;
                     }
@@ -483,36 +1011,63 @@ impl StakingLedgerStore for IndexerStore {
 
     fn staking_ledger_account_balance_iterator(
         &self,
-        epoch: u32,
+        flat_epoch: u32,
         direction: Direction,
     ) -> DBIterator<'_> {
-        let fstart = staking_ledger_sort_key(epoch, 0, &PublicKey::lower_bound());
-        let rstart = staking_ledger_sort_key(epoch, u64::MAX, &PublicKey::upper_bound());
+        let fstart = staking_ledger_sort_key(flat_epoch, 0, &PublicKey::lower_bound());
+        let rstart = staking_ledger_sort_key(flat_epoch, u64::MAX, &PublicKey::upper_bound());
         let mode = match direction {
             Direction::Forward => IteratorMode::From(&fstart, Direction::Forward),
             Direction::Reverse => IteratorMode::From(&rstart, Direction::Reverse),
         };
-        self.database
-            .iterator_cf(self.staking_ledger_balance_sort_cf(), mode)
+        self.iterator_cf(self.staking_ledger_balance_sort_cf(), mode)
     }
 
     fn staking_ledger_account_stake_iterator(
         &self,
-        epoch: u32,
+        flat_epoch: u32,
+        direction: Direction,
+    ) -> DBIterator<'_> {
+        let fstart = staking_ledger_sort_key(flat_epoch, 0, &PublicKey::lower_bound());
+        let rstart = staking_ledger_sort_key(flat_epoch, u64::MAX, &PublicKey::upper_bound());
+        let mode = match direction {
+            Direction::Forward => IteratorMode::From(&fstart, Direction::Forward),
+            Direction::Reverse => IteratorMode::From(&rstart, Direction::Reverse),
+        };
+        self.iterator_cf(self.staking_ledger_stake_sort_cf(), mode)
+    }
+
+    fn staking_ledger_delegate_balance_iterator(
+        &self,
+        flat_epoch: u32,
+        delegate: &PublicKey,
         direction: Direction,
     ) -> DBIterator<'_> {
-        let fstart = staking_ledger_sort_key(epoch, 0, &PublicKey::lower_bound());
-        let rstart = staking_ledger_sort_key(epoch, u64::MAX, &PublicKey::upper_bound());
+        let fstart =
+            staking_ledger_delegate_sort_key(flat_epoch, delegate, 0, &PublicKey::lower_bound());
+        let rstart = staking_ledger_delegate_sort_key(
+            flat_epoch,
+            delegate,
+            u64::MAX,
+            &PublicKey::upper_bound(),
+        );
         let mode = match direction {
             Direction::Forward => IteratorMode::From(&fstart, Direction::Forward),
             Direction::Reverse => IteratorMode::From(&rstart, Direction::Reverse),
         };
-        self.database
-            .iterator_cf(self.staking_ledger_stake_sort_cf(), mode)
+        self.iterator_cf(self.staking_ledger_delegate_balance_sort_cf(), mode)
     }
 
     fn staking_ledger_epoch_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.staking_ledger_persisted_cf(), mode)
+        self.iterator_cf(self.staking_ledger_persisted_cf(), mode)
+    }
+}
+
+impl IndexerStore {
+    /// Set the number of staking ledger accounts written per write batch,
+    /// `0` writes each staking ledger's accounts in a single batch
+    pub fn set_staking_ledger_batch_size(&self, batch_size: usize) {
+        self.staking_ledger_batch_size
+            .store(batch_size, Ordering::Relaxed);
     }
 }
@@ -0,0 +1,323 @@
+//! Versioned sub-stores of the [IndexerStore]
+//!
+//! Each store module declares the column families it owns, the format
+//! version of their data, & a migration which steps the data from one version
+//! to the next. When the store is opened, each sub-store's version is checked
+//! against the version recorded in the sub-store versions CF & pending
+//! migrations are run in dependency order.
+
+use super::{column_families::ColumnFamilyHelpers, IndexerStore, Result};
+use crate::utility::store::common::u32_from_be_bytes;
+use anyhow::{bail, Context};
+use log::{info, trace};
+use speedb::IteratorMode;
+use std::collections::HashSet;
+
+/// Column family storing each sub-store's recorded version
+pub const SUB_STORE_VERSIONS_CF: &str = "sub-store-versions";
+
+/// Migrates a sub-store's data from the given version to the next one
+pub type Migration = fn(&IndexerStore, u32) -> Result<()>;
+
+#[derive(Clone, Copy)]
+pub struct SubStore {
+    /// Unique name of the sub-store, key of its recorded version
+    pub name: &'static str,
+
+    /// Format version of the sub-store's data
+    pub version: u32,
+
+    /// Column families owned by the sub-store
+    pub column_families: &'static [&'static str],
+
+    /// Sub-stores which must be migrated before this one
+    pub dependencies: &'static [&'static str],
+
+    /// Called once per version step, with the version being migrated from
+    pub migrate: Migration,
+}
+
+/// Migration of a sub-store from its stored version to the binary's version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingMigration {
+    pub sub_store: &'static str,
+    pub from_version: u32,
+    pub to_version: u32,
+}
+
+impl SubStore {
+    /// Version of sub-stores populated before versions were recorded
+    pub const BASELINE_VERSION: u32 = 1;
+
+    /// Migration of sub-stores which have never changed format
+    pub fn no_migration(_: &IndexerStore, from_version: u32) -> Result<()> {
+        bail!("No migration from sub-store version {from_version}")
+    }
+}
+
+impl std::fmt::Debug for SubStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubStore")
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .field("column_families", &self.column_families)
+            .field("dependencies", &self.dependencies)
+            .finish()
+    }
+}
+
+impl std::fmt::Display for PendingMigration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: v{} -> v{}",
+            self.sub_store, self.from_version, self.to_version
+        )
+    }
+}
+
+/// Validates the sub-stores & orders them so that each one comes after its
+/// dependencies, otherwise preserving registration order
+pub fn sort_sub_stores(sub_stores: &[SubStore]) -> Result<Vec<SubStore>> {
+    let mut names = HashSet::new();
+    let mut column_families = HashSet::new();
+
+    for sub_store in sub_stores {
+        if !names.insert(sub_store.name) {
+            bail!("Duplicate sub-store {}", sub_store.name)
+        }
+
+        if sub_store.version < SubStore::BASELINE_VERSION {
+            bail!(
+                "Sub-store {} has invalid version {}",
+                sub_store.name,
+                sub_store.version
+            )
+        }
+
+        for cf in sub_store.column_families {
+            if *cf == SUB_STORE_VERSIONS_CF || !column_families.insert(*cf) {
+                bail!(
+                    "Column family {cf} of sub-store {} is already registered",
+                    sub_store.name
+                )
+            }
+        }
+    }
+
+    for sub_store in sub_stores {
+        for dep in sub_store.dependencies {
+            if !names.contains(dep) {
+                bail!(
+                    "Sub-store {} depends on unknown sub-store {dep}",
+                    sub_store.name
+                )
+            }
+        }
+    }
+
+    let mut sorted: Vec<SubStore> = Vec::with_capacity(sub_stores.len());
+    while sorted.len() < sub_stores.len() {
+        let next = sub_stores.iter().find(|sub_store| {
+            !sorted.iter().any(|done| done.name == sub_store.name)
+                && sub_store
+                    .dependencies
+                    .iter()
+                    .all(|dep| sorted.iter().any(|done| done.name == *dep))
+        });
+
+        match next {
+            Some(sub_store) => sorted.push(*sub_store),
+            None => bail!("Cyclic sub-store dependencies"),
+        }
+    }
+
+    Ok(sorted)
+}
+
+impl IndexerStore {
+    /// Version recorded for the sub-store
+    pub fn get_sub_store_version(&self, name: &str) -> Result<Option<u32>> {
+        trace!("Getting sub-store {name} version");
+        self.get_cf(self.sub_store_versions_cf(), name.as_bytes())?
+            .map(|bytes| u32_from_be_bytes(&bytes))
+            .transpose()
+    }
+
+    /// Records the sub-store's version
+    pub fn set_sub_store_version(&self, name: &str, version: u32) -> Result<()> {
+        trace!("Setting sub-store {name} version {version}");
+        self.database.put_cf(
+            self.sub_store_versions_cf(),
+            name.as_bytes(),
+            version.to_be_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// Migrations needed to bring the sub-stores up to their current versions,
+    /// in dependency order
+    ///
+    /// Errors if any sub-store's stored version is newer than its current
+    /// version
+    pub fn pending_migrations(&self, sub_stores: &[SubStore]) -> Result<Vec<PendingMigration>> {
+        let mut pending = vec![];
+        for sub_store in sort_sub_stores(sub_stores)? {
+            match self.stored_sub_store_version(&sub_store)? {
+                Some(stored) if stored > sub_store.version => bail!(
+                    "Sub-store {} version {stored} is newer than this binary's version {}. Upgrade mina-indexer to open this database",
+                    sub_store.name,
+                    sub_store.version
+                ),
+                Some(stored) if stored < sub_store.version => pending.push(PendingMigration {
+                    sub_store: sub_store.name,
+                    from_version: stored,
+                    to_version: sub_store.version,
+                }),
+                _ => (),
+            }
+        }
+
+        Ok(pending)
+    }
+
+    /// Runs all pending sub-store migrations & records each sub-store's
+    /// version. With `dry_run`, pending migrations are only logged.
+    pub fn migrate_sub_stores(&self, sub_stores: &[SubStore], dry_run: bool) -> Result<()> {
+        // check all stored versions before running any migrations
+        let pending = self.pending_migrations(sub_stores)?;
+
+        if dry_run {
+            if pending.is_empty() {
+                info!("No pending sub-store migrations");
+            }
+
+            for migration in pending {
+                info!("Pending sub-store migration {migration}");
+            }
+
+            return Ok(());
+        }
+
+        let num_pending = pending.len();
+        let mut num_started = 0;
+        for sub_store in sort_sub_stores(sub_stores)? {
+            let Some(mut version) = self.stored_sub_store_version(&sub_store)? else {
+                // fresh sub-store
+                self.set_sub_store_version(sub_store.name, sub_store.version)?;
+                continue;
+            };
+
+            if version < sub_store.version {
+                num_started += 1;
+                info!(
+                    "Migrating sub-store {} from v{version} to v{} ({num_started}/{num_pending})",
+                    sub_store.name, sub_store.version,
+                );
+            }
+
+            while version < sub_store.version {
+                (sub_store.migrate)(self, version).with_context(|| {
+                    format!(
+                        "Failed to migrate sub-store {} from v{version}",
+                        sub_store.name
+                    )
+                })?;
+
+                version += 1;
+                self.set_sub_store_version(sub_store.name, version)?;
+                info!("Migrated sub-store {} to v{version}", sub_store.name);
+            }
+
+            if self.get_sub_store_version(sub_store.name)?.is_none() {
+                self.set_sub_store_version(sub_store.name, version)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Recorded version, [SubStore::BASELINE_VERSION] for populated sub-stores
+    /// without a recorded version, or `None` for fresh sub-stores
+    fn stored_sub_store_version(&self, sub_store: &SubStore) -> Result<Option<u32>> {
+        if let Some(version) = self.get_sub_store_version(sub_store.name)? {
+            return Ok(Some(version));
+        }
+
+        for cf in sub_store.column_families {
+            let cf_name = self.cf_name(cf);
+            let cf_handle = self
+                .database
+                .cf_handle(&cf_name)
+                .with_context(|| format!("{cf_name} column family exists"))?;
+
+            if self
+                .iterator_cf(cf_handle, IteratorMode::Start)
+                .next()
+                .is_some()
+            {
+                return Ok(Some(SubStore::BASELINE_VERSION));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sub_store(name: &'static str, dependencies: &'static [&'static str]) -> SubStore {
+        SubStore {
+            name,
+            version: SubStore::BASELINE_VERSION,
+            column_families: &[],
+            dependencies,
+            migrate: SubStore::no_migration,
+        }
+    }
+
+    #[test]
+    fn registered_sub_stores_are_valid() -> Result<()> {
+        let sorted = sort_sub_stores(&IndexerStore::SUB_STORES)?;
+        assert_eq!(sorted.len(), IndexerStore::SUB_STORES.len());
+        Ok(())
+    }
+
+    #[test]
+    fn dependency_order() -> Result<()> {
+        let sub_stores = [
+            sub_store("c", &["b"]),
+            sub_store("a", &[]),
+            sub_store("b", &["a"]),
+            sub_store("d", &[]),
+        ];
+        let names: Vec<_> = sort_sub_stores(&sub_stores)?
+            .into_iter()
+            .map(|sub_store| sub_store.name)
+            .collect();
+
+        assert_eq!(names, vec!["a", "b", "c", "d"]);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_sub_stores() {
+        // duplicate names
+        assert!(sort_sub_stores(&[sub_store("a", &[]), sub_store("a", &[])]).is_err());
+
+        // unknown dependency
+        assert!(sort_sub_stores(&[sub_store("a", &["b"])]).is_err());
+
+        // cycle
+        assert!(sort_sub_stores(&[sub_store("a", &["b"]), sub_store("b", &["a"])]).is_err());
+
+        // shared column family
+        let mut a = sub_store("a", &[]);
+        let mut b = sub_store("b", &[]);
+        a.column_families = &["cf"];
+        b.column_families = &["cf"];
+        assert!(sort_sub_stores(&[a, b]).is_err());
+    }
+}
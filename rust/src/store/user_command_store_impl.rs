@@ -1,6 +1,6 @@
 use super::{
-    column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, user_command_db_key_pk,
-    username::UsernameStore, IndexerStore,
+    column_families::ColumnFamilyHelpers, fixed_keys::FixedKeys, sub_store::SubStore,
+    user_command_db_key_pk, username::UsernameStore, IndexerStore,
 };
 use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
@@ -9,21 +9,169 @@ use crate::{
         store::{BlockStore, DbBlockUpdate},
         BlockComparison,
     },
+    canonicity::{as_of::CanonicalChainAsOf, store::CanonicityStore, Canonicity},
     command::{
         signed::{SignedCommand, SignedCommandWithData, TxnHash},
-        store::UserCommandStore,
+        store::{DelegationChange, UserCommandStore},
         UserCommandWithStatus, UserCommandWithStatusT,
     },
     constants::millis_to_iso_date_string,
+    ledger::token::TokenAddress,
     utility::store::{
         command::user::*,
-        common::{from_be_bytes, pk_key_prefix, pk_txn_sort_key_sort, u32_prefix_key},
+        common::{from_be_bytes, pk_key_prefix, pk_txn_sort_key_sort, u32_prefix_key, U64_LEN},
     },
 };
 use anyhow::bail;
 use log::{trace, warn};
-use speedb::{DBIterator, IteratorMode, WriteBatch};
-use std::path::PathBuf;
+use speedb::{DBIterator, Direction, IteratorMode, WriteBatch};
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::PathBuf,
+};
+
+/// Add the corresponding CF helpers to [ColumnFamilyHelpers] & bump the
+/// sub-store version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "user-commands",
+    version: 5,
+    column_families: &[
+        "user-commands",
+        "user-commands-pk",
+        "user-commands-pk-num",
+        "user-commands-block",
+        "user-commands-block-order",
+        "user-commands-num-blocks",
+        "user-commands-slot-sort",
+        "user-commands-height-sort",
+        "user-commands-memo",
+        "user-commands-token",
+        "user-commands-failure",
+        "user-commands-delegations",
+        "user-commands-to-global-slot",
+        "user-commands-to-block-height",
+        "user-command-state-hashes",
+        // sorting user commands by sender/receiver
+        "txn-from-slot-sort",
+        "txn-from-height-sort",
+        "txn-to-slot-sort",
+        "txn-to-height-sort",
+        // user command counts
+        "user-commands-epoch",
+        "user-commands-pk-epoch",
+        "user-commands-pk-total",
+    ],
+    dependencies: &["blocks"],
+    migrate,
+};
+
+fn migrate(store: &IndexerStore, from_version: u32) -> anyhow::Result<()> {
+    match from_version {
+        1 => migrate_memo_index(store),
+        2 => migrate_delegation_index(store),
+        3 => migrate_token_index(store),
+        4 => migrate_failure_index(store),
+        _ => SubStore::no_migration(store, from_version),
+    }
+}
+
+/// Indexes the memos of the stored user commands
+fn migrate_memo_index(store: &IndexerStore) -> anyhow::Result<()> {
+    let mut batch = WriteBatch::default();
+    for (_, value) in store
+        .iterator_cf(store.user_commands_cf(), IteratorMode::Start)
+        .flatten()
+    {
+        let cmd: SignedCommandWithData = serde_json::from_slice(&value)?;
+        store.set_user_command_memo_batch(
+            cmd.blockchain_length,
+            &cmd.tx_hash,
+            &cmd.state_hash,
+            &cmd.command,
+            &mut batch,
+        );
+
+        if batch.len() >= 1000 {
+            store.database.write(std::mem::take(&mut batch))?;
+        }
+    }
+
+    Ok(store.database.write(batch)?)
+}
+
+/// Indexes the custom tokens of the stored user commands
+fn migrate_token_index(store: &IndexerStore) -> anyhow::Result<()> {
+    let mut batch = WriteBatch::default();
+    for (_, value) in store
+        .iterator_cf(store.user_commands_cf(), IteratorMode::Start)
+        .flatten()
+    {
+        let cmd: SignedCommandWithData = serde_json::from_slice(&value)?;
+        let tokens: Vec<_> = cmd
+            .command
+            .receiver_accounts()
+            .into_iter()
+            .map(|(_, token)| token)
+            .collect();
+        store.set_user_command_tokens_batch(
+            cmd.blockchain_length,
+            &cmd.tx_hash,
+            &cmd.state_hash,
+            &tokens,
+            &mut batch,
+        );
+
+        if batch.len() >= 1000 {
+            store.database.write(std::mem::take(&mut batch))?;
+        }
+    }
+
+    Ok(store.database.write(batch)?)
+}
+
+/// Indexes the failure kinds of the stored failed user commands
+fn migrate_failure_index(store: &IndexerStore) -> anyhow::Result<()> {
+    let mut batch = WriteBatch::default();
+    for (_, value) in store
+        .iterator_cf(store.user_commands_cf(), IteratorMode::Start)
+        .flatten()
+    {
+        let cmd: SignedCommandWithData = serde_json::from_slice(&value)?;
+        if let Some(kind) = cmd.status.failure_kind() {
+            store.set_user_command_failure_batch(
+                cmd.blockchain_length,
+                &cmd.tx_hash,
+                &cmd.state_hash,
+                &kind,
+                &mut batch,
+            );
+        }
+
+        if batch.len() >= 1000 {
+            store.database.write(std::mem::take(&mut batch))?;
+        }
+    }
+
+    Ok(store.database.write(batch)?)
+}
+
+/// Indexes the delegation changes of the stored blocks
+fn migrate_delegation_index(store: &IndexerStore) -> anyhow::Result<()> {
+    let mut batch = WriteBatch::default();
+    for (_, value) in store
+        .iterator_cf(store.blocks_cf(), IteratorMode::Start)
+        .flatten()
+    {
+        let block: PrecomputedBlock = serde_json::from_slice(&value[U64_LEN..])?;
+        store.set_block_delegations_batch(&block, &mut batch)?;
+
+        if batch.len() >= 1000 {
+            store.database.write(std::mem::take(&mut batch))?;
+        }
+    }
+
+    Ok(store.database.write(batch)?)
+}
 
 impl UserCommandStore for IndexerStore {
     fn add_user_commands_batch(
@@ -36,14 +184,18 @@ impl UserCommandStore for IndexerStore {
         let epoch = block.epoch_count();
         let state_hash = block.state_hash();
         let user_commands = block.commands();
+        let accounts_created = block.command_accounts_created();
 
         // per block
         self.set_block_user_commands_batch(block, batch)?;
         self.set_block_user_commands_count_batch(&state_hash, user_commands.len() as u32, batch)?;
         self.set_block_username_updates_batch(&state_hash, &block.username_updates(), batch)?;
+        self.set_block_delegations_batch(block, batch)?;
 
         // per command
-        for command in &user_commands {
+        for (index, (command, accounts_created)) in
+            user_commands.iter().zip(&accounts_created).enumerate()
+        {
             let signed = SignedCommand::from(command.clone());
             let txn_hash = signed.hash_signed_command()?;
             trace!("Adding user command {txn_hash} block {}", block.summary());
@@ -58,6 +210,8 @@ impl UserCommandStore for IndexerStore {
                     block.blockchain_length(),
                     block.timestamp(),
                     block.global_slot_since_genesis(),
+                    accounts_created.clone(),
+                    index as u32,
                 ))?,
             );
 
@@ -78,6 +232,35 @@ impl UserCommandStore for IndexerStore {
                 b"",
             );
 
+            // add index for memo search
+            self.set_user_command_memo_batch(
+                block.blockchain_length(),
+                &txn_hash,
+                &state_hash,
+                &signed,
+                batch,
+            );
+
+            // add index for token activity
+            self.set_user_command_tokens_batch(
+                block.blockchain_length(),
+                &txn_hash,
+                &state_hash,
+                &command.tokens(),
+                batch,
+            );
+
+            // add index for failure analytics
+            if let Some(kind) = command.status_data().failure_kind() {
+                self.set_user_command_failure_batch(
+                    block.blockchain_length(),
+                    &txn_hash,
+                    &state_hash,
+                    &kind,
+                    batch,
+                );
+            }
+
             // increment counts
             self.increment_user_commands_counts(command, epoch)?;
 
@@ -151,14 +334,18 @@ impl UserCommandStore for IndexerStore {
                 .unwrap_or_default();
             let block_pk_commands: Vec<SignedCommandWithData> = user_commands
                 .iter()
-                .filter(|cmd| cmd.contains_public_key(&pk))
-                .map(|c| {
+                .zip(&accounts_created)
+                .enumerate()
+                .filter(|(_, (cmd, _))| cmd.contains_public_key(&pk))
+                .map(|(index, (c, accounts_created))| {
                     SignedCommandWithData::from(
                         c,
                         &state_hash.0,
                         block.blockchain_length(),
                         block.timestamp(),
                         block.global_slot_since_genesis(),
+                        accounts_created.clone(),
+                        index as u32,
                     )
                 })
                 .collect();
@@ -182,6 +369,135 @@ impl UserCommandStore for IndexerStore {
         Ok(())
     }
 
+    fn remove_user_commands_batch(
+        &self,
+        blocks: &[PrecomputedBlock],
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()> {
+        let state_hashes: HashSet<StateHash> = blocks.iter().map(|b| b.state_hash()).collect();
+        let mut txn_hashes = HashSet::new();
+
+        for block in blocks {
+            trace!("Removing user commands from block {}", block.summary());
+            let state_hash = block.state_hash();
+            let block_height = block.blockchain_length();
+            let global_slot = block.global_slot_since_genesis();
+
+            // per block
+            batch.delete_cf(self.user_commands_per_block_cf(), state_hash.0.as_bytes());
+            batch.delete_cf(self.block_user_command_counts_cf(), state_hash.0.as_bytes());
+            batch.delete_cf(self.usernames_per_block_cf(), state_hash.0.as_bytes());
+
+            for change in DelegationChange::from_precomputed(block) {
+                batch.delete_cf(
+                    self.user_commands_delegations_cf(),
+                    delegation_history_key(
+                        &change.delegator,
+                        change.blockchain_length,
+                        &change.state_hash,
+                        change.index_in_block,
+                    ),
+                );
+            }
+
+            // per command
+            for command in block.commands() {
+                let signed = SignedCommand::from(command.clone());
+                let txn_hash = signed.hash_signed_command()?;
+                let nonce = command.nonce().0;
+
+                batch.delete_cf(
+                    self.user_commands_cf(),
+                    txn_block_key(&txn_hash, &state_hash),
+                );
+                batch.delete_cf(
+                    self.user_commands_slot_sort_cf(),
+                    txn_sort_key(global_slot, &txn_hash, &state_hash),
+                );
+                batch.delete_cf(
+                    self.user_commands_height_sort_cf(),
+                    txn_sort_key(block_height, &txn_hash, &state_hash),
+                );
+
+                if !signed.memo().is_empty() {
+                    batch.delete_cf(
+                        self.user_commands_memo_cf(),
+                        txn_sort_key(block_height, &txn_hash, &state_hash),
+                    );
+                }
+
+                let default_token = TokenAddress::default();
+                for token in command.tokens().iter().filter(|t| **t != default_token) {
+                    batch.delete_cf(
+                        self.user_commands_token_cf(),
+                        token_txn_sort_key(token, block_height, &txn_hash, &state_hash),
+                    );
+                }
+
+                if let Some(kind) = command.status_data().failure_kind() {
+                    batch.delete_cf(
+                        self.user_commands_failure_cf(),
+                        failure_txn_sort_key(&kind, block_height, &txn_hash, &state_hash),
+                    );
+                }
+
+                // sender & receiver indexes
+                let sender = command.sender();
+                batch.delete_cf(
+                    self.txn_from_height_sort_cf(),
+                    pk_txn_sort_key(&sender, block_height, nonce, &txn_hash, &state_hash),
+                );
+                batch.delete_cf(
+                    self.txn_from_slot_sort_cf(),
+                    pk_txn_sort_key(&sender, global_slot, nonce, &txn_hash, &state_hash),
+                );
+
+                for receiver in command.receiver() {
+                    batch.delete_cf(
+                        self.txn_to_height_sort_cf(),
+                        pk_txn_sort_key(&receiver, block_height, nonce, &txn_hash, &state_hash),
+                    );
+                    batch.delete_cf(
+                        self.txn_to_slot_sort_cf(),
+                        pk_txn_sort_key(&receiver, global_slot, nonce, &txn_hash, &state_hash),
+                    );
+                }
+
+                txn_hashes.insert(txn_hash);
+            }
+        }
+
+        // drop the removed blocks from the commands' containing blocks, all
+        // at once since the batch's writes aren't visible to reads
+        for txn_hash in txn_hashes {
+            let blocks: Vec<StateHash> = self
+                .get_user_command_state_hashes(&txn_hash)?
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|state_hash| !state_hashes.contains(state_hash))
+                .collect();
+            let key = txn_hash.ref_inner().as_bytes();
+
+            if blocks.is_empty() {
+                batch.delete_cf(self.user_command_state_hashes_cf(), key);
+                batch.delete_cf(self.user_commands_num_containing_blocks_cf(), key);
+                batch.delete_cf(self.user_commands_txn_hash_to_global_slot_cf(), key);
+            } else {
+                batch.put_cf(
+                    self.user_commands_num_containing_blocks_cf(),
+                    key,
+                    (blocks.len() as u32).to_be_bytes(),
+                );
+                batch.put_cf(
+                    self.user_command_state_hashes_cf(),
+                    key,
+                    serde_json::to_vec(&blocks)?,
+                );
+            }
+        }
+        Ok(())
+    }
+
     fn get_user_command(
         &self,
         txn_hash: &TxnHash,
@@ -208,18 +524,118 @@ impl UserCommandStore for IndexerStore {
     ) -> anyhow::Result<Option<SignedCommandWithData>> {
         trace!("Getting user command {txn_hash} in block {state_hash}");
         Ok(self
-            .database
             .get_pinned_cf(self.user_commands_cf(), txn_block_key(txn_hash, state_hash))?
             .and_then(|bytes| serde_json::from_slice(&bytes).ok()))
     }
 
+    fn set_user_command_memo_batch(
+        &self,
+        block_height: u32,
+        txn_hash: &TxnHash,
+        state_hash: &StateHash,
+        command: &SignedCommand,
+        batch: &mut WriteBatch,
+    ) {
+        let memo = command.memo();
+        if memo.is_empty() {
+            return;
+        }
+
+        batch.put_cf(
+            self.user_commands_memo_cf(),
+            txn_sort_key(block_height, txn_hash, state_hash),
+            txn_memo_value(command.fee(), &memo),
+        );
+    }
+
+    fn set_user_command_tokens_batch(
+        &self,
+        block_height: u32,
+        txn_hash: &TxnHash,
+        state_hash: &StateHash,
+        tokens: &[TokenAddress],
+        batch: &mut WriteBatch,
+    ) {
+        let default_token = TokenAddress::default();
+        for token in tokens.iter().filter(|token| **token != default_token) {
+            batch.put_cf(
+                self.user_commands_token_cf(),
+                token_txn_sort_key(token, block_height, txn_hash, state_hash),
+                b"",
+            );
+        }
+    }
+
+    fn set_user_command_failure_batch(
+        &self,
+        block_height: u32,
+        txn_hash: &TxnHash,
+        state_hash: &StateHash,
+        failure_kind: &str,
+        batch: &mut WriteBatch,
+    ) {
+        batch.put_cf(
+            self.user_commands_failure_cf(),
+            failure_txn_sort_key(failure_kind, block_height, txn_hash, state_hash),
+            b"",
+        );
+    }
+
+    fn set_block_delegations_batch(
+        &self,
+        block: &PrecomputedBlock,
+        batch: &mut WriteBatch,
+    ) -> anyhow::Result<()> {
+        for change in DelegationChange::from_precomputed(block) {
+            batch.put_cf(
+                self.user_commands_delegations_cf(),
+                delegation_history_key(
+                    &change.delegator,
+                    change.blockchain_length,
+                    &change.state_hash,
+                    change.index_in_block,
+                ),
+                serde_json::to_vec(&change)?,
+            );
+        }
+
+        Ok(())
+    }
+
+    fn get_delegation_history(
+        &self,
+        delegator: &PublicKey,
+    ) -> anyhow::Result<Vec<DelegationChange>> {
+        trace!("Getting delegation history of {delegator}");
+
+        let mut changes = vec![];
+        let prefix = delegator.0.as_bytes();
+        for (key, value) in self
+            .iterator_cf(
+                self.user_commands_delegations_cf(),
+                IteratorMode::From(prefix, Direction::Forward),
+            )
+            .flatten()
+        {
+            if !key.starts_with(prefix) {
+                break;
+            }
+
+            let change: DelegationChange = serde_json::from_slice(&value)?;
+            if self.get_block_canonicity(&change.state_hash)? == Some(Canonicity::Canonical) {
+                changes.push(change);
+            }
+        }
+
+        Ok(changes)
+    }
+
     fn get_user_command_state_hashes(
         &self,
         txn_hash: &TxnHash,
     ) -> anyhow::Result<Option<Vec<StateHash>>> {
         trace!("Getting user command blocks {txn_hash}");
         Ok(self
-            .database
             .get_pinned_cf(
                 self.user_command_state_hashes_cf(),
                 txn_hash.ref_inner().as_bytes(),
@@ -284,7 +700,6 @@ impl UserCommandStore for IndexerStore {
     ) -> anyhow::Result<Option<Vec<UserCommandWithStatus>>> {
         trace!("Getting block user commands {state_hash}");
         Ok(self
-            .database
             .get_pinned_cf(self.user_commands_per_block_cf(), state_hash.0.as_bytes())?
             .and_then(|bytes| serde_json::from_slice(&bytes).ok()))
     }
@@ -304,7 +719,6 @@ impl UserCommandStore for IndexerStore {
             // collect user commands from all pk's blocks
             for m in 0..n {
                 if let Some(mut block_m_commands) = self
-                    .database
                     .get_pinned_cf(self.user_commands_pk_cf(), key_n(pk, m))?
                     .and_then(|bytes| {
                         serde_json::from_slice::<Vec<SignedCommandWithData>>(&bytes).ok()
@@ -373,7 +787,6 @@ impl UserCommandStore for IndexerStore {
     ) -> anyhow::Result<Option<u32>> {
         trace!("Getting user commands num containing blocks {txn_hash}");
         Ok(self
-            .database
             .get_cf(
                 self.user_commands_num_containing_blocks_cf(),
                 txn_hash.ref_inner().as_bytes(),
@@ -385,7 +798,12 @@ impl UserCommandStore for IndexerStore {
         &self,
         pk: &PublicKey,
         path: Option<PathBuf>,
+        as_of_height: Option<u32>,
     ) -> anyhow::Result<PathBuf> {
+        let mut as_of = as_of_height
+            .map(|height| CanonicalChainAsOf::new(self, height))
+            .transpose()?;
+
         let mut txns = vec![];
         let start = pk_txn_sort_key_prefix(pk, u32::MAX);
         let mode = IteratorMode::From(&start, speedb::Direction::Reverse);
@@ -399,7 +817,8 @@ impl UserCommandStore for IndexerStore {
             let height = pk_txn_sort_key_sort(&key);
             let nonce = pk_txn_sort_key_nonce(&key);
             let txn_hash = txn_hash_of_key(&key);
-            txns.push((height, nonce, txn_hash));
+            let state_hash = pk_txn_sort_key_state_hash(&key);
+            txns.push((height, nonce, txn_hash, state_hash));
         }
 
         // to txns
@@ -411,7 +830,8 @@ impl UserCommandStore for IndexerStore {
             let height = pk_txn_sort_key_sort(&key);
             let nonce = pk_txn_sort_key_nonce(&key);
             let txn_hash = txn_hash_of_key(&key);
-            txns.push((height, nonce, txn_hash));
+            let state_hash = pk_txn_sort_key_state_hash(&key);
+            txns.push((height, nonce, txn_hash, state_hash));
         }
 
         txns.sort();
@@ -431,8 +851,17 @@ impl UserCommandStore for IndexerStore {
         let mut csv_writer = csv::WriterBuilder::new()
             .has_headers(true)
             .from_path(path.clone())?;
-        for (_, _, txn_hash) in txns {
-            if let Some(cmd) = self.get_user_command(&txn_hash, 0)?.as_ref() {
+        for (height, _, txn_hash, state_hash) in txns {
+            if let Some(as_of) = as_of.as_mut() {
+                if !as_of.contains_at(self, &state_hash, height)? {
+                    continue;
+                }
+            }
+
+            if let Some(cmd) = self
+                .get_user_command_state_hash(&txn_hash, &state_hash)?
+                .as_ref()
+            {
                 csv_writer.serialize(TxnCsvRecord::from_user_command(cmd))?;
             } else {
                 bail!("User command missing: {txn_hash}")
@@ -448,32 +877,39 @@ impl UserCommandStore for IndexerStore {
     ///////////////
 
     fn user_commands_slot_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.user_commands_slot_sort_cf(), mode)
+        self.iterator_cf(self.user_commands_slot_sort_cf(), mode)
     }
 
     fn user_commands_height_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.user_commands_height_sort_cf(), mode)
+        self.iterator_cf(self.user_commands_height_sort_cf(), mode)
+    }
+
+    fn user_commands_memo_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
+        self.iterator_cf(self.user_commands_memo_cf(), mode)
+    }
+
+    fn user_commands_token_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
+        self.iterator_cf(self.user_commands_token_cf(), mode)
+    }
+
+    fn user_commands_failure_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
+        self.iterator_cf(self.user_commands_failure_cf(), mode)
     }
 
     fn txn_from_height_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.txn_from_height_sort_cf(), mode)
+        self.iterator_cf(self.txn_from_height_sort_cf(), mode)
     }
 
     fn txn_from_slot_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.txn_from_slot_sort_cf(), mode)
+        self.iterator_cf(self.txn_from_slot_sort_cf(), mode)
     }
 
     fn txn_to_height_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database
-            .iterator_cf(self.txn_to_height_sort_cf(), mode)
+        self.iterator_cf(self.txn_to_height_sort_cf(), mode)
     }
 
     fn txn_to_slot_iterator(&self, mode: IteratorMode) -> DBIterator<'_> {
-        self.database.iterator_cf(self.txn_to_slot_sort_cf(), mode)
+        self.iterator_cf(self.txn_to_slot_sort_cf(), mode)
     }
 
     /////////////////////////
@@ -483,7 +919,6 @@ impl UserCommandStore for IndexerStore {
     fn get_pk_num_user_commands_blocks(&self, pk: &PublicKey) -> anyhow::Result<Option<u32>> {
         trace!("Getting number of user commands for {pk}");
         Ok(self
-            .database
             .get_cf(self.user_commands_pk_num_cf(), pk.0.as_bytes())?
             .map(from_be_bytes))
     }
@@ -492,7 +927,6 @@ impl UserCommandStore for IndexerStore {
         let epoch = epoch.unwrap_or(self.get_current_epoch()?);
         trace!("Getting user command epoch {epoch}");
         Ok(self
-            .database
             .get_cf(self.user_commands_epoch_cf(), epoch.to_be_bytes())?
             .map_or(0, from_be_bytes))
     }
@@ -510,17 +944,18 @@ impl UserCommandStore for IndexerStore {
     fn get_user_commands_total_count(&self) -> anyhow::Result<u32> {
         trace!("Getting user command total");
         Ok(self
-            .database
-            .get(Self::TOTAL_NUM_USER_COMMANDS_KEY)?
+            .get_cf(self.default_cf(), Self::TOTAL_NUM_USER_COMMANDS_KEY)?
             .map_or(0, from_be_bytes))
     }
 
     fn increment_user_commands_total_count(&self) -> anyhow::Result<()> {
         trace!("Incrementing user command total");
         let old = self.get_user_commands_total_count()?;
-        Ok(self
-            .database
-            .put(Self::TOTAL_NUM_USER_COMMANDS_KEY, (old + 1).to_be_bytes())?)
+        Ok(self.database.put_cf(
+            self.default_cf(),
+            Self::TOTAL_NUM_USER_COMMANDS_KEY,
+            (old + 1).to_be_bytes(),
+        )?)
     }
 
     fn get_user_commands_pk_epoch_count(
@@ -531,7 +966,6 @@ impl UserCommandStore for IndexerStore {
         let epoch = epoch.unwrap_or(self.get_current_epoch()?);
         trace!("Getting user command epoch {epoch} num {pk}");
         Ok(self
-            .database
             .get_pinned_cf(self.user_commands_pk_epoch_cf(), u32_prefix_key(epoch, pk))?
             .map_or(0, |bytes| from_be_bytes(bytes.to_vec())))
     }
@@ -553,7 +987,6 @@ impl UserCommandStore for IndexerStore {
     fn get_user_commands_pk_total_count(&self, pk: &PublicKey) -> anyhow::Result<u32> {
         trace!("Getting pk total user commands count {pk}");
         Ok(self
-            .database
             .get_pinned_cf(self.user_commands_pk_total_cf(), pk.0.as_bytes())?
             .map_or(0, |bytes| from_be_bytes(bytes.to_vec())))
     }
@@ -586,7 +1019,6 @@ impl UserCommandStore for IndexerStore {
     fn get_block_user_commands_count(&self, state_hash: &StateHash) -> anyhow::Result<Option<u32>> {
         trace!("Getting block user command count {state_hash}");
         Ok(self
-            .database
             .get_pinned_cf(self.block_user_command_counts_cf(), state_hash.0.as_bytes())?
             .map(|bytes| from_be_bytes(bytes.to_vec())))
     }
@@ -629,8 +1061,7 @@ impl UserCommandStore for IndexerStore {
     fn get_applied_user_commands_count(&self) -> anyhow::Result<u32> {
         trace!("Getting applied user command count");
         Ok(self
-            .database
-            .get(Self::TOTAL_NUM_APPLIED_USER_COMMANDS_KEY)?
+            .get_cf(self.default_cf(), Self::TOTAL_NUM_APPLIED_USER_COMMANDS_KEY)?
             .map_or(0, from_be_bytes))
     }
 
@@ -638,16 +1069,70 @@ impl UserCommandStore for IndexerStore {
     fn get_failed_user_commands_count(&self) -> anyhow::Result<u32> {
         trace!("Getting failed user command count");
         Ok(self
-            .database
-            .get(Self::TOTAL_NUM_FAILED_USER_COMMANDS_KEY)?
+            .get_cf(self.default_cf(), Self::TOTAL_NUM_FAILED_USER_COMMANDS_KEY)?
             .map_or(0, from_be_bytes))
     }
 
+    fn get_user_command_failure_counts(
+        &self,
+        from_height: u32,
+        to_height: u32,
+        canonical: Option<bool>,
+    ) -> anyhow::Result<BTreeMap<String, u32>> {
+        trace!("Getting user command failure counts {from_height}..={to_height}");
+        let mut counts = BTreeMap::new();
+        let mut seek = vec![];
+
+        // seek each failure kind's height range, then past the kind
+        loop {
+            let mut iter =
+                self.user_commands_failure_iterator(IteratorMode::From(&seek, Direction::Forward));
+            let Some((key, _)) = iter.next().transpose()? else {
+                break;
+            };
+
+            let (kind, _) = failure_kind_of_key(&key)?;
+            for (key, _) in self
+                .user_commands_failure_iterator(IteratorMode::From(
+                    &failure_txn_sort_key_prefix(&kind, from_height),
+                    Direction::Forward,
+                ))
+                .flatten()
+            {
+                let (key_kind, key) = failure_kind_of_key(&key)?;
+                if key_kind != kind || user_commands_iterator_u32_prefix(key) > to_height {
+                    break;
+                }
+
+                if let Some(canonical) = canonical {
+                    let state_hash = user_commands_iterator_state_hash(key)?;
+                    let is_canonical = matches!(
+                        self.get_block_canonicity(&state_hash)?,
+                        Some(Canonicity::Canonical)
+                    );
+
+                    if is_canonical != canonical {
+                        continue;
+                    }
+                }
+
+                *counts.entry(kind.clone()).or_insert(0) += 1;
+            }
+
+            // the terminator is the least byte, so this is past all the
+            // kind's keys
+            seek = [kind.as_bytes(), &[1]].concat();
+        }
+
+        Ok(counts)
+    }
+
     /// Increment applied user commands count
     fn increment_applied_user_commands_count(&self, incr: u32) -> anyhow::Result<()> {
         trace!("Incrementing applied user command count");
         let old = self.get_applied_user_commands_count()?;
-        Ok(self.database.put(
+        Ok(self.database.put_cf(
+            self.default_cf(),
             Self::TOTAL_NUM_APPLIED_USER_COMMANDS_KEY,
             (old + incr).to_be_bytes(),
         )?)
@@ -657,7 +1142,8 @@ impl UserCommandStore for IndexerStore {
     fn increment_failed_user_commands_count(&self, incr: u32) -> anyhow::Result<()> {
         trace!("Incrementing failed user command count");
         let old = self.get_failed_user_commands_count()?;
-        Ok(self.database.put(
+        Ok(self.database.put_cf(
+            self.default_cf(),
             Self::TOTAL_NUM_FAILED_USER_COMMANDS_KEY,
             (old + incr).to_be_bytes(),
         )?)
@@ -667,7 +1153,8 @@ impl UserCommandStore for IndexerStore {
     fn decrement_applied_user_commands_count(&self, incr: u32) -> anyhow::Result<()> {
         trace!("Decrementing applied user command count");
         let old = self.get_applied_user_commands_count()?;
-        Ok(self.database.put(
+        Ok(self.database.put_cf(
+            self.default_cf(),
             Self::TOTAL_NUM_APPLIED_USER_COMMANDS_KEY,
             (old.saturating_sub(incr)).to_be_bytes(),
         )?)
@@ -677,7 +1164,8 @@ impl UserCommandStore for IndexerStore {
     fn decrement_failed_user_commands_count(&self, incr: u32) -> anyhow::Result<()> {
         trace!("Decrementing failed user command count");
         let old = self.get_failed_user_commands_count()?;
-        Ok(self.database.put(
+        Ok(self.database.put_cf(
+            self.default_cf(),
             Self::TOTAL_NUM_FAILED_USER_COMMANDS_KEY,
             (old.saturating_sub(incr)).to_be_bytes(),
         )?)
@@ -687,8 +1175,10 @@ impl UserCommandStore for IndexerStore {
     fn get_canonical_user_commands_count(&self) -> anyhow::Result<u32> {
         trace!("Getting canonical user command count");
         Ok(self
-            .database
-            .get(Self::TOTAL_NUM_CANONICAL_USER_COMMANDS_KEY)?
+            .get_cf(
+                self.default_cf(),
+                Self::TOTAL_NUM_CANONICAL_USER_COMMANDS_KEY,
+            )?
             .map_or(0, from_be_bytes))
     }
 
@@ -696,7 +1186,8 @@ impl UserCommandStore for IndexerStore {
     fn increment_canonical_user_commands_count(&self, incr: u32) -> anyhow::Result<()> {
         trace!("Incrementing canonical user command count");
         let old = self.get_canonical_user_commands_count()?;
-        Ok(self.database.put(
+        Ok(self.database.put_cf(
+            self.default_cf(),
             Self::TOTAL_NUM_CANONICAL_USER_COMMANDS_KEY,
             (old + incr).to_be_bytes(),
         )?)
@@ -706,7 +1197,8 @@ impl UserCommandStore for IndexerStore {
     fn decrement_canonical_user_commands_count(&self, incr: u32) -> anyhow::Result<()> {
         trace!("Decrementing canonical user command count");
         let old = self.get_canonical_user_commands_count()?;
-        Ok(self.database.put(
+        Ok(self.database.put_cf(
+            self.default_cf(),
             Self::TOTAL_NUM_CANONICAL_USER_COMMANDS_KEY,
             (old.saturating_sub(incr)).to_be_bytes(),
         )?)
@@ -716,8 +1208,10 @@ impl UserCommandStore for IndexerStore {
     fn get_applied_canonical_user_commands_count(&self) -> anyhow::Result<u32> {
         trace!("Getting applied canonical user command count");
         Ok(self
-            .database
-            .get(Self::TOTAL_NUM_APPLIED_CANONICAL_USER_COMMANDS_KEY)?
+            .get_cf(
+                self.default_cf(),
+                Self::TOTAL_NUM_APPLIED_CANONICAL_USER_COMMANDS_KEY,
+            )?
             .map_or(0, from_be_bytes))
     }
 
@@ -725,7 +1219,8 @@ impl UserCommandStore for IndexerStore {
     fn increment_applied_canonical_user_commands_count(&self, incr: u32) -> anyhow::Result<()> {
         trace!("Incrementing applied canonical user command count");
         let old = self.get_applied_canonical_user_commands_count()?;
-        Ok(self.database.put(
+        Ok(self.database.put_cf(
+            self.default_cf(),
             Self::TOTAL_NUM_APPLIED_CANONICAL_USER_COMMANDS_KEY,
             (old + incr).to_be_bytes(),
         )?)
@@ -735,7 +1230,8 @@ impl UserCommandStore for IndexerStore {
     fn decrement_applied_canonical_user_commands_count(&self, incr: u32) -> anyhow::Result<()> {
         trace!("Decrementing applied canonical user command count");
         let old = self.get_applied_canonical_user_commands_count()?;
-        Ok(self.database.put(
+        Ok(self.database.put_cf(
+            self.default_cf(),
             Self::TOTAL_NUM_APPLIED_CANONICAL_USER_COMMANDS_KEY,
             (old.saturating_sub(incr)).to_be_bytes(),
         )?)
@@ -745,8 +1241,10 @@ impl UserCommandStore for IndexerStore {
     fn get_failed_canonical_user_commands_count(&self) -> anyhow::Result<u32> {
         trace!("Getting failed canonical user command count");
         Ok(self
-            .database
-            .get(Self::TOTAL_NUM_FAILED_CANONICAL_USER_COMMANDS_KEY)?
+            .get_cf(
+                self.default_cf(),
+                Self::TOTAL_NUM_FAILED_CANONICAL_USER_COMMANDS_KEY,
+            )?
             .map_or(0, from_be_bytes))
     }
 
@@ -754,7 +1252,8 @@ impl UserCommandStore for IndexerStore {
     fn increment_failed_canonical_user_commands_count(&self, incr: u32) -> anyhow::Result<()> {
         trace!("Incrementing failed canonical user command count");
         let old = self.get_failed_canonical_user_commands_count()?;
-        Ok(self.database.put(
+        Ok(self.database.put_cf(
+            self.default_cf(),
             Self::TOTAL_NUM_FAILED_CANONICAL_USER_COMMANDS_KEY,
             (old + incr).to_be_bytes(),
         )?)
@@ -764,7 +1263,8 @@ impl UserCommandStore for IndexerStore {
     fn decrement_failed_canonical_user_commands_count(&self, incr: u32) -> anyhow::Result<()> {
         trace!("Decrementing failed canonical user command count");
         let old = self.get_failed_canonical_user_commands_count()?;
-        Ok(self.database.put(
+        Ok(self.database.put_cf(
+            self.default_cf(),
             Self::TOTAL_NUM_FAILED_CANONICAL_USER_COMMANDS_KEY,
             (old.saturating_sub(incr)).to_be_bytes(),
         )?)
@@ -23,6 +23,9 @@ pub trait UsernameStore {
     /// Get pk's index-th username
     fn get_pk_username(&self, pk: &PublicKey, index: u32) -> anyhow::Result<Option<Username>>;
 
+    /// Get the pks whose current username is `username`
+    fn get_username_pks(&self, username: &str) -> anyhow::Result<Vec<PublicKey>>;
+
     /// Set the user name updates in the block
     fn set_block_username_updates_batch(
         &self,
@@ -1,5 +1,6 @@
 use super::{
     column_families::ColumnFamilyHelpers,
+    sub_store::SubStore,
     username::{UsernameAccountUpdate, UsernameStore, UsernameUpdate},
     DbUpdate, IndexerStore,
 };
@@ -10,9 +11,65 @@ use crate::{
     utility::store::common::{from_be_bytes, pk_index_key},
 };
 use log::{error, trace};
-use speedb::WriteBatch;
+use speedb::{Direction, IteratorMode, WriteBatch};
 use std::collections::HashMap;
 
+/// Add the corresponding CF helpers to [ColumnFamilyHelpers] & bump the
+/// sub-store version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "usernames",
+    version: 2,
+    column_families: &[
+        "username-pk-num",
+        "username-pk-index",
+        "usernames-per-block",
+        "username-name-pk",
+    ],
+    dependencies: &[],
+    migrate,
+};
+
+/// - v1 -> v2: index the pks of existing current usernames
+fn migrate(store: &IndexerStore, from_version: u32) -> anyhow::Result<()> {
+    match from_version {
+        1 => migrate_username_pks(store),
+        _ => SubStore::no_migration(store, from_version),
+    }
+}
+
+fn migrate_username_pks(store: &IndexerStore) -> anyhow::Result<()> {
+    let mut batch = WriteBatch::default();
+    for (key, _) in store
+        .iterator_cf(store.username_pk_num_cf(), IteratorMode::Start)
+        .flatten()
+    {
+        let pk = PublicKey::from_bytes(&key)?;
+        if let Some(username) = store.get_username(&pk)? {
+            batch.put_cf(
+                store.username_name_pk_cf(),
+                username_pk_key(&username.0, &pk),
+                b"",
+            );
+        }
+    }
+
+    Ok(store.database.write(batch)?)
+}
+
+/// Key for the current username's pks, length-prefixed so no username is a
+/// prefix of another's keys
+fn username_pk_key(username: &str, pk: &PublicKey) -> Vec<u8> {
+    let mut key = username_prefix(username);
+    key.extend_from_slice(pk.0.as_bytes());
+    key
+}
+
+fn username_prefix(username: &str) -> Vec<u8> {
+    let mut prefix = vec![username.len() as u8];
+    prefix.extend_from_slice(username.as_bytes());
+    prefix
+}
+
 impl UsernameStore for IndexerStore {
     fn get_username(&self, pk: &PublicKey) -> anyhow::Result<Option<Username>> {
         trace!("Getting {pk} username");
@@ -43,7 +100,6 @@ impl UsernameStore for IndexerStore {
     ) -> anyhow::Result<Option<HashMap<PublicKey, Username>>> {
         trace!("Getting block username updates {state_hash}");
         Ok(self
-            .database
             .get_pinned_cf(self.usernames_per_block_cf(), state_hash.0.as_bytes())?
             .and_then(|bytes| serde_json::from_slice(&bytes).ok()))
     }
@@ -74,6 +130,7 @@ impl UsernameStore for IndexerStore {
         // unapply
         for updates in update.unapply {
             for pk in updates.0.keys() {
+                self.delete_current_username_pk(pk)?;
                 if let Some(num) = self.get_pk_num_username_updates(pk)? {
                     // decr pk num username updates
                     if num == 0 {
@@ -95,12 +152,22 @@ impl UsernameStore for IndexerStore {
                 } else {
                     error!("Invalid username pk num {pk}");
                 }
+
+                // previous username is current again
+                if let Some(username) = self.get_username(pk)? {
+                    self.database.put_cf(
+                        self.username_name_pk_cf(),
+                        username_pk_key(&username.0, pk),
+                        b"",
+                    )?;
+                }
             }
         }
 
         // apply
         for updates in update.apply {
             for (pk, username) in updates.0 {
+                self.delete_current_username_pk(&pk)?;
                 let index = if let Some(num) = self.get_pk_num_username_updates(&pk)? {
                     // incr pk num username updates
                     num + 1
@@ -121,6 +188,11 @@ impl UsernameStore for IndexerStore {
                     pk_index_key(&pk, index),
                     username.0.as_bytes(),
                 )?;
+                self.database.put_cf(
+                    self.username_name_pk_cf(),
+                    username_pk_key(&username.0, &pk),
+                    b"",
+                )?;
             }
         }
         Ok(())
@@ -129,16 +201,45 @@ impl UsernameStore for IndexerStore {
     fn get_pk_username(&self, pk: &PublicKey, index: u32) -> anyhow::Result<Option<Username>> {
         trace!("Getting pk's {index}th username {pk}");
         Ok(self
-            .database
             .get_cf(self.username_pk_index_cf(), pk_index_key(pk, index))?
             .and_then(|bytes| Username::from_bytes(bytes).ok()))
     }
 
+    fn get_username_pks(&self, username: &str) -> anyhow::Result<Vec<PublicKey>> {
+        trace!("Getting pks with username {username}");
+        let prefix = username_prefix(username);
+
+        let mut pks = vec![];
+        for (key, _) in self
+            .iterator_cf(
+                self.username_name_pk_cf(),
+                IteratorMode::From(&prefix, Direction::Forward),
+            )
+            .flatten()
+        {
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            pks.push(PublicKey::from_bytes(&key[prefix.len()..])?);
+        }
+        Ok(pks)
+    }
+
     fn get_pk_num_username_updates(&self, pk: &PublicKey) -> anyhow::Result<Option<u32>> {
         trace!("Getting pk's number of username updates {pk}");
         Ok(self
-            .database
             .get_cf(self.username_pk_num_cf(), pk.0.as_bytes())?
             .map(from_be_bytes))
     }
 }
+
+impl IndexerStore {
+    /// Remove the pk from its current username's pks
+    fn delete_current_username_pk(&self, pk: &PublicKey) -> anyhow::Result<()> {
+        if let Some(username) = self.get_username(pk)? {
+            self.database
+                .delete_cf(self.username_name_pk_cf(), username_pk_key(&username.0, pk))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use super::column_families::ColumnFamilyHelpers;
+use crate::{
+    base::state_hash::StateHash,
+    block::validation::{store::ValidationStore, ValidationError},
+    store::{sub_store::SubStore, IndexerStore},
+    utility::store::block::validation_error_key,
+};
+use log::trace;
+use speedb::{Direction, IteratorMode, WriteBatch};
+
+/// Add the corresponding CF helpers to [ColumnFamilyHelpers] & bump the
+/// sub-store version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "validation-errors",
+    version: 1,
+    column_families: &["validation-errors"],
+    dependencies: &[],
+    migrate: SubStore::no_migration,
+};
+
+impl ValidationStore for IndexerStore {
+    /// Key: [validation_error_key]
+    /// Value: error (serialized with [serde_json::to_vec])
+    fn set_validation_errors(
+        &self,
+        state_hash: &StateHash,
+        blockchain_length: u32,
+        errors: &[ValidationError],
+    ) -> anyhow::Result<()> {
+        trace!(
+            "Setting {} validation errors of block {state_hash}",
+            errors.len()
+        );
+        let mut batch = WriteBatch::default();
+
+        // drop the previously recorded errors
+        batch.delete_range_cf(
+            self.validation_errors_cf(),
+            validation_error_key(blockchain_length, state_hash, 0),
+            validation_error_key(blockchain_length, state_hash, u32::MAX),
+        );
+
+        for (num, error) in errors.iter().enumerate() {
+            batch.put_cf(
+                self.validation_errors_cf(),
+                validation_error_key(blockchain_length, state_hash, num as u32),
+                serde_json::to_vec(error)?,
+            );
+        }
+
+        self.database.write(batch)?;
+        Ok(())
+    }
+
+    fn get_validation_errors(
+        &self,
+        direction: Direction,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ValidationError>> {
+        trace!("Getting validation errors {direction:?} limit {limit}");
+        let mode = match direction {
+            Direction::Forward => IteratorMode::Start,
+            Direction::Reverse => IteratorMode::End,
+        };
+
+        let mut errors = vec![];
+        for (_, value) in self
+            .iterator_cf(self.validation_errors_cf(), mode)
+            .flatten()
+            .take(limit)
+        {
+            errors.push(serde_json::from_slice(&value)?);
+        }
+        Ok(errors)
+    }
+}
@@ -20,11 +20,11 @@ impl VersionStore for IndexerStore {
         };
         trace!("Setting database version: {version:#?}");
         if self
-            .database
-            .get(Self::INDEXER_STORE_VERSION_KEY)?
+            .get_cf(self.default_cf(), Self::INDEXER_STORE_VERSION_KEY)?
             .is_none()
         {
-            self.database.put(
+            self.database.put_cf(
+                self.default_cf(),
                 Self::INDEXER_STORE_VERSION_KEY,
                 serde_json::to_vec(&version)?,
             )?;
@@ -35,8 +35,7 @@ impl VersionStore for IndexerStore {
     fn get_db_version(&self) -> anyhow::Result<IndexerStoreVersion> {
         trace!("Getting database version");
         Ok(self
-            .database
-            .get(Self::INDEXER_STORE_VERSION_KEY)?
+            .get_cf(self.default_cf(), Self::INDEXER_STORE_VERSION_KEY)?
             .map(|bytes| serde_json::from_slice(&bytes).expect("db version bytes"))
             .expect("db version some"))
     }
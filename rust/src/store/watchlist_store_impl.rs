@@ -0,0 +1,160 @@
+use super::{column_families::ColumnFamilyHelpers, sub_store::SubStore, IndexerStore};
+use crate::{
+    base::{public_key::PublicKey, state_hash::StateHash},
+    block::{activity::pk_hash, store::BlockStore},
+    utility::store::{common::U32_LEN, watchlist::watch_notification_key},
+    watchlist::{store::WatchlistStore, WatchNotification, WatchedAccount},
+};
+use log::{info, trace};
+use speedb::IteratorMode;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Add the corresponding CF helpers to [ColumnFamilyHelpers] & bump the
+/// sub-store version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "watchlist",
+    version: 1,
+    column_families: &["watchlist", "watchlist-notifications"],
+    dependencies: &[],
+    migrate: SubStore::no_migration,
+};
+
+impl WatchlistStore for IndexerStore {
+    fn add_watched_account(&self, account: &WatchedAccount) -> anyhow::Result<bool> {
+        trace!("Adding watched account {}", account.public_key);
+        let pk = account.public_key.0.as_bytes();
+        let is_new = self.get_pinned_cf(self.watchlist_cf(), pk)?.is_none();
+
+        self.database
+            .put_cf(self.watchlist_cf(), pk, serde_json::to_vec(account)?)?;
+        Ok(is_new)
+    }
+
+    fn remove_watched_account(&self, pk: &PublicKey) -> anyhow::Result<bool> {
+        trace!("Removing watched account {pk}");
+        let was_watched = self
+            .get_pinned_cf(self.watchlist_cf(), pk.0.as_bytes())?
+            .is_some();
+
+        self.database
+            .delete_cf(self.watchlist_cf(), pk.0.as_bytes())?;
+        Ok(was_watched)
+    }
+
+    fn get_watchlist(&self) -> anyhow::Result<Vec<WatchedAccount>> {
+        trace!("Getting watchlist");
+        let mut watchlist = vec![];
+        for (_, value) in self
+            .iterator_cf(self.watchlist_cf(), IteratorMode::Start)
+            .flatten()
+        {
+            watchlist.push(serde_json::from_slice(&value)?);
+        }
+        Ok(watchlist)
+    }
+
+    /// Key: [watch_notification_key]
+    /// Value: notification (serialized with [serde_json::to_vec])
+    fn add_watch_notifications(
+        &self,
+        state_hash: &StateHash,
+        blockchain_length: u32,
+        global_slot_since_genesis: u32,
+    ) -> anyhow::Result<Vec<WatchNotification>> {
+        let watchlist = self.get_watchlist()?;
+        if watchlist.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // involved accounts without deserializing the PCB
+        let Some(activity) = self.get_block_activity(state_hash)? else {
+            return Ok(vec![]);
+        };
+
+        let mut notifications = vec![];
+        for account in watchlist {
+            let pk = &account.public_key;
+            if activity
+                .active_accounts
+                .binary_search(&pk_hash(pk))
+                .is_err()
+            {
+                continue;
+            }
+
+            // notify once per block, e.g. a block which becomes canonical
+            // again after a reorg
+            let key = watch_notification_key(blockchain_length, state_hash, pk);
+            if self
+                .get_pinned_cf(self.watchlist_notifications_cf(), key)?
+                .is_some()
+            {
+                continue;
+            }
+
+            let notification = WatchNotification {
+                public_key: account.public_key,
+                label: account.label,
+                state_hash: state_hash.clone(),
+                blockchain_length,
+                global_slot_since_genesis,
+            };
+
+            info!("Notifying {notification}");
+            self.database.put_cf(
+                self.watchlist_notifications_cf(),
+                key,
+                serde_json::to_vec(&notification)?,
+            )?;
+
+            self.publish_watch_notification(notification.clone());
+            notifications.push(notification);
+        }
+        Ok(notifications)
+    }
+
+    fn get_watch_notifications(
+        &self,
+        pk: Option<&PublicKey>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<WatchNotification>> {
+        trace!("Getting {limit} watch notifications {pk:?}");
+        let mut notifications = vec![];
+        if limit == 0 {
+            return Ok(notifications);
+        }
+
+        for (key, value) in self
+            .iterator_cf(self.watchlist_notifications_cf(), IteratorMode::End)
+            .flatten()
+        {
+            if pk.is_some_and(|pk| key[U32_LEN + StateHash::LEN..] != *pk.0.as_bytes()) {
+                continue;
+            }
+
+            notifications.push(serde_json::from_slice(&value)?);
+            if notifications.len() >= limit {
+                break;
+            }
+        }
+        Ok(notifications)
+    }
+}
+
+impl IndexerStore {
+    /// Subscribe to watched account notifications, in publication order
+    pub fn subscribe_watch_notifications(&self) -> broadcast::Receiver<Arc<WatchNotification>> {
+        self.watch_notifications.subscribe()
+    }
+
+    /// Publishes the watched account notification to any subscribers
+    fn publish_watch_notification(&self, notification: WatchNotification) {
+        // no subscribers
+        if self.watch_notifications.receiver_count() == 0 {
+            return;
+        }
+
+        self.watch_notifications.send(Arc::new(notification)).ok();
+    }
+}
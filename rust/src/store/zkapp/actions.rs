@@ -1,9 +1,12 @@
 //! Zkapp action store trait
 
 use crate::{
-    base::public_key::PublicKey, ledger::token::TokenAddress, mina_blocks::v2::ActionState,
+    base::{public_key::PublicKey, state_hash::StateHash},
+    ledger::token::TokenAddress,
+    mina_blocks::v2::ActionState,
     store::Result,
 };
+use serde::{Deserialize, Serialize};
 
 pub trait ZkappActionStore {
     /// Add actions to the token account
@@ -43,4 +46,72 @@ pub trait ZkappActionStore {
 
     /// Remove the action at the specified index from the account
     fn remove_action(&self, pk: &PublicKey, token: &TokenAddress, index: u32) -> Result<()>;
+
+    /// Set the token account's action state transition applied by the best
+    /// chain block at `transition.blockchain_length`
+    fn set_action_state_transition(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        transition: &ActionStateTransition,
+    ) -> Result<()>;
+
+    /// Get the token account's action state transition applied by the best
+    /// chain block at `height`
+    fn get_action_state_transition(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<ActionStateTransition>>;
+
+    /// Get the token account's action state transitions, in ascending height
+    /// order
+    fn get_action_state_transitions(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+    ) -> Result<Vec<ActionStateTransition>>;
+
+    /// Get the token account's action state transitions at heights
+    /// `start_height..=end_height`, in ascending height order (at most `limit`
+    /// transitions)
+    fn get_action_state_transitions_range(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        start_height: u32,
+        end_height: u32,
+        limit: usize,
+    ) -> Result<Vec<ActionStateTransition>>;
+
+    /// Remove the token account's action state transition at `height`
+    ///
+    /// Returns the removed transition
+    fn remove_action_state_transition(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<ActionStateTransition>>;
+}
+
+/// Token account action state commitments before & after a best chain block's
+/// actions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActionStateTransition {
+    pub state_hash: StateHash,
+    pub blockchain_length: u32,
+
+    /// Index of the block's first action for the account
+    pub first_action_index: u32,
+
+    /// Number of the block's actions for the account
+    pub num_actions: u32,
+
+    /// Action state commitments before the block
+    pub before: [ActionState; 5],
+
+    /// Action state commitments after the block
+    pub after: [ActionState; 5],
 }
@@ -1,9 +1,12 @@
 //! Zkapp event store trait
 
 use crate::{
-    base::public_key::PublicKey, ledger::token::TokenAddress, mina_blocks::v2::ZkappEvent,
+    base::{public_key::PublicKey, state_hash::StateHash},
+    ledger::token::TokenAddress,
+    mina_blocks::v2::ZkappEvent,
     store::Result,
 };
+use serde::{Deserialize, Serialize};
 
 pub trait ZkappEventStore {
     /// Add events to the token account
@@ -43,4 +46,58 @@ pub trait ZkappEventStore {
 
     /// Remove the event at the specified index from the account
     fn remove_event(&self, pk: &PublicKey, token: &TokenAddress, index: u32) -> Result<()>;
+
+    /// Set the token account's events emitted by the best chain block at
+    /// `block_events.blockchain_length`
+    fn set_block_events(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        block_events: &ZkappBlockEvents,
+    ) -> Result<()>;
+
+    /// Get the token account's events emitted by the best chain block at
+    /// `height`
+    fn get_block_events(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<ZkappBlockEvents>>;
+
+    /// Get the token account's events emitted by best chain blocks at heights
+    /// `start_height..=end_height`, in ascending height order (at most `limit`
+    /// blocks)
+    fn get_block_events_range(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        start_height: u32,
+        end_height: u32,
+        limit: usize,
+    ) -> Result<Vec<ZkappBlockEvents>>;
+
+    /// Remove the token account's events emitted by the best chain block at
+    /// `height`
+    ///
+    /// Returns the removed block events
+    fn remove_block_events(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<ZkappBlockEvents>>;
+}
+
+/// Token account events emitted by a best chain block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZkappBlockEvents {
+    pub state_hash: StateHash,
+    pub blockchain_length: u32,
+
+    /// Index of the block's first event for the account
+    pub first_event_index: u32,
+
+    /// Number of the block's events for the account
+    pub num_events: u32,
 }
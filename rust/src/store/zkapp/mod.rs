@@ -4,6 +4,9 @@
 //! - minted tokens
 //! - actions
 //! - events
+//!
+//! as well as zkapp event & action subscriptions
 
 pub mod actions;
 pub mod events;
+pub mod subscriptions;
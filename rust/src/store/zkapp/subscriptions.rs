@@ -0,0 +1,315 @@
+//! Zkapp event & action subscriptions
+//!
+//! After each canonicity update, the primary store publishes the events &
+//! actions of newly canonical blocks, as well as removals for blocks which
+//! are no longer canonical. Subscribers filter the published notifications
+//! by token account & dedupe them by their [ZkappNotificationId], so each
+//! event is delivered exactly once per canonical block & removed at most
+//! once.
+
+use crate::{
+    base::{public_key::PublicKey, state_hash::StateHash},
+    block::{precomputed::PrecomputedBlock, store::BlockStore},
+    canonicity::CanonicityUpdate,
+    command::{signed::SignedCommand, Command, UserCommandWithStatusT},
+    ledger::token::TokenAddress,
+    mina_blocks::v2::staged_ledger_diff::Elt,
+    store::IndexerStore,
+};
+use log::{trace, warn};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::broadcast::{self, error::RecvError, error::TryRecvError};
+
+/// Max number of published notifications buffered for slow subscribers
+pub const ZKAPP_NOTIFICATIONS_CAPACITY: usize = 4096;
+
+pub type ZkappNotificationSender = broadcast::Sender<Arc<ZkappNotification>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ZkappNotificationKind {
+    Event,
+    Action,
+}
+
+/// Event or action emitted by a zkapp token account in a canonical block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZkappNotification {
+    pub public_key: PublicKey,
+    pub token: TokenAddress,
+    pub state_hash: StateHash,
+    pub blockchain_length: u32,
+
+    /// Hash of the emitting zkapp command (`None` if it couldn't be
+    /// computed)
+    pub txn_hash: Option<String>,
+
+    /// Index of the emitting zkapp command in the block
+    pub txn_index: u32,
+
+    pub kind: ZkappNotificationKind,
+
+    /// Index of the event/action among the account's events/actions in the
+    /// zkapp command
+    pub index: u32,
+
+    /// Event/action field elements
+    pub data: Vec<String>,
+
+    /// Account's action state commitments after the block, most recent
+    /// first
+    pub action_state_after: Option<Vec<String>>,
+
+    /// Whether the block is no longer canonical
+    pub removed: bool,
+}
+
+/// Identity of a zkapp event/action on the canonical chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ZkappNotificationId {
+    pub blockchain_length: u32,
+    pub txn_index: u32,
+    pub kind: ZkappNotificationKind,
+    pub index: u32,
+}
+
+/// Subscription to a token account's zkapp notifications
+pub struct ZkappSubscription {
+    public_key: PublicKey,
+    token: TokenAddress,
+    receiver: broadcast::Receiver<Arc<ZkappNotification>>,
+
+    /// Block each delivered notification was delivered for
+    delivered: HashMap<ZkappNotificationId, StateHash>,
+}
+
+impl ZkappNotification {
+    pub fn id(&self) -> ZkappNotificationId {
+        ZkappNotificationId {
+            blockchain_length: self.blockchain_length,
+            txn_index: self.txn_index,
+            kind: self.kind,
+            index: self.index,
+        }
+    }
+
+    /// Events & actions of the block's applied zkapp commands, in block
+    /// order
+    pub fn from_precomputed(block: &PrecomputedBlock, removed: bool) -> Vec<Self> {
+        // action states after the block
+        let action_states: HashMap<_, _> = block
+            .accounts_accessed()
+            .into_iter()
+            .filter_map(|accessed| {
+                let account = accessed.account;
+                account.zkapp.map(|zkapp| {
+                    (
+                        (account.public_key, account.token.unwrap_or_default()),
+                        zkapp
+                            .action_state
+                            .into_iter()
+                            .map(|action_state| action_state.0)
+                            .collect::<Vec<_>>(),
+                    )
+                })
+            })
+            .collect();
+
+        let mut notifications = vec![];
+        for (txn_index, command) in block.commands().into_iter().enumerate() {
+            if !command.is_applied() || !command.is_zkapp_command() {
+                continue;
+            }
+
+            let Command::Zkapp(zkapp) = command.to_command() else {
+                continue;
+            };
+
+            let mut updates = vec![];
+            for update in zkapp.account_updates.iter() {
+                zkapp_updates(&update.elt, &mut updates);
+            }
+
+            let mut txn_notifications: Vec<Self> = vec![];
+            let mut num_events: HashMap<(PublicKey, TokenAddress), u32> = HashMap::new();
+            let mut num_actions: HashMap<(PublicKey, TokenAddress), u32> = HashMap::new();
+
+            for elt in updates {
+                let body = &elt.account_update.body;
+                let key = (body.public_key.to_owned(), body.token_id.to_owned());
+
+                let emitted = body
+                    .events
+                    .iter()
+                    .map(|event| (ZkappNotificationKind::Event, &event.0))
+                    .chain(
+                        body.actions
+                            .iter()
+                            .map(|action| (ZkappNotificationKind::Action, &action.0)),
+                    );
+
+                for (kind, data) in emitted {
+                    let counts = match kind {
+                        ZkappNotificationKind::Event => &mut num_events,
+                        ZkappNotificationKind::Action => &mut num_actions,
+                    };
+                    let index = counts.entry(key.clone()).or_default();
+
+                    txn_notifications.push(Self {
+                        public_key: key.0.clone(),
+                        token: key.1.clone(),
+                        state_hash: block.state_hash(),
+                        blockchain_length: block.blockchain_length(),
+                        txn_hash: None,
+                        txn_index: txn_index as u32,
+                        kind,
+                        index: *index,
+                        data: data.to_owned(),
+                        action_state_after: action_states.get(&key).cloned(),
+                        removed,
+                    });
+                    *index += 1;
+                }
+            }
+
+            if txn_notifications.is_empty() {
+                continue;
+            }
+
+            // only hash commands which emitted something
+            let txn_hash = match SignedCommand::from(command).hash_signed_command() {
+                Ok(txn_hash) => Some(txn_hash.inner()),
+                Err(e) => {
+                    warn!(
+                        "Failed to hash zkapp command {txn_index} of block {}: {e}",
+                        block.state_hash()
+                    );
+                    None
+                }
+            };
+
+            for mut notification in txn_notifications {
+                notification.txn_hash.clone_from(&txn_hash);
+                notifications.push(notification);
+            }
+        }
+
+        notifications
+    }
+}
+
+/// Pushes the account update followed by its calls
+fn zkapp_updates<'a>(elt: &'a Elt, updates: &mut Vec<&'a Elt>) {
+    updates.push(elt);
+
+    for call in elt.calls.iter() {
+        zkapp_updates(&call.elt, updates);
+    }
+}
+
+impl ZkappSubscription {
+    /// Next notification for the token account, waits for one to be
+    /// published (`None` once the store is dropped)
+    pub async fn next(&mut self) -> Option<Arc<ZkappNotification>> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(notification) => {
+                    if self.deliver(&notification) {
+                        return Some(notification);
+                    }
+                }
+                Err(RecvError::Lagged(num)) => {
+                    warn!(
+                        "Zkapp subscription {} {} missed {num} notifications",
+                        self.public_key, self.token
+                    );
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Next already published notification for the token account, if any
+    pub fn try_next(&mut self) -> Option<Arc<ZkappNotification>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(notification) => {
+                    if self.deliver(&notification) {
+                        return Some(notification);
+                    }
+                }
+                Err(TryRecvError::Lagged(_)) => continue,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Whether the notification is for the subscribed account & not a
+    /// duplicate, recording its delivery
+    fn deliver(&mut self, notification: &ZkappNotification) -> bool {
+        if notification.public_key != self.public_key || notification.token != self.token {
+            return false;
+        }
+
+        let id = notification.id();
+        if notification.removed {
+            // only remove delivered notifications
+            if self.delivered.get(&id) == Some(&notification.state_hash) {
+                self.delivered.remove(&id);
+                return true;
+            }
+
+            return false;
+        }
+
+        if self.delivered.get(&id) == Some(&notification.state_hash) {
+            trace!("Skipping duplicate zkapp notification {id:?}");
+            return false;
+        }
+
+        self.delivered.insert(id, notification.state_hash.clone());
+        true
+    }
+}
+
+impl IndexerStore {
+    /// Subscribe to the token account's zkapp events & actions
+    pub fn subscribe_zkapp_events(&self, pk: PublicKey, token: TokenAddress) -> ZkappSubscription {
+        ZkappSubscription {
+            public_key: pk,
+            token,
+            receiver: self.zkapp_notifications.subscribe(),
+            delivered: HashMap::new(),
+        }
+    }
+
+    /// Publishes the zkapp events & actions of the canonicity update's
+    /// blocks, unapplied blocks first
+    pub(crate) fn publish_zkapp_notifications(
+        &self,
+        updates: &CanonicityUpdate,
+    ) -> anyhow::Result<()> {
+        if self.zkapp_notifications.receiver_count() == 0 {
+            return Ok(());
+        }
+
+        let unapply = updates.unapply.iter().map(|diff| (diff, true));
+        let apply = updates.apply.iter().map(|diff| (diff, false));
+
+        for (diff, removed) in unapply.chain(apply) {
+            if let Some((block, _)) = self.get_block(&diff.state_hash)? {
+                for notification in ZkappNotification::from_precomputed(&block, removed) {
+                    // no subscribers left
+                    if self
+                        .zkapp_notifications
+                        .send(Arc::new(notification))
+                        .is_err()
+                    {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
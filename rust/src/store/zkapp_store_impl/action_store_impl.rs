@@ -5,16 +5,18 @@ use crate::{
     ledger::token::TokenAddress,
     mina_blocks::v2::ActionState,
     store::{
-        column_families::ColumnFamilyHelpers, zkapp::actions::ZkappActionStore, IndexerStore,
-        Result,
+        column_families::ColumnFamilyHelpers,
+        zkapp::actions::{ActionStateTransition, ZkappActionStore},
+        IndexerStore, Result,
     },
     utility::store::{
-        common::from_be_bytes,
-        zkapp::actions::{zkapp_actions_key, zkapp_actions_pk_num_key},
+        common::{from_be_bytes, token_pk_key, u32_from_be_bytes},
+        zkapp::actions::{zkapp_action_state_key, zkapp_actions_key, zkapp_actions_pk_num_key},
     },
 };
 use anyhow::Context;
 use log::trace;
+use speedb::{Direction, IteratorMode};
 
 impl ZkappActionStore for IndexerStore {
     fn add_actions(
@@ -53,7 +55,6 @@ impl ZkappActionStore for IndexerStore {
         trace!("Getting action {index} for token account ({pk}, {token})");
 
         Ok(self
-            .database
             .get_pinned_cf(self.zkapp_actions_cf(), zkapp_actions_key(token, pk, index))?
             .map(|bytes| {
                 serde_json::from_slice(&bytes)
@@ -82,7 +83,6 @@ impl ZkappActionStore for IndexerStore {
         trace!("Getting number of actions for token account ({pk}, {token})");
 
         Ok(self
-            .database
             .get_cf(
                 self.zkapp_actions_pk_num_cf(),
                 zkapp_actions_pk_num_key(token, pk),
@@ -119,4 +119,118 @@ impl ZkappActionStore for IndexerStore {
             .database
             .delete_cf(self.zkapp_actions_cf(), zkapp_actions_key(token, pk, index))?)
     }
+
+    fn set_action_state_transition(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        transition: &ActionStateTransition,
+    ) -> Result<()> {
+        let height = transition.blockchain_length;
+        trace!("Setting action state transition {height} for token account ({pk}, {token})");
+
+        Ok(self.database.put_cf(
+            self.zkapp_action_states_cf(),
+            zkapp_action_state_key(token, pk, height),
+            serde_json::to_vec(transition)?,
+        )?)
+    }
+
+    fn get_action_state_transition(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<ActionStateTransition>> {
+        trace!("Getting action state transition {height} for token account ({pk}, {token})");
+
+        Ok(self
+            .get_pinned_cf(
+                self.zkapp_action_states_cf(),
+                zkapp_action_state_key(token, pk, height),
+            )?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+
+    fn get_action_state_transitions(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+    ) -> Result<Vec<ActionStateTransition>> {
+        trace!("Getting action state transitions for token account ({pk}, {token})");
+
+        let prefix = token_pk_key(token, pk);
+        let mut transitions = vec![];
+
+        for (key, value) in self
+            .iterator_cf(
+                self.zkapp_action_states_cf(),
+                IteratorMode::From(&prefix, Direction::Forward),
+            )
+            .flatten()
+        {
+            if !key.starts_with(&prefix) {
+                break;
+            }
+
+            transitions.push(serde_json::from_slice(&value)?);
+        }
+
+        Ok(transitions)
+    }
+
+    fn get_action_state_transitions_range(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        start_height: u32,
+        end_height: u32,
+        limit: usize,
+    ) -> Result<Vec<ActionStateTransition>> {
+        trace!("Getting action state transitions at heights {start_height}..={end_height} for token account ({pk}, {token})");
+
+        let prefix = token_pk_key(token, pk);
+        let start = zkapp_action_state_key(token, pk, start_height);
+        let mut transitions = vec![];
+
+        for (key, value) in self
+            .iterator_cf(
+                self.zkapp_action_states_cf(),
+                IteratorMode::From(&start, Direction::Forward),
+            )
+            .flatten()
+        {
+            if !key.starts_with(&prefix) || transitions.len() >= limit {
+                break;
+            }
+
+            if u32_from_be_bytes(&key[prefix.len()..])? > end_height {
+                break;
+            }
+
+            transitions.push(serde_json::from_slice(&value)?);
+        }
+
+        Ok(transitions)
+    }
+
+    fn remove_action_state_transition(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<ActionStateTransition>> {
+        trace!("Removing action state transition {height} from token account ({pk}, {token})");
+
+        let transition = self.get_action_state_transition(pk, token, height)?;
+        if transition.is_some() {
+            self.database.delete_cf(
+                self.zkapp_action_states_cf(),
+                zkapp_action_state_key(token, pk, height),
+            )?;
+        }
+
+        Ok(transition)
+    }
 }
@@ -5,15 +5,18 @@ use crate::{
     ledger::token::TokenAddress,
     mina_blocks::v2::ZkappEvent,
     store::{
-        column_families::ColumnFamilyHelpers, zkapp::events::ZkappEventStore, IndexerStore, Result,
+        column_families::ColumnFamilyHelpers,
+        zkapp::events::{ZkappBlockEvents, ZkappEventStore},
+        IndexerStore, Result,
     },
     utility::store::{
-        common::from_be_bytes,
-        zkapp::events::{zkapp_events_key, zkapp_events_pk_num_key},
+        common::{from_be_bytes, token_pk_key, u32_from_be_bytes},
+        zkapp::events::{zkapp_events_height_key, zkapp_events_key, zkapp_events_pk_num_key},
     },
 };
 use anyhow::Context;
 use log::trace;
+use speedb::{Direction, IteratorMode};
 
 impl ZkappEventStore for IndexerStore {
     fn add_events(
@@ -52,7 +55,6 @@ impl ZkappEventStore for IndexerStore {
         trace!("Getting event {index} for token account ({pk}, {token})");
 
         Ok(self
-            .database
             .get_pinned_cf(self.zkapp_events_cf(), zkapp_events_key(token, pk, index))?
             .map(|bytes| {
                 serde_json::from_slice(&bytes)
@@ -81,7 +83,6 @@ impl ZkappEventStore for IndexerStore {
         trace!("Getting number of events for token account ({pk}, {token})");
 
         Ok(self
-            .database
             .get_cf(
                 self.zkapp_events_pk_num_cf(),
                 zkapp_events_pk_num_key(token, pk),
@@ -118,4 +119,91 @@ impl ZkappEventStore for IndexerStore {
             .database
             .delete_cf(self.zkapp_events_cf(), zkapp_events_key(token, pk, index))?)
     }
+
+    fn set_block_events(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        block_events: &ZkappBlockEvents,
+    ) -> Result<()> {
+        let height = block_events.blockchain_length;
+        trace!("Setting block {height} events for token account ({pk}, {token})");
+
+        Ok(self.database.put_cf(
+            self.zkapp_events_height_cf(),
+            zkapp_events_height_key(token, pk, height),
+            serde_json::to_vec(block_events)?,
+        )?)
+    }
+
+    fn get_block_events(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<ZkappBlockEvents>> {
+        trace!("Getting block {height} events for token account ({pk}, {token})");
+
+        Ok(self
+            .get_pinned_cf(
+                self.zkapp_events_height_cf(),
+                zkapp_events_height_key(token, pk, height),
+            )?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+
+    fn get_block_events_range(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        start_height: u32,
+        end_height: u32,
+        limit: usize,
+    ) -> Result<Vec<ZkappBlockEvents>> {
+        trace!("Getting block events at heights {start_height}..={end_height} for token account ({pk}, {token})");
+
+        let prefix = token_pk_key(token, pk);
+        let start = zkapp_events_height_key(token, pk, start_height);
+        let mut blocks = vec![];
+
+        for (key, value) in self
+            .iterator_cf(
+                self.zkapp_events_height_cf(),
+                IteratorMode::From(&start, Direction::Forward),
+            )
+            .flatten()
+        {
+            if !key.starts_with(&prefix) || blocks.len() >= limit {
+                break;
+            }
+
+            if u32_from_be_bytes(&key[prefix.len()..])? > end_height {
+                break;
+            }
+
+            blocks.push(serde_json::from_slice(&value)?);
+        }
+
+        Ok(blocks)
+    }
+
+    fn remove_block_events(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+        height: u32,
+    ) -> Result<Option<ZkappBlockEvents>> {
+        trace!("Removing block {height} events from token account ({pk}, {token})");
+
+        let block_events = self.get_block_events(pk, token, height)?;
+        if block_events.is_some() {
+            self.database.delete_cf(
+                self.zkapp_events_height_cf(),
+                zkapp_events_height_key(token, pk, height),
+            )?;
+        }
+
+        Ok(block_events)
+    }
 }
@@ -1,2 +1,49 @@
 pub mod action_store_impl;
 pub mod event_store_impl;
+
+use crate::store::{
+    best_ledger_store_impl::{ActionStateData, BlockEventsData},
+    sub_store::SubStore,
+    IndexerStore,
+};
+
+/// Add the corresponding CF helpers to
+/// [crate::store::column_families::ColumnFamilyHelpers] & bump the sub-store
+/// version with a migration when changing the data format!
+pub const SUB_STORE: SubStore = SubStore {
+    name: "zkapps",
+    version: 3,
+    column_families: &[
+        "zkapp-actions",
+        "zkapp-actions-pk-num",
+        "zkapp-events",
+        "zkapp-events-pk-num",
+        "zkapp-action-states",
+        "zkapp-events-height",
+    ],
+    dependencies: &["canonicity", "staged-ledger"],
+    migrate,
+};
+
+/// Version 1 stores don't record zkapp action state transitions, they're
+/// backfilled by replaying the canonical chain, see [ActionStateData]
+///
+/// Version 2 stores don't record per-block zkapp events, they're backfilled
+/// by replaying the canonical chain, see [BlockEventsData]
+fn migrate(store: &IndexerStore, from_version: u32) -> anyhow::Result<()> {
+    match from_version {
+        1 => migrate_action_states(store),
+        2 => migrate_block_events(store),
+        _ => SubStore::no_migration(store, from_version),
+    }
+}
+
+fn migrate_action_states(store: &IndexerStore) -> anyhow::Result<()> {
+    store.replay_canonical_chain(&[&ActionStateData])?;
+    Ok(())
+}
+
+fn migrate_block_events(store: &IndexerStore) -> anyhow::Result<()> {
+    store.replay_canonical_chain(&[&BlockEventsData])?;
+    Ok(())
+}
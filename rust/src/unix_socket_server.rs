@@ -1,26 +1,41 @@
 use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
-    block::{precomputed::PrecomputedBlockWithCanonicity, store::BlockStore, BlockWithoutHeight},
-    canonicity::store::CanonicityStore,
+    block::{
+        inspect::inspect_block_file,
+        precomputed::{PcbVersion, PrecomputedBlockWithCanonicity},
+        store::BlockStore,
+        BlockWithoutHeight,
+    },
+    canonicity::{as_of::CanonicalChainAsOf, store::CanonicityStore},
+    chain::Network,
     client::*,
     command::{
         internal::store::InternalCommandStore, signed::TxnHash, store::UserCommandStore, Command,
     },
+    constants::EVENTS_MAX_PAGE_SIZE,
+    event::store::EventStore,
     ledger::{
+        bootstrap::Bootstrap,
         staking::AggregatedEpochStakeDelegation,
         store::{best::BestLedgerStore, staged::StagedLedgerStore, staking::StakingLedgerStore},
         token::TokenAddress,
+        view::LedgerView,
         Ledger, LedgerHash,
     },
+    server::NetworkIndexers,
     snark_work::store::SnarkStore,
     state::{summary::SummaryShort, IndexerState},
-    store::version::VersionStore,
+    store::{
+        version::VersionStore,
+        zkapp::{actions::ZkappActionStore, events::ZkappEventStore},
+    },
+    watchlist::{store::WatchlistStore, WatchedAccount},
 };
 use anyhow::{bail, Context};
 use log::{debug, error, info, trace, warn};
 use std::{
     io::{self, ErrorKind},
-    path::Path,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 use tokio::{
@@ -70,6 +85,7 @@ async fn parse_conn_to_cli(stream: &UnixStream) -> anyhow::Result<ClientCli> {
 pub async fn handle_connection(
     listener: UnixListener,
     state: Arc<RwLock<IndexerState>>,
+    indexers: NetworkIndexers,
     subsys: SubsystemHandle,
 ) -> anyhow::Result<()> {
     use helpers::*;
@@ -98,7 +114,14 @@ pub async fn handle_connection(
                         invalid_public_key(&pk)
                     } else {
                         let pk: PublicKey = pk.into();
-                        if let Some(account) = db.get_best_account(&pk, &TokenAddress::default())? {
+                        let account = match state
+                            .best_ledger_view()
+                            .account(&pk, &TokenAddress::default())
+                        {
+                            Some(account) => Some(account.clone()),
+                            None => db.get_best_account(&pk, &TokenAddress::default())?,
+                        };
+                        if let Some(account) = account {
                             info!("Writing account {pk} to client");
                             Some(format!("{account}"))
                         } else {
@@ -106,6 +129,59 @@ pub async fn handle_connection(
                         }
                     }
                 }
+                Accounts::BalanceHistory {
+                    public_key: pk,
+                    token,
+                    start_height,
+                    end_height,
+                    limit,
+                    path,
+                } => {
+                    info!("Received account balance history command for {pk}");
+                    let token_address = match token.as_ref() {
+                        Some(token) => TokenAddress::new(token),
+                        None => Some(TokenAddress::default()),
+                    };
+
+                    if !PublicKey::is_valid(&pk) {
+                        invalid_public_key(&pk)
+                    } else if let Some(token_address) = token_address {
+                        let pk: PublicKey = pk.into();
+                        let end_height = end_height.unwrap_or(u32::MAX);
+
+                        let mut history = vec![];
+                        for (height, entry) in db.get_best_account_balance_history_range(
+                            &pk,
+                            &token_address,
+                            start_height,
+                            end_height,
+                            limit as usize,
+                        )? {
+                            history.push(serde_json::json!({
+                                "block_height": height,
+                                "state_hash": db.get_canonical_hash_at_height(height)?,
+                                "balance_before": entry.before,
+                                "balance": entry.after,
+                            }));
+                        }
+                        let history_str = serde_json::to_string_pretty(&history)?;
+
+                        match path {
+                            None => {
+                                info!("Writing account {pk} balance history to stdout");
+                                Some(history_str)
+                            }
+                            Some(path) if !path.is_dir() => {
+                                info!("Writing account {pk} balance history to {path:?}");
+                                std::fs::write(&path, history_str)?;
+                                Some(format!("Account {pk} balance history written to {path:?}"))
+                            }
+                            Some(path) => file_must_not_be_a_directory(&path),
+                        }
+                    } else {
+                        invalid_token_address(&token.unwrap_or_default())
+                    }
+                }
             },
             ClientCli::Blocks(__) => match __ {
                 Blocks::Best { verbose, path } => {
@@ -448,6 +524,34 @@ pub async fn handle_connection(
                         }
                     }
                 }
+                Blocks::Missing { limit, path } => {
+                    info!("Received missing-blocks command with limit {limit}");
+                    let missing_blocks = db.get_missing_blocks(limit)?;
+                    let missing_blocks_str = serde_json::to_string_pretty(&missing_blocks)?;
+
+                    if path.is_none() {
+                        info!("Writing missing blocks to stdout");
+                        Some(missing_blocks_str)
+                    } else {
+                        let path = path.unwrap();
+                        if !path.is_dir() {
+                            info!("Writing missing blocks to {path:?}");
+                            std::fs::write(path.clone(), missing_blocks_str)?;
+                            Some(format!("Missing blocks written to {path:?}"))
+                        } else {
+                            file_must_not_be_a_directory(&path)
+                        }
+                    }
+                }
+                Blocks::Inspect {
+                    path,
+                    hex_context,
+                    v2,
+                } => {
+                    info!("Received block-inspect command for {path:?}");
+                    let version = if v2 { PcbVersion::V2 } else { PcbVersion::V1 };
+                    Some(inspect_block_file(&path, version, hex_context)?)
+                }
             },
             ClientCli::Chain(__) => match __ {
                 Chain::Best {
@@ -552,14 +656,85 @@ pub async fn handle_connection(
                         best_tip_missing_from_db()
                     }
                 }
+                Chain::Reorgs { limit } => {
+                    info!("Received reorgs command with limit {limit}");
+                    let reorgs = db.get_reorgs(limit as usize)?;
+                    Some(serde_json::to_string_pretty(&reorgs)?)
+                }
+                Chain::Supply { epoch } => {
+                    info!("Received supply command for epoch {epoch:?}");
+                    match db.get_supply(epoch)? {
+                        Some(supply) => Some(serde_json::to_string_pretty(&supply)?),
+                        None => Some(match epoch {
+                            Some(epoch) => format!("No canonical blocks in epoch {epoch}"),
+                            None => "No canonical blocks".to_string(),
+                        }),
+                    }
+                }
             },
-            ClientCli::CreateSnapshot { output_path } => {
+            ClientCli::Bootstrap { path, chunk_size } => {
+                info!("Received bootstrap command");
+                match Bootstrap::new(db, chunk_size)? {
+                    Some(bootstrap) => {
+                        let manifest = &bootstrap.manifest;
+                        let summary = format!(
+                            "canonical root (length {}): {}, next event {}",
+                            manifest.root_height, manifest.root_state_hash, manifest.next_seq_num
+                        );
+                        match path {
+                            None => {
+                                debug!("Writing bootstrap to stdout");
+                                let mut bytes = vec![];
+                                bootstrap.write(&mut bytes)?;
+                                Some(String::from_utf8(bytes)?)
+                            }
+                            Some(path) if path.is_dir() => file_must_not_be_a_directory(&path),
+                            Some(path) => {
+                                debug!("Writing bootstrap to {path:?}");
+                                let mut writer =
+                                    std::io::BufWriter::new(std::fs::File::create(&path)?);
+                                bootstrap.write(&mut writer)?;
+                                Some(format!("Bootstrap from {summary} written to {path:?}"))
+                            }
+                        }
+                    }
+                    None => {
+                        error!("Canonical root missing from store");
+                        Some("Canonical root missing from store".to_string())
+                    }
+                }
+            }
+            ClientCli::CreateSnapshot {
+                output_path,
+                incremental,
+            } => {
                 info!("Received create-snapshot command");
-                match db.create_snapshot(&output_path) {
+                let snapshot = if incremental {
+                    db.create_incremental_snapshot(&output_path)
+                        .map(|snapshot| snapshot.to_string())
+                } else {
+                    db.create_snapshot(&output_path)
+                };
+
+                match snapshot {
                     Err(e) => Some(e.to_string()),
                     Ok(s) => Some(s),
                 }
             }
+            ClientCli::Events {
+                kinds,
+                from_seq,
+                limit,
+                ..
+            } => {
+                info!("Received events command from {from_seq} with limit {limit}");
+                let page = db.get_event_page(
+                    &kinds,
+                    from_seq,
+                    (limit as usize).min(EVENTS_MAX_PAGE_SIZE),
+                )?;
+                Some(serde_json::to_string(&page)?)
+            }
             ClientCli::Ledgers(__) => match __ {
                 Ledgers::Best { path, memoize } => {
                     info!("Received best-ledger command");
@@ -641,36 +816,79 @@ pub async fn handle_connection(
                     height,
                     path,
                     memoize,
+                    public_keys,
                 } => {
                     info!("Received staged ledger at height {height} command");
                     if let Ok(Some(best_tip_height)) = db.get_best_block_height() {
                         if height > best_tip_height {
                             // ahead of witness tree - cannot compute
                             Some(format!("Invalid query: ledger at height {height} cannot be determined from a chain of length {best_tip_height}"))
+                        } else if let Some(pk) =
+                            public_keys.iter().find(|pk| !PublicKey::is_valid(pk))
+                        {
+                            invalid_public_key(pk)
+                        } else if let Some(ledger) =
+                            db.get_staged_ledger_at_block_height(height, memoize)?
+                        {
+                            write_filtered_ledger(
+                                ledger,
+                                &public_keys,
+                                path,
+                                &format!("height {height}"),
+                            )?
                         } else {
-                            let ledger_str = db
-                                .get_staged_ledger_at_block_height(height, memoize)?
-                                .unwrap()
-                                .to_string_pretty();
-                            if path.is_none() {
-                                debug!("Writing ledger at height {height} to stdout");
-                                Some(ledger_str)
-                            } else {
-                                let path = path.unwrap();
-                                if !path.is_dir() {
-                                    debug!("Writing ledger at height {height} to {path:?}");
-                                    std::fs::write(&path, ledger_str)?;
-                                    Some(format!("Ledger at height {height} written to {path:?}"))
-                                } else {
-                                    file_must_not_be_a_directory(&path)
-                                }
-                            }
+                            Some(format!("No canonical ledger at height {height}"))
                         }
                     } else {
                         best_tip_missing_from_db()
                     }
                 }
+                Ledgers::Slot {
+                    global_slot,
+                    path,
+                    memoize,
+                    public_keys,
+                } => {
+                    info!("Received staged ledger at slot {global_slot} command");
+                    if let Some(pk) = public_keys.iter().find(|pk| !PublicKey::is_valid(pk)) {
+                        invalid_public_key(pk)
+                    } else if let Some(ledger) =
+                        db.get_staged_ledger_at_slot(global_slot, memoize)?
+                    {
+                        write_filtered_ledger(
+                            ledger,
+                            &public_keys,
+                            path,
+                            &format!("slot {global_slot}"),
+                        )?
+                    } else {
+                        Some(format!("No canonical ledger at slot {global_slot}"))
+                    }
+                }
             },
+            ClientCli::Maintenance(__) => {
+                if let Some(maintenance) = state.maintenance.as_ref() {
+                    let mut maintenance = maintenance.lock().unwrap();
+                    match __ {
+                        Maintenance::Pause => {
+                            info!("Received pause maintenance command");
+                            maintenance.pause();
+                            Some("Maintenance paused".to_string())
+                        }
+                        Maintenance::Resume => {
+                            info!("Received resume maintenance command");
+                            maintenance.resume();
+                            Some("Maintenance resumed".to_string())
+                        }
+                        Maintenance::Status => {
+                            info!("Received maintenance status command");
+                            Some(serde_json::to_string_pretty(&maintenance.summary())?)
+                        }
+                    }
+                } else {
+                    Some("Maintenance is not running".to_string())
+                }
+            }
             ClientCli::StakingLedgers(__) => match __ {
                 StakingLedgers::Hash { hash, path } => {
                     info!("Received staking-ledgers-hash command for {hash}");
@@ -822,6 +1040,47 @@ pub async fn handle_connection(
                         ))
                     }
                 }
+                StakingLedgers::Rewards {
+                    epoch,
+                    genesis_state_hash,
+                    public_key: pk,
+                    csv,
+                    path,
+                } => {
+                    info!("Received staking-ledgers-rewards command for pk {pk} epoch {epoch}");
+                    if !StateHash::is_valid(&genesis_state_hash) {
+                        invalid_state_hash(&genesis_state_hash)
+                    } else if !PublicKey::is_valid(&pk) {
+                        invalid_public_key(&pk)
+                    } else {
+                        let rewards = db.get_epoch_rewards(
+                            epoch,
+                            &pk.clone().into(),
+                            Some(&genesis_state_hash.into()),
+                        )?;
+                        let rewards_str = if csv {
+                            rewards.delegators_csv()?
+                        } else {
+                            serde_json::to_string_pretty(&rewards)?
+                        };
+
+                        if path.is_none() {
+                            debug!("Writing rewards for {pk} epoch {epoch} to stdout");
+                            Some(rewards_str)
+                        } else {
+                            let path = path.unwrap();
+                            if !path.is_dir() {
+                                debug!("Writing rewards for {pk} epoch {epoch} to {path:?}");
+                                std::fs::write(&path, rewards_str)?;
+                                Some(format!(
+                                    "Rewards for {pk} epoch {epoch} written to {path:?}"
+                                ))
+                            } else {
+                                file_must_not_be_a_directory(&path)
+                            }
+                        }
+                    }
+                }
             },
             ClientCli::Snarks(__) => match __ {
                 Snarks::PublicKey {
@@ -901,29 +1160,60 @@ pub async fn handle_connection(
             ClientCli::Summary {
                 verbose,
                 json,
+                tree_format,
                 path,
+                network,
             } => {
                 info!("Received summary command");
+                let summary_str = |state: &IndexerState| {
+                    if let Some(tree_format) = tree_format {
+                        return state.witness_tree_graph().format(tree_format);
+                    }
 
-                let summary = state.summary_verbose();
-                let summary_str = if verbose {
-                    format_json(&summary, json)
-                } else {
-                    let summary: SummaryShort = summary.clone().into();
-                    format_json(&summary, json)
+                    let summary = state.summary_verbose();
+                    Ok(if verbose {
+                        format_json(&summary, json)
+                    } else {
+                        let summary: SummaryShort = summary.into();
+                        format_json(&summary, json)
+                    })
                 };
 
-                if path.is_none() {
-                    info!("Writing summary to stdout");
-                    Some(summary_str)
-                } else {
-                    let path = path.unwrap();
-                    if !path.is_dir() {
-                        info!("Writing summary to {path:?}");
-                        std::fs::write(&path, summary_str)?;
-                        Some(format!("Summary written to {path:?}"))
-                    } else {
-                        file_must_not_be_a_directory(&path)
+                // another network indexed by the process
+                let network = network
+                    .map(|network| Network::from(network.as_str()))
+                    .filter(|network| *network != state.version.network);
+                let summary = match network {
+                    None => Some(summary_str(&state)?),
+                    Some(ref network) => {
+                        let indexer = indexers
+                            .read()
+                            .expect("network indexers lock")
+                            .get(network)
+                            .cloned();
+                        match indexer {
+                            Some(indexer) => Some(summary_str(&*indexer.read().await)?),
+                            None => None,
+                        }
+                    }
+                };
+
+                match summary {
+                    None => network.as_ref().and_then(network_indexer_not_running),
+                    Some(summary_str) => {
+                        if path.is_none() {
+                            info!("Writing summary to stdout");
+                            Some(summary_str)
+                        } else {
+                            let path = path.unwrap();
+                            if !path.is_dir() {
+                                info!("Writing summary to {path:?}");
+                                std::fs::write(&path, summary_str)?;
+                                Some(format!("Summary written to {path:?}"))
+                            } else {
+                                file_must_not_be_a_directory(&path)
+                            }
+                        }
                     }
                 }
             }
@@ -935,6 +1225,7 @@ pub async fn handle_connection(
                     end_state_hash,
                     path,
                     csv,
+                    as_of_height,
                 } => {
                     let start_state_hash: StateHash = start_state_hash.into();
                     let end_state_hash: StateHash = {
@@ -959,7 +1250,7 @@ pub async fn handle_connection(
                     } else if !StateHash::is_valid(&end_state_hash.0) {
                         invalid_state_hash(&end_state_hash.0)
                     } else if csv {
-                        match db.write_user_commands_csv(&pk.clone().into(), path) {
+                        match db.write_user_commands_csv(&pk.clone().into(), path, as_of_height) {
                             Ok(path) => Some(format!(
                                 "Successfully wrote user commands CSV for {pk} to {path:?}"
                             )),
@@ -968,9 +1259,19 @@ pub async fn handle_connection(
                             }
                         }
                     } else {
-                        let transactions = db
+                        let mut transactions = db
                             .get_user_commands_for_public_key(&pk.clone().into())?
                             .unwrap_or_default();
+                        if let Some(height) = as_of_height {
+                            let mut as_of = CanonicalChainAsOf::new(db, height)?;
+                            let mut as_of_txns = Vec::with_capacity(transactions.len());
+                            for txn in transactions {
+                                if as_of.contains_at(db, &txn.state_hash, txn.blockchain_length)? {
+                                    as_of_txns.push(txn);
+                                }
+                            }
+                            transactions = as_of_txns;
+                        }
                         let transaction_str = if verbose {
                             format_vec_jq_compatible(&transactions)
                         } else {
@@ -1049,11 +1350,13 @@ pub async fn handle_connection(
                     path,
                     public_key: pk,
                     csv,
+                    as_of_height,
                 } => {
                     if !PublicKey::is_valid(&pk) {
                         invalid_public_key(&pk)
                     } else if csv {
-                        match db.write_internal_commands_csv(pk.clone().into(), path) {
+                        match db.write_internal_commands_csv(pk.clone().into(), path, as_of_height)
+                        {
                             Ok(path) => Some(format!(
                                 "Successfully wrote internal commands CSV for {pk} to {path:?}"
                             )),
@@ -1062,8 +1365,18 @@ pub async fn handle_connection(
                             }
                         }
                     } else {
-                        let internal_cmds =
+                        let mut internal_cmds =
                             db.get_internal_commands_public_key(&pk.clone().into(), 0, usize::MAX)?;
+                        if let Some(height) = as_of_height {
+                            let mut as_of = CanonicalChainAsOf::new(db, height)?;
+                            let mut as_of_cmds = Vec::with_capacity(internal_cmds.len());
+                            for cmd in internal_cmds {
+                                if as_of.contains_at(db, cmd.state_hash(), cmd.block_height())? {
+                                    as_of_cmds.push(cmd);
+                                }
+                            }
+                            internal_cmds = as_of_cmds;
+                        }
                         let internal_cmds_str = serde_json::to_string_pretty(&internal_cmds)?;
 
                         if path.is_none() {
@@ -1111,6 +1424,176 @@ pub async fn handle_connection(
                     }
                 }
             },
+            ClientCli::Zkapps(__) => match __ {
+                Zkapps::Events {
+                    public_key: pk,
+                    token,
+                    start_height,
+                    end_height,
+                    limit,
+                    path,
+                } => {
+                    info!("Received zkapp events command for {pk}");
+                    let token_address = match token.as_ref() {
+                        Some(token) => TokenAddress::new(token),
+                        None => Some(TokenAddress::default()),
+                    };
+
+                    if !PublicKey::is_valid(&pk) {
+                        invalid_public_key(&pk)
+                    } else if let Some(token_address) = token_address {
+                        let pk: PublicKey = pk.into();
+
+                        let mut blocks = vec![];
+                        for block_events in db.get_block_events_range(
+                            &pk,
+                            &token_address,
+                            start_height,
+                            end_height.unwrap_or(u32::MAX),
+                            limit as usize,
+                        )? {
+                            let mut events = vec![];
+                            for index in block_events.first_event_index
+                                ..block_events.first_event_index + block_events.num_events
+                            {
+                                if let Some(event) = db.get_event(&pk, &token_address, index)? {
+                                    events.push(event.0);
+                                }
+                            }
+
+                            blocks.push(serde_json::json!({
+                                "block_height": block_events.blockchain_length,
+                                "state_hash": block_events.state_hash,
+                                "first_event_index": block_events.first_event_index,
+                                "events": events,
+                            }));
+                        }
+                        let events_str = serde_json::to_string_pretty(&blocks)?;
+
+                        match path {
+                            None => {
+                                info!("Writing zkapp {pk} events to stdout");
+                                Some(events_str)
+                            }
+                            Some(path) if !path.is_dir() => {
+                                info!("Writing zkapp {pk} events to {path:?}");
+                                std::fs::write(&path, events_str)?;
+                                Some(format!("Zkapp {pk} events written to {path:?}"))
+                            }
+                            Some(path) => file_must_not_be_a_directory(&path),
+                        }
+                    } else {
+                        invalid_token_address(&token.unwrap_or_default())
+                    }
+                }
+                Zkapps::Actions {
+                    public_key: pk,
+                    token,
+                    start_height,
+                    end_height,
+                    limit,
+                    path,
+                } => {
+                    info!("Received zkapp actions command for {pk}");
+                    let token_address = match token.as_ref() {
+                        Some(token) => TokenAddress::new(token),
+                        None => Some(TokenAddress::default()),
+                    };
+
+                    if !PublicKey::is_valid(&pk) {
+                        invalid_public_key(&pk)
+                    } else if let Some(token_address) = token_address {
+                        let pk: PublicKey = pk.into();
+
+                        let mut blocks = vec![];
+                        for transition in db.get_action_state_transitions_range(
+                            &pk,
+                            &token_address,
+                            start_height,
+                            end_height.unwrap_or(u32::MAX),
+                            limit as usize,
+                        )? {
+                            let mut actions = vec![];
+                            for index in transition.first_action_index
+                                ..transition.first_action_index + transition.num_actions
+                            {
+                                if let Some(action) = db.get_action(&pk, &token_address, index)? {
+                                    actions.push(action.0);
+                                }
+                            }
+
+                            blocks.push(serde_json::json!({
+                                "block_height": transition.blockchain_length,
+                                "state_hash": transition.state_hash,
+                                "first_action_index": transition.first_action_index,
+                                "actions": actions,
+                                "action_state_before": transition.before,
+                                "action_state_after": transition.after,
+                            }));
+                        }
+                        let actions_str = serde_json::to_string_pretty(&blocks)?;
+
+                        match path {
+                            None => {
+                                info!("Writing zkapp {pk} actions to stdout");
+                                Some(actions_str)
+                            }
+                            Some(path) if !path.is_dir() => {
+                                info!("Writing zkapp {pk} actions to {path:?}");
+                                std::fs::write(&path, actions_str)?;
+                                Some(format!("Zkapp {pk} actions written to {path:?}"))
+                            }
+                            Some(path) => file_must_not_be_a_directory(&path),
+                        }
+                    } else {
+                        invalid_token_address(&token.unwrap_or_default())
+                    }
+                }
+            },
+            ClientCli::Watchlist(__) => match __ {
+                Watchlist::Add { public_key, label } => {
+                    info!("Received watchlist add command for {public_key}");
+                    if !PublicKey::is_valid(&public_key) {
+                        invalid_public_key(&public_key)
+                    } else {
+                        let account = WatchedAccount {
+                            public_key: public_key.clone().into(),
+                            label,
+                        };
+                        if db.add_watched_account(&account)? {
+                            Some(format!("Watching {public_key}"))
+                        } else {
+                            Some(format!("Updated watched account {public_key}"))
+                        }
+                    }
+                }
+                Watchlist::Remove { public_key } => {
+                    info!("Received watchlist remove command for {public_key}");
+                    if !PublicKey::is_valid(&public_key) {
+                        invalid_public_key(&public_key)
+                    } else if db.remove_watched_account(&public_key.clone().into())? {
+                        Some(format!("Stopped watching {public_key}"))
+                    } else {
+                        Some(format!("{public_key} is not watched"))
+                    }
+                }
+                Watchlist::List => {
+                    info!("Received watchlist list command");
+                    Some(serde_json::to_string_pretty(&db.get_watchlist()?)?)
+                }
+                Watchlist::Notifications { public_key, limit } => {
+                    info!("Received watchlist notifications command with limit {limit}");
+                    match public_key {
+                        Some(pk) if !PublicKey::is_valid(&pk) => invalid_public_key(&pk),
+                        pk => {
+                            let pk = pk.map(PublicKey::from);
+                            let notifications =
+                                db.get_watch_notifications(pk.as_ref(), limit as usize)?;
+                            Some(serde_json::to_string_pretty(&notifications)?)
+                        }
+                    }
+                }
+            },
             ClientCli::DbVersion => {
                 Some(format!("mina-indexer database v{}", db.get_db_version()?))
             }
@@ -1126,6 +1609,34 @@ pub async fn handle_connection(
     Ok(())
 }
 
+/// Writes the ledger, only including the given accounts if any, to the path
+/// or returns it
+fn write_filtered_ledger(
+    mut ledger: Ledger,
+    public_keys: &[String],
+    path: Option<PathBuf>,
+    at: &str,
+) -> anyhow::Result<Option<String>> {
+    if !public_keys.is_empty() {
+        let pks: Vec<PublicKey> = public_keys.iter().cloned().map(Into::into).collect();
+        ledger.retain_accounts(&pks);
+    }
+
+    let ledger = ledger.to_string_pretty();
+    match path {
+        None => {
+            debug!("Writing ledger at {at} to stdout");
+            Ok(Some(ledger))
+        }
+        Some(path) if path.is_dir() => Ok(file_must_not_be_a_directory(&path)),
+        Some(path) => {
+            debug!("Writing ledger at {at} to {path:?}");
+            std::fs::write(&path, ledger)?;
+            Ok(Some(format!("Ledger at {at} written to {path:?}")))
+        }
+    }
+}
+
 fn file_must_not_be_a_directory(path: &std::path::Path) -> Option<String> {
     Some(format!(
         "The path provided must not be a directory: {}",
@@ -1158,6 +1669,12 @@ mod helpers {
         Some(msg)
     }
 
+    pub fn invalid_token_address(input: &str) -> Option<String> {
+        let msg = format!("Invalid token address: {input}");
+        error!("Invalid token address: {input}");
+        Some(msg)
+    }
+
     pub fn invalid_state_hash(input: &str) -> Option<String> {
         let msg = format!("Invalid state hash: {input}");
         error!("Invalid state hash: {input}");
@@ -1170,6 +1687,12 @@ mod helpers {
         Some(msg)
     }
 
+    pub fn network_indexer_not_running(network: &Network) -> Option<String> {
+        let msg = format!("No indexer running for network: {network}");
+        error!("No indexer running for network: {network}");
+        Some(msg)
+    }
+
     pub fn block_missing_from_db(state_hash: &str) -> String {
         let msg = format!("Block missing from store: {state_hash}");
         error!("Block missing from store: {state_hash}");
@@ -1,3 +1,4 @@
+use crate::block::block_file_size;
 use rust_decimal::Decimal;
 use std::{
     path::{Path, PathBuf},
@@ -53,16 +54,22 @@ pub fn nanomina_to_mina(nanomina: u64) -> String {
     dec.normalize().to_string()
 }
 
-/// Calculate the total size of the file paths
+/// Calculate the total (decompressed) size of the block file paths
 pub fn calculate_total_size(paths: &[PathBuf]) -> u64 {
     paths.iter().fold(0, |acc, p| {
-        match p.metadata() {
-            Ok(metadata) => acc + metadata.len(),
+        match block_file_size(p) {
+            Ok(size) => acc + size,
             Err(_) => acc, // Skip files that can't be read
         }
     })
 }
 
+/// Nearest-rank percentile of the sorted, non-empty `values`
+pub fn percentile<T: Copy>(values: &[T], pct: usize) -> T {
+    let rank = (pct * values.len()).div_ceil(100).max(1);
+    values[rank - 1]
+}
+
 pub fn is_valid_file_name<P>(path: P, hash_validator: &dyn Fn(&str) -> bool) -> bool
 where
     P: AsRef<Path>,
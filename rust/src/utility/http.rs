@@ -0,0 +1,63 @@
+//! HTTP(S) & `file://` URL fetching
+//!
+//! `file://` URLs are read from the local filesystem, HTTP(S) URLs require
+//! the `http_client` feature.
+
+/// HTTP(S) client
+#[cfg(feature = "http_client")]
+pub type HttpClient = reqwest::Client;
+
+/// Placeholder client, only `file://` URLs are fetched without the
+/// `http_client` feature
+#[cfg(not(feature = "http_client"))]
+#[derive(Debug, Default, Clone)]
+pub struct HttpClient;
+
+/// Whether HTTP(S) URLs can be fetched
+pub const HTTP_CLIENT_ENABLED: bool = cfg!(feature = "http_client");
+
+/// Fetches the contents of the HTTP(S) or `file://` URL
+pub async fn fetch(client: &HttpClient, url: &str) -> anyhow::Result<Vec<u8>> {
+    if let Some(path) = url.strip_prefix("file://") {
+        return Ok(tokio::fs::read(path).await?);
+    }
+    fetch_http(client, url).await
+}
+
+/// Fetches the contents of the HTTP(S) or `file://` URL, `None` if it's not
+/// found (`404` or missing file)
+pub async fn fetch_optional(client: &HttpClient, url: &str) -> anyhow::Result<Option<Vec<u8>>> {
+    if let Some(path) = url.strip_prefix("file://") {
+        return match tokio::fs::read(path).await {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        };
+    }
+    fetch_http_optional(client, url).await
+}
+
+#[cfg(feature = "http_client")]
+async fn fetch_http(client: &HttpClient, url: &str) -> anyhow::Result<Vec<u8>> {
+    let response = client.get(url).send().await?.error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
+}
+
+#[cfg(feature = "http_client")]
+async fn fetch_http_optional(client: &HttpClient, url: &str) -> anyhow::Result<Option<Vec<u8>>> {
+    let response = client.get(url).send().await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    Ok(Some(response.error_for_status()?.bytes().await?.to_vec()))
+}
+
+#[cfg(not(feature = "http_client"))]
+async fn fetch_http(_client: &HttpClient, url: &str) -> anyhow::Result<Vec<u8>> {
+    anyhow::bail!("Cannot fetch {url}, built without the http_client feature")
+}
+
+#[cfg(not(feature = "http_client"))]
+async fn fetch_http_optional(_client: &HttpClient, url: &str) -> anyhow::Result<Option<Vec<u8>>> {
+    anyhow::bail!("Cannot fetch {url}, built without the http_client feature")
+}
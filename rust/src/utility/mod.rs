@@ -1,4 +1,5 @@
 pub mod compression;
 pub mod functions;
+pub mod http;
 pub mod serde;
 pub mod store;
@@ -1,7 +1,7 @@
 use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
     block::precomputed::PrecomputedBlock,
-    utility::store::common::U32_LEN,
+    utility::store::common::{U32_LEN, U64_LEN},
 };
 
 /// `{block height BE}{state hash}`
@@ -12,6 +12,17 @@ pub fn block_height_key(block: &PrecomputedBlock) -> [u8; U32_LEN + StateHash::L
     key
 }
 
+/// `{block height BE}{state hash}` of a missing block
+pub fn missing_block_key(
+    blockchain_length: u32,
+    state_hash: &StateHash,
+) -> [u8; U32_LEN + StateHash::LEN] {
+    let mut key = [0; U32_LEN + StateHash::LEN];
+    key[..U32_LEN].copy_from_slice(&blockchain_length.to_be_bytes());
+    key[U32_LEN..].copy_from_slice(state_hash.0.as_bytes());
+    key
+}
+
 /// `{global slot BE}{state hash}`
 pub fn block_global_slot_key(block: &PrecomputedBlock) -> [u8; U32_LEN + StateHash::LEN] {
     let mut key = [0; U32_LEN + StateHash::LEN];
@@ -20,6 +31,28 @@ pub fn block_global_slot_key(block: &PrecomputedBlock) -> [u8; U32_LEN + StateHa
     key
 }
 
+/// `{block height or global slot BE}{state hash}`, for keys of blocks which
+/// may no longer be stored, see [block_height_key] & [block_global_slot_key]
+pub fn block_sort_key(prefix: u32, state_hash: &StateHash) -> [u8; U32_LEN + StateHash::LEN] {
+    let mut key = [0; U32_LEN + StateHash::LEN];
+    key[..U32_LEN].copy_from_slice(&prefix.to_be_bytes());
+    key[U32_LEN..].copy_from_slice(state_hash.0.as_bytes());
+    key
+}
+
+/// Key format
+/// ```
+/// {date_time}{height}
+/// where
+/// - date_time: u64 BE bytes (epoch millis)
+/// - height:    u32 BE bytes
+pub fn canonical_date_time_key(date_time: u64, height: u32) -> [u8; U64_LEN + U32_LEN] {
+    let mut key = [0; U64_LEN + U32_LEN];
+    key[..U64_LEN].copy_from_slice(&date_time.to_be_bytes());
+    key[U64_LEN..].copy_from_slice(&height.to_be_bytes());
+    key
+}
+
 /// Key format
 /// ```
 /// {pk}{sort_value}{state_hash}
@@ -103,6 +136,25 @@ pub fn epoch_block_num_key(
     key
 }
 
+/// Key format
+/// ```
+/// {height}{state_hash}{num}
+/// where
+/// - height:     u32 BE bytes
+/// - state_hash: [StateHash] bytes
+/// - num:        u32 BE bytes (index of the block's validation error)
+pub fn validation_error_key(
+    height: u32,
+    state_hash: &StateHash,
+    num: u32,
+) -> [u8; U32_LEN + StateHash::LEN + U32_LEN] {
+    let mut key = [0; U32_LEN + StateHash::LEN + U32_LEN];
+    key[..U32_LEN].copy_from_slice(&height.to_be_bytes());
+    key[U32_LEN..][..StateHash::LEN].copy_from_slice(state_hash.0.as_bytes());
+    key[U32_LEN..][StateHash::LEN..].copy_from_slice(&num.to_be_bytes());
+    key
+}
+
 #[cfg(test)]
 mod block_store_impl_tests {
     use super::*;
@@ -2,6 +2,7 @@ use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
     utility::store::common::{u32_from_be_bytes, U32_LEN},
 };
+use speedb::Direction;
 
 pub fn internal_commmand_block_key(
     state_hash: &StateHash,
@@ -49,6 +50,40 @@ pub fn internal_commmand_pk_sort_key(
     bytes
 }
 
+/// Seek key for the first (`Forward`) or last (`Reverse`) internal command
+/// sort key at `height`
+pub fn internal_command_sort_key_start(
+    height: u32,
+    direction: Direction,
+) -> [u8; U32_LEN + StateHash::LEN + U32_LEN] {
+    let fill = match direction {
+        Direction::Forward => 0,
+        Direction::Reverse => u8::MAX,
+    };
+
+    let mut bytes = [fill; U32_LEN + StateHash::LEN + U32_LEN];
+    bytes[..U32_LEN].copy_from_slice(&height.to_be_bytes());
+    bytes
+}
+
+/// Seek key for the account's first (`Forward`) or last (`Reverse`) internal
+/// command pk sort key at `height`
+pub fn internal_command_pk_sort_key_start(
+    pk: &PublicKey,
+    height: u32,
+    direction: Direction,
+) -> [u8; PublicKey::LEN + U32_LEN + StateHash::LEN + U32_LEN + 1] {
+    let fill = match direction {
+        Direction::Forward => 0,
+        Direction::Reverse => u8::MAX,
+    };
+
+    let mut bytes = [fill; PublicKey::LEN + U32_LEN + StateHash::LEN + U32_LEN + 1];
+    bytes[..PublicKey::LEN].copy_from_slice(pk.0.as_bytes());
+    bytes[PublicKey::LEN..][..U32_LEN].copy_from_slice(&height.to_be_bytes());
+    bytes
+}
+
 pub fn internal_command_pk_key(pk: &PublicKey, index: u32) -> [u8; PublicKey::LEN + U32_LEN] {
     let mut bytes = [0; PublicKey::LEN + U32_LEN];
     bytes[..PublicKey::LEN].copy_from_slice(pk.0.as_bytes());
@@ -1,7 +1,10 @@
 use crate::{
     base::{nonce::Nonce, public_key::PublicKey, state_hash::StateHash},
     command::signed::TxnHash,
-    utility::store::common::{state_hash_suffix, u32_from_be_bytes, U32_LEN},
+    ledger::token::TokenAddress,
+    utility::store::common::{
+        state_hash_suffix, u32_from_be_bytes, u64_from_be_bytes, U32_LEN, U64_LEN,
+    },
 };
 
 /// Key format for sorting txns by block height/global slot & txn hash
@@ -109,3 +112,119 @@ pub fn user_commands_iterator_txn_hash(key: &[u8]) -> anyhow::Result<TxnHash> {
 pub fn user_commands_iterator_state_hash(key: &[u8]) -> anyhow::Result<StateHash> {
     StateHash::from_bytes(&key[U32_LEN..][TxnHash::V1_LEN..])
 }
+
+/// Value format for searching txns by memo & fee
+/// `{fee}{memo}`
+/// ```
+/// - fee:  [u64] BE bytes
+/// - memo: decoded memo bytes
+pub fn txn_memo_value(fee: u64, memo: &str) -> Vec<u8> {
+    [fee.to_be_bytes().as_slice(), memo.as_bytes()].concat()
+}
+
+/// Fee & memo from `value`
+/// - [user_commands_memo_iterator]
+pub fn txn_memo_of_value(value: &[u8]) -> anyhow::Result<(u64, String)> {
+    let fee = u64_from_be_bytes(&value[..U64_LEN])?;
+    let memo = String::from_utf8_lossy(&value[U64_LEN..]).to_string();
+    Ok((fee, memo))
+}
+
+/// Key format for txns by involved custom token
+/// `{token}{height}{txn_hash}{state_hash}`
+/// ```
+/// - token:      [TokenAddress::LEN] bytes
+/// - height:     [u32] BE bytes
+/// - txn_hash:   [TxnHash::V1_LEN] bytes
+/// - state_hash: [StateHash::LEN] bytes
+///
+/// The key less the token is a [txn_sort_key]
+pub fn token_txn_sort_key(
+    token: &TokenAddress,
+    block_height: u32,
+    txn_hash: &TxnHash,
+    state_hash: &StateHash,
+) -> [u8; TokenAddress::LEN + U32_LEN + TxnHash::V1_LEN + StateHash::LEN] {
+    let mut bytes = [0; TokenAddress::LEN + U32_LEN + TxnHash::V1_LEN + StateHash::LEN];
+    bytes[..TokenAddress::LEN].copy_from_slice(token.0.as_bytes());
+    bytes[TokenAddress::LEN..].copy_from_slice(&txn_sort_key(block_height, txn_hash, state_hash));
+    bytes
+}
+
+/// Prefix `{token}{height}`
+pub fn token_txn_sort_key_prefix(
+    token: &TokenAddress,
+    block_height: u32,
+) -> [u8; TokenAddress::LEN + U32_LEN] {
+    let mut bytes = [0; TokenAddress::LEN + U32_LEN];
+    bytes[..TokenAddress::LEN].copy_from_slice(token.0.as_bytes());
+    bytes[TokenAddress::LEN..].copy_from_slice(&block_height.to_be_bytes());
+    bytes
+}
+
+/// Key format for failed txns by failure kind
+/// `{kind}\0{height}{txn_hash}{state_hash}`
+/// ```
+/// - kind:       failure kind bytes, \0-terminated
+/// - height:     [u32] BE bytes
+/// - txn_hash:   [TxnHash::V1_LEN] bytes
+/// - state_hash: [StateHash::LEN] bytes
+///
+/// The key less the kind is a [txn_sort_key]
+pub fn failure_txn_sort_key(
+    kind: &str,
+    block_height: u32,
+    txn_hash: &TxnHash,
+    state_hash: &StateHash,
+) -> Vec<u8> {
+    [
+        failure_kind_prefix(kind).as_slice(),
+        &txn_sort_key(block_height, txn_hash, state_hash),
+    ]
+    .concat()
+}
+
+/// Prefix `{kind}\0{height}`
+pub fn failure_txn_sort_key_prefix(kind: &str, block_height: u32) -> Vec<u8> {
+    [
+        failure_kind_prefix(kind).as_slice(),
+        &block_height.to_be_bytes(),
+    ]
+    .concat()
+}
+
+/// Prefix `{kind}\0`
+pub fn failure_kind_prefix(kind: &str) -> Vec<u8> {
+    [kind.as_bytes(), &[0]].concat()
+}
+
+/// Failure kind & the [txn_sort_key] of a failed txn `key`
+/// - [user_commands_failure_iterator]
+pub fn failure_kind_of_key(key: &[u8]) -> anyhow::Result<(String, &[u8])> {
+    let Some(end) = key.iter().position(|byte| *byte == 0) else {
+        anyhow::bail!("Failure kind is not terminated")
+    };
+
+    Ok((String::from_utf8(key[..end].to_vec())?, &key[end + 1..]))
+}
+
+/// Key format for delegation changes by delegator
+/// `{delegator}{height}{state_hash}{index}`
+/// ```
+/// - delegator:  [PublicKey::LEN] bytes
+/// - height:     [u32] BE bytes
+/// - state_hash: [StateHash::LEN] bytes
+/// - index:      [u32] BE bytes
+pub fn delegation_history_key(
+    delegator: &PublicKey,
+    block_height: u32,
+    state_hash: &StateHash,
+    index: u32,
+) -> [u8; PublicKey::LEN + U32_LEN + StateHash::LEN + U32_LEN] {
+    let mut bytes = [0; PublicKey::LEN + U32_LEN + StateHash::LEN + U32_LEN];
+    bytes[..PublicKey::LEN].copy_from_slice(delegator.0.as_bytes());
+    bytes[PublicKey::LEN..][..U32_LEN].copy_from_slice(&block_height.to_be_bytes());
+    bytes[PublicKey::LEN..][U32_LEN..][..StateHash::LEN].copy_from_slice(state_hash.0.as_bytes());
+    bytes[PublicKey::LEN..][U32_LEN..][StateHash::LEN..].copy_from_slice(&index.to_be_bytes());
+    bytes
+}
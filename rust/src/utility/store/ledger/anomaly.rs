@@ -0,0 +1,15 @@
+use crate::{base::state_hash::StateHash, utility::store::common::U32_LEN};
+
+/// Key format for storing amount anomalies
+/// ```
+/// {state_hash}{seq}
+/// where
+/// - state_hash: [StateHash::LEN] bytes
+/// - seq:        [u32] BE bytes, the anomaly's index among the block's
+pub fn amount_anomaly_key(state_hash: &StateHash, seq: u32) -> [u8; StateHash::LEN + U32_LEN] {
+    let mut key = [0; StateHash::LEN + U32_LEN];
+
+    key[..StateHash::LEN].copy_from_slice(state_hash.0.as_bytes());
+    key[StateHash::LEN..].copy_from_slice(&seq.to_be_bytes());
+    key
+}
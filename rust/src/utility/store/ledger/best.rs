@@ -1,7 +1,7 @@
 use crate::{
     base::public_key::PublicKey,
     ledger::token::TokenAddress,
-    utility::store::common::{balance_key_prefix, pk_key_prefix, U64_LEN},
+    utility::store::common::{balance_key_prefix, pk_key_prefix, U32_LEN, U64_LEN},
 };
 
 /// Key format for storing best ledger accounts
@@ -41,6 +41,46 @@ pub fn best_account_sort_key(
     key
 }
 
+/// Key format for storing best ledger account balance history
+/// ```
+/// {token}{pk}{height}
+/// where
+/// - token:  [TokenAddress::LEN] bytes
+/// - pk:     [PublicKey::LEN] bytes
+/// - height: [u32] BE bytes
+pub fn best_account_balance_history_key(
+    token: &TokenAddress,
+    pk: &PublicKey,
+    height: u32,
+) -> [u8; TokenAddress::LEN + PublicKey::LEN + U32_LEN] {
+    let mut key = [0; TokenAddress::LEN + PublicKey::LEN + U32_LEN];
+
+    key[..TokenAddress::LEN].copy_from_slice(token.0.as_bytes());
+    key[TokenAddress::LEN..][..PublicKey::LEN].copy_from_slice(pk.0.as_bytes());
+    key[TokenAddress::LEN..][PublicKey::LEN..].copy_from_slice(&height.to_be_bytes());
+    key
+}
+
+/// Key format for storing custom token transfers
+/// ```
+/// {token}{height}{pk}
+/// where
+/// - token:  [TokenAddress::LEN] bytes
+/// - height: [u32] BE bytes
+/// - pk:     [PublicKey::LEN] bytes
+pub fn token_transfer_key(
+    token: &TokenAddress,
+    height: u32,
+    pk: &PublicKey,
+) -> [u8; TokenAddress::LEN + U32_LEN + PublicKey::LEN] {
+    let mut key = [0; TokenAddress::LEN + U32_LEN + PublicKey::LEN];
+
+    key[..TokenAddress::LEN].copy_from_slice(token.0.as_bytes());
+    key[TokenAddress::LEN..][..U32_LEN].copy_from_slice(&height.to_be_bytes());
+    key[TokenAddress::LEN..][U32_LEN..].copy_from_slice(pk.0.as_bytes());
+    key
+}
+
 /// Split [best_account_sort_key] into constituent parts
 pub fn split_best_account_sort_key(key: &[u8]) -> Option<(TokenAddress, u64, PublicKey)> {
     if key.len() == TokenAddress::LEN + U64_LEN + PublicKey::LEN {
@@ -92,6 +132,27 @@ mod tests {
         assert_eq!(&key[TokenAddress::LEN..][U64_LEN..], pk.0.as_bytes());
     }
 
+    #[test]
+    fn best_account_balance_history_key_content() {
+        let token = TokenAddress::default();
+        let pk = PublicKey::default();
+        let height = 42;
+
+        let key = best_account_balance_history_key(&token, &pk, height);
+
+        // prefixed by the account key
+        assert_eq!(
+            &key[..TokenAddress::LEN + PublicKey::LEN],
+            best_account_key(&token, &pk).as_slice()
+        );
+
+        // remaining bytes match the height
+        assert_eq!(
+            &key[TokenAddress::LEN..][PublicKey::LEN..],
+            &height.to_be_bytes()
+        );
+    }
+
     #[test]
     fn best_account_key_split() {
         let token = TokenAddress::default();
@@ -1,3 +1,4 @@
+pub mod anomaly;
 pub mod best;
 pub mod staged;
 pub mod staking;
@@ -36,7 +36,8 @@ pub fn split_staking_ledger_sort_key(key: &[u8]) -> anyhow::Result<(u32, u64, Pu
 /// ```
 /// {epoch}{amount}{pk}
 /// where
-/// - epoch:  [u32] BE bytes
+/// - epoch:  flattened epoch [u32] BE bytes (see
+///           [GenesisRegistry::flat_epoch](crate::chain::GenesisRegistry::flat_epoch))
 /// - amount: [u64] BE bytes
 /// - pk:     [PublicKey] bytes
 pub fn staking_ledger_sort_key(
@@ -53,6 +54,47 @@ pub fn staking_ledger_sort_key(
     key
 }
 
+/// Split [staking_ledger_delegate_sort_key] into constituent parts
+pub fn split_staking_ledger_delegate_sort_key(
+    key: &[u8],
+) -> anyhow::Result<(u32, PublicKey, u64, PublicKey)> {
+    if key.len() == U32_LEN + PublicKey::LEN + U64_LEN + PublicKey::LEN {
+        let epoch = u32_from_be_bytes(&key[..U32_LEN])?;
+        let delegate = pk_key_prefix(&key[U32_LEN..]);
+        let balance = balance_key_prefix(&key[U32_LEN..][PublicKey::LEN..]);
+        let pk = pk_key_prefix(&key[U32_LEN..][PublicKey::LEN..][U64_LEN..]);
+
+        return Ok((epoch, delegate, balance, pk));
+    }
+
+    bail!("Invlid staking_ledger_delegate_sort_key length")
+}
+
+/// Staking ledger delegate balance sort key
+/// ```
+/// {epoch}{delegate}{balance}{pk}
+/// where
+/// - epoch:    flattened epoch [u32] BE bytes (see
+///             [GenesisRegistry::flat_epoch](crate::chain::GenesisRegistry::flat_epoch))
+/// - delegate: [PublicKey] bytes
+/// - balance:  [u64] BE bytes
+/// - pk:       [PublicKey] bytes
+pub fn staking_ledger_delegate_sort_key(
+    epoch: u32,
+    delegate: &PublicKey,
+    balance: u64,
+    pk: &PublicKey,
+) -> [u8; U32_LEN + PublicKey::LEN + U64_LEN + PublicKey::LEN] {
+    let mut key = [0; U32_LEN + PublicKey::LEN + U64_LEN + PublicKey::LEN];
+
+    key[..U32_LEN].copy_from_slice(&epoch.to_be_bytes());
+    key[U32_LEN..][..PublicKey::LEN].copy_from_slice(delegate.0.as_bytes());
+    key[U32_LEN..][PublicKey::LEN..][..U64_LEN].copy_from_slice(&balance.to_be_bytes());
+    key[U32_LEN..][PublicKey::LEN..][U64_LEN..].copy_from_slice(pk.0.as_bytes());
+
+    key
+}
+
 /// Staking ledger account key
 /// ```
 /// {genesis_hash}{epoch}{ledger_hash}{pk}
@@ -165,4 +207,32 @@ mod staking_ledger_store_impl_tests {
             pk.0.as_bytes()
         );
     }
+
+    #[test]
+    fn test_staking_ledger_delegate_sort_key() -> anyhow::Result<()> {
+        let epoch = 42;
+        let balance = 1_000_000_000;
+        let pk = PublicKey::default();
+        let delegate = PublicKey::default();
+        let key = staking_ledger_delegate_sort_key(epoch, &delegate, balance, &pk);
+
+        // key == {epoch BE bytes}{delegate bytes}{balance BE bytes}{pk bytes}
+        assert_eq!(&key[..U32_LEN], epoch.to_be_bytes());
+        assert_eq!(&key[U32_LEN..][..PublicKey::LEN], delegate.0.as_bytes());
+        assert_eq!(
+            &key[U32_LEN..][PublicKey::LEN..][..U64_LEN],
+            balance.to_be_bytes()
+        );
+        assert_eq!(
+            &key[U32_LEN..][PublicKey::LEN..][U64_LEN..],
+            pk.0.as_bytes()
+        );
+
+        // round trip
+        assert_eq!(
+            split_staking_ledger_delegate_sort_key(&key)?,
+            (epoch, delegate, balance, pk)
+        );
+        Ok(())
+    }
 }
@@ -81,6 +81,28 @@ pub fn snark_fee_epoch_sort_key(
     key
 }
 
+/// Key format
+/// ```
+/// {prover}{height}{hash}{index}
+/// where
+/// - prover: [PublicKey] bytes
+/// - height: [u32] BE bytes
+/// - hash:   [StateHash] bytes
+/// - index:  [u32] BE bytes
+pub fn snark_prover_job_key(
+    prover: &PublicKey,
+    block_height: u32,
+    state_hash: &StateHash,
+    index: u32,
+) -> [u8; PublicKey::LEN + U32_LEN + StateHash::LEN + U32_LEN] {
+    let mut key = [0; PublicKey::LEN + U32_LEN + StateHash::LEN + U32_LEN];
+    key[..PublicKey::LEN].copy_from_slice(prover.0.as_bytes());
+    key[PublicKey::LEN..][..U32_LEN].copy_from_slice(&block_height.to_be_bytes());
+    key[PublicKey::LEN..][U32_LEN..][..StateHash::LEN].copy_from_slice(state_hash.0.as_bytes());
+    key[PublicKey::LEN..][U32_LEN..][StateHash::LEN..].copy_from_slice(&index.to_be_bytes());
+    key
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,4 +146,27 @@ mod tests {
         );
         assert_eq!(&key[PublicKey::LEN..][U32_LEN..], &index.to_be_bytes());
     }
+
+    #[test]
+    fn test_snark_prover_job_key() {
+        let index = 25;
+        let block_height = 50;
+        let pk = PublicKey::default();
+        let state_hash = StateHash::default();
+        let key = snark_prover_job_key(&pk, block_height, &state_hash, index);
+
+        assert_eq!(&key[..PublicKey::LEN], pk.0.as_bytes());
+        assert_eq!(
+            &key[PublicKey::LEN..][..U32_LEN],
+            &block_height.to_be_bytes()
+        );
+        assert_eq!(
+            &key[PublicKey::LEN..][U32_LEN..][..StateHash::LEN],
+            state_hash.0.as_bytes()
+        );
+        assert_eq!(
+            &key[PublicKey::LEN..][U32_LEN..][StateHash::LEN..],
+            &index.to_be_bytes()
+        );
+    }
 }
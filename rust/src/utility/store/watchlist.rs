@@ -0,0 +1,24 @@
+use crate::{
+    base::{public_key::PublicKey, state_hash::StateHash},
+    utility::store::common::U32_LEN,
+};
+
+/// Key format for storing watched account notifications
+/// ```
+/// {height}{state_hash}{pk}
+/// where
+/// - height:     [u32] BE bytes
+/// - state_hash: [StateHash::LEN] bytes
+/// - pk:         [PublicKey::LEN] bytes
+pub fn watch_notification_key(
+    height: u32,
+    state_hash: &StateHash,
+    pk: &PublicKey,
+) -> [u8; U32_LEN + StateHash::LEN + PublicKey::LEN] {
+    let mut key = [0; U32_LEN + StateHash::LEN + PublicKey::LEN];
+
+    key[..U32_LEN].copy_from_slice(&height.to_be_bytes());
+    key[U32_LEN..][..StateHash::LEN].copy_from_slice(state_hash.0.as_bytes());
+    key[U32_LEN..][StateHash::LEN..].copy_from_slice(pk.0.as_bytes());
+    key
+}
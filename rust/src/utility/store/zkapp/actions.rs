@@ -19,6 +19,21 @@ pub fn zkapp_actions_pk_num_key(
     token_pk_key(token, pk)
 }
 
+/// Key format for storing zkapp action state transitions
+/// ```
+/// {token}{pk}{height}
+/// where
+/// - token:  [TokenAddress::LEN] bytes
+/// - pk:     [PublicKey::LEN] bytes
+/// - height: [u32] BE bytes
+pub fn zkapp_action_state_key(
+    token: &TokenAddress,
+    pk: &PublicKey,
+    height: u32,
+) -> [u8; TokenAddress::LEN + PublicKey::LEN + U32_LEN] {
+    token_pk_index_key(token, pk, height)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,69 @@
+//! Address watchlist
+//!
+//! Operators register public keys via the CLI or the config file. Whenever a
+//! watched account is involved in a newly canonical block, the primary store
+//! records a [WatchNotification] & publishes it to subscribers, e.g. the
+//! webhook configured with `--watch-webhook`.
+
+pub mod store;
+pub mod webhook;
+
+use crate::base::{public_key::PublicKey, state_hash::StateHash};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Max number of published notifications buffered for slow subscribers
+pub const WATCH_NOTIFICATIONS_CAPACITY: usize = 1024;
+
+pub type WatchNotificationSender = broadcast::Sender<Arc<WatchNotification>>;
+
+/// Account on the watchlist
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchedAccount {
+    pub public_key: PublicKey,
+
+    /// Operator-provided label, included in notifications
+    pub label: Option<String>,
+}
+
+/// Watched account involved in a canonical block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchNotification {
+    pub public_key: PublicKey,
+    pub label: Option<String>,
+    pub state_hash: StateHash,
+    pub blockchain_length: u32,
+    pub global_slot_since_genesis: u32,
+}
+
+impl WatchedAccount {
+    /// Parses & validates the watched public keys
+    pub fn parse_all<S: AsRef<str>>(pks: &[S]) -> anyhow::Result<Vec<Self>> {
+        let mut accounts = vec![];
+        for pk in pks.iter().map(AsRef::as_ref).map(str::trim) {
+            if pk.is_empty() {
+                continue;
+            }
+            if !PublicKey::is_valid(pk) {
+                anyhow::bail!("Invalid watched account public key: {pk}");
+            }
+
+            accounts.push(Self {
+                public_key: pk.into(),
+                label: None,
+            });
+        }
+        Ok(accounts)
+    }
+}
+
+impl std::fmt::Display for WatchNotification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "watched account {} in canonical block (length {}): {}",
+            self.public_key, self.blockchain_length, self.state_hash
+        )
+    }
+}
@@ -0,0 +1,36 @@
+//! Store of watched accounts & their notifications
+
+use super::{WatchNotification, WatchedAccount};
+use crate::base::{public_key::PublicKey, state_hash::StateHash};
+
+pub trait WatchlistStore {
+    /// Add the account to the watchlist, replacing its label
+    ///
+    /// Returns whether the account wasn't already watched
+    fn add_watched_account(&self, account: &WatchedAccount) -> anyhow::Result<bool>;
+
+    /// Remove the account from the watchlist
+    ///
+    /// Returns whether the account was watched
+    fn remove_watched_account(&self, pk: &PublicKey) -> anyhow::Result<bool>;
+
+    /// Get the watched accounts
+    fn get_watchlist(&self) -> anyhow::Result<Vec<WatchedAccount>>;
+
+    /// Record & publish notifications for the watched accounts involved in
+    /// the canonical block, returns the new notifications
+    fn add_watch_notifications(
+        &self,
+        state_hash: &StateHash,
+        blockchain_length: u32,
+        global_slot_since_genesis: u32,
+    ) -> anyhow::Result<Vec<WatchNotification>>;
+
+    /// Get up to `limit` notifications, optionally only those of the given
+    /// account, highest block first
+    fn get_watch_notifications(
+        &self,
+        pk: Option<&PublicKey>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<WatchNotification>>;
+}
@@ -0,0 +1,47 @@
+//! Watched account notification webhook
+
+use super::WatchNotification;
+use log::{debug, info, warn};
+use reqwest::header::CONTENT_TYPE;
+use std::sync::Arc;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+/// POSTs each published notification to `url` as JSON until the store's
+/// sender is dropped
+///
+/// Failed deliveries are logged & not retried, notifications are persisted
+/// regardless
+pub async fn run_webhook(mut receiver: Receiver<Arc<WatchNotification>>, url: String) {
+    info!("Posting watched account notifications to {url}");
+    let client = reqwest::Client::new();
+
+    loop {
+        match receiver.recv().await {
+            Ok(notification) => {
+                debug!("Posting {notification}");
+                if let Err(e) = post(&client, &url, &notification).await {
+                    warn!("Failed to post {notification} to {url}: {e}");
+                }
+            }
+            Err(RecvError::Lagged(num)) => {
+                warn!("Watch notification webhook skipped {num} notifications")
+            }
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+async fn post(
+    client: &reqwest::Client,
+    url: &str,
+    notification: &WatchNotification,
+) -> anyhow::Result<()> {
+    client
+        .post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .body(serde_json::to_vec(notification)?)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
@@ -0,0 +1,135 @@
+//! Best tip & canonical block WebSocket feed
+//!
+//! Clients connecting to [ENDPOINT_BLOCK_FEED] receive a JSON text message
+//! for each best tip change & each new canonical block, so downstream
+//! services (payout scripts, bots, ...) can react to the chain without
+//! running a node or polling the Unix domain socket. Another network's feed
+//! is selected with the `network` query parameter, e.g.
+//! `/feed/blocks?network=devnet`.
+
+use super::NetworkQuery;
+use crate::{
+    block::store::BlockStore,
+    chain::Network,
+    store::{
+        block_subscriptions::{BlockNotification, BlockNotificationKind},
+        IndexerStore,
+    },
+};
+use actix_web::{
+    web::{self, Data},
+    HttpRequest, HttpResponse,
+};
+use actix_ws::Message;
+use log::{debug, error};
+use serde::Serialize;
+use std::sync::Arc;
+
+pub const ENDPOINT_BLOCK_FEED: &str = "/feed/blocks";
+
+/// Block feed message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockFeedMessage {
+    /// `bestTip` or `canonicalBlock`
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub state_hash: String,
+    pub block_height: u32,
+    pub global_slot_since_genesis: u32,
+    pub staged_ledger_hash: Option<String>,
+    pub snarked_ledger_hash: Option<String>,
+}
+
+/// Feeds best tip changes & new canonical blocks to the WebSocket client
+pub async fn block_feed(
+    store: Data<Arc<IndexerStore>>,
+    query: web::Query<NetworkQuery>,
+    req: HttpRequest,
+    payload: web::Payload,
+) -> actix_web::Result<HttpResponse> {
+    let store = match query.into_inner().network {
+        Some(network) => store
+            .network_store(&Network::from(network.as_str()))
+            .map_err(actix_web::error::ErrorBadRequest)?,
+        None => store.get_ref().clone(),
+    };
+
+    let (response, mut session, mut messages) = actix_ws::handle(&req, payload)?;
+    let mut subscription = store.subscribe_block_kinds(&[
+        BlockNotificationKind::BestTipChanged,
+        BlockNotificationKind::NewCanonicalBlock,
+    ]);
+
+    actix_web::rt::spawn(async move {
+        loop {
+            tokio::select! {
+                notification = subscription.next() => {
+                    // the store was dropped
+                    let Some(notification) = notification else {
+                        break;
+                    };
+
+                    let message = match BlockFeedMessage::new(&store, &notification) {
+                        Ok(message) => message,
+                        Err(e) => {
+                            error!("Error building block feed message: {e}");
+                            continue;
+                        }
+                    };
+
+                    let text = serde_json::to_string(&message).expect("block feed message");
+                    if session.text(text).await.is_err() {
+                        // the client disconnected
+                        return;
+                    }
+                }
+                message = messages.recv() => match message {
+                    Some(Ok(Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            return;
+                        }
+                    }
+                    Some(Ok(Message::Close(reason))) => {
+                        debug!("Block feed client closed the connection: {reason:?}");
+                        break;
+                    }
+                    Some(Ok(_)) => (),
+                    Some(Err(_)) | None => break,
+                },
+            }
+        }
+
+        let _ = session.close(None).await;
+    });
+
+    Ok(response)
+}
+
+impl BlockFeedMessage {
+    /// Feed message of the block notification, with the block's ledger hashes
+    pub fn new(store: &IndexerStore, notification: &BlockNotification) -> anyhow::Result<Self> {
+        let kind = match notification.kind {
+            BlockNotificationKind::BestTipChanged => "bestTip",
+            BlockNotificationKind::NewCanonicalBlock => "canonicalBlock",
+            BlockNotificationKind::NewBlock => "newBlock",
+        };
+
+        let block = store
+            .get_block(&notification.state_hash)?
+            .map(|(block, _)| block);
+
+        Ok(Self {
+            kind,
+            state_hash: notification.state_hash.to_string(),
+            block_height: notification.blockchain_length,
+            global_slot_since_genesis: notification.global_slot_since_genesis,
+            staged_ledger_hash: block
+                .as_ref()
+                .map(|block| block.staged_ledger_hash().to_string()),
+            snarked_ledger_hash: block
+                .and_then(|block| block.snarked_ledger_hash())
+                .map(|hash| hash.to_string()),
+        })
+    }
+}
@@ -0,0 +1,171 @@
+use super::{db, error::store_error, max_limit};
+use crate::{
+    base::public_key::PublicKey,
+    command::activity::{self, AccountActivityCursor},
+};
+use async_graphql::{Context, Enum, Object, Result, SimpleObject};
+
+#[derive(Default)]
+pub struct AccountActivityQueryRoot;
+
+/// Page of an account's activity, most recent first
+#[derive(SimpleObject)]
+pub struct AccountActivityFeed {
+    entries: Vec<AccountActivity>,
+
+    /// Cursor of the next page (`null` on the last page)
+    next_cursor: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct AccountActivity {
+    kind: AccountActivityKind,
+    block_height: u32,
+    state_hash: String,
+
+    /// Index of the command in the block's ledger application order
+    index_in_block: u32,
+
+    direction: ActivityDirection,
+
+    /// Other account of a user command (null for internal commands &
+    /// creation fees)
+    counterparty: Option<String>,
+
+    amount: u64,
+    token: String,
+
+    /// Hash of the user command (null for internal commands)
+    txn_hash: Option<String>,
+
+    canonical: bool,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum AccountActivityKind {
+    Payment,
+    Delegation,
+    Zkapp,
+    Coinbase,
+    FeeTransfer,
+    CreationFee,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum ActivityDirection {
+    In,
+    Out,
+
+    #[graphql(name = "SELF")]
+    SelfTransfer,
+}
+
+#[Object]
+impl AccountActivityQueryRoot {
+    /// User commands, internal commands & creation fees of the account
+    /// interleaved by block & intra-block order, most recent first
+    async fn account_activity<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        public_key: String,
+        #[graphql(default = 100)] limit: usize,
+        cursor: Option<String>,
+        kinds: Option<Vec<AccountActivityKind>>,
+    ) -> Result<AccountActivityFeed> {
+        let limit = max_limit(ctx, limit);
+        if !PublicKey::is_valid(&public_key) {
+            return Err(format!("Invalid public key: {public_key}").into());
+        }
+
+        let cursor = match cursor {
+            Some(cursor) => Some(
+                cursor
+                    .parse::<AccountActivityCursor>()
+                    .map_err(|_| format!("Invalid cursor: {cursor}"))?,
+            ),
+            None => None,
+        };
+
+        let db = db(ctx);
+        let pk: PublicKey = public_key.into();
+        let kinds: Vec<activity::AccountActivityKind> = kinds
+            .unwrap_or_default()
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        let entries = db
+            .get_account_activity(&pk, &kinds, cursor.as_ref(), limit)
+            .map_err(|e| store_error("account activity", &pk, e))?;
+
+        let next_cursor = if entries.len() >= limit {
+            entries.last().map(|entry| entry.cursor().to_string())
+        } else {
+            None
+        };
+
+        Ok(AccountActivityFeed {
+            entries: entries.into_iter().map(Into::into).collect(),
+            next_cursor,
+        })
+    }
+}
+
+/////////////////
+// Conversions //
+/////////////////
+
+impl From<activity::AccountActivity> for AccountActivity {
+    fn from(value: activity::AccountActivity) -> Self {
+        Self {
+            kind: value.kind.into(),
+            block_height: value.blockchain_length,
+            state_hash: value.state_hash.0,
+            index_in_block: value.index_in_block,
+            direction: value.direction.into(),
+            counterparty: value.counterparty.map(|pk| pk.0),
+            amount: value.amount,
+            token: value.token.0,
+            txn_hash: value.txn_hash.map(|txn_hash| txn_hash.to_string()),
+            canonical: value.canonical,
+        }
+    }
+}
+
+impl From<activity::AccountActivityKind> for AccountActivityKind {
+    fn from(value: activity::AccountActivityKind) -> Self {
+        use activity::AccountActivityKind::*;
+
+        match value {
+            Payment => Self::Payment,
+            Delegation => Self::Delegation,
+            Zkapp => Self::Zkapp,
+            Coinbase => Self::Coinbase,
+            FeeTransfer => Self::FeeTransfer,
+            CreationFee => Self::CreationFee,
+        }
+    }
+}
+
+impl From<AccountActivityKind> for activity::AccountActivityKind {
+    fn from(value: AccountActivityKind) -> Self {
+        match value {
+            AccountActivityKind::Payment => Self::Payment,
+            AccountActivityKind::Delegation => Self::Delegation,
+            AccountActivityKind::Zkapp => Self::Zkapp,
+            AccountActivityKind::Coinbase => Self::Coinbase,
+            AccountActivityKind::FeeTransfer => Self::FeeTransfer,
+            AccountActivityKind::CreationFee => Self::CreationFee,
+        }
+    }
+}
+
+impl From<activity::ActivityDirection> for ActivityDirection {
+    fn from(value: activity::ActivityDirection) -> Self {
+        match value {
+            activity::ActivityDirection::In => Self::In,
+            activity::ActivityDirection::Out => Self::Out,
+            activity::ActivityDirection::SelfTransfer => Self::SelfTransfer,
+        }
+    }
+}
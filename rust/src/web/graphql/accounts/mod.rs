@@ -1,14 +1,23 @@
-use super::db;
+use super::{
+    canonical_block_at, db,
+    error::{not_found, store_error},
+    max_limit, DateTime,
+};
 use crate::{
-    base::public_key::PublicKey,
+    base::{public_key::PublicKey, state_hash::StateHash},
     block::store::BlockStore,
+    canonicity::store::CanonicityStore,
     command::{internal::store::InternalCommandStore, store::UserCommandStore},
-    ledger::{account, store::best::BestLedgerStore, token::TokenAddress},
+    ledger::{
+        account,
+        store::best::{self, BestLedgerStore},
+        token::{provenance, TokenAddress},
+    },
     snark_work::store::SnarkStore,
-    store::username::UsernameStore,
-    web::graphql::Timing,
+    store::{username::UsernameStore, IndexerStore},
+    web::graphql::{tokens::TokenSymbols, Timing},
 };
-use async_graphql::{Context, Enum, InputObject, Object, Result, SimpleObject};
+use async_graphql::{ComplexObject, Context, Enum, InputObject, Object, Result, SimpleObject};
 use speedb::IteratorMode;
 
 #[derive(InputObject)]
@@ -46,10 +55,21 @@ pub enum AccountSortByInput {
 pub struct AccountQueryRoot;
 
 #[derive(SimpleObject)]
+#[graphql(complex)]
 pub struct Account {
     public_key: String,
     username: Option<String>,
+    token: String,
+
+    /// Symbol of the account's token (null if never set)
+    token_symbol: Option<String>,
+
+    /// Delegate, the account itself if it never delegated
     delegate: String,
+
+    /// Whether the account delegates to itself
+    is_self_delegated: bool,
+
     balance: u64,
     nonce: u32,
     time_locked: bool,
@@ -83,16 +103,511 @@ pub struct Account {
     pk_total_num_internal_commands: u32,
 }
 
+#[ComplexObject]
+impl Account {
+    /// Delegates the account delegated to, oldest first (empty if it never
+    /// delegated)
+    async fn delegation_history<'ctx>(&self, ctx: &Context<'ctx>) -> Result<Vec<String>> {
+        Ok(db(ctx)
+            .get_pk_delegations(&self.public_key.clone().into())?
+            .into_iter()
+            .map(|pk| pk.0)
+            .collect())
+    }
+}
+
+/// Page of token holders
+#[derive(SimpleObject)]
+pub struct TokenHolders {
+    token: String,
+
+    /// Symbol of the token (null if never set)
+    token_symbol: Option<String>,
+
+    /// Holders ordered by public key
+    holders: Vec<TokenHolder>,
+
+    /// Cursor of the next page (`null` on the last page)
+    next_cursor: Option<String>,
+
+    /// Provenance of the token (null if never used in a block)
+    provenance: Option<TokenProvenance>,
+}
+
+/// Where a token was first used & whether its owner is known
+#[derive(SimpleObject)]
+pub struct TokenProvenance {
+    /// `UNKNOWN` if the token was used before its owner was seen, i.e. the
+    /// ingested blocks are incomplete
+    provenance: ProvenanceStatus,
+
+    /// State hash of the block the token was first used in
+    first_seen_state_hash: String,
+
+    /// Length of the block the token was first used in
+    first_seen_blockchain_length: u32,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum ProvenanceStatus {
+    Known,
+    Unknown,
+}
+
+/// Token holder's balance
+#[derive(SimpleObject)]
+pub struct TokenHolder {
+    public_key: String,
+    balance: u64,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum TokenTransferSortByInput {
+    BlockHeightAsc,
+    BlockHeightDesc,
+}
+
+/// Page of custom token transfers
+#[derive(SimpleObject)]
+pub struct TokenTransfers {
+    token: String,
+
+    /// Symbol of the token (null if never set)
+    token_symbol: Option<String>,
+
+    /// Transfers ordered by block height, then public key
+    transfers: Vec<TokenTransfer>,
+}
+
+/// Custom token credit or debit of an account by a best chain block
+#[derive(SimpleObject)]
+pub struct TokenTransfer {
+    public_key: String,
+
+    /// Whether the account was credited, otherwise debited
+    credit: bool,
+
+    /// Absolute balance change
+    amount: u64,
+
+    #[graphql(flatten)]
+    snapshot: BalanceSnapshot,
+}
+
+/// Token account balance as of the last canonical block of an epoch
+#[derive(SimpleObject)]
+pub struct EpochBalance {
+    epoch: u32,
+
+    /// Height of the epoch's last canonical block (null if the epoch has no
+    /// canonical blocks)
+    block_height: Option<u32>,
+
+    /// State hash of the epoch's last canonical block
+    state_hash: Option<String>,
+
+    /// Balance at the epoch's last canonical block (null if the account
+    /// didn't exist)
+    balance: Option<u64>,
+}
+
+/// Account balance before & after a best chain block
+#[derive(SimpleObject)]
+pub struct BalanceSnapshot {
+    block_height: u32,
+
+    /// State hash of the canonical block at the height (null until the block
+    /// is canonical)
+    state_hash: Option<String>,
+
+    /// Balance before the block (null if the block created the account)
+    balance_before: Option<u64>,
+
+    /// Balance after the block
+    balance: u64,
+}
+
+/// Token account nonce as of a canonical block
+#[derive(SimpleObject)]
+pub struct AccountNonce {
+    public_key: String,
+    block_height: u32,
+    state_hash: String,
+
+    /// Nonce after the block (null if the account didn't exist)
+    nonce: Option<u32>,
+}
+
+/// Minimal status of a best tip token account
+#[derive(SimpleObject)]
+pub struct AccountStatus {
+    /// Whether the account exists in the best tip ledger
+    exists: bool,
+
+    /// Whether the account is a zkapp account
+    is_zkapp: bool,
+
+    /// Whether sending to the account charges the account creation fee
+    will_charge_creation_fee: bool,
+
+    /// Whether the account only exists due to blocks which aren't canonical
+    /// yet
+    pending_only: bool,
+}
+
 #[Object]
 impl AccountQueryRoot {
+    /// Cheap existence & zkapp check of a best tip token account
+    async fn account_status<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        public_key: String,
+        token: Option<String>,
+    ) -> Result<AccountStatus> {
+        if !PublicKey::is_valid(&public_key) {
+            return Err(format!("Invalid public key: {public_key}").into());
+        }
+
+        let token = match token {
+            Some(token) => TokenAddress::new(&token)
+                .ok_or_else(|| format!("Invalid token address: {token}"))?,
+            None => TokenAddress::default(),
+        };
+
+        let status = best::AccountStatus::resolve(db(ctx).as_ref(), &public_key.into(), &token)?;
+        Ok(status.into())
+    }
+
+    /// Balances of the token account as of the last canonical block of each
+    /// epoch from `from_epoch` to `to_epoch` (defaults to the best tip's
+    /// epoch), ascending
+    async fn account_epoch_balances<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        public_key: String,
+        token: Option<String>,
+        #[graphql(default = 0)] from_epoch: u32,
+        to_epoch: Option<u32>,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<Option<Vec<EpochBalance>>> {
+        let limit = max_limit(ctx, limit);
+        if !PublicKey::is_valid(&public_key) {
+            return Err(format!("Invalid public key: {public_key}").into());
+        }
+
+        let token = match token {
+            Some(token) => TokenAddress::new(&token)
+                .ok_or_else(|| format!("Invalid token address: {token}"))?,
+            None => TokenAddress::default(),
+        };
+
+        let to_epoch = to_epoch.unwrap_or(u32::MAX);
+        if from_epoch > to_epoch {
+            return Err("fromEpoch must not be after toEpoch".into());
+        }
+
+        if limit == 0 {
+            return Ok(Some(vec![]));
+        }
+
+        // at most `limit` epochs
+        let max_epochs = u32::try_from(limit - 1).unwrap_or(u32::MAX);
+        let to_epoch = to_epoch.min(from_epoch.saturating_add(max_epochs));
+
+        let balances = db(ctx)
+            .get_best_account_epoch_balances(
+                &public_key.clone().into(),
+                &token,
+                from_epoch,
+                to_epoch,
+            )
+            .map_err(|e| store_error("account epoch balances", &public_key, e))?;
+
+        Ok(Some(
+            balances
+                .into_iter()
+                .map(|epoch_balance| {
+                    let (block_height, state_hash) = match epoch_balance.boundary {
+                        Some((height, state_hash)) => (Some(height), Some(state_hash.0)),
+                        None => (None, None),
+                    };
+
+                    EpochBalance {
+                        epoch: epoch_balance.epoch,
+                        block_height,
+                        state_hash,
+                        balance: epoch_balance.balance,
+                    }
+                })
+                .collect(),
+        ))
+    }
+
+    /// Balance snapshots of the token account after each best chain block
+    /// from `start_height` to `end_height` (or the canonical block at
+    /// `at_date_time`, defaults to the best tip) which changed its balance,
+    /// ascending
+    async fn account_balance_history<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        public_key: String,
+        token: Option<String>,
+        #[graphql(default = 0)] start_height: u32,
+        end_height: Option<u32>,
+        at_date_time: Option<DateTime>,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<Option<Vec<BalanceSnapshot>>> {
+        let limit = max_limit(ctx, limit);
+        if !PublicKey::is_valid(&public_key) {
+            return Err(format!("Invalid public key: {public_key}").into());
+        }
+
+        let token = match token {
+            Some(token) => TokenAddress::new(&token)
+                .ok_or_else(|| format!("Invalid token address: {token}"))?,
+            None => TokenAddress::default(),
+        };
+
+        let db = db(ctx);
+        let end_height = match (end_height, at_date_time) {
+            (Some(_), Some(_)) => return Err("At most one of endHeight & atDateTime".into()),
+            (_, Some(date_time)) => canonical_block_at(db, &date_time)?.0,
+            (end_height, None) => end_height.unwrap_or(u32::MAX),
+        };
+        if start_height > end_height {
+            return Err("startHeight must not be after endHeight".into());
+        }
+
+        let history = db
+            .get_best_account_balance_history_range(
+                &public_key.clone().into(),
+                &token,
+                start_height,
+                end_height,
+                limit,
+            )
+            .map_err(|e| store_error("account balance history", &public_key, e))?;
+
+        let mut snapshots = Vec::with_capacity(history.len());
+        for (block_height, entry) in history {
+            let state_hash = db
+                .get_canonical_hash_at_height(block_height)
+                .map_err(|e| store_error("canonical block", block_height, e))?;
+
+            snapshots.push(BalanceSnapshot {
+                block_height,
+                state_hash: state_hash.map(|state_hash| state_hash.0),
+                balance_before: entry.before,
+                balance: entry.after,
+            });
+        }
+
+        Ok(Some(snapshots))
+    }
+
+    /// Nonce of the token account as of the canonical block at
+    /// `block_height`, with `state_hash` or at `at_date_time`, resolved from
+    /// the stored nonce changes of best chain blocks
+    async fn account_nonce_at<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        public_key: String,
+        token: Option<String>,
+        block_height: Option<u32>,
+        state_hash: Option<String>,
+        at_date_time: Option<DateTime>,
+    ) -> Result<Option<AccountNonce>> {
+        if !PublicKey::is_valid(&public_key) {
+            return Err(format!("Invalid public key: {public_key}").into());
+        }
+
+        let token = match token {
+            Some(token) => TokenAddress::new(&token)
+                .ok_or_else(|| format!("Invalid token address: {token}"))?,
+            None => TokenAddress::default(),
+        };
+
+        let db = db(ctx);
+        let (block_height, state_hash) = match (block_height, state_hash, at_date_time) {
+            (Some(height), None, None) => {
+                let state_hash = db
+                    .get_canonical_hash_at_height(height)
+                    .map_err(|e| store_error("canonical block", height, e))?
+                    .ok_or_else(|| not_found("canonical block", height))?;
+                (height, state_hash)
+            }
+            (None, Some(state_hash), None) => {
+                if !StateHash::is_valid(&state_hash) {
+                    return Err(format!("Invalid state hash: {state_hash}").into());
+                }
+
+                let state_hash = StateHash::from(state_hash);
+                let height = db
+                    .get_block_height(&state_hash)
+                    .map_err(|e| store_error("block", &state_hash, e))?
+                    .ok_or_else(|| not_found("block", &state_hash))?;
+
+                let canonical = db
+                    .get_canonical_hash_at_height(height)
+                    .map_err(|e| store_error("canonical block", height, e))?;
+                if canonical.as_ref() != Some(&state_hash) {
+                    return Err(format!("Block is not canonical: {state_hash}").into());
+                }
+                (height, state_hash)
+            }
+            (None, None, Some(date_time)) => canonical_block_at(db, &date_time)?,
+            _ => {
+                return Err("Exactly one of blockHeight, stateHash & atDateTime is required".into())
+            }
+        };
+
+        let nonce = db
+            .get_best_account_nonce_at(&public_key.clone().into(), &token, block_height)
+            .map_err(|e| store_error("account nonce", &public_key, e))?;
+
+        Ok(Some(AccountNonce {
+            public_key,
+            block_height,
+            state_hash: state_hash.0,
+            nonce,
+        }))
+    }
+
+    /// Holders of the token as of the best chain block at `at_block_height`
+    /// (defaults to the best tip), paginated by public key
+    async fn token_holders<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        token: String,
+        at_block_height: Option<u32>,
+        #[graphql(default = 0)] min_balance: u64,
+        #[graphql(default = 100)] limit: usize,
+        cursor: Option<String>,
+    ) -> Result<Option<TokenHolders>> {
+        let limit = max_limit(ctx, limit);
+        let db = db(ctx);
+        let token_address =
+            TokenAddress::new(&token).ok_or_else(|| format!("Invalid token address: {token}"))?;
+
+        let cursor = match cursor {
+            Some(cursor) if PublicKey::is_valid(&cursor) => Some(PublicKey::from(cursor)),
+            Some(cursor) => return Err(format!("Invalid cursor: {cursor}").into()),
+            None => None,
+        };
+
+        if let Some(height) = at_block_height {
+            match db.get_best_block_height() {
+                Ok(Some(best_height)) if height <= best_height => (),
+                Ok(_) => return Err(not_found("block", height)),
+                Err(e) => return Err(store_error("block", height, e)),
+            }
+        }
+
+        let holders = db
+            .get_token_holders(
+                &token_address,
+                at_block_height,
+                min_balance,
+                cursor.as_ref(),
+                limit,
+            )
+            .map_err(|e| store_error("token holders", &token, e))?;
+
+        let next_cursor = if holders.len() >= limit {
+            holders.last().map(|(pk, _)| pk.to_string())
+        } else {
+            None
+        };
+
+        let token_symbol = TokenSymbols::resolve(db, [token.as_str()])
+            .map_err(|e| store_error("token symbol", &token, e))?
+            .get(&token);
+
+        let provenance = db
+            .get_token_provenance(&token_address)
+            .map_err(|e| store_error("token provenance", &token, e))?
+            .map(TokenProvenance::from);
+
+        Ok(Some(TokenHolders {
+            token,
+            token_symbol,
+            holders: holders
+                .into_iter()
+                .map(|(pk, balance)| TokenHolder {
+                    public_key: pk.to_string(),
+                    balance,
+                })
+                .collect(),
+            next_cursor,
+            provenance,
+        }))
+    }
+
+    /// Transfers of the custom (non-MINA) token on the best chain, i.e. its
+    /// accounts' balance changes
+    async fn token_transfers<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        token: String,
+        sort_by: Option<TokenTransferSortByInput>,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<Option<TokenTransfers>> {
+        let limit = max_limit(ctx, limit);
+        let db = db(ctx);
+        let token_address =
+            TokenAddress::new(&token).ok_or_else(|| format!("Invalid token address: {token}"))?;
+        if token_address == TokenAddress::default() {
+            return Err("MINA transfers are queried via transactions".into());
+        }
+
+        let descending = matches!(
+            sort_by.unwrap_or(TokenTransferSortByInput::BlockHeightDesc),
+            TokenTransferSortByInput::BlockHeightDesc
+        );
+        let entries = db
+            .get_token_transfers(&token_address, descending, limit)
+            .map_err(|e| store_error("token transfers", &token, e))?;
+
+        let mut transfers = Vec::with_capacity(entries.len());
+        for (block_height, pk, entry) in entries {
+            let state_hash = db
+                .get_canonical_hash_at_height(block_height)
+                .map_err(|e| store_error("canonical block", block_height, e))?;
+            let before = entry.before.unwrap_or_default();
+
+            transfers.push(TokenTransfer {
+                public_key: pk.to_string(),
+                credit: entry.after >= before,
+                amount: entry.after.abs_diff(before),
+                snapshot: BalanceSnapshot {
+                    block_height,
+                    state_hash: state_hash.map(|state_hash| state_hash.0),
+                    balance_before: entry.before,
+                    balance: entry.after,
+                },
+            });
+        }
+
+        let token_symbol = TokenSymbols::resolve(db, [token.as_str()])
+            .map_err(|e| store_error("token symbol", &token, e))?
+            .get(&token);
+
+        Ok(Some(TokenTransfers {
+            token,
+            token_symbol,
+            transfers,
+        }))
+    }
+
     async fn accounts<'ctx>(
         &self,
         ctx: &Context<'ctx>,
         query: Option<AccountQueryInput>,
         sort_by: Option<AccountSortByInput>,
         #[graphql(default = 100)] limit: usize,
-    ) -> Result<Vec<Account>> {
+    ) -> Result<Option<Vec<Account>>> {
         use AccountSortByInput::*;
+        let limit = max_limit(ctx, limit);
 
         let db = db(ctx);
         let token = query
@@ -105,7 +620,7 @@ impl AccountQueryRoot {
         // public key query handler
         if let Some(public_key) = query.as_ref().and_then(|q| q.public_key.clone()) {
             let pk: PublicKey = public_key.into();
-            return Ok(db
+            let mut accounts: Vec<Account> = db
                 .get_best_account_display(&pk, &token)?
                 .iter()
                 .filter_map(|acct| {
@@ -138,7 +653,10 @@ impl AccountQueryRoot {
                         None
                     }
                 })
-                .collect());
+                .collect();
+
+            set_token_symbols(db, &mut accounts)?;
+            return Ok(Some(accounts));
         }
 
         // default query handler use balance-sorted accounts
@@ -198,10 +716,21 @@ impl AccountQueryRoot {
             }
         }
 
-        Ok(accounts)
+        set_token_symbols(db, &mut accounts)?;
+        Ok(Some(accounts))
     }
 }
 
+/// Sets the accounts' token symbols with one batched lookup
+fn set_token_symbols(db: &IndexerStore, accounts: &mut [Account]) -> anyhow::Result<()> {
+    let symbols = TokenSymbols::resolve(db, accounts.iter().map(|acct| acct.token.as_str()))?;
+    for account in accounts.iter_mut() {
+        account.token_symbol = symbols.get(&account.token);
+    }
+
+    Ok(())
+}
+
 impl AccountQueryInput {
     fn matches(&self, account: &account::Account, username: Option<&String>) -> bool {
         let AccountQueryInput {
@@ -321,6 +850,9 @@ impl
     ) -> Self {
         Self {
             public_key: account.0.public_key.0,
+            token: account.0.token.unwrap_or_default().0,
+            token_symbol: None,
+            is_self_delegated: account.0.is_self_delegated(),
             delegate: account.0.delegate.0,
             nonce: account.0.nonce.map_or(0, |n| n.0),
             balance: account.0.balance.0,
@@ -351,3 +883,33 @@ impl From<account::Timing> for Timing {
         }
     }
 }
+
+impl From<best::AccountStatus> for AccountStatus {
+    fn from(value: best::AccountStatus) -> Self {
+        Self {
+            exists: value.exists,
+            is_zkapp: value.is_zkapp,
+            will_charge_creation_fee: value.will_charge_creation_fee(),
+            pending_only: value.pending_only,
+        }
+    }
+}
+
+impl From<provenance::TokenProvenance> for TokenProvenance {
+    fn from(value: provenance::TokenProvenance) -> Self {
+        Self {
+            provenance: value.provenance.into(),
+            first_seen_state_hash: value.first_seen_state_hash.0,
+            first_seen_blockchain_length: value.first_seen_blockchain_length,
+        }
+    }
+}
+
+impl From<provenance::ProvenanceStatus> for ProvenanceStatus {
+    fn from(value: provenance::ProvenanceStatus) -> Self {
+        match value {
+            provenance::ProvenanceStatus::Known => Self::Known,
+            provenance::ProvenanceStatus::Unknown => Self::Unknown,
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use super::{date_time_to_scalar, db, max_limit, DateTime};
+use crate::{
+    block::{
+        activity::{day_of, ActivityStats as Stats, MILLIS_PER_DAY},
+        store::BlockStore,
+    },
+    constants::millis_to_iso_date_string,
+};
+use async_graphql::{Context, Enum, Object, Result, SimpleObject};
+
+#[derive(Default)]
+pub struct ActivityStatsQueryRoot;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum ActivityGroupBy {
+    /// Per canonical block
+    Block,
+
+    /// Per (UTC) day
+    #[default]
+    Day,
+}
+
+/// Account activity of a canonical block or a day's canonical blocks
+#[derive(SimpleObject)]
+pub struct ActivityStats {
+    /// Block date time, or the start of the day
+    date_time: DateTime,
+
+    /// Canonical block height (block grouping only)
+    block_height: Option<u32>,
+
+    /// Canonical block state hash (block grouping only)
+    state_hash: Option<String>,
+
+    /// Number of canonical blocks
+    num_blocks: u32,
+
+    /// Number of distinct accounts involved
+    active_accounts: u32,
+
+    /// Number of distinct user command senders
+    txn_senders: u32,
+
+    /// Number of accounts created
+    new_accounts: u32,
+
+    /// Number of distinct zkapp accounts updated
+    zkapp_accounts: u32,
+}
+
+#[Object]
+impl ActivityStatsQueryRoot {
+    /// Active account, new account & zkapp account series of the canonical
+    /// blocks from `startDate` to `endDate` (inclusive), ascending
+    async fn activity_stats<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        start_date: DateTime,
+        end_date: DateTime,
+        #[graphql(default)] group_by: ActivityGroupBy,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<Vec<ActivityStats>> {
+        let limit = max_limit(ctx, limit);
+        let start = parse_date_time(&start_date)?;
+        let end = parse_date_time(&end_date)?;
+        if start > end {
+            return Err("startDate must not be after endDate".into());
+        }
+
+        let db = db(ctx);
+        Ok(match group_by {
+            ActivityGroupBy::Block => db
+                .get_canonical_block_activities(start, end, limit)?
+                .into_iter()
+                .map(|(height, state_hash, activity)| {
+                    let mut stats = ActivityStats::new(
+                        date_time_to_scalar(activity.date_time),
+                        activity.stats(),
+                    );
+                    stats.block_height = Some(height);
+                    stats.state_hash = Some(state_hash.0);
+                    stats
+                })
+                .collect(),
+            ActivityGroupBy::Day => db
+                .get_daily_activities(day_of(start), day_of(end), limit)?
+                .into_iter()
+                .map(|(day, daily)| {
+                    let date_time = millis_to_iso_date_string(day as i64 * MILLIS_PER_DAY);
+                    ActivityStats::new(DateTime(date_time), daily.stats())
+                })
+                .collect(),
+        })
+    }
+}
+
+impl ActivityStats {
+    fn new(date_time: DateTime, stats: Stats) -> Self {
+        Self {
+            date_time,
+            block_height: None,
+            state_hash: None,
+            num_blocks: stats.num_blocks,
+            active_accounts: stats.active_accounts,
+            txn_senders: stats.txn_senders,
+            new_accounts: stats.new_accounts,
+            zkapp_accounts: stats.zkapp_accounts,
+        }
+    }
+}
+
+fn parse_date_time(date_time: &DateTime) -> Result<i64> {
+    chrono::DateTime::parse_from_rfc3339(&date_time.0)
+        .map(|date_time| date_time.timestamp_millis())
+        .map_err(|_| format!("Invalid RFC3339 date time: {}", date_time.0).into())
+}
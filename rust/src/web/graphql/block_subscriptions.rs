@@ -0,0 +1,71 @@
+use super::db;
+use crate::store::block_subscriptions::{BlockNotification, BlockNotificationKind};
+use async_graphql::{Context, SimpleObject, Subscription};
+use futures_util::{stream, Stream};
+
+#[derive(Default)]
+pub struct BlockSubscriptionRoot;
+
+/// Block updated in the witness tree
+#[derive(SimpleObject)]
+pub struct BlockUpdateNotification {
+    state_hash: String,
+    parent_state_hash: String,
+    block_height: u32,
+    global_slot_since_genesis: u32,
+}
+
+#[Subscription]
+impl BlockSubscriptionRoot {
+    /// Stream of blocks as they're added to the witness tree
+    async fn new_block<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> impl Stream<Item = BlockUpdateNotification> {
+        block_notifications(ctx, BlockNotificationKind::NewBlock)
+    }
+
+    /// Stream of blocks as they become canonical
+    async fn new_canonical_block<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> impl Stream<Item = BlockUpdateNotification> {
+        block_notifications(ctx, BlockNotificationKind::NewCanonicalBlock)
+    }
+
+    /// Stream of best tips as they change
+    async fn best_tip_changed<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> impl Stream<Item = BlockUpdateNotification> {
+        block_notifications(ctx, BlockNotificationKind::BestTipChanged)
+    }
+}
+
+fn block_notifications(
+    ctx: &Context<'_>,
+    kind: BlockNotificationKind,
+) -> impl Stream<Item = BlockUpdateNotification> {
+    let subscription = db(ctx).subscribe_blocks(kind);
+    stream::unfold(subscription, |mut subscription| async move {
+        subscription
+            .next()
+            .await
+            .map(|notification| (BlockUpdateNotification::from(&*notification), subscription))
+    })
+}
+
+/////////////////
+// conversions //
+/////////////////
+
+impl From<&BlockNotification> for BlockUpdateNotification {
+    fn from(value: &BlockNotification) -> Self {
+        Self {
+            state_hash: value.state_hash.to_string(),
+            parent_state_hash: value.parent_hash.to_string(),
+            block_height: value.blockchain_length,
+            global_slot_since_genesis: value.global_slot_since_genesis,
+        }
+    }
+}
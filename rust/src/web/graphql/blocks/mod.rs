@@ -1,12 +1,23 @@
 use super::{
-    db, get_block_canonicity, millis_to_iso_date_string, transactions::TransactionWithoutBlock,
-    MAINNET_COINBASE_REWARD, MAINNET_EPOCH_SLOT_COUNT, PK,
+    date_time_to_scalar, db,
+    error::{not_found, StoreResultExt},
+    get_block_canonicity, max_limit, millis_to_iso_date_string, options,
+    transactions::TransactionWithoutBlock,
+    DateTime, MAINNET_COINBASE_REWARD, MAINNET_EPOCH_SLOT_COUNT, PK, RAW_BLOCK_JSON_MAX_BYTES,
 };
 use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
-    block::{precomputed::PrecomputedBlock, store::BlockStore},
+    block::{
+        fee_stats::{BlockFeeStats, FeeEstimate},
+        precomputed::PrecomputedBlock,
+        receipt::LatencyStats,
+        store::BlockStore,
+        supercharge::SuperchargeAudit,
+    },
+    canonicity::{store::CanonicityStore, CanonicalBlockAt},
+    chain::store::ChainStore,
     command::{
-        internal::{store::InternalCommandStore, DbInternalCommand, DbInternalCommandWithData},
+        internal::{store::InternalCommandStore, DbInternalCommandWithData},
         signed::SignedCommandWithData,
         store::UserCommandStore,
     },
@@ -20,7 +31,7 @@ use crate::{
         get_block,
     },
 };
-use async_graphql::{self, Enum, Object, Result, SimpleObject};
+use async_graphql::{self, ComplexObject, Context, Enum, Object, Result, SimpleObject};
 use log::error;
 use serde::Serialize;
 use speedb::{Direction, IteratorMode};
@@ -69,7 +80,9 @@ impl BlocksQueryRoot {
             .flatten()
         {
             let state_hash = state_hash_suffix(&key)?;
-            let pcb = get_block(db, &state_hash);
+            let Some(pcb) = get_block(db, &state_hash)? else {
+                continue;
+            };
 
             let block = Block::from_precomputed(db, &pcb, get_counts(db).await?);
 
@@ -87,9 +100,10 @@ impl BlocksQueryRoot {
         query: Option<BlockQueryInput>,
         #[graphql(default = 100)] limit: usize,
         sort_by: Option<BlockSortByInput>,
-    ) -> Result<Vec<Block>> {
+    ) -> Result<Option<Vec<Block>>> {
         use speedb::{Direction::*, IteratorMode::*};
         use BlockSortByInput::*;
+        let limit = max_limit(ctx, limit);
         let db = db(ctx);
 
         // unique block producer query
@@ -122,10 +136,10 @@ impl BlocksQueryRoot {
                     }
                     error!("Block creator index missing (length {height}) {state_hash}")
                 }
-                return Ok(vec![Block {
+                return Ok(Some(vec![Block {
                     num_unique_block_producers_last_n_blocks: Some(producers.len() as u32),
                     ..Default::default()
-                }]);
+                }]));
             }
         }
 
@@ -136,16 +150,20 @@ impl BlocksQueryRoot {
         // state hash query
         if let Some(state_hash) = query.as_ref().and_then(|q| q.state_hash.clone()) {
             let block = db.get_block(&state_hash.into())?;
-            return Ok(block
-                .iter()
-                .filter_map(|(b, _)| precomputed_matches_query(db, &query, b, counts))
-                .collect());
+            return Ok(Some(
+                block
+                    .iter()
+                    .filter_map(|(b, _)| precomputed_matches_query(db, &query, b, counts))
+                    .collect(),
+            ));
         }
 
         // block height query
         if let Some(block_height) = query.as_ref().and_then(|q| q.block_height) {
             for state_hash in db.get_blocks_at_height(block_height)?.iter() {
-                let pcb = get_block(db, state_hash);
+                let Some(pcb) = get_block(db, state_hash)? else {
+                    continue;
+                };
                 if let Some(block) = precomputed_matches_query(db, &query, &pcb, counts) {
                     blocks.push(block);
                     if blocks.len() >= limit {
@@ -153,7 +171,7 @@ impl BlocksQueryRoot {
                     }
                 }
             }
-            return Ok(blocks);
+            return Ok(Some(blocks));
         }
 
         // global slot query
@@ -165,7 +183,9 @@ impl BlocksQueryRoot {
             .or(query.as_ref().and_then(|q| q.global_slot_since_genesis))
         {
             for state_hash in db.get_blocks_at_slot(global_slot)?.iter() {
-                let pcb = get_block(db, state_hash);
+                let Some(pcb) = get_block(db, state_hash)? else {
+                    continue;
+                };
                 if let Some(block) = precomputed_matches_query(db, &query, &pcb, counts) {
                     blocks.push(block);
                     if blocks.len() >= limit {
@@ -173,7 +193,7 @@ impl BlocksQueryRoot {
                     }
                 }
             }
-            return Ok(blocks);
+            return Ok(Some(blocks));
         }
 
         // coinbase receiver query
@@ -206,7 +226,9 @@ impl BlocksQueryRoot {
                     }
                 }
 
-                let pcb = get_block(db, &state_hash);
+                let Some(pcb) = get_block(db, &state_hash)? else {
+                    continue;
+                };
                 if let Some(block) = precomputed_matches_query(db, &query, &pcb, counts) {
                     blocks.push(block);
                     if blocks.len() >= limit {
@@ -214,7 +236,7 @@ impl BlocksQueryRoot {
                     }
                 }
             }
-            return Ok(blocks);
+            return Ok(Some(blocks));
         }
 
         // creator account query
@@ -244,6 +266,7 @@ impl BlocksQueryRoot {
                 GlobalSlotAsc => db.block_creator_global_slot_iterator(From(start, Forward)),
                 GlobalSlotDesc => db.block_creator_global_slot_iterator(From(&end, Reverse)),
             };
+            let query_epoch = query.as_ref().and_then(|q| q.epoch);
             for (key, _) in iter.flatten() {
                 if key[..PublicKey::LEN] != *creator_account.as_bytes() {
                     break;
@@ -251,13 +274,26 @@ impl BlocksQueryRoot {
 
                 // avoid deserializing PCB if possible
                 let state_hash = state_hash_suffix(&key)?;
+                if let Some(query_epoch) = query_epoch {
+                    let epoch = db.get_block_epoch(&state_hash)?.unwrap_or_default();
+
+                    // epochs are monotonic in global slot
+                    match (sort_by, epoch.cmp(&query_epoch)) {
+                        (GlobalSlotAsc, std::cmp::Ordering::Greater)
+                        | (GlobalSlotDesc, std::cmp::Ordering::Less) => break,
+                        (_, std::cmp::Ordering::Equal) => (),
+                        _ => continue,
+                    }
+                }
                 if let Some(query_canonicity) = query.as_ref().and_then(|q| q.canonical) {
                     if get_block_canonicity(db, &state_hash) != query_canonicity {
                         continue;
                     }
                 }
 
-                let pcb = get_block(db, &state_hash);
+                let Some(pcb) = get_block(db, &state_hash)? else {
+                    continue;
+                };
                 if let Some(block) = precomputed_matches_query(db, &query, &pcb, counts) {
                     blocks.push(block);
                     if blocks.len() >= limit {
@@ -265,7 +301,7 @@ impl BlocksQueryRoot {
                     }
                 }
             }
-            return Ok(blocks);
+            return Ok(Some(blocks));
         }
 
         // block height bounded query
@@ -319,7 +355,9 @@ impl BlocksQueryRoot {
                     }
                 }
 
-                let pcb = get_block(db, &state_hash);
+                let Some(pcb) = get_block(db, &state_hash)? else {
+                    continue;
+                };
                 if let Some(block_with_canonicity) =
                     precomputed_matches_query(db, &query, &pcb, counts)
                 {
@@ -330,7 +368,7 @@ impl BlocksQueryRoot {
                 }
             }
             reorder(db, &mut blocks, sort_by);
-            return Ok(blocks);
+            return Ok(Some(blocks));
         }
 
         // global slot bounded query
@@ -390,7 +428,9 @@ impl BlocksQueryRoot {
                     }
                 }
 
-                let pcb = get_block(db, &state_hash);
+                let Some(pcb) = get_block(db, &state_hash)? else {
+                    continue;
+                };
                 if let Some(block_with_canonicity) =
                     precomputed_matches_query(db, &query, &pcb, counts)
                 {
@@ -401,7 +441,7 @@ impl BlocksQueryRoot {
                 }
             }
             reorder(db, &mut blocks, sort_by);
-            return Ok(blocks);
+            return Ok(Some(blocks));
         }
 
         // default query handler
@@ -422,7 +462,9 @@ impl BlocksQueryRoot {
                 }
             }
 
-            let pcb = get_block(db, &state_hash);
+            let Some(pcb) = get_block(db, &state_hash)? else {
+                continue;
+            };
             if let Some(block_with_canonicity) = precomputed_matches_query(db, &query, &pcb, counts)
             {
                 blocks.push(block_with_canonicity);
@@ -431,10 +473,219 @@ impl BlocksQueryRoot {
                 }
             }
         }
-        Ok(blocks)
+        Ok(Some(blocks))
+    }
+
+    /// Canonical block at the given date time
+    /// Number of blocks matching the query
+    ///
+    /// Creator, epoch & canonical filters are answered from the block
+    /// production counts without iterating blocks
+    async fn blocks_count<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        query: Option<BlockQueryInput>,
+    ) -> Result<u32> {
+        let db = db(ctx);
+        if let Some(count) = aggregate_blocks_count(db, query.as_ref())? {
+            return Ok(count);
+        }
+
+        Ok(self
+            .blocks(ctx, query, usize::MAX, None)
+            .await?
+            .map_or(0, |blocks| blocks.len() as u32))
+    }
+
+    async fn canonical_block_at<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        date_time: DateTime,
+    ) -> Result<CanonicalBlockAtDateTime> {
+        let db = db(ctx);
+
+        Ok(
+            match db.get_canonical_block_at(date_time.timestamp_millis())? {
+                CanonicalBlockAt::Block {
+                    height,
+                    state_hash,
+                    date_time,
+                } => {
+                    let block = match get_block(db, &state_hash)? {
+                        Some(pcb) => Some(Block::from_precomputed(db, &pcb, get_counts(db).await?)),
+                        None => None,
+                    };
+                    CanonicalBlockAtDateTime {
+                        block_height: Some(height),
+                        state_hash: Some(state_hash.0),
+                        date_time: Some(date_time_to_scalar(date_time)),
+                        block,
+                        out_of_range: None,
+                    }
+                }
+                CanonicalBlockAt::BeforeGenesis => {
+                    CanonicalBlockAtDateTime::out_of_range(CanonicalBlockOutOfRange::BeforeGenesis)
+                }
+                CanonicalBlockAt::AfterTip => {
+                    CanonicalBlockAtDateTime::out_of_range(CanonicalBlockOutOfRange::AfterTip)
+                }
+            },
+        )
+    }
+
+    /// Receipt latency stats over the best chain's last `last_n_blocks` blocks
+    async fn block_latency_stats<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        #[graphql(default = 100)] last_n_blocks: u32,
+    ) -> Result<Option<BlockLatencyStats>> {
+        const MAX_NUM_BLOCKS: u32 = 1000;
+        let db = db(ctx);
+
+        let mut latencies = vec![];
+        let mut num_reliable = 0;
+        let mut state_hash = db.get_best_block_hash()?;
+
+        for _ in 0..last_n_blocks.min(MAX_NUM_BLOCKS) {
+            let Some(curr) = state_hash else {
+                break;
+            };
+
+            if let (Some(receipt), Some(global_slot)) = (
+                db.get_block_receipt(&curr)?,
+                db.get_block_global_slot(&curr)?,
+            ) {
+                if let Some(latency) = receipt.latency_seconds(global_slot) {
+                    latencies.push(latency);
+                    num_reliable += receipt.latency_reliable as u32;
+                }
+            }
+
+            state_hash = db.get_block_parent_hash(&curr)?;
+        }
+
+        Ok(LatencyStats::new(latencies).map(|stats| BlockLatencyStats {
+            latency_reliable: num_reliable == stats.num_blocks,
+            num_blocks: stats.num_blocks,
+            num_reliable,
+            min_seconds: stats.min,
+            max_seconds: stats.max,
+            mean_seconds: stats.mean,
+            p50_seconds: stats.p50,
+            p90_seconds: stats.p90,
+            p99_seconds: stats.p99,
+        }))
+    }
+
+    /// Suggested fees from the last `target_blocks` canonical blocks' user
+    /// command fees
+    async fn fee_estimate<'ctx>(
+        &self,
+        ctx: &async_graphql::Context<'ctx>,
+        #[graphql(default = 10)] target_blocks: u32,
+    ) -> Result<Option<BlockFeeEstimate>> {
+        const MAX_NUM_BLOCKS: u32 = 1000;
+        let db = db(ctx);
+
+        let mut stats = vec![];
+        let mut state_hash = db.get_best_block_hash()?;
+
+        while stats.len() < target_blocks.min(MAX_NUM_BLOCKS) as usize {
+            let Some(curr) = state_hash else {
+                break;
+            };
+
+            // skip the best chain's non-canonical blocks
+            if get_block_canonicity(db, &curr) {
+                match db.get_block_fee_stats(&curr)? {
+                    Some(fee_stats) => stats.push(fee_stats),
+                    None => {
+                        if let Some(block) = get_block(db, &curr)? {
+                            stats.push(BlockFeeStats::from_precomputed(&block));
+                        }
+                    }
+                }
+            }
+
+            state_hash = db.get_block_parent_hash(&curr)?;
+        }
+
+        Ok(FeeEstimate::new(&stats).map(|estimate| BlockFeeEstimate {
+            num_blocks: estimate.num_blocks,
+            num_full_blocks: estimate.num_full_blocks,
+            num_fees: estimate.num_fees,
+            low: estimate.low,
+            medium: estimate.medium,
+            high: estimate.high,
+        }))
     }
 }
 
+/// Block count from the block production counts, if the query only filters
+/// by creator, epoch & canonicity
+fn aggregate_blocks_count(
+    db: &Arc<IndexerStore>,
+    query: Option<&BlockQueryInput>,
+) -> anyhow::Result<Option<u32>> {
+    let (creator, epoch, canonical) = match query {
+        None => (None, None, None),
+        Some(BlockQueryInput {
+            creator_account,
+            epoch,
+            canonical,
+            coinbase_receiver: None,
+            protocol_state: None,
+            or: None,
+            and: None,
+            state_hash: None,
+            block_height: None,
+            unique_block_producers_last_n_blocks: None,
+            global_slot_since_genesis: None,
+            block_height_gt: None,
+            block_height_gte: None,
+            block_height_lt: None,
+            block_height_lte: None,
+        }) => (
+            creator_account.as_ref().and_then(|c| c.public_key.as_ref()),
+            *epoch,
+            *canonical,
+        ),
+        Some(_) => return Ok(None),
+    };
+
+    let (num_blocks, num_canonical) = match (creator, epoch) {
+        (Some(pk), _) if !PublicKey::is_valid(pk) => return Ok(Some(0)),
+        (Some(pk), Some(epoch)) => {
+            let pk: PublicKey = pk.as_str().into();
+            (
+                db.get_block_production_pk_epoch_count(&pk, Some(epoch))?,
+                db.get_block_production_pk_canonical_epoch_count(&pk, Some(epoch))?,
+            )
+        }
+        (Some(pk), None) => {
+            let pk: PublicKey = pk.as_str().into();
+            (
+                db.get_block_production_pk_total_count(&pk)?,
+                db.get_block_production_pk_canonical_total_count(&pk)?,
+            )
+        }
+        (None, Some(epoch)) => (
+            db.get_block_production_epoch_count(Some(epoch))?,
+            db.get_block_production_canonical_epoch_count(Some(epoch))?,
+        ),
+        (None, None) => (
+            db.get_block_production_total_count()?,
+            db.get_block_production_canonical_total_count()?,
+        ),
+    };
+
+    Ok(Some(match canonical {
+        None => num_blocks,
+        Some(true) => num_canonical,
+        Some(false) => num_blocks.saturating_sub(num_canonical),
+    }))
+}
+
 fn precomputed_matches_query(
     db: &Arc<IndexerStore>,
     query: &Option<BlockQueryInput>,
@@ -466,6 +717,7 @@ pub enum BlockSortByInput {
 }
 
 #[derive(Default, SimpleObject, Serialize)]
+#[graphql(complex)]
 pub struct Block {
     /// Value canonical
     pub canonical: bool,
@@ -514,11 +766,83 @@ pub struct Block {
     #[graphql(name = "num_unique_block_producers_last_n_blocks")]
     pub num_unique_block_producers_last_n_blocks: Option<u32>,
 
+    /// Time the indexer received the block as ISO 8601 string
+    pub received_at: Option<String>,
+
+    /// Nominal start of the block's slot as ISO 8601 string
+    pub slot_start_time: String,
+
+    /// Seconds between the slot start & receipt
+    pub latency_seconds: Option<f64>,
+
+    /// Whether the block was received live (vs ingested from a blocks dir)
+    pub latency_reliable: bool,
+
+    /// User command fees included in the block
+    pub fee_stats: FeeStats,
+
+    /// Whether the pre-hardfork coinbase matches the winner account's
+    /// supercharge eligibility (null for post-hardfork & non-canonical blocks)
+    pub supercharge_audit: Option<SuperchargeAuditStatus>,
+
     /// Value block
     #[graphql(flatten)]
     pub block: BlockWithoutCanonicity,
 }
 
+#[ComplexObject]
+impl Block {
+    /// Raw JSON of the stored precomputed block, or of its subtree at the
+    /// dotted `path` (e.g. `protocol_state.body.consensus_state`), as a JSON
+    /// string. Only available if enabled by the operator
+    async fn raw_json(&self, ctx: &Context<'_>, path: Option<String>) -> Result<String> {
+        if !options(ctx).expose_raw_block_json {
+            return Err("Raw block JSON is disabled".into());
+        }
+
+        let state_hash: StateHash = self.block.state_hash.clone().into();
+        let (block, _) = db(ctx)
+            .get_block(&state_hash)
+            .or_not_found("block", &state_hash)?;
+        let json = block.to_file_json()?;
+
+        let path = path.unwrap_or_default();
+        let selected =
+            select_json_path(&json, &path).ok_or_else(|| not_found("block JSON path", &path))?;
+        let raw = serde_json::to_string(selected)?;
+        if raw.len() > RAW_BLOCK_JSON_MAX_BYTES {
+            return Err(format!(
+                "Block JSON selection {path:?} exceeds {RAW_BLOCK_JSON_MAX_BYTES} bytes: {}",
+                raw.len()
+            )
+            .into());
+        }
+
+        Ok(raw)
+    }
+}
+
+/// Selects the subtree at the dotted path, numeric segments index arrays
+fn select_json_path<'a>(json: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(json, |value, segment| match value {
+            serde_json::Value::Array(values) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| values.get(index)),
+            _ => value.get(segment),
+        })
+}
+
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SuperchargeAuditStatus {
+    ExpectedMatches,
+    Mismatch,
+    LedgerUnavailable,
+}
+
 impl std::fmt::Debug for Block {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{self}")
@@ -534,11 +858,136 @@ impl std::fmt::Display for Block {
     }
 }
 
+#[derive(SimpleObject)]
+pub struct CanonicalBlockAtDateTime {
+    /// Canonical block height, null if out of range
+    block_height: Option<u32>,
+
+    /// Canonical block state hash, null if out of range
+    state_hash: Option<String>,
+
+    /// Canonical block date time, null if out of range
+    date_time: Option<DateTime>,
+
+    /// Canonical block, null if out of range
+    block: Option<Block>,
+
+    /// Why the date time doesn't resolve to a canonical block
+    out_of_range: Option<CanonicalBlockOutOfRange>,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum CanonicalBlockOutOfRange {
+    /// Date time precedes the first canonical block
+    #[graphql(name = "BEFORE_GENESIS")]
+    BeforeGenesis,
+
+    /// Date time follows the canonical tip
+    #[graphql(name = "AFTER_TIP")]
+    AfterTip,
+}
+
+impl CanonicalBlockAtDateTime {
+    fn out_of_range(reason: CanonicalBlockOutOfRange) -> Self {
+        Self {
+            block_height: None,
+            state_hash: None,
+            date_time: None,
+            block: None,
+            out_of_range: Some(reason),
+        }
+    }
+}
+
+#[derive(Default, SimpleObject, Serialize)]
+pub struct FeeStats {
+    /// Min user command fee (nanomina)
+    min: Option<u64>,
+
+    /// Median user command fee (nanomina)
+    median: Option<u64>,
+
+    /// Max user command fee (nanomina)
+    max: Option<u64>,
+
+    /// Number of user commands
+    count: u32,
+
+    /// Whether the block is (nearly) full of user commands
+    full: bool,
+}
+
+#[derive(SimpleObject)]
+pub struct BlockFeeEstimate {
+    /// Number of canonical blocks
+    num_blocks: u32,
+
+    /// Number of (nearly) full canonical blocks
+    num_full_blocks: u32,
+
+    /// Number of included user command fees
+    num_fees: u32,
+
+    /// 25th percentile fee (nanomina)
+    low: u64,
+
+    /// Median fee (nanomina)
+    medium: u64,
+
+    /// 90th percentile fee (nanomina)
+    high: u64,
+}
+
+impl From<BlockFeeStats> for FeeStats {
+    fn from(stats: BlockFeeStats) -> Self {
+        Self {
+            min: stats.min(),
+            median: stats.median(),
+            max: stats.max(),
+            count: stats.count(),
+            full: stats.is_full(),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct BlockLatencyStats {
+    /// Number of blocks with receipt times
+    num_blocks: u32,
+
+    /// Number of blocks received live by the watcher
+    num_reliable: u32,
+
+    /// Whether all blocks were received live by the watcher
+    latency_reliable: bool,
+
+    /// Min latency (seconds)
+    min_seconds: f64,
+
+    /// Max latency (seconds)
+    max_seconds: f64,
+
+    /// Mean latency (seconds)
+    mean_seconds: f64,
+
+    /// Median latency (seconds)
+    p50_seconds: f64,
+
+    /// 90th percentile latency (seconds)
+    p90_seconds: f64,
+
+    /// 99th percentile latency (seconds)
+    p99_seconds: f64,
+}
+
 #[derive(Default, SimpleObject, Serialize)]
 pub struct BlockWithoutCanonicity {
     /// Value state_hash
     state_hash: String,
 
+    /// State hash of the genesis block of the block's chain
+    genesis_state_hash: String,
+
     /// Value block_height
     block_height: u32,
 
@@ -727,6 +1176,12 @@ struct BlockchainState {
 
     /// Value staged ledger hash
     staged_ledger_hash: String,
+
+    /// Value first pass ledger hash (staged ledger hash pre-hardfork)
+    first_pass_ledger_hash: String,
+
+    /// Value second pass ledger hash (null pre-hardfork)
+    second_pass_ledger_hash: Option<String>,
 }
 
 #[derive(Default, SimpleObject, Serialize)]
@@ -761,6 +1216,8 @@ impl BlockWithoutCanonicity {
         // blockchain state
         let snarked_ledger_hash = block.snarked_ledger_hash().map(|hash| hash.0);
         let staged_ledger_hash = block.staged_ledger_hash().0;
+        let first_pass_ledger_hash = block.first_pass_ledger_hash().0;
+        let second_pass_ledger_hash = block.second_pass_ledger_hash().map(|hash| hash.0);
 
         // consensus state
         let total_currency = block.total_currency();
@@ -798,19 +1255,12 @@ impl BlockWithoutCanonicity {
             MAINNET_COINBASE_REWARD
         };
 
-        let fee_transfers: Vec<BlockFeetransfer> = DbInternalCommand::from_precomputed(block)
-            .into_iter()
-            .map(|cmd| {
-                DbInternalCommandWithData::from_internal_cmd(
-                    cmd,
-                    block.state_hash(),
-                    block.blockchain_length(),
-                    block.timestamp() as i64,
-                )
-            })
-            .filter(|x| matches!(x, DbInternalCommandWithData::FeeTransfer { .. }))
-            .map(|ft| ft.into())
-            .collect();
+        let fee_transfers: Vec<BlockFeetransfer> =
+            DbInternalCommandWithData::from_precomputed(block)
+                .into_iter()
+                .filter(|x| matches!(x, DbInternalCommandWithData::FeeTransfer { .. }))
+                .map(|ft| ft.into())
+                .collect();
 
         let user_commands: Vec<TransactionWithoutBlock> =
             SignedCommandWithData::from_precomputed(block)
@@ -834,6 +1284,7 @@ impl BlockWithoutCanonicity {
             date_time,
             snark_jobs,
             state_hash: block.state_hash().0,
+            genesis_state_hash: block.genesis_state_hash().0,
             block_height: block.blockchain_length(),
             global_slot_since_genesis: block.global_slot_since_genesis(),
             coinbase_receiver: PK {
@@ -854,6 +1305,8 @@ impl BlockWithoutCanonicity {
                     utc_date,
                     snarked_ledger_hash,
                     staged_ledger_hash,
+                    first_pass_ledger_hash,
+                    second_pass_ledger_hash,
                 },
                 consensus_state: ConsensusState {
                     total_currency,
@@ -912,6 +1365,7 @@ impl BlockQueryInput {
             and,
             state_hash,
             block_height: blockchain_length,
+            epoch,
             global_slot_since_genesis,
             block_height_gt,
             block_height_gte,
@@ -935,6 +1389,11 @@ impl BlockQueryInput {
                 return false;
             }
         }
+        if let Some(epoch) = epoch {
+            if block.block.protocol_state.consensus_state.epoch != *epoch {
+                return false;
+            }
+        }
 
         // global slot
         if protocol_state
@@ -1157,6 +1616,19 @@ impl Block {
             .expect("internal command counts")
             .unwrap_or_default();
         let epoch_num_slots_produced = counts[12];
+
+        let global_slot = block.global_slot_since_genesis();
+        let receipt = db
+            .get_block_receipt(&state_hash)
+            .expect("block receipt")
+            .unwrap_or_default();
+        let fee_stats = db
+            .get_block_fee_stats(&state_hash)
+            .expect("block fee stats")
+            .unwrap_or_else(|| BlockFeeStats::from_precomputed(block));
+        let supercharge_audit = SuperchargeAudit::audit(db, block)
+            .expect("supercharge audit")
+            .map(Into::into);
         Self {
             canonical,
             epoch_num_blocks,
@@ -1176,6 +1648,28 @@ impl Block {
             ),
             epoch_num_slots_produced,
             num_unique_block_producers_last_n_blocks: None,
+            received_at: receipt
+                .received_at()
+                .map(|millis| millis_to_iso_date_string(millis as i64)),
+            slot_start_time: millis_to_iso_date_string(
+                db.get_chain_constants()
+                    .expect("chain constants")
+                    .global_slot_to_millis(global_slot) as i64,
+            ),
+            latency_seconds: receipt.latency_seconds(global_slot),
+            latency_reliable: receipt.latency_reliable,
+            fee_stats: fee_stats.into(),
+            supercharge_audit,
+        }
+    }
+}
+
+impl From<SuperchargeAudit> for SuperchargeAuditStatus {
+    fn from(value: SuperchargeAudit) -> Self {
+        match value {
+            SuperchargeAudit::ExpectedMatches => Self::ExpectedMatches,
+            SuperchargeAudit::Mismatch => Self::Mismatch,
+            SuperchargeAudit::LedgerUnavailable => Self::LedgerUnavailable,
         }
     }
 }
@@ -0,0 +1,51 @@
+use super::{db, error::store_error};
+use crate::{base::public_key::PublicKey, command::store::UserCommandStore};
+use async_graphql::{Context, Object, Result, SimpleObject};
+
+#[derive(Default)]
+pub struct DelegationsQueryRoot;
+
+/// Delegate change of an account in a canonical block
+#[derive(SimpleObject)]
+pub struct DelegationChange {
+    delegate: String,
+
+    /// Delegator's nonce after the change
+    nonce: u32,
+    block_height: u32,
+    state_hash: String,
+
+    /// Hash of the stake delegation or zkapp command
+    txn_hash: String,
+}
+
+#[Object]
+impl DelegationsQueryRoot {
+    /// Every delegation change of `publicKey` across the canonical chain,
+    /// oldest first
+    async fn delegation_history<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        public_key: String,
+    ) -> Result<Vec<DelegationChange>> {
+        if !PublicKey::is_valid(&public_key) {
+            return Err(format!("Invalid public key: {public_key}").into());
+        }
+
+        let pk: PublicKey = public_key.into();
+        let changes = db(ctx)
+            .get_delegation_history(&pk)
+            .map_err(|e| store_error("delegation history", &pk, e))?;
+
+        Ok(changes
+            .into_iter()
+            .map(|change| DelegationChange {
+                delegate: change.delegate.0,
+                nonce: change.nonce.0,
+                block_height: change.blockchain_length,
+                state_hash: change.state_hash.0,
+                txn_hash: change.txn_hash.to_string(),
+            })
+            .collect())
+    }
+}
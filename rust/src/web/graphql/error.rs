@@ -0,0 +1,105 @@
+//! GraphQL field errors
+//!
+//! Root resolvers are nullable so a failing field resolves to `null` with a
+//! scoped error, leaving the rest of the response's `data` intact. Errors
+//! carry machine-readable `code`, `entity` & `key` extensions.
+
+use async_graphql::{Error, ErrorExtensions};
+use std::fmt::Display;
+
+/// Machine-readable error codes, set as the `code` extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// The requested entity is missing from the store
+    NotFound,
+
+    /// Reading the requested entity from the store failed
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NotFound => "NOT_FOUND",
+            Self::Internal => "INTERNAL",
+        }
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Field error with `code`, `entity` & `key` extensions
+pub fn field_error(code: ErrorCode, entity: &str, key: impl Display, message: String) -> Error {
+    let key = key.to_string();
+    Error::new(message).extend_with(|_, extensions| {
+        extensions.set("code", code.as_str());
+        extensions.set("entity", entity);
+        extensions.set("key", key.as_str());
+    })
+}
+
+/// Field error for an entity missing from the store
+pub fn not_found(entity: &str, key: impl Display) -> Error {
+    field_error(
+        ErrorCode::NotFound,
+        entity,
+        &key,
+        format!("{entity} not found: {key}"),
+    )
+}
+
+/// Field error for a failed store read
+pub fn store_error(entity: &str, key: impl Display, err: impl Display) -> Error {
+    field_error(
+        ErrorCode::Internal,
+        entity,
+        &key,
+        format!("Failed to read {entity} {key}: {err}"),
+    )
+}
+
+/// Converts store lookups into scoped field errors
+pub trait StoreResultExt<T> {
+    /// Missing values become [ErrorCode::NotFound] errors & store failures
+    /// become [ErrorCode::Internal] errors
+    fn or_not_found(self, entity: &str, key: impl Display) -> async_graphql::Result<T>;
+}
+
+impl<T> StoreResultExt<T> for anyhow::Result<Option<T>> {
+    fn or_not_found(self, entity: &str, key: impl Display) -> async_graphql::Result<T> {
+        match self {
+            Ok(Some(value)) => Ok(value),
+            Ok(None) => Err(not_found(entity, key)),
+            Err(err) => Err(store_error(entity, key, err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::Value;
+
+    #[test]
+    fn extensions() {
+        let err = anyhow::Ok(None::<u32>)
+            .or_not_found("staking ledger", 42)
+            .unwrap_err();
+        let extensions = err.extensions.expect("extensions");
+
+        assert_eq!(err.message, "staking ledger not found: 42");
+        assert_eq!(
+            extensions.get("code"),
+            Some(&Value::from(ErrorCode::NotFound.as_str()))
+        );
+        assert_eq!(
+            extensions.get("entity"),
+            Some(&Value::from("staking ledger"))
+        );
+        assert_eq!(extensions.get("key"), Some(&Value::from("42")));
+    }
+}
@@ -0,0 +1,164 @@
+use super::{db, max_limit};
+use crate::{
+    constants::EVENTS_MAX_PAGE_SIZE,
+    event::{
+        db::ReorgEvent,
+        page::{EventKind as Kind, LoggedEvent},
+        store::EventStore,
+    },
+};
+use async_graphql::{Context, Enum, Object, Result, SimpleObject};
+
+#[derive(Default)]
+pub struct EventsQueryRoot;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum EventKind {
+    NewBlock,
+    NewBestTip,
+    NewCanonical,
+    Ledger,
+    Staking,
+    Reorg,
+    Other,
+}
+
+/// Event log entry
+#[derive(SimpleObject)]
+pub struct Event {
+    seq_num: u32,
+
+    /// Blockchain length prefix of block & reorg events, otherwise 0
+    height: u32,
+
+    kind: EventKind,
+
+    /// Decoded event description, null if written by an incompatible version
+    description: Option<String>,
+
+    /// Event JSON as written
+    json: String,
+}
+
+/// Best tip branch switch
+#[derive(SimpleObject)]
+pub struct Reorg {
+    old_tip: String,
+    old_tip_height: u32,
+    new_tip: String,
+    new_tip_height: u32,
+
+    /// Last block shared by the old & new best chains
+    common_ancestor: String,
+    common_ancestor_height: u32,
+
+    /// Number of orphaned blocks
+    depth: u32,
+
+    /// Old best chain blocks above the common ancestor, highest first
+    orphaned_blocks: Vec<String>,
+}
+
+#[Object]
+impl EventsQueryRoot {
+    /// Event log entries of the given kinds (all if omitted), from sequence
+    /// number `fromSeq` in sequence order
+    async fn events<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        kinds: Option<Vec<EventKind>>,
+        #[graphql(default)] from_seq: u32,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<Vec<Event>> {
+        let limit = max_limit(ctx, limit);
+        let kinds: Vec<Kind> = kinds
+            .unwrap_or_default()
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        Ok(db(ctx)
+            .get_event_page(&kinds, from_seq, limit.min(EVENTS_MAX_PAGE_SIZE))?
+            .events
+            .into_iter()
+            .map(Event::from)
+            .collect())
+    }
+
+    /// Most recent best tip branch switches, most recent first
+    async fn reorgs<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<Vec<Reorg>> {
+        let limit = max_limit(ctx, limit);
+        Ok(db(ctx)
+            .get_reorgs(limit)?
+            .into_iter()
+            .map(Reorg::from)
+            .collect())
+    }
+}
+
+/////////////////
+// Conversions //
+/////////////////
+
+impl From<EventKind> for Kind {
+    fn from(value: EventKind) -> Self {
+        match value {
+            EventKind::NewBlock => Self::NewBlock,
+            EventKind::NewBestTip => Self::NewBestTip,
+            EventKind::NewCanonical => Self::NewCanonical,
+            EventKind::Ledger => Self::Ledger,
+            EventKind::Staking => Self::Staking,
+            EventKind::Reorg => Self::Reorg,
+            EventKind::Other => Self::Other,
+        }
+    }
+}
+
+impl From<Kind> for EventKind {
+    fn from(value: Kind) -> Self {
+        match value {
+            Kind::NewBlock => Self::NewBlock,
+            Kind::NewBestTip => Self::NewBestTip,
+            Kind::NewCanonical => Self::NewCanonical,
+            Kind::Ledger => Self::Ledger,
+            Kind::Staking => Self::Staking,
+            Kind::Reorg => Self::Reorg,
+            Kind::Other => Self::Other,
+        }
+    }
+}
+
+impl From<ReorgEvent> for Reorg {
+    fn from(value: ReorgEvent) -> Self {
+        Self {
+            depth: value.depth(),
+            old_tip: value.old_tip.0,
+            old_tip_height: value.old_tip_height,
+            new_tip: value.new_tip.0,
+            new_tip_height: value.new_tip_height,
+            common_ancestor: value.common_ancestor.0,
+            common_ancestor_height: value.common_ancestor_height,
+            orphaned_blocks: value
+                .orphaned_blocks
+                .into_iter()
+                .map(|state_hash| state_hash.0)
+                .collect(),
+        }
+    }
+}
+
+impl From<LoggedEvent> for Event {
+    fn from(value: LoggedEvent) -> Self {
+        Self {
+            seq_num: value.seq_num,
+            height: value.height,
+            kind: value.kind.into(),
+            description: value.description,
+            json: value.json.to_string(),
+        }
+    }
+}
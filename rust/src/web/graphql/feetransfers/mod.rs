@@ -1,11 +1,16 @@
 use super::{
     blocks::{Block, BlockWithoutCanonicity},
+    error::store_error,
     gen::BlockQueryInput,
-    get_block, get_block_canonicity,
+    get_block, get_block_canonicity, in_chain_as_of, max_limit,
 };
 use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
-    block::{precomputed::PrecomputedBlock, store::BlockStore},
+    block::{
+        fee_stats::BlockFeeStats, precomputed::PrecomputedBlock, store::BlockStore,
+        supercharge::SuperchargeAudit,
+    },
+    canonicity::as_of::CanonicalChainAsOf,
     command::{
         internal::{store::InternalCommandStore, DbInternalCommandWithData},
         store::UserCommandStore,
@@ -13,7 +18,7 @@ use crate::{
     constants::*,
     snark_work::store::SnarkStore,
     store::IndexerStore,
-    utility::store::common::{from_be_bytes, U32_LEN},
+    utility::store::common::{from_be_bytes, state_hash_suffix, U32_LEN},
     web::graphql::db,
 };
 use async_graphql::{Context, Enum, InputObject, Object, Result, SimpleObject};
@@ -28,6 +33,9 @@ pub struct Feetransfer {
     pub block_height: u32,
     pub date_time: String,
 
+    /// Index in the block's ledger application order
+    pub index_in_block: u32,
+
     #[graphql(name = "type")]
     pub feetransfer_kind: String,
 
@@ -61,6 +69,13 @@ impl FeetransferWithMeta {
         &self.feetransfer
     }
 
+    /// Value block creator
+    async fn block_creator(&self) -> Option<String> {
+        self.block
+            .as_ref()
+            .map(|block| block.block_creator().to_string())
+    }
+
     async fn block_state_hash<'ctx>(&self, ctx: &Context<'ctx>) -> Result<Option<Block>> {
         let db = db(ctx);
         let epoch_num_blocks = db.get_block_production_epoch_count(None)?;
@@ -103,6 +118,12 @@ impl FeetransferWithMeta {
                 block_num_internal_commands,
                 epoch_num_slots_produced,
                 num_unique_block_producers_last_n_blocks: None,
+                fee_stats: db
+                    .get_block_fee_stats(&block.state_hash())?
+                    .unwrap_or_else(|| BlockFeeStats::from_precomputed(&block))
+                    .into(),
+                supercharge_audit: SuperchargeAudit::audit(db, &block)?.map(Into::into),
+                ..Default::default()
             }))
         } else {
             Ok(None)
@@ -118,12 +139,20 @@ pub struct FeetransferQueryInput {
     /// Value block state hash
     block_state_hash: Option<BlockQueryInput>,
 
-    /// Value canonical
+    /// Value canonical (defaults to true when querying by both recipient &
+    /// block creator)
     canonical: Option<bool>,
 
     /// Value recipient
     recipient: Option<String>,
 
+    /// Value block creator
+    block_creator: Option<String>,
+
+    /// Value fee transfer type
+    #[graphql(name = "type")]
+    feetransfer_kind: Option<String>,
+
     /// Value block height greater than
     #[graphql(name = "blockHeight_gt")]
     pub block_height_gt: Option<u32>,
@@ -167,13 +196,18 @@ impl FeetransferQueryRoot {
         query: Option<FeetransferQueryInput>,
         sort_by: Option<FeetransferSortByInput>,
         #[graphql(default = 100)] limit: usize,
-    ) -> Result<Vec<FeetransferWithMeta>> {
-        use FeetransferSortByInput::*;
+        as_of_height: Option<u32>,
+    ) -> Result<Option<Vec<FeetransferWithMeta>>> {
+        let limit = max_limit(ctx, limit);
 
         let db = db(ctx);
         let epoch_num_internal_commands = db.get_internal_commands_epoch_count(None)?;
         let total_num_internal_commands = db.get_internal_commands_total_count()?;
-        let mut fee_transfers = vec![];
+
+        // restrict to the canonical chain as of the given height
+        let mut as_of = as_of_height
+            .map(|height| CanonicalChainAsOf::new(db, height))
+            .transpose()?;
 
         // state_hash query
         if let Some(state_hash) = query
@@ -181,223 +215,281 @@ impl FeetransferQueryRoot {
             .and_then(|f| f.block_state_hash.as_ref())
             .and_then(|f| f.state_hash.clone())
         {
-            return Ok(get_fee_transfers_for_state_hash(
+            let state_hash: StateHash = state_hash.into();
+            if !in_chain_as_of(db, &mut as_of, &state_hash)? {
+                return Ok(Some(vec![]));
+            }
+
+            return get_fee_transfers_for_state_hash(
                 db,
                 &query,
-                &state_hash.into(),
+                &state_hash,
                 sort_by,
                 limit,
                 epoch_num_internal_commands,
                 total_num_internal_commands,
-            ));
+            )
+            .map(Some);
         }
 
-        // block height bounded query
-        if query.as_ref().map_or(false, |q| {
-            q.block_height_gt.is_some()
-                || q.block_height_gte.is_some()
-                || q.block_height_lt.is_some()
-                || q.block_height_lte.is_some()
-        }) {
-            let (min, max) = {
-                let FeetransferQueryInput {
-                    block_height_gt,
-                    block_height_gte,
-                    block_height_lt,
-                    block_height_lte,
-                    ..
-                } = query.as_ref().expect("query will contain a value");
-                let min_bound = match (*block_height_gte, *block_height_gt) {
-                    (Some(gte), Some(gt)) => gte.max(gt + 1),
-                    (Some(gte), None) => gte,
-                    (None, Some(gt)) => gt + 1,
-                    (None, None) => 1,
-                };
-
-                let max_bound = match (*block_height_lte, *block_height_lt) {
-                    (Some(lte), Some(lt)) => lte.min(lt - 1),
-                    (Some(lte), None) => lte,
-                    (None, Some(lt)) => lt - 1,
-                    (None, None) => db.get_best_block_height()?.unwrap(),
-                };
-                (min_bound, max_bound + 1)
-            };
-            let iter = match sort_by {
-                Some(BlockHeightAsc) => db.internal_commands_block_height_iterator(
-                    IteratorMode::From(&min.to_be_bytes(), Direction::Forward),
-                ),
-                Some(BlockHeightDesc) | None => db.internal_commands_block_height_iterator(
-                    IteratorMode::From(&max.to_be_bytes(), Direction::Reverse),
-                ),
-            };
-
-            for (key, value) in iter.flatten() {
-                let state_hash = StateHash::from_bytes(&key[U32_LEN..][..StateHash::LEN])?;
-
-                // avoid deserializing internal command & PCB if possible
-                let canonical = get_block_canonicity(db, &state_hash);
-                if let Some(q) = query.as_ref() {
-                    if let Some(query_canonicity) = q.canonical {
-                        if canonical != query_canonicity {
-                            continue;
-                        }
-                    }
-
-                    if block_out_of_bounds(from_be_bytes(key[..U32_LEN].to_vec()), q) {
-                        break;
-                    }
-                }
-
-                let internal_cmd: DbInternalCommandWithData = serde_json::from_slice(&value)?;
-                let ft = Feetransfer::from((
-                    internal_cmd,
-                    epoch_num_internal_commands,
-                    total_num_internal_commands,
-                ));
-                let feetransfer_with_meta = FeetransferWithMeta {
-                    canonical,
-                    feetransfer: ft,
-                    block: Some(get_block(db, &state_hash)),
-                };
-
-                if query
-                    .as_ref()
-                    .map_or(true, |q| q.matches(&feetransfer_with_meta))
-                {
-                    fee_transfers.push(feetransfer_with_meta);
-
-                    if fee_transfers.len() >= limit {
-                        break;
-                    }
-                }
-            }
-            return Ok(fee_transfers);
-        }
-
-        // recipient query
-        if let Some(recipient) = query.as_ref().and_then(|q| q.recipient.as_ref()) {
-            let iter = match sort_by {
-                Some(BlockHeightAsc) => db.internal_commands_pk_block_height_iterator(
-                    recipient.clone().into(),
-                    Direction::Forward,
-                ),
-                Some(BlockHeightDesc) | None => db.internal_commands_pk_block_height_iterator(
-                    recipient.clone().into(),
-                    Direction::Reverse,
-                ),
-            };
-
-            for (key, value) in iter.flatten() {
-                if key[..PublicKey::LEN] != *recipient.as_bytes() {
-                    // we've gone beyond our recipient
-                    break;
-                }
-
-                let state_hash =
-                    StateHash::from_bytes(&key[PublicKey::LEN..][U32_LEN..][..StateHash::LEN])?;
-                let canonical = get_block_canonicity(db, &state_hash);
-                if let Some(q) = query.as_ref() {
-                    if let Some(query_canonicity) = q.canonical {
-                        if canonical != query_canonicity {
-                            continue;
-                        }
-                    }
-
-                    if block_out_of_bounds(
-                        from_be_bytes(key[PublicKey::LEN..][..U32_LEN].to_vec()),
-                        q,
-                    ) {
-                        break;
-                    }
-                }
-
-                let internal_command: DbInternalCommandWithData = serde_json::from_slice(&value)?;
-                let pcb = get_block(db, &state_hash);
-                let ft = FeetransferWithMeta {
-                    canonical,
-                    block: Some(pcb),
-                    feetransfer: Feetransfer::from((
-                        internal_command,
-                        epoch_num_internal_commands,
-                        total_num_internal_commands,
-                    )),
-                };
-
-                if query.as_ref().map_or(true, |q| q.matches(&ft)) {
-                    fee_transfers.push(ft);
-                }
-
-                if fee_transfers.len() >= limit {
-                    break;
-                }
-            }
-            return Ok(fee_transfers);
+        // recipient & block creator query
+        if let Some((recipient, creator)) = query
+            .as_ref()
+            .filter(|q| !q.has_block_height_filter())
+            .and_then(|q| q.recipient.as_ref().zip(q.block_creator.as_ref()))
+        {
+            return get_fee_transfers_for_recipient_and_creator(
+                db,
+                query.as_ref().expect("query will contain a value"),
+                &recipient.to_owned().into(),
+                &creator.to_owned().into(),
+                sort_by,
+                limit,
+                &mut as_of,
+                epoch_num_internal_commands,
+                total_num_internal_commands,
+            )
+            .map(Some);
         }
 
-        get_default_fee_transfers(
+        // block height range query, optionally by recipient
+        get_fee_transfers_in_height_range(
             db,
-            query,
+            query.as_ref(),
             sort_by,
             limit,
+            &mut as_of,
             epoch_num_internal_commands,
             total_num_internal_commands,
         )
+        .map(Some)
     }
 }
 
-fn get_default_fee_transfers(
+/// Fee transfers in the query's block height range, in block height order
+///
+/// Seeks the recipient's internal commands if the query has a recipient, else
+/// all internal commands, to the start of the range & stops at its end
+fn get_fee_transfers_in_height_range(
     db: &Arc<IndexerStore>,
-    query: Option<FeetransferQueryInput>,
+    query: Option<&FeetransferQueryInput>,
     sort_by: Option<FeetransferSortByInput>,
     limit: usize,
+    as_of: &mut Option<CanonicalChainAsOf>,
     epoch_num_internal_commands: u32,
     total_num_internal_commands: u32,
 ) -> Result<Vec<FeetransferWithMeta>> {
     let mut fee_transfers = Vec::new();
-    let mode = if let Some(FeetransferSortByInput::BlockHeightAsc) = sort_by {
-        IteratorMode::Start
-    } else {
-        IteratorMode::End
+    let Some((min, max)) = query.map_or(Some((0, u32::MAX)), |q| q.block_height_range()) else {
+        return Ok(fee_transfers);
     };
 
-    for (key, value) in db.internal_commands_block_height_iterator(mode).flatten() {
-        let state_hash = StateHash::from_bytes(&key[U32_LEN..][..StateHash::LEN])?;
-        let canonical = get_block_canonicity(db, &state_hash);
-        if let Some(q) = query.as_ref() {
-            if let Some(query_canonicity) = q.canonical {
-                if canonical != query_canonicity {
-                    continue;
-                }
-            }
+    let direction = match sort_by {
+        Some(FeetransferSortByInput::BlockHeightAsc) => Direction::Forward,
+        Some(FeetransferSortByInput::BlockHeightDesc) | None => Direction::Reverse,
+    };
+    let start = match direction {
+        Direction::Forward => min,
+        Direction::Reverse => max,
+    };
+
+    let recipient: Option<PublicKey> = query.and_then(|q| q.recipient.clone()).map(Into::into);
+    let (iter, prefix_len) = match recipient.as_ref() {
+        Some(recipient) => (
+            db.internal_commands_pk_block_height_iterator_from(recipient, start, direction),
+            PublicKey::LEN,
+        ),
+        None => (
+            db.internal_commands_block_height_iterator_from(start, direction),
+            0,
+        ),
+    };
 
-            if block_out_of_bounds(from_be_bytes(key[..U32_LEN].to_vec()), q) {
+    for (key, value) in iter.flatten() {
+        if let Some(recipient) = recipient.as_ref() {
+            if key[..PublicKey::LEN] != *recipient.0.as_bytes() {
+                // we've gone beyond our recipient
                 break;
             }
         }
 
+        let blockchain_length: u32 = from_be_bytes(key[prefix_len..][..U32_LEN].to_vec());
+        if query.map_or(false, |q| block_out_of_bounds(blockchain_length, q)) {
+            break;
+        }
+
+        let state_hash = StateHash::from_bytes(&key[prefix_len..][U32_LEN..][..StateHash::LEN])?;
+        if !in_chain_as_of(db, as_of, &state_hash)? {
+            continue;
+        }
+
+        // avoid deserializing internal command & PCB if possible
+        let canonical = get_block_canonicity(db, &state_hash);
+        if let Some(query_canonicity) = query.and_then(|q| q.canonical) {
+            if canonical != query_canonicity {
+                continue;
+            }
+        }
+
+        let Some(block) = get_block(db, &state_hash)? else {
+            continue;
+        };
+
         let ft = Feetransfer::from((
             serde_json::from_slice::<DbInternalCommandWithData>(&value)?,
             epoch_num_internal_commands,
             total_num_internal_commands,
         ));
-        let pcb = get_block(db, &state_hash);
         let feetransfer_with_meta = FeetransferWithMeta {
             canonical,
             feetransfer: ft,
-            block: Some(pcb),
+            block: Some(block),
         };
 
-        if query
-            .as_ref()
-            .map_or(true, |q| q.matches(&feetransfer_with_meta))
-        {
+        if query.map_or(true, |q| q.matches(&feetransfer_with_meta)) {
             fee_transfers.push(feetransfer_with_meta);
+
+            if fee_transfers.len() >= limit {
+                break;
+            }
         }
+    }
+    Ok(fee_transfers)
+}
 
-        if fee_transfers.len() >= limit {
-            break;
+/// Fee transfers to `recipient` in blocks created by `creator`
+///
+/// Iterates the narrower of the recipient's internal commands & the creator's
+/// blocks, point-checking the other. Canonical defaults to true.
+#[allow(clippy::too_many_arguments)]
+fn get_fee_transfers_for_recipient_and_creator(
+    db: &Arc<IndexerStore>,
+    query: &FeetransferQueryInput,
+    recipient: &PublicKey,
+    creator: &PublicKey,
+    sort_by: Option<FeetransferSortByInput>,
+    limit: usize,
+    as_of: &mut Option<CanonicalChainAsOf>,
+    epoch_num_internal_commands: u32,
+    total_num_internal_commands: u32,
+) -> Result<Vec<FeetransferWithMeta>> {
+    let direction = match sort_by {
+        Some(FeetransferSortByInput::BlockHeightAsc) => Direction::Forward,
+        Some(FeetransferSortByInput::BlockHeightDesc) | None => Direction::Reverse,
+    };
+    let query_canonicity = query.canonical.unwrap_or(true);
+
+    let mut fee_transfers = vec![];
+    let mut push_matching = |internal_command: DbInternalCommandWithData,
+                             canonical: bool,
+                             block: &PrecomputedBlock|
+     -> bool {
+        let ft = FeetransferWithMeta {
+            canonical,
+            block: Some(block.clone()),
+            feetransfer: Feetransfer::from((
+                internal_command,
+                epoch_num_internal_commands,
+                total_num_internal_commands,
+            )),
+        };
+
+        if query.matches(&ft) {
+            fee_transfers.push(ft);
+        }
+
+        fee_transfers.len() >= limit
+    };
+
+    let num_recipient_internal_commands = db
+        .get_pk_num_internal_commands(recipient)?
+        .unwrap_or_default();
+    let num_creator_blocks = db.get_block_production_pk_total_count(creator)?;
+
+    if num_recipient_internal_commands <= num_creator_blocks {
+        // iterate the recipient's internal commands, checking the block creator
+        for (key, value) in db
+            .internal_commands_pk_block_height_iterator(recipient.clone(), direction)
+            .flatten()
+        {
+            if key[..PublicKey::LEN] != *recipient.0.as_bytes() {
+                break;
+            }
+
+            let state_hash =
+                StateHash::from_bytes(&key[PublicKey::LEN..][U32_LEN..][..StateHash::LEN])?;
+            if !in_chain_as_of(db, as_of, &state_hash)? {
+                continue;
+            }
+
+            // avoid deserializing internal command & PCB if possible
+            let canonical = get_block_canonicity(db, &state_hash);
+            if canonical != query_canonicity
+                || db.get_block_creator(&state_hash)?.as_ref() != Some(creator)
+            {
+                continue;
+            }
+
+            let Some(block) = get_block(db, &state_hash)? else {
+                continue;
+            };
+
+            let internal_command: DbInternalCommandWithData = serde_json::from_slice(&value)?;
+            if push_matching(internal_command, canonical, &block) {
+                break;
+            }
+        }
+    } else {
+        // iterate the creator's blocks, checking the internal command recipients
+        let mut end = [0; PublicKey::LEN + U32_LEN];
+        end[..PublicKey::LEN].copy_from_slice(creator.0.as_bytes());
+        end[PublicKey::LEN..].copy_from_slice(&u32::MAX.to_be_bytes());
+
+        let mode = match direction {
+            Direction::Forward => IteratorMode::From(creator.0.as_bytes(), direction),
+            Direction::Reverse => IteratorMode::From(&end, direction),
+        };
+
+        'blocks: for (key, _) in db.block_creator_block_height_iterator(mode).flatten() {
+            if key[..PublicKey::LEN] != *creator.0.as_bytes() {
+                break;
+            }
+
+            let state_hash = state_hash_suffix(&key)?;
+            if !in_chain_as_of(db, as_of, &state_hash)? {
+                continue;
+            }
+
+            let canonical = get_block_canonicity(db, &state_hash);
+            if canonical != query_canonicity {
+                continue;
+            }
+
+            let mut internal_commands: Vec<_> = db
+                .get_internal_commands(&state_hash)?
+                .into_iter()
+                .filter(|internal_command| internal_command.contains_pk(recipient))
+                .collect();
+            if internal_commands.is_empty() {
+                continue;
+            }
+
+            // same order as the recipient index
+            if matches!(direction, Direction::Reverse) {
+                internal_commands.reverse();
+            }
+
+            let Some(block) = get_block(db, &state_hash)? else {
+                continue;
+            };
+            for internal_command in internal_commands {
+                if push_matching(internal_command, canonical, &block) {
+                    break 'blocks;
+                }
+            }
         }
     }
+
     Ok(fee_transfers)
 }
 
@@ -409,17 +501,18 @@ fn get_fee_transfers_for_state_hash(
     limit: usize,
     epoch_num_internal_commands: u32,
     total_num_internal_commands: u32,
-) -> Vec<FeetransferWithMeta> {
+) -> Result<Vec<FeetransferWithMeta>> {
     let canonical = get_block_canonicity(db, state_hash);
     if let Some(query_canonicity) = query.as_ref().and_then(|q| q.canonical) {
         if canonical != query_canonicity {
-            return vec![];
+            return Ok(vec![]);
         }
     }
 
     let pcb = match db.get_block(state_hash) {
         Ok(Some(pcb)) => pcb.0,
-        _ => return vec![],
+        Ok(None) => return Ok(vec![]),
+        Err(e) => return Err(store_error("block", state_hash, e)),
     };
     match db.get_internal_commands(state_hash) {
         Ok(internal_commands) => {
@@ -453,14 +546,15 @@ fn get_fee_transfers_for_state_hash(
             }
 
             internal_commands.truncate(limit);
-            internal_commands
+            Ok(internal_commands)
         }
-        Err(_) => vec![],
+        Err(e) => Err(store_error("internal commands", state_hash, e)),
     }
 }
 
 impl From<(DbInternalCommandWithData, u32, u32)> for Feetransfer {
     fn from(int_cmd: (DbInternalCommandWithData, u32, u32)) -> Self {
+        let index_in_block = int_cmd.0.index_in_block();
         match int_cmd.0 {
             DbInternalCommandWithData::FeeTransfer {
                 receiver,
@@ -477,6 +571,7 @@ impl From<(DbInternalCommandWithData, u32, u32)> for Feetransfer {
                 feetransfer_kind: kind.to_string(),
                 block_height,
                 date_time: millis_to_iso_date_string(date_time),
+                index_in_block,
                 epoch_num_internal_commands: int_cmd.1,
                 total_num_internal_commands: int_cmd.2,
             },
@@ -487,6 +582,7 @@ impl From<(DbInternalCommandWithData, u32, u32)> for Feetransfer {
                 kind,
                 date_time,
                 block_height,
+                ..
             } => Self {
                 state_hash: state_hash.0,
                 fee: amount,
@@ -494,6 +590,7 @@ impl From<(DbInternalCommandWithData, u32, u32)> for Feetransfer {
                 feetransfer_kind: kind.to_string(),
                 block_height,
                 date_time: millis_to_iso_date_string(date_time),
+                index_in_block,
                 epoch_num_internal_commands: int_cmd.1,
                 total_num_internal_commands: int_cmd.2,
             },
@@ -502,6 +599,35 @@ impl From<(DbInternalCommandWithData, u32, u32)> for Feetransfer {
 }
 
 impl FeetransferQueryInput {
+    /// Whether the query filters on block height
+    fn has_block_height_filter(&self) -> bool {
+        self.block_height.is_some()
+            || self.block_height_gt.is_some()
+            || self.block_height_gte.is_some()
+            || self.block_height_lt.is_some()
+            || self.block_height_lte.is_some()
+    }
+
+    /// Inclusive block height range of the query's height filters, `None` if
+    /// no height satisfies them
+    fn block_height_range(&self) -> Option<(u32, u32)> {
+        let mut min = self.block_height_gte.unwrap_or_default();
+        let mut max = self.block_height_lte.unwrap_or(u32::MAX);
+
+        if let Some(gt) = self.block_height_gt {
+            min = min.max(gt.checked_add(1)?);
+        }
+        if let Some(lt) = self.block_height_lt {
+            max = max.min(lt.checked_sub(1)?);
+        }
+        if let Some(height) = self.block_height {
+            min = min.max(height);
+            max = max.min(height);
+        }
+
+        (min <= max).then_some((min, max))
+    }
+
     pub fn matches(&self, ft: &FeetransferWithMeta) -> bool {
         let Self {
             block_height_gt,
@@ -512,6 +638,8 @@ impl FeetransferQueryInput {
             block_state_hash,
             canonical,
             recipient,
+            block_creator,
+            feetransfer_kind,
             and,
             or,
         } = self;
@@ -527,7 +655,19 @@ impl FeetransferQueryInput {
             }
         }
 
+        if let Some(kind) = feetransfer_kind.as_ref() {
+            if ft.feetransfer.feetransfer_kind != *kind {
+                return false;
+            }
+        }
+
         let pcb = ft.block.as_ref().expect("block will exist");
+        if let Some(block_creator) = block_creator.as_ref() {
+            if pcb.block_creator().0 != *block_creator {
+                return false;
+            }
+        }
+
         let blockchain_length = pcb.blockchain_length();
         if let Some(height) = block_height.as_ref() {
             if blockchain_length != *height {
@@ -578,19 +718,11 @@ impl FeetransferQueryInput {
 }
 
 fn block_out_of_bounds(blockchain_length: u32, query: &FeetransferQueryInput) -> bool {
-    query
-        .block_height_gt
-        .map_or(false, |gt| blockchain_length <= gt)
-        || query
-            .block_height_gte
-            .map_or(false, |gte| blockchain_length < gte)
-        || query
-            .block_height_lt
-            .map_or(false, |lt| blockchain_length >= lt)
-        || query
-            .block_height_lte
-            .map_or(false, |lte| blockchain_length > lte)
+    query.block_height_range().map_or(true, |(min, max)| {
+        blockchain_length < min || blockchain_length > max
+    })
 }
+
 #[cfg(test)]
 mod block_out_of_bounds_tests {
     use super::*;
@@ -666,4 +798,27 @@ mod block_out_of_bounds_tests {
         };
         assert!(!block_out_of_bounds(100, &query));
     }
+
+    #[test]
+    fn test_empty_height_ranges() {
+        let query = FeetransferQueryInput {
+            block_height_lt: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(query.block_height_range(), None);
+        assert!(block_out_of_bounds(0, &query));
+
+        let query = FeetransferQueryInput {
+            block_height_gt: Some(u32::MAX),
+            ..Default::default()
+        };
+        assert_eq!(query.block_height_range(), None);
+
+        let query = FeetransferQueryInput {
+            block_height: Some(10),
+            block_height_gte: Some(11),
+            ..Default::default()
+        };
+        assert_eq!(query.block_height_range(), None);
+    }
 }
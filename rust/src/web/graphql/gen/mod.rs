@@ -567,6 +567,7 @@ pub struct BlockQueryInput {
     pub and: Option<Vec<BlockQueryInput>>,
     pub state_hash: Option<String>,
     pub block_height: Option<u32>,
+    pub epoch: Option<u32>,
 
     #[graphql(name = "unique_block_producers_last_n_blocks")]
     pub unique_block_producers_last_n_blocks: Option<u32>,
@@ -801,6 +802,11 @@ pub struct TransactionQueryInput {
     pub canonical: Option<bool>,
     pub kind: Option<String>,
     pub memo: Option<String>,
+
+    /// Substring of the decoded memo (case-sensitive)
+    #[graphql(name = "memo_contains")]
+    pub memo_contains: Option<String>,
+
     pub token: Option<u64>,
     pub is_delegation: Option<bool>,
 
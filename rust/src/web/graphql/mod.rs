@@ -1,29 +1,49 @@
+pub mod account_activity;
 pub mod accounts;
+pub mod activity_stats;
+pub mod block_subscriptions;
 pub mod blocks;
+pub mod delegations;
+pub mod error;
+pub mod events;
 pub mod feetransfers;
 pub mod gen;
+pub mod producer_stats;
+pub mod rewards;
+pub mod snapshot;
+pub mod snark_jobs;
 pub mod snarks;
 pub mod staged_ledgers;
 pub mod stakes;
+pub mod supply;
+pub mod tokens;
 pub mod top_snarkers;
 pub mod top_stakers;
 pub mod transactions;
+pub mod usernames;
+pub mod validation;
 pub mod version;
+pub mod zkapp_actions;
+pub mod zkapp_events;
+pub mod zkapp_subscriptions;
 
-use super::ENDPOINT_GRAPHQL;
+use super::{public_api::PublicApiOptions, ENDPOINT_GRAPHQL};
 use crate::{
     base::state_hash::StateHash,
     block::{precomputed::PrecomputedBlock, store::BlockStore},
+    canonicity::as_of::CanonicalChainAsOf,
     constants::*,
     store::IndexerStore,
 };
 use actix_web::HttpResponse;
 use anyhow::Context as aContext;
 use async_graphql::{
-    http::GraphiQLSource, Context, EmptyMutation, EmptySubscription, InputValueError,
-    InputValueResult, MergedObject, Scalar, ScalarType, Schema, SimpleObject, Value,
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextValidation},
+    http::GraphiQLSource,
+    Context, EmptyMutation, InputValueError, InputValueResult, MergedObject, MergedSubscription,
+    Scalar, ScalarType, Schema, ServerError, SimpleObject, ValidationResult, Value,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 #[derive(MergedObject, Default)]
@@ -37,7 +57,26 @@ pub struct Root(
     staged_ledgers::StagedLedgerQueryRoot,
     top_stakers::TopStakersQueryRoot,
     top_snarkers::TopSnarkersQueryRoot,
+    snark_jobs::SnarkJobsQueryRoot,
+    producer_stats::ProducerStatsQueryRoot,
+    activity_stats::ActivityStatsQueryRoot,
     version::VersionQueryRoot,
+    zkapp_actions::ZkappActionsQueryRoot,
+    zkapp_events::ZkappEventsQueryRoot,
+    account_activity::AccountActivityQueryRoot,
+    delegations::DelegationsQueryRoot,
+    events::EventsQueryRoot,
+    supply::SupplyQueryRoot,
+    usernames::UsernameQueryRoot,
+    rewards::RewardsQueryRoot,
+    validation::ValidationQueryRoot,
+    snapshot::SnapshotQueryRoot,
+);
+
+#[derive(MergedSubscription, Default)]
+pub struct SubscriptionRoot(
+    zkapp_subscriptions::ZkappSubscriptionRoot,
+    block_subscriptions::BlockSubscriptionRoot,
 );
 
 #[derive(SimpleObject)]
@@ -58,17 +97,115 @@ pub struct Timing {
     pub vesting_increment: Option<u64>,
 }
 
+/// Operator configurable GraphQL behavior, reloadable at runtime (see
+/// [RuntimeConfig](crate::web::runtime_config::RuntimeConfig))
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GraphQLOptions {
+    /// Expose stored blocks' raw JSON via `block { rawJson }`
+    pub expose_raw_block_json: bool,
+
+    /// Reject queries nested deeper than this
+    pub max_query_depth: Option<usize>,
+
+    /// Reject queries more complex than this
+    pub max_query_complexity: Option<usize>,
+
+    /// Cap the `limit` argument of list queries at this
+    pub max_limit: Option<usize>,
+}
+
+/// Rejects queries exceeding the request's [GraphQLOptions] &
+/// [PublicApiOptions] max query depth & complexity
+struct QueryLimits;
+
+#[async_trait::async_trait]
+impl Extension for QueryLimits {
+    async fn validation(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        next: NextValidation<'_>,
+    ) -> Result<ValidationResult, Vec<ServerError>> {
+        let result = next.run(ctx).await?;
+        let options = ctx.data_opt::<GraphQLOptions>();
+        let public_api = ctx.data_opt::<PublicApiOptions>();
+
+        let max_depth = min_limit(
+            options.and_then(|options| options.max_query_depth),
+            public_api.map(|public_api| public_api.max_query_depth),
+        );
+        if let Some(max_depth) = max_depth.filter(|max_depth| result.depth > *max_depth) {
+            return Err(vec![ServerError::new(
+                format!(
+                    "Query depth {} exceeds the max query depth {max_depth}",
+                    result.depth
+                ),
+                None,
+            )]);
+        }
+
+        let max_complexity = min_limit(
+            options.and_then(|options| options.max_query_complexity),
+            public_api.map(|public_api| public_api.max_query_complexity),
+        );
+        if let Some(max_complexity) =
+            max_complexity.filter(|max_complexity| result.complexity > *max_complexity)
+        {
+            return Err(vec![ServerError::new(
+                format!(
+                    "Query complexity {} exceeds the max query complexity {max_complexity}",
+                    result.complexity
+                ),
+                None,
+            )]);
+        }
+
+        Ok(result)
+    }
+}
+
+impl ExtensionFactory for QueryLimits {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(QueryLimits)
+    }
+}
+
+fn min_limit(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        _ => a.or(b),
+    }
+}
+
+pub type IndexerSchema = Schema<Root, EmptyMutation, SubscriptionRoot>;
+
 /// Build schema for all endpoints
-pub fn build_schema(store: Arc<IndexerStore>) -> Schema<Root, EmptyMutation, EmptySubscription> {
-    Schema::build(Root::default(), EmptyMutation, EmptySubscription)
+pub fn build_schema(store: Arc<IndexerStore>) -> IndexerSchema {
+    build_schema_with_options(store, GraphQLOptions::default())
+}
+
+/// Build schema for all endpoints with the given options
+pub fn build_schema_with_options(
+    store: Arc<IndexerStore>,
+    options: GraphQLOptions,
+) -> IndexerSchema {
+    Schema::build(Root::default(), EmptyMutation, Default::default())
         .data(store)
+        .data(options)
+        .extension(QueryLimits)
+        .extension(snapshot::SnapshotPinning)
         .finish()
 }
 
 pub async fn indexer_graphiql() -> actix_web::Result<HttpResponse> {
     Ok(HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
-        .body(GraphiQLSource::build().endpoint(ENDPOINT_GRAPHQL).finish()))
+        .body(
+            GraphiQLSource::build()
+                .endpoint(ENDPOINT_GRAPHQL)
+                .subscription_endpoint(ENDPOINT_GRAPHQL)
+                .finish(),
+        ))
 }
 
 pub(crate) fn db<'a>(ctx: &'a Context) -> &'a Arc<IndexerStore> {
@@ -76,6 +213,19 @@ pub(crate) fn db<'a>(ctx: &'a Context) -> &'a Arc<IndexerStore> {
         .expect("Database should be in the context")
 }
 
+pub(crate) fn options(ctx: &Context) -> GraphQLOptions {
+    ctx.data_opt::<GraphQLOptions>()
+        .copied()
+        .unwrap_or_default()
+}
+
+/// The requested `limit`, capped at the [GraphQLOptions::max_limit]
+pub(crate) fn max_limit(ctx: &Context, limit: usize) -> usize {
+    options(ctx)
+        .max_limit
+        .map_or(limit, |max_limit| limit.min(max_limit))
+}
+
 #[derive(Debug, Clone)]
 pub struct Long(pub String);
 
@@ -122,6 +272,26 @@ pub(crate) fn date_time_to_scalar(millis: i64) -> DateTime {
     DateTime(millis_to_iso_date_string(millis))
 }
 
+/// Canonical block height & state hash at the date time, with a field error
+/// if the date time is before genesis or after the best tip
+pub(crate) fn canonical_block_at(
+    db: &Arc<IndexerStore>,
+    date_time: &DateTime,
+) -> async_graphql::Result<(u32, StateHash)> {
+    use crate::canonicity::{store::CanonicityStore, CanonicalBlockAt};
+    match db.get_canonical_block_at(date_time.timestamp_millis())? {
+        CanonicalBlockAt::Block {
+            height, state_hash, ..
+        } => Ok((height, state_hash)),
+        CanonicalBlockAt::BeforeGenesis => {
+            Err(format!("Date time is before genesis: {}", date_time.0).into())
+        }
+        CanonicalBlockAt::AfterTip => {
+            Err(format!("Date time is after best tip: {}", date_time.0).into())
+        }
+    }
+}
+
 /// Convenience function for obtaining a block's canonicity
 pub(crate) fn get_block_canonicity(db: &Arc<IndexerStore>, state_hash: &StateHash) -> bool {
     use crate::canonicity::{store::CanonicityStore, Canonicity};
@@ -130,12 +300,28 @@ pub(crate) fn get_block_canonicity(db: &Arc<IndexerStore>, state_hash: &StateHas
         .unwrap_or(false)
 }
 
-pub(crate) fn get_block(db: &Arc<IndexerStore>, state_hash: &StateHash) -> PrecomputedBlock {
-    db.get_block(state_hash)
-        .with_context(|| format!("block missing from store {state_hash}"))
-        .unwrap()
-        .unwrap()
-        .0
+/// Convenience function for checking whether a block is in the canonical chain
+/// as of the queried height. Without an `asOfHeight`, all blocks are included
+pub(crate) fn in_chain_as_of(
+    db: &Arc<IndexerStore>,
+    as_of: &mut Option<CanonicalChainAsOf>,
+    state_hash: &StateHash,
+) -> anyhow::Result<bool> {
+    match as_of {
+        Some(as_of) => as_of.contains(db, state_hash),
+        None => Ok(true),
+    }
+}
+
+/// Convenience function for obtaining a stored block, `None` if it was pruned
+pub(crate) fn get_block(
+    db: &Arc<IndexerStore>,
+    state_hash: &StateHash,
+) -> anyhow::Result<Option<PrecomputedBlock>> {
+    Ok(db
+        .get_block(state_hash)
+        .with_context(|| format!("reading block {state_hash}"))?
+        .map(|(block, _)| block))
 }
 
 #[derive(Default, Clone, Debug, PartialEq, SimpleObject, Serialize)]
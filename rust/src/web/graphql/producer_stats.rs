@@ -0,0 +1,116 @@
+use super::db;
+use crate::{base::public_key::PublicKey, block::store::BlockStore};
+use async_graphql::{Context, Object, Result, SimpleObject};
+
+#[derive(Default)]
+pub struct ProducerStatsQueryRoot;
+
+/// Block production outcomes of a producer in an epoch
+#[derive(SimpleObject)]
+pub struct ProducerStats {
+    public_key: String,
+    epoch: u32,
+
+    /// Number of blocks produced, including orphans
+    produced: u32,
+
+    /// Number of produced blocks on the best chain
+    canonical: u32,
+
+    /// Number of produced blocks settled off the best chain
+    orphaned: u32,
+
+    /// Number of produced blocks which haven't settled yet
+    pending: u32,
+
+    /// Fraction of settled blocks which were orphaned
+    orphan_rate: Option<f64>,
+}
+
+/// Epoch-level chain quality
+#[derive(SimpleObject)]
+pub struct ChainQuality {
+    epoch: u32,
+
+    /// Number of blocks produced, including orphans
+    produced: u32,
+
+    /// Number of blocks on the best chain
+    canonical: u32,
+
+    /// Number of blocks settled off the best chain
+    orphaned: u32,
+
+    /// Number of slots with at least one block
+    slots_filled: u32,
+
+    /// Canonical blocks per filled slot
+    chain_quality: Option<f64>,
+}
+
+#[Object]
+impl ProducerStatsQueryRoot {
+    /// Produced, canonical & orphaned block counts of the producer in `epoch`
+    /// (defaults to the current epoch)
+    async fn producer_stats<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        public_key: String,
+        epoch: Option<u32>,
+    ) -> Result<Option<ProducerStats>> {
+        if !PublicKey::is_valid(&public_key) {
+            return Err(format!("Invalid public key: {public_key}").into());
+        }
+
+        let db = db(ctx);
+        let pk: PublicKey = public_key.into();
+        let epoch = match epoch {
+            Some(epoch) => epoch,
+            None => db.get_current_epoch()?,
+        };
+
+        let produced = db.get_block_production_pk_epoch_count(&pk, Some(epoch))?;
+        let canonical = db.get_block_production_pk_canonical_epoch_count(&pk, Some(epoch))?;
+        let orphaned = db.get_block_production_pk_orphaned_epoch_count(&pk, Some(epoch))?;
+
+        Ok(Some(ProducerStats {
+            public_key: pk.0,
+            epoch,
+            produced,
+            canonical,
+            orphaned,
+            pending: produced.saturating_sub(canonical + orphaned),
+            orphan_rate: ratio(orphaned, canonical + orphaned),
+        }))
+    }
+
+    /// Canonical blocks per filled slot in `epoch` (defaults to the current
+    /// epoch)
+    async fn chain_quality<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        epoch: Option<u32>,
+    ) -> Result<Option<ChainQuality>> {
+        let db = db(ctx);
+        let epoch = match epoch {
+            Some(epoch) => epoch,
+            None => db.get_current_epoch()?,
+        };
+
+        let canonical = db.get_block_production_canonical_epoch_count(Some(epoch))?;
+        let slots_filled = db.get_epoch_slots_produced_count(Some(epoch))?;
+
+        Ok(Some(ChainQuality {
+            epoch,
+            produced: db.get_block_production_epoch_count(Some(epoch))?,
+            canonical,
+            orphaned: db.get_block_production_orphaned_epoch_count(Some(epoch))?,
+            slots_filled,
+            chain_quality: ratio(canonical, slots_filled),
+        }))
+    }
+}
+
+fn ratio(num: u32, denom: u32) -> Option<f64> {
+    (denom > 0).then(|| num as f64 / denom as f64)
+}
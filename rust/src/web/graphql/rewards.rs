@@ -0,0 +1,138 @@
+use super::{db, error::store_error};
+use crate::{
+    base::public_key::PublicKey,
+    ledger::staking::rewards::{BlockReward, DelegatorPayout, EpochRewards},
+};
+use async_graphql::{Context, Object, Result, SimpleObject};
+
+#[derive(Default)]
+pub struct RewardsQueryRoot;
+
+/// Delegate's rewards of an epoch's canonical blocks & the delegators'
+/// pro-rata payouts (nanomina)
+#[derive(SimpleObject)]
+pub struct Rewards {
+    epoch: u32,
+    delegate: String,
+
+    /// Number of canonical blocks produced
+    num_blocks: u32,
+
+    /// Number of supercharged coinbases
+    num_supercharged: u32,
+
+    /// Coinbase, less the supercharged halves
+    coinbase: u64,
+
+    /// Supercharged halves of the supercharged coinbases
+    supercharged_coinbase: u64,
+
+    tx_fees: u64,
+    snark_fees: u64,
+
+    /// Coinbase & transaction fees, less SNARK fees
+    total_income: u64,
+
+    /// Delegate's total delegated stake
+    total_delegated: u64,
+
+    blocks: Vec<RewardsBlock>,
+
+    /// Delegators' payouts, by descending balance
+    delegators: Vec<RewardsPayout>,
+}
+
+#[derive(SimpleObject)]
+pub struct RewardsBlock {
+    state_hash: String,
+    block_height: u32,
+    global_slot: u32,
+    coinbase: u64,
+    supercharged: bool,
+    tx_fees: u64,
+    snark_fees: u64,
+}
+
+#[derive(SimpleObject)]
+pub struct RewardsPayout {
+    public_key: String,
+    balance: u64,
+
+    /// Fraction of the delegate's total delegated stake
+    share: f64,
+
+    /// Total payout, including the supercharged payout
+    payout: u64,
+
+    /// Share of the supercharged coinbases, only paid to delegators unlocked
+    /// at the supercharged block
+    supercharged_payout: u64,
+}
+
+#[Object]
+impl RewardsQueryRoot {
+    /// Block production, income & per-delegator payouts of `delegate` in
+    /// `epoch`, split pro rata by the epoch's staking ledger balances
+    async fn rewards<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        epoch: u32,
+        delegate: String,
+    ) -> Result<Option<Rewards>> {
+        if !PublicKey::is_valid(&delegate) {
+            return Err(format!("Invalid public key: {delegate}").into());
+        }
+
+        let pk: PublicKey = delegate.into();
+        let rewards = db(ctx)
+            .get_epoch_rewards(epoch, &pk, None)
+            .map_err(|e| store_error("rewards", &pk, e))?;
+
+        Ok(Some(rewards.into()))
+    }
+}
+
+impl From<EpochRewards> for Rewards {
+    fn from(rewards: EpochRewards) -> Self {
+        Self {
+            epoch: rewards.epoch,
+            delegate: rewards.delegate.0,
+            num_blocks: rewards.num_blocks,
+            num_supercharged: rewards.num_supercharged,
+            coinbase: rewards.coinbase,
+            supercharged_coinbase: rewards.supercharged_coinbase,
+            tx_fees: rewards.tx_fees,
+            snark_fees: rewards.snark_fees,
+            total_income: rewards.total_income,
+            total_delegated: rewards.total_delegated,
+            blocks: rewards.blocks.into_iter().map(Into::into).collect(),
+            delegators: rewards.delegators.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<BlockReward> for RewardsBlock {
+    fn from(block: BlockReward) -> Self {
+        Self {
+            state_hash: block.state_hash.0,
+            block_height: block.blockchain_length,
+            global_slot: block.global_slot,
+            coinbase: block.coinbase,
+            supercharged: block.supercharged,
+            tx_fees: block.tx_fees,
+            snark_fees: block.snark_fees,
+        }
+    }
+}
+
+impl From<DelegatorPayout> for RewardsPayout {
+    fn from(payout: DelegatorPayout) -> Self {
+        Self {
+            public_key: payout.pk.0,
+            balance: payout.balance,
+            share: payout.share,
+            payout: payout.payout,
+            supercharged_payout: payout.supercharged_payout,
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Snapshot-consistent GraphQL requests
+//!
+//! A request may pin all of its resolvers to a single consistent store view
+//! (see [crate::store::snapshot]) with the root `snapshot` field's arguments,
+//!
+//! ```graphql
+//! query ($atStateHash: String) {
+//!   snapshot(atStateHash: $atStateHash) { stateHash }
+//!   blocks(limit: 10) { stateHash }
+//! }
+//! ```
+//!
+//! or the same keys in its `extensions`:
+//!
+//! - `atBestTip: true` pins the current best tip
+//! - `atStateHash: "3N..."` pins a recently pinned best tip, or the current
+//!   one
+//!
+//! Pinned views bypass the store's in-memory caches, e.g. the canonical hash
+//! cache, so every read comes from the snapshot. The pinned best tip is
+//! returned by the `snapshot` field & in the response's `pinnedStateHash`
+//! extension.
+
+use crate::{base::state_hash::StateHash, store::IndexerStore};
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest, NextRequest},
+    parser::{
+        parse_query,
+        types::{DocumentOperations, OperationDefinition, Selection},
+    },
+    Context, Name, Object, Request, Response, ServerError, ServerResult, SimpleObject, Value,
+};
+use std::{
+    any::TypeId,
+    convert::Infallible,
+    sync::{Arc, Mutex},
+};
+
+pub const AT_BEST_TIP_EXTENSION: &str = "atBestTip";
+pub const AT_STATE_HASH_EXTENSION: &str = "atStateHash";
+pub const PINNED_STATE_HASH_EXTENSION: &str = "pinnedStateHash";
+
+/// Root field pinning the request
+pub const SNAPSHOT_FIELD: &str = "snapshot";
+
+#[derive(Default)]
+pub struct SnapshotQueryRoot;
+
+/// Store view the request is pinned to
+#[derive(SimpleObject)]
+pub struct Snapshot {
+    /// Best tip of the pinned view
+    pub state_hash: String,
+}
+
+/// Best tip the request is pinned to
+struct PinnedStateHash(StateHash);
+
+/// Pins requests to the store view selected by their extensions
+pub(crate) struct SnapshotPinning;
+
+#[derive(Default)]
+struct SnapshotPinningExtension {
+    pinned_state_hash: Mutex<Option<StateHash>>,
+}
+
+#[Object]
+impl SnapshotQueryRoot {
+    /// Pins all of the request's resolvers to a single consistent store view
+    /// of the current best tip (`atBestTip: true`), or of a recently pinned
+    /// best tip (`atStateHash`). Returns the pinned best tip
+    async fn snapshot(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "atBestTip")] _at_best_tip: Option<bool>,
+        #[graphql(name = "atStateHash")] _at_state_hash: Option<String>,
+    ) -> Option<Snapshot> {
+        // the arguments are read when preparing the request, see
+        // [SnapshotPinning]
+        ctx.data_opt::<PinnedStateHash>().map(|pinned| Snapshot {
+            state_hash: pinned.0 .0.clone(),
+        })
+    }
+}
+
+impl ExtensionFactory for SnapshotPinning {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(SnapshotPinningExtension::default())
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for SnapshotPinningExtension {
+    async fn request(&self, ctx: &ExtensionContext<'_>, next: NextRequest<'_>) -> Response {
+        let mut response = next.run(ctx).await;
+        if let Some(state_hash) = self
+            .pinned_state_hash
+            .lock()
+            .expect("pinned state hash lock")
+            .take()
+        {
+            response.extensions.insert(
+                PINNED_STATE_HASH_EXTENSION.to_string(),
+                Value::String(state_hash.0),
+            );
+        }
+
+        response
+    }
+
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        // root field arguments take precedence over the extensions
+        let arguments = snapshot_field_arguments(&request);
+        let pin_value = |key: &str| {
+            arguments
+                .iter()
+                .find(|(name, _)| name.as_str() == key)
+                .map(|(_, value)| value)
+                .filter(|value| **value != Value::Null)
+                .or_else(|| request.extensions.0.get(key))
+        };
+
+        let at_best_tip = match pin_value(AT_BEST_TIP_EXTENSION) {
+            None | Some(Value::Boolean(false)) => false,
+            Some(Value::Boolean(true)) => true,
+            Some(_) => return Err(pinning_error("atBestTip must be a boolean")),
+        };
+        let at_state_hash = match pin_value(AT_STATE_HASH_EXTENSION) {
+            None => None,
+            Some(Value::String(state_hash)) if StateHash::is_valid(state_hash) => {
+                Some(StateHash::from(state_hash.as_str()))
+            }
+            Some(value) => return Err(pinning_error(format!("Invalid atStateHash: {value}"))),
+        };
+
+        if !at_best_tip && at_state_hash.is_none() {
+            return next.run(ctx, request).await;
+        }
+
+        // the request's store (e.g. a network's) takes precedence
+        let store = request
+            .data
+            .get(&TypeId::of::<Arc<IndexerStore>>())
+            .and_then(|store| store.downcast_ref::<Arc<IndexerStore>>())
+            .map_or_else(|| ctx.data::<Arc<IndexerStore>>(), Ok)
+            .map_err(|e| pinning_error(e.message))?;
+        let (state_hash, pinned) = match at_state_hash {
+            Some(state_hash) => store
+                .pinned_at(&state_hash)
+                .map(|pinned| (state_hash, pinned)),
+            None => store.pin(),
+        }
+        .map_err(|e| pinning_error(e.to_string()))?;
+
+        *self
+            .pinned_state_hash
+            .lock()
+            .expect("pinned state hash lock") = Some(state_hash.clone());
+        next.run(ctx, request.data(pinned).data(PinnedStateHash(state_hash)))
+            .await
+    }
+}
+
+/// Arguments of the executed operation's root `snapshot` field, with their
+/// variables substituted
+fn snapshot_field_arguments(request: &Request) -> Vec<(Name, Value)> {
+    let Ok(document) = parse_query(&request.query) else {
+        return vec![];
+    };
+
+    let operation = match (&document.operations, request.operation_name.as_deref()) {
+        (DocumentOperations::Single(operation), _) => &operation.node,
+        (DocumentOperations::Multiple(operations), Some(name)) => {
+            match operations
+                .iter()
+                .find(|(op_name, _)| op_name.as_str() == name)
+            {
+                Some((_, operation)) => &operation.node,
+                None => return vec![],
+            }
+        }
+        (DocumentOperations::Multiple(_), None) => return vec![],
+    };
+
+    let Some(field) = operation
+        .selection_set
+        .node
+        .items
+        .iter()
+        .find_map(|selection| match &selection.node {
+            Selection::Field(field) if field.node.name.node == SNAPSHOT_FIELD => Some(&field.node),
+            _ => None,
+        })
+    else {
+        return vec![];
+    };
+
+    field
+        .arguments
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .node
+                .clone()
+                .into_const_with(|variable| {
+                    Ok::<_, Infallible>(variable_value(request, operation, &variable))
+                })
+                .unwrap_or(Value::Null);
+            (name.node.clone(), value)
+        })
+        .collect()
+}
+
+/// Value of the operation's variable, or its default
+fn variable_value(request: &Request, operation: &OperationDefinition, variable: &Name) -> Value {
+    request
+        .variables
+        .get(variable)
+        .cloned()
+        .or_else(|| {
+            operation
+                .variable_definitions
+                .iter()
+                .find(|definition| definition.node.name.node == *variable)
+                .and_then(|definition| definition.node.default_value.as_ref())
+                .map(|value| value.node.clone())
+        })
+        .unwrap_or(Value::Null)
+}
+
+fn pinning_error(message: impl Into<String>) -> ServerError {
+    ServerError::new(message, None)
+}
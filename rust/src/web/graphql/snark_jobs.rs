@@ -0,0 +1,138 @@
+use super::{db, max_limit};
+use crate::{
+    base::public_key::PublicKey,
+    block::store::BlockStore,
+    snark_work::store::SnarkStore,
+    store::username::UsernameStore,
+    utility::store::common::{u32_from_be_bytes, U32_LEN, U64_LEN},
+};
+use async_graphql::{Context, Object, Result, SimpleObject};
+use speedb::Direction;
+
+#[derive(Default)]
+pub struct SnarkJobsQueryRoot;
+
+/// Best chain SNARK jobs & fees of a prover in an epoch
+#[derive(SimpleObject)]
+pub struct SnarkProver {
+    public_key: String,
+    username: Option<String>,
+    epoch: u32,
+
+    /// Number of SNARK jobs sold
+    job_count: u32,
+
+    /// Fees earned from the SNARK jobs sold
+    total_fees: u64,
+}
+
+/// Best chain SNARK jobs of a prover in a block height range
+#[derive(SimpleObject)]
+pub struct SnarkProverJobs {
+    prover: String,
+    from_height: u32,
+    to_height: u32,
+
+    /// Number of SNARK jobs sold
+    job_count: u32,
+
+    /// Fees earned from the SNARK jobs sold
+    total_fees: u64,
+    jobs: Vec<SnarkJob>,
+}
+
+/// SNARK job sold in a best chain block
+#[derive(SimpleObject)]
+pub struct SnarkJob {
+    block_height: u32,
+    state_hash: String,
+
+    /// Index of the job in the block's SNARK work
+    index: u32,
+    fee: u64,
+}
+
+#[Object]
+impl SnarkJobsQueryRoot {
+    /// SNARK provers of `epoch` (defaults to the current epoch) by best chain
+    /// job fees, highest first
+    async fn top_snark_provers<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        epoch: Option<u32>,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<Vec<SnarkProver>> {
+        let limit = max_limit(ctx, limit);
+        let db = db(ctx);
+        let epoch = match epoch {
+            Some(epoch) => epoch,
+            None => db.get_current_epoch()?,
+        };
+
+        let mut provers = vec![];
+        for (key, _) in db
+            .snark_prover_jobs_epoch_iterator(epoch, Direction::Reverse)
+            .flatten()
+        {
+            if provers.len() >= limit || u32_from_be_bytes(&key[..U32_LEN])? != epoch {
+                break;
+            }
+
+            let pk = PublicKey::from_bytes(&key[U32_LEN..][U64_LEN..])?;
+            let jobs = db.get_snark_prover_epoch_jobs(&pk, epoch)?;
+            provers.push(SnarkProver {
+                username: db.get_username(&pk)?.map(|username| username.0),
+                public_key: pk.0,
+                epoch,
+                job_count: jobs.num_jobs,
+                total_fees: jobs.total_fees,
+            });
+        }
+
+        Ok(provers)
+    }
+
+    /// Best chain SNARK jobs of `prover` in blocks `fromHeight` to `toHeight`
+    /// (inclusive, defaults to the best tip), sorted by block height
+    async fn snark_jobs<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        prover: String,
+        #[graphql(default = 0)] from_height: u32,
+        to_height: Option<u32>,
+    ) -> Result<Option<SnarkProverJobs>> {
+        if !PublicKey::is_valid(&prover) {
+            return Err(format!("Invalid public key: {prover}").into());
+        }
+
+        let db = db(ctx);
+        let to_height = match to_height {
+            Some(to_height) => to_height,
+            None => db.get_best_block_height()?.unwrap_or_default(),
+        };
+        if from_height > to_height {
+            return Err(format!("Invalid height range {from_height} to {to_height}").into());
+        }
+
+        let pk: PublicKey = prover.into();
+        let jobs: Vec<_> = db
+            .get_snark_prover_jobs(&pk, from_height, to_height)?
+            .into_iter()
+            .map(|job| SnarkJob {
+                block_height: job.block_height,
+                state_hash: job.state_hash.0,
+                index: job.index,
+                fee: job.fee,
+            })
+            .collect();
+
+        Ok(Some(SnarkProverJobs {
+            prover: pk.0,
+            from_height,
+            to_height,
+            job_count: jobs.len() as u32,
+            total_fees: jobs.iter().map(|job| job.fee).sum(),
+            jobs,
+        }))
+    }
+}
@@ -1,4 +1,4 @@
-use super::{db, gen::BlockQueryInput, get_block, get_block_canonicity};
+use super::{db, gen::BlockQueryInput, get_block, get_block_canonicity, max_limit};
 use crate::{
     base::{public_key::PublicKey, state_hash::StateHash},
     block::{precomputed::PrecomputedBlock, store::BlockStore},
@@ -100,7 +100,8 @@ impl SnarkQueryRoot {
         query: Option<SnarkQueryInput>,
         sort_by: Option<SnarkSortByInput>,
         #[graphql(default = 100)] limit: usize,
-    ) -> Result<Vec<SnarkWithCanonicity>> {
+    ) -> Result<Option<Vec<SnarkWithCanonicity>>> {
+        let limit = max_limit(ctx, limit);
         let db = db(ctx);
         let mut snarks = <Vec<SnarkWithCanonicity>>::new();
         let sort_by = sort_by.unwrap_or(SnarkSortByInput::BlockHeightDesc);
@@ -136,22 +137,23 @@ impl SnarkQueryRoot {
             }
 
             snarks.truncate(limit);
-            return Ok(snarks);
+            return Ok(Some(snarks));
         }
 
         // block height
         if let Some(block_height) = query.as_ref().and_then(|q| q.block_height) {
-            let mut snarks: Vec<SnarkWithCanonicity> = db
-                .get_blocks_at_height(block_height)?
-                .iter()
-                .flat_map(|state_hash| {
-                    let block = get_block(db, state_hash);
+            let mut snarks: Vec<SnarkWithCanonicity> = vec![];
+            for state_hash in db.get_blocks_at_height(block_height)?.iter() {
+                let Some(block) = get_block(db, state_hash)? else {
+                    continue;
+                };
+
+                snarks.extend(
                     SnarkWorkSummaryWithStateHash::from_precomputed(&block)
                         .into_iter()
-                        .filter_map(|s| snark_summary_matches_query(db, &query, s).ok().flatten())
-                        .collect::<Vec<SnarkWithCanonicity>>()
-                })
-                .collect();
+                        .filter_map(|s| snark_summary_matches_query(db, &query, s).ok().flatten()),
+                );
+            }
 
             match sort_by {
                 SnarkSortByInput::BlockHeightAsc => snarks.reverse(),
@@ -159,7 +161,7 @@ impl SnarkQueryRoot {
             }
 
             snarks.truncate(limit);
-            return Ok(snarks);
+            return Ok(Some(snarks));
         }
         // prover query filter and sort by height
         if let (Some(prover), Some(block_height_lte)) = (
@@ -195,7 +197,9 @@ impl SnarkQueryRoot {
                         }
                     }
 
-                    let pcb = get_block(db, &state_hash);
+                    let Some(pcb) = get_block(db, &state_hash)? else {
+                        continue;
+                    };
                     let snark = serde_json::from_slice(&snark)?;
                     let sw = SnarkWithCanonicity {
                         canonical,
@@ -216,7 +220,7 @@ impl SnarkQueryRoot {
                     }
                 }
             }
-            return Ok(snarks);
+            return Ok(Some(snarks));
         }
 
         // prover query
@@ -251,7 +255,9 @@ impl SnarkQueryRoot {
                         }
                     }
 
-                    let pcb = get_block(db, &state_hash);
+                    let Some(pcb) = get_block(db, &state_hash)? else {
+                        continue;
+                    };
                     let snark = serde_json::from_slice(&snark)?;
                     let sw = SnarkWithCanonicity {
                         canonical,
@@ -272,7 +278,7 @@ impl SnarkQueryRoot {
                     }
                 }
             }
-            return Ok(snarks);
+            return Ok(Some(snarks));
         }
 
         // block height bounded query
@@ -321,7 +327,9 @@ impl SnarkQueryRoot {
                         }
                     }
 
-                    let block = get_block(db, &state_hash);
+                    let Some(block) = get_block(db, &state_hash)? else {
+                        continue;
+                    };
                     let snark_work = db.get_block_snark_work(&state_hash)?;
                     let snarks_with_canonicity = snark_work.map_or(vec![], |summaries| {
                         summaries
@@ -351,7 +359,7 @@ impl SnarkQueryRoot {
                     }
                 }
             }
-            return Ok(snarks);
+            return Ok(Some(snarks));
         }
 
         // general query
@@ -371,13 +379,17 @@ impl SnarkQueryRoot {
                 }
             }
 
+            let Some(pcb) = get_block(db, &state_hash)? else {
+                continue;
+            };
+
             let snark_work = db.get_block_snark_work(&state_hash)?;
             let snarks_with_canonicity = snark_work.map_or(vec![], |summaries| {
                 summaries
                     .into_iter()
                     .map(|snark| SnarkWithCanonicity {
                         canonical,
-                        pcb: get_block(db, &state_hash),
+                        pcb: pcb.clone(),
                         snark: (
                             snark,
                             state_hash.clone(),
@@ -399,7 +411,7 @@ impl SnarkQueryRoot {
                 }
             }
         }
-        Ok(snarks)
+        Ok(Some(snarks))
     }
 }
 
@@ -408,9 +420,13 @@ fn snark_summary_matches_query(
     query: &Option<SnarkQueryInput>,
     snark: SnarkWorkSummaryWithStateHash,
 ) -> anyhow::Result<Option<SnarkWithCanonicity>> {
+    let Some(pcb) = get_block(db, &snark.state_hash)? else {
+        return Ok(None);
+    };
+
     let canonical = get_block_canonicity(db, &snark.state_hash);
     let snark_with_canonicity = SnarkWithCanonicity {
-        pcb: get_block(db, &snark.state_hash),
+        pcb,
         canonical,
         snark: (
             snark,
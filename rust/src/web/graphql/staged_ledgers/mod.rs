@@ -1,5 +1,6 @@
-use super::{db, MAINNET_ACCOUNT_CREATION_FEE};
+use super::{canonical_block_at, db, max_limit, DateTime, MAINNET_ACCOUNT_CREATION_FEE};
 use crate::{
+    block::store::BlockStore,
     canonicity::store::CanonicityStore,
     ledger::{account::Account, store::staged::StagedLedgerStore, token::TokenAddress},
 };
@@ -15,6 +16,16 @@ pub struct StagedLedgerQueryInput {
 
     #[graphql(name = "blockchain_length")]
     blockchain_length: Option<u32>,
+
+    /// Canonical block at the date time, converted to its blockchain length
+    at_date_time: Option<DateTime>,
+
+    /// Highest canonical block at or before the global slot, converted to
+    /// its blockchain length
+    global_slot: Option<u32>,
+
+    /// Only these accounts of the built staged ledger
+    public_keys: Option<Vec<String>>,
 }
 
 #[derive(Enum, Copy, Clone, Eq, PartialEq)]
@@ -40,6 +51,7 @@ impl StagedLedgerQueryRoot {
         sort_by: Option<StagedLedgerSortByInput>,
         #[graphql(default = 100)] limit: usize,
     ) -> Result<Option<Vec<StagedLedgerAccount>>> {
+        let limit = max_limit(ctx, limit);
         let db = db(ctx);
         let token = query
             .as_ref()
@@ -48,6 +60,26 @@ impl StagedLedgerQueryRoot {
                 None => TokenAddress::default(),
             });
 
+        // canonical block height, directly or at the given date time or slot
+        let blockchain_length = match query.as_ref() {
+            Some(StagedLedgerQueryInput {
+                blockchain_length: Some(blockchain_length),
+                ..
+            }) => Some(*blockchain_length),
+            Some(StagedLedgerQueryInput {
+                at_date_time: Some(date_time),
+                ..
+            }) => Some(canonical_block_at(db, date_time)?.0),
+            Some(StagedLedgerQueryInput {
+                global_slot: Some(global_slot),
+                ..
+            }) => match db.get_canonical_hash_at_or_before_slot(*global_slot)? {
+                Some(state_hash) => db.get_block_height(&state_hash)?,
+                None => return Ok(None),
+            },
+            _ => None,
+        };
+
         // pk staged account query
         if let Some(pk) = query.as_ref().and_then(|q| q.public_key.clone()) {
             if let Some(state_hash) = query.as_ref().and_then(|q| q.state_hash.clone()) {
@@ -62,7 +94,7 @@ impl StagedLedgerQueryRoot {
                         .get_staged_account(&pk.into(), &token, &state_hash)?
                         .map(|acct| vec![acct.into()]));
                 }
-            } else if let Some(block_height) = query.as_ref().and_then(|q| q.blockchain_length) {
+            } else if let Some(block_height) = blockchain_length {
                 if let Some(state_hash) = db.get_canonical_hash_at_height(block_height)? {
                     return Ok(db
                         .get_staged_account(&pk.into(), &token, &state_hash)?
@@ -75,13 +107,13 @@ impl StagedLedgerQueryRoot {
         // otherwise build the staged ledger from
         // - block state hash
         // - staged ledger hash
-        // - canonical block height
+        // - canonical block height (or date time)
         let staged_ledger =
             if let Some(state_hash) = query.as_ref().and_then(|q| q.state_hash.clone()) {
                 db.get_staged_ledger_at_state_hash(&state_hash.into(), false)?
             } else if let Some(ledger_hash) = query.as_ref().and_then(|q| q.ledger_hash.clone()) {
                 db.get_staged_ledger_at_ledger_hash(&ledger_hash.into(), false)?
-            } else if let Some(block_height) = query.as_ref().and_then(|q| q.blockchain_length) {
+            } else if let Some(block_height) = blockchain_length {
                 db.get_staged_ledger_at_block_height(block_height, false)?
             } else {
                 return Ok(None);
@@ -102,6 +134,10 @@ impl StagedLedgerQueryRoot {
                 .expect("MINA token ledger")
         });
 
+        if let Some(pks) = query.as_ref().and_then(|q| q.public_keys.as_ref()) {
+            accounts.retain(|account| pks.contains(&account.public_key));
+        }
+
         reorder(&mut accounts, sort_by);
         accounts.truncate(limit);
         Ok(Some(accounts))
@@ -1,16 +1,23 @@
-use super::db;
+use super::{
+    db,
+    error::{not_found, StoreResultExt},
+    max_limit,
+};
 use crate::{
+    base::{public_key::PublicKey, state_hash::StateHash},
     block::store::BlockStore,
-    chain::store::ChainStore,
+    chain::{store::ChainStore, GenesisEra},
     command::{internal::store::InternalCommandStore, store::UserCommandStore},
-    constants::MAINNET_GENESIS_HASH,
+    constants::STAKES_QUERY_MAX_SCAN,
     ledger::{
-        staking::{EpochStakeDelegation, StakingAccount},
+        staking::{EpochDelegator, EpochStakeDelegation, StakingAccount},
         store::staking::{StakingAccountWithEpochDelegation, StakingLedgerStore},
+        token::TokenId,
+        LedgerHash,
     },
     snark_work::store::SnarkStore,
     store::{username::UsernameStore, IndexerStore},
-    utility::store::common::U32_LEN,
+    utility::store::{common::U32_LEN, ledger::staking::split_staking_ledger_delegate_sort_key},
     web::graphql::Timing,
 };
 use async_graphql::{ComplexObject, Context, Enum, InputObject, Object, Result, SimpleObject};
@@ -30,6 +37,10 @@ pub struct StakeQueryInput {
     #[graphql(name = "public_key")]
     public_key: Option<String>,
     username: Option<String>,
+
+    /// Genesis state hash of the era to search (default: all eras)
+    #[graphql(name = "genesis_state_hash")]
+    genesis_state_hash: Option<String>,
 }
 
 #[derive(Enum, Copy, Clone, Eq, PartialEq)]
@@ -50,6 +61,11 @@ pub struct StakeQueryRoot;
 
 #[Object]
 impl StakeQueryRoot {
+    /// Staking ledger accounts of an epoch, with their aggregated delegations
+    ///
+    /// Without a genesis state hash (or ledger hash), the epoch is searched in
+    /// all genesis eras, most recent first, with results tagged by era. Pages
+    /// are selected with `offset` & `limit`
     // Cache for 1 day
     #[graphql(cache_control(max_age = 86400))]
     async fn stakes<'ctx>(
@@ -58,8 +74,11 @@ impl StakeQueryRoot {
         query: Option<StakeQueryInput>,
         sort_by: Option<StakeSortByInput>,
         #[graphql(default = 100)] limit: usize,
-    ) -> Result<Vec<StakesLedgerAccountWithMeta>> {
+        #[graphql(default = 0)] offset: usize,
+    ) -> Result<Option<Vec<StakesLedgerAccountWithMeta>>> {
+        let limit = max_limit(ctx, limit);
         let db = db(ctx);
+        let registry = db.get_genesis_registry()?;
 
         // default to current epoch
         let curr_epoch = db.get_current_epoch()?;
@@ -71,91 +90,365 @@ impl StakeQueryRoot {
         // short-circuited epoch number query
         if limit == 0 {
             if let Some(ledger_hash) = query.as_ref().and_then(|q| q.ledger_hash.clone()) {
-                return match db.get_epoch(&ledger_hash.clone().into())? {
-                    Some(epoch) => Ok(vec![StakesLedgerAccountWithMeta {
-                        epoch,
-                        ledger_hash,
-                        ..Default::default()
-                    }]),
-                    None => Ok(vec![]),
+                let ledger_hash_key: LedgerHash = ledger_hash.clone().into();
+                return match db.get_epoch(&ledger_hash_key)? {
+                    Some(epoch) => {
+                        let genesis_state_hash = db
+                            .get_genesis_state_hash(&ledger_hash_key)?
+                            .unwrap_or_default();
+                        let era = registry.era(&genesis_state_hash);
+                        Ok(Some(vec![StakesLedgerAccountWithMeta {
+                            epoch,
+                            ledger_hash,
+                            era: era.map(|era| era.era).unwrap_or_default(),
+                            flat_epoch: registry.flat_epoch(&genesis_state_hash, epoch),
+                            genesis_state_hash: genesis_state_hash.0,
+                            ..Default::default()
+                        }]))
+                    }
+                    None => Ok(Some(vec![])),
                 };
             }
         }
 
+        // the requested era, the ledger hash's era, or all eras
+        let genesis_state_hash = match query.as_ref() {
+            Some(StakeQueryInput {
+                genesis_state_hash: Some(genesis_state_hash),
+                ..
+            }) => Some(StateHash::from(genesis_state_hash.as_str())),
+            Some(StakeQueryInput {
+                ledger_hash: Some(ledger_hash),
+                ..
+            }) => db.get_genesis_state_hash(&ledger_hash.clone().into())?,
+            _ => None,
+        };
+
         // if ledger hash is provided as a query input, use it for the ledger
         // otherwise, use the provided or current epoch number
-        let (ledger_hash, epoch) = match query.as_ref().map(|q| (q.ledger_hash.clone(), q.epoch)) {
-            Some((Some(ledger_hash), Some(query_epoch))) => (ledger_hash, query_epoch),
-            Some((Some(ledger_hash), None)) => (
-                ledger_hash.clone(),
-                db.get_epoch(&ledger_hash.clone().into())?
-                    .unwrap_or_default(),
-            ),
-            Some((None, Some(query_epoch))) => (
-                db.get_staking_ledger_hash_by_epoch(query_epoch, None)?
-                    .unwrap_or_default()
-                    .0,
-                query_epoch,
-            ),
-            Some((None, None)) | None => (
-                db.get_staking_ledger_hash_by_epoch(epoch, None)?
-                    .unwrap_or_default()
-                    .0,
-                epoch,
-            ),
-        };
-        let total_currency = db
-            .get_total_currency(&ledger_hash.clone().into())?
-            .unwrap_or_default();
-
-        // balance/stake-sorted queries
-        let mut accounts = Vec::new();
-        let iter = match sort_by {
-            Some(StakeSortByInput::StakeDesc) | None => {
-                db.staking_ledger_account_stake_iterator(epoch, Direction::Reverse)
-            }
-            Some(StakeSortByInput::StakeAsc) => {
-                db.staking_ledger_account_stake_iterator(epoch, Direction::Forward)
-            }
-            Some(StakeSortByInput::BalanceDesc) => {
-                db.staking_ledger_account_balance_iterator(epoch, Direction::Reverse)
-            }
-            Some(StakeSortByInput::BalanceAsc) => {
-                db.staking_ledger_account_balance_iterator(epoch, Direction::Forward)
+        let mut era_ledgers = vec![];
+        for era in registry.eras_desc().filter(|era| {
+            genesis_state_hash
+                .as_ref()
+                .map_or(true, |genesis_state_hash| {
+                    *genesis_state_hash == era.genesis_state_hash
+                })
+        }) {
+            let genesis_state_hash = Some(&era.genesis_state_hash);
+            let ledger = match query.as_ref().map(|q| (q.ledger_hash.clone(), q.epoch)) {
+                Some((Some(ledger_hash), Some(query_epoch))) => Some((ledger_hash, query_epoch)),
+                Some((Some(ledger_hash), None)) => Some((
+                    ledger_hash.clone(),
+                    db.get_epoch(&ledger_hash.clone().into())?
+                        .unwrap_or_default(),
+                )),
+                Some((None, _)) | None => db
+                    .get_staking_ledger_hash_by_epoch(epoch, genesis_state_hash)?
+                    .map(|ledger_hash| (ledger_hash.0, epoch)),
+            };
+
+            if let Some((ledger_hash, epoch)) = ledger {
+                era_ledgers.push((era, ledger_hash, epoch));
             }
-        };
 
-        for (key, value) in iter.flatten() {
-            if key[..U32_LEN] != epoch.to_be_bytes() || accounts.len() >= limit {
-                // no longer the desired staking ledger
+            // a ledger hash belongs to a single era
+            if query.as_ref().is_some_and(|q| q.ledger_hash.is_some()) {
                 break;
             }
+        }
 
-            let StakingAccountWithEpochDelegation {
-                account,
-                delegation,
-            } = serde_json::from_slice(&value)?;
-            if StakeQueryInput::matches_staking_account(
+        if era_ledgers.is_empty() {
+            return Err(not_found("staking ledger", epoch));
+        }
+
+        let mut accounts = vec![];
+        let mut offset = offset;
+        for (era, ledger_hash, epoch) in era_ledgers {
+            let limit = limit.saturating_sub(accounts.len());
+            accounts.append(&mut era_stakes(
+                db,
                 query.as_ref(),
-                &account,
-                &ledger_hash,
+                sort_by,
+                era,
                 epoch,
+                ledger_hash,
+                limit,
+                &mut offset,
+            )?);
+        }
+
+        Ok(Some(accounts))
+    }
+
+    /// All of a delegate's delegators in an epoch with their share of the
+    /// delegate's total stake
+    // Cache for 1 day
+    #[graphql(cache_control(max_age = 86400))]
+    async fn delegators<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        delegate: String,
+        epoch: Option<u32>,
+        sort_by: Option<StakeSortByInput>,
+        #[graphql(default = 100)] limit: usize,
+        #[graphql(default = 0)] offset: usize,
+        cursor: Option<String>,
+    ) -> Result<Option<Vec<StakesDelegator>>> {
+        let limit = max_limit(ctx, limit);
+        let db = db(ctx);
+
+        if !PublicKey::is_valid(&delegate) {
+            return Ok(Some(vec![]));
+        }
+
+        // default to current epoch
+        let epoch = match epoch {
+            Some(epoch) => epoch,
+            None => db.get_current_epoch()?,
+        };
+
+        // delegators are only known if the epoch's staking ledger is present
+        db.get_staking_ledger_hash_by_epoch(epoch, None)
+            .or_not_found("staking ledger", epoch)?;
+
+        Ok(Some(delegator_page(
+            db,
+            &delegate.into(),
+            epoch,
+            None,
+            sort_by,
+            limit,
+            offset,
+            cursor,
+        )?))
+    }
+}
+
+/// Page of the delegate's delegators, sorted by balance, starting after the
+/// `cursor` delegator's public key
+///
+/// Delegators contribute their balance to the delegate's stake
+#[allow(clippy::too_many_arguments)]
+fn delegator_page(
+    db: &Arc<IndexerStore>,
+    delegate: &PublicKey,
+    epoch: u32,
+    genesis_state_hash: Option<&StateHash>,
+    sort_by: Option<StakeSortByInput>,
+    limit: usize,
+    offset: usize,
+    cursor: Option<String>,
+) -> Result<Vec<StakesDelegator>> {
+    let cursor = match cursor {
+        Some(cursor) if PublicKey::is_valid(&cursor) => Some(PublicKey::from(cursor)),
+        Some(cursor) => return Err(format!("Invalid cursor: {cursor}").into()),
+        None => None,
+    };
+
+    let direction = match sort_by {
+        Some(StakeSortByInput::BalanceDesc | StakeSortByInput::StakeDesc) | None => {
+            Direction::Reverse
+        }
+        Some(StakeSortByInput::BalanceAsc | StakeSortByInput::StakeAsc) => Direction::Forward,
+    };
+
+    Ok(db
+        .get_epoch_delegators(
+            delegate,
+            epoch,
+            genesis_state_hash,
+            cursor.as_ref(),
+            direction,
+            limit.saturating_add(offset),
+        )?
+        .into_iter()
+        .skip(offset)
+        .map(|delegator| StakesDelegator::new(db, delegator))
+        .collect())
+}
+
+/// Stakes query results of a genesis era's staking ledger
+///
+/// The first `offset` matching accounts are skipped, `offset` is decremented
+/// by the number of skipped accounts
+#[allow(clippy::too_many_arguments)]
+fn era_stakes(
+    db: &Arc<IndexerStore>,
+    query: Option<&StakeQueryInput>,
+    sort_by: Option<StakeSortByInput>,
+    era: &GenesisEra,
+    epoch: u32,
+    ledger_hash: String,
+    limit: usize,
+    offset: &mut usize,
+) -> Result<Vec<StakesLedgerAccountWithMeta>> {
+    let genesis_state_hash = Some(&era.genesis_state_hash);
+    let flat_epoch = era.flat_epoch(epoch);
+    let total_currency = db
+        .get_total_currency(&ledger_hash.clone().into())?
+        .unwrap_or_default();
+
+    // public key point lookup
+    if let Some(pk) = query
+        .and_then(|q| q.public_key.as_ref())
+        .filter(|pk| PublicKey::is_valid(pk))
+    {
+        let pk = PublicKey::from(pk.as_str());
+        let (Some(account), Some(delegation)) = (
+            db.get_staking_account(&pk, epoch, genesis_state_hash)?,
+            db.get_epoch_delegations(&pk, epoch, genesis_state_hash)?,
+        ) else {
+            return Ok(vec![]);
+        };
+
+        if limit == 0
+            || !StakeQueryInput::matches_staking_account(query, &account, &ledger_hash, epoch)
+        {
+            return Ok(vec![]);
+        }
+
+        let account = StakesLedgerAccountWithMeta::new(
+            db,
+            account,
+            &delegation,
+            era,
+            epoch,
+            ledger_hash,
+            total_currency,
+        );
+        if !StakeQueryInput::matches(query, &account) {
+            return Ok(vec![]);
+        }
+
+        return Ok(if *offset > 0 {
+            *offset -= 1;
+            vec![]
+        } else {
+            vec![account]
+        });
+    }
+
+    // balance/stake-sorted queries stream the epoch's sort index,
+    // applying the filters while scanning a bounded number of entries
+    let mut page = StakesPage {
+        db,
+        query,
+        era,
+        epoch,
+        ledger_hash,
+        total_currency,
+        limit,
+        offset: *offset,
+        accounts: vec![],
+    };
+    let direction = match sort_by {
+        Some(StakeSortByInput::BalanceDesc | StakeSortByInput::StakeDesc) | None => {
+            Direction::Reverse
+        }
+        Some(StakeSortByInput::BalanceAsc | StakeSortByInput::StakeAsc) => Direction::Forward,
+    };
+    let delegate = query
+        .and_then(|q| q.delegate.as_ref())
+        .filter(|pk| PublicKey::is_valid(pk))
+        .map(|pk| PublicKey::from(pk.as_str()));
+
+    // delegate-centric balance queries only scan the delegate's delegators
+    if let (Some(StakeSortByInput::BalanceDesc | StakeSortByInput::BalanceAsc), Some(delegate)) =
+        (sort_by, delegate.as_ref())
+    {
+        for (key, _) in db
+            .staking_ledger_delegate_balance_iterator(flat_epoch, delegate, direction)
+            .flatten()
+            .take(STAKES_QUERY_MAX_SCAN)
+        {
+            let (key_epoch, key_delegate, _, pk) = split_staking_ledger_delegate_sort_key(&key)?;
+            if key_epoch != flat_epoch || key_delegate != *delegate || page.is_full() {
+                // no longer the delegate of interest
+                break;
+            }
+
+            if let (Some(account), Some(delegation)) = (
+                db.get_staking_account(&pk, epoch, genesis_state_hash)?,
+                db.get_epoch_delegations(&pk, epoch, genesis_state_hash)?,
             ) {
-                let account = StakesLedgerAccountWithMeta::new(
-                    db,
-                    account,
-                    &delegation,
-                    epoch,
-                    ledger_hash.clone(),
-                    total_currency,
-                );
-                if StakeQueryInput::matches(query.as_ref(), &account) {
-                    accounts.push(account);
-                }
+                page.push(account, delegation);
             }
         }
-        Ok(accounts)
+
+        *offset = page.offset;
+        return Ok(page.accounts);
     }
+
+    let iter = match sort_by {
+        Some(StakeSortByInput::StakeDesc | StakeSortByInput::StakeAsc) | None => {
+            db.staking_ledger_account_stake_iterator(flat_epoch, direction)
+        }
+        Some(StakeSortByInput::BalanceDesc | StakeSortByInput::BalanceAsc) => {
+            db.staking_ledger_account_balance_iterator(flat_epoch, direction)
+        }
+    };
+
+    for (key, value) in iter.flatten().take(STAKES_QUERY_MAX_SCAN) {
+        if key[..U32_LEN] != flat_epoch.to_be_bytes() || page.is_full() {
+            // no longer the desired staking ledger
+            break;
+        }
+
+        let StakingAccountWithEpochDelegation {
+            account,
+            delegation,
+        } = serde_json::from_slice(&value)?;
+        page.push(account, delegation);
+    }
+
+    *offset = page.offset;
+    Ok(page.accounts)
+}
+
+/// Page of stakes query results, filled while streaming a sort index
+struct StakesPage<'a> {
+    db: &'a Arc<IndexerStore>,
+    query: Option<&'a StakeQueryInput>,
+    era: &'a GenesisEra,
+    epoch: u32,
+    ledger_hash: String,
+    total_currency: u64,
+    limit: usize,
+
+    /// Number of matching accounts left to skip
+    offset: usize,
+    accounts: Vec<StakesLedgerAccountWithMeta>,
+}
+
+#[derive(SimpleObject)]
+pub struct StakesDelegator {
+    /// Value epoch
+    epoch: u32,
+
+    /// Value public key
+    #[graphql(name = "public_key")]
+    public_key: String,
+
+    /// Value username
+    username: Option<String>,
+
+    /// Value delegate
+    delegate: String,
+
+    /// Value balance
+    balance: f64,
+
+    /// Value balance nanomina
+    balance_nanomina: u64,
+
+    /// Value percentage of the delegate's total delegated stake
+    stake_percentage: String,
+
+    /// Value delegate in the previous epoch
+    prev_delegate: Option<String>,
+
+    /// Value delegation changed since the previous epoch
+    delegation_changed: bool,
 }
 
 #[derive(SimpleObject, Default)]
@@ -163,6 +456,17 @@ pub struct StakesLedgerAccountWithMeta {
     /// Value current epoch
     epoch: u32,
 
+    /// Value genesis era (the original genesis' era is 0)
+    era: u32,
+
+    /// Value genesis state hash of the era
+    #[graphql(name = "genesis_state_hash")]
+    genesis_state_hash: String,
+
+    /// Value epoch number continuing across genesis eras
+    #[graphql(name = "flat_epoch")]
+    flat_epoch: u32,
+
     /// Value current ledger hash
     ledger_hash: String,
 
@@ -232,9 +536,12 @@ pub struct StakesLedgerAccount {
     /// Value nonce
     pub nonce: u32,
 
-    /// Value delegate
+    /// Value delegate, the account itself if it has no delegate
     pub delegate: String,
 
+    /// Value delegates to itself
+    pub is_self_delegated: bool,
+
     /// Value public key
     pub pk: String,
 
@@ -248,6 +555,10 @@ pub struct StakesLedgerAccount {
     /// Value token
     pub token: u64,
 
+    /// Value token address
+    #[graphql(name = "token_address")]
+    pub token_address: String,
+
     /// Value receipt chain hash
     #[graphql(name = "receipt_chain_hash")]
     pub receipt_chain_hash: String,
@@ -317,6 +628,15 @@ pub struct StakesDelegationTotals {
 
     /// Value delegates
     pub delegates: Vec<String>,
+
+    #[graphql(skip)]
+    pub delegate: PublicKey,
+
+    #[graphql(skip)]
+    pub epoch: u32,
+
+    #[graphql(skip)]
+    pub genesis_state_hash: StateHash,
 }
 
 #[ComplexObject]
@@ -330,9 +650,34 @@ impl StakesDelegationTotals {
         } else {
             Decimal::ZERO
         };
-        let rounded_ratio = ratio.round_dp(2);
-        format!("{:.2}", rounded_ratio)
+        format_percentage(ratio)
     }
+
+    /// Value delegators with their share of the total delegated stake
+    async fn delegators<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        sort_by: Option<StakeSortByInput>,
+        #[graphql(default = 100)] limit: usize,
+        #[graphql(default = 0)] offset: usize,
+        cursor: Option<String>,
+    ) -> Result<Vec<StakesDelegator>> {
+        delegator_page(
+            db(ctx),
+            &self.delegate,
+            self.epoch,
+            Some(&self.genesis_state_hash),
+            sort_by,
+            max_limit(ctx, limit),
+            offset,
+            cursor,
+        )
+    }
+}
+
+fn format_percentage(ratio: Decimal) -> String {
+    let rounded_ratio = ratio.round_dp(2);
+    format!("{:.2}", rounded_ratio)
 }
 
 impl
@@ -375,10 +720,12 @@ impl
 
         let balance = decimal.to_f64().unwrap_or_default();
         let nonce = acc.0.nonce.unwrap_or_default();
+        let is_self_delegated = acc.0.is_self_delegated();
         let delegate = acc.0.delegate.0;
         let pk = acc.0.pk.0;
         let public_key = pk.clone();
-        let token = acc.0.token.unwrap_or_default();
+        let token_address = acc.0.token.0.clone();
+        let token = TokenId::from(acc.0.token).0;
         let receipt_chain_hash = acc.0.receipt_chain_hash.0;
         let voting_for = acc.0.voting_for.0;
         Self {
@@ -386,9 +733,11 @@ impl
             balance,
             nonce: nonce.0,
             delegate,
+            is_self_delegated,
             pk,
             public_key,
             token,
+            token_address,
             receipt_chain_hash,
             voting_for,
             balance_nanomina,
@@ -412,9 +761,23 @@ impl StakeQueryInput {
         query: Option<&Self>,
         stakes_ledger_account: &StakesLedgerAccountWithMeta,
     ) -> bool {
+        Self::matches_total_delegated_mina(
+            query,
+            stakes_ledger_account.delegation_totals.total_delegated,
+        )
+    }
+
+    /// Checks the `stake_lte` filter against the nanomina total delegated
+    pub fn matches_total_delegated(query: Option<&Self>, total_delegated_nanomina: u64) -> bool {
+        let mut decimal = Decimal::from(total_delegated_nanomina);
+        decimal.set_scale(9).ok();
+        Self::matches_total_delegated_mina(query, decimal.to_f64().unwrap_or_default())
+    }
+
+    fn matches_total_delegated_mina(query: Option<&Self>, total_delegated: f64) -> bool {
         if let Some(query) = query {
             if let Some(stake_lte) = query.stake_lte.as_ref().and_then(|s| s.parse::<f64>().ok()) {
-                if stakes_ledger_account.delegation_totals.total_delegated > stake_lte {
+                if total_delegated > stake_lte {
                     return false;
                 }
             }
@@ -436,6 +799,7 @@ impl StakeQueryInput {
                 ledger_hash: query_ledger_hash,
                 username,
                 stake_lte: _,
+                genesis_state_hash: _,
             } = query;
             if let Some(public_key) = public_key {
                 if *public_key != account.pk.0 {
@@ -471,11 +835,72 @@ impl StakeQueryInput {
     }
 }
 
+impl StakesPage<'_> {
+    fn is_full(&self) -> bool {
+        self.accounts.len() >= self.limit
+    }
+
+    /// Adds the account if it matches the query & isn't skipped
+    ///
+    /// The account's meta data is only gathered once the cheap filters pass
+    fn push(&mut self, account: StakingAccount, delegation: EpochStakeDelegation) {
+        if StakeQueryInput::matches_staking_account(
+            self.query,
+            &account,
+            &self.ledger_hash,
+            self.epoch,
+        ) && StakeQueryInput::matches_total_delegated(
+            self.query,
+            delegation.total_delegated.unwrap_or_default(),
+        ) {
+            if self.offset > 0 {
+                self.offset -= 1;
+                return;
+            }
+
+            self.accounts.push(StakesLedgerAccountWithMeta::new(
+                self.db,
+                account,
+                &delegation,
+                self.era,
+                self.epoch,
+                self.ledger_hash.clone(),
+                self.total_currency,
+            ));
+        }
+    }
+}
+
+impl StakesDelegator {
+    pub fn new(db: &Arc<IndexerStore>, delegator: EpochDelegator) -> Self {
+        let mut decimal = Decimal::from(delegator.balance);
+        decimal.set_scale(9).ok();
+
+        let username = match db.get_username(&delegator.pk) {
+            Ok(None) | Err(_) => Some("Unknown".to_string()),
+            Ok(username) => username.map(|u| u.0),
+        };
+
+        Self {
+            username,
+            stake_percentage: format_percentage(delegator.stake_percentage()),
+            epoch: delegator.epoch,
+            public_key: delegator.pk.0,
+            delegate: delegator.delegate.0,
+            balance: decimal.to_f64().unwrap_or_default(),
+            balance_nanomina: delegator.balance,
+            prev_delegate: delegator.prev_delegate.map(|pk| pk.0),
+            delegation_changed: delegator.delegation_changed,
+        }
+    }
+}
+
 impl StakesLedgerAccountWithMeta {
     pub fn new(
         db: &Arc<IndexerStore>,
         account: StakingAccount,
         delegations: &EpochStakeDelegation,
+        era: &GenesisEra,
         epoch: u32,
         ledger_hash: String,
         total_currency: u64,
@@ -540,6 +965,9 @@ impl StakesLedgerAccountWithMeta {
         Self {
             epoch,
             ledger_hash,
+            era: era.era,
+            genesis_state_hash: era.genesis_state_hash.0.clone(),
+            flat_epoch: era.flat_epoch(epoch),
             account: StakesLedgerAccount::from((
                 account,
                 chain_id,
@@ -561,6 +989,9 @@ impl StakesLedgerAccountWithMeta {
                 total_delegated_nanomina,
                 total_currency,
                 delegates,
+                delegate: pk.clone(),
+                epoch,
+                genesis_state_hash: era.genesis_state_hash.clone(),
             },
             timing,
             epoch_num_blocks: db
@@ -592,7 +1023,7 @@ impl StakesLedgerAccountWithMeta {
                 .get_internal_commands_total_count()
                 .expect("total internal command count"),
             epoch_num_accounts: db
-                .get_staking_ledger_accounts_count_epoch(epoch, &MAINNET_GENESIS_HASH.into())
+                .get_staking_ledger_accounts_count_epoch(epoch, &era.genesis_state_hash)
                 .expect("total internal command count"),
         }
     }
@@ -0,0 +1,66 @@
+use super::db;
+use crate::block::{store::BlockStore, supply::Supply as EpochSupply};
+use async_graphql::{Context, Object, Result, SimpleObject};
+
+#[derive(Default)]
+pub struct SupplyQueryRoot;
+
+/// Supply & coinbase emission of an epoch's canonical blocks (nanomina)
+#[derive(SimpleObject)]
+pub struct Supply {
+    epoch: u32,
+
+    /// Number of canonical blocks
+    num_blocks: u32,
+
+    /// Height of the epoch's highest canonical block
+    block_height: u32,
+
+    /// Global slot of the epoch's highest canonical block
+    global_slot: u32,
+
+    /// Total currency after the epoch's highest canonical block
+    total_currency: u64,
+
+    /// Time-locked balance of the staking ledger's timed accounts, if the
+    /// epoch's staking ledger is present
+    locked_supply: Option<u64>,
+
+    /// Total currency less the locked supply
+    circulating_supply: Option<u64>,
+
+    /// Total coinbase amount
+    coinbase_emission: u64,
+
+    /// Number of supercharged coinbases
+    num_supercharged: u32,
+}
+
+#[Object]
+impl SupplyQueryRoot {
+    /// Total, locked & circulating supply & coinbase emission of the epoch,
+    /// defaults to the latest epoch with canonical blocks
+    async fn supply<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        epoch: Option<u32>,
+    ) -> Result<Option<Supply>> {
+        Ok(db(ctx).get_supply(epoch)?.map(Supply::from))
+    }
+}
+
+impl From<EpochSupply> for Supply {
+    fn from(supply: EpochSupply) -> Self {
+        Self {
+            epoch: supply.epoch,
+            num_blocks: supply.num_blocks,
+            block_height: supply.blockchain_length,
+            global_slot: supply.global_slot,
+            total_currency: supply.total_currency,
+            locked_supply: supply.locked_supply,
+            circulating_supply: supply.circulating_supply,
+            coinbase_emission: supply.coinbase_emission,
+            num_supercharged: supply.num_supercharged,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Batched token symbol resolution for GraphQL outputs
+
+use crate::{
+    ledger::{store::best::BestLedgerStore, token::TokenAddress},
+    store::IndexerStore,
+};
+use std::collections::{BTreeSet, HashMap};
+
+/// Symbols of the tokens in a single response
+///
+/// All distinct tokens are resolved with one batched store lookup, rather
+/// than one lookup per row
+#[derive(Debug, Default)]
+pub struct TokenSymbols {
+    symbols: HashMap<String, Option<String>>,
+    num_lookups: u32,
+}
+
+impl TokenSymbols {
+    /// Resolve the symbols of the given tokens
+    pub fn resolve<'a, I>(db: &IndexerStore, tokens: I) -> anyhow::Result<Self>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let tokens: BTreeSet<&str> = tokens.into_iter().collect();
+        let tokens: Vec<TokenAddress> = tokens.into_iter().filter_map(TokenAddress::new).collect();
+
+        if tokens.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let symbols = db.get_token_symbols(&tokens)?;
+        Ok(Self {
+            symbols: tokens
+                .into_iter()
+                .zip(symbols)
+                .map(|(token, symbol)| (token.0, symbol.map(|symbol| symbol.0)))
+                .collect(),
+            num_lookups: 1,
+        })
+    }
+
+    /// Symbol of the token (`None` if it was never set)
+    pub fn get(&self, token: &str) -> Option<String> {
+        self.symbols.get(token).cloned().flatten()
+    }
+
+    /// Number of batched store lookups made
+    pub fn num_lookups(&self) -> u32 {
+        self.num_lookups
+    }
+}
@@ -1,4 +1,4 @@
-use super::db;
+use super::{db, max_limit};
 use crate::{
     base::public_key::PublicKey,
     block::store::BlockStore,
@@ -56,6 +56,7 @@ impl TopSnarkersQueryRoot {
         #[graphql(default = 100)] limit: usize,
     ) -> Result<Vec<TopSnarker>> {
         use TopSnarkersSortByInput::*;
+        let limit = max_limit(ctx, limit);
         let db = db(ctx);
         let epoch = query
             .as_ref()
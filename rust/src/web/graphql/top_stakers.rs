@@ -1,4 +1,4 @@
-use super::db;
+use super::{db, max_limit};
 use crate::{
     base::public_key::PublicKey,
     block::store::BlockStore,
@@ -53,6 +53,7 @@ impl TopStakersQueryRoot {
         sort_by: Option<TopStakersSortByInput>,
         #[graphql(default = 100)] limit: usize,
     ) -> Result<Vec<TopStakerAccount>> {
+        let limit = max_limit(ctx, limit);
         let db = db(ctx);
         let epoch = query
             .as_ref()
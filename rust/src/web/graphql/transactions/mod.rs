@@ -1,22 +1,35 @@
-use super::{date_time_to_scalar, db, get_block_canonicity, PK};
+use super::{
+    date_time_to_scalar, db, error::StoreResultExt, get_block_canonicity, in_chain_as_of,
+    max_limit, PK,
+};
 use crate::{
     base::public_key::PublicKey,
-    block::store::BlockStore,
+    block::{store::BlockStore, AccountCreated},
+    canonicity::as_of::CanonicalChainAsOf,
+    chain::store::ChainStore,
     command::{
-        signed::{SignedCommandWithData, TxnHash},
+        precondition::{
+            AccountUpdatePreconditions, FailedPrecondition, PreconditionCategory,
+            ZkappPreconditionSummary,
+        },
+        signed::{SignedCommand, SignedCommandWithData, TxnHash},
         store::UserCommandStore,
-        CommandStatusData,
+        AccountUpdateStatus, CommandStatusData,
     },
-    constants::millis_to_global_slot,
+    ledger::token::TokenAddress,
+    mina_blocks::v2::staged_ledger_diff::{Elt, UserCommandData},
+    protocol::serialization_types::staged_ledger_diff::TransactionStatusFailedType,
     store::IndexerStore,
     utility::store::{
         command::user::{
-            pk_txn_sort_key_prefix, txn_hash_of_key, user_commands_iterator_state_hash,
-            user_commands_iterator_txn_hash,
+            failure_kind_of_key, failure_txn_sort_key_prefix, pk_txn_sort_key_prefix,
+            token_txn_sort_key_prefix, txn_hash_of_key, txn_memo_of_value,
+            user_commands_iterator_state_hash, user_commands_iterator_txn_hash,
+            user_commands_iterator_u32_prefix,
         },
         common::{state_hash_suffix, U32_LEN},
     },
-    web::graphql::{gen::TransactionQueryInput, DateTime},
+    web::graphql::{gen::TransactionQueryInput, tokens::TokenSymbols, DateTime},
 };
 use async_graphql::{Context, Enum, Object, Result, SimpleObject};
 use serde::Serialize;
@@ -39,6 +52,12 @@ pub enum TransactionSortByInput {
     GlobalSlotAsc,
     #[graphql(name = "GLOBALSLOT_DESC")]
     GlobalSlotDesc,
+
+    /// Block height, then index in block (ledger application order)
+    #[graphql(name = "INDEX_ASC")]
+    IndexAsc,
+    #[graphql(name = "INDEX_DESC")]
+    IndexDesc,
 }
 
 #[derive(Clone, Debug, SimpleObject, Serialize)]
@@ -59,6 +78,26 @@ pub struct TransactionWithoutBlock {
     to: String,
     token: Option<u64>,
 
+    /// Index of the transaction in the block's ledger application order
+    index_in_block: u32,
+
+    /// Whether the payment created the receiver's account
+    /// (null for zkapp commands, see account updates)
+    receiver_account_created: Option<bool>,
+
+    /// Account creation fee deducted from the payment (nanomina)
+    account_creation_fee: Option<String>,
+
+    /// Whether the command is signed
+    /// (fee payer authorization for zkapp commands)
+    signature_present: bool,
+
+    /// Kind of the command's signature
+    signature_kind: Option<String>,
+
+    /// Zkapp command account update tree
+    zkapp_command: Option<ZkappCommand>,
+
     /// Total number of user commands in the given epoch
     /// (default: current epoch)
     #[graphql(name = "epoch_num_user_commands")]
@@ -69,6 +108,109 @@ pub struct TransactionWithoutBlock {
     total_num_user_commands: u32,
 }
 
+#[derive(Clone, Debug, SimpleObject, Serialize)]
+pub struct ZkappCommand {
+    /// Global slot the fee payer's command is valid until
+    fee_payer_valid_until: Option<u64>,
+
+    /// Nonce precondition of the fee payer
+    fee_payer_nonce: u32,
+
+    account_updates: Vec<ZkappAccountUpdate>,
+
+    /// Precondition failures of a failed command, with whether the failing
+    /// account update checks the precondition
+    failed_precondition_detail: Vec<ZkappFailedPrecondition>,
+}
+
+#[derive(Clone, Debug, SimpleObject, Serialize)]
+pub struct ZkappAccountUpdate {
+    public_key: String,
+    token_id: String,
+
+    /// Symbol of the update's token (null if never set)
+    token_symbol: Option<String>,
+
+    authorization_kind: String,
+
+    /// Whether the account update took effect
+    status: ZkappAccountUpdateStatus,
+
+    /// Whether the account update created the account
+    account_created: bool,
+
+    /// Account creation fee (nanomina)
+    account_creation_fee: Option<String>,
+
+    /// Precondition categories checked by the account update
+    preconditions: ZkappAccountUpdatePreconditions,
+
+    /// Child account updates
+    calls: Vec<ZkappAccountUpdate>,
+}
+
+#[derive(Clone, Debug, SimpleObject, Serialize)]
+pub struct ZkappAccountUpdatePreconditions {
+    nonce: bool,
+    balance: bool,
+
+    /// Indices of the checked app state fields
+    app_state: Vec<u32>,
+
+    action_state: bool,
+    receipt_chain_hash: bool,
+    delegate: bool,
+    proved_state: bool,
+    is_new: bool,
+
+    /// Whether any protocol state precondition is checked
+    network: bool,
+
+    /// Whether the global slot range is checked
+    valid_while: bool,
+}
+
+#[derive(Clone, Debug, SimpleObject, Serialize)]
+pub struct ZkappFailedPrecondition {
+    /// Depth-first index of the failing account update
+    account_update_index: u32,
+
+    category: ZkappPreconditionCategory,
+
+    /// Index of the unsatisfied app state field
+    app_state_index: Option<u32>,
+
+    /// Whether the failing account update checks the precondition
+    checked: bool,
+}
+
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ZkappPreconditionCategory {
+    Nonce,
+    Balance,
+    AppState,
+    ActionState,
+    ReceiptChainHash,
+    Delegate,
+    ProvedState,
+    IsNew,
+    Network,
+    ValidWhile,
+}
+
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ZkappAccountUpdateStatus {
+    Applied,
+
+    /// The account update itself failed
+    Failed,
+
+    /// Not applied due to another account update's failure
+    Cancelled,
+}
+
 #[derive(Clone, Debug, SimpleObject)]
 pub struct Transaction {
     block: TransactionBlock,
@@ -83,6 +225,28 @@ struct TransactionBlock {
     state_hash: String,
 }
 
+/// Failed transactions per failure kind
+#[derive(Clone, Debug, SimpleObject)]
+pub struct FailureStats {
+    from_height: u32,
+    to_height: u32,
+
+    /// Number of failed transactions
+    num_failed: u32,
+
+    /// Failed transaction counts per failure kind, most frequent first
+    failures: Vec<FailureKindCount>,
+}
+
+#[derive(Clone, Debug, SimpleObject)]
+pub struct FailureKindCount {
+    /// Kind of the failure which caused the transactions to fail, e.g.
+    /// `Amount_insufficient_to_create_account`
+    kind: String,
+
+    count: u32,
+}
+
 #[derive(Default)]
 pub struct TransactionsQueryRoot;
 
@@ -99,22 +263,165 @@ impl TransactionsQueryRoot {
         if let Some(hash) = query.hash {
             let hash = TxnHash::from(hash);
             if hash.is_valid() {
-                return Ok(db.get_user_command(&hash, 0)?.map(|cmd| {
+                let mut transaction = db.get_user_command(&hash, 0)?.map(|cmd| {
                     Transaction::new(cmd, db, epoch_num_user_commands, total_num_user_commands)
-                }));
+                });
+
+                if let Some(transaction) = transaction.as_mut() {
+                    set_token_symbols(db, std::slice::from_mut(transaction))?;
+                }
+                return Ok(transaction);
             }
         }
         Ok(None)
     }
 
-    #[allow(clippy::too_many_lines)]
     pub async fn transactions(
         &self,
         ctx: &Context<'_>,
         query: Option<TransactionQueryInput>,
         #[graphql(default = 100)] limit: usize,
         sort_by: Option<TransactionSortByInput>,
-    ) -> Result<Vec<Transaction>> {
+        as_of_height: Option<u32>,
+    ) -> Result<Option<Vec<Transaction>>> {
+        let limit = max_limit(ctx, limit);
+        let mut transactions = self
+            .get_transactions(ctx, query, limit, sort_by, as_of_height)
+            .await?;
+
+        if let Some(transactions) = transactions.as_mut() {
+            set_token_symbols(db(ctx), transactions)?;
+        }
+        Ok(transactions)
+    }
+
+    /// Transactions involving the token, e.g. its accounts' payments &
+    /// zkapp account updates, sorted by block height (default descending) or
+    /// index in block
+    pub async fn transactions_by_token(
+        &self,
+        ctx: &Context<'_>,
+        token_id: String,
+        #[graphql(default = 100)] limit: usize,
+        sort_by: Option<TransactionSortByInput>,
+        canonical: Option<bool>,
+    ) -> Result<Option<Vec<Transaction>>> {
+        use TransactionSortByInput::*;
+
+        let limit = max_limit(ctx, limit);
+        let token = TokenAddress::new(&token_id)
+            .ok_or_else(|| format!("Invalid token address: {token_id}"))?;
+
+        let sort_by = sort_by.unwrap_or(BlockHeightDesc);
+        let direction = match sort_by {
+            BlockHeightAsc | IndexAsc => Direction::Forward,
+            BlockHeightDesc | IndexDesc => Direction::Reverse,
+            _ => return Err("Token transactions are sorted by block height or index".into()),
+        };
+
+        let db = db(ctx);
+        let epoch_num_user_commands = db.get_user_commands_epoch_count(None)?;
+        let total_num_user_commands = db.get_user_commands_total_count()?;
+
+        // MINA is involved in every transaction, custom tokens are indexed
+        let height = match direction {
+            Direction::Forward => 0,
+            Direction::Reverse => u32::MAX,
+        };
+        let (iter, prefix) = if token == TokenAddress::default() {
+            (
+                db.user_commands_height_iterator(IteratorMode::From(
+                    &height.to_be_bytes(),
+                    direction,
+                )),
+                vec![],
+            )
+        } else {
+            (
+                db.user_commands_token_iterator(IteratorMode::From(
+                    &token_txn_sort_key_prefix(&token, height),
+                    direction,
+                )),
+                token.0.as_bytes().to_vec(),
+            )
+        };
+
+        let mut transactions = vec![];
+        for (key, _) in iter.flatten() {
+            if !key.starts_with(&prefix) || transactions.len() >= limit {
+                break;
+            }
+
+            let key = &key[prefix.len()..];
+            let state_hash = user_commands_iterator_state_hash(key)?;
+            if let Some(canonical) = canonical {
+                if get_block_canonicity(db, &state_hash) != canonical {
+                    continue;
+                }
+            }
+
+            let txn_hash = user_commands_iterator_txn_hash(key)?;
+            if let Some(cmd) = db.get_user_command_state_hash(&txn_hash, &state_hash)? {
+                transactions.push(Transaction::new(
+                    cmd,
+                    db,
+                    epoch_num_user_commands,
+                    total_num_user_commands,
+                ));
+            }
+        }
+
+        sort_by_index(&mut transactions, sort_by, limit);
+        set_token_symbols(db, &mut transactions)?;
+        Ok(Some(transactions))
+    }
+
+    /// Number of failed transactions per failure kind in the inclusive height
+    /// range (default: all blocks), optionally of the given canonicity
+    pub async fn failure_stats(
+        &self,
+        ctx: &Context<'_>,
+        from_height: Option<u32>,
+        to_height: Option<u32>,
+        canonical: Option<bool>,
+    ) -> Result<FailureStats> {
+        let db = db(ctx);
+        let from_height = from_height.unwrap_or(1);
+        let to_height = match to_height {
+            Some(to_height) => to_height,
+            None => db.get_best_block_height()?.unwrap_or_default(),
+        };
+
+        if from_height > to_height {
+            return Err(format!("Invalid height range: {from_height} > {to_height}").into());
+        }
+
+        let mut failures: Vec<_> = db
+            .get_user_command_failure_counts(from_height, to_height, canonical)?
+            .into_iter()
+            .map(|(kind, count)| FailureKindCount { kind, count })
+            .collect();
+        failures.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.kind.cmp(&b.kind)));
+
+        Ok(FailureStats {
+            from_height,
+            to_height,
+            num_failed: failures.iter().map(|failure| failure.count).sum(),
+            failures,
+        })
+    }
+}
+
+impl TransactionsQueryRoot {
+    #[allow(clippy::too_many_lines)]
+    async fn get_transactions(
+        &self,
+        ctx: &Context<'_>,
+        query: Option<TransactionQueryInput>,
+        limit: usize,
+        sort_by: Option<TransactionSortByInput>,
+        as_of_height: Option<u32>,
+    ) -> Result<Option<Vec<Transaction>>> {
         use TransactionSortByInput::*;
 
         let db = db(ctx);
@@ -123,6 +430,14 @@ impl TransactionsQueryRoot {
         let sort_by = sort_by.unwrap_or(TransactionSortByInput::BlockHeightDesc);
         let mut transactions = vec![];
 
+        // restrict to the canonical chain as of the given height
+        let mut as_of = as_of_height
+            .map(|height| CanonicalChainAsOf::new(db, height))
+            .transpose()?;
+
+        // each block's transactions are sorted once collected
+        let by_index = matches!(sort_by, IndexAsc | IndexDesc);
+
         // state hash query
         if let Some(state_hash) = query
             .as_ref()
@@ -131,10 +446,12 @@ impl TransactionsQueryRoot {
         {
             let query = query.expect("query input to exists");
             let block_height = db
-                .get_block_height(&state_hash.into())?
-                .expect("block height");
+                .get_block_height(&state_hash.clone().into())
+                .or_not_found("block", &state_hash)?;
             let (min, max) = match sort_by {
-                BlockHeightAsc | BlockHeightDesc => (block_height, block_height + 1),
+                BlockHeightAsc | BlockHeightDesc | IndexAsc | IndexDesc => {
+                    (block_height, block_height + 1)
+                }
                 GlobalSlotAsc | GlobalSlotDesc | DateTimeAsc | DateTimeDesc => {
                     let min_slots = db
                         .get_block_global_slots_from_height(block_height)?
@@ -148,23 +465,21 @@ impl TransactionsQueryRoot {
                     )
                 }
             };
-            let iter = match sort_by {
-                BlockHeightAsc => db.user_commands_height_iterator(IteratorMode::From(
-                    &min.to_be_bytes(),
-                    Direction::Forward,
-                )),
-                BlockHeightDesc => db.user_commands_height_iterator(IteratorMode::From(
-                    &max.to_be_bytes(),
-                    Direction::Reverse,
-                )),
-                GlobalSlotAsc | DateTimeAsc => db.user_commands_slot_iterator(IteratorMode::From(
-                    &min.to_be_bytes(),
-                    Direction::Forward,
-                )),
-                GlobalSlotDesc | DateTimeDesc => db.user_commands_slot_iterator(
-                    IteratorMode::From(&max.to_be_bytes(), Direction::Reverse),
-                ),
-            };
+            let iter =
+                match sort_by {
+                    BlockHeightAsc | IndexAsc => db.user_commands_height_iterator(
+                        IteratorMode::From(&min.to_be_bytes(), Direction::Forward),
+                    ),
+                    BlockHeightDesc | IndexDesc => db.user_commands_height_iterator(
+                        IteratorMode::From(&max.to_be_bytes(), Direction::Reverse),
+                    ),
+                    GlobalSlotAsc | DateTimeAsc => db.user_commands_slot_iterator(
+                        IteratorMode::From(&min.to_be_bytes(), Direction::Forward),
+                    ),
+                    GlobalSlotDesc | DateTimeDesc => db.user_commands_slot_iterator(
+                        IteratorMode::From(&max.to_be_bytes(), Direction::Reverse),
+                    ),
+                };
 
             for (key, _) in iter.flatten() {
                 if key[..U32_LEN] < *min.to_be_bytes().as_slice()
@@ -176,6 +491,10 @@ impl TransactionsQueryRoot {
 
                 let txn_hash = user_commands_iterator_txn_hash(&key)?;
                 let state_hash = state_hash_suffix(&key)?;
+
+                if !in_chain_as_of(db, &mut as_of, &state_hash)? {
+                    continue;
+                }
                 let cmd = db
                     .get_user_command_state_hash(&txn_hash, &state_hash)?
                     .expect("txn at hash");
@@ -184,12 +503,13 @@ impl TransactionsQueryRoot {
                 if query.matches(&txn) {
                     transactions.push(txn);
 
-                    if transactions.len() >= limit {
+                    if !by_index && transactions.len() >= limit {
                         break;
                     }
                 }
             }
-            return Ok(transactions);
+            sort_by_index(&mut transactions, sort_by, limit);
+            return Ok(Some(transactions));
         }
 
         // txn hash query (no state hash)
@@ -198,6 +518,10 @@ impl TransactionsQueryRoot {
             let query = query.expect("query input to exists");
             if let Some(state_hashes) = db.get_user_command_state_hashes(&txn_hash)? {
                 for state_hash in state_hashes.iter() {
+                    if !in_chain_as_of(db, &mut as_of, state_hash)? {
+                        continue;
+                    }
+
                     if let Some(cmd) = db.get_user_command_state_hash(&txn_hash, state_hash)? {
                         let txn = Transaction::new(
                             cmd,
@@ -215,14 +539,16 @@ impl TransactionsQueryRoot {
                     }
                 }
             }
-            return Ok(transactions);
+            return Ok(Some(transactions));
         }
 
         // block height query
         if let Some(block_height) = query.as_ref().and_then(|input| input.block_height) {
             let query = query.expect("query input to exists");
             let (min, max) = match sort_by {
-                BlockHeightAsc | BlockHeightDesc => (block_height, block_height + 1),
+                BlockHeightAsc | BlockHeightDesc | IndexAsc | IndexDesc => {
+                    (block_height, block_height + 1)
+                }
                 GlobalSlotAsc | GlobalSlotDesc | DateTimeAsc | DateTimeDesc => {
                     let min_slots = db
                         .get_block_global_slots_from_height(block_height)?
@@ -236,23 +562,21 @@ impl TransactionsQueryRoot {
                     )
                 }
             };
-            let iter = match sort_by {
-                BlockHeightAsc => db.user_commands_height_iterator(IteratorMode::From(
-                    &min.to_be_bytes(),
-                    Direction::Forward,
-                )),
-                BlockHeightDesc => db.user_commands_height_iterator(IteratorMode::From(
-                    &max.to_be_bytes(),
-                    Direction::Reverse,
-                )),
-                GlobalSlotAsc | DateTimeAsc => db.user_commands_slot_iterator(IteratorMode::From(
-                    &min.to_be_bytes(),
-                    Direction::Forward,
-                )),
-                GlobalSlotDesc | DateTimeDesc => db.user_commands_slot_iterator(
-                    IteratorMode::From(&max.to_be_bytes(), Direction::Reverse),
-                ),
-            };
+            let iter =
+                match sort_by {
+                    BlockHeightAsc | IndexAsc => db.user_commands_height_iterator(
+                        IteratorMode::From(&min.to_be_bytes(), Direction::Forward),
+                    ),
+                    BlockHeightDesc | IndexDesc => db.user_commands_height_iterator(
+                        IteratorMode::From(&max.to_be_bytes(), Direction::Reverse),
+                    ),
+                    GlobalSlotAsc | DateTimeAsc => db.user_commands_slot_iterator(
+                        IteratorMode::From(&min.to_be_bytes(), Direction::Forward),
+                    ),
+                    GlobalSlotDesc | DateTimeDesc => db.user_commands_slot_iterator(
+                        IteratorMode::From(&max.to_be_bytes(), Direction::Reverse),
+                    ),
+                };
 
             for (key, _) in iter.flatten() {
                 if key[..U32_LEN] != block_height.to_be_bytes() {
@@ -261,6 +585,10 @@ impl TransactionsQueryRoot {
                 }
 
                 let state_hash = state_hash_suffix(&key)?;
+
+                if !in_chain_as_of(db, &mut as_of, &state_hash)? {
+                    continue;
+                }
                 let canonical = get_block_canonicity(db, &state_hash);
                 if let Some(query_canonicity) = query.canonical {
                     if canonical != query_canonicity {
@@ -277,18 +605,21 @@ impl TransactionsQueryRoot {
                 if query.matches(&txn) {
                     transactions.push(txn);
 
-                    if transactions.len() >= limit {
+                    if !by_index && transactions.len() >= limit {
                         break;
                     }
                 }
             }
-            return Ok(transactions);
+            sort_by_index(&mut transactions, sort_by, limit);
+            return Ok(Some(transactions));
         }
 
         // iterator mode & direction determined by desired sorting
         let (start, direction) = match sort_by {
-            BlockHeightAsc | DateTimeAsc | GlobalSlotAsc => (0, Direction::Forward),
-            BlockHeightDesc | DateTimeDesc | GlobalSlotDesc => (u32::MAX, Direction::Reverse),
+            BlockHeightAsc | DateTimeAsc | GlobalSlotAsc | IndexAsc => (0, Direction::Forward),
+            BlockHeightDesc | DateTimeDesc | GlobalSlotDesc | IndexDesc => {
+                (u32::MAX, Direction::Reverse)
+            }
         };
 
         // from/to account (sender/receiver) query
@@ -316,6 +647,10 @@ impl TransactionsQueryRoot {
                 }
 
                 let state_hash = state_hash_suffix(&key)?;
+
+                if !in_chain_as_of(db, &mut as_of, &state_hash)? {
+                    continue;
+                }
                 let canonical = get_block_canonicity(db, &state_hash);
                 if let Some(query_canonicity) = query.canonical {
                     if canonical != query_canonicity {
@@ -339,7 +674,155 @@ impl TransactionsQueryRoot {
                     }
                 };
             }
-            return Ok(transactions);
+            sort_by_index(&mut transactions, sort_by, limit);
+            return Ok(Some(transactions));
+        }
+
+        // failure reason query, via the failure index
+        if matches!(
+            sort_by,
+            BlockHeightAsc | BlockHeightDesc | IndexAsc | IndexDesc
+        ) && query.as_ref().map_or(false, |q| q.failure_reason.is_some())
+        {
+            let query = query.expect("query input to exists");
+            let failure_reason = query.failure_reason.as_deref().expect("failure reason");
+
+            // no transaction fails with an unknown reason
+            let Some(failure) = TransactionStatusFailedType::from_reason(failure_reason) else {
+                return Ok(Some(transactions));
+            };
+
+            let kind = failure.kind();
+            let (min, max) = calculate_inclusive_height_bounds(
+                query.block_height_gte,
+                query.block_height_gt,
+                query.block_height_lte,
+                query.block_height_lt,
+                db.get_best_block_height()?.expect("best block height"),
+            )?;
+
+            // reverse is exclusive so we increment
+            let iter = match sort_by {
+                BlockHeightAsc | IndexAsc => db.user_commands_failure_iterator(IteratorMode::From(
+                    &failure_txn_sort_key_prefix(&kind, min),
+                    Direction::Forward,
+                )),
+                _ => db.user_commands_failure_iterator(IteratorMode::From(
+                    &failure_txn_sort_key_prefix(&kind, max.saturating_add(1)),
+                    Direction::Reverse,
+                )),
+            };
+
+            for (key, _) in iter.flatten() {
+                let (key_kind, key) = failure_kind_of_key(&key)?;
+                let height = user_commands_iterator_u32_prefix(key);
+                if key_kind != kind || height < min || height > max {
+                    // we've gone beyond the query bounds
+                    break;
+                }
+
+                let state_hash = user_commands_iterator_state_hash(key)?;
+                if !in_chain_as_of(db, &mut as_of, &state_hash)? {
+                    continue;
+                }
+
+                let canonical = get_block_canonicity(db, &state_hash);
+                if let Some(query_canonicity) = query.canonical {
+                    if canonical != query_canonicity {
+                        continue;
+                    }
+                }
+
+                let txn_hash = user_commands_iterator_txn_hash(key)?;
+                let cmd = db
+                    .get_user_command_state_hash(&txn_hash, &state_hash)?
+                    .expect("txn at hash");
+                let txn =
+                    Transaction::new(cmd, db, epoch_num_user_commands, total_num_user_commands);
+
+                if query.matches(&txn) {
+                    transactions.push(txn);
+
+                    if transactions.len() >= limit {
+                        break;
+                    }
+                }
+            }
+            sort_by_index(&mut transactions, sort_by, limit);
+            return Ok(Some(transactions));
+        }
+
+        // memo search query, via the memo index (fee bounds are checked
+        // before fetching the command)
+        if matches!(
+            sort_by,
+            BlockHeightAsc | BlockHeightDesc | IndexAsc | IndexDesc
+        ) && query.as_ref().map_or(false, |q| q.memo_contains.is_some())
+        {
+            let query = query.expect("query input to exists");
+            let memo_contains = query.memo_contains.as_deref().expect("memo to exist");
+            let (min, max) = calculate_inclusive_height_bounds(
+                query.block_height_gte,
+                query.block_height_gt,
+                query.block_height_lte,
+                query.block_height_lt,
+                db.get_best_block_height()?.expect("best block height"),
+            )?;
+
+            // reverse is exclusive so we increment
+            let iter = match sort_by {
+                BlockHeightAsc | IndexAsc => db.user_commands_memo_iterator(IteratorMode::From(
+                    &min.to_be_bytes(),
+                    Direction::Forward,
+                )),
+                _ => db.user_commands_memo_iterator(IteratorMode::From(
+                    &max.saturating_add(1).to_be_bytes(),
+                    Direction::Reverse,
+                )),
+            };
+
+            for (key, value) in iter.flatten() {
+                if key[..U32_LEN] > *max.to_be_bytes().as_slice()
+                    || key[..U32_LEN] < *min.to_be_bytes().as_slice()
+                {
+                    // we've gone beyond the query bounds
+                    break;
+                }
+
+                let (fee, memo) = txn_memo_of_value(&value)?;
+                if !memo.contains(memo_contains) || !query.matches_fee(fee) {
+                    continue;
+                }
+
+                let state_hash = state_hash_suffix(&key)?;
+
+                if !in_chain_as_of(db, &mut as_of, &state_hash)? {
+                    continue;
+                }
+                let canonical = get_block_canonicity(db, &state_hash);
+                if let Some(query_canonicity) = query.canonical {
+                    if canonical != query_canonicity {
+                        continue;
+                    }
+                }
+
+                let txn_hash = user_commands_iterator_txn_hash(&key)?;
+                let cmd = db
+                    .get_user_command_state_hash(&txn_hash, &state_hash)?
+                    .expect("txn at hash");
+                let txn =
+                    Transaction::new(cmd, db, epoch_num_user_commands, total_num_user_commands);
+
+                if query.matches(&txn) {
+                    transactions.push(txn);
+
+                    if transactions.len() >= limit {
+                        break;
+                    }
+                }
+            }
+            sort_by_index(&mut transactions, sort_by, limit);
+            return Ok(Some(transactions));
         }
 
         // block height bounded query
@@ -361,7 +844,9 @@ impl TransactionsQueryRoot {
                 )?;
 
                 match sort_by {
-                    BlockHeightAsc | BlockHeightDesc => (min_bound, max_bound),
+                    BlockHeightAsc | BlockHeightDesc | IndexAsc | IndexDesc => {
+                        (min_bound, max_bound)
+                    }
                     GlobalSlotAsc | GlobalSlotDesc | DateTimeAsc | DateTimeDesc => {
                         let min_slots = db
                             .get_block_global_slots_from_height(min_bound)?
@@ -379,14 +864,13 @@ impl TransactionsQueryRoot {
 
             // reverse is exclusive so we increment
             let iter = match sort_by {
-                BlockHeightAsc => db.user_commands_height_iterator(IteratorMode::From(
+                BlockHeightAsc | IndexAsc => db.user_commands_height_iterator(IteratorMode::From(
                     &min.to_be_bytes(),
                     Direction::Forward,
                 )),
-                BlockHeightDesc => db.user_commands_height_iterator(IteratorMode::From(
-                    &max.saturating_add(1).to_be_bytes(),
-                    Direction::Reverse,
-                )),
+                BlockHeightDesc | IndexDesc => db.user_commands_height_iterator(
+                    IteratorMode::From(&max.saturating_add(1).to_be_bytes(), Direction::Reverse),
+                ),
                 GlobalSlotAsc | DateTimeAsc => db.user_commands_slot_iterator(IteratorMode::From(
                     &min.to_be_bytes(),
                     Direction::Forward,
@@ -405,6 +889,10 @@ impl TransactionsQueryRoot {
                 }
 
                 let state_hash = state_hash_suffix(&key)?;
+
+                if !in_chain_as_of(db, &mut as_of, &state_hash)? {
+                    continue;
+                }
                 let canonical = get_block_canonicity(db, &state_hash);
                 if let Some(query_canonicity) = query.canonical {
                     if canonical != query_canonicity {
@@ -427,7 +915,8 @@ impl TransactionsQueryRoot {
                     }
                 }
             }
-            return Ok(transactions);
+            sort_by_index(&mut transactions, sort_by, limit);
+            return Ok(Some(transactions));
         }
 
         // date time/global slot bounded query
@@ -456,7 +945,7 @@ impl TransactionsQueryRoot {
                 )?;
 
                 match sort_by {
-                    BlockHeightAsc | BlockHeightDesc => {
+                    BlockHeightAsc | BlockHeightDesc | IndexAsc | IndexDesc => {
                         let min_heights = db
                             .get_block_heights_from_global_slot(min_bound)?
                             .expect("heights at min slot");
@@ -476,14 +965,13 @@ impl TransactionsQueryRoot {
 
             // reverse is exclusive so we increment
             let iter = match sort_by {
-                BlockHeightAsc => db.user_commands_height_iterator(IteratorMode::From(
+                BlockHeightAsc | IndexAsc => db.user_commands_height_iterator(IteratorMode::From(
                     &min.to_be_bytes(),
                     Direction::Forward,
                 )),
-                BlockHeightDesc => db.user_commands_height_iterator(IteratorMode::From(
-                    &max.saturating_add(1).to_be_bytes(),
-                    Direction::Reverse,
-                )),
+                BlockHeightDesc | IndexDesc => db.user_commands_height_iterator(
+                    IteratorMode::From(&max.saturating_add(1).to_be_bytes(), Direction::Reverse),
+                ),
                 GlobalSlotAsc | DateTimeAsc => db.user_commands_slot_iterator(IteratorMode::From(
                     &min.to_be_bytes(),
                     Direction::Forward,
@@ -502,6 +990,10 @@ impl TransactionsQueryRoot {
                 }
 
                 let state_hash = state_hash_suffix(&key)?;
+
+                if !in_chain_as_of(db, &mut as_of, &state_hash)? {
+                    continue;
+                }
                 let canonical = get_block_canonicity(db, &state_hash);
                 if let Some(query_canonicity) = query.canonical {
                     if canonical != query_canonicity {
@@ -524,12 +1016,13 @@ impl TransactionsQueryRoot {
                     }
                 }
             }
-            return Ok(transactions);
+            sort_by_index(&mut transactions, sort_by, limit);
+            return Ok(Some(transactions));
         }
 
         let iter = match sort_by {
-            BlockHeightAsc => db.user_commands_height_iterator(IteratorMode::Start),
-            BlockHeightDesc => db.user_commands_height_iterator(IteratorMode::End),
+            BlockHeightAsc | IndexAsc => db.user_commands_height_iterator(IteratorMode::Start),
+            BlockHeightDesc | IndexDesc => db.user_commands_height_iterator(IteratorMode::End),
             DateTimeAsc | GlobalSlotAsc => db.user_commands_slot_iterator(IteratorMode::Start),
             DateTimeDesc | GlobalSlotDesc => db.user_commands_slot_iterator(IteratorMode::End),
         };
@@ -547,6 +1040,9 @@ impl TransactionsQueryRoot {
             }
 
             let state_hash = user_commands_iterator_state_hash(&key)?;
+            if !in_chain_as_of(db, &mut as_of, &state_hash)? {
+                continue;
+            }
             let canonical = get_block_canonicity(db, &state_hash);
             if let Some(query_canonicity) = query.as_ref().and_then(|q| q.canonical) {
                 if canonical != query_canonicity {
@@ -572,10 +1068,73 @@ impl TransactionsQueryRoot {
             };
         }
 
-        Ok(transactions)
+        sort_by_index(&mut transactions, sort_by, limit);
+        Ok(Some(transactions))
     }
 }
 
+/// Sets the account updates' token symbols with one batched lookup
+fn set_token_symbols(db: &IndexerStore, transactions: &mut [Transaction]) -> anyhow::Result<()> {
+    let mut tokens = vec![];
+    for txn in transactions.iter() {
+        if let Some(zkapp_command) = txn.transaction.zkapp_command.as_ref() {
+            for update in zkapp_command.account_updates.iter() {
+                update.tokens(&mut tokens);
+            }
+        }
+    }
+
+    if tokens.is_empty() {
+        return Ok(());
+    }
+
+    let symbols = TokenSymbols::resolve(db, tokens)?;
+    for txn in transactions.iter_mut() {
+        if let Some(zkapp_command) = txn.transaction.zkapp_command.as_mut() {
+            for update in zkapp_command.account_updates.iter_mut() {
+                update.set_token_symbols(&symbols);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Sorts index-sorted transactions by block height, then index in block &
+/// applies the limit. Blocks at the same height are kept together, canonical
+/// first
+fn sort_by_index(
+    transactions: &mut Vec<Transaction>,
+    sort_by: TransactionSortByInput,
+    limit: usize,
+) {
+    use TransactionSortByInput::*;
+
+    let reverse = match sort_by {
+        IndexAsc => false,
+        IndexDesc => true,
+        _ => return,
+    };
+
+    transactions.sort_by(|a, b| {
+        let mut height = a.transaction.block_height.cmp(&b.transaction.block_height);
+        let mut index = a
+            .transaction
+            .index_in_block
+            .cmp(&b.transaction.index_in_block);
+        if reverse {
+            height = height.reverse();
+            index = index.reverse();
+        }
+
+        height
+            .then(b.transaction.canonical.cmp(&a.transaction.canonical))
+            .then(a.block.state_hash.cmp(&b.block.state_hash))
+            .then(index)
+    });
+    transactions.truncate(limit);
+}
+
 fn calculate_inclusive_height_bounds(
     block_height_gte: Option<u32>,
     block_height_gt: Option<u32>,
@@ -614,13 +1173,14 @@ fn calculate_inclusive_slot_bounds(
     date_time_lt: &Option<DateTime>,
     date_time_lte: &Option<DateTime>,
 ) -> Result<(u32, u32)> {
+    let constants = db.get_chain_constants()?;
     let min_bound = match (
         global_slot_gte.or(date_time_gte
             .as_ref()
-            .map(|dt| millis_to_global_slot(dt.timestamp_millis()))),
+            .map(|dt| constants.millis_to_global_slot(dt.timestamp_millis()))),
         global_slot_gt.or(date_time_gt
             .as_ref()
-            .map(|dt| millis_to_global_slot(dt.timestamp_millis()))),
+            .map(|dt| constants.millis_to_global_slot(dt.timestamp_millis()))),
     ) {
         (Some(gte), Some(gt)) => gte.max(gt.saturating_add(1)),
         (Some(gte), None) => gte,
@@ -634,10 +1194,10 @@ fn calculate_inclusive_slot_bounds(
     let max_bound = match (
         global_slot_lte.or(date_time_lte
             .as_ref()
-            .map(|dt| millis_to_global_slot(dt.timestamp_millis()))),
+            .map(|dt| constants.millis_to_global_slot(dt.timestamp_millis()))),
         global_slot_lt.or(date_time_lt
             .as_ref()
-            .map(|dt| millis_to_global_slot(dt.timestamp_millis()))),
+            .map(|dt| constants.millis_to_global_slot(dt.timestamp_millis()))),
     ) {
         (Some(lte), Some(lt)) => lte.min(lt.saturating_sub(1)),
         (Some(lte), None) => lte,
@@ -690,6 +1250,59 @@ impl TransactionWithoutBlock {
             }
         };
         let is_applied = failure_reason.is_none();
+        let signature_kind = cmd.command.signature_kind();
+        let account_creation_fee = cmd.receiver_account_creation_fee();
+        let zkapp_command = match &cmd.command {
+            SignedCommand::V2(UserCommandData::ZkappCommandData(data)) => {
+                let mut accounts_created = cmd.accounts_created.clone();
+
+                // commands stored without per-update statuses share the
+                // command's status
+                let default_status = if is_applied {
+                    AccountUpdateStatus::Applied
+                } else {
+                    AccountUpdateStatus::Failed
+                };
+                let mut statuses = cmd
+                    .account_update_statuses
+                    .iter()
+                    .copied()
+                    .chain(std::iter::repeat(default_status));
+
+                // commands stored without a summary have no failure detail
+                let summary = cmd
+                    .precondition_summary
+                    .clone()
+                    .unwrap_or_else(|| ZkappPreconditionSummary::from_zkapp_command(data));
+                let mut preconditions = summary.account_updates.into_iter();
+
+                Some(ZkappCommand {
+                    fee_payer_valid_until: summary.fee_payer_valid_until,
+                    fee_payer_nonce: summary.fee_payer_nonce.0,
+                    account_updates: data
+                        .account_updates
+                        .iter()
+                        .map(|update| {
+                            ZkappAccountUpdate::new(
+                                &update.elt,
+                                &mut accounts_created,
+                                &mut statuses,
+                                &mut preconditions,
+                            )
+                        })
+                        .collect(),
+                    failed_precondition_detail: summary
+                        .failed_preconditions
+                        .into_iter()
+                        .map(Into::into)
+                        .collect(),
+                })
+            }
+            _ => None,
+        };
+        let receiver_account_created = zkapp_command
+            .is_none()
+            .then_some(account_creation_fee.is_some());
 
         Self {
             canonical,
@@ -709,12 +1322,133 @@ impl TransactionWithoutBlock {
             },
             to: receiver.first().expect("receiver").0.to_owned(),
             token: cmd.command.fee_token(),
+            index_in_block: cmd.index_in_block,
+            receiver_account_created,
+            account_creation_fee: account_creation_fee.map(|fee| fee.0.to_string()),
+            signature_present: signature_kind.is_some(),
+            signature_kind: signature_kind.map(|kind| kind.to_string()),
+            zkapp_command,
             epoch_num_user_commands,
             total_num_user_commands,
         }
     }
 }
 
+impl ZkappAccountUpdate {
+    /// Builds the account update tree depth-first, taking each update's
+    /// created account from the command's `accounts_created`, its status
+    /// from `statuses` & its checked preconditions from `preconditions`
+    fn new(
+        value: &Elt,
+        accounts_created: &mut Vec<AccountCreated>,
+        statuses: &mut impl Iterator<Item = AccountUpdateStatus>,
+        preconditions: &mut impl Iterator<Item = AccountUpdatePreconditions>,
+    ) -> Self {
+        let body = &value.account_update.body;
+        let account_creation_fee = accounts_created
+            .iter()
+            .position(|created| {
+                created.public_key == body.public_key && created.token == body.token_id
+            })
+            .map(|idx| accounts_created.remove(idx).creation_fee);
+        let status = statuses
+            .next()
+            .unwrap_or(AccountUpdateStatus::Failed)
+            .into();
+        let update_preconditions = preconditions
+            .next()
+            .unwrap_or_else(|| AccountUpdatePreconditions::from(&body.preconditions))
+            .into();
+
+        Self {
+            public_key: body.public_key.0.to_owned(),
+            token_id: body.token_id.0.to_owned(),
+            token_symbol: None,
+            authorization_kind: body.authorization_kind.kind.to_string(),
+            status,
+            account_created: account_creation_fee.is_some(),
+            account_creation_fee: account_creation_fee.map(|fee| fee.0.to_string()),
+            preconditions: update_preconditions,
+            calls: value
+                .calls
+                .iter()
+                .map(|call| Self::new(call.elt.as_ref(), accounts_created, statuses, preconditions))
+                .collect(),
+        }
+    }
+
+    /// Collects the update tree's token ids
+    fn tokens<'a>(&'a self, tokens: &mut Vec<&'a str>) {
+        tokens.push(&self.token_id);
+        for call in self.calls.iter() {
+            call.tokens(tokens);
+        }
+    }
+
+    /// Sets the update tree's token symbols
+    fn set_token_symbols(&mut self, symbols: &TokenSymbols) {
+        self.token_symbol = symbols.get(&self.token_id);
+        for call in self.calls.iter_mut() {
+            call.set_token_symbols(symbols);
+        }
+    }
+}
+
+impl From<AccountUpdateStatus> for ZkappAccountUpdateStatus {
+    fn from(value: AccountUpdateStatus) -> Self {
+        match value {
+            AccountUpdateStatus::Applied => Self::Applied,
+            AccountUpdateStatus::Failed => Self::Failed,
+            AccountUpdateStatus::Cancelled => Self::Cancelled,
+        }
+    }
+}
+
+impl From<AccountUpdatePreconditions> for ZkappAccountUpdatePreconditions {
+    fn from(value: AccountUpdatePreconditions) -> Self {
+        Self {
+            nonce: value.nonce,
+            balance: value.balance,
+            app_state: value.app_state,
+            action_state: value.action_state,
+            receipt_chain_hash: value.receipt_chain_hash,
+            delegate: value.delegate,
+            proved_state: value.proved_state,
+            is_new: value.is_new,
+            network: value.network,
+            valid_while: value.valid_while,
+        }
+    }
+}
+
+impl From<FailedPrecondition> for ZkappFailedPrecondition {
+    fn from(value: FailedPrecondition) -> Self {
+        Self {
+            account_update_index: value.account_update_index,
+            category: value.category.into(),
+            app_state_index: value.app_state_index,
+            checked: value.checked,
+        }
+    }
+}
+
+impl From<PreconditionCategory> for ZkappPreconditionCategory {
+    fn from(value: PreconditionCategory) -> Self {
+        match value {
+            PreconditionCategory::Nonce => Self::Nonce,
+            PreconditionCategory::Balance => Self::Balance,
+            PreconditionCategory::AppState => Self::AppState,
+            PreconditionCategory::ActionState => Self::ActionState,
+            PreconditionCategory::ReceiptChainHash => Self::ReceiptChainHash,
+            PreconditionCategory::Delegate => Self::Delegate,
+            PreconditionCategory::ProvedState => Self::ProvedState,
+            PreconditionCategory::IsNew => Self::IsNew,
+            PreconditionCategory::Network => Self::Network,
+            PreconditionCategory::ValidWhile => Self::ValidWhile,
+        }
+    }
+}
+
 impl TransactionQueryInput {
     #[allow(clippy::too_many_lines)]
     fn matches(&self, transaction: &Transaction) -> bool {
@@ -723,13 +1457,14 @@ impl TransactionQueryInput {
             canonical,
             kind,
             memo,
+            memo_contains,
             from,
             to,
-            fee,
-            fee_gt,
-            fee_gte,
-            fee_lt,
-            fee_lte,
+            fee: _,
+            fee_gt: _,
+            fee_gte: _,
+            fee_lt: _,
+            fee_lte: _,
             fee_token,
             amount,
             amount_gt,
@@ -804,6 +1539,15 @@ impl TransactionQueryInput {
                 return false;
             }
         }
+        if let Some(memo_contains) = memo_contains {
+            if !transaction
+                .transaction
+                .memo
+                .contains(memo_contains.as_str())
+            {
+                return false;
+            }
+        }
         if let Some(fee_token) = fee_token {
             if transaction.transaction.token != Some(*fee_token) {
                 return false;
@@ -862,30 +1606,8 @@ impl TransactionQueryInput {
         }
 
         // fee
-        if let Some(fee) = fee {
-            if transaction.transaction.fee != *fee {
-                return false;
-            }
-        }
-        if let Some(fee_gt) = fee_gt {
-            if transaction.transaction.fee <= *fee_gt {
-                return false;
-            }
-        }
-        if let Some(fee_gte) = fee_gte {
-            if transaction.transaction.fee < *fee_gte {
-                return false;
-            }
-        }
-        if let Some(fee_lt) = fee_lt {
-            if transaction.transaction.fee >= *fee_lt {
-                return false;
-            }
-        }
-        if let Some(fee_lte) = fee_lte {
-            if transaction.transaction.fee > *fee_lte {
-                return false;
-            }
+        if !self.matches_fee(transaction.transaction.fee) {
+            return false;
         }
 
         // block height
@@ -998,6 +1720,36 @@ impl TransactionQueryInput {
         }
         true
     }
+
+    /// Whether the fee satisfies the fee bounds
+    fn matches_fee(&self, txn_fee: u64) -> bool {
+        if let Some(fee) = self.fee {
+            if txn_fee != fee {
+                return false;
+            }
+        }
+        if let Some(fee_gt) = self.fee_gt {
+            if txn_fee <= fee_gt {
+                return false;
+            }
+        }
+        if let Some(fee_gte) = self.fee_gte {
+            if txn_fee < fee_gte {
+                return false;
+            }
+        }
+        if let Some(fee_lt) = self.fee_lt {
+            if txn_fee >= fee_lt {
+                return false;
+            }
+        }
+        if let Some(fee_lte) = self.fee_lte {
+            if txn_fee > fee_lte {
+                return false;
+            }
+        }
+        true
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,69 @@
+use super::db;
+use crate::{
+    base::public_key::PublicKey,
+    identity::{Identity, IdentityConfig, IdentityResolver},
+};
+use async_graphql::{Context, Object, Result, SimpleObject};
+
+#[derive(Default)]
+pub struct UsernameQueryRoot;
+
+/// Username & the public keys registered with it
+#[derive(SimpleObject)]
+pub struct Username {
+    username: String,
+    public_keys: Vec<String>,
+
+    /// Identity provider of the registration, e.g. `on-chain`
+    provider: String,
+}
+
+#[Object]
+impl UsernameQueryRoot {
+    /// Public keys registered with the username
+    async fn resolve_username<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        username: String,
+    ) -> Result<Option<Username>> {
+        Ok(resolver(ctx)
+            .resolve_username(&username)
+            .await?
+            .map(Username::from))
+    }
+
+    /// Username registered by the public key
+    async fn lookup_username<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        public_key: String,
+    ) -> Result<Option<Username>> {
+        if !PublicKey::is_valid(&public_key) {
+            return Err(format!("Invalid public key: {public_key}").into());
+        }
+
+        Ok(resolver(ctx)
+            .lookup_username(&public_key.into())
+            .await?
+            .map(Username::from))
+    }
+}
+
+/// Identity providers of the request's runtime config
+fn resolver(ctx: &Context) -> IdentityResolver {
+    let config = ctx
+        .data_opt::<IdentityConfig>()
+        .cloned()
+        .unwrap_or_default();
+    IdentityResolver::from_config(db(ctx).clone(), &config)
+}
+
+impl From<Identity> for Username {
+    fn from(identity: Identity) -> Self {
+        Self {
+            username: identity.username.0,
+            public_keys: identity.public_keys.into_iter().map(|pk| pk.0).collect(),
+            provider: identity.provider.to_string(),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use super::{db, max_limit};
+use crate::block::validation::{
+    store::ValidationStore, ValidationError as BlockValidationError, ValidationErrorKind as Kind,
+};
+use async_graphql::{Context, Enum, Object, Result, SimpleObject};
+use speedb::Direction;
+
+#[derive(Default)]
+pub struct ValidationQueryRoot;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum ValidationErrorKind {
+    AccountCreatedExists,
+    AccountCreatedMissing,
+    AccountAccessedMissing,
+    AccountAccessedBalance,
+    AccountAccessedNonce,
+    AccountAccessedDelegate,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum ValidationErrorSortByInput {
+    #[graphql(name = "BLOCKHEIGHT_ASC")]
+    BlockHeightAsc,
+
+    #[graphql(name = "BLOCKHEIGHT_DESC")]
+    BlockHeightDesc,
+}
+
+/// Mismatch between a canonical block's created or accessed accounts & the
+/// ledger, recorded in block validation mode
+#[derive(SimpleObject)]
+pub struct ValidationError {
+    state_hash: String,
+    block_height: u32,
+    kind: ValidationErrorKind,
+    public_key: String,
+    token: String,
+
+    /// Value asserted by the block
+    expected: Option<String>,
+
+    /// Value in the ledger
+    actual: Option<String>,
+}
+
+#[Object]
+impl ValidationQueryRoot {
+    /// Recorded block validation errors, most recent first by default
+    async fn validation_errors<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        sort_by: Option<ValidationErrorSortByInput>,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<Vec<ValidationError>> {
+        let direction = match sort_by {
+            Some(ValidationErrorSortByInput::BlockHeightAsc) => Direction::Forward,
+            Some(ValidationErrorSortByInput::BlockHeightDesc) | None => Direction::Reverse,
+        };
+
+        Ok(db(ctx)
+            .get_validation_errors(direction, max_limit(ctx, limit))?
+            .into_iter()
+            .map(ValidationError::from)
+            .collect())
+    }
+}
+
+/////////////////
+// Conversions //
+/////////////////
+
+impl From<Kind> for ValidationErrorKind {
+    fn from(value: Kind) -> Self {
+        match value {
+            Kind::AccountCreatedExists => Self::AccountCreatedExists,
+            Kind::AccountCreatedMissing => Self::AccountCreatedMissing,
+            Kind::AccountAccessedMissing => Self::AccountAccessedMissing,
+            Kind::AccountAccessedBalance => Self::AccountAccessedBalance,
+            Kind::AccountAccessedNonce => Self::AccountAccessedNonce,
+            Kind::AccountAccessedDelegate => Self::AccountAccessedDelegate,
+        }
+    }
+}
+
+impl From<BlockValidationError> for ValidationError {
+    fn from(value: BlockValidationError) -> Self {
+        Self {
+            state_hash: value.state_hash.0,
+            block_height: value.blockchain_length,
+            kind: value.kind.into(),
+            public_key: value.public_key.0,
+            token: value.token.0,
+            expected: value.expected,
+            actual: value.actual,
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use super::{db, max_limit};
+use crate::{
+    base::public_key::PublicKey,
+    ledger::token::TokenAddress,
+    mina_blocks::v2::ActionState,
+    store::zkapp::actions::{ActionStateTransition, ZkappActionStore},
+};
+use async_graphql::{Context, Object, Result, SimpleObject};
+
+#[derive(Default)]
+pub struct ZkappActionsQueryRoot;
+
+/// Actions of a zkapp token account in a best chain block
+#[derive(SimpleObject)]
+pub struct ZkappBlockActions {
+    block_height: u32,
+    state_hash: String,
+
+    /// Index of the block's first action for the account
+    first_action_index: u32,
+
+    actions: Vec<String>,
+
+    /// Action state commitments around the block's actions
+    action_state: ZkappActionState,
+}
+
+/// Rolling action state commitments, most recent first
+#[derive(SimpleObject)]
+pub struct ZkappActionState {
+    before: Vec<String>,
+    after: Vec<String>,
+}
+
+#[Object]
+impl ZkappActionsQueryRoot {
+    /// Per-block actions & action state commitments of the best chain zkapp
+    /// token account, in ascending block height order
+    async fn zkapp_actions<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        public_key: String,
+        token: Option<String>,
+        block_height_gte: Option<u32>,
+        block_height_lte: Option<u32>,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<Vec<ZkappBlockActions>> {
+        let limit = max_limit(ctx, limit);
+        if !PublicKey::is_valid(&public_key) {
+            return Err(format!("Invalid public key: {public_key}").into());
+        }
+
+        let token = match token {
+            Some(token) => TokenAddress::new(&token)
+                .ok_or_else(|| format!("Invalid token address: {token}"))?,
+            None => TokenAddress::default(),
+        };
+
+        let db = db(ctx);
+        let pk: PublicKey = public_key.into();
+
+        let mut blocks = vec![];
+        for transition in db.get_action_state_transitions_range(
+            &pk,
+            &token,
+            block_height_gte.unwrap_or_default(),
+            block_height_lte.unwrap_or(u32::MAX),
+            limit,
+        )? {
+            let mut actions = Vec::with_capacity(transition.num_actions as usize);
+            for index in transition.first_action_index
+                ..transition.first_action_index + transition.num_actions
+            {
+                if let Some(action) = db.get_action(&pk, &token, index)? {
+                    actions.push(action.0);
+                }
+            }
+
+            blocks.push(ZkappBlockActions::new(transition, actions));
+        }
+
+        Ok(blocks)
+    }
+}
+
+impl ZkappBlockActions {
+    fn new(transition: ActionStateTransition, actions: Vec<String>) -> Self {
+        let to_strings = |action_state: [ActionState; 5]| {
+            action_state.into_iter().map(|state| state.0).collect()
+        };
+
+        Self {
+            block_height: transition.blockchain_length,
+            state_hash: transition.state_hash.0,
+            first_action_index: transition.first_action_index,
+            actions,
+            action_state: ZkappActionState {
+                before: to_strings(transition.before),
+                after: to_strings(transition.after),
+            },
+        }
+    }
+}
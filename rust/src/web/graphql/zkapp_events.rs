@@ -0,0 +1,84 @@
+use super::{db, max_limit};
+use crate::{
+    base::public_key::PublicKey,
+    ledger::token::TokenAddress,
+    store::zkapp::events::{ZkappBlockEvents as StoredBlockEvents, ZkappEventStore},
+};
+use async_graphql::{Context, Object, Result, SimpleObject};
+
+#[derive(Default)]
+pub struct ZkappEventsQueryRoot;
+
+/// Events of a zkapp token account in a best chain block
+#[derive(SimpleObject)]
+pub struct ZkappBlockEvents {
+    block_height: u32,
+    state_hash: String,
+
+    /// Index of the block's first event for the account
+    first_event_index: u32,
+
+    events: Vec<String>,
+}
+
+#[Object]
+impl ZkappEventsQueryRoot {
+    /// Per-block events of the best chain zkapp token account, in ascending
+    /// block height order
+    async fn zkapp_events<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        public_key: String,
+        token: Option<String>,
+        block_height_gte: Option<u32>,
+        block_height_lte: Option<u32>,
+        #[graphql(default = 100)] limit: usize,
+    ) -> Result<Vec<ZkappBlockEvents>> {
+        let limit = max_limit(ctx, limit);
+        if !PublicKey::is_valid(&public_key) {
+            return Err(format!("Invalid public key: {public_key}").into());
+        }
+
+        let token = match token {
+            Some(token) => TokenAddress::new(&token)
+                .ok_or_else(|| format!("Invalid token address: {token}"))?,
+            None => TokenAddress::default(),
+        };
+
+        let db = db(ctx);
+        let pk: PublicKey = public_key.into();
+
+        let mut blocks = vec![];
+        for block_events in db.get_block_events_range(
+            &pk,
+            &token,
+            block_height_gte.unwrap_or_default(),
+            block_height_lte.unwrap_or(u32::MAX),
+            limit,
+        )? {
+            let mut events = Vec::with_capacity(block_events.num_events as usize);
+            for index in block_events.first_event_index
+                ..block_events.first_event_index + block_events.num_events
+            {
+                if let Some(event) = db.get_event(&pk, &token, index)? {
+                    events.push(event.0);
+                }
+            }
+
+            blocks.push(ZkappBlockEvents::new(block_events, events));
+        }
+
+        Ok(blocks)
+    }
+}
+
+impl ZkappBlockEvents {
+    fn new(block_events: StoredBlockEvents, events: Vec<String>) -> Self {
+        Self {
+            block_height: block_events.blockchain_length,
+            state_hash: block_events.state_hash.0,
+            first_event_index: block_events.first_event_index,
+            events,
+        }
+    }
+}
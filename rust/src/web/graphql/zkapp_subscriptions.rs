@@ -0,0 +1,107 @@
+use super::db;
+use crate::{
+    base::public_key::PublicKey,
+    ledger::token::TokenAddress,
+    store::zkapp::subscriptions::{ZkappNotification, ZkappNotificationKind},
+};
+use async_graphql::{Context, Enum, Result, SimpleObject, Subscription};
+use futures_util::{stream, Stream};
+
+#[derive(Default)]
+pub struct ZkappSubscriptionRoot;
+
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZkappEventKind {
+    Event,
+    Action,
+}
+
+/// Event or action emitted by a zkapp token account in a canonical block
+#[derive(SimpleObject)]
+pub struct ZkappEventNotification {
+    state_hash: String,
+    block_height: u32,
+
+    /// Emitting zkapp command's hash
+    txn_hash: Option<String>,
+
+    /// Emitting zkapp command's index in the block
+    txn_index: u32,
+
+    kind: ZkappEventKind,
+
+    /// Index among the account's events/actions of this kind in the zkapp
+    /// command
+    index: u32,
+
+    data: Vec<String>,
+
+    /// Account's action state commitments after the block, most recent
+    /// first
+    action_state_after: Option<Vec<String>>,
+
+    /// Whether the block is no longer canonical, i.e. the previously
+    /// delivered notification should be reverted
+    removed: bool,
+}
+
+#[Subscription]
+impl ZkappSubscriptionRoot {
+    /// Stream of the zkapp token account's events & actions as their blocks
+    /// become canonical, & their removals when reorged out
+    async fn zkapp_events<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        public_key: String,
+        token: Option<String>,
+    ) -> Result<impl Stream<Item = ZkappEventNotification>> {
+        if !PublicKey::is_valid(&public_key) {
+            return Err(format!("Invalid public key: {public_key}").into());
+        }
+
+        let token = match token {
+            Some(token) => TokenAddress::new(&token)
+                .ok_or_else(|| format!("Invalid token address: {token}"))?,
+            None => TokenAddress::default(),
+        };
+
+        let subscription = db(ctx).subscribe_zkapp_events(public_key.into(), token);
+        Ok(stream::unfold(
+            subscription,
+            |mut subscription| async move {
+                subscription.next().await.map(|notification| {
+                    (ZkappEventNotification::from(&*notification), subscription)
+                })
+            },
+        ))
+    }
+}
+
+/////////////////
+// conversions //
+/////////////////
+
+impl From<ZkappNotificationKind> for ZkappEventKind {
+    fn from(value: ZkappNotificationKind) -> Self {
+        match value {
+            ZkappNotificationKind::Event => Self::Event,
+            ZkappNotificationKind::Action => Self::Action,
+        }
+    }
+}
+
+impl From<&ZkappNotification> for ZkappEventNotification {
+    fn from(value: &ZkappNotification) -> Self {
+        Self {
+            state_hash: value.state_hash.to_string(),
+            block_height: value.blockchain_length,
+            txn_hash: value.txn_hash.clone(),
+            txn_index: value.txn_index,
+            kind: value.kind.into(),
+            index: value.index,
+            data: value.data.clone(),
+            action_state_after: value.action_state_after.clone(),
+            removed: value.removed,
+        }
+    }
+}
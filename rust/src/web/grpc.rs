@@ -0,0 +1,348 @@
+//! gRPC API
+//!
+//! Typed block, transaction, account & staking queries served alongside
+//! GraphQL, see `proto/mina_indexer/v1/indexer.proto`. Invalid arguments are
+//! `INVALID_ARGUMENT`, missing entities `NOT_FOUND` & store errors `INTERNAL`.
+
+use crate::{
+    base::{public_key::PublicKey, state_hash::StateHash},
+    block::{precomputed::PrecomputedBlock, store::BlockStore},
+    canonicity::{store::CanonicityStore, Canonicity},
+    command::{signed::SignedCommandWithData, store::UserCommandStore, TxnHash},
+    constants::GRPC_MAX_PAGE_SIZE,
+    ledger::{
+        store::{best::BestLedgerStore, staking::StakingLedgerStore},
+        token::TokenAddress,
+    },
+    store::IndexerStore,
+};
+use anyhow::Context;
+use log::error;
+use speedb::Direction;
+use std::{net::ToSocketAddrs, sync::Arc};
+use tokio_graceful_shutdown::SubsystemHandle;
+use tonic::{transport::Server, Request, Response, Status};
+
+/// Generated service & message types
+pub mod proto {
+    tonic::include_proto!("mina_indexer.v1");
+}
+
+use proto::mina_indexer_server::{MinaIndexer, MinaIndexerServer};
+
+/// gRPC service of the store's queries
+pub struct GrpcService {
+    store: Arc<IndexerStore>,
+}
+
+/// Serves the gRPC API until shutdown
+pub async fn start_grpc_server<A: ToSocketAddrs>(
+    subsys: SubsystemHandle,
+    store: Arc<IndexerStore>,
+    addrs: A,
+) -> anyhow::Result<()> {
+    let addr = addrs
+        .to_socket_addrs()?
+        .next()
+        .context("gRPC server address did not resolve")?;
+
+    Server::builder()
+        .add_service(MinaIndexerServer::new(GrpcService::new(store)))
+        .serve_with_shutdown(addr, subsys.on_shutdown_requested())
+        .await?;
+
+    Ok(())
+}
+
+impl GrpcService {
+    pub fn new(store: Arc<IndexerStore>) -> Self {
+        Self { store }
+    }
+
+    fn block(&self, block: &PrecomputedBlock) -> Result<proto::Block, Status> {
+        let state_hash = block.state_hash();
+        let canonicity = self
+            .store
+            .get_block_canonicity(&state_hash)
+            .map_err(internal)?;
+
+        Ok(proto::Block {
+            state_hash: state_hash.0,
+            previous_state_hash: block.previous_state_hash().0,
+            genesis_state_hash: block.genesis_state_hash().0,
+            blockchain_length: block.blockchain_length(),
+            global_slot_since_genesis: block.global_slot_since_genesis(),
+            epoch: block.epoch_count(),
+            timestamp: block.timestamp(),
+            block_creator: block.block_creator().0,
+            coinbase_receiver: block.coinbase_receiver().0,
+            canonicity: proto_canonicity(canonicity).into(),
+            num_user_commands: block.commands().len() as u32,
+            tx_fees: block.tx_fees(),
+            snark_fees: block.snark_fees(),
+            total_currency: block.total_currency(),
+        })
+    }
+
+    fn transaction(&self, cmd: SignedCommandWithData) -> Result<proto::Transaction, Status> {
+        let canonicity = self
+            .store
+            .get_block_canonicity(&cmd.state_hash)
+            .map_err(internal)?;
+
+        Ok(proto::Transaction {
+            txn_hash: cmd.tx_hash.to_string(),
+            kind: cmd.command.kind().to_string(),
+            state_hash: cmd.state_hash.0,
+            blockchain_length: cmd.blockchain_length,
+            global_slot_since_genesis: cmd.global_slot_since_genesis,
+            date_time: cmd.date_time,
+            sender: cmd.command.source_pk().0,
+            receivers: cmd
+                .command
+                .receiver_pk()
+                .into_iter()
+                .map(|pk| pk.0)
+                .collect(),
+            amount: cmd.command.amount(),
+            fee: cmd.command.fee(),
+            nonce: cmd.nonce.0,
+            memo: cmd.command.memo(),
+            applied: cmd.status.is_applied(),
+            failure: cmd.status.failure_kind(),
+            canonicity: proto_canonicity(canonicity).into(),
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl MinaIndexer for GrpcService {
+    async fn get_block(
+        &self,
+        request: Request<proto::GetBlockRequest>,
+    ) -> Result<Response<proto::Block>, Status> {
+        let block = match request.into_inner().state_hash {
+            Some(state_hash) => {
+                let state_hash = state_hash_arg(state_hash)?;
+                self.store
+                    .get_block(&state_hash)
+                    .map_err(internal)?
+                    .map(|(block, _)| block)
+            }
+            None => self.store.get_best_block().map_err(internal)?,
+        };
+
+        match block {
+            Some(block) => Ok(Response::new(self.block(&block)?)),
+            None => Err(Status::not_found("Block not found")),
+        }
+    }
+
+    async fn list_canonical_blocks(
+        &self,
+        request: Request<proto::ListCanonicalBlocksRequest>,
+    ) -> Result<Response<proto::ListBlocksResponse>, Status> {
+        let request = request.into_inner();
+        let to_height = match request.to_height {
+            Some(to_height) => to_height,
+            None => self
+                .store
+                .get_best_block_height()
+                .map_err(internal)?
+                .unwrap_or_default(),
+        };
+        let limit = page_size(request.limit);
+
+        let mut blocks = vec![];
+        for height in request.from_height..=to_height {
+            if blocks.len() >= limit {
+                break;
+            }
+
+            let Some(state_hash) = self
+                .store
+                .get_canonical_hash_at_height(height)
+                .map_err(internal)?
+            else {
+                continue;
+            };
+
+            if let Some((block, _)) = self.store.get_block(&state_hash).map_err(internal)? {
+                blocks.push(self.block(&block)?);
+            }
+        }
+
+        Ok(Response::new(proto::ListBlocksResponse { blocks }))
+    }
+
+    async fn get_transaction(
+        &self,
+        request: Request<proto::GetTransactionRequest>,
+    ) -> Result<Response<proto::GetTransactionResponse>, Status> {
+        let txn_hash = request.into_inner().txn_hash;
+        let txn_hash = TxnHash::new(txn_hash.clone())
+            .map_err(|_| Status::invalid_argument(format!("Invalid txn hash: {txn_hash}")))?;
+
+        let Some(state_hashes) = self
+            .store
+            .get_user_command_state_hashes(&txn_hash)
+            .map_err(internal)?
+        else {
+            return Err(Status::not_found(format!(
+                "Transaction not found: {txn_hash}"
+            )));
+        };
+
+        let mut transactions = vec![];
+        for state_hash in state_hashes.iter() {
+            if let Some(cmd) = self
+                .store
+                .get_user_command_state_hash(&txn_hash, state_hash)
+                .map_err(internal)?
+            {
+                transactions.push(self.transaction(cmd)?);
+            }
+        }
+
+        Ok(Response::new(proto::GetTransactionResponse {
+            transactions,
+        }))
+    }
+
+    async fn get_account(
+        &self,
+        request: Request<proto::GetAccountRequest>,
+    ) -> Result<Response<proto::Account>, Status> {
+        let request = request.into_inner();
+        let pk = public_key_arg(request.public_key)?;
+        let token = match request.token {
+            Some(token) => TokenAddress::new(token.clone())
+                .ok_or_else(|| Status::invalid_argument(format!("Invalid token: {token}")))?,
+            None => TokenAddress::default(),
+        };
+
+        let Some(account) = self
+            .store
+            .get_best_account_display(&pk, &token)
+            .map_err(internal)?
+        else {
+            return Err(Status::not_found(format!("Account not found: {pk}")));
+        };
+
+        Ok(Response::new(proto::Account {
+            public_key: account.public_key.0,
+            token: token.0,
+            balance: account.balance.0,
+            nonce: account.nonce.unwrap_or_default().0,
+            delegate: account.delegate.0,
+            username: account.username.map(|username| username.0),
+            genesis_account: account.genesis_account,
+        }))
+    }
+
+    async fn get_staking_account(
+        &self,
+        request: Request<proto::GetStakingAccountRequest>,
+    ) -> Result<Response<proto::StakingAccount>, Status> {
+        let request = request.into_inner();
+        let pk = public_key_arg(request.public_key)?;
+        let genesis_state_hash = request.genesis_state_hash.map(state_hash_arg).transpose()?;
+
+        let Some(account) = self
+            .store
+            .get_staking_account(&pk, request.epoch, genesis_state_hash.as_ref())
+            .map_err(internal)?
+        else {
+            return Err(Status::not_found(format!(
+                "Staking account not found: {pk} epoch {}",
+                request.epoch
+            )));
+        };
+
+        Ok(Response::new(proto::StakingAccount {
+            public_key: account.pk.0,
+            epoch: request.epoch,
+            balance: account.balance,
+            delegate: account.delegate.0,
+            nonce: account.nonce.unwrap_or_default().0,
+            username: account.username,
+            timed: account.timing.is_some(),
+        }))
+    }
+
+    async fn list_delegators(
+        &self,
+        request: Request<proto::ListDelegatorsRequest>,
+    ) -> Result<Response<proto::ListDelegatorsResponse>, Status> {
+        let request = request.into_inner();
+        let delegate = public_key_arg(request.delegate)?;
+        let genesis_state_hash = request.genesis_state_hash.map(state_hash_arg).transpose()?;
+
+        let delegators = self
+            .store
+            .get_epoch_delegators(
+                &delegate,
+                request.epoch,
+                genesis_state_hash.as_ref(),
+                None,
+                Direction::Reverse,
+                page_size(request.limit),
+            )
+            .map_err(internal)?;
+
+        Ok(Response::new(proto::ListDelegatorsResponse {
+            total_delegated: delegators
+                .first()
+                .map_or(0, |delegator| delegator.total_delegated),
+            delegate: delegate.0,
+            epoch: request.epoch,
+            delegators: delegators
+                .into_iter()
+                .map(|delegator| proto::Delegator {
+                    public_key: delegator.pk.0,
+                    balance: delegator.balance,
+                    prev_delegate: delegator.prev_delegate.map(|pk| pk.0),
+                    delegation_changed: delegator.delegation_changed,
+                })
+                .collect(),
+        }))
+    }
+}
+
+fn proto_canonicity(canonicity: Option<Canonicity>) -> proto::Canonicity {
+    match canonicity {
+        Some(Canonicity::Canonical) => proto::Canonicity::Canonical,
+        Some(Canonicity::Orphaned) => proto::Canonicity::Orphaned,
+        Some(Canonicity::Pending) => proto::Canonicity::Pending,
+        None => proto::Canonicity::Unspecified,
+    }
+}
+
+fn page_size(limit: Option<u32>) -> usize {
+    limit.map_or(GRPC_MAX_PAGE_SIZE, |limit| {
+        (limit as usize).min(GRPC_MAX_PAGE_SIZE)
+    })
+}
+
+fn public_key_arg(pk: String) -> Result<PublicKey, Status> {
+    if !PublicKey::is_valid(&pk) {
+        return Err(Status::invalid_argument(format!(
+            "Invalid public key: {pk}"
+        )));
+    }
+    Ok(pk.into())
+}
+
+fn state_hash_arg(state_hash: String) -> Result<StateHash, Status> {
+    if !StateHash::is_valid(&state_hash) {
+        return Err(Status::invalid_argument(format!(
+            "Invalid state hash: {state_hash}"
+        )));
+    }
+    Ok(state_hash.into())
+}
+
+fn internal(e: anyhow::Error) -> Status {
+    error!("gRPC store error: {e}");
+    Status::internal(e.to_string())
+}
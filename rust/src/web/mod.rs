@@ -1,20 +1,56 @@
+pub mod feed;
 pub mod graphql;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod public_api;
 pub mod rest;
+pub mod runtime_config;
 
 pub const ENDPOINT_GRAPHQL: &str = "/graphql";
 
 use self::{
-    graphql::{build_schema, indexer_graphiql},
-    rest::{accounts, blockchain, blocks, locked_balances::LockedBalances},
+    feed::{block_feed, ENDPOINT_BLOCK_FEED},
+    graphql::{build_schema_with_options, indexer_graphiql, IndexerSchema},
+    public_api::{PublicApi, PublicApiOptions},
+    rest::{
+        accounts, blockchain, blocks, bootstrap,
+        health::{self, LAG_BLOCKS_HEADER},
+        locked_balances::LockedBalances,
+    },
+    runtime_config::SharedRuntimeConfig,
 };
-use crate::store::IndexerStore;
+use crate::{chain::Network, store::IndexerStore};
 use actix_cors::Cors;
-use actix_web::{guard, middleware, web, web::Data, App, HttpServer};
-use async_graphql_actix_web::GraphQL;
+use actix_web::{
+    dev::Service,
+    guard,
+    http::header::{HeaderName, HeaderValue},
+    middleware, web,
+    web::Data,
+    App, HttpRequest, HttpResponse, HttpServer,
+};
+use async_graphql_actix_web::{GraphQLBatchRequest, GraphQLResponse, GraphQLSubscription};
 use log::warn;
+use serde::Deserialize;
 use std::{net, sync::Arc};
 use tokio_graceful_shutdown::{FutureExt, SubsystemHandle};
 
+#[derive(Debug, Clone, Default)]
+pub struct WebServerOptions {
+    /// Reloadable settings, see [runtime_config]
+    pub runtime_config: SharedRuntimeConfig,
+
+    /// Max number of blocks a read-only replica can lag its primary & be
+    /// ready
+    pub max_replication_lag_blocks: Option<u32>,
+
+    /// Add the [LAG_BLOCKS_HEADER] to GraphQL responses
+    pub lag_header: bool,
+
+    /// Rate & size limits of a publicly exposed web server, see [public_api]
+    pub public_api: Option<PublicApiOptions>,
+}
+
 fn load_locked_balances() -> LockedBalances {
     match LockedBalances::new() {
         Ok(locked_balances) => locked_balances,
@@ -25,31 +61,140 @@ fn load_locked_balances() -> LockedBalances {
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct NetworkQuery {
+    /// Network namespace queried, e.g. `/graphql?network=devnet`
+    network: Option<String>,
+}
+
+/// GraphQL queries, against the selected network's store namespace, under
+/// the runtime config current when the request arrived
+async fn graphql(
+    schema: Data<IndexerSchema>,
+    store: Data<Arc<IndexerStore>>,
+    options: Data<WebServerOptions>,
+    query: web::Query<NetworkQuery>,
+    req: GraphQLBatchRequest,
+) -> actix_web::Result<GraphQLResponse> {
+    let config = options.runtime_config.load();
+    let mut req = req
+        .into_inner()
+        .data(config.graphql)
+        .data(config.identity.clone());
+    if let Some(public_api) = options.public_api {
+        req = req.data(public_api);
+    }
+
+    if let Some(network) = query.into_inner().network {
+        let store = store
+            .network_store(&Network::from(network.as_str()))
+            .map_err(actix_web::error::ErrorBadRequest)?;
+        req = req.data(store);
+    }
+
+    Ok(schema.execute_batch(req).await.into())
+}
+
+/// GraphiQL playground, unless disabled
+async fn graphiql(options: Data<WebServerOptions>) -> actix_web::Result<HttpResponse> {
+    if !options.runtime_config.load().graphiql {
+        return Ok(HttpResponse::NotFound().finish());
+    }
+
+    indexer_graphiql().await
+}
+
+/// GraphQL subscriptions over websocket
+async fn graphql_subscription(
+    schema: Data<IndexerSchema>,
+    req: HttpRequest,
+    payload: web::Payload,
+) -> actix_web::Result<HttpResponse> {
+    GraphQLSubscription::new(schema.as_ref().clone()).start(&req, payload)
+}
+
 pub async fn start_web_server<A: net::ToSocketAddrs>(
     subsys: SubsystemHandle,
     state: Arc<IndexerStore>,
     addrs: A,
+    options: WebServerOptions,
 ) -> anyhow::Result<()> {
     let locked = Arc::new(load_locked_balances());
+    let public_api = options
+        .public_api
+        .map(|options| Arc::new(PublicApi::new(options)));
 
     let _ = HttpServer::new(move || {
+        let schema =
+            build_schema_with_options(state.clone(), options.runtime_config.load().graphql);
         App::new()
             .app_data(Data::new(state.clone()))
+            .app_data(Data::new(schema))
             .app_data(Data::new(locked.clone()))
+            .app_data(Data::new(options.clone()))
             .service(blocks::get_blocks)
             .service(blocks::get_block_by_state_hash)
             .service(accounts::get_account)
             .service(blockchain::get_blockchain_summary)
+            .service(bootstrap::get_bootstrap)
+            .service(health::get_health)
+            .service(health::get_ready)
             .service(
                 web::resource(ENDPOINT_GRAPHQL)
                     .guard(guard::Post())
-                    .to(GraphQL::new(build_schema(state.clone()))),
+                    .wrap_fn({
+                        let state = state.clone();
+                        let lag_header = options.lag_header;
+                        move |req, srv| {
+                            let lag_blocks = lag_header
+                                .then(|| health::lag_blocks_header_value(&state))
+                                .flatten();
+                            let res = srv.call(req);
+                            async move {
+                                let mut res = res.await?;
+                                if let Some(lag_blocks) = lag_blocks {
+                                    res.headers_mut().insert(
+                                        HeaderName::from_static(LAG_BLOCKS_HEADER),
+                                        HeaderValue::from(lag_blocks),
+                                    );
+                                }
+                                Ok(res)
+                            }
+                        }
+                    })
+                    .to(graphql),
+            )
+            .service(
+                web::resource(ENDPOINT_GRAPHQL)
+                    .guard(guard::Get())
+                    .guard(guard::Header("upgrade", "websocket"))
+                    .to(graphql_subscription),
             )
             .service(
                 web::resource(ENDPOINT_GRAPHQL)
                     .guard(guard::Get())
-                    .to(indexer_graphiql),
+                    .to(graphiql),
             )
+            .service(
+                web::resource(ENDPOINT_BLOCK_FEED)
+                    .guard(guard::Get())
+                    .to(block_feed),
+            )
+            .wrap_fn({
+                let public_api = public_api.clone();
+                move |req, srv| {
+                    let res = match public_api.as_ref().and_then(|api| api.check(&req)) {
+                        Some(rejection) => Err(req.into_response(rejection)),
+                        None => Ok(srv.call(req)),
+                    };
+                    async move {
+                        match res {
+                            Ok(res) => Ok(res.await?.map_into_left_body()),
+                            Err(rejection) => Ok(rejection.map_into_right_body()),
+                        }
+                    }
+                }
+            })
             .wrap(Cors::permissive())
             .wrap(middleware::Logger::default())
     })
@@ -61,3 +206,15 @@ pub async fn start_web_server<A: net::ToSocketAddrs>(
 
     Ok(())
 }
+
+#[cfg(feature = "grpc")]
+pub use grpc::start_grpc_server;
+
+#[cfg(not(feature = "grpc"))]
+pub async fn start_grpc_server<A: net::ToSocketAddrs>(
+    _subsys: SubsystemHandle,
+    _state: Arc<IndexerStore>,
+    _addrs: A,
+) -> anyhow::Result<()> {
+    anyhow::bail!("Cannot serve the gRPC API, built without the grpc feature")
+}
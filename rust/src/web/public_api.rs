@@ -0,0 +1,394 @@
+//! Public API mode
+//!
+//! Lets the web server be exposed publicly without a separate proxy:
+//! - each client IP (IPv6 /64 prefix) is rate limited by a token bucket,
+//!   refilled at the configured requests per minute & holding up to the burst
+//!   size
+//! - clients beyond the tracked max share a single overflow bucket
+//! - requests without a client address are rejected
+//! - request bodies are capped at the max request bytes
+//! - GraphQL queries are capped at the max query depth & complexity, on top
+//!   of the [RuntimeConfig](super::runtime_config::RuntimeConfig) limits
+//!
+//! Rejected requests get GraphQL style JSON errors, e.g.
+//!
+//! ```json
+//! {
+//!   "errors": [{
+//!     "message": "Rate limit exceeded, retry in 2s",
+//!     "extensions": { "code": "RATE_LIMITED", "retryAfter": 2 }
+//!   }]
+//! }
+//! ```
+//!
+//! with a `Retry-After` header on `429 Too Many Requests` responses.
+//!
+//! Behind a trusted reverse proxy, clients are identified by the last
+//! `X-Forwarded-For` address, i.e. the one appended by the proxy.
+
+use actix_web::{
+    dev::ServiceRequest,
+    http::{
+        header::{HeaderMap, CONTENT_LENGTH, RETRY_AFTER},
+        Method, StatusCode,
+    },
+    HttpResponse,
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv6Addr},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Max number of client buckets tracked, further clients share the overflow
+/// bucket
+const MAX_CLIENTS: usize = 100_000;
+
+/// Interval between sweeps of the full (idle) client buckets
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Public API limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicApiOptions {
+    /// Requests allowed per client IP per minute
+    pub rate_limit_per_min: u32,
+
+    /// Requests a client IP can make in a burst
+    pub rate_limit_burst: u32,
+
+    /// Max size of a request body
+    pub max_request_bytes: usize,
+
+    /// Reject GraphQL queries nested deeper than this
+    pub max_query_depth: usize,
+
+    /// Reject GraphQL queries more complex than this
+    pub max_query_complexity: usize,
+
+    /// Identify clients by the `X-Forwarded-For` address appended by a
+    /// trusted reverse proxy
+    #[serde(default)]
+    pub trust_forwarded_for: bool,
+}
+
+/// Per-IP token bucket rate limiter
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Tokens refilled per second
+    rate: f64,
+
+    /// Bucket capacity
+    burst: f64,
+
+    /// Max number of client buckets
+    max_clients: usize,
+
+    buckets: Mutex<Buckets>,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    clients: HashMap<IpAddr, Bucket>,
+
+    /// Shared by the clients beyond `max_clients`
+    overflow: Bucket,
+
+    /// Time of the last sweep
+    swept: Instant,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Public API gate of the web server's requests
+#[derive(Debug)]
+pub struct PublicApi {
+    pub options: PublicApiOptions,
+    limiter: RateLimiter,
+}
+
+impl RateLimiter {
+    pub fn new(rate_limit_per_min: u32, rate_limit_burst: u32) -> Self {
+        Self::with_max_clients(rate_limit_per_min, rate_limit_burst, MAX_CLIENTS)
+    }
+
+    fn with_max_clients(
+        rate_limit_per_min: u32,
+        rate_limit_burst: u32,
+        max_clients: usize,
+    ) -> Self {
+        let burst = rate_limit_burst.max(1) as f64;
+        let now = Instant::now();
+        Self {
+            rate: rate_limit_per_min as f64 / 60.0,
+            burst,
+            max_clients,
+            buckets: Mutex::new(Buckets {
+                clients: HashMap::new(),
+                overflow: Bucket {
+                    tokens: burst,
+                    updated: now,
+                },
+                swept: now,
+            }),
+        }
+    }
+
+    /// Takes a token from the client's bucket, otherwise the time until one
+    /// is available
+    pub fn check(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+        let key = client_key(ip);
+        let mut guard = self.buckets.lock().expect("rate limiter lock");
+        let buckets = &mut *guard;
+
+        // full buckets are the same as new ones, drop them periodically
+        if now.saturating_duration_since(buckets.swept) >= SWEEP_INTERVAL {
+            buckets
+                .clients
+                .retain(|_, bucket| self.refill(bucket, now) < self.burst);
+            buckets.swept = now;
+        }
+
+        let bucket = if buckets.clients.len() < self.max_clients {
+            buckets.clients.entry(key).or_insert(Bucket {
+                tokens: self.burst,
+                updated: now,
+            })
+        } else {
+            match buckets.clients.get_mut(&key) {
+                Some(bucket) => bucket,
+                None => &mut buckets.overflow,
+            }
+        };
+        bucket.tokens = self.refill(bucket, now);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+
+        if self.rate == 0.0 {
+            return Err(Duration::MAX);
+        }
+        Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate))
+    }
+
+    fn refill(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * self.rate).min(self.burst)
+    }
+}
+
+/// Rate limiting key of the client, IPv6 clients are keyed by their /64
+/// prefix
+fn client_key(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(_) => ip,
+        IpAddr::V6(ipv6) => match ipv6.to_ipv4_mapped() {
+            Some(ipv4) => IpAddr::V4(ipv4),
+            None => IpAddr::V6(Ipv6Addr::from(u128::from(ipv6) & (u128::MAX << 64))),
+        },
+    }
+}
+
+/// Last `X-Forwarded-For` address, i.e. the one appended by the proxy
+fn forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get_all("x-forwarded-for")
+        .last()?
+        .to_str()
+        .ok()?
+        .rsplit(',')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+impl PublicApi {
+    pub fn new(options: PublicApiOptions) -> Self {
+        Self {
+            limiter: RateLimiter::new(options.rate_limit_per_min, options.rate_limit_burst),
+            options,
+        }
+    }
+
+    /// Rejection response of the request, if it exceeds a limit
+    pub fn check(&self, req: &ServiceRequest) -> Option<HttpResponse> {
+        let forwarded = self
+            .options
+            .trust_forwarded_for
+            .then(|| forwarded_for(req.headers()))
+            .flatten();
+
+        match forwarded.or(req.peer_addr().map(|addr| addr.ip())) {
+            Some(ip) => {
+                if let Err(retry_after) = self.limiter.check(ip, Instant::now()) {
+                    return Some(rate_limited(retry_after));
+                }
+            }
+            None => {
+                warn!("Rejecting public API request without a client address");
+                return Some(error_response(
+                    StatusCode::BAD_REQUEST,
+                    "UNKNOWN_CLIENT",
+                    "Client address unknown".to_string(),
+                    json!({}),
+                ));
+            }
+        }
+
+        let max_bytes = self.options.max_request_bytes;
+        match req
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok()?.parse::<usize>().ok())
+        {
+            Some(len) if len > max_bytes => Some(error_response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "PAYLOAD_TOO_LARGE",
+                format!("Request body exceeds {max_bytes} bytes"),
+                json!({ "maxRequestBytes": max_bytes }),
+            )),
+            None if has_body(req.method()) => Some(error_response(
+                StatusCode::LENGTH_REQUIRED,
+                "LENGTH_REQUIRED",
+                "Request body length required".to_string(),
+                json!({ "maxRequestBytes": max_bytes }),
+            )),
+            _ => None,
+        }
+    }
+}
+
+fn has_body(method: &Method) -> bool {
+    [Method::POST, Method::PUT, Method::PATCH].contains(method)
+}
+
+/// `429 Too Many Requests`, retry after the whole number of seconds
+pub fn rate_limited(retry_after: Duration) -> HttpResponse {
+    let secs = retry_after.as_secs_f64().ceil().min(u32::MAX as f64) as u64;
+    let mut response = error_response(
+        StatusCode::TOO_MANY_REQUESTS,
+        "RATE_LIMITED",
+        format!("Rate limit exceeded, retry in {secs}s"),
+        json!({ "retryAfter": secs }),
+    );
+
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, secs.to_string().parse().expect("retry after"));
+    response
+}
+
+fn error_response(
+    status: StatusCode,
+    code: &str,
+    message: String,
+    mut extensions: serde_json::Value,
+) -> HttpResponse {
+    extensions["code"] = code.into();
+    HttpResponse::build(status).json(json!({
+        "errors": [{
+            "message": message,
+            "extensions": extensions,
+        }]
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_bucket() {
+        let limiter = RateLimiter::new(60, 2);
+        let ip: IpAddr = [127, 0, 0, 1].into();
+        let other: IpAddr = [127, 0, 0, 2].into();
+        let start = Instant::now();
+
+        // burst
+        assert!(limiter.check(ip, start).is_ok());
+        assert!(limiter.check(ip, start).is_ok());
+        assert_eq!(limiter.check(ip, start), Err(Duration::from_secs(1)));
+
+        // other clients are unaffected
+        assert!(limiter.check(other, start).is_ok());
+
+        // refilled at 1 request per sec
+        let later = start + Duration::from_millis(500);
+        assert_eq!(limiter.check(ip, later), Err(Duration::from_millis(500)));
+        assert!(limiter.check(ip, start + Duration::from_secs(1)).is_ok());
+
+        // capped at the burst
+        let much_later = start + Duration::from_secs(60);
+        assert!(limiter.check(ip, much_later).is_ok());
+        assert!(limiter.check(ip, much_later).is_ok());
+        assert!(limiter.check(ip, much_later).is_err());
+    }
+
+    #[test]
+    fn ipv6_clients_keyed_by_prefix() {
+        let limiter = RateLimiter::new(60, 1);
+        let ip: IpAddr = "2001:db8::1".parse().unwrap();
+        let same_prefix: IpAddr = "2001:db8::ffff:2".parse().unwrap();
+        let other_prefix: IpAddr = "2001:db8:0:1::1".parse().unwrap();
+        let start = Instant::now();
+
+        assert!(limiter.check(ip, start).is_ok());
+        assert!(limiter.check(same_prefix, start).is_err());
+        assert!(limiter.check(other_prefix, start).is_ok());
+
+        // IPv4-mapped clients are keyed by their IPv4 address
+        let ipv4: IpAddr = [127, 0, 0, 1].into();
+        let mapped: IpAddr = "::ffff:127.0.0.1".parse().unwrap();
+        assert!(limiter.check(ipv4, start).is_ok());
+        assert!(limiter.check(mapped, start).is_err());
+    }
+
+    #[test]
+    fn overflow_bucket() {
+        let limiter = RateLimiter::with_max_clients(60, 1, 2);
+        let ip = |n: u8| IpAddr::from([10, 0, 0, n]);
+        let start = Instant::now();
+
+        assert!(limiter.check(ip(1), start).is_ok());
+        assert!(limiter.check(ip(2), start).is_ok());
+
+        // further clients share a bucket
+        assert!(limiter.check(ip(3), start).is_ok());
+        assert!(limiter.check(ip(4), start).is_err());
+
+        // full buckets are swept
+        let later = start + SWEEP_INTERVAL;
+        assert!(limiter.check(ip(4), later).is_ok());
+        assert_eq!(limiter.buckets.lock().unwrap().clients.len(), 1);
+    }
+
+    #[test]
+    fn forwarded_for_last_address() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(forwarded_for(&headers), None);
+
+        headers.insert(
+            "x-forwarded-for".parse().unwrap(),
+            "1.1.1.1, 2.2.2.2".parse().unwrap(),
+        );
+        assert_eq!(forwarded_for(&headers), Some([2, 2, 2, 2].into()));
+    }
+
+    #[test]
+    fn rate_limited_response() {
+        let response = rate_limited(Duration::from_millis(1500));
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers().get(RETRY_AFTER).unwrap(), "2");
+    }
+}
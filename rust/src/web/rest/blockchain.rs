@@ -7,6 +7,7 @@ use crate::{
     ledger::store::best::BestLedgerStore,
     snark_work::store::SnarkStore,
     store::{
+        replication::ReplicationLag,
         version::{IndexerStoreVersion, VersionStore},
         IndexerStore,
     },
@@ -15,7 +16,7 @@ use crate::{
 };
 use actix_web::{get, http::header::ContentType, web::Data, HttpResponse};
 use chrono::DateTime;
-use log::trace;
+use log::{error, trace};
 use serde::Serialize;
 use std::sync::Arc;
 
@@ -57,6 +58,10 @@ pub struct BlockchainSummary {
     total_num_canonical_internal_commands: u32,
     db_version: String,
     indexer_version: String,
+
+    /// Only reported by read-only replicas
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replication_lag: Option<ReplicationLag>,
 }
 
 fn millis_to_date_string(millis: i64) -> String {
@@ -87,6 +92,7 @@ struct SummaryInput {
     total_num_internal_commands: u32,
     total_num_canonical_internal_commands: u32,
     total_num_accounts: u32,
+    replication_lag: Option<ReplicationLag>,
 }
 
 fn calculate_summary(input: SummaryInput) -> Option<BlockchainSummary> {
@@ -112,6 +118,7 @@ fn calculate_summary(input: SummaryInput) -> Option<BlockchainSummary> {
         total_num_internal_commands,
         total_num_canonical_internal_commands,
         total_num_accounts,
+        replication_lag,
     } = input;
     let blockchain_length = best_tip.blockchain_length();
     let date_time = millis_to_date_string(best_tip.timestamp() as i64);
@@ -128,7 +135,8 @@ fn calculate_summary(input: SummaryInput) -> Option<BlockchainSummary> {
     let total_currency_u64 = best_tip.total_currency();
     let locked_currency_u64 = locked_balance.map(|a| a.0).unwrap_or_default();
     let total_currency = nanomina_to_mina(total_currency_u64);
-    let circulating_supply = nanomina_to_mina(total_currency_u64 - locked_currency_u64);
+    let circulating_supply =
+        nanomina_to_mina(total_currency_u64.saturating_sub(locked_currency_u64));
     let locked_supply = nanomina_to_mina(locked_currency_u64);
     let db_version = db_version.to_string();
 
@@ -167,6 +175,7 @@ fn calculate_summary(input: SummaryInput) -> Option<BlockchainSummary> {
         total_num_canonical_internal_commands,
         db_version,
         indexer_version,
+        replication_lag,
     })
 }
 
@@ -236,6 +245,10 @@ pub async fn get_blockchain_summary(
         let total_num_canonical_internal_commands = store
             .get_canonical_internal_commands_count()
             .expect("total number of canonical internal commands");
+        let replication_lag = store.replication_lag().unwrap_or_else(|e| {
+            error!("Failed to read replication lag: {e}");
+            None
+        });
 
         if let Some(ref summary) = calculate_summary(SummaryInput {
             chain_id,
@@ -259,6 +272,7 @@ pub async fn get_blockchain_summary(
             total_num_internal_commands,
             total_num_canonical_internal_commands,
             total_num_accounts,
+            replication_lag,
         }) {
             trace!("Blockchain summary: {summary:?}");
             let body = serde_json::to_string_pretty(summary).expect("blockchain summary");
@@ -46,7 +46,7 @@ pub async fn get_blocks(
             let blocks = blocks
                 .iter()
                 .flat_map(|state_hash| {
-                    let block = get_block(db, state_hash);
+                    let block = get_block(db, state_hash).ok().flatten()?;
                     Some(Block::from_precomputed(db, &block, counts))
                 })
                 .take(limit as usize)
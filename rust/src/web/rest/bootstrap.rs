@@ -0,0 +1,69 @@
+use crate::{ledger::bootstrap::Bootstrap, store::IndexerStore};
+use actix_web::{
+    body::{BodySize, MessageBody},
+    get,
+    web::{self, Bytes, Data},
+    HttpResponse,
+};
+use log::error;
+use serde::Deserialize;
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+#[derive(Deserialize)]
+struct Params {
+    chunk_size: Option<u32>,
+}
+
+/// Streams the bootstrap one line at a time, manifest first
+struct BootstrapBody {
+    bootstrap: Bootstrap,
+    index: u32,
+}
+
+impl MessageBody for BootstrapBody {
+    type Error = anyhow::Error;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+        Poll::Ready(match this.bootstrap.line(this.index) {
+            Ok(Some(line)) => {
+                this.index += 1;
+                Some(Ok(Bytes::from(line)))
+            }
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        })
+    }
+}
+
+#[get("/bootstrap")]
+pub async fn get_bootstrap(
+    store: Data<Arc<IndexerStore>>,
+    params: web::Query<Params>,
+) -> HttpResponse {
+    let chunk_size = params.chunk_size.unwrap_or(Bootstrap::DEFAULT_CHUNK_SIZE);
+    match Bootstrap::new(store.as_ref(), chunk_size) {
+        Ok(Some(bootstrap)) => HttpResponse::Ok()
+            .content_type("application/x-ndjson")
+            .body(BootstrapBody {
+                bootstrap,
+                index: 0,
+            }),
+        Ok(None) => HttpResponse::NotFound().finish(),
+        Err(e) => {
+            error!("Bootstrap failed: {e}");
+            HttpResponse::BadRequest().body(e.to_string())
+        }
+    }
+}
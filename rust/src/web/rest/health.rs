@@ -0,0 +1,74 @@
+use crate::{
+    store::{replication::ReplicationLag, IndexerStore},
+    web::WebServerOptions,
+};
+use actix_web::{get, web::Data, HttpResponse};
+use log::error;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Replication lag (blocks) response header of a read-only replica
+pub const LAG_BLOCKS_HEADER: &str = "x-indexer-lag-blocks";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthStatus {
+    /// Whether the store is fresh enough to serve queries
+    pub ready: bool,
+    pub is_primary: bool,
+    pub replication_lag: Option<ReplicationLag>,
+    pub max_replication_lag_blocks: Option<u32>,
+}
+
+impl HealthStatus {
+    /// A replica isn't ready if its lag exceeds the bound or can't be read
+    pub fn new(store: &IndexerStore, max_replication_lag_blocks: Option<u32>) -> Self {
+        let (ready, replication_lag) = match store.replication_lag() {
+            Ok(lag) => (
+                lag.zip(max_replication_lag_blocks)
+                    .map_or(true, |(lag, max)| lag.blocks <= max),
+                lag,
+            ),
+            Err(e) => {
+                error!("Failed to read replication lag: {e}");
+                (false, None)
+            }
+        };
+
+        Self {
+            ready,
+            is_primary: store.is_primary,
+            replication_lag,
+            max_replication_lag_blocks,
+        }
+    }
+}
+
+/// Value of the [LAG_BLOCKS_HEADER] (`None` for the primary)
+pub fn lag_blocks_header_value(store: &IndexerStore) -> Option<u32> {
+    store.replication_lag().ok().flatten().map(|lag| lag.blocks)
+}
+
+#[get("/health")]
+pub async fn get_health(
+    store: Data<Arc<IndexerStore>>,
+    options: Data<WebServerOptions>,
+) -> HttpResponse {
+    HttpResponse::Ok().json(HealthStatus::new(
+        &store,
+        options.max_replication_lag_blocks,
+    ))
+}
+
+#[get("/ready")]
+pub async fn get_ready(
+    store: Data<Arc<IndexerStore>>,
+    options: Data<WebServerOptions>,
+) -> HttpResponse {
+    let status = HealthStatus::new(&store, options.max_replication_lag_blocks);
+    if status.ready {
+        HttpResponse::Ok().json(status)
+    } else {
+        HttpResponse::ServiceUnavailable().json(status)
+    }
+}
@@ -1,4 +1,6 @@
 pub mod accounts;
 pub mod blockchain;
 pub mod blocks;
+pub mod bootstrap;
+pub mod health;
 pub mod locked_balances;
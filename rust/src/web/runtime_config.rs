@@ -0,0 +1,194 @@
+//! Web server settings reloadable without a restart
+//!
+//! The [RuntimeConfig] is read from a JSON file, e.g.
+//!
+//! ```json
+//! {
+//!   "graphiql": false,
+//!   "graphql": {
+//!     "max_query_depth": 16,
+//!     "max_query_complexity": 1000,
+//!     "max_limit": 1000,
+//!     "expose_raw_block_json": true
+//!   },
+//!   "identity": {
+//!     "https": {
+//!       "resolve_url": "https://names.example/resolve/{username}",
+//!       "lookup_url": "https://names.example/lookup/{public_key}"
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! which is watched for changes. Each valid change is swapped in atomically:
+//! requests in flight complete under the config they started with & the next
+//! request observes the new one. Invalid configs are rejected, keeping the
+//! current one.
+//!
+//! Only the GraphQL limits & feature flags & the identity providers of the
+//! [RuntimeConfig] are reloadable. All other settings (e.g. database paths, networks, web
+//! hostname & port) are boot-only & require a restart.
+
+use super::graphql::GraphQLOptions;
+use crate::identity::IdentityConfig;
+use anyhow::{bail, Context};
+use arc_swap::ArcSwap;
+use log::{error, info};
+use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{runtime::Handle, sync::mpsc};
+use tokio_graceful_shutdown::SubsystemHandle;
+
+/// Reloadable web server settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuntimeConfig {
+    /// Serve the GraphiQL playground
+    pub graphiql: bool,
+
+    /// GraphQL limits & feature flags
+    pub graphql: GraphQLOptions,
+
+    /// Username identity providers
+    pub identity: IdentityConfig,
+}
+
+/// Atomically swappable [RuntimeConfig], shared by the web server's workers
+#[derive(Debug, Clone, Default)]
+pub struct SharedRuntimeConfig(Arc<ArcSwap<RuntimeConfig>>);
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            graphiql: true,
+            graphql: GraphQLOptions::default(),
+            identity: IdentityConfig::default(),
+        }
+    }
+}
+
+impl RuntimeConfig {
+    /// Parses & validates the JSON config file
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents =
+            fs::read(path).with_context(|| format!("Failed to read runtime config {path:#?}"))?;
+        let config: Self = serde_json::from_slice(&contents)
+            .with_context(|| format!("Failed to parse runtime config {path:#?}"))?;
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.graphql.max_query_depth == Some(0) {
+            bail!("Max query depth must be positive")
+        }
+
+        if self.graphql.max_query_complexity == Some(0) {
+            bail!("Max query complexity must be positive")
+        }
+
+        if self.graphql.max_limit == Some(0) {
+            bail!("Max limit must be positive")
+        }
+
+        self.identity.validate()
+    }
+}
+
+impl SharedRuntimeConfig {
+    pub fn new(config: RuntimeConfig) -> Self {
+        Self(Arc::new(ArcSwap::from_pointee(config)))
+    }
+
+    /// Snapshot of the current config, unaffected by later swaps
+    pub fn load(&self) -> Arc<RuntimeConfig> {
+        self.0.load_full()
+    }
+
+    /// Swaps in the config if it's valid, otherwise keeps the current one
+    pub fn store(&self, config: RuntimeConfig) -> anyhow::Result<()> {
+        config.validate()?;
+        self.0.store(Arc::new(config));
+        Ok(())
+    }
+
+    /// Swaps in the config file if it's valid, otherwise keeps the current
+    /// one
+    pub fn reload(&self, path: &Path) -> anyhow::Result<()> {
+        let config = RuntimeConfig::from_file(path)?;
+        if *self.load() != config {
+            info!("Reloaded runtime config {path:#?}: {config:?}");
+        }
+
+        self.0.store(Arc::new(config));
+        Ok(())
+    }
+
+    /// Reloads the config file on each change, until shutdown
+    pub async fn watch(self, path: PathBuf, subsys: SubsystemHandle) -> anyhow::Result<()> {
+        let file_name = path
+            .file_name()
+            .with_context(|| format!("Runtime config {path:#?} is not a file"))?
+            .to_owned();
+
+        // editors often replace the file, so watch its directory
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let rt = Handle::current();
+        let mut watcher = RecommendedWatcher::new(
+            move |result| {
+                let tx = tx.clone();
+                rt.spawn(async move {
+                    if let Err(e) = tx.send(result).await {
+                        error!("Failed to send runtime config watcher event: {e}");
+                    }
+                });
+            },
+            Config::default(),
+        )?;
+
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        info!("Watching runtime config {path:#?}");
+
+        loop {
+            tokio::select! {
+                // watch for shutdown signals
+                _ = subsys.on_shutdown_requested() => {
+                    break;
+                }
+
+                // watch for config changes
+                Some(res) = rx.recv() => {
+                    match res {
+                        Ok(event) => {
+                            let changed = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                                && event
+                                    .paths
+                                    .iter()
+                                    .any(|path| path.file_name() == Some(file_name.as_os_str()));
+
+                            if changed {
+                                if let Err(e) = self.reload(&path) {
+                                    error!("Rejected runtime config, keeping the current one: {e:#}");
+                                }
+                            }
+                        }
+                        Err(e) => error!("Runtime config watcher error: {e}"),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
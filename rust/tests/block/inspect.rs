@@ -0,0 +1,70 @@
+use mina_indexer::{
+    block::{
+        inspect::{bin_prot_offset, inspect_block_file},
+        precomputed::{PcbVersion, PrecomputedBlock},
+    },
+    protocol::serialization_types::version_bytes,
+};
+use std::path::Path;
+
+const BLOCK_FILE: &str =
+    "./tests/data/sequential_blocks/mainnet-105489-3NK4huLvUDiL4XuCUcyrWCKynmvhqfKsx5h2MfBXVVUq2Qwzi5uT.json";
+
+/// Bytes of the versioned ledger hash kept in the truncated fixture
+const TRUNCATED_LEN: usize = 19;
+
+/// Writes a copy of the v1 block with its first `snarked_ledger_hash`
+/// truncated to [TRUNCATED_LEN] bytes
+fn write_truncated_fixture(dir: &Path) -> anyhow::Result<std::path::PathBuf> {
+    let contents = std::fs::read_to_string(BLOCK_FILE)?;
+    let key = "\"snarked_ledger_hash\":\"";
+    let start = contents.find(key).expect("snarked ledger hash") + key.len();
+    let end = start + contents[start..].find('"').unwrap();
+
+    let bytes = bs58::decode(&contents[start..end])
+        .with_check(Some(version_bytes::LEDGER_HASH))
+        .into_vec()?;
+    let truncated = bs58::encode(&bytes[1..=TRUNCATED_LEN])
+        .with_check_version(version_bytes::LEDGER_HASH)
+        .into_string();
+
+    let path = dir.join(Path::new(BLOCK_FILE).file_name().unwrap());
+    std::fs::write(
+        &path,
+        format!("{}{truncated}{}", &contents[..start], &contents[end..]),
+    )?;
+    Ok(path)
+}
+
+#[test]
+fn truncated_v1_block_error_context() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::with_prefix("truncated-v1-block")?;
+    let path = write_truncated_fixture(dir.path())?;
+
+    // the error names the offset & the innermost versioned type being read
+    let error = PrecomputedBlock::parse_file(&path, PcbVersion::V1)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains(&format!("at byte offset {TRUNCATED_LEN} reading")),
+        "{error}"
+    );
+    assert!(
+        error.contains("Versioned<[u8; 32], 1> > Versioned > t"),
+        "{error}"
+    );
+    assert_eq!(bin_prot_offset(&error), Some(TRUNCATED_LEN as u64));
+
+    // the report dumps the decoded field up to the failing offset
+    let report = inspect_block_file(&path, PcbVersion::V1, 8)?;
+    assert!(report.contains(&format!(
+        "bin_prot error at byte offset {TRUNCATED_LEN} of the decoded field"
+    )));
+    assert!(report.contains(&format!("{TRUNCATED_LEN:08x} <end of input>")));
+
+    // intact blocks parse
+    let report = inspect_block_file(Path::new(BLOCK_FILE), PcbVersion::V1, 8)?;
+    assert!(report.starts_with("Parsed v1 block"), "{report}");
+
+    Ok(())
+}
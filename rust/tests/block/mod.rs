@@ -1,2 +1,5 @@
+mod inspect;
 mod parser;
 mod store;
+mod supercharge;
+mod validation;
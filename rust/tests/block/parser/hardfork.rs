@@ -44,3 +44,46 @@ async fn hardfork() -> anyhow::Result<()> {
     assert_eq!(block_parser.version, PcbVersion::V2);
     Ok(())
 }
+
+#[test]
+fn two_pass_ledger_hashes() -> anyhow::Result<()> {
+    // final pre-hardfork v1 block has a single pass
+    let path = PathBuf::from("./tests/data/hardfork/mainnet-359604-3NLRTfY4kZyJtvaP4dFenDcxfoMfT3uEpkWS913KkeXLtziyVd15.json");
+    let block = PrecomputedBlock::parse_file(&path, PcbVersion::V1)?;
+
+    assert_eq!(
+        block.first_pass_ledger_hash().0,
+        "jxzsDCwThbSpdSN1SiqpoiSex6o9gncVJ57N67qSe6xB67kVSrE"
+    );
+    assert_eq!(block.first_pass_ledger_hash(), block.staged_ledger_hash());
+    assert_eq!(block.second_pass_ledger_hash(), None);
+
+    // post-hardfork v2 blocks
+    for (path, first_pass, second_pass) in [
+        (
+            "./tests/data/hardfork/mainnet-359605-3NK4BpDSekaqsG6tx8Nse2zJchRft2JpnbvMiog55WCr5xJZaKeP.json",
+            "jwNw4qb6tnNhpQNxiMLem9WumxZTwmbSx3fYXW4FP3hZRkoQJSE",
+            "jwNw4qb6tnNhpQNxiMLem9WumxZTwmbSx3fYXW4FP3hZRkoQJSE",
+        ),
+        (
+            "./tests/data/hardfork/mainnet-359617-3NKZ5poCAjtGqg9hHvAVZ7QwriqJsL8mpQsSHFGzqW6ddEEjYfvW.json",
+            "jwNw4qb6tnNhpQNxiMLem9WumxZTwmbSx3fYXW4FP3hZRkoQJSE",
+            "jwNw4qb6tnNhpQNxiMLem9WumxZTwmbSx3fYXW4FP3hZRkoQJSE",
+        ),
+        (
+            "./tests/data/misc_blocks/mainnet-397612-3NLh3tvZpMPXxUhCLz1898BDV6CwtExJqDWpzcZQebVCsZxghoXK.json",
+            "jxQrHXnDgHPfC9NbMhxbafWsEcE7ByCcSsMEsfgRQgngnYjetEM",
+            "jxqJTkZBToqhCgUDwAWNyojqxLx3Z7VejupZzWAXNrHKFyryH9h",
+        ),
+    ] {
+        let block = PrecomputedBlock::parse_file(&PathBuf::from(path), PcbVersion::V2)?;
+
+        assert_eq!(block.first_pass_ledger_hash().0, first_pass);
+        assert_eq!(
+            block.second_pass_ledger_hash().map(|hash| hash.0),
+            Some(second_pass.to_string())
+        );
+    }
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 mod hardfork;
+mod nested;
 
 use mina_indexer::block::{
     parser::BlockParser,
@@ -73,6 +74,7 @@ async fn orphaned_blocks() -> anyhow::Result<()> {
         MAINNET_CANONICAL_THRESHOLD,
         false,
         MAINNET_TRANSITION_FRONTIER_K,
+        true,
     )
     .await?;
 
@@ -126,3 +128,57 @@ async fn orphaned_blocks() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn parse_ahead_preserves_order() -> anyhow::Result<()> {
+    use mina_indexer::constants::*;
+
+    let log_dir = PathBuf::from("./tests/data/sequential_blocks");
+    let mut state_hashes = vec![];
+
+    for parse_ahead in [1, BLOCK_PARSER_PARSE_AHEAD] {
+        let mut block_parser = BlockParser::new_with_canonical_chain_discovery(
+            &log_dir,
+            PcbVersion::V1,
+            MAINNET_CANONICAL_THRESHOLD,
+            false,
+            BLOCK_REPORTING_FREQ_NUM,
+            true,
+        )
+        .await?
+        .with_parse_ahead(parse_ahead);
+
+        let expected_paths = block_parser.paths();
+        let mut hashes = vec![];
+        while let Some((block, _, path)) = block_parser.next_block_with_path().await? {
+            // unconsumed paths include the blocks being parsed ahead
+            let paths = block_parser.paths();
+            assert_eq!(
+                paths.canonical_paths.len() + paths.recent_paths.len() + paths.orphaned_paths.len(),
+                (block_parser.total_num_blocks - block_parser.blocks_processed) as usize
+            );
+
+            let block: PrecomputedBlock = block.into();
+            hashes.push((block.state_hash(), path));
+        }
+
+        let expected: Vec<_> = expected_paths
+            .canonical_paths
+            .into_iter()
+            .chain(expected_paths.recent_paths)
+            .chain(expected_paths.orphaned_paths)
+            .collect();
+        assert_eq!(
+            hashes
+                .iter()
+                .map(|(_, path)| path.clone())
+                .collect::<Vec<_>>(),
+            expected
+        );
+
+        state_hashes.push(hashes);
+    }
+
+    assert_eq!(state_hashes[0], state_hashes[1]);
+    Ok(())
+}
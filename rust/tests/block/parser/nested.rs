@@ -0,0 +1,184 @@
+use crate::helpers::{state::*, store::*};
+use flate2::{write::GzEncoder, Compression};
+use mina_indexer::{
+    block::{
+        extract_block_height, get_block_file_paths, parser::BlockParser, precomputed::PcbVersion,
+    },
+    constants::*,
+};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+const FLAT_BLOCKS_DIR: &str = "./tests/data/sequential_blocks";
+
+/// Copies the flat blocks dir into subdirs by height range, e.g.
+///
+/// ```text
+/// 105485-105489/mainnet-105489-3NK4huLvUDiL4XuCUcyrWCKynmvhqfKsx5h2MfBXVVUq2Qwzi5uT.json
+/// 105490-105494/mainnet-105490-3NKxEA9gztvEGxL4uk4eTncZAxuRmMsB8n81UkeAMevUjMbLHmkC.json.gz
+/// ...
+/// ```
+///
+/// Every other block is gzip compressed & the lowest blocks are duplicated in
+/// a separate subdir
+fn nested_blocks_dir(flat_blocks_dir: &Path) -> anyhow::Result<tempfile::TempDir> {
+    let nested_dir = tempfile::TempDir::with_prefix("nested-blocks")?;
+    let mut paths = get_block_file_paths(flat_blocks_dir, false)?;
+    paths.sort();
+
+    for (n, path) in paths.iter().enumerate() {
+        let height = extract_block_height(path);
+        let range_start = height - height % 5;
+        let range_dir = nested_dir
+            .path()
+            .join(format!("{range_start}-{}", range_start + 4));
+        fs::create_dir_all(&range_dir)?;
+
+        let file_name = path.file_name().unwrap().to_str().unwrap();
+        if n % 2 == 0 {
+            fs::copy(path, range_dir.join(file_name))?;
+        } else {
+            let mut encoder = GzEncoder::new(
+                fs::File::create(range_dir.join(format!("{file_name}.gz")))?,
+                Compression::default(),
+            );
+            encoder.write_all(&fs::read(path)?)?;
+            encoder.finish()?;
+        }
+
+        // duplicates in another subdir
+        if n < 3 {
+            let dup_dir = nested_dir.path().join("duplicates");
+            fs::create_dir_all(&dup_dir)?;
+            fs::copy(path, dup_dir.join(file_name))?;
+        }
+    }
+
+    Ok(nested_dir)
+}
+
+/// Block file names without the `.gz` extension
+fn file_names(paths: &[PathBuf]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|p| {
+            let name = p.file_name().unwrap().to_str().unwrap();
+            name.strip_suffix(".gz").unwrap_or(name).to_string()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn nested_layout_paths() -> anyhow::Result<()> {
+    let flat_dir = PathBuf::from(FLAT_BLOCKS_DIR);
+    let nested_dir = nested_blocks_dir(&flat_dir)?;
+
+    let flat = BlockParser::new_with_canonical_chain_discovery(
+        &flat_dir,
+        PcbVersion::V1,
+        MAINNET_CANONICAL_THRESHOLD,
+        false,
+        BLOCK_REPORTING_FREQ_NUM,
+        true,
+    )
+    .await?;
+    let nested = BlockParser::new_with_canonical_chain_discovery(
+        nested_dir.path(),
+        PcbVersion::V1,
+        MAINNET_CANONICAL_THRESHOLD,
+        false,
+        BLOCK_REPORTING_FREQ_NUM,
+        true,
+    )
+    .await?;
+
+    // duplicates are only counted once & gzip files count their
+    // decompressed size
+    assert_eq!(nested.total_num_blocks, flat.total_num_blocks);
+    assert_eq!(nested.total_num_bytes, flat.total_num_bytes);
+    assert_eq!(
+        nested.num_deep_canonical_blocks,
+        flat.num_deep_canonical_blocks
+    );
+
+    let (flat_paths, nested_paths) = (flat.paths(), nested.paths());
+    assert_eq!(
+        file_names(&nested_paths.canonical_paths),
+        file_names(&flat_paths.canonical_paths)
+    );
+    assert_eq!(
+        file_names(&nested_paths.recent_paths),
+        file_names(&flat_paths.recent_paths)
+    );
+    assert_eq!(
+        file_names(&nested_paths.orphaned_paths),
+        file_names(&flat_paths.orphaned_paths)
+    );
+
+    // opting out of recursion only sees the top level
+    let non_recursive = BlockParser::new_with_canonical_chain_discovery(
+        nested_dir.path(),
+        PcbVersion::V1,
+        MAINNET_CANONICAL_THRESHOLD,
+        false,
+        BLOCK_REPORTING_FREQ_NUM,
+        false,
+    )
+    .await?;
+    assert_eq!(non_recursive.total_num_blocks, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn nested_layout_ingestion() -> anyhow::Result<()> {
+    let flat_dir = PathBuf::from(FLAT_BLOCKS_DIR);
+    let nested_dir = nested_blocks_dir(&flat_dir)?;
+
+    // ingest the flat layout
+    let flat_store_dir = setup_new_db_dir("nested-layout-flat")?;
+    let mut flat_state = mainnet_genesis_state(flat_store_dir.as_ref())?;
+    let mut flat_parser = BlockParser::new_with_canonical_chain_discovery(
+        &flat_dir,
+        PcbVersion::V1,
+        MAINNET_CANONICAL_THRESHOLD,
+        false,
+        BLOCK_REPORTING_FREQ_NUM,
+        true,
+    )
+    .await?;
+    flat_state.add_blocks(&mut flat_parser).await?;
+
+    // ingest the nested layout
+    let nested_store_dir = setup_new_db_dir("nested-layout-nested")?;
+    let mut nested_state = mainnet_genesis_state(nested_store_dir.as_ref())?;
+    let mut nested_parser = BlockParser::new_with_canonical_chain_discovery(
+        nested_dir.path(),
+        PcbVersion::V1,
+        MAINNET_CANONICAL_THRESHOLD,
+        false,
+        BLOCK_REPORTING_FREQ_NUM,
+        true,
+    )
+    .await?;
+    nested_state.add_blocks(&mut nested_parser).await?;
+
+    assert_eq!(nested_parser.blocks_processed, flat_parser.blocks_processed);
+    assert_eq!(nested_state.best_tip_block(), flat_state.best_tip_block());
+    assert_eq!(
+        nested_state.canonical_root_block(),
+        flat_state.canonical_root_block()
+    );
+    assert_eq!(
+        nested_state
+            .diffs_map
+            .keys()
+            .collect::<std::collections::HashSet<_>>(),
+        flat_state.diffs_map.keys().collect()
+    );
+
+    Ok(())
+}
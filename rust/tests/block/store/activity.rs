@@ -0,0 +1,124 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{
+        activity::{ActivityStats, MILLIS_PER_DAY},
+        parser::BlockParser,
+        precomputed::PrecomputedBlock,
+        store::BlockStore,
+    },
+    canonicity::store::CanonicityStore,
+    command::UserCommandWithStatusT,
+    ledger::diff::LedgerDiff,
+    store::IndexerStore,
+};
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::PathBuf,
+};
+
+#[derive(Default)]
+struct DayRecount {
+    num_blocks: u32,
+    active_accounts: HashSet<String>,
+    txn_senders: HashSet<String>,
+    new_accounts: u32,
+}
+
+impl DayRecount {
+    fn add(&mut self, block: &PrecomputedBlock) {
+        let diff = LedgerDiff::from_precomputed(block);
+        self.num_blocks += 1;
+        self.active_accounts
+            .extend(diff.public_keys_seen.into_iter().map(|pk| pk.0));
+        self.txn_senders
+            .extend(block.commands().iter().map(|cmd| cmd.sender().0));
+        self.new_accounts += diff
+            .new_pk_balances
+            .values()
+            .map(|balances| balances.len() as u32)
+            .sum::<u32>();
+    }
+
+    fn stats(&self) -> ActivityStats {
+        ActivityStats {
+            num_blocks: self.num_blocks,
+            active_accounts: self.active_accounts.len() as u32,
+            txn_senders: self.txn_senders.len() as u32,
+            new_accounts: self.new_accounts,
+            // no zkapps before the hardfork
+            zkapp_accounts: 0,
+        }
+    }
+}
+
+/// Brute-force daily recount of the canonical blocks at `heights`
+fn recount(
+    store: &IndexerStore,
+    heights: impl Iterator<Item = u32>,
+) -> anyhow::Result<BTreeMap<u32, ActivityStats>> {
+    let mut days: BTreeMap<u32, DayRecount> = BTreeMap::new();
+    for height in heights {
+        if let Some(state_hash) = store.get_canonical_hash_at_height(height)? {
+            let (block, _) = store.get_block(&state_hash)?.unwrap();
+            let day = (block.timestamp() as i64 / MILLIS_PER_DAY) as u32;
+            days.entry(day).or_default().add(&block);
+        }
+    }
+
+    Ok(days
+        .into_iter()
+        .map(|(day, recount)| (day, recount.stats()))
+        .collect())
+}
+
+fn rollups(store: &IndexerStore) -> anyhow::Result<BTreeMap<u32, ActivityStats>> {
+    Ok(store
+        .get_daily_activities(0, u32::MAX, usize::MAX)?
+        .into_iter()
+        .map(|(day, daily)| (day, daily.stats()))
+        .collect())
+}
+
+#[tokio::test]
+async fn daily_rollups_match_recount() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("block-activity-store")?;
+    let blocks_dir = PathBuf::from("./tests/data/sequential_blocks");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&blocks_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let best_height = store.get_best_block_height()?.unwrap();
+    let canonical_heights = || (1..=1).chain(105489..=best_height);
+
+    // the rollups match the canonical blocks after the ingestion reorgs
+    let expected = recount(store, canonical_heights())?;
+    assert!(expected.values().any(|stats| stats.active_accounts > 0));
+    assert_eq!(rollups(store)?, expected);
+
+    // per-block records of the canonical blocks
+    let best_tip = store.get_best_block_hash()?.unwrap();
+    let best_activity = store.get_block_activity(&best_tip)?.unwrap();
+    let blocks = store.get_canonical_block_activities(
+        best_activity.date_time,
+        best_activity.date_time,
+        usize::MAX,
+    )?;
+    assert!(blocks.contains(&(best_height, best_tip.clone(), best_activity.clone())));
+
+    // unapplying the best tip removes only its contributions
+    store.unapply_block_activity(&best_tip, best_height)?;
+    let without_tip = recount(store, canonical_heights().filter(|h| *h != best_height))?;
+    assert_eq!(rollups(store)?, without_tip);
+
+    // unapplying twice is a no-op & reapplying restores the rollup
+    store.unapply_block_activity(&best_tip, best_height)?;
+    assert_eq!(rollups(store)?, without_tip);
+
+    store.apply_block_activity(&best_tip, best_height)?;
+    store.apply_block_activity(&best_tip, best_height)?;
+    assert_eq!(rollups(store)?, expected);
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{
+        fee_stats::{BlockFeeStats, FeeEstimate},
+        get_block_file_paths,
+        parser::BlockParser,
+        precomputed::{PcbVersion, PrecomputedBlock},
+        store::BlockStore,
+    },
+    command::signed::SignedCommandWithData,
+};
+use std::path::PathBuf;
+
+#[tokio::test]
+async fn sequential_blocks() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("block-fee-stats-store")?;
+    let blocks_dir = PathBuf::from("./tests/data/sequential_blocks");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&blocks_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let mut all_stats = vec![];
+    let mut all_fees = vec![];
+
+    for path in get_block_file_paths(&blocks_dir, false)? {
+        let block = PrecomputedBlock::parse_file(&path, PcbVersion::V1)?;
+        let mut fees: Vec<u64> = SignedCommandWithData::from_precomputed(&block)
+            .iter()
+            .map(|cmd| cmd.command.fee())
+            .collect();
+        fees.sort();
+
+        // fee stats are indexed when the block is added
+        let stats = store.get_block_fee_stats(&block.state_hash())?.unwrap();
+        assert_eq!(stats, BlockFeeStats::from_precomputed(&block));
+        assert_eq!(stats.fees, fees);
+        assert_eq!(stats.count(), block.commands().len() as u32);
+        assert_eq!(stats.min(), fees.first().copied());
+        assert_eq!(stats.max(), fees.last().copied());
+        assert!(stats.min() <= stats.median() && stats.median() <= stats.max());
+
+        // none of the test blocks are full
+        assert!(!stats.is_full());
+
+        all_fees.extend(fees);
+        all_stats.push(stats);
+    }
+
+    // the estimate covers all included fees
+    all_fees.sort();
+    let estimate = FeeEstimate::new(&all_stats).unwrap();
+    assert_eq!(estimate.num_blocks, all_stats.len() as u32);
+    assert_eq!(estimate.num_full_blocks, 0);
+    assert_eq!(estimate.num_fees, all_fees.len() as u32);
+    assert!(estimate.low <= estimate.medium && estimate.medium <= estimate.high);
+    assert!(all_fees[0] <= estimate.low && estimate.high <= *all_fees.last().unwrap());
+
+    Ok(())
+}
+
+#[test]
+fn zkapp_fee_payer_fees() -> anyhow::Result<()> {
+    // 1 payment & 3 zkapp commands
+    let path = PathBuf::from("./tests/data/hardfork/mainnet-359610-3NLe669kJ89t48btn8NX6jMy7vnWNjP9caBdGgsCw2VSMjzP1anW.json");
+    let block = PrecomputedBlock::parse_file(&path, PcbVersion::V2)?;
+    let stats = BlockFeeStats::from_precomputed(&block);
+
+    let mut fees: Vec<u64> = SignedCommandWithData::from_precomputed(&block)
+        .iter()
+        .map(|cmd| cmd.command.fee())
+        .collect();
+    fees.sort();
+
+    assert_eq!(stats.count(), 4);
+    assert_eq!(stats.fees, fees);
+    Ok(())
+}
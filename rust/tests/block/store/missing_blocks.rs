@@ -0,0 +1,61 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::block::{
+    missing::MissingBlock,
+    precomputed::{PcbVersion, PrecomputedBlock},
+    store::BlockStore,
+};
+use std::{collections::HashMap, path::PathBuf};
+
+/// Contiguous blocks, by length
+fn contiguous_blocks() -> anyhow::Result<HashMap<u32, (PrecomputedBlock, u64)>> {
+    let dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    let mut blocks = HashMap::new();
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let block = PrecomputedBlock::parse_file(&path, PcbVersion::V1)?;
+        blocks.insert(block.blockchain_length(), (block, path.metadata()?.len()));
+    }
+
+    Ok(blocks)
+}
+
+#[test]
+fn gaps_recorded_and_filled() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("missing-blocks-store")?;
+    let state = mainnet_genesis_state(store_dir.as_ref())?;
+    let store = state.indexer_store.as_ref().unwrap();
+    let blocks = contiguous_blocks()?;
+
+    // add all blocks except 7 & 13
+    for length in (2..=21).filter(|length| *length != 7 && *length != 13) {
+        let (block, block_bytes) = &blocks[&length];
+        store.add_block(block, *block_bytes)?;
+    }
+
+    let expect: Vec<MissingBlock> = [7, 13]
+        .into_iter()
+        .map(|length| MissingBlock {
+            state_hash: blocks[&length].0.state_hash(),
+            blockchain_length: length,
+            referenced_by: blocks[&(length + 1)].0.state_hash(),
+        })
+        .collect();
+    assert_eq!(store.get_missing_blocks(100)?, expect);
+    assert_eq!(store.get_missing_blocks(1)?, expect[..1]);
+    assert_eq!(store.get_num_missing_blocks()?, 2);
+
+    // fill the gaps
+    for length in [7, 13] {
+        let (block, block_bytes) = &blocks[&length];
+        assert_eq!(
+            block.previous_state_hash(),
+            blocks[&(length - 1)].0.state_hash()
+        );
+        store.add_block(block, *block_bytes)?;
+    }
+
+    assert!(store.get_missing_blocks(100)?.is_empty());
+    assert_eq!(store.get_num_missing_blocks()?, 0);
+    Ok(())
+}
@@ -1,4 +1,12 @@
+mod activity;
 mod blocks;
 mod blocks_at_height;
 mod blocks_at_slot;
+mod fee_stats;
 mod genesis;
+mod missing_blocks;
+mod orphan_counts;
+mod orphan_pruning;
+mod receipt;
+mod supply;
+mod token_provenance;
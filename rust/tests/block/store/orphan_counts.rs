@@ -0,0 +1,187 @@
+use crate::helpers::store::*;
+use async_graphql::Request;
+use mina_indexer::{
+    base::{public_key::PublicKey, state_hash::StateHash},
+    block::{parser::BlockParser, store::BlockStore},
+    canonicity::{store::CanonicityStore, Canonicity, CanonicityDiff},
+    constants::*,
+    state::IndexerState,
+    store::{DbUpdate, IndexerStore},
+    web::graphql::build_schema,
+};
+use serde_json::json;
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+/// Small enough for the fork at height 10 to settle
+const CANONICAL_THRESHOLD: u32 = 2;
+
+/// Expected `(produced, orphaned)` counts per creator & epoch
+fn expected_counts(
+    store: &IndexerStore,
+    settled_height: u32,
+) -> anyhow::Result<HashMap<(PublicKey, u32), (u32, u32)>> {
+    let mut counts = HashMap::new();
+    let best_height = store.get_best_block_height()?.unwrap();
+
+    for height in 1..=best_height {
+        for state_hash in store.get_blocks_at_height(height)? {
+            let creator = store.get_block_creator(&state_hash)?.unwrap();
+            let epoch = store.get_block_epoch(&state_hash)?.unwrap();
+            let entry: &mut (u32, u32) = counts.entry((creator, epoch)).or_default();
+
+            entry.0 += 1;
+            if height <= settled_height
+                && store.get_block_canonicity(&state_hash)? == Some(Canonicity::Orphaned)
+            {
+                entry.1 += 1;
+            }
+        }
+    }
+
+    Ok(counts)
+}
+
+fn assert_counts(
+    store: &IndexerStore,
+    expected: &HashMap<(PublicKey, u32), (u32, u32)>,
+) -> anyhow::Result<()> {
+    for ((pk, epoch), (produced, orphaned)) in expected.iter() {
+        let epoch = Some(*epoch);
+        assert_eq!(
+            store.get_block_production_pk_epoch_count(pk, epoch)?,
+            *produced,
+            "{pk}"
+        );
+        assert_eq!(
+            store.get_block_production_pk_orphaned_epoch_count(pk, epoch)?,
+            *orphaned,
+            "{pk}"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn settled_orphans_and_reorg_flips() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("block-orphan-counts")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/gaps");
+
+    let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+    let mut state = IndexerState::new_v1(
+        indexer_store,
+        CANONICAL_THRESHOLD,
+        MAINNET_TRANSITION_FRONTIER_K,
+        false,
+    )?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let best_height = store.get_best_block_height()?.unwrap();
+    let settled_height = store.get_orphans_settled_height()?;
+    assert_eq!(settled_height, best_height - CANONICAL_THRESHOLD);
+
+    // the fork at height 10 has settled
+    let canonical: StateHash = "3NKGgTk7en3347KH81yDra876GPAUSoSePrfVKPmwR1KHfMpvJC5".into();
+    let orphan: StateHash = "3NKHYHrqKpDcon6ToV5CLDiheanjshk5gcsNqefnK78phCFTR2aL".into();
+    let orphan_creator: PublicKey =
+        "B62qpEMj7NwUbeWyyPrPUHtJDqJb1RkPxJtm6JxHrXUKDFMgJYp1zzT".into();
+    assert!(settled_height >= 10);
+    assert_eq!(
+        store.get_block_canonicity(&orphan)?,
+        Some(Canonicity::Orphaned)
+    );
+
+    // counts match the stored blocks' canonicities
+    let epoch = store.get_block_epoch(&orphan)?.unwrap();
+    let expected = expected_counts(store, settled_height)?;
+    assert_eq!(expected[&(orphan_creator.clone(), epoch)], (1, 1));
+    assert_counts(store, &expected)?;
+    assert_eq!(
+        store.get_block_production_orphaned_epoch_count(Some(epoch))?,
+        1
+    );
+
+    // settling is idempotent
+    store.settle_orphaned_blocks(settled_height)?;
+    assert_counts(store, &expected)?;
+
+    // a deep reorg moves counts between the canonical & orphaned buckets
+    let creator = store.get_block_creator(&canonical)?.unwrap();
+    let num_canonical =
+        store.get_block_production_pk_canonical_epoch_count(&creator, Some(epoch))?;
+    store.update_canonicity(DbUpdate {
+        apply: vec![CanonicityDiff {
+            state_hash: orphan.clone(),
+            blockchain_length: 10,
+            global_slot: store.get_block_global_slot(&orphan)?.unwrap(),
+        }],
+        unapply: vec![CanonicityDiff {
+            state_hash: canonical.clone(),
+            blockchain_length: 10,
+            global_slot: store.get_block_global_slot(&canonical)?.unwrap(),
+        }],
+    })?;
+
+    assert_eq!(
+        store.get_block_production_pk_orphaned_epoch_count(&orphan_creator, Some(epoch))?,
+        0
+    );
+    assert_eq!(
+        store.get_block_production_pk_canonical_epoch_count(&orphan_creator, Some(epoch))?,
+        1
+    );
+    assert_eq!(
+        store.get_block_production_pk_orphaned_epoch_count(&creator, Some(epoch))?,
+        expected[&(creator.clone(), epoch)].1 + 1
+    );
+    assert_eq!(
+        store.get_block_production_pk_canonical_epoch_count(&creator, Some(epoch))?,
+        num_canonical - 1
+    );
+    assert_eq!(
+        store.get_block_production_orphaned_epoch_count(Some(epoch))?,
+        1
+    );
+
+    // GraphQL
+    let schema = build_schema(store.clone());
+    let response = schema
+        .execute(Request::new(format!(
+            r#"{{
+              producerStats(publicKey: "{orphan_creator}", epoch: {epoch}) {{
+                produced
+                canonical
+                orphaned
+                pending
+                orphanRate
+              }}
+              chainQuality(epoch: {epoch}) {{
+                orphaned
+                slotsFilled
+              }}
+            }}"#
+        )))
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json()?;
+    assert_eq!(
+        data["producerStats"],
+        json!({
+            "produced": 1,
+            "canonical": 1,
+            "orphaned": 0,
+            "pending": 0,
+            "orphanRate": 0.0,
+        })
+    );
+    assert_eq!(data["chainQuality"]["orphaned"], 1);
+    assert_eq!(
+        data["chainQuality"]["slotsFilled"],
+        store.get_epoch_slots_produced_count(Some(epoch))?
+    );
+
+    Ok(())
+}
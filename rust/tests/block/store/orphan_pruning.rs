@@ -0,0 +1,254 @@
+use crate::helpers::store::*;
+use mina_indexer::{
+    base::state_hash::StateHash,
+    block::{parser::BlockParser, store::BlockStore},
+    canonicity::{store::CanonicityStore, Canonicity},
+    command::{internal::store::InternalCommandStore, store::UserCommandStore},
+    constants::*,
+    event::{db::*, store::EventStore, IndexerEvent},
+    ledger::genesis::GenesisLedger,
+    maintenance::{
+        tasks::{BlockRetentionTask, OrphanPruningTask},
+        FakeClock, MaintenanceConfig, MaintenanceScheduler, RetentionPolicy, Schedule, StepOutcome,
+    },
+    server::IndexerVersion,
+    snark_work::store::SnarkStore,
+    state::{IndexerState, IndexerStateConfig},
+    store::{column_families::ColumnFamilyHelpers, IndexerStore},
+};
+use std::{path::PathBuf, sync::Arc};
+
+/// Small enough for the fork at height 10 to settle
+const CANONICAL_THRESHOLD: u32 = 2;
+
+#[tokio::test]
+async fn prune_settled_orphan_ledger_diffs() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("block-orphan-pruning")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/gaps");
+
+    let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+    let mut state = IndexerState::new_v1(
+        indexer_store.clone(),
+        CANONICAL_THRESHOLD,
+        MAINNET_TRANSITION_FRONTIER_K,
+        false,
+    )?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = &indexer_store;
+    let orphan: StateHash = "3NKHYHrqKpDcon6ToV5CLDiheanjshk5gcsNqefnK78phCFTR2aL".into();
+    let canonical: StateHash = "3NKGgTk7en3347KH81yDra876GPAUSoSePrfVKPmwR1KHfMpvJC5".into();
+    assert_eq!(
+        store.get_block_canonicity(&orphan)?,
+        Some(Canonicity::Orphaned)
+    );
+
+    let orphan_diff = store.get_block_ledger_diff(&orphan)?;
+    assert!(orphan_diff.is_some());
+
+    let stored_diff = |state_hash: &StateHash| -> anyhow::Result<bool> {
+        Ok(store
+            .database
+            .get_pinned_cf(store.block_ledger_diff_cf(), state_hash.0.as_bytes())?
+            .is_some())
+    };
+
+    // run the task under the scheduler, one height per batch
+    let clock = Arc::new(FakeClock::new(0));
+    let mut scheduler = MaintenanceScheduler::new(
+        MaintenanceConfig {
+            max_batch_size: 1,
+            ..Default::default()
+        },
+        clock.clone(),
+    );
+    scheduler.register(Box::new(OrphanPruningTask::new(store.clone())));
+
+    let Schedule::Interval { millis } = Schedule::hourly() else {
+        unreachable!()
+    };
+    clock.advance(millis);
+
+    let settled_height = store.get_orphans_settled_height()?;
+    let mut num_batches = 0;
+    while let StepOutcome::Ran { done, .. } = scheduler.step() {
+        num_batches += 1;
+        if done {
+            break;
+        }
+    }
+    assert_eq!(num_batches, settled_height);
+    assert_eq!(store.get_orphans_pruned_height()?, settled_height);
+
+    // only the settled orphan's diff is dropped
+    assert!(!stored_diff(&orphan)?);
+    assert!(stored_diff(&canonical)?);
+
+    let status = &scheduler.statuses()[0];
+    assert_eq!(status.name, OrphanPruningTask::NAME);
+    assert_eq!(
+        status.last_run.as_ref().unwrap().num_processed,
+        settled_height as u64
+    );
+
+    // pruned diffs are rebuilt from the block
+    assert_eq!(store.get_block_ledger_diff(&orphan)?, orphan_diff);
+
+    // pruning is idempotent
+    assert_eq!(store.prune_orphaned_ledger_diffs(settled_height)?, 0);
+    assert_eq!(scheduler.step(), StepOutcome::Idle);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn retention_prunes_settled_orphaned_blocks() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("block-retention")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/gaps");
+
+    let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+    let mut state = IndexerState::new_v1(
+        indexer_store.clone(),
+        CANONICAL_THRESHOLD,
+        MAINNET_TRANSITION_FRONTIER_K,
+        false,
+    )?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = &indexer_store;
+    let orphan: StateHash = "3NKHYHrqKpDcon6ToV5CLDiheanjshk5gcsNqefnK78phCFTR2aL".into();
+    let canonical: StateHash = "3NKGgTk7en3347KH81yDra876GPAUSoSePrfVKPmwR1KHfMpvJC5".into();
+    let (orphan_block, orphan_bytes) = store.get_block(&orphan)?.unwrap();
+
+    let height_sorted = |state_hash: &StateHash| {
+        store
+            .blocks_height_iterator(speedb::IteratorMode::Start)
+            .flatten()
+            .any(|(key, _)| key.ends_with(state_hash.0.as_bytes()))
+    };
+    assert!(height_sorted(&orphan));
+
+    // prune everything settled
+    let clock = Arc::new(FakeClock::new(0));
+    let mut scheduler = MaintenanceScheduler::new(MaintenanceConfig::default(), clock.clone());
+    scheduler.register(Box::new(BlockRetentionTask::new(
+        store.clone(),
+        RetentionPolicy {
+            prune_noncanonical_below_height: Some(u32::MAX),
+            ..Default::default()
+        },
+    )));
+
+    let Schedule::Interval { millis } = Schedule::hourly() else {
+        unreachable!()
+    };
+    clock.advance(millis);
+    while let StepOutcome::Ran { done: false, .. } = scheduler.step() {}
+
+    let settled_height = store.get_orphans_settled_height()?;
+    assert_eq!(store.get_orphaned_blocks_pruned_height()?, settled_height);
+    assert_eq!(scheduler.statuses()[0].name, BlockRetentionTask::NAME);
+
+    // the settled orphan's data is dropped, its metadata is kept
+    assert!(store.get_block(&orphan)?.is_none());
+    assert!(store.get_block_ledger_diff(&orphan)?.is_none());
+    assert!(!height_sorted(&orphan));
+    assert_eq!(
+        store.get_block_canonicity(&orphan)?,
+        Some(Canonicity::Orphaned)
+    );
+    assert_eq!(store.get_block_height(&orphan)?, Some(10));
+
+    // canonical blocks are kept
+    assert!(store.get_block(&canonical)?.is_some());
+    assert!(height_sorted(&canonical));
+
+    // pruned blocks aren't ingested again
+    assert!(store.add_block(&orphan_block, orphan_bytes)?.is_none());
+
+    // pruning is idempotent
+    assert_eq!(store.prune_orphaned_blocks(settled_height)?, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn replay_after_pruning_orphaned_blocks() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("block-orphan-pruning-replay")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/gaps");
+
+    let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+    let mut state = IndexerState::new_v1(
+        indexer_store.clone(),
+        CANONICAL_THRESHOLD,
+        MAINNET_TRANSITION_FRONTIER_K,
+        false,
+    )?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = &indexer_store;
+    let orphan: StateHash = "3NKHYHrqKpDcon6ToV5CLDiheanjshk5gcsNqefnK78phCFTR2aL".into();
+    let canonical: StateHash = "3NKGgTk7en3347KH81yDra876GPAUSoSePrfVKPmwR1KHfMpvJC5".into();
+    assert!(store.get_blocks_at_height(10)?.contains(&orphan));
+
+    let is_block_event = |state_hash: &StateHash, event: &IndexerEvent| match event {
+        IndexerEvent::Db(DbEvent::Block(
+            DbBlockEvent::NewBlock {
+                state_hash: hash, ..
+            }
+            | DbBlockEvent::NewBestTip {
+                state_hash: hash, ..
+            },
+        )) => hash == state_hash,
+        _ => false,
+    };
+    assert!(store
+        .get_event_log()?
+        .iter()
+        .any(|event| is_block_event(&orphan, event)));
+
+    assert!(store.prune_orphaned_blocks(u32::MAX)? > 0);
+
+    // the orphan's events, height entry & command/SNARK indexes are dropped
+    assert!(store.get_block(&orphan)?.is_none());
+    assert!(!store.get_blocks_at_height(10)?.contains(&orphan));
+    assert!(store.get_blocks_at_height(10)?.contains(&canonical));
+    assert!(!store
+        .get_event_log()?
+        .iter()
+        .any(|event| is_block_event(&orphan, event)));
+    assert!(store.get_block_user_commands(&orphan)?.is_none());
+    assert!(store.get_block_internal_commands_count(&orphan)?.is_none());
+    assert!(store
+        .get_block_snark_work(&orphan)?
+        .unwrap_or_default()
+        .is_empty());
+
+    // replaying & syncing the pruned store rebuild the witness tree
+    let new_state = || {
+        IndexerState::new_without_genesis_events(IndexerStateConfig::new(
+            GenesisLedger::new_v1()?,
+            IndexerVersion::default(),
+            store.clone(),
+            CANONICAL_THRESHOLD,
+            10,
+            false,
+        ))
+    };
+
+    let mut replayed = new_state()?;
+    replayed.replay_events(&state)?;
+    assert_eq!(state.best_tip_block(), replayed.best_tip_block());
+
+    let mut synced = new_state()?;
+    synced.sync_from_db()?;
+    assert_eq!(
+        state.best_tip_block().state_hash,
+        synced.best_tip_block().state_hash
+    );
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{
+        get_block_file_paths,
+        parser::BlockParser,
+        precomputed::{PcbVersion, PrecomputedBlock},
+        receipt::{BlockReceipt, LatencyStats},
+        store::BlockStore,
+    },
+    constants::*,
+};
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, UNIX_EPOCH},
+};
+
+#[tokio::test]
+async fn synthetic_mtimes() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("block-receipt-store")?;
+    let blocks_dir = tempfile::TempDir::with_prefix("block-receipt-blocks")?;
+
+    // copy test blocks & set each mtime to {blockchain length - 105488}
+    // seconds after the block's slot start
+    let mut expected = vec![];
+    for path in get_block_file_paths(&PathBuf::from("./tests/data/sequential_blocks"), false)? {
+        let block = PrecomputedBlock::parse_file(&path, PcbVersion::V1)?;
+        let latency = (block.blockchain_length() - 105488) as u64;
+        let mtime = global_slot_to_millis(block.global_slot_since_genesis()) + latency * 1000;
+
+        let copy = blocks_dir.path().join(path.file_name().unwrap());
+        fs::copy(&path, &copy)?;
+        fs::File::options()
+            .write(true)
+            .open(&copy)?
+            .set_modified(UNIX_EPOCH + Duration::from_millis(mtime))?;
+
+        expected.push((copy, block.state_hash(), latency as f64, mtime));
+    }
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_with_canonical_chain_discovery(
+        blocks_dir.path(),
+        PcbVersion::V1,
+        MAINNET_CANONICAL_THRESHOLD,
+        false,
+        BLOCK_REPORTING_FREQ_NUM,
+        true,
+    )
+    .await?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let mut latencies = vec![];
+
+    for (_, state_hash, latency, mtime) in expected.iter() {
+        let receipt = store.get_block_receipt(state_hash)?.unwrap();
+        let global_slot = store.get_block_global_slot(state_hash)?.unwrap();
+
+        // blocks dir ingestion isn't live
+        assert_eq!(receipt.file_modified_at, Some(*mtime));
+        assert_eq!(receipt.watcher_seen_at, None);
+        assert!(!receipt.latency_reliable);
+        assert_eq!(receipt.latency_seconds(global_slot), Some(*latency));
+
+        latencies.push(*latency);
+    }
+
+    // watcher receipts don't clobber the first receipt
+    let (path, state_hash, _, mtime) = &expected[0];
+    state.record_block_receipt(state_hash, &BlockReceipt::from_watcher(path, mtime + 2000)?)?;
+
+    let receipt = store.get_block_receipt(state_hash)?.unwrap();
+    assert_eq!(receipt.watcher_seen_at, None);
+    assert!(!receipt.latency_reliable);
+
+    // sequential blocks span lengths 105489..=105501
+    let stats = LatencyStats::new(latencies).unwrap();
+    assert_eq!(stats.num_blocks, expected.len() as u32);
+    assert_eq!(stats.min, 1.0);
+    assert_eq!(stats.max, 13.0);
+    assert_eq!(stats.p99, 13.0);
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{parser::BlockParser, store::BlockStore, supply::BlockSupply},
+    canonicity::store::CanonicityStore,
+};
+use std::path::PathBuf;
+
+#[tokio::test]
+async fn epoch_rollups_match_canonical_blocks() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("block-supply-store")?;
+    let blocks_dir = PathBuf::from("./tests/data/sequential_blocks");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&blocks_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let best_height = store.get_best_block_height()?.unwrap();
+    let best_tip = store.get_best_block_hash()?.unwrap();
+    let best_supply = store.get_block_supply(&best_tip)?.unwrap();
+    let epoch = best_supply.epoch;
+
+    // brute-force recount of the epoch's canonical blocks
+    let mut canonical: Vec<(u32, BlockSupply)> = vec![];
+    for height in (1..=1).chain(105489..=best_height) {
+        if let Some(state_hash) = store.get_canonical_hash_at_height(height)? {
+            let supply = store.get_block_supply(&state_hash)?.unwrap();
+            if supply.epoch == epoch {
+                canonical.push((height, supply));
+            }
+        }
+    }
+
+    let supply = store.get_supply(None)?.unwrap();
+    assert_eq!(supply, store.get_supply(Some(epoch))?.unwrap());
+    assert_eq!(supply.epoch, epoch);
+    assert_eq!(supply.num_blocks, canonical.len() as u32);
+    assert_eq!(supply.blockchain_length, best_height);
+    assert_eq!(supply.global_slot, best_supply.global_slot);
+    assert_eq!(supply.total_currency, best_supply.total_currency);
+    assert_eq!(
+        supply.coinbase_emission,
+        canonical
+            .iter()
+            .map(|(_, supply)| supply.coinbase)
+            .sum::<u64>()
+    );
+    assert!(supply.coinbase_emission > 0);
+
+    // no staking ledger, no locked supply
+    assert_eq!(supply.locked_supply, None);
+    assert_eq!(supply.circulating_supply, None);
+    assert_eq!(store.get_supply(Some(epoch + 1))?, None);
+
+    // unapplying the best tip reverts to its canonical parent
+    let (prev_height, prev_supply) = canonical[canonical.len() - 2];
+    store.unapply_block_supply(&best_tip, best_height)?;
+    store.unapply_block_supply(&best_tip, best_height)?;
+
+    let without_tip = store.get_supply(Some(epoch))?.unwrap();
+    assert_eq!(without_tip.num_blocks, supply.num_blocks - 1);
+    assert_eq!(without_tip.blockchain_length, prev_height);
+    assert_eq!(without_tip.total_currency, prev_supply.total_currency);
+    assert_eq!(
+        without_tip.coinbase_emission,
+        supply.coinbase_emission - best_supply.coinbase
+    );
+
+    // reapplying is idempotent & restores the rollup
+    store.apply_block_supply(&best_tip, best_height)?;
+    store.apply_block_supply(&best_tip, best_height)?;
+    assert_eq!(store.get_supply(None)?.unwrap(), supply);
+
+    Ok(())
+}
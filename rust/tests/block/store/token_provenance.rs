@@ -0,0 +1,96 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::Request;
+use mina_indexer::{
+    block::{
+        precomputed::{PcbVersion, PrecomputedBlock},
+        store::BlockStore,
+    },
+    ledger::token::{provenance::ProvenanceStatus, TokenAddress},
+    web::graphql::build_schema,
+};
+use std::{path::PathBuf, sync::Arc};
+
+const TOKEN: &str = "xBxjFpJkbWpbGua7Lf36S1NLhffFoEChyP3pz6SYKnx7dFCTwg";
+
+/// Block creating the custom token & a later block using it, without its
+/// owner in `tokens_used`
+fn creation_and_later_blocks() -> anyhow::Result<(PrecomputedBlock, PrecomputedBlock)> {
+    let path = PathBuf::from("./tests/data/misc_blocks/mainnet-397612-3NLh3tvZpMPXxUhCLz1898BDV6CwtExJqDWpzcZQebVCsZxghoXK.json");
+    let creation = PrecomputedBlock::parse_file(&path, PcbVersion::V2)?;
+    let mut later = creation.clone();
+
+    if let PrecomputedBlock::V2(v2) = &mut later {
+        v2.state_hash = "3NLLmswaSwYVSERiQMdvTdKdBN6TNMgUGmd548zK7e82CaS3tNJK".into();
+        v2.blockchain_length.0 += 1;
+        for (token, owner) in v2.tokens_used.iter_mut() {
+            if token.0 == TOKEN {
+                *owner = None;
+            }
+        }
+    }
+
+    Ok((creation, later))
+}
+
+#[tokio::test]
+async fn unknown_until_backfilled() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("token-provenance")?;
+    let state = mainnet_genesis_state(store_dir.as_ref())?;
+    let store = state.indexer_store.as_ref().unwrap();
+    let token = TokenAddress::new(TOKEN).unwrap();
+    let (creation, later) = creation_and_later_blocks()?;
+
+    // only the later block is ingested
+    store.add_block(&later, 0)?;
+
+    let provenance = store.get_token_provenance(&token)?.unwrap();
+    assert_eq!(provenance.provenance, ProvenanceStatus::Unknown);
+    assert_eq!(provenance.first_seen_state_hash, later.state_hash());
+    assert_eq!(
+        provenance.first_seen_blockchain_length,
+        later.blockchain_length()
+    );
+    assert_eq!(store.get_num_unknown_provenance_tokens()?, 1);
+    assert_eq!(state.summary_short().num_unknown_provenance_tokens, 1);
+
+    // MINA is never recorded
+    assert!(store
+        .get_token_provenance(&TokenAddress::default())?
+        .is_none());
+
+    // backfill the creation block
+    store.add_block(&creation, 0)?;
+
+    let resolved = store.get_token_provenance(&token)?.unwrap();
+    assert_eq!(resolved.provenance, ProvenanceStatus::Known);
+    assert_eq!(resolved.first_seen_state_hash, later.state_hash());
+    assert_eq!(store.get_num_unknown_provenance_tokens()?, 0);
+    assert_eq!(state.summary_verbose().num_unknown_provenance_tokens, 0);
+
+    // GraphQL
+    let schema = build_schema(Arc::clone(store));
+    let response = schema
+        .execute(Request::new(format!(
+            r#"{{
+              tokenHolders(token: "{TOKEN}") {{
+                provenance {{
+                  provenance
+                  firstSeenStateHash
+                  firstSeenBlockchainLength
+                }}
+              }}
+            }}"#
+        )))
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json()?;
+    let provenance = &data["tokenHolders"]["provenance"];
+    assert_eq!(provenance["provenance"], "KNOWN");
+    assert_eq!(provenance["firstSeenStateHash"], later.state_hash().0);
+    assert_eq!(
+        provenance["firstSeenBlockchainLength"],
+        later.blockchain_length()
+    );
+    Ok(())
+}
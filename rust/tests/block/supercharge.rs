@@ -0,0 +1,89 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::Request;
+use mina_indexer::{
+    block::{
+        parser::BlockParser,
+        precomputed::{PcbVersion, PrecomputedBlock},
+        supercharge::{is_supercharge_eligible, SuperchargeAudit},
+    },
+    ledger::store::staking::StakingLedgerStore,
+    web::graphql::build_schema,
+};
+use std::path::PathBuf;
+
+const SUPERCHARGED_BLOCK: &str = "./tests/data/canonical_chain_discovery/contiguous/mainnet-6-3NKqRR2BZFV7Ad5kxtGKNNL59neXohf4ZEC5EMKrrnijB1jy4R5v.json";
+const REGULAR_BLOCK: &str = "./tests/data/canonical_chain_discovery/contiguous/mainnet-7-3NLGcwFVQF1p1PrZpusw2fZwBe5HKXGtrGy1Vc4aPkeBtT8nMNUc.json";
+
+#[tokio::test]
+async fn audit_supercharged_and_regular() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("supercharge-audit")?;
+    let blocks_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    let ledgers_dir = PathBuf::from("./tests/data/staking_ledgers");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&blocks_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.clone().unwrap();
+    let supercharged =
+        PrecomputedBlock::parse_file(&PathBuf::from(SUPERCHARGED_BLOCK), PcbVersion::V1)?;
+    let regular = PrecomputedBlock::parse_file(&PathBuf::from(REGULAR_BLOCK), PcbVersion::V1)?;
+    assert!(supercharged.supercharge_coinbase());
+    assert!(!regular.supercharge_coinbase());
+
+    // no epoch 0 staking ledger yet
+    assert_eq!(
+        SuperchargeAudit::audit(&store, &supercharged)?,
+        Some(SuperchargeAudit::LedgerUnavailable)
+    );
+
+    state
+        .add_startup_staking_ledgers_to_store(&ledgers_dir)
+        .await?;
+
+    // untimed winner account
+    let winner = store
+        .get_staking_account(&supercharged.block_stake_winner(), 0, None)?
+        .unwrap();
+    assert!(winner.timing.is_none());
+    assert!(is_supercharge_eligible(
+        &winner,
+        supercharged.global_slot_since_genesis()
+    ));
+    assert_eq!(
+        SuperchargeAudit::audit(&store, &supercharged)?,
+        Some(SuperchargeAudit::ExpectedMatches)
+    );
+
+    // winner account with locked tokens
+    let winner = store
+        .get_staking_account(&regular.block_stake_winner(), 0, None)?
+        .unwrap();
+    assert!(winner.timing.is_some());
+    assert!(!is_supercharge_eligible(
+        &winner,
+        regular.global_slot_since_genesis()
+    ));
+    assert_eq!(
+        SuperchargeAudit::audit(&store, &regular)?,
+        Some(SuperchargeAudit::ExpectedMatches)
+    );
+
+    // GraphQL
+    let schema = build_schema(store.clone());
+    let response = schema
+        .execute(Request::new(format!(
+            r#"{{
+              block(query: {{ stateHash: "{}" }}) {{
+                superchargeAudit
+              }}
+            }}"#,
+            regular.state_hash()
+        )))
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json()?;
+    assert_eq!(data["block"]["superchargeAudit"], "EXPECTED_MATCHES");
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use crate::helpers::store::*;
+use mina_indexer::{
+    block::{
+        precomputed::{PcbVersion, PrecomputedBlock},
+        validation::{
+            check_accounts_accessed, check_accounts_created, store::ValidationStore,
+            ValidationErrorKind,
+        },
+    },
+    constants::MAINNET_ACCOUNT_CREATION_FEE,
+    ledger::Ledger,
+    store::IndexerStore,
+};
+use speedb::Direction;
+use std::path::PathBuf;
+
+const BLOCK: &str = "./tests/data/misc_blocks/mainnet-397612-3NLh3tvZpMPXxUhCLz1898BDV6CwtExJqDWpzcZQebVCsZxghoXK.json";
+
+/// Ledger of the block's accessed accounts, as asserted by the block
+fn accessed_ledger(block: &PrecomputedBlock) -> Ledger {
+    let mut ledger = Ledger::new();
+    for accessed in block.accounts_accessed() {
+        let mut account = accessed.account;
+        let token = account.token.clone().unwrap_or_default();
+
+        // ledger balances include the account creation fee
+        account.balance = account.balance.saturating_add(MAINNET_ACCOUNT_CREATION_FEE);
+        ledger.insert_account(account, &token);
+    }
+    ledger
+}
+
+#[test]
+fn accounts_accessed_cross_check() -> anyhow::Result<()> {
+    let block = PrecomputedBlock::parse_file(&PathBuf::from(BLOCK), PcbVersion::V2)?;
+    let accessed = block.accounts_accessed();
+    assert!(!accessed.is_empty());
+
+    // agreeing ledger
+    let mut ledger = accessed_ledger(&block);
+    assert_eq!(check_accounts_accessed(&block, &ledger), vec![]);
+
+    // created accounts are already in the ledger before the block
+    let created = check_accounts_created(&block, &ledger);
+    assert_eq!(created.len(), block.accounts_created_v2().len());
+    assert!(created
+        .iter()
+        .all(|error| error.kind == ValidationErrorKind::AccountCreatedExists));
+
+    // balance mismatch
+    let expected = accessed[0].account.clone();
+    let token = expected.token.clone().unwrap_or_default();
+    let account = ledger
+        .get_mut_account(&expected.public_key, &token)
+        .unwrap();
+    account.balance = (account.balance + 1u64)?;
+
+    let errors = check_accounts_accessed(&block, &ledger);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ValidationErrorKind::AccountAccessedBalance);
+    assert_eq!(errors[0].public_key, expected.public_key);
+    assert_eq!(errors[0].state_hash, block.state_hash());
+    assert_eq!(errors[0].expected, Some(expected.balance.0.to_string()));
+    assert_eq!(errors[0].actual, Some((expected.balance.0 + 1).to_string()));
+
+    // missing account
+    let empty = Ledger::new();
+    let errors = check_accounts_accessed(&block, &empty);
+    assert!(errors.len() >= accessed.len());
+    assert!(errors.iter().any(|error| {
+        error.kind == ValidationErrorKind::AccountAccessedMissing
+            && error.public_key == expected.public_key
+    }));
+
+    // nothing was created before the block
+    assert_eq!(check_accounts_created(&block, &empty), vec![]);
+
+    Ok(())
+}
+
+#[test]
+fn validation_error_store() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("validation-error-store")?;
+    let store = IndexerStore::new(store_dir.path())?;
+
+    let block = PrecomputedBlock::parse_file(&PathBuf::from(BLOCK), PcbVersion::V2)?;
+    let errors = check_accounts_accessed(&block, &Ledger::new());
+    assert!(!errors.is_empty());
+
+    let state_hash = block.state_hash();
+    let length = block.blockchain_length();
+    store.set_validation_errors(&state_hash, length, &errors)?;
+    assert_eq!(
+        store.get_validation_errors(Direction::Forward, usize::MAX)?,
+        errors
+    );
+
+    // limited & reversed
+    let mut reversed = errors.clone();
+    reversed.reverse();
+    assert_eq!(
+        store.get_validation_errors(Direction::Reverse, 1)?,
+        reversed[..1]
+    );
+
+    // revalidating replaces the block's errors
+    store.set_validation_errors(&state_hash, length, &errors[..1])?;
+    assert_eq!(
+        store.get_validation_errors(Direction::Forward, usize::MAX)?,
+        errors[..1]
+    );
+
+    store.set_validation_errors(&state_hash, length, &[])?;
+    assert!(store
+        .get_validation_errors(Direction::Forward, usize::MAX)?
+        .is_empty());
+
+    Ok(())
+}
@@ -18,6 +18,7 @@ async fn gaps() -> anyhow::Result<()> {
         MAINNET_CANONICAL_THRESHOLD,
         false,
         BLOCK_REPORTING_FREQ_NUM,
+        true,
     )
     .await?;
 
@@ -41,6 +42,7 @@ async fn contiguous() -> anyhow::Result<()> {
         MAINNET_CANONICAL_THRESHOLD,
         false,
         BLOCK_REPORTING_FREQ_NUM,
+        true,
     )
     .await?;
 
@@ -64,6 +66,7 @@ async fn missing_parent() -> anyhow::Result<()> {
         MAINNET_CANONICAL_THRESHOLD,
         false,
         BLOCK_REPORTING_FREQ_NUM,
+        true,
     )
     .await?;
 
@@ -98,6 +101,7 @@ async fn one_block() -> anyhow::Result<()> {
         MAINNET_CANONICAL_THRESHOLD,
         false,
         BLOCK_REPORTING_FREQ_NUM,
+        true,
     )
     .await?;
 
@@ -116,6 +120,7 @@ async fn canonical_threshold() -> anyhow::Result<()> {
         canonical_threshold,
         false,
         BLOCK_REPORTING_FREQ_NUM,
+        true,
     )
     .await?;
 
@@ -0,0 +1,80 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{parser::BlockParser, store::BlockStore},
+    canonicity::{consistency::CanonicityMismatch, store::CanonicityStore},
+    constants::MAINNET_GENESIS_HASH,
+    event::{
+        db::{DbCanonicityEvent, DbEvent},
+        store::EventStore,
+        IndexerEvent,
+    },
+};
+use std::path::PathBuf;
+
+#[tokio::test]
+async fn detect_bogus_canonicity_entry() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("canonicity-consistency")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    let mut block_parser = BlockParser::new_testing(&block_dir)?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    state.add_blocks(&mut block_parser).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let best_height = store.get_best_block_height()?.unwrap();
+
+    // consistent
+    let report = store.verify_canonicity(1)?;
+    assert!(report.mismatches.is_empty(), "{:?}", report.mismatches);
+    assert_eq!(report.to_height, best_height);
+    assert!(report.num_heights >= 5);
+
+    // canonical block at height 4 is also recorded at height 5
+    let expected = store.get_canonical_hash_at_height(5)?.unwrap();
+    let bogus = store.get_canonical_hash_at_height(4)?.unwrap();
+    let bogus_slot = store.get_block_global_slot(&bogus)?.unwrap();
+    let bogus_seq_num = store.get_next_seq_num()?;
+
+    store.add_canonical_block(5, bogus_slot, &bogus, &MAINNET_GENESIS_HASH.into(), None)?;
+
+    let report = store.verify_canonicity(1)?;
+    assert_eq!(report.mismatches.len(), 1);
+
+    let CanonicityMismatch {
+        height,
+        expected: walked,
+        stored,
+        events,
+    } = &report.mismatches[0];
+    assert_eq!(*height, 5);
+    assert_eq!(*walked, expected);
+    assert_eq!(stored.as_ref(), Some(&bogus));
+
+    // the original & bogus canonical block events
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].state_hash, expected);
+    assert!(events[0].seq_num < bogus_seq_num);
+    assert_eq!(events[1].state_hash, bogus);
+    assert_eq!(events[1].seq_num, bogus_seq_num);
+
+    assert!(report.mismatches[0]
+        .to_string()
+        .contains(&format!("#{bogus_seq_num} {bogus}")));
+
+    // heights below the bogus entry are skipped
+    assert!(store.verify_canonicity(6)?.mismatches.is_empty());
+
+    // the recorded verification
+    store.record_canonicity_verification(1)?;
+    assert_eq!(
+        store.get_event_log()?.last(),
+        Some(&IndexerEvent::Db(DbEvent::Canonicity(
+            DbCanonicityEvent::CanonicityVerified {
+                from_height: 1,
+                to_height: best_height,
+                num_mismatches: 1,
+            }
+        )))
+    );
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{parser::BlockParser, store::BlockStore},
+    canonicity::{store::CanonicityStore, CanonicalBlockAt},
+    store::IndexerStore,
+};
+use std::path::PathBuf;
+
+/// Highest canonical block with date time at or before `date_time`
+fn expected_height(date_times: &[(u32, i64)], date_time: i64) -> Option<u32> {
+    date_times
+        .iter()
+        .filter(|(_, dt)| *dt <= date_time)
+        .map(|(height, _)| *height)
+        .max()
+}
+
+fn resolved_height(store: &IndexerStore, date_time: i64) -> anyhow::Result<Option<u32>> {
+    Ok(match store.get_canonical_block_at(date_time)? {
+        CanonicalBlockAt::Block {
+            height,
+            state_hash,
+            date_time: block_date_time,
+        } => {
+            assert_eq!(
+                store.get_canonical_hash_at_height(height)?,
+                Some(state_hash)
+            );
+            assert!(block_date_time <= date_time);
+            Some(height)
+        }
+        _ => None,
+    })
+}
+
+#[tokio::test]
+async fn canonical_block_at_date_time() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("canonicity-date-time")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut block_parser = BlockParser::new_testing(&block_dir)?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    state.add_blocks(&mut block_parser).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let best_block_height = store.get_best_block_height()?.unwrap();
+
+    let mut date_times = vec![];
+    for height in 1..=best_block_height {
+        let state_hash = store.get_canonical_hash_at_height(height)?.unwrap();
+        date_times.push((height, store.get_block_date_time(&state_hash)?.unwrap()));
+    }
+
+    let genesis_date_time = date_times.iter().map(|(_, dt)| *dt).min().unwrap();
+    let tip_date_time = date_times.iter().map(|(_, dt)| *dt).max().unwrap();
+
+    for (height, date_time) in date_times.iter() {
+        // exactly at the block's date time
+        assert_eq!(
+            resolved_height(store, *date_time)?,
+            expected_height(&date_times, *date_time)
+        );
+        assert!(resolved_height(store, *date_time)?.unwrap() >= *height);
+
+        // between block date times
+        for offset in [1, 60_000, 179_999] {
+            let between = date_time + offset;
+            if between <= tip_date_time {
+                assert_eq!(
+                    resolved_height(store, between)?,
+                    expected_height(&date_times, between)
+                );
+            }
+        }
+    }
+
+    // before genesis
+    assert_eq!(
+        store.get_canonical_block_at(genesis_date_time - 1)?,
+        CanonicalBlockAt::BeforeGenesis
+    );
+    assert_eq!(
+        store.get_canonical_block_at(-1)?,
+        CanonicalBlockAt::BeforeGenesis
+    );
+
+    // after the canonical tip
+    assert_eq!(
+        store.get_canonical_block_at(tip_date_time + 1)?,
+        CanonicalBlockAt::AfterTip
+    );
+    assert_eq!(
+        resolved_height(store, tip_date_time)?,
+        expected_height(&date_times, tip_date_time)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::state_hash::StateHash,
+    block::{
+        parser::BlockParser,
+        precomputed::{PcbVersion, PrecomputedBlock},
+        store::BlockStore,
+    },
+    constants::{
+        berkeley::BERKELEY_GENESIS_STATE_HASH, HARDFORK_GENESIS_HASH, MAINNET_GENESIS_HASH,
+    },
+    ingestion::{GenesisMismatch, IngestionPhase},
+};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const TIP_FILE: &str = "mainnet-21-3NKZ6DTHiMtuaeP3tJq2xe4uujVRnGT9FX1rBiZY521uNToSppUZ.json";
+
+/// Writes the tip block with its genesis state hash replaced
+fn write_tip(
+    contiguous_dir: &Path,
+    dir: &Path,
+    genesis_state_hash: &str,
+) -> anyhow::Result<PathBuf> {
+    let contents = std::fs::read_to_string(contiguous_dir.join(TIP_FILE))?;
+    assert!(contents.contains(MAINNET_GENESIS_HASH));
+
+    let path = dir.join(TIP_FILE);
+    std::fs::write(
+        &path,
+        contents.replace(MAINNET_GENESIS_HASH, genesis_state_hash),
+    )?;
+    Ok(path)
+}
+
+#[tokio::test]
+async fn reject_cross_network_blocks() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("canonicity-genesis-lineage")?;
+    let contiguous_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    // all blocks except the tip
+    let blocks_dir = TempDir::with_prefix("genesis-lineage-blocks")?;
+    for entry in std::fs::read_dir(&contiguous_dir)? {
+        let path = entry?.path();
+        if path.file_name().unwrap() != TIP_FILE {
+            std::fs::copy(&path, blocks_dir.path().join(path.file_name().unwrap()))?;
+        }
+    }
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(blocks_dir.path())?;
+    state.add_blocks(&mut bp).await?;
+    assert!(state.ingestion_report.is_empty());
+
+    // a v1 block claiming the hardfork genesis is rejected
+    let tip: StateHash = "3NKZ6DTHiMtuaeP3tJq2xe4uujVRnGT9FX1rBiZY521uNToSppUZ".into();
+    let hardfork_dir = TempDir::with_prefix("genesis-lineage-hardfork")?;
+    let path = write_tip(&contiguous_dir, hardfork_dir.path(), HARDFORK_GENESIS_HASH)?;
+
+    let block = PrecomputedBlock::parse_file(&path, PcbVersion::V1)?;
+    assert_eq!(block.genesis_state_hash().0, HARDFORK_GENESIS_HASH);
+    assert!(!state.block_pipeline(&block, path.metadata()?.len())?);
+    assert_eq!(
+        state.ingestion_report.genesis_mismatches,
+        vec![GenesisMismatch {
+            state_hash: tip.clone(),
+            blockchain_length: 21,
+            version: PcbVersion::V1,
+            genesis_state_hash: HARDFORK_GENESIS_HASH.into(),
+            expected_genesis_state_hash: Some(MAINNET_GENESIS_HASH.into()),
+        }]
+    );
+
+    let store = state.indexer_store.clone().unwrap();
+    assert_eq!(store.get_block_height(&tip)?, None);
+    assert_eq!(store.get_best_block_height()?, Some(20));
+
+    // a block from another network is rejected
+    let berkeley_dir = TempDir::with_prefix("genesis-lineage-berkeley")?;
+    let path = write_tip(
+        &contiguous_dir,
+        berkeley_dir.path(),
+        BERKELEY_GENESIS_STATE_HASH,
+    )?;
+
+    let mut bp = BlockParser::new_testing(berkeley_dir.path())?;
+    state.add_blocks(&mut bp).await?;
+
+    let skipped = &state.ingestion_report.skipped_blocks;
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].path.as_ref(), Some(&path));
+    assert_eq!(skipped[0].phase, IngestionPhase::Parse);
+    assert!(skipped[0].error.contains(BERKELEY_GENESIS_STATE_HASH));
+    assert_eq!(store.get_block_height(&tip)?, None);
+
+    let block = PrecomputedBlock::parse_file(&path, PcbVersion::V1)?;
+    assert!(!state.block_pipeline(&block, path.metadata()?.len())?);
+    assert_eq!(state.ingestion_report.genesis_mismatches.len(), 2);
+    assert_eq!(
+        state.ingestion_report.genesis_mismatches[1]
+            .genesis_state_hash
+            .0,
+        BERKELEY_GENESIS_STATE_HASH
+    );
+
+    // the untouched tip ingests as before
+    let tip_dir = TempDir::with_prefix("genesis-lineage-tip")?;
+    std::fs::copy(contiguous_dir.join(TIP_FILE), tip_dir.path().join(TIP_FILE))?;
+
+    let mut bp = BlockParser::new_testing(tip_dir.path())?;
+    state.add_blocks(&mut bp).await?;
+
+    assert_eq!(state.ingestion_report.genesis_mismatches.len(), 2);
+    assert_eq!(store.get_block_height(&tip)?, Some(21));
+    assert_eq!(store.get_best_block_height()?, Some(21));
+    Ok(())
+}
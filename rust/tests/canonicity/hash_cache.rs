@@ -0,0 +1,130 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::state_hash::StateHash,
+    block::{parser::BlockParser, store::BlockStore},
+    canonicity::{store::CanonicityStore, CanonicityDiff, CanonicityUpdate},
+    state::IndexerState,
+    store::IndexerStore,
+};
+use quickcheck::{Arbitrary, Gen};
+use std::{path::PathBuf, time::Instant};
+
+async fn contiguous_state(store_dir: &tempfile::TempDir) -> anyhow::Result<IndexerState> {
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    let mut block_parser = BlockParser::new_testing(&block_dir)?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+
+    state.add_blocks(&mut block_parser).await?;
+    Ok(state)
+}
+
+/// Canonical state hashes from genesis
+fn canonical_hashes(store: &IndexerStore) -> anyhow::Result<Vec<StateHash>> {
+    let mut hashes = vec![];
+    while let Some(state_hash) = store.get_canonical_hash_at_height(hashes.len() as u32 + 1)? {
+        hashes.push(state_hash);
+    }
+
+    Ok(hashes)
+}
+
+fn canonicity_diff(store: &IndexerStore, state_hash: &StateHash) -> anyhow::Result<CanonicityDiff> {
+    Ok(CanonicityDiff {
+        state_hash: state_hash.clone(),
+        blockchain_length: store.get_block_height(state_hash)?.unwrap(),
+        global_slot: store.get_block_global_slot(state_hash)?.unwrap(),
+    })
+}
+
+#[tokio::test]
+async fn truncated_on_reorg() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("canonical-hash-cache-reorg")?;
+    let state = contiguous_state(&store_dir).await?;
+    let store = state.indexer_store.as_ref().unwrap();
+
+    // lazily loaded by the first lookup
+    let hashes = canonical_hashes(store)?;
+    let num_canonical = hashes.len() as u32;
+    assert!(num_canonical > 5);
+
+    let stats = store.canonical_hash_cache.stats();
+    assert!(stats.loaded);
+    assert_eq!((stats.start_height, stats.len), (1, num_canonical));
+
+    // reorg from height 5
+    let reorg_height = 5;
+    let unapply = hashes[(reorg_height - 1) as usize..]
+        .iter()
+        .rev()
+        .map(|state_hash| canonicity_diff(store, state_hash))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    store.update_canonicity(CanonicityUpdate {
+        apply: vec![],
+        unapply,
+    })?;
+
+    assert_eq!(store.canonical_hash_cache.stats().len, reorg_height - 1);
+    assert_eq!(
+        store.get_canonical_hash_at_height(reorg_height - 1)?,
+        Some(hashes[(reorg_height - 2) as usize].clone())
+    );
+    assert_eq!(store.get_canonical_hash_at_height(reorg_height)?, None);
+
+    // new canonical blocks repopulate the cache
+    let apply = hashes[(reorg_height - 1) as usize..]
+        .iter()
+        .map(|state_hash| canonicity_diff(store, state_hash))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    store.update_canonicity(CanonicityUpdate {
+        apply,
+        unapply: vec![],
+    })?;
+
+    let stats = store.canonical_hash_cache.stats();
+    assert_eq!(stats.len, num_canonical);
+
+    let hits = stats.hits;
+    assert_eq!(canonical_hashes(store)?, hashes);
+    assert_eq!(
+        store.canonical_hash_cache.stats().hits,
+        hits + num_canonical as u64
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "benchmark"]
+async fn random_lookups_benchmark() -> anyhow::Result<()> {
+    const NUM_LOOKUPS: usize = 1_000_000;
+
+    let store_dir = setup_new_db_dir("canonical-hash-cache-bench")?;
+    let state = contiguous_state(&store_dir).await?;
+    let store = state.indexer_store.as_ref().unwrap();
+    let num_canonical = canonical_hashes(store)?.len() as u64;
+
+    // secondary stores don't use the cache
+    let secondary_dir = setup_new_db_dir("canonical-hash-cache-bench-secondary")?;
+    let secondary = IndexerStore::read_only(store_dir.path(), secondary_dir.path())?;
+
+    let mut gen = Gen::new(100);
+    let heights: Vec<u32> = (0..NUM_LOOKUPS)
+        .map(|_| (u64::arbitrary(&mut gen) % num_canonical) as u32 + 1)
+        .collect();
+
+    let time_lookups = |store: &IndexerStore| -> anyhow::Result<f64> {
+        let start = Instant::now();
+        for height in heights.iter() {
+            assert!(store.get_canonical_hash_at_height(*height)?.is_some());
+        }
+
+        Ok(start.elapsed().as_secs_f64())
+    };
+
+    let uncached = time_lookups(&secondary)?;
+    let cached = time_lookups(store)?;
+    println!("{NUM_LOOKUPS} random canonical lookups: cached {cached:.3}s, store {uncached:.3}s");
+
+    assert!(cached < uncached);
+    Ok(())
+}
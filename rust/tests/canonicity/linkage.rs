@@ -0,0 +1,102 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::state_hash::StateHash,
+    block::{parser::BlockParser, store::BlockStore},
+    canonicity::{linkage::LinkageError, store::CanonicityStore},
+    ingestion::ParentLinkageError,
+};
+use serde_json::Value;
+use speedb::WriteBatch;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+const TIP_FILE: &str = "mainnet-21-3NKZ6DTHiMtuaeP3tJq2xe4uujVRnGT9FX1rBiZY521uNToSppUZ.json";
+
+#[tokio::test]
+async fn reject_parent_mismatch_and_verify_linkage() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("canonicity-linkage")?;
+    let contiguous_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    // all blocks except the tip
+    let blocks_dir = TempDir::with_prefix("linkage-blocks")?;
+    for entry in std::fs::read_dir(&contiguous_dir)? {
+        let path = entry?.path();
+        if path.file_name().unwrap() != TIP_FILE {
+            std::fs::copy(&path, blocks_dir.path().join(path.file_name().unwrap()))?;
+        }
+    }
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(blocks_dir.path())?;
+    state.add_blocks(&mut bp).await?;
+    assert!(state.ingestion_report.is_empty());
+
+    // the tip claims the right height, but its grandparent as parent
+    let tip: StateHash = "3NKZ6DTHiMtuaeP3tJq2xe4uujVRnGT9FX1rBiZY521uNToSppUZ".into();
+    let wrong_parent: StateHash = "3NLEu5K5pmEH1CSKZJd94eJatDTM3djoeJTVE3RkcNztJ4z63bM6".into();
+
+    let mut json: Value = serde_json::from_slice(&std::fs::read(contiguous_dir.join(TIP_FILE))?)?;
+    json["protocol_state"]["previous_state_hash"] = Value::String(wrong_parent.to_string());
+
+    let corrupt_dir = TempDir::with_prefix("linkage-corrupt")?;
+    std::fs::write(
+        corrupt_dir.path().join(TIP_FILE),
+        serde_json::to_vec(&json)?,
+    )?;
+
+    let mut bp = BlockParser::new_testing(corrupt_dir.path())?;
+    state.add_blocks(&mut bp).await?;
+
+    // the block is rejected rather than creating a dangling branch
+    assert_eq!(
+        state.ingestion_report.parent_linkage_errors,
+        vec![ParentLinkageError {
+            state_hash: tip.clone(),
+            blockchain_length: 21,
+            parent_hash: wrong_parent.clone(),
+            stored_parent_hash: wrong_parent,
+            stored_parent_length: 19,
+        }]
+    );
+    assert!(state.dangling_branches.is_empty());
+
+    let store = state.indexer_store.as_ref().unwrap();
+    assert_eq!(store.get_block_height(&tip)?, None);
+    assert_eq!(store.get_best_block_height()?, Some(20));
+
+    // the canonical chain is linked
+    let mut num_canonical = 0;
+    for height in 1..=20 {
+        if store.get_canonical_hash_at_height(height)?.is_some() {
+            num_canonical += 1;
+        }
+    }
+    assert!(num_canonical >= 5);
+
+    let report = store.verify_linkage()?;
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+    assert_eq!(report.num_blocks, num_canonical);
+
+    // corrupt a canonical block's parent pointer
+    let state_hash = store.get_canonical_hash_at_height(5)?.unwrap();
+    let expected = store.get_canonical_hash_at_height(4)?.unwrap();
+    let corrupt_parent = store.get_canonical_hash_at_height(3)?.unwrap();
+
+    let mut batch = WriteBatch::default();
+    store.set_block_parent_hash_batch(&state_hash, &corrupt_parent, &mut batch)?;
+    store.database.write(batch)?;
+
+    let report = store.verify_linkage()?;
+    assert_eq!(report.num_blocks, num_canonical);
+    assert_eq!(
+        report.errors,
+        vec![LinkageError::ParentMismatch {
+            state_hash,
+            height: 5,
+            parent_hash: Some(corrupt_parent),
+            expected,
+        }]
+    );
+
+    Ok(())
+}
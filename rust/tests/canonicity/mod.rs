@@ -1,3 +1,8 @@
 pub mod blocks;
 pub mod chain_discovery;
+pub mod consistency;
+pub mod date_time;
+pub mod genesis_lineage;
+pub mod hash_cache;
 pub mod ledgers;
+pub mod linkage;
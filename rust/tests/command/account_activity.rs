@@ -0,0 +1,151 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::Request;
+use mina_indexer::{
+    base::public_key::PublicKey,
+    block::{
+        parser::BlockParser,
+        precomputed::{PcbVersion, PrecomputedBlock},
+    },
+    command::{
+        activity::{AccountActivity, AccountActivityKind, ActivityDirection},
+        UserCommandWithStatusT,
+    },
+    web::graphql::build_schema,
+};
+use std::path::PathBuf;
+
+/// Sends payments to others & itself
+const BUSY_PK: &str = "B62qre3erTHfzQckNuibViWQGyyKwZseztqrjPZBv6SQF384Rg6ESAy";
+
+/// Receives coinbases & fee transfers
+const PRODUCER_PK: &str = "B62qrdhG66vK71Jbdz6Xs7cnDxQ8f6jZUFvefkp3pje4EejYUTvotGP";
+
+fn assert_descending(activity: &[AccountActivity]) {
+    for pair in activity.windows(2) {
+        assert!(
+            pair[0].cursor() > pair[1].cursor(),
+            "{:?} before {:?}",
+            pair[0],
+            pair[1]
+        );
+    }
+}
+
+#[tokio::test]
+async fn interleaved_and_paginated() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("account-activity")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let pk: PublicKey = BUSY_PK.into();
+
+    // each of the account's commands appears exactly once
+    let mut num_commands = 0;
+    let mut num_self_transfers = 0;
+    for entry in std::fs::read_dir(&block_dir)? {
+        let block = PrecomputedBlock::parse_file(&entry?.path(), PcbVersion::V1)?;
+        for cmd in block.commands() {
+            if cmd.sender() == pk || cmd.receiver().contains(&pk) {
+                num_commands += 1;
+            }
+            if cmd.sender() == pk && cmd.receiver().contains(&pk) {
+                num_self_transfers += 1;
+            }
+        }
+    }
+    assert!(num_self_transfers > 0);
+
+    let activity = store.get_account_activity(&pk, &[], None, usize::MAX)?;
+    assert_descending(&activity);
+    assert_eq!(activity.len(), num_commands);
+    assert_eq!(
+        activity
+            .iter()
+            .filter(|entry| entry.direction == ActivityDirection::SelfTransfer)
+            .count(),
+        num_self_transfers
+    );
+    assert!(activity
+        .iter()
+        .all(|entry| entry.kind == AccountActivityKind::Payment && entry.txn_hash.is_some()));
+
+    // cursor pagination yields the same feed
+    let mut paginated = vec![];
+    let mut cursor = None;
+    loop {
+        let page = store.get_account_activity(&pk, &[], cursor.as_ref(), 3)?;
+        if page.is_empty() {
+            break;
+        }
+
+        cursor = page.last().map(AccountActivity::cursor);
+        paginated.extend(page);
+    }
+    assert_eq!(paginated, activity);
+
+    // coinbases & fee transfers interleaved by block & intra-block order
+    let producer: PublicKey = PRODUCER_PK.into();
+    let activity = store.get_account_activity(&producer, &[], None, usize::MAX)?;
+    assert_descending(&activity);
+    assert!(activity
+        .iter()
+        .any(|entry| entry.kind == AccountActivityKind::FeeTransfer));
+
+    let coinbases =
+        store.get_account_activity(&producer, &[AccountActivityKind::Coinbase], None, 100)?;
+    assert_eq!(
+        coinbases
+            .iter()
+            .map(|entry| entry.blockchain_length)
+            .collect::<Vec<_>>(),
+        vec![19, 13, 10, 6]
+    );
+    assert!(coinbases
+        .iter()
+        .all(|entry| entry.direction == ActivityDirection::In && entry.counterparty.is_none()));
+
+    // GraphQL pages match the store
+    let schema = build_schema(store.clone());
+    let query = |cursor: Option<String>| {
+        let cursor = cursor.map_or(String::new(), |cursor| format!(r#", cursor: "{cursor}""#));
+        Request::new(format!(
+            r#"{{
+              accountActivity(publicKey: "{BUSY_PK}", limit: 5{cursor}) {{
+                entries {{ kind blockHeight stateHash indexInBlock direction txnHash }}
+                nextCursor
+              }}
+            }}"#
+        ))
+    };
+
+    let response = schema.execute(query(None)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let first = response.data.into_json()?;
+
+    let next_cursor = first["accountActivity"]["nextCursor"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let response = schema.execute(query(Some(next_cursor))).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let second = response.data.into_json()?;
+
+    let expect = store.get_account_activity(&pk, &[], None, 10)?;
+    let entries = first["accountActivity"]["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .chain(second["accountActivity"]["entries"].as_array().unwrap());
+    for (entry, expect) in entries.zip(expect.iter()) {
+        assert_eq!(entry["blockHeight"], expect.blockchain_length);
+        assert_eq!(entry["stateHash"], expect.state_hash.0);
+        assert_eq!(entry["indexInBlock"], expect.index_in_block);
+        assert_eq!(entry["kind"], "PAYMENT");
+    }
+
+    Ok(())
+}
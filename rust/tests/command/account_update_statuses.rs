@@ -0,0 +1,116 @@
+use mina_indexer::{
+    block::precomputed::{PcbVersion, PrecomputedBlock},
+    command::{
+        signed::SignedCommandWithData, AccountUpdateStatus, CommandStatusData,
+        UserCommandWithStatus, UserCommandWithStatusT,
+    },
+    ledger::diff::{
+        account::{AccountDiff, FailedTransactionNonceDiff},
+        LedgerDiff,
+    },
+    mina_blocks::v2::staged_ledger_diff::Status,
+    protocol::serialization_types::staged_ledger_diff::TransactionStatusFailedType,
+    web::graphql::transactions::TransactionWithoutBlock,
+};
+use serde_json::json;
+use std::path::PathBuf;
+
+/// Fails the block's zkapp command with 4 account updates: the second fails,
+/// the others are cancelled
+fn partially_failed_block() -> anyhow::Result<(PrecomputedBlock, UserCommandWithStatus)> {
+    let path = "./tests/data/misc_blocks/mainnet-397612-3NLh3tvZpMPXxUhCLz1898BDV6CwtExJqDWpzcZQebVCsZxghoXK.json";
+    let mut block = PrecomputedBlock::parse_file(&PathBuf::from(path), PcbVersion::V2)?;
+    let applied = block.commands_post_diff()[5].clone();
+
+    // index-mapped failures, the fee payer first
+    let status: Status = serde_json::from_value(json!([
+        "Failed",
+        [
+            [],
+            [["Cancelled"]],
+            [["Account_balance_precondition_unsatisfied"]],
+            [["Cancelled"]],
+            [["Cancelled"]],
+        ]
+    ]))?;
+
+    if let PrecomputedBlock::V2(v2) = &mut block {
+        v2.staged_ledger_diff.diff[1].as_mut().unwrap().commands[5].status = status;
+    }
+
+    Ok((block, applied))
+}
+
+#[test]
+fn per_update_statuses() -> anyhow::Result<()> {
+    let (block, applied) = partially_failed_block()?;
+    let failed = block.commands_post_diff()[5].clone();
+
+    assert!(applied.is_zkapp_command());
+    assert_eq!(
+        applied.account_update_statuses(),
+        vec![AccountUpdateStatus::Applied; 4]
+    );
+
+    assert!(!failed.is_applied());
+    assert_eq!(
+        failed.account_update_statuses(),
+        vec![
+            AccountUpdateStatus::Cancelled,
+            AccountUpdateStatus::Failed,
+            AccountUpdateStatus::Cancelled,
+            AccountUpdateStatus::Cancelled,
+        ]
+    );
+
+    // the cause is reported first
+    match failed.status_data() {
+        CommandStatusData::Failed(failures, _) => assert_eq!(
+            failures.first(),
+            Some(&TransactionStatusFailedType::AccountBalancePreconditionUnsatisfied)
+        ),
+        status => panic!("unexpected status {status:?}"),
+    }
+
+    // stored & exposed per update
+    let commands = SignedCommandWithData::from_precomputed(&block);
+    let stored = commands
+        .iter()
+        .find(|cmd| cmd.index_in_block as usize == block.commands_pre_diff().len() + 5)
+        .unwrap();
+    assert_eq!(
+        stored.account_update_statuses,
+        failed.account_update_statuses()
+    );
+
+    let txn = serde_json::to_value(TransactionWithoutBlock::new(stored.clone(), true, 0, 0))?;
+    let updates = &txn["zkapp_command"]["account_updates"];
+    assert_eq!(updates[0]["status"], "CANCELLED");
+    assert_eq!(updates[1]["status"], "FAILED");
+    assert_eq!(updates[1]["calls"][0]["status"], "CANCELLED");
+    assert_eq!(updates[1]["calls"][1]["status"], "CANCELLED");
+    Ok(())
+}
+
+#[test]
+fn no_diffs_for_unapplied_updates() -> anyhow::Result<()> {
+    let (block, applied) = partially_failed_block()?;
+    let ledger_diff = LedgerDiff::from_precomputed_unexpanded(&block);
+
+    // only the fee payer's nonce is incremented
+    assert!(ledger_diff
+        .account_diffs
+        .contains(&vec![AccountDiff::FailedTransactionNonce(
+            FailedTransactionNonceDiff {
+                public_key: applied.fee_payer_pk(),
+                nonce: applied.nonce() + 1,
+            }
+        )]));
+
+    // none of the account updates' balance or state diffs
+    for diffs in AccountDiff::from_command(applied.to_command()) {
+        assert!(!ledger_diff.account_diffs.contains(&diffs));
+    }
+
+    Ok(())
+}
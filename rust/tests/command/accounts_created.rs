@@ -0,0 +1,111 @@
+use mina_indexer::{
+    base::{amount::Amount, public_key::PublicKey},
+    block::precomputed::{PcbVersion, PrecomputedBlock},
+    command::{signed::SignedCommandWithData, Command, CommandType},
+    constants::MINA_SCALE,
+    ledger::token::TokenAddress,
+};
+use std::path::PathBuf;
+
+fn block_commands(path: &str, version: PcbVersion) -> anyhow::Result<Vec<SignedCommandWithData>> {
+    let block = PrecomputedBlock::parse_file(&PathBuf::from(path), version)?;
+    Ok(SignedCommandWithData::from_precomputed(&block))
+}
+
+#[test]
+fn v1_receiver_account_created() -> anyhow::Result<()> {
+    let commands = block_commands(
+        "./tests/data/sequential_blocks/mainnet-105490-3NKxEA9gztvEGxL4uk4eTncZAxuRmMsB8n81UkeAMevUjMbLHmkC.json",
+        PcbVersion::V1,
+    )?;
+    let receiver = PublicKey::from("B62qq66ZuaVGxVvNwR752jPoZfN4uyZWrKkLeBS8FxdG9S76dhscRLy");
+
+    assert_eq!(commands.len(), 4);
+    for cmd in commands {
+        let is_new_receiver_account = cmd.command.receiver_pk() == vec![receiver.clone()];
+        let fee = cmd.receiver_account_creation_fee();
+
+        if is_new_receiver_account {
+            assert_eq!(fee, Some(Amount(MINA_SCALE)));
+        } else {
+            // ordinary payments
+            assert_eq!(fee, None);
+            assert!(cmd.accounts_created.is_empty());
+        }
+
+        match Command::from(cmd) {
+            Command::Payment(payment) => {
+                assert_eq!(payment.is_new_receiver_account, is_new_receiver_account)
+            }
+            _ => panic!("expected payment"),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn v2_receiver_account_created() -> anyhow::Result<()> {
+    let commands = block_commands(
+        "./tests/data/hardfork/mainnet-359610-3NLe669kJ89t48btn8NX6jMy7vnWNjP9caBdGgsCw2VSMjzP1anW.json",
+        PcbVersion::V2,
+    )?;
+    let receiver = PublicKey::from("B62qpf8NdK4A2oRCjQxEcvzeP2K3gbtAHEJXpic3F34e3R2s77k1czt");
+
+    // 1 payment & 3 zkapp commands
+    assert_eq!(commands.len(), 4);
+    assert_eq!(commands[0].command.kind(), CommandType::Payment);
+    assert_eq!(commands[0].command.receiver_pk(), vec![receiver]);
+    assert_eq!(
+        commands[0].receiver_account_creation_fee(),
+        Some(Amount(MINA_SCALE))
+    );
+
+    for cmd in &commands[1..] {
+        assert_eq!(cmd.command.kind(), CommandType::Zkapp);
+        assert_eq!(cmd.receiver_account_creation_fee(), None);
+        assert!(cmd.accounts_created.is_empty());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn v2_zkapp_account_updates_created() -> anyhow::Result<()> {
+    let path = "./tests/data/misc_blocks/mainnet-397612-3NLh3tvZpMPXxUhCLz1898BDV6CwtExJqDWpzcZQebVCsZxghoXK.json";
+    let block = PrecomputedBlock::parse_file(&PathBuf::from(path), PcbVersion::V2)?;
+    let commands = SignedCommandWithData::from_precomputed(&block);
+
+    // the only created account is a nested custom token account update,
+    // not the earlier MINA account update for the same public key
+    let creators: Vec<&SignedCommandWithData> = commands
+        .iter()
+        .filter(|cmd| !cmd.accounts_created.is_empty())
+        .collect();
+    assert_eq!(creators.len(), 1);
+    assert_eq!(creators[0].command.kind(), CommandType::Zkapp);
+    assert_eq!(creators[0].accounts_created, block.accounts_created_v2());
+    assert_eq!(creators[0].receiver_account_creation_fee(), None);
+
+    let created = &creators[0].accounts_created[0];
+    assert_eq!(
+        created.public_key,
+        PublicKey::from("B62qjSHAcwTouw5pxYECuJSFtmG6xup3DeK6f5BWW3BBhvEumW6daEm")
+    );
+    assert_ne!(created.token, TokenAddress::default());
+    assert_eq!(created.creation_fee, Amount(MINA_SCALE));
+
+    // multiple account updates of a single zkapp command
+    let path = "./tests/data/hardfork/mainnet-359617-3NKZ5poCAjtGqg9hHvAVZ7QwriqJsL8mpQsSHFGzqW6ddEEjYfvW.json";
+    let block = PrecomputedBlock::parse_file(&PathBuf::from(path), PcbVersion::V2)?;
+    let commands = SignedCommandWithData::from_precomputed(&block);
+    let last = commands.last().unwrap();
+
+    assert_eq!(last.command.kind(), CommandType::Zkapp);
+    assert_eq!(last.accounts_created, block.accounts_created_v2());
+    assert!(commands[..commands.len() - 1]
+        .iter()
+        .all(|cmd| cmd.accounts_created.is_empty()));
+
+    Ok(())
+}
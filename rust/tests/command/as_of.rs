@@ -0,0 +1,121 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::public_key::PublicKey,
+    block::{
+        parser::BlockParser,
+        store::{BlockStore, BlockUpdate, DbBlockUpdate},
+    },
+    canonicity::{as_of::CanonicalChainAsOf, store::CanonicityStore},
+    command::{
+        internal::{store::InternalCommandStore, DbInternalCommandWithData},
+        store::UserCommandStore,
+    },
+    store::DbUpdate,
+};
+use std::path::PathBuf;
+
+const AS_OF_HEIGHT: u32 = 10;
+
+#[tokio::test]
+async fn export_unchanged_by_reorg() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("as-of-export")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let best_height = store.get_best_block_height()?.unwrap();
+
+    // internal command receiver with commands on both sides of the asOf height
+    let mut pk = None;
+    for height in 2..=AS_OF_HEIGHT {
+        let state_hash = store.get_canonical_hash_at_height(height)?.unwrap();
+        let block = store.get_block(&state_hash)?.unwrap().0;
+
+        for cmd in DbInternalCommandWithData::from_precomputed(&block) {
+            let receiver = cmd.public_keys();
+            if store
+                .get_internal_commands_public_key(&receiver, 0, usize::MAX)?
+                .iter()
+                .any(|cmd| cmd.block_height() > AS_OF_HEIGHT)
+            {
+                pk = Some(receiver);
+                break;
+            }
+        }
+
+        if pk.is_some() {
+            break;
+        }
+    }
+    let pk: PublicKey = pk.expect("receiver above & below the asOf height");
+
+    // only blocks at or below the asOf height are included
+    let mut as_of = CanonicalChainAsOf::new(store, AS_OF_HEIGHT)?;
+    for height in 1..=best_height {
+        let state_hash = store.get_canonical_hash_at_height(height)?.unwrap();
+        assert_eq!(
+            as_of.contains(store, &state_hash)?,
+            height <= AS_OF_HEIGHT,
+            "{state_hash} (length {height})"
+        );
+    }
+
+    // record the asOf exports
+    let internal_path = store_dir.path().join("internal-commands.csv");
+    let user_path = store_dir.path().join("user-commands.csv");
+    let current_path = store_dir.path().join("current.csv");
+
+    store.write_internal_commands_csv(
+        pk.clone(),
+        Some(internal_path.clone()),
+        Some(AS_OF_HEIGHT),
+    )?;
+    store.write_user_commands_csv(&pk, Some(user_path.clone()), Some(AS_OF_HEIGHT))?;
+    store.write_internal_commands_csv(pk.clone(), Some(current_path.clone()), None)?;
+
+    let internal_csv = std::fs::read_to_string(&internal_path)?;
+    let user_csv = std::fs::read_to_string(&user_path)?;
+    let current_csv = std::fs::read_to_string(&current_path)?;
+
+    let num_as_of_cmds = store
+        .get_internal_commands_public_key(&pk, 0, usize::MAX)?
+        .iter()
+        .filter(|cmd| cmd.block_height() <= AS_OF_HEIGHT)
+        .count();
+    assert_eq!(internal_csv.lines().count(), num_as_of_cmds + 1);
+    assert_ne!(internal_csv, current_csv);
+
+    // reorg away the blocks above the asOf height
+    let mut unapply = vec![];
+    for height in (AS_OF_HEIGHT + 1..=best_height).rev() {
+        let state_hash = store.get_canonical_hash_at_height(height)?.unwrap();
+        let global_slot_since_genesis = store.get_block_global_slot(&state_hash)?.unwrap();
+
+        unapply.push(BlockUpdate {
+            state_hash,
+            blockchain_length: height,
+            global_slot_since_genesis,
+        });
+    }
+    let reorg: DbBlockUpdate = DbUpdate {
+        apply: vec![],
+        unapply,
+    };
+    store.update_block_canonicities(&reorg)?;
+
+    // asOf exports are unchanged
+    store.write_internal_commands_csv(
+        pk.clone(),
+        Some(internal_path.clone()),
+        Some(AS_OF_HEIGHT),
+    )?;
+    store.write_user_commands_csv(&pk, Some(user_path.clone()), Some(AS_OF_HEIGHT))?;
+
+    assert_eq!(std::fs::read_to_string(&internal_path)?, internal_csv);
+    assert_eq!(std::fs::read_to_string(&user_path)?, user_csv);
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use crate::helpers::store::*;
+use mina_indexer::{
+    base::public_key::PublicKey,
+    block::{
+        precomputed::{PcbVersion, PrecomputedBlock},
+        store::BlockStore,
+    },
+    command::{
+        internal::store::InternalCommandStore, signed::SignedCommandWithData,
+        store::UserCommandStore,
+    },
+    store::IndexerStore,
+};
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// `(fee payer, nonce)` of the block's commands in the raw block JSON order,
+/// pre diff then post diff
+fn raw_command_order(path: &str) -> anyhow::Result<Vec<(PublicKey, u32)>> {
+    let json: Value = serde_json::from_slice(&std::fs::read(path)?)?;
+    let block = json.get("data").unwrap_or(&json);
+
+    let mut commands = vec![];
+    for diff in block["staged_ledger_diff"]["diff"].as_array().unwrap() {
+        if diff.is_null() {
+            continue;
+        }
+
+        for command in diff["commands"].as_array().unwrap() {
+            let data = &command["data"][1];
+            let (fee_payer, nonce) = match command["data"][0].as_str().unwrap() {
+                "Signed_command" => (
+                    &data["payload"]["common"]["fee_payer_pk"],
+                    &data["payload"]["common"]["nonce"],
+                ),
+                "Zkapp_command" => (
+                    &data["fee_payer"]["body"]["public_key"],
+                    &data["fee_payer"]["body"]["nonce"],
+                ),
+                kind => panic!("unexpected command kind {kind}"),
+            };
+
+            commands.push((
+                PublicKey::from(fee_payer.as_str().unwrap()),
+                nonce.as_str().unwrap().parse()?,
+            ));
+        }
+    }
+
+    Ok(commands)
+}
+
+fn assert_apply_order(name: &str, path: &str, version: PcbVersion) -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir(name)?;
+    let store = IndexerStore::new(store_dir.path())?;
+    let block = PrecomputedBlock::parse_file(&PathBuf::from(path), version)?;
+    let state_hash = block.state_hash();
+    store.add_block(&block, 0)?;
+
+    let mut commands: Vec<SignedCommandWithData> = block
+        .command_hashes()
+        .iter()
+        .map(|txn_hash| {
+            store
+                .get_user_command_state_hash(txn_hash, &state_hash)
+                .unwrap()
+                .unwrap()
+        })
+        .collect();
+    commands.sort_by_key(|cmd| cmd.index_in_block);
+
+    // stored indices are the raw block order
+    let expected = raw_command_order(path)?;
+    let num_commands = expected.len() as u32;
+    assert_eq!(
+        commands
+            .iter()
+            .map(|cmd| cmd.index_in_block)
+            .collect::<Vec<_>>(),
+        (0..num_commands).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        commands
+            .iter()
+            .map(|cmd| (cmd.command.fee_payer_pk(), cmd.command.nonce().0))
+            .collect::<Vec<_>>(),
+        expected
+    );
+
+    // internal commands are applied after user commands
+    let internal_commands = store.get_internal_commands(&state_hash)?;
+    assert!(!internal_commands.is_empty());
+    assert_eq!(block.internal_commands_start_index(), num_commands);
+
+    for (i, cmd) in internal_commands.iter().enumerate() {
+        assert_eq!(cmd.index_in_block(), num_commands + i as u32);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn v1_apply_order() -> anyhow::Result<()> {
+    assert_apply_order(
+        "index-in-block-v1",
+        "./tests/data/non_sequential_blocks/mainnet-84161-3NK8iBQSkCQtCpnm2qWCvhixuEsiHQq7SL7YY31nyXkiLGEDMyGk.json",
+        PcbVersion::V1,
+    )
+}
+
+#[test]
+fn v2_apply_order() -> anyhow::Result<()> {
+    assert_apply_order(
+        "index-in-block-v2",
+        "./tests/data/misc_blocks/mainnet-397612-3NLh3tvZpMPXxUhCLz1898BDV6CwtExJqDWpzcZQebVCsZxghoXK.json",
+        PcbVersion::V2,
+    )
+}
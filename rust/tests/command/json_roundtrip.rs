@@ -0,0 +1,80 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{
+        parser::BlockParser,
+        precomputed::{PcbVersion, PrecomputedBlock},
+    },
+    command::roundtrip::{verify_block, CommandMismatch},
+};
+use std::path::PathBuf;
+
+/// Mainnet block fixture directories, new fixtures are covered automatically
+const BLOCK_DIRS: [&str; 4] = [
+    "./tests/data/canonical_chain_discovery",
+    "./tests/data/hardfork",
+    "./tests/data/non_sequential_blocks",
+    "./tests/data/sequential_blocks",
+];
+
+fn assert_no_mismatches(block: &PrecomputedBlock, mismatches: &[CommandMismatch]) {
+    assert!(
+        mismatches.is_empty(),
+        "{}\n{}",
+        block.summary(),
+        mismatches
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
+
+#[tokio::test]
+async fn test_data_blocks() -> anyhow::Result<()> {
+    let mut num_commands = 0;
+
+    for blocks_dir in BLOCK_DIRS {
+        let mut bp = BlockParser::new_testing(&PathBuf::from(blocks_dir))?;
+        while let Some((block, _)) = bp.next_block().await? {
+            let block: PrecomputedBlock = block.into();
+            num_commands += block.commands().len();
+            assert_no_mismatches(&block, &verify_block(&block)?);
+        }
+    }
+
+    // zkapp commands
+    let path = PathBuf::from(
+        "./tests/data/misc_blocks/mainnet-397612-3NLh3tvZpMPXxUhCLz1898BDV6CwtExJqDWpzcZQebVCsZxghoXK.json",
+    );
+    let block = PrecomputedBlock::parse_file(&path, PcbVersion::V2)?;
+    num_commands += block.commands().len();
+    assert_no_mismatches(&block, &verify_block(&block)?);
+
+    assert!(num_commands > 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn stored_blocks() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("json-roundtrip")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+
+    // all stored blocks
+    let all = store.verify_json_roundtrip(None)?;
+    assert!(all.num_commands > 0);
+    assert!(all.mismatches.is_empty(), "{:?}", all.mismatches);
+
+    // evenly spaced sample
+    let sample = store.verify_json_roundtrip(Some(5))?;
+    assert!(sample.num_blocks > 0 && sample.num_blocks <= 5);
+    assert!(sample.num_blocks < all.num_blocks);
+    assert!(sample.mismatches.is_empty(), "{:?}", sample.mismatches);
+
+    Ok(())
+}
@@ -1 +1,9 @@
+mod account_activity;
+mod account_update_statuses;
+mod accounts_created;
+mod as_of;
+mod index_in_block;
+mod json_roundtrip;
+mod precondition_summary;
 mod store;
+mod tokens;
@@ -0,0 +1,123 @@
+use mina_indexer::{
+    base::nonce::Nonce,
+    block::precomputed::{PcbVersion, PrecomputedBlock},
+    command::{
+        precondition::{
+            AccountUpdatePreconditions, FailedPrecondition, PreconditionCategory,
+            ZkappPreconditionSummary,
+        },
+        signed::SignedCommandWithData,
+    },
+    mina_blocks::v2::staged_ledger_diff::Status,
+    web::graphql::transactions::TransactionWithoutBlock,
+};
+use serde_json::json;
+use std::path::PathBuf;
+
+/// Index of the zkapp command whose account update checks its nonce
+const ZKAPP_INDEX: usize = 8;
+
+/// Fails the block's zkapp command with a nonce precondition failure & an
+/// unchecked balance precondition failure
+fn failed_precondition_block() -> anyhow::Result<PrecomputedBlock> {
+    let path = "./tests/data/misc_blocks/mainnet-359630-3NLjRmTyUzeA7meRAT3Yjqxzfe95GKBgkLPD2iLeVE5RMCFcw8eL.json";
+    let mut block = PrecomputedBlock::parse_file(&PathBuf::from(path), PcbVersion::V2)?;
+
+    // index-mapped failures, the fee payer first
+    let status: Status = serde_json::from_value(json!([
+        "Failed",
+        [
+            [],
+            [
+                ["Account_nonce_precondition_unsatisfied"],
+                ["Account_balance_precondition_unsatisfied"]
+            ],
+        ]
+    ]))?;
+
+    if let PrecomputedBlock::V2(v2) = &mut block {
+        v2.staged_ledger_diff.diff[0].as_mut().unwrap().commands[ZKAPP_INDEX].status = status;
+    }
+
+    Ok(block)
+}
+
+fn stored_command(block: &PrecomputedBlock) -> SignedCommandWithData {
+    SignedCommandWithData::from_precomputed(block)
+        .into_iter()
+        .find(|cmd| cmd.index_in_block as usize == ZKAPP_INDEX)
+        .unwrap()
+}
+
+#[test]
+fn applied_summary() -> anyhow::Result<()> {
+    let path = "./tests/data/misc_blocks/mainnet-359630-3NLjRmTyUzeA7meRAT3Yjqxzfe95GKBgkLPD2iLeVE5RMCFcw8eL.json";
+    let block = PrecomputedBlock::parse_file(&PathBuf::from(path), PcbVersion::V2)?;
+    let commands = SignedCommandWithData::from_precomputed(&block);
+
+    // signed commands have no summary
+    assert!(commands
+        .iter()
+        .filter(|cmd| !cmd.command.is_zkapp_command())
+        .all(|cmd| cmd.precondition_summary.is_none()));
+
+    let summary = stored_command(&block).precondition_summary.unwrap();
+    assert_eq!(
+        summary,
+        ZkappPreconditionSummary {
+            fee_payer_valid_until: None,
+            fee_payer_nonce: Nonce(4),
+            account_updates: vec![AccountUpdatePreconditions {
+                nonce: true,
+                ..Default::default()
+            }],
+            failed_preconditions: vec![],
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn failed_precondition_detail() -> anyhow::Result<()> {
+    let block = failed_precondition_block()?;
+    let stored = stored_command(&block);
+
+    // the nonce failure is checked, the balance failure isn't
+    let summary = stored.precondition_summary.clone().unwrap();
+    assert_eq!(
+        summary.failed_preconditions,
+        vec![
+            FailedPrecondition {
+                account_update_index: 0,
+                category: PreconditionCategory::Nonce,
+                app_state_index: None,
+                checked: true,
+            },
+            FailedPrecondition {
+                account_update_index: 0,
+                category: PreconditionCategory::Balance,
+                app_state_index: None,
+                checked: false,
+            },
+        ]
+    );
+
+    // GraphQL
+    let txn = serde_json::to_value(TransactionWithoutBlock::new(stored, true, 0, 0))?;
+    let zkapp_command = &txn["zkapp_command"];
+    assert_eq!(zkapp_command["fee_payer_nonce"], 4);
+    assert_eq!(zkapp_command["fee_payer_valid_until"], json!(null));
+
+    let preconditions = &zkapp_command["account_updates"][0]["preconditions"];
+    assert_eq!(preconditions["nonce"], true);
+    assert_eq!(preconditions["balance"], false);
+    assert_eq!(preconditions["app_state"], json!([]));
+
+    let detail = &zkapp_command["failed_precondition_detail"];
+    assert_eq!(detail[0]["category"], "NONCE");
+    assert_eq!(detail[0]["checked"], true);
+    assert_eq!(detail[1]["category"], "BALANCE");
+    assert_eq!(detail[1]["checked"], false);
+    Ok(())
+}
@@ -40,6 +40,7 @@ async fn add_and_get() -> anyhow::Result<()> {
         MAINNET_CANONICAL_THRESHOLD,
         false,
         BLOCK_REPORTING_FREQ_NUM,
+        true,
     )
     .await?;
 
@@ -0,0 +1,61 @@
+use mina_indexer::{
+    base::numeric::Numeric,
+    block::precomputed::{PcbVersion, PrecomputedBlock},
+    command::{UserCommandWithStatus, UserCommandWithStatusT},
+    ledger::token::TokenAddress,
+    mina_blocks::v2::staged_ledger_diff::{Elt, UserCommandData},
+};
+use std::path::PathBuf;
+
+/// Removes the balance changes of the non-MINA account updates
+fn zero_token_balance_changes(elt: &mut Elt) {
+    let body = &mut elt.account_update.body;
+    if body.token_id != TokenAddress::default() {
+        body.balance_change.magnitude = Numeric(0);
+    }
+
+    for call in elt.calls.iter_mut() {
+        zero_token_balance_changes(&mut call.elt);
+    }
+}
+
+#[test]
+fn fee_token_first() -> anyhow::Result<()> {
+    let path = "./tests/data/sequential_blocks/mainnet-105490-3NKxEA9gztvEGxL4uk4eTncZAxuRmMsB8n81UkeAMevUjMbLHmkC.json";
+    let block = PrecomputedBlock::parse_file(&PathBuf::from(path), PcbVersion::V1)?;
+
+    for command in block.commands() {
+        assert_eq!(command.tokens(), vec![TokenAddress::default()]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn zkapp_precondition_only_token() -> anyhow::Result<()> {
+    let path = "./tests/data/misc_blocks/mainnet-397612-3NLh3tvZpMPXxUhCLz1898BDV6CwtExJqDWpzcZQebVCsZxghoXK.json";
+    let block = PrecomputedBlock::parse_file(&PathBuf::from(path), PcbVersion::V2)?;
+
+    // the zkapp command accessing a custom token
+    let mut command = block
+        .commands()
+        .into_iter()
+        .find(|command| command.tokens().len() > 1)
+        .expect("custom token zkapp command");
+    let tokens = command.tokens();
+    assert!(command.is_zkapp_command());
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(tokens[0], TokenAddress::default());
+
+    // the custom token account updates only access the accounts
+    if let UserCommandWithStatus::V2(v2) = &mut command {
+        if let UserCommandData::ZkappCommandData(data) = &mut v2.data.1 {
+            for update in data.account_updates.iter_mut() {
+                zero_token_balance_changes(&mut update.elt);
+            }
+        }
+    }
+
+    assert_eq!(command.tokens(), tokens);
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{parser::BlockParser, store::BlockStore, BlockWithoutHeight},
+    constants::*,
+    ingestion::{store::IngestionQueueStore, QueuedBlock},
+    ledger::genesis::GenesisLedger,
+    server::IndexerVersion,
+    state::{IndexerState, IndexerStateConfig},
+};
+use std::{panic::AssertUnwindSafe, path::PathBuf};
+
+/// Number of journaled blocks applied before the applier panics
+const PANIC_AFTER: usize = 8;
+
+#[tokio::test]
+async fn restart_after_applier_panic() -> anyhow::Result<()> {
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    // uninterrupted run
+    let expected_store_dir = setup_new_db_dir("ingestion-queue-expected")?;
+    let mut block_parser = BlockParser::new_testing(&block_dir)?;
+    let mut expected = mainnet_genesis_state(expected_store_dir.as_ref())?;
+    expected.add_blocks(&mut block_parser).await?;
+
+    let expected_store = expected.indexer_store.as_ref().unwrap();
+    assert_eq!(expected_store.get_ingestion_queue_size()?, (0, 0));
+
+    // journal all parsed blocks
+    let store_dir = setup_new_db_dir("ingestion-queue-panic")?;
+    let mut block_parser = BlockParser::new_testing(&block_dir)?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let store = state.indexer_store.clone().unwrap();
+
+    let mut journaled = vec![];
+    while let Some((parsed_block, block_bytes, path)) = block_parser.next_block_with_path().await? {
+        let entry = QueuedBlock::new(path, &parsed_block, block_bytes);
+        let seq_num = store.enqueue_block(&entry)?;
+        journaled.push((seq_num, entry, parsed_block));
+    }
+
+    let num_blocks = journaled.len();
+    assert!(num_blocks > PANIC_AFTER);
+    assert_eq!(store.get_ingestion_queue_size()?.0 as usize, num_blocks);
+
+    // the applier panics mid-stream
+    let res = std::panic::catch_unwind(AssertUnwindSafe(|| -> anyhow::Result<()> {
+        for (n, (seq_num, entry, parsed_block)) in journaled.into_iter().enumerate() {
+            if n == PANIC_AFTER {
+                panic!("injected applier panic");
+            }
+            state.apply_queued_block(seq_num, &entry, parsed_block)?;
+        }
+        Ok(())
+    }));
+    assert!(res.is_err());
+    drop(state);
+
+    // unapplied blocks remain journaled in sequence order
+    let unapplied = store.get_unapplied_blocks()?;
+    assert_eq!(unapplied.len(), num_blocks - PANIC_AFTER);
+    assert!(unapplied.windows(2).all(|w| w[0].0 < w[1].0));
+
+    // restart from the store & recover the journaled blocks
+    let config = IndexerStateConfig::new(
+        GenesisLedger::new_v1()?,
+        IndexerVersion::default(),
+        store.clone(),
+        MAINNET_CANONICAL_THRESHOLD,
+        MAINNET_TRANSITION_FRONTIER_K,
+        false,
+    );
+    let mut restarted = IndexerState::new_without_genesis_events(config)?;
+    restarted.sync_from_db()?;
+    restarted.recover_ingestion_queue()?;
+
+    // the queue is drained
+    assert_eq!(store.get_ingestion_queue_size()?, (0, 0));
+
+    // the chain is completed identically
+    let best_tip: BlockWithoutHeight = expected.best_tip_block().clone().into();
+    let canonical_root: BlockWithoutHeight = expected.canonical_root_block().clone().into();
+    let best_tip_restarted: BlockWithoutHeight = restarted.best_tip_block().clone().into();
+    let canonical_root_restarted: BlockWithoutHeight =
+        restarted.canonical_root_block().clone().into();
+
+    assert_eq!(best_tip, best_tip_restarted);
+    assert_eq!(canonical_root, canonical_root_restarted);
+    assert_eq!(
+        expected_store.get_best_block_hash()?,
+        store.get_best_block_hash()?
+    );
+
+    for (_, entry) in unapplied {
+        assert!(store.get_block(&entry.state_hash)?.is_some());
+    }
+    Ok(())
+}
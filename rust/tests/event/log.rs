@@ -22,6 +22,7 @@ async fn test() -> anyhow::Result<()> {
         MAINNET_CANONICAL_THRESHOLD,
         false,
         BLOCK_REPORTING_FREQ_NUM,
+        true,
     )
     .await?;
 
@@ -32,6 +33,7 @@ async fn test() -> anyhow::Result<()> {
         MAINNET_CANONICAL_THRESHOLD,
         false,
         BLOCK_REPORTING_FREQ_NUM,
+        true,
     )
     .await?;
 
@@ -1,6 +1,10 @@
+mod ingestion_queue;
 mod log;
 mod memoize_ledger;
+mod page;
+mod reorgs;
 mod replay;
+mod skipped_blocks;
 mod store;
 mod sync;
 mod witness_tree;
@@ -0,0 +1,139 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    block::parser::BlockParser,
+    event::{
+        page::{EventKind, LoggedEvent},
+        store::EventStore,
+    },
+    store::{column_families::ColumnFamilyHelpers, IndexerStore},
+    web::graphql::build_schema,
+};
+use serde_json::json;
+use std::path::PathBuf;
+
+const EVENTS_QUERY: &str = r#"
+query Events($fromSeq: Int!) {
+  events(kinds: [NEW_CANONICAL], fromSeq: $fromSeq, limit: 3) {
+    seqNum
+    height
+    kind
+    description
+  }
+}
+"#;
+
+/// All events of the kinds, paging `limit` at a time
+fn page_through(
+    store: &IndexerStore,
+    kinds: &[EventKind],
+    limit: usize,
+) -> anyhow::Result<Vec<LoggedEvent>> {
+    let mut events = vec![];
+    let mut from_seq_num = 0;
+    loop {
+        let page = store.get_event_page(kinds, from_seq_num, limit)?;
+        assert!(page.events.len() <= limit);
+
+        if page.next_seq_num == from_seq_num {
+            return Ok(events);
+        }
+
+        events.extend(page.events);
+        from_seq_num = page.next_seq_num;
+    }
+}
+
+#[tokio::test]
+async fn page_event_log() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("event-log-pages")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    let mut block_parser = BlockParser::new_testing(&block_dir)?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    state.add_blocks(&mut block_parser).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let event_log = store.get_event_log()?;
+
+    // all events, in sequence order
+    let events = page_through(store, &[], 7)?;
+    assert_eq!(events.len(), event_log.len());
+    assert!(events
+        .iter()
+        .enumerate()
+        .all(|(n, event)| event.seq_num == n as u32));
+    assert!(events.iter().all(|event| event.description.is_some()));
+
+    // block events carry their height prefix
+    assert!(events
+        .iter()
+        .filter(|event| matches!(event.kind, EventKind::NewBlock | EventKind::NewBestTip))
+        .all(|event| event.height > 0));
+
+    // kind filtering
+    let canonical = page_through(store, &[EventKind::NewCanonical], 5)?;
+    assert_eq!(
+        canonical.len(),
+        event_log
+            .iter()
+            .filter(|event| event.is_canonical_block_event())
+            .count()
+    );
+    assert!(canonical
+        .iter()
+        .all(|event| event.kind == EventKind::NewCanonical));
+    assert!(canonical
+        .windows(2)
+        .all(|pair| pair[0].seq_num < pair[1].seq_num));
+
+    let blocks = page_through(store, &[EventKind::NewBlock, EventKind::NewBestTip], 4)?;
+    assert_eq!(
+        blocks.len(),
+        events
+            .iter()
+            .filter(|event| matches!(event.kind, EventKind::NewBlock | EventKind::NewBestTip))
+            .count()
+    );
+
+    // unknown variants written by another version are surfaced as raw JSON
+    let seq_num = store.get_next_seq_num()?;
+    let mut value = 0u32.to_be_bytes().to_vec();
+    value.push(3);
+    value.append(&mut serde_json::to_vec(&json!({
+        "Db": { "StakingLedger": { "FutureStakingEvent": { "epoch": 3 } } }
+    }))?);
+    store
+        .database
+        .put_cf(store.events_cf(), seq_num.to_be_bytes(), value)?;
+
+    let page = store.get_event_page(&[EventKind::Staking], seq_num, 10)?;
+    assert_eq!(page.events.len(), 1);
+    assert_eq!(page.events[0].seq_num, seq_num);
+    assert_eq!(page.events[0].description, None);
+    assert_eq!(
+        page.events[0].json["Db"]["StakingLedger"]["FutureStakingEvent"]["epoch"],
+        3
+    );
+
+    // GraphQL
+    let from_seq = canonical[1].seq_num;
+    let response = build_schema(store.clone())
+        .execute(
+            Request::new(EVENTS_QUERY).variables(Variables::from_json(json!({
+                "fromSeq": from_seq,
+            }))),
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json()?;
+    let gql_events = data["events"].as_array().unwrap();
+    assert_eq!(gql_events.len(), 3);
+    for (gql_event, event) in gql_events.iter().zip(canonical[1..].iter()) {
+        assert_eq!(gql_event["seqNum"], event.seq_num);
+        assert_eq!(gql_event["kind"], "NEW_CANONICAL");
+        assert_eq!(gql_event["description"], json!(event.description));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::Request;
+use mina_indexer::{
+    base::state_hash::StateHash,
+    block::extract_height_and_hash,
+    event::{page::EventKind, store::EventStore},
+    server::ingest_block,
+    state::IndexerState,
+    web::graphql::build_schema,
+};
+use std::{fs, path::PathBuf, sync::Arc};
+use tokio::sync::RwLock;
+
+const REORGS_QUERY: &str = r#"
+query Reorgs {
+  reorgs(limit: 10) {
+    oldTip
+    newTip
+    commonAncestor
+    commonAncestorHeight
+    depth
+    orphanedBlocks
+  }
+}
+"#;
+
+/// Sibling of the contiguous chain's block at height 10
+const FORK_BLOCK: &str =
+    "./tests/data/canonical_chain_discovery/gaps/mainnet-10-3NKHYHrqKpDcon6ToV5CLDiheanjshk5gcsNqefnK78phCFTR2aL.json";
+
+#[tokio::test]
+async fn best_tip_branch_switch() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("event-reorgs")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut paths = fs::read_dir(block_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.retain(|path| extract_height_and_hash(path).0 <= 11);
+    paths.sort_by_key(|path| extract_height_and_hash(path).0);
+
+    // the fork block becomes the best tip before its sibling's child arrives
+    let fork_path = PathBuf::from(FORK_BLOCK);
+    let (main_paths, tail_paths) = paths.split_at(8);
+    let state = Arc::new(RwLock::new(mainnet_genesis_state(store_dir.as_ref())?));
+    for path in main_paths.iter().chain([&fork_path]).chain(tail_paths) {
+        let block = IndexerState::parse_file(&state, path).await?;
+        ingest_block(&state, &block, path.metadata()?.len(), None).await?;
+    }
+
+    let state = state.read().await;
+    let store = state.indexer_store.as_ref().unwrap();
+    let (_, parent_hash) = extract_height_and_hash(&main_paths[7]);
+    let (_, fork_hash) = extract_height_and_hash(&fork_path);
+    let (_, tip_hash) = extract_height_and_hash(&tail_paths[1]);
+    assert_eq!(state.best_tip_block().state_hash, StateHash::from(tip_hash));
+
+    // a single reorg off of the fork block
+    let reorgs = store.get_reorgs(10)?;
+    assert_eq!(reorgs.len(), 1);
+
+    let reorg = &reorgs[0];
+    assert_eq!(reorg.old_tip, StateHash::from(fork_hash));
+    assert_eq!(reorg.old_tip_height, 10);
+    assert!(reorg.new_tip_height >= 10);
+    assert_eq!(reorg.common_ancestor, StateHash::from(parent_hash));
+    assert_eq!(reorg.common_ancestor_height, 9);
+    assert_eq!(reorg.orphaned_blocks, vec![StateHash::from(fork_hash)]);
+    assert_eq!(reorg.depth(), 1);
+    assert!(store.get_reorgs(0)?.is_empty());
+
+    // reorgs are paged like other events
+    let page = store.get_event_page(&[EventKind::Reorg], 0, 10)?;
+    assert_eq!(page.events.len(), 1);
+    assert_eq!(page.events[0].height, reorg.new_tip_height);
+
+    let schema = build_schema(store.clone());
+    let response = schema.execute(Request::new(REORGS_QUERY)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json()?;
+    let reorgs = data["reorgs"].as_array().unwrap();
+    assert_eq!(reorgs.len(), 1);
+    assert_eq!(reorgs[0]["oldTip"], fork_hash);
+    assert_eq!(reorgs[0]["commonAncestor"], parent_hash);
+    assert_eq!(reorgs[0]["commonAncestorHeight"], 9);
+    assert_eq!(reorgs[0]["depth"], 1);
+    assert_eq!(reorgs[0]["orphanedBlocks"][0], fork_hash);
+
+    Ok(())
+}
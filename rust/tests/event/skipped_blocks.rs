@@ -0,0 +1,117 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{
+        parser::BlockParser, precomputed::PrecomputedBlock, store::BlockStore, BlockWithoutHeight,
+    },
+    constants::*,
+    ingestion::{IngestionPhase, SkippedBlock},
+    ledger::genesis::GenesisLedger,
+    server::IndexerVersion,
+    state::{IndexerState, IndexerStateConfig},
+    store::column_families::ColumnFamilyHelpers,
+};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+const TIP_FILE: &str = "mainnet-21-3NKZ6DTHiMtuaeP3tJq2xe4uujVRnGT9FX1rBiZY521uNToSppUZ.json";
+
+#[tokio::test]
+async fn skip_and_report_unparsable_block() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("skipped-blocks-parse")?;
+    let contiguous_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    // truncate the tip's contents
+    let blocks_dir = TempDir::with_prefix("skipped-blocks")?;
+    for entry in std::fs::read_dir(&contiguous_dir)? {
+        let path = entry?.path();
+        let dest = blocks_dir.path().join(path.file_name().unwrap());
+        if path.file_name().unwrap() == TIP_FILE {
+            let contents = std::fs::read(&path)?;
+            std::fs::write(&dest, &contents[..contents.len() / 2])?;
+        } else {
+            std::fs::copy(&path, dest)?;
+        }
+    }
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(blocks_dir.path())?;
+    state.add_blocks(&mut bp).await?;
+
+    // the tip is skipped & reported
+    let skipped = &state.ingestion_report.skipped_blocks;
+    assert_eq!(skipped.len(), 1, "{skipped:?}");
+    assert_eq!(
+        skipped[0].path.as_ref().and_then(|path| path.file_name()),
+        Some(TIP_FILE.as_ref())
+    );
+    assert_eq!(skipped[0].state_hash, None);
+    assert_eq!(skipped[0].phase, IngestionPhase::Parse);
+
+    // all other blocks are ingested
+    let store = state.indexer_store.as_ref().unwrap();
+    assert_eq!(store.get_best_block_height()?, Some(20));
+    assert_eq!(state.best_tip_block().blockchain_length, 20);
+
+    // invalid file names are errors
+    let invalid = blocks_dir.path().join("mainnet-twenty-one.json");
+    std::fs::copy(contiguous_dir.join(TIP_FILE), &invalid)?;
+    assert!(PrecomputedBlock::parse_file(&invalid, Default::default()).is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn skip_and_report_block_missing_during_sync() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("skipped-blocks-sync")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    state.add_blocks(&mut bp).await?;
+    assert!(state.ingestion_report.is_empty());
+
+    // corrupt the best tip in the store
+    let store = state.indexer_store.as_ref().unwrap();
+    let best_tip = state.best_tip_block().clone();
+    store.database.put_cf(
+        store.blocks_cf(),
+        best_tip.state_hash.0.as_bytes(),
+        b"corrupt",
+    )?;
+    assert_eq!(store.get_block(&best_tip.state_hash)?, None);
+
+    // sync continues without the corrupt block
+    let config = IndexerStateConfig::new(
+        GenesisLedger::new_v1()?,
+        IndexerVersion::default(),
+        store.clone(),
+        MAINNET_CANONICAL_THRESHOLD,
+        10,
+        false,
+    );
+    let mut state_sync = IndexerState::new_without_genesis_events(config)?;
+    state_sync.sync_from_db()?;
+
+    assert_eq!(
+        state_sync.ingestion_report.skipped_blocks,
+        vec![SkippedBlock {
+            path: None,
+            state_hash: Some(best_tip.state_hash.clone()),
+            phase: IngestionPhase::Sync,
+            error: format!(
+                "block missing from db (length {})",
+                best_tip.blockchain_length
+            ),
+        }]
+    );
+    assert_eq!(
+        state_sync.best_tip_block().blockchain_length,
+        best_tip.blockchain_length - 1
+    );
+
+    let canonical_root: BlockWithoutHeight = state.canonical_root_block().clone().into();
+    let canonical_root_sync: BlockWithoutHeight = state_sync.canonical_root_block().clone().into();
+    assert_eq!(canonical_root, canonical_root_sync);
+
+    Ok(())
+}
@@ -1,7 +1,20 @@
-use mina_indexer::mina_blocks::v2::{ActionState, ZkappEvent};
+use mina_indexer::{
+    base::{amount::Amount, nonce::Nonce, public_key::PublicKey},
+    ledger::{
+        diff::{
+            account::{
+                AccountDiff, CoinbaseDiff, DelegationDiff, FailedTransactionNonceDiff, PaymentDiff,
+                UpdateType,
+            },
+            LedgerDiff,
+        },
+        token::TokenAddress,
+    },
+    mina_blocks::v2::{ActionState, ZkappEvent},
+};
 use quickcheck::{Arbitrary, Gen};
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct TestGen<T>(pub T)
 where
     T: Clone;
@@ -33,3 +46,92 @@ impl Arbitrary for TestGen<ZkappEvent> {
         Self(format!("0x{}", hex::encode(bytes)).into())
     }
 }
+
+////////////
+// ledger //
+////////////
+
+/// Number of distinct accounts in generated ledger diffs, kept small so
+/// accounts are touched by several diffs
+const NUM_TEST_ACCOUNTS: u8 = 6;
+
+impl Arbitrary for TestGen<PublicKey> {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let n = u8::arbitrary(g) % NUM_TEST_ACCOUNTS;
+        Self(format!("B62qTESTTESTTESTTESTTESTTESTTESTTESTTESTTESTTESTTESTPK{n}").into())
+    }
+}
+
+impl Arbitrary for TestGen<TokenAddress> {
+    fn arbitrary(g: &mut Gen) -> Self {
+        if bool::arbitrary(g) {
+            return Self(TokenAddress::default());
+        }
+
+        Self(TokenAddress::new("x".repeat(TokenAddress::LEN)).expect("token address"))
+    }
+}
+
+impl Arbitrary for TestGen<AccountDiff> {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let public_key = <TestGen<PublicKey>>::arbitrary(g).0;
+
+        // occasionally near max, to exercise overflow anomalies
+        let amount = if u8::arbitrary(g) % 16 == 0 {
+            Amount(u64::MAX - u64::arbitrary(g) % 1_000)
+        } else {
+            Amount(u64::arbitrary(g) % 1_000_000)
+        };
+        let payment = |g: &mut Gen| PaymentDiff {
+            public_key: public_key.clone(),
+            amount,
+            update_type: if bool::arbitrary(g) {
+                UpdateType::Credit
+            } else {
+                UpdateType::Debit(bool::arbitrary(g).then(|| Nonce(u32::arbitrary(g) % 100)))
+            },
+            token: <TestGen<TokenAddress>>::arbitrary(g).0,
+        };
+
+        Self(match u8::arbitrary(g) % 6 {
+            0 => AccountDiff::Payment(payment(g)),
+            1 => AccountDiff::FeeTransfer(PaymentDiff {
+                token: TokenAddress::default(),
+                ..payment(g)
+            }),
+            2 => AccountDiff::FeeTransferViaCoinbase(PaymentDiff {
+                token: TokenAddress::default(),
+                ..payment(g)
+            }),
+            3 => AccountDiff::Coinbase(CoinbaseDiff { public_key, amount }),
+            4 => AccountDiff::Delegation(DelegationDiff {
+                nonce: Nonce(u32::arbitrary(g) % 100),
+                delegator: public_key,
+                delegate: <TestGen<PublicKey>>::arbitrary(g).0,
+            }),
+            _ => AccountDiff::FailedTransactionNonce(FailedTransactionNonceDiff {
+                public_key,
+                nonce: Nonce(u32::arbitrary(g) % 100),
+            }),
+        })
+    }
+}
+
+impl Arbitrary for TestGen<LedgerDiff> {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let num_groups = usize::arbitrary(g) % g.size();
+        let account_diffs = (0..num_groups)
+            .map(|_| {
+                let num_diffs = 1 + usize::arbitrary(g) % 3;
+                (0..num_diffs)
+                    .map(|_| <TestGen<AccountDiff>>::arbitrary(g).0)
+                    .collect()
+            })
+            .collect();
+
+        Self(LedgerDiff {
+            account_diffs,
+            ..Default::default()
+        })
+    }
+}
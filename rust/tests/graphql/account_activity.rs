@@ -0,0 +1,77 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    block::{parser::BlockParser, store::BlockStore},
+    web::graphql::build_schema,
+};
+use serde_json::json;
+use std::path::PathBuf;
+
+const ACCOUNT_ACTIVITY_QUERY: &str = r#"
+query AccountActivity($pk: String!) {
+  blocks(query: { creatorAccount: { publicKey: $pk } }, limit: 10) {
+    blockHeight
+    stateHash
+  }
+  transactions(query: { from: $pk }, limit: 10) {
+    hash
+    blockHeight
+  }
+  feetransfers(query: { recipient: $pk }, limit: 10) {
+    fee
+    blockHeight
+  }
+  delegators(delegate: $pk, epoch: 0) {
+    public_key
+    balance
+  }
+}
+"#;
+
+#[tokio::test]
+async fn missing_staking_ledger() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-account-activity")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    // no staking ledgers are ingested
+    let store = state.indexer_store.as_ref().unwrap();
+    let best_tip = state.best_tip_block().state_hash.clone();
+    let pk = store.get_block_creator(&best_tip)?.unwrap();
+
+    let schema = build_schema(store.clone());
+    let response = schema
+        .execute(
+            Request::new(ACCOUNT_ACTIVITY_QUERY)
+                .variables(Variables::from_json(json!({ "pk": pk.to_string() }))),
+        )
+        .await;
+
+    // the successful sections are populated
+    let data = response.data.clone().into_json()?;
+    let blocks = data["blocks"].as_array().expect("blocks section");
+    assert!(!blocks.is_empty());
+    assert!(blocks.iter().any(|block| block["stateHash"] == best_tip.0));
+    assert!(data["transactions"].is_array());
+    assert!(data["feetransfers"].is_array());
+
+    // the delegators section is null with a scoped error
+    assert!(data["delegators"].is_null());
+    assert_eq!(response.errors.len(), 1);
+
+    let error = serde_json::to_value(&response.errors[0])?;
+    assert_eq!(error["path"], json!(["delegators"]));
+    assert_eq!(
+        error["extensions"],
+        json!({
+            "code": "NOT_FOUND",
+            "entity": "staking ledger",
+            "key": "0",
+        })
+    );
+
+    Ok(())
+}
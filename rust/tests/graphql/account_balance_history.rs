@@ -0,0 +1,85 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    block::{parser::BlockParser, store::BlockStore},
+    ledger::{store::best::BestLedgerStore, token::TokenAddress},
+    web::graphql::build_schema,
+};
+use serde_json::json;
+use std::path::PathBuf;
+
+const BALANCE_HISTORY_QUERY: &str = r#"
+query BalanceHistory($pk: String!, $start: Int, $end: Int, $limit: Int) {
+  accountBalanceHistory(publicKey: $pk, startHeight: $start, endHeight: $end, limit: $limit) {
+    blockHeight
+    stateHash
+    balanceBefore
+    balance
+  }
+}
+"#;
+
+#[tokio::test]
+async fn coinbase_receiver_balance_history() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-account-balance-history")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    // the best tip's coinbase receiver's balance changes at the best tip
+    let store = state.indexer_store.as_ref().unwrap();
+    let best_tip = state.best_tip_block().clone();
+    let pk = store.get_coinbase_receiver(&best_tip.state_hash)?.unwrap();
+    let balance = store
+        .get_best_account(&pk, &TokenAddress::default())?
+        .unwrap()
+        .balance
+        .0;
+
+    let schema = build_schema(store.clone());
+    let query = |variables: serde_json::Value| {
+        let schema = schema.clone();
+        async move {
+            let response = schema
+                .execute(
+                    Request::new(BALANCE_HISTORY_QUERY).variables(Variables::from_json(variables)),
+                )
+                .await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+            let data = response.data.into_json().unwrap();
+            data["accountBalanceHistory"].as_array().unwrap().clone()
+        }
+    };
+
+    // full history, ascending & chained
+    let history = query(json!({ "pk": pk.to_string() })).await;
+    assert!(!history.is_empty());
+    assert!(history
+        .windows(2)
+        .all(|pair| pair[0]["blockHeight"].as_u64() < pair[1]["blockHeight"].as_u64()));
+    assert!(history
+        .windows(2)
+        .all(|pair| pair[0]["balance"] == pair[1]["balanceBefore"]));
+
+    let last = history.last().unwrap();
+    assert_eq!(last["blockHeight"], best_tip.blockchain_length);
+    assert_eq!(last["balance"], balance);
+
+    // single height range
+    let range = query(json!({
+        "pk": pk.to_string(),
+        "start": best_tip.blockchain_length,
+        "end": best_tip.blockchain_length,
+    }))
+    .await;
+    assert_eq!(range, vec![last.clone()]);
+
+    // limited
+    let limited = query(json!({ "pk": pk.to_string(), "limit": 1 })).await;
+    assert_eq!(limited, vec![history[0].clone()]);
+
+    Ok(())
+}
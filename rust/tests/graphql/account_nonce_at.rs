@@ -0,0 +1,114 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    block::{parser::BlockParser, store::BlockStore},
+    canonicity::store::CanonicityStore,
+    command::UserCommandWithStatusT,
+    constants::millis_to_iso_date_string,
+    ledger::{store::best::BestLedgerStore, token::TokenAddress},
+    web::graphql::build_schema,
+};
+use serde_json::json;
+use std::path::PathBuf;
+
+const NONCE_AT_QUERY: &str = r#"
+query NonceAt($pk: String!, $height: Int, $stateHash: String, $atDateTime: DateTime) {
+  accountNonceAt(
+    publicKey: $pk
+    blockHeight: $height
+    stateHash: $stateHash
+    atDateTime: $atDateTime
+  ) {
+    blockHeight
+    stateHash
+    nonce
+  }
+}
+"#;
+
+#[tokio::test]
+async fn fee_payer_nonce_at() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-account-nonce-at")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    // the first canonical block with a user command
+    let store = state.indexer_store.as_ref().unwrap();
+    let root_height = state.canonical_root_block().blockchain_length;
+    let (height, state_hash, date_time, pk) = (2..=root_height)
+        .find_map(|height| {
+            let state_hash = store.get_canonical_hash_at_height(height).ok()??;
+            let (block, _) = store.get_block(&state_hash).ok()??;
+            let command = block.commands().into_iter().next()?;
+            Some((
+                height,
+                state_hash,
+                block.timestamp() as i64,
+                command.fee_payer_pk(),
+            ))
+        })
+        .expect("canonical block with a user command");
+
+    let schema = build_schema(store.clone());
+    let query = |variables: serde_json::Value| {
+        let schema = schema.clone();
+        async move {
+            let response = schema
+                .execute(Request::new(NONCE_AT_QUERY).variables(Variables::from_json(variables)))
+                .await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+            let data = response.data.into_json().unwrap();
+            data["accountNonceAt"].clone()
+        }
+    };
+
+    // the block's commands increment the fee payer's nonce
+    let at = query(json!({ "pk": pk.to_string(), "height": height })).await;
+    let before = query(json!({ "pk": pk.to_string(), "height": height - 1 })).await;
+    assert_eq!(at["stateHash"], state_hash.0);
+    assert!(at["nonce"].as_u64() > before["nonce"].as_u64());
+
+    let entry = store
+        .get_best_account_nonce_history(&pk, &TokenAddress::default(), height)?
+        .unwrap();
+    assert_eq!(Some(entry.after as u64), at["nonce"].as_u64());
+    assert_eq!(entry.before.map(u64::from), before["nonce"].as_u64());
+
+    // by state hash
+    let by_hash = query(json!({ "pk": pk.to_string(), "stateHash": state_hash.0 })).await;
+    assert_eq!(by_hash, at);
+
+    // at the block's date time
+    let by_date_time = query(json!({
+        "pk": pk.to_string(),
+        "atDateTime": millis_to_iso_date_string(date_time),
+    }))
+    .await;
+    assert_eq!(by_date_time, at);
+
+    // date times out of the canonical chain's range are errors
+    let response = schema
+        .execute(
+            Request::new(NONCE_AT_QUERY).variables(Variables::from_json(json!({
+                "pk": pk.to_string(),
+                "atDateTime": millis_to_iso_date_string(0),
+            }))),
+        )
+        .await;
+    assert!(response.errors[0].message.contains("before genesis"));
+
+    // the current nonce as of the canonical root
+    let current = store
+        .get_best_account(&pk, &TokenAddress::default())?
+        .unwrap()
+        .nonce
+        .unwrap_or_default()
+        .0;
+    assert!(at["nonce"].as_u64() <= Some(current as u64));
+
+    Ok(())
+}
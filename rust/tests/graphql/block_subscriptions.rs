@@ -0,0 +1,111 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::Request;
+use futures_util::{FutureExt, StreamExt};
+use mina_indexer::{
+    block::{
+        extract_height_and_hash,
+        precomputed::{PcbVersion, PrecomputedBlock},
+    },
+    state::IndexerState,
+    store::block_subscriptions::BlockNotificationKind,
+    web::graphql::build_schema,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const BEST_TIP_CHANGED_SUBSCRIPTION: &str = r#"
+subscription {
+  bestTipChanged {
+    stateHash
+    parentStateHash
+    blockHeight
+  }
+}
+"#;
+
+/// Contiguous mainnet blocks, in height order
+fn block_paths() -> anyhow::Result<Vec<PathBuf>> {
+    let blocks_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    let mut paths = fs::read_dir(blocks_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    paths.sort_by_key(|path| extract_height_and_hash(path).0);
+    Ok(paths)
+}
+
+fn pipeline(state: &mut IndexerState, path: &Path) -> anyhow::Result<PrecomputedBlock> {
+    let block = PrecomputedBlock::parse_file(path, PcbVersion::V1)?;
+    assert!(state.block_pipeline(&block, fs::metadata(path)?.len())?);
+    Ok(block)
+}
+
+#[test]
+fn witness_tree_updates_published() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("block-subscriptions")?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let store = state.indexer_store.clone().unwrap();
+
+    let mut new_blocks = store.subscribe_blocks(BlockNotificationKind::NewBlock);
+    let mut canonical_blocks = store.subscribe_blocks(BlockNotificationKind::NewCanonicalBlock);
+    let mut best_tips = store.subscribe_blocks(BlockNotificationKind::BestTipChanged);
+
+    let paths = block_paths()?;
+    for path in paths.iter() {
+        let block = pipeline(&mut state, path)?;
+
+        // each block is new & extends the best tip
+        let new_block = new_blocks.try_next().unwrap();
+        assert_eq!(new_block.kind, BlockNotificationKind::NewBlock);
+        assert_eq!(new_block.state_hash, block.state_hash());
+        assert_eq!(new_block.parent_hash, block.previous_state_hash());
+        assert_eq!(new_block.blockchain_length, block.blockchain_length());
+        assert!(new_blocks.try_next().is_none());
+
+        let best_tip = best_tips.try_next().unwrap();
+        assert_eq!(best_tip.state_hash, block.state_hash());
+        assert!(best_tips.try_next().is_none());
+    }
+
+    // canonical blocks are published in order, below the best tip
+    let mut canonical = vec![];
+    while let Some(notification) = canonical_blocks.try_next() {
+        assert_eq!(notification.kind, BlockNotificationKind::NewCanonicalBlock);
+        canonical.push(notification.blockchain_length);
+    }
+
+    assert!(!canonical.is_empty());
+    assert!(canonical.windows(2).all(|pair| pair[0] + 1 == pair[1]));
+    assert!(*canonical.last().unwrap() < state.best_tip_block().blockchain_length);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn graphql_best_tip_changed_subscription() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-block-subscriptions")?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let store = state.indexer_store.clone().unwrap();
+
+    let schema = build_schema(store);
+    let mut stream = schema.execute_stream(Request::new(BEST_TIP_CHANGED_SUBSCRIPTION));
+
+    // subscribe before anything is published
+    assert!(stream.next().now_or_never().is_none());
+
+    let paths = block_paths()?;
+    let block = pipeline(&mut state, &paths[0])?;
+
+    let response = stream.next().await.unwrap();
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json()?;
+    let best_tip = &data["bestTipChanged"];
+    assert_eq!(best_tip["stateHash"], block.state_hash().0);
+    assert_eq!(best_tip["parentStateHash"], block.previous_state_hash().0);
+    assert_eq!(best_tip["blockHeight"], block.blockchain_length());
+
+    Ok(())
+}
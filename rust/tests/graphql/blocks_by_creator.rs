@@ -0,0 +1,168 @@
+use crate::helpers::store::*;
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    base::{public_key::PublicKey, state_hash::StateHash},
+    block::{parser::BlockParser, precomputed::PrecomputedBlock, store::BlockStore},
+    canonicity::{store::CanonicityStore, CanonicityDiff},
+    store::{DbUpdate, IndexerStore},
+    web::graphql::build_schema,
+};
+use serde_json::{json, Value};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+const CREATOR_EPOCH_QUERY: &str = r#"
+query CreatorEpoch($pk: String!, $epoch: Int!, $otherEpoch: Int!, $height: Int!) {
+  all: blocks(
+    query: { creatorAccount: { publicKey: $pk }, epoch: $epoch }
+    sortBy: BLOCKHEIGHT_ASC
+    limit: 100
+  ) {
+    stateHash
+    blockHeight
+    canonical
+  }
+  canonical: blocks(
+    query: { creatorAccount: { publicKey: $pk }, epoch: $epoch, canonical: true }
+  ) {
+    stateHash
+  }
+  page: blocks(
+    query: { creatorAccount: { publicKey: $pk }, epoch: $epoch, canonical: false }
+    sortBy: GLOBALSLOT_DESC
+    limit: 2
+  ) {
+    stateHash
+  }
+  otherEpoch: blocks(query: { creatorAccount: { publicKey: $pk }, epoch: $otherEpoch }) {
+    stateHash
+  }
+  allCount: blocksCount(query: { creatorAccount: { publicKey: $pk }, epoch: $epoch })
+  canonicalCount: blocksCount(
+    query: { creatorAccount: { publicKey: $pk }, epoch: $epoch, canonical: true }
+  )
+  nonCanonicalCount: blocksCount(
+    query: { creatorAccount: { publicKey: $pk }, epoch: $epoch, canonical: false }
+  )
+  otherEpochCount: blocksCount(query: { creatorAccount: { publicKey: $pk }, epoch: $otherEpoch })
+  totalCount: blocksCount(query: { creatorAccount: { publicKey: $pk } })
+  boundedCount: blocksCount(
+    query: { creatorAccount: { publicKey: $pk }, epoch: $epoch, blockHeight_gt: $height }
+  )
+}
+"#;
+
+fn state_hashes(blocks: &Value) -> Vec<String> {
+    blocks
+        .as_array()
+        .expect("blocks")
+        .iter()
+        .map(|block| block["stateHash"].as_str().expect("state hash").to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn creator_epoch_blocks_and_count() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-blocks-by-creator")?;
+    let blocks_dir = PathBuf::from("./tests/data/sequential_blocks");
+    let store = Arc::new(IndexerStore::new(store_dir.path())?);
+
+    // (height, global slot, state hash) of each creator's blocks
+    let mut created: HashMap<(PublicKey, u32), Vec<(u32, u32, StateHash)>> = HashMap::new();
+    let mut bp = BlockParser::new_testing(&blocks_dir)?;
+    while let Some((block, block_bytes)) = bp.next_block().await? {
+        let block: PrecomputedBlock = block.into();
+        store.add_block(&block, block_bytes)?;
+
+        created
+            .entry((block.block_creator(), block.epoch_count()))
+            .or_default()
+            .push((
+                block.blockchain_length(),
+                block.global_slot_since_genesis(),
+                block.state_hash(),
+            ));
+    }
+
+    // the most prolific producer
+    let ((pk, epoch), mut expected) = created
+        .into_iter()
+        .max_by_key(|((pk, _), blocks)| (blocks.len(), pk.0.clone()))
+        .unwrap();
+    expected.sort_by_key(|(height, _, state_hash)| (*height, state_hash.clone()));
+    assert!(expected.len() >= 3, "{}", expected.len());
+
+    // make its first block canonical
+    let (height, global_slot, first) = expected[0].clone();
+    store.update_canonicity(DbUpdate {
+        apply: vec![CanonicityDiff {
+            state_hash: first.clone(),
+            blockchain_length: height,
+            global_slot,
+        }],
+        unapply: vec![],
+    })?;
+
+    let schema = build_schema(store.clone());
+    let response = schema
+        .execute(
+            Request::new(CREATOR_EPOCH_QUERY).variables(Variables::from_json(json!({
+                "pk": pk.to_string(),
+                "epoch": epoch,
+                "otherEpoch": epoch + 1,
+                "height": height,
+            }))),
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json()?;
+    let num_blocks = expected.len();
+    let expected_hashes: Vec<String> = expected
+        .iter()
+        .map(|(_, _, state_hash)| state_hash.to_string())
+        .collect();
+
+    // pages
+    assert_eq!(state_hashes(&data["all"]), expected_hashes);
+    assert_eq!(data["all"][0]["canonical"], true);
+    assert_eq!(data["all"][1]["canonical"], false);
+    assert_eq!(state_hashes(&data["canonical"]), vec![first.to_string()]);
+    assert!(state_hashes(&data["otherEpoch"]).is_empty());
+
+    // non-canonical page holds the latest slots
+    let mut slots: Vec<u32> = expected[1..].iter().map(|(_, slot, _)| *slot).collect();
+    slots.sort_by(|a, b| b.cmp(a));
+
+    let page = state_hashes(&data["page"]);
+    assert_eq!(page.len(), 2);
+    for state_hash in page.iter() {
+        let (_, slot, _) = expected[1..]
+            .iter()
+            .find(|(_, _, expected)| expected.0 == *state_hash)
+            .expect("non-canonical block");
+        assert!(*slot >= slots[1]);
+    }
+
+    // counts
+    assert_eq!(data["allCount"], json!(num_blocks));
+    assert_eq!(data["canonicalCount"], json!(1));
+    assert_eq!(data["nonCanonicalCount"], json!(num_blocks - 1));
+    assert_eq!(data["otherEpochCount"], json!(0));
+    assert_eq!(data["totalCount"], json!(num_blocks));
+    assert_eq!(
+        data["boundedCount"],
+        json!(expected.iter().filter(|(h, _, _)| *h > height).count())
+    );
+
+    // counts come from the block production aggregates
+    assert_eq!(
+        store.get_block_production_pk_epoch_count(&pk, Some(epoch))?,
+        num_blocks as u32
+    );
+    assert_eq!(
+        store.get_block_production_pk_canonical_epoch_count(&pk, Some(epoch))?,
+        1
+    );
+
+    Ok(())
+}
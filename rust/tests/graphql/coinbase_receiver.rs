@@ -0,0 +1,106 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    base::{public_key::PublicKey, state_hash::StateHash},
+    block::{parser::BlockParser, store::BlockStore},
+    web::graphql::build_schema,
+};
+use serde_json::json;
+use speedb::{Direction, IteratorMode};
+use std::path::PathBuf;
+
+const COINBASE_RECEIVER_QUERY: &str = r#"
+query CoinbaseReceiver($pk: String!) {
+  received: blocks(query: { coinbaseReceiver: { publicKey: $pk } }, limit: 100) {
+    blockHeight
+    stateHash
+    creator
+    coinbaseReceiver {
+      publicKey
+    }
+  }
+  created: blocks(query: { creatorAccount: { publicKey: $pk } }, limit: 100) {
+    stateHash
+  }
+  feetransfers(query: { recipient: $pk }, limit: 100) {
+    stateHash
+    type
+  }
+}
+"#;
+
+#[tokio::test]
+async fn creator_distinct_from_coinbase_receiver() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-coinbase-receiver")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+
+    // mainnet-10 coinbase goes to a different account than the creator
+    let state_hash: StateHash = "3NKGgTk7en3347KH81yDra876GPAUSoSePrfVKPmwR1KHfMpvJC5".into();
+    let creator: PublicKey = "B62qqhURJQo3CvWC3WFo9LhUhtcaJWLBcJsaA3DXaU2GH5KgXujZiwB".into();
+    let receiver: PublicKey = "B62qrdhG66vK71Jbdz6Xs7cnDxQ8f6jZUFvefkp3pje4EejYUTvotGP".into();
+
+    assert_eq!(store.get_block_creator(&state_hash)?, Some(creator.clone()));
+    assert_eq!(
+        store.get_coinbase_receiver(&state_hash)?,
+        Some(receiver.clone())
+    );
+
+    // receiver index
+    let mut heights = vec![];
+    for (key, _) in store
+        .coinbase_receiver_block_height_iterator(IteratorMode::From(
+            receiver.0.as_bytes(),
+            Direction::Forward,
+        ))
+        .flatten()
+    {
+        if key[..PublicKey::LEN] != *receiver.0.as_bytes() {
+            break;
+        }
+
+        heights.push(u32::from_be_bytes(
+            key[PublicKey::LEN..][..4].try_into().unwrap(),
+        ));
+    }
+    assert_eq!(heights, vec![6, 10, 13, 19]);
+
+    let schema = build_schema(store.clone());
+    let response = schema
+        .execute(
+            Request::new(COINBASE_RECEIVER_QUERY)
+                .variables(Variables::from_json(json!({ "pk": receiver.to_string() }))),
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    // blocks filtered by coinbase receiver
+    let data = response.data.into_json()?;
+    let received = data["received"].as_array().expect("received blocks");
+    assert!(received
+        .iter()
+        .any(|block| block["stateHash"] == state_hash.0 && block["creator"] == creator.0));
+    for block in received {
+        assert_eq!(block["coinbaseReceiver"]["publicKey"], receiver.0);
+        assert_ne!(block["creator"], receiver.0);
+    }
+
+    // the receiver never created a block
+    assert!(data["created"]
+        .as_array()
+        .expect("created blocks")
+        .is_empty());
+
+    // coinbase receipts show up in the receiver's internal commands
+    let feetransfers = data["feetransfers"].as_array().expect("feetransfers");
+    assert!(feetransfers
+        .iter()
+        .any(|ft| ft["stateHash"] == state_hash.0 && ft["type"] == "Coinbase"));
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use crate::helpers::store::*;
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    block::{
+        precomputed::{PcbVersion, PrecomputedBlock},
+        store::BlockStore,
+    },
+    canonicity::store::CanonicityStore,
+    command::store::{DelegationChange, UserCommandStore},
+    constants::MAINNET_GENESIS_HASH,
+    store::IndexerStore,
+    web::graphql::build_schema,
+};
+use serde_json::json;
+use std::{path::PathBuf, sync::Arc};
+
+const DELEGATION_HISTORY_QUERY: &str = r#"
+query DelegationHistory($publicKey: String!) {
+  delegationHistory(publicKey: $publicKey) {
+    delegate
+    nonce
+    blockHeight
+    stateHash
+    txnHash
+  }
+}
+"#;
+
+#[tokio::test]
+async fn canonical_delegation_history() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-delegation-history")?;
+    let blocks_dir = PathBuf::from("./tests/data/canonical_chain_discovery/missing_parent");
+    let store = Arc::new(IndexerStore::new(store_dir.path())?);
+
+    // two blocks at the same height, both with stake delegations
+    let mut blocks = vec![];
+    for entry in std::fs::read_dir(blocks_dir)? {
+        let block = PrecomputedBlock::parse_file(&entry?.path(), PcbVersion::V1)?;
+        store.add_block(&block, 0)?;
+        blocks.push(block);
+    }
+    blocks.sort_by_key(|block| block.state_hash());
+
+    let canonical = &blocks[0];
+    let changes = DelegationChange::from_precomputed(canonical);
+    let delegator = changes.first().expect("stake delegation").delegator.clone();
+
+    // no canonical blocks yet
+    assert!(store.get_delegation_history(&delegator)?.is_empty());
+
+    store.add_canonical_block(
+        canonical.blockchain_length(),
+        canonical.global_slot_since_genesis(),
+        &canonical.state_hash(),
+        &MAINNET_GENESIS_HASH.into(),
+        None,
+    )?;
+
+    // only the canonical block's delegation changes
+    let expected: Vec<_> = changes
+        .into_iter()
+        .filter(|change| change.delegator == delegator)
+        .collect();
+    assert_eq!(store.get_delegation_history(&delegator)?, expected);
+
+    let schema = build_schema(store.clone());
+    let response = schema
+        .execute(
+            Request::new(DELEGATION_HISTORY_QUERY)
+                .variables(Variables::from_json(json!({ "publicKey": delegator.0 }))),
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json()?;
+    let history = data["delegationHistory"].as_array().unwrap();
+    assert_eq!(history.len(), expected.len());
+
+    for (change, expected) in history.iter().zip(expected.iter()) {
+        assert_eq!(change["delegate"], json!(expected.delegate.0));
+        assert_eq!(change["nonce"], json!(expected.nonce.0));
+        assert_eq!(change["blockHeight"], json!(expected.blockchain_length));
+        assert_eq!(change["stateHash"], json!(canonical.state_hash().0));
+        assert_eq!(change["txnHash"], json!(expected.txn_hash.to_string()));
+    }
+
+    // invalid public key
+    let response = schema
+        .execute(
+            Request::new(DELEGATION_HISTORY_QUERY)
+                .variables(Variables::from_json(json!({ "publicKey": "B62" }))),
+        )
+        .await;
+    assert!(!response.errors.is_empty());
+
+    Ok(())
+}
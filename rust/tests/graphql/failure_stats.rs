@@ -0,0 +1,120 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    block::parser::BlockParser, command::store::UserCommandStore, web::graphql::build_schema,
+};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+const FAILURE_KIND: &str = "Amount_insufficient_to_create_account";
+
+const FAILURE_STATS_QUERY: &str = r#"
+query FailureStats($fromHeight: Int, $toHeight: Int) {
+  failureStats(fromHeight: $fromHeight, toHeight: $toHeight) {
+    fromHeight
+    toHeight
+    numFailed
+    failures {
+      kind
+      count
+    }
+  }
+}
+"#;
+
+const FAILED_TRANSACTIONS_QUERY: &str = r#"
+query FailedTransactions($failureReason: String!, $sortBy: TransactionSortByInput) {
+  transactions(query: { failureReason: $failureReason }, limit: 1000, sortBy: $sortBy) {
+    hash
+    blockHeight
+    failureReason
+  }
+}
+"#;
+
+#[tokio::test]
+async fn failure_stats() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-failure-stats")?;
+    let blocks_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&blocks_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let schema = build_schema(store.clone());
+    let execute = |query: &'static str, variables: Value| {
+        let schema = schema.clone();
+        async move {
+            let response = schema
+                .execute(Request::new(query).variables(Variables::from_json(variables)))
+                .await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+            response.data.into_json().unwrap()
+        }
+    };
+
+    // every failed command is counted
+    let stats = execute(FAILURE_STATS_QUERY, json!({})).await;
+    let stats = &stats["failureStats"];
+    let num_failed = store.get_failed_user_commands_count()?;
+    assert!(num_failed > 0);
+    assert_eq!(stats["numFailed"], num_failed);
+    assert_eq!(
+        stats["failures"],
+        json!([{ "kind": FAILURE_KIND, "count": num_failed }])
+    );
+
+    // filtered by failure reason, most recent first
+    let desc = execute(
+        FAILED_TRANSACTIONS_QUERY,
+        json!({ "failureReason": FAILURE_KIND }),
+    )
+    .await["transactions"]
+        .as_array()
+        .unwrap()
+        .clone();
+    assert_eq!(desc.len() as u32, num_failed);
+    assert!(desc.iter().all(|txn| txn["failureReason"] == FAILURE_KIND));
+    assert!(desc
+        .windows(2)
+        .all(|pair| pair[0]["blockHeight"].as_u64() >= pair[1]["blockHeight"].as_u64()));
+
+    // ascending is the reverse
+    let asc = execute(
+        FAILED_TRANSACTIONS_QUERY,
+        json!({ "failureReason": FAILURE_KIND, "sortBy": "BLOCKHEIGHT_ASC" }),
+    )
+    .await["transactions"]
+        .as_array()
+        .unwrap()
+        .clone();
+    assert_eq!(asc.len(), desc.len());
+    assert_eq!(
+        asc.first().unwrap()["blockHeight"],
+        desc.last().unwrap()["blockHeight"]
+    );
+
+    // height range counts match the filtered transactions
+    let height = desc.last().unwrap()["blockHeight"].as_u64().unwrap();
+    let stats = execute(
+        FAILURE_STATS_QUERY,
+        json!({ "fromHeight": height, "toHeight": height }),
+    )
+    .await;
+    let num_at_height = desc
+        .iter()
+        .filter(|txn| txn["blockHeight"].as_u64() == Some(height))
+        .count();
+    assert_eq!(stats["failureStats"]["numFailed"], num_at_height);
+
+    // unknown failure reasons
+    let unknown = execute(
+        FAILED_TRANSACTIONS_QUERY,
+        json!({ "failureReason": "Not_a_failure" }),
+    )
+    .await;
+    assert_eq!(unknown["transactions"], json!([]));
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use crate::helpers::store::*;
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    block::{parser::BlockParser, precomputed::PcbVersion, store::BlockStore},
+    constants::*,
+    ledger::genesis::GenesisLedger,
+    server::IndexerVersion,
+    state::IndexerState,
+    store::IndexerStore,
+    web::graphql::build_schema,
+};
+use serde_json::json;
+use std::{path::PathBuf, sync::Arc};
+
+const FEETRANSFERS_QUERY: &str = r#"
+query Feetransfers($recipient: String!, $creator: String!, $canonical: Boolean) {
+  feetransfers(
+    query: {
+      recipient: $recipient
+      blockCreator: $creator
+      type: "Fee_transfer_via_coinbase"
+      canonical: $canonical
+    }
+    limit: 10
+  ) {
+    stateHash
+    recipient
+    blockCreator
+    blockHeight
+    canonical
+    fee
+    type
+  }
+}
+"#;
+
+#[tokio::test]
+async fn recipient_and_block_creator() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-feetransfers-by-creator")?;
+    let blocks_dir = &PathBuf::from("./tests/data/non_sequential_blocks");
+    let indexer_store = Arc::new(IndexerStore::new(store_dir.path())?);
+
+    let mut indexer = IndexerState::new(
+        GenesisLedger::new_v1()?,
+        IndexerVersion::default(),
+        indexer_store.clone(),
+        MAINNET_CANONICAL_THRESHOLD,
+        MAINNET_TRANSITION_FRONTIER_K,
+        false,
+    )?;
+    let mut bp = BlockParser::new_with_canonical_chain_discovery(
+        blocks_dir,
+        PcbVersion::V1,
+        MAINNET_CANONICAL_THRESHOLD,
+        false,
+        BLOCK_REPORTING_FREQ_NUM,
+        true,
+    )
+    .await?;
+
+    // mainnet-320081 pays a SNARK fee via coinbase
+    let state_hash = "3NK3bLM3eMyCum34ovAGCUw2GWUqDxkNwiti8XtKBYrocinp8oZM";
+    let creator = "B62qkoey9KVcsfNP1uVjmpaHiFkSHJYpK8eF6z6UCuXyFocjjfVBJWZ";
+    let recipient = "B62qosqzHi58Czax2RXfqPhMDzLogBeDVzSpsRDTCN1xeYUfrVy2F8P";
+    let other_creator = "B62qkMUJyt7LmPnfu8in6qshaQSvTgLgNjx6h7YySRJ28wJegJ82n6u";
+
+    let (block, block_bytes) = bp.get_precomputed_block(state_hash).await?;
+    indexer.add_block_to_store(&block, block_bytes, true)?;
+    assert_eq!(
+        indexer_store.get_block_creator(&state_hash.into())?,
+        Some(creator.into())
+    );
+
+    let schema = build_schema(indexer_store.clone());
+    let feetransfers = |creator: &str, canonical: Option<bool>| {
+        let request = Request::new(FEETRANSFERS_QUERY).variables(Variables::from_json(json!({
+            "recipient": recipient,
+            "creator": creator,
+            "canonical": canonical,
+        })));
+        let schema = schema.clone();
+
+        async move {
+            let response = schema.execute(request).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+            let data = response.data.into_json().unwrap();
+            data["feetransfers"].as_array().unwrap().clone()
+        }
+    };
+
+    // the block isn't canonical
+    let fts = feetransfers(creator, Some(false)).await;
+    assert_eq!(fts.len(), 1);
+    assert_eq!(fts[0]["stateHash"], state_hash);
+    assert_eq!(fts[0]["recipient"], recipient);
+    assert_eq!(fts[0]["blockCreator"], creator);
+    assert_eq!(fts[0]["blockHeight"], 320081);
+    assert_eq!(fts[0]["canonical"], false);
+    assert_eq!(fts[0]["fee"], 10_000_000);
+    assert_eq!(fts[0]["type"], "Fee_transfer_via_coinbase");
+
+    // canonical defaults to true
+    assert!(feetransfers(creator, None).await.is_empty());
+    assert!(feetransfers(creator, Some(true)).await.is_empty());
+
+    // different block creator
+    assert!(feetransfers(other_creator, Some(false)).await.is_empty());
+
+    Ok(())
+}
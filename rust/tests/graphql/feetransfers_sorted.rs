@@ -0,0 +1,146 @@
+use crate::helpers::store::*;
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    block::{
+        precomputed::{PcbVersion, PrecomputedBlock},
+        store::BlockStore,
+    },
+    command::internal::store::InternalCommandStore,
+    store::IndexerStore,
+    web::graphql::build_schema,
+};
+use serde_json::{json, Value};
+use std::{path::PathBuf, sync::Arc};
+
+const FEETRANSFERS_QUERY: &str = r#"
+query Feetransfers($query: FeetransferQueryInput, $sortBy: FeetransferSortByInput) {
+  feetransfers(query: $query, sortBy: $sortBy, limit: 1000) {
+    stateHash
+    recipient
+    blockHeight
+    indexInBlock
+    type
+  }
+}
+"#;
+
+const MIN_HEIGHT: u32 = 105492;
+const MAX_HEIGHT: u32 = 105496;
+
+#[tokio::test]
+async fn height_range_sort_and_filters() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-feetransfers-sorted")?;
+    let blocks_dir = PathBuf::from("./tests/data/sequential_blocks");
+    let store = Arc::new(IndexerStore::new(store_dir.path())?);
+
+    let mut blocks = vec![];
+    for entry in std::fs::read_dir(blocks_dir)? {
+        let block = PrecomputedBlock::parse_file(&entry?.path(), PcbVersion::V1)?;
+        store.add_block(&block, 0)?;
+        blocks.push(block);
+    }
+
+    // all internal commands in the height range
+    let mut expected = vec![];
+    for block in blocks
+        .iter()
+        .filter(|block| (MIN_HEIGHT..=MAX_HEIGHT).contains(&block.blockchain_length()))
+    {
+        expected.extend(store.get_internal_commands(&block.state_hash())?);
+    }
+    assert!(!expected.is_empty());
+
+    let schema = build_schema(store.clone());
+    let feetransfers = |query: Value, sort_by: Option<&str>| {
+        let request = Request::new(FEETRANSFERS_QUERY).variables(Variables::from_json(json!({
+            "query": query,
+            "sortBy": sort_by,
+        })));
+        let schema = schema.clone();
+
+        async move {
+            let response = schema.execute(request).await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+            let data = response.data.into_json().unwrap();
+            data["feetransfers"].as_array().unwrap().clone()
+        }
+    };
+    let heights = |fts: &[Value]| -> Vec<u64> {
+        fts.iter()
+            .map(|ft| ft["blockHeight"].as_u64().unwrap())
+            .collect()
+    };
+
+    // descending by default
+    let range = json!({ "blockHeight_gte": MIN_HEIGHT, "blockHeight_lte": MAX_HEIGHT });
+    let desc = feetransfers(range.clone(), None).await;
+    assert_eq!(desc.len(), expected.len());
+    assert!(heights(&desc).windows(2).all(|w| w[0] >= w[1]));
+    assert_eq!(heights(&desc).first(), Some(&(MAX_HEIGHT as u64)));
+
+    let asc = feetransfers(range.clone(), Some("BLOCKHEIGHT_ASC")).await;
+    assert_eq!(asc.len(), expected.len());
+    assert!(heights(&asc).windows(2).all(|w| w[0] <= w[1]));
+    assert_eq!(heights(&asc).first(), Some(&(MIN_HEIGHT as u64)));
+
+    // exclusive bounds
+    let exclusive = feetransfers(
+        json!({ "blockHeight_gt": MIN_HEIGHT, "blockHeight_lt": MAX_HEIGHT }),
+        Some("BLOCKHEIGHT_ASC"),
+    )
+    .await;
+    assert!(heights(&exclusive)
+        .iter()
+        .all(|h| *h > MIN_HEIGHT as u64 && *h < MAX_HEIGHT as u64));
+
+    // recipient within the height range, via the recipient's index
+    let recipient = desc[0]["recipient"].as_str().unwrap().to_string();
+    let num_recipient = expected
+        .iter()
+        .filter(|cmd| cmd.contains_pk(&recipient.clone().into()))
+        .count();
+    for sort_by in ["BLOCKHEIGHT_ASC", "BLOCKHEIGHT_DESC"] {
+        let mut query = range.clone();
+        query["recipient"] = json!(recipient);
+
+        let fts = feetransfers(query, Some(sort_by)).await;
+        assert_eq!(fts.len(), num_recipient);
+        assert!(fts.iter().all(|ft| ft["recipient"] == json!(recipient)));
+
+        let heights = heights(&fts);
+        if sort_by == "BLOCKHEIGHT_ASC" {
+            assert!(heights.windows(2).all(|w| w[0] <= w[1]));
+        } else {
+            assert!(heights.windows(2).all(|w| w[0] >= w[1]));
+        }
+    }
+
+    // type
+    let mut query = range.clone();
+    query["type"] = json!("Coinbase");
+    let coinbases = feetransfers(query, None).await;
+    assert!(!coinbases.is_empty());
+    assert!(coinbases.iter().all(|ft| ft["type"] == "Coinbase"));
+
+    // exact height
+    let exact = feetransfers(json!({ "blockHeight": MIN_HEIGHT }), None).await;
+    assert!(!exact.is_empty());
+    assert!(heights(&exact).iter().all(|h| *h == MIN_HEIGHT as u64));
+
+    // empty ranges
+    assert!(feetransfers(json!({ "blockHeight_lt": 0 }), None)
+        .await
+        .is_empty());
+    assert!(feetransfers(json!({ "blockHeight_gt": u32::MAX }), None)
+        .await
+        .is_empty());
+    assert!(feetransfers(
+        json!({ "blockHeight_gte": MAX_HEIGHT, "blockHeight_lte": MIN_HEIGHT }),
+        None
+    )
+    .await
+    .is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+mod account_activity;
+mod account_balance_history;
+mod account_nonce_at;
+mod block_subscriptions;
+mod blocks_by_creator;
+mod coinbase_receiver;
+mod delegation_history;
+mod failure_stats;
+mod feetransfers_by_creator;
+mod feetransfers_sorted;
+mod pinned_snapshots;
+mod public_api;
+mod raw_block_json;
+mod rewards;
+mod runtime_config;
+mod snark_jobs;
+mod stakes_eras;
+mod stakes_sorted;
+mod token_symbols;
+mod transactions_by_token;
+mod transactions_memo;
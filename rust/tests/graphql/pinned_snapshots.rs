@@ -0,0 +1,136 @@
+use crate::helpers::store::*;
+use async_graphql::{Request, Value, Variables};
+use mina_indexer::{
+    block::{parser::BlockParser, precomputed::PrecomputedBlock, store::BlockStore},
+    store::IndexerStore,
+    web::graphql::{build_schema, IndexerSchema},
+};
+use serde_json::json;
+use std::{path::PathBuf, sync::Arc, thread};
+
+// sections read the same blocks in separate resolvers
+const BLOCKS_QUERY: &str = r#"
+{
+  first: blocks(limit: 100) {
+    stateHash
+  }
+  second: blocks(limit: 100) {
+    stateHash
+  }
+}
+"#;
+
+async fn pinned_query(
+    schema: &IndexerSchema,
+    extensions: serde_json::Value,
+) -> anyhow::Result<(String, serde_json::Value)> {
+    let mut request = Request::new(BLOCKS_QUERY);
+    request.extensions = serde_json::from_value(extensions)?;
+
+    let response = schema.execute(request).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let Some(Value::String(pinned)) = response.extensions.get("pinnedStateHash") else {
+        panic!("missing pinned state hash: {:?}", response.extensions);
+    };
+    Ok((pinned.clone(), response.data.into_json()?))
+}
+
+#[tokio::test]
+async fn consistent_under_concurrent_ingest() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-pinned-snapshots")?;
+    let blocks_dir = PathBuf::from("./tests/data/sequential_blocks");
+    let store = Arc::new(IndexerStore::new(store_dir.path())?);
+
+    let mut blocks = vec![];
+    let mut bp = BlockParser::new_testing(&blocks_dir)?;
+    while let Some((block, block_bytes)) = bp.next_block().await? {
+        blocks.push((PrecomputedBlock::from(block), block_bytes));
+    }
+
+    // seed the best tip
+    let (first, first_bytes) = blocks.remove(0);
+    store.add_block(&first, first_bytes)?;
+    store.set_best_block(&first.state_hash())?;
+
+    let ingest = thread::spawn({
+        let store = store.clone();
+        move || -> anyhow::Result<()> {
+            for (block, block_bytes) in blocks {
+                store.add_block(&block, block_bytes)?;
+                store.set_best_block(&block.state_hash())?;
+            }
+            Ok(())
+        }
+    });
+
+    let schema = build_schema(store.clone());
+    let mut snapshots = vec![];
+    while !ingest.is_finished() {
+        let (pinned, data) = pinned_query(&schema, json!({ "atBestTip": true })).await?;
+        assert_eq!(data["first"], data["second"]);
+        snapshots.push((pinned, data));
+    }
+    ingest.join().unwrap()?;
+
+    // the latest pinned best tip, & its view, are retained
+    let (pinned, data) = pinned_query(&schema, json!({ "atBestTip": true })).await?;
+    assert_eq!(pinned, store.get_best_block_hash()?.unwrap().0);
+    snapshots.push((pinned, data));
+
+    let (pinned, data) = snapshots.last().unwrap();
+    let (repinned, redata) = pinned_query(&schema, json!({ "atStateHash": pinned })).await?;
+    assert_eq!(&repinned, pinned);
+    assert_eq!(&redata, data);
+
+    // blocks are only ever added, so each view contains the previous one's
+    for window in snapshots.windows(2) {
+        let num_blocks = |data: &serde_json::Value| data["first"].as_array().unwrap().len();
+        assert!(num_blocks(&window[0].1) <= num_blocks(&window[1].1));
+    }
+
+    // the snapshot root field pins the request too
+    let response = schema
+        .execute(
+            Request::new(
+                r#"
+                query ($atStateHash: String) {
+                  snapshot(atStateHash: $atStateHash) {
+                    stateHash
+                  }
+                  first: blocks(limit: 100) {
+                    stateHash
+                  }
+                }
+                "#,
+            )
+            .variables(Variables::from_json(json!({ "atStateHash": pinned }))),
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.extensions.get("pinnedStateHash"),
+        Some(&Value::String(pinned.clone()))
+    );
+    let field_data = response.data.into_json()?;
+    assert_eq!(&field_data["snapshot"]["stateHash"], &json!(pinned));
+    assert_eq!(field_data["first"], data["first"]);
+
+    let response = schema
+        .execute({
+            let mut request = Request::new(BLOCKS_QUERY);
+            request.extensions = serde_json::from_value(json!({
+                "atStateHash": "3NKeMoncuHab5ScarV5ViyF16cJPT4taWNSaTLS64Dp67wuXigPZ"
+            }))?;
+            request
+        })
+        .await;
+    assert!(!response.errors.is_empty());
+
+    // unpinned requests have no pinned state hash
+    let response = schema.execute(Request::new(BLOCKS_QUERY)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert!(!response.extensions.contains_key("pinnedStateHash"));
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use crate::helpers::store::*;
+use async_graphql::Request;
+use mina_indexer::{
+    store::IndexerStore,
+    web::{
+        graphql::{build_schema, GraphQLOptions},
+        public_api::PublicApiOptions,
+    },
+};
+use std::sync::Arc;
+
+const QUERY: &str = r#"
+{
+  blocks(limit: 1) {
+    stateHash
+    blockHeight
+    creatorAccount {
+      publicKey
+    }
+  }
+}
+"#;
+
+fn public_api(max_query_depth: usize, max_query_complexity: usize) -> PublicApiOptions {
+    PublicApiOptions {
+        rate_limit_per_min: 60,
+        rate_limit_burst: 10,
+        max_request_bytes: 1024,
+        max_query_depth,
+        max_query_complexity,
+        trust_forwarded_for: false,
+    }
+}
+
+#[tokio::test]
+async fn public_api_query_limits() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-public-api")?;
+    let store = Arc::new(IndexerStore::new(store_dir.path())?);
+    let schema = build_schema(store);
+
+    // within the limits
+    let response = schema
+        .execute(Request::new(QUERY).data(public_api(16, 1000)))
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    // too deep
+    let response = schema
+        .execute(Request::new(QUERY).data(public_api(2, 1000)))
+        .await;
+    assert_eq!(response.errors.len(), 1);
+    assert!(response.errors[0].message.contains("max query depth 2"));
+
+    // too complex
+    let response = schema
+        .execute(Request::new(QUERY).data(public_api(16, 3)))
+        .await;
+    assert_eq!(response.errors.len(), 1);
+    assert!(response.errors[0]
+        .message
+        .contains("max query complexity 3"));
+
+    // the stricter of the runtime config & public API limits applies
+    let options = GraphQLOptions {
+        max_query_complexity: Some(3),
+        ..Default::default()
+    };
+    let response = schema
+        .execute(Request::new(QUERY).data(options).data(public_api(16, 1000)))
+        .await;
+    assert!(response.errors[0]
+        .message
+        .contains("max query complexity 3"));
+
+    Ok(())
+}
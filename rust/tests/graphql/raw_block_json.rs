@@ -0,0 +1,114 @@
+use crate::helpers::store::*;
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    block::{
+        precomputed::{PcbVersion, PrecomputedBlock},
+        store::BlockStore,
+    },
+    store::IndexerStore,
+    web::graphql::{build_schema, build_schema_with_options, GraphQLOptions},
+};
+use serde_json::{json, Value};
+use std::{fs, path::PathBuf, sync::Arc};
+
+const V1_PATH: &str = "./tests/data/misc_blocks/mainnet-128743-3NLmYZD9eaV58opgC5RzQXaoPbyC15McNxw1CuCNatj7F9vGBbNz.json";
+const V2_PATH: &str = "./tests/data/misc_blocks/mainnet-359630-3NLjRmTyUzeA7meRAT3Yjqxzfe95GKBgkLPD2iLeVE5RMCFcw8eL.json";
+
+const RAW_JSON_QUERY: &str = r#"
+query RawJson($stateHash: String!, $path: String) {
+  block(query: { stateHash: $stateHash }) {
+    rawJson(path: $path)
+  }
+}
+"#;
+
+/// Adds the block & returns its state hash
+fn add_block(store: &IndexerStore, path: &str, version: PcbVersion) -> anyhow::Result<String> {
+    let path = PathBuf::from(path);
+    let block = PrecomputedBlock::parse_file(&path, version)?;
+    store.add_block(&block, fs::metadata(&path)?.len())?;
+    Ok(block.state_hash().0)
+}
+
+/// The block file's JSON at the dotted path
+fn file_json(path: &str, json_path: &str) -> anyhow::Result<Value> {
+    let contents = fs::read(path)?;
+    let mut json: Value = serde_json::from_str(&String::from_utf8_lossy(&contents))?;
+    for segment in json_path.split('.') {
+        json = json[segment].take();
+    }
+    Ok(json)
+}
+
+async fn raw_json(
+    store: &Arc<IndexerStore>,
+    options: GraphQLOptions,
+    state_hash: &str,
+    path: &str,
+) -> async_graphql::Response {
+    build_schema_with_options(store.clone(), options)
+        .execute(
+            Request::new(RAW_JSON_QUERY).variables(Variables::from_json(json!({
+                "stateHash": state_hash,
+                "path": path,
+            }))),
+        )
+        .await
+}
+
+#[tokio::test]
+async fn raw_block_json_selection() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-raw-block-json")?;
+    let store = Arc::new(IndexerStore::new(store_dir.path())?);
+    let v1 = add_block(&store, V1_PATH, PcbVersion::V1)?;
+    let v2 = add_block(&store, V2_PATH, PcbVersion::V2)?;
+
+    let options = GraphQLOptions {
+        expose_raw_block_json: true,
+        ..Default::default()
+    };
+    let path = "protocol_state.body.consensus_state.last_vrf_output";
+
+    // nested fields match the block files
+    for (state_hash, file, file_path) in [
+        (&v1, V1_PATH, path.to_string()),
+        (&v2, V2_PATH, format!("data.{path}")),
+    ] {
+        let response = raw_json(&store, options, state_hash, path).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+        let data = response.data.into_json()?;
+        let raw = data["block"]["rawJson"].as_str().expect("raw json");
+        assert_eq!(
+            serde_json::from_str::<Value>(raw)?,
+            file_json(file, &file_path)?
+        );
+    }
+
+    // missing path
+    let response = raw_json(&store, options, &v1, "protocol_state.body.missing").await;
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(
+        response.errors[0].message,
+        "block JSON path not found: protocol_state.body.missing"
+    );
+
+    // selection exceeding the size cap
+    let response = raw_json(&store, options, &v2, "staged_ledger_diff").await;
+    assert_eq!(response.errors.len(), 1);
+    assert!(response.errors[0].message.contains("exceeds"));
+
+    // disabled by default
+    let response = build_schema(store.clone())
+        .execute(
+            Request::new(RAW_JSON_QUERY).variables(Variables::from_json(json!({
+                "stateHash": v1,
+                "path": path,
+            }))),
+        )
+        .await;
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(response.errors[0].message, "Raw block JSON is disabled");
+
+    Ok(())
+}
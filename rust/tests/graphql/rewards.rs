@@ -0,0 +1,107 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    block::{parser::BlockParser, store::BlockStore},
+    canonicity::store::CanonicityStore,
+    constants::MAINNET_COINBASE_REWARD,
+    web::graphql::build_schema,
+};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+const REWARDS_QUERY: &str = r#"
+query Rewards($epoch: Int!, $delegate: String!) {
+  rewards(epoch: $epoch, delegate: $delegate) {
+    epoch
+    delegate
+    numBlocks
+    numSupercharged
+    coinbase
+    superchargedCoinbase
+    txFees
+    snarkFees
+    totalIncome
+    totalDelegated
+    blocks {
+      stateHash
+      blockHeight
+      coinbase
+      supercharged
+    }
+    delegators {
+      publicKey
+      payout
+    }
+  }
+}
+"#;
+
+#[tokio::test]
+async fn rewards() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-rewards")?;
+    let blocks_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&blocks_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let schema = build_schema(store.clone());
+    let execute = |variables: Value| {
+        let schema = schema.clone();
+        async move {
+            schema
+                .execute(Request::new(REWARDS_QUERY).variables(Variables::from_json(variables)))
+                .await
+        }
+    };
+
+    // delegate of the highest canonical block
+    let supply = store.get_epoch_supply(0)?.expect("epoch 0 supply");
+    let height = *supply.heights.iter().max().unwrap();
+    let state_hash = store.get_canonical_hash_at_height(height)?.unwrap();
+    let delegate = store.get_block_creator(&state_hash)?.unwrap();
+
+    let mut canonical_blocks = 0;
+    for height in supply.heights.iter() {
+        let state_hash = store.get_canonical_hash_at_height(*height)?.unwrap();
+        if store.get_block_creator(&state_hash)?.as_ref() == Some(&delegate) {
+            canonical_blocks += 1;
+        }
+    }
+
+    let response = execute(json!({ "epoch": 0, "delegate": delegate.0 })).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let rewards = &response.data.into_json()?["rewards"];
+    assert_eq!(rewards["epoch"], 0);
+    assert_eq!(rewards["delegate"], delegate.0);
+    assert_eq!(rewards["numBlocks"], canonical_blocks);
+    assert_eq!(
+        rewards["blocks"].as_array().unwrap().len(),
+        canonical_blocks
+    );
+
+    // each supercharged coinbase splits into the base & supercharged halves
+    let num_supercharged = rewards["numSupercharged"].as_u64().unwrap();
+    assert_eq!(
+        rewards["superchargedCoinbase"],
+        num_supercharged * MAINNET_COINBASE_REWARD
+    );
+
+    let income = rewards["coinbase"].as_u64().unwrap()
+        + rewards["superchargedCoinbase"].as_u64().unwrap()
+        + rewards["txFees"].as_u64().unwrap()
+        - rewards["snarkFees"].as_u64().unwrap();
+    assert_eq!(rewards["totalIncome"], income);
+
+    // no staking ledger, no delegators
+    assert_eq!(rewards["totalDelegated"], 0);
+    assert_eq!(rewards["delegators"], json!([]));
+
+    // invalid delegate
+    let response = execute(json!({ "epoch": 0, "delegate": "B62qinvalid" })).await;
+    assert!(!response.errors.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use crate::helpers::store::*;
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    block::{parser::BlockParser, precomputed::PrecomputedBlock, store::BlockStore},
+    store::IndexerStore,
+    web::{
+        graphql::{build_schema, GraphQLOptions, IndexerSchema},
+        runtime_config::{RuntimeConfig, SharedRuntimeConfig},
+    },
+};
+use serde_json::json;
+use std::{fs, path::PathBuf, sync::Arc};
+
+const BLOCKS_QUERY: &str = r#"
+query Blocks($limit: Int) {
+  blocks(limit: $limit) {
+    stateHash
+  }
+}
+"#;
+
+const DEEP_QUERY: &str = r#"
+{
+  blocks(limit: 1) {
+    creatorAccount {
+      publicKey
+    }
+  }
+}
+"#;
+
+fn with_max_limit(max_limit: Option<usize>) -> RuntimeConfig {
+    RuntimeConfig {
+        graphql: GraphQLOptions {
+            max_limit,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Request under the current runtime config, as the web server does
+fn request(config: &SharedRuntimeConfig, query: &str, limit: usize) -> Request {
+    Request::new(query)
+        .variables(Variables::from_json(json!({ "limit": limit })))
+        .data(config.load().graphql)
+}
+
+async fn num_blocks(schema: &IndexerSchema, request: Request) -> usize {
+    let response = schema.execute(request).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json().unwrap();
+    data["blocks"].as_array().unwrap().len()
+}
+
+#[tokio::test]
+async fn reload_max_limit() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-runtime-config")?;
+    let blocks_dir = PathBuf::from("./tests/data/sequential_blocks");
+    let store = Arc::new(IndexerStore::new(store_dir.path())?);
+
+    let mut bp = BlockParser::new_testing(&blocks_dir)?;
+    while let Some((block, block_bytes)) = bp.next_block().await? {
+        let block: PrecomputedBlock = block.into();
+        store.add_block(&block, block_bytes)?;
+    }
+
+    let schema = build_schema(store);
+    let config = SharedRuntimeConfig::new(with_max_limit(Some(5)));
+    assert_eq!(
+        num_blocks(&schema, request(&config, BLOCKS_QUERY, 10)).await,
+        5
+    );
+
+    // in flight under the old config
+    let in_flight = schema.execute(request(&config, BLOCKS_QUERY, 10));
+
+    config.store(with_max_limit(Some(3)))?;
+    assert_eq!(
+        num_blocks(&schema, request(&config, BLOCKS_QUERY, 10)).await,
+        3
+    );
+
+    let response = in_flight.await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json()?["blocks"]
+            .as_array()
+            .unwrap()
+            .len(),
+        5
+    );
+
+    // no max limit
+    config.store(with_max_limit(None))?;
+    assert_eq!(
+        num_blocks(&schema, request(&config, BLOCKS_QUERY, 10)).await,
+        10
+    );
+
+    // invalid configs are rejected, keeping the current one
+    assert!(config.store(with_max_limit(Some(0))).is_err());
+    assert_eq!(config.load().graphql.max_limit, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn reload_max_query_depth() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-runtime-config-depth")?;
+    let schema = build_schema(Arc::new(IndexerStore::new(store_dir.path())?));
+
+    let config_dir = tempfile::TempDir::new()?;
+    let path = config_dir.path().join("runtime-config.json");
+    fs::write(&path, r#"{ "graphql": { "max_query_depth": 1 } }"#)?;
+
+    let config = SharedRuntimeConfig::new(RuntimeConfig::from_file(&path)?);
+    let response = schema.execute(request(&config, DEEP_QUERY, 1)).await;
+    assert!(!response.errors.is_empty());
+
+    fs::write(&path, r#"{ "graphql": { "max_query_depth": 8 } }"#)?;
+    config.reload(&path)?;
+    let response = schema.execute(request(&config, DEEP_QUERY, 1)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    // malformed & invalid files are rejected, keeping the current config
+    fs::write(&path, r#"{ "graphql": { "max_query_depth": "#)?;
+    assert!(config.reload(&path).is_err());
+    fs::write(&path, r#"{ "unknown": true }"#)?;
+    assert!(config.reload(&path).is_err());
+    assert_eq!(config.load().graphql.max_query_depth, Some(8));
+
+    // settings default when omitted
+    fs::write(&path, "{}")?;
+    config.reload(&path)?;
+    assert_eq!(*config.load(), RuntimeConfig::default());
+    assert!(config.load().graphiql);
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use crate::helpers::store::*;
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    base::public_key::PublicKey,
+    block::{parser::BlockParser, precomputed::PrecomputedBlock, store::BlockStore},
+    constants::MAINNET_EPOCH_SLOT_COUNT,
+    snark_work::SnarkWorkSummary,
+    store::IndexerStore,
+    web::graphql::build_schema,
+};
+use serde_json::{json, Value};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+const SNARK_JOBS_QUERY: &str = r#"
+query SnarkJobs($epoch: Int!, $prover: String!, $fromHeight: Int!, $toHeight: Int!) {
+  topSnarkProvers(epoch: $epoch, limit: 1000) {
+    publicKey
+    jobCount
+    totalFees
+  }
+  snarkJobs(prover: $prover, fromHeight: $fromHeight, toHeight: $toHeight) {
+    jobCount
+    totalFees
+    jobs {
+      blockHeight
+      stateHash
+      fee
+    }
+  }
+}
+"#;
+
+#[tokio::test]
+async fn prover_jobs_follow_best_chain() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-snark-jobs")?;
+    let blocks_dir = PathBuf::from("./tests/data/sequential_blocks");
+    let store = Arc::new(IndexerStore::new(store_dir.path())?);
+
+    let mut blocks = HashMap::new();
+    let mut bp = BlockParser::new_testing(&blocks_dir)?;
+    while let Some((block, block_bytes)) = bp.next_block().await? {
+        let block = PrecomputedBlock::from(block);
+        store.add_block(&block, block_bytes)?;
+        blocks.insert(block.state_hash(), block);
+    }
+
+    // best chain, from the highest block back to the lowest
+    let mut best_chain = vec![blocks
+        .values()
+        .max_by_key(|block| block.blockchain_length())
+        .unwrap()];
+    while let Some(parent) = blocks.get(&best_chain.last().unwrap().previous_state_hash()) {
+        best_chain.push(parent);
+    }
+
+    let root = best_chain.pop().unwrap();
+    store.set_best_block(&root.state_hash())?;
+    store.set_best_block(&best_chain[0].state_hash())?;
+
+    // per prover jobs & fees of the blocks applied above the root
+    let epoch = best_chain[0].global_slot_since_genesis() / MAINNET_EPOCH_SLOT_COUNT;
+    let mut expected: HashMap<PublicKey, (u32, u64)> = HashMap::new();
+    for block in best_chain.iter() {
+        assert_eq!(
+            block.global_slot_since_genesis() / MAINNET_EPOCH_SLOT_COUNT,
+            epoch
+        );
+
+        for snark in SnarkWorkSummary::from_precomputed(block) {
+            let (num_jobs, total_fees) = expected.entry(snark.prover).or_default();
+            *num_jobs += 1;
+            *total_fees += snark.fee.0;
+        }
+    }
+
+    let (prover, (prover_jobs, prover_fees)) = expected
+        .iter()
+        .max_by_key(|(pk, (num_jobs, _))| (*num_jobs, (*pk).clone()))
+        .map(|(pk, totals)| (pk.clone(), *totals))
+        .expect("best chain SNARK work");
+
+    let schema = build_schema(store.clone());
+    let query = |from_height: u32, to_height: u32| {
+        Request::new(SNARK_JOBS_QUERY).variables(Variables::from_json(json!({
+            "epoch": epoch,
+            "prover": prover.0,
+            "fromHeight": from_height,
+            "toHeight": to_height,
+        })))
+    };
+
+    let (from_height, to_height) = (root.blockchain_length(), best_chain[0].blockchain_length());
+    let response = schema.execute(query(from_height, to_height)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json()?;
+
+    // provers sorted by fees, descending
+    let top_provers = data["topSnarkProvers"].as_array().unwrap();
+    assert_eq!(top_provers.len(), expected.len());
+    for pair in top_provers.windows(2) {
+        assert!(pair[0]["totalFees"].as_u64() >= pair[1]["totalFees"].as_u64());
+    }
+
+    for top_prover in top_provers {
+        let pk = PublicKey::from(top_prover["publicKey"].as_str().unwrap());
+        let (num_jobs, total_fees) = expected[&pk];
+        assert_eq!(top_prover["jobCount"], json!(num_jobs));
+        assert_eq!(top_prover["totalFees"], json!(total_fees));
+    }
+
+    // prover's jobs across the best chain
+    let jobs = &data["snarkJobs"];
+    assert_eq!(jobs["jobCount"], json!(prover_jobs));
+    assert_eq!(jobs["totalFees"], json!(prover_fees));
+
+    let heights: Vec<u64> = jobs["jobs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|job| job["blockHeight"].as_u64().unwrap())
+        .collect();
+    assert_eq!(heights.len() as u32, prover_jobs);
+    assert!(heights.windows(2).all(|pair| pair[0] <= pair[1]));
+
+    for job in jobs["jobs"].as_array().unwrap() {
+        let state_hash = job["stateHash"].as_str().unwrap();
+        assert!(best_chain
+            .iter()
+            .any(|block| block.state_hash().0 == state_hash));
+    }
+
+    // height range excluding the prover's highest job
+    let highest = *heights.last().unwrap() as u32;
+    let response = schema.execute(query(from_height, highest - 1)).await;
+    let data = response.data.into_json()?;
+    assert!(data["snarkJobs"]["jobs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|job| job["blockHeight"].as_u64().unwrap() < highest as u64));
+
+    // invalid height range
+    let response = schema.execute(query(to_height, from_height)).await;
+    assert!(!response.errors.is_empty());
+
+    // unapplying the best chain removes its jobs
+    store.set_best_block(&root.state_hash())?;
+    let response = schema.execute(query(from_height, to_height)).await;
+    let data = response.data.into_json()?;
+    assert_eq!(data["topSnarkProvers"], Value::Array(vec![]));
+    assert_eq!(data["snarkJobs"]["jobCount"], json!(0));
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::Request;
+use mina_indexer::{
+    constants::{HARDFORK_GENESIS_EPOCH_OFFSET, HARDFORK_GENESIS_HASH, MAINNET_GENESIS_HASH},
+    ledger::{staking::StakingLedger, store::staking::StakingLedgerStore},
+    web::graphql::build_schema,
+};
+use serde_json::Value;
+use std::path::PathBuf;
+
+const STAKES_QUERY: &str = r#"
+query {
+  all: stakes(query: { epoch: 0 }, sortBy: BALANCE_DESC, limit: 4000) {
+    era
+    epoch
+    flat_epoch
+    genesis_state_hash
+    public_key
+  }
+  hardfork: stakes(
+    query: { epoch: 0, genesis_state_hash: "3NK4BpDSekaqsG6tx8Nse2zJchRft2JpnbvMiog55WCr5xJZaKeP" }
+    sortBy: BALANCE_DESC
+    limit: 4000
+  ) {
+    era
+    genesis_state_hash
+  }
+}
+"#;
+
+fn eras(accounts: &Value) -> Vec<u64> {
+    accounts
+        .as_array()
+        .expect("stakes")
+        .iter()
+        .map(|account| account["era"].as_u64().expect("era"))
+        .collect()
+}
+
+#[tokio::test]
+async fn stakes_in_both_genesis_eras() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-stakes-eras")?;
+    let ledger_path = PathBuf::from("./tests/data/staking_ledgers")
+        .join("mainnet-0-jx7buQVWFLsXTtzRgSxbYcT8EYLS8KCZbLrfDcJxMtyy4thw2Ee.json");
+
+    let state = mainnet_genesis_state(store_dir.as_ref())?;
+    let store = state.indexer_store.as_ref().unwrap();
+
+    // the same epoch 0 ledger under the original & hardfork genesis
+    let mut num_accounts = 0;
+    for genesis_state_hash in [MAINNET_GENESIS_HASH, HARDFORK_GENESIS_HASH] {
+        let staking_ledger =
+            StakingLedger::parse_file(&ledger_path, genesis_state_hash.into()).await?;
+        num_accounts = staking_ledger.staking_ledger.len();
+        store.add_staking_ledger(staking_ledger, &genesis_state_hash.into())?;
+    }
+
+    let schema = build_schema(store.clone());
+    let response = schema.execute(Request::new(STAKES_QUERY)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json()?;
+
+    // both eras are searched, most recent first
+    let all = eras(&data["all"]);
+    assert_eq!(all.len(), 2 * num_accounts);
+    assert!(all[..num_accounts].iter().all(|era| *era == 1));
+    assert!(all[num_accounts..].iter().all(|era| *era == 0));
+
+    let first = &data["all"][0];
+    assert_eq!(first["epoch"], 0);
+    assert_eq!(first["flat_epoch"], HARDFORK_GENESIS_EPOCH_OFFSET);
+    assert_eq!(first["genesis_state_hash"], HARDFORK_GENESIS_HASH);
+
+    let last = &data["all"][2 * num_accounts - 1];
+    assert_eq!(last["flat_epoch"], 0);
+    assert_eq!(last["genesis_state_hash"], MAINNET_GENESIS_HASH);
+
+    // era-scoped query
+    let hardfork = eras(&data["hardfork"]);
+    assert_eq!(hardfork.len(), num_accounts);
+    assert!(hardfork.iter().all(|era| *era == 1));
+
+    Ok(())
+}
@@ -0,0 +1,249 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    base::public_key::PublicKey, ledger::store::staking::StakingLedgerStore,
+    web::graphql::build_schema,
+};
+use serde_json::{json, Value};
+use std::{collections::HashMap, path::PathBuf};
+
+const STAKES_QUERY: &str = r#"
+query Stakes($epoch: Int!, $delegate: String!, $stakeLte: String!, $limit: Int!) {
+  balanceDesc: stakes(query: { epoch: $epoch }, sortBy: BALANCE_DESC, limit: $limit) {
+    public_key
+  }
+  balanceAsc: stakes(query: { epoch: $epoch }, sortBy: BALANCE_ASC, limit: $limit) {
+    public_key
+  }
+  stakeDesc: stakes(query: { epoch: $epoch }, sortBy: STAKE_DESC, limit: $limit) {
+    public_key
+  }
+  stakeLte: stakes(
+    query: { epoch: $epoch, stake_lte: $stakeLte }
+    sortBy: STAKE_DESC
+    limit: $limit
+  ) {
+    public_key
+  }
+  delegateBalanceDesc: stakes(
+    query: { epoch: $epoch, delegate: $delegate }
+    sortBy: BALANCE_DESC
+    limit: 1000
+  ) {
+    public_key
+  }
+  delegateBalanceAsc: stakes(
+    query: { epoch: $epoch, delegate: $delegate }
+    sortBy: BALANCE_ASC
+    limit: $limit
+  ) {
+    public_key
+  }
+  delegateStakeDesc: stakes(
+    query: { epoch: $epoch, delegate: $delegate }
+    sortBy: STAKE_DESC
+    limit: $limit
+  ) {
+    public_key
+  }
+}
+"#;
+
+const PAGED_STAKES_QUERY: &str = r#"
+query PagedStakes($epoch: Int!, $limit: Int!, $offset: Int!) {
+  stakes(query: { epoch: $epoch }, sortBy: STAKE_DESC, limit: $limit, offset: $offset) {
+    public_key
+    delegationTotals {
+      countDelegates
+      totalDelegatedNanomina
+      delegators(limit: 1000) {
+        public_key
+        balance_nanomina
+      }
+    }
+  }
+}
+"#;
+
+const LIMIT: usize = 25;
+
+fn public_keys(accounts: &Value) -> Vec<String> {
+    accounts
+        .as_array()
+        .expect("stakes")
+        .iter()
+        .map(|account| account["public_key"].as_str().expect("pk").to_string())
+        .collect()
+}
+
+/// Naive path: sort the whole staking ledger in memory
+fn naive_sort(
+    accounts: &[(PublicKey, PublicKey, u64, u64)],
+    sort_key: impl Fn(&(PublicKey, PublicKey, u64, u64)) -> u64,
+    filter: impl Fn(&(PublicKey, PublicKey, u64, u64)) -> bool,
+    reverse: bool,
+    limit: usize,
+) -> Vec<String> {
+    let mut accounts: Vec<_> = accounts.iter().filter(|a| filter(a)).collect();
+    accounts.sort_by(|a, b| (sort_key(a), &a.0).cmp(&(sort_key(b), &b.0)));
+    if reverse {
+        accounts.reverse();
+    }
+
+    accounts
+        .into_iter()
+        .take(limit)
+        .map(|(pk, _, _, _)| pk.to_string())
+        .collect()
+}
+
+fn nanomina_to_mina(nanomina: u64) -> String {
+    format!(
+        "{}.{:09}",
+        nanomina / 1_000_000_000,
+        nanomina % 1_000_000_000
+    )
+}
+
+#[tokio::test]
+async fn sorted_stakes_match_naive_path() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-stakes-sorted")?;
+    let ledgers_dir = PathBuf::from("./tests/data/staking_ledgers");
+    let epoch = 0;
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    state
+        .add_startup_staking_ledgers_to_store(&ledgers_dir)
+        .await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let staking_ledger = store.build_staking_ledger(epoch, None)?.unwrap();
+    let delegations = store.build_aggregated_delegations(epoch, None)?.unwrap();
+
+    // (pk, delegate, balance, stake)
+    let accounts: Vec<_> = staking_ledger
+        .staking_ledger
+        .iter()
+        .map(|(pk, account)| {
+            let stake = delegations
+                .delegations
+                .get(pk)
+                .and_then(|delegation| delegation.total_delegated)
+                .unwrap_or_default();
+            (pk.clone(), account.delegate.clone(), account.balance, stake)
+        })
+        .collect();
+    assert!(accounts.len() > LIMIT);
+
+    // the delegate with the most delegators
+    let mut num_delegators: HashMap<&PublicKey, usize> = HashMap::new();
+    for (_, delegate, _, _) in accounts.iter() {
+        *num_delegators.entry(delegate).or_default() += 1;
+    }
+    let (delegate, _) = num_delegators
+        .into_iter()
+        .max_by_key(|(pk, n)| (*n, (*pk).clone()))
+        .unwrap();
+    let delegate = delegate.clone();
+
+    // stake of the 10th highest staker
+    let mut stakes: Vec<u64> = accounts.iter().map(|(_, _, _, stake)| *stake).collect();
+    stakes.sort_by(|a, b| b.cmp(a));
+    let stake_lte = stakes[10];
+
+    let schema = build_schema(store.clone());
+    let response = schema
+        .execute(
+            Request::new(STAKES_QUERY).variables(Variables::from_json(json!({
+                "epoch": epoch,
+                "delegate": delegate.to_string(),
+                "stakeLte": nanomina_to_mina(stake_lte),
+                "limit": LIMIT,
+            }))),
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json()?;
+
+    let balance = |a: &(PublicKey, PublicKey, u64, u64)| a.2;
+    let stake = |a: &(PublicKey, PublicKey, u64, u64)| a.3;
+    let all = |_: &(PublicKey, PublicKey, u64, u64)| true;
+    let delegated = |a: &(PublicKey, PublicKey, u64, u64)| a.1 == delegate;
+
+    assert_eq!(
+        public_keys(&data["balanceDesc"]),
+        naive_sort(&accounts, balance, all, true, LIMIT)
+    );
+    assert_eq!(
+        public_keys(&data["balanceAsc"]),
+        naive_sort(&accounts, balance, all, false, LIMIT)
+    );
+    assert_eq!(
+        public_keys(&data["stakeDesc"]),
+        naive_sort(&accounts, stake, all, true, LIMIT)
+    );
+    assert_eq!(
+        public_keys(&data["stakeLte"]),
+        naive_sort(&accounts, stake, |a| a.3 <= stake_lte, true, LIMIT)
+    );
+
+    // delegate filters
+    let delegate_balance_desc = naive_sort(&accounts, balance, delegated, true, 1000);
+    assert!(delegate_balance_desc.len() > 1);
+    assert_eq!(
+        public_keys(&data["delegateBalanceDesc"]),
+        delegate_balance_desc
+    );
+    assert_eq!(
+        public_keys(&data["delegateBalanceAsc"]),
+        naive_sort(&accounts, balance, delegated, false, LIMIT)
+    );
+    assert_eq!(
+        public_keys(&data["delegateStakeDesc"]),
+        naive_sort(&accounts, stake, delegated, true, LIMIT)
+    );
+
+    // pages of the stake-sorted accounts
+    let stake_desc = naive_sort(&accounts, stake, all, true, 3 * LIMIT);
+    for page in 0..3 {
+        let response = schema
+            .execute(
+                Request::new(PAGED_STAKES_QUERY).variables(Variables::from_json(json!({
+                    "epoch": epoch,
+                    "limit": LIMIT,
+                    "offset": page * LIMIT,
+                }))),
+            )
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json()?;
+
+        assert_eq!(
+            public_keys(&data["stakes"]),
+            stake_desc[page * LIMIT..][..LIMIT]
+        );
+
+        // each delegator's balance contributes to the delegate's stake
+        for account in data["stakes"].as_array().unwrap() {
+            let totals = &account["delegationTotals"];
+            if totals["countDelegates"] == 0 {
+                continue;
+            }
+
+            let delegators = totals["delegators"].as_array().unwrap();
+            assert_eq!(
+                delegators.len() as u64,
+                totals["countDelegates"].as_u64().unwrap()
+            );
+            assert_eq!(
+                delegators
+                    .iter()
+                    .map(|delegator| delegator["balance_nanomina"].as_u64().unwrap())
+                    .sum::<u64>(),
+                totals["totalDelegatedNanomina"].as_u64().unwrap()
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    base::public_key::PublicKey,
+    block::parser::BlockParser,
+    ledger::{
+        store::best::BestLedgerStore,
+        token::{TokenAddress, TokenSymbol},
+    },
+    web::graphql::{build_schema, tokens::TokenSymbols},
+};
+use serde_json::{json, Value};
+use std::{collections::HashMap, path::PathBuf, str::FromStr};
+
+const MINU_TOKEN: &str = "wfG3GivPMttpt6nQnPuX9eDPnoyA5RJZY23LTc4kkNkCRH2gUd";
+const MINU_OWNER: &str = "B62qkPg6P2We1SZhCq84ZvDKknrWy8P3Moi99Baz8KFpYsMoFJKHHqF";
+
+const TOKEN_SYMBOLS_QUERY: &str = r#"
+query TokenSymbols($token: String!, $pk: String!) {
+  transactions(limit: 10000) {
+    hash
+    zkappCommand {
+      accountUpdates {
+        tokenId
+        tokenSymbol
+        calls {
+          tokenId
+          tokenSymbol
+          calls {
+            tokenId
+            tokenSymbol
+          }
+        }
+      }
+    }
+  }
+  tokenHolders(token: $token) {
+    token
+    tokenSymbol
+  }
+  accounts(query: { publicKey: $pk, token: $token }) {
+    token
+    tokenSymbol
+  }
+}
+"#;
+
+/// Collects the `(tokenId, tokenSymbol)` pairs of an account update tree
+fn collect_update_tokens(updates: &Value, tokens: &mut Vec<(String, Value)>) {
+    for update in updates.as_array().into_iter().flatten() {
+        tokens.push((
+            update["tokenId"].as_str().expect("token id").to_string(),
+            update["tokenSymbol"].clone(),
+        ));
+        collect_update_tokens(&update["calls"], tokens);
+    }
+}
+
+#[ignore = "only tested in tier 1 via cargo nextest --run-ignored all"]
+#[tokio::test]
+async fn zkapp_token_symbols() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-token-symbols")?;
+    let blocks_dir = &PathBuf::from("./tests/data/hardfork");
+
+    let mut state = hardfork_genesis_state(store_dir.path())?;
+    let mut bp = BlockParser::new_testing(blocks_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let minu_token = TokenAddress::from_str(MINU_TOKEN)?;
+    let unknown_token = TokenAddress::new("x".repeat(TokenAddress::LEN)).unwrap();
+
+    // MINA is hardcoded, MINU is set by its owner & unknown tokens have none
+    assert_eq!(
+        store.get_token_symbols(&[TokenAddress::default(), minu_token.clone(), unknown_token])?,
+        vec![
+            Some(TokenSymbol::default()),
+            Some(TokenSymbol::new("MINU")),
+            None
+        ]
+    );
+
+    let schema = build_schema(store.clone());
+    let response = schema
+        .execute(
+            Request::new(TOKEN_SYMBOLS_QUERY).variables(Variables::from_json(json!({
+                "token": MINU_TOKEN,
+                "pk": PublicKey::from(MINU_OWNER).to_string(),
+            }))),
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    // zkapp account updates carry their token's symbol
+    let data = response.data.into_json()?;
+    let mut update_tokens = vec![];
+    for txn in data["transactions"].as_array().expect("transactions") {
+        collect_update_tokens(&txn["zkappCommand"]["accountUpdates"], &mut update_tokens);
+    }
+
+    assert!(update_tokens
+        .iter()
+        .any(|(token, symbol)| token == MINU_TOKEN && symbol == "MINU"));
+    for (token, symbol) in update_tokens.iter() {
+        if *token == TokenAddress::default().0 {
+            assert_eq!(symbol, "MINA");
+        }
+    }
+
+    // token holders & accounts
+    assert_eq!(
+        data["tokenHolders"],
+        json!({ "token": MINU_TOKEN, "tokenSymbol": "MINU" })
+    );
+    assert_eq!(
+        data["accounts"],
+        json!([{ "token": MINU_TOKEN, "tokenSymbol": "MINU" }])
+    );
+
+    // all distinct tokens resolve with a single batched lookup
+    let symbols = TokenSymbols::resolve(store, update_tokens.iter().map(|(t, _)| t.as_str()))?;
+    assert_eq!(symbols.num_lookups(), 1);
+
+    let expected: HashMap<_, _> = update_tokens.into_iter().collect();
+    for (token, symbol) in expected {
+        assert_eq!(json!(symbols.get(&token)), symbol, "{token}");
+    }
+
+    Ok(())
+}
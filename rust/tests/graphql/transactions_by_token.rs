@@ -0,0 +1,91 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::{Request, Variables};
+use mina_indexer::{block::parser::BlockParser, web::graphql::build_schema};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+const MINU_TOKEN: &str = "wfG3GivPMttpt6nQnPuX9eDPnoyA5RJZY23LTc4kkNkCRH2gUd";
+
+const TOKEN_TRANSACTIONS_QUERY: &str = r#"
+query TokenTransactions($token: String!, $sortBy: TransactionSortByInput) {
+  transactionsByToken(tokenId: $token, limit: 1000, sortBy: $sortBy) {
+    hash
+    blockHeight
+    zkappCommand {
+      accountUpdates {
+        tokenId
+        calls {
+          tokenId
+          calls {
+            tokenId
+          }
+        }
+      }
+    }
+  }
+}
+"#;
+
+/// Whether the account update tree involves the token
+fn involves_token(updates: &Value, token: &str) -> bool {
+    updates
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|update| update["tokenId"] == token || involves_token(&update["calls"], token))
+}
+
+#[ignore = "only tested in tier 1 via cargo nextest --run-ignored all"]
+#[tokio::test]
+async fn zkapp_token_transactions() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-transactions-by-token")?;
+    let blocks_dir = &PathBuf::from("./tests/data/hardfork");
+
+    let mut state = hardfork_genesis_state(store_dir.path())?;
+    let mut bp = BlockParser::new_testing(blocks_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let schema = build_schema(store.clone());
+    let query = |variables: Value| {
+        let schema = schema.clone();
+        async move {
+            let response = schema
+                .execute(
+                    Request::new(TOKEN_TRANSACTIONS_QUERY)
+                        .variables(Variables::from_json(variables)),
+                )
+                .await;
+            assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+            let data = response.data.into_json().unwrap();
+            data["transactionsByToken"].as_array().unwrap().clone()
+        }
+    };
+
+    // each transaction involves the token, most recent first
+    let desc = query(json!({ "token": MINU_TOKEN })).await;
+    assert!(!desc.is_empty());
+    assert!(desc
+        .iter()
+        .all(|txn| involves_token(&txn["zkappCommand"]["accountUpdates"], MINU_TOKEN)));
+    assert!(desc
+        .windows(2)
+        .all(|pair| pair[0]["blockHeight"].as_u64() >= pair[1]["blockHeight"].as_u64()));
+
+    // ascending is the reverse
+    let mut asc = query(json!({ "token": MINU_TOKEN, "sortBy": "BLOCKHEIGHT_ASC" })).await;
+    asc.reverse();
+    let heights = |txns: &[Value]| {
+        txns.iter()
+            .map(|txn| txn["blockHeight"].as_u64())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(heights(&asc), heights(&desc));
+
+    // tokens without transactions
+    let unknown = "x".repeat(MINU_TOKEN.len());
+    assert!(query(json!({ "token": unknown })).await.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use crate::helpers::store::*;
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    block::{parser::BlockParser, precomputed::PrecomputedBlock, store::BlockStore},
+    command::store::UserCommandStore,
+    store::IndexerStore,
+    utility::store::command::user::{
+        txn_memo_of_value, user_commands_iterator_state_hash, user_commands_iterator_txn_hash,
+    },
+    web::graphql::build_schema,
+};
+use serde_json::json;
+use speedb::IteratorMode;
+use std::{path::PathBuf, sync::Arc};
+
+const MEMO_SEARCH_QUERY: &str = r#"
+query MemoSearch($memo: String!) {
+  transactions(query: { memo_contains: $memo, feeGte: FEE_GTE }, sortBy: BLOCKHEIGHT_DESC) {
+    hash
+    memo
+    fee
+    blockHeight
+    block {
+      stateHash
+    }
+  }
+}
+"#;
+
+#[tokio::test]
+async fn memo_and_fee_search() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-transactions-memo")?;
+    let blocks_dir = PathBuf::from("./tests/data/sequential_blocks");
+    let store = Arc::new(IndexerStore::new(store_dir.path())?);
+
+    let mut bp = BlockParser::new_testing(&blocks_dir)?;
+    let mut best_block: Option<PrecomputedBlock> = None;
+    while let Some((block, block_bytes)) = bp.next_block().await? {
+        let block = PrecomputedBlock::from(block);
+        store.add_block(&block, block_bytes)?;
+
+        if best_block.as_ref().map_or(true, |best| {
+            best.blockchain_length() < block.blockchain_length()
+        }) {
+            best_block = Some(block);
+        }
+    }
+    store.set_best_block(&best_block.unwrap().state_hash())?;
+
+    // all commands with memos are indexed
+    let mut commands = vec![];
+    for (key, _) in store
+        .user_commands_height_iterator(IteratorMode::Start)
+        .flatten()
+    {
+        let txn_hash = user_commands_iterator_txn_hash(&key)?;
+        let state_hash = user_commands_iterator_state_hash(&key)?;
+        let cmd = store
+            .get_user_command_state_hash(&txn_hash, &state_hash)?
+            .unwrap();
+
+        if !cmd.command.memo().is_empty() {
+            commands.push(cmd);
+        }
+    }
+
+    let mut num_indexed = 0;
+    for (_, value) in store
+        .user_commands_memo_iterator(IteratorMode::Start)
+        .flatten()
+    {
+        let (_, memo) = txn_memo_of_value(&value)?;
+        assert!(!memo.is_empty());
+        num_indexed += 1;
+    }
+    assert!(!commands.is_empty());
+    assert_eq!(num_indexed, commands.len());
+
+    // search by a memo substring & the median fee
+    let memo = commands[0].command.memo();
+    let needle: String = memo.chars().skip(1).take(3).collect();
+    let mut fees: Vec<u64> = commands.iter().map(|cmd| cmd.command.fee()).collect();
+    fees.sort();
+    let fee_gte = fees[fees.len() / 2];
+
+    let mut expected: Vec<(String, String)> = commands
+        .iter()
+        .filter(|cmd| cmd.command.memo().contains(&needle) && cmd.command.fee() >= fee_gte)
+        .map(|cmd| (cmd.tx_hash.to_string(), cmd.state_hash.0.clone()))
+        .collect();
+    expected.sort();
+    assert!(!expected.is_empty());
+
+    let schema = build_schema(store.clone());
+    let response = schema
+        .execute(
+            Request::new(MEMO_SEARCH_QUERY.replace("FEE_GTE", &fee_gte.to_string()))
+                .variables(Variables::from_json(json!({ "memo": needle }))),
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json()?;
+    let transactions = data["transactions"].as_array().unwrap();
+    for txn in transactions {
+        assert!(txn["memo"].as_str().unwrap().contains(&needle));
+        assert!(txn["fee"].as_u64().unwrap() >= fee_gte);
+    }
+
+    // sorted by block height
+    let heights: Vec<u64> = transactions
+        .iter()
+        .map(|txn| txn["blockHeight"].as_u64().unwrap())
+        .collect();
+    assert!(heights.windows(2).all(|pair| pair[0] >= pair[1]));
+
+    let mut found: Vec<(String, String)> = transactions
+        .iter()
+        .map(|txn| {
+            (
+                txn["hash"].as_str().unwrap().to_string(),
+                txn["block"]["stateHash"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    found.sort();
+    assert_eq!(found, expected);
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::state_hash::StateHash,
+    block::{extract_height_and_hash, extract_network_height_hash},
+    chain::Network,
+    ingestion::backfill::{
+        backfill_missing_blocks, backfill_targets, BackfillTarget, BlockBackfillConfig,
+        BlocksDirHeights,
+    },
+    server::ingest_block,
+    state::IndexerState,
+};
+use std::{fs, path::PathBuf, sync::Arc};
+use tempfile::TempDir;
+use tokio::sync::RwLock;
+
+const MISSING_HEIGHT: u32 = 10;
+
+#[tokio::test]
+async fn backfill_dangling_branch_parent() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("block-backfill")?;
+    let archive_dir = fs::canonicalize("./tests/data/canonical_chain_discovery/contiguous")?;
+    let blocks_dir = TempDir::with_prefix("block-backfill-blocks")?;
+
+    let mut paths = fs::read_dir(&archive_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort_by_key(|path| extract_height_and_hash(path).0);
+
+    // the blocks dir skips a height
+    let (missing_path, paths): (Vec<PathBuf>, Vec<PathBuf>) = paths
+        .into_iter()
+        .partition(|path| extract_height_and_hash(path).0 == MISSING_HEIGHT);
+    for path in paths.iter() {
+        fs::copy(path, blocks_dir.path().join(path.file_name().unwrap()))?;
+    }
+
+    let (_, _, missing_hash) = extract_network_height_hash(&missing_path[0])?;
+    let mut blocks_dir_heights =
+        BlocksDirHeights::scan(blocks_dir.path(), &Network::Mainnet, false)?;
+    assert_eq!(blocks_dir_heights.gaps(), vec![MISSING_HEIGHT]);
+
+    // blocks above the gap form a dangling branch
+    let state = Arc::new(RwLock::new(mainnet_genesis_state(store_dir.as_ref())?));
+    for path in paths.iter() {
+        let block = IndexerState::parse_file(&state, path).await?;
+        ingest_block(&state, &block, path.metadata()?.len(), None).await?;
+    }
+
+    {
+        let state = state.read().await;
+        assert_eq!(state.best_tip_block().blockchain_length, MISSING_HEIGHT - 1);
+        assert_eq!(
+            backfill_targets(&state, Some(&blocks_dir_heights))?,
+            vec![BackfillTarget {
+                blockchain_length: MISSING_HEIGHT,
+                state_hash: Some(missing_hash.clone()),
+            }]
+        );
+    }
+
+    // backfill from a local archive mirror
+    let config = BlockBackfillConfig {
+        url_template: format!(
+            "file://{}/{{network}}-{{height}}-{{state_hash}}.json",
+            archive_dir.display()
+        ),
+        list_url_template: format!("file://{}/{{network}}-{{height}}-", archive_dir.display()),
+        ..Default::default()
+    };
+    let report = backfill_missing_blocks(
+        &state,
+        &config,
+        Some(blocks_dir.path()),
+        Some(&mut blocks_dir_heights),
+    )
+    .await?;
+    assert_eq!(report.num_fetched, 1);
+    assert_eq!(report.num_added, 1);
+    assert!(report.failed.is_empty());
+
+    // the dangling branch is connected & the blocks dir is complete
+    let (best_length, best_hash) = extract_height_and_hash(paths.last().unwrap());
+    let state = state.read().await;
+    assert!(state.dangling_branches.is_empty());
+    assert_eq!(state.best_tip_block().blockchain_length, best_length);
+    assert_eq!(
+        state.best_tip_block().state_hash,
+        StateHash::from(best_hash)
+    );
+    assert!(blocks_dir
+        .path()
+        .join(missing_path[0].file_name().unwrap())
+        .exists());
+
+    // the backfilled height is recorded without rescanning
+    assert!(blocks_dir_heights.gaps().is_empty());
+    assert_eq!(
+        BlocksDirHeights::scan(blocks_dir.path(), &Network::Mainnet, false)?,
+        blocks_dir_heights
+    );
+
+    Ok(())
+}
+
+#[test]
+fn archive_url_templates() {
+    let config = BlockBackfillConfig::default();
+    let state_hash = StateHash::from("3NKGgTk7en3347KH81yDra876GPAUSoSePrfVKPmwR1KHfMpvJC5");
+
+    assert_eq!(
+        config.block_url(&Network::Mainnet, 10, &state_hash),
+        "https://storage.googleapis.com/mina_network_block_data/mainnet-10-3NKGgTk7en3347KH81yDra876GPAUSoSePrfVKPmwR1KHfMpvJC5.json"
+    );
+    assert_eq!(
+        config.list_url(&Network::Devnet, 10),
+        "https://storage.googleapis.com/storage/v1/b/mina_network_block_data/o?prefix=devnet-10-"
+    );
+}
@@ -0,0 +1,78 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{
+        extract_height_and_hash,
+        precomputed::{PcbVersion, PrecomputedBlock},
+    },
+    store::block_subscriptions::BlockNotificationKind,
+    web::feed::BlockFeedMessage,
+};
+use std::{fs, path::PathBuf};
+
+#[test]
+fn best_tip_and_canonical_block_messages() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("block-feed")?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let store = state.indexer_store.clone().unwrap();
+
+    let mut subscription = store.subscribe_block_kinds(&[
+        BlockNotificationKind::BestTipChanged,
+        BlockNotificationKind::NewCanonicalBlock,
+    ]);
+
+    let blocks_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    let mut paths = fs::read_dir(blocks_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort_by_key(|path| extract_height_and_hash(path).0);
+
+    let mut blocks = vec![];
+    for path in paths.iter() {
+        let block = PrecomputedBlock::parse_file(path, PcbVersion::V1)?;
+        assert!(state.block_pipeline(&block, fs::metadata(path)?.len())?);
+        blocks.push(block);
+    }
+
+    let mut best_tips = vec![];
+    let mut canonical = vec![];
+    while let Some(notification) = subscription.try_next() {
+        let message = BlockFeedMessage::new(&store, &notification)?;
+        match message.kind {
+            "bestTip" => best_tips.push(message),
+            "canonicalBlock" => canonical.push(message),
+            kind => panic!("unexpected block feed message type {kind}"),
+        }
+    }
+
+    // each block became the best tip, with its ledger hashes
+    assert_eq!(best_tips.len(), blocks.len());
+    for (message, block) in best_tips.iter().zip(blocks.iter()) {
+        assert_eq!(message.state_hash, block.state_hash().0);
+        assert_eq!(message.block_height, block.blockchain_length());
+        assert_eq!(
+            message.global_slot_since_genesis,
+            block.global_slot_since_genesis()
+        );
+        assert_eq!(
+            message.staged_ledger_hash,
+            Some(block.staged_ledger_hash().0)
+        );
+        assert_eq!(
+            message.snarked_ledger_hash,
+            block.snarked_ledger_hash().map(|hash| hash.0)
+        );
+    }
+
+    // canonical blocks are fed in height order
+    assert!(!canonical.is_empty());
+    assert!(canonical
+        .windows(2)
+        .all(|pair| pair[0].block_height + 1 == pair[1].block_height));
+
+    // messages are JSON objects tagged by type
+    let json = serde_json::to_value(&best_tips[0])?;
+    assert_eq!(json["type"], "bestTip");
+    assert_eq!(json["blockHeight"], blocks[0].blockchain_length());
+
+    Ok(())
+}
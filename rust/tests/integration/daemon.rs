@@ -0,0 +1,260 @@
+//! Simulates a running daemon: block files are delivered out of order, with
+//! forks, to a watched dir & the indexer is restarted mid-way. The result
+//! must match a single-pass ingest of the same blocks.
+
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::state_hash::StateHash,
+    block::{extract_state_hash, parser::BlockParser, precomputed::PcbVersion, store::BlockStore},
+    canonicity::store::CanonicityStore,
+    command::store::UserCommandStore,
+    constants::*,
+    event::{
+        db::{DbBlockEvent, DbCanonicityEvent, DbEvent},
+        store::EventStore,
+        IndexerEvent,
+    },
+    ledger::{genesis::GenesisLedger, store::best::BestLedgerStore},
+    maintenance::FakeClock,
+    server::{process_block_file, IndexerVersion},
+    state::{IndexerState, IndexerStateConfig},
+    store::IndexerStore,
+};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tempfile::TempDir;
+use tokio::sync::RwLock;
+
+/// Fork of the canonical block at length 10
+const FORK_BLOCK: &str = "mainnet-10-3NKHYHrqKpDcon6ToV5CLDiheanjshk5gcsNqefnK78phCFTR2aL.json";
+
+/// Block files delivered, in order, before the restart
+const BEFORE_RESTART: &[&str] = &[
+    "2", "4", "3", "5", "7", "6", "8", FORK_BLOCK, "9", "10", "12", "11",
+];
+
+/// Block files delivered while the indexer is down
+const WHILE_DOWN: &[&str] = &["14", "13"];
+
+/// Block files delivered after the restart
+const AFTER_RESTART: &[&str] = &["16", "15", "17", "19", "18", "21", "20"];
+
+/// Source path of the block file, given either its length or file name
+fn block_path(block: &str) -> anyhow::Result<PathBuf> {
+    if block == FORK_BLOCK {
+        return Ok(PathBuf::from("./tests/data/canonical_chain_discovery/gaps").join(block));
+    }
+
+    let dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    for entry in std::fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let file_name = path.file_name().unwrap().to_str().unwrap();
+
+        if file_name.starts_with(&format!("mainnet-{block}-")) {
+            return Ok(path);
+        }
+    }
+
+    anyhow::bail!("No contiguous block of length {block}")
+}
+
+/// Copies the block file to the watched dir
+fn deliver(block: &str, watched_dir: &Path) -> anyhow::Result<PathBuf> {
+    let path = block_path(block)?;
+    let dest = watched_dir.join(path.file_name().unwrap());
+
+    std::fs::copy(&path, &dest)?;
+    Ok(dest)
+}
+
+fn restart_config(store: Arc<IndexerStore>) -> anyhow::Result<IndexerStateConfig> {
+    Ok(IndexerStateConfig::new(
+        GenesisLedger::new_v1()?,
+        IndexerVersion::default(),
+        store,
+        MAINNET_CANONICAL_THRESHOLD,
+        MAINNET_TRANSITION_FRONTIER_K,
+        false,
+    ))
+}
+
+/// New canonical block events, in order, & new block events
+fn event_log(store: &IndexerStore) -> anyhow::Result<(Vec<(u32, StateHash)>, BTreeSet<StateHash>)> {
+    let mut canonical = vec![];
+    let mut new_blocks = BTreeSet::new();
+
+    for seq_num in 0..store.get_next_seq_num()? {
+        match store.get_event(seq_num)? {
+            Some(IndexerEvent::Db(DbEvent::Canonicity(DbCanonicityEvent::NewCanonicalBlock {
+                state_hash,
+                blockchain_length,
+            }))) => canonical.push((blockchain_length, state_hash)),
+            Some(IndexerEvent::Db(DbEvent::Block(DbBlockEvent::NewBlock {
+                state_hash, ..
+            }))) => {
+                new_blocks.insert(state_hash);
+            }
+            _ => (),
+        }
+    }
+
+    Ok((canonical, new_blocks))
+}
+
+/// Witness trees & event log differences, for debugging
+fn dump(state: &IndexerState, reference: &IndexerState) -> anyhow::Result<String> {
+    let store = state.indexer_store.as_ref().unwrap();
+    let reference_store = reference.indexer_store.as_ref().unwrap();
+    let (canonical, new_blocks) = event_log(store)?;
+    let (reference_canonical, reference_new_blocks) = event_log(reference_store)?;
+
+    Ok(format!(
+        "===== Witness tree =====\n{state}\n===== Reference witness tree =====\n{reference}\n\
+         ===== Canonical block events =====\n{canonical:?}\n\
+         ===== Reference canonical block events =====\n{reference_canonical:?}\n\
+         ===== New block events missing =====\n{:?}\n\
+         ===== New block events not in reference =====\n{:?}",
+        reference_new_blocks
+            .difference(&new_blocks)
+            .collect::<Vec<_>>(),
+        new_blocks
+            .difference(&reference_new_blocks)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// Compares the canonical chain, best ledger, aggregates & event log
+fn mismatches(state: &IndexerState, reference: &IndexerState) -> anyhow::Result<Vec<String>> {
+    let store = state.indexer_store.as_ref().unwrap();
+    let reference_store = reference.indexer_store.as_ref().unwrap();
+    let mut mismatches = vec![];
+
+    macro_rules! check {
+        ($name:expr, $f:expr) => {
+            let (value, expect) = ($f(store)?, $f(reference_store)?);
+            if value != expect {
+                mismatches.push(format!("{}: {value:?} != {expect:?}", $name));
+            }
+        };
+    }
+
+    // witness tree
+    if state.best_tip_block().state_hash != reference.best_tip_block().state_hash {
+        mismatches.push(format!(
+            "best tip: {} != {}",
+            state.best_tip_block().summary(),
+            reference.best_tip_block().summary()
+        ));
+    }
+
+    // canonical chain
+    check!("best block", |s: &IndexerStore| s.get_best_block_hash());
+    for height in 1..=reference.best_tip_block().blockchain_length {
+        check!(format!("canonical hash at {height}"), |s: &IndexerStore| s
+            .get_canonical_hash_at_height(height));
+        check!(format!("num blocks at {height}"), |s: &IndexerStore| s
+            .get_num_blocks_at_height(height));
+    }
+
+    for block in BEFORE_RESTART.iter().chain(WHILE_DOWN).chain(AFTER_RESTART) {
+        let state_hash: StateHash = extract_state_hash(&block_path(block)?).into();
+        check!(format!("canonicity of {state_hash}"), |s: &IndexerStore| s
+            .get_block_canonicity(&state_hash));
+    }
+
+    // final ledger
+    check!("best ledger", |s: &IndexerStore| s.get_best_ledger(false));
+    check!("num accounts", |s: &IndexerStore| s.get_num_accounts());
+
+    // aggregates
+    check!("block production", |s: &IndexerStore| s
+        .get_block_production_total_count());
+    check!("canonical block production", |s: &IndexerStore| s
+        .get_block_production_canonical_total_count());
+    check!("user commands", |s: &IndexerStore| s
+        .get_user_commands_total_count());
+    check!("canonical user commands", |s: &IndexerStore| s
+        .get_canonical_user_commands_count());
+
+    // event log
+    check!("event log", event_log);
+    Ok(mismatches)
+}
+
+#[tokio::test]
+#[ignore = "only tested in tier 1 via cargo nextest --run-ignored all"]
+async fn deliver_with_forks_and_restart() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("daemon-simulation")?;
+    let watched_dir = TempDir::with_prefix("daemon-simulation-blocks")?;
+    let clock = FakeClock::new(MAINNET_GENESIS_TIMESTAMP);
+
+    // deliver blocks to the running indexer
+    let state = Arc::new(RwLock::new(mainnet_genesis_state(store_dir.as_ref())?));
+    for block in BEFORE_RESTART {
+        let path = deliver(block, watched_dir.path())?;
+        process_block_file(&path, &state, &clock).await?;
+        clock.advance(MAINNET_BLOCK_SLOT_TIME_MILLIS);
+    }
+
+    // shut down & deliver blocks while down
+    let state = Arc::try_unwrap(state).expect("sole state handle");
+    let store = state.into_inner().shutdown()?.expect("indexer store");
+    for block in WHILE_DOWN {
+        deliver(block, watched_dir.path())?;
+    }
+
+    // restart from the store & catch up with the blocks dir
+    let mut state = IndexerState::new_without_genesis_events(restart_config(store)?)?;
+    let min_length_filter = state.startup_from_db()?;
+    let mut block_parser = BlockParser::new_length_sorted_min_filtered(
+        watched_dir.path(),
+        PcbVersion::V1,
+        min_length_filter,
+        false,
+    )?;
+    state.add_blocks(&mut block_parser).await?;
+
+    // continue delivering blocks
+    let state = Arc::new(RwLock::new(state));
+    for block in AFTER_RESTART {
+        let path = deliver(block, watched_dir.path())?;
+        process_block_file(&path, &state, &clock).await?;
+        clock.advance(MAINNET_BLOCK_SLOT_TIME_MILLIS);
+    }
+    let state = Arc::try_unwrap(state)
+        .expect("sole state handle")
+        .into_inner();
+
+    // single-pass reference ingest of the same blocks
+    let reference_store_dir = setup_new_db_dir("daemon-simulation-reference")?;
+    let mut reference = mainnet_genesis_state(reference_store_dir.as_ref())?;
+    let mut block_parser = BlockParser::new_testing(watched_dir.path())?;
+    reference.add_blocks(&mut block_parser).await?;
+
+    let mismatches = mismatches(&state, &reference)?;
+    assert!(
+        mismatches.is_empty(),
+        "{}\n{}",
+        mismatches.join("\n"),
+        dump(&state, &reference)?
+    );
+
+    // watcher receipts use the injected clock
+    let store = state.indexer_store.as_ref().unwrap();
+    let path = block_path(AFTER_RESTART[0])?;
+    let state_hash: StateHash = extract_state_hash(&path).into();
+    let receipt = store
+        .get_block_receipt(&state_hash)?
+        .expect("block receipt");
+    assert_eq!(
+        receipt.watcher_seen_at,
+        Some(
+            MAINNET_GENESIS_TIMESTAMP
+                + BEFORE_RESTART.len() as u64 * MAINNET_BLOCK_SLOT_TIME_MILLIS
+        )
+    );
+    Ok(())
+}
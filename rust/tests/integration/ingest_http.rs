@@ -0,0 +1,97 @@
+use crate::helpers::{state::*, store::*};
+use actix_web::http::StatusCode;
+use mina_indexer::{
+    base::state_hash::StateHash,
+    block::{extract_height_and_hash, store::BlockStore},
+    chain::Network,
+    ingestion::http::{ingest_posted_block, IngestedBlock},
+    maintenance::FakeClock,
+    server::{BlockIngestOutcome, NetworkIndexers},
+    store::IndexerStore,
+};
+use std::{path::PathBuf, sync::Arc};
+use tokio::sync::RwLock;
+
+fn status_code(error: actix_web::Error) -> StatusCode {
+    error.error_response().status()
+}
+
+#[tokio::test]
+async fn post_blocks_to_network_indexer() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("ingest-http")?;
+    let blocks_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let state = Arc::new(RwLock::new(mainnet_genesis_state(store_dir.as_ref())?));
+    let indexers = NetworkIndexers::default();
+    indexers
+        .write()
+        .unwrap()
+        .insert(Network::Mainnet, state.clone());
+
+    let mut paths = std::fs::read_dir(&blocks_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort_by_key(|path| extract_height_and_hash(path).0);
+
+    let clock = FakeClock::new(1_000);
+    for path in paths.iter() {
+        let file_name = path.file_name().unwrap().to_str().unwrap();
+        let ingested = ingest_posted_block(&indexers, file_name, std::fs::read(path)?, &clock)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            ingested,
+            IngestedBlock {
+                state_hash: extract_height_and_hash(path).1.into(),
+                outcome: BlockIngestOutcome::Added,
+            }
+        );
+    }
+
+    // the posted blocks are indexed like watched block files
+    let (best_length, best_hash) = extract_height_and_hash(paths.last().unwrap());
+    let state_hash: StateHash = best_hash.into();
+    {
+        let state = state.read().await;
+        assert_eq!(state.best_tip_block().blockchain_length, best_length);
+        assert_eq!(state.best_tip_block().state_hash, state_hash);
+
+        let store: &IndexerStore = state.indexer_store.as_ref().unwrap();
+        assert_eq!(store.get_best_block_hash()?, Some(state_hash.clone()));
+
+        let receipt = store.get_block_receipt(&state_hash)?.unwrap();
+        assert_eq!(receipt.watcher_seen_at, Some(1_000));
+        assert_eq!(receipt.file_modified_at, None);
+        assert!(receipt.latency_reliable);
+    }
+
+    // reposted blocks are already present
+    let path = paths.last().unwrap();
+    let file_name = path.file_name().unwrap().to_str().unwrap();
+    let ingested = ingest_posted_block(&indexers, file_name, std::fs::read(path)?, &clock)
+        .await
+        .unwrap();
+    assert_eq!(ingested.outcome, BlockIngestOutcome::AlreadyPresent);
+
+    // invalid file names
+    let error = ingest_posted_block(&indexers, "block.json", std::fs::read(path)?, &clock)
+        .await
+        .unwrap_err();
+    assert_eq!(status_code(error), StatusCode::BAD_REQUEST);
+
+    // unindexed networks
+    let devnet_file_name = file_name.replacen("mainnet", "devnet", 1);
+    let error = ingest_posted_block(&indexers, &devnet_file_name, std::fs::read(path)?, &clock)
+        .await
+        .unwrap_err();
+    assert_eq!(status_code(error), StatusCode::NOT_FOUND);
+
+    // malformed contents
+    let error = ingest_posted_block(&indexers, file_name, b"{}".to_vec(), &clock)
+        .await
+        .unwrap_err();
+    assert_eq!(status_code(error), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
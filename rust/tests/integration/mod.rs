@@ -0,0 +1,4 @@
+mod block_backfill;
+mod block_feed;
+mod daemon;
+mod ingest_http;
@@ -0,0 +1,171 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::{amount::Amount, public_key::PublicKey, state_hash::StateHash},
+    block::{parser::BlockParser, store::BlockStore},
+    ledger::{
+        diff::account::{AccountDiff, PaymentDiff, UpdateType},
+        store::best::{AccountStatus, AccountStatusReads, BestLedgerStore, DbAccountUpdate},
+        token::TokenAddress,
+    },
+    store::IndexerStore,
+    utility::store::ledger::best::split_best_account_sort_key,
+};
+use std::{cell::Cell, collections::HashSet, path::PathBuf};
+
+/// Counts the store reads performed while resolving an [AccountStatus]
+struct CountingStore<'a> {
+    store: &'a IndexerStore,
+    reads: Cell<u32>,
+}
+
+impl AccountStatusReads for CountingStore<'_> {
+    fn get_best_account_pending(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+    ) -> anyhow::Result<Option<bool>> {
+        self.reads.set(self.reads.get() + 1);
+        self.store.get_best_account_pending(pk, token)
+    }
+
+    fn is_best_account_zkapp(&self, pk: &PublicKey, token: &TokenAddress) -> anyhow::Result<bool> {
+        self.reads.set(self.reads.get() + 1);
+        self.store.is_best_account_zkapp(pk, token)
+    }
+}
+
+impl<'a> CountingStore<'a> {
+    fn new(store: &'a IndexerStore) -> Self {
+        Self {
+            store,
+            reads: Cell::new(0),
+        }
+    }
+
+    /// Resolve the account status, returning the number of reads
+    fn resolve(
+        &self,
+        pk: &PublicKey,
+        token: &TokenAddress,
+    ) -> anyhow::Result<(AccountStatus, u32)> {
+        self.reads.set(0);
+        let status = AccountStatus::resolve(self, pk, token)?;
+        Ok((status, self.reads.get()))
+    }
+}
+
+#[tokio::test]
+async fn bounded_reads() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("account-status-reads")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let counting = CountingStore::new(store);
+    let mina = TokenAddress::default();
+
+    // missing accounts take a single read
+    let missing = PublicKey::new("B62qkKXbv3bzzHWxW7zkoV8TYWyz7SV2PQgd6ySUk2xGhPk4pgJXiAm");
+    let (status, reads) = counting.resolve(&missing, &mina)?;
+    assert_eq!(status, AccountStatus::default());
+    assert!(status.will_charge_creation_fee());
+    assert_eq!(reads, 1);
+
+    // the largest genesis account
+    let (key, _) = store
+        .best_ledger_account_balance_iterator(speedb::IteratorMode::End)
+        .flatten()
+        .next()
+        .unwrap();
+    let (token, _, pk) = split_best_account_sort_key(&key).unwrap();
+
+    let (status, reads) = counting.resolve(&pk, &token)?;
+    assert_eq!(
+        status,
+        AccountStatus {
+            exists: true,
+            is_zkapp: false,
+            pending_only: false,
+        }
+    );
+    assert!(!status.will_charge_creation_fee());
+    assert!(reads <= 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn pending_accounts() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("account-status-pending")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+
+    // accounts created by blocks above the canonical root are pending
+    let canonical_root_length = state.canonical_root_block().blockchain_length;
+    let mut state_hash = state.best_tip_block().state_hash.clone();
+
+    while let Some(diff) = store.get_block_ledger_diff(&state_hash)? {
+        for (pk, tokens) in diff.new_pk_balances {
+            for token in tokens.into_keys() {
+                let status = AccountStatus::resolve(store.as_ref(), &pk, &token)?;
+                assert!(status.exists, "{pk}");
+                assert_eq!(
+                    status.pending_only,
+                    diff.blockchain_length > canonical_root_length,
+                    "{pk} (length {})",
+                    diff.blockchain_length
+                );
+            }
+        }
+
+        match store.get_block_parent_hash(&state_hash)? {
+            Some(parent_hash) => state_hash = parent_hash,
+            None => break,
+        }
+    }
+
+    // a best chain block creates a new account
+    let pk = PublicKey::new("B62qkKXbv3bzzHWxW7zkoV8TYWyz7SV2PQgd6ySUk2xGhPk4pgJXiAm");
+    let token = TokenAddress::default();
+    let payment = vec![AccountDiff::Payment(PaymentDiff {
+        update_type: UpdateType::Credit,
+        public_key: pk.clone(),
+        amount: Amount(2_000_000_000),
+        token: token.clone(),
+    })];
+    let new_accounts = HashSet::from([(pk.clone(), token.clone())]);
+    let best_tip: StateHash = state.best_tip_block().state_hash.clone();
+
+    store.update_best_accounts(
+        &best_tip,
+        DbAccountUpdate::new(vec![(payment.clone(), new_accounts.clone())], vec![]),
+    )?;
+    assert_eq!(
+        AccountStatus::resolve(store.as_ref(), &pk, &token)?,
+        AccountStatus {
+            exists: true,
+            is_zkapp: false,
+            pending_only: true,
+        }
+    );
+
+    // unapplying the block removes the account
+    store.update_best_accounts(
+        &best_tip,
+        DbAccountUpdate::new(vec![], vec![(payment, new_accounts)]),
+    )?;
+    assert_eq!(
+        AccountStatus::resolve(store.as_ref(), &pk, &token)?,
+        AccountStatus::default()
+    );
+
+    Ok(())
+}
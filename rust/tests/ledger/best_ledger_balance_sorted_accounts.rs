@@ -20,6 +20,7 @@ async fn check_best_accounts() -> anyhow::Result<()> {
         MAINNET_CANONICAL_THRESHOLD,
         false,
         BLOCK_REPORTING_FREQ_NUM,
+        true,
     )
     .await?;
 
@@ -76,7 +77,7 @@ async fn check_best_accounts() -> anyhow::Result<()> {
     }
 
     // check best ledger accounts equal sorted store best accounts
-    for (token, token_ledger) in state.best_ledger().tokens.iter() {
+    for (token, token_ledger) in state.best_ledger()?.tokens.iter() {
         for (pk, best_acct) in token_ledger.accounts.iter() {
             assert_eq!(*best_acct, store.get_best_account(pk, token)?.unwrap());
         }
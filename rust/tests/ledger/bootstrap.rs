@@ -0,0 +1,125 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{parser::BlockParser, store::BlockStore},
+    canonicity::store::CanonicityStore,
+    event::{
+        db::{DbCanonicityEvent, DbEvent},
+        store::EventStore,
+        IndexerEvent,
+    },
+    ledger::{bootstrap::Bootstrap, store::staged::StagedLedgerStore, Ledger},
+};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Copies the contiguous blocks with lengths in `lengths` to a new dir
+fn blocks_dir(
+    contiguous_dir: &Path,
+    lengths: std::ops::RangeInclusive<u32>,
+) -> anyhow::Result<TempDir> {
+    let dir = TempDir::with_prefix("bootstrap-blocks")?;
+    for entry in std::fs::read_dir(contiguous_dir)? {
+        let path = entry?.path();
+        let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
+        let length: u32 = file_name.split('-').nth(1).unwrap().parse()?;
+
+        if lengths.contains(&length) {
+            std::fs::copy(&path, dir.path().join(file_name))?;
+        }
+    }
+
+    Ok(dir)
+}
+
+/// Asserts the ledgers have the same accounts
+fn assert_ledgers_eq(ledger: &Ledger, expect: &Ledger) {
+    let num_accounts =
+        |ledger: &Ledger| -> usize { ledger.tokens.values().map(|t| t.accounts.len()).sum() };
+    assert_eq!(num_accounts(ledger), num_accounts(expect));
+
+    for (token, token_ledger) in expect.tokens.iter() {
+        for (pk, account) in token_ledger.accounts.iter() {
+            assert_eq!(ledger.get_account(pk, token), Some(account), "{pk} {token}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn bootstrap_mid_ingest() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("ledger-bootstrap")?;
+    let contiguous_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let store = state.indexer_store.clone().unwrap();
+
+    // ingest the first part of the chain
+    let dir = blocks_dir(&contiguous_dir, 2..=15)?;
+    let mut bp = BlockParser::new_testing(dir.path())?;
+    state.add_blocks(&mut bp).await?;
+
+    // bootstrap a consumer from the stream
+    let bootstrap = Bootstrap::new(&store, 50)?.expect("bootstrap");
+    let mut bytes = vec![];
+    bootstrap.write(&mut bytes)?;
+
+    let (manifest, mut ledger) = Bootstrap::read(bytes.as_slice())?;
+    assert_eq!(manifest, bootstrap.manifest);
+    assert!(manifest.num_chunks > 1);
+    assert_eq!(
+        bytes.iter().filter(|b| **b == b'\n').count() as u32,
+        manifest.num_chunks + 1
+    );
+
+    // the root is the latest canonical block
+    let root_height = manifest.root_height;
+    assert!(root_height > 1);
+    assert_eq!(
+        store.get_canonical_hash_at_height(root_height)?,
+        Some(manifest.root_state_hash.clone())
+    );
+    assert_eq!(store.get_canonical_hash_at_height(root_height + 1)?, None);
+    assert_ledgers_eq(
+        &ledger,
+        &store
+            .get_staged_ledger_at_state_hash(&manifest.root_state_hash, false)?
+            .unwrap(),
+    );
+
+    // ingest the rest of the chain
+    let dir = blocks_dir(&contiguous_dir, 16..=21)?;
+    let mut bp = BlockParser::new_testing(dir.path())?;
+    state.add_blocks(&mut bp).await?;
+
+    // follow the event log from the manifest's sequence number
+    let mut height = root_height;
+    for seq_num in manifest.next_seq_num..store.get_next_seq_num()? {
+        if let Some(IndexerEvent::Db(DbEvent::Canonicity(DbCanonicityEvent::NewCanonicalBlock {
+            state_hash,
+            blockchain_length,
+        }))) = store.get_event(seq_num)?
+        {
+            // no gap
+            assert_eq!(blockchain_length, height + 1, "{state_hash}");
+            height = blockchain_length;
+
+            let diff = store.get_block_ledger_diff(&state_hash)?.unwrap();
+            ledger._apply_diff(&diff)?;
+
+            assert_ledgers_eq(
+                &ledger,
+                &store
+                    .get_staged_ledger_at_state_hash(&state_hash, false)?
+                    .unwrap(),
+            );
+        }
+    }
+
+    // the consumer caught up with the new canonical root
+    assert!(height > root_height);
+    assert_eq!(store.get_canonical_hash_at_height(height + 1)?, None);
+
+    // a fresh bootstrap agrees with the consumer
+    let bootstrap = Bootstrap::new(&store, Bootstrap::DEFAULT_CHUNK_SIZE)?.unwrap();
+    assert_eq!(bootstrap.manifest.root_height, height);
+    Ok(())
+}
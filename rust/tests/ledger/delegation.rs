@@ -0,0 +1,136 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    base::{amount::Amount, nonce::Nonce, public_key::PublicKey, state_hash::StateHash},
+    block::parser::BlockParser,
+    ledger::{
+        diff::account::{AccountDiff, DelegationDiff, PaymentDiff, UpdateType},
+        store::best::{BestLedgerStore, DbAccountUpdate},
+        token::TokenAddress,
+    },
+    store::IndexerStore,
+    web::graphql::build_schema,
+};
+use serde_json::json;
+use std::{collections::HashSet, path::PathBuf, sync::Arc};
+
+const ACCOUNT_QUERY: &str = r#"
+query Account($pk: String!) {
+  accounts(query: { publicKey: $pk }) {
+    delegate
+    isSelfDelegated
+    delegationHistory
+  }
+}
+"#;
+
+fn delegation(delegator: &PublicKey, delegate: &PublicKey, nonce: u32) -> Vec<AccountDiff> {
+    vec![AccountDiff::Delegation(DelegationDiff {
+        nonce: Nonce(nonce),
+        delegator: delegator.clone(),
+        delegate: delegate.clone(),
+    })]
+}
+
+/// Account's delegate, whether it's self-delegated & its delegation history
+async fn delegation_state(
+    store: &Arc<IndexerStore>,
+    pk: &PublicKey,
+) -> anyhow::Result<(PublicKey, bool, Vec<PublicKey>)> {
+    let account = store
+        .get_best_account(pk, &TokenAddress::default())?
+        .unwrap();
+    let history = store.get_pk_delegations(pk)?;
+
+    // GraphQL agrees
+    let response = build_schema(store.clone())
+        .execute(
+            Request::new(ACCOUNT_QUERY)
+                .variables(Variables::from_json(json!({ "pk": pk.to_string() }))),
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json()?;
+    assert_eq!(
+        data["accounts"][0],
+        json!({
+            "delegate": account.delegate.to_string(),
+            "isSelfDelegated": account.is_self_delegated(),
+            "delegationHistory": history.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        })
+    );
+
+    let is_self_delegated = account.is_self_delegated();
+    Ok((account.delegate, is_self_delegated, history))
+}
+
+#[tokio::test]
+async fn self_delegation() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("best-ledger-delegation")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let best_tip: StateHash = state.best_tip_block().state_hash.clone();
+    let token = TokenAddress::default();
+
+    let pk = PublicKey::new("B62qkKXbv3bzzHWxW7zkoV8TYWyz7SV2PQgd6ySUk2xGhPk4pgJXiAm");
+    let other = PublicKey::new("B62qrdhG66vK71Jbdz6Xs7cnDxQ8f6jZUFvefkp3pje4EejYUTvotGP");
+
+    // never delegated
+    let payment = vec![AccountDiff::Payment(PaymentDiff {
+        update_type: UpdateType::Credit,
+        public_key: pk.clone(),
+        amount: Amount(2_000_000_000),
+        token: token.clone(),
+    })];
+    store.update_best_accounts(
+        &best_tip,
+        DbAccountUpdate::new(
+            vec![(payment, HashSet::from([(pk.clone(), token.clone())]))],
+            vec![],
+        ),
+    )?;
+    assert_eq!(
+        delegation_state(store, &pk).await?,
+        (pk.clone(), true, vec![])
+    );
+
+    // delegated away
+    let delegate_away = delegation(&pk, &other, 1);
+    store.update_best_accounts(
+        &best_tip,
+        DbAccountUpdate::new(vec![(delegate_away, HashSet::new())], vec![]),
+    )?;
+    assert_eq!(
+        delegation_state(store, &pk).await?,
+        (other.clone(), false, vec![other.clone()])
+    );
+
+    // re-self-delegated
+    let delegate_back = delegation(&pk, &pk, 2);
+    store.update_best_accounts(
+        &best_tip,
+        DbAccountUpdate::new(vec![(delegate_back.clone(), HashSet::new())], vec![]),
+    )?;
+    assert_eq!(
+        delegation_state(store, &pk).await?,
+        (pk.clone(), true, vec![other.clone(), pk.clone()])
+    );
+
+    // unapplying the re-self-delegation restores the previous delegate
+    store.update_best_accounts(
+        &best_tip,
+        DbAccountUpdate::new(vec![], vec![(delegate_back, HashSet::new())]),
+    )?;
+    assert_eq!(
+        delegation_state(store, &pk).await?,
+        (other.clone(), false, vec![other])
+    );
+
+    Ok(())
+}
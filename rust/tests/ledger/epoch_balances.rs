@@ -0,0 +1,176 @@
+use crate::helpers::store::*;
+use mina_indexer::{
+    base::{public_key::PublicKey, state_hash::StateHash},
+    block::store::BlockStore,
+    canonicity::store::CanonicityStore,
+    constants::{MAINNET_EPOCH_SLOT_COUNT, MAINNET_GENESIS_HASH},
+    ledger::{
+        store::best::{BalanceHistoryEntry, BestLedgerStore, EpochBalance},
+        token::TokenAddress,
+    },
+    store::{fixed_keys::FixedKeys, IndexerStore},
+};
+use speedb::WriteBatch;
+
+/// Blocks per non-empty epoch
+const EPOCH_BLOCKS: u32 = 8;
+
+/// Epochs of the synthetic chain, epoch 2 has no blocks
+const EPOCHS: [u32; 4] = [0, 1, 3, 4];
+
+fn state_hash(height: u32) -> StateHash {
+    format!("3N{height:0>50}").into()
+}
+
+/// Adds a synthetic canonical chain, returning each block's height & slot
+fn add_synthetic_chain(store: &IndexerStore) -> anyhow::Result<Vec<(u32, u32)>> {
+    let blocks: Vec<(u32, u32)> = EPOCHS
+        .iter()
+        .flat_map(|epoch| {
+            (0..EPOCH_BLOCKS).map(move |n| epoch * MAINNET_EPOCH_SLOT_COUNT + n * 900)
+        })
+        .enumerate()
+        .map(|(n, slot)| (n as u32 + 1, slot))
+        .collect();
+
+    let genesis_state_hash: StateHash = MAINNET_GENESIS_HASH.into();
+    let mut batch = WriteBatch::default();
+    for (height, slot) in blocks.iter() {
+        let state_hash = state_hash(*height);
+        store.set_block_height_batch(&state_hash, *height, &mut batch)?;
+        store.set_block_global_slot_batch(&state_hash, *slot, &mut batch)?;
+    }
+    store.database.write(batch)?;
+
+    for (height, slot) in blocks.iter() {
+        store.add_canonical_block(
+            *height,
+            *slot,
+            &state_hash(*height),
+            &genesis_state_hash,
+            None,
+        )?;
+    }
+
+    let (best_height, _) = blocks.last().unwrap();
+    store.database.put(
+        IndexerStore::BEST_TIP_STATE_HASH_KEY,
+        state_hash(*best_height).0.as_bytes(),
+    )?;
+    Ok(blocks)
+}
+
+/// Records the balance history & returns the brute-force balance at each
+/// height
+fn add_history(
+    store: &IndexerStore,
+    pk: &PublicKey,
+    best_height: u32,
+    changes: &[(u32, u64)],
+) -> anyhow::Result<Vec<Option<u64>>> {
+    let token = TokenAddress::default();
+    let mut before = None;
+    for (height, after) in changes {
+        let entry = BalanceHistoryEntry {
+            before,
+            after: *after,
+        };
+        store.set_best_account_balance_history(pk, &token, *height, &entry)?;
+        before = Some(*after);
+    }
+
+    Ok((0..=best_height)
+        .map(|height| {
+            changes
+                .iter()
+                .take_while(|(change_height, _)| *change_height <= height)
+                .last()
+                .map(|(_, balance)| *balance)
+        })
+        .collect())
+}
+
+#[test]
+fn epoch_boundary_balances() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("epoch-balances")?;
+    let store = IndexerStore::new(store_dir.path())?;
+    let token = TokenAddress::default();
+
+    let blocks = add_synthetic_chain(&store)?;
+    let best_height = blocks.len() as u32;
+
+    // changes at, before & after epoch boundaries
+    let pks: [PublicKey; 3] = [
+        "B62qrRvo5wngd5WA1dgXkQpCdQMRDndusmjfWXWT1LgsSFFdBS9RCsV".into(),
+        "B62qkPg6P2We1SZhCq84ZvDKknrWy8P3Moi99Baz8KFpYsMoFJKHHqF".into(),
+        "B62qjZkZgKV6tdxBmS9tF1SpyF5bJ2KRd7gnrGm8RWFz9X5GmnbGHTM".into(),
+    ];
+    let changes: [&[(u32, u64)]; 3] = [
+        &[(5, 100), (8, 150), (12, 90), (17, 300), (26, 250)],
+        &[(20, 1_000), (21, 999)],
+        &[],
+    ];
+
+    for (pk, changes) in pks.iter().zip(changes) {
+        let brute_force = add_history(&store, pk, best_height, changes)?;
+
+        // brute-force epoch boundaries
+        let expect: Vec<EpochBalance> = (0..=4)
+            .map(|epoch| {
+                let boundary = blocks
+                    .iter()
+                    .filter(|(_, slot)| slot / MAINNET_EPOCH_SLOT_COUNT == epoch)
+                    .max_by_key(|(_, slot)| *slot)
+                    .map(|(height, _)| (*height, state_hash(*height)));
+                let balance = boundary
+                    .as_ref()
+                    .and_then(|(height, _)| brute_force[*height as usize]);
+
+                EpochBalance {
+                    epoch,
+                    boundary,
+                    balance,
+                }
+            })
+            .collect();
+
+        // epochs past the best tip are omitted
+        let balances = store.get_best_account_epoch_balances(pk, &token, 0, 10)?;
+        assert_eq!(balances, expect, "{pk}");
+
+        // agrees with the per-height lookup
+        for epoch_balance in balances.iter() {
+            if let Some((height, _)) = epoch_balance.boundary {
+                assert_eq!(
+                    epoch_balance.balance,
+                    store.get_best_account_balance_at(pk, &token, height)?,
+                    "{pk} epoch {}",
+                    epoch_balance.epoch
+                );
+            }
+        }
+
+        // sub-range
+        assert_eq!(
+            store.get_best_account_epoch_balances(pk, &token, 1, 3)?,
+            expect[1..=3].to_vec(),
+            "{pk}"
+        );
+    }
+
+    // epoch 2 has no canonical blocks, so no boundary or balance
+    let balances = store.get_best_account_epoch_balances(&pks[0], &token, 0, 4)?;
+    assert_eq!(
+        balances.iter().map(|b| b.balance).collect::<Vec<_>>(),
+        vec![Some(150), Some(90), None, Some(300), Some(250)]
+    );
+
+    // the account didn't exist before its creation
+    let balances = store.get_best_account_epoch_balances(&pks[1], &token, 0, 4)?;
+    assert_eq!(
+        balances.iter().map(|b| b.balance).collect::<Vec<_>>(),
+        vec![None, None, None, Some(999), Some(999)]
+    );
+
+    Ok(())
+}
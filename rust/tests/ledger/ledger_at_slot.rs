@@ -0,0 +1,73 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::{public_key::PublicKey, state_hash::StateHash},
+    block::{parser::BlockParser, store::BlockStore},
+    canonicity::store::CanonicityStore,
+    ledger::{store::staged::StagedLedgerStore, token::TokenAddress},
+};
+use std::path::PathBuf;
+
+#[tokio::test]
+async fn ledger_at_slot_and_filtered() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("ledger-at-slot")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut block_parser = BlockParser::new_testing(&block_dir)?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    state.add_blocks(&mut block_parser).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+
+    // mainnet-7-3NLGcwFVQF1p1PrZpusw2fZwBe5HKXGtrGy1Vc4aPkeBtT8nMNUc.json
+    let height = 7;
+    let state_hash = StateHash("3NLGcwFVQF1p1PrZpusw2fZwBe5HKXGtrGy1Vc4aPkeBtT8nMNUc".into());
+    assert_eq!(
+        store.get_canonical_hash_at_height(height)?,
+        Some(state_hash.clone())
+    );
+
+    let global_slot = store.get_block_global_slot(&state_hash)?.unwrap();
+    let at_height = store
+        .get_staged_ledger_at_block_height(height, false)?
+        .unwrap();
+
+    // the block's slot & any later slot before the next canonical block
+    let next_slot = store
+        .get_block_global_slot(&store.get_canonical_hash_at_height(height + 1)?.unwrap())?
+        .unwrap();
+    for slot in global_slot..next_slot {
+        assert_eq!(
+            store.get_canonical_hash_at_or_before_slot(slot)?,
+            Some(state_hash.clone())
+        );
+        assert_eq!(
+            store.get_staged_ledger_at_slot(slot, false)?,
+            Some(at_height.clone())
+        );
+    }
+
+    // filtered ledger only contains the given accounts
+    let pk: PublicKey = at_height
+        .get_token_ledger(&TokenAddress::default())
+        .and_then(|ledger| ledger.accounts.keys().next().cloned())
+        .unwrap();
+    let mut filtered = at_height.clone();
+    filtered.retain_accounts(&[pk.clone()]);
+
+    let mina_ledger = filtered.get_token_ledger(&TokenAddress::default()).unwrap();
+    assert_eq!(mina_ledger.accounts.len(), 1);
+    assert_eq!(
+        mina_ledger.accounts.get(&pk),
+        at_height
+            .get_token_ledger(&TokenAddress::default())
+            .unwrap()
+            .accounts
+            .get(&pk)
+    );
+
+    // no accounts
+    filtered.retain_accounts(&[]);
+    assert!(filtered.tokens.is_empty());
+
+    Ok(())
+}
@@ -1,5 +1,20 @@
+mod account_status;
 mod best_ledger_balance_sorted_accounts;
+mod bootstrap;
+mod delegation;
+mod epoch_balances;
+mod ledger_at_slot;
+#[cfg(feature = "parallel_diffs")]
+mod parallel_diffs;
 mod staged_ledger_balance_sorted_accounts;
+mod staking_ledger_accounts;
 mod staking_ledger_balance_sorted_accounts;
+mod staking_ledger_batches;
+mod staking_ledger_delegators;
+mod staking_ledger_replace;
+mod staking_ledger_resume;
+mod token_holders;
 mod token_ledger;
+mod trace_accounts;
+mod verify_replay;
 mod zkapp_best_ledger_accounts;
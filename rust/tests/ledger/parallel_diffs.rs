@@ -0,0 +1,118 @@
+use crate::generators::TestGen;
+use mina_indexer::{
+    block::{
+        extract_block_height, extract_network, get_block_file_paths,
+        precomputed::{PcbVersion, PrecomputedBlock},
+    },
+    chain::Network,
+    constants::HARDFORK_GENESIS_BLOCKCHAIN_LENGTH,
+    ledger::{diff::LedgerDiff, Ledger},
+};
+use quickcheck::{QuickCheck, TestResult};
+use std::{
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+const NUM_WORKERS: usize = 4;
+
+fn block_version(path: &Path) -> PcbVersion {
+    if extract_network(path) == Network::Berkeley
+        || extract_block_height(path) >= HARDFORK_GENESIS_BLOCKCHAIN_LENGTH
+    {
+        PcbVersion::V2
+    } else {
+        PcbVersion::V1
+    }
+}
+
+#[test]
+fn parallel_equals_sequential() -> anyhow::Result<()> {
+    let mut num_blocks = 0;
+
+    for path in get_block_file_paths(Path::new("./tests/data"), true)? {
+        // skip intentionally malformed fixtures
+        let Ok(block) = PrecomputedBlock::parse_file(&path, block_version(&path)) else {
+            continue;
+        };
+        let diff = LedgerDiff::from_precomputed(&block);
+
+        let mut sequential = Ledger::new();
+        let sequential_anomalies = sequential._apply_diff(&diff)?;
+
+        let mut parallel = Ledger::new();
+        let parallel_anomalies = parallel._apply_diff_with_workers(&diff, NUM_WORKERS)?;
+
+        assert_eq!(parallel, sequential, "{path:#?}");
+        assert_eq!(parallel_anomalies, sequential_anomalies, "{path:#?}");
+
+        // applied on top of the previous result
+        let sequential_anomalies = sequential._apply_diff(&diff)?;
+        let parallel_anomalies = parallel._apply_diff_with_workers(&diff, NUM_WORKERS)?;
+
+        assert_eq!(parallel, sequential, "{path:#?}");
+        assert_eq!(parallel_anomalies, sequential_anomalies, "{path:#?}");
+
+        num_blocks += 1;
+    }
+
+    assert!(num_blocks > 100);
+    Ok(())
+}
+
+/// Applies the diffs sequentially & in parallel on top of each other
+fn parallel_equals_sequential_prop(diffs: Vec<TestGen<LedgerDiff>>, num_workers: u8) -> TestResult {
+    let num_workers = 1 + num_workers as usize % 8;
+    let mut sequential = Ledger::new();
+    let mut parallel = Ledger::new();
+
+    for TestGen(diff) in diffs {
+        let sequential_anomalies = sequential._apply_diff(&diff).unwrap();
+        let parallel_anomalies = parallel
+            ._apply_diff_with_workers(&diff, num_workers)
+            .unwrap();
+
+        if parallel_anomalies != sequential_anomalies || parallel != sequential {
+            return TestResult::error(format!("{num_workers} workers diverged on {diff:#?}"));
+        }
+    }
+
+    TestResult::passed()
+}
+
+#[test]
+fn arbitrary_parallel_equals_sequential() {
+    QuickCheck::new()
+        .tests(500)
+        .quickcheck(parallel_equals_sequential_prop as fn(_, _) -> TestResult);
+}
+
+#[test]
+#[ignore = "benchmark"]
+fn zkapp_block_benchmark() -> anyhow::Result<()> {
+    const NUM_ITERS: u32 = 20;
+
+    let path = PathBuf::from("./tests/data/misc_blocks/mainnet-397612-3NLh3tvZpMPXxUhCLz1898BDV6CwtExJqDWpzcZQebVCsZxghoXK.json");
+    let block = PrecomputedBlock::parse_file(&path, PcbVersion::V2)?;
+    let diff = LedgerDiff::from_precomputed(&block);
+    let num_diffs = diff.account_diffs.iter().map(Vec::len).sum::<usize>();
+
+    let time_apply = |apply: &dyn Fn(&mut Ledger) -> anyhow::Result<()>| -> anyhow::Result<f64> {
+        let start = Instant::now();
+        for _ in 0..NUM_ITERS {
+            apply(&mut Ledger::new())?;
+        }
+
+        Ok(start.elapsed().as_secs_f64())
+    };
+
+    let sequential = time_apply(&|ledger| ledger._apply_diff(&diff).map(|_| ()))?;
+    let parallel = time_apply(&|ledger| ledger._apply_diff_parallel(&diff).map(|_| ()))?;
+
+    println!(
+        "{num_diffs} account diffs x {NUM_ITERS}: sequential {sequential:.3}s, parallel {parallel:.3}s ({:.2}x)",
+        sequential / parallel
+    );
+
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::public_key::PublicKey,
+    constants::{MAINNET_GENESIS_HASH, MINA_SCALE, MINA_TOKEN_ADDRESS, MINA_TOKEN_ID},
+    ledger::{
+        account::Timing,
+        staking::{StakingAccount, StakingAccountJson, StakingLedger},
+        store::staking::StakingLedgerStore,
+        token::{TokenAddress, TokenId},
+    },
+};
+use std::path::PathBuf;
+
+const LEDGER_PATH: &str = "./tests/data/staking_ledgers/mainnet-0-jx7buQVWFLsXTtzRgSxbYcT8EYLS8KCZbLrfDcJxMtyy4thw2Ee.json";
+
+/// Timed account of the genesis staking ledger
+const TIMED_PK: &str = "B62qmqMrgPshhHKLJ7DqWn1KeizEgga5MuGmWb2bXajUnyivfeMW6JE";
+
+fn assert_timed_account(account: &StakingAccount) {
+    assert_eq!(account.token, TokenAddress::default());
+    assert_eq!(
+        account.receipt_chain_hash.0,
+        "2mzbV7WevxLuchs2dAMY4vQBS6XttnCUF8Hvks4XNBQ5qiSGGBQe"
+    );
+    assert_eq!(
+        account.voting_for.0,
+        "3NK2tkzqqK5spR2sZ7tujjqPksL45M3UUrcA4WhCkeiPtnugyE2x"
+    );
+    assert_eq!(
+        account.timing,
+        Some(Timing {
+            initial_minimum_balance: (372093 * MINA_SCALE).into(),
+            cliff_time: 86400u32.into(),
+            cliff_amount: (372093 * MINA_SCALE).into(),
+            vesting_period: 1u32.into(),
+            vesting_increment: 0u64.into(),
+        })
+    );
+}
+
+#[tokio::test]
+async fn staking_account_fields_round_trip() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("staking-ledger-accounts-db")?;
+    let ledgers_dir = PathBuf::from("./tests/data/staking_ledgers");
+    let pk = PublicKey::from(TIMED_PK);
+
+    let staking_ledger =
+        StakingLedger::parse_file(&PathBuf::from(LEDGER_PATH), MAINNET_GENESIS_HASH.into()).await?;
+    assert_timed_account(staking_ledger.staking_ledger.get(&pk).unwrap());
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    state
+        .add_startup_staking_ledgers_to_store(&ledgers_dir)
+        .await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let stored = store.get_staking_account(&pk, 0, None)?.unwrap();
+    assert_timed_account(&stored);
+    assert_eq!(stored, *staking_ledger.staking_ledger.get(&pk).unwrap());
+
+    Ok(())
+}
+
+#[test]
+fn staking_account_token_layouts() -> anyhow::Result<()> {
+    let mut json: serde_json::Value = serde_json::from_slice(&std::fs::read(LEDGER_PATH)?)?;
+    let mut account_json = json
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|account| account["pk"] == TIMED_PK)
+        .unwrap()
+        .take();
+
+    // pre-hardfork numeric token id
+    let account: StakingAccount =
+        serde_json::from_value::<StakingAccountJson>(account_json.clone())?.into();
+    assert_eq!(account.token.0, MINA_TOKEN_ADDRESS);
+
+    // post-hardfork token address
+    let custom_token = TokenAddress::from(TokenId(2));
+    account_json["token"] = custom_token.0.clone().into();
+    let account: StakingAccount =
+        serde_json::from_value::<StakingAccountJson>(account_json)?.into();
+    assert_eq!(account.token, custom_token);
+
+    // stored accounts round trip
+    let stored: StakingAccount = serde_json::from_slice(&serde_json::to_vec(&account)?)?;
+    assert_eq!(stored, account);
+
+    // accounts stored with numeric token ids
+    let mut legacy = serde_json::to_value(&account)?;
+    legacy["token"] = MINA_TOKEN_ID.into();
+    let stored: StakingAccount = serde_json::from_value(legacy.clone())?;
+    assert_eq!(stored.token, TokenAddress::default());
+
+    legacy["token"] = serde_json::Value::Null;
+    let stored: StakingAccount = serde_json::from_value(legacy)?;
+    assert_eq!(stored.token, TokenAddress::default());
+
+    Ok(())
+}
+
+#[test]
+fn staking_account_without_delegate() -> anyhow::Result<()> {
+    let mut json: serde_json::Value = serde_json::from_slice(&std::fs::read(LEDGER_PATH)?)?;
+    let mut account_json = json
+        .as_array_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|account| account["pk"] == TIMED_PK)
+        .unwrap()
+        .take();
+
+    // accounts without a delegate self-delegate
+    account_json.as_object_mut().unwrap().remove("delegate");
+    let account: StakingAccount =
+        serde_json::from_value::<StakingAccountJson>(account_json)?.into();
+    assert_eq!(account.delegate, PublicKey::new(TIMED_PK));
+    assert!(account.is_self_delegated());
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::state_hash::StateHash,
+    constants::MAINNET_GENESIS_HASH,
+    ledger::{staking::StakingLedger, store::staking::StakingLedgerStore},
+};
+use std::path::PathBuf;
+
+const LEDGER_PATH: &str = "./tests/data/staking_ledgers/mainnet-0-jx7buQVWFLsXTtzRgSxbYcT8EYLS8KCZbLrfDcJxMtyy4thw2Ee.json";
+
+#[tokio::test]
+async fn batched_staking_accounts() -> anyhow::Result<()> {
+    let path = PathBuf::from(LEDGER_PATH);
+    let genesis_state_hash: StateHash = MAINNET_GENESIS_HASH.into();
+
+    // single batch, uneven batches & one account per batch
+    for batch_size in [0, 7, 1] {
+        let store_dir = setup_new_db_dir(&format!("staking-ledger-batches-{batch_size}"))?;
+        let state = mainnet_genesis_state(store_dir.as_ref())?;
+        let store = state.indexer_store.as_ref().unwrap();
+        store.set_staking_ledger_batch_size(batch_size);
+
+        let staking_ledger = StakingLedger::parse_file(&path, genesis_state_hash.clone()).await?;
+        let ledger_hash = staking_ledger.ledger_hash.clone();
+        let num_accounts = staking_ledger.staking_ledger.len();
+        store.add_staking_ledger(staking_ledger, &genesis_state_hash)?;
+
+        // every account is written
+        let expected = StakingLedger::parse_file(&path, genesis_state_hash.clone()).await?;
+        let stored = store
+            .get_staking_ledger(&ledger_hash, Some(0), Some(&genesis_state_hash))?
+            .unwrap();
+        assert_eq!(stored.staking_ledger.len(), num_accounts);
+        assert_eq!(stored.staking_ledger, expected.staking_ledger);
+    }
+
+    Ok(())
+}
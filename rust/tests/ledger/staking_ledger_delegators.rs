@@ -0,0 +1,140 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::public_key::PublicKey,
+    constants::MAINNET_GENESIS_HASH,
+    ledger::{
+        staking::{EpochDelegator, StakingLedger},
+        store::staking::StakingLedgerStore,
+        LedgerHash,
+    },
+};
+use rust_decimal::Decimal;
+use speedb::Direction;
+use std::{collections::HashMap, path::PathBuf};
+
+const EPOCH_1_LEDGER_HASH: &str = "jxYFH645cwMMMDmDe7KnvTuKJ5Ev8zZbWtA73fDFn7Jyh8p6SwH";
+
+/// Asserts the delegators' balances are decreasing & their shares of the
+/// delegate's stake sum to ~100%
+fn assert_delegator_shares(delegators: &[EpochDelegator]) {
+    assert!(!delegators.is_empty());
+    assert!(delegators.windows(2).all(|w| w[0].balance >= w[1].balance));
+
+    let total_percentage: Decimal = delegators.iter().map(|d| d.stake_percentage()).sum();
+    assert!((total_percentage - Decimal::from(100)).abs() < Decimal::new(1, 6));
+}
+
+#[tokio::test]
+async fn delegators_with_stake_shares() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("staking-ledger-delegators-db")?;
+    let ledgers_dir = PathBuf::from("./tests/data/staking_ledgers");
+    let ledger_path =
+        ledgers_dir.join("mainnet-0-jx7buQVWFLsXTtzRgSxbYcT8EYLS8KCZbLrfDcJxMtyy4thw2Ee.json");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    state
+        .add_startup_staking_ledgers_to_store(&ledgers_dir)
+        .await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let genesis_state_hash = MAINNET_GENESIS_HASH.into();
+
+    // adjacent epoch 1 staking ledger, re-delegates some of the epoch 0
+    // delegate's delegators
+    let mut staking_ledger =
+        StakingLedger::parse_file(&ledger_path, MAINNET_GENESIS_HASH.into()).await?;
+    let mut delegators: HashMap<PublicKey, Vec<PublicKey>> = HashMap::new();
+    for (pk, account) in staking_ledger.staking_ledger.iter() {
+        delegators
+            .entry(account.delegate.clone())
+            .or_default()
+            .push(pk.clone());
+    }
+
+    // self-delegated delegates sorted by number of delegators
+    let mut delegates: Vec<_> = delegators
+        .iter()
+        .filter(|(delegate, _)| {
+            staking_ledger
+                .staking_ledger
+                .get(delegate)
+                .is_some_and(|account| account.delegate == **delegate)
+        })
+        .collect();
+    delegates.sort_by(|(pk0, d0), (pk1, d1)| d1.len().cmp(&d0.len()).then(pk0.cmp(pk1)));
+
+    let delegate = delegates[0].0.clone();
+    let new_delegate = delegates[1].0.clone();
+
+    // redelegated accounts are not delegated to
+    let mut moved: Vec<_> = delegators[&delegate]
+        .iter()
+        .filter(|pk| **pk != delegate && !delegators.contains_key(*pk))
+        .cloned()
+        .collect();
+    moved.sort();
+    moved.truncate(2);
+    assert_eq!(moved.len(), 2);
+
+    for pk in moved.iter() {
+        staking_ledger.staking_ledger.get_mut(pk).unwrap().delegate = new_delegate.clone();
+    }
+
+    staking_ledger.epoch = 1;
+    staking_ledger.ledger_hash = LedgerHash::new(EPOCH_1_LEDGER_HASH)?;
+    store.add_staking_ledger(staking_ledger, &genesis_state_hash)?;
+
+    // epoch 0 delegators, no previous ledger
+    let epoch_0 =
+        store.get_epoch_delegators(&delegate, 0, None, None, Direction::Reverse, usize::MAX)?;
+    assert_delegator_shares(&epoch_0);
+    assert_eq!(epoch_0.len(), delegators[&delegate].len());
+    assert!(epoch_0
+        .iter()
+        .all(|d| !d.delegation_changed && d.prev_delegate.is_none()));
+
+    // epoch 1 delegators, unchanged delegations
+    let epoch_1 =
+        store.get_epoch_delegators(&delegate, 1, None, None, Direction::Reverse, usize::MAX)?;
+    assert_delegator_shares(&epoch_1);
+    assert_eq!(epoch_1.len(), epoch_0.len() - moved.len());
+    assert!(epoch_1
+        .iter()
+        .all(|d| !d.delegation_changed && d.prev_delegate.as_ref() == Some(&delegate)));
+
+    // epoch 1 new delegate's delegators, only the moved delegations changed
+    let new_epoch_1 =
+        store.get_epoch_delegators(&new_delegate, 1, None, None, Direction::Reverse, usize::MAX)?;
+    assert_delegator_shares(&new_epoch_1);
+    assert_eq!(
+        new_epoch_1.len(),
+        delegators[&new_delegate].len() + moved.len()
+    );
+
+    for delegator in new_epoch_1 {
+        let was_moved = moved.contains(&delegator.pk);
+        assert_eq!(delegator.delegation_changed, was_moved);
+        if was_moved {
+            assert_eq!(delegator.prev_delegate, Some(delegate.clone()));
+        }
+    }
+
+    // pagination & ascending sort
+    let limited = store.get_epoch_delegators(&delegate, 1, None, None, Direction::Reverse, 2)?;
+    assert_eq!(limited, epoch_1[..2]);
+
+    let next_page = store.get_epoch_delegators(
+        &delegate,
+        1,
+        None,
+        Some(&limited[1].pk),
+        Direction::Reverse,
+        2,
+    )?;
+    assert_eq!(next_page, epoch_1[2..4]);
+
+    let ascending =
+        store.get_epoch_delegators(&delegate, 1, None, None, Direction::Forward, usize::MAX)?;
+    assert_eq!(ascending, epoch_1.into_iter().rev().collect::<Vec<_>>());
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::public_key::PublicKey,
+    constants::MAINNET_GENESIS_HASH,
+    event::{db::*, store::EventStore, IndexerEvent},
+    ledger::{
+        staking::StakingLedger,
+        store::staking::{StakingLedgerFileStatus, StakingLedgerStore},
+    },
+    state::IndexerState,
+};
+use std::{fs, path::PathBuf};
+
+const LEDGER_FILE_NAME: &str = "mainnet-0-jx7buQVWFLsXTtzRgSxbYcT8EYLS8KCZbLrfDcJxMtyy4thw2Ee.json";
+
+/// New balance of the corrected account (nanomina)
+const CORRECTED_BALANCE: u64 = 1_000_000_000_000;
+
+fn num_replace_events(events: &[IndexerEvent]) -> usize {
+    events
+        .iter()
+        .filter(|event| {
+            matches!(
+                event,
+                IndexerEvent::Db(DbEvent::StakingLedger(
+                    DbStakingLedgerEvent::ReplaceStakingLedger { .. }
+                ))
+            )
+        })
+        .count()
+}
+
+#[tokio::test]
+async fn replace_changed_staking_ledger() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("staking-ledger-replace-db")?;
+    let ledgers_dir = tempfile::TempDir::with_prefix("staking-ledger-replace")?;
+    let ledger_path = ledgers_dir.path().join(LEDGER_FILE_NAME);
+    fs::copy(
+        PathBuf::from("./tests/data/staking_ledgers").join(LEDGER_FILE_NAME),
+        &ledger_path,
+    )?;
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let store = state.indexer_store.clone().unwrap();
+    let genesis_state_hash = MAINNET_GENESIS_HASH.into();
+
+    state
+        .add_startup_staking_ledgers_to_store(ledgers_dir.path())
+        .await?;
+
+    let original = StakingLedger::parse_file(&ledger_path, MAINNET_GENESIS_HASH.into()).await?;
+    assert_eq!(
+        IndexerState::staking_ledger_file_status(&store, &ledger_path, &genesis_state_hash)?,
+        StakingLedgerFileStatus::Unchanged
+    );
+
+    // a new epoch's file isn't content hashed
+    assert!(store
+        .get_staking_ledger_content_hash(0, &genesis_state_hash)?
+        .is_none());
+
+    // re-ingesting the same file is a no-op
+    let num_events = store.get_event_log()?.len();
+    state
+        .add_startup_staking_ledgers_to_store(ledgers_dir.path())
+        .await?;
+    assert_eq!(store.get_event_log()?.len(), num_events);
+
+    // correct a delegated account's delegate & balance
+    let mut delegates: Vec<&PublicKey> = original
+        .staking_ledger
+        .iter()
+        .filter(|(pk, account)| account.delegate == **pk)
+        .map(|(pk, _)| pk)
+        .collect();
+    delegates.sort();
+
+    let (pk, account) = original
+        .staking_ledger
+        .iter()
+        .filter(|(pk, account)| {
+            account.delegate != **pk && original.staking_ledger.contains_key(&account.delegate)
+        })
+        .min_by_key(|(pk, _)| (*pk).clone())
+        .unwrap();
+    let old_delegate = account.delegate.clone();
+    let new_delegate = delegates
+        .into_iter()
+        .find(|delegate| **delegate != old_delegate)
+        .unwrap()
+        .clone();
+
+    let mut accounts: Vec<serde_json::Value> = serde_json::from_slice(&fs::read(&ledger_path)?)?;
+    for value in accounts.iter_mut() {
+        if value["pk"] == pk.0 {
+            value["delegate"] = new_delegate.0.clone().into();
+            value["balance"] = "1000".into();
+        }
+    }
+    fs::write(&ledger_path, serde_json::to_vec(&accounts)?)?;
+
+    let corrected = StakingLedger::parse_file(&ledger_path, MAINNET_GENESIS_HASH.into()).await?;
+    let diff = original.diff(&corrected);
+    assert!(!diff.is_empty());
+    assert!(diff.accounts_added.is_empty() && diff.accounts_removed.is_empty());
+    assert_eq!(diff.accounts_changed, 1);
+    assert_eq!(diff.balance_changes, 1);
+    assert_eq!(diff.delegate_changes, 1);
+    assert_eq!(
+        diff.total_currency_delta,
+        CORRECTED_BALANCE as i128 - account.balance as i128
+    );
+
+    let old_delegate_total = store
+        .get_epoch_delegations(&old_delegate, 0, None)?
+        .and_then(|delegation| delegation.total_delegated)
+        .unwrap();
+    let new_delegate_total = store
+        .get_epoch_delegations(&new_delegate, 0, None)?
+        .and_then(|delegation| delegation.total_delegated)
+        .unwrap();
+
+    // changed ledgers aren't replaced by default
+    state
+        .add_startup_staking_ledgers_to_store(ledgers_dir.path())
+        .await?;
+    assert_eq!(
+        store.get_staking_account(pk, 0, None)?.unwrap().delegate,
+        old_delegate
+    );
+    assert_eq!(
+        IndexerState::staking_ledger_file_status(&store, &ledger_path, &genesis_state_hash)?,
+        StakingLedgerFileStatus::Changed {
+            content_hash: StakingLedger::content_hash(&ledger_path)?
+        }
+    );
+    assert_eq!(num_replace_events(&store.get_event_log()?), 0);
+
+    // replace the changed ledger
+    state.replace_staking_ledger = true;
+    state
+        .add_startup_staking_ledgers_to_store(ledgers_dir.path())
+        .await?;
+
+    let stored = store.get_staking_account(pk, 0, None)?.unwrap();
+    assert_eq!(stored.delegate, new_delegate);
+    assert_eq!(stored.balance, CORRECTED_BALANCE);
+
+    let rebuilt = store
+        .build_staking_ledger(0, Some(&genesis_state_hash))?
+        .unwrap();
+    assert_eq!(rebuilt.staking_ledger, corrected.staking_ledger);
+    assert_eq!(rebuilt.total_currency, corrected.total_currency);
+    assert_eq!(
+        store.get_staking_ledger_accounts_count_epoch(0, &genesis_state_hash)?,
+        corrected.staking_ledger.len() as u32
+    );
+    assert_eq!(
+        IndexerState::staking_ledger_file_status(&store, &ledger_path, &genesis_state_hash)?,
+        StakingLedgerFileStatus::Unchanged
+    );
+    assert_eq!(
+        store.get_staking_ledger_content_hash(0, &genesis_state_hash)?,
+        Some(StakingLedger::content_hash(&ledger_path)?)
+    );
+
+    // persisted aggregation is recomputed
+    let aggregated = store
+        .build_aggregated_delegations(0, Some(&genesis_state_hash))?
+        .unwrap();
+    let expected = corrected.aggregate_delegations()?;
+    assert_eq!(aggregated.delegations, expected.delegations);
+    assert_eq!(aggregated.total_delegations, expected.total_delegations);
+    assert_eq!(
+        store
+            .get_epoch_delegations(&old_delegate, 0, None)?
+            .and_then(|delegation| delegation.total_delegated),
+        Some(old_delegate_total - account.balance)
+    );
+    assert_eq!(
+        store
+            .get_epoch_delegations(&new_delegate, 0, None)?
+            .and_then(|delegation| delegation.total_delegated),
+        Some(new_delegate_total + CORRECTED_BALANCE)
+    );
+
+    // the old delegate's delegators no longer include the account
+    let delegators = store.get_epoch_delegators(
+        &old_delegate,
+        0,
+        None,
+        None,
+        speedb::Direction::Reverse,
+        usize::MAX,
+    )?;
+    assert!(delegators.iter().all(|delegator| delegator.pk != *pk));
+
+    // replacement is logged
+    assert_eq!(num_replace_events(&store.get_event_log()?), 1);
+
+    Ok(())
+}
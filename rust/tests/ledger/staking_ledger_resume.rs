@@ -0,0 +1,144 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::state_hash::StateHash,
+    constants::{HARDFORK_GENESIS_HASH, MAINNET_GENESIS_HASH},
+    ledger::{
+        staking::StakingLedger,
+        store::staking::{
+            StakingAccountWithEpochDelegation, StakingLedgerCheckpoint, StakingLedgerStore,
+        },
+    },
+    store::column_families::ColumnFamilyHelpers,
+    utility::store::ledger::staking::staking_ledger_epoch_key_prefix,
+};
+use speedb::WriteBatch;
+use std::path::PathBuf;
+
+const LEDGERS_DIR: &str = "./tests/data/staking_ledgers";
+const LEDGER_FILE: &str = "mainnet-0-jx7buQVWFLsXTtzRgSxbYcT8EYLS8KCZbLrfDcJxMtyy4thw2Ee.json";
+
+/// Number of accounts written before the interruption
+const NUM_INGESTED: usize = 100;
+
+#[tokio::test]
+async fn resume_interrupted_ingestion() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("staking-ledger-resume")?;
+    let ledgers_dir = PathBuf::from(LEDGERS_DIR);
+    let ledger_path = ledgers_dir.join(LEDGER_FILE);
+    let genesis_state_hash: StateHash = MAINNET_GENESIS_HASH.into();
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let store = state.indexer_store.clone().unwrap();
+
+    // the ingestion was interrupted after the first accounts in public key
+    // order, the partial ledger is already indexed by epoch
+    let staking_ledger =
+        StakingLedger::parse_file(&ledger_path, genesis_state_hash.clone()).await?;
+    let ledger_hash = staking_ledger.ledger_hash.clone();
+    let aggregated_delegations = staking_ledger.aggregate_delegations()?;
+    store.set_staking_ledger_hash_epoch_pair(&ledger_hash, 0, Some(&genesis_state_hash))?;
+    store.set_staking_ledger_hash_genesis_pair(&ledger_hash, &genesis_state_hash)?;
+    store.set_total_currency(&ledger_hash, staking_ledger.total_currency)?;
+
+    let mut accounts: Vec<_> = staking_ledger.staking_ledger.into_iter().collect();
+    accounts.sort_by(|(pk0, _), (pk1, _)| pk0.cmp(pk1));
+
+    let mut batch = WriteBatch::default();
+    for (pk, account) in accounts.into_iter().take(NUM_INGESTED) {
+        let delegation = aggregated_delegations
+            .delegations
+            .get(&pk)
+            .cloned()
+            .unwrap();
+        store.set_staking_account_batch(
+            &pk,
+            0,
+            &ledger_hash,
+            &genesis_state_hash,
+            &StakingAccountWithEpochDelegation {
+                account,
+                delegation,
+            },
+            &mut batch,
+        )?;
+    }
+
+    let checkpoint = StakingLedgerCheckpoint {
+        ledger_hash: ledger_hash.clone(),
+        num_accounts: NUM_INGESTED as u32,
+    };
+    batch.put_cf(
+        store.staking_ledger_checkpoints_cf(),
+        staking_ledger_epoch_key_prefix(&genesis_state_hash, 0),
+        serde_json::to_vec(&checkpoint)?,
+    );
+    store.database.write(batch)?;
+
+    assert_eq!(
+        store.get_staking_ledger_checkpoint(0, &genesis_state_hash)?,
+        Some(checkpoint)
+    );
+    assert_eq!(
+        store.get_last_staking_ledger_epoch(&genesis_state_hash)?,
+        None
+    );
+
+    // skipped before the override epoch
+    state
+        .add_startup_staking_ledgers_to_store_from(&ledgers_dir, Some(1))
+        .await?;
+    assert!(store
+        .get_staking_ledger_checkpoint(0, &genesis_state_hash)?
+        .is_some());
+
+    // resumed, rather than skipped as changed
+    state
+        .add_startup_staking_ledgers_to_store(&ledgers_dir)
+        .await?;
+    assert_eq!(
+        store.get_staking_ledger_checkpoint(0, &genesis_state_hash)?,
+        None
+    );
+    assert_eq!(
+        store.get_last_staking_ledger_epoch(&genesis_state_hash)?,
+        Some(0)
+    );
+
+    let expected = StakingLedger::parse_file(&ledger_path, genesis_state_hash.clone()).await?;
+    let stored = store
+        .get_staking_ledger(&ledger_hash, Some(0), Some(&genesis_state_hash))?
+        .unwrap();
+    assert_eq!(stored.staking_ledger, expected.staking_ledger);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn complete_ingestion_removes_checkpoint() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("staking-ledger-checkpoints")?;
+    let genesis_state_hash: StateHash = MAINNET_GENESIS_HASH.into();
+    let state = mainnet_genesis_state(store_dir.as_ref())?;
+    let store = state.indexer_store.as_ref().unwrap();
+    store.set_staking_ledger_batch_size(NUM_INGESTED);
+
+    let path = PathBuf::from(LEDGERS_DIR).join(LEDGER_FILE);
+    let staking_ledger = StakingLedger::parse_file(&path, genesis_state_hash.clone()).await?;
+    store.add_staking_ledger(staking_ledger, &genesis_state_hash)?;
+
+    // the complete ledger's batches' checkpoint is removed & other eras are
+    // unaffected
+    assert_eq!(
+        store.get_staking_ledger_checkpoint(0, &genesis_state_hash)?,
+        None
+    );
+    assert_eq!(
+        store.get_last_staking_ledger_epoch(&genesis_state_hash)?,
+        Some(0)
+    );
+    assert_eq!(
+        store.get_last_staking_ledger_epoch(&HARDFORK_GENESIS_HASH.into())?,
+        None
+    );
+
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::public_key::PublicKey,
+    block::{parser::BlockParser, store::BlockStore},
+    constants::HARDFORK_GENESIS_BLOCKCHAIN_LENGTH,
+    ledger::{
+        store::{best::BestLedgerStore, staged::StagedLedgerStore},
+        token::TokenAddress,
+    },
+};
+use std::{path::PathBuf, str::FromStr};
+
+#[tokio::test]
+async fn historical_snapshots() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("token-holders")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let token = TokenAddress::default();
+    let best_height = store.get_best_block_height()?.unwrap();
+
+    for height in [1, 5, 10, best_height] {
+        let ledger = store
+            .get_staged_ledger_at_block_height(height, false)?
+            .unwrap();
+
+        // holders match the staged ledger at the height
+        let mut expect: Vec<(PublicKey, u64)> = ledger
+            .get_token_ledger(&token)
+            .unwrap()
+            .accounts
+            .iter()
+            .map(|(pk, account)| (pk.clone(), account.balance.0))
+            .collect();
+        expect.sort();
+
+        let holders = store.get_token_holders(&token, Some(height), 0, None, usize::MAX)?;
+        assert_eq!(holders, expect, "height {height}");
+
+        // min balance filter
+        let min_balance = expect[expect.len() / 2].1;
+        assert_eq!(
+            store.get_token_holders(&token, Some(height), min_balance, None, usize::MAX)?,
+            expect
+                .iter()
+                .filter(|(_, balance)| *balance >= min_balance)
+                .cloned()
+                .collect::<Vec<_>>(),
+            "height {height}"
+        );
+
+        // cursor pagination covers the same holders
+        let mut paginated = vec![];
+        let mut cursor = None;
+        loop {
+            let page = store.get_token_holders(&token, Some(height), 0, cursor.as_ref(), 7)?;
+            cursor = page.last().map(|(pk, _)| pk.clone());
+            paginated.extend(page);
+
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(paginated, expect, "height {height}");
+    }
+
+    // current holders match the best tip snapshot
+    assert_eq!(
+        store.get_token_holders(&token, None, 0, None, usize::MAX)?,
+        store.get_token_holders(&token, Some(best_height), 0, None, usize::MAX)?
+    );
+
+    Ok(())
+}
+
+#[ignore = "only tested in tier 1 via cargo nextest --run-ignored all"]
+#[tokio::test]
+async fn zkapp_token_holders() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("zkapp-token-holders")?;
+    let blocks_dir = &PathBuf::from("./tests/data/hardfork");
+
+    let mut state = hardfork_genesis_state(store_dir.path())?;
+    let mut bp = BlockParser::new_testing(blocks_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let best_height = store.get_best_block_height()?.unwrap();
+
+    let pk: PublicKey = "B62qkPg6P2We1SZhCq84ZvDKknrWy8P3Moi99Baz8KFpYsMoFJKHHqF".into();
+    let minu_token = TokenAddress::from_str("wfG3GivPMttpt6nQnPuX9eDPnoyA5RJZY23LTc4kkNkCRH2gUd")?;
+    let minu_holders = vec![(pk.clone(), 100000000000000)];
+
+    // no MINU holders before the token is created
+    assert!(store
+        .get_token_holders(
+            &minu_token,
+            Some(HARDFORK_GENESIS_BLOCKCHAIN_LENGTH),
+            0,
+            None,
+            usize::MAX
+        )?
+        .is_empty());
+    assert_eq!(
+        store.get_best_account_balance_at(&pk, &minu_token, HARDFORK_GENESIS_BLOCKCHAIN_LENGTH)?,
+        None
+    );
+
+    // once created, MINU is held at every later height
+    let mut created = false;
+    for height in HARDFORK_GENESIS_BLOCKCHAIN_LENGTH..=best_height {
+        let holders = store.get_token_holders(&minu_token, Some(height), 0, None, usize::MAX)?;
+        if created {
+            assert_eq!(holders, minu_holders, "height {height}");
+        } else if !holders.is_empty() {
+            assert_eq!(holders, minu_holders, "height {height}");
+            created = true;
+        }
+    }
+    assert!(created);
+
+    // current MINU holders
+    assert_eq!(
+        store.get_token_holders(&minu_token, None, 0, None, usize::MAX)?,
+        minu_holders
+    );
+
+    // min balance above the holder's balance
+    assert!(store
+        .get_token_holders(&minu_token, None, 100000000000001, None, usize::MAX)?
+        .is_empty());
+
+    Ok(())
+}
+
+#[ignore = "only tested in tier 1 via cargo nextest --run-ignored all"]
+#[tokio::test]
+async fn zkapp_token_transfers() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("zkapp-token-transfers")?;
+    let blocks_dir = &PathBuf::from("./tests/data/hardfork");
+
+    let mut state = hardfork_genesis_state(store_dir.path())?;
+    let mut bp = BlockParser::new_testing(blocks_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let pk: PublicKey = "B62qkPg6P2We1SZhCq84ZvDKknrWy8P3Moi99Baz8KFpYsMoFJKHHqF".into();
+    let minu_token = TokenAddress::from_str("wfG3GivPMttpt6nQnPuX9eDPnoyA5RJZY23LTc4kkNkCRH2gUd")?;
+
+    // MINU transfers match the holder's balance history
+    let transfers = store.get_token_transfers(&minu_token, false, usize::MAX)?;
+    assert!(!transfers.is_empty());
+    assert!(transfers
+        .windows(2)
+        .all(|pair| (pair[0].0, &pair[0].1) <= (pair[1].0, &pair[1].1)));
+
+    let history =
+        store.get_best_account_balance_history_range(&pk, &minu_token, 0, u32::MAX, usize::MAX)?;
+    assert_eq!(
+        transfers
+            .iter()
+            .filter(|(_, transfer_pk, _)| *transfer_pk == pk)
+            .map(|(height, _, entry)| (*height, *entry))
+            .collect::<Vec<_>>(),
+        history
+    );
+    assert_eq!(transfers.last().unwrap().2.after, 100000000000000);
+
+    // descending is the reverse & limited
+    let mut descending = store.get_token_transfers(&minu_token, true, usize::MAX)?;
+    descending.reverse();
+    assert_eq!(descending, transfers);
+    assert_eq!(store.get_token_transfers(&minu_token, true, 1)?.len(), 1);
+
+    // MINA transfers aren't recorded
+    assert!(store
+        .get_token_transfers(&TokenAddress::default(), false, usize::MAX)?
+        .is_empty());
+
+    Ok(())
+}
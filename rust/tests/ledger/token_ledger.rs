@@ -26,7 +26,7 @@ async fn check_token_accounts() -> anyhow::Result<()> {
 
     println!("{}", state);
 
-    let best_ledger = state.best_ledger();
+    let best_ledger = state.best_ledger()?;
     let pk = "B62qkPg6P2We1SZhCq84ZvDKknrWy8P3Moi99Baz8KFpYsMoFJKHHqF".into();
     let minu_token = TokenAddress::from_str("wfG3GivPMttpt6nQnPuX9eDPnoyA5RJZY23LTc4kkNkCRH2gUd")?;
     let mina_token = TokenAddress::default();
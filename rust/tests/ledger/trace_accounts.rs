@@ -0,0 +1,63 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::public_key::PublicKey,
+    block::precomputed::{PcbVersion, PrecomputedBlock},
+    ledger::{diff::LedgerDiff, token::TokenAddress, trace::TracedAccounts},
+};
+use std::path::PathBuf;
+
+const BLOCK_FILE: &str =
+    "./tests/data/sequential_blocks/mainnet-105500-3NKvv2iBAPhZ8SRCxQEuGTgqTYuFXd2WVANXW6pcsR8pdzLuUj7C.json";
+
+/// Public key which is not involved in [BLOCK_FILE]
+const UNTRACED_PK: &str = "B62qrRvo5wngd5WA1dgXkQpCdQMRDndusmjfWXWT1LgsSFFdBS9RCsV";
+
+#[test]
+fn parse() -> anyhow::Result<()> {
+    let pk = UNTRACED_PK;
+
+    assert_eq!(TracedAccounts::new::<&str>(&[])?, None);
+    assert_eq!(TracedAccounts::new(&["", " "])?, None);
+    assert!(TracedAccounts::new(&[" B62qinvalid"]).is_err());
+
+    let traced = TracedAccounts::new(&[pk, pk])?.expect("traced accounts");
+    assert!(traced.contains(&PublicKey::from(pk)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn traced_account_diffs() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("trace-accounts")?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+
+    let block = PrecomputedBlock::parse_file(&PathBuf::from(BLOCK_FILE), PcbVersion::V1)?;
+    let diff = LedgerDiff::from_precomputed(&block);
+
+    // the coinbase receiver is active in every block
+    let pk = block.coinbase_receiver();
+    let traced = TracedAccounts::new(&[pk.to_string()])?.expect("traced accounts");
+    state.trace_accounts = Some(traced.clone());
+    state.apply_canonical_diff(&diff)?;
+
+    let balance = state
+        .ledger
+        .get_account(&pk, &TokenAddress::default())
+        .expect("coinbase receiver account")
+        .balance
+        .0;
+    let traces = traced.traces(&diff, &state.ledger);
+
+    assert!(!traces.is_empty());
+    assert!(traces.iter().any(|trace| trace.contains("diff=coinbase")));
+    for trace in traces {
+        assert!(trace.contains(&format!("pk={pk} ")), "{trace}");
+        assert!(trace.contains(&format!("height={} ", block.blockchain_length())));
+        assert!(trace.contains(&format!("state_hash={} ", block.state_hash())));
+        assert!(trace.contains(&format!("balance={balance} ")), "{trace}");
+    }
+
+    // accounts not in the block are not logged
+    let untraced = TracedAccounts::new(&[UNTRACED_PK])?.expect("traced accounts");
+    assert!(untraced.traces(&diff, &state.ledger).is_empty());
+    Ok(())
+}
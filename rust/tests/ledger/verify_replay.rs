@@ -0,0 +1,66 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::parser::BlockParser,
+    canonicity::store::CanonicityStore,
+    constants::MAINNET_GENESIS_LEDGER_HASH,
+    ledger::{
+        diff::LedgerDiff, store::staged::StagedLedgerStore, verify::ReplayMismatch, LedgerHash,
+    },
+};
+use speedb::WriteBatch;
+use std::path::PathBuf;
+
+#[tokio::test]
+async fn detects_checkpoint_mismatches() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("verify-replay")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut block_parser = BlockParser::new_testing(&block_dir)?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    state.add_blocks(&mut block_parser).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let ledger_cadence = 5;
+
+    // consistent store
+    let report = store.verify_replay(1, ledger_cadence)?;
+    assert!(report.mismatches.is_empty(), "{:?}", report.mismatches);
+    assert!(report.num_blocks >= 2 * ledger_cadence);
+    assert!(report.num_checkpoints >= 2);
+
+    // invalid cadence
+    assert!(store.verify_replay(1, 0).is_err());
+
+    // corrupt the stored ledger diff below the first checkpoint
+    let state_hash = store.get_canonical_hash_at_height(3)?.unwrap();
+    let mut batch = WriteBatch::default();
+    store.set_block_ledger_diff_batch(&state_hash, &LedgerDiff::default(), &mut batch)?;
+
+    // corrupt the second checkpoint's staged ledger hash
+    let checkpoint_hash = store
+        .get_canonical_hash_at_height(2 * ledger_cadence)?
+        .unwrap();
+    let genesis_ledger_hash = LedgerHash::new_or_panic(MAINNET_GENESIS_LEDGER_HASH.to_string());
+    store.set_block_staged_ledger_hash_batch(&checkpoint_hash, &genesis_ledger_hash, &mut batch)?;
+    store.database.write(batch)?;
+
+    let report = store.verify_replay(1, ledger_cadence)?;
+    assert!(report.mismatches.iter().any(|mismatch| matches!(
+        mismatch,
+        ReplayMismatch::LedgerMismatch { height, accounts, .. }
+            if *height == ledger_cadence && !accounts.is_empty()
+    )));
+    assert!(report.mismatches.iter().any(|mismatch| matches!(
+        mismatch,
+        ReplayMismatch::LedgerHashMismatch { height, stored, .. }
+            if *height == 2 * ledger_cadence && stored.as_ref() == Some(&genesis_ledger_hash)
+    )));
+
+    // diffs recomputed from the blocks still apply
+    assert!(report
+        .mismatches
+        .iter()
+        .all(|mismatch| !matches!(mismatch, ReplayMismatch::ApplyFailed { .. })));
+
+    Ok(())
+}
@@ -6,12 +6,17 @@ mod block;
 mod canonicity;
 mod command;
 mod event;
+mod graphql;
+mod integration;
 mod ledger;
 #[cfg(all(test, feature = "mina_rs"))]
 mod protocol;
 mod snark_work;
 mod state;
+mod store;
 mod usernames;
+#[cfg(feature = "grpc")]
+mod web;
 mod zkapps;
 
 //////////////////
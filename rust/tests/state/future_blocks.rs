@@ -0,0 +1,91 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{
+        precomputed::{PcbVersion, PrecomputedBlock},
+        store::BlockStore,
+    },
+    state::IndexerState,
+};
+use std::path::PathBuf;
+
+/// Parses the contiguous block of the given length
+fn contiguous_block(length: u32) -> anyhow::Result<(PrecomputedBlock, u64)> {
+    let dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
+
+        if file_name.starts_with(&format!("mainnet-{length}-")) {
+            let block_bytes = path.metadata()?.len();
+            return Ok((
+                PrecomputedBlock::parse_file(&path, PcbVersion::V1)?,
+                block_bytes,
+            ));
+        }
+    }
+
+    anyhow::bail!("No contiguous block of length {length}")
+}
+
+fn add_blocks(
+    state: &mut IndexerState,
+    lengths: std::ops::RangeInclusive<u32>,
+) -> anyhow::Result<()> {
+    for length in lengths {
+        let (block, block_bytes) = contiguous_block(length)?;
+        assert!(state.block_pipeline(&block, block_bytes)?, "{length}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn defer_and_promote() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("future-blocks")?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let store = state.indexer_store.clone().unwrap();
+    state.future_block_horizon = 5;
+
+    add_blocks(&mut state, 2..=8)?;
+    assert_eq!(state.best_tip_block().blockchain_length, 8);
+
+    // more than 5 blocks above the best tip
+    let (future_block, block_bytes) = contiguous_block(20)?;
+    let state_hash = future_block.state_hash();
+    assert!(state.block_pipeline(&future_block, block_bytes)?);
+
+    // stored, but not in the witness tree
+    assert!(store.get_block(&state_hash)?.is_some());
+    assert!(!state.diffs_map.contains_key(&state_hash));
+    assert!(state.dangling_branches.is_empty());
+    assert_eq!(state.best_tip_block().blockchain_length, 8);
+    assert_eq!(state.summary_short().witness_tree.num_deferred, 1);
+
+    let deferred = &state.ingestion_report.future_blocks_deferred;
+    assert_eq!(deferred.len(), 1);
+    assert_eq!(deferred[0].state_hash, state_hash);
+    assert_eq!(deferred[0].blockchain_length, 20);
+    assert_eq!(deferred[0].best_tip_length, 8);
+    assert_eq!(deferred[0].horizon, 5);
+
+    // still beyond the horizon
+    add_blocks(&mut state, 9..=14)?;
+    assert_eq!(state.deferred_blocks.len(), 1);
+    assert!(!state.diffs_map.contains_key(&state_hash));
+
+    // promoted once within the horizon, missing its parent
+    add_blocks(&mut state, 15..=15)?;
+    assert!(state.deferred_blocks.is_empty());
+    assert!(state.diffs_map.contains_key(&state_hash));
+    assert_eq!(state.dangling_branches.len(), 1);
+    assert_eq!(state.summary_short().witness_tree.num_deferred, 0);
+
+    // the missing blocks connect it to the best chain
+    add_blocks(&mut state, 16..=19)?;
+    assert!(state.dangling_branches.is_empty());
+    assert_eq!(state.best_tip_block().state_hash, state_hash);
+
+    add_blocks(&mut state, 21..=21)?;
+    assert_eq!(state.best_tip_block().blockchain_length, 21);
+    Ok(())
+}
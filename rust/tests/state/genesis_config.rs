@@ -0,0 +1,118 @@
+use crate::helpers::store::*;
+use mina_indexer::{
+    base::public_key::PublicKey,
+    chain::{store::ChainStore, ChainConstants},
+    cli::server::ServerArgsJson,
+    constants::{berkeley::BERKELEY_GENESIS_STATE_HASH, *},
+    ledger::{token::TokenAddress, Ledger},
+    server::IndexerConfiguration,
+    store::IndexerStore,
+};
+use std::{fs, path::PathBuf};
+use tempfile::TempDir;
+
+const GENESIS_ACCOUNT: &str = "B62qqq99cVWftgR6CEDGBt3SK38MrrZEzgNoqu9yrwFgyfv99yRjf32";
+
+#[test]
+fn toml_genesis_config() -> anyhow::Result<()> {
+    let config_dir = TempDir::with_prefix("genesis-config")?;
+    let ledger_path = config_dir.path().join("genesis_ledger.json");
+    fs::write(
+        &ledger_path,
+        format!(
+            r#"{{ "ledger": {{ "name": "testnet", "accounts": [{{ "pk": "{GENESIS_ACCOUNT}", "balance": "1000" }}] }} }}"#
+        ),
+    )?;
+
+    let config_path = config_dir.path().join("indexer.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"
+genesis_hash = "{MAINNET_GENESIS_HASH}"
+database_dir = "./database"
+log_level = "info"
+ledger_cadence = 100
+reporting_freq = 1000
+ingestion_queue_depth = 64
+ingestion_queue_bytes = 1024
+prune_interval = 10
+canonical_threshold = 10
+canonical_update_threshold = 2
+web_hostname = "localhost"
+web_port = 8080
+do_not_ingest_orphan_blocks = false
+no_recursive = false
+replace_staking_ledger = false
+accept_constants_change = false
+network = "testnet"
+
+[genesis]
+ledger = "{}"
+state_hash = "{BERKELEY_GENESIS_STATE_HASH}"
+blockchain_length = 1
+k = 10
+slots_per_epoch = 48
+slot_time_millis = 20000
+genesis_timestamp = 1700000000000
+"#,
+            ledger_path.display()
+        ),
+    )?;
+
+    let args = ServerArgsJson::from_file(&config_path)?;
+    let config = IndexerConfiguration::from((args, PathBuf::from("./mina-indexer.sock")));
+
+    // configured genesis
+    assert_eq!(
+        config.version.genesis.state_hash.0,
+        BERKELEY_GENESIS_STATE_HASH
+    );
+    assert_eq!(config.version.genesis.blockchain_lenth, 1);
+    assert_eq!(
+        config.version.genesis.prev_hash.0,
+        MAINNET_GENESIS_PREV_STATE_HASH
+    );
+    assert_eq!(
+        config.chain_constants,
+        ChainConstants {
+            k: 10,
+            slots_per_epoch: 48,
+            slot_time_millis: 20_000,
+            genesis_timestamp: 1_700_000_000_000,
+        }
+    );
+
+    let ledger: Ledger = config.genesis_ledger.into();
+    assert!(ledger
+        .get_account(&PublicKey::from(GENESIS_ACCOUNT), &TokenAddress::default())
+        .is_some());
+
+    // unknown genesis keys are rejected
+    fs::write(
+        &config_path,
+        fs::read_to_string(&config_path)?.replace("slots_per_epoch", "slots_per_epoc"),
+    )?;
+    assert!(ServerArgsJson::from_file(&config_path).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn store_chain_constants() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("chain-constants")?;
+    let store = IndexerStore::new(store_dir.path())?;
+
+    // mainnet's by default
+    assert_eq!(store.get_chain_constants()?, ChainConstants::default());
+
+    let constants = ChainConstants {
+        k: 10,
+        slots_per_epoch: 48,
+        ..Default::default()
+    };
+    store.set_chain_constants(&constants)?;
+    assert_eq!(store.get_chain_constants()?, constants);
+
+    Ok(())
+}
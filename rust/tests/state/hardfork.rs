@@ -14,8 +14,13 @@ use std::path::PathBuf;
 #[tokio::test]
 async fn hardfork() -> anyhow::Result<()> {
     let blocks_dir = PathBuf::from("./tests/data/hardfork");
-    let mut block_parser =
-        BlockParser::new_length_sorted_filtered(&blocks_dir, PcbVersion::default(), None, None)?;
+    let mut block_parser = BlockParser::new_length_sorted_filtered(
+        &blocks_dir,
+        PcbVersion::default(),
+        None,
+        None,
+        true,
+    )?;
 
     // final pre-harfork v1 block
     let (root_block, root_block_bytes) = block_parser.next_block().await?.unwrap();
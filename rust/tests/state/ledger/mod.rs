@@ -1,3 +1,4 @@
 mod apply_diff;
 mod diff_from_precomputed;
 mod genesis;
+mod overlay;
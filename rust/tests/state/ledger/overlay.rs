@@ -0,0 +1,99 @@
+use mina_indexer::{block::parser::BlockParser, ledger::view::LedgerView, state::IndexerState};
+use std::path::PathBuf;
+
+/// Compares the best tip overlay to cloning the canonical root ledger and
+/// applying the best chain diffs
+fn assert_overlay_matches_best_ledger(state: &IndexerState) {
+    assert_eq!(state.ledger_overlay.tip, state.best_tip.state_hash);
+
+    let mut best_chain = state.best_chain();
+    best_chain.reverse();
+
+    let mut best_ledger = state.ledger.clone();
+    for block in best_chain
+        .iter()
+        .skip_while(|b| *b != state.canonical_root_block())
+        .skip(1)
+    {
+        best_ledger
+            ._apply_diff(&state.diffs_map[&block.state_hash])
+            .unwrap();
+    }
+
+    let view = state.best_ledger_view();
+    assert_eq!(state.best_ledger().unwrap(), best_ledger);
+
+    for (token, token_ledger) in best_ledger.tokens.iter() {
+        for (pk, account) in token_ledger.accounts.iter() {
+            assert_eq!(view.account(pk, token), Some(account));
+            assert_eq!(view.balance(pk, token), Some(account.balance));
+            assert_eq!(view.nonce(pk, token), account.nonce);
+        }
+    }
+}
+
+#[tokio::test]
+async fn best_tip_overlay() -> anyhow::Result<()> {
+    // 0
+    // | \
+    // 1  f
+    // |
+    // 2
+    // |
+    // 3
+    // |
+    // 4
+
+    let blocks_dir = PathBuf::from("./tests/data/sequential_blocks");
+    let mut block_parser = BlockParser::new_testing(&blocks_dir)?;
+
+    // root_block =
+    // mainnet-105491-3NKizDx3nnhXha2WqHDNUvJk9jW7GsonsEGYs26tCPW2Wow1ZoR3.json
+    let (root_block, root_block_bytes) = block_parser
+        .get_precomputed_block("3NKizDx3nnhXha2WqHDNUvJk9jW7GsonsEGYs26tCPW2Wow1ZoR3")
+        .await?;
+    let mut state =
+        IndexerState::new_testing(&root_block, root_block_bytes, None, None, None, None, None)?;
+
+    // fork_block =
+    // mainnet-105492-3NKsUS3TtwvXsfFFnRAJ8US8wPLKKaRDTnbv4vzrwCDkb8HNaMWN.json
+    let (fork_block, _) = block_parser
+        .get_precomputed_block("3NKsUS3TtwvXsfFFnRAJ8US8wPLKKaRDTnbv4vzrwCDkb8HNaMWN")
+        .await?;
+    state.add_block_to_witness_tree(&fork_block, true, true)?;
+
+    assert_eq!(state.best_tip.state_hash, fork_block.state_hash());
+    assert_overlay_matches_best_ledger(&state);
+
+    // main chain blocks
+    // mainnet-105492-3NKAqzELKDp2BbdKKwdRWEoMNehyMrxJGCoGCyH1t1PyyH7VQMgk.json
+    // mainnet-105493-3NKakum3B2Tigw9TSsxwvXvV3x8L2LvrJ3yXFLEAJDMZu2vkn7db.json
+    // mainnet-105494-3NKqd3XGqkLmZVmPC3iG6AnrwQoZdBKdmYTzEJT3vwwnn2H1Z4ww.json
+    // mainnet-105495-3NKmDYoFs5MRNE4PoGMkMT5udM4JrnB5NJYFLJcDUUob363aj5e9.json
+    for state_hash in [
+        "3NKAqzELKDp2BbdKKwdRWEoMNehyMrxJGCoGCyH1t1PyyH7VQMgk",
+        "3NKakum3B2Tigw9TSsxwvXvV3x8L2LvrJ3yXFLEAJDMZu2vkn7db",
+        "3NKqd3XGqkLmZVmPC3iG6AnrwQoZdBKdmYTzEJT3vwwnn2H1Z4ww",
+        "3NKmDYoFs5MRNE4PoGMkMT5udM4JrnB5NJYFLJcDUUob363aj5e9",
+    ] {
+        let (block, _) = block_parser.get_precomputed_block(state_hash).await?;
+        state.add_block_to_witness_tree(&block, true, true)?;
+
+        assert_overlay_matches_best_ledger(&state);
+    }
+
+    // the main chain replaced the fork & the canonical root moved up
+    assert_eq!(
+        state.best_tip.state_hash.0,
+        "3NKmDYoFs5MRNE4PoGMkMT5udM4JrnB5NJYFLJcDUUob363aj5e9"
+    );
+    assert!(state.canonical_root_block().blockchain_length > root_block.blockchain_length());
+
+    // rebuilding from scratch matches the incrementally maintained overlay
+    let incremental = state.ledger_overlay.clone();
+    state.rebuild_ledger_overlay()?;
+
+    assert_eq!(state.ledger_overlay, incremental);
+    assert_overlay_matches_best_ledger(&state);
+    Ok(())
+}
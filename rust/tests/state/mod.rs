@@ -1,5 +1,11 @@
 mod dangling_branches;
+mod future_blocks;
+mod genesis_config;
 mod hardfork;
 mod ledger;
 mod orphaned_blocks;
+mod rollback;
 mod root_branch;
+mod sibling_order;
+mod tree_graph;
+mod watchlist;
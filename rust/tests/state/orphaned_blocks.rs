@@ -7,9 +7,15 @@ async fn not_added_to_witness_tree() -> anyhow::Result<()> {
     let store_dir = setup_new_db_dir("orphaned-blocks")?;
     let block_dir = PathBuf::from("./tests/data/sequential_blocks");
 
-    let mut block_parser =
-        BlockParser::new_with_canonical_chain_discovery(&block_dir, PcbVersion::V1, 10, false, 10)
-            .await?;
+    let mut block_parser = BlockParser::new_with_canonical_chain_discovery(
+        &block_dir,
+        PcbVersion::V1,
+        10,
+        false,
+        10,
+        true,
+    )
+    .await?;
 
     let mut state = mainnet_genesis_state(store_dir.as_ref())?;
 
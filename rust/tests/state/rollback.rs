@@ -0,0 +1,121 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{
+        precomputed::{PcbVersion, PrecomputedBlock},
+        store::BlockStore,
+    },
+    canonicity::store::CanonicityStore,
+    ledger::{store::best::BestLedgerStore, token::TokenAddress},
+    state::IndexerState,
+};
+use std::path::PathBuf;
+
+/// Parses the contiguous block of the given length
+fn contiguous_block(length: u32) -> anyhow::Result<(PrecomputedBlock, u64)> {
+    let dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
+
+        if file_name.starts_with(&format!("mainnet-{length}-")) {
+            let block_bytes = path.metadata()?.len();
+            return Ok((
+                PrecomputedBlock::parse_file(&path, PcbVersion::V1)?,
+                block_bytes,
+            ));
+        }
+    }
+
+    anyhow::bail!("No contiguous block of length {length}")
+}
+
+fn add_blocks(
+    state: &mut IndexerState,
+    lengths: std::ops::RangeInclusive<u32>,
+) -> anyhow::Result<Vec<PrecomputedBlock>> {
+    let mut blocks = vec![];
+    for length in lengths {
+        let (block, block_bytes) = contiguous_block(length)?;
+        assert!(state.block_pipeline(&block, block_bytes)?, "{length}");
+        blocks.push(block);
+    }
+
+    Ok(blocks)
+}
+
+#[test]
+fn rollback_to_best_chain_block() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("rollback")?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let store = state.indexer_store.clone().unwrap();
+
+    add_blocks(&mut state, 2..=18)?;
+    let target = state.best_tip_block().clone();
+    let best_ledger = state.best_ledger()?;
+    let store_best_ledger = store.build_best_ledger()?.unwrap();
+
+    let blocks = add_blocks(&mut state, 19..=21)?;
+    assert_eq!(state.best_tip_block().blockchain_length, 21);
+    assert!(target.blockchain_length > state.canonical_root_block().blockchain_length);
+
+    // the blocks above the target are rolled back, highest first
+    let rolled_back = state.rollback_to(&target.state_hash)?;
+    assert_eq!(
+        rolled_back
+            .iter()
+            .map(|block| block.blockchain_length)
+            .collect::<Vec<_>>(),
+        vec![21, 20, 19]
+    );
+
+    // witness tree
+    assert_eq!(state.best_tip_block(), &target);
+    for block in blocks.iter() {
+        assert!(!state.diffs_map.contains_key(&block.state_hash()));
+    }
+    assert_eq!(state.best_ledger()?, best_ledger);
+
+    // store
+    assert_eq!(
+        store.get_best_block_hash()?,
+        Some(target.state_hash.clone())
+    );
+    assert_eq!(
+        store.get_canonical_hash_at_height(18)?,
+        Some(target.state_hash.clone())
+    );
+    assert_eq!(store.get_canonical_hash_at_height(19)?, None);
+    assert_eq!(store.build_best_ledger()?.unwrap(), store_best_ledger);
+
+    let coinbase_receiver = blocks[2].coinbase_receiver();
+    assert!(store
+        .get_best_account_balance_history_range(
+            &coinbase_receiver,
+            &TokenAddress::default(),
+            19,
+            21,
+            usize::MAX,
+        )?
+        .is_empty());
+
+    // rolled back blocks stay in the block store
+    for block in blocks.iter() {
+        assert!(store.get_block(&block.state_hash())?.is_some());
+    }
+
+    // rolling back to the best tip is a no-op
+    assert!(state.rollback_to(&target.state_hash)?.is_empty());
+
+    // dropped blocks & blocks below the canonical root can't be rolled back to
+    assert!(state.rollback_to(&blocks[0].state_hash()).is_err());
+    let canonical_root = state.canonical_root_block().clone();
+    let below_root = store
+        .get_canonical_hash_at_height(canonical_root.blockchain_length - 1)?
+        .unwrap();
+    assert!(state.rollback_to(&below_root).is_err());
+
+    // only the best block can be unapplied from the store
+    assert!(store.unapply_block(&blocks[0].state_hash()).is_err());
+
+    Ok(())
+}
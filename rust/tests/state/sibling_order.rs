@@ -0,0 +1,126 @@
+use mina_indexer::{
+    base::{amount::Amount, state_hash::StateHash},
+    block::{parser::BlockParser, precomputed::PrecomputedBlock},
+    constants::MINA_SCALE,
+    ledger::{account::Account, genesis::GenesisLedger, token::TokenAddress, Ledger},
+    state::IndexerState,
+};
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// Number of shuffled ingestion orders
+const NUM_ORDERS: u64 = 8;
+
+#[derive(Debug, PartialEq)]
+struct WitnessTreeOutcome {
+    best_tip: StateHash,
+    canonical_root: StateHash,
+    best_chain: Vec<StateHash>,
+    summary: Value,
+}
+
+/// Root ledger with the accounts required by the sequential blocks
+fn root_ledger() -> anyhow::Result<Ledger> {
+    let mut ledger: Ledger = GenesisLedger::new_v1()?.into();
+    for pk in [
+        "B62qrdhG66vK71Jbdz6Xs7cnDxQ8f6jZUFvefkp3pje4EejYUTvotGP",
+        "B62qrRvo5wngd5WA1dgXkQpCdQMRDndusmjfWXWT1LgsSFFdBS9RCsV",
+    ] {
+        ledger.insert_account(
+            Account {
+                public_key: pk.into(),
+                balance: Amount(1000 * MINA_SCALE),
+                ..Default::default()
+            },
+            &TokenAddress::default(),
+        );
+    }
+
+    Ok(ledger)
+}
+
+/// Deterministic Fisher-Yates shuffle (xorshift)
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    for i in (1..items.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        items.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+}
+
+fn ingest(
+    root_block: &PrecomputedBlock,
+    root_block_bytes: u64,
+    blocks: &[PrecomputedBlock],
+) -> anyhow::Result<WitnessTreeOutcome> {
+    let mut state = IndexerState::new_testing(
+        root_block,
+        root_block_bytes,
+        Some(root_ledger()?),
+        None,
+        None,
+        None,
+        None,
+    )?;
+    for block in blocks {
+        state.add_block_to_witness_tree(block, true, true)?;
+    }
+
+    Ok(WitnessTreeOutcome {
+        best_tip: state.best_tip_block().state_hash.clone(),
+        canonical_root: state.canonical_root_block().state_hash.clone(),
+        best_chain: state
+            .best_chain()
+            .into_iter()
+            .map(|block| block.state_hash)
+            .collect(),
+        summary: serde_json::to_value(state.summary_short().witness_tree)?,
+    })
+}
+
+/// Ingests the forked sequential blocks in shuffled orders, siblings
+/// included, & checks the witness tree outcome never changes
+#[tokio::test]
+async fn sibling_order_determinism() -> anyhow::Result<()> {
+    let block_dir = PathBuf::from("./tests/data/sequential_blocks");
+    let mut block_parser = BlockParser::new_testing(&block_dir)?;
+
+    let (root_block, root_block_bytes) = block_parser.next_block().await?.unwrap();
+    let root_block: PrecomputedBlock = root_block.into();
+
+    let mut blocks = vec![];
+    while let Some((block, _)) = block_parser.next_block().await? {
+        blocks.push(PrecomputedBlock::from(block));
+    }
+
+    let expected = ingest(&root_block, root_block_bytes, &blocks)?;
+
+    // the best tip is the best top leaf
+    let best_tip_length = blocks
+        .iter()
+        .map(|block| block.blockchain_length())
+        .max()
+        .unwrap();
+    assert_eq!(expected.best_chain.first(), Some(&expected.best_tip));
+    assert_eq!(
+        expected.summary["best_tip_length"].as_u64(),
+        Some(best_tip_length as u64)
+    );
+
+    // reversed order, children before their parents & siblings
+    blocks.reverse();
+    assert_eq!(ingest(&root_block, root_block_bytes, &blocks)?, expected);
+
+    for seed in 1..=NUM_ORDERS {
+        shuffle(&mut blocks, seed);
+        assert_eq!(
+            ingest(&root_block, root_block_bytes, &blocks)?,
+            expected,
+            "order seed {seed}"
+        );
+    }
+
+    Ok(())
+}
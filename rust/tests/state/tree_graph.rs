@@ -0,0 +1,80 @@
+use mina_indexer::{
+    block::parser::BlockParser,
+    canonicity::Canonicity,
+    state::{
+        tree_graph::{TreeFormat, WitnessTreeGraph},
+        IndexerState,
+    },
+};
+use std::path::PathBuf;
+
+const ROOT_HASH: &str = "3NK4huLvUDiL4XuCUcyrWCKynmvhqfKsx5h2MfBXVVUq2Qwzi5uT";
+const CHILD_HASH: &str = "3NKxEA9gztvEGxL4uk4eTncZAxuRmMsB8n81UkeAMevUjMbLHmkC";
+const GRANDCHILD_HASH: &str = "3NKizDx3nnhXha2WqHDNUvJk9jW7GsonsEGYs26tCPW2Wow1ZoR3";
+
+#[tokio::test]
+async fn witness_tree_graph() -> anyhow::Result<()> {
+    let blocks_dir = PathBuf::from("./tests/data/sequential_blocks");
+    let mut block_parser = BlockParser::new_testing(&blocks_dir)?;
+
+    let (root_block, root_block_bytes) = block_parser.get_precomputed_block(ROOT_HASH).await?;
+    let (child_block, _) = block_parser.get_precomputed_block(CHILD_HASH).await?;
+    let (grandchild_block, _) = block_parser.get_precomputed_block(GRANDCHILD_HASH).await?;
+
+    let mut state =
+        IndexerState::new_testing(&root_block, root_block_bytes, None, None, None, None, None)?;
+
+    // the grandchild makes a dangling branch
+    state.add_block_to_witness_tree(&grandchild_block, true, true)?;
+
+    let graph = state.witness_tree_graph();
+    assert_eq!(graph.root_branch.root_hash.0, ROOT_HASH);
+    assert_eq!(graph.root_branch.nodes.len(), 1);
+
+    let root = &graph.root_branch.nodes[0];
+    assert_eq!(root.canonicity, Canonicity::Canonical);
+    assert!(root.best_tip && root.canonical_root);
+
+    assert_eq!(graph.dangling_branches.len(), 1);
+    let dangling = &graph.dangling_branches[0].nodes;
+    assert_eq!(dangling.len(), 1);
+    assert_eq!(dangling[0].state_hash.0, GRANDCHILD_HASH);
+    assert_eq!(dangling[0].canonicity, Canonicity::Pending);
+    assert!(!dangling[0].best_tip);
+
+    let dot = graph.format(TreeFormat::Dot)?;
+    assert!(dot.starts_with("digraph witness_tree {"));
+    assert!(dot.contains("subgraph \"cluster_root\""));
+    assert!(dot.contains("subgraph \"cluster_dangling_0\""));
+    assert!(!dot.contains("->"));
+
+    // the child merges the dangling branch into the root branch
+    state.add_block_to_witness_tree(&child_block, true, true)?;
+
+    let graph = state.witness_tree_graph();
+    assert!(graph.dangling_branches.is_empty());
+
+    let nodes = &graph.root_branch.nodes;
+    let hashes: Vec<_> = nodes
+        .iter()
+        .map(|node| node.state_hash.0.as_str())
+        .collect();
+    assert_eq!(hashes, vec![ROOT_HASH, CHILD_HASH, GRANDCHILD_HASH]);
+
+    // below the canonical threshold, only the root is canonical
+    assert!(nodes[0].canonical_root && !nodes[0].best_tip);
+    assert!(nodes[2].best_tip);
+    assert!(nodes[1..]
+        .iter()
+        .all(|node| node.canonicity == Canonicity::Pending));
+
+    let dot = graph.to_dot();
+    assert!(dot.contains(&format!("\"{ROOT_HASH}\" -> \"{CHILD_HASH}\";")));
+    assert!(dot.contains(&format!("\"{CHILD_HASH}\" -> \"{GRANDCHILD_HASH}\";")));
+
+    // JSON round trip
+    let json = graph.format(TreeFormat::Json)?;
+    assert_eq!(serde_json::from_str::<WitnessTreeGraph>(&json)?, graph);
+
+    Ok(())
+}
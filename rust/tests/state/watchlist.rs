@@ -0,0 +1,101 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::extract_height_and_hash,
+    canonicity::store::CanonicityStore,
+    server::ingest_block,
+    state::IndexerState,
+    watchlist::{store::WatchlistStore, WatchedAccount},
+};
+use std::{collections::HashSet, fs, path::PathBuf, sync::Arc};
+use tokio::sync::RwLock;
+
+const WATCHED_HEIGHT: u32 = 5;
+
+#[tokio::test]
+async fn canonical_block_notifications() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("state-watchlist")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut paths = fs::read_dir(block_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort_by_key(|path| extract_height_and_hash(path).0);
+
+    let state = Arc::new(RwLock::new(mainnet_genesis_state(store_dir.as_ref())?));
+    let store = state.read().await.indexer_store.clone().unwrap();
+
+    // watch the creator of the block at the watched height
+    let watched_path = paths
+        .iter()
+        .find(|path| extract_height_and_hash(path).0 == WATCHED_HEIGHT)
+        .unwrap();
+    let watched_block = IndexerState::parse_file(&state, watched_path).await?;
+    let account = WatchedAccount {
+        public_key: watched_block.block_creator(),
+        label: Some("creator".to_string()),
+    };
+    let pk = &account.public_key;
+
+    assert!(store.add_watched_account(&account)?);
+    assert!(!store.add_watched_account(&account)?);
+    assert_eq!(store.get_watchlist()?, vec![account.clone()]);
+
+    let mut receiver = store.subscribe_watch_notifications();
+    for path in paths.iter() {
+        let block = IndexerState::parse_file(&state, path).await?;
+        ingest_block(&state, &block, path.metadata()?.len(), None).await?;
+    }
+
+    // the watched block is canonical & notified
+    let notifications = store.get_watch_notifications(None, 100)?;
+    assert!(notifications.iter().any(
+        |n| n.blockchain_length == WATCHED_HEIGHT && n.state_hash == watched_block.state_hash()
+    ));
+
+    // notifications are only of canonical blocks, once each, highest first
+    let mut state_hashes = HashSet::new();
+    for notification in notifications.iter() {
+        assert_eq!(&notification.public_key, pk);
+        assert_eq!(notification.label.as_deref(), Some("creator"));
+        assert_eq!(
+            store.get_canonical_hash_at_height(notification.blockchain_length)?,
+            Some(notification.state_hash.clone())
+        );
+        assert!(state_hashes.insert(notification.state_hash.clone()));
+    }
+    assert!(notifications
+        .windows(2)
+        .all(|w| w[0].blockchain_length >= w[1].blockchain_length));
+
+    // each notification is published
+    let mut num_published = 0;
+    while let Ok(notification) = receiver.try_recv() {
+        assert!(state_hashes.contains(&notification.state_hash));
+        num_published += 1;
+    }
+    assert_eq!(num_published, notifications.len());
+
+    // per account & limited
+    assert_eq!(store.get_watch_notifications(Some(pk), 100)?, notifications);
+    assert_eq!(store.get_watch_notifications(None, 1)?.len(), 1);
+    assert!(store.get_watch_notifications(None, 0)?.is_empty());
+
+    // removal
+    assert!(store.remove_watched_account(pk)?);
+    assert!(!store.remove_watched_account(pk)?);
+    assert!(store.get_watchlist()?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn parse_watched_accounts() -> anyhow::Result<()> {
+    let pk = "B62qrecVjpoZ4Re3a5arN6gXZ6orhmj1enUtA887XdG5mtZfdUbBUh4";
+    let accounts = WatchedAccount::parse_all(&[pk, " ", ""])?;
+
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].public_key, pk.into());
+    assert!(WatchedAccount::parse_all(&["not a public key"]).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::state_hash::StateHash,
+    block::parser::BlockParser,
+    event::{
+        db::{DbBlockEvent, DbEvent},
+        store::EventStore,
+        IndexerEvent,
+    },
+    store::column_families::ColumnFamilyHelpers,
+};
+use std::path::PathBuf;
+
+#[tokio::test]
+async fn marker_and_event_log_tail() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("clean-shutdown")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut block_parser = BlockParser::new_testing(&block_dir)?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    state.add_blocks(&mut block_parser).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+
+    // no marker until the first clean shutdown
+    assert!(!store.is_clean_shutdown()?);
+    store.clean_shutdown()?;
+    assert!(store.is_clean_shutdown()?);
+    store.set_running()?;
+    assert!(!store.is_clean_shutdown()?);
+
+    // the tail of an intact event log is valid
+    let next_seq_num = store.get_next_seq_num()?;
+    let report = store.validate_event_log_tail(next_seq_num)?;
+    assert_eq!(report.from_seq_num, 0);
+    assert_eq!(report.next_seq_num, next_seq_num);
+    assert_eq!(report.num_dangling, 0);
+    assert!(report.invalid.is_empty());
+
+    let (min_height, max_height) = report.canonical_heights.unwrap();
+    assert!(min_height < max_height);
+
+    // torn block write, its event was appended
+    let missing_hash = StateHash("3NK4huLvUDiL4XuCUcyrWCKynmvhqfKsx5h2MfBXVVUq2Qwzi5uT".into());
+    store.add_event(&IndexerEvent::Db(DbEvent::Block(DbBlockEvent::NewBlock {
+        state_hash: missing_hash,
+        blockchain_length: max_height + 1,
+    })))?;
+
+    let report = store.recover_unclean_shutdown(10)?;
+    assert_eq!(report.from_seq_num, next_seq_num + 1 - 10);
+    assert_eq!(report.num_dangling, 0);
+    assert_eq!(report.invalid, vec![next_seq_num]);
+
+    // dangling events past the next sequence number are removed
+    store.database.put_cf(
+        store.events_cf(),
+        (next_seq_num + 1).to_be_bytes(),
+        store
+            .database
+            .get_cf(store.events_cf(), next_seq_num.to_be_bytes())?
+            .unwrap(),
+    )?;
+
+    let report = store.validate_event_log_tail(10)?;
+    assert_eq!(report.num_dangling, 1);
+    assert!(store.get_event(next_seq_num + 1)?.is_none());
+    assert_eq!(store.validate_event_log_tail(10)?.num_dangling, 0);
+
+    Ok(())
+}
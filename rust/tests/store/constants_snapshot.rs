@@ -0,0 +1,95 @@
+use crate::helpers::store::*;
+use mina_indexer::{
+    constants::MAINNET_COINBASE_REWARD,
+    event::{
+        db::{DbConstantsEvent, DbEvent},
+        store::EventStore,
+        IndexerEvent,
+    },
+    store::{
+        constants_snapshot::{ConstantsSnapshot, ConstantsSnapshotStore, Reindexable},
+        IndexerStore,
+    },
+};
+
+#[test]
+fn changed_coinbase_reward() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("constants-snapshot")?;
+    let path = store_dir.path();
+
+    // the snapshot is persisted on creation & reopening is a no-op
+    {
+        let store = IndexerStore::new(path)?;
+        assert_eq!(
+            store.get_constants_snapshot()?,
+            Some(ConstantsSnapshot::current())
+        );
+    }
+    let store = IndexerStore::new(path)?;
+    assert!(store.get_event_log()?.is_empty());
+
+    // simulate a store created with a different coinbase reward
+    let mut snapshot = ConstantsSnapshot::current();
+    snapshot.values.insert(
+        "MAINNET_COINBASE_REWARD".into(),
+        (MAINNET_COINBASE_REWARD / 2).to_string(),
+    );
+    store.set_constants_snapshot(&snapshot)?;
+    drop(store);
+
+    // opening fails without accepting the change
+    let err = IndexerStore::new(path).unwrap_err().to_string();
+    assert!(err.contains("MAINNET_COINBASE_REWARD"), "{err}");
+    assert!(err.contains("internal-commands"), "{err}");
+
+    // accepting the change records the affected derived data
+    let store = IndexerStore::new_accept_constants_change(path)?;
+    assert_eq!(
+        store.get_event_log()?,
+        vec![IndexerEvent::Db(DbEvent::Constants(
+            DbConstantsEvent::ConstantsChanged {
+                changed: vec!["MAINNET_COINBASE_REWARD".into()],
+                reindex: vec![
+                    Reindexable::Blocks,
+                    Reindexable::Canonicity,
+                    Reindexable::InternalCommands,
+                    Reindexable::StagedLedgers,
+                    Reindexable::BestLedger,
+                ],
+            }
+        ))]
+    );
+    assert_eq!(
+        store.get_constants_snapshot()?,
+        Some(ConstantsSnapshot::current())
+    );
+    drop(store);
+
+    // the accepted snapshot is current
+    let store = IndexerStore::new(path)?;
+    assert_eq!(store.get_event_log()?.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn changed_non_derivation_constant() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("constants-snapshot-other")?;
+    let path = store_dir.path();
+
+    let store = IndexerStore::new(path)?;
+    let mut snapshot = ConstantsSnapshot::current();
+    snapshot.values.insert("LEDGER_CADENCE".into(), "1".into());
+    store.set_constants_snapshot(&snapshot)?;
+    drop(store);
+
+    // only warns & updates the snapshot
+    let store = IndexerStore::new(path)?;
+    assert!(store.get_event_log()?.is_empty());
+    assert_eq!(
+        store.get_constants_snapshot()?,
+        Some(ConstantsSnapshot::current())
+    );
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::parser::BlockParser,
+    state::IndexerState,
+    store::dataset::{compare_datasets, DATASET_TABLES},
+};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+async fn ingest_test_chain(name: &str) -> anyhow::Result<IndexerState> {
+    let store_dir = setup_new_db_dir(name)?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    Ok(state)
+}
+
+fn table_bytes(dir: &Path, table: &str) -> anyhow::Result<Vec<u8>> {
+    Ok(std::fs::read(dir.join(format!("{table}.ndjson")))?)
+}
+
+#[tokio::test]
+async fn deterministic_export_and_compare() -> anyhow::Result<()> {
+    let state_a = ingest_test_chain("dataset-export-a").await?;
+    let state_b = ingest_test_chain("dataset-export-b").await?;
+    let dataset_a = TempDir::with_prefix("dataset-a")?;
+    let dataset_b = TempDir::with_prefix("dataset-b")?;
+
+    let export_a = state_a
+        .indexer_store
+        .as_ref()
+        .unwrap()
+        .export_dataset(dataset_a.path())?;
+    let export_b = state_b
+        .indexer_store
+        .as_ref()
+        .unwrap()
+        .export_dataset(dataset_b.path())?;
+    assert_eq!(export_a, export_b);
+
+    // every table is non-empty & byte-identical
+    for (table, num_rows) in export_a.num_rows.iter() {
+        assert!(*num_rows > 0, "{table}");
+        assert_eq!(
+            table_bytes(dataset_a.path(), table)?,
+            table_bytes(dataset_b.path(), table)?,
+            "{table}"
+        );
+    }
+
+    let comparison = compare_datasets(dataset_a.path(), dataset_b.path(), 10)?;
+    assert!(comparison.is_identical(), "{comparison}");
+
+    // perturb a single user command
+    let path = dataset_b.path().join("user_commands.ndjson");
+    let mut rows: Vec<String> = std::fs::read_to_string(&path)?
+        .lines()
+        .map(ToString::to_string)
+        .collect();
+    let perturbed_line = rows.len() / 2;
+    let original = rows[perturbed_line].clone();
+    rows[perturbed_line] = original.replacen("\"fee\":", "\"fee\":1", 1);
+    assert_ne!(rows[perturbed_line], original);
+    std::fs::write(&path, rows.join("\n") + "\n")?;
+
+    let comparison = compare_datasets(dataset_a.path(), dataset_b.path(), 10)?;
+    assert!(!comparison.is_identical());
+
+    for table in comparison.tables.iter() {
+        if table.table != "user_commands" {
+            assert_eq!(table.num_differing_rows, 0, "{}", table.table);
+            continue;
+        }
+
+        assert_eq!(table.num_rows_a, table.num_rows_b);
+        assert_eq!(table.num_differing_rows, 1);
+        assert_eq!(
+            table.first_differences,
+            vec![(
+                perturbed_line + 1,
+                Some(original.clone()),
+                Some(rows[perturbed_line].clone())
+            )]
+        );
+    }
+
+    assert_eq!(comparison.tables.len(), DATASET_TABLES.len());
+    Ok(())
+}
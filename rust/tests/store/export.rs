@@ -0,0 +1,109 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{parser::BlockParser, store::BlockStore},
+    canonicity::store::CanonicityStore,
+    command::{internal::DbInternalCommandWithData, signed::SignedCommandWithData},
+    store::export::{ExportFormat, EXPORT_TABLES},
+};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Rows of the exported CSV table, excluding the header
+fn csv_rows(dir: &Path, table: &str) -> anyhow::Result<Vec<csv::StringRecord>> {
+    let mut reader = csv::Reader::from_path(dir.join(format!("{table}.csv")))?;
+    Ok(reader.records().collect::<Result<_, _>>()?)
+}
+
+#[tokio::test]
+async fn csv_export_height_range() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("canonical-chain-export")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let (start_height, end_height) = (2, 5);
+    let output_dir = TempDir::with_prefix("canonical-chain-export")?;
+
+    let export = store.export_canonical_chain(
+        output_dir.path(),
+        ExportFormat::Csv,
+        start_height,
+        Some(end_height),
+    )?;
+    assert_eq!(
+        export
+            .num_rows
+            .iter()
+            .map(|(table, _)| *table)
+            .collect::<Vec<_>>(),
+        EXPORT_TABLES
+    );
+
+    // expected row counts of the height range
+    let (mut num_user_commands, mut num_internal_commands) = (0, 0);
+    for height in start_height..=end_height {
+        let state_hash = store.get_canonical_hash_at_height(height)?.unwrap();
+        let (block, _) = store.get_block(&state_hash)?.unwrap();
+
+        num_user_commands += SignedCommandWithData::from_precomputed(&block).len();
+        num_internal_commands += DbInternalCommandWithData::from_precomputed(&block).len();
+    }
+
+    for (table, num_rows) in export.num_rows.iter() {
+        let rows = csv_rows(output_dir.path(), table)?;
+        assert_eq!(rows.len(), *num_rows, "{table}");
+
+        // every row is in the height range
+        for row in rows.iter() {
+            let height: u32 = row[0].parse()?;
+            assert!((start_height..=end_height).contains(&height), "{table}");
+        }
+    }
+
+    let blocks = csv_rows(output_dir.path(), "blocks")?;
+    let heights: Vec<u32> = blocks
+        .iter()
+        .map(|row| row[0].parse())
+        .collect::<Result<_, _>>()?;
+    assert_eq!(heights, (start_height..=end_height).collect::<Vec<_>>());
+
+    for row in blocks.iter() {
+        let height = row[0].parse()?;
+        assert_eq!(
+            store.get_canonical_hash_at_height(height)?.unwrap().0,
+            row[1]
+        );
+    }
+
+    assert_eq!(export.num_rows[1].1, num_user_commands);
+    assert_eq!(export.num_rows[2].1, num_internal_commands);
+
+    // the header matches the SQL mirror's columns
+    let mut reader = csv::Reader::from_path(output_dir.path().join("user_commands.csv"))?;
+    assert_eq!(
+        reader.headers()?.iter().collect::<Vec<_>>(),
+        [
+            "height",
+            "index_in_block",
+            "txn_hash",
+            "kind",
+            "sender",
+            "receiver",
+            "amount",
+            "fee",
+            "nonce",
+            "memo",
+            "applied"
+        ]
+    );
+
+    // invalid height range
+    assert!(store
+        .export_canonical_chain(output_dir.path(), ExportFormat::Csv, 5, Some(2))
+        .is_err());
+
+    Ok(())
+}
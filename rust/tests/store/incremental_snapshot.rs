@@ -0,0 +1,58 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{parser::BlockParser, store::BlockStore},
+    store::{
+        incremental_snapshot::{restore_incremental_snapshot, snapshot_manifests},
+        IndexerStore,
+    },
+};
+use std::path::PathBuf;
+
+#[tokio::test]
+async fn snapshot_and_restore() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("incremental-snapshot-store")?;
+    let snapshot_dir = setup_new_db_dir("incremental-snapshot")?;
+    let restore_dir = setup_new_db_dir("incremental-snapshot-restore")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut block_parser = BlockParser::new_testing(&block_dir)?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    state.add_blocks(&mut block_parser).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let best_tip = store.get_best_block_hash()?;
+    assert!(best_tip.is_some());
+
+    let first = store.create_incremental_snapshot(snapshot_dir.path())?;
+    assert_eq!(first.files_reused, 0);
+    assert_eq!(first.manifest.best_tip, best_tip);
+
+    // unchanged table files are shared with the first snapshot
+    let second = store.create_incremental_snapshot(snapshot_dir.path())?;
+    assert!(second.files_reused > 0);
+    assert!(second.files_added < first.files_added);
+
+    let manifests = snapshot_manifests(snapshot_dir.path())?;
+    assert_eq!(manifests, vec![first.manifest.clone(), second.manifest]);
+
+    // restore the first snapshot by name
+    let restored_dir = restore_dir.path().join("restored");
+    let manifest = restore_incremental_snapshot(
+        snapshot_dir.path(),
+        Some(&first.manifest.name),
+        &restored_dir,
+    )?;
+    assert_eq!(manifest, first.manifest);
+
+    // existing restore dirs are not overwritten
+    assert!(restore_incremental_snapshot(snapshot_dir.path(), None, &restored_dir).is_err());
+
+    let restored = IndexerStore::new(&restored_dir)?;
+    assert_eq!(restored.get_best_block_hash()?, best_tip);
+    assert_eq!(
+        restored.get_best_block_height()?,
+        store.get_best_block_height()?
+    );
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+mod clean_shutdown;
+mod constants_snapshot;
+mod dataset;
+mod export;
+mod incremental_snapshot;
+mod network_namespaces;
+mod read_only;
+mod replay_range;
+mod replication_lag;
+#[cfg(feature = "sql_mirror")]
+mod sql_mirror;
+mod sub_store;
@@ -0,0 +1,85 @@
+use crate::helpers::store::*;
+use mina_indexer::{
+    block::{
+        precomputed::{PcbVersion, PrecomputedBlock},
+        store::BlockStore,
+    },
+    chain::Network,
+    store::IndexerStore,
+};
+use std::{fs, path::PathBuf, sync::Arc};
+
+const MAINNET_BLOCK: &str = "./tests/data/sequential_blocks/mainnet-105490-3NKxEA9gztvEGxL4uk4eTncZAxuRmMsB8n81UkeAMevUjMbLHmkC.json";
+const DEVNET_BLOCK: &str = "./tests/data/sequential_blocks/mainnet-105491-3NKizDx3nnhXha2WqHDNUvJk9jW7GsonsEGYs26tCPW2Wow1ZoR3.json";
+
+fn add_block(store: &IndexerStore, path: &str) -> anyhow::Result<PrecomputedBlock> {
+    let path = PathBuf::from(path);
+    let block = PrecomputedBlock::parse_file(&path, PcbVersion::V1)?;
+    store.add_block(&block, fs::metadata(&path)?.len())?;
+    Ok(block)
+}
+
+#[test]
+fn isolated_network_namespaces() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("network-namespaces")?;
+    let path = store_dir.path();
+
+    let (mainnet_hash, devnet_hash) = {
+        let store = Arc::new(IndexerStore::new_with_networks(
+            path,
+            &[Network::Mainnet, Network::Devnet],
+            false,
+        )?);
+
+        // the first network claims the legacy namespace
+        let mainnet = store.network_store(&Network::Mainnet)?;
+        let devnet = store.network_store(&Network::Devnet)?;
+        assert_eq!(mainnet.namespace(), None);
+        assert_eq!(devnet.namespace(), Some(&Network::Devnet));
+        assert_eq!(
+            store.get_networks()?,
+            vec![Network::Mainnet, Network::Devnet]
+        );
+
+        // live namespace stores are reused
+        assert!(Arc::ptr_eq(
+            &devnet,
+            &store.network_store(&Network::Devnet)?
+        ));
+
+        let mainnet_block = add_block(&mainnet, MAINNET_BLOCK)?;
+        let devnet_block = add_block(&devnet, DEVNET_BLOCK)?;
+
+        // blocks are only visible in their network's namespace
+        let mainnet_hash = mainnet_block.state_hash();
+        let devnet_hash = devnet_block.state_hash();
+        assert!(mainnet.get_block(&mainnet_hash)?.is_some());
+        assert!(mainnet.get_block(&devnet_hash)?.is_none());
+        assert!(devnet.get_block(&devnet_hash)?.is_some());
+        assert!(devnet.get_block(&mainnet_hash)?.is_none());
+
+        // networks which weren't opened with the store
+        assert!(store.network_store(&Network::Testworld).is_err());
+
+        (mainnet_hash, devnet_hash)
+    };
+
+    // reopening without networks keeps the legacy network's data readable
+    let store = Arc::new(IndexerStore::new(path)?);
+    assert_eq!(store.get_legacy_network()?, Some(Network::Mainnet));
+    assert_eq!(
+        store.get_networks()?,
+        vec![Network::Mainnet, Network::Devnet]
+    );
+
+    let mainnet = store.network_store(&Network::Mainnet)?;
+    assert!(Arc::ptr_eq(&mainnet, &store));
+    assert!(mainnet.get_block(&mainnet_hash)?.is_some());
+
+    // existing namespaces are opened with the store
+    let devnet = store.network_store(&Network::Devnet)?;
+    assert!(devnet.get_block(&devnet_hash)?.is_some());
+    assert!(devnet.get_block(&mainnet_hash)?.is_none());
+
+    Ok(())
+}
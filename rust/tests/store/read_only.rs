@@ -0,0 +1,40 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{parser::BlockParser, store::BlockStore},
+    store::IndexerStore,
+};
+use std::path::PathBuf;
+
+#[tokio::test]
+async fn concurrent_read_only_instances() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("read-only-primary")?;
+    let secondary_dir0 = setup_new_db_dir("read-only-secondary-0")?;
+    let secondary_dir1 = setup_new_db_dir("read-only-secondary-1")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let primary = state.indexer_store.clone().unwrap();
+
+    // read-only instances open alongside the live primary & each other
+    let read_only0 = IndexerStore::read_only(store_dir.path(), secondary_dir0.path())?;
+    let read_only1 = IndexerStore::read_only(store_dir.path(), secondary_dir1.path())?;
+    assert_eq!(read_only0.get_best_block_height()?, Some(1));
+    assert_eq!(read_only1.get_best_block_height()?, Some(1));
+
+    // the primary keeps writing
+    let mut block_parser = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut block_parser).await?;
+
+    let best_height = primary.get_best_block_height()?;
+    assert!(best_height > Some(1));
+
+    // read-only instances see the writes once caught up
+    assert_eq!(read_only0.get_best_block_height()?, Some(1));
+    read_only0.catch_up_with_primary()?;
+    assert_eq!(read_only0.get_best_block_height()?, best_height);
+
+    read_only1.catch_up_with_primary()?;
+    assert_eq!(read_only1.get_best_block_height()?, best_height);
+
+    Ok(())
+}
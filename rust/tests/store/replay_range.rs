@@ -0,0 +1,147 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::{public_key::PublicKey, state_hash::StateHash},
+    block::{parser::BlockParser, store::BlockStore},
+    canonicity::store::CanonicityStore,
+    command::internal::{store::InternalCommandStore, DbInternalCommandWithData},
+    ledger::{
+        account::Account, diff::LedgerDiff, store::staged::StagedLedgerStore, token::TokenAddress,
+    },
+    store::IndexerStore,
+};
+use speedb::WriteBatch;
+use std::path::PathBuf;
+
+/// Overwrite a staged ledger account with an incorrect balance
+fn corrupt_staged_account(
+    store: &IndexerStore,
+    state_hash: &StateHash,
+    height: u32,
+) -> anyhow::Result<(PublicKey, Account)> {
+    let ledger = store.build_staged_ledger(state_hash)?.unwrap();
+    let (pk, account) = ledger
+        .get_token_ledger(&TokenAddress::default())
+        .unwrap()
+        .accounts
+        .iter()
+        .min_by_key(|(pk, _)| (*pk).clone())
+        .unwrap();
+
+    let mut corrupted = account.clone();
+    corrupted.balance.0 += 1;
+    store.set_staged_account(pk, &TokenAddress::default(), state_hash, height, &corrupted)?;
+
+    Ok((pk.clone(), account.clone()))
+}
+
+/// Overwrite the block's first internal command with an incorrect amount
+fn corrupt_internal_command(
+    store: &IndexerStore,
+    state_hash: &StateHash,
+) -> anyhow::Result<DbInternalCommandWithData> {
+    let (block, _) = store.get_block(state_hash)?.unwrap();
+    let original = store.get_block_internal_command(state_hash, 0)?.unwrap();
+
+    let mut corrupted = original.clone();
+    match &mut corrupted {
+        DbInternalCommandWithData::Coinbase { amount, .. }
+        | DbInternalCommandWithData::FeeTransfer { amount, .. } => *amount += 1,
+    }
+    store.set_block_internal_command(&block, 0, &corrupted)?;
+
+    Ok(original)
+}
+
+/// Overwrite the block's ledger diff with an empty diff
+fn corrupt_ledger_diff(store: &IndexerStore, state_hash: &StateHash) -> anyhow::Result<()> {
+    let mut batch = WriteBatch::default();
+    store.set_block_ledger_diff_batch(state_hash, &LedgerDiff::default(), &mut batch)?;
+    store.database.write(batch)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn repairs_only_the_range() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("replay-range-store")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut block_parser = BlockParser::new_testing(&block_dir)?;
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    state.add_blocks(&mut block_parser).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let hash_at = |height| {
+        store
+            .get_canonical_hash_at_height(height)
+            .map(|state_hash| state_hash.unwrap())
+    };
+
+    // persist staged ledgers inside & outside the range
+    for height in [4, 9] {
+        store.get_staged_ledger_at_block_height(height, true)?;
+    }
+
+    // corrupt derived data inside (4 & 5) & outside (8 & 9) the range
+    let (in_pk, in_account) = corrupt_staged_account(store, &hash_at(4)?, 4)?;
+    let (out_pk, out_account) = corrupt_staged_account(store, &hash_at(9)?, 9)?;
+
+    let in_cmd = corrupt_internal_command(store, &hash_at(5)?)?;
+    let out_cmd = corrupt_internal_command(store, &hash_at(8)?)?;
+
+    corrupt_ledger_diff(store, &hash_at(5)?)?;
+    corrupt_ledger_diff(store, &hash_at(8)?)?;
+
+    // invalid ranges
+    assert!(store.replay_range(0, 6).is_err());
+    assert!(store.replay_range(6, 3).is_err());
+    assert!(store.replay_range(3, u32::MAX).is_err());
+
+    // only the corrupted entries are rewritten
+    assert_eq!(store.replay_range(3, 6)?, 3);
+
+    // inside the range is repaired
+    let token = TokenAddress::default();
+    assert_eq!(
+        store.get_staged_account(&in_pk, &token, &hash_at(4)?)?,
+        Some(in_account.clone())
+    );
+    assert_eq!(
+        store
+            .build_staged_ledger(&hash_at(4)?)?
+            .unwrap()
+            .get_account(&in_pk, &token),
+        Some(&in_account)
+    );
+    assert_eq!(
+        store.get_block_internal_command(&hash_at(5)?, 0)?,
+        Some(in_cmd)
+    );
+
+    let (block, _) = store.get_block(&hash_at(5)?)?.unwrap();
+    assert_eq!(
+        store.get_block_ledger_diff(&hash_at(5)?)?,
+        Some(LedgerDiff::from_precomputed(&block))
+    );
+
+    // outside the range is untouched
+    assert_ne!(
+        store.get_staged_account(&out_pk, &token, &hash_at(9)?)?,
+        Some(out_account)
+    );
+    assert_ne!(
+        store.get_block_internal_command(&hash_at(8)?, 0)?,
+        Some(out_cmd)
+    );
+    assert_eq!(
+        store.get_block_ledger_diff(&hash_at(8)?)?,
+        Some(LedgerDiff::default())
+    );
+
+    // replaying again is a no-op
+    assert_eq!(store.replay_range(3, 6)?, 0);
+
+    // replaying from genesis starts from the genesis ledger
+    assert_eq!(store.replay_range(1, 6)?, 0);
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    block::{parser::BlockParser, store::BlockStore},
+    store::IndexerStore,
+    web::rest::health::{lag_blocks_header_value, HealthStatus},
+};
+use std::path::PathBuf;
+
+const MAX_LAG_BLOCKS: u32 = 5;
+
+#[tokio::test]
+async fn frozen_replica_catch_up() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("replication-lag-primary")?;
+    let replica_dir = setup_new_db_dir("replication-lag-replica")?;
+    let block_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let primary = state.indexer_store.clone().unwrap();
+    let replica = IndexerStore::read_only(store_dir.path(), replica_dir.path())?;
+
+    // the primary doesn't lag
+    let status = HealthStatus::new(&primary, Some(MAX_LAG_BLOCKS));
+    assert!(status.ready && status.is_primary);
+    assert_eq!(status.replication_lag, None);
+    assert_eq!(lag_blocks_header_value(&primary), None);
+
+    // caught up replica
+    let status = HealthStatus::new(&replica, Some(MAX_LAG_BLOCKS));
+    assert!(status.ready && !status.is_primary);
+    assert_eq!(status.replication_lag.unwrap().blocks, 0);
+    assert_eq!(lag_blocks_header_value(&replica), Some(0));
+
+    // the primary advances while the replica's catch-up is frozen
+    let mut block_parser = BlockParser::new_testing(&block_dir)?;
+    state.add_blocks(&mut block_parser).await?;
+
+    let best_height = primary.get_best_block_height()?.unwrap();
+    assert!(best_height - 1 > MAX_LAG_BLOCKS);
+
+    let status = HealthStatus::new(&replica, Some(MAX_LAG_BLOCKS));
+    assert!(!status.ready);
+    assert_eq!(status.replication_lag.unwrap().blocks, best_height - 1);
+    assert_eq!(lag_blocks_header_value(&replica), Some(best_height - 1));
+
+    // without a bound, the replica is still ready
+    assert!(HealthStatus::new(&replica, None).ready);
+
+    // resumed catch-up
+    replica.catch_up_with_primary()?;
+
+    let status = HealthStatus::new(&replica, Some(MAX_LAG_BLOCKS));
+    assert!(status.ready);
+    assert_eq!(status.replication_lag.unwrap().blocks, 0);
+    assert_eq!(lag_blocks_header_value(&replica), Some(0));
+
+    Ok(())
+}
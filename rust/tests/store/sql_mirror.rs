@@ -0,0 +1,192 @@
+use crate::helpers::store::*;
+use mina_indexer::{
+    base::state_hash::StateHash,
+    block::{
+        parser::BlockParser,
+        store::{BlockStore, BlockUpdate, DbBlockUpdate},
+    },
+    canonicity::store::CanonicityStore,
+    command::{internal::DbInternalCommandWithData, signed::SignedCommandWithData},
+    constants::*,
+    state::IndexerState,
+    store::{sql_mirror::SqlMirror, IndexerStore},
+};
+use std::{path::PathBuf, sync::Arc};
+
+/// Evaluates a single `UBIGINT` valued query
+fn query_u64(connection: &duckdb::Connection, sql: &str) -> anyhow::Result<u64> {
+    Ok(
+        connection.query_row(&format!("SELECT CAST(({sql}) AS UBIGINT)"), [], |row| {
+            row.get(0)
+        })?,
+    )
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Totals {
+    blocks: u64,
+    user_commands: u64,
+    user_command_amount: u64,
+    user_command_fees: u64,
+    internal_commands: u64,
+    internal_command_amount: u64,
+    coinbase_amount: u64,
+}
+
+impl Totals {
+    /// Totals of the store's canonical blocks
+    fn from_store(store: &IndexerStore) -> anyhow::Result<Self> {
+        let mut totals = Self::default();
+        let best_height = store.get_best_block_height()?.expect("best block height");
+
+        for height in 1..=best_height {
+            let state_hash = store
+                .get_canonical_hash_at_height(height)?
+                .expect("canonical block");
+            let (block, _) = store.get_block(&state_hash)?.expect("block");
+            totals.blocks += 1;
+
+            for cmd in SignedCommandWithData::from_precomputed(&block) {
+                totals.user_commands += 1;
+                totals.user_command_amount += cmd.command.amount();
+                totals.user_command_fees += cmd.command.fee();
+            }
+
+            for cmd in DbInternalCommandWithData::from_precomputed(&block) {
+                totals.internal_commands += 1;
+                match cmd {
+                    DbInternalCommandWithData::Coinbase { amount, .. } => {
+                        totals.internal_command_amount += amount;
+                        totals.coinbase_amount += amount;
+                    }
+                    DbInternalCommandWithData::FeeTransfer { amount, .. } => {
+                        totals.internal_command_amount += amount
+                    }
+                }
+            }
+        }
+
+        Ok(totals)
+    }
+
+    /// Totals of the mirror's blocks
+    fn from_mirror(connection: &duckdb::Connection) -> anyhow::Result<Self> {
+        Ok(Self {
+            blocks: query_u64(connection, "SELECT count(*) FROM blocks")?,
+            user_commands: query_u64(connection, "SELECT count(*) FROM user_commands")?,
+            user_command_amount: query_u64(
+                connection,
+                "SELECT coalesce(sum(amount), 0) FROM user_commands",
+            )?,
+            user_command_fees: query_u64(
+                connection,
+                "SELECT coalesce(sum(fee), 0) FROM user_commands",
+            )?,
+            internal_commands: query_u64(connection, "SELECT count(*) FROM internal_commands")?,
+            internal_command_amount: query_u64(
+                connection,
+                "SELECT coalesce(sum(amount), 0) FROM internal_commands",
+            )?,
+            coinbase_amount: query_u64(
+                connection,
+                "SELECT coalesce(sum(amount), 0) FROM internal_commands WHERE kind = 'Coinbase'",
+            )?,
+        })
+    }
+}
+
+#[tokio::test]
+async fn mirror_canonical_chain() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("sql-mirror-store")?;
+    let mirror_dir = tempfile::TempDir::with_prefix("sql-mirror")?;
+    let blocks_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    // mirror from genesis
+    let store = Arc::new(IndexerStore::new(store_dir.path())?);
+    store.enable_sql_mirror(&mirror_dir.path().join("mirror.duckdb"))?;
+
+    let mut state = IndexerState::new_v1(
+        store.clone(),
+        MAINNET_CANONICAL_THRESHOLD,
+        MAINNET_TRANSITION_FRONTIER_K,
+        false,
+    )?;
+    let mut bp = BlockParser::new_testing(&blocks_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let mirror = store.sql_mirror.get().expect("SQL mirror enabled");
+    let connection = mirror.connection()?;
+
+    // one block per canonical height
+    let best_height = store.get_best_block_height()?.expect("best block height");
+    assert_eq!(
+        query_u64(&connection, "SELECT max(height) FROM blocks")?,
+        best_height as u64
+    );
+    for height in 1..=best_height {
+        let state_hash: String = connection.query_row(
+            "SELECT state_hash FROM blocks WHERE height = ?",
+            [height],
+            |row| row.get(0),
+        )?;
+        assert_eq!(
+            Some(StateHash::from(state_hash)),
+            store.get_canonical_hash_at_height(height)?
+        );
+    }
+
+    // commands & amounts match the store's
+    let expected = Totals::from_store(&store)?;
+    assert_eq!(Totals::from_mirror(&connection)?, expected);
+    assert!(expected.internal_commands > 0);
+
+    // coinbase account diffs match the coinbase internal commands
+    assert_eq!(
+        query_u64(
+            &connection,
+            "SELECT coalesce(sum(amount), 0) FROM account_diffs WHERE kind = 'coinbase'"
+        )?,
+        expected.coinbase_amount
+    );
+
+    // unapplying the best tip removes its rows, reapplying restores them
+    let best_tip = BlockUpdate {
+        state_hash: store.get_best_block_hash()?.expect("best block"),
+        blockchain_length: best_height,
+        global_slot_since_genesis: store.get_best_block_global_slot()?.expect("global slot"),
+    };
+    mirror.update(
+        &store,
+        &DbBlockUpdate {
+            apply: vec![],
+            unapply: vec![best_tip.clone()],
+        },
+    )?;
+    assert_eq!(
+        query_u64(&connection, "SELECT count(*) FROM blocks")?,
+        expected.blocks - 1
+    );
+    assert_eq!(
+        query_u64(
+            &connection,
+            &format!("SELECT count(*) FROM account_diffs WHERE height = {best_height}")
+        )?,
+        0
+    );
+
+    mirror.update(
+        &store,
+        &DbBlockUpdate {
+            apply: vec![best_tip],
+            unapply: vec![],
+        },
+    )?;
+    assert_eq!(Totals::from_mirror(&connection)?, expected);
+
+    // a fresh mirror syncs from the store
+    let synced = SqlMirror::open(&mirror_dir.path().join("synced.duckdb"))?;
+    synced.sync(&store)?;
+    assert_eq!(Totals::from_mirror(&synced.connection()?)?, expected);
+
+    Ok(())
+}
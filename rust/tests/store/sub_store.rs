@@ -0,0 +1,114 @@
+use crate::helpers::store::*;
+use mina_indexer::store::{
+    sub_store::{PendingMigration, SubStore},
+    IndexerStore,
+};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+const TEST_CF: &str = "test-sub-store";
+const MIGRATED_KEY: &[u8] = b"migrated";
+
+static NUM_MIGRATIONS: AtomicU32 = AtomicU32::new(0);
+
+fn migrate_test_sub_store(store: &IndexerStore, from_version: u32) -> anyhow::Result<()> {
+    assert_eq!(from_version, 1);
+    NUM_MIGRATIONS.fetch_add(1, Ordering::SeqCst);
+
+    let cf = store.database.cf_handle(TEST_CF).unwrap();
+    store.database.put_cf(cf, MIGRATED_KEY, b"v2")?;
+    Ok(())
+}
+
+fn test_sub_store(version: u32) -> SubStore {
+    SubStore {
+        name: "test",
+        version,
+        column_families: &[TEST_CF],
+        dependencies: &["blocks"],
+        migrate: migrate_test_sub_store,
+    }
+}
+
+#[test]
+fn migrate_old_sub_store() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("sub-store-migration")?;
+    let path = store_dir.path();
+
+    // fresh sub-stores record their current versions without migrating
+    {
+        let store = IndexerStore::new_with_sub_stores(path, &[test_sub_store(1)], false)?;
+        assert_eq!(store.get_sub_store_version("test")?, Some(1));
+        assert_eq!(
+            store.get_sub_store_version("blocks")?,
+            Some(SubStore::BASELINE_VERSION)
+        );
+
+        // simulate old data
+        let cf = store.database.cf_handle(TEST_CF).unwrap();
+        store.database.put_cf(cf, b"old", b"v1")?;
+    }
+    assert_eq!(NUM_MIGRATIONS.load(Ordering::SeqCst), 0);
+
+    // dry run neither runs nor records the migration
+    {
+        let store = IndexerStore::new_with_sub_stores(path, &[test_sub_store(2)], true)?;
+        assert_eq!(
+            store.pending_migrations(&[test_sub_store(2)])?,
+            vec![PendingMigration {
+                sub_store: "test",
+                from_version: 1,
+                to_version: 2,
+            }]
+        );
+        assert_eq!(store.get_sub_store_version("test")?, Some(1));
+    }
+    assert_eq!(NUM_MIGRATIONS.load(Ordering::SeqCst), 0);
+
+    // the migration runs once & is recorded
+    for _ in 0..2 {
+        let store = IndexerStore::new_with_sub_stores(path, &[test_sub_store(2)], false)?;
+        let cf = store.database.cf_handle(TEST_CF).unwrap();
+
+        assert_eq!(store.get_sub_store_version("test")?, Some(2));
+        assert_eq!(
+            store.database.get_cf(cf, MIGRATED_KEY)?,
+            Some(b"v2".to_vec())
+        );
+        assert!(store.pending_migrations(&[test_sub_store(2)])?.is_empty());
+        assert_eq!(NUM_MIGRATIONS.load(Ordering::SeqCst), 1);
+    }
+
+    // stored version is newer than the binary's
+    assert!(IndexerStore::new_with_sub_stores(path, &[test_sub_store(1)], false).is_err());
+    assert!(IndexerStore::new_with_sub_stores(path, &[test_sub_store(1)], true).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn unversioned_populated_sub_store() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("sub-store-unversioned")?;
+    let path = store_dir.path();
+
+    // populated before sub-store versions were recorded
+    {
+        let store = IndexerStore::new(path)?;
+        store.database.delete_cf(
+            store.database.cf_handle("sub-store-versions").unwrap(),
+            b"events",
+        )?;
+        store.database.put_cf(
+            store.database.cf_handle("events").unwrap(),
+            b"key",
+            b"value",
+        )?;
+    }
+
+    let store = IndexerStore::new(path)?;
+    assert_eq!(
+        store.get_sub_store_version("events")?,
+        Some(SubStore::BASELINE_VERSION)
+    );
+
+    Ok(())
+}
@@ -1,14 +1,18 @@
 use crate::helpers::{state::*, store::*};
+use async_graphql::Request;
 use mina_indexer::{
     block::{
         parser::BlockParser,
         precomputed::{PcbVersion, PrecomputedBlock},
     },
     constants::*,
+    identity::{https::HttpsIdentityConfig, IdentityConfig, IdentityResolver},
     server::IndexerVersion,
-    store::{username::UsernameStore, DbUpdate},
+    store::{username::UsernameStore, DbUpdate, IndexerStore},
+    web::graphql::build_schema,
 };
-use std::path::PathBuf;
+use serde_json::json;
+use std::{fs, path::PathBuf, sync::Arc};
 
 #[tokio::test]
 async fn set_usernames() -> anyhow::Result<()> {
@@ -22,6 +26,7 @@ async fn set_usernames() -> anyhow::Result<()> {
         MAINNET_CANONICAL_THRESHOLD,
         false,
         BLOCK_REPORTING_FREQ_NUM,
+        true,
     )
     .await?;
 
@@ -46,3 +51,94 @@ async fn set_usernames() -> anyhow::Result<()> {
     );
     Ok(())
 }
+
+#[tokio::test]
+async fn on_chain_identity_provider() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("usernames-on-chain-provider")?;
+    let store = Arc::new(IndexerStore::new(store_dir.path())?);
+
+    let block = PrecomputedBlock::parse_file(&PathBuf::from("./tests/data/non_sequential_blocks/mainnet-338728-3NLe2WXRaJq85Ldj1ycEQRa2R6vmemVAoXpvkncccuuKNuWs6WYf.json"), PcbVersion::V1)?;
+    store.update_usernames(DbUpdate {
+        apply: vec![block.username_updates()],
+        ..Default::default()
+    })?;
+
+    let pk = "B62qkEtH1PxqjJPKitAmzfV2ozCuCcibBL4tLgpeXHvsaqVgrENjFhX";
+    assert_eq!(store.get_username_pks("Betelgeuse")?, vec![pk.into()]);
+
+    // resolve on-chain registrations
+    let resolver = IdentityResolver::from_config(store.clone(), &IdentityConfig::default());
+    let identity = resolver.resolve_username("Betelgeuse").await?.unwrap();
+    assert_eq!(identity.public_keys, vec![pk.into()]);
+    assert_eq!(identity.provider, "on-chain");
+
+    let identity = resolver.lookup_username(&pk.into()).await?.unwrap();
+    assert_eq!(identity.username.0, "Betelgeuse");
+    assert!(resolver.resolve_username("Rigel").await?.is_none());
+
+    // unapply the registration
+    store.update_usernames(DbUpdate {
+        unapply: vec![block.username_updates()],
+        ..Default::default()
+    })?;
+    assert!(store.get_username_pks("Betelgeuse")?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn https_identity_provider() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("usernames-https-provider")?;
+    let store = Arc::new(IndexerStore::new(store_dir.path())?);
+
+    // file:// provider responses
+    let provider_dir = store_dir.path().join("provider");
+    fs::create_dir_all(provider_dir.join("resolve"))?;
+    fs::create_dir_all(provider_dir.join("lookup"))?;
+
+    let pk = "B62qkEtH1PxqjJPKitAmzfV2ozCuCcibBL4tLgpeXHvsaqVgrENjFhX";
+    fs::write(
+        provider_dir.join("resolve").join("Betelgeuse.json"),
+        json!({ "publicKeys": [pk] }).to_string(),
+    )?;
+    fs::write(
+        provider_dir.join("lookup").join(format!("{pk}.json")),
+        json!({ "username": "Betelgeuse" }).to_string(),
+    )?;
+
+    let config = IdentityConfig {
+        https: Some(HttpsIdentityConfig {
+            resolve_url: format!(
+                "file://{}/resolve/{{username}}.json",
+                provider_dir.display()
+            ),
+            lookup_url: format!(
+                "file://{}/lookup/{{public_key}}.json",
+                provider_dir.display()
+            ),
+        }),
+    };
+    config.validate()?;
+
+    let schema = build_schema(store);
+    let query = format!(
+        r#"{{
+  resolveUsername(username: "Betelgeuse") {{ username publicKeys provider }}
+  lookupUsername(publicKey: "{pk}") {{ username publicKeys provider }}
+  unknown: resolveUsername(username: "Rigel") {{ username }}
+}}"#
+    );
+
+    let response = schema.execute(Request::new(query).data(config)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json()?;
+    let expected = json!({
+        "username": "Betelgeuse",
+        "publicKeys": [pk],
+        "provider": "https",
+    });
+    assert_eq!(data["resolveUsername"], expected);
+    assert_eq!(data["lookupUsername"], expected);
+    assert!(data["unknown"].is_null());
+    Ok(())
+}
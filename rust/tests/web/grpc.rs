@@ -0,0 +1,152 @@
+use crate::helpers::{state::*, store::*};
+use mina_indexer::{
+    base::state_hash::StateHash,
+    block::{parser::BlockParser, store::BlockStore},
+    canonicity::store::CanonicityStore,
+    command::signed::SignedCommand,
+    web::grpc::{
+        proto::{self, mina_indexer_server::MinaIndexer},
+        GrpcService,
+    },
+};
+use std::{path::PathBuf, sync::Arc};
+use tonic::{Code, Request};
+
+#[tokio::test]
+async fn grpc_queries() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("grpc-queries")?;
+    let blocks_dir = PathBuf::from("./tests/data/canonical_chain_discovery/contiguous");
+
+    let mut state = mainnet_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(&blocks_dir)?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let service = GrpcService::new(Arc::clone(store));
+
+    // best block
+    let best = service
+        .get_block(Request::new(proto::GetBlockRequest { state_hash: None }))
+        .await?
+        .into_inner();
+    assert_eq!(
+        Some(StateHash::from(best.state_hash.clone())),
+        store.get_best_block_hash()?
+    );
+
+    // block by state hash
+    let block = service
+        .get_block(Request::new(proto::GetBlockRequest {
+            state_hash: Some(best.state_hash.clone()),
+        }))
+        .await?
+        .into_inner();
+    assert_eq!(block, best);
+
+    // canonical blocks, ascending & limited
+    let canonical = service
+        .list_canonical_blocks(Request::new(proto::ListCanonicalBlocksRequest {
+            from_height: 1,
+            to_height: None,
+            limit: Some(5),
+        }))
+        .await?
+        .into_inner()
+        .blocks;
+    assert_eq!(canonical.len(), 5);
+
+    for (n, block) in canonical.iter().enumerate() {
+        let height = n as u32 + 1;
+        assert_eq!(block.blockchain_length, height);
+        assert_eq!(block.canonicity(), proto::Canonicity::Canonical);
+        assert_eq!(
+            Some(StateHash::from(block.state_hash.clone())),
+            store.get_canonical_hash_at_height(height)?
+        );
+    }
+
+    // block creator's best ledger account
+    let account = service
+        .get_account(Request::new(proto::GetAccountRequest {
+            public_key: best.block_creator.clone(),
+            token: None,
+        }))
+        .await?
+        .into_inner();
+    assert_eq!(account.public_key, best.block_creator);
+
+    // transactions of the best chain
+    let mut num_txns = 0;
+    for block in canonical.iter() {
+        let (pcb, _) = store
+            .get_block(&StateHash::from(block.state_hash.clone()))?
+            .unwrap();
+
+        for cmd in SignedCommand::from_precomputed(&pcb) {
+            let txn_hash = cmd.signed_command.hash_signed_command()?;
+            let txns = service
+                .get_transaction(Request::new(proto::GetTransactionRequest {
+                    txn_hash: txn_hash.to_string(),
+                }))
+                .await?
+                .into_inner()
+                .transactions;
+
+            assert!(txns
+                .iter()
+                .any(|txn| txn.txn_hash == txn_hash.to_string()
+                    && txn.state_hash == block.state_hash));
+            num_txns += 1;
+        }
+    }
+    assert_eq!(
+        num_txns,
+        canonical
+            .iter()
+            .map(|block| block.num_user_commands)
+            .sum::<u32>()
+    );
+
+    // no staking ledger
+    let status = service
+        .get_staking_account(Request::new(proto::GetStakingAccountRequest {
+            public_key: best.block_creator.clone(),
+            epoch: 0,
+            genesis_state_hash: None,
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+
+    let delegators = service
+        .list_delegators(Request::new(proto::ListDelegatorsRequest {
+            delegate: best.block_creator.clone(),
+            epoch: 0,
+            genesis_state_hash: None,
+            limit: None,
+        }))
+        .await?
+        .into_inner();
+    assert_eq!(delegators.total_delegated, 0);
+    assert!(delegators.delegators.is_empty());
+
+    // invalid arguments
+    let status = service
+        .get_block(Request::new(proto::GetBlockRequest {
+            state_hash: Some("3NKinvalid".into()),
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    let status = service
+        .get_account(Request::new(proto::GetAccountRequest {
+            public_key: "B62qinvalid".into(),
+            token: None,
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    Ok(())
+}
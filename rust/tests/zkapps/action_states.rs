@@ -0,0 +1,190 @@
+use crate::helpers::{state::*, store::*};
+use async_graphql::{Request, Variables};
+use mina_indexer::{
+    base::public_key::PublicKey,
+    block::{
+        parser::BlockParser,
+        precomputed::{PcbVersion, PrecomputedBlock},
+    },
+    ledger::{
+        diff::{account::AccountDiff, LedgerDiff},
+        store::best::BestLedgerStore,
+        token::TokenAddress,
+    },
+    mina_blocks::v2::ActionState,
+    store::zkapp::actions::ZkappActionStore,
+    web::graphql::build_schema,
+};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const BLOCK_FILE: &str = "mainnet-359611-3NKybkb8C3R5PjwkxNUVCL6tb5qVf5i4jPWkDCcyJbka9Qgvr8CG.json";
+const ZKAPP_PK: &str = "B62qrgc2UBuyVYZLYU5eS9VFMzSHoKkQGubVm2UXX22q458VSm2Wn9P";
+const ZKAPP_TOKEN: &str = "wSHV2S4qX9jFsLjQo8r1BsMLH2ZRKsZx6EJd1sbozGPieEC4Jf";
+
+const ACTIONS: [&str; 2] = [
+    "0x0000000000000000000000000000000000000000000000000000000000000001",
+    "0x0000000000000000000000000000000000000000000000000000000000000002",
+];
+const NEW_ACTION_STATE: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+
+const ZKAPP_ACTIONS_QUERY: &str = r#"
+query ZkappActions($pk: String!, $token: String!) {
+  zkappActions(publicKey: $pk, token: $token) {
+    blockHeight
+    stateHash
+    firstActionIndex
+    actions
+    actionState {
+      before
+      after
+    }
+  }
+}
+"#;
+
+/// Action state commitments after the zkapp account's actions
+fn action_state_after() -> [ActionState; 5] {
+    let mut action_state: [ActionState; 5] = Default::default();
+    action_state[0] = NEW_ACTION_STATE.into();
+    action_state
+}
+
+/// Copies the hardfork blocks, adding actions to the zkapp account's update &
+/// the corresponding action state to its accessed account
+fn write_blocks_with_actions(blocks_dir: &Path) -> anyhow::Result<()> {
+    let hardfork_dir = PathBuf::from("./tests/data/hardfork");
+    for entry in std::fs::read_dir(&hardfork_dir)? {
+        let path = entry?.path();
+        let dest = blocks_dir.join(path.file_name().unwrap());
+        if path.file_name().unwrap() != BLOCK_FILE {
+            std::fs::copy(&path, dest)?;
+            continue;
+        }
+
+        let mut block: Value = serde_json::from_slice(&std::fs::read(&path)?)?;
+        let data = &mut block["data"];
+
+        let body = &mut data["staged_ledger_diff"]["diff"][0]["commands"][10]["data"][1]
+            ["account_updates"][1]["elt"]["account_update"]["body"];
+        assert_eq!(body["public_key"], ZKAPP_PK);
+        body["actions"] = json!([ACTIONS]);
+
+        for accessed in data["accounts_accessed"].as_array_mut().unwrap() {
+            let account = &mut accessed[1];
+            if account["public_key"] == ZKAPP_PK && account["token_id"] == ZKAPP_TOKEN {
+                account["zkapp"]["action_state"] =
+                    json!(action_state_after().map(|action_state| action_state.0));
+            }
+        }
+
+        std::fs::write(dest, serde_json::to_vec(&block)?)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn actions_diff_action_state() -> anyhow::Result<()> {
+    let blocks_dir = TempDir::with_prefix("zkapp-action-states-diff")?;
+    write_blocks_with_actions(blocks_dir.path())?;
+
+    let block = PrecomputedBlock::parse_file(&blocks_dir.path().join(BLOCK_FILE), PcbVersion::V2)?;
+    let diff = LedgerDiff::from_precomputed(&block);
+
+    let actions_diffs: Vec<_> = diff
+        .account_diffs
+        .into_iter()
+        .flatten()
+        .filter_map(|diff| match diff {
+            AccountDiff::ZkappActionsDiff(diff) => Some(diff),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(actions_diffs.len(), 1, "{actions_diffs:?}");
+
+    // the action state matches the accessed account's
+    let diff = &actions_diffs[0];
+    assert_eq!(diff.public_key.0, ZKAPP_PK);
+    assert_eq!(diff.token.0, ZKAPP_TOKEN);
+    assert_eq!(
+        diff.actions,
+        ACTIONS.map(ActionState::from).to_vec(),
+        "{diff:?}"
+    );
+    assert_eq!(diff.action_state, Some(action_state_after()));
+
+    Ok(())
+}
+
+#[ignore = "only tested in tier 1 via cargo nextest --run-ignored all"]
+#[tokio::test]
+async fn zkapp_action_state_transitions() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("zkapp-action-states")?;
+    let blocks_dir = TempDir::with_prefix("zkapp-action-states")?;
+    write_blocks_with_actions(blocks_dir.path())?;
+
+    let mut state = hardfork_genesis_state(store_dir.as_ref())?;
+    let mut bp = BlockParser::new_testing(blocks_dir.path())?;
+    state.add_blocks(&mut bp).await?;
+
+    let store = state.indexer_store.as_ref().unwrap();
+    let pk: PublicKey = ZKAPP_PK.into();
+    let token = TokenAddress::new(ZKAPP_TOKEN).unwrap();
+
+    // the best account holds the accessed account's action state
+    let account = store.get_best_account(&pk, &token)?.unwrap();
+    assert_eq!(account.zkapp.unwrap().action_state, action_state_after());
+
+    // one transition, from the empty action state
+    let transitions = store.get_action_state_transitions(&pk, &token)?;
+    assert_eq!(transitions.len(), 1, "{transitions:?}");
+
+    let transition = &transitions[0];
+    assert_eq!(transition.blockchain_length, 359611);
+    assert_eq!(transition.first_action_index, 0);
+    assert_eq!(transition.num_actions, ACTIONS.len() as u32);
+    assert_eq!(transition.before, <[ActionState; 5]>::default());
+    assert_eq!(transition.after, action_state_after());
+    assert_eq!(
+        store
+            .get_action_state_transition(&pk, &token, 359611)?
+            .as_ref(),
+        Some(transition)
+    );
+
+    // graphql
+    let schema = build_schema(store.clone());
+    let response = schema
+        .execute(
+            Request::new(ZKAPP_ACTIONS_QUERY).variables(Variables::from_json(json!({
+                "pk": ZKAPP_PK,
+                "token": ZKAPP_TOKEN,
+            }))),
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let empty = ActionState::default().0;
+    let mut after = vec![empty.clone(); 5];
+    after[0] = NEW_ACTION_STATE.to_string();
+
+    assert_eq!(
+        response.data.into_json()?,
+        json!({
+            "zkappActions": [{
+                "blockHeight": 359611,
+                "stateHash": transition.state_hash.0,
+                "firstActionIndex": 0,
+                "actions": ACTIONS,
+                "actionState": {
+                    "before": vec![empty; 5],
+                    "after": after,
+                },
+            }]
+        })
+    );
+
+    Ok(())
+}
@@ -1 +1,3 @@
+mod action_states;
 mod store;
+mod subscriptions;
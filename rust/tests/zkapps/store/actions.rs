@@ -1,9 +1,12 @@
 use crate::{generators::TestGen, helpers::store::*};
 use mina_indexer::{
-    base::public_key::PublicKey,
+    base::{public_key::PublicKey, state_hash::StateHash},
     ledger::token::TokenAddress,
     mina_blocks::v2::ActionState,
-    store::{zkapp::actions::ZkappActionStore, IndexerStore},
+    store::{
+        zkapp::actions::{ActionStateTransition, ZkappActionStore},
+        IndexerStore,
+    },
 };
 use quickcheck::{Arbitrary, Gen};
 
@@ -86,3 +89,68 @@ fn action_store_test() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn action_state_transition_store_test() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("zkapp-action-state-store")?;
+    let indexer_store = IndexerStore::new(store_dir.path())?;
+
+    let mut gen = Gen::new(100);
+    let pk = PublicKey::default();
+    let token = TokenAddress::default();
+
+    // chain of transitions at consecutive heights
+    let mut before: [ActionState; 5] = Default::default();
+    let mut transitions = vec![];
+    for height in 10..13 {
+        let mut after = before.clone();
+        after.rotate_right(1);
+        after[0] = <TestGen<ActionState>>::arbitrary(&mut gen).0;
+
+        let transition = ActionStateTransition {
+            state_hash: StateHash::default(),
+            blockchain_length: height,
+            first_action_index: height - 10,
+            num_actions: 1,
+            before,
+            after: after.clone(),
+        };
+        indexer_store.set_action_state_transition(&pk, &token, &transition)?;
+
+        transitions.push(transition);
+        before = after;
+    }
+
+    // other token account's transitions are excluded
+    let other_pk: PublicKey = "B62qqhURJQo3CvWC3WFo9LhUhtcaJWLBcJsaA3DXaU2GH5KgXujZiwB".into();
+    indexer_store.set_action_state_transition(&other_pk, &token, &transitions[0])?;
+
+    assert_eq!(
+        indexer_store.get_action_state_transitions(&pk, &token)?,
+        transitions
+    );
+    assert_eq!(
+        indexer_store.get_action_state_transition(&pk, &token, 11)?,
+        Some(transitions[1].clone())
+    );
+    assert_eq!(
+        indexer_store.get_action_state_transition(&pk, &token, 13)?,
+        None
+    );
+
+    // remove the last
+    assert_eq!(
+        indexer_store.remove_action_state_transition(&pk, &token, 12)?,
+        transitions.pop()
+    );
+    assert_eq!(
+        indexer_store.remove_action_state_transition(&pk, &token, 12)?,
+        None
+    );
+    assert_eq!(
+        indexer_store.get_action_state_transitions(&pk, &token)?,
+        transitions
+    );
+
+    Ok(())
+}
@@ -1,9 +1,12 @@
 use crate::{generators::TestGen, helpers::store::*};
 use mina_indexer::{
-    base::public_key::PublicKey,
+    base::{public_key::PublicKey, state_hash::StateHash},
     ledger::token::TokenAddress,
     mina_blocks::v2::ZkappEvent,
-    store::{zkapp::events::ZkappEventStore, IndexerStore},
+    store::{
+        zkapp::events::{ZkappBlockEvents, ZkappEventStore},
+        IndexerStore,
+    },
 };
 use quickcheck::{Arbitrary, Gen};
 
@@ -86,3 +89,62 @@ fn event_store_test() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn block_events_store_test() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("zkapp-block-events-store")?;
+    let indexer_store = IndexerStore::new(store_dir.path())?;
+
+    let pk = PublicKey::default();
+    let token = TokenAddress::default();
+
+    // events at every other height
+    let mut blocks = vec![];
+    for (n, height) in (10..20).step_by(2).enumerate() {
+        let block_events = ZkappBlockEvents {
+            state_hash: StateHash::default(),
+            blockchain_length: height,
+            first_event_index: 2 * n as u32,
+            num_events: 2,
+        };
+        indexer_store.set_block_events(&pk, &token, &block_events)?;
+        blocks.push(block_events);
+    }
+
+    // other token account's events are excluded
+    let other_pk: PublicKey = "B62qqhURJQo3CvWC3WFo9LhUhtcaJWLBcJsaA3DXaU2GH5KgXujZiwB".into();
+    indexer_store.set_block_events(&other_pk, &token, &blocks[0])?;
+
+    assert_eq!(
+        indexer_store.get_block_events_range(&pk, &token, 0, u32::MAX, usize::MAX)?,
+        blocks
+    );
+    assert_eq!(
+        indexer_store.get_block_events(&pk, &token, 12)?,
+        Some(blocks[1].clone())
+    );
+    assert_eq!(indexer_store.get_block_events(&pk, &token, 13)?, None);
+
+    // height bounds are inclusive
+    assert_eq!(
+        indexer_store.get_block_events_range(&pk, &token, 12, 16, usize::MAX)?,
+        blocks[1..4]
+    );
+    assert_eq!(
+        indexer_store.get_block_events_range(&pk, &token, 11, 17, 2)?,
+        blocks[1..3]
+    );
+
+    // remove the last
+    assert_eq!(
+        indexer_store.remove_block_events(&pk, &token, 18)?,
+        blocks.pop()
+    );
+    assert_eq!(indexer_store.remove_block_events(&pk, &token, 18)?, None);
+    assert_eq!(
+        indexer_store.get_block_events_range(&pk, &token, 0, u32::MAX, usize::MAX)?,
+        blocks
+    );
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use crate::helpers::store::*;
+use async_graphql::{Request, Variables};
+use futures_util::{FutureExt, StreamExt};
+use mina_indexer::{
+    block::{
+        precomputed::{PcbVersion, PrecomputedBlock},
+        store::BlockStore,
+    },
+    canonicity::{store::CanonicityStore, CanonicityDiff, CanonicityUpdate},
+    ledger::token::TokenAddress,
+    store::{zkapp::subscriptions::ZkappNotificationKind, IndexerStore},
+    web::graphql::build_schema,
+};
+use serde_json::json;
+use std::{fs, path::PathBuf, sync::Arc};
+
+const BLOCK_PATH: &str = "./tests/data/berkeley/non_sequential_blocks/berkeley-4969-3NL8QoLQMtsBH8vUnccQw3vt8PgYuZRMApq1yZT1jwhZjbBLMRJU.json";
+
+// emits a single event in the block's zkapp command 12
+const ZKAPP_PK: &str = "B62qqpAFkz374qJpuFKYZPjT1KxSmnLoY4zEc878FaW4DSxgYNXZiny";
+const OTHER_PK: &str = "B62qkoey9KVcsfNP1uVjmpaHiFkSHJYpK8eF6z6UCuXyFocjjfVBJWZ";
+
+const ZKAPP_EVENTS_SUBSCRIPTION: &str = r#"
+subscription ZkappEvents($publicKey: String!) {
+  zkappEvents(publicKey: $publicKey) {
+    stateHash
+    blockHeight
+    txnIndex
+    kind
+    index
+    data
+    removed
+  }
+}
+"#;
+
+fn event_data() -> Vec<String> {
+    vec![
+        "0x000000000000000000000000000000000000000000000000000000000000000D".into(),
+        "0x000000000000000000000000000000000000000000000000000000000134D75C".into(),
+    ]
+}
+
+/// Adds the block & returns its canonicity diff
+fn add_block(store: &IndexerStore) -> anyhow::Result<CanonicityDiff> {
+    let path = PathBuf::from(BLOCK_PATH);
+    let block = PrecomputedBlock::parse_file(&path, PcbVersion::V2)?;
+    store.add_block(&block, fs::metadata(&path)?.len())?;
+
+    Ok(CanonicityDiff {
+        state_hash: block.state_hash(),
+        blockchain_length: block.blockchain_length(),
+        global_slot: block.global_slot_since_genesis(),
+    })
+}
+
+fn apply(diff: &CanonicityDiff) -> CanonicityUpdate {
+    CanonicityUpdate {
+        apply: vec![diff.clone()],
+        unapply: vec![],
+    }
+}
+
+fn unapply(diff: &CanonicityDiff) -> CanonicityUpdate {
+    CanonicityUpdate {
+        apply: vec![],
+        unapply: vec![diff.clone()],
+    }
+}
+
+#[test]
+fn exactly_once_with_removals() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("zkapp-subscriptions")?;
+    let store = IndexerStore::new(store_dir.path())?;
+    let diff = add_block(&store)?;
+
+    let mut subscription = store.subscribe_zkapp_events(ZKAPP_PK.into(), TokenAddress::default());
+    let mut other = store.subscribe_zkapp_events(OTHER_PK.into(), TokenAddress::default());
+
+    // republishing the same canonical block is delivered once
+    store.update_canonicity(apply(&diff))?;
+    store.update_canonicity(apply(&diff))?;
+
+    let notification = subscription.try_next().unwrap();
+    assert_eq!(notification.state_hash, diff.state_hash);
+    assert_eq!(notification.blockchain_length, 4969);
+    assert_eq!(notification.txn_index, 12);
+    assert_eq!(notification.kind, ZkappNotificationKind::Event);
+    assert_eq!(notification.index, 0);
+    assert_eq!(notification.data, event_data());
+    assert_eq!(notification.action_state_after.as_ref().unwrap().len(), 5);
+    assert!(!notification.removed);
+    assert!(subscription.try_next().is_none());
+
+    // only the subscribed account's notifications
+    assert!(other.try_next().is_none());
+
+    // reorged out
+    store.update_canonicity(unapply(&diff))?;
+    store.update_canonicity(unapply(&diff))?;
+
+    let removal = subscription.try_next().unwrap();
+    assert_eq!(removal.id(), notification.id());
+    assert_eq!(removal.data, event_data());
+    assert!(removal.removed);
+    assert!(subscription.try_next().is_none());
+
+    // canonical again
+    store.update_canonicity(apply(&diff))?;
+    assert!(!subscription.try_next().unwrap().removed);
+    assert!(subscription.try_next().is_none());
+
+    // removals of undelivered notifications aren't delivered
+    let mut late = store.subscribe_zkapp_events(ZKAPP_PK.into(), TokenAddress::default());
+    store.update_canonicity(unapply(&diff))?;
+    assert!(late.try_next().is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn graphql_zkapp_events_subscription() -> anyhow::Result<()> {
+    let store_dir = setup_new_db_dir("graphql-zkapp-subscriptions")?;
+    let store = Arc::new(IndexerStore::new(store_dir.path())?);
+    let diff = add_block(&store)?;
+
+    let schema = build_schema(store.clone());
+    let mut stream = schema.execute_stream(Request::new(ZKAPP_EVENTS_SUBSCRIPTION).variables(
+        Variables::from_json(json!({
+            "publicKey": ZKAPP_PK,
+        })),
+    ));
+
+    // subscribe before anything is published
+    assert!(stream.next().now_or_never().is_none());
+    store.update_canonicity(apply(&diff))?;
+
+    let response = stream.next().await.unwrap();
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json()?;
+    let event = &data["zkappEvents"];
+    assert_eq!(event["stateHash"], diff.state_hash.0);
+    assert_eq!(event["blockHeight"], 4969);
+    assert_eq!(event["txnIndex"], 12);
+    assert_eq!(event["kind"], "EVENT");
+    assert_eq!(event["index"], 0);
+    assert_eq!(event["data"], json!(event_data()));
+    assert_eq!(event["removed"], false);
+
+    // invalid public key
+    let response = schema
+        .execute_stream(
+            Request::new(ZKAPP_EVENTS_SUBSCRIPTION).variables(Variables::from_json(json!({
+                "publicKey": "invalid",
+            }))),
+        )
+        .next()
+        .await
+        .unwrap();
+    assert!(!response.errors.is_empty());
+
+    Ok(())
+}